@@ -0,0 +1,66 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Free disk space detection.
+
+use std::path::Path;
+
+/// Returns the number of bytes available to unprivileged users
+/// on the filesystem containing given path.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> Option<u64> {
+	use std::ffi::CString;
+	use std::mem;
+	use std::os::unix::ffi::OsStrExt;
+	use libc;
+
+	let path = match CString::new(path.as_os_str().as_bytes()) {
+		Ok(path) => path,
+		Err(_) => return None,
+	};
+
+	unsafe {
+		let mut stat: libc::statvfs = mem::zeroed();
+		if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+			return None;
+		}
+		Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+	}
+}
+
+/// Returns the number of bytes available to unprivileged users
+/// on the filesystem containing given path.
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> Option<u64> {
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use super::available_space;
+
+	#[cfg(unix)]
+	#[test]
+	fn should_report_space_of_existing_dir() {
+		assert!(available_space(&env::temp_dir()).is_some());
+	}
+
+	#[test]
+	fn should_not_report_space_of_missing_dir() {
+		assert_eq!(available_space(&env::temp_dir().join("non-existent-dir/for/sure")), None);
+	}
+}
@@ -16,12 +16,16 @@
 
 //! Reporting node's health.
 
+use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time;
 use futures::{Future, BoxFuture};
 use futures::sync::oneshot;
+use futures_cpupool::CpuPool;
 use types::{HealthInfo, HealthStatus, Health};
 use time::{TimeChecker, MAX_DRIFT};
+use disk;
 use parity_reactor::Remote;
 use parking_lot::Mutex;
 use {SyncStatus};
@@ -29,71 +33,154 @@ use {SyncStatus};
 const TIMEOUT_SECS: u64 = 5;
 const PROOF: &str = "Only one closure is invoked.";
 
+/// Pass/fail thresholds used by the health checks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+	/// Minimal number of peers for the node to be considered healthy.
+	/// Having no peers at all is always reported as bad.
+	pub min_peers: usize,
+	/// Maximal number of blocks the node can be behind the network.
+	pub max_block_lag: u64,
+	/// Maximal acceptable clock drift (in ms).
+	pub max_time_drift: i64,
+	/// Minimal free disk space (in bytes) in the data directory.
+	pub min_disk_space: u64,
+	/// Maximal time (in ms) the client may take to report its status.
+	/// Client not reporting within this time is considered stalled.
+	pub max_response_time: u64,
+}
+
+impl Default for Thresholds {
+	fn default() -> Self {
+		Thresholds {
+			min_peers: 2,
+			max_block_lag: 10,
+			max_time_drift: MAX_DRIFT,
+			min_disk_space: 1024 * 1024 * 1024,
+			max_response_time: 1000,
+		}
+	}
+}
+
+/// Client status queried in a single round-trip.
+struct ClientStatus {
+	peers: (usize, usize),
+	is_syncing: bool,
+	lag: Option<u64>,
+	/// Time (in ms) it took to query the client.
+	elapsed: u64,
+}
+
 /// A struct enabling you to query for node's health.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct NodeHealth {
 	sync_status: Arc<SyncStatus>,
 	time: TimeChecker,
+	pool: CpuPool,
 	remote: Remote,
+	thresholds: Thresholds,
+	data_dir: Option<PathBuf>,
+}
+
+impl fmt::Debug for NodeHealth {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f
+			.debug_struct("NodeHealth")
+			.field("sync_status", &self.sync_status)
+			.field("time", &self.time)
+			.field("remote", &self.remote)
+			.field("thresholds", &self.thresholds)
+			.field("data_dir", &self.data_dir)
+			.finish()
+	}
 }
 
 impl NodeHealth {
 	/// Creates new `NodeHealth`.
-	pub fn new(sync_status: Arc<SyncStatus>, time: TimeChecker, remote: Remote) -> Self {
-		NodeHealth { sync_status, time, remote, }
+	///
+	/// The client is queried on the `pool`, so that a stalled client doesn't block the event loop.
+	/// Disk space is checked only if `data_dir` is provided.
+	pub fn new(
+		sync_status: Arc<SyncStatus>,
+		time: TimeChecker,
+		pool: CpuPool,
+		remote: Remote,
+		thresholds: Thresholds,
+		data_dir: Option<PathBuf>,
+	) -> Self {
+		NodeHealth { sync_status, time, pool: pool, remote, thresholds: thresholds, data_dir: data_dir, }
 	}
 
-	/// Query latest health report.
-	pub fn health(&self) -> BoxFuture<Health, ()> {
-		trace!(target: "dapps", "Checking node health.");
-		// Check timediff
-		let sync_status = self.sync_status.clone();
-		let time = self.time.time_drift();
+	/// Resolves given future on the event loop, or fails if it doesn't complete in time.
+	fn with_timeout<F>(&self, future: F, timeout: time::Duration) -> oneshot::Receiver<Result<Result<F::Item, F::Error>, ()>> where
+		F: Future + Send + 'static,
+		F::Item: Send + 'static,
+		F::Error: Send + 'static,
+	{
 		let (tx, rx) = oneshot::channel();
 		let tx = Arc::new(Mutex::new(Some(tx)));
 		let tx2 = tx.clone();
 		self.remote.spawn_with_timeout(
-			move || time.then(move |result| {
+			move || future.then(move |result| {
 				let _ = tx.lock().take().expect(PROOF).send(Ok(result));
 				Ok(())
 			}),
-			time::Duration::from_secs(TIMEOUT_SECS),
+			timeout,
 			move || {
 				let _ = tx2.lock().take().expect(PROOF).send(Err(()));
 			},
 		);
+		rx
+	}
+
+	/// Query latest health report.
+	pub fn health(&self) -> BoxFuture<Health, ()> {
+		trace!(target: "dapps", "Checking node health.");
+		let thresholds = self.thresholds.clone();
+		let data_dir = self.data_dir.clone();
+		// Check timediff
+		let time = self.with_timeout(self.time.time_drift(), time::Duration::from_secs(TIMEOUT_SECS));
+		// Query the client
+		let sync_status = self.sync_status.clone();
+		let client = self.pool.spawn_fn(move || {
+			let started = time::Instant::now();
+			let peers = sync_status.peers();
+			let is_syncing = sync_status.is_major_importing();
+			let lag = sync_status.block_lag();
+			let elapsed = started.elapsed();
+			Ok::<_, ()>(ClientStatus {
+				peers: peers,
+				is_syncing: is_syncing,
+				lag: lag,
+				elapsed: elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000,
+			})
+		});
+		let client = self.with_timeout(client, time::Duration::from_millis(thresholds.max_response_time));
 
-		rx.map_err(|err| {
+		time.join(client).map_err(|err| {
 			warn!(target: "dapps", "Health request cancelled: {:?}", err);
-		}).and_then(move |time| {
-			// Check peers
-			let peers = {
-				let (connected, max) = sync_status.peers();
-				let (status, message) = match connected {
-					0 => {
-						(HealthStatus::Bad, "You are not connected to any peers. There is most likely some network issue. Fix connectivity.".into())
+		}).and_then(move |(time, client)| {
+			// Check disk space
+			let disk = {
+				let space = data_dir.as_ref().and_then(|dir| disk::available_space(dir));
+				let (status, message) = match space {
+					Some(space) if space < thresholds.min_disk_space => (HealthStatus::Bad, format!(
+						"You are running out of disk space: {}MB left. Free some space or move the data directory.",
+						space / (1024 * 1024),
+					)),
+					None if cfg!(unix) && data_dir.is_some() => {
+						(HealthStatus::NeedsAttention, "Unable to determine free disk space. Make sure the data directory is accessible.".into())
 					},
-					1 => (HealthStatus::NeedsAttention, "You are connected to only one peer. Your node might not be reliable. Check your network connection.".into()),
 					_ => (HealthStatus::Ok, "".into()),
 				};
-				HealthInfo { status, message, details: (connected, max) }
-			};
-
-			// Check sync
-			let sync = {
-				let is_syncing = sync_status.is_major_importing();
-				let (status, message) = if is_syncing {
-					(HealthStatus::NeedsAttention, "Your node is still syncing, the values you see might be outdated. Wait until it's fully synced.".into())
-				} else {
-					(HealthStatus::Ok, "".into())
-				};
-				HealthInfo { status, message, details: is_syncing }
+				HealthInfo { status: status, message: message, details: space }
 			};
 
 			// Check time
 			let time = {
+				let max_drift = thresholds.max_time_drift;
 				let (status, message, details) = match time {
-					Ok(Ok(diff)) if diff < MAX_DRIFT && diff > -MAX_DRIFT => {
+					Ok(Ok(diff)) if diff < max_drift && diff > -max_drift => {
 						(HealthStatus::Ok, "".into(), diff)
 					},
 					Ok(Ok(diff)) => {
@@ -116,7 +203,145 @@ impl NodeHealth {
 				HealthInfo { status, message, details, }
 			};
 
-			Ok(Health { peers, sync, time})
+			let client = match client {
+				Ok(Ok(client)) => client,
+				_ => return Ok(Self::stalled(thresholds.max_response_time, time, disk)),
+			};
+
+			// Check peers
+			let peers = {
+				let (connected, max) = client.peers;
+				let (status, message) = match connected {
+					0 => {
+						(HealthStatus::Bad, "You are not connected to any peers. There is most likely some network issue. Fix connectivity.".into())
+					},
+					n if n < thresholds.min_peers => (HealthStatus::NeedsAttention, format!(
+						"You are connected to only {} peer(s). Your node might not be reliable. Check your network connection.",
+						n,
+					)),
+					_ => (HealthStatus::Ok, "".into()),
+				};
+				HealthInfo { status, message, details: (connected, max) }
+			};
+
+			// Check sync
+			let sync = {
+				let is_syncing = client.is_syncing;
+				let (status, message) = if is_syncing {
+					(HealthStatus::NeedsAttention, "Your node is still syncing, the values you see might be outdated. Wait until it's fully synced.".into())
+				} else {
+					(HealthStatus::Ok, "".into())
+				};
+				HealthInfo { status, message, details: is_syncing }
+			};
+
+			// Check block lag
+			let lag = {
+				let lag = client.lag;
+				let (status, message) = match lag {
+					Some(lag) if lag > thresholds.max_block_lag => (HealthStatus::NeedsAttention, format!(
+						"Your node is {} blocks behind the network. Wait until it catches up.",
+						lag,
+					)),
+					_ => (HealthStatus::Ok, "".into()),
+				};
+				HealthInfo { status: status, message: message, details: lag }
+			};
+
+			// Check responsiveness
+			let responsiveness = {
+				let elapsed = client.elapsed;
+				let (status, message) = if elapsed > thresholds.max_response_time {
+					(HealthStatus::NeedsAttention, format!(
+						"Your node took {}ms to report its status. It might be overloaded.",
+						elapsed,
+					))
+				} else {
+					(HealthStatus::Ok, "".into())
+				};
+				HealthInfo { status: status, message: message, details: elapsed }
+			};
+
+			Ok(Health { peers, sync, time, lag: lag, disk: disk, responsiveness: responsiveness })
 		}).boxed()
 	}
+
+	/// Report of a client which didn't report its status in time.
+	/// Checks that depend on the client can't be performed.
+	fn stalled(max_response_time: u64, time: HealthInfo<i64>, disk: HealthInfo<Option<u64>>) -> Health {
+		let unknown = || "Unable to check, your node did not report its status.".to_owned();
+		Health {
+			peers: HealthInfo { status: HealthStatus::NeedsAttention, message: unknown(), details: (0, 0) },
+			sync: HealthInfo { status: HealthStatus::NeedsAttention, message: unknown(), details: true },
+			time: time,
+			lag: HealthInfo { status: HealthStatus::NeedsAttention, message: unknown(), details: None },
+			disk: disk,
+			responsiveness: HealthInfo {
+				status: HealthStatus::Bad,
+				message: format!(
+					"Your node did not report its status within {}ms. It might be stalled.",
+					max_response_time,
+				),
+				details: max_response_time,
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
+	use futures::Future;
+	use futures_cpupool::CpuPool;
+	use parity_reactor::EventLoop;
+	use time::TimeChecker;
+	use types::HealthStatus;
+	use SyncStatus;
+	use super::{NodeHealth, Thresholds};
+
+	#[derive(Debug)]
+	struct FakeSync(Duration);
+	impl SyncStatus for FakeSync {
+		fn is_major_importing(&self) -> bool { false }
+		fn peers(&self) -> (usize, usize) {
+			thread::sleep(self.0);
+			(5, 25)
+		}
+		fn block_lag(&self) -> Option<u64> { Some(0) }
+	}
+
+	fn node_health(event_loop: &EventLoop, response_time: Duration) -> NodeHealth {
+		NodeHealth::new(
+			Arc::new(FakeSync(response_time)),
+			TimeChecker::new::<String>(&[], CpuPool::new(1)),
+			CpuPool::new(1),
+			event_loop.remote(),
+			Thresholds { max_response_time: 100, ..Default::default() },
+			None,
+		)
+	}
+
+	#[test]
+	fn should_report_responsive_client() {
+		let event_loop = EventLoop::spawn();
+		let health = node_health(&event_loop, Duration::from_millis(0)).health().wait().unwrap();
+
+		assert_eq!(health.responsiveness.status, HealthStatus::Ok);
+		assert_eq!(health.peers.details, (5, 25));
+		assert_eq!(health.lag.status, HealthStatus::Ok);
+	}
+
+	#[test]
+	fn should_report_stalled_client_as_unresponsive() {
+		let event_loop = EventLoop::spawn();
+		let health = node_health(&event_loop, Duration::from_secs(10)).health().wait().unwrap();
+
+		assert_eq!(health.responsiveness.status, HealthStatus::Bad);
+		assert_eq!(health.responsiveness.details, 100);
+		assert_eq!(health.peers.status, HealthStatus::NeedsAttention);
+		assert_eq!(health.status(), HealthStatus::Bad);
+		assert!(!health.is_ready());
+	}
 }
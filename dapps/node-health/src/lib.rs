@@ -20,6 +20,7 @@
 
 extern crate futures;
 extern crate futures_cpupool;
+extern crate libc;
 extern crate ntp;
 extern crate time as time_crate;
 extern crate parity_reactor;
@@ -30,12 +31,13 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
+mod disk;
 mod health;
 mod time;
 mod types;
 
 pub use futures_cpupool::CpuPool;
-pub use health::NodeHealth;
+pub use health::{NodeHealth, Thresholds};
 pub use types::{Health, HealthInfo, HealthStatus};
 pub use time::{TimeChecker, Error};
 
@@ -46,4 +48,8 @@ pub trait SyncStatus: ::std::fmt::Debug + Send + Sync {
 
 	/// Returns number of connected and ideal peers.
 	fn peers(&self) -> (usize, usize);
+
+	/// Returns number of blocks between the best imported block
+	/// and the highest block seen on the network (if known).
+	fn block_lag(&self) -> Option<u64>;
 }
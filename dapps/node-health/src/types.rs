@@ -17,7 +17,7 @@
 //! Base health types.
 
 /// Health API endpoint status.
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum HealthStatus {
 	/// Everything's OK.
 	#[serde(rename = "ok")]
@@ -54,4 +54,76 @@ pub struct Health {
 	pub sync: HealthInfo<bool>,
 	/// Time diff info.
 	pub time: HealthInfo<i64>,
+	/// Number of blocks the node is behind the best block seen on the network.
+	pub lag: HealthInfo<Option<u64>>,
+	/// Free disk space (in bytes) available in the data directory.
+	pub disk: HealthInfo<Option<u64>>,
+	/// Time (in ms) it took the client to report its sync status and peers.
+	pub responsiveness: HealthInfo<u64>,
+}
+
+impl Health {
+	/// Returns the most severe status of all the checks.
+	pub fn status(&self) -> HealthStatus {
+		[
+			self.peers.status,
+			self.sync.status,
+			self.time.status,
+			self.lag.status,
+			self.disk.status,
+			self.responsiveness.status,
+		].iter().cloned().max().expect("The array is not empty; qed")
+	}
+
+	/// Returns true if the node is ready to serve requests,
+	/// i.e. it's fully synced and none of the checks report a bad status.
+	pub fn is_ready(&self) -> bool {
+		self.sync.status == HealthStatus::Ok
+			&& self.lag.status == HealthStatus::Ok
+			&& self.status() != HealthStatus::Bad
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Health, HealthInfo, HealthStatus};
+
+	fn info<T>(status: HealthStatus, details: T) -> HealthInfo<T> {
+		HealthInfo { status: status, message: "".into(), details: details }
+	}
+
+	fn healthy() -> Health {
+		Health {
+			peers: info(HealthStatus::Ok, (25, 50)),
+			sync: info(HealthStatus::Ok, false),
+			time: info(HealthStatus::Ok, 0),
+			lag: info(HealthStatus::Ok, Some(0)),
+			disk: info(HealthStatus::Ok, None),
+			responsiveness: info(HealthStatus::Ok, 0),
+		}
+	}
+
+	#[test]
+	fn should_report_most_severe_status() {
+		let mut health = healthy();
+		assert_eq!(health.status(), HealthStatus::Ok);
+		assert!(health.is_ready());
+
+		health.time.status = HealthStatus::NeedsAttention;
+		assert_eq!(health.status(), HealthStatus::NeedsAttention);
+		assert!(health.is_ready());
+
+		health.disk.status = HealthStatus::Bad;
+		assert_eq!(health.status(), HealthStatus::Bad);
+		assert!(!health.is_ready());
+	}
+
+	#[test]
+	fn should_not_be_ready_when_lagging() {
+		let mut health = healthy();
+		health.lag = info(HealthStatus::NeedsAttention, Some(100));
+
+		assert_eq!(health.status(), HealthStatus::NeedsAttention);
+		assert!(!health.is_ready());
+	}
 }
@@ -54,6 +54,17 @@ impl Endpoint for RestApi {
 	}
 }
 
+/// Kind of the health check requested.
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+	/// Detailed status report (`/api/health`).
+	Status,
+	/// Liveness probe (`/api/health/live`).
+	Liveness,
+	/// Readiness probe (`/api/health/ready`).
+	Readiness,
+}
+
 struct RestApiRouter {
 	api: RestApi,
 	path: Option<EndpointPath>,
@@ -85,15 +96,40 @@ impl RestApiRouter {
 		}
 	}
 
-	fn health(&self, control: Control) -> Box<Handler> {
+	fn health(&self, probe: Option<&str>, control: Control) -> Box<Handler> {
+		let probe = match probe {
+			None => Probe::Status,
+			Some("live") => Probe::Liveness,
+			Some("ready") => Probe::Readiness,
+			Some(_) => return Box::new(response::as_json_error(StatusCode::NotFound, &types::ApiError {
+				code: "404".into(),
+				title: "Unknown probe".into(),
+				detail: "Only `live` and `ready` health probes are supported.".into(),
+			})),
+		};
+
 		let map = move |health: Result<Result<Health, ()>, ()>| {
 			let status = match health {
-				Ok(Ok(ref health)) => {
-					if [&health.peers.status, &health.sync.status].iter().any(|x| *x != &HealthStatus::Ok) {
-						StatusCode::PreconditionFailed // HTTP 412
-					} else {
-						StatusCode::Ok // HTTP 200
-					}
+				Ok(Ok(ref health)) => match probe {
+					Probe::Status => {
+						let checks = [
+							&health.peers.status,
+							&health.sync.status,
+							&health.lag.status,
+							&health.disk.status,
+							&health.responsiveness.status,
+						];
+						if checks.iter().any(|x| *x != &HealthStatus::Ok) {
+							StatusCode::PreconditionFailed // HTTP 412
+						} else {
+							StatusCode::Ok // HTTP 200
+						}
+					},
+					// The node managed to produce a report, so it's alive unless the client is stalled.
+					Probe::Liveness if health.responsiveness.status == HealthStatus::Bad => StatusCode::ServiceUnavailable, // HTTP 503
+					Probe::Liveness => StatusCode::Ok, // HTTP 200
+					Probe::Readiness if health.is_ready() => StatusCode::Ok, // HTTP 200
+					Probe::Readiness => StatusCode::ServiceUnavailable, // HTTP 503
 				},
 				_ => StatusCode::ServiceUnavailable, // HTTP 503
 			};
@@ -132,7 +168,7 @@ impl server::Handler<net::HttpStream> for RestApiRouter {
 
 		let handler = endpoint.and_then(|v| match v {
 			"ping" => Some(response::ping()),
-			"health" => Some(self.health(control)),
+			"health" => Some(self.health(hash, control)),
 			"content" => self.resolve_content(hash, path, control),
 			_ => None
 		});
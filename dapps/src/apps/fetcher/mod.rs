@@ -286,6 +286,7 @@ mod tests {
 	impl SyncStatus for FakeSync {
 		fn is_major_importing(&self) -> bool { self.0 }
 		fn peers(&self) -> (usize, usize) { (0, 5) }
+		fn block_lag(&self) -> Option<u64> { None }
 	}
 
 	#[test]
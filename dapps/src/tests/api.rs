@@ -83,3 +83,65 @@ fn should_try_to_resolve_dapp() {
 	assert_eq!(registrar.calls.lock().len(), 2);
 	assert_security_headers(&response.headers);
 }
+
+#[test]
+fn should_report_liveness() {
+	// given
+	let server = serve();
+
+	// when
+	let response = request(server,
+		"\
+			GET /api/health/live HTTP/1.1\r\n\
+			Host: home.parity\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	response.assert_status("HTTP/1.1 200 OK");
+	response.assert_header("Content-Type", "application/json");
+	assert_security_headers(&response.headers);
+}
+
+#[test]
+fn should_report_not_ready_without_peers() {
+	// given
+	let server = serve();
+
+	// when
+	let response = request(server,
+		"\
+			GET /api/health/ready HTTP/1.1\r\n\
+			Host: home.parity\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	response.assert_status("HTTP/1.1 503 Service Unavailable");
+	response.assert_header("Content-Type", "application/json");
+	assert_security_headers(&response.headers);
+}
+
+#[test]
+fn should_return_error_for_unknown_probe() {
+	// given
+	let server = serve();
+
+	// when
+	let response = request(server,
+		"\
+			GET /api/health/unknown HTTP/1.1\r\n\
+			Host: home.parity\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	response.assert_status("HTTP/1.1 404 Not Found");
+	assert_security_headers(&response.headers);
+}
@@ -44,6 +44,7 @@ struct FakeSync(bool);
 impl SyncStatus for FakeSync {
 	fn is_major_importing(&self) -> bool { self.0 }
 	fn peers(&self) -> (usize, usize) { (0, 5) }
+	fn block_lag(&self) -> Option<u64> { None }
 }
 
 fn init_logger() {
@@ -258,7 +259,10 @@ impl Server {
 		let health = NodeHealth::new(
 			sync_status.clone(),
 			TimeChecker::new::<String>(&[], CpuPool::new(1)),
+			CpuPool::new(1),
 			remote.clone(),
+			Default::default(),
+			None,
 		);
 		let middleware = Middleware::dapps(
 			health,
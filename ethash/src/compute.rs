@@ -25,11 +25,13 @@ use std::mem;
 use std::ptr;
 use hash;
 use std::slice;
+use std::sync::Arc;
 use std::path::{Path, PathBuf};
 use std::io::{self, Read, Write};
 use std::fs::{self, File};
 
 use parking_lot::Mutex;
+use progpow::{self, CDag};
 
 pub const ETHASH_EPOCH_LENGTH: u64 = 30000;
 pub const ETHASH_CACHE_ROUNDS: usize = 3;
@@ -41,11 +43,11 @@ const DATASET_BYTES_INIT: u64 = 1 << 30;
 const DATASET_BYTES_GROWTH: u64 = 1 << 23;
 const CACHE_BYTES_INIT: u64 = 1 << 24;
 const CACHE_BYTES_GROWTH: u64 = 1 << 17;
-const NODE_WORDS: usize = 64 / 4;
+pub const NODE_WORDS: usize = 64 / 4;
 const NODE_BYTES: usize = 64;
 const MIX_WORDS: usize = ETHASH_MIX_BYTES / 4;
 const MIX_NODES: usize = MIX_WORDS / NODE_WORDS;
-const FNV_PRIME: u32 = 0x01000193;
+pub const FNV_PRIME: u32 = 0x01000193;
 
 /// Computation result
 pub struct ProofOfWork {
@@ -55,7 +57,7 @@ pub struct ProofOfWork {
 	pub mix_hash: H256,
 }
 
-struct Node {
+pub struct Node {
 	bytes: [u8; NODE_BYTES],
 }
 
@@ -73,7 +75,7 @@ impl Clone for Node {
 
 impl Node {
 	#[inline]
-	fn as_words(&self) -> &[u32; NODE_WORDS] {
+	pub fn as_words(&self) -> &[u32; NODE_WORDS] {
 		unsafe { mem::transmute(&self.bytes) }
 	}
 
@@ -89,6 +91,7 @@ pub struct Light {
 	cache_dir: PathBuf,
 	block_number: u64,
 	cache: Vec<Node>,
+	c_dag: Mutex<Option<Arc<CDag>>>,
 	seed_compute: Mutex<SeedHashCompute>,
 }
 
@@ -106,6 +109,23 @@ impl Light {
 		light_compute(self, header_hash, nonce)
 	}
 
+	/// Calculate the ProgPoW boundary data
+	/// `block_number` - The block number, selecting the ProgPoW program
+	/// `header_hash` - The header hash to pack into the mix
+	/// `nonce` - The nonce to pack into the mix
+	pub fn progpow_compute(&self, block_number: u64, header_hash: &H256, nonce: u64) -> ProofOfWork {
+		let c_dag = self.c_dag();
+		progpow::progpow(header_hash, nonce, block_number, &self.cache, &c_dag)
+	}
+
+	fn c_dag(&self) -> Arc<CDag> {
+		let mut c_dag = self.c_dag.lock();
+		if c_dag.is_none() {
+			*c_dag = Some(Arc::new(progpow::generate_cdag(&self.cache)));
+		}
+		c_dag.as_ref().expect("initialized above; qed").clone()
+	}
+
 	pub fn file_path<T: AsRef<Path>>(cache_dir: T, seed_hash: H256) -> PathBuf {
 		let mut cache_dir = cache_dir.as_ref().to_path_buf();
 		cache_dir.push(to_hex(&seed_hash));
@@ -132,6 +152,7 @@ impl Light {
 			block_number,
 			cache_dir: cache_dir.as_ref().to_path_buf(),
 			cache: nodes,
+			c_dag: Mutex::new(None),
 			seed_compute: Mutex::new(seed_compute),
 		})
 	}
@@ -233,7 +254,7 @@ fn get_cache_size(block_number: u64) -> usize {
 	sz as usize
 }
 
-fn get_data_size(block_number: u64) -> usize {
+pub fn get_data_size(block_number: u64) -> usize {
 	let mut sz: u64 = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * (block_number / ETHASH_EPOCH_LENGTH);
 	sz = sz - ETHASH_MIX_BYTES as u64;
 	while !is_prime(sz / ETHASH_MIX_BYTES as u64) {
@@ -247,8 +268,13 @@ fn get_data_size(block_number: u64) -> usize {
 /// `header_hash`      The hash of the header
 /// `nonce`            The block's nonce
 /// `mix_hash`         The mix digest hash
+/// `progpow`          Whether the block is sealed with ProgPoW
 /// Boundary recovered from mix hash
-pub fn quick_get_difficulty(header_hash: &H256, nonce: u64, mix_hash: &H256) -> H256 {
+pub fn quick_get_difficulty(header_hash: &H256, nonce: u64, mix_hash: &H256, progpow: bool) -> H256 {
+	if progpow {
+		return progpow::quick_get_difficulty(header_hash, nonce, mix_hash);
+	}
+
 	unsafe {
 		// This is safe - the `keccak_512` call below reads the first 40 bytes (which we explicitly set
 		// with two `copy_nonoverlapping` calls) but writes the first 64, and then we explicitly write
@@ -445,7 +471,7 @@ fn hash_compute(light: &Light, full_size: usize, header_hash: &H256, nonce: u64)
 	}
 }
 
-fn calculate_dag_item(node_index: u32, cache: &[Node]) -> Node {
+pub fn calculate_dag_item(node_index: u32, cache: &[Node]) -> Node {
 	let num_parent_nodes = cache.len();
 	let mut ret = cache[node_index as usize % num_parent_nodes].clone();
 	ret.as_words_mut()[0] ^= node_index;
@@ -513,6 +539,7 @@ fn light_new<T: AsRef<Path>>(cache_dir: T, block_number: u64) -> Light {
 		block_number,
 		cache_dir: cache_dir.as_ref().to_path_buf(),
 		cache: nodes,
+		c_dag: Mutex::new(None),
 		seed_compute: Mutex::new(seed_compute),
 	}
 }
@@ -559,9 +586,9 @@ fn test_difficulty_test() {
 	let mix_hash = [0x1f, 0xff, 0x04, 0xce, 0xc9, 0x41, 0x73, 0xfd, 0x59, 0x1e, 0x3d, 0x89, 0x60, 0xce, 0x6b, 0xdf, 0x8b, 0x19, 0x71, 0x04, 0x8c, 0x71, 0xff, 0x93, 0x7b, 0xb2, 0xd3, 0x2a, 0x64, 0x31, 0xab, 0x6d];
 	let nonce = 0xd7b3ac70a301a249;
 	let boundary_good = [0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x3e, 0x9b, 0x6c, 0x69, 0xbc, 0x2c, 0xe2, 0xa2, 0x4a, 0x8e, 0x95, 0x69, 0xef, 0xc7, 0xd7, 0x1b, 0x33, 0x35, 0xdf, 0x36, 0x8c, 0x9a, 0xe9, 0x7e, 0x53, 0x84];
-	assert_eq!(quick_get_difficulty(&hash, nonce, &mix_hash)[..], boundary_good[..]);
+	assert_eq!(quick_get_difficulty(&hash, nonce, &mix_hash, false)[..], boundary_good[..]);
 	let boundary_bad = [0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x3a, 0x9b, 0x6c, 0x69, 0xbc, 0x2c, 0xe2, 0xa2, 0x4a, 0x8e, 0x95, 0x69, 0xef, 0xc7, 0xd7, 0x1b, 0x33, 0x35, 0xdf, 0x36, 0x8c, 0x9a, 0xe9, 0x7e, 0x53, 0x84];
-	assert!(quick_get_difficulty(&hash, nonce, &mix_hash)[..] != boundary_bad[..]);
+	assert!(quick_get_difficulty(&hash, nonce, &mix_hash, false)[..] != boundary_bad[..]);
 }
 
 #[test]
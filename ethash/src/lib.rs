@@ -16,6 +16,9 @@
 
 //! Ethash implementation
 //! See https://github.com/ethereum/wiki/wiki/Ethash
+//!
+//! Chains may switch to ProgPoW (see https://github.com/ifdefelse/ProgPOW) at a transition block;
+//! it reuses the Ethash epochs, light cache and DAG.
 
 #![cfg_attr(feature = "benches", feature(test))]
 
@@ -28,6 +31,7 @@ extern crate crunchy;
 #[macro_use]
 extern crate log;
 mod compute;
+mod progpow;
 
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -48,13 +52,16 @@ struct LightCache {
 pub struct EthashManager {
 	cache: Mutex<LightCache>,
 	cache_dir: PathBuf,
+	progpow_transition: u64,
 }
 
 impl EthashManager {
-	/// Create a new new instance of ethash manager
-	pub fn new<T: AsRef<Path>>(cache_dir: T) -> EthashManager {
+	/// Create a new new instance of ethash manager.
+	/// Blocks starting from `progpow_transition` are computed with ProgPoW.
+	pub fn new<T: AsRef<Path>>(cache_dir: T, progpow_transition: u64) -> EthashManager {
 		EthashManager {
 			cache_dir: cache_dir.as_ref().to_path_buf(),
+			progpow_transition: progpow_transition,
 			cache: Mutex::new(LightCache {
 				recent_epoch: None,
 				recent: None,
@@ -70,57 +77,68 @@ impl EthashManager {
 	/// `header_hash` - The header hash to pack into the mix
 	/// `nonce` - The nonce to pack into the mix
 	pub fn compute_light(&self, block_number: u64, header_hash: &H256, nonce: u64) -> ProofOfWork {
+		let light = self.light(block_number);
+		if self.is_progpow(block_number) {
+			light.progpow_compute(block_number, header_hash, nonce)
+		} else {
+			light.compute(header_hash, nonce)
+		}
+	}
+
+	/// Returns true if given block is sealed with ProgPoW.
+	pub fn is_progpow(&self, block_number: u64) -> bool {
+		block_number >= self.progpow_transition
+	}
+
+	fn light(&self, block_number: u64) -> Arc<Light> {
 		let epoch = block_number / ETHASH_EPOCH_LENGTH;
-		let light = {
-			let mut lights = self.cache.lock();
-			let light = match lights.recent_epoch.clone() {
-				Some(ref e) if *e == epoch => lights.recent.clone(),
-				_ => match lights.prev_epoch.clone() {
-					Some(e) if e == epoch => {
-						// don't swap if recent is newer.
-						if lights.recent_epoch > lights.prev_epoch {
-							None
-						} else {
-							// swap
-							let t = lights.prev_epoch;
-							lights.prev_epoch = lights.recent_epoch;
-							lights.recent_epoch = t;
-							let t = lights.prev.clone();
-							lights.prev = lights.recent.clone();
-							lights.recent = t;
-							lights.recent.clone()
-						}
+		let mut lights = self.cache.lock();
+		let light = match lights.recent_epoch.clone() {
+			Some(ref e) if *e == epoch => lights.recent.clone(),
+			_ => match lights.prev_epoch.clone() {
+				Some(e) if e == epoch => {
+					// don't swap if recent is newer.
+					if lights.recent_epoch > lights.prev_epoch {
+						None
+					} else {
+						// swap
+						let t = lights.prev_epoch;
+						lights.prev_epoch = lights.recent_epoch;
+						lights.recent_epoch = t;
+						let t = lights.prev.clone();
+						lights.prev = lights.recent.clone();
+						lights.recent = t;
+						lights.recent.clone()
 					}
-					_ => None,
-				},
-			};
-			match light {
-				None => {
-					let light = match Light::from_file(&self.cache_dir, block_number) {
-						Ok(light) => Arc::new(light),
-						Err(e) => {
-							debug!("Light cache file not found for {}:{}", block_number, e);
-							let light = Light::new(&self.cache_dir, block_number);
-							if let Err(e) = light.to_file() {
-								warn!("Light cache file write error: {}", e);
-							}
-							Arc::new(light)
-						}
-					};
-					lights.prev_epoch = mem::replace(&mut lights.recent_epoch, Some(epoch));
-					lights.prev = mem::replace(&mut lights.recent, Some(light.clone()));
-					light
 				}
-				Some(light) => light,
-			}
+				_ => None,
+			},
 		};
-		light.compute(header_hash, nonce)
+		match light {
+			None => {
+				let light = match Light::from_file(&self.cache_dir, block_number) {
+					Ok(light) => Arc::new(light),
+					Err(e) => {
+						debug!("Light cache file not found for {}:{}", block_number, e);
+						let light = Light::new(&self.cache_dir, block_number);
+						if let Err(e) = light.to_file() {
+							warn!("Light cache file write error: {}", e);
+						}
+						Arc::new(light)
+					}
+				};
+				lights.prev_epoch = mem::replace(&mut lights.recent_epoch, Some(epoch));
+				lights.prev = mem::replace(&mut lights.recent, Some(light.clone()));
+				light
+			}
+			Some(light) => light,
+		}
 	}
 }
 
 #[test]
 fn test_lru() {
-	let ethash = EthashManager::new(&::std::env::temp_dir());
+	let ethash = EthashManager::new(&::std::env::temp_dir(), u64::max_value());
 	let hash = [0u8; 32];
 	ethash.compute_light(1, &hash, 1);
 	ethash.compute_light(50000, &hash, 1);
@@ -134,6 +152,19 @@ fn test_lru() {
 	assert_eq!(ethash.cache.lock().prev_epoch.unwrap(), 0);
 }
 
+#[test]
+fn test_progpow_transition() {
+	let ethash = EthashManager::new(&::std::env::temp_dir(), 10);
+	let hash = [0u8; 32];
+	let ethash_result = ethash.compute_light(9, &hash, 1);
+	let progpow_result = ethash.compute_light(10, &hash, 1);
+
+	assert!(!ethash.is_progpow(9));
+	assert!(ethash.is_progpow(10));
+	assert_eq!(quick_get_difficulty(&hash, 1, &ethash_result.mix_hash, false), ethash_result.value);
+	assert_eq!(quick_get_difficulty(&hash, 1, &progpow_result.mix_hash, true), progpow_result.value);
+}
+
 #[cfg(feature = "benches")]
 mod benchmarks {
 	extern crate test;
@@ -0,0 +1,482 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ProgPoW implementation (version 0.9.2)
+//! See https://github.com/ifdefelse/ProgPOW
+
+use std::cmp;
+use compute::{Node, ProofOfWork, H256, FNV_PRIME, NODE_WORDS, calculate_dag_item, get_data_size};
+
+const PROGPOW_CACHE_BYTES: usize = 16 * 1024;
+const PROGPOW_CACHE_WORDS: usize = PROGPOW_CACHE_BYTES / 4;
+const PROGPOW_CNT_CACHE: usize = 12;
+const PROGPOW_CNT_MATH: usize = 20;
+const PROGPOW_CNT_DAG: usize = 64;
+const PROGPOW_DAG_LOADS: usize = 4;
+const PROGPOW_MIX_BYTES: usize = 256;
+const PROGPOW_PERIOD_LENGTH: u64 = 50;
+const PROGPOW_LANES: usize = 16;
+const PROGPOW_REGS: usize = 32;
+const FNV_HASH: u32 = 0x811c9dc5;
+
+const KECCAKF_RNDC: [u32; 24] = [
+	0x00000001, 0x00008082, 0x0000808a, 0x80008000, 0x0000808b, 0x80000001,
+	0x80008081, 0x00008009, 0x0000008a, 0x00000088, 0x80008009, 0x8000000a,
+	0x8000808b, 0x0000008b, 0x00008089, 0x00008003, 0x00008002, 0x00000080,
+	0x0000800a, 0x8000000a, 0x80008081, 0x00008080, 0x80000001, 0x80008008,
+];
+
+const KECCAKF_ROTC: [u32; 24] = [
+	1, 3, 6, 10, 15, 21, 28, 4, 13, 23, 2, 14,
+	27, 9, 24, 8, 25, 11, 30, 18, 7, 29, 20, 12,
+];
+
+const KECCAKF_PILN: [usize; 24] = [
+	10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4,
+	15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// First `PROGPOW_CACHE_BYTES` of the DAG, read by every lane on each loop.
+pub type CDag = [u32; PROGPOW_CACHE_WORDS];
+
+fn keccak_f800_round(st: &mut [u32; 25], r: usize) {
+	// Theta
+	let mut bc = [0u32; 5];
+	for i in 0..5 {
+		bc[i] = st[i] ^ st[i + 5] ^ st[i + 10] ^ st[i + 15] ^ st[i + 20];
+	}
+	for i in 0..5 {
+		let t = bc[(i + 4) % 5] ^ bc[(i + 1) % 5].rotate_left(1);
+		for j in 0..5 {
+			st[j * 5 + i] ^= t;
+		}
+	}
+
+	// Rho Pi
+	let mut t = st[1];
+	for i in 0..24 {
+		let j = KECCAKF_PILN[i];
+		bc[0] = st[j];
+		st[j] = t.rotate_left(KECCAKF_ROTC[i]);
+		t = bc[0];
+	}
+
+	// Chi
+	for j in 0..5 {
+		for i in 0..5 {
+			bc[i] = st[j * 5 + i];
+		}
+		for i in 0..5 {
+			st[j * 5 + i] ^= !bc[(i + 1) % 5] & bc[(i + 2) % 5];
+		}
+	}
+
+	// Iota
+	st[0] ^= KECCAKF_RNDC[r];
+}
+
+fn keccak_f800(header_hash: &H256, nonce: u64, digest: &[u32; 8]) -> [u32; 25] {
+	let mut st = [0u32; 25];
+	st[..8].copy_from_slice(&to_words(header_hash));
+	st[8] = nonce as u32;
+	st[9] = (nonce >> 32) as u32;
+	st[10..18].copy_from_slice(digest);
+
+	for r in 0..22 {
+		keccak_f800_round(&mut st, r);
+	}
+	st
+}
+
+/// Seed of the mix: first 64 bits of the absorbed header and nonce.
+fn keccak_f800_short(header_hash: &H256, nonce: u64, digest: &[u32; 8]) -> u64 {
+	let st = keccak_f800(header_hash, nonce, digest);
+	// endian swap so byte 0 of the hash is the MSB of the value
+	(st[0].swap_bytes() as u64) << 32 | st[1].swap_bytes() as u64
+}
+
+/// Final hash compared against the boundary.
+fn keccak_f800_long(header_hash: &H256, seed: u64, digest: &[u32; 8]) -> H256 {
+	let st = keccak_f800(header_hash, seed, digest);
+	let mut words = [0u32; 8];
+	words.copy_from_slice(&st[..8]);
+	to_bytes(&words)
+}
+
+fn to_words(bytes: &H256) -> [u32; 8] {
+	let mut words = [0u32; 8];
+	for i in 0..8 {
+		words[i] = bytes[i * 4] as u32
+			| (bytes[i * 4 + 1] as u32) << 8
+			| (bytes[i * 4 + 2] as u32) << 16
+			| (bytes[i * 4 + 3] as u32) << 24;
+	}
+	words
+}
+
+fn to_bytes(words: &[u32; 8]) -> H256 {
+	let mut bytes = [0u8; 32];
+	for i in 0..8 {
+		bytes[i * 4] = words[i] as u8;
+		bytes[i * 4 + 1] = (words[i] >> 8) as u8;
+		bytes[i * 4 + 2] = (words[i] >> 16) as u8;
+		bytes[i * 4 + 3] = (words[i] >> 24) as u8;
+	}
+	bytes
+}
+
+#[inline]
+fn fnv1a_hash(h: u32, d: u32) -> u32 {
+	(h ^ d).wrapping_mul(FNV_PRIME)
+}
+
+#[derive(Clone)]
+struct Kiss99 {
+	z: u32,
+	w: u32,
+	jsr: u32,
+	jcong: u32,
+}
+
+impl Kiss99 {
+	fn new(z: u32, w: u32, jsr: u32, jcong: u32) -> Kiss99 {
+		Kiss99 { z: z, w: w, jsr: jsr, jcong: jcong }
+	}
+
+	#[inline]
+	fn next_u32(&mut self) -> u32 {
+		self.z = 36969u32.wrapping_mul(self.z & 65535).wrapping_add(self.z >> 16);
+		self.w = 18000u32.wrapping_mul(self.w & 65535).wrapping_add(self.w >> 16);
+		let mwc = (self.z << 16).wrapping_add(self.w);
+		self.jsr ^= self.jsr << 17;
+		self.jsr ^= self.jsr >> 13;
+		self.jsr ^= self.jsr << 5;
+		self.jcong = 69069u32.wrapping_mul(self.jcong).wrapping_add(1234567);
+
+		(mwc ^ self.jcong).wrapping_add(self.jsr)
+	}
+}
+
+fn fill_mix(seed: u64, lane_id: u32) -> [u32; PROGPOW_REGS] {
+	// Use FNV to expand the per-warp seed to per-lane
+	// Use KISS to expand the per-lane seed to fill mix
+	let z = fnv1a_hash(FNV_HASH, seed as u32);
+	let w = fnv1a_hash(z, (seed >> 32) as u32);
+	let jsr = fnv1a_hash(w, lane_id);
+	let jcong = fnv1a_hash(jsr, lane_id);
+	let mut rnd = Kiss99::new(z, w, jsr, jcong);
+
+	let mut mix = [0u32; PROGPOW_REGS];
+	for i in 0..PROGPOW_REGS {
+		mix[i] = rnd.next_u32();
+	}
+	mix
+}
+
+// Merge new data from `b` into the value in `a`. Assuming `a` has high entropy only
+// do ops that retain entropy even if `b` has low entropy (i.e. don't do `a & b`).
+fn merge(a: u32, b: u32, r: u32) -> u32 {
+	match r % 4 {
+		0 => a.wrapping_mul(33).wrapping_add(b),
+		1 => (a ^ b).wrapping_mul(33),
+		2 => a.rotate_left(((r >> 16) % 31) + 1) ^ b,
+		_ => a.rotate_right(((r >> 16) % 31) + 1) ^ b,
+	}
+}
+
+fn math(a: u32, b: u32, r: u32) -> u32 {
+	match r % 11 {
+		0 => a.wrapping_add(b),
+		1 => a.wrapping_mul(b),
+		2 => ((a as u64 * b as u64) >> 32) as u32,
+		3 => cmp::min(a, b),
+		4 => a.rotate_left(b),
+		5 => a.rotate_right(b),
+		6 => a & b,
+		7 => a | b,
+		8 => a ^ b,
+		9 => a.leading_zeros() + b.leading_zeros(),
+		_ => a.count_ones() + b.count_ones(),
+	}
+}
+
+fn progpow_init(seed: u64) -> (Kiss99, [u32; PROGPOW_REGS], [u32; PROGPOW_REGS]) {
+	let z = fnv1a_hash(FNV_HASH, seed as u32);
+	let w = fnv1a_hash(z, (seed >> 32) as u32);
+	let jsr = fnv1a_hash(w, seed as u32);
+	let jcong = fnv1a_hash(jsr, (seed >> 32) as u32);
+	let mut rnd = Kiss99::new(z, w, jsr, jcong);
+
+	// Create a random sequence of mix destinations for merge() and mix sources for cache reads.
+	// Guarantees every destination is merged once and there are no duplicate cache reads,
+	// which could be optimized away. Uses Fisher-Yates shuffle.
+	let mut mix_seq_dst = [0u32; PROGPOW_REGS];
+	let mut mix_seq_cache = [0u32; PROGPOW_REGS];
+	for i in 0..PROGPOW_REGS {
+		mix_seq_dst[i] = i as u32;
+		mix_seq_cache[i] = i as u32;
+	}
+	for i in (1..PROGPOW_REGS).rev() {
+		let j = rnd.next_u32() as usize % (i + 1);
+		mix_seq_dst.swap(i, j);
+		let j = rnd.next_u32() as usize % (i + 1);
+		mix_seq_cache.swap(i, j);
+	}
+
+	(rnd, mix_seq_dst, mix_seq_cache)
+}
+
+fn progpow_loop(
+	init: &(Kiss99, [u32; PROGPOW_REGS], [u32; PROGPOW_REGS]),
+	loop_: usize,
+	mix: &mut [[u32; PROGPOW_REGS]; PROGPOW_LANES],
+	cache: &[Node],
+	c_dag: &CDag,
+	data_size: usize,
+) {
+	// All lanes share a base address for the global load. Global offset uses
+	// mix[0] to guarantee it depends on the load result.
+	let g_offset = mix[loop_ % PROGPOW_LANES][0] as usize % data_size;
+
+	// 256 bytes of DAG data, fetched as 64 byte nodes
+	let mut dag_item = [0u32; PROGPOW_LANES * PROGPOW_DAG_LOADS];
+	for l in 0..PROGPOW_DAG_LOADS {
+		let index = g_offset * PROGPOW_LANES * PROGPOW_DAG_LOADS + l * NODE_WORDS;
+		let node = calculate_dag_item((index / NODE_WORDS) as u32, cache);
+		dag_item[l * NODE_WORDS..(l + 1) * NODE_WORDS].copy_from_slice(node.as_words());
+	}
+
+	let (ref rnd, ref mix_seq_dst, ref mix_seq_cache) = *init;
+
+	// Lanes can execute in parallel and will be convergent
+	for l in 0..PROGPOW_LANES {
+		let mut rnd = rnd.clone();
+		let mut mix_seq_dst_cnt = 0;
+		let mut mix_seq_cache_cnt = 0;
+
+		for i in 0..cmp::max(PROGPOW_CNT_CACHE, PROGPOW_CNT_MATH) {
+			if i < PROGPOW_CNT_CACHE {
+				// Cached memory access, lanes access random 32-bit locations
+				// within the first portion of the DAG
+				let src = mix_seq_cache[mix_seq_cache_cnt % PROGPOW_REGS] as usize;
+				mix_seq_cache_cnt += 1;
+				let dst = mix_seq_dst[mix_seq_dst_cnt % PROGPOW_REGS] as usize;
+				mix_seq_dst_cnt += 1;
+
+				let data = c_dag[mix[l][src] as usize % PROGPOW_CACHE_WORDS];
+				mix[l][dst] = merge(mix[l][dst], data, rnd.next_u32());
+			}
+
+			if i < PROGPOW_CNT_MATH {
+				// Random math on two unique sources
+				let src_rnd = rnd.next_u32() % (PROGPOW_REGS * (PROGPOW_REGS - 1)) as u32;
+				let src1 = (src_rnd % PROGPOW_REGS as u32) as usize;
+				let mut src2 = (src_rnd / PROGPOW_REGS as u32) as usize;
+				if src2 >= src1 {
+					src2 += 1;
+				}
+
+				let data = math(mix[l][src1], mix[l][src2], rnd.next_u32());
+				let dst = mix_seq_dst[mix_seq_dst_cnt % PROGPOW_REGS] as usize;
+				mix_seq_dst_cnt += 1;
+				mix[l][dst] = merge(mix[l][dst], data, rnd.next_u32());
+			}
+		}
+
+		// Global load to sequential locations
+		let index = ((l ^ loop_) % PROGPOW_LANES) * PROGPOW_DAG_LOADS;
+
+		// Consume the global load data at the very end of the loop to allow full latency hiding.
+		// Always merge into mix[0] to feed the offset calculation.
+		mix[l][0] = merge(mix[l][0], dag_item[index], rnd.next_u32());
+		for i in 1..PROGPOW_DAG_LOADS {
+			let dst = mix_seq_dst[mix_seq_dst_cnt % PROGPOW_REGS] as usize;
+			mix_seq_dst_cnt += 1;
+			mix[l][dst] = merge(mix[l][dst], dag_item[index + i], rnd.next_u32());
+		}
+	}
+}
+
+/// Generate the cached part of the DAG from the light cache.
+pub fn generate_cdag(cache: &[Node]) -> CDag {
+	let mut c_dag = [0u32; PROGPOW_CACHE_WORDS];
+	for i in 0..PROGPOW_CACHE_WORDS / NODE_WORDS {
+		let node = calculate_dag_item(i as u32, cache);
+		c_dag[i * NODE_WORDS..(i + 1) * NODE_WORDS].copy_from_slice(node.as_words());
+	}
+	c_dag
+}
+
+/// Calculate the ProgPoW result for given block.
+/// `header_hash` - The header hash to pack into the mix
+/// `nonce` - The nonce to pack into the mix
+pub fn progpow(header_hash: &H256, nonce: u64, block_number: u64, cache: &[Node], c_dag: &CDag) -> ProofOfWork {
+	let mut mix = [[0u32; PROGPOW_REGS]; PROGPOW_LANES];
+	let data_size = get_data_size(block_number) / PROGPOW_MIX_BYTES;
+	assert!(data_size > 0, "DAG size is never smaller than a single mix; qed");
+
+	// Initialize mix for all lanes
+	let seed = keccak_f800_short(header_hash, nonce, &[0u32; 8]);
+	for l in 0..PROGPOW_LANES {
+		mix[l] = fill_mix(seed, l as u32);
+	}
+
+	// Execute the randomly generated inner loop
+	// The random program only depends on the period, so it is generated once for all loops
+	let init = progpow_init(block_number / PROGPOW_PERIOD_LENGTH);
+	for i in 0..PROGPOW_CNT_DAG {
+		progpow_loop(&init, i, &mut mix, cache, c_dag, data_size);
+	}
+
+	// Reduce mix data to a single per-lane result
+	let mut lane_results = [FNV_HASH; PROGPOW_LANES];
+	for l in 0..PROGPOW_LANES {
+		for i in 0..PROGPOW_REGS {
+			lane_results[l] = fnv1a_hash(lane_results[l], mix[l][i]);
+		}
+	}
+
+	// Reduce all lanes to a single 256-bit result
+	let mut result = [FNV_HASH; 8];
+	for l in 0..PROGPOW_LANES {
+		result[l % 8] = fnv1a_hash(result[l % 8], lane_results[l]);
+	}
+
+	ProofOfWork {
+		value: keccak_f800_long(header_hash, seed, &result),
+		mix_hash: to_bytes(&result),
+	}
+}
+
+/// Difficulty quick check for ProgPoW preverification
+///
+/// `header_hash`      The hash of the header
+/// `nonce`            The block's nonce
+/// `mix_hash`         The mix digest hash
+/// Boundary recovered from mix hash
+pub fn quick_get_difficulty(header_hash: &H256, nonce: u64, mix_hash: &H256) -> H256 {
+	let seed = keccak_f800_short(header_hash, nonce, &[0u32; 8]);
+	keccak_f800_long(header_hash, seed, &to_words(mix_hash))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use compute::Light;
+	use super::{Kiss99, fnv1a_hash, merge, math, quick_get_difficulty, to_bytes, to_words};
+
+	#[test]
+	fn test_kiss99() {
+		let mut rnd = Kiss99::new(362436069, 521288629, 123456789, 380116160);
+		assert_eq!(rnd.next_u32(), 769445856);
+		assert_eq!(rnd.next_u32(), 742012328);
+		assert_eq!(rnd.next_u32(), 2121196314);
+		assert_eq!(rnd.next_u32(), 2805620942);
+
+		for _ in 0..100000 - 5 {
+			rnd.next_u32();
+		}
+
+		// The 100000th number.
+		assert_eq!(rnd.next_u32(), 941074834);
+	}
+
+	#[test]
+	fn test_fnv1a_hash() {
+		assert_eq!(fnv1a_hash(0x811c9dc5, 0xddd0a47b), 0xd37ee61a);
+	}
+
+	#[test]
+	fn test_merge() {
+		assert_eq!(merge(0x3b0bb37d, 0xa0212004, 0x9bd26ab0), 0x3ca34321);
+		assert_eq!(merge(0x10c02f0d, 0x870fa227, 0xeab0d05d), 0x91c1326a);
+		assert_eq!(merge(0x24d2bae4, 0x0ffb4c9b, 0x4bf7b66b), 0x537fd6cc);
+		assert_eq!(merge(0xda39e821, 0x089c4008, 0x8b6cd8c3), 0x8a81e396);
+	}
+
+	#[test]
+	fn test_math() {
+		assert_eq!(math(0x8626bb1f, 0xbbdfbc4e, 0x883e5b49), 0x4206776d);
+		assert_eq!(math(0x3f4bdfac, 0xd79e414f, 0x36b71236), 0x4c5cb214);
+		assert_eq!(math(0x6d175b7e, 0xc4e89d4c, 0x944ecabb), 0x53e9023f);
+		assert_eq!(math(0x2eddd94c, 0x7e70cb54, 0x3f472a85), 0x2eddd94c);
+		assert_eq!(math(0x8a81e396, 0x3f4bdfac, 0xcec46e67), 0x1e3968a8);
+		assert_eq!(math(0xa7352f36, 0xa0eb7045, 0x59e7b9d8), 0xa0212004);
+		assert_eq!(math(0xc89805af, 0x64291e2f, 0x1bdc84a9), 0xecb91faf);
+		assert_eq!(math(0x760726d3, 0x79fc6a48, 0xc675cac5), 0x0ffb4c9b);
+		assert_eq!(math(0x75551d43, 0x3383ba34, 0x2863ad31), 0x00000003);
+		assert_eq!(math(0xea260841, 0xe92c44b7, 0xf83ffe7d), 0x0000001b);
+	}
+
+	#[test]
+	fn test_words_roundtrip() {
+		let bytes = [0xf5, 0x7e, 0x6f, 0x3a, 0xcf, 0xc0, 0xdd, 0x4b, 0x5b, 0xf2, 0xbe, 0xe4, 0x0a, 0xb3, 0x35, 0x8a, 0xa6, 0x87, 0x73, 0xa8, 0xd0, 0x9f, 0x5e, 0x59, 0x5e, 0xab, 0x55, 0x94, 0x05, 0x52, 0x7d, 0x72];
+		assert_eq!(to_words(&bytes)[0], 0x3a6f7ef5);
+		assert_eq!(to_bytes(&to_words(&bytes)), bytes);
+	}
+
+	#[test]
+	fn test_progpow_quick_get_difficulty() {
+		let hash = [0xf5, 0x7e, 0x6f, 0x3a, 0xcf, 0xc0, 0xdd, 0x4b, 0x5b, 0xf2, 0xbe, 0xe4, 0x0a, 0xb3, 0x35, 0x8a, 0xa6, 0x87, 0x73, 0xa8, 0xd0, 0x9f, 0x5e, 0x59, 0x5e, 0xab, 0x55, 0x94, 0x05, 0x52, 0x7d, 0x72];
+		let nonce = 0xd7b3ac70a301a249;
+		let light = Light::new(&env::temp_dir(), 0);
+
+		let result = light.progpow_compute(30, &hash, nonce);
+		assert_eq!(quick_get_difficulty(&hash, nonce, &result.mix_hash)[..], result.value[..]);
+		// ethash and progpow results differ
+		assert!(light.compute(&hash, nonce).mix_hash[..] != result.mix_hash[..]);
+
+		// period changes every 50 blocks
+		assert_eq!(light.progpow_compute(49, &hash, nonce).mix_hash[..], light.progpow_compute(0, &hash, nonce).mix_hash[..]);
+		assert!(light.progpow_compute(50, &hash, nonce).mix_hash[..] != result.mix_hash[..]);
+	}
+
+	#[test]
+	fn test_progpow_reference_vectors() {
+		// (block number, header hash, nonce, mix hash, final hash) from the ProgPoW 0.9.2 test vectors
+		let vectors = [
+			(
+				0,
+				"0000000000000000000000000000000000000000000000000000000000000000",
+				0x0000000000000000,
+				"faeb1be51075b03a4ff44b335067951ead07a3b078539ace76fd56fc410557a3",
+				"63155f732f2bf556967f906155b510c917e48e99685ead76ea83f4eca03ab12b",
+			),
+			(
+				30000,
+				"ffeeddccbbaa9988776655443322110000112233445566778899aabbccddeeff",
+				0x123456789abcdef0,
+				"11f19805c58ab46610ff9c719dcf0a5f18fa2f1605798eef770c47219274767d",
+				"5b7ccd472dbefdd95b895cac8ece67ff0deb5a6bd2ecc6e162383d00c3728ece",
+			),
+		];
+
+		for &(block_number, header_hash, nonce, mix_hash, value) in vectors.iter() {
+			let light = Light::new(&env::temp_dir(), block_number);
+			let result = light.progpow_compute(block_number, &from_hex(header_hash), nonce);
+			assert_eq!(result.mix_hash, from_hex(mix_hash));
+			assert_eq!(result.value, from_hex(value));
+		}
+	}
+
+	fn from_hex(s: &str) -> [u8; 32] {
+		let mut bytes = [0u8; 32];
+		for i in 0..32 {
+			bytes[i] = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).unwrap();
+		}
+		bytes
+	}
+}
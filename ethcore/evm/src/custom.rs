@@ -0,0 +1,66 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Chain-specific instructions.
+//!
+//! Chain specs may activate additional opcodes (see `vm::Schedule::custom_instructions`).
+//! Each of them is executed by an implementation registered here under the instruction name.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use bigint::prelude::U256;
+use parking_lot::RwLock;
+use instructions::{self, GasPriceTier};
+
+/// Implementation of a chain-specific instruction.
+pub trait CustomInstruction: Send + Sync {
+	/// Executes the instruction. `args[0]` is the top of the stack.
+	/// Returned values are pushed so that the first one ends up on top.
+	fn execute(&self, args: &[U256]) -> Vec<U256>;
+}
+
+lazy_static! {
+	static ref REGISTRY: RwLock<HashMap<String, Arc<CustomInstruction>>> = RwLock::new(HashMap::new());
+}
+
+/// Registers implementation of chain-specific instruction, replacing the previous one (if any).
+pub fn register(name: &str, implementation: Arc<CustomInstruction>) {
+	REGISTRY.write().insert(name.to_owned(), implementation);
+}
+
+/// Returns implementation registered under given name.
+pub fn implementation(name: &str) -> Option<Arc<CustomInstruction>> {
+	REGISTRY.read().get(name).cloned()
+}
+
+/// Whether given opcode is already taken by a standard instruction.
+pub fn is_standard(opcode: u8) -> bool {
+	instructions::INSTRUCTIONS[opcode as usize].tier != GasPriceTier::Invalid
+}
+
+#[cfg(test)]
+mod tests {
+	use super::is_standard;
+
+	#[test]
+	fn should_detect_standard_opcodes() {
+		assert!(is_standard(0x01));
+		assert!(is_standard(0xfb));
+		assert!(!is_standard(0x0c));
+		assert!(!is_standard(0xef));
+	}
+}
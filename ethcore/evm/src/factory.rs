@@ -32,25 +32,18 @@ pub struct Factory {
 impl Factory {
 	/// Create fresh instance of VM
 	/// Might choose implementation depending on supplied gas.
-	#[cfg(feature = "jit")]
 	pub fn create(&self, gas: U256) -> Box<Vm> {
 		match self.evm {
+			#[cfg(feature = "jit")]
 			VMType::Jit => {
 				Box::new(super::jit::JitEvm::default())
 			},
-			VMType::Interpreter => if Self::can_fit_in_usize(gas) {
-				Box::new(super::interpreter::Interpreter::<usize>::new(self.evm_cache.clone()))
+			#[cfg(feature = "translated")]
+			VMType::Translated => if Self::can_fit_in_usize(gas) {
+				Box::new(super::interpreter::TranslatedEvm::<usize>::new(self.evm_cache.clone()))
 			} else {
-				Box::new(super::interpreter::Interpreter::<U256>::new(self.evm_cache.clone()))
-			}
-		}
-	}
-
-	/// Create fresh instance of VM
-	/// Might choose implementation depending on supplied gas.
-	#[cfg(not(feature = "jit"))]
-	pub fn create(&self, gas: U256) -> Box<Vm> {
-		match self.evm {
+				Box::new(super::interpreter::TranslatedEvm::<U256>::new(self.evm_cache.clone()))
+			},
 			VMType::Interpreter => if Self::can_fit_in_usize(gas) {
 				Box::new(super::interpreter::Interpreter::<usize>::new(self.evm_cache.clone()))
 			} else {
@@ -60,7 +53,7 @@ impl Factory {
 	}
 
 	/// Create new instance of specific `VMType` factory, with a size in bytes
-	/// for caching jump destinations.
+	/// for caching jump destinations and translated code.
 	pub fn new(evm: VMType, cache_size: usize) -> Self {
 		Factory {
 			evm: evm,
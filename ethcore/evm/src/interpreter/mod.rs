@@ -22,6 +22,7 @@ mod gasometer;
 mod stack;
 mod memory;
 mod shared_cache;
+mod translated;
 
 use std::marker::PhantomData;
 use std::{cmp, mem};
@@ -42,6 +43,7 @@ use self::gasometer::Gasometer;
 use self::stack::{Stack, VecStack};
 use self::memory::Memory;
 pub use self::shared_cache::SharedCache;
+pub use self::translated::TranslatedEvm;
 
 use bit_set::BitSet;
 
@@ -60,6 +62,9 @@ const TWO_POW_96: U256 = U256([0, 0x100000000, 0, 0]); //0x1 00000000 00000000 0
 const TWO_POW_224: U256 = U256([0, 0, 0, 0x100000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000
 const TWO_POW_248: U256 = U256([0, 0, 0, 0x100000000000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000 000000
 
+/// Number of jumps between checks of the execution deadline.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
 /// Abstraction over raw vector of Bytes. Easier state management of PC.
 struct CodeReader<'a> {
 	position: ProgramCounter,
@@ -125,6 +130,7 @@ impl<Cost: CostType> vm::Vm for Interpreter<Cost> {
 
 		let code = &params.code.as_ref().expect("exec always called with code; qed");
 		let mut valid_jump_destinations = None;
+		let mut jumps = 0usize;
 
 		let mut gasometer = Gasometer::<Cost>::new(Cost::from_u256(params.gas)?);
 		let mut stack = VecStack::with_capacity(ext.schedule().stack_limit, U256::zero());
@@ -139,6 +145,10 @@ impl<Cost: CostType> vm::Vm for Interpreter<Cost> {
 			do_trace = do_trace && ext.trace_next_instruction(reader.position - 1, instruction);
 
 			let info = &infos[instruction as usize];
+			if info.tier == instructions::GasPriceTier::Invalid &&
+				self.exec_custom_instruction(ext, reader.position - 1, instruction, &mut gasometer, &mut stack, do_trace)? {
+				continue;
+			}
 			self.verify_instruction(ext, instruction, info, &stack)?;
 
 			// Calculate gas cost
@@ -189,6 +199,12 @@ impl<Cost: CostType> vm::Vm for Interpreter<Cost> {
 					let jump_destinations = valid_jump_destinations.as_ref().expect("jump_destinations are initialized on first jump; qed");
 					let pos = self.verify_jump(position, jump_destinations)?;
 					reader.position = pos;
+
+					// every loop jumps, so this bounds the time spent between checks.
+					jumps += 1;
+					if jumps % DEADLINE_CHECK_INTERVAL == 0 && ext.deadline_exceeded() {
+						return Err(vm::Error::Internal("Execution deadline exceeded".into()));
+					}
 				},
 				InstructionResult::StopExecutionNeedsReturn {gas, init_off, init_size, apply} => {
 					informant.done();
@@ -219,6 +235,79 @@ impl<Cost: CostType> Interpreter<Cost> {
 		}
 	}
 
+	/// Executes chain-specific instruction activated by the schedule.
+	/// Returns `false` if there is no such instruction for given opcode.
+	#[cfg(feature = "custom-opcodes")]
+	fn exec_custom_instruction(
+		&mut self,
+		ext: &mut vm::Ext,
+		position: usize,
+		instruction: Instruction,
+		gasometer: &mut Gasometer<Cost>,
+		stack: &mut Stack<U256>,
+		do_trace: bool,
+	) -> vm::Result<bool> {
+		let custom = match ext.schedule().custom_instructions.get(&instruction) {
+			Some(custom) => custom.clone(),
+			None => return Ok(false),
+		};
+		let implementation = ::custom::implementation(&custom.name).ok_or(vm::Error::BadInstruction {
+			instruction: instruction
+		})?;
+		let stack_limit = ext.schedule().stack_limit;
+
+		if !stack.has(custom.args) {
+			return Err(vm::Error::StackUnderflow {
+				instruction: "CUSTOM",
+				wanted: custom.args,
+				on_stack: stack.size()
+			});
+		}
+		if stack.size() - custom.args + custom.ret > stack_limit {
+			return Err(vm::Error::OutOfStack {
+				instruction: "CUSTOM",
+				wanted: custom.ret - custom.args,
+				limit: stack_limit
+			});
+		}
+
+		let gas_cost = Cost::from(custom.gas);
+		if do_trace {
+			ext.trace_prepare_execute(position, instruction, gas_cost.as_u256());
+		}
+		gasometer.verify_gas(&gas_cost)?;
+		gasometer.current_gas = gasometer.current_gas - gas_cost;
+
+		let args: Vec<U256> = (0..custom.args).map(|_| stack.pop_back()).collect();
+		let result = implementation.execute(&args);
+		if result.len() != custom.ret {
+			return Err(vm::Error::Internal(format!(
+				"Custom instruction {} returned {} items, expected {}", custom.name, result.len(), custom.ret
+			)));
+		}
+		for value in result.into_iter().rev() {
+			stack.push(value);
+		}
+
+		if do_trace {
+			ext.trace_executed(gasometer.current_gas.as_u256(), stack.peek_top(custom.ret), None, None);
+		}
+		Ok(true)
+	}
+
+	#[cfg(not(feature = "custom-opcodes"))]
+	fn exec_custom_instruction(
+		&mut self,
+		_ext: &mut vm::Ext,
+		_position: usize,
+		_instruction: Instruction,
+		_gasometer: &mut Gasometer<Cost>,
+		_stack: &mut Stack<U256>,
+		_do_trace: bool,
+	) -> vm::Result<bool> {
+		Ok(false)
+	}
+
 	fn verify_instruction(&self, ext: &vm::Ext, instruction: Instruction, info: &InstructionInfo, stack: &Stack<U256>) -> vm::Result<()> {
 		let schedule = ext.schedule();
 
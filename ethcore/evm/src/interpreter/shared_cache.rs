@@ -22,6 +22,7 @@ use parking_lot::Mutex;
 use util::cache::MemoryLruCache;
 use bit_set::BitSet;
 use super::super::instructions;
+use super::translated::Program;
 
 const DEFAULT_CACHE_SIZE: usize = 4 * 1024 * 1024;
 
@@ -35,17 +36,28 @@ impl HeapSizeOf for Bits {
 	}
 }
 
+// stub for a HeapSizeOf implementation.
+struct Translation(Arc<Program>);
+
+impl HeapSizeOf for Translation {
+	fn heap_size_of_children(&self) -> usize {
+		self.0.heap_size_of_children()
+	}
+}
+
 /// Global cache for EVM interpreter
 pub struct SharedCache {
 	jump_destinations: Mutex<MemoryLruCache<H256, Bits>>,
+	programs: Mutex<MemoryLruCache<H256, Translation>>,
 }
 
 impl SharedCache {
-	/// Create a jump destinations cache with a maximum size in bytes
-	/// to cache.
+	/// Create a jump destinations and translated code cache with a maximum size in bytes
+	/// to cache each.
 	pub fn new(max_size: usize) -> Self {
 		SharedCache {
 			jump_destinations: Mutex::new(MemoryLruCache::new(max_size)),
+			programs: Mutex::new(MemoryLruCache::new(max_size)),
 		}
 	}
 
@@ -65,6 +77,22 @@ impl SharedCache {
 		d
 	}
 
+	/// Get translated code of a contract.
+	pub fn program(&self, code_hash: &H256, code: &[u8]) -> Arc<Program> {
+		if code_hash == &KECCAK_EMPTY {
+			return Arc::new(Program::new(code));
+		}
+
+		if let Some(p) = self.programs.lock().get_mut(code_hash) {
+			return p.0.clone();
+		}
+
+		let p = Arc::new(Program::new(code));
+		self.programs.lock().insert(code_hash.clone(), Translation(p.clone()));
+
+		p
+	}
+
 	fn find_jump_destinations(code: &[u8]) -> Arc<BitSet> {
 		let mut jump_dests = BitSet::with_capacity(code.len());
 		let mut position = 0;
@@ -0,0 +1,415 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EVM executing code translated ahead of time.
+//!
+//! Code is decoded once into a sequence of operations with push data, static gas costs
+//! and jump destinations resolved, and the translation is cached per code hash.
+//! Instructions themselves are executed by the interpreter, so results and gas usage are
+//! identical. Executions the translation can't reproduce (VM tracing, chain-specific
+//! instructions) are handed over to the interpreter as a whole.
+
+use std::collections::HashMap;
+use std::mem::{self, size_of};
+use std::sync::Arc;
+use hash::keccak;
+use heapsize::HeapSizeOf;
+use bigint::prelude::U256;
+use vm::{self, ActionParams, GasLeft, Vm};
+use evm::CostType;
+use instructions::{self, Instruction};
+use super::{Interpreter, InstructionResult, CodeReader, SharedCache, DEADLINE_CHECK_INTERVAL};
+use super::gasometer::Gasometer;
+use super::stack::{Stack, VecStack};
+use super::memory::Memory;
+
+/// Gas cost of an operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GasCost {
+	/// Fixed cost of the given tier.
+	Tier(usize),
+	/// Cost depending on the machine state, calculated by the gasometer.
+	Dynamic,
+}
+
+/// Decoded instruction.
+#[derive(Debug, Clone)]
+struct Op {
+	instruction: Instruction,
+	/// Position of the instruction in the code.
+	position: usize,
+	gas: GasCost,
+	/// Validity depends on the schedule and has to be verified on execution.
+	checked: bool,
+	/// Pushed value of `PUSH*` instructions.
+	push: U256,
+}
+
+/// Code translated into operations.
+#[derive(Debug)]
+pub struct Program {
+	ops: Vec<Op>,
+	/// Index of the operation at each valid jump destination.
+	jump_destinations: HashMap<usize, usize>,
+	/// Contains instructions unknown to the EVM.
+	has_invalid: bool,
+}
+
+impl Program {
+	/// Translates the code.
+	pub fn new(code: &[u8]) -> Self {
+		let infos = &*instructions::INSTRUCTIONS;
+		let mut ops = Vec::new();
+		let mut jump_destinations = HashMap::new();
+		let mut has_invalid = false;
+		let mut position = 0;
+
+		while position < code.len() {
+			let instruction = code[position];
+			let info = &infos[instruction as usize];
+			let invalid = info.tier == instructions::GasPriceTier::Invalid;
+			let mut reader = CodeReader { position: position + 1, code: code };
+			let push = if instructions::is_push(instruction) {
+				reader.read(instructions::get_push_bytes(instruction))
+			} else {
+				U256::zero()
+			};
+
+			if instruction == instructions::JUMPDEST {
+				jump_destinations.insert(position, ops.len());
+			}
+			has_invalid = has_invalid || invalid;
+			ops.push(Op {
+				instruction: instruction,
+				position: position,
+				gas: if has_dynamic_cost(instruction) {
+					GasCost::Dynamic
+				} else {
+					GasCost::Tier(instructions::get_tier_idx(info.tier))
+				},
+				checked: invalid || is_optional(instruction),
+				push: push,
+			});
+			position = reader.position;
+		}
+
+		ops.shrink_to_fit();
+		Program {
+			ops: ops,
+			jump_destinations: jump_destinations,
+			has_invalid: has_invalid,
+		}
+	}
+
+	/// Returns index of the operation at given jump destination.
+	fn jump_target(&self, destination: U256) -> vm::Result<usize> {
+		let position = destination.low_u64() as usize;
+		match self.jump_destinations.get(&position) {
+			Some(index) if U256::from(position) == destination => Ok(*index),
+			_ => Err(vm::Error::BadJumpDestination {
+				destination: position
+			}),
+		}
+	}
+}
+
+impl HeapSizeOf for Program {
+	fn heap_size_of_children(&self) -> usize {
+		self.ops.capacity() * size_of::<Op>() + self.jump_destinations.heap_size_of_children()
+	}
+}
+
+/// Instructions not charged by their tier, see `Gasometer::requirements`.
+fn has_dynamic_cost(instruction: Instruction) -> bool {
+	match instruction {
+		instructions::JUMPDEST | instructions::SSTORE | instructions::SLOAD | instructions::BALANCE |
+		instructions::EXTCODESIZE | instructions::SUICIDE | instructions::MSTORE | instructions::MLOAD |
+		instructions::MSTORE8 | instructions::RETURN | instructions::REVERT | instructions::SHA3 |
+		instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY |
+		instructions::EXTCODECOPY | instructions::LOG0...instructions::LOG4 |
+		instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL |
+		instructions::CREATE | instructions::CREATE2 | instructions::EXP | instructions::BLOCKHASH => true,
+		_ => false,
+	}
+}
+
+/// Instructions enabled by the schedule, see `Interpreter::verify_instruction`.
+fn is_optional(instruction: Instruction) -> bool {
+	match instruction {
+		instructions::DELEGATECALL | instructions::CREATE2 | instructions::STATICCALL |
+		instructions::RETURNDATACOPY | instructions::RETURNDATASIZE | instructions::REVERT => true,
+		_ => false,
+	}
+}
+
+/// Translated EVM implementation, falling back to the interpreter.
+pub struct TranslatedEvm<Cost: CostType> {
+	interpreter: Interpreter<Cost>,
+}
+
+impl<Cost: CostType> TranslatedEvm<Cost> {
+	/// Create a new `TranslatedEvm` instance with shared cache.
+	pub fn new(cache: Arc<SharedCache>) -> Self {
+		TranslatedEvm {
+			interpreter: Interpreter::new(cache),
+		}
+	}
+
+	/// Whether executing the translation is indistinguishable from interpreting the code.
+	fn can_execute(program: &Program, code: &[u8], ext: &mut vm::Ext) -> bool {
+		if program.has_invalid && !ext.schedule().custom_instructions.is_empty() {
+			return false;
+		}
+		// VM tracers are notified about every step by the interpreter.
+		code.is_empty() || !ext.trace_next_instruction(0, code[0])
+	}
+}
+
+impl<Cost: CostType> vm::Vm for TranslatedEvm<Cost> {
+	fn exec(&mut self, params: ActionParams, ext: &mut vm::Ext) -> vm::Result<GasLeft> {
+		let code = params.code.clone().expect("exec always called with code; qed");
+		let code_hash = params.code_hash.clone().unwrap_or_else(|| keccak(code.as_ref()));
+		let program = self.interpreter.cache.program(&code_hash, &code);
+		if !Self::can_execute(&program, &code, ext) {
+			return self.interpreter.exec(params, ext);
+		}
+
+		self.interpreter.mem.clear();
+
+		let stack_limit = ext.schedule().stack_limit;
+		let tier_step_gas = ext.schedule().tier_step_gas;
+		let mut gasometer = Gasometer::<Cost>::new(Cost::from_u256(params.gas)?);
+		let mut stack = VecStack::with_capacity(stack_limit, U256::zero());
+		let infos = &*instructions::INSTRUCTIONS;
+		let mut index = 0;
+		let mut jumps = 0usize;
+
+		while index < program.ops.len() {
+			let op = &program.ops[index];
+			let info = &infos[op.instruction as usize];
+			index += 1;
+
+			if op.checked {
+				self.interpreter.verify_instruction(ext, op.instruction, info, &stack)?;
+			} else if !stack.has(info.args) {
+				return Err(vm::Error::StackUnderflow {
+					instruction: info.name,
+					wanted: info.args,
+					on_stack: stack.size()
+				});
+			} else if stack.size() - info.args + info.ret > stack_limit {
+				return Err(vm::Error::OutOfStack {
+					instruction: info.name,
+					wanted: info.ret - info.args,
+					limit: stack_limit
+				});
+			}
+
+			let provided = match op.gas {
+				GasCost::Tier(tier) => {
+					let gas_cost = Cost::from(tier_step_gas[tier]);
+					gasometer.verify_gas(&gas_cost)?;
+					gasometer.current_gas = gasometer.current_gas - gas_cost;
+					None
+				},
+				GasCost::Dynamic => {
+					let requirements = gasometer.requirements(ext, op.instruction, info, &stack, self.interpreter.mem.size())?;
+					gasometer.verify_gas(&requirements.gas_cost)?;
+					self.interpreter.mem.expand(requirements.memory_required_size);
+					gasometer.current_mem_gas = requirements.memory_total_gas;
+					gasometer.current_gas = gasometer.current_gas - requirements.gas_cost;
+					requirements.provide_gas
+				},
+			};
+
+			let result = if instructions::is_push(op.instruction) {
+				stack.push(op.push);
+				InstructionResult::Ok
+			} else {
+				let mut reader = CodeReader { position: op.position + 1, code: &code };
+				self.interpreter.exec_instruction(
+					gasometer.current_gas, &params, ext, op.instruction, &mut reader, &mut stack, provided
+				)?
+			};
+
+			match result {
+				InstructionResult::UnusedGas(gas) => {
+					gasometer.current_gas = gasometer.current_gas + gas;
+				},
+				InstructionResult::JumpToPosition(position) => {
+					index = program.jump_target(position)?;
+
+					// every loop jumps, so this bounds the time spent between checks.
+					jumps += 1;
+					if jumps % DEADLINE_CHECK_INTERVAL == 0 && ext.deadline_exceeded() {
+						return Err(vm::Error::Internal("Execution deadline exceeded".into()));
+					}
+				},
+				InstructionResult::StopExecutionNeedsReturn {gas, init_off, init_size, apply} => {
+					let mem = mem::replace(&mut self.interpreter.mem, Vec::new());
+					return Ok(GasLeft::NeedsReturn {
+						gas_left: gas.as_u256(),
+						data: mem.into_return_data(init_off, init_size),
+						apply_state: apply
+					});
+				},
+				InstructionResult::StopExecution => break,
+				InstructionResult::Ok => {},
+			}
+		}
+		Ok(GasLeft::Known(gasometer.current_gas.as_u256()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::sync::Arc;
+	use rustc_hex::{FromHex, ToHex};
+	use bigint::prelude::U256;
+	use bigint::hash::H256;
+	use vm::{self, ActionParams, GasLeft, Vm};
+	use vm::tests::FakeExt;
+	use interpreter::{Interpreter, SharedCache};
+	use super::{Program, TranslatedEvm};
+
+	type Outcome = (vm::Result<(U256, Vec<u8>, bool)>, HashMap<H256, H256>);
+
+	fn run<V: Vm>(vm: &mut V, code: &[u8]) -> Outcome {
+		let mut params = ActionParams::default();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code.to_vec()));
+		params.data = Some(vec![0xaa; 40]);
+		let mut ext = FakeExt::new();
+
+		let result = vm.exec(params, &mut ext).map(|gas_left| match gas_left {
+			GasLeft::Known(gas) => (gas, Vec::new(), true),
+			GasLeft::NeedsReturn { gas_left, data, apply_state } => (gas_left, data.to_vec(), apply_state),
+		});
+		(result, ext.store)
+	}
+
+	fn assert_same(code: &[u8]) {
+		let cache = Arc::new(SharedCache::default());
+		let expected = run(&mut Interpreter::<usize>::new(cache.clone()), code);
+
+		assert_eq!(run(&mut TranslatedEvm::<usize>::new(cache.clone()), code), expected, "code: {}", code.to_hex());
+		assert_eq!(run(&mut TranslatedEvm::<U256>::new(cache), code), expected, "code: {}", code.to_hex());
+	}
+
+	#[test]
+	fn should_translate_push_data_and_jump_destinations() {
+		let code = "6003565b615b5b5b".from_hex().unwrap();
+
+		let program = Program::new(&code);
+
+		assert_eq!(program.ops.len(), 5);
+		assert_eq!(program.ops[3].push, U256::from(0x5b5b));
+		assert_eq!(program.jump_destinations.len(), 2);
+		assert_eq!(program.jump_destinations[&3], 2);
+		assert_eq!(program.jump_destinations[&7], 4);
+		assert!(!program.has_invalid);
+	}
+
+	#[test]
+	fn should_match_interpreter() {
+		let codes = [
+			// arithmetic and storage
+			"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff01600055",
+			// memory and sha3
+			"6000600020600055",
+			// loop
+			"600a5b600190038060025760015500",
+			// valid jump
+			"6003565b6001600055",
+			// jump into push data
+			"600456605b00",
+			// return and revert
+			"60aa60005260206000f3",
+			"60aa60005260206000fd",
+			// call data and code
+			"60003560005536600155",
+			"600a600060003960005160005500",
+			// program counter, gas and memory size
+			"5860005559600155",
+			"5a600055",
+			// truncated push
+			"6001617f",
+			// stack underflow and invalid instruction
+			"01",
+			"6001600cfe",
+		];
+
+		for code in &codes {
+			assert_same(&code.from_hex().unwrap());
+		}
+	}
+
+	#[test]
+	fn should_match_interpreter_on_random_code() {
+		let instructions: Vec<u8> = (0x00..0x0c).chain(0x10..0x1b).chain(vec![0x20])
+			.chain(0x30..0x3a).chain(0x50..0x5c).chain(vec![0x60, 0x60, 0x60, 0x80, 0x81, 0x90, 0x91, 0xf3, 0xfd])
+			.collect();
+		// xorshift, deterministic across runs
+		let mut seed = 0x2545f4914f6cdd1du64;
+		let mut next = move || {
+			seed ^= seed << 13;
+			seed ^= seed >> 7;
+			seed ^= seed << 17;
+			seed
+		};
+
+		for _ in 0..1000 {
+			let len = 1 + next() % 64;
+			let mut code = Vec::new();
+			while (code.len() as u64) < len {
+				let instruction = instructions[(next() % instructions.len() as u64) as usize];
+				code.push(instruction);
+				if instruction == 0x60 {
+					code.push((next() % 64) as u8);
+				}
+			}
+			assert_same(&code);
+		}
+	}
+
+	#[test]
+	fn should_abort_after_deadline() {
+		// 5b - jumpdest, 6000 - push 0, 56 - jump
+		let code = "5b600056".from_hex().unwrap();
+		let mut params = ActionParams::default();
+		params.gas = U256::from(1_000_000);
+		params.code = Some(Arc::new(code));
+		let mut ext = FakeExt::new();
+		ext.deadline_exceeded = true;
+
+		match TranslatedEvm::<usize>::new(Default::default()).exec(params, &mut ext) {
+			Err(vm::Error::Internal(_)) => {},
+			other => panic!("Expected internal error, got {:?}", other.map(|_| ())),
+		}
+	}
+
+	#[test]
+	fn should_fall_back_to_interpreter_when_tracing() {
+		let code = "6001600055".from_hex().unwrap();
+		let program = Program::new(&code);
+		let mut ext = FakeExt::new();
+
+		assert!(TranslatedEvm::<usize>::can_execute(&program, &code, &mut ext));
+		ext.tracing = true;
+		assert!(!TranslatedEvm::<usize>::can_execute(&program, &code, &mut ext));
+	}
+}
@@ -50,6 +50,9 @@ pub mod factory;
 mod vmtype;
 mod instructions;
 
+#[cfg(feature = "custom-opcodes")]
+pub mod custom;
+
 #[cfg(feature = "jit" )]
 mod jit;
 
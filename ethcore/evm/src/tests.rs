@@ -724,6 +724,52 @@ fn test_jumps(factory: super::Factory) {
 	assert_eq!(gas_left, U256::from(54_117));
 }
 
+#[test]
+fn test_deadline_exceeded_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	// 5b - jumpdest, 6000 - push 0, 56 - jump
+	let code = "5b600056".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(1_000_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.deadline_exceeded = true;
+
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		vm::Error::Internal(_) => (),
+		_ => assert!(false, "Expected internal error"),
+	}
+}
+
+#[test]
+#[cfg(feature = "translated")]
+fn test_deadline_exceeded_trn() {
+	let factory = super::Factory::new(VMType::Translated, 1024 * 32);
+	// 5b - jumpdest, 6000 - push 0, 56 - jump
+	let code = "5b600056".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(1_000_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.deadline_exceeded = true;
+
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		vm::Error::Internal(_) => (),
+		_ => assert!(false, "Expected internal error"),
+	}
+}
 
 evm_test!{test_calls: test_calls_jit, test_calls_int}
 fn test_calls(factory: super::Factory) {
@@ -782,3 +828,69 @@ fn assert_store(ext: &FakeExt, pos: u64, val: &str) {
 	assert_eq!(ext.store.get(&H256::from(pos)).unwrap(), &H256::from_str(val).unwrap());
 }
 
+
+#[cfg(feature = "custom-opcodes")]
+#[test]
+fn test_custom_instruction_int() {
+	use custom::{self, CustomInstruction};
+
+	struct Sub;
+	impl CustomInstruction for Sub {
+		fn execute(&self, args: &[U256]) -> Vec<U256> {
+			vec![args[0] - args[1]]
+		}
+	}
+
+	custom::register("test_sub", Arc::new(Sub));
+
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	// 6003 - push 3, 6005 - push 5, 0c - custom, 600055 - store at 0
+	let code = "600360050c600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.custom_instructions.insert(0x0c, vm::CustomInstruction {
+		name: "test_sub".into(),
+		args: 2,
+		ret: 1,
+		gas: 7,
+	});
+
+	let gas_left = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap()
+	};
+
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000002");
+	assert_eq!(gas_left, U256::from(79_984));
+}
+
+#[cfg(feature = "custom-opcodes")]
+#[test]
+fn test_unregistered_custom_instruction_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = "0d".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.custom_instructions.insert(0x0d, vm::CustomInstruction {
+		name: "test_missing".into(),
+		args: 0,
+		ret: 0,
+		gas: 1,
+	});
+
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		vm::Error::BadInstruction { instruction: 0x0d } => (),
+		_ => assert!(false, "Expected bad instruction")
+	}
+}
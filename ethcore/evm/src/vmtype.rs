@@ -22,21 +22,20 @@ pub enum VMType {
 	/// JIT EVM
 	#[cfg(feature = "jit")]
 	Jit,
+	/// RUST EVM executing translated code, falling back to the interpreter
+	#[cfg(feature = "translated")]
+	Translated,
 	/// RUST EVM
 	Interpreter
 }
 
 impl fmt::Display for VMType {
-	#[cfg(feature="jit")]
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "{}", match *self {
+			#[cfg(feature = "jit")]
 			VMType::Jit => "JIT",
-			VMType::Interpreter => "INT"
-		})
-	}
-	#[cfg(not(feature="jit"))]
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}", match *self {
+			#[cfg(feature = "translated")]
+			VMType::Translated => "TRN",
 			VMType::Interpreter => "INT"
 		})
 	}
@@ -49,16 +48,11 @@ impl Default for VMType {
 }
 
 impl VMType {
-	/// Return all possible VMs (JIT, Interpreter)
-	#[cfg(feature = "jit")]
+	/// Return all possible VMs (JIT, Translated, Interpreter)
 	pub fn all() -> Vec<VMType> {
-		vec![VMType::Jit, VMType::Interpreter]
-	}
-
-	/// Return all possible VMs (Interpreter)
-	#[cfg(not(feature = "jit"))]
-	pub fn all() -> Vec<VMType> {
-		vec![VMType::Interpreter]
+		let mut all: Vec<_> = VMType::jit().into_iter().chain(VMType::translated()).collect();
+		all.push(VMType::Interpreter);
+		all
 	}
 
 	/// Return new jit if it's possible
@@ -72,4 +66,16 @@ impl VMType {
 	pub fn jit() -> Option<Self> {
 		Some(VMType::Jit)
 	}
+
+	/// Return new translated EVM if it's possible
+	#[cfg(not(feature = "translated"))]
+	pub fn translated() -> Option<Self> {
+		None
+	}
+
+	/// Return new translated EVM if it's possible
+	#[cfg(feature = "translated")]
+	pub fn translated() -> Option<Self> {
+		Some(VMType::Translated)
+	}
 }
@@ -21,6 +21,7 @@
 //! we discarded.
 
 use ethcore::ids::BlockId;
+use heapsize::HeapSizeOf;
 use bigint::prelude::U256;
 use bigint::hash::H256;
 use util::{Bytes, HashDB, MemoryDB};
@@ -73,6 +74,12 @@ impl<DB: HashDB> CHT<DB> {
 	}
 }
 
+impl HeapSizeOf for CHT<MemoryDB> {
+	fn heap_size_of_children(&self) -> usize {
+		self.db.mem_used()
+	}
+}
+
 /// Block information necessary to build a CHT.
 pub struct BlockInfo {
 	/// The block's hash.
@@ -118,6 +125,30 @@ pub fn build<F>(cht_num: u64, mut fetcher: F) ->  Option<CHT<MemoryDB>>
 	})
 }
 
+/// Build an in-memory CHT from the (hash, td) pairs of its blocks, ordered by number
+/// from `start_number(cht_num)`. Fails unless exactly `SIZE` items are given.
+pub fn build_from_entries(cht_num: u64, entries: &[(H256, U256)]) -> Option<CHT<MemoryDB>> {
+	if entries.len() != SIZE as usize { return None }
+
+	let mut db = MemoryDB::new();
+	let mut root = H256::default();
+	let start_num = start_number(cht_num);
+
+	{
+		let mut t = TrieDBMut::new(&mut db, &mut root);
+		for (i, &(ref hash, ref td)) in entries.iter().enumerate() {
+			t.insert(&key!(start_num + i as u64), &val!(hash, td))
+				.expect("fresh in-memory database is infallible; qed");
+		}
+	}
+
+	Some(CHT {
+		db: db,
+		root: root,
+		number: cht_num,
+	})
+}
+
 /// Compute a CHT root from an iterator of (hash, td) pairs. Fails if shorter than
 /// SIZE items. The items are assumed to proceed sequentially from `start_number(cht_num)`.
 /// Discards the trie's nodes.
@@ -197,6 +228,20 @@ mod tests {
 		assert_eq!(::cht::block_to_cht_number(::cht::SIZE).unwrap(), 0);
 	}
 
+	#[test]
+	fn build_from_entries() {
+		use bigint::hash::H256;
+		use bigint::prelude::U256;
+
+		let entries: Vec<(H256, U256)> = (0..::cht::SIZE).map(|i| (H256::from(i), U256::from(i * 10))).collect();
+		let cht = ::cht::build_from_entries(1, &entries).unwrap();
+
+		assert_eq!(Some(cht.root()), ::cht::compute_root(1, entries.iter().cloned()));
+		let proof = cht.prove(::cht::SIZE + 5, 0).unwrap().unwrap();
+		assert_eq!(::cht::check_proof(&proof, ::cht::SIZE + 5, cht.root()), Some((H256::from(4), U256::from(40))));
+		assert!(::cht::build_from_entries(1, &entries[1..]).is_none());
+	}
+
 	#[test]
 	fn start_number() {
 		assert_eq!(::cht::start_number(0), 1);
@@ -0,0 +1,231 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistent store of canonical hash tries for full nodes serving light clients.
+//!
+//! A CHT is generated once its last block is `SIZE` blocks deep. Only the (hash, td)
+//! entries are persisted; the trie is rebuilt in memory from a single database read
+//! when a proof is requested and kept in a memory-bounded LRU cache.
+
+use std::sync::Arc;
+
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use ethcore::client::BlockChainClient;
+use ethcore::ids::BlockId;
+use parking_lot::Mutex;
+use rlp::{RlpStream, UntrustedRlp};
+use util::{Bytes, MemoryDB};
+use util::cache::MemoryLruCache;
+use util::kvdb::{DBTransaction, KeyValueDB};
+
+use cht::{self, CHT};
+
+/// Default memory budget for CHTs kept in memory.
+pub const DEFAULT_CACHE_SIZE: usize = 16 * 1024 * 1024;
+
+const NEXT_CHT_KEY: &'static [u8] = b"cht_next";
+
+fn cht_key(number: u64) -> Vec<u8> {
+	let mut key = b"cht".to_vec();
+	key.extend((0..8).rev().map(|i| (number >> (i * 8)) as u8));
+	key
+}
+
+/// A header proof from a stored CHT.
+pub struct HeaderProof {
+	/// Trie nodes proving the entry.
+	pub proof: Vec<Bytes>,
+	/// Hash of the block.
+	pub hash: H256,
+	/// Total difficulty of the block.
+	pub td: U256,
+}
+
+/// Canonical hash tries persisted in the database.
+pub struct ChtStore {
+	db: Arc<KeyValueDB>,
+	col: Option<u32>,
+	next: Mutex<u64>,
+	cache: Mutex<MemoryLruCache<u64, CHT<MemoryDB>>>,
+}
+
+impl ChtStore {
+	/// Create a store using the given database column and memory budget for cached tries.
+	pub fn new(db: Arc<KeyValueDB>, col: Option<u32>, cache_size: usize) -> Self {
+		let next = match db.get(col, NEXT_CHT_KEY) {
+			Ok(Some(next)) => ::rlp::decode(&next),
+			Ok(None) => 0,
+			Err(e) => {
+				warn!(target: "cht", "Error reading CHT progress: {}", e);
+				0
+			},
+		};
+
+		ChtStore {
+			db: db,
+			col: col,
+			next: Mutex::new(next),
+			cache: Mutex::new(MemoryLruCache::new(cache_size)),
+		}
+	}
+
+	/// Number of the next CHT to generate.
+	pub fn next_cht(&self) -> u64 {
+		*self.next.lock()
+	}
+
+	/// Whether the next CHT is final given the best block number.
+	pub fn can_generate(&self, best_block: u64) -> bool {
+		let last = cht::start_number(self.next_cht() + 1) - 1;
+		best_block >= last + cht::SIZE
+	}
+
+	/// Generate the next CHT if it is final. CHTs whose blocks are missing
+	/// (e.g. ancient blocks not downloaded yet) are skipped.
+	/// Returns `false` if there was nothing to generate.
+	pub fn generate_next<C: BlockChainClient + ?Sized>(&self, client: &C) -> bool {
+		if !self.can_generate(client.chain_info().best_block_number) {
+			return false;
+		}
+
+		let number = self.next_cht();
+		let start = cht::start_number(number);
+		let entries: Option<Vec<(H256, U256)>> = (start..start + cht::SIZE)
+			.map(|num| {
+				let id = BlockId::Number(num);
+				match (client.block_hash(id), client.block_total_difficulty(id)) {
+					(Some(hash), Some(td)) => Some((hash, td)),
+					_ => None,
+				}
+			})
+			.collect();
+
+		let mut batch = DBTransaction::new();
+		match entries {
+			Some(entries) => {
+				let mut stream = RlpStream::new_list(entries.len());
+				for &(ref hash, ref td) in &entries {
+					stream.begin_list(2).append(hash).append(td);
+				}
+				batch.put_vec(self.col, &cht_key(number), stream.out());
+			},
+			None => debug!(target: "cht", "Skipping CHT {} with missing blocks", number),
+		}
+		batch.put_vec(self.col, NEXT_CHT_KEY, ::rlp::encode(&(number + 1)).into_vec());
+
+		if let Err(e) = self.db.write(batch) {
+			warn!(target: "cht", "Error writing CHT {}: {}", number, e);
+			return false;
+		}
+		trace!(target: "cht", "Generated CHT {}", number);
+		*self.next.lock() = number + 1;
+		true
+	}
+
+	fn load(&self, number: u64) -> Option<CHT<MemoryDB>> {
+		let entries = match self.db.get(self.col, &cht_key(number)) {
+			Ok(Some(entries)) => entries,
+			Ok(None) => return None,
+			Err(e) => {
+				warn!(target: "cht", "Error reading CHT {}: {}", number, e);
+				return None;
+			},
+		};
+
+		let entries: Result<Vec<(H256, U256)>, _> = UntrustedRlp::new(&entries).iter()
+			.map(|entry| Ok((entry.val_at(0)?, entry.val_at(1)?)))
+			.collect();
+
+		match entries {
+			Ok(entries) => cht::build_from_entries(number, &entries),
+			Err(e) => {
+				warn!(target: "cht", "Corrupted CHT {}: {:?}", number, e);
+				None
+			},
+		}
+	}
+
+	/// Prove the canonical hash and total difficulty of a block from a stored CHT.
+	pub fn prove(&self, block_num: u64) -> Option<HeaderProof> {
+		let number = match cht::block_to_cht_number(block_num) {
+			Some(number) if number < self.next_cht() => number,
+			_ => return None,
+		};
+
+		let mut cache = self.cache.lock();
+		if cache.get_mut(&number).is_none() {
+			match self.load(number) {
+				Some(cht) => cache.insert(number, cht),
+				None => return None,
+			}
+		}
+		let cht = match cache.get_mut(&number) {
+			Some(cht) => cht,
+			None => return None,
+		};
+
+		let proof = match cht.prove(block_num, 0) {
+			Ok(Some(proof)) => proof,
+			Ok(None) => return None,
+			Err(e) => {
+				debug!(target: "cht", "Error proving block {} from CHT {}: {}", block_num, number, e);
+				return None;
+			},
+		};
+
+		// the proven leaf carries the hash and total difficulty.
+		cht::check_proof(&proof, block_num, cht.root()).map(|(hash, td)| HeaderProof {
+			proof: proof,
+			hash: hash,
+			td: td,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethcore::client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
+	use ethcore::ids::BlockId;
+	use util::kvdb::in_memory;
+	use cht;
+	use super::ChtStore;
+
+	#[test]
+	fn generates_final_chts() {
+		let db = Arc::new(in_memory(1));
+		let client = TestBlockChainClient::new();
+		client.add_blocks(cht::SIZE as usize * 2 - 1, EachBlockWith::Nothing);
+
+		let store = ChtStore::new(db.clone(), Some(0), super::DEFAULT_CACHE_SIZE);
+		assert!(!store.generate_next(&client));
+		assert!(store.prove(5).is_none());
+
+		client.add_blocks(1, EachBlockWith::Nothing);
+		assert!(store.generate_next(&client));
+		assert!(!store.generate_next(&client));
+
+		let proof = store.prove(5).unwrap();
+		assert_eq!(Some(proof.hash), client.block_hash(BlockId::Number(5)));
+		assert!(store.prove(cht::SIZE + 1).is_none());
+
+		// progress and tries are persisted.
+		let store = ChtStore::new(db, Some(0), super::DEFAULT_CACHE_SIZE);
+		assert_eq!(store.next_cht(), 1);
+		assert!(store.prove(cht::SIZE).is_some());
+	}
+}
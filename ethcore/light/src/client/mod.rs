@@ -625,4 +625,10 @@ impl<T: ChainDataFetcher> ::ethcore::client::EngineClient for Client<T> {
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
 		self.block_header(id).map(|hdr| hdr.number())
 	}
+
+	fn engine_data(&self) -> Option<Vec<u8>> {
+		None
+	}
+
+	fn store_engine_data(&self, _data: Vec<u8>) { }
 }
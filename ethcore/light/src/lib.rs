@@ -34,6 +34,7 @@
 
 pub mod client;
 pub mod cht;
+pub mod cht_store;
 pub mod net;
 pub mod on_demand;
 pub mod transaction_queue;
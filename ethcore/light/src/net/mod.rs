@@ -85,6 +85,9 @@ pub const MAX_PROTOCOL_VERSION: u8 = 1;
 /// Packet count for PIP.
 pub const PACKET_COUNT: u8 = 9;
 
+/// Discovery topic light servers are advertised under.
+pub const DISCOVERY_TOPIC: &'static str = "pip";
+
 // packet ID definitions.
 mod packet {
 	// the status packet.
@@ -676,6 +679,20 @@ impl LightProtocol {
 		}
 	}
 
+	/// Forget that the given transactions were propagated, so they are
+	/// sent to all relay peers again on the next propagation.
+	pub fn resubmit_transactions(&self, hashes: &[H256]) {
+		if hashes.is_empty() { return }
+
+		trace!(target: "pip", "resubmitting {} transactions", hashes.len());
+		for peer_info in self.peers.read().values() {
+			let mut peer_info = peer_info.lock();
+			for hash in hashes {
+				peer_info.propagated_transactions.remove(hash);
+			}
+		}
+	}
+
 	/// called when a peer connects.
 	pub fn on_connect(&self, peer: &PeerId, io: &IoContext) {
 		let proto_version = match io.protocol_version(*peer).ok_or(Error::WrongNetwork) {
@@ -1075,6 +1092,12 @@ fn punish(peer: PeerId, io: &IoContext, e: Error) {
 
 impl NetworkProtocolHandler for LightProtocol {
 	fn initialize(&self, io: &NetworkContext, _host_info: &HostInfo) {
+		// servers make themselves known to light clients looking for them.
+		if self.capabilities.read().serve_headers {
+			io.advertise_topic(DISCOVERY_TOPIC);
+		} else {
+			io.search_topic(DISCOVERY_TOPIC);
+		}
 		io.register_timer(TIMEOUT, TIMEOUT_INTERVAL_MS)
 			.expect("Error registering sync timer.");
 		io.register_timer(TICK_TIMEOUT, TICK_TIMEOUT_INTERVAL_MS)
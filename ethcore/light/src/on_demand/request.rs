@@ -35,7 +35,7 @@ use bigint::hash::H256;
 use parking_lot::Mutex;
 use util::{Address, Bytes, DBValue, HashDB};
 use util::memorydb::MemoryDB;
-use util::trie::{Trie, TrieDB, TrieError};
+use util::trie::{Trie, TrieDB, TrieError, TrieHasher};
 
 const SUPPLIED_MATCHES: &'static str = "supplied responses always match produced requests; enforced by `check_response`; qed";
 
@@ -283,7 +283,7 @@ impl From<Request> for CheckedRequest {
 			Request::Account(req) => {
 				let net_req = net_request::IncompleteAccountRequest {
 					block_hash: req.header.field(),
-					address_hash: ::hash::keccak(&req.address).into(),
+					address_hash: req.hasher.hash(&req.address).into(),
 				};
 				CheckedRequest::Account(req, net_req)
 			}
@@ -779,6 +779,8 @@ pub struct Account {
 	pub header: HeaderRef,
 	/// Address requested.
 	pub address: Address,
+	/// Hasher of the chain's state trie.
+	pub hasher: TrieHasher,
 }
 
 impl Account {
@@ -788,9 +790,9 @@ impl Account {
 		let state_root = header.state_root();
 
 		let mut db = MemoryDB::new();
-		for node in proof { db.insert(&node[..]); }
+		for node in proof { db.emplace(self.hasher.hash(&node[..]), DBValue::from_slice(&node[..])); }
 
-		match TrieDB::new(&db, &state_root).and_then(|t| t.get(&keccak(&self.address)))? {
+		match TrieDB::new(&db, &state_root).and_then(|t| t.get(&self.hasher.hash(&self.address)))? {
 			Some(val) => {
 				let rlp = UntrustedRlp::new(&val);
 				Ok(Some(BasicAccount {
@@ -1035,6 +1037,7 @@ mod tests {
 		let req = Account {
 			header: encoded::Header::new(::rlp::encode(&header).into_vec()).into(),
 			address: addr,
+			hasher: Default::default(),
 		};
 
 		let cache = Mutex::new(make_cache());
@@ -18,16 +18,21 @@
 //! give as much data as necessary to its peers.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
 
 use ethcore::blockchain_info::BlockChainInfo;
-use ethcore::client::{BlockChainClient, ProvingBlockChainClient};
+use ethcore::client::{BlockChainClient, ProvingBlockChainClient, ChainNotify};
 use ethcore::transaction::PendingTransaction;
 use ethcore::ids::BlockId;
 use ethcore::encoded;
 use bigint::hash::H256;
 use parking_lot::RwLock;
+use util::Bytes;
 
 use cht::{self, BlockInfo};
+use cht_store::ChtStore;
 use client::{LightChainClient, AsLightClient};
 use transaction_queue::TransactionQueue;
 
@@ -276,6 +281,121 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 	}
 }
 
+/// Pause between generating consecutive CHTs in the background, spreading the
+/// header reads of a catch-up over time.
+const CHT_GENERATION_PAUSE_MS: u64 = 100;
+
+/// The full node "provider" implementation. This wraps a client and serves
+/// header proofs from CHTs persisted in the `ChtStore`, generating them in the
+/// background as blocks become final.
+pub struct ChtProvider<C> {
+	client: Arc<C>,
+	store: Arc<ChtStore>,
+	generating: Arc<AtomicBool>,
+}
+
+impl<C> ChtProvider<C> {
+	/// Create a new `ChtProvider` from the given client and CHT store.
+	pub fn new(client: Arc<C>, store: Arc<ChtStore>) -> Self {
+		ChtProvider {
+			client: client,
+			store: store,
+			generating: Arc::new(AtomicBool::new(false)),
+		}
+	}
+}
+
+impl<C: ProvingBlockChainClient + 'static> ChainNotify for ChtProvider<C> {
+	fn new_blocks(&self, _imported: Vec<H256>, _invalid: Vec<H256>, enacted: Vec<H256>, _retracted: Vec<H256>, _sealed: Vec<H256>, _proposed: Vec<Bytes>, _duration: u64) {
+		if enacted.is_empty() || !self.store.can_generate(BlockChainClient::chain_info(&*self.client).best_block_number) {
+			return;
+		}
+		if self.generating.swap(true, Ordering::SeqCst) {
+			return;
+		}
+
+		let client = Arc::downgrade(&self.client);
+		let store = self.store.clone();
+		let generating = self.generating.clone();
+		let spawned = thread::Builder::new().name("cht-generation".into()).spawn(move || {
+			while let Some(client) = client.upgrade() {
+				if !store.generate_next(&*client) {
+					break;
+				}
+				drop(client);
+				thread::sleep(Duration::from_millis(CHT_GENERATION_PAUSE_MS));
+			}
+			generating.store(false, Ordering::SeqCst);
+		});
+		if let Err(e) = spawned {
+			warn!(target: "cht", "Error starting CHT generation: {}", e);
+			self.generating.store(false, Ordering::SeqCst);
+		}
+	}
+}
+
+impl<C: ProvingBlockChainClient> Provider for ChtProvider<C> {
+	fn chain_info(&self) -> BlockChainInfo {
+		Provider::chain_info(&*self.client)
+	}
+
+	fn reorg_depth(&self, a: &H256, b: &H256) -> Option<u64> {
+		Provider::reorg_depth(&*self.client, a, b)
+	}
+
+	fn earliest_state(&self) -> Option<u64> {
+		Provider::earliest_state(&*self.client)
+	}
+
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		Provider::block_header(&*self.client, id)
+	}
+
+	fn block_body(&self, req: request::CompleteBodyRequest) -> Option<request::BodyResponse> {
+		Provider::block_body(&*self.client, req)
+	}
+
+	fn block_receipts(&self, req: request::CompleteReceiptsRequest) -> Option<request::ReceiptsResponse> {
+		Provider::block_receipts(&*self.client, req)
+	}
+
+	fn account_proof(&self, req: request::CompleteAccountRequest) -> Option<request::AccountResponse> {
+		Provider::account_proof(&*self.client, req)
+	}
+
+	fn storage_proof(&self, req: request::CompleteStorageRequest) -> Option<request::StorageResponse> {
+		Provider::storage_proof(&*self.client, req)
+	}
+
+	fn contract_code(&self, req: request::CompleteCodeRequest) -> Option<request::CodeResponse> {
+		Provider::contract_code(&*self.client, req)
+	}
+
+	fn header_proof(&self, req: request::CompleteHeaderProofRequest) -> Option<request::HeaderProofResponse> {
+		match self.store.prove(req.num) {
+			Some(proof) => Some(request::HeaderProofResponse {
+				proof: proof.proof,
+				hash: proof.hash,
+				td: proof.td,
+			}),
+			// not generated yet or skipped, build it from the chain.
+			None => Provider::header_proof(&*self.client, req),
+		}
+	}
+
+	fn transaction_proof(&self, req: request::CompleteExecutionRequest) -> Option<request::ExecutionResponse> {
+		Provider::transaction_proof(&*self.client, req)
+	}
+
+	fn epoch_signal(&self, req: request::CompleteSignalRequest) -> Option<request::SignalResponse> {
+		Provider::epoch_signal(&*self.client, req)
+	}
+
+	fn ready_transactions(&self) -> Vec<PendingTransaction> {
+		Provider::ready_transactions(&*self.client)
+	}
+}
+
 /// The light client "provider" implementation. This wraps a `LightClient` and
 /// a light transaction queue.
 pub struct LightProvider<L> {
@@ -362,6 +482,26 @@ mod tests {
 	use ethcore::client::{EachBlockWith, TestBlockChainClient};
 	use super::Provider;
 
+	#[test]
+	fn stored_cht_proof() {
+		use std::sync::Arc;
+		use util::kvdb::in_memory;
+		use cht_store::{ChtStore, DEFAULT_CACHE_SIZE};
+		use super::ChtProvider;
+
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(4096, EachBlockWith::Nothing);
+		let store = Arc::new(ChtStore::new(Arc::new(in_memory(1)), Some(0), DEFAULT_CACHE_SIZE));
+		assert!(store.generate_next(&*client));
+		let provider = ChtProvider::new(client.clone(), store);
+
+		let req = ::request::CompleteHeaderProofRequest {
+			num: 1500,
+		};
+
+		assert_eq!(provider.header_proof(req.clone()), client.header_proof(req));
+	}
+
 	#[test]
 	fn cht_proof() {
 		let client = TestBlockChainClient::new();
@@ -22,6 +22,9 @@
 //! Under the assumption that light nodes will have a relatively limited set of
 //! accounts for which they create transactions, this queue is structured in an
 //! address-wise manner.
+//!
+//! Queued transactions are periodically resubmitted to peers with exponential
+//! backoff until they are culled as mined or expire.
 
 use std::collections::{BTreeMap, HashMap};
 use std::collections::hash_map::Entry;
@@ -32,6 +35,13 @@ use bigint::prelude::U256;
 use bigint::hash::{H256, H256FastMap};
 use util::Address;
 
+/// Delay before the first resubmission of a transaction, in seconds.
+pub const RESUBMIT_BASE_DELAY_SECS: u64 = 120;
+/// Maximal delay between resubmissions of a transaction, in seconds.
+pub const RESUBMIT_MAX_DELAY_SECS: u64 = 60 * 60;
+/// Time after which transactions are dropped from the queue, in seconds.
+pub const TRANSACTION_LIFETIME_SECS: u64 = 3 * 60 * 60;
+
 // Knowledge of an account's current nonce.
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum CurrentNonce {
@@ -115,11 +125,39 @@ impl AccountTransactions {
 	}
 }
 
+// resubmission schedule of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Resubmission {
+	// when the transaction was first seen by the scheduler.
+	first_seen: u64,
+	// number of resubmissions so far.
+	attempts: u32,
+	// when the transaction is due to be resubmitted.
+	next_at: u64,
+}
+
+impl Resubmission {
+	fn new(now: u64) -> Self {
+		Resubmission {
+			first_seen: now,
+			attempts: 0,
+			next_at: now + RESUBMIT_BASE_DELAY_SECS,
+		}
+	}
+
+	fn note_resubmitted(&mut self, now: u64) {
+		self.attempts += 1;
+		let delay = RESUBMIT_BASE_DELAY_SECS << ::std::cmp::min(self.attempts, 16);
+		self.next_at = now + ::std::cmp::min(delay, RESUBMIT_MAX_DELAY_SECS);
+	}
+}
+
 /// Light transaction queue. See module docs for more details.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct TransactionQueue {
 	by_account: HashMap<Address, AccountTransactions>,
 	by_hash: H256FastMap<PendingTransaction>,
+	resubmissions: H256FastMap<Resubmission>,
 }
 
 impl TransactionQueue {
@@ -161,6 +199,7 @@ impl TransactionQueue {
 
 						let old = ::std::mem::replace(&mut acct_txs.current[idx], tx_info);
 						self.by_hash.remove(&old.hash);
+						self.resubmissions.remove(&old.hash);
 
 						TransactionImportResult::Current
 					}
@@ -319,6 +358,61 @@ impl TransactionQueue {
 
 		for hash in removed_hashes {
 			self.by_hash.remove(&hash);
+			self.resubmissions.remove(&hash);
+		}
+	}
+
+	/// Get the transactions due to be resubmitted at `now` (in seconds) and schedule
+	/// their next resubmission with exponential backoff.
+	pub fn due_resubmissions(&mut self, now: u64) -> Vec<H256> {
+		let mut due = Vec::new();
+		for hash in self.by_hash.keys() {
+			let resubmission = self.resubmissions.entry(*hash).or_insert_with(|| Resubmission::new(now));
+			if resubmission.next_at <= now {
+				resubmission.note_resubmitted(now);
+				due.push(*hash);
+			}
+		}
+		due
+	}
+
+	/// Drop transactions queued for longer than `TRANSACTION_LIFETIME_SECS` at `now` (in seconds).
+	/// Returns the number of dropped transactions.
+	pub fn expire(&mut self, now: u64) -> usize {
+		let expired: Vec<H256> = self.resubmissions.iter()
+			.filter(|&(_, r)| r.first_seen + TRANSACTION_LIFETIME_SECS <= now)
+			.map(|(hash, _)| *hash)
+			.collect();
+
+		for hash in &expired {
+			self.remove(hash);
+		}
+		expired.len()
+	}
+
+	// remove a transaction, moving any later current transactions of the sender to future.
+	fn remove(&mut self, hash: &H256) {
+		self.resubmissions.remove(hash);
+		let (sender, nonce) = match self.by_hash.remove(hash) {
+			Some(tx) => (tx.sender(), tx.nonce),
+			None => return,
+		};
+
+		if let Entry::Occupied(mut entry) = self.by_account.entry(sender) {
+			{
+				let acct_txs = entry.get_mut();
+				acct_txs.future.remove(&nonce);
+				if let Some(idx) = acct_txs.current.iter().position(|tx| tx.hash == *hash) {
+					for later in acct_txs.current.split_off(idx + 1) {
+						acct_txs.future.insert(later.nonce, later);
+					}
+					acct_txs.current.pop();
+				}
+			}
+
+			if entry.get_mut().is_empty() {
+				entry.remove();
+			}
 		}
 	}
 }
@@ -329,6 +423,62 @@ mod tests {
 	use util::Address;
 	use ethcore::transaction::{Transaction, PendingTransaction, Condition};
 
+	#[test]
+	fn resubmission_backoff() {
+		use super::{RESUBMIT_BASE_DELAY_SECS as BASE, RESUBMIT_MAX_DELAY_SECS};
+
+		let sender = Address::default();
+		let mut txq = TransactionQueue::default();
+		let tx = Transaction::default().fake_sign(sender);
+		let hash = tx.hash();
+
+		txq.import(tx.into()).unwrap();
+
+		assert!(txq.due_resubmissions(0).is_empty());
+		assert!(txq.due_resubmissions(BASE - 1).is_empty());
+		assert_eq!(txq.due_resubmissions(BASE), vec![hash]);
+		assert!(txq.due_resubmissions(BASE + 2 * BASE - 1).is_empty());
+		assert_eq!(txq.due_resubmissions(BASE + 2 * BASE), vec![hash]);
+
+		let mut now = 3 * BASE;
+		for _ in 0..10 {
+			now += RESUBMIT_MAX_DELAY_SECS;
+			assert_eq!(txq.due_resubmissions(now), vec![hash]);
+		}
+
+		txq.cull(sender, 1.into());
+		assert!(txq.due_resubmissions(now * 2).is_empty());
+		assert!(txq.resubmissions.is_empty());
+	}
+
+	#[test]
+	fn expire_transactions() {
+		use super::TRANSACTION_LIFETIME_SECS as LIFETIME;
+
+		let sender = Address::default();
+		let mut txq = TransactionQueue::default();
+
+		for i in 0..3 {
+			let mut tx = Transaction::default();
+			tx.nonce = i.into();
+			txq.import(tx.fake_sign(sender).into()).unwrap();
+		}
+		txq.due_resubmissions(0);
+
+		let mut tx = Transaction::default();
+		tx.nonce = 3.into();
+		txq.import(tx.fake_sign(sender).into()).unwrap();
+		txq.due_resubmissions(10);
+
+		assert_eq!(txq.expire(LIFETIME - 1), 0);
+		assert_eq!(txq.expire(LIFETIME), 3);
+		assert_eq!(txq.ready_transactions(0, 0).len(), 0);
+		assert_eq!(txq.future_transactions(0, 0).len(), 1);
+
+		assert_eq!(txq.expire(LIFETIME + 10), 1);
+		assert_eq!(txq.queued_senders(), vec![]);
+	}
+
 	#[test]
 	fn queued_senders() {
 		let sender = Address::default();
@@ -32,6 +32,8 @@ mod validator_set;
 mod validator_report;
 mod peer_set;
 mod tx_acl;
+mod block_reward;
+mod private;
 
 pub mod test_contracts;
 
@@ -44,3 +46,5 @@ pub use self::validator_set::ValidatorSet;
 pub use self::validator_report::ValidatorReport;
 pub use self::peer_set::PeerSet;
 pub use self::tx_acl::TransactAcl;
+pub use self::block_reward::BlockReward;
+pub use self::private::PrivateContract;
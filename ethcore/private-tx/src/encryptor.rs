@@ -0,0 +1,226 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encryption of private contract data.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bigint::hash::{H128, H256};
+use crypto::aes;
+use ethcore::account_provider::AccountProvider;
+use fetch::{Client as FetchClient, Fetch};
+use hash::keccak;
+use parking_lot::Mutex;
+use rustc_hex::FromHex;
+use serde_json;
+use util::{Address, Bytes};
+
+use error::Error;
+
+/// Length of the AES initialisation vector.
+pub const INIT_VEC_LEN: usize = 16;
+/// Length of the message authentication code.
+pub const MAC_LEN: usize = 32;
+/// Length of the AES key.
+const KEY_LEN: usize = 16;
+/// How long retrieved keys are cached.
+const KEY_CACHE_DURATION_SECS: u64 = 60;
+
+/// Encrypts and decrypts data with the key of a private contract.
+pub trait Encryptor: Send + Sync + 'static {
+	/// Encrypt data with the key of given contract.
+	fn encrypt(&self, contract_address: &Address, initialisation_vector: &H128, plain_data: &[u8]) -> Result<Bytes, Error>;
+
+	/// Decrypt data encrypted with the key of given contract.
+	fn decrypt(&self, contract_address: &Address, cypher: &[u8]) -> Result<Bytes, Error>;
+}
+
+/// Identifier of the Secret Store key of given contract.
+pub fn key_id(contract_address: &Address) -> H256 {
+	keccak(contract_address)
+}
+
+/// Authentication code of the encrypted data and initialisation vector.
+fn mac(key: &[u8], authenticated: &[u8]) -> H256 {
+	let mut message = keccak(key).to_vec();
+	message.extend_from_slice(authenticated);
+	keccak(&message)
+}
+
+/// Encrypt data with AES-128-CTR. The initialisation vector and the MAC of
+/// the encrypted data and initialisation vector are appended to the cypher.
+pub fn encrypt_with_key(key: &[u8], initialisation_vector: &H128, plain_data: &[u8]) -> Bytes {
+	let mut cypher = vec![0; plain_data.len() + INIT_VEC_LEN];
+	aes::encrypt(key, initialisation_vector, plain_data, &mut cypher[..plain_data.len()]);
+	cypher[plain_data.len()..].copy_from_slice(initialisation_vector);
+	let mac = mac(key, &cypher);
+	cypher.extend_from_slice(&mac);
+	cypher
+}
+
+/// Decrypt data produced by `encrypt_with_key`, rejecting data that has been tampered with.
+pub fn decrypt_with_key(key: &[u8], cypher: &[u8]) -> Result<Bytes, Error> {
+	if cypher.len() < INIT_VEC_LEN + MAC_LEN {
+		return Err(Error::Decrypt("Cypher is too short".into()));
+	}
+	let (authenticated, expected_mac) = cypher.split_at(cypher.len() - MAC_LEN);
+	if mac(key, authenticated)[..] != expected_mac[..] {
+		return Err(Error::Decrypt("Invalid MAC".into()));
+	}
+	let (encrypted, initialisation_vector) = authenticated.split_at(authenticated.len() - INIT_VEC_LEN);
+	let mut plain_data = vec![0; encrypted.len()];
+	aes::decrypt(key, initialisation_vector, encrypted, &mut plain_data);
+	Ok(plain_data)
+}
+
+/// Secret Store encryptor configuration.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct EncryptorConfig {
+	/// URL of the Secret Store HTTP API.
+	pub base_url: Option<String>,
+	/// Account used to authorise key requests.
+	pub key_server_account: Option<Address>,
+	/// Passwords used to unlock the account.
+	pub passwords: Vec<String>,
+}
+
+/// Encryptor retrieving contract keys from the Secret Store.
+///
+/// The document key stored under `keccak(contract_address)` is shared by all validators of the contract.
+pub struct SecretStoreEncryptor {
+	config: EncryptorConfig,
+	client: FetchClient,
+	accounts: Arc<AccountProvider>,
+	keys: Mutex<HashMap<Address, (Instant, Bytes)>>,
+}
+
+impl SecretStoreEncryptor {
+	/// Create a new encryptor.
+	pub fn new(config: EncryptorConfig, client: FetchClient, accounts: Arc<AccountProvider>) -> Self {
+		SecretStoreEncryptor {
+			config: config,
+			client: client,
+			accounts: accounts,
+			keys: Mutex::new(HashMap::new()),
+		}
+	}
+
+	fn retrieve_key(&self, contract_address: &Address) -> Result<Bytes, Error> {
+		if let Some(&(ref time, ref key)) = self.keys.lock().get(contract_address) {
+			if time.elapsed() < Duration::from_secs(KEY_CACHE_DURATION_SECS) {
+				return Ok(key.clone());
+			}
+		}
+
+		let base_url = self.config.base_url.as_ref()
+			.ok_or_else(|| Error::SecretStore("Secret Store URL is not set".into()))?;
+		let requester = self.config.key_server_account
+			.ok_or_else(|| Error::SecretStore("Key server account is not set".into()))?;
+
+		let key_id = key_id(contract_address);
+		let signature = ::sign_with_passwords(&self.accounts, requester, &self.config.passwords, key_id)?;
+		let url = format!("{}/{}/{}", base_url.trim_right_matches('/'), key_id.hex(), signature);
+		trace!(target: "privatetx", "Retrieving key of {:?} from {}", contract_address, url);
+
+		let mut response = self.client.fetch_sync(&url).map_err(|e| Error::SecretStore(format!("{:?}", e)))?;
+		if !response.is_success() {
+			return Err(Error::SecretStore(format!("Request failed with status {:?}", response.status())));
+		}
+		let mut body = String::new();
+		response.read_to_string(&mut body).map_err(|e| Error::SecretStore(e.to_string()))?;
+		let encrypted_key: String = serde_json::from_str(&body).map_err(|e| Error::SecretStore(e.to_string()))?;
+		let encrypted_key = if encrypted_key.starts_with("0x") { &encrypted_key[2..] } else { &encrypted_key[..] };
+		let encrypted_key = encrypted_key.from_hex().map_err(|e| Error::SecretStore(e.to_string()))?;
+
+		let document_key = ::decrypt_with_passwords(&self.accounts, requester, &self.config.passwords, &encrypted_key)?;
+		let key = keccak(&document_key)[..KEY_LEN].to_vec();
+		self.keys.lock().insert(*contract_address, (Instant::now(), key.clone()));
+		Ok(key)
+	}
+}
+
+impl Encryptor for SecretStoreEncryptor {
+	fn encrypt(&self, contract_address: &Address, initialisation_vector: &H128, plain_data: &[u8]) -> Result<Bytes, Error> {
+		let key = self.retrieve_key(contract_address)?;
+		Ok(encrypt_with_key(&key, initialisation_vector, plain_data))
+	}
+
+	fn decrypt(&self, contract_address: &Address, cypher: &[u8]) -> Result<Bytes, Error> {
+		let key = self.retrieve_key(contract_address)?;
+		decrypt_with_key(&key, cypher)
+	}
+}
+
+/// Encryptor which leaves the data as it is. Only useful for testing.
+#[derive(Default)]
+pub struct NoopEncryptor;
+
+impl Encryptor for NoopEncryptor {
+	fn encrypt(&self, _contract_address: &Address, _initialisation_vector: &H128, plain_data: &[u8]) -> Result<Bytes, Error> {
+		Ok(plain_data.to_vec())
+	}
+
+	fn decrypt(&self, _contract_address: &Address, cypher: &[u8]) -> Result<Bytes, Error> {
+		Ok(cypher.to_vec())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{encrypt_with_key, decrypt_with_key, Encryptor, NoopEncryptor, INIT_VEC_LEN, MAC_LEN};
+
+	#[test]
+	fn aes_roundtrip() {
+		let key = [7u8; 16];
+		let plain = b"private contract state".to_vec();
+		let cypher = encrypt_with_key(&key, &3.into(), &plain);
+
+		assert_eq!(cypher.len(), plain.len() + INIT_VEC_LEN + MAC_LEN);
+		assert!(cypher[..plain.len()] != plain[..]);
+		assert_eq!(decrypt_with_key(&key, &cypher).unwrap(), plain);
+	}
+
+	#[test]
+	fn aes_is_deterministic_for_given_iv() {
+		let key = [7u8; 16];
+		assert_eq!(encrypt_with_key(&key, &3.into(), b"state"), encrypt_with_key(&key, &3.into(), b"state"));
+		assert!(encrypt_with_key(&key, &3.into(), b"state") != encrypt_with_key(&key, &4.into(), b"state"));
+	}
+
+	#[test]
+	fn rejects_truncated_cypher() {
+		assert!(decrypt_with_key(&[7u8; 16], &[1, 2, 3]).is_err());
+	}
+
+	#[test]
+	fn rejects_tampered_cypher() {
+		let key = [7u8; 16];
+		let mut cypher = encrypt_with_key(&key, &3.into(), b"state");
+		cypher[0] ^= 1;
+		assert!(decrypt_with_key(&key, &cypher).is_err());
+		assert!(decrypt_with_key(&[8u8; 16], &encrypt_with_key(&key, &3.into(), b"state")).is_err());
+	}
+
+	#[test]
+	fn noop_roundtrip() {
+		let encryptor = NoopEncryptor;
+		let cypher = encryptor.encrypt(&5.into(), &3.into(), &[1, 2, 3]).unwrap();
+		assert_eq!(encryptor.decrypt(&5.into(), &cypher).unwrap(), vec![1, 2, 3]);
+	}
+}
@@ -0,0 +1,132 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions related errors.
+
+use std::fmt;
+
+use ethcore::account_provider::SignError;
+use ethcore::error::Error as EthcoreError;
+use ethcore::executed::{CallError, ExecutionError};
+use ethkey::Error as KeyError;
+use util::trie::TrieError;
+use rlp::DecoderError;
+
+/// Private transactions related errors.
+#[derive(Debug)]
+pub enum Error {
+	/// Decoder error.
+	Decoder(DecoderError),
+	/// Trie error.
+	Trie(TrieError),
+	/// Key error.
+	Key(KeyError),
+	/// Signing error.
+	Sign(SignError),
+	/// Error of the private execution.
+	Execution(ExecutionError),
+	/// Other ethcore error.
+	Ethcore(EthcoreError),
+	/// Call to the public contract failed.
+	Contract(String),
+	/// Encryption failed.
+	Encrypt(String),
+	/// Decryption failed.
+	Decrypt(String),
+	/// Key could not be retrieved from the Secret Store.
+	SecretStore(String),
+	/// State at the requested block is not available.
+	StatePruned,
+	/// Transaction is not suitable for the requested operation.
+	BadTransactionType,
+	/// Private transaction is not awaiting signatures.
+	PrivateTransactionNotFound,
+	/// Signature does not belong to any validator of the contract.
+	NotValidator,
+	/// No account is configured for signing public transactions.
+	SignerAccountNotSet,
+	/// No account with a known password is unlocked.
+	AccountNotUnlocked,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Decoder(ref err) => write!(f, "Malformed data: {}", err),
+			Error::Trie(ref err) => write!(f, "Trie error: {}", err),
+			Error::Key(ref err) => write!(f, "Key error: {}", err),
+			Error::Sign(ref err) => write!(f, "Signing error: {}", err),
+			Error::Execution(ref err) => write!(f, "Private execution failed: {}", err),
+			Error::Ethcore(ref err) => write!(f, "Ethcore error: {}", err),
+			Error::Contract(ref err) => write!(f, "Private contract call failed: {}", err),
+			Error::Encrypt(ref err) => write!(f, "Encryption failed: {}", err),
+			Error::Decrypt(ref err) => write!(f, "Decryption failed: {}", err),
+			Error::SecretStore(ref err) => write!(f, "Secret Store key retrieval failed: {}", err),
+			Error::StatePruned => write!(f, "State at the requested block is not available."),
+			Error::BadTransactionType => write!(f, "Transaction is not suitable for the requested operation."),
+			Error::PrivateTransactionNotFound => write!(f, "Private transaction is not awaiting signatures."),
+			Error::NotValidator => write!(f, "Signature does not belong to any validator of the contract."),
+			Error::SignerAccountNotSet => write!(f, "Account for signing public transactions is not configured."),
+			Error::AccountNotUnlocked => write!(f, "Account could not be unlocked with any of the given passwords."),
+		}
+	}
+}
+
+impl From<DecoderError> for Error {
+	fn from(err: DecoderError) -> Self {
+		Error::Decoder(err)
+	}
+}
+
+impl From<Box<TrieError>> for Error {
+	fn from(err: Box<TrieError>) -> Self {
+		Error::Trie(*err)
+	}
+}
+
+impl From<KeyError> for Error {
+	fn from(err: KeyError) -> Self {
+		Error::Key(err)
+	}
+}
+
+impl From<SignError> for Error {
+	fn from(err: SignError) -> Self {
+		Error::Sign(err)
+	}
+}
+
+impl From<ExecutionError> for Error {
+	fn from(err: ExecutionError) -> Self {
+		Error::Execution(err)
+	}
+}
+
+impl From<EthcoreError> for Error {
+	fn from(err: EthcoreError) -> Self {
+		Error::Ethcore(err)
+	}
+}
+
+impl From<CallError> for Error {
+	fn from(err: CallError) -> Self {
+		match err {
+			CallError::StatePruned => Error::StatePruned,
+			CallError::Execution(err) => Error::Execution(err),
+			other => Error::Contract(other.to_string()),
+		}
+	}
+}
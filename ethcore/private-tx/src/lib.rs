@@ -0,0 +1,575 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions.
+//!
+//! A private contract is deployed as a public contract (see `res/private.evm`) holding the list
+//! of its validators and its code and state, encrypted with a key shared by the validators.
+//! The public contract interface is:
+//!
+//! - `constructor(address[] validators, bytes code, bytes state)`
+//! - `getValidators() returns (address[])`, `code() returns (bytes)`, `state() returns (bytes)`,
+//! `nonce() returns (uint256)`
+//! - `setState(bytes newState, uint8[] v, bytes32[] r, bytes32[] s)`, which requires signatures
+//! of all validators, in order, of `keccak(keccak(newState) ++ nonce)` and increments the nonce.
+//!
+//! A private transaction is encrypted with the contract key and broadcast to the validators,
+//! who execute it against the decrypted state and sign the resulting encrypted state.
+//! Once all validators have signed, the originating node commits the state through `setState`.
+
+extern crate ethabi;
+extern crate ethcore;
+extern crate ethcore_bigint as bigint;
+extern crate ethcore_util as util;
+extern crate ethcrypto as crypto;
+extern crate ethkey;
+extern crate ethsync;
+extern crate fetch;
+extern crate futures;
+extern crate hash;
+extern crate lru_cache;
+extern crate native_contracts;
+extern crate parking_lot;
+extern crate rlp;
+extern crate rustc_hex;
+extern crate serde_json;
+
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlp_derive;
+
+mod encryptor;
+mod error;
+mod messages;
+mod private_transactions;
+
+pub use encryptor::{Encryptor, EncryptorConfig, SecretStoreEncryptor, NoopEncryptor, key_id};
+pub use error::Error;
+pub use messages::{PrivateTransaction, SignedPrivateTransaction};
+pub use private_transactions::{PrivateTransactionDesc, SigningStore};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use bigint::hash::{H128, H256};
+use bigint::prelude::U256;
+use ethabi::{Encoder, Token};
+use ethcore::account_provider::AccountProvider;
+use ethcore::client::{Client, BlockChainClient, BlockId};
+use ethcore::executed::Executed;
+use ethcore::miner::MinerService;
+use ethcore::transaction::{Action, SignedTransaction, Transaction};
+use ethcore::{contract_address, CreateContractAddress, Executive, TransactOptions};
+use ethkey::Signature;
+use ethsync::PrivateTxHandler;
+use futures::Future;
+use hash::keccak;
+use lru_cache::LruCache;
+use native_contracts::PrivateContract;
+use parking_lot::Mutex;
+use rlp::{Encodable, RlpStream, UntrustedRlp};
+use rustc_hex::FromHex;
+use util::{Address, Bytes};
+
+/// Initialisation code of the public part of private contracts.
+const PRIVATE_CONTRACT_CODE: &'static str = include_str!("../res/private.evm");
+
+/// Number of remembered message hashes.
+const MAX_IMPORTED_CACHE_SIZE: usize = 4096;
+/// Number of network messages awaiting import; further messages are dropped.
+const MAX_QUEUED_MESSAGES: usize = 256;
+
+/// Private transactions provider configuration.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ProviderConfig {
+	/// Accounts used to validate private transactions.
+	pub validator_accounts: Vec<Address>,
+	/// Account used to sign public transactions committing private state.
+	pub signer_account: Option<Address>,
+	/// Passwords used to unlock the accounts.
+	pub passwords: Vec<String>,
+}
+
+/// Receipt of a private transaction sent for validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+	/// Hash of the original transaction.
+	pub hash: H256,
+	/// Address of the private contract.
+	pub contract_address: Address,
+}
+
+/// Result of a private execution.
+#[derive(Debug)]
+pub struct PrivateExecutionResult {
+	/// Address of the executed contract.
+	pub contract_address: Address,
+	/// Resulting code of the contract.
+	pub code: Option<Bytes>,
+	/// Resulting (unencrypted) state of the contract.
+	pub state: Bytes,
+	/// Execution result.
+	pub result: Executed,
+}
+
+/// Creates, validates and commits private transactions.
+pub struct Provider {
+	encryptor: Box<Encryptor>,
+	validator_accounts: HashSet<Address>,
+	signer_account: Option<Address>,
+	passwords: Vec<String>,
+	transactions_for_signing: Mutex<SigningStore>,
+	imported: Mutex<LruCache<H256, ()>>,
+	client: Arc<Client>,
+	accounts: Arc<AccountProvider>,
+}
+
+impl Provider {
+	/// Create a new provider.
+	pub fn new(client: Arc<Client>, accounts: Arc<AccountProvider>, encryptor: Box<Encryptor>, config: ProviderConfig) -> Self {
+		Provider {
+			encryptor: encryptor,
+			validator_accounts: config.validator_accounts.into_iter().collect(),
+			signer_account: config.signer_account,
+			passwords: config.passwords,
+			transactions_for_signing: Mutex::new(SigningStore::default()),
+			imported: Mutex::new(LruCache::new(MAX_IMPORTED_CACHE_SIZE)),
+			client: client,
+			accounts: accounts,
+		}
+	}
+
+	/// Send a transaction calling a private contract to the contract validators.
+	///
+	/// The transaction itself never reaches the chain; once validated, the resulting state
+	/// is committed by a public transaction signed with the signer account.
+	pub fn create_private_transaction(&self, signed_transaction: SignedTransaction) -> Result<Receipt, Error> {
+		trace!(target: "privatetx", "Creating private transaction from {:?}", signed_transaction.hash());
+		if self.signer_account.is_none() {
+			return Err(Error::SignerAccountNotSet);
+		}
+		let contract = match signed_transaction.action {
+			Action::Call(contract) => contract,
+			Action::Create => return Err(Error::BadTransactionType),
+		};
+
+		let block = self.latest_block();
+		let validators = self.get_validators(&contract, block)?;
+		let contract_nonce = self.get_contract_nonce(&contract, block)?;
+		let executed = self.execute_private(&signed_transaction, block)?;
+		// Validators encrypt the state they compute with the same IV to sign the same hash.
+		let state_iv = H128::random();
+		let state = self.encryptor.encrypt(&contract, &state_iv, &executed.state)?;
+		let state_hash = state_hash(&state, contract_nonce);
+
+		let private = PrivateTransaction {
+			encrypted: self.encryptor.encrypt(&contract, &H128::random(), &signed_transaction.rlp_bytes())?,
+			contract: contract,
+			state_iv: state_iv,
+		};
+		let private_hash = private.hash();
+		self.note_imported(private_hash);
+
+		let own_validator = self.own_validator(&validators);
+		let receipt = Receipt {
+			hash: signed_transaction.hash(),
+			contract_address: contract,
+		};
+		let desc = PrivateTransactionDesc::new(signed_transaction, contract, validators, state, state_hash);
+		self.transactions_for_signing.lock().add(private_hash, desc);
+		self.client.broadcast_private_transaction(private.rlp_bytes().into_vec());
+
+		if let Some(validator) = own_validator {
+			let signature = self.sign(validator, state_hash)?;
+			self.add_signature(&private_hash, signature)?;
+		}
+		Ok(receipt)
+	}
+
+	/// Import a private transaction received from the network.
+	///
+	/// If one of our accounts validates the contract, the transaction is executed
+	/// and the signature of the resulting state is broadcast.
+	/// The transaction is relayed to other validators (which may not be our peers)
+	/// only if it targets a private contract and, when validating, executes successfully.
+	pub fn import_private_transaction(&self, rlp: &[u8]) -> Result<(), Error> {
+		let private: PrivateTransaction = UntrustedRlp::new(rlp).as_val()?;
+		let private_hash = private.hash();
+		if !self.note_imported(private_hash) {
+			return Ok(());
+		}
+
+		let block = self.latest_block();
+		let validators = self.get_validators(&private.contract, block)?;
+		let validator = match self.own_validator(&validators) {
+			Some(validator) => validator,
+			None => {
+				trace!(target: "privatetx", "Not a validator of {:?}, relaying {:?}", private.contract, private_hash);
+				self.client.broadcast_private_transaction(rlp.to_vec());
+				return Ok(());
+			},
+		};
+
+		let transaction = self.encryptor.decrypt(&private.contract, &private.encrypted)?;
+		let transaction = SignedTransaction::new(UntrustedRlp::new(&transaction).as_val()?)?;
+		if transaction.action != Action::Call(private.contract) {
+			return Err(Error::BadTransactionType);
+		}
+
+		let contract_nonce = self.get_contract_nonce(&private.contract, block)?;
+		let executed = self.execute_private(&transaction, block)?;
+		let state = self.encryptor.encrypt(&private.contract, &private.state_iv, &executed.state)?;
+		let signature = self.sign(validator, state_hash(&state, contract_nonce))?;
+
+		trace!(target: "privatetx", "Validated private transaction {:?} as {:?}", private_hash, validator);
+		self.client.broadcast_private_transaction(rlp.to_vec());
+		let signed = SignedPrivateTransaction::new(private_hash, signature);
+		self.note_imported(signed.hash());
+		self.client.broadcast_signed_private_transaction(signed.rlp_bytes().into_vec());
+		Ok(())
+	}
+
+	/// Import a validator's signature received from the network.
+	///
+	/// Signatures of transactions created by this node are collected; others are relayed.
+	pub fn import_signed_private_transaction(&self, rlp: &[u8]) -> Result<(), Error> {
+		let signed: SignedPrivateTransaction = UntrustedRlp::new(rlp).as_val()?;
+		if !self.note_imported(signed.hash()) {
+			return Ok(());
+		}
+		if !self.transactions_for_signing.lock().contains(&signed.private_transaction_hash) {
+			self.client.broadcast_signed_private_transaction(rlp.to_vec());
+			return Ok(());
+		}
+		self.add_signature(&signed.private_transaction_hash, signed.signature())
+	}
+
+	/// Execute a call to a private contract without committing anything.
+	pub fn private_call(&self, block: BlockId, transaction: &SignedTransaction) -> Result<Executed, Error> {
+		Ok(self.execute_private(transaction, block)?.result)
+	}
+
+	/// Build the public transaction deploying a private contract.
+	///
+	/// `source` is the contract creation transaction, which is executed privately.
+	/// Its resulting code and state are encrypted and embedded in the public contract.
+	/// Returns the unsigned public transaction and the address of the contract,
+	/// assuming contract addresses derived from sender and nonce.
+	pub fn public_creation_transaction(&self, block: BlockId, source: &SignedTransaction, validators: &[Address], gas_price: U256) -> Result<(Transaction, Address), Error> {
+		if source.action != Action::Create {
+			return Err(Error::BadTransactionType);
+		}
+		let sender = source.sender();
+		let executed = self.execute_private(source, block)?;
+		let (address, _) = contract_address(CreateContractAddress::FromSenderAndNonce, &sender, &source.nonce, &[]);
+		let code = executed.code.unwrap_or_default();
+		let encrypted_code = self.encryptor.encrypt(&address, &H128::random(), &code)?;
+		let encrypted_state = self.encryptor.encrypt(&address, &H128::random(), &executed.state)?;
+
+		let mut data: Bytes = PRIVATE_CONTRACT_CODE.trim().from_hex().expect("private contract code is valid hex; qed");
+		data.extend(Encoder::encode(vec![
+			Token::Array(validators.iter().map(|validator| Token::Address(validator.0)).collect()),
+			Token::Bytes(encrypted_code),
+			Token::Bytes(encrypted_state),
+		]));
+
+		let mut transaction = Transaction {
+			nonce: source.nonce,
+			action: Action::Create,
+			gas: U256::zero(),
+			gas_price: gas_price,
+			value: U256::zero(),
+			data: data,
+		};
+		transaction.gas = self.client.estimate_gas(&transaction.clone().fake_sign(sender), block, None)?;
+		Ok((transaction, address))
+	}
+
+	/// Number of private transactions awaiting signatures.
+	pub fn pending_signatures(&self) -> usize {
+		self.transactions_for_signing.lock().len()
+	}
+
+	fn add_signature(&self, private_hash: &H256, signature: Signature) -> Result<(), Error> {
+		let desc = match self.transactions_for_signing.lock().add_signature(private_hash, signature)? {
+			Some(desc) => desc,
+			None => return Ok(()),
+		};
+
+		trace!(target: "privatetx", "All validators signed {:?}, committing state", private_hash);
+		let chain_id = desc.original_transaction.chain_id();
+		let v = desc.signatures.iter().map(|s| s.as_ref().expect("transaction is complete; qed").v() + 27).collect();
+		let r = desc.signatures.iter().map(|s| H256::from_slice(s.as_ref().expect("transaction is complete; qed").r())).collect();
+		let s = desc.signatures.iter().map(|s| H256::from_slice(s.as_ref().expect("transaction is complete; qed").s())).collect();
+
+		PrivateContract::new(desc.contract).set_state(
+			|address, data| self.transact_public(address, data, chain_id).map_err(|e| e.to_string()),
+			desc.state,
+			v,
+			r,
+			s,
+		).wait().map_err(Error::Contract)
+	}
+
+	fn transact_public(&self, contract: Address, data: Bytes, chain_id: Option<u64>) -> Result<Bytes, Error> {
+		let signer = self.signer_account.ok_or(Error::SignerAccountNotSet)?;
+		let miner = self.client.miner();
+		let nonce = miner.last_nonce(&signer)
+			.map_or_else(|| self.client.latest_nonce(&signer), |nonce| nonce + U256::one());
+
+		let mut transaction = Transaction {
+			nonce: nonce,
+			action: Action::Call(contract),
+			gas: U256::zero(),
+			gas_price: miner.sensible_gas_price(),
+			value: U256::zero(),
+			data: data,
+		};
+		transaction.gas = self.client.estimate_gas(&transaction.clone().fake_sign(signer), BlockId::Latest, None)?;
+		let signature = self.sign(signer, transaction.hash(chain_id))?;
+		let transaction = SignedTransaction::new(transaction.with_signature(signature, chain_id))?;
+		miner.import_own_transaction(&*self.client, transaction.into())?;
+		Ok(Vec::new())
+	}
+
+	fn execute_private(&self, transaction: &SignedTransaction, block: BlockId) -> Result<PrivateExecutionResult, Error> {
+		// Execution is bounded by the block gas limit, just like public transactions.
+		let env_info = self.client.env_info(block).ok_or(Error::StatePruned)?;
+		let mut state = self.client.state_at(block).ok_or(Error::StatePruned)?;
+		let engine = self.client.engine();
+
+		let contract_address = match transaction.action {
+			Action::Call(ref contract_address) => {
+				let code = self.get_decrypted_code(contract_address, block)?;
+				let storage = storage_from_snapshot(&self.get_decrypted_state(contract_address, block)?)?;
+				state.patch_account(contract_address, Arc::new(code), storage)?;
+				*contract_address
+			},
+			Action::Create => {
+				let sender = transaction.sender();
+				let nonce = state.nonce(&sender)?;
+				contract_address(engine.create_address_scheme(env_info.number), &sender, &nonce, &transaction.data).0
+			},
+		};
+
+		let options = TransactOptions::with_no_tracing().dont_check_nonce().save_output_from_contract();
+		let result = Executive::new(&mut state, &env_info, engine).transact_virtual(transaction, options)?;
+		if let Some(ref exception) = result.exception {
+			return Err(Error::Contract(format!("Private execution failed: {:?}", exception)));
+		}
+
+		let (code, storage) = state.into_account(&contract_address)?;
+		Ok(PrivateExecutionResult {
+			contract_address: contract_address,
+			code: code.map(|code| (*code).clone()),
+			state: snapshot_from_storage(&storage),
+			result: result,
+		})
+	}
+
+	fn get_validators(&self, address: &Address, block: BlockId) -> Result<Vec<Address>, Error> {
+		PrivateContract::new(*address)
+			.get_validators(|addr, data| futures::done(self.client.call_contract(block, addr, data)))
+			.wait()
+			.map_err(Error::Contract)
+	}
+
+	fn get_contract_nonce(&self, address: &Address, block: BlockId) -> Result<U256, Error> {
+		PrivateContract::new(*address)
+			.nonce(|addr, data| futures::done(self.client.call_contract(block, addr, data)))
+			.wait()
+			.map_err(Error::Contract)
+	}
+
+	fn get_decrypted_code(&self, address: &Address, block: BlockId) -> Result<Bytes, Error> {
+		let code = PrivateContract::new(*address)
+			.code(|addr, data| futures::done(self.client.call_contract(block, addr, data)))
+			.wait()
+			.map_err(Error::Contract)?;
+		self.encryptor.decrypt(address, &code)
+	}
+
+	fn get_decrypted_state(&self, address: &Address, block: BlockId) -> Result<Bytes, Error> {
+		let state = PrivateContract::new(*address)
+			.state(|addr, data| futures::done(self.client.call_contract(block, addr, data)))
+			.wait()
+			.map_err(Error::Contract)?;
+		self.encryptor.decrypt(address, &state)
+	}
+
+	fn own_validator(&self, validators: &[Address]) -> Option<Address> {
+		validators.iter().find(|v| self.validator_accounts.contains(v)).cloned()
+	}
+
+	fn sign(&self, account: Address, hash: H256) -> Result<Signature, Error> {
+		sign_with_passwords(&self.accounts, account, &self.passwords, hash)
+	}
+
+	// Pin the block all contract calls and the execution refer to.
+	fn latest_block(&self) -> BlockId {
+		BlockId::Hash(self.client.chain_info().best_block_hash)
+	}
+
+	// Returns `false` if the message has already been seen.
+	// The least recently seen messages are forgotten first.
+	fn note_imported(&self, hash: H256) -> bool {
+		let mut imported = self.imported.lock();
+		if imported.get_mut(&hash).is_some() {
+			return false;
+		}
+		imported.insert(hash, ());
+		true
+	}
+}
+
+enum QueuedMessage {
+	Private(Bytes),
+	SignedPrivate(Bytes),
+}
+
+/// Network handler importing private transaction messages on a dedicated thread.
+///
+/// Importing executes transactions and may query the Secret Store, so it must not
+/// run on the network IO thread. Messages arriving when the queue is full are dropped.
+pub struct ImportQueue {
+	sender: Mutex<mpsc::SyncSender<QueuedMessage>>,
+}
+
+impl ImportQueue {
+	/// Create a new queue importing messages with given provider.
+	/// The import thread stops once the queue is dropped.
+	pub fn new(provider: Arc<Provider>) -> Self {
+		let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_MESSAGES);
+		thread::Builder::new()
+			.name("PrivateTxImport".into())
+			.spawn(move || {
+				for message in receiver {
+					let result = match message {
+						QueuedMessage::Private(rlp) => provider.import_private_transaction(&rlp),
+						QueuedMessage::SignedPrivate(rlp) => provider.import_signed_private_transaction(&rlp),
+					};
+					if let Err(e) = result {
+						debug!(target: "privatetx", "Error importing private transaction message: {}", e);
+					}
+				}
+			})
+			.expect("Error creating private transactions import thread");
+
+		ImportQueue {
+			sender: Mutex::new(sender),
+		}
+	}
+
+	fn push(&self, message: QueuedMessage) -> Result<(), String> {
+		self.sender.lock().try_send(message).map_err(|e| match e {
+			mpsc::TrySendError::Full(_) => "Private transactions import queue is full".into(),
+			mpsc::TrySendError::Disconnected(_) => "Private transactions import thread has stopped".into(),
+		})
+	}
+}
+
+impl PrivateTxHandler for ImportQueue {
+	fn import_private_transaction(&self, rlp: &[u8]) -> Result<(), String> {
+		self.push(QueuedMessage::Private(rlp.to_vec()))
+	}
+
+	fn import_signed_private_transaction(&self, rlp: &[u8]) -> Result<(), String> {
+		self.push(QueuedMessage::SignedPrivate(rlp.to_vec()))
+	}
+}
+
+fn sign_with_passwords(accounts: &AccountProvider, account: Address, passwords: &[String], hash: H256) -> Result<Signature, Error> {
+	for password in passwords {
+		if let Ok(signature) = accounts.sign(account, Some(password.clone()), hash) {
+			return Ok(signature);
+		}
+	}
+	// the account may have been unlocked permanently
+	accounts.sign(account, None, hash).map_err(|_| Error::AccountNotUnlocked)
+}
+
+fn decrypt_with_passwords(accounts: &AccountProvider, account: Address, passwords: &[String], cypher: &[u8]) -> Result<Bytes, Error> {
+	for password in passwords {
+		if let Ok(plain) = accounts.decrypt(account, Some(password.clone()), &crypto::DEFAULT_MAC, cypher) {
+			return Ok(plain);
+		}
+	}
+	accounts.decrypt(account, None, &crypto::DEFAULT_MAC, cypher).map_err(|_| Error::AccountNotUnlocked)
+}
+
+/// Hash of the contract state signed by the validators.
+fn state_hash(state: &[u8], nonce: U256) -> H256 {
+	let mut message = [0u8; 64];
+	message[..32].copy_from_slice(&keccak(state));
+	nonce.to_big_endian(&mut message[32..]);
+	keccak(&message[..])
+}
+
+/// Encode non-empty storage entries as an RLP list of `[key, value]` pairs ordered by key.
+fn snapshot_from_storage(storage: &HashMap<H256, H256>) -> Bytes {
+	let mut entries: Vec<_> = storage.iter().filter(|&(_, value)| !value.is_zero()).collect();
+	entries.sort();
+
+	let mut stream = RlpStream::new_list(entries.len());
+	for (key, value) in entries {
+		stream.begin_list(2).append(key).append(value);
+	}
+	stream.out()
+}
+
+fn storage_from_snapshot(snapshot: &[u8]) -> Result<HashMap<H256, H256>, Error> {
+	let mut storage = HashMap::new();
+	if snapshot.is_empty() {
+		return Ok(storage);
+	}
+	for entry in UntrustedRlp::new(snapshot).iter() {
+		storage.insert(entry.val_at(0)?, entry.val_at(1)?);
+	}
+	Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use rustc_hex::FromHex;
+	use super::{snapshot_from_storage, storage_from_snapshot, state_hash, PRIVATE_CONTRACT_CODE};
+
+	#[test]
+	fn storage_snapshot_roundtrip() {
+		let mut storage = HashMap::new();
+		storage.insert(2.into(), 5.into());
+		storage.insert(1.into(), 7.into());
+		storage.insert(3.into(), 0.into());
+
+		let snapshot = snapshot_from_storage(&storage);
+		storage.remove(&3.into());
+		assert_eq!(storage_from_snapshot(&snapshot).unwrap(), storage);
+		assert_eq!(snapshot_from_storage(&storage), snapshot);
+		assert!(storage_from_snapshot(&[]).unwrap().is_empty());
+	}
+
+	#[test]
+	fn state_hash_matches_contract() {
+		assert_eq!(state_hash(&[1, 2], 5.into()), "6e72b5745618fcd650e32caa65bdb7fdd7f75d938e662d98984700a878291aa2".into());
+	}
+
+	#[test]
+	fn private_contract_code_is_valid() {
+		assert!(PRIVATE_CONTRACT_CODE.trim().from_hex().is_ok());
+	}
+}
@@ -0,0 +1,107 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Network messages of the private transactions protocol.
+
+use bigint::hash::{H128, H256};
+use ethkey::Signature;
+use hash::keccak;
+use rlp::Encodable;
+use util::{Address, Bytes};
+
+/// Private transaction, encrypted for the validators of a contract.
+#[derive(Default, Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct PrivateTransaction {
+	/// Encrypted RLP of the original transaction.
+	pub encrypted: Bytes,
+	/// Address of the private contract.
+	pub contract: Address,
+	/// Random IV used to encrypt the resulting state, so that all validators sign the same ciphertext.
+	pub state_iv: H128,
+}
+
+impl PrivateTransaction {
+	/// Hash of the message.
+	pub fn hash(&self) -> H256 {
+		keccak(&*self.rlp_bytes())
+	}
+}
+
+/// Validator's signature of the state resulting from a private transaction.
+#[derive(Default, Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct SignedPrivateTransaction {
+	/// Hash of the signed `PrivateTransaction` message.
+	pub private_transaction_hash: H256,
+	/// V part of the signature.
+	pub v: u8,
+	/// R part of the signature.
+	pub r: H256,
+	/// S part of the signature.
+	pub s: H256,
+}
+
+impl SignedPrivateTransaction {
+	/// Create a new message from a validator's signature.
+	pub fn new(private_transaction_hash: H256, signature: Signature) -> Self {
+		SignedPrivateTransaction {
+			private_transaction_hash: private_transaction_hash,
+			v: signature.v(),
+			r: H256::from_slice(signature.r()),
+			s: H256::from_slice(signature.s()),
+		}
+	}
+
+	/// Signature of the validator.
+	pub fn signature(&self) -> Signature {
+		Signature::from_rsv(&self.r, &self.s, self.v)
+	}
+
+	/// Hash of the message.
+	pub fn hash(&self) -> H256 {
+		keccak(&*self.rlp_bytes())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Generator, Random, sign};
+	use rlp;
+	use super::{PrivateTransaction, SignedPrivateTransaction};
+
+	#[test]
+	fn private_transaction_rlp_roundtrip() {
+		let tx = PrivateTransaction {
+			encrypted: vec![1, 2, 3, 4],
+			contract: 5.into(),
+			state_iv: 6.into(),
+		};
+
+		let decoded: PrivateTransaction = rlp::decode(&rlp::encode(&tx));
+		assert_eq!(decoded, tx);
+		assert_eq!(decoded.hash(), tx.hash());
+	}
+
+	#[test]
+	fn signed_private_transaction_keeps_signature() {
+		let key = Random.generate().unwrap();
+		let signature = sign(key.secret(), &7.into()).unwrap();
+		let signed = SignedPrivateTransaction::new(3.into(), signature.clone());
+
+		let decoded: SignedPrivateTransaction = rlp::decode(&rlp::encode(&signed));
+		assert_eq!(decoded, signed);
+		assert_eq!(decoded.signature(), signature);
+	}
+}
@@ -0,0 +1,201 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions awaiting validators' signatures.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use bigint::hash::H256;
+use ethcore::transaction::SignedTransaction;
+use ethkey::{Signature, recover, public_to_address};
+use util::{Address, Bytes};
+
+use error::Error;
+
+/// Private transaction awaiting signatures of the contract validators.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivateTransactionDesc {
+	/// Original transaction.
+	pub original_transaction: SignedTransaction,
+	/// Address of the private contract.
+	pub contract: Address,
+	/// Validators of the contract, in the order expected by the contract.
+	pub validators: Vec<Address>,
+	/// Encrypted state resulting from the private execution.
+	pub state: Bytes,
+	/// Hash of the resulting state and contract nonce, signed by the validators.
+	pub state_hash: H256,
+	/// Signatures received so far, one slot per validator.
+	pub signatures: Vec<Option<Signature>>,
+}
+
+impl PrivateTransactionDesc {
+	/// Create a new description with no signatures.
+	pub fn new(original_transaction: SignedTransaction, contract: Address, validators: Vec<Address>, state: Bytes, state_hash: H256) -> Self {
+		let signatures = vec![None; validators.len()];
+		PrivateTransactionDesc {
+			original_transaction: original_transaction,
+			contract: contract,
+			validators: validators,
+			state: state,
+			state_hash: state_hash,
+			signatures: signatures,
+		}
+	}
+
+	/// Whether all validators have signed.
+	pub fn is_complete(&self) -> bool {
+		self.signatures.iter().all(Option::is_some)
+	}
+}
+
+/// Time after which a transaction still missing signatures is dropped.
+const SIGNING_TIMEOUT_SECS: u64 = 60 * 60;
+
+/// Storage of private transactions created by this node and awaiting validation.
+pub struct SigningStore {
+	transactions: HashMap<H256, (PrivateTransactionDesc, Instant)>,
+	timeout: Duration,
+}
+
+impl Default for SigningStore {
+	fn default() -> Self {
+		SigningStore::new(Duration::from_secs(SIGNING_TIMEOUT_SECS))
+	}
+}
+
+impl SigningStore {
+	/// Create a store dropping transactions not signed by all validators within `timeout`.
+	pub fn new(timeout: Duration) -> Self {
+		SigningStore {
+			transactions: HashMap::new(),
+			timeout: timeout,
+		}
+	}
+
+	/// Add a private transaction identified by the hash of its `PrivateTransaction` message.
+	pub fn add(&mut self, private_hash: H256, desc: PrivateTransactionDesc) {
+		self.remove_expired();
+		self.transactions.insert(private_hash, (desc, Instant::now()));
+	}
+
+	/// Drop transactions awaiting signatures for longer than the timeout.
+	pub fn remove_expired(&mut self) {
+		let now = Instant::now();
+		let timeout = self.timeout;
+		self.transactions.retain(|_, &mut (_, added)| now.duration_since(added) < timeout);
+	}
+
+	/// Whether the private transaction awaits signatures.
+	pub fn contains(&self, private_hash: &H256) -> bool {
+		self.transactions.contains_key(private_hash)
+	}
+
+	/// Number of transactions awaiting signatures.
+	pub fn len(&self) -> usize {
+		self.transactions.len()
+	}
+
+	/// Record a validator's signature of the resulting state.
+	///
+	/// Returns the transaction, removed from the store, once all validators have signed it.
+	pub fn add_signature(&mut self, private_hash: &H256, signature: Signature) -> Result<Option<PrivateTransactionDesc>, Error> {
+		self.remove_expired();
+		let complete = {
+			let desc = &mut self.transactions.get_mut(private_hash).ok_or(Error::PrivateTransactionNotFound)?.0;
+			let signer = public_to_address(&recover(&signature, &desc.state_hash)?);
+			let index = desc.validators.iter().position(|v| *v == signer).ok_or(Error::NotValidator)?;
+			desc.signatures[index] = Some(signature);
+			desc.is_complete()
+		};
+
+		if complete {
+			Ok(self.transactions.remove(private_hash).map(|(desc, _)| desc))
+		} else {
+			Ok(None)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::transaction::Transaction;
+	use ethkey::{Generator, Random, sign};
+	use error::Error;
+	use std::time::Duration;
+	use super::{SigningStore, PrivateTransactionDesc};
+
+	#[test]
+	fn collects_signatures_of_all_validators() {
+		let validators = vec![Random.generate().unwrap(), Random.generate().unwrap()];
+		let state_hash = 42.into();
+		let tx = Transaction::default().fake_sign(1.into());
+		let desc = PrivateTransactionDesc::new(tx, 5.into(), validators.iter().map(|k| k.address()).collect(), vec![1, 2], state_hash);
+
+		let mut store = SigningStore::default();
+		store.add(7.into(), desc);
+
+		let second = sign(validators[1].secret(), &state_hash).unwrap();
+		assert_eq!(store.add_signature(&7.into(), second.clone()).unwrap(), None);
+		assert!(store.contains(&7.into()));
+
+		let first = sign(validators[0].secret(), &state_hash).unwrap();
+		let complete = store.add_signature(&7.into(), first.clone()).unwrap().unwrap();
+		assert_eq!(complete.signatures, vec![Some(first), Some(second)]);
+		assert_eq!(store.len(), 0);
+	}
+
+	#[test]
+	fn rejects_foreign_signatures() {
+		let validator = Random.generate().unwrap();
+		let tx = Transaction::default().fake_sign(1.into());
+		let desc = PrivateTransactionDesc::new(tx, 5.into(), vec![validator.address()], vec![], 42.into());
+
+		let mut store = SigningStore::default();
+		store.add(7.into(), desc);
+
+		let stranger = Random.generate().unwrap();
+		match store.add_signature(&7.into(), sign(stranger.secret(), &42.into()).unwrap()) {
+			Err(Error::NotValidator) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		// signature of a different state
+		match store.add_signature(&7.into(), sign(validator.secret(), &43.into()).unwrap()) {
+			Err(Error::NotValidator) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match store.add_signature(&8.into(), sign(validator.secret(), &42.into()).unwrap()) {
+			Err(Error::PrivateTransactionNotFound) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn drops_expired_transactions() {
+		let tx = Transaction::default().fake_sign(1.into());
+		let desc = PrivateTransactionDesc::new(tx, 5.into(), vec![1.into()], vec![], 42.into());
+
+		let mut store = SigningStore::new(Duration::from_secs(0));
+		store.add(7.into(), desc.clone());
+		store.add(8.into(), desc);
+		assert!(!store.contains(&7.into()));
+		assert_eq!(store.len(), 1);
+
+		store.remove_expired();
+		assert_eq!(store.len(), 0);
+	}
+}
@@ -757,6 +757,12 @@ impl AccountProvider {
 			.map_err(Into::into)
 	}
 
+	/// Re-encrypt all keys in the vault under a fresh key and new password.
+	pub fn rotate_vault_key(&self, name: &str, password: &str, new_password: &str) -> Result<(), Error> {
+		self.sstore.rotate_vault_key(name, password, new_password)
+			.map_err(Into::into)
+	}
+
 	/// Change vault of the given address.
 	pub fn change_vault(&self, address: Address, new_vault: &str) -> Result<(), Error> {
 		let new_vault_ref = if new_vault.is_empty() { SecretVaultRef::Root } else { SecretVaultRef::Vault(new_vault.to_owned()) };
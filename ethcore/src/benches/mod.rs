@@ -0,0 +1,163 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! benchmarking for transaction execution and block verification
+//! should be started with:
+//! ```bash
+//! multirust run nightly cargo bench --features benches
+//! ```
+
+extern crate test;
+
+use self::test::Bencher;
+
+use std::env;
+use hash::keccak;
+use ethereum;
+use ethkey::{KeyPair, Secret};
+use header::Header;
+use state::{State, CleanupMode};
+use state_db::StateDB;
+use tests::helpers::*;
+use transaction::{Transaction, Action, SignedTransaction};
+use util::{Address, Bytes};
+use verification::verification::verify_block_unordered;
+use views::BlockView;
+use vm::EnvInfo;
+
+const TRANSACTIONS: usize = 200;
+
+/// First Byzantium block on the main network.
+const MAINNET_BLOCK: u64 = 4_370_000;
+
+fn transfer(secret: &Secret, nonce: u64, to: Address) -> SignedTransaction {
+	signed_transfer(secret, nonce, to, None)
+}
+
+fn signed_transfer(secret: &Secret, nonce: u64, to: Address, chain_id: Option<u64>) -> SignedTransaction {
+	Transaction {
+		nonce: nonce.into(),
+		gas_price: 1.into(),
+		gas: 21_000.into(),
+		action: Action::Call(to),
+		value: 1000.into(),
+		data: vec![],
+	}.sign(secret, chain_id)
+}
+
+fn setup(senders: usize) -> (State<StateDB>, Vec<SignedTransaction>) {
+	let secrets: Vec<Secret> = (0..senders).map(|i| keccak(i.to_string()).into()).collect();
+	let mut state = get_temp_state();
+	for secret in &secrets {
+		let address = KeyPair::from_secret(secret.clone()).unwrap().address();
+		state.add_balance(&address, &1_000_000_000.into(), CleanupMode::NoEmpty).unwrap();
+	}
+	state.commit().unwrap();
+
+	let transactions = (0..TRANSACTIONS)
+		.map(|i| transfer(&secrets[i % senders], (i / senders) as u64, Address::from(0x1000 + i as u64)))
+		.collect();
+	(state, transactions)
+}
+
+fn env_info() -> EnvInfo {
+	let mut info = EnvInfo::default();
+	info.gas_limit = 100_000_000.into();
+	info.author = Address::from(0xaa);
+	info
+}
+
+fn sequential(senders: usize, b: &mut Bencher) {
+	let engine = TestEngine::new_metropolis();
+	let (state, transactions) = setup(senders);
+
+	b.iter(|| {
+		let mut state = state.clone();
+		let mut info = env_info();
+		for t in &transactions {
+			info.gas_used = state.apply(&info, &engine, t, false).unwrap().receipt.gas_used;
+		}
+	});
+}
+
+fn parallel(senders: usize, b: &mut Bencher) {
+	let engine = TestEngine::new_metropolis();
+	let (state, transactions) = setup(senders);
+
+	b.iter(|| {
+		let mut state = state.clone();
+		state.apply_parallel(&env_info(), &engine, &transactions, false).unwrap();
+	});
+}
+
+#[bench]
+fn transfers_from_distinct_senders_sequential(b: &mut Bencher) {
+	sequential(TRANSACTIONS, b)
+}
+
+#[bench]
+fn transfers_from_distinct_senders_parallel(b: &mut Bencher) {
+	parallel(TRANSACTIONS, b)
+}
+
+#[bench]
+fn transfers_from_single_sender_sequential(b: &mut Bencher) {
+	sequential(1, b)
+}
+
+#[bench]
+fn transfers_from_single_sender_parallel(b: &mut Bencher) {
+	parallel(1, b)
+}
+
+/// Block of EIP-155 signed transfers verified under the main network rules at `MAINNET_BLOCK`.
+fn mainnet_block() -> (Header, Bytes) {
+	let transactions: Vec<_> = (0..TRANSACTIONS)
+		.map(|i| {
+			let secret: Secret = keccak(i.to_string()).into();
+			signed_transfer(&secret, 0, Address::from(0x1000 + i as u64), Some(1))
+		})
+		.collect();
+
+	let mut header = Header::new();
+	header.set_number(MAINNET_BLOCK);
+	header.set_gas_limit(8_000_000.into());
+	let bytes = create_test_block_with_data(&header, &transactions, &[]);
+	(header, bytes)
+}
+
+#[bench]
+fn mainnet_block_senders_sequential(b: &mut Bencher) {
+	let spec = ethereum::new_foundation(&env::temp_dir());
+	let (header, bytes) = mainnet_block();
+
+	b.iter(|| {
+		for t in BlockView::new(&bytes).transactions() {
+			spec.engine.verify_transaction(t, &header).unwrap();
+		}
+	});
+}
+
+#[bench]
+fn mainnet_block_senders_parallel(b: &mut Bencher) {
+	let spec = ethereum::new_foundation(&env::temp_dir());
+	let (header, bytes) = mainnet_block();
+
+	b.iter(|| {
+		verify_block_unordered(header.clone(), bytes.clone(), &*spec.engine, false).unwrap();
+	});
+}
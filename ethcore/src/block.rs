@@ -223,6 +223,7 @@ pub struct OpenBlock<'x> {
 	block: ExecutedBlock,
 	engine: &'x Engine,
 	last_hashes: Arc<LastHashes>,
+	executive_author: Option<Address>,
 }
 
 /// Just like `OpenBlock`, except that we've applied `Engine::on_close_block`, finished up the non-seal header fields,
@@ -275,6 +276,7 @@ impl<'x> OpenBlock<'x> {
 			block: ExecutedBlock::new(state, tracing),
 			engine: engine,
 			last_hashes: last_hashes.clone(),
+			executive_author: None,
 		};
 
 		r.block.header.set_parent_hash(parent.hash());
@@ -345,7 +347,7 @@ impl<'x> OpenBlock<'x> {
 		// TODO: memoise.
 		EnvInfo {
 			number: self.block.header.number(),
-			author: self.block.header.author().clone(),
+			author: self.executive_author.unwrap_or_else(|| self.block.header.author().clone()),
 			timestamp: self.block.header.timestamp(),
 			difficulty: self.block.header.difficulty().clone(),
 			last_hashes: self.last_hashes.clone(),
@@ -382,8 +384,36 @@ impl<'x> OpenBlock<'x> {
 		push_transactions(self, transactions)
 	}
 
+	/// Push transactions onto the block executing them optimistically in parallel.
+	///
+	/// The resulting block is the same as with `push_transactions`.
+	pub fn push_transactions_parallel(&mut self, transactions: &[SignedTransaction]) -> Result<(), Error> {
+		let mut hashes = HashSet::new();
+		let valid = transactions.iter()
+			.position(|t| self.block.transactions_set.contains(&t.hash()) || !hashes.insert(t.hash()))
+			.unwrap_or(transactions.len());
+
+		let env_info = self.env_info();
+		let tracing = self.block.traces.is_some();
+		let outcomes = self.block.state.apply_parallel(&env_info, self.engine, &transactions[..valid], tracing)?;
+		for (t, outcome) in transactions.iter().zip(outcomes) {
+			self.block.transactions_set.insert(t.hash());
+			self.block.transactions.push(t.clone().into());
+			self.block.traces.as_mut().map(|traces| traces.push(outcome.trace));
+			self.block.receipts.push(outcome.receipt);
+		}
+
+		if valid < transactions.len() {
+			return Err(From::from(TransactionError::AlreadyImported));
+		}
+		Ok(())
+	}
+
 	/// Populate self from a header.
+	///
+	/// Transactions are executed for the account the engine credits with the fees of `header`.
 	pub fn populate_from(&mut self, header: &Header) {
+		self.executive_author = Some(self.engine.executive_author(header));
 		self.set_difficulty(*header.difficulty());
 		self.set_gas_limit(*header.gas_limit());
 		self.set_timestamp(header.timestamp());
@@ -581,6 +611,7 @@ pub fn enact(
 	last_hashes: Arc<LastHashes>,
 	factories: Factories,
 	is_epoch_begin: bool,
+	parallel_transactions: bool,
 ) -> Result<LockedBlock, Error> {
 	{
 		if ::log::max_log_level() >= ::log::LogLevel::Trace {
@@ -604,7 +635,11 @@ pub fn enact(
 	)?;
 
 	b.populate_from(header);
-	b.push_transactions(transactions)?;
+	if parallel_transactions {
+		b.push_transactions_parallel(transactions)?;
+	} else {
+		b.push_transactions(transactions)?;
+	}
 
 	for u in uncles {
 		b.push_uncle(u.clone())?;
@@ -653,6 +688,7 @@ pub fn enact_verified(
 	last_hashes: Arc<LastHashes>,
 	factories: Factories,
 	is_epoch_begin: bool,
+	parallel_transactions: bool,
 ) -> Result<LockedBlock, Error> {
 	let view = BlockView::new(&block.bytes);
 
@@ -667,6 +703,7 @@ pub fn enact_verified(
 		last_hashes,
 		factories,
 		is_epoch_begin,
+		parallel_transactions,
 	)
 }
 
@@ -18,7 +18,9 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::mem;
+use std::{cmp, fmt, mem};
+use hash::keccak;
+use triehash::ordered_trie_root;
 use itertools::Itertools;
 use bloomchain as bc;
 use heapsize::HeapSizeOf;
@@ -179,6 +181,45 @@ impl bc::group::BloomGroupDatabase for BlockChain {
 	}
 }
 
+/// Inconsistency found in the stored data of a canonical block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockCorruption {
+	/// No hash is stored for the block number.
+	MissingHash,
+	/// The header is missing or can't be decoded.
+	BadHeader,
+	/// The header doesn't hash to the hash stored for its number.
+	WrongHeaderHash(H256),
+	/// The header's parent isn't the canonical block before it.
+	WrongParent(H256),
+	/// The body is missing or can't be decoded.
+	BadBody,
+	/// The transactions don't match the header's transactions root.
+	WrongTransactionsRoot(H256),
+	/// The uncles don't match the header's uncles hash.
+	WrongUnclesHash(H256),
+	/// The receipts are missing or can't be decoded.
+	BadReceipts,
+	/// The receipts don't match the header's receipts root.
+	WrongReceiptsRoot(H256),
+}
+
+impl fmt::Display for BlockCorruption {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BlockCorruption::MissingHash => write!(f, "block hash is missing"),
+			BlockCorruption::BadHeader => write!(f, "header is missing or invalid"),
+			BlockCorruption::WrongHeaderHash(ref found) => write!(f, "header hashes to {:?}", found),
+			BlockCorruption::WrongParent(ref found) => write!(f, "header has non-canonical parent {:?}", found),
+			BlockCorruption::BadBody => write!(f, "body is missing or invalid"),
+			BlockCorruption::WrongTransactionsRoot(ref found) => write!(f, "transactions have root {:?}", found),
+			BlockCorruption::WrongUnclesHash(ref found) => write!(f, "uncles have hash {:?}", found),
+			BlockCorruption::BadReceipts => write!(f, "receipts are missing or invalid"),
+			BlockCorruption::WrongReceiptsRoot(ref found) => write!(f, "receipts have root {:?}", found),
+		}
+	}
+}
+
 /// Structure providing fast access to blockchain data.
 ///
 /// **Does not do input data verification.**
@@ -213,6 +254,9 @@ pub struct BlockChain {
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
 	pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
+
+	// Number of the first block whose body and receipts haven't been pruned.
+	history_start: RwLock<BlockNumber>,
 }
 
 impl BlockProvider for BlockChain {
@@ -347,11 +391,34 @@ impl BlockProvider for BlockChain {
 
 	/// Get receipts of block with given hash.
 	fn block_receipts(&self, hash: &H256) -> Option<BlockReceipts> {
-		let result = self.db.read_with_cache(db::COL_EXTRA, &self.block_receipts, hash);
-		self.cache_man.lock().note_used(CacheId::BlockReceipts(hash.clone()));
+		{
+			let read = self.block_receipts.read();
+			if let Some(receipts) = read.get(hash) {
+				return Some(receipts.clone());
+			}
+		}
+
+		let result = self.block_receipts_rlp(hash).map(|bytes| decode::<BlockReceipts>(&bytes));
+		if let Some(ref receipts) = result {
+			self.block_receipts.write().insert(hash.clone(), receipts.clone());
+			self.cache_man.lock().note_used(CacheId::BlockReceipts(hash.clone()));
+		}
 		result
 	}
 
+	/// Get transaction receipt. Decodes only the requested receipt if the block isn't cached.
+	fn transaction_receipt(&self, address: &TransactionAddress) -> Option<Receipt> {
+		{
+			let read = self.block_receipts.read();
+			if let Some(receipts) = read.get(&address.block_hash) {
+				return receipts.receipts.get(address.index).cloned();
+			}
+		}
+
+		self.block_receipts_rlp(&address.block_hash)
+			.and_then(|bytes| UntrustedRlp::new(&bytes).val_at(address.index).ok())
+	}
+
 	/// Returns numbers of blocks containing given bloom.
 	fn blocks_with_bloom(&self, bloom: &H2048, from_block: BlockNumber, to_block: BlockNumber) -> Vec<BlockNumber> {
 		let range = from_block as bc::Number..to_block as bc::Number;
@@ -504,6 +571,7 @@ impl BlockChain {
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
+			history_start: RwLock::new(0),
 		};
 
 		// load best block
@@ -586,6 +654,10 @@ impl BlockChain {
 				},
 			}
 
+			if let Some(raw_start) = bc.db.get(db::COL_EXTRA, b"history_start").unwrap() {
+				*bc.history_start.get_mut() = decode(&raw_start);
+			}
+
 			// and write them
 			let mut best_block = bc.best_block.write();
 			*best_block = BestBlock {
@@ -614,11 +686,13 @@ impl BlockChain {
 		self.db.read_with_cache(db::COL_EXTRA, &self.block_details, parent).map_or(false, |d| d.children.contains(hash))
 	}
 
-	/// Rewind to a previous block
-	#[cfg(test)]
-	fn rewind(&self) -> Option<H256> {
+	/// Rewind the best block to its parent, removing it from the canon chain.
+	///
+	/// Returns the hash of the new best block or `None` if the best block is genesis.
+	/// Must not be called concurrently with block import.
+	pub fn rewind(&self) -> Option<H256> {
 		use db::Key;
-		let mut batch =self.db.transaction();
+		let mut batch = self.db.transaction();
 		// track back to the best block we have in the blocks database
 		if let Some(best_block_hash) = self.db.get(db::COL_EXTRA, b"best").unwrap() {
 			let best_block_hash = H256::from_slice(&best_block_hash);
@@ -627,7 +701,15 @@ impl BlockChain {
 			}
 			if let Some(extras) = self.db.read(db::COL_EXTRA, &best_block_hash) as Option<BlockDetails> {
 				type DetailsKey = Key<BlockDetails, Target=::bigint::hash::H264>;
+				type HashKey = Key<H256, Target=BlockNumberKey>;
+				type AddressKey = Key<TransactionAddress, Target=::bigint::hash::H264>;
 				batch.delete(db::COL_EXTRA, &(DetailsKey::key(&best_block_hash)));
+				batch.delete(db::COL_EXTRA, &(HashKey::key(&extras.number)));
+				if let Some(block) = self.block(&best_block_hash) {
+					for transaction_hash in block.transaction_hashes() {
+						batch.delete(db::COL_EXTRA, &(AddressKey::key(&transaction_hash)));
+					}
+				}
 				let hash = extras.parent;
 				let range = extras.number as bc::Number .. extras.number as bc::Number;
 				let chain = bc::group::BloomGroupChain::new(self.blooms_config, self);
@@ -650,7 +732,7 @@ impl BlockChain {
 				};
 				// update parent extras
 				if let Some(mut details) = self.db.read(db::COL_EXTRA, &hash) as Option<BlockDetails> {
-					details.children.clear();
+					details.children.retain(|child| child != &best_block_hash);
 					batch.write(db::COL_EXTRA, &hash, &details);
 				}
 				self.db.write(batch).expect("Writing to db failed");
@@ -659,6 +741,7 @@ impl BlockChain {
 				self.block_headers.write().clear();
 				self.block_bodies.write().clear();
 				self.block_receipts.write().clear();
+				self.transaction_addresses.write().clear();
 				return Some(hash);
 			}
 		}
@@ -666,6 +749,133 @@ impl BlockChain {
 		None
 	}
 
+	/// Number of the first block whose body and receipts are still stored,
+	/// if there are any earlier blocks at all.
+	pub fn history_start(&self) -> BlockNumber {
+		*self.history_start.read()
+	}
+
+	/// Removes bodies and receipts of canonical blocks before `until`, at most `max_blocks` of them.
+	/// The genesis block and all headers are kept. Returns the new history start.
+	pub fn prune_history(&self, batch: &mut DBTransaction, until: BlockNumber, max_blocks: u64) -> BlockNumber {
+		let start = cmp::max(self.history_start(), 1);
+		let end = cmp::min(until, start.saturating_add(max_blocks));
+		if end <= start {
+			return self.history_start();
+		}
+
+		let hashes: Vec<H256> = (start..end).filter_map(|number| self.block_hash(number)).collect();
+		{
+			let mut bodies = self.block_bodies.write();
+			let mut receipts = self.block_receipts.write();
+			for hash in hashes {
+				batch.delete(db::COL_BODIES, &hash);
+				batch.delete(db::COL_RECEIPTS, &<H256 as db::Key<BlockReceipts>>::key(&hash));
+				bodies.remove(&hash);
+				receipts.remove(&hash);
+			}
+		}
+
+		batch.put(db::COL_EXTRA, b"history_start", &encode(&end));
+		*self.history_start.write() = end;
+		end
+	}
+
+	/// Checks that the stored header, body and receipts of the canonical block with given
+	/// number are consistent with each other. Bodies and receipts of pruned history aren't checked.
+	pub fn check_block_integrity(&self, number: BlockNumber) -> Result<(), BlockCorruption> {
+		let hash = self.block_hash(number).ok_or(BlockCorruption::MissingHash)?;
+
+		let header = self.block_header_data(&hash).ok_or(BlockCorruption::BadHeader)?.into_inner();
+		let found = keccak(&header);
+		if found != hash {
+			return Err(BlockCorruption::WrongHeaderHash(found));
+		}
+		let header: Header = UntrustedRlp::new(&header).as_val().map_err(|_| BlockCorruption::BadHeader)?;
+		if number > 0 && self.block_hash(number - 1) != Some(*header.parent_hash()) {
+			return Err(BlockCorruption::WrongParent(*header.parent_hash()));
+		}
+
+		if number == 0 || number < self.history_start() {
+			return Ok(());
+		}
+
+		let body = self.block_body(&hash).ok_or(BlockCorruption::BadBody)?.into_inner();
+		let body = UntrustedRlp::new(&body);
+		let (transactions, uncles) = match (body.at(0), body.at(1)) {
+			(Ok(ref transactions), Ok(ref uncles)) if transactions.is_list() && uncles.is_list() =>
+				(ordered_trie_root(transactions.iter().map(|t| t.as_raw().to_vec())), keccak(uncles.as_raw())),
+			_ => return Err(BlockCorruption::BadBody),
+		};
+		if transactions != *header.transactions_root() {
+			return Err(BlockCorruption::WrongTransactionsRoot(transactions));
+		}
+		if uncles != *header.uncles_hash() {
+			return Err(BlockCorruption::WrongUnclesHash(uncles));
+		}
+
+		let key = <H256 as db::Key<BlockReceipts>>::key(&hash);
+		let receipts = self.db.get(db::COL_RECEIPTS, &key)
+			.expect("Low level database error. Some issue with disk?")
+			.ok_or(BlockCorruption::BadReceipts)
+			.and_then(|compressed| BlockReceipts::decompress(&compressed).map_err(|_| BlockCorruption::BadReceipts))?;
+		let receipts = UntrustedRlp::new(&receipts);
+		if !receipts.is_list() {
+			return Err(BlockCorruption::BadReceipts);
+		}
+		let found = ordered_trie_root(receipts.iter().map(|r| r.as_raw().to_vec()));
+		if found != *header.receipts_root() {
+			return Err(BlockCorruption::WrongReceiptsRoot(found));
+		}
+
+		Ok(())
+	}
+
+	/// Removes canonical blocks `from..to` entirely, leaving a gap which is filled by
+	/// downloading old blocks again after the next start. The range is expected to lie
+	/// strictly between the genesis and the best block, with no gap in the chain already.
+	pub fn forget_blocks(&self, batch: &mut DBTransaction, from: BlockNumber, to: BlockNumber) {
+		use db::Key;
+		type DetailsKey = Key<BlockDetails, Target=::bigint::hash::H264>;
+		type HashKey = Key<H256, Target=BlockNumberKey>;
+
+		let (parent, removed, first) = match (self.block_hash(from - 1), self.block_hash(from), self.block_hash(to)) {
+			(Some(parent), Some(removed), Some(first)) => (parent, removed, first),
+			_ => return,
+		};
+
+		for number in from..to {
+			if let Some(hash) = self.block_hash(number) {
+				batch.delete(db::COL_EXTRA, &HashKey::key(&number));
+				batch.delete(db::COL_EXTRA, &DetailsKey::key(&hash));
+				batch.delete(db::COL_HEADERS, &hash);
+				batch.delete(db::COL_BODIES, &hash);
+				batch.delete(db::COL_RECEIPTS, &<H256 as db::Key<BlockReceipts>>::key(&hash));
+			}
+		}
+
+		if let Some(mut details) = self.block_details(&parent) {
+			details.children.retain(|child| child != &removed);
+			batch.write(db::COL_EXTRA, &parent, &details);
+		}
+		batch.put(db::COL_EXTRA, b"ancient", &parent);
+		batch.put(db::COL_EXTRA, b"first", &first);
+
+		self.block_details.write().clear();
+		self.block_hashes.write().clear();
+		self.block_headers.write().clear();
+		self.block_bodies.write().clear();
+		self.block_receipts.write().clear();
+	}
+
+	/// Get RLP of receipts of block with given hash, bypassing the cache.
+	pub fn block_receipts_rlp(&self, hash: &H256) -> Option<Bytes> {
+		let key = <H256 as db::Key<BlockReceipts>>::key(hash);
+		self.db.get(db::COL_RECEIPTS, &key)
+			.expect("Low level database error. Some issue with disk?")
+			.map(|compressed| BlockReceipts::decompress(&compressed).expect("Receipts are compressed on insertion; qed"))
+	}
+
 	/// Returns a tree route between `from` and `to`, which is a tuple of:
 	///
 	/// - a vector of hashes of all blocks, ordered from `from` to `to`.
@@ -1052,7 +1262,10 @@ impl BlockChain {
 
 		{
 			let mut write_receipts = self.block_receipts.write();
-			batch.extend_with_cache(db::COL_EXTRA, &mut *write_receipts, update.block_receipts, CacheUpdatePolicy::Remove);
+			for (hash, receipts) in update.block_receipts {
+				batch.put(db::COL_RECEIPTS, &<H256 as db::Key<BlockReceipts>>::key(&hash), &receipts.compress());
+				write_receipts.remove(&hash);
+			}
 		}
 
 		{
@@ -2248,6 +2461,8 @@ mod tests {
 		assert!(!bc.is_known(&second_hash));
 		assert_eq!(bc.best_block_number(), 1);
 		assert_eq!(bc.best_block_hash(), first_hash.clone());
+		assert_eq!(bc.block_hash(2), None);
+		assert!(bc.block_details(&first_hash).unwrap().children.is_empty());
 
 		assert_eq!(bc.rewind(), Some(genesis_hash.clone()));
 		assert_eq!(bc.rewind(), None);
@@ -2385,4 +2600,112 @@ mod tests {
 			assert_eq!(bc.epoch_transition_for(fork_hash).unwrap().block_number, 0);
 		}
 	}
+
+	#[test]
+	fn prune_history_keeps_headers() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		for _ in 0..10 {
+			let block = canon_chain.generate(&mut finalizer).unwrap();
+			insert_block(&db, &bc, &block, vec![Receipt::new(None, 0.into(), vec![])]);
+		}
+		assert_eq!(bc.history_start(), 0);
+
+		let mut batch = db.transaction();
+		assert_eq!(bc.prune_history(&mut batch, 8, 5), 6);
+		db.write(batch).unwrap();
+
+		let mut batch = db.transaction();
+		assert_eq!(bc.prune_history(&mut batch, 8, 5), 8);
+		db.write(batch).unwrap();
+
+		assert!(bc.block_body(&bc.genesis_hash()).is_some());
+		for number in 1..8 {
+			let hash = bc.block_hash(number).unwrap();
+			assert!(bc.block_header(&hash).is_some());
+			assert!(bc.block_body(&hash).is_none());
+			assert!(bc.block_receipts(&hash).is_none());
+		}
+		let hash = bc.block_hash(8).unwrap();
+		assert!(bc.block_body(&hash).is_some());
+		assert!(bc.block_receipts(&hash).is_some());
+
+		let bc = new_chain(&genesis, db.clone());
+		assert_eq!(bc.history_start(), 8);
+	}
+
+	#[test]
+	fn detects_and_forgets_corrupted_blocks() {
+		use db;
+		use blockchain::BlockCorruption;
+		use blockchain::extras::BlockReceipts;
+
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		for _ in 0..6 {
+			let block = canon_chain.generate(&mut finalizer).unwrap();
+			insert_block(&db, &bc, &block, vec![]);
+		}
+		for number in 0..7 {
+			assert_eq!(bc.check_block_integrity(number), Ok(()));
+		}
+
+		let mut batch = db.transaction();
+		batch.put(db::COL_BODIES, &bc.block_hash(3).unwrap(), &[0xc0]);
+		let receipts = BlockReceipts::new(vec![Receipt::new(None, 1.into(), vec![])]);
+		batch.put(db::COL_RECEIPTS, &<H256 as db::Key<BlockReceipts>>::key(&bc.block_hash(4).unwrap()), &receipts.compress());
+		db.write(batch).unwrap();
+		bc.block_bodies.write().clear();
+
+		assert_eq!(bc.check_block_integrity(3), Err(BlockCorruption::BadBody));
+		match bc.check_block_integrity(4) {
+			Err(BlockCorruption::WrongReceiptsRoot(_)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert_eq!(bc.check_block_integrity(7), Err(BlockCorruption::MissingHash));
+
+		let first = bc.block_hash(5).unwrap();
+		let mut batch = db.transaction();
+		bc.forget_blocks(&mut batch, 3, 5);
+		db.write(batch).unwrap();
+
+		let bc = new_chain(&genesis, db.clone());
+		assert_eq!(bc.best_block_number(), 6);
+		assert_eq!(bc.block_hash(3), None);
+		assert_eq!(bc.block_hash(4), None);
+		assert_eq!(bc.best_ancient_number(), Some(2));
+		assert_eq!(bc.first_block(), Some(first));
+		assert_eq!(bc.check_block_integrity(5), Err(BlockCorruption::WrongParent(bc.block_header(&first).unwrap().parent_hash().clone())));
+	}
+
+	#[test]
+	fn detects_undecodable_receipts() {
+		use db;
+		use blockchain::BlockCorruption;
+		use blockchain::extras::BlockReceipts;
+
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		let block = canon_chain.generate(&mut finalizer).unwrap();
+		insert_block(&db, &bc, &block, vec![]);
+		assert_eq!(bc.check_block_integrity(1), Ok(()));
+
+		let mut batch = db.transaction();
+		batch.put(db::COL_RECEIPTS, &<H256 as db::Key<BlockReceipts>>::key(&bc.block_hash(1).unwrap()), &[0xff, 0xff, 0xff]);
+		db.write(batch).unwrap();
+
+		assert_eq!(bc.check_block_integrity(1), Err(BlockCorruption::BadReceipts));
+	}
 }
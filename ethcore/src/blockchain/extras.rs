@@ -29,6 +29,8 @@ use heapsize::HeapSizeOf;
 use bigint::prelude::U256;
 use bigint::hash::{H256, H264};
 use util::kvdb::PREFIX_LEN as DB_PREFIX_LEN;
+use util::snappy;
+use rlp;
 
 /// Represents index of extra data in database
 #[derive(Copy, Debug, Hash, Eq, PartialEq, Clone)]
@@ -227,6 +229,16 @@ impl BlockReceipts {
 			receipts: receipts
 		}
 	}
+
+	/// Encodes the receipts as stored in the receipts column.
+	pub fn compress(&self) -> Vec<u8> {
+		snappy::compress(&rlp::encode(self))
+	}
+
+	/// Decompresses receipts read from the receipts column into RLP.
+	pub fn decompress(compressed: &[u8]) -> Result<Vec<u8>, snappy::InvalidInput> {
+		snappy::decompress(compressed)
+	}
 }
 
 impl HeapSizeOf for BlockReceipts {
@@ -247,6 +259,19 @@ mod tests {
 	use rlp::*;
 	use super::BlockReceipts;
 
+	#[test]
+	fn compressed_block_receipts_roundtrip() {
+		use bigint::hash::H256;
+		use receipt::Receipt;
+
+		let br = BlockReceipts::new(vec![Receipt::new(Some(H256::zero()), 21000.into(), vec![]); 10]);
+		let compressed = br.compress();
+		assert!(compressed.len() < encode(&br).len());
+
+		let decoded: BlockReceipts = decode(&BlockReceipts::decompress(&compressed).unwrap());
+		assert_eq!(decoded.receipts, br.receipts);
+	}
+
 	#[test]
 	fn encode_block_receipts() {
 		let br = BlockReceipts::new(Vec::new());
@@ -28,7 +28,7 @@ mod update;
 #[cfg(test)]
 pub mod generator;
 
-pub use self::blockchain::{BlockProvider, BlockChain};
+pub use self::blockchain::{BlockProvider, BlockChain, BlockCorruption};
 pub use self::cache::CacheSize;
 pub use self::config::Config;
 pub use types::tree_route::TreeRoute;
@@ -17,7 +17,7 @@
 use std::cmp::{max, min};
 use std::io::{self, Read};
 
-use byteorder::{ByteOrder, BigEndian};
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
 use crypto::sha2::Sha256 as Sha256Digest;
 use crypto::ripemd160::Ripemd160 as Ripemd160Digest;
 use crypto::digest::Digest;
@@ -68,6 +68,17 @@ struct Modexp {
 	divisor: usize,
 }
 
+/// A pricing model for pairing check: base cost plus cost per pair of points (192 bytes).
+struct AltBn128PairingPricer {
+	base: usize,
+	pair: usize,
+}
+
+/// A pricing model for the Blake2 F compression function: cost per round.
+struct Blake2FPricer {
+	gas_per_round: u64,
+}
+
 impl Pricer for Linear {
 	fn cost(&self, input: &[u8]) -> U256 {
 		U256::from(self.base) + U256::from(self.word) * U256::from((input.len() + 31) / 32)
@@ -103,6 +114,22 @@ impl Pricer for Modexp {
 	}
 }
 
+impl Pricer for AltBn128PairingPricer {
+	fn cost(&self, input: &[u8]) -> U256 {
+		U256::from(self.base) + U256::from(self.pair) * U256::from(input.len() / 192)
+	}
+}
+
+impl Pricer for Blake2FPricer {
+	fn cost(&self, input: &[u8]) -> U256 {
+		if input.len() < 4 {
+			return U256::zero();
+		}
+		let rounds = BigEndian::read_u32(&input[0..4]);
+		U256::from(rounds) * U256::from(self.gas_per_round)
+	}
+}
+
 /// Pricing scheme, execution definition, and activation block for a built-in contract.
 ///
 /// Call `cost` to compute cost for the given input, `execute` to execute the contract
@@ -113,6 +140,7 @@ pub struct Builtin {
 	pricer: Box<Pricer>,
 	native: Box<Impl>,
 	activate_at: u64,
+	definition: Option<ethjson::spec::Builtin>,
 }
 
 impl Builtin {
@@ -126,11 +154,19 @@ impl Builtin {
 
 	/// Whether the builtin is activated at the given block number.
 	pub fn is_active(&self, at: u64) -> bool { at >= self.activate_at }
-}
 
-impl From<ethjson::spec::Builtin> for Builtin {
-	fn from(b: ethjson::spec::Builtin) -> Self {
-		let pricer: Box<Pricer> = match b.pricing {
+	/// Block number at which the builtin is activated.
+	pub fn activate_at(&self) -> u64 { self.activate_at }
+
+	/// Spec definition (implementation, pricing and activation) the builtin was created from.
+	pub fn definition(&self) -> Option<&ethjson::spec::Builtin> { self.definition.as_ref() }
+
+	/// Create a builtin from its spec definition.
+	///
+	/// Fails if no native implementation is registered under the given name.
+	pub fn from_json(b: ethjson::spec::Builtin) -> Result<Self, String> {
+		let native = ethereum_builtin(&b.name)?;
+		let pricer: Box<Pricer> = match b.pricing.clone() {
 			ethjson::spec::Pricing::Linear(linear) => {
 				Box::new(Linear {
 					base: linear.base,
@@ -147,29 +183,56 @@ impl From<ethjson::spec::Builtin> for Builtin {
 					}
 				})
 			}
+			ethjson::spec::Pricing::AltBn128Pairing(pricing) => {
+				Box::new(AltBn128PairingPricer {
+					base: pricing.base,
+					pair: pricing.pair,
+				})
+			}
+			ethjson::spec::Pricing::Blake2F(pricing) => {
+				Box::new(Blake2FPricer {
+					gas_per_round: pricing.gas_per_round,
+				})
+			}
 		};
 
-		Builtin {
+		Ok(Builtin {
 			pricer: pricer,
-			native: ethereum_builtin(&b.name),
+			native: native,
 			activate_at: b.activate_at.map(Into::into).unwrap_or(0),
-		}
+			definition: Some(b),
+		})
 	}
 }
 
+/// Native builtin implementations, by the name chain specifications refer to them with.
+const IMPLEMENTATIONS: &'static [(&'static str, fn() -> Box<Impl>)] = &[
+	("identity", boxed::<Identity>),
+	("ecrecover", boxed::<EcRecover>),
+	("sha256", boxed::<Sha256>),
+	("ripemd160", boxed::<Ripemd160>),
+	("modexp", boxed::<ModexpImpl>),
+	("bn128_add", boxed::<Bn128AddImpl>),
+	("bn128_mul", boxed::<Bn128MulImpl>),
+	("bn128_pairing", boxed::<Bn128PairingImpl>),
+	("blake2_f", boxed::<Blake2F>),
+];
+
+fn boxed<T: Impl + Default + 'static>() -> Box<Impl> {
+	Box::new(T::default())
+}
+
+/// Whether a native implementation is registered under the given name.
+pub fn is_known(name: &str) -> bool {
+	IMPLEMENTATIONS.iter().any(|&(n, _)| n == name)
+}
+
 // Ethereum builtin creator.
-fn ethereum_builtin(name: &str) -> Box<Impl> {
-	match name {
-		"identity" => Box::new(Identity) as Box<Impl>,
-		"ecrecover" => Box::new(EcRecover) as Box<Impl>,
-		"sha256" => Box::new(Sha256) as Box<Impl>,
-		"ripemd160" => Box::new(Ripemd160) as Box<Impl>,
-		"modexp" => Box::new(ModexpImpl) as Box<Impl>,
-		"bn128_add" => Box::new(Bn128AddImpl) as Box<Impl>,
-		"bn128_mul" => Box::new(Bn128MulImpl) as Box<Impl>,
-		"bn128_pairing" => Box::new(Bn128PairingImpl) as Box<Impl>,
-		_ => panic!("invalid builtin name: {}", name),
-	}
+fn ethereum_builtin(name: &str) -> Result<Box<Impl>, String> {
+	IMPLEMENTATIONS.iter()
+		.find(|&&(n, _)| n == name)
+		.map(|&(_, create)| create())
+		.ok_or_else(|| format!("Unknown builtin: {}", name))
 }
 
 // Ethereum builtins:
@@ -179,31 +242,35 @@ fn ethereum_builtin(name: &str) -> Box<Impl> {
 // - sha256
 // - ripemd160
 // - modexp (EIP198)
+// - blake2 F compression (EIP152)
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Identity;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct EcRecover;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Sha256;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Ripemd160;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct ModexpImpl;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Bn128AddImpl;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Bn128MulImpl;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Bn128PairingImpl;
 
+#[derive(Debug, Default)]
+struct Blake2F;
+
 impl Impl for Identity {
 	fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error> {
 		output.write(0, input);
@@ -504,9 +571,111 @@ impl Impl for Bn128PairingImpl {
 	}
 }
 
+const BLAKE2B_IV: [u64; 8] = [
+	0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+	0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+	[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+	[14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+	[11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+	[7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+	[9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+	[2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+	[12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+	[13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+	[6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+	[10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+#[inline(always)]
+fn blake2b_g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+	v[d] = (v[d] ^ v[a]).rotate_right(32);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(24);
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+	v[d] = (v[d] ^ v[a]).rotate_right(16);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+// The BLAKE2b compression function with a configurable number of rounds (RFC 7693, section 3.2).
+fn blake2b_compress(h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool, rounds: u32) {
+	let mut v = [0u64; 16];
+	v[..8].copy_from_slice(&h[..]);
+	v[8..].copy_from_slice(&BLAKE2B_IV);
+
+	v[12] ^= t[0];
+	v[13] ^= t[1];
+	if f {
+		v[14] = !v[14];
+	}
+
+	for i in 0..rounds as usize {
+		let s = &BLAKE2B_SIGMA[i % 10];
+		blake2b_g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+		blake2b_g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+		blake2b_g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+		blake2b_g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+
+		blake2b_g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+		blake2b_g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+		blake2b_g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+		blake2b_g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+	}
+
+	for i in 0..8 {
+		h[i] ^= v[i] ^ v[i + 8];
+	}
+}
+
+impl Impl for Blake2F {
+	/// Input is `rounds (4 bytes, BE) || h (64 bytes) || m (128 bytes) || t (16 bytes) || f (1 byte)`,
+	/// with all words except `rounds` little-endian. Output is the 64 byte state vector `h`.
+	fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error> {
+		const BLAKE2_F_ARG_LEN: usize = 213;
+
+		if input.len() != BLAKE2_F_ARG_LEN {
+			return Err("input length for Blake2 F precompile should be exactly 213 bytes".into());
+		}
+
+		let f = match input[212] {
+			1 => true,
+			0 => false,
+			_ => return Err("incorrect final block indicator flag".into()),
+		};
+
+		let rounds = BigEndian::read_u32(&input[0..4]);
+
+		let mut h = [0u64; 8];
+		for (i, word) in h.iter_mut().enumerate() {
+			*word = LittleEndian::read_u64(&input[4 + i * 8..]);
+		}
+
+		let mut m = [0u64; 16];
+		for (i, word) in m.iter_mut().enumerate() {
+			*word = LittleEndian::read_u64(&input[68 + i * 8..]);
+		}
+
+		let t = [LittleEndian::read_u64(&input[196..]), LittleEndian::read_u64(&input[204..])];
+
+		blake2b_compress(&mut h, &m, t, f, rounds);
+
+		let mut out = [0u8; 64];
+		for (i, word) in h.iter().enumerate() {
+			LittleEndian::write_u64(&mut out[i * 8..], *word);
+		}
+		output.write(0, &out[..]);
+
+		Ok(())
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{Builtin, Linear, ethereum_builtin, Pricer, Modexp, modexp as me};
+	use super::{Builtin, Linear, ethereum_builtin, is_known, Pricer, Modexp, Blake2FPricer, modexp as me};
 	use ethjson;
 	use bigint::prelude::U256;
 	use util::BytesRef;
@@ -548,7 +717,7 @@ mod tests {
 
 	#[test]
 	fn identity() {
-		let f = ethereum_builtin("identity");
+		let f = ethereum_builtin("identity").unwrap();
 
 		let i = [0u8, 1, 2, 3];
 
@@ -568,7 +737,7 @@ mod tests {
 
 	#[test]
 	fn sha256() {
-		let f = ethereum_builtin("sha256");
+		let f = ethereum_builtin("sha256").unwrap();
 
 		let i = [0u8; 0];
 
@@ -591,7 +760,7 @@ mod tests {
 
 	#[test]
 	fn ripemd160() {
-		let f = ethereum_builtin("ripemd160");
+		let f = ethereum_builtin("ripemd160").unwrap();
 
 		let i = [0u8; 0];
 
@@ -610,7 +779,7 @@ mod tests {
 
 	#[test]
 	fn ecrecover() {
-		let f = ethereum_builtin("ecrecover");
+		let f = ethereum_builtin("ecrecover").unwrap();
 
 		let i = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b650acf9d3f5f0a2c799776a1254355d5f4061762a237396a99a0e0e3fc2bcd6729514a0dacb2e623ac4abd157cb18163ff942280db4d5caad66ddf941ba12e03").unwrap();
 
@@ -663,8 +832,9 @@ mod tests {
 
 		let f = Builtin {
 			pricer: Box::new(Modexp { divisor: 20 }),
-			native: ethereum_builtin("modexp"),
+			native: ethereum_builtin("modexp").unwrap(),
 			activate_at: 0,
+			definition: None,
 		};
 		// fermat's little theorem example.
 		{
@@ -749,8 +919,9 @@ mod tests {
 
 		let f = Builtin {
 			pricer: Box::new(Linear { base: 0, word: 0 }),
-			native: ethereum_builtin("bn128_add"),
+			native: ethereum_builtin("bn128_add").unwrap(),
 			activate_at: 0,
+			definition: None,
 		};
 
 		// zero-points additions
@@ -810,8 +981,9 @@ mod tests {
 
 		let f = Builtin {
 			pricer: Box::new(Linear { base: 0, word: 0 }),
-			native: ethereum_builtin("bn128_mul"),
+			native: ethereum_builtin("bn128_mul").unwrap(),
 			activate_at: 0,
+			definition: None,
 		};
 
 		// zero-point multiplication
@@ -850,8 +1022,9 @@ mod tests {
 	fn builtin_pairing() -> Builtin {
 		Builtin {
 			pricer: Box::new(Linear { base: 0, word: 0 }),
-			native: ethereum_builtin("bn128_pairing"),
+			native: ethereum_builtin("bn128_pairing").unwrap(),
 			activate_at: 0,
+			definition: None,
 		}
 	}
 
@@ -924,9 +1097,23 @@ mod tests {
 	}
 
 	#[test]
-	#[should_panic]
 	fn from_unknown_linear() {
-		let _ = ethereum_builtin("foo");
+		assert!(ethereum_builtin("foo").is_err());
+		assert!(!is_known("foo"));
+	}
+
+	#[test]
+	fn unknown_builtin_from_json_fails() {
+		let b = Builtin::from_json(ethjson::spec::Builtin {
+			name: "foo".to_owned(),
+			pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
+				base: 10,
+				word: 20,
+			}),
+			activate_at: None,
+		});
+
+		assert_eq!(b.err(), Some("Unknown builtin: foo".into()));
 	}
 
 	#[test]
@@ -934,8 +1121,9 @@ mod tests {
 		let pricer = Box::new(Linear { base: 10, word: 20} );
 		let b = Builtin {
 			pricer: pricer as Box<Pricer>,
-			native: ethereum_builtin("identity"),
+			native: ethereum_builtin("identity").unwrap(),
 			activate_at: 100_000,
+			definition: None,
 		};
 
 		assert!(!b.is_active(99_999));
@@ -948,8 +1136,9 @@ mod tests {
 		let pricer = Box::new(Linear { base: 10, word: 20 });
 		let b = Builtin {
 			pricer: pricer as Box<Pricer>,
-			native: ethereum_builtin("identity"),
+			native: ethereum_builtin("identity").unwrap(),
 			activate_at: 1,
+			definition: None,
 		};
 
 		assert_eq!(b.cost(&[0; 0]), U256::from(10));
@@ -965,14 +1154,14 @@ mod tests {
 
 	#[test]
 	fn from_json() {
-		let b = Builtin::from(ethjson::spec::Builtin {
+		let b = Builtin::from_json(ethjson::spec::Builtin {
 			name: "identity".to_owned(),
 			pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
 				base: 10,
 				word: 20,
 			}),
 			activate_at: None,
-		});
+		}).unwrap();
 
 		assert_eq!(b.cost(&[0; 0]), U256::from(10));
 		assert_eq!(b.cost(&[0; 1]), U256::from(30));
@@ -984,4 +1173,163 @@ mod tests {
 		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..])).expect("Builtin should not fail");
 		assert_eq!(i, o);
 	}
+
+	#[test]
+	fn bn128_pairing_pricing_from_json() {
+		let b = Builtin::from_json(ethjson::spec::Builtin {
+			name: "bn128_pairing".to_owned(),
+			pricing: ethjson::spec::Pricing::AltBn128Pairing(ethjson::spec::AltBn128Pairing {
+				base: 45_000,
+				pair: 34_000,
+			}),
+			activate_at: Some(ethjson::uint::Uint(10.into())),
+		}).unwrap();
+
+		assert!(!b.is_active(9));
+		assert!(b.is_active(10));
+		assert_eq!(b.cost(&[0; 0]), U256::from(45_000));
+		assert_eq!(b.cost(&[0; 192]), U256::from(79_000));
+		assert_eq!(b.cost(&[0; 384]), U256::from(113_000));
+	}
+
+	#[test]
+	fn blake2_f() {
+		let f = Builtin {
+			pricer: Box::new(Blake2FPricer { gas_per_round: 1 }),
+			native: ethereum_builtin("blake2_f").unwrap(),
+			activate_at: 0,
+			definition: None,
+		};
+
+		// 12 rounds (blake2b of "abc")
+		{
+			let input = FromHex::from_hex("\
+				0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f\
+				3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13\
+				19cde05b61626300000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				000000000300000000000000000000000000000001"
+			).unwrap();
+
+			let mut output = vec![0u8; 64];
+			let expected = FromHex::from_hex("\
+				ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+				7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+			).unwrap();
+
+			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
+			assert_eq!(output, expected);
+		}
+
+		// 0 rounds
+		{
+			let input = FromHex::from_hex("\
+				0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f\
+				3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13\
+				19cde05b61626300000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				000000000300000000000000000000000000000001"
+			).unwrap();
+
+			let mut output = vec![0u8; 64];
+			let expected = FromHex::from_hex("\
+				08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+				d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b"
+			).unwrap();
+
+			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
+			assert_eq!(output, expected);
+		}
+
+		// 12 rounds, not final block
+		{
+			let input = FromHex::from_hex("\
+				0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f\
+				3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13\
+				19cde05b61626300000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				000000000300000000000000000000000000000000"
+			).unwrap();
+
+			let mut output = vec![0u8; 64];
+			let expected = FromHex::from_hex("\
+				75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d28752\
+				98743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735"
+			).unwrap();
+
+			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
+			assert_eq!(output, expected);
+		}
+
+		// 1 round
+		{
+			let input = FromHex::from_hex("\
+				0000000148c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f\
+				3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13\
+				19cde05b61626300000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				000000000300000000000000000000000000000001"
+			).unwrap();
+
+			let mut output = vec![0u8; 64];
+			let expected = FromHex::from_hex("\
+				b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fb\
+				a551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421"
+			).unwrap();
+
+			f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..])).expect("Builtin should not fail");
+			assert_eq!(output, expected);
+		}
+
+		// invalid final block indicator flag
+		{
+			let input = FromHex::from_hex("\
+				0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f\
+				3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13\
+				19cde05b61626300000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				0000000000000000000000000000000000000000000000000000000000000000\
+				000000000300000000000000000000000000000002"
+			).unwrap();
+			error_test(f, &input[..], Some("final block indicator"));
+		}
+	}
+
+	#[test]
+	fn blake2_f_invalid_length() {
+		let f = Builtin {
+			pricer: Box::new(Blake2FPricer { gas_per_round: 1 }),
+			native: ethereum_builtin("blake2_f").unwrap(),
+			activate_at: 0,
+			definition: None,
+		};
+
+		error_test(f, &[0u8; 212], Some("exactly 213 bytes"));
+	}
+
+	#[test]
+	fn blake2_f_pricing_from_json() {
+		let b = Builtin::from_json(ethjson::spec::Builtin {
+			name: "blake2_f".to_owned(),
+			pricing: ethjson::spec::Pricing::Blake2F(ethjson::spec::Blake2F {
+				gas_per_round: 1,
+			}),
+			activate_at: Some(ethjson::uint::Uint(100.into())),
+		}).unwrap();
+
+		assert!(!b.is_active(99));
+		assert!(b.is_active(100));
+		assert_eq!(b.cost(&[0; 0]), U256::zero());
+		assert_eq!(b.cost(&[0, 0, 0, 12]), U256::from(12));
+		assert_eq!(b.cost(&[0xff; 213]), U256::from(0xffffffffu64));
+	}
 }
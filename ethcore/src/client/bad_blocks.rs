@@ -0,0 +1,214 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistent store of recently rejected blocks.
+
+use std::collections::VecDeque;
+use bigint::hash::H256;
+use rlp::{self, Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
+use util::{Bytes, DBTransaction, KeyValueDB};
+
+/// Key of the bad blocks in the node info column.
+const BAD_BLOCKS_KEY: &'static [u8] = b"bad_blocks";
+/// Key of the manually invalidated block hashes in the node info column.
+const INVALIDATED_BLOCKS_KEY: &'static [u8] = b"invalidated_blocks";
+/// Maximal number of bad blocks kept.
+const MAX_BAD_BLOCKS: usize = 32;
+
+/// Block which failed to import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadBlock {
+	/// Block hash.
+	pub hash: H256,
+	/// Block RLP.
+	pub rlp: Bytes,
+	/// Why the block was rejected.
+	pub reason: String,
+	/// Peer the block was received from, if known.
+	pub origin: Option<String>,
+	/// Unix timestamp of the rejection.
+	pub timestamp: u64,
+}
+
+impl Encodable for BadBlock {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(if self.origin.is_some() { 5 } else { 4 });
+		s.append(&self.hash);
+		s.append(&self.rlp);
+		s.append(&self.reason);
+		s.append(&self.timestamp);
+		if let Some(ref origin) = self.origin {
+			s.append(origin);
+		}
+	}
+}
+
+impl Decodable for BadBlock {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		Ok(BadBlock {
+			hash: rlp.val_at(0)?,
+			rlp: rlp.val_at(1)?,
+			reason: rlp.val_at(2)?,
+			timestamp: rlp.val_at(3)?,
+			origin: match rlp.item_count()? {
+				5 => Some(rlp.val_at(4)?),
+				_ => None,
+			},
+		})
+	}
+}
+
+/// Most recently rejected blocks, newest first.
+///
+/// Only blocks failing the checks done against the chain (family, external and
+/// final verification or enactment) are kept. Blocks rejected by the stateless checks of
+/// the verification queue are dropped there without their RLP.
+///
+/// Hashes of manually invalidated blocks are kept separately and never evicted.
+#[derive(Debug, Default)]
+pub struct BadBlocks {
+	blocks: VecDeque<BadBlock>,
+	invalidated: Vec<H256>,
+}
+
+impl BadBlocks {
+	/// Loads bad blocks persisted in the database.
+	pub fn load(db: &KeyValueDB) -> Self {
+		BadBlocks {
+			blocks: load_list(db, BAD_BLOCKS_KEY).into(),
+			invalidated: load_list(db, INVALIDATED_BLOCKS_KEY),
+		}
+	}
+
+	/// Records a rejected block and persists the store.
+	pub fn report(&mut self, block: BadBlock, db: &KeyValueDB) {
+		self.push(block);
+		self.persist(db);
+	}
+
+	/// Records a block invalidated by the user, which should be rejected until the hash is
+	/// removed from the database, and persists the store.
+	pub fn invalidate(&mut self, block: BadBlock, db: &KeyValueDB) {
+		if !self.invalidated.contains(&block.hash) {
+			self.invalidated.push(block.hash);
+		}
+		self.push(block);
+		self.persist(db);
+	}
+
+	/// Returns the recorded blocks, newest first.
+	pub fn blocks(&self) -> Vec<BadBlock> {
+		self.blocks.iter().cloned().collect()
+	}
+
+	/// Returns hashes of all manually invalidated blocks.
+	pub fn invalidated(&self) -> &[H256] {
+		&self.invalidated
+	}
+
+	fn push(&mut self, block: BadBlock) {
+		self.blocks.retain(|b| b.hash != block.hash);
+		self.blocks.push_front(block);
+		self.blocks.truncate(MAX_BAD_BLOCKS);
+	}
+
+	fn persist(&self, db: &KeyValueDB) {
+		let blocks: Vec<_> = self.blocks.iter().cloned().collect();
+		let mut batch = DBTransaction::new();
+		batch.put(::db::COL_NODE_INFO, BAD_BLOCKS_KEY, &rlp::encode_list(&blocks));
+		batch.put(::db::COL_NODE_INFO, INVALIDATED_BLOCKS_KEY, &rlp::encode_list(&self.invalidated));
+		if let Err(e) = db.write(batch) {
+			warn!(target: "client", "Failed to persist bad blocks: {}", e);
+		}
+	}
+}
+
+fn load_list<T: Decodable>(db: &KeyValueDB, key: &[u8]) -> Vec<T> {
+	db.get(::db::COL_NODE_INFO, key)
+		.expect("Low level database error. Some issue with disk?")
+		.map(|data| match UntrustedRlp::new(&data).as_list() {
+			Ok(items) => items,
+			Err(e) => {
+				warn!(target: "client", "Discarding corrupted bad blocks record: {}", e);
+				Vec::new()
+			},
+		})
+		.unwrap_or_else(Vec::new)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use bigint::hash::H256;
+	use util::kvdb::in_memory;
+	use super::{BadBlock, BadBlocks, MAX_BAD_BLOCKS};
+
+	fn bad_block(n: u64, origin: Option<String>) -> BadBlock {
+		BadBlock {
+			hash: n.into(),
+			rlp: vec![0xc0 + n as u8],
+			reason: format!("reason {}", n),
+			origin: origin,
+			timestamp: 1_500_000_000 + n,
+		}
+	}
+
+	#[test]
+	fn should_persist_bad_blocks() {
+		let db = Arc::new(in_memory(::db::NUM_COLUMNS.unwrap()));
+		{
+			let mut bad_blocks = BadBlocks::load(&*db);
+			bad_blocks.report(bad_block(1, None), &*db);
+			bad_blocks.report(bad_block(2, Some("Parity/v1.8.0".into())), &*db);
+		}
+
+		let bad_blocks = BadBlocks::load(&*db);
+		assert_eq!(bad_blocks.blocks(), vec![bad_block(2, Some("Parity/v1.8.0".into())), bad_block(1, None)]);
+	}
+
+	#[test]
+	fn should_keep_invalidated_blocks() {
+		let db = Arc::new(in_memory(::db::NUM_COLUMNS.unwrap()));
+		{
+			let mut bad_blocks = BadBlocks::load(&*db);
+			bad_blocks.invalidate(bad_block(1, None), &*db);
+			for n in 2..(MAX_BAD_BLOCKS as u64 + 3) {
+				bad_blocks.report(bad_block(n, None), &*db);
+			}
+		}
+
+		let bad_blocks = BadBlocks::load(&*db);
+		assert!(bad_blocks.blocks().iter().all(|b| b.hash != 1.into()));
+		assert_eq!(bad_blocks.invalidated().to_vec(), vec![H256::from(1)]);
+	}
+
+	#[test]
+	fn should_keep_most_recent_bad_blocks() {
+		let db = Arc::new(in_memory(::db::NUM_COLUMNS.unwrap()));
+		let mut bad_blocks = BadBlocks::load(&*db);
+		for n in 0..(MAX_BAD_BLOCKS as u64 + 2) {
+			bad_blocks.report(bad_block(n, None), &*db);
+		}
+		bad_blocks.report(bad_block(10, None), &*db);
+
+		let blocks = bad_blocks.blocks();
+		assert_eq!(blocks.len(), MAX_BAD_BLOCKS);
+		assert_eq!(blocks[0], bad_block(10, None));
+		assert_eq!(blocks[1], bad_block(MAX_BAD_BLOCKS as u64 + 1, None));
+		assert!(blocks.iter().filter(|b| b.hash == 10.into()).count() == 1);
+		assert!(blocks.iter().all(|b| b.hash != 0.into() && b.hash != 1.into()));
+	}
+}
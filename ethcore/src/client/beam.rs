@@ -0,0 +1,365 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Experimental on-demand ("beam") state sync.
+//!
+//! A beam-syncing client restores only the block chunks of a snapshot and starts importing
+//! blocks from its pivot right away. State trie nodes the local database lacks are recorded
+//! when block execution or queries touch them, fetched from peers and written to the database,
+//! while a background walk over the latest state requests whatever remains.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bigint::hash::H256;
+use hash::{KECCAK_EMPTY, KECCAK_NULL_RLP};
+use parking_lot::Mutex;
+use util::{Bytes, DBValue, HashDB, Trie, UtilError};
+use util::journaldb::JournalDB;
+use util::kvdb::{self, DBTransaction, KeyValueDB};
+use util::memorydb::MemoryDB;
+use util::trie::{self, TrieHasher};
+
+use basic_account::BasicAccount;
+use factory::Factories;
+
+/// Number of times a node is requested before it's assumed no peer has it.
+const MAX_NODE_REQUESTS: usize = 16;
+
+/// State nodes missing from the local database, keyed as they were looked up.
+///
+/// Storage trie nodes and code are looked up by keys mangled with the address hash, which
+/// differ from the node hash only past the first `kvdb::PREFIX_LEN` bytes. Peers serve
+/// `GetNodeData` requests by that prefix, so fetched nodes are matched on it as well.
+#[derive(Default, Clone)]
+pub struct MissingNodes(Arc<Mutex<HashMap<H256, usize>>>, Arc<AtomicUsize>);
+
+impl MissingNodes {
+	fn record(&self, key: &H256) {
+		if *key != KECCAK_NULL_RLP {
+			self.0.lock().entry(*key).or_insert(0);
+			self.1.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	/// Total number of lookups of missing nodes so far.
+	/// Compared before and after an operation to tell whether it touched missing state.
+	pub fn misses(&self) -> usize {
+		self.1.load(Ordering::SeqCst)
+	}
+
+	/// Whether all recorded nodes have been fetched.
+	pub fn is_empty(&self) -> bool {
+		self.0.lock().is_empty()
+	}
+
+	/// Returns up to `max` nodes to request, forgetting the ones requested too many times.
+	pub fn request(&self, max: usize) -> Vec<H256> {
+		let mut missing = self.0.lock();
+		let exhausted: Vec<H256> = missing.iter()
+			.filter(|&(_, requests)| *requests >= MAX_NODE_REQUESTS)
+			.map(|(key, _)| *key)
+			.collect();
+		for key in exhausted {
+			debug!(target: "client", "Giving up on fetching state node {}", key);
+			missing.remove(&key);
+		}
+
+		let mut keys = Vec::new();
+		for (key, requests) in missing.iter_mut().take(max) {
+			*requests += 1;
+			keys.push(*key);
+		}
+		keys
+	}
+
+	/// Removes and returns all missing keys the node with given hash resolves.
+	fn resolve(&self, hash: &H256) -> Vec<H256> {
+		let mut missing = self.0.lock();
+		let keys: Vec<H256> = missing.keys()
+			.filter(|key| key[..kvdb::PREFIX_LEN] == hash[..kvdb::PREFIX_LEN])
+			.cloned()
+			.collect();
+		for key in &keys {
+			missing.remove(key);
+		}
+		keys
+	}
+
+	/// Stores fetched nodes in `db`, under the keys they were missing by.
+	/// Returns the number of nodes which were actually missing.
+	pub fn import(&self, db: &mut JournalDB, batch: &mut DBTransaction, hasher: TrieHasher, nodes: Vec<Bytes>) -> Result<usize, UtilError> {
+		let mut imported = 0;
+		for node in nodes {
+			let keys = self.resolve(&hasher.hash(&node));
+			if keys.is_empty() { continue }
+
+			imported += 1;
+			for key in keys {
+				db.emplace(key, DBValue::from_slice(&node));
+			}
+		}
+		db.inject(batch)?;
+		Ok(imported)
+	}
+}
+
+/// Journal database recording lookups of nodes it doesn't have.
+pub struct RecordingJournalDB {
+	db: Box<JournalDB>,
+	missing: MissingNodes,
+}
+
+impl RecordingJournalDB {
+	/// Wraps given database, recording missing nodes in `missing`.
+	pub fn new(db: Box<JournalDB>, missing: MissingNodes) -> Self {
+		RecordingJournalDB {
+			db: db,
+			missing: missing,
+		}
+	}
+}
+
+impl HashDB for RecordingJournalDB {
+	fn keys(&self) -> HashMap<H256, i32> {
+		self.db.keys()
+	}
+
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		let value = self.db.get(key);
+		if value.is_none() {
+			self.missing.record(key);
+		}
+		value
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		let contains = self.db.contains(key);
+		if !contains {
+			self.missing.record(key);
+		}
+		contains
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		self.db.insert(value)
+	}
+
+	fn emplace(&mut self, key: H256, value: DBValue) {
+		self.db.emplace(key, value)
+	}
+
+	fn remove(&mut self, key: &H256) {
+		self.db.remove(key)
+	}
+}
+
+impl JournalDB for RecordingJournalDB {
+	fn boxed_clone(&self) -> Box<JournalDB> {
+		Box::new(RecordingJournalDB::new(self.db.boxed_clone(), self.missing.clone()))
+	}
+
+	fn mem_used(&self) -> usize {
+		self.db.mem_used()
+	}
+
+	fn journal_size(&self) -> usize {
+		self.db.journal_size()
+	}
+
+	fn is_empty(&self) -> bool {
+		self.db.is_empty()
+	}
+
+	fn earliest_era(&self) -> Option<u64> {
+		self.db.earliest_era()
+	}
+
+	fn latest_era(&self) -> Option<u64> {
+		self.db.latest_era()
+	}
+
+	fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, id: &H256) -> Result<u32, UtilError> {
+		self.db.journal_under(batch, now, id)
+	}
+
+	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> Result<u32, UtilError> {
+		self.db.mark_canonical(batch, era, id)
+	}
+
+	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
+		self.db.inject(batch)
+	}
+
+	fn state(&self, id: &H256) -> Option<Bytes> {
+		self.db.state(id)
+	}
+
+	fn is_pruned(&self) -> bool {
+		self.db.is_pruned()
+	}
+
+	fn backing(&self) -> &Arc<KeyValueDB> {
+		self.db.backing()
+	}
+
+	fn flush(&self) {
+		self.db.flush()
+	}
+
+	fn consolidate(&mut self, overlay: MemoryDB) {
+		self.db.consolidate(overlay)
+	}
+}
+
+/// Background walk over the latest state, visiting accounts in key order.
+///
+/// The walk follows the best block, so the root changes between steps; parts of the state
+/// created after they were passed are written by block import and already present.
+#[derive(Default)]
+pub struct Backfill {
+	// last fully visited account.
+	account: Option<Vec<u8>>,
+	// account being visited and its last visited storage key.
+	storage: Option<(Vec<u8>, Vec<u8>)>,
+	complete: bool,
+}
+
+impl Backfill {
+	/// Whether the whole state has been visited.
+	pub fn is_complete(&self) -> bool {
+		self.complete
+	}
+
+	/// Visits at most `budget` accounts and storage entries of the state under `root`.
+	/// Lookups of missing nodes abort the step with an error and are recorded by the database.
+	pub fn step(&mut self, db: &HashDB, factories: &Factories, root: &H256, budget: usize) -> trie::Result<()> {
+		if self.complete { return Ok(()) }
+
+		let mut budget = budget;
+		let accounts = factories.trie.readonly(db, root)?;
+		let mut iter = accounts.iter()?;
+		if let Some(ref account) = self.account {
+			iter.seek(account)?;
+		}
+
+		for item in iter {
+			let (key, value) = item?;
+			let account: BasicAccount = ::rlp::decode(&value);
+			let account_db = factories.accountdb.readonly(db, H256::from_slice(&key));
+
+			if account.storage_root != KECCAK_NULL_RLP {
+				let storage = factories.trie.readonly(&*account_db, &account.storage_root)?;
+				let mut storage_iter = storage.iter()?;
+				match self.storage {
+					Some((ref visiting, ref last)) if *visiting == key => storage_iter.seek(last)?,
+					_ => {},
+				}
+
+				for entry in storage_iter {
+					let (storage_key, _) = entry?;
+					budget = budget.saturating_sub(1);
+					if budget == 0 {
+						self.storage = Some((key, storage_key));
+						return Ok(());
+					}
+				}
+			}
+
+			if account.code_hash != KECCAK_EMPTY && account_db.get(&account.code_hash).is_none() {
+				return Ok(());
+			}
+
+			self.account = Some(key);
+			self.storage = None;
+			budget = budget.saturating_sub(1);
+			if budget == 0 {
+				return Ok(());
+			}
+		}
+
+		self.complete = true;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use hash::keccak;
+	use util::{DBValue, HashDB};
+	use util::journaldb::{self, Algorithm, JournalDB};
+	use util::kvdb::in_memory;
+	use util::trie::TrieHasher;
+	use super::{MissingNodes, RecordingJournalDB};
+
+	fn recording_db(missing: &MissingNodes) -> RecordingJournalDB {
+		let backing = Arc::new(in_memory(0));
+		RecordingJournalDB::new(journaldb::new(backing, Algorithm::OverlayRecent, None), missing.clone())
+	}
+
+	#[test]
+	fn records_missing_nodes() {
+		let missing = MissingNodes::default();
+		let mut db = recording_db(&missing);
+		let present = db.insert(b"present");
+
+		assert!(db.get(&present).is_some());
+		assert!(missing.is_empty());
+
+		let absent = keccak(b"absent");
+		assert!(db.get(&absent).is_none());
+		assert_eq!(missing.request(10), vec![absent]);
+
+		// clones share the record.
+		let clone = db.boxed_clone();
+		assert!(!clone.contains(&keccak(b"other")));
+		assert_eq!(missing.request(10).len(), 2);
+	}
+
+	#[test]
+	fn imports_nodes_under_mangled_keys() {
+		let missing = MissingNodes::default();
+		let mut db = recording_db(&missing);
+
+		let node = b"storage node".to_vec();
+		let mut mangled = keccak(&node);
+		mangled[31] ^= 0xff;
+		assert!(db.get(&mangled).is_none());
+
+		let mut batch = db.backing().transaction();
+		let imported = missing.import(&mut db, &mut batch, TrieHasher::Keccak, vec![node.clone(), b"unrequested".to_vec()]).unwrap();
+		db.backing().write(batch).unwrap();
+
+		assert_eq!(imported, 1);
+		assert!(missing.is_empty());
+		assert_eq!(db.get(&mangled), Some(DBValue::from_slice(&node)));
+	}
+
+	#[test]
+	fn gives_up_on_unavailable_nodes() {
+		let missing = MissingNodes::default();
+		let db = recording_db(&missing);
+		assert!(db.get(&keccak(b"unavailable")).is_none());
+
+		for _ in 0..super::MAX_NODE_REQUESTS {
+			assert_eq!(missing.request(10).len(), 1);
+		}
+		assert!(missing.request(10).is_empty());
+		assert!(missing.is_empty());
+	}
+}
@@ -36,6 +36,16 @@ pub trait ChainNotify : Send + Sync {
 		// does nothing by default
 	}
 
+	/// fires when import is halted by a reorganization deeper than the configured limit.
+	/// The last enacted hash is the head of the rejected branch.
+	fn reorg_halted(
+		&self,
+		_enacted: Vec<H256>,
+		_retracted: Vec<H256>,
+	) {
+		// does nothing by default
+	}
+
 	/// fires when chain achieves active mode
 	fn start(&self) {
 		// does nothing by default
@@ -49,6 +59,12 @@ pub trait ChainNotify : Send + Sync {
 	/// fires when chain broadcasts a message
 	fn broadcast(&self, _data: Vec<u8>) {}
 
+	/// fires when a private transaction has to be broadcast
+	fn broadcast_private_transaction(&self, _packet: Vec<u8>) {}
+
+	/// fires when a private transaction signature has to be broadcast
+	fn broadcast_signed_private_transaction(&self, _packet: Vec<u8>) {}
+
 	/// fires when new transactions are received from a peer
 	fn transactions_received(&self,
 		_hashes: Vec<H256>,
@@ -18,7 +18,8 @@ use std::collections::{HashSet, HashMap, BTreeMap, VecDeque};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
-use std::time::{Instant};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use time::precise_time_ns;
 use itertools::Itertools;
 
@@ -27,7 +28,7 @@ use hash::keccak;
 use timer::PerfTimer;
 use util::UtilError;
 use util::Bytes;
-use util::{journaldb, DBValue, TrieFactory, Trie};
+use util::{journaldb, DBValue, TrieFactory, TrieHasher, Trie};
 use util::Address;
 use util::trie::TrieSpec;
 use util::kvdb::*;
@@ -37,14 +38,17 @@ use bigint::prelude::U256;
 use bigint::hash::{H256, H2048};
 use basic_types::Seal;
 use block::*;
-use blockchain::{BlockChain, BlockProvider,  TreeRoute, ImportRoute};
+use blockchain::{BlockChain, BlockProvider,  TreeRoute, ImportRoute, BlockCorruption};
 use blockchain::extras::TransactionAddress;
 use client::ancient_import::AncientVerifier;
+use client::bad_blocks::BadBlocks;
+use client::gas_prices::{GasPriceTracker, GAS_PRICE_HISTORY, GAS_PRICE_SAMPLE_BLOCKS};
+use client::beam::{Backfill, MissingNodes, RecordingJournalDB};
 use client::Error as ClientError;
 use client::{
 	BlockId, TransactionId, UncleId, TraceId, ClientConfig, BlockChainClient,
 	MiningBlockChainClient, TraceFilter, CallAnalytics, BlockImportError, Mode,
-	ChainNotify, PruningInfo, ProvingBlockChainClient,
+	ChainNotify, PruningInfo, ProvingBlockChainClient, BadBlock, BlockGasPrices,
 };
 use encoded;
 use engines::{Engine, EpochTransition};
@@ -52,12 +56,14 @@ use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, Er
 use vm::{EnvInfo, LastHashes};
 use evm::{Factory as EvmFactory, Schedule};
 use executive::{Executive, Executed, TransactOptions, contract_address};
+use executed::GasEstimate;
 use factory::Factories;
 use futures::{future, Future};
 use header::{BlockNumber, Header};
 use io::*;
-use log_entry::LocalizedLogEntry;
-use miner::{Miner, MinerService, TransactionImportResult};
+use log_entry::{LocalizedLogEntry, LogEntry};
+use lru_cache::LruCache;
+use miner::{Miner, MinerService, TransactionImportResult, UnclePolicy, LocalTransactionStatus};
 use native_contracts::Registry;
 use parking_lot::{Mutex, RwLock, MutexGuard};
 use rand::OsRng;
@@ -65,15 +71,17 @@ use receipt::{Receipt, LocalizedReceipt};
 use rlp::UntrustedRlp;
 use service::ClientIoMessage;
 use snapshot::{self, io as snapshot_io};
-use spec::Spec;
-use state_db::StateDB;
+use spec::{Spec, ForkFilter, check_builtins_reload};
+use state_db::{StateDB, BloomStatus};
 use state::{self, State};
+use state::backend::Recording;
 use trace;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace::FlatTransactionTraces;
 use transaction::{LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, PendingTransaction, Action};
 use types::filter::Filter;
 use types::mode::Mode as IpcMode;
+use types::state_diff::StateDiff;
 use verification;
 use verification::{PreverifiedBlock, Verifier};
 use verification::queue::BlockQueue;
@@ -88,6 +96,20 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+/// Minimal number of recent block bodies and receipts kept, enough for uncle verification and snapshots.
+const MIN_HISTORY_RETENTION: u64 = 10_000;
+/// Maximal number of blocks whose bodies and receipts are pruned on a single tick.
+const HISTORY_PRUNE_BATCH: u64 = 1_000;
+/// Key of the data persisted by the consensus engine in the node info column.
+const ENGINE_DATA_KEY: &'static [u8] = b"engine_data";
+/// Key of the blocks left in the verification queue on shutdown in the node info column.
+const QUEUED_BLOCKS_KEY: &'static [u8] = b"queued_blocks";
+/// Number of recently imported blocks for which the peer they came from is remembered.
+const BLOCK_ORIGINS: usize = 1024;
+/// Number of epoch signal proofs kept in memory for serving light clients.
+const EPOCH_PROOFS: usize = 128;
+/// Number of accounts and storage entries the state backfill visits per step.
+const BACKFILL_STEP: usize = 1024;
 
 /// Report on the status of a client.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -141,6 +163,22 @@ impl SleepState {
 	}
 }
 
+/// Reason a verified block couldn't be enacted on top of the chain.
+enum CloseBlockError {
+	/// The block is invalid.
+	Invalid,
+	/// Enactment looked up state nodes not fetched by beam sync yet.
+	MissingState,
+}
+
+/// Blocks held back because importing them would reorganize the chain
+/// deeper than `ClientConfig::max_reorg_depth`.
+struct HaltedReorg {
+	blocks: Vec<PreverifiedBlock>,
+	enacted: Vec<H256>,
+	retracted: Vec<H256>,
+}
+
 /// Blockchain database client backed by a persistent database. Owns and manages a blockchain and a block queue.
 /// Call `import_block()` to import a block asynchronously; `flush_queue()` flushes the queue.
 pub struct Client {
@@ -152,10 +190,19 @@ pub struct Client {
 	config: ClientConfig,
 	pruning: journaldb::Algorithm,
 	db: RwLock<Arc<KeyValueDB>>,
+	cold_db: Option<Arc<KeyValueDB>>,
 	state_db: Mutex<StateDB>,
 	block_queue: BlockQueue,
 	report: RwLock<ClientReport>,
 	import_lock: Mutex<()>,
+	halted_reorg: Mutex<Option<HaltedReorg>>,
+	missing_state: Option<MissingNodes>,
+	beam_pending: Mutex<Vec<PreverifiedBlock>>,
+	backfill: Mutex<Backfill>,
+	bad_blocks: RwLock<BadBlocks>,
+	gas_prices: RwLock<GasPriceTracker>,
+	block_origins: Mutex<LruCache<H256, String>>,
+	epoch_proofs: Mutex<LruCache<H256, Option<Vec<u8>>>>,
 	verifier: Box<Verifier>,
 	miner: Arc<Miner>,
 	sleep_state: Mutex<SleepState>,
@@ -188,15 +235,32 @@ impl Client {
 			false => TrieSpec::Secure,
 		};
 
-		let trie_factory = TrieFactory::new(trie_spec);
+		let trie_factory = TrieFactory::new(trie_spec).with_hasher(spec.params().trie_hasher);
 		let factories = Factories {
 			vm: EvmFactory::new(config.vm_type.clone(), config.jump_table_size),
 			trie: trie_factory,
 			accountdb: Default::default(),
 		};
 
-		let journal_db = journaldb::new(db.clone(), config.pruning, ::db::COL_STATE);
-		let mut state_db = StateDB::new(journal_db, config.state_cache_size);
+		let cold_db = match config.cold_state {
+			Some(ref cold) if config.pruning == journaldb::Algorithm::Archive => {
+				let mut cold_config = DatabaseConfig::default();
+				cold_config.compaction = config.db_compaction.compaction_profile(&cold.path);
+				cold_config.wal = config.db_wal;
+				let path = cold.path.to_str().expect("DB path could not be converted to string.");
+				let cold_db: Arc<KeyValueDB> = Arc::new(Database::open(&cold_config, path).map_err(ClientError::Database)?);
+				Some(cold_db)
+			},
+			Some(_) => {
+				warn!(target: "client", "Cold state storage is only supported by archive nodes; ignoring.");
+				None
+			},
+			None => None,
+		};
+
+		let missing_state = if config.beam_sync { Some(MissingNodes::default()) } else { None };
+		let journal_db = Self::state_journal_db(&config, db.clone(), &cold_db, &missing_state);
+		let mut state_db = Self::new_state_db(&config, journal_db, config.state_cache_size, spec.params().trie_hasher);
 		if state_db.journal_db().is_empty() {
 			// Sets the correct state root.
 			state_db = spec.ensure_db_good(state_db, &factories)?;
@@ -222,11 +286,15 @@ impl Client {
 
 		if !chain.block_header(&chain.best_block_hash()).map_or(true, |h| state_db.journal_db().contains(h.state_root())) {
 			warn!("State root not found for block #{} ({})", chain.best_block_number(), chain.best_block_hash().hex());
+		} else if let Some(header) = chain.block_header(&chain.best_block_hash()) {
+			Self::verify_account_bloom(&state_db, header.state_root().clone());
 		}
 
 		let engine = spec.engine.clone();
 
 		let block_queue = BlockQueue::new(config.queue.clone(), engine.clone(), message_channel.clone(), config.verifier_type.verifying_seal());
+		let bad_blocks = BadBlocks::load(&*db);
+		block_queue.mark_as_bad(bad_blocks.invalidated());
 
 		let awake = match config.mode { Mode::Dark(..) | Mode::Off => false, _ => true };
 
@@ -238,14 +306,23 @@ impl Client {
 			chain: RwLock::new(chain),
 			tracedb: tracedb,
 			engine: engine,
+			bad_blocks: RwLock::new(bad_blocks),
+			gas_prices: RwLock::new(GasPriceTracker::new(GAS_PRICE_HISTORY, GAS_PRICE_SAMPLE_BLOCKS)),
+			block_origins: Mutex::new(LruCache::new(BLOCK_ORIGINS)),
+			epoch_proofs: Mutex::new(LruCache::new(EPOCH_PROOFS)),
 			pruning: config.pruning.clone(),
 			verifier: verification::new(config.verifier_type.clone()),
 			config: config,
 			db: RwLock::new(db),
+			cold_db: cold_db,
 			state_db: Mutex::new(state_db),
 			block_queue: block_queue,
 			report: RwLock::new(Default::default()),
 			import_lock: Mutex::new(()),
+			halted_reorg: Mutex::new(None),
+			missing_state: missing_state,
+			beam_pending: Mutex::new(Vec::new()),
+			backfill: Mutex::new(Backfill::default()),
 			miner: miner,
 			io_channel: Mutex::new(message_channel),
 			notify: RwLock::new(Vec::new()),
@@ -307,9 +384,52 @@ impl Client {
 
 		// ensure buffered changes are flushed.
 		client.db.read().flush().map_err(ClientError::Database)?;
+		client.update_gas_prices();
+		client.restore_queued_blocks();
 		Ok(client)
 	}
 
+	/// Re-queues the blocks which were waiting for import at last shutdown.
+	fn restore_queued_blocks(&self) {
+		let data = match self.db.read().get(::db::COL_NODE_INFO, QUEUED_BLOCKS_KEY) {
+			Ok(Some(data)) => data,
+			Ok(None) => return,
+			Err(e) => {
+				warn!(target: "client", "Failed to read queued blocks: {}", e);
+				return;
+			},
+		};
+
+		let blocks: Vec<Bytes> = UntrustedRlp::new(&data).as_list().unwrap_or_else(|e| {
+			warn!(target: "client", "Ignoring corrupted queued blocks: {}", e);
+			Vec::new()
+		});
+		let count = blocks.len();
+		let queued = blocks.into_iter().map(|block| self.import_block(block)).filter(Result::is_ok).count();
+		info!(target: "client", "Resumed import of {} blocks queued before shutdown ({} already known or invalid)", queued, count - queued);
+
+		let mut batch = DBTransaction::new();
+		batch.delete(::db::COL_NODE_INFO, QUEUED_BLOCKS_KEY);
+		if let Err(e) = self.db.read().write(batch) {
+			warn!(target: "client", "Failed to clear queued blocks: {}", e);
+		}
+	}
+
+	/// Persists the blocks waiting in the verification queue, so the import can resume after restart.
+	fn persist_queued_blocks(&self) {
+		let blocks = self.block_queue.take_blocks();
+		if blocks.is_empty() {
+			return;
+		}
+
+		let mut batch = DBTransaction::new();
+		batch.put(::db::COL_NODE_INFO, QUEUED_BLOCKS_KEY, &::rlp::encode_list(&blocks));
+		match self.db.read().write(batch) {
+			Ok(()) => info!(target: "client", "Saved {} queued blocks", blocks.len()),
+			Err(e) => warn!(target: "client", "Failed to save queued blocks: {}", e),
+		}
+	}
+
 	/// Wakes up client if it's a sleep.
 	pub fn keep_alive(&self) {
 		let should_wake = match *self.mode.lock() {
@@ -327,6 +447,16 @@ impl Client {
 		self.notify.write().push(Arc::downgrade(&target));
 	}
 
+	/// Broadcast a private transaction to the network.
+	pub fn broadcast_private_transaction(&self, packet: Bytes) {
+		self.notify(|notify| notify.broadcast_private_transaction(packet.clone()));
+	}
+
+	/// Broadcast a private transaction signature to the network.
+	pub fn broadcast_signed_private_transaction(&self, packet: Bytes) {
+		self.notify(|notify| notify.broadcast_signed_private_transaction(packet.clone()));
+	}
+
 	/// Set a closure to call when we want to restart the client
 	pub fn set_exit_handler<F>(&self, f: F) where F: Fn(bool, Option<String>) + 'static + Send {
 		*self.exit_handler.lock() = Some(Box::new(f));
@@ -374,7 +504,7 @@ impl Client {
 		self.block_header(id).map(|header| {
 			EnvInfo {
 				number: header.number(),
-				author: header.author(),
+				author: self.engine.executive_author(&header.decode()),
 				timestamp: header.timestamp(),
 				difficulty: header.difficulty(),
 				last_hashes: self.build_last_hashes(header.parent_hash()),
@@ -410,7 +540,7 @@ impl Client {
 		Arc::new(last_hashes)
 	}
 
-	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, ()> {
+	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, CloseBlockError> {
 		let engine = &*self.engine;
 		let header = &block.header;
 
@@ -419,20 +549,22 @@ impl Client {
 		let best_block_number = chain.best_block_number();
 		if self.pruning_info().earliest_state > header.number() {
 			warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
-			return Err(());
+			return Err(CloseBlockError::Invalid);
 		}
 
 		// Verify Block Family
 		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
 		if let Err(e) = verify_family_result {
 			warn!(target: "client", "Stage 3 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			self.report_bad_block(block, format!("Stage 3 block verification failed: {:?}", e));
+			return Err(CloseBlockError::Invalid);
 		};
 
 		let verify_external_result = self.verifier.verify_block_external(header, &block.bytes, engine);
 		if let Err(e) = verify_external_result {
 			warn!(target: "client", "Stage 4 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			self.report_bad_block(block, format!("Stage 4 block verification failed: {:?}", e));
+			return Err(CloseBlockError::Invalid);
 		};
 
 		// Check if Parent is in chain
@@ -443,6 +575,7 @@ impl Client {
 			let db = self.state_db.lock().boxed_clone_canon(header.parent_hash());
 
 			let is_epoch_begin = chain.epoch_transition(parent.number(), *header.parent_hash()).is_some();
+			let misses = self.missing_state.as_ref().map(|missing| missing.misses());
 			let enact_result = enact_verified(block,
 				engine,
 				self.tracedb.read().tracing_enabled(),
@@ -451,9 +584,17 @@ impl Client {
 				last_hashes,
 				self.factories.clone(),
 				is_epoch_begin,
+				self.config.parallel_transactions,
 			);
 			let mut locked_block = enact_result.map_err(|e| {
+				// only a failure which looked up missing nodes may be caused by them.
+				if misses.is_some() && misses != self.missing_state.as_ref().map(|missing| missing.misses()) {
+					debug!(target: "client", "Block #{} ({}) is waiting for state: {:?}", header.number(), header.hash(), e);
+					return CloseBlockError::MissingState;
+				}
 				warn!(target: "client", "Block import failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+				self.report_bad_block(block, format!("Block enactment failed: {:?}", e));
+				CloseBlockError::Invalid
 			})?;
 
 			if header.number() < self.engine().params().validate_receipts_transition && header.receipts_root() != locked_block.block().header().receipts_root() {
@@ -463,16 +604,31 @@ impl Client {
 			// Final Verification
 			if let Err(e) = self.verifier.verify_block_final(header, locked_block.block().header()) {
 				warn!(target: "client", "Stage 5 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-				return Err(());
+				self.report_bad_block(block, format!("Stage 5 block verification failed: {:?}", e));
+				return Err(CloseBlockError::Invalid);
 			}
 
 			Ok(locked_block)
 		} else {
 			warn!(target: "client", "Block import failed for #{} ({}): Parent not found ({}) ", header.number(), header.hash(), header.parent_hash());
-			Err(())
+			Err(CloseBlockError::Invalid)
 		}
 	}
 
+	/// Persists a block which failed verification against the chain.
+	fn report_bad_block(&self, block: &PreverifiedBlock, reason: String) {
+		let hash = block.header.hash();
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		let bad_block = BadBlock {
+			hash: hash,
+			rlp: block.bytes.clone(),
+			reason: reason,
+			origin: self.block_origins.lock().remove(&hash),
+			timestamp: timestamp,
+		};
+		self.bad_blocks.write().report(bad_block, &**self.db.read());
+	}
+
 	fn calculate_enacted_retracted(&self, import_results: &[ImportRoute]) -> (Vec<H256>, Vec<H256>) {
 		fn map_to_vec(map: Vec<(H256, bool)>) -> Vec<H256> {
 			map.into_iter().map(|(k, _v)| k).collect()
@@ -499,6 +655,33 @@ impl Client {
 		(map_to_vec(enacted), map_to_vec(retracted))
 	}
 
+	/// Returns enacted and retracted blocks if importing block with given header would make
+	/// it the new best block and retract more than `max_depth` canonical blocks.
+	fn deep_reorg(&self, header: &Header, max_depth: u64) -> Option<(Vec<H256>, Vec<H256>)> {
+		let chain = self.chain.read();
+		let best_hash = chain.best_block_hash();
+		if header.parent_hash() == &best_hash {
+			return None;
+		}
+
+		let parent_difficulty = match chain.block_details(header.parent_hash()) {
+			Some(details) => details.total_difficulty,
+			None => return None,
+		};
+		if parent_difficulty + *header.difficulty() <= chain.best_block_total_difficulty() {
+			return None;
+		}
+
+		match chain.tree_route(best_hash, *header.parent_hash()) {
+			Some(ref route) if route.index as u64 > max_depth => {
+				let mut enacted = route.blocks[route.index..].to_vec();
+				enacted.push(header.hash());
+				Some((enacted, route.blocks[..route.index].to_vec()))
+			},
+			_ => None,
+		}
+	}
+
 	/// This is triggered by a message coming from a block queue when the block is ready for insertion
 	pub fn import_verified_blocks(&self) -> usize {
 
@@ -508,41 +691,104 @@ impl Client {
 		}
 
 		let max_blocks_to_import = 4;
-		let (imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration, is_empty) = {
-			let mut imported_blocks = Vec::with_capacity(max_blocks_to_import);
+		let import_lock = self.import_lock.lock();
+		// import stays halted until the operator accepts the reorganization.
+		if self.halted_reorg.lock().is_some() {
+			return 0;
+		}
+		// as does beam sync until the state the pending blocks touch is fetched.
+		if !self.beam_pending.lock().is_empty() {
+			return 0;
+		}
+
+		let blocks = self.block_queue.drain(max_blocks_to_import);
+		if blocks.is_empty() {
+			return 0;
+		}
+		self.import_blocks(import_lock, blocks, self.config.max_reorg_depth)
+	}
+
+	/// Retry importing blocks which were waiting for state fetched by beam sync.
+	pub fn import_stalled_blocks(&self) {
+		if self.is_missing_state() {
+			return;
+		}
+		let import_lock = self.import_lock.lock();
+		let pending = ::std::mem::replace(&mut *self.beam_pending.lock(), Vec::new());
+		if !pending.is_empty() {
+			trace!(target: "client", "Retrying import of {} blocks waiting for state", pending.len());
+			self.import_blocks(import_lock, pending, self.config.max_reorg_depth);
+			self.import_verified_blocks();
+		}
+	}
+
+	fn import_blocks(&self, import_lock: MutexGuard<()>, blocks: Vec<PreverifiedBlock>, max_reorg_depth: Option<u64>) -> usize {
+		let (imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration, is_empty, halted) = {
+			let mut imported_blocks = Vec::with_capacity(blocks.len());
 			let mut invalid_blocks = HashSet::new();
-			let mut proposed_blocks = Vec::with_capacity(max_blocks_to_import);
-			let mut import_results = Vec::with_capacity(max_blocks_to_import);
+			let mut proposed_blocks = Vec::with_capacity(blocks.len());
+			let mut import_results = Vec::with_capacity(blocks.len());
+			let mut halted: Option<HaltedReorg> = None;
+			let mut stalled = Vec::new();
 
-			let _import_lock = self.import_lock.lock();
-			let blocks = self.block_queue.drain(max_blocks_to_import);
-			if blocks.is_empty() {
-				return 0;
-			}
+			let _import_lock = import_lock;
 			let _timer = PerfTimer::new("import_verified_blocks");
 			let start = precise_time_ns();
 
 			for block in blocks {
-				let header = &block.header;
-				let is_invalid = invalid_blocks.contains(header.parent_hash());
+				// descendants of a halted block wait together with it.
+				if let Some(ref mut halted) = halted {
+					halted.blocks.push(block);
+					continue;
+				}
+				// as do descendants of a block waiting for state.
+				if !stalled.is_empty() {
+					stalled.push(block);
+					continue;
+				}
+
+				let is_invalid = invalid_blocks.contains(block.header.parent_hash());
 				if is_invalid {
-					invalid_blocks.insert(header.hash());
+					invalid_blocks.insert(block.header.hash());
 					continue;
 				}
-				if let Ok(closed_block) = self.check_and_close_block(&block) {
-					if self.engine.is_proposal(&block.header) {
-						self.block_queue.mark_as_good(&[header.hash()]);
-						proposed_blocks.push(block.bytes);
-					} else {
-						imported_blocks.push(header.hash());
+				let closed_block = match self.check_and_close_block(&block) {
+					Ok(closed_block) => closed_block,
+					Err(CloseBlockError::MissingState) => {
+						stalled.push(block);
+						continue;
+					}
+					Err(CloseBlockError::Invalid) => {
+						invalid_blocks.insert(block.header.hash());
+						continue;
+					}
+				};
 
-						let route = self.commit_block(closed_block, &header, &block.bytes);
-						import_results.push(route);
+				let is_proposal = self.engine.is_proposal(&block.header);
+				let deep_reorg = match max_reorg_depth {
+					Some(max_depth) if !is_proposal => self.deep_reorg(&block.header, max_depth),
+					_ => None,
+				};
+				if let Some((enacted, retracted)) = deep_reorg {
+					halted = Some(HaltedReorg {
+						blocks: vec![block],
+						enacted: enacted,
+						retracted: retracted,
+					});
+					continue;
+				}
 
-						self.report.write().accrue_block(&block);
-					}
+				let header = &block.header;
+				if is_proposal {
+					self.block_queue.mark_as_good(&[header.hash()]);
+					proposed_blocks.push(block.bytes);
 				} else {
-					invalid_blocks.insert(header.hash());
+					imported_blocks.push(header.hash());
+
+					let route = self.commit_block(closed_block, &header, &block.bytes);
+					import_results.push(route);
+
+					self.report.write().accrue_block(&block);
 				}
 			}
 
@@ -554,13 +800,46 @@ impl Client {
 			}
 			let is_empty = self.block_queue.mark_as_good(&imported_blocks);
 			let duration_ns = precise_time_ns() - start;
-			(imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration_ns, is_empty)
+
+			// stalled blocks are retried once the state they touch is fetched.
+			if !stalled.is_empty() {
+				*self.beam_pending.lock() = stalled;
+			}
+
+			// blocks of the halted branch stay in the queue's processing set until accepted.
+			let halted = halted.map(|reorg| {
+				let alert = (reorg.enacted.clone(), reorg.retracted.clone());
+				*self.halted_reorg.lock() = Some(reorg);
+				alert
+			});
+			(imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration_ns, is_empty, halted)
 		};
 
+		if let Some((enacted, retracted)) = halted {
+			let head = enacted.last().cloned().unwrap_or_default();
+			error!(
+				target: "client",
+				"Block import halted: importing {} would retract {} canonical blocks (limit is {}). Call `parity_acceptReorg` with the block hash to switch to the new branch.",
+				head, retracted.len(), max_reorg_depth.unwrap_or_default()
+			);
+			self.notify(|notify| notify.reorg_halted(enacted.clone(), retracted.clone()));
+		}
+
+		if !imported_blocks.is_empty() {
+			self.update_gas_prices();
+		}
+
 		{
 			if !imported_blocks.is_empty() && is_empty {
 				let (enacted, retracted) = self.calculate_enacted_retracted(&import_results);
 
+				{
+					let mut epoch_proofs = self.epoch_proofs.lock();
+					for hash in &retracted {
+						epoch_proofs.remove(hash);
+					}
+				}
+
 				if is_empty {
 					self.miner.chain_new_blocks(self, &imported_blocks, &invalid_blocks, &enacted, &retracted);
 				}
@@ -820,6 +1099,44 @@ impl Client {
 		}
 	}
 
+	// regenerate the epoch signal proof of a block which has none stored, e.g. the
+	// genesis or blocks restored from a snapshot, and persist it as a pending transition.
+	fn generate_epoch_signal(&self, hash: H256) -> Option<Vec<u8>> {
+		use engines::{EpochChange, Proof};
+		use engines::epoch::PendingTransition;
+
+		let (header, block, receipts) = {
+			let chain = self.chain.read();
+			match (chain.block_header(&hash), chain.block(&hash)) {
+				(Some(header), Some(block)) => (header, block, chain.block_receipts(&hash)),
+				_ => return None,
+			}
+		};
+
+		let receipts = receipts.as_ref().map(|r| &r.receipts[..]);
+		let proof = match self.engine.signals_epoch_end(&header, Some(&block.into_inner()), receipts) {
+			EpochChange::Yes(Proof::Known(proof)) => proof,
+			EpochChange::Yes(Proof::WithState(with_state)) => {
+				match self.with_proving_caller(BlockId::Hash(hash), |call| with_state.generate_proof(call)) {
+					Ok(proof) => proof,
+					Err(e) => {
+						debug!(target: "client", "Failed to generate transition proof for block {}: {}", hash, e);
+						return None;
+					}
+				}
+			},
+			EpochChange::No | EpochChange::Unsure(_) => return None,
+		};
+
+		let mut batch = DBTransaction::new();
+		self.chain.read().insert_pending_transition(&mut batch, hash, PendingTransition { proof: proof.clone() });
+		if let Err(e) = self.db.read().write(batch) {
+			warn!(target: "client", "Failed to persist transition proof for block {}: {}", hash, e);
+		}
+
+		Some(proof)
+	}
+
 	// use a state-proving closure for the given block.
 	fn with_proving_caller<F, T>(&self, id: BlockId, with_call: F) -> T
 		where F: FnOnce(&::engines::Call) -> T
@@ -837,6 +1154,32 @@ impl Client {
 	}
 
 	// prune ancient states until below the memory limit or only the minimum amount remain.
+	/// Opens the state database, tiered if cold storage is configured.
+	fn state_journal_db(config: &ClientConfig, db: Arc<KeyValueDB>, cold_db: &Option<Arc<KeyValueDB>>, missing_state: &Option<MissingNodes>) -> Box<journaldb::JournalDB> {
+		let journal_db = match (cold_db.as_ref(), config.cold_state.as_ref()) {
+			(Some(cold_db), Some(cold)) => journaldb::new_tiered(db, cold_db.clone(), cold.after, ::db::COL_STATE),
+			_ => journaldb::new(db, config.pruning, ::db::COL_STATE),
+		};
+		match *missing_state {
+			Some(ref missing) => Box::new(RecordingJournalDB::new(journal_db, missing.clone())),
+			None => journal_db,
+		}
+	}
+
+	fn new_state_db(config: &ClientConfig, journal_db: Box<journaldb::JournalDB>, cache_size: usize, hasher: TrieHasher) -> StateDB {
+		let state_db = StateDB::new(journal_db, cache_size).with_hasher(hasher);
+		// the bloom doesn't know about accounts which haven't been fetched yet.
+		match config.beam_sync {
+			true => state_db.without_bloom(),
+			false => state_db,
+		}
+	}
+
+	// whether block import is waiting for state nodes to be fetched.
+	fn is_missing_state(&self) -> bool {
+		self.missing_state.as_ref().map_or(false, |missing| !missing.is_empty())
+	}
+
 	fn prune_ancient(&self, mut state_db: StateDB, chain: &BlockChain) -> Result<(), ClientError> {
 		let number = match state_db.journal_db().latest_era() {
 			Some(n) => n,
@@ -886,7 +1229,13 @@ impl Client {
 		trace!(target: "external_tx", "Importing queued");
 		let _timer = PerfTimer::new("import_queued_transactions");
 		self.queue_transactions.fetch_sub(transactions.len(), AtomicOrdering::SeqCst);
-		let txs: Vec<UnverifiedTransaction> = transactions.iter().filter_map(|bytes| UntrustedRlp::new(bytes).as_val().ok()).collect();
+		let txs: Vec<UnverifiedTransaction> = transactions.iter().filter_map(|bytes| match UntrustedRlp::new(bytes).as_val() {
+			Ok(tx) => Some(tx),
+			Err(e) => {
+				trace!(target: "external_tx", "Rejecting malformed transaction: {:?}", e);
+				None
+			},
+		}).collect();
 		let hashes: Vec<_> = txs.iter().map(|tx| tx.hash()).collect();
 		self.notify(|notify| {
 			notify.transactions_received(hashes.clone(), peer_id);
@@ -900,6 +1249,150 @@ impl Client {
 		self.miner.clone()
 	}
 
+	/// Checks the account bloom against the state and rebuilds it in background if it's missing or corrupted.
+	fn verify_account_bloom(state_db: &StateDB, state_root: H256) {
+		match state_db.check_bloom(&state_root) {
+			Ok(BloomStatus::Consistent) => return,
+			Ok(status) => warn!(target: "client", "Account bloom is {:?}, rebuilding in background.", status),
+			Err(e) => {
+				warn!(target: "client", "Unable to check account bloom: {}", e);
+				return;
+			},
+		}
+
+		let state_db = state_db.boxed_clone();
+		let spawned = thread::Builder::new().name("Bloom rebuild".into()).spawn(move || {
+			match state_db.rebuild_bloom(&state_root) {
+				Ok(count) => info!(target: "client", "Account bloom rebuilt with {} accounts.", count),
+				Err(e) => warn!(target: "client", "Account bloom rebuild failed: {}. Bloom stays disabled until restart.", e),
+			}
+		});
+		if let Err(e) = spawned {
+			warn!(target: "client", "Unable to start account bloom rebuild: {}", e);
+		}
+	}
+
+	/// Check the stored headers, bodies and receipts of canonical blocks `from..to`
+	/// for consistency. Blocks which haven't been downloaded yet are skipped.
+	pub fn check_integrity(&self, from: BlockNumber, to: BlockNumber) -> Vec<(BlockNumber, BlockCorruption)> {
+		let chain = self.chain.read();
+		let gap = match (chain.best_ancient_number(), chain.first_block_number()) {
+			(Some(ancient), Some(first)) => ancient + 1..first,
+			_ => 0..0,
+		};
+
+		(from..::std::cmp::min(to, chain.best_block_number() + 1))
+			.filter(|number| *number < gap.start || *number >= gap.end)
+			.filter_map(|number| chain.check_block_integrity(number).err().map(|e| (number, e)))
+			.collect()
+	}
+
+	/// Remove canonical blocks `from..to`, so that they are downloaded again from peers
+	/// after the next start.
+	pub fn forget_blocks(&self, from: BlockNumber, to: BlockNumber) -> Result<(), String> {
+		let chain = self.chain.read();
+		if chain.best_ancient_block().is_some() {
+			return Err("Old blocks are still being downloaded.".into());
+		}
+		if from == 0 || from >= to {
+			return Err(format!("Invalid range of blocks to remove: #{}..#{}", from, to));
+		}
+		if to > chain.best_block_number() {
+			return Err("Cannot remove the best block, use `parity db rewind` instead.".into());
+		}
+
+		let mut batch = DBTransaction::new();
+		chain.forget_blocks(&mut batch, from, to);
+		self.db.read().write(batch).map_err(|e| format!("Failed to remove blocks: {}", e))
+	}
+
+	/// Rebuild the account bloom from the state of the best block.
+	/// Returns the number of accounts in the bloom.
+	pub fn rebuild_account_bloom(&self) -> Result<usize, EthcoreError> {
+		let state_root = self.chain.read().best_block_header().state_root();
+		let state_db = self.state_db.lock().boxed_clone();
+		state_db.rebuild_bloom(&state_root)
+	}
+
+	/// Rewind the chain head back to given ancestor of the best block.
+	///
+	/// The state of the target block must be available.
+	/// Returns hashes of the retracted blocks, most recent first.
+	pub fn rewind_to(&self, target: BlockId) -> Result<Vec<H256>, String> {
+		let (number, hash) = {
+			let chain = self.chain.read();
+			let number = match target {
+				BlockId::Number(number) => Some(number),
+				BlockId::Hash(ref hash) => chain.block_number(hash),
+				BlockId::Earliest => Some(0),
+				BlockId::Latest | BlockId::Pending => return Ok(Vec::new()),
+			}.ok_or_else(|| format!("Unknown block: {:?}", target))?;
+			let hash = chain.block_hash(number).ok_or_else(|| format!("Unknown block: {:?}", target))?;
+			if let BlockId::Hash(ref requested) = target {
+				if *requested != hash {
+					return Err(format!("Block {} is not in the canonical chain", requested));
+				}
+			}
+			if number >= chain.best_block_number() {
+				return Ok(Vec::new());
+			}
+			(number, hash)
+		};
+		if self.state_at(BlockId::Hash(hash)).is_none() {
+			return Err(format!("State of block #{} is not available. Rewinding that far requires a longer pruning history or --pruning archive.", number));
+		}
+
+		let retracted = {
+			let _import_lock = self.import_lock.lock();
+			let chain = self.chain.read();
+			let mut retracted = Vec::new();
+			while chain.best_block_number() > number {
+				let best = chain.best_block_hash();
+				if chain.rewind().is_none() {
+					break;
+				}
+				retracted.push(best);
+			}
+			let mut batch = DBTransaction::new();
+			self.tracedb.read().rewind(&mut batch, number, &retracted);
+			self.db.read().write(batch).map_err(|e| format!("Failed to remove traces of rewound blocks: {}", e))?;
+			self.state_db.lock().sync_cache(&[], &retracted, false);
+			info!(target: "client", "Rewound {} blocks to #{} ({})", retracted.len(), number, hash);
+			retracted
+		};
+
+		self.update_gas_prices();
+		self.miner.chain_new_blocks(self, &[], &[], &[], &retracted);
+		self.notify(|notify| {
+			notify.new_blocks(vec![], vec![], vec![], retracted.clone(), vec![], vec![], 0);
+		});
+		Ok(retracted)
+	}
+
+	/// Bring gas price statistics in line with the canonical chain.
+	fn update_gas_prices(&self) {
+		let chain = self.chain.read();
+		self.gas_prices.write().sync(
+			chain.best_block_number(),
+			|number| chain.block_hash(number),
+			|_, hash| Self::load_gas_prices(&chain, &hash),
+		);
+	}
+
+	fn load_gas_prices(chain: &BlockChain, hash: &H256) -> Option<BlockGasPrices> {
+		chain.block(hash).map(|block| {
+			let gas_used = chain.block_receipts(hash).map(|receipts| {
+				let mut prior_gas_used = U256::zero();
+				receipts.receipts.into_iter().map(|receipt| {
+					let gas_used = receipt.gas_used - prior_gas_used;
+					prior_gas_used = receipt.gas_used;
+					gas_used
+				}).collect()
+			});
+			BlockGasPrices::from_block(&block, gas_used)
+		})
+	}
+
 	/// Replace io channel. Useful for testing.
 	pub fn set_io_channel(&self, io_channel: IoChannel<ClientIoMessage>) {
 		*self.io_channel.lock() = io_channel;
@@ -979,6 +1472,8 @@ impl Client {
 	pub fn tick(&self) {
 		self.check_garbage();
 		self.check_snooze();
+		self.check_history();
+		self.check_traces();
 	}
 
 	fn check_garbage(&self) {
@@ -987,6 +1482,63 @@ impl Client {
 		self.tracedb.read().collect_garbage();
 	}
 
+	/// Prunes bodies and receipts of blocks outside of the configured retention window.
+	fn check_history(&self) {
+		let retention = match self.config.history_retention {
+			Some(retention) => ::std::cmp::max(retention, MIN_HISTORY_RETENTION),
+			None => return,
+		};
+
+		let chain = self.chain.read();
+		// don't prune while there's a gap in the chain being filled.
+		if chain.best_ancient_block().is_some() {
+			return;
+		}
+
+		let until = chain.best_block_number().saturating_sub(retention);
+		let start = chain.history_start();
+		if until <= start {
+			return;
+		}
+
+		let mut batch = DBTransaction::new();
+		let new_start = chain.prune_history(&mut batch, until, HISTORY_PRUNE_BATCH);
+		match self.db.read().write(batch) {
+			Ok(()) => debug!(target: "client", "Pruned bodies and receipts of blocks before #{}", new_start),
+			Err(e) => warn!(target: "client", "Failed to prune block history: {}", e),
+		}
+	}
+
+	/// Prunes traces of blocks outside of the configured retention window.
+	fn check_traces(&self) {
+		let retention = match self.config.tracing.retention {
+			Some(retention) => ::std::cmp::max(retention, MIN_HISTORY_RETENTION),
+			None => return,
+		};
+
+		let chain = self.chain.read();
+		if chain.best_ancient_block().is_some() {
+			return;
+		}
+
+		let tracedb = self.tracedb.read();
+		if !tracedb.tracing_enabled() {
+			return;
+		}
+
+		let until = chain.best_block_number().saturating_sub(retention);
+		if until <= tracedb.traces_start() {
+			return;
+		}
+
+		let mut batch = DBTransaction::new();
+		let new_start = tracedb.prune(&mut batch, until, HISTORY_PRUNE_BATCH);
+		match self.db.read().write(batch) {
+			Ok(()) => debug!(target: "client", "Pruned traces of blocks before #{}", new_start),
+			Err(e) => warn!(target: "client", "Failed to prune traces: {}", e),
+		}
+	}
+
 	fn check_snooze(&self) {
 		let mode = self.mode.lock().clone();
 		match mode {
@@ -1052,6 +1604,11 @@ impl Client {
 			},
 		};
 
+		let throttle = &self.config.snapshot;
+		let writer = snapshot_io::ThrottledWriter::new(writer, throttle.io_budget, || {
+			throttle.pause_on_import && !self.block_queue.queue_info().is_empty()
+		});
+
 		snapshot::take_snapshot(&*self.engine, &self.chain.read(), start_hash, db.as_hashdb(), writer, p)?;
 
 		Ok(())
@@ -1125,6 +1682,7 @@ impl Client {
 			env_info: &EnvInfo,
 			engine: &E,
 			state_diff: bool,
+			deadline: Option<Instant>,
 			transaction: &SignedTransaction,
 			options: TransactOptions<T, V>,
 		) -> Result<Executed, CallError> where
@@ -1137,7 +1695,12 @@ impl Client {
 				.save_output_from_contract();
 			let original_state = if state_diff { Some(state.clone()) } else { None };
 
-			let mut ret = Executive::new(state, env_info, engine).transact_virtual(transaction, options)?;
+			let result = Executive::new(state, env_info, engine).with_deadline(deadline).transact_virtual(transaction, options);
+			// an aborted execution fails with an internal error (or a failed nested call).
+			if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+				return Err(CallError::Timeout);
+			}
+			let mut ret = result?;
 
 			if let Some(original) = original_state {
 				ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
@@ -1146,15 +1709,66 @@ impl Client {
 		}
 
 		let state_diff = analytics.state_diffing;
+		let deadline = analytics.timeout.map(|timeout| Instant::now() + timeout);
 		let engine = &*self.engine;
 
+		if let Some(step_tracing) = analytics.step_tracing {
+			let vm_tracer = trace::StepTracer::new(step_tracing);
+			return if analytics.transaction_tracing {
+				call(state, env_info, engine, state_diff, deadline, t, TransactOptions::new(trace::ExecutiveTracer::default(), vm_tracer))
+			} else {
+				call(state, env_info, engine, state_diff, deadline, t, TransactOptions::new(trace::NoopTracer, vm_tracer))
+			};
+		}
+
 		match (analytics.transaction_tracing, analytics.vm_tracing) {
-			(true, true) => call(state, env_info, engine, state_diff, t, TransactOptions::with_tracing_and_vm_tracing()),
-			(true, false) => call(state, env_info, engine, state_diff, t, TransactOptions::with_tracing()),
-			(false, true) => call(state, env_info, engine, state_diff, t, TransactOptions::with_vm_tracing()),
-			(false, false) => call(state, env_info, engine, state_diff, t, TransactOptions::with_no_tracing()),
+			(true, true) => call(state, env_info, engine, state_diff, deadline, t, TransactOptions::with_tracing_and_vm_tracing()),
+			(true, false) => call(state, env_info, engine, state_diff, deadline, t, TransactOptions::with_tracing()),
+			(false, true) => call(state, env_info, engine, state_diff, deadline, t, TransactOptions::with_vm_tracing()),
+			(false, false) => call(state, env_info, engine, state_diff, deadline, t, TransactOptions::with_no_tracing()),
 		}
 	}
+
+	/// Re-executes a stored block on top of the state database created for its parent.
+	/// Returns the state of the parent and the re-executed block.
+	fn reenact_block<F>(&self, id: BlockId, db: F) -> Result<(State<StateDB>, LockedBlock), CallError> where
+		F: FnOnce(&H256) -> StateDB,
+	{
+		let block = self.block(id).ok_or(CallError::StatePruned)?;
+		let header = block.decode_header();
+		let parent_hash = *header.parent_hash();
+		let parent = self.block_header(BlockId::Hash(parent_hash)).ok_or(CallError::StatePruned)?.decode();
+		let original = self.state_at(BlockId::Hash(parent_hash)).ok_or(CallError::StatePruned)?;
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		let transactions: Vec<_> = block.transactions().into_iter()
+			.map(|t| SignedTransaction::new(t).expect(PROOF))
+			.collect();
+		let is_epoch_begin = self.chain.read().epoch_transition(parent.number(), parent_hash).is_some();
+		let db = db(&parent_hash);
+
+		let enacted = enact(
+			&header,
+			&transactions,
+			&block.uncles(),
+			&*self.engine,
+			false,
+			db,
+			&parent,
+			self.build_last_hashes(parent_hash),
+			self.factories.clone(),
+			is_epoch_begin,
+			false,
+		).map_err(|e| match e {
+			EthcoreError::Execution(e) => CallError::Execution(e),
+			e => {
+				warn!(target: "client", "Stored block #{} ({}) failed to re-execute: {}", header.number(), header.hash(), e);
+				CallError::StateCorrupt
+			},
+		})?;
+
+		Ok((original, enacted))
+	}
 }
 
 impl snapshot::DatabaseRestore for Client {
@@ -1171,7 +1785,10 @@ impl snapshot::DatabaseRestore for Client {
 		db.restore(new_db)?;
 
 		let cache_size = state_db.cache_size();
-		*state_db = StateDB::new(journaldb::new(db.clone(), self.pruning, ::db::COL_STATE), cache_size);
+		let journal_db = Self::state_journal_db(&self.config, db.clone(), &self.cold_db, &self.missing_state);
+		*state_db = Self::new_state_db(&self.config, journal_db, cache_size, self.engine.params().trie_hasher);
+		*self.backfill.lock() = Backfill::default();
+		*self.gas_prices.write() = GasPriceTracker::new(GAS_PRICE_HISTORY, GAS_PRICE_SAMPLE_BLOCKS);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
 		Ok(())
@@ -1206,8 +1823,13 @@ impl BlockChainClient for Client {
 		Ok(results)
 	}
 
-	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError> {
+	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId, timeout: Option<Duration>) -> Result<U256, CallError> {
+		self.estimate_gas_breakdown(t, block, timeout).map(|estimate| estimate.gas)
+	}
+
+	fn estimate_gas_breakdown(&self, t: &SignedTransaction, block: BlockId, timeout: Option<Duration>) -> Result<GasEstimate, CallError> {
 		const UPPER_CEILING: u64 = 1_000_000_000_000u64;
+		let deadline = timeout.map(|timeout| Instant::now() + timeout);
 		let (mut upper, env_info)  = {
 			let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
 			let initial_upper = env_info.gas_limit;
@@ -1218,34 +1840,77 @@ impl BlockChainClient for Client {
 		// that's just a copy of the state.
 		let original_state = self.state_at(block).ok_or(CallError::StatePruned)?;
 		let sender = t.sender();
-		let options = || TransactOptions::with_tracing();
 
-		let cond = |gas| {
+		// Executes the transaction with given gas, returning its receipt along with the output, logs
+		// and state changes it made, or `None` if it failed.
+		let execute = |gas: U256| -> Result<Option<(Executed, (Bytes, Vec<LogEntry>, StateDiff))>, CallError> {
 			let mut tx = t.as_unsigned().clone();
 			tx.gas = gas;
 			let tx = tx.fake_sign(sender);
 
 			let mut state = original_state.clone();
-			Ok(Executive::new(&mut state, &env_info, &*self.engine)
-				.transact_virtual(&tx, options())
-				.map(|r| r.exception.is_none())
-				.unwrap_or(false))
+			let result = Executive::new(&mut state, &env_info, &*self.engine)
+				.with_deadline(deadline)
+				.transact_virtual(&tx, TransactOptions::with_no_tracing());
+			if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+				return Err(CallError::Timeout);
+			}
+			let executed = match result {
+				Ok(ref executed) if executed.exception.is_some() => return Ok(None),
+				Ok(executed) => executed,
+				Err(_) => return Ok(None),
+			};
+			let mut diff = state.diff_from(original_state.clone()).map_err(ExecutionError::from)?;
+			// the sender is topped up depending on the gas given, see `transact_virtual`.
+			diff.raw.remove(&sender);
+			let effects = (executed.output.clone(), executed.logs.clone(), diff);
+			Ok(Some((executed, effects)))
 		};
 
-		if !cond(upper)? {
-			// impossible at block gas limit - try `UPPER_CEILING` instead.
-			// TODO: consider raising limit by powers of two.
-			upper = UPPER_CEILING.into();
-			if !cond(upper)? {
-				trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
-				let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
-				return Err(err.into())
-			}
+		let (executed, reference) = match execute(upper)? {
+			Some(result) => result,
+			None => {
+				// impossible at block gas limit - try `UPPER_CEILING` instead.
+				upper = UPPER_CEILING.into();
+				match execute(upper)? {
+					Some(result) => result,
+					None => {
+						trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
+						let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
+						return Err(err.into())
+					},
+				}
+			},
+		};
+
+		// Refunds are only paid out at the end, so the gas consumed before refunds is needed up front.
+		// A limit only counts if the transaction has the same effects with it as with abundant gas;
+		// it may succeed with less e.g. when a call running out of gas doesn't revert the caller.
+		let intrinsic: U256 = t.gas_required(&self.engine.schedule(env_info.number)).into();
+		let consumed = executed.gas_used + executed.refunded;
+		let estimate = |gas| GasEstimate {
+			gas: gas,
+			intrinsic: intrinsic,
+			execution: consumed - intrinsic,
+			refund: executed.refunded,
+		};
+		let cond = |gas| execute(gas).map(|result| result.map_or(false, |(_, effects)| effects == reference));
+
+		if cond(consumed)? {
+			trace!(target: "estimate_gas", "estimate_gas succeeded with {}", consumed);
+			return Ok(estimate(consumed))
 		}
-		let lower = t.gas_required(&self.engine.schedule(env_info.number)).into();
-		if cond(lower)? {
-			trace!(target: "estimate_gas", "estimate_gas succeeded with {}", lower);
-			return Ok(lower)
+
+		// Calls forward at most 63/64 of the remaining gas (and value transfers come with a stipend),
+		// so nested calls may need more gas up front than they consume. Each level of nesting keeps
+		// back a 64th, grow the limit accordingly until the effects match.
+		let (mut lower, mut candidate) = (consumed, consumed);
+		loop {
+			candidate = ::std::cmp::min(candidate + candidate / 63.into() + 1.into(), upper);
+			if candidate == upper || cond(candidate)? {
+				break;
+			}
+			lower = candidate;
 		}
 
 		/// Find transition point between `lower` and `upper` where `cond` changes from `false` to `true`.
@@ -1267,9 +1932,8 @@ impl BlockChainClient for Client {
 			Ok(upper)
 		}
 
-		// binary chop to non-excepting call with gas somewhere between 21000 and block gas limit
-		trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
-		binary_chop(lower, upper, cond)
+		trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, candidate);
+		binary_chop(lower, candidate, cond).map(estimate)
 	}
 
 	fn replay(&self, id: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError> {
@@ -1296,6 +1960,29 @@ impl BlockChainClient for Client {
 		self.do_virtual_call(&env_info, &mut state, &t, analytics)
 	}
 
+	fn block_state_diff(&self, id: BlockId) -> Result<StateDiff, CallError> {
+		let (original, enacted) = self.reenact_block(id, |parent_hash| self.state_db.lock().boxed_clone_canon(parent_hash))?;
+		Ok(enacted.state().diff_from(original).map_err(ExecutionError::from)?)
+	}
+
+	fn block_witness(&self, id: BlockId) -> Result<Vec<Bytes>, CallError> {
+		let recording = Recording::new(self.state_db.lock().journal_db().boxed_clone());
+		let witness = recording.witness();
+		// a fresh `StateDB` without caches and bloom, so that every lookup hits the trie.
+		let db = StateDB::new(Box::new(recording), 0)
+			.with_hasher(self.engine.params().trie_hasher)
+			.without_bloom();
+		self.reenact_block(id, move |_| db)?;
+
+		let mut nodes: Vec<Bytes> = witness.lock().iter().map(|node| node.to_vec()).collect();
+		nodes.sort();
+		Ok(nodes)
+	}
+
+	fn bad_blocks(&self) -> Vec<BadBlock> {
+		self.bad_blocks.read().blocks()
+	}
+
 	fn mode(&self) -> IpcMode {
 		let r = self.mode.lock().clone().into();
 		trace!(target: "mode", "Asked for mode = {:?}. returning {:?}", &*self.mode.lock(), r);
@@ -1345,6 +2032,73 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn invalidate_block(&self, hash: H256) -> Result<Vec<H256>, String> {
+		let (number, canonical, rlp) = {
+			let chain = self.chain.read();
+			let number = chain.block_number(&hash).ok_or_else(|| format!("Unknown block: {}", hash))?;
+			let rlp = chain.block(&hash).map(|block| block.into_inner()).unwrap_or_default();
+			(number, chain.block_hash(number) == Some(hash), rlp)
+		};
+		if number == 0 {
+			return Err("Cannot invalidate the genesis block".into());
+		}
+
+		let retracted = if canonical {
+			self.rewind_to(BlockId::Number(number - 1))?
+		} else {
+			Vec::new()
+		};
+
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		self.bad_blocks.write().invalidate(BadBlock {
+			hash: hash,
+			rlp: rlp,
+			reason: "Invalidated manually".into(),
+			origin: None,
+			timestamp: timestamp,
+		}, &**self.db.read());
+		self.block_queue.mark_as_bad(&[hash]);
+		warn!(target: "client", "Block #{} ({}) marked as invalid", number, hash);
+		self.notify(|notify| {
+			notify.new_blocks(vec![], vec![hash], vec![], vec![], vec![], vec![], 0);
+		});
+		Ok(retracted)
+	}
+
+	fn reload_chain_spec(&self, spec: Bytes) -> Result<(), String> {
+		let (params, builtins) = Spec::load_params(&spec[..])?;
+		{
+			let _import_lock = self.import_lock.lock();
+			let best_block = self.chain.read().best_block_number();
+			self.engine.params().check_reload(&params, best_block)?;
+			check_builtins_reload(&self.engine.builtins(), &builtins, best_block)?;
+			if !self.engine.reload_params(params, builtins) {
+				return Err(format!("{} engine doesn't support reloading the chain spec.", self.engine.name()));
+			}
+			info!(target: "client", "Reloaded chain spec parameters at block #{}", best_block);
+		}
+		self.miner.update_sealing(self);
+		Ok(())
+	}
+
+	fn accept_reorg(&self, head: H256) -> Result<(), String> {
+		let import_lock = self.import_lock.lock();
+		let halted = {
+			let mut halted = self.halted_reorg.lock();
+			match *halted {
+				Some(ref reorg) if reorg.enacted.last() == Some(&head) => {},
+				Some(ref reorg) => return Err(format!("Halted reorganization leads to {}, not {}.", reorg.enacted.last().cloned().unwrap_or_default(), head)),
+				None => return Err("Block import is not halted.".into()),
+			}
+			halted.take().expect("halted reorganization checked above; qed")
+		};
+
+		warn!(target: "client", "Reorganization retracting {} blocks accepted, importing {}.", halted.retracted.len(), head);
+		self.import_blocks(import_lock, halted.blocks, None);
+		self.import_verified_blocks();
+		Ok(())
+	}
+
 	fn best_block_header(&self) -> encoded::Header {
 		self.chain.read().best_block_header()
 	}
@@ -1515,7 +2269,7 @@ impl BlockChainClient for Client {
 		};
 
 		let (_, db) = state.drop();
-		let account_db = self.factories.accountdb.readonly(db.as_hashdb(), keccak(account));
+		let account_db = self.factories.accountdb.readonly(db.as_hashdb(), self.factories.trie.hasher().hash(account));
 		let trie = match self.factories.trie.readonly(account_db.as_hashdb(), &root) {
 			Ok(trie) => trie,
 			_ => {
@@ -1579,6 +2333,65 @@ impl BlockChainClient for Client {
 			}))
 	}
 
+	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>> {
+		let chain = self.chain.read();
+		Self::block_hash(&chain, &self.miner, id).and_then(|hash| chain.block_number(&hash).and_then(|number| {
+			let transactions = match chain.block_body(&hash) {
+				Some(body) => body.view().localized_transactions(&hash, number),
+				None => return None,
+			};
+			let receipts = match chain.block_receipts(&hash) {
+				Some(receipts) => receipts.receipts,
+				None => return None,
+			};
+
+			let mut prior_gas_used = U256::zero();
+			let mut prior_no_of_logs = 0;
+			Some(transactions.into_iter().zip(receipts).map(|(tx, receipt)| {
+				let gas_used = receipt.gas_used;
+				let no_of_logs = receipt.logs.len();
+				let receipt = localized_receipt(self.engine(), tx, receipt, prior_gas_used, prior_no_of_logs);
+				prior_gas_used = gas_used;
+				prior_no_of_logs += no_of_logs;
+				receipt
+			}).collect())
+		}))
+	}
+
+	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
+		let gas_prices = self.gas_prices.read();
+		if sample_size == GAS_PRICE_SAMPLE_BLOCKS && !gas_prices.corpus().is_empty() {
+			return gas_prices.corpus().to_vec().into();
+		}
+
+		// at least `sample_size` blocks, more until any transactions are found
+		let mut corpus = Vec::new();
+		for (i, block) in gas_prices.blocks().enumerate() {
+			if i >= sample_size && !corpus.is_empty() {
+				break;
+			}
+			corpus.extend_from_slice(block.prices());
+		}
+		corpus.into()
+	}
+
+	fn gas_price_median(&self) -> Option<U256> {
+		self.gas_prices.read().median()
+	}
+
+	fn block_gas_prices(&self, id: BlockId) -> Option<BlockGasPrices> {
+		let chain = self.chain.read();
+		let hash = match Self::block_hash(&chain, &self.miner, id) {
+			Some(hash) => hash,
+			None => return None,
+		};
+		let tracked = chain.block_number(&hash)
+			.and_then(|number| self.gas_prices.read().block(number).cloned())
+			.and_then(|block| if block.hash == hash { Some(block) } else { None });
+
+		tracked.or_else(|| Self::load_gas_prices(&chain, &hash))
+	}
+
 	fn tree_route(&self, from: &H256, to: &H256) -> Option<TreeRoute> {
 		let chain = self.chain.read();
 		match chain.is_known(from) && chain.is_known(to) {
@@ -1595,8 +2408,66 @@ impl BlockChainClient for Client {
 		self.state_db.lock().journal_db().state(hash)
 	}
 
+	fn missing_state(&self, max: usize) -> Vec<H256> {
+		let missing = match self.missing_state {
+			Some(ref missing) => missing,
+			None => return Vec::new(),
+		};
+
+		// walk the latest state for more once nothing more urgent is missing.
+		if missing.is_empty() && self.beam_pending.lock().is_empty() {
+			let mut backfill = self.backfill.lock();
+			if !backfill.is_complete() {
+				let root = self.chain.read().best_block_header().state_root();
+				let state_db = self.state_db.lock().boxed_clone();
+				if let Err(e) = backfill.step(state_db.as_hashdb(), &self.factories, &root, BACKFILL_STEP) {
+					trace!(target: "client", "State backfill waiting for {}", e);
+				}
+				if backfill.is_complete() {
+					info!(target: "client", "Beam sync: the state at #{} is complete.", self.chain.read().best_block_number());
+				}
+			}
+		}
+
+		missing.request(max)
+	}
+
+	fn import_state(&self, nodes: Vec<Bytes>) -> usize {
+		let missing = match self.missing_state {
+			Some(ref missing) => missing,
+			None => return 0,
+		};
+
+		let import_lock = self.import_lock.lock();
+		let imported = {
+			let mut state_db = self.state_db.lock();
+			let mut batch = DBTransaction::new();
+			match missing.import(state_db.journal_db_mut(), &mut batch, self.engine.params().trie_hasher, nodes) {
+				Ok(imported) => {
+					self.db.read().write_buffered(batch);
+					imported
+				},
+				Err(e) => {
+					warn!(target: "client", "Failed to import state nodes: {}", e);
+					return 0;
+				}
+			}
+		};
+		self.db.read().flush().expect("DB flush failed.");
+
+		drop(import_lock);
+
+		// blocks are imported on the client IO thread, not the network thread delivering nodes.
+		if missing.is_empty() && !self.beam_pending.lock().is_empty() {
+			if let Err(e) = self.io_channel.lock().send(ClientIoMessage::StateFetched) {
+				debug!(target: "client", "Ignoring fetched state, error queueing: {}", e);
+			}
+		}
+		imported
+	}
+
 	fn block_receipts(&self, hash: &H256) -> Option<Bytes> {
-		self.chain.read().block_receipts(hash).map(|receipts| ::rlp::encode(&receipts).into_vec())
+		self.chain.read().block_receipts_rlp(hash)
 	}
 
 	fn import_block(&self, bytes: Bytes) -> Result<H256, BlockImportError> {
@@ -1618,6 +2489,12 @@ impl BlockChainClient for Client {
 		Ok(self.block_queue.import(unverified)?)
 	}
 
+	fn import_block_from(&self, bytes: Bytes, origin: String) -> Result<H256, BlockImportError> {
+		let hash = self.import_block(bytes)?;
+		self.block_origins.lock().insert(hash, origin);
+		Ok(hash)
+	}
+
 	fn import_block_with_receipts(&self, block_bytes: Bytes, receipts_bytes: Bytes) -> Result<H256, BlockImportError> {
 		{
 			// check block order
@@ -1647,6 +2524,10 @@ impl BlockChainClient for Client {
 		chain_info
 	}
 
+	fn fork_filter(&self) -> ForkFilter {
+		ForkFilter::new(self.chain.read().genesis_hash(), self.engine.fork_blocks())
+	}
+
 	fn additional_params(&self) -> BTreeMap<String, String> {
 		self.engine.additional_params().into_iter().collect()
 	}
@@ -1741,6 +2622,22 @@ impl BlockChainClient for Client {
 		self.miner.ready_transactions(number, timestamp)
 	}
 
+	fn pending_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		let number = self.chain.read().best_block_number();
+		self.miner.transaction(number, hash)
+	}
+
+	fn local_transaction_hashes(&self) -> Vec<H256> {
+		self.miner.local_transactions()
+			.into_iter()
+			.filter(|&(_, ref status)| match *status {
+				LocalTransactionStatus::Pending | LocalTransactionStatus::Future => true,
+				_ => false,
+			})
+			.map(|(hash, _)| hash)
+			.collect()
+	}
+
 	fn queue_consensus_message(&self, message: Bytes) {
 		let channel = self.io_channel.lock().clone();
 		if let Err(e) = channel.send(ClientIoMessage::NewMessage(message)) {
@@ -1754,7 +2651,14 @@ impl BlockChainClient for Client {
 
 	fn block_extra_info(&self, id: BlockId) -> Option<BTreeMap<String, String>> {
 		self.block_header(id)
-			.map(|header| self.engine.extra_info(&header.decode()))
+			.map(|header| {
+				let header = header.decode();
+				let mut info = self.engine.extra_info(&header);
+				if self.engine.is_finalized(&header) {
+					info.insert("finalized".into(), "true".into());
+				}
+				info
+			})
 	}
 
 	fn uncle_extra_info(&self, id: UncleId) -> Option<BTreeMap<String, String>> {
@@ -1764,8 +2668,12 @@ impl BlockChainClient for Client {
 
 	fn pruning_info(&self) -> PruningInfo {
 		PruningInfo {
-			earliest_chain: self.chain.read().first_block_number().unwrap_or(1),
+			earliest_chain: {
+				let chain = self.chain.read();
+				::std::cmp::max(chain.first_block_number().unwrap_or(1), chain.history_start())
+			},
 			earliest_state: self.state_db.lock().journal_db().earliest_era().unwrap_or(0),
+			earliest_trace: self.tracedb.read().traces_start(),
 		}
 	}
 
@@ -1819,7 +2727,7 @@ impl MiningBlockChainClient for Client {
 		self.engine.schedule(self.latest_env_info().number)
 	}
 
-	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
+	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes, uncles: &UnclePolicy) -> OpenBlock {
 		let engine = &*self.engine;
 		let chain = self.chain.read();
 		let h = chain.best_block_hash();
@@ -1841,13 +2749,13 @@ impl MiningBlockChainClient for Client {
 		).expect("OpenBlock::new only fails if parent state root invalid; state root of best block's header is never invalid; qed");
 
 		// Add uncles
-		chain
-			.find_uncle_headers(&h, engine.maximum_uncle_age())
-			.unwrap_or_else(Vec::new)
+		let candidates = chain
+			.find_uncle_headers(&h, uncles.max_age(engine))
+			.unwrap_or_else(Vec::new);
+		uncles.select(engine, best_header.number() + 1, candidates)
 			.into_iter()
-			.take(engine.maximum_uncle_count())
 			.foreach(|h| {
-				open_block.push_uncle(h).expect("pushing maximum_uncle_count;
+				open_block.push_uncle(h).expect("selecting at most maximum_uncle_count;
 												open_block was just created;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -1857,21 +2765,25 @@ impl MiningBlockChainClient for Client {
 		open_block
 	}
 
-	fn reopen_block(&self, block: ClosedBlock) -> OpenBlock {
+	fn reopen_block(&self, block: ClosedBlock, policy: &UnclePolicy) -> OpenBlock {
 		let engine = &*self.engine;
 		let mut block = block.reopen(engine);
-		let max_uncles = engine.maximum_uncle_count();
+		let max_uncles = policy.max_count(engine);
 		if block.uncles().len() < max_uncles {
 			let chain = self.chain.read();
 			let h = chain.best_block_hash();
+			let number = block.header().number();
 			// Add new uncles
 			let uncles = chain
-				.find_uncle_hashes(&h, engine.maximum_uncle_age())
+				.find_uncle_hashes(&h, policy.max_age(engine))
 				.unwrap_or_else(Vec::new);
 
 			for h in uncles {
 				if !block.uncles().iter().any(|header| header.hash() == h) {
 					let uncle = chain.block_header(&h).expect("find_uncle_hashes only returns hashes for existing headers; qed");
+					if !policy.accepts(engine, &uncle, number) {
+						continue;
+					}
 					block.push_uncle(uncle).expect("pushing up to maximum_uncle_count;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -1920,6 +2832,7 @@ impl MiningBlockChainClient for Client {
 			route
 		};
 		let (enacted, retracted) = self.calculate_enacted_retracted(&[route]);
+		self.update_gas_prices();
 		self.miner.chain_new_blocks(self, &[h.clone()], &[], &enacted, &retracted);
 		self.notify(|notify| {
 			notify.new_blocks(
@@ -1965,6 +2878,20 @@ impl super::traits::EngineClient for Client {
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
 		BlockChainClient::block_number(self, id)
 	}
+
+	fn engine_data(&self) -> Option<Bytes> {
+		self.db.read().get(::db::COL_NODE_INFO, ENGINE_DATA_KEY)
+			.expect("Low level database error. Some issue with disk?")
+			.map(|data| data.into_vec())
+	}
+
+	fn store_engine_data(&self, data: Bytes) {
+		let mut batch = DBTransaction::new();
+		batch.put(::db::COL_NODE_INFO, ENGINE_DATA_KEY, &data);
+		if let Err(e) = self.db.read().write(batch) {
+			warn!(target: "client", "Failed to persist engine data: {}", e);
+		}
+	}
 }
 
 impl ProvingBlockChainClient for Client {
@@ -2000,30 +2927,48 @@ impl ProvingBlockChainClient for Client {
 
 
 	fn epoch_signal(&self, hash: H256) -> Option<Vec<u8>> {
+		if let Some(proof) = self.epoch_proofs.lock().get_mut(&hash) {
+			return proof.clone();
+		}
+
+		if !self.chain.read().is_known(&hash) {
+			return None;
+		}
+
 		// pending transitions are never deleted, and do not contain
 		// finality proofs by definition.
-		self.chain.read().get_pending_transition(hash).map(|pending| pending.proof)
+		let pending = self.chain.read().get_pending_transition(hash).map(|pending| pending.proof);
+		let proof = pending.or_else(|| self.generate_epoch_signal(hash));
+		self.epoch_proofs.lock().insert(hash, proof.clone());
+		proof
 	}
 }
 
 impl Drop for Client {
 	fn drop(&mut self) {
+		self.persist_queued_blocks();
 		self.engine.stop();
 	}
 }
 
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
-fn transaction_receipt(engine: &Engine, mut tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
+fn transaction_receipt(engine: &Engine, tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
 	assert_eq!(receipts.len(), tx.transaction_index + 1, "All previous receipts are provided.");
 
-	let sender = tx.sender();
 	let receipt = receipts.pop().expect("Current receipt is provided; qed");
 	let prior_gas_used = match tx.transaction_index {
 		0 => 0.into(),
 		i => receipts.get(i - 1).expect("All previous receipts are provided; qed").gas_used,
 	};
 	let no_of_logs = receipts.into_iter().map(|receipt| receipt.logs.len()).sum::<usize>();
+	localized_receipt(engine, tx, receipt, prior_gas_used, no_of_logs)
+}
+
+/// Returns `LocalizedReceipt` given `LocalizedTransaction`, its receipt, cumulative gas used
+/// and the number of logs emitted by all preceding transactions in the block.
+fn localized_receipt(engine: &Engine, mut tx: LocalizedTransaction, receipt: Receipt, prior_gas_used: U256, no_of_logs: usize) -> LocalizedReceipt {
+	let sender = tx.sender();
 	let transaction_hash = tx.hash();
 	let block_hash = tx.block_hash;
 	let block_number = tx.block_number;
@@ -2090,6 +3035,28 @@ mod tests {
 		assert!(client.tree_route(&genesis, &new_hash).is_none());
 	}
 
+	#[test]
+	fn should_generate_and_cache_missing_epoch_signal() {
+		use client::ProvingBlockChainClient;
+		use blockchain::BlockProvider;
+		use spec::Spec;
+		use tests::helpers::generate_dummy_client_with_spec_and_accounts;
+
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_validator_safe_contract, None);
+		let genesis = client.chain.read().genesis_hash();
+
+		// the genesis signals the transition to the contract, but is never imported.
+		assert!(client.chain.read().get_pending_transition(genesis).is_none());
+
+		let proof = client.epoch_signal(genesis).unwrap();
+		assert!(!proof.is_empty());
+		assert_eq!(client.chain.read().get_pending_transition(genesis).unwrap().proof, proof);
+		assert_eq!(client.epoch_proofs.lock().get_mut(&genesis).cloned(), Some(Some(proof)));
+
+		assert_eq!(client.epoch_signal(5.into()), None);
+		assert!(client.epoch_proofs.lock().get_mut(&5.into()).is_none());
+	}
+
 	#[test]
 	fn should_return_correct_log_index() {
 		use hash::keccak;
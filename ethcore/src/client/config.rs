@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::FromStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fmt::{Display, Formatter, Error as FmtError};
 
 use mode::Mode as IpcMode;
@@ -160,8 +160,40 @@ pub struct ClientConfig {
 	pub history: u64,
 	/// Ideal memory usage for state pruning history.
 	pub history_mem: usize,
+	/// Number of recent blocks whose bodies and receipts are kept. `None` keeps the whole history.
+	pub history_retention: Option<u64>,
+	/// Execute transactions of imported blocks optimistically in parallel (experimental).
+	pub parallel_transactions: bool,
 	/// Check seal valididity on block import
 	pub check_seal: bool,
+	/// Snapshot creation configuration.
+	pub snapshot: SnapshotConfig,
+	/// Secondary database for historical state (archive nodes only).
+	pub cold_state: Option<ColdStateConfig>,
+	/// Maximum number of canonical blocks a reorganization may retract before block import
+	/// is halted until the operator accepts the new branch. `None` disables the limit.
+	pub max_reorg_depth: Option<u64>,
+	/// Experimental: import blocks before the state is complete, fetching missing state
+	/// nodes from peers on demand.
+	pub beam_sync: bool,
+}
+
+/// Tiered state storage: state older than `after` blocks is moved to a secondary database.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColdStateConfig {
+	/// Path to the secondary database.
+	pub path: PathBuf,
+	/// Number of recent blocks whose state is kept in the main database.
+	pub after: u64,
+}
+
+/// Snapshot creation throttling.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SnapshotConfig {
+	/// Maximum average rate of writing snapshot chunks, in bytes per second.
+	pub io_budget: Option<usize>,
+	/// Suspend writing snapshot chunks while there are blocks being imported.
+	pub pause_on_import: bool,
 }
 
 #[cfg(test)]
@@ -0,0 +1,378 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price statistics of recent canonical blocks.
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::collections::vec_deque;
+use std::iter::Rev;
+
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use encoded;
+use header::BlockNumber;
+
+/// Number of most recent blocks whose gas prices are tracked.
+pub const GAS_PRICE_HISTORY: usize = 1024;
+/// Number of most recent blocks forming the gas price corpus.
+pub const GAS_PRICE_SAMPLE_BLOCKS: usize = 100;
+
+/// Gas prices paid in a single block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockGasPrices {
+	/// Block number.
+	pub number: BlockNumber,
+	/// Block hash.
+	pub hash: H256,
+	/// Gas used by the block.
+	pub gas_used: U256,
+	/// Gas limit of the block.
+	pub gas_limit: U256,
+	/// Gas prices of the transactions, ascending.
+	prices: Vec<U256>,
+	/// Gas used by the transactions up to and including the corresponding entry of `prices`.
+	cumulative_gas: Vec<U256>,
+}
+
+impl BlockGasPrices {
+	/// Create gas prices of a block from `(gas price, gas used)` of its transactions.
+	pub fn new(number: BlockNumber, hash: H256, gas_used: U256, gas_limit: U256, mut transactions: Vec<(U256, U256)>) -> Self {
+		transactions.sort();
+		let mut total = U256::zero();
+		let (prices, cumulative_gas) = transactions.into_iter()
+			.map(|(price, gas)| {
+				total = total.saturating_add(gas);
+				(price, total)
+			})
+			.unzip();
+
+		BlockGasPrices {
+			number: number,
+			hash: hash,
+			gas_used: gas_used,
+			gas_limit: gas_limit,
+			prices: prices,
+			cumulative_gas: cumulative_gas,
+		}
+	}
+
+	/// Gas prices of given block.
+	/// Transactions are weighted by their gas limit unless the gas they used is given.
+	pub fn from_block(block: &encoded::Block, gas_used: Option<Vec<U256>>) -> Self {
+		let transactions = block.transaction_views();
+		let gas_used = match gas_used {
+			Some(ref gas_used) if gas_used.len() == transactions.len() => gas_used.clone(),
+			_ => transactions.iter().map(|t| t.gas()).collect(),
+		};
+
+		let header = block.header_view();
+		BlockGasPrices::new(
+			header.number(),
+			header.hash(),
+			header.gas_used(),
+			header.gas_limit(),
+			transactions.iter().map(|t| t.gas_price()).zip(gas_used).collect(),
+		)
+	}
+
+	/// Gas prices of the block's transactions, ascending.
+	pub fn prices(&self) -> &[U256] {
+		&self.prices
+	}
+
+	/// Ratio of the gas used to the gas limit of the block.
+	pub fn gas_used_ratio(&self) -> f64 {
+		if self.gas_limit.is_zero() {
+			return 0.0;
+		}
+		let ppm = self.gas_used.saturating_mul(1_000_000.into()) / self.gas_limit;
+		ppm.low_u64() as f64 / 1_000_000.0
+	}
+
+	/// Lowest gas price paid by transactions of the block which together with all cheaper ones
+	/// used at least `percentile` percent of the gas used by the block's transactions.
+	/// Zero for blocks without transactions.
+	pub fn percentile(&self, percentile: f64) -> U256 {
+		let total = match self.cumulative_gas.last() {
+			Some(total) => *total,
+			None => return U256::zero(),
+		};
+		let basis_points = (percentile.max(0.0).min(100.0) * 100.0) as u64;
+		let threshold = total.saturating_mul(basis_points.into()) / U256::from(10_000);
+
+		// first transaction reaching the threshold
+		let (mut low, mut high) = (0, self.cumulative_gas.len() - 1);
+		while low < high {
+			let mid = (low + high) / 2;
+			if self.cumulative_gas[mid] < threshold {
+				low = mid + 1;
+			} else {
+				high = mid;
+			}
+		}
+		self.prices[low]
+	}
+}
+
+/// Gas prices of the most recent canonical blocks.
+///
+/// Updated incrementally as the canonical chain changes. A sorted corpus of the gas prices
+/// paid within the last `sample_blocks` blocks is kept, so that its percentiles
+/// are available in constant time.
+#[derive(Debug)]
+pub struct GasPriceTracker {
+	history: usize,
+	sample_blocks: usize,
+	blocks: VecDeque<BlockGasPrices>,
+	corpus: Vec<U256>,
+}
+
+impl GasPriceTracker {
+	/// Create new tracker keeping `history` blocks, the last `sample_blocks` of which form the corpus.
+	pub fn new(history: usize, sample_blocks: usize) -> Self {
+		GasPriceTracker {
+			history: cmp::max(cmp::max(history, sample_blocks), 1),
+			sample_blocks: sample_blocks,
+			blocks: VecDeque::new(),
+			corpus: Vec::new(),
+		}
+	}
+
+	/// Bring the tracked blocks in line with the canonical chain ending with block number `best`.
+	///
+	/// `hash` returns the canonical hash of a block number and `load` the gas prices of a block.
+	/// Only blocks not tracked yet are loaded.
+	pub fn sync<H, L>(&mut self, best: BlockNumber, hash: H, load: L) where
+		H: Fn(BlockNumber) -> Option<H256>,
+		L: Fn(BlockNumber, H256) -> Option<BlockGasPrices>,
+	{
+		// drop retracted blocks
+		loop {
+			let retracted = match self.blocks.back() {
+				Some(block) => block.number > best || hash(block.number) != Some(block.hash),
+				None => false,
+			};
+			if !retracted {
+				break;
+			}
+			self.pop();
+		}
+
+		// the genesis has no transactions
+		let oldest = cmp::max(best.saturating_sub(self.history as u64 - 1), 1);
+		let first = match self.blocks.back().map(|block| block.number + 1) {
+			Some(next) if next >= oldest => next,
+			_ => {
+				self.clear();
+				oldest
+			},
+		};
+
+		for number in first..best + 1 {
+			match hash(number).and_then(|hash| load(number, hash)) {
+				Some(block) => self.push(block),
+				// tracked blocks have to be contiguous
+				None => self.clear(),
+			}
+		}
+	}
+
+	/// Sorted gas prices paid within the last `sample_blocks` tracked blocks.
+	pub fn corpus(&self) -> &[U256] {
+		&self.corpus
+	}
+
+	/// Median gas price paid within the last `sample_blocks` tracked blocks.
+	pub fn median(&self) -> Option<U256> {
+		self.corpus.get(self.corpus.len() / 2).cloned()
+	}
+
+	/// Gas prices of a tracked block.
+	pub fn block(&self, number: BlockNumber) -> Option<&BlockGasPrices> {
+		let first = match self.blocks.front() {
+			Some(block) => block.number,
+			None => return None,
+		};
+		if number < first {
+			return None;
+		}
+		self.blocks.get((number - first) as usize)
+	}
+
+	/// Tracked blocks, most recent first.
+	pub fn blocks(&self) -> Rev<vec_deque::Iter<BlockGasPrices>> {
+		self.blocks.iter().rev()
+	}
+
+	fn push(&mut self, block: BlockGasPrices) {
+		merge(&mut self.corpus, &block.prices);
+		self.blocks.push_back(block);
+
+		if self.blocks.len() > self.sample_blocks {
+			let leaving = self.blocks.len() - self.sample_blocks - 1;
+			remove(&mut self.corpus, &self.blocks[leaving].prices);
+		}
+		if self.blocks.len() > self.history {
+			self.blocks.pop_front();
+		}
+	}
+
+	fn pop(&mut self) {
+		if let Some(block) = self.blocks.pop_back() {
+			remove(&mut self.corpus, &block.prices);
+			if self.blocks.len() >= self.sample_blocks && self.sample_blocks > 0 {
+				let entering = self.blocks.len() - self.sample_blocks;
+				merge(&mut self.corpus, &self.blocks[entering].prices);
+			}
+		}
+	}
+
+	fn clear(&mut self) {
+		self.blocks.clear();
+		self.corpus.clear();
+	}
+}
+
+/// Merge sorted `prices` into sorted `corpus`.
+fn merge(corpus: &mut Vec<U256>, prices: &[U256]) {
+	if prices.is_empty() {
+		return;
+	}
+
+	let mut merged = Vec::with_capacity(corpus.len() + prices.len());
+	{
+		let (mut i, mut j) = (0, 0);
+		while i < corpus.len() && j < prices.len() {
+			if corpus[i] <= prices[j] {
+				merged.push(corpus[i]);
+				i += 1;
+			} else {
+				merged.push(prices[j]);
+				j += 1;
+			}
+		}
+		merged.extend_from_slice(&corpus[i..]);
+		merged.extend_from_slice(&prices[j..]);
+	}
+	*corpus = merged;
+}
+
+/// Remove single occurrence of each of sorted `prices` from sorted `corpus`.
+fn remove(corpus: &mut Vec<U256>, prices: &[U256]) {
+	if prices.is_empty() {
+		return;
+	}
+
+	let mut j = 0;
+	corpus.retain(|price| {
+		while j < prices.len() && prices[j] < *price {
+			j += 1;
+		}
+		if j < prices.len() && prices[j] == *price {
+			j += 1;
+			false
+		} else {
+			true
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use bigint::prelude::U256;
+	use bigint::hash::H256;
+	use header::BlockNumber;
+	use super::{BlockGasPrices, GasPriceTracker};
+
+	fn block(number: BlockNumber, fork: u64, prices: &[u64]) -> BlockGasPrices {
+		BlockGasPrices::new(
+			number,
+			H256::from(number * 1000 + fork),
+			U256::from(21_000 * prices.len() as u64),
+			1_000_000.into(),
+			prices.iter().map(|p| (U256::from(*p), U256::from(21_000))).collect(),
+		)
+	}
+
+	fn corpus(tracker: &GasPriceTracker) -> Vec<u64> {
+		tracker.corpus().iter().map(|p| p.low_u64()).collect()
+	}
+
+	#[test]
+	fn should_compute_gas_weighted_percentiles() {
+		let prices = BlockGasPrices::new(1, H256::default(), 100_000.into(), 200_000.into(), vec![
+			(30.into(), 10_000.into()),
+			(10.into(), 60_000.into()),
+			(20.into(), 30_000.into()),
+		]);
+
+		assert_eq!(prices.prices().to_vec(), vec![U256::from(10), U256::from(20), U256::from(30)]);
+		assert_eq!(prices.percentile(0.0), 10.into());
+		assert_eq!(prices.percentile(60.0), 10.into());
+		assert_eq!(prices.percentile(61.0), 20.into());
+		assert_eq!(prices.percentile(95.0), 30.into());
+		assert_eq!(prices.percentile(100.0), 30.into());
+		assert_eq!(prices.gas_used_ratio(), 0.5);
+		assert_eq!(block(1, 0, &[]).percentile(50.0), 0.into());
+	}
+
+	#[test]
+	fn should_keep_corpus_of_sample_blocks() {
+		let mut tracker = GasPriceTracker::new(4, 2);
+		let chain = vec![block(1, 0, &[5, 1]), block(2, 0, &[3]), block(3, 0, &[4, 2]), block(4, 0, &[])];
+
+		tracker.sync(2, |n| Some(chain[n as usize - 1].hash), |n, _| Some(chain[n as usize - 1].clone()));
+		assert_eq!(corpus(&tracker), vec![1, 3, 5]);
+		assert_eq!(tracker.median(), Some(3.into()));
+
+		tracker.sync(3, |n| Some(chain[n as usize - 1].hash), |n, _| Some(chain[n as usize - 1].clone()));
+		assert_eq!(corpus(&tracker), vec![2, 3, 4]);
+
+		tracker.sync(4, |n| Some(chain[n as usize - 1].hash), |n, _| Some(chain[n as usize - 1].clone()));
+		assert_eq!(corpus(&tracker), vec![2, 4]);
+		assert_eq!(tracker.block(1).map(|b| b.number), Some(1));
+		assert_eq!(tracker.blocks().map(|b| b.number).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+	}
+
+	#[test]
+	fn should_follow_reorganisations() {
+		let mut tracker = GasPriceTracker::new(3, 2);
+		let old = vec![block(1, 0, &[1]), block(2, 0, &[2]), block(3, 0, &[3])];
+		let new = vec![block(1, 0, &[1]), block(2, 1, &[7]), block(3, 1, &[8]), block(4, 1, &[9])];
+
+		tracker.sync(3, |n| Some(old[n as usize - 1].hash), |n, _| Some(old[n as usize - 1].clone()));
+		assert_eq!(corpus(&tracker), vec![2, 3]);
+
+		tracker.sync(4, |n| Some(new[n as usize - 1].hash), |n, _| Some(new[n as usize - 1].clone()));
+		assert_eq!(corpus(&tracker), vec![8, 9]);
+		assert_eq!(tracker.blocks().map(|b| b.hash).collect::<Vec<_>>(), vec![new[3].hash, new[2].hash, new[1].hash]);
+
+		// rewind
+		tracker.sync(2, |n| Some(new[n as usize - 1].hash), |_, _| None);
+		assert_eq!(corpus(&tracker), vec![7]);
+	}
+
+	#[test]
+	fn should_skip_blocks_too_old() {
+		let mut tracker = GasPriceTracker::new(2, 2);
+		tracker.sync(1000, |n| Some(H256::from(n * 1000)), |n, _| {
+			assert!(n >= 999);
+			Some(block(n, 0, &[n]))
+		});
+		assert_eq!(corpus(&tracker), vec![999, 1000]);
+	}
+}
@@ -17,15 +17,20 @@
 //! Blockchain database client.
 
 mod ancient_import;
+mod bad_blocks;
+mod beam;
 mod config;
 mod error;
 mod evm_test_client;
+mod gas_prices;
 mod test_client;
 mod trace;
 mod client;
 
 pub use self::client::*;
-pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType};
+pub use self::bad_blocks::BadBlock;
+pub use self::gas_prices::BlockGasPrices;
+pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType, SnapshotConfig, ColdStateConfig};
 pub use self::error::Error;
 pub use self::evm_test_client::{EvmTestClient, EvmTestError, TransactResult};
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
@@ -37,9 +42,10 @@ pub use self::traits::ProvingBlockChainClient;
 pub use types::ids::*;
 pub use types::trace_filter::Filter as TraceFilter;
 pub use types::pruning_info::PruningInfo;
-pub use types::call_analytics::CallAnalytics;
+pub use types::call_analytics::{CallAnalytics, StepTracing};
 
 pub use executive::{Executed, Executive, TransactOptions};
+pub use executed::GasEstimate;
 pub use vm::{LastHashes, EnvInfo};
 
 pub use error::{BlockImportError, TransactionImportError, TransactionImportResult};
@@ -18,8 +18,9 @@
 
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrder};
 use std::sync::Arc;
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 use std::mem;
+use std::time::Duration;
 use itertools::Itertools;
 use rustc_hex::FromHex;
 use hash::keccak;
@@ -35,7 +36,7 @@ use blockchain::TreeRoute;
 use client::{
 	BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockId,
 	TransactionId, UncleId, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError,
-	ProvingBlockChainClient,
+	ProvingBlockChainClient, BadBlock,
 };
 use db::{NUM_COLUMNS, COL_STATE};
 use header::{Header as BlockHeader, BlockNumber};
@@ -46,15 +47,17 @@ use blockchain::extras::BlockReceipts;
 use error::{ImportResult, Error as EthcoreError};
 use evm::{Factory as EvmFactory, VMType};
 use vm::Schedule;
-use miner::{Miner, MinerService, TransactionImportResult};
-use spec::Spec;
+use miner::{Miner, MinerService, TransactionImportResult, UnclePolicy, LocalTransactionStatus};
+use spec::{Spec, ForkFilter};
 use types::basic_account::BasicAccount;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::state_diff::StateDiff;
 
 use verification::queue::QueueInfo;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
 use executive::Executed;
+use executed::GasEstimate;
 use error::CallError;
 use trace::LocalizedTrace;
 use state_db::StateDB;
@@ -106,6 +109,8 @@ pub struct TestBlockChainClient {
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
+	/// Data persisted by the engine.
+	pub engine_data: RwLock<Option<Bytes>>,
 }
 
 /// Used for generating test client blocks.
@@ -172,6 +177,7 @@ impl TestBlockChainClient {
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
+			engine_data: RwLock::new(None),
 		};
 
 		// insert genesis hash.
@@ -366,7 +372,7 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		Schedule::new_post_eip150(24576, true, true, true)
 	}
 
-	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
+	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes, _uncles: &UnclePolicy) -> OpenBlock {
 		let engine = &*self.spec.engine;
 		let genesis_header = self.spec.genesis_header();
 		let mut db_result = get_temp_state_db();
@@ -390,7 +396,7 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		open_block
 	}
 
-	fn reopen_block(&self, block: ClosedBlock) -> OpenBlock {
+	fn reopen_block(&self, block: ClosedBlock, _uncles: &UnclePolicy) -> OpenBlock {
 		block.reopen(&*self.spec.engine)
 	}
 
@@ -418,14 +424,35 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(res)
 	}
 
-	fn estimate_gas(&self, _t: &SignedTransaction, _block: BlockId) -> Result<U256, CallError> {
+	fn estimate_gas(&self, _t: &SignedTransaction, _block: BlockId, _timeout: Option<Duration>) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
 
+	fn estimate_gas_breakdown(&self, _t: &SignedTransaction, _block: BlockId, _timeout: Option<Duration>) -> Result<GasEstimate, CallError> {
+		Ok(GasEstimate {
+			gas: 21000.into(),
+			intrinsic: 21000.into(),
+			execution: 0.into(),
+			refund: 0.into(),
+		})
+	}
+
 	fn replay(&self, _id: TransactionId, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn block_state_diff(&self, _id: BlockId) -> Result<StateDiff, CallError> {
+		Err(CallError::StatePruned)
+	}
+
+	fn block_witness(&self, _id: BlockId) -> Result<Vec<Bytes>, CallError> {
+		Err(CallError::StatePruned)
+	}
+
+	fn bad_blocks(&self) -> Vec<BadBlock> {
+		Vec::new()
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -441,8 +468,16 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
-	fn storage_root(&self, _address: &Address, _id: BlockId) -> Option<H256> {
-		None
+	fn storage_root(&self, address: &Address, id: BlockId) -> Option<H256> {
+		match id {
+			BlockId::Latest | BlockId::Pending => Some(::triehash::sec_trie_root(
+				self.storage.read().iter()
+					.filter(|&(&(ref a, _), value)| a == address && !value.is_zero())
+					.map(|(&(_, ref key), value)| (key.to_vec(), encode(&U256::from(value.clone())).into_vec()))
+					.collect()
+			)),
+			_ => None,
+		}
 	}
 
 	fn latest_nonce(&self, address: &Address) -> U256 {
@@ -481,12 +516,31 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
-	fn list_accounts(&self, _id: BlockId, _after: Option<&Address>, _count: u64) -> Option<Vec<Address>> {
-		None
+	fn list_accounts(&self, id: BlockId, after: Option<&Address>, count: u64) -> Option<Vec<Address>> {
+		match id {
+			BlockId::Latest | BlockId::Pending => {
+				let accounts: BTreeSet<Address> = self.balances.read().keys().cloned()
+					.chain(self.nonces.read().keys().cloned())
+					.chain(self.code.read().keys().cloned())
+					.chain(self.storage.read().keys().map(|&(ref a, _)| a.clone()))
+					.collect();
+				Some(accounts.into_iter().filter(|a| after.map_or(true, |after| a > after)).take(count as usize).collect())
+			},
+			_ => None,
+		}
 	}
 
-	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
-		None
+	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>> {
+		match id {
+			BlockId::Latest | BlockId::Pending => {
+				let keys: BTreeSet<H256> = self.storage.read().keys()
+					.filter(|&&(ref a, _)| a == account)
+					.map(|&(_, ref key)| key.clone())
+					.collect();
+				Some(keys.into_iter().filter(|k| after.map_or(true, |after| k > after)).take(count as usize).collect())
+			},
+			_ => None,
+		}
 	}
 	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
 		None	// Simple default.
@@ -508,6 +562,25 @@ impl BlockChainClient for TestBlockChainClient {
 		self.receipts.read().get(&id).cloned()
 	}
 
+	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>> {
+		let best_block_number = self.chain_info().best_block_number;
+		let mut receipts: Vec<_> = self.receipts.read().values()
+			.filter(|receipt| match id {
+				BlockId::Hash(ref hash) => receipt.block_hash == *hash,
+				BlockId::Number(number) => receipt.block_number == number,
+				BlockId::Earliest => receipt.block_number == 0,
+				BlockId::Latest | BlockId::Pending => receipt.block_number == best_block_number,
+			})
+			.cloned()
+			.collect();
+
+		if receipts.is_empty() {
+			return None;
+		}
+		receipts.sort_by_key(|receipt| receipt.transaction_index);
+		Some(receipts)
+	}
+
 	fn blocks_with_bloom(&self, _bloom: &H2048, _from_block: BlockId, _to_block: BlockId) -> Option<Vec<BlockNumber>> {
 		unimplemented!();
 	}
@@ -686,7 +759,7 @@ impl BlockChainClient for TestBlockChainClient {
 			unverified_queue_size: 0,
 			verifying_queue_size: 0,
 			max_queue_size: 0,
-			max_mem_use: 0,
+			max_mem_use: usize::max_value(),
 			mem_used: 0,
 		}
 	}
@@ -714,6 +787,10 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn fork_filter(&self) -> ForkFilter {
+		self.spec.fork_filter()
+	}
+
 	fn filter_traces(&self, _filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		self.traces.read().clone()
 	}
@@ -745,6 +822,21 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(info.best_block_number, info.best_block_timestamp)
 	}
 
+	fn pending_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		self.miner.transaction(self.chain_info().best_block_number, hash)
+	}
+
+	fn local_transaction_hashes(&self) -> Vec<H256> {
+		self.miner.local_transactions()
+			.into_iter()
+			.filter(|&(_, ref status)| match *status {
+				LocalTransactionStatus::Pending | LocalTransactionStatus::Future => true,
+				_ => false,
+			})
+			.map(|(hash, _)| hash)
+			.collect()
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -755,6 +847,12 @@ impl BlockChainClient for TestBlockChainClient {
 
 	fn set_spec_name(&self, _: String) { unimplemented!(); }
 
+	fn invalidate_block(&self, _hash: H256) -> Result<Vec<H256>, String> { Err("Not supported".into()) }
+
+	fn reload_chain_spec(&self, _spec: Bytes) -> Result<(), String> { Err("Not supported".into()) }
+
+	fn accept_reorg(&self, _head: H256) -> Result<(), String> { Err("Not supported".into()) }
+
 	fn disable(&self) { unimplemented!(); }
 
 	fn pruning_info(&self) -> PruningInfo {
@@ -762,6 +860,7 @@ impl BlockChainClient for TestBlockChainClient {
 		PruningInfo {
 			earliest_chain: 1,
 			earliest_state: self.history.read().as_ref().map(|x| best_num - x).unwrap_or(0),
+			earliest_trace: 0,
 		}
 	}
 
@@ -833,4 +932,12 @@ impl super::traits::EngineClient for TestBlockChainClient {
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
 		BlockChainClient::block_number(self, id)
 	}
+
+	fn engine_data(&self) -> Option<Bytes> {
+		self.engine_data.read().clone()
+	}
+
+	fn store_engine_data(&self, data: Bytes) {
+		*self.engine_data.write() = Some(data);
+	}
 }
@@ -15,9 +15,11 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
+use std::time::Duration;
 use itertools::Itertools;
 
 use block::{OpenBlock, SealedBlock, ClosedBlock};
+use client::{BadBlock, BlockGasPrices};
 use blockchain::TreeRoute;
 use encoded;
 use vm::LastHashes;
@@ -25,11 +27,14 @@ use error::{ImportResult, CallError, Error as EthcoreError};
 use error::{TransactionImportResult, BlockImportError};
 use evm::{Factory as EvmFactory, Schedule};
 use executive::Executed;
+use executed::GasEstimate;
 use filter::Filter;
 use header::{BlockNumber};
 use ipc::IpcConfig;
 use log_entry::LocalizedLogEntry;
+use miner::UnclePolicy;
 use receipt::LocalizedReceipt;
+use spec::ForkFilter;
 use trace::LocalizedTrace;
 use transaction::{LocalizedTransaction, PendingTransaction, SignedTransaction};
 use verification::queue::QueueInfo as BlockQueueInfo;
@@ -47,6 +52,7 @@ use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::state_diff::StateDiff;
 
 #[ipc(client_ident="RemoteClient")]
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
@@ -147,6 +153,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get transaction receipt with given hash.
 	fn transaction_receipt(&self, id: TransactionId) -> Option<LocalizedReceipt>;
 
+	/// Get receipts of all transactions in given block.
+	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>>;
+
 	/// Get a tree route between `from` and `to`.
 	/// See `BlockChain::tree_route`.
 	fn tree_route(&self, from: &H256, to: &H256) -> Option<TreeRoute>;
@@ -157,12 +166,25 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get latest state node
 	fn state_data(&self, hash: &H256) -> Option<Bytes>;
 
+	/// Get keys of at most `max` state nodes to fetch from peers. Always empty unless beam syncing.
+	fn missing_state(&self, _max: usize) -> Vec<H256> { Vec::new() }
+
+	/// Import state nodes fetched from peers. Returns the number of nodes which were missing.
+	/// Blocks waiting for the state are retried asynchronously.
+	fn import_state(&self, _nodes: Vec<Bytes>) -> usize { 0 }
+
 	/// Get raw block receipts data by block header hash.
 	fn block_receipts(&self, hash: &H256) -> Option<Bytes>;
 
 	/// Import a block into the blockchain.
 	fn import_block(&self, bytes: Bytes) -> Result<H256, BlockImportError>;
 
+	/// Import a block received from given peer into the blockchain.
+	/// The origin is recorded in case the block turns out to be bad.
+	fn import_block_from(&self, bytes: Bytes, _origin: String) -> Result<H256, BlockImportError> {
+		self.import_block(bytes)
+	}
+
 	/// Import a block with transaction receipts. Does no sealing and transaction validation.
 	fn import_block_with_receipts(&self, block_bytes: Bytes, receipts_bytes: Bytes) -> Result<H256, BlockImportError>;
 
@@ -175,6 +197,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get blockchain information.
 	fn chain_info(&self) -> BlockChainInfo;
 
+	/// Get the filter of fork identifiers compatible with this chain.
+	fn fork_filter(&self) -> ForkFilter;
+
 	/// Get the registrar address, if it exists.
 	fn additional_params(&self) -> BTreeMap<String, String>;
 
@@ -195,11 +220,25 @@ pub trait BlockChainClient : Sync + Send {
 	fn call_many(&self, txs: &[(SignedTransaction, CallAnalytics)], block: BlockId) -> Result<Vec<Executed>, CallError>;
 
 	/// Estimates how much gas will be necessary for a call.
-	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError>;
+	/// Fails with `CallError::Timeout` if the estimation runs longer than `timeout`.
+	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId, timeout: Option<Duration>) -> Result<U256, CallError>;
+
+	/// Estimates how much gas will be necessary for a call, along with what the gas is spent on.
+	fn estimate_gas_breakdown(&self, t: &SignedTransaction, block: BlockId, timeout: Option<Duration>) -> Result<GasEstimate, CallError>;
 
 	/// Replays a given transaction for inspection.
 	fn replay(&self, t: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
+	/// Re-executes a given block and returns the aggregate state diff of its transactions and rewards.
+	fn block_state_diff(&self, id: BlockId) -> Result<StateDiff, CallError>;
+
+	/// Re-executes a given block and returns all state values (trie nodes and code) it accessed, sorted.
+	/// Along with the parent state root, these are enough to re-execute the block without the state.
+	fn block_witness(&self, id: BlockId) -> Result<Vec<Bytes>, CallError>;
+
+	/// Returns most recently rejected blocks, newest first.
+	fn bad_blocks(&self) -> Vec<BadBlock>;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -224,6 +263,12 @@ pub trait BlockChainClient : Sync + Send {
 	/// List all transactions that are allowed into the next block.
 	fn ready_transactions(&self) -> Vec<PendingTransaction>;
 
+	/// Find a pending transaction by hash.
+	fn pending_transaction(&self, hash: &H256) -> Option<PendingTransaction>;
+
+	/// Hashes of transactions of local origin which are still in the queue.
+	fn local_transaction_hashes(&self) -> Vec<H256>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -245,6 +290,20 @@ pub trait BlockChainClient : Sync + Send {
 		corpus.into()
 	}
 
+	/// Median gas price of transactions included in recent blocks.
+	fn gas_price_median(&self) -> Option<U256> {
+		self.gas_price_corpus(100).median().cloned()
+	}
+
+	/// Gas prices paid in given block.
+	fn block_gas_prices(&self, id: BlockId) -> Option<BlockGasPrices> {
+		self.block(id).map(|block| {
+			let gas_used = self.localized_block_receipts(id)
+				.map(|receipts| receipts.into_iter().map(|receipt| receipt.gas_used).collect());
+			BlockGasPrices::from_block(&block, gas_used)
+		})
+	}
+
 	/// Get the preferred chain ID to sign on
 	fn signing_chain_id(&self) -> Option<u64>;
 
@@ -260,6 +319,21 @@ pub trait BlockChainClient : Sync + Send {
 	/// Set the chain via a spec name.
 	fn set_spec_name(&self, spec_name: String);
 
+	/// Mark given block as invalid, rewinding the chain if the block is canonical.
+	///
+	/// Descendants of the block are rejected on import and sync is restarted.
+	/// Returns hashes of the retracted blocks.
+	fn invalidate_block(&self, hash: H256) -> Result<Vec<H256>, String>;
+
+	/// Replace chain parameters and builtins with the ones from given JSON chain spec.
+	/// Only changes affecting future blocks are accepted; genesis, accounts and
+	/// engine-specific parameters are ignored.
+	fn reload_chain_spec(&self, spec: Bytes) -> Result<(), String>;
+
+	/// Resume block import halted by a reorganization deeper than the configured limit,
+	/// accepting the branch leading to given block.
+	fn accept_reorg(&self, head: H256) -> Result<(), String>;
+
 	/// Disable the client from importing blocks. This cannot be undone in this session and indicates
 	/// that a subsystem has reason to believe this executable incapable of syncing the chain.
 	fn disable(&self);
@@ -293,15 +367,16 @@ impl IpcConfig for BlockChainClient { }
 
 /// Extended client interface used for mining
 pub trait MiningBlockChainClient: BlockChainClient {
-	/// Returns OpenBlock prepared for closing.
+	/// Returns OpenBlock prepared for closing, including uncles allowed by the policy.
 	fn prepare_open_block(&self,
 		author: Address,
 		gas_range_target: (U256, U256),
-		extra_data: Bytes
+		extra_data: Bytes,
+		uncles: &UnclePolicy,
 	) -> OpenBlock;
 
-	/// Reopens an OpenBlock and updates uncles.
-	fn reopen_block(&self, block: ClosedBlock) -> OpenBlock;
+	/// Reopens an OpenBlock and updates uncles according to the policy.
+	fn reopen_block(&self, block: ClosedBlock, uncles: &UnclePolicy) -> OpenBlock;
 
 	/// Returns EvmFactory.
 	fn vm_factory(&self) -> &EvmFactory;
@@ -342,6 +417,12 @@ pub trait EngineClient: Sync + Send {
 
 	/// Get a block number by ID.
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber>;
+
+	/// Get data previously persisted by the engine.
+	fn engine_data(&self) -> Option<Bytes>;
+
+	/// Persist engine data (e.g. consensus votes) across restarts, replacing data stored before.
+	fn store_engine_data(&self, data: Bytes);
 }
 
 /// Extended client interface for providing proofs of the state.
@@ -39,10 +39,12 @@ pub const COL_TRACE: Option<u32> = Some(4);
 pub const COL_ACCOUNT_BLOOM: Option<u32> = Some(5);
 /// Column for general information from the local node which can persist.
 pub const COL_NODE_INFO: Option<u32> = Some(6);
-/// Column for the light client chain.
+/// Column for the light client chain and the CHTs served by full nodes.
 pub const COL_LIGHT_CHAIN: Option<u32> = Some(7);
+/// Column for block receipts, snappy-compressed per block.
+pub const COL_RECEIPTS: Option<u32> = Some(8);
 /// Number of columns in DB
-pub const NUM_COLUMNS: Option<u32> = Some(8);
+pub const NUM_COLUMNS: Option<u32> = Some(9);
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
@@ -33,14 +33,18 @@ use header::{Header, BlockNumber};
 use spec::CommonParams;
 use transaction::UnverifiedTransaction;
 
+use super::reloadable::Reloadable;
 use super::signer::EngineSigner;
 use super::validator_set::{ValidatorSet, SimpleList, new_validator_set};
 
 use self::finality::RollingFinality;
+use self::reports::{ReportKind, ReportLimiter, StepHeaders};
 
 use ethkey::{verify_address, Signature};
+use futures::Future;
 use io::{IoContext, IoHandler, TimerToken, IoService};
 use itertools::{self, Itertools};
+use native_contracts::ValidatorReport;
 use rlp::{UntrustedRlp, encode};
 use bigint::prelude::{U256, U128};
 use bigint::hash::{H256, H520};
@@ -50,6 +54,12 @@ use unexpected::{Mismatch, OutOfBounds};
 use util::*;
 
 mod finality;
+mod reports;
+
+/// Minimal distance in blocks between two benign misbehaviour reports of one validator.
+const BENIGN_REPORT_INTERVAL: BlockNumber = 100;
+/// Number of recent steps in which validators sealing multiple blocks per step are detected.
+const EQUIVOCATION_WINDOW: usize = 128;
 
 /// `AuthorityRound` params.
 pub struct AuthorityRoundParams {
@@ -65,6 +75,8 @@ pub struct AuthorityRoundParams {
 	pub validate_step_transition: u64,
 	/// Immediate transitions.
 	pub immediate_transitions: bool,
+	/// Contract receiving misbehaviour reports instead of the validator set.
+	pub reporting_contract: Option<Address>,
 }
 
 impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
@@ -76,6 +88,7 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 			validate_score_transition: p.validate_score_transition.map_or(0, Into::into),
 			validate_step_transition: p.validate_step_transition.map_or(0, Into::into),
 			immediate_transitions: p.immediate_transitions.unwrap_or(false),
+			reporting_contract: p.reporting_contract.map(Into::into),
 		}
 	}
 }
@@ -207,8 +220,8 @@ impl EpochManager {
 
 /// Engine using `AuthorityRound` proof-of-authority BFT consensus.
 pub struct AuthorityRound {
-	params: CommonParams,
-	builtins: BTreeMap<Address, Builtin>,
+	params: Reloadable<CommonParams>,
+	builtins: Reloadable<BTreeMap<Address, Builtin>>,
 	transition_service: IoService<()>,
 	step: Arc<Step>,
 	can_propose: AtomicBool,
@@ -219,6 +232,9 @@ pub struct AuthorityRound {
 	validate_step_transition: u64,
 	epoch_manager: Mutex<EpochManager>,
 	immediate_transitions: bool,
+	reporting_contract: Option<ValidatorReport>,
+	report_limiter: Mutex<ReportLimiter>,
+	step_headers: Mutex<StepHeaders>,
 }
 
 // header-chain validator.
@@ -268,7 +284,6 @@ impl super::EpochVerifier for EpochVerifier {
 
 // Report misbehavior
 #[derive(Debug)]
-#[allow(dead_code)]
 enum Report {
 	// Malicious behavior
 	Malicious(Address, BlockNumber, Bytes),
@@ -351,8 +366,8 @@ impl AuthorityRound {
 		let initial_step = our_params.start_step.unwrap_or_else(|| (unix_now().as_secs() / our_params.step_duration.as_secs())) as usize;
 		let engine = Arc::new(
 			AuthorityRound {
-				params: params,
-				builtins: builtins,
+				params: Reloadable::new(params),
+				builtins: Reloadable::new(builtins),
 				transition_service: IoService::<()>::start()?,
 				step: Arc::new(Step {
 					inner: AtomicUsize::new(initial_step),
@@ -367,6 +382,9 @@ impl AuthorityRound {
 				validate_step_transition: our_params.validate_step_transition,
 				epoch_manager: Mutex::new(EpochManager::blank()),
 				immediate_transitions: our_params.immediate_transitions,
+				reporting_contract: our_params.reporting_contract.map(ValidatorReport::new),
+				report_limiter: Mutex::new(ReportLimiter::new(BENIGN_REPORT_INTERVAL, 1)),
+				step_headers: Mutex::new(StepHeaders::new(EQUIVOCATION_WINDOW)),
 			});
 
 		// Do not initialize timeouts for tests.
@@ -376,6 +394,59 @@ impl AuthorityRound {
 		}
 		Ok(engine)
 	}
+
+	/// Report misbehaviour to the reporting contract or, if there is none, to the validator set.
+	/// Reports of the same validator are rate limited.
+	fn report(&self, report: Report, set_number: BlockNumber) {
+		let (validator, kind, block) = match report {
+			Report::Benign(address, block_number) => (address, ReportKind::Benign, block_number),
+			Report::Malicious(address, block_number, _) => (address, ReportKind::Malicious, block_number),
+		};
+		if !self.report_limiter.lock().note(&validator, kind, block) {
+			trace!(target: "engine", "Skipping repeated report of {} at block {}", validator, block);
+			return;
+		}
+
+		let contract = match self.reporting_contract {
+			Some(ref contract) => contract,
+			None => {
+				match report {
+					Report::Benign(address, block_number) =>
+						self.validators.report_benign(&address, set_number, block_number),
+					Report::Malicious(address, block_number, proof) =>
+						self.validators.report_malicious(&address, set_number, block_number, proof),
+				}
+				return;
+			},
+		};
+
+		let client = match self.client.read().as_ref().and_then(|weak| weak.upgrade()) {
+			Some(client) => client,
+			None => {
+				warn!(target: "engine", "Unable to report validator {}: missing client ref.", validator);
+				return;
+			},
+		};
+		let transact = |address: Address, data: Bytes| -> Result<Bytes, String> {
+			match client.as_full_client() {
+				Some(c) => c.transact_contract(address, data)
+					.map(|_| Default::default())
+					.map_err(|e| format!("Transaction import error: {}", e)),
+				None => Err("No full client!".into()),
+			}
+		};
+
+		let result = match report {
+			Report::Benign(address, block_number) =>
+				contract.report_benign(&transact, address, block_number.into()).wait(),
+			Report::Malicious(address, block_number, proof) =>
+				contract.report_malicious(&transact, address, block_number.into(), proof).wait(),
+		};
+		match result {
+			Ok(_) => warn!(target: "engine", "Reported {:?} misbehaviour of validator {}", kind, validator),
+			Err(s) => warn!(target: "engine", "Validator {} could not be reported {}", validator, s),
+		}
+	}
 }
 
 fn unix_now() -> Duration {
@@ -417,13 +488,19 @@ impl Engine for AuthorityRound {
 	/// Two fields - consensus step and the corresponding proposer signature.
 	fn seal_fields(&self) -> usize { 2 }
 
-	fn params(&self) -> &CommonParams { &self.params }
+	fn params(&self) -> Arc<CommonParams> { self.params.get() }
 
 	fn additional_params(&self) -> HashMap<String, String> {
 		hash_map!["registrar".to_owned() => self.params().registrar.hex()]
 	}
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> { self.builtins.get() }
+
+	fn reload_params(&self, params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> bool {
+		self.params.replace(params);
+		self.builtins.replace(builtins);
+		true
+	}
 
 	fn step(&self) {
 		self.step.increment();
@@ -591,9 +668,10 @@ impl Engine for AuthorityRound {
 			|| (header.number() >= self.validate_step_transition && step <= parent_step) {
 			trace!(target: "engine", "Multiple blocks proposed for step {}.", parent_step);
 
-			self.validators.report_malicious(header.author(), header.number(), header.number(), Default::default());
+			self.report(Report::Malicious(*header.author(), header.number(), Default::default()), header.number());
 			Err(EngineError::DoubleVote(header.author().clone()))?;
 		}
+
 		// Report skipped primaries.
 		if let (true, Some(me)) = (step > parent_step + 1, self.signer.read().address()) {
 			debug!(target: "engine", "Author {} built block with step gap. current step: {}, parent step: {}",
@@ -603,7 +681,7 @@ impl Engine for AuthorityRound {
 				let skipped_primary = step_proposer(&*self.validators, &parent.hash(), s);
 				// Do not report this signer.
 				if skipped_primary != me {
-					self.validators.report_benign(&skipped_primary, header.number(), header.number());
+					self.report(Report::Benign(skipped_primary, header.number()), header.number());
 				}
 				// Stop reporting once validators start repeating.
 				if !reported.insert(skipped_primary) { break; }
@@ -649,16 +727,22 @@ impl Engine for AuthorityRound {
 
 		// always report with "self.validators" so that the report actually gets
 		// to the contract.
-		let report = |report| match report {
-			Report::Benign(address, block_number) =>
-				self.validators.report_benign(&address, set_number, block_number),
-			Report::Malicious(address, block_number, proof) =>
-				self.validators.report_malicious(&address, set_number, block_number, proof),
-		};
+		let report = |report| self.report(report, set_number);
 
 		// verify signature against fixed list, but reports should go to the
 		// contract itself.
-		verify_external(header, validators, &*self.step, report)
+		verify_external(header, validators, &*self.step, report)?;
+
+		// Report validators sealing different blocks in one step.
+		// Only headers with verified seals are noted, so the author can't be forged.
+		let step = header_step(header)?;
+		let equivocation = self.step_headers.lock().insert(step, header);
+		if let Some(other) = equivocation {
+			debug!(target: "engine", "Author {} sealed multiple blocks for step {}.", header.author(), step);
+			let proof = ::rlp::encode_list(&[other, header.clone()]).into_vec();
+			self.report(Report::Malicious(*header.author(), header.number(), proof), set_number);
+		}
+		Ok(())
 	}
 
 	fn genesis_epoch_data(&self, header: &Header, call: &Call) -> Result<Vec<u8>, String> {
@@ -706,6 +790,18 @@ impl Engine for AuthorityRound {
 			return None;
 		}
 
+		// apply signalled transitions which don't wait for finality.
+		if self.validators.has_immediate_transitions(chain_head.number()) {
+			if let Some(pending) = transition_store(chain_head.hash()) {
+				epoch_manager.note_new_epoch();
+				info!(target: "engine", "Applying validator set change signalled at block {}", chain_head.number());
+
+				// see below for why proposing is disabled on validator set change.
+				self.can_propose.store(false, AtomicOrdering::SeqCst);
+				return Some(combine_proofs(chain_head.number(), &pending.proof, &[]));
+			}
+		}
+
 		if epoch_manager.finality_checker.subchain_head() != Some(*chain_head.parent_hash()) {
 			// build new finality checker from ancestry of chain head,
 			// not including chain head itself yet.
@@ -1012,6 +1108,7 @@ mod tests {
 			validate_score_transition: 0,
 			validate_step_transition: 0,
 			immediate_transitions: true,
+			reporting_contract: None,
 		};
 
 		let aura = {
@@ -1036,5 +1133,66 @@ mod tests {
 
 		assert!(aura.verify_block_family(&header, &parent_header, None).is_ok());
 		assert_eq!(last_benign.load(AtomicOrdering::SeqCst), 1);
+
+		// Repeated reports are skipped.
+		last_benign.store(0, AtomicOrdering::SeqCst);
+		assert!(aura.verify_block_family(&header, &parent_header, None).is_ok());
+		assert_eq!(last_benign.load(AtomicOrdering::SeqCst), 0);
+	}
+
+	#[test]
+	fn reports_multiple_blocks_in_one_step() {
+		let last_malicious = Arc::new(AtomicUsize::new(0));
+		let params = AuthorityRoundParams {
+			step_duration: Default::default(),
+			start_step: Some(1),
+			validators: Box::new(TestSet::new(last_malicious.clone(), Default::default())),
+			validate_score_transition: 0,
+			validate_step_transition: 0,
+			immediate_transitions: true,
+			reporting_contract: None,
+		};
+
+		let aura = {
+			let mut c_params = ::spec::CommonParams::default();
+			c_params.gas_limit_bound_divisor = 5.into();
+			AuthorityRound::new(c_params, params, Default::default()).unwrap()
+		};
+
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account(keccak("1").into(), "1").unwrap();
+		let seal = |header: &mut Header, signed: &Header| {
+			let signature = tap.sign(addr, Some("1".into()), signed.bare_hash()).unwrap();
+			header.set_seal(vec![encode(&2usize).into_vec(), encode(&(&*signature as &[u8])).into_vec()]);
+		};
+
+		let mut parent_header: Header = Header::default();
+		parent_header.set_seal(vec![encode(&1usize).into_vec()]);
+		parent_header.set_gas_limit("222222".parse::<U256>().unwrap());
+		let mut header: Header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit("222222".parse::<U256>().unwrap());
+		header.set_author(addr);
+		let unsealed = header.clone();
+		seal(&mut header, &unsealed);
+
+		assert!(aura.verify_block_family(&header, &parent_header, None).is_ok());
+		assert!(aura.verify_block_external(&header, None).is_ok());
+		assert_eq!(last_malicious.load(AtomicOrdering::SeqCst), 0);
+
+		// Forged header with the same author and step is rejected without reporting.
+		let mut forged = header.clone();
+		forged.set_timestamp(1);
+		assert!(aura.verify_block_family(&forged, &parent_header, None).is_ok());
+		assert!(aura.verify_block_external(&forged, None).is_err());
+		assert_eq!(last_malicious.load(AtomicOrdering::SeqCst), 0);
+
+		let mut other = unsealed.clone();
+		other.set_timestamp(1);
+		let unsealed_other = other.clone();
+		seal(&mut other, &unsealed_other);
+		assert!(aura.verify_block_family(&other, &parent_header, None).is_ok());
+		assert!(aura.verify_block_external(&other, None).is_ok());
+		assert_eq!(last_malicious.load(AtomicOrdering::SeqCst), 1);
 	}
 }
@@ -0,0 +1,171 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Misbehaviour detection helpers: equivocation tracking and report rate limiting.
+
+use std::collections::{BTreeMap, HashMap};
+
+use util::Address;
+
+use header::{Header, BlockNumber};
+
+/// Kind of reported misbehaviour.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ReportKind {
+	/// Missed steps, blocks from the future.
+	Benign,
+	/// Blocks sealed twice in one step.
+	Malicious,
+}
+
+/// Keeps track of sent reports so that misbehaviour of a validator is reported at most once
+/// within `interval` blocks for each kind. Reports older than both intervals are forgotten.
+pub struct ReportLimiter {
+	benign_interval: BlockNumber,
+	malicious_interval: BlockNumber,
+	last: HashMap<(Address, ReportKind), BlockNumber>,
+}
+
+impl ReportLimiter {
+	/// Create new limiter with given minimal distances (in blocks) between reports of one validator.
+	pub fn new(benign_interval: BlockNumber, malicious_interval: BlockNumber) -> Self {
+		ReportLimiter {
+			benign_interval: benign_interval,
+			malicious_interval: malicious_interval,
+			last: HashMap::new(),
+		}
+	}
+
+	/// Returns true and notes the report if `validator` may be reported for misbehaviour at `block`.
+	pub fn note(&mut self, validator: &Address, kind: ReportKind, block: BlockNumber) -> bool {
+		let interval = match kind {
+			ReportKind::Benign => self.benign_interval,
+			ReportKind::Malicious => self.malicious_interval,
+		};
+
+		let key = (*validator, kind);
+		if let Some(last) = self.last.get(&key) {
+			if distance(block, *last) < interval {
+				return false;
+			}
+		}
+
+		let max_interval = ::std::cmp::max(self.benign_interval, self.malicious_interval);
+		self.last.retain(|_, last| distance(block, *last) < max_interval);
+		self.last.insert(key, block);
+		true
+	}
+}
+
+fn distance(a: BlockNumber, b: BlockNumber) -> BlockNumber {
+	if a > b { a - b } else { b - a }
+}
+
+/// Recently imported headers by step and author, used to detect validators sealing
+/// different blocks in the same step.
+pub struct StepHeaders {
+	window: usize,
+	headers: BTreeMap<(usize, Address), Header>,
+}
+
+impl StepHeaders {
+	/// Create new tracker remembering headers of given number of most recent steps.
+	pub fn new(window: usize) -> Self {
+		StepHeaders {
+			window: window,
+			headers: BTreeMap::new(),
+		}
+	}
+
+	/// Note a header sealed at `step`. Returns a header of the same author sealed
+	/// in the same step with different contents, if one was seen.
+	/// Headers differing only in seal are not reported, as seals can be re-created.
+	pub fn insert(&mut self, step: usize, header: &Header) -> Option<Header> {
+		let key = (step, *header.author());
+		if let Some(existing) = self.headers.get(&key) {
+			if existing.bare_hash() != header.bare_hash() {
+				return Some(existing.clone());
+			}
+			return None;
+		}
+
+		self.headers.insert(key, header.clone());
+		if step > self.window {
+			let keep = self.headers.split_off(&(step - self.window, Address::zero()));
+			self.headers = keep;
+		}
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::Address;
+	use header::Header;
+	use super::{ReportLimiter, ReportKind, StepHeaders};
+
+	#[test]
+	fn limits_reports_per_validator_and_kind() {
+		let mut limiter = ReportLimiter::new(10, 1);
+		let (a, b) = (Address::from(1), Address::from(2));
+
+		assert!(limiter.note(&a, ReportKind::Benign, 100));
+		assert!(!limiter.note(&a, ReportKind::Benign, 100));
+		assert!(!limiter.note(&a, ReportKind::Benign, 109));
+		assert!(limiter.note(&b, ReportKind::Benign, 101));
+		assert!(limiter.note(&a, ReportKind::Malicious, 101));
+		assert!(!limiter.note(&a, ReportKind::Malicious, 101));
+		assert!(limiter.note(&a, ReportKind::Malicious, 102));
+		assert!(limiter.note(&a, ReportKind::Benign, 110));
+	}
+
+	#[test]
+	fn forgets_old_reports() {
+		let mut limiter = ReportLimiter::new(10, 1);
+		for i in 0..100u64 {
+			assert!(limiter.note(&Address::from(i), ReportKind::Benign, i));
+		}
+		assert_eq!(limiter.last.len(), 10);
+		assert!(!limiter.note(&Address::from(99), ReportKind::Benign, 105));
+	}
+
+	#[test]
+	fn detects_two_headers_in_one_step() {
+		let mut steps = StepHeaders::new(10);
+		let mut header = Header::default();
+		header.set_author(Address::from(1));
+		header.set_number(1);
+
+		assert!(steps.insert(5, &header).is_none());
+		assert!(steps.insert(5, &header).is_none());
+
+		let mut resealed = header.clone();
+		resealed.set_seal(vec![vec![1]]);
+		assert!(steps.insert(5, &resealed).is_none());
+
+		let mut other = header.clone();
+		other.set_gas_limit(1.into());
+		assert_eq!(steps.insert(5, &other).map(|h| h.hash()), Some(header.hash()));
+
+		let mut another_author = other.clone();
+		another_author.set_author(Address::from(2));
+		assert!(steps.insert(5, &another_author).is_none());
+
+		// old steps are forgotten.
+		assert!(steps.insert(20, &header).is_none());
+		assert!(steps.insert(5, &other).is_none());
+	}
+}
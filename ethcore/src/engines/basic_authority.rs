@@ -36,6 +36,7 @@ use ethjson;
 use header::{Header, BlockNumber};
 use client::EngineClient;
 use semantic_version::SemanticVersion;
+use super::reloadable::Reloadable;
 use super::signer::EngineSigner;
 use super::validator_set::{ValidatorSet, SimpleList, new_validator_set};
 
@@ -83,8 +84,8 @@ fn verify_external(header: &Header, validators: &ValidatorSet) -> Result<(), Err
 
 /// Engine using `BasicAuthority`, trivial proof-of-authority consensus.
 pub struct BasicAuthority {
-	params: CommonParams,
-	builtins: BTreeMap<Address, Builtin>,
+	params: Reloadable<CommonParams>,
+	builtins: Reloadable<BTreeMap<Address, Builtin>>,
 	signer: RwLock<EngineSigner>,
 	validators: Box<ValidatorSet>,
 }
@@ -93,8 +94,8 @@ impl BasicAuthority {
 	/// Create a new instance of BasicAuthority engine
 	pub fn new(params: CommonParams, our_params: BasicAuthorityParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		BasicAuthority {
-			params: params,
-			builtins: builtins,
+			params: Reloadable::new(params),
+			builtins: Reloadable::new(builtins),
 			validators: new_validator_set(our_params.validators),
 			signer: Default::default(),
 		}
@@ -107,8 +108,14 @@ impl Engine for BasicAuthority {
 	// One field - the signature
 	fn seal_fields(&self) -> usize { 1 }
 
-	fn params(&self) -> &CommonParams { &self.params }
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+	fn params(&self) -> Arc<CommonParams> { self.params.get() }
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> { self.builtins.get() }
+
+	fn reload_params(&self, params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> bool {
+		self.params.replace(params);
+		self.builtins.replace(builtins);
+		true
+	}
 
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> BTreeMap<String, String> { map!["signature".to_owned() => "TODO".to_owned()] }
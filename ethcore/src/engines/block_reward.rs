@@ -0,0 +1,167 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block reward contract support.
+
+use futures::{self, Future};
+use native_contracts::BlockReward as Contract;
+
+use bigint::prelude::U256;
+use util::{Address, Bytes};
+
+use error::Error;
+use trace::RewardType;
+use super::EngineError;
+
+/// Type of the benefactor of a block reward.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RewardKind {
+	/// Reward attributed to the block author.
+	Author = 0,
+	/// Reward attributed to the author of an included uncle.
+	Uncle = 1,
+}
+
+impl From<RewardKind> for RewardType {
+	fn from(kind: RewardKind) -> Self {
+		match kind {
+			RewardKind::Author => RewardType::Block,
+			RewardKind::Uncle => RewardType::Uncle,
+		}
+	}
+}
+
+/// A contract deciding the block rewards. It is called by the system address
+/// on every block close with the list of benefactors and returns receivers and amounts.
+pub struct BlockRewardContract {
+	contract: Contract,
+}
+
+impl BlockRewardContract {
+	/// Create a new block reward contract client at given address.
+	pub fn new(address: Address) -> BlockRewardContract {
+		BlockRewardContract {
+			contract: Contract::new(address),
+		}
+	}
+
+	/// Calls the contract with given benefactors and returns the rewards to apply.
+	/// Receivers which are benefactors get the kind of their first occurrence, others are treated as authors.
+	///
+	/// `caller` should execute the call as the system address, with no gas accounting.
+	pub fn reward<F>(&self, benefactors: &[(Address, RewardKind)], caller: F) -> Result<Vec<(Address, RewardKind, U256)>, Error>
+		where F: FnOnce(Address, Bytes) -> Result<Bytes, String>
+	{
+		let addresses = benefactors.iter().map(|&(address, _)| address).collect();
+		let kinds = benefactors.iter().map(|&(_, kind)| kind as u16).collect();
+
+		let (receivers, rewards) = self.contract.reward(|addr, data| futures::done(caller(addr, data)), addresses, kinds)
+			.wait()
+			.map_err(EngineError::FailedSystemCall)?;
+
+		if receivers.len() != rewards.len() {
+			return Err(EngineError::FailedSystemCall(
+				format!("invalid data returned by reward contract: {} receivers and {} rewards", receivers.len(), rewards.len())
+			).into());
+		}
+
+		Ok(receivers.into_iter().zip(rewards).map(|(receiver, reward)| {
+			let kind = benefactors.iter()
+				.find(|&&(address, _)| address == receiver)
+				.map_or(RewardKind::Author, |&(_, kind)| kind);
+			(receiver, kind, reward)
+		}).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_hex::FromHex;
+	use util::Address;
+	use super::{BlockRewardContract, RewardKind};
+
+	#[test]
+	fn decodes_rewards() {
+		let contract_address = Address::from(0x42);
+		let contract = BlockRewardContract::new(contract_address);
+		let benefactors = [(Address::from(1), RewardKind::Author), (Address::from(2), RewardKind::Uncle)];
+
+		let rewards = contract.reward(&benefactors, |address, data| {
+			assert_eq!(address, contract_address);
+			assert!(!data.is_empty());
+			Ok(FromHex::from_hex("\
+				0000000000000000000000000000000000000000000000000000000000000040\
+				0000000000000000000000000000000000000000000000000000000000000080\
+				0000000000000000000000000000000000000000000000000000000000000001\
+				0000000000000000000000000000000000000000000000000000000000000001\
+				0000000000000000000000000000000000000000000000000000000000000001\
+				00000000000000000000000000000000000000000000000000000000000003e8"
+			).unwrap())
+		}).unwrap();
+
+		assert_eq!(rewards, vec![(Address::from(1), RewardKind::Author, 1000.into())]);
+	}
+
+	#[test]
+	fn classifies_uncle_rewards() {
+		let contract = BlockRewardContract::new(Address::from(0x42));
+		let benefactors = [(Address::from(1), RewardKind::Author), (Address::from(2), RewardKind::Uncle)];
+
+		let rewards = contract.reward(&benefactors, |_, _| {
+			Ok(FromHex::from_hex("\
+				0000000000000000000000000000000000000000000000000000000000000040\
+				00000000000000000000000000000000000000000000000000000000000000a0\
+				0000000000000000000000000000000000000000000000000000000000000002\
+				0000000000000000000000000000000000000000000000000000000000000002\
+				0000000000000000000000000000000000000000000000000000000000000003\
+				0000000000000000000000000000000000000000000000000000000000000002\
+				00000000000000000000000000000000000000000000000000000000000001f4\
+				0000000000000000000000000000000000000000000000000000000000000064"
+			).unwrap())
+		}).unwrap();
+
+		assert_eq!(rewards, vec![
+			(Address::from(2), RewardKind::Uncle, 500.into()),
+			(Address::from(3), RewardKind::Author, 100.into()),
+		]);
+	}
+
+	#[test]
+	fn rejects_mismatched_rewards() {
+		let contract = BlockRewardContract::new(Address::from(0x42));
+		let benefactors = [(Address::from(1), RewardKind::Author)];
+
+		let res = contract.reward(&benefactors, |_, _| {
+			Ok(FromHex::from_hex("\
+				0000000000000000000000000000000000000000000000000000000000000040\
+				0000000000000000000000000000000000000000000000000000000000000080\
+				0000000000000000000000000000000000000000000000000000000000000001\
+				0000000000000000000000000000000000000000000000000000000000000001\
+				0000000000000000000000000000000000000000000000000000000000000000"
+			).unwrap())
+		});
+
+		assert!(res.is_err());
+	}
+
+	#[test]
+	fn propagates_call_errors() {
+		let contract = BlockRewardContract::new(Address::from(0x42));
+		let benefactors = [(Address::from(1), RewardKind::Author)];
+
+		assert!(contract.reward(&benefactors, |_, _| Err("out of gas".into())).is_err());
+	}
+}
@@ -0,0 +1,555 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique proof-of-authority engine.
+//!
+//! Authorized signers seal blocks in turn. They are listed in the extra data of checkpoint blocks
+//! and voted in and out through the beneficiary and nonce of the blocks they seal.
+//! Only verification is implemented: the seal is kept in the extra data, which engines can't
+//! rewrite when sealing, so this node may follow a Clique chain but not sign blocks.
+
+mod snapshot;
+
+use std::sync::{Weak, Arc};
+use std::collections::BTreeMap;
+use std::cmp;
+use bigint::prelude::U256;
+use bigint::hash::{H64, H256, H520};
+use hash::{keccak, KECCAK_EMPTY_LIST_RLP};
+use lru_cache::LruCache;
+use parking_lot::{Mutex, RwLock};
+use rlp::{RlpStream, UntrustedRlp};
+use ethkey::{recover, public_to_address};
+use util::*;
+use unexpected::{Mismatch, OutOfBounds};
+use builtin::Builtin;
+use client::EngineClient;
+use engines::{Engine, EngineError};
+use error::{BlockError, Error};
+use ethjson;
+use header::{Header, BlockNumber};
+use ids::BlockId;
+use semantic_version::SemanticVersion;
+use spec::CommonParams;
+use self::snapshot::Snapshot;
+
+/// Length of the vanity prefix of extra data.
+const VANITY_LENGTH: usize = 32;
+/// Length of the signature suffix of extra data.
+const SIGNATURE_LENGTH: usize = 65;
+/// Difficulty of blocks sealed in turn.
+const DIFF_IN_TURN: u64 = 2;
+/// Difficulty of blocks sealed out of turn.
+const DIFF_NO_TURN: u64 = 1;
+/// Nonce of blocks voting to authorize the beneficiary.
+const NONCE_AUTH_VOTE: [u8; 8] = [0xff; 8];
+/// Number of signer set snapshots kept in memory.
+const SNAPSHOT_CACHE_SIZE: usize = 128;
+
+/// `Clique` params.
+#[derive(Debug, PartialEq)]
+pub struct CliqueParams {
+	/// Minimal number of seconds between blocks.
+	pub period: u64,
+	/// Number of blocks after which votes are reset and signers checkpointed.
+	pub epoch: u64,
+}
+
+impl From<ethjson::spec::CliqueParams> for CliqueParams {
+	fn from(p: ethjson::spec::CliqueParams) -> Self {
+		let epoch = p.epoch.map_or(0, Into::into);
+		CliqueParams {
+			period: p.period.map_or(15, Into::into),
+			epoch: if epoch == 0 { 30_000 } else { epoch },
+		}
+	}
+}
+
+/// Hash of the header with the signature stripped from the extra data, which is what signers sign.
+fn seal_hash(header: &Header) -> H256 {
+	let extra = header.extra_data();
+	let unsigned = &extra[..extra.len().saturating_sub(SIGNATURE_LENGTH)];
+
+	let mut s = RlpStream::new_list(13 + header.seal().len());
+	s.append(header.parent_hash());
+	s.append(header.uncles_hash());
+	s.append(header.author());
+	s.append(header.state_root());
+	s.append(header.transactions_root());
+	s.append(header.receipts_root());
+	s.append(header.log_bloom());
+	s.append(header.difficulty());
+	s.append(&header.number());
+	s.append(header.gas_limit());
+	s.append(header.gas_used());
+	s.append(&header.timestamp());
+	s.append(&unsigned);
+	for field in header.seal() {
+		s.append_raw(field, 1);
+	}
+	keccak(s.out())
+}
+
+/// Recover the signer of a block from the signature in its extra data.
+fn recover_signer(header: &Header) -> Result<Address, Error> {
+	let extra = header.extra_data();
+	if extra.len() < VANITY_LENGTH + SIGNATURE_LENGTH {
+		return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: Some(VANITY_LENGTH + SIGNATURE_LENGTH), max: None, found: extra.len() }).into());
+	}
+
+	let signature = H520::from_slice(&extra[extra.len() - SIGNATURE_LENGTH..]);
+	Ok(public_to_address(&recover(&signature.into(), &seal_hash(header))?))
+}
+
+/// Signers listed in the extra data of a checkpoint block.
+fn checkpoint_signers(header: &Header) -> Vec<Address> {
+	let extra = header.extra_data();
+	if extra.len() < VANITY_LENGTH + SIGNATURE_LENGTH {
+		return Vec::new();
+	}
+	extra[VANITY_LENGTH..extra.len() - SIGNATURE_LENGTH].chunks(20).map(Address::from_slice).collect()
+}
+
+/// Nonce of the block, carrying the vote of its signer.
+fn nonce(header: &Header) -> Result<H64, Error> {
+	Ok(UntrustedRlp::new(&header.seal()[1]).as_val::<H64>()?)
+}
+
+/// Engine using `Clique` proof-of-authority consensus.
+pub struct Clique {
+	params: Arc<CommonParams>,
+	builtins: Arc<BTreeMap<Address, Builtin>>,
+	period: u64,
+	epoch: u64,
+	snapshots: Mutex<LruCache<H256, Snapshot>>,
+	client: RwLock<Option<Weak<EngineClient>>>,
+}
+
+impl Clique {
+	/// Create a new instance of Clique engine.
+	pub fn new(params: CommonParams, our_params: CliqueParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		Clique {
+			params: Arc::new(params),
+			builtins: Arc::new(builtins),
+			period: our_params.period,
+			epoch: our_params.epoch,
+			snapshots: Mutex::new(LruCache::new(SNAPSHOT_CACHE_SIZE)),
+			client: RwLock::new(None),
+		}
+	}
+
+	fn is_checkpoint(&self, number: BlockNumber) -> bool {
+		number % self.epoch == 0
+	}
+
+	/// Signer set after given block. Walks back the chain to the nearest known snapshot or checkpoint.
+	fn snapshot<F>(&self, header: &Header, header_by_hash: &F) -> Result<Snapshot, Error> where
+		F: Fn(&H256) -> Option<Header>,
+	{
+		let mut pending = Vec::new();
+		let mut current = header.clone();
+		let mut snapshot;
+		loop {
+			if let Some(cached) = self.snapshots.lock().get_mut(&current.hash()) {
+				snapshot = cached.clone();
+				break;
+			}
+			if self.is_checkpoint(current.number()) {
+				snapshot = self.checkpoint_snapshot(&current, header_by_hash)?;
+				break;
+			}
+
+			let parent = header_by_hash(current.parent_hash()).ok_or_else(|| BlockError::UnknownParent(*current.parent_hash()))?;
+			pending.push(current);
+			current = parent;
+		}
+
+		for header in pending.into_iter().rev() {
+			let signer = recover_signer(&header)?;
+			let authorize = nonce(&header)? == H64::from(NONCE_AUTH_VOTE);
+			snapshot.apply(header.hash(), header.number(), signer, *header.author(), authorize, self.epoch)?;
+		}
+
+		self.snapshots.lock().insert(snapshot.hash, snapshot.clone());
+		Ok(snapshot)
+	}
+
+	/// Signer set listed in a checkpoint block, with the signers of the blocks up to it
+	/// marked as recent.
+	fn checkpoint_snapshot<F>(&self, checkpoint: &Header, header_by_hash: &F) -> Result<Snapshot, Error> where
+		F: Fn(&H256) -> Option<Header>,
+	{
+		let mut snapshot = Snapshot::new(checkpoint.hash(), checkpoint.number(), checkpoint_signers(checkpoint));
+		let mut current = checkpoint.clone();
+		for _ in 0..snapshot.signer_limit() {
+			if current.number() == 0 {
+				break;
+			}
+
+			snapshot.recents.insert(current.number(), recover_signer(&current)?);
+			current = header_by_hash(current.parent_hash()).ok_or_else(|| BlockError::UnknownParent(*current.parent_hash()))?;
+		}
+		Ok(snapshot)
+	}
+
+	fn verify_family_with<F>(&self, header: &Header, parent: &Header, header_by_hash: &F) -> Result<(), Error> where
+		F: Fn(&H256) -> Option<Header>,
+	{
+		// Do not calculate difficulty for genesis blocks.
+		if header.number() == 0 {
+			return Err(From::from(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() })));
+		}
+
+		let min_timestamp = parent.timestamp().saturating_add(self.period);
+		if header.timestamp() < min_timestamp {
+			return Err(From::from(BlockError::InvalidTimestamp(OutOfBounds { min: Some(min_timestamp), max: None, found: header.timestamp() })));
+		}
+
+		let gas_limit_divisor = self.params().gas_limit_bound_divisor;
+		let min_gas = parent.gas_limit().clone() - parent.gas_limit().clone() / gas_limit_divisor;
+		let max_gas = parent.gas_limit().clone() + parent.gas_limit().clone() / gas_limit_divisor;
+		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
+			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
+		}
+
+		let snapshot = self.snapshot(parent, header_by_hash)?;
+		if self.is_checkpoint(header.number()) && checkpoint_signers(header) != snapshot.signers.iter().cloned().collect::<Vec<_>>() {
+			trace!(target: "clique", "verify_block_family: checkpoint {} lists wrong signers", header.number());
+			return Err(From::from(BlockError::InvalidSeal));
+		}
+
+		let signer = recover_signer(header)?;
+		if !snapshot.signers.contains(&signer) {
+			return Err(EngineError::NotAuthorized(signer).into());
+		}
+		if snapshot.recently_signed(&signer, header.number()) {
+			return Err(EngineError::DoubleVote(signer).into());
+		}
+
+		let difficulty = if snapshot.is_in_turn(&signer, header.number()) { DIFF_IN_TURN } else { DIFF_NO_TURN };
+		if *header.difficulty() != difficulty.into() {
+			return Err(From::from(BlockError::InvalidDifficulty(Mismatch { expected: difficulty.into(), found: *header.difficulty() })));
+		}
+		Ok(())
+	}
+}
+
+impl Engine for Clique {
+	fn name(&self) -> &str { "Clique" }
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+	// Two fields - mix hash and nonce
+	fn seal_fields(&self) -> usize { 2 }
+
+	fn params(&self) -> Arc<CommonParams> { self.params.clone() }
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> { self.builtins.clone() }
+
+	fn maximum_uncle_count(&self) -> usize { 0 }
+
+	/// Fees go to the signer, the author field holds the vote beneficiary.
+	fn executive_author(&self, header: &Header) -> Address {
+		recover_signer(header).unwrap_or_else(|_| *header.author())
+	}
+
+	/// Additional engine-specific information for the user/developer concerning `header`.
+	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> {
+		match recover_signer(header) {
+			Ok(signer) => map!["signer".to_owned() => format!("0x{}", signer.hex())],
+			Err(_) => BTreeMap::new(),
+		}
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
+		header.set_difficulty(DIFF_NO_TURN.into());
+		header.set_gas_limit({
+			let gas_limit = parent.gas_limit().clone();
+			let bound_divisor = self.params().gas_limit_bound_divisor;
+			if gas_limit < gas_floor_target {
+				cmp::min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else {
+				cmp::max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
+			}
+		});
+	}
+
+	fn seals_internally(&self) -> Option<bool> {
+		Some(false)
+	}
+
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		if header.seal().len() != self.seal_fields() {
+			return Err(From::from(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: header.seal().len() }
+			)));
+		}
+
+		let checkpoint = self.is_checkpoint(header.number());
+		let min_extra = VANITY_LENGTH + SIGNATURE_LENGTH;
+		let extra = header.extra_data().len();
+		if extra < min_extra || (!checkpoint && extra != min_extra) {
+			let max = if checkpoint { None } else { Some(min_extra) };
+			return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: Some(min_extra), max: max, found: extra })));
+		}
+		if (extra - min_extra) % 20 != 0 {
+			trace!(target: "clique", "verify_block_basic: checkpoint {} has malformed signer list", header.number());
+			return Err(From::from(BlockError::InvalidSeal));
+		}
+
+		let mix_hash = UntrustedRlp::new(&header.seal()[0]).as_val::<H256>()?;
+		if !mix_hash.is_zero() {
+			return Err(From::from(BlockError::MismatchedH256SealElement(Mismatch { expected: H256::zero(), found: mix_hash })));
+		}
+
+		// Votes are cast through the nonce, checkpoints carry none.
+		let nonce = nonce(header)?;
+		let is_vote = nonce == H64::from(NONCE_AUTH_VOTE) || nonce.is_zero();
+		if !is_vote || (checkpoint && (!nonce.is_zero() || !header.author().is_zero())) {
+			trace!(target: "clique", "verify_block_basic: block {} has invalid vote", header.number());
+			return Err(From::from(BlockError::InvalidSeal));
+		}
+
+		if *header.uncles_hash() != KECCAK_EMPTY_LIST_RLP {
+			return Err(From::from(BlockError::InvalidUnclesHash(Mismatch { expected: KECCAK_EMPTY_LIST_RLP, found: *header.uncles_hash() })));
+		}
+
+		if *header.difficulty() != DIFF_IN_TURN.into() && *header.difficulty() != DIFF_NO_TURN.into() {
+			return Err(From::from(BlockError::DifficultyOutOfBounds(OutOfBounds { min: Some(DIFF_NO_TURN.into()), max: Some(DIFF_IN_TURN.into()), found: *header.difficulty() })));
+		}
+		Ok(())
+	}
+
+	fn verify_block_unordered(&self, _header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		let client = match self.client.read().as_ref().and_then(|weak| weak.upgrade()) {
+			Some(client) => client,
+			None => {
+				debug!(target: "clique", "Unable to verify block: missing client ref.");
+				return Err(EngineError::RequiresClient.into());
+			},
+		};
+		let chain = match client.as_full_client() {
+			Some(chain) => chain,
+			None => return Err(EngineError::RequiresClient.into()),
+		};
+
+		self.verify_family_with(header, parent, &|hash: &H256| chain.block_header(BlockId::Hash(*hash)).map(|h| h.decode()))
+	}
+
+	fn register_client(&self, client: Weak<EngineClient>) {
+		*self.client.write() = Some(client);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{BTreeMap, HashMap};
+	use bigint::hash::{H64, H256, H520};
+	use ethkey::{KeyPair, Secret, sign};
+	use hash::keccak;
+	use rlp::encode;
+	use util::*;
+	use error::{Error, BlockError};
+	use engines::{Engine, EngineError};
+	use header::Header;
+	use spec::Spec;
+	use rustc_hex::FromHex;
+	use super::{Clique, CliqueParams, NONCE_AUTH_VOTE, checkpoint_signers, seal_hash};
+
+	fn key(seed: &str) -> KeyPair {
+		KeyPair::from_secret(Secret::from(keccak(seed))).unwrap()
+	}
+
+	fn engine() -> Clique {
+		let spec = Spec::new_test_clique();
+		Clique::new((*spec.params()).clone(), CliqueParams { period: 1, epoch: 4 }, BTreeMap::new())
+	}
+
+	fn child(parent: &Header, signer: &KeyPair, difficulty: u64, beneficiary: Address, authorize: bool, signers: &[Address]) -> Header {
+		let mut header = Header::default();
+		header.set_parent_hash(parent.hash());
+		header.set_number(parent.number() + 1);
+		header.set_timestamp(parent.timestamp() + 1);
+		header.set_gas_limit(parent.gas_limit().clone());
+		header.set_difficulty(difficulty.into());
+		header.set_author(beneficiary);
+
+		let nonce = if authorize { H64::from(NONCE_AUTH_VOTE) } else { H64::zero() };
+		header.set_seal(vec![encode(&H256::zero()).into_vec(), encode(&nonce).into_vec()]);
+
+		let mut extra = vec![0u8; 32];
+		for signer in signers {
+			extra.extend_from_slice(&**signer);
+		}
+		extra.extend_from_slice(&[0u8; 65]);
+		header.set_extra_data(extra.clone());
+
+		let signature = sign(signer.secret(), &seal_hash(&header)).unwrap();
+		let len = extra.len();
+		extra[len - 65..].copy_from_slice(&*H520::from(signature));
+		header.set_extra_data(extra);
+		header
+	}
+
+	fn difficulty(signers: &[Address], signer: &KeyPair, number: u64) -> u64 {
+		let mut signers = signers.to_vec();
+		signers.sort();
+		match signers.iter().position(|s| *s == signer.address()) {
+			Some(offset) if number % signers.len() as u64 == offset as u64 => 2,
+			_ => 1,
+		}
+	}
+
+	fn verify(engine: &Clique, chain: &HashMap<H256, Header>, header: &Header) -> Result<(), Error> {
+		engine.verify_block_basic(header, None)?;
+		let parent = chain.get(header.parent_hash()).unwrap();
+		engine.verify_family_with(header, parent, &|hash: &H256| chain.get(hash).cloned())
+	}
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = Spec::new_test_clique().engine;
+		assert_eq!(engine.name(), "Clique");
+		assert_eq!(engine.seal_fields(), 2);
+		assert_eq!(engine.maximum_uncle_count(), 0);
+	}
+
+	#[test]
+	fn should_verify_turns_and_reject_recent_signers() {
+		let engine = engine();
+		let genesis = Spec::new_test_clique().genesis_header();
+		let (first, second) = (key("0"), key("1"));
+		let signers = [first.address(), second.address()];
+		let mut chain = HashMap::new();
+		chain.insert(genesis.hash(), genesis.clone());
+
+		let wrong_difficulty = child(&genesis, &first, 3 - difficulty(&signers, &first, 1), Address::zero(), false, &[]);
+		match verify(&engine, &chain, &wrong_difficulty) {
+			Err(Error::Block(BlockError::InvalidDifficulty(_))) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+
+		let block1 = child(&genesis, &first, difficulty(&signers, &first, 1), Address::zero(), false, &[]);
+		assert!(verify(&engine, &chain, &block1).is_ok());
+		chain.insert(block1.hash(), block1.clone());
+
+		let again = child(&block1, &first, difficulty(&signers, &first, 2), Address::zero(), false, &[]);
+		match verify(&engine, &chain, &again) {
+			Err(Error::Engine(EngineError::DoubleVote(_))) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+
+		let block2 = child(&block1, &second, difficulty(&signers, &second, 2), Address::zero(), false, &[]);
+		assert!(verify(&engine, &chain, &block2).is_ok());
+
+		let stranger = child(&block1, &key("2"), 1, Address::zero(), false, &[]);
+		match verify(&engine, &chain, &stranger) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+	}
+
+	#[test]
+	fn should_apply_votes_and_check_checkpoint() {
+		let engine = engine();
+		let genesis = Spec::new_test_clique().genesis_header();
+		let (first, second, third) = (key("0"), key("1"), key("2"));
+		let mut chain = HashMap::new();
+		chain.insert(genesis.hash(), genesis.clone());
+
+		let signers = [first.address(), second.address()];
+		let block1 = child(&genesis, &first, difficulty(&signers, &first, 1), third.address(), true, &[]);
+		assert!(verify(&engine, &chain, &block1).is_ok());
+		chain.insert(block1.hash(), block1.clone());
+
+		let block2 = child(&block1, &second, difficulty(&signers, &second, 2), third.address(), true, &[]);
+		assert!(verify(&engine, &chain, &block2).is_ok());
+		chain.insert(block2.hash(), block2.clone());
+
+		// the third signer is now authorized.
+		let mut signers = vec![first.address(), second.address(), third.address()];
+		signers.sort();
+		let block3 = child(&block2, &third, difficulty(&signers, &third, 3), Address::zero(), false, &[]);
+		assert!(verify(&engine, &chain, &block3).is_ok());
+		chain.insert(block3.hash(), block3.clone());
+
+		let outdated = child(&block3, &first, difficulty(&signers, &first, 4), Address::zero(), false, &signers[..2]);
+		match verify(&engine, &chain, &outdated) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+
+		let checkpoint = child(&block3, &first, difficulty(&signers, &first, 4), Address::zero(), false, &signers);
+		assert!(verify(&engine, &chain, &checkpoint).is_ok());
+	}
+
+	#[test]
+	fn should_read_signers_of_goerli_genesis() {
+		let mut genesis = Header::default();
+		genesis.set_state_root("5d6cded585e73c4e322c30c2f782a336316f17dd85a4863b9d838d2d4b8b3008".into());
+		genesis.set_difficulty(1.into());
+		genesis.set_gas_limit(0xa00000.into());
+		genesis.set_timestamp(0x5c51a607);
+		genesis.set_extra_data(format!("{}{}{}",
+			"22466c6578692069732061207468696e6722202d204166726900000000000000",
+			"e0a2bd4258d2768837baa26a28fe71dc079f84c7",
+			"00".repeat(65),
+		).from_hex().unwrap());
+		genesis.set_seal(vec![encode(&H256::zero()).into_vec(), encode(&H64::zero()).into_vec()]);
+
+		assert_eq!(genesis.hash(), "bf7e331f7f7c1dd2e05159666b3bf8bc7a8a3a9eb1d518969eab529dd9b88c1a".into());
+		assert_eq!(checkpoint_signers(&genesis), vec!["e0a2bd4258d2768837baa26a28fe71dc079f84c7".into()]);
+	}
+
+	#[test]
+	fn should_credit_signer_instead_of_beneficiary() {
+		let engine = engine();
+		let genesis = Spec::new_test_clique().genesis_header();
+		let (first, third) = (key("0"), key("2"));
+
+		let block1 = child(&genesis, &first, 1, third.address(), true, &[]);
+		assert_eq!(engine.executive_author(&block1), first.address());
+		// unsigned headers fall back to the author field.
+		assert_eq!(engine.executive_author(&genesis), *genesis.author());
+	}
+
+	#[test]
+	fn should_reject_malformed_extra_data_and_votes() {
+		let engine = engine();
+		let genesis = Spec::new_test_clique().genesis_header();
+		let first = key("0");
+
+		let signers_outside_checkpoint = child(&genesis, &first, 1, Address::zero(), false, &[first.address()]);
+		match engine.verify_block_basic(&signers_outside_checkpoint, None) {
+			Err(Error::Block(BlockError::ExtraDataOutOfBounds(_))) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+
+		let mut bad_nonce = child(&genesis, &first, 1, Address::zero(), false, &[]);
+		bad_nonce.set_seal(vec![encode(&H256::zero()).into_vec(), encode(&H64::from(1)).into_vec()]);
+		match engine.verify_block_basic(&bad_nonce, None) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+
+		let bad_difficulty = child(&genesis, &first, 3, Address::zero(), false, &[]);
+		match engine.verify_block_basic(&bad_difficulty, None) {
+			Err(Error::Block(BlockError::DifficultyOutOfBounds(_))) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+	}
+}
@@ -0,0 +1,273 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique signer set snapshots and vote tallying.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use bigint::hash::H256;
+use util::Address;
+use engines::EngineError;
+use error::Error;
+use header::BlockNumber;
+
+/// Vote cast by a signer in a block it sealed.
+#[derive(Debug, Clone, PartialEq)]
+struct Vote {
+	signer: Address,
+	address: Address,
+	authorize: bool,
+}
+
+/// Votes cast so far for a change of the given signer.
+#[derive(Debug, Clone, PartialEq)]
+struct Tally {
+	authorize: bool,
+	votes: usize,
+}
+
+/// State of the signer set after a given block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+	/// Hash of the block.
+	pub hash: H256,
+	/// Number of the block.
+	pub number: BlockNumber,
+	/// Authorized signers, ordered.
+	pub signers: BTreeSet<Address>,
+	/// Signers of the most recent blocks by block number.
+	pub recents: BTreeMap<BlockNumber, Address>,
+	votes: Vec<Vote>,
+	tally: HashMap<Address, Tally>,
+}
+
+impl Snapshot {
+	/// Create snapshot of a checkpoint block listing given signers.
+	pub fn new(hash: H256, number: BlockNumber, signers: Vec<Address>) -> Self {
+		Snapshot {
+			hash: hash,
+			number: number,
+			signers: signers.into_iter().collect(),
+			recents: BTreeMap::new(),
+			votes: Vec::new(),
+			tally: HashMap::new(),
+		}
+	}
+
+	/// Number of consecutive blocks out of which a signer may seal only one.
+	pub fn signer_limit(&self) -> u64 {
+		self.signers.len() as u64 / 2 + 1
+	}
+
+	/// Returns true if `signer` sealed a block too recently to seal block `number`.
+	pub fn recently_signed(&self, signer: &Address, number: BlockNumber) -> bool {
+		let limit = self.signer_limit();
+		self.recents.iter().any(|(seen, recent)| recent == signer && (number < limit || *seen > number - limit))
+	}
+
+	/// Returns true if it's `signer`'s turn to seal block `number`.
+	pub fn is_in_turn(&self, signer: &Address, number: BlockNumber) -> bool {
+		match self.signers.iter().position(|s| s == signer) {
+			Some(offset) => number % self.signers.len() as u64 == offset as u64,
+			None => false,
+		}
+	}
+
+	/// Vote tally for adding (`authorize`) or removing given address.
+	pub fn votes(&self, address: &Address, authorize: bool) -> usize {
+		match self.tally.get(address) {
+			Some(tally) if tally.authorize == authorize => tally.votes,
+			_ => 0,
+		}
+	}
+
+	/// Apply a block sealed by `signer`, voting to add (`authorize`) or remove `beneficiary`.
+	/// Votes are reset on blocks which are multiples of `epoch`.
+	pub fn apply(&mut self, hash: H256, number: BlockNumber, signer: Address, beneficiary: Address, authorize: bool, epoch: u64) -> Result<(), Error> {
+		if number % epoch == 0 {
+			self.votes.clear();
+			self.tally.clear();
+		}
+
+		// the oldest signer may seal again.
+		let limit = self.signer_limit();
+		if number >= limit {
+			self.recents.remove(&(number - limit));
+		}
+
+		if !self.signers.contains(&signer) {
+			return Err(EngineError::NotAuthorized(signer).into());
+		}
+		if self.recents.values().any(|recent| *recent == signer) {
+			return Err(EngineError::DoubleVote(signer).into());
+		}
+		self.recents.insert(number, signer);
+
+		// a signer may only have one vote per address.
+		if let Some(index) = self.votes.iter().position(|v| v.signer == signer && v.address == beneficiary) {
+			let vote = self.votes.remove(index);
+			self.uncast(&vote.address, vote.authorize);
+		}
+
+		if self.cast(beneficiary, authorize) {
+			self.votes.push(Vote { signer: signer, address: beneficiary, authorize: authorize });
+		}
+
+		let passed = match self.tally.get(&beneficiary) {
+			Some(tally) if tally.votes > self.signers.len() / 2 => Some(tally.authorize),
+			_ => None,
+		};
+
+		if let Some(authorize) = passed {
+			if authorize {
+				self.signers.insert(beneficiary);
+			} else {
+				self.signers.remove(&beneficiary);
+
+				// the signer set shrunk, release the now stale recent signer.
+				let limit = self.signer_limit();
+				if number >= limit {
+					self.recents.remove(&(number - limit));
+				}
+
+				// discard the votes of the removed signer.
+				let (discarded, kept): (Vec<_>, Vec<_>) = self.votes.drain(..).partition(|v| v.signer == beneficiary);
+				self.votes = kept;
+				for vote in discarded {
+					self.uncast(&vote.address, vote.authorize);
+				}
+			}
+
+			self.votes.retain(|v| v.address != beneficiary);
+			self.tally.remove(&beneficiary);
+		}
+
+		self.hash = hash;
+		self.number = number;
+		Ok(())
+	}
+
+	fn cast(&mut self, address: Address, authorize: bool) -> bool {
+		// only votes which would change the signer set count.
+		if self.signers.contains(&address) == authorize {
+			return false;
+		}
+
+		self.tally.entry(address).or_insert(Tally { authorize: authorize, votes: 0 }).votes += 1;
+		true
+	}
+
+	fn uncast(&mut self, address: &Address, authorize: bool) {
+		let remove = match self.tally.get_mut(address) {
+			Some(tally) => {
+				if tally.authorize != authorize {
+					return;
+				}
+				tally.votes -= 1;
+				tally.votes == 0
+			},
+			None => false,
+		};
+		if remove {
+			self.tally.remove(address);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::Address;
+	use super::Snapshot;
+
+	const EPOCH: u64 = 30_000;
+
+	fn snapshot(signers: &[u64]) -> Snapshot {
+		Snapshot::new(Default::default(), 0, signers.iter().map(|s| Address::from(*s)).collect())
+	}
+
+	fn apply(snapshot: &mut Snapshot, signer: u64, beneficiary: u64, authorize: bool) {
+		let number = snapshot.number + 1;
+		snapshot.apply(Default::default(), number, signer.into(), beneficiary.into(), authorize, EPOCH).unwrap();
+	}
+
+	#[test]
+	fn should_add_signer_with_majority() {
+		let mut snapshot = snapshot(&[1, 2]);
+		apply(&mut snapshot, 1, 3, true);
+		assert_eq!(snapshot.votes(&3.into(), true), 1);
+		assert!(!snapshot.signers.contains(&3.into()));
+
+		apply(&mut snapshot, 2, 3, true);
+		assert!(snapshot.signers.contains(&3.into()));
+		assert_eq!(snapshot.votes(&3.into(), true), 0);
+	}
+
+	#[test]
+	fn should_remove_signer_and_discard_its_votes() {
+		let mut snapshot = snapshot(&[1, 2, 3]);
+		apply(&mut snapshot, 3, 4, true);
+		apply(&mut snapshot, 1, 3, false);
+		assert_eq!(snapshot.votes(&4.into(), true), 1);
+
+		apply(&mut snapshot, 2, 3, false);
+		assert!(!snapshot.signers.contains(&3.into()));
+		assert_eq!(snapshot.votes(&4.into(), true), 0);
+		assert_eq!(snapshot.signers.len(), 2);
+	}
+
+	#[test]
+	fn should_ignore_repeated_and_pointless_votes() {
+		let mut snapshot = snapshot(&[1, 2, 3]);
+		// voting to add an existing signer has no effect.
+		apply(&mut snapshot, 1, 2, true);
+		assert_eq!(snapshot.votes(&2.into(), true), 0);
+
+		apply(&mut snapshot, 2, 4, true);
+		apply(&mut snapshot, 3, 0, false);
+		apply(&mut snapshot, 2, 4, true);
+		assert_eq!(snapshot.votes(&4.into(), true), 1);
+	}
+
+	#[test]
+	fn should_reject_unauthorized_and_recent_signers() {
+		let mut snapshot = snapshot(&[1, 2, 3]);
+		assert!(snapshot.apply(Default::default(), 1, 4.into(), 0.into(), false, EPOCH).is_err());
+
+		apply(&mut snapshot, 1, 0, false);
+		assert!(snapshot.recently_signed(&1.into(), 2));
+		assert!(snapshot.apply(Default::default(), 2, 1.into(), 0.into(), false, EPOCH).is_err());
+
+		apply(&mut snapshot, 2, 0, false);
+		assert!(!snapshot.recently_signed(&1.into(), 3));
+		apply(&mut snapshot, 1, 0, false);
+	}
+
+	#[test]
+	fn should_reset_votes_on_epoch() {
+		let mut snapshot = snapshot(&[1, 2, 3]);
+		apply(&mut snapshot, 1, 4, true);
+		snapshot.apply(Default::default(), EPOCH, 2.into(), 0.into(), false, EPOCH).unwrap();
+		assert_eq!(snapshot.votes(&4.into(), true), 0);
+	}
+
+	#[test]
+	fn should_compute_turn() {
+		let snapshot = snapshot(&[1, 2, 3]);
+		assert!(snapshot.is_in_turn(&1.into(), 3));
+		assert!(snapshot.is_in_turn(&2.into(), 4));
+		assert!(!snapshot.is_in_turn(&2.into(), 3));
+		assert!(!snapshot.is_in_turn(&4.into(), 3));
+	}
+}
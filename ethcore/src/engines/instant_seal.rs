@@ -15,24 +15,26 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use util::Address;
 use builtin::Builtin;
 use engines::{Engine, Seal};
+use engines::reloadable::Reloadable;
 use spec::CommonParams;
 use block::{ExecutedBlock, IsBlock};
 
 /// An engine which does not provide any consensus mechanism, just seals blocks internally.
 pub struct InstantSeal {
-	params: CommonParams,
-	builtins: BTreeMap<Address, Builtin>,
+	params: Reloadable<CommonParams>,
+	builtins: Reloadable<BTreeMap<Address, Builtin>>,
 }
 
 impl InstantSeal {
 	/// Returns new instance of InstantSeal with default VM Factory
 	pub fn new(params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		InstantSeal {
-			params: params,
-			builtins: builtins,
+			params: Reloadable::new(params),
+			builtins: Reloadable::new(builtins),
 		}
 	}
 }
@@ -42,16 +44,22 @@ impl Engine for InstantSeal {
 		"InstantSeal"
 	}
 
-	fn params(&self) -> &CommonParams {
-		&self.params
+	fn params(&self) -> Arc<CommonParams> {
+		self.params.get()
 	}
 
 	fn additional_params(&self) -> HashMap<String, String> {
 		hash_map!["registrar".to_owned() => self.params().registrar.hex()]
 	}
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
-		&self.builtins
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> {
+		self.builtins.get()
+	}
+
+	fn reload_params(&self, params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> bool {
+		self.params.replace(params);
+		self.builtins.replace(builtins);
+		true
 	}
 
 	fn seals_internally(&self) -> Option<bool> { Some(true) }
@@ -59,6 +67,8 @@ impl Engine for InstantSeal {
 	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
 		if block.transactions().is_empty() { Seal::None } else { Seal::Regular(Vec::new()) }
 	}
+
+	fn generate_seal_on_demand(&self, _block: &ExecutedBlock) -> Seal { Seal::Regular(Vec::new()) }
 }
 
 #[cfg(test)]
@@ -86,6 +96,22 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn instant_seals_empty_blocks_on_demand() {
+		let spec = Spec::new_instant();
+		let engine = &*spec.engine;
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, Address::default(), (3141562.into(), 31415620.into()), vec![], false).unwrap();
+		let b = b.close_and_lock();
+		assert_eq!(engine.generate_seal(b.block()), Seal::None);
+		match engine.generate_seal_on_demand(b.block()) {
+			Seal::Regular(seal) => assert!(b.try_seal(engine, seal).is_ok()),
+			_ => panic!("expected a regular seal"),
+		}
+	}
+
 	#[test]
 	fn instant_cant_verify() {
 		let engine = Spec::new_instant().engine;
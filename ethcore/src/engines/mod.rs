@@ -18,8 +18,11 @@
 
 mod authority_round;
 mod basic_authority;
+mod block_reward;
+mod clique;
 mod instant_seal;
 mod null_engine;
+mod reloadable;
 mod signer;
 mod tendermint;
 mod transition;
@@ -30,6 +33,8 @@ pub mod epoch;
 
 pub use self::authority_round::AuthorityRound;
 pub use self::basic_authority::BasicAuthority;
+pub use self::block_reward::{BlockRewardContract, RewardKind};
+pub use self::clique::Clique;
 pub use self::epoch::{EpochVerifier, Transition as EpochTransition};
 pub use self::instant_seal::InstantSeal;
 pub use self::null_engine::NullEngine;
@@ -202,8 +207,12 @@ pub trait Engine : Sync + Send {
 	/// Additional information.
 	fn additional_params(&self) -> HashMap<String, String> { HashMap::new() }
 
+	/// Account credited with transaction fees and returned by `COINBASE` when executing `header`.
+	/// Defaults to the header author; engines keeping the block signer elsewhere override it.
+	fn executive_author(&self, header: &Header) -> Address { header.author().clone() }
+
 	/// Get the general parameters of the chain.
-	fn params(&self) -> &CommonParams;
+	fn params(&self) -> Arc<CommonParams>;
 
 	/// Get the EVM schedule for the given `block_number`.
 	fn schedule(&self, block_number: BlockNumber) -> Schedule {
@@ -212,7 +221,14 @@ pub trait Engine : Sync + Send {
 
 	/// Builtin-contracts we would like to see in the chain.
 	/// (In principle these are just hints for the engine since that has the last word on them.)
-	fn builtins(&self) -> &BTreeMap<Address, Builtin>;
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>>;
+
+	/// Blocks at which the consensus rules change, used to compute the fork identifier.
+	fn fork_blocks(&self) -> Vec<BlockNumber> {
+		let mut forks = self.params().fork_blocks();
+		forks.extend(self.builtins().values().map(Builtin::activate_at));
+		forks
+	}
 
 	/// Some intrinsic operation parameters; by default they take their value from the `spec()`'s `engine_params`.
 	fn maximum_extra_data_size(&self) -> usize { self.params().maximum_extra_data_size }
@@ -220,6 +236,9 @@ pub trait Engine : Sync + Send {
 	fn maximum_uncle_count(&self) -> usize { 2 }
 	/// The number of generations back that uncles can be.
 	fn maximum_uncle_age(&self) -> usize { 6 }
+	/// Reward received by the author of given uncle when included in a block with given number.
+	/// `None` if the engine doesn't reward uncles.
+	fn uncle_reward(&self, _uncle: &Header, _block_number: BlockNumber) -> Option<U256> { None }
 	/// The nonce with which accounts begin at given block.
 	fn account_start_nonce(&self, block: u64) -> U256 {
 		if block >= self.params().dust_protection_transition {
@@ -258,6 +277,19 @@ pub trait Engine : Sync + Send {
 	/// be returned.
 	fn generate_seal(&self, _block: &ExecutedBlock) -> Seal { Seal::None }
 
+	/// Attempt to seal the block internally on explicit request.
+	///
+	/// Unlike `generate_seal` it should produce a seal whenever possible, e.g. also for empty blocks.
+	fn generate_seal_on_demand(&self, block: &ExecutedBlock) -> Seal { self.generate_seal(block) }
+
+	/// Number of first block sealed with ProgPoW, if the engine switches proof-of-work algorithm.
+	/// External miners need block numbers of such blocks to derive the ProgPoW program.
+	fn progpow_transition(&self) -> Option<BlockNumber> { None }
+
+	/// Difficulty met by a proof-of-work seal of the block with given number and bare hash,
+	/// fully computed by the engine. `None` if the engine has no proof-of-work or `mix_hash` is wrong.
+	fn pow_difficulty(&self, _number: BlockNumber, _bare_hash: &H256, _nonce: u64, _mix_hash: &H256) -> Option<U256> { None }
+
 	/// Phase 1 quick block verification. Only does checks that are cheap. `block` (the header's full block)
 	/// may be provided for additional checks. Returns either a null `Ok` or a general error detailing the problem with import.
 	fn verify_block_basic(&self, _header: &Header,  _block: Option<&[u8]>) -> Result<(), Error> { Ok(()) }
@@ -350,20 +382,19 @@ pub trait Engine : Sync + Send {
 	/// updating consensus state and potentially issuing a new one.
 	fn handle_message(&self, _message: &[u8]) -> Result<(), Error> { Err(EngineError::UnexpectedMessage.into()) }
 
-	/// Attempt to get a handle to a built-in contract.
-	/// Only returns references to activated built-ins.
-	// TODO: builtin contract routing - to do this properly, it will require removing the built-in configuration-reading logic
-	// from Spec into here and removing the Spec::builtins field.
-	fn builtin(&self, a: &Address, block_number: ::header::BlockNumber) -> Option<&Builtin> {
-		self.builtins()
-			.get(a)
-			.and_then(|b| if b.is_active(block_number) { Some(b) } else { None })
-	}
-
 	/// Find out if the block is a proposal block and should not be inserted into the DB.
 	/// Takes a header of a fully verified block.
 	fn is_proposal(&self, _verified_header: &Header) -> bool { false }
 
+	/// Whether the block can't be reverted once it's part of the chain, e.g. carries
+	/// a commit signed by enough validators.
+	fn is_finalized(&self, _header: &Header) -> bool { false }
+
+	/// Replace the chain parameters and builtins at runtime.
+	/// Compatibility with the existing chain is checked by the caller.
+	/// Returns `false` if the engine doesn't support reloading.
+	fn reload_params(&self, _params: CommonParams, _builtins: BTreeMap<Address, Builtin>) -> bool { false }
+
 	/// Register an account which signs consensus messages.
 	fn set_signer(&self, _account_provider: Arc<AccountProvider>, _address: Address, _password: String) {}
 
@@ -413,14 +444,14 @@ pub mod common {
 	use transaction::SYSTEM_ADDRESS;
 	use executive::Executive;
 	use vm::{CallType, ActionParams, ActionValue, EnvInfo, LastHashes};
-	use trace::{NoopTracer, NoopVMTracer, Tracer, ExecutiveTracer, RewardType};
+	use trace::{NoopTracer, NoopVMTracer, Tracer, ExecutiveTracer};
 	use state::Substate;
 	use state::CleanupMode;
 
 	use bigint::prelude::U256;
 	use bigint::hash::H256;
 	use util::*;
-	use super::Engine;
+	use super::{Engine, BlockRewardContract, RewardKind};
 
 	/// Execute a call as the system address.
 	pub fn execute_as_system<E: Engine + ?Sized>(
@@ -431,6 +462,44 @@ pub mod common {
 		gas: U256,
 		data: Option<Bytes>,
 	) -> Result<Bytes, Error> {
+		match call_as_system(block, last_hashes, engine, contract_address, gas, data) {
+			Ok(output) => Ok(output),
+			Err(SystemCallError::State(e)) => Err(e),
+			Err(SystemCallError::Execution(e)) => {
+				warn!("Encountered error on making system call: {}", e);
+				Ok(Vec::new())
+			},
+		}
+	}
+
+	/// Execute a call as the system address without gas accounting, failing on execution errors.
+	pub fn system_call<E: Engine + ?Sized>(
+		block: &mut ExecutedBlock,
+		engine: &E,
+		contract_address: Address,
+		data: Bytes,
+	) -> Result<Bytes, String> {
+		// Block hashes are not available when closing the block.
+		call_as_system(block, Default::default(), engine, contract_address, U256::max_value(), Some(data))
+			.map_err(|e| match e {
+				SystemCallError::State(e) => format!("{}", e),
+				SystemCallError::Execution(e) => format!("{}", e),
+			})
+	}
+
+	enum SystemCallError {
+		State(Error),
+		Execution(::vm::Error),
+	}
+
+	fn call_as_system<E: Engine + ?Sized>(
+		block: &mut ExecutedBlock,
+		last_hashes: Arc<LastHashes>,
+		engine: &E,
+		contract_address: Address,
+		gas: U256,
+		data: Option<Bytes>,
+	) -> Result<Bytes, SystemCallError> {
 		let env_info = {
 			let header = block.fields().header;
 			EnvInfo {
@@ -445,6 +514,8 @@ pub mod common {
 		};
 
 		let mut state = block.fields_mut().state;
+		let code = state.code(&contract_address).map_err(|e| SystemCallError::State(e.into()))?;
+		let code_hash = state.code_hash(&contract_address).map_err(|e| SystemCallError::State(e.into()))?;
 		let params = ActionParams {
 			code_address: contract_address.clone(),
 			address: contract_address.clone(),
@@ -453,17 +524,16 @@ pub mod common {
 			gas: gas,
 			gas_price: 0.into(),
 			value: ActionValue::Transfer(0.into()),
-			code: state.code(&contract_address)?,
-			code_hash: Some(state.code_hash(&contract_address)?),
+			code: code,
+			code_hash: Some(code_hash),
 			data: data,
 			call_type: CallType::Call,
 		};
 		let mut ex = Executive::new(&mut state, &env_info, engine);
 		let mut substate = Substate::new();
 		let mut output = Vec::new();
-		if let Err(e) = ex.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut NoopTracer, &mut NoopVMTracer) {
-			warn!("Encountered error on making system call: {}", e);
-		}
+		ex.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut NoopTracer, &mut NoopVMTracer)
+			.map_err(SystemCallError::Execution)?;
 
 		Ok(output)
 	}
@@ -489,18 +559,40 @@ pub mod common {
 
 	/// Trace rewards on closing block
 	pub fn bestow_block_reward<E: Engine + ?Sized>(block: &mut ExecutedBlock, engine: &E) -> Result<(), Error> {
+		let author = block.fields().header.author().clone();
+		let rewards = match engine.params().block_reward_contract {
+			Some(address) => {
+				let mut benefactors = vec![(author, RewardKind::Author)];
+				benefactors.extend(block.fields().uncles.iter().map(|u| (u.author().clone(), RewardKind::Uncle)));
+
+				let contract = BlockRewardContract::new(address);
+				contract.reward(&benefactors, |address, data| system_call(block, engine, address, data))?
+			},
+			None => vec![(author, RewardKind::Author, engine.params().block_reward)],
+		};
+
+		apply_block_rewards(&rewards, block)
+	}
+
+	/// Add given rewards to the balances and trace them.
+	pub fn apply_block_rewards(rewards: &[(Address, RewardKind, U256)], block: &mut ExecutedBlock) -> Result<(), Error> {
 		let fields = block.fields_mut();
-		// Bestow block reward
-		let reward = engine.params().block_reward;
-		let res = fields.state.add_balance(fields.header.author(), &reward, CleanupMode::NoEmpty)
-			.map_err(::error::Error::from)
-			.and_then(|_| fields.state.commit());
 
-		let block_author = fields.header.author().clone();
+		let mut res = Ok(());
+		for &(ref address, _, ref reward) in rewards {
+			res = fields.state.add_balance(address, reward, CleanupMode::NoEmpty).map_err(::error::Error::from);
+			if res.is_err() {
+				break;
+			}
+		}
+		let res = res.and_then(|_| fields.state.commit());
+
 		fields.traces.as_mut().map(|mut traces| {
-  			let mut tracer = ExecutiveTracer::default();
-  			tracer.trace_reward(block_author, engine.params().block_reward, RewardType::Block);
-  			traces.push(tracer.drain())
+			let mut tracer = ExecutiveTracer::default();
+			for &(address, kind, reward) in rewards {
+				tracer.trace_reward(address, reward, kind.into());
+			}
+			traces.push(tracer.drain())
 		});
 
 		// Commit state so that we can actually figure out the state root.
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
+use std::sync::Arc;
 use util::Address;
 use builtin::Builtin;
 use block::{ExecutedBlock, IsBlock};
@@ -29,16 +30,16 @@ use trace::{Tracer, ExecutiveTracer, RewardType};
 
 /// An engine which does not provide any consensus mechanism and does not seal blocks.
 pub struct NullEngine {
-	params: CommonParams,
-	builtins: BTreeMap<Address, Builtin>,
+	params: Arc<CommonParams>,
+	builtins: Arc<BTreeMap<Address, Builtin>>,
 }
 
 impl NullEngine {
 	/// Returns new instance of NullEngine with default VM Factory
 	pub fn new(params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		NullEngine{
-			params: params,
-			builtins: builtins,
+			params: Arc::new(params),
+			builtins: Arc::new(builtins),
 		}
 	}
 }
@@ -54,12 +55,12 @@ impl Engine for NullEngine {
 		"NullEngine"
 	}
 
-	fn params(&self) -> &CommonParams {
-		&self.params
+	fn params(&self) -> Arc<CommonParams> {
+		self.params.clone()
 	}
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
-		&self.builtins
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> {
+		self.builtins.clone()
 	}
 
 	fn schedule(&self, _block_number: BlockNumber) -> Schedule {
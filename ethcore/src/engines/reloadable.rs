@@ -0,0 +1,67 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Engine parameters replaceable at runtime.
+
+use std::sync::Arc;
+use parking_lot::RwLock;
+
+/// A value which can be replaced while previous versions are still in use.
+///
+/// Readers get a reference-counted snapshot; previous versions are freed once the last
+/// snapshot of them is dropped.
+pub struct Reloadable<T> {
+	current: RwLock<Arc<T>>,
+}
+
+impl<T> Reloadable<T> {
+	/// Create a new container holding given value.
+	pub fn new(value: T) -> Self {
+		Reloadable {
+			current: RwLock::new(Arc::new(value)),
+		}
+	}
+
+	/// Current version of the value.
+	pub fn get(&self) -> Arc<T> {
+		self.current.read().clone()
+	}
+
+	/// Replace the value. Snapshots of previous versions stay valid.
+	pub fn replace(&self, value: T) {
+		*self.current.write() = Arc::new(value);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use super::Reloadable;
+
+	#[test]
+	fn keeps_previous_versions_alive_while_used() {
+		let value = Reloadable::new(vec![1, 2, 3]);
+		let old = value.get();
+		value.replace(vec![4]);
+
+		assert_eq!(*old, vec![1, 2, 3]);
+		assert_eq!(*value.get(), vec![4]);
+
+		let old = Arc::downgrade(&old);
+		assert!(old.upgrade().is_none());
+	}
+}
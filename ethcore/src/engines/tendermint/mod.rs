@@ -24,6 +24,7 @@
 
 mod message;
 mod params;
+mod persistence;
 
 use std::sync::{Weak, Arc};
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
@@ -52,6 +53,7 @@ use super::transition::TransitionHandler;
 use super::vote_collector::VoteCollector;
 use self::message::*;
 use self::params::TendermintParams;
+use self::persistence::PersistedState;
 use semantic_version::SemanticVersion;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -77,8 +79,8 @@ pub type BlockHash = H256;
 
 /// Engine using `Tendermint` consensus algorithm, suitable for EVM chain.
 pub struct Tendermint {
-	params: CommonParams,
-	builtins: BTreeMap<Address, Builtin>,
+	params: Arc<CommonParams>,
+	builtins: Arc<BTreeMap<Address, Builtin>>,
 	step_service: IoService<Step>,
 	client: RwLock<Option<Weak<EngineClient>>>,
 	/// Blockchain height.
@@ -169,8 +171,8 @@ impl Tendermint {
 	pub fn new(params: CommonParams, our_params: TendermintParams, builtins: BTreeMap<Address, Builtin>) -> Result<Arc<Self>, Error> {
 		let engine = Arc::new(
 			Tendermint {
-				params: params,
-				builtins: builtins,
+				params: Arc::new(params),
+				builtins: Arc::new(builtins),
 				client: RwLock::new(None),
 				step_service: IoService::<Step>::start()?,
 				height: AtomicUsize::new(1),
@@ -261,6 +263,52 @@ impl Tendermint {
 		self.view.store(0, AtomicOrdering::SeqCst);
 		*self.lock_change.write() = None;
 		*self.proposal.write() = None;
+		self.persist();
+	}
+
+	/// Persist the votes and the lock at the current height.
+	fn persist(&self) {
+		let client = match self.client.read().as_ref().and_then(|weak| weak.upgrade()) {
+			Some(client) => client,
+			None => return,
+		};
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let state = PersistedState {
+			height: height,
+			view: self.view.load(AtomicOrdering::SeqCst),
+			last_lock: self.last_lock.load(AtomicOrdering::SeqCst),
+			lock_change: self.lock_change.read().clone(),
+			votes: self.votes.messages_from(&VoteStep::new(height, 0, Step::Propose)),
+		};
+		client.store_engine_data(::rlp::encode(&state).into_vec());
+	}
+
+	/// Restore the votes and the lock persisted before a restart, if they concern the current height.
+	fn restore(&self, client: &EngineClient) {
+		let data = match client.engine_data() {
+			Some(data) => data,
+			None => return,
+		};
+		let state: PersistedState = match UntrustedRlp::new(&data).as_val() {
+			Ok(state) => state,
+			Err(e) => {
+				warn!(target: "engine", "Ignoring corrupted consensus state: {}", e);
+				return;
+			},
+		};
+		if state.height != self.height.load(AtomicOrdering::SeqCst) {
+			return;
+		}
+
+		debug!(target: "engine", "Restoring {} votes at height {}, view {}.", state.votes.len(), state.height, state.view);
+		self.view.store(state.view, AtomicOrdering::SeqCst);
+		self.last_lock.store(state.last_lock, AtomicOrdering::SeqCst);
+		*self.lock_change.write() = state.lock_change;
+		for vote in state.votes {
+			if let Ok(sender) = vote.verify() {
+				self.votes.vote(vote, &sender);
+			}
+		}
 	}
 
 	/// Use via step_service to transition steps.
@@ -269,6 +317,7 @@ impl Tendermint {
 			warn!(target: "engine", "Could not proceed to step {}.", io_err)
 		}
 		*self.step.write() = step;
+		self.persist();
 		match step {
 			Step::Propose => {
 				self.update_sealing()
@@ -371,6 +420,17 @@ impl Tendermint {
 		}
 	}
 
+	/// More than a third of validators voting in a future view means at least one honest validator moved on.
+	fn has_enough_future_view_votes(&self, vote_step: &VoteStep) -> bool {
+		if vote_step.view > self.view.load(AtomicOrdering::SeqCst) {
+			let first = VoteStep::new(vote_step.height, vote_step.view, Step::Propose);
+			let last = VoteStep::new(vote_step.height, vote_step.view, Step::Commit);
+			self.votes.count_voters(&first, &last) > self.validators.count(&*self.proposal_parent.read()) / 3
+		} else {
+			false
+		}
+	}
+
 	fn has_enough_aligned_votes(&self, message: &ConsensusMessage) -> bool {
 		let aligned_count = self.votes.count_aligned_votes(&message);
 		self.check_above_threshold(aligned_count).is_ok()
@@ -390,6 +450,17 @@ impl Tendermint {
 			trace!(target: "engine", "handle_valid_message: Lock change.");
 			*self.lock_change.write() = Some(message.clone());
 		}
+		// PoLC for nil in a newer view releases the lock.
+		let unlock = is_newer_than_lock
+			&& vote_step.step == Step::Prevote
+			&& message.block_hash.is_none()
+			&& self.lock_change.read().is_some()
+			&& self.has_enough_aligned_votes(message);
+		if unlock {
+			trace!(target: "engine", "handle_valid_message: Unlock.");
+			*self.lock_change.write() = None;
+			self.last_lock.store(0, AtomicOrdering::SeqCst);
+		}
 		// Check if it can affect the step transition.
 		if self.is_height(message) {
 			let next_step = match *self.step.read() {
@@ -426,13 +497,23 @@ impl Tendermint {
 					self.increment_view(vote_step.view - self.view.load(AtomicOrdering::SeqCst));
 					Some(Step::Prevote)
 				},
+				// Skip views which the others already left.
+				_ if self.has_enough_future_view_votes(&vote_step) => {
+					self.increment_view(vote_step.view - self.view.load(AtomicOrdering::SeqCst));
+					Some(Step::Propose)
+				},
 				_ => None,
 			};
 
-			if let Some(step) = next_step {
-				trace!(target: "engine", "Transition to {:?} triggered.", step);
-				self.to_step(step);
+			match next_step {
+				Some(step) => {
+					trace!(target: "engine", "Transition to {:?} triggered.", step);
+					self.to_step(step);
+				},
+				None => self.persist(),
 			}
+		} else {
+			self.persist();
 		}
 	}
 }
@@ -445,13 +526,13 @@ impl Engine for Tendermint {
 	/// (consensus view, proposal signature, authority signatures)
 	fn seal_fields(&self) -> usize { 3 }
 
-	fn params(&self) -> &CommonParams { &self.params }
+	fn params(&self) -> Arc<CommonParams> { self.params.clone() }
 
 	fn additional_params(&self) -> HashMap<String, String> {
 		hash_map!["registrar".to_owned() => self.params().registrar.hex()]
 	}
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> { self.builtins.clone() }
 
 	fn maximum_uncle_count(&self) -> usize { 0 }
 
@@ -700,6 +781,11 @@ impl Engine for Tendermint {
 		self.step_service.stop()
 	}
 
+	/// Committed blocks carry precommits of more than two thirds of validators.
+	fn is_finalized(&self, header: &Header) -> bool {
+		header.seal().len() == self.seal_fields() && header.seal()[2] != ::rlp::EMPTY_LIST_RLP
+	}
+
 	fn is_proposal(&self, header: &Header) -> bool {
 		let signatures_len = header.seal()[2].len();
 		// Signatures have to be an empty list rlp.
@@ -764,6 +850,7 @@ impl Engine for Tendermint {
 	fn register_client(&self, client: Weak<EngineClient>) {
 		if let Some(c) = client.upgrade() {
 			self.height.store(c.chain_info().best_block_number as usize + 1, AtomicOrdering::SeqCst);
+			self.restore(&*c);
 		}
 		*self.client.write() = Some(client.clone());
 		self.validators.register_client(client);
@@ -1140,4 +1227,80 @@ mod tests {
 
 		engine.stop();
 	}
+
+	fn persisted_state(client: &EngineClient) -> PersistedState {
+		::rlp::decode(&client.engine_data().expect("state has been persisted; qed"))
+	}
+
+	#[test]
+	fn restores_votes_after_restart() {
+		let (spec, tap) = setup();
+		let v0 = insert_and_unlock(&tap, "0");
+		let client = generate_dummy_client(0);
+		spec.engine.register_client(Arc::downgrade(&client) as _);
+
+		let h = 1;
+		let r = 0;
+		vote(spec.engine.as_ref(), |mh| tap.sign(v0, None, mh).map(H520::from), h, r, Step::Prevote, Some(1.into()));
+		assert_eq!(persisted_state(&*client).votes.len(), 1);
+		spec.engine.stop();
+
+		let restarted = Spec::new_test_tendermint().engine;
+		restarted.register_client(Arc::downgrade(&client) as _);
+
+		let mi = message_info_rlp(&VoteStep::new(h, r, Step::Prevote), Some(2.into()));
+		let m = message_full_rlp(&tap.sign(v0, None, keccak(&mi)).unwrap().into(), &mi);
+		match restarted.handle_message(&m) {
+			Err(Error::Engine(EngineError::DoubleVote(ref sender))) if *sender == v0 => {},
+			other => panic!("expected a double vote, got {:?}", other),
+		}
+		restarted.stop();
+	}
+
+	#[test]
+	fn skips_to_view_with_enough_votes() {
+		let (spec, tap) = setup();
+		let v0 = insert_and_unlock(&tap, "0");
+		let client = generate_dummy_client(0);
+		spec.engine.register_client(Arc::downgrade(&client) as _);
+
+		vote(spec.engine.as_ref(), |mh| tap.sign(v0, None, mh).map(H520::from), 1, 2, Step::Prevote, None);
+		assert_eq!(persisted_state(&*client).view, 2);
+		spec.engine.stop();
+	}
+
+	#[test]
+	fn unlocks_on_nil_polka() {
+		let (spec, tap) = setup();
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let client = generate_dummy_client(0);
+		spec.engine.register_client(Arc::downgrade(&client) as _);
+		let engine = spec.engine.as_ref();
+
+		let h = 1;
+		let proposal = Some(1.into());
+		vote(engine, |mh| tap.sign(v0, None, mh).map(H520::from), h, 0, Step::Prevote, proposal);
+		vote(engine, |mh| tap.sign(v1, None, mh).map(H520::from), h, 0, Step::Prevote, proposal);
+		assert!(persisted_state(&*client).lock_change.is_some());
+
+		vote(engine, |mh| tap.sign(v0, None, mh).map(H520::from), h, 1, Step::Prevote, None);
+		vote(engine, |mh| tap.sign(v1, None, mh).map(H520::from), h, 1, Step::Prevote, None);
+		let state = persisted_state(&*client);
+		assert!(state.lock_change.is_none());
+		assert_eq!(state.last_lock, 0);
+		spec.engine.stop();
+	}
+
+	#[test]
+	fn commit_seal_is_final() {
+		let engine = Spec::new_test_tendermint().engine;
+		let mut header = Header::default();
+		header.set_seal(vec![::rlp::encode(&0usize).into_vec(), vec![1], ::rlp::EMPTY_LIST_RLP.to_vec()]);
+		assert!(!engine.is_finalized(&header));
+
+		header.set_seal(vec![::rlp::encode(&0usize).into_vec(), ::rlp::NULL_RLP.to_vec(), ::rlp::encode_list(&vec![H520::default()]).into_vec()]);
+		assert!(engine.is_finalized(&header));
+		engine.stop();
+	}
 }
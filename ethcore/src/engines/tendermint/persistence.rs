@@ -0,0 +1,89 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Consensus state kept across restarts.
+
+use rlp::{UntrustedRlp, RlpStream, Encodable, Decodable, DecoderError};
+use super::{Height, View};
+use super::message::ConsensusMessage;
+
+/// Votes and lock at the current height, stored so that a restarted validator
+/// neither signs conflicting votes nor forgets the block it's locked on.
+#[derive(Debug, PartialEq)]
+pub struct PersistedState {
+	/// Current height.
+	pub height: Height,
+	/// Current view.
+	pub view: View,
+	/// View of the last lock.
+	pub last_lock: View,
+	/// Message of the last PoLC.
+	pub lock_change: Option<ConsensusMessage>,
+	/// Votes seen at the current height.
+	pub votes: Vec<ConsensusMessage>,
+}
+
+impl Encodable for PersistedState {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(5)
+			.append(&self.height)
+			.append(&self.view)
+			.append(&self.last_lock);
+		match self.lock_change {
+			Some(ref lock) => { s.begin_list(1).append(lock); },
+			None => { s.begin_list(0); },
+		}
+		s.append_list(&self.votes);
+	}
+}
+
+impl Decodable for PersistedState {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		let lock_change: Vec<ConsensusMessage> = rlp.list_at(3)?;
+		Ok(PersistedState {
+			height: rlp.val_at(0)?,
+			view: rlp.val_at(1)?,
+			last_lock: rlp.val_at(2)?,
+			lock_change: lock_change.into_iter().next(),
+			votes: rlp.list_at(4)?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bigint::hash::{H256, H520};
+	use rlp;
+	use super::super::Step;
+	use super::super::message::ConsensusMessage;
+	use super::PersistedState;
+
+	#[test]
+	fn encode_decode() {
+		let vote = ConsensusMessage::new(H520::from(1), 5, 2, Step::Prevote, Some(H256::from(3)));
+		let state = PersistedState {
+			height: 5,
+			view: 2,
+			last_lock: 1,
+			lock_change: Some(vote.clone()),
+			votes: vec![vote.clone(), ConsensusMessage::new(H520::from(2), 5, 2, Step::Precommit, None)],
+		};
+		assert_eq!(rlp::decode::<PersistedState>(&rlp::encode(&state)), state);
+
+		let unlocked = PersistedState { lock_change: None, votes: vec![], ..state };
+		assert_eq!(rlp::decode::<PersistedState>(&rlp::encode(&unlocked)), unlocked);
+	}
+}
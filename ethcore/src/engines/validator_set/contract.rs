@@ -86,6 +86,10 @@ impl ValidatorSet for ValidatorContract {
 		self.validators.is_epoch_end(first, chain_head)
 	}
 
+	fn has_immediate_transitions(&self, number: BlockNumber) -> bool {
+		self.validators.has_immediate_transitions(number)
+	}
+
 	fn signals_epoch_end(
 		&self,
 		first: bool,
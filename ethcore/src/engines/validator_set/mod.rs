@@ -48,6 +48,7 @@ pub fn new_validator_set(spec: ValidatorSpec) -> Box<ValidatorSet> {
 	match spec {
 		ValidatorSpec::List(list) => Box::new(SimpleList::new(list.into_iter().map(Into::into).collect())),
 		ValidatorSpec::SafeContract(address) => Box::new(ValidatorSafeContract::new(address.into())),
+		ValidatorSpec::ImmediateSafeContract(address) => Box::new(ValidatorSafeContract::new_immediate(address.into())),
 		ValidatorSpec::Contract(address) => Box::new(ValidatorContract::new(address.into())),
 		ValidatorSpec::Multi(sequence) => Box::new(
 			Multi::new(sequence.into_iter().map(|(block, set)| (block.into(), new_validator_set(set))).collect())
@@ -116,6 +117,12 @@ pub trait ValidatorSet: Send + Sync {
 		receipts: Option<&[::receipt::Receipt]>,
 	) -> ::engines::EpochChange;
 
+	/// Whether transitions signalled by the set active at given block take effect
+	/// immediately after the signalling block instead of waiting for finality.
+	///
+	/// Proofs of such transitions are expected to be verifiable without a finality proof.
+	fn has_immediate_transitions(&self, _number: BlockNumber) -> bool { false }
+
 	/// Recover the validator set from the given proof, the block number, and
 	/// whether this header is first in its set.
 	///
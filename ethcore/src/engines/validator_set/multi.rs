@@ -101,6 +101,10 @@ impl ValidatorSet for Multi {
 		set.signals_epoch_end(first, header, block, receipts)
 	}
 
+	fn has_immediate_transitions(&self, number: BlockNumber) -> bool {
+		self.correct_set_by_number(number).1.has_immediate_transitions(number)
+	}
+
 	fn epoch_set(&self, _first: bool, engine: &Engine, number: BlockNumber, proof: &[u8]) -> Result<(super::SimpleList, Option<H256>), ::error::Error> {
 		let (set_block, set) = self.correct_set_by_number(number);
 		let first = set_block == number;
@@ -79,6 +79,7 @@ pub struct ValidatorSafeContract {
 	validators: RwLock<MemoryLruCache<H256, SimpleList>>,
 	provider: Provider,
 	client: RwLock<Option<Weak<EngineClient>>>, // TODO [keorn]: remove
+	immediate: bool,
 }
 
 // first proof is just a state proof call of `getValidators` at header's state.
@@ -205,6 +206,23 @@ impl ValidatorSafeContract {
 			validators: RwLock::new(MemoryLruCache::new(MEMOIZE_CAPACITY)),
 			provider: Provider::new(contract_address),
 			client: RwLock::new(None),
+			immediate: false,
+		}
+	}
+
+	/// Contract whose validator set changes take effect right after the signalling block.
+	pub fn new_immediate(contract_address: Address) -> Self {
+		ValidatorSafeContract {
+			immediate: true,
+			..Self::new(contract_address)
+		}
+	}
+
+	// hash of the block whose finality must be proven before trusting the set, if any.
+	fn requires_finality(&self, hash: H256) -> Option<H256> {
+		match self.immediate {
+			true => None,
+			false => Some(hash),
 		}
 	}
 
@@ -371,6 +389,10 @@ impl ValidatorSet for ValidatorSafeContract {
 		}
 	}
 
+	fn has_immediate_transitions(&self, _number: ::header::BlockNumber) -> bool {
+		self.immediate
+	}
+
 	fn epoch_set(&self, first: bool, engine: &Engine, _number: ::header::BlockNumber, proof: &[u8])
 		-> Result<(SimpleList, Option<H256>), ::error::Error>
 	{
@@ -388,7 +410,7 @@ impl ValidatorSet for ValidatorSafeContract {
 			trace!(target: "engine", "extracted epoch set at #{}: {} addresses",
 				number, addresses.len());
 
-			Ok((SimpleList::new(addresses), Some(old_hash)))
+			Ok((SimpleList::new(addresses), self.requires_finality(old_hash)))
 		} else {
 			let (old_header, receipts) = decode_proof(&rlp)?;
 
@@ -406,7 +428,7 @@ impl ValidatorSet for ValidatorSafeContract {
 			let bloom = self.expected_bloom(&old_header);
 
 			match self.extract_from_event(bloom, &old_header, &receipts) {
-				Some(list) => Ok((list, Some(old_header.hash()))),
+				Some(list) => Ok((list, self.requires_finality(old_header.hash()))),
 				None => Err(::engines::EngineError::InsufficientProof("No log event in proof.".into()).into()),
 			}
 		}
@@ -611,4 +633,51 @@ mod tests {
 			_ => panic!("Expected state to be required to prove initial signal"),
 		};
 	}
+
+	#[test]
+	fn immediate_transition_proof_needs_no_finality() {
+		use bigint::hash::H256;
+		use header::Header;
+		use log_entry::LogEntry;
+		use receipt::Receipt;
+		use super::encode_proof;
+
+		let spec = Spec::new_test();
+		let contract_address = Address::from(5);
+		let parent_hash = H256::from(1);
+		let new_validator = Address::from(0x42);
+
+		// ABI-encoded `address[]` with a single element.
+		let mut data = vec![0u8; 96];
+		data[31] = 0x20;
+		data[63] = 1;
+		data[76..96].copy_from_slice(&*new_validator);
+
+		let log = LogEntry {
+			address: contract_address,
+			topics: vec![*EVENT_NAME_HASH, parent_hash],
+			data: data,
+		};
+		let receipts = vec![Receipt::new(None, 21000.into(), vec![log])];
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_parent_hash(parent_hash);
+		header.set_log_bloom(receipts[0].log_bloom.clone());
+		header.set_receipts_root(::triehash::ordered_trie_root(
+			receipts.iter().map(::rlp::encode).map(|x| x.to_vec())
+		));
+		let proof = encode_proof(&header, &receipts);
+
+		let immediate = ValidatorSafeContract::new_immediate(contract_address);
+		assert!(immediate.has_immediate_transitions(1));
+		let (list, finalize) = immediate.epoch_set(false, &*spec.engine, 1, &proof).unwrap();
+		assert_eq!(list.into_inner(), vec![new_validator]);
+		assert_eq!(finalize, None);
+
+		let safe = ValidatorSafeContract::new(contract_address);
+		assert!(!safe.has_immediate_transitions(1));
+		let (_, finalize) = safe.epoch_set(false, &*spec.engine, 1, &proof).unwrap();
+		assert_eq!(finalize, Some(header.hash()));
+	}
 }
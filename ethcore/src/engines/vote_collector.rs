@@ -193,6 +193,26 @@ impl <M: Message + Default + Encodable + Debug> VoteCollector<M> {
 			.fold(Vec::new(), |mut acc, mut messages| { acc.append(&mut messages); acc })
 	}
 
+	/// Get all messages starting with the round.
+	pub fn messages_from(&self, round: &M::Round) -> Vec<M> {
+		let guard = self.votes.read();
+		guard
+			.range(round.clone()..)
+			.flat_map(|(_, c)| c.messages.iter().cloned())
+			.collect()
+	}
+
+	/// Count distinct voters in the rounds between `from` and `to` inclusive.
+	pub fn count_voters(&self, from: &M::Round, to: &M::Round) -> usize {
+		let guard = self.votes.read();
+		guard
+			.range(from.clone()..)
+			.take_while(|&(r, _)| r <= to)
+			.flat_map(|(_, c)| c.voted.keys())
+			.collect::<HashSet<_>>()
+			.len()
+	}
+
 	/// Retrieve address from which the message was sent from cache.
 	pub fn get(&self, message: &M) -> Option<Address> {
 		let guard = self.votes.read();
@@ -349,4 +369,20 @@ mod tests {
 		assert!(!full_vote(&collector, H520::random(), round, Some(keccak("1")), &Address::default()));
 		assert_eq!(collector.count_round_votes(&round), 1);
 	}
+
+	#[test]
+	fn counts_voters_and_collects_newer_messages() {
+		let collector = VoteCollector::default();
+		let (a, b) = (Address::from(1), Address::from(2));
+		full_vote(&collector, H520::random(), 2, Some(keccak("0")), &a);
+		full_vote(&collector, H520::random(), 3, Some(keccak("0")), &a);
+		full_vote(&collector, H520::random(), 3, None, &b);
+		full_vote(&collector, H520::random(), 5, None, &b);
+
+		assert_eq!(collector.count_voters(&2, &3), 2);
+		assert_eq!(collector.count_voters(&3, &4), 2);
+		assert_eq!(collector.count_voters(&4, &5), 1);
+		assert_eq!(collector.messages_from(&3).len(), 3);
+		assert_eq!(collector.messages_from(&6).len(), 0);
+	}
 }
@@ -42,9 +42,18 @@ pub enum TransactionError {
 	Old,
 	/// Transaction has too low fee
 	/// (there is already a transaction with the same sender-nonce but higher gas price)
-	TooCheapToReplace,
+	TooCheapToReplace {
+		/// Minimal gas price required to replace the transaction
+		minimal: U256,
+		/// Transaction gas price
+		got: U256,
+	},
+	/// Transaction with the same sender and nonce has been replaced too recently.
+	TooSoonToReplace,
 	/// Transaction was not imported to the queue because limit has been reached.
 	LimitReached,
+	/// Transaction was not imported to the queue because its sender reached per-sender limits.
+	SenderLimitReached,
 	/// Transaction's gas price is below threshold.
 	InsufficientGasPrice {
 		/// Minimal expected gas price
@@ -85,6 +94,13 @@ pub enum TransactionError {
 	InvalidChainId,
 	/// Not enough permissions given by permission contract.
 	NotAllowed,
+	/// Transaction is bigger than the chain allows.
+	TooBig {
+		/// Maximal allowed size
+		limit: usize,
+		/// Transaction size
+		got: usize,
+	},
 }
 
 impl fmt::Display for TransactionError {
@@ -93,8 +109,11 @@ impl fmt::Display for TransactionError {
 		let msg = match *self {
 			AlreadyImported => "Already imported".into(),
 			Old => "No longer valid".into(),
-			TooCheapToReplace => "Gas price too low to replace".into(),
+			TooCheapToReplace { minimal, got } =>
+				format!("Gas price too low to replace. Min={}, Given={}", minimal, got),
+			TooSoonToReplace => "Transaction with the same nonce was replaced too recently".into(),
 			LimitReached => "Transaction limit reached".into(),
+			SenderLimitReached => "Transaction limit of the sender reached".into(),
 			InsufficientGasPrice { minimal, got } =>
 				format!("Insufficient gas price. Min={}, Given={}", minimal, got),
 			InsufficientGas { minimal, got } =>
@@ -110,6 +129,8 @@ impl fmt::Display for TransactionError {
 			CodeBanned => "Contract code is temporarily banned.".into(),
 			InvalidChainId => "Transaction of this chain ID is not allowed on this chain.".into(),
 			NotAllowed => "Sender does not have permissions to execute this type of transction".into(),
+			TooBig { limit, got } =>
+				format!("Transaction too big. Limit={}, Given={}", limit, got),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
@@ -33,7 +33,7 @@ use header::{Header, BlockNumber};
 use state::CleanupMode;
 use spec::CommonParams;
 use transaction::{UnverifiedTransaction, SignedTransaction};
-use engines::{self, Engine};
+use engines::{self, Engine, Seal};
 use evm::Schedule;
 use ethjson;
 use rlp::{self, UntrustedRlp};
@@ -50,6 +50,8 @@ pub const PARITY_GAS_LIMIT_DETERMINANT: U256 = U256([37, 0, 0, 0]);
 const SNAPSHOT_BLOCKS: u64 = 5000;
 /// Maximum number of blocks allowed in an ethash snapshot.
 const MAX_SNAPSHOT_BLOCKS: u64 = 30000;
+/// Number of nonces tried when sealing a block on the CPU.
+const CPU_SEALING_ATTEMPTS: u64 = 1024;
 
 
 /// Ethash params.
@@ -105,6 +107,10 @@ pub struct EthashParams {
 	pub min_gas_price_transition: u64,
 	/// Do not alow transactions with lower gas price.
 	pub min_gas_price: U256,
+	/// Number of first block sealed with ProgPoW instead of Ethash.
+	pub progpow_transition: u64,
+	/// Seal blocks by searching for a nonce on the CPU. Only usable with trivial difficulty (test chains).
+	pub cpu_sealing: bool,
 }
 
 impl From<ethjson::spec::EthashParams> for EthashParams {
@@ -135,6 +141,8 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			max_gas_limit: p.max_gas_limit.map_or(U256::max_value(), Into::into),
 			min_gas_price_transition: p.min_gas_price_transition.map_or(u64::max_value(), Into::into),
 			min_gas_price: p.min_gas_price.map_or(U256::zero(), Into::into),
+			progpow_transition: p.progpow_transition.map_or(u64::max_value(), Into::into),
+			cpu_sealing: p.cpu_sealing.unwrap_or(false),
 		}
 	}
 }
@@ -142,9 +150,9 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 /// Engine using Ethash proof-of-work consensus algorithm, suitable for Ethereum
 /// mainnet chains in the Olympic, Frontier and Homestead eras.
 pub struct Ethash {
-	params: CommonParams,
+	params: Arc<CommonParams>,
 	ethash_params: EthashParams,
-	builtins: BTreeMap<Address, Builtin>,
+	builtins: Arc<BTreeMap<Address, Builtin>>,
 	pow: EthashManager,
 	tx_filter: Option<TransactionFilter>,
 }
@@ -159,10 +167,10 @@ impl Ethash {
 	) -> Arc<Self> {
 		Arc::new(Ethash {
 			tx_filter: TransactionFilter::from_params(&params),
-			params,
+			pow: EthashManager::new(cache_dir, ethash_params.progpow_transition),
+			params: Arc::new(params),
 			ethash_params,
-			builtins,
-			pow: EthashManager::new(cache_dir),
+			builtins: Arc::new(builtins),
 		})
 	}
 }
@@ -188,11 +196,34 @@ impl Engine for Arc<Ethash> {
 	// Two fields - mix
 	fn seal_fields(&self) -> usize { 2 }
 
-	fn params(&self) -> &CommonParams { &self.params }
+	fn params(&self) -> Arc<CommonParams> { self.params.clone() }
 	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.params().registrar.hex()] }
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
-		&self.builtins
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> {
+		self.builtins.clone()
+	}
+
+	fn fork_blocks(&self) -> Vec<BlockNumber> {
+		let mut forks = self.params().fork_blocks();
+		forks.extend(self.builtins().values().map(Builtin::activate_at));
+		let p = &self.ethash_params;
+		forks.extend(&[
+			p.homestead_transition,
+			p.dao_hardfork_transition,
+			p.difficulty_hardfork_transition,
+			p.bomb_defuse_transition,
+			p.eip100b_transition,
+			p.eip150_transition,
+			p.eip160_transition,
+			p.eip161abc_transition,
+			p.eip161d_transition,
+			p.ecip1010_pause_transition,
+			p.ecip1010_continue_transition,
+			p.max_gas_limit_transition,
+			p.min_gas_price_transition,
+			p.progpow_transition,
+		]);
+		forks
 	}
 
 	/// Additional engine-specific information for the user/developer concerning `header`.
@@ -292,6 +323,43 @@ impl Engine for Arc<Ethash> {
 		Ok(())
 	}
 
+	fn seals_internally(&self) -> Option<bool> {
+		if self.ethash_params.cpu_sealing { Some(true) } else { None }
+	}
+
+	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
+		if !self.ethash_params.cpu_sealing {
+			return Seal::None;
+		}
+
+		let header = &block.fields().header;
+		match self.cpu_seal(header) {
+			Some(seal) => Seal::Regular(seal),
+			None => {
+				warn!(target: "ethash", "No seal found for block #{} within {} nonces; difficulty too high for CPU sealing.", header.number(), CPU_SEALING_ATTEMPTS);
+				Seal::None
+			},
+		}
+	}
+
+	fn progpow_transition(&self) -> Option<BlockNumber> {
+		let transition = self.ethash_params.progpow_transition;
+		if transition == u64::max_value() { None } else { Some(transition) }
+	}
+
+	fn pow_difficulty(&self, number: BlockNumber, bare_hash: &H256, nonce: u64, mix_hash: &H256) -> Option<U256> {
+		let result = self.pow.compute_light(number, &bare_hash.0, nonce);
+		if H256(result.mix_hash) != *mix_hash {
+			return None;
+		}
+		Some(Ethash::boundary_to_difficulty(&H256(result.value)))
+	}
+
+	fn uncle_reward(&self, uncle: &Header, block_number: BlockNumber) -> Option<U256> {
+		let (eras, reward) = ecip1017_eras_block_reward(self.ethash_params.ecip1017_era_rounds, self.params().block_reward, block_number);
+		Some(Ethash::uncle_reward_in_era(eras, reward, uncle.number(), block_number))
+	}
+
 	/// Apply the block reward on finalisation of the block.
 	/// This assumes that all uncles are valid uncles (i.e. of at least one generation before the current).
 	fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
@@ -320,23 +388,12 @@ impl Engine for Arc<Ethash> {
 		let current_number = fields.header.number();
 		for u in fields.uncles.iter() {
 			let uncle_author = u.author().clone();
-			let result_uncle_reward: U256;
-
-			if eras == 0 {
-				result_uncle_reward = (reward * U256::from(8 + u.number() - current_number)).shr(3);
-				fields.state.add_balance(
-					u.author(),
-					&result_uncle_reward,
-					CleanupMode::NoEmpty
-				)
-			} else {
-				result_uncle_reward = reward.shr(5);
-				fields.state.add_balance(
-					u.author(),
-					&result_uncle_reward,
-					CleanupMode::NoEmpty
-				)
-			}?;
+			let result_uncle_reward = Ethash::uncle_reward_in_era(eras, reward, u.number(), current_number);
+			fields.state.add_balance(
+				u.author(),
+				&result_uncle_reward,
+				CleanupMode::NoEmpty
+			)?;
 
 			// Trace uncle rewards
 			if tracing_enabled {
@@ -371,7 +428,8 @@ impl Engine for Arc<Ethash> {
 		let difficulty = Ethash::boundary_to_difficulty(&H256(quick_get_difficulty(
 			&header.bare_hash().0,
 			header.nonce().low_u64(),
-			&header.mix_hash().0
+			&header.mix_hash().0,
+			self.pow.is_progpow(header.number())
 		)));
 		if &difficulty < header.difficulty() {
 			return Err(From::from(BlockError::InvalidProofOfWork(OutOfBounds { min: Some(header.difficulty().clone()), max: None, found: difficulty })));
@@ -499,6 +557,28 @@ fn ecip1017_eras_block_reward(era_rounds: u64, mut reward: U256, block_number:u6
 
 #[cfg_attr(feature="dev", allow(wrong_self_convention))]
 impl Ethash {
+	/// Search for a nonce meeting the difficulty of given header.
+	fn cpu_seal(&self, header: &Header) -> Option<Vec<Bytes>> {
+		let bare_hash = header.bare_hash();
+		for nonce in 0..CPU_SEALING_ATTEMPTS {
+			let result = self.pow.compute_light(header.number(), &bare_hash.0, nonce);
+			if &Ethash::boundary_to_difficulty(&H256(result.value)) >= header.difficulty() {
+				return Some(vec![rlp::encode(&H256(result.mix_hash)).into_vec(), rlp::encode(&H64::from(nonce)).into_vec()]);
+			}
+		}
+		None
+	}
+
+	/// Reward of an uncle included in given block, given the block reward of the era.
+	fn uncle_reward_in_era(eras: u64, reward: U256, uncle_number: BlockNumber, block_number: BlockNumber) -> U256 {
+		use std::ops::Shr;
+		if eras == 0 {
+			(reward * U256::from(8 + uncle_number - block_number)).shr(3)
+		} else {
+			reward.shr(5)
+		}
+	}
+
 	fn calculate_difficulty(&self, header: &Header, parent: &Header) -> U256 {
 		const EXP_DIFF_PERIOD: u64 = 100000;
 		if header.number() == 0 {
@@ -650,6 +730,7 @@ mod tests {
 		let mut uncle = Header::new();
 		let uncle_author: Address = "ef2d6d194084c2de36e0dabfce45d046b37d1106".into();
 		uncle.set_author(uncle_author);
+		assert_eq!(engine.uncle_reward(&uncle, 1), Some("3cb71f51fc558000".into()));
 		b.push_uncle(uncle).unwrap();
 
 		let b = b.close();
@@ -657,6 +738,38 @@ mod tests {
 		assert_eq!(b.state().balance(&uncle_author).unwrap(), "3cb71f51fc558000".into());
 	}
 
+	#[test]
+	fn should_seal_and_verify_progpow_blocks() {
+		let spec = test_spec();
+		let ethparams = EthashParams {
+			minimum_difficulty: U256::from(1),
+			progpow_transition: 2,
+			cpu_sealing: true,
+			..get_default_ethash_params()
+		};
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
+		assert_eq!(ethash.seals_internally(), Some(true));
+		assert_eq!(ethash.progpow_transition(), Some(2));
+
+		let mut header = Header::default();
+		header.set_difficulty(U256::from(16));
+		for number in 1..3 {
+			header.set_number(number);
+			let seal = ethash.cpu_seal(&header).unwrap();
+			header.set_seal(seal);
+			assert!(ethash.verify_block_basic(&header, None).is_ok());
+			assert!(ethash.verify_block_unordered(&header, None).is_ok());
+		}
+
+		// progpow seal is not a valid ethash seal
+		let ethparams = EthashParams {
+			minimum_difficulty: U256::from(1),
+			..get_default_ethash_params()
+		};
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
+		assert!(ethash.verify_block_unordered(&header, None).is_err());
+	}
+
 	#[test]
 	fn has_valid_metadata() {
 		let engine = test_spec().engine;
@@ -828,7 +941,7 @@ mod tests {
 	fn difficulty_frontier() {
 		let spec = new_homestead_test();
 		let ethparams = get_default_ethash_params();
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 
 		let mut parent_header = Header::default();
 		parent_header.set_number(1000000);
@@ -846,7 +959,7 @@ mod tests {
 	fn difficulty_homestead() {
 		let spec = new_homestead_test();
 		let ethparams = get_default_ethash_params();
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 
 		let mut parent_header = Header::default();
 		parent_header.set_number(1500000);
@@ -899,7 +1012,7 @@ mod tests {
 			ecip1010_pause_transition: 3000000,
 			..get_default_ethash_params()
 		};
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 
 		let mut parent_header = Header::default();
 		parent_header.set_number(3500000);
@@ -933,7 +1046,7 @@ mod tests {
 			ecip1010_continue_transition: 5000000,
 			..get_default_ethash_params()
 		};
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 
 		let mut parent_header = Header::default();
 		parent_header.set_number(5000102);
@@ -979,7 +1092,7 @@ mod tests {
 	fn gas_limit_is_multiple_of_determinant() {
 		let spec = new_homestead_test();
 		let ethparams = get_default_ethash_params();
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 		let mut parent = Header::new();
 		let mut header = Header::new();
 		header.set_number(1);
@@ -1023,7 +1136,7 @@ mod tests {
 	fn difficulty_max_timestamp() {
 		let spec = new_homestead_test();
 		let ethparams = get_default_ethash_params();
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 
 		let mut parent_header = Header::default();
 		parent_header.set_number(1000000);
@@ -1051,7 +1164,7 @@ mod tests {
 		header.set_number(parent_header.number() + 1);
 		header.set_gas_limit(100_001.into());
 		header.set_difficulty(ethparams.minimum_difficulty);
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 		assert!(ethash.verify_block_family(&header, &parent_header, None).is_ok());
 
 		parent_header.set_number(9);
@@ -1106,7 +1219,7 @@ mod tests {
 			nonce: U256::zero(),
 		}.sign(keypair.secret(), None).into();
 
-		let ethash = Ethash::new(&::std::env::temp_dir(), spec.params().clone(), ethparams, BTreeMap::new());
+		let ethash = Ethash::new(&::std::env::temp_dir(), (*spec.params()).clone(), ethparams, BTreeMap::new());
 		assert!(ethash.verify_transaction_basic(&tx1, &header).is_ok());
 		assert!(ethash.verify_transaction_basic(&tx2, &header).is_ok());
 
@@ -19,7 +19,7 @@
 use bigint::prelude::{U256, U512};
 use util::{Bytes, Address, trie};
 use vm;
-use trace::{VMTrace, FlatTrace};
+use trace::{VMTrace, StepTrace, FlatTrace};
 use log_entry::LogEntry;
 use state_diff::StateDiff;
 
@@ -64,10 +64,27 @@ pub struct Executed {
 	pub trace: Vec<FlatTrace>,
 	/// The VM trace of this transaction.
 	pub vm_trace: Option<VMTrace>,
+	/// The execution steps of this transaction, if recorded.
+	pub step_trace: Option<StepTrace>,
 	/// The state diff, if we traced it.
 	pub state_diff: Option<StateDiff>,
 }
 
+/// Gas required by a transaction, as found by `estimate_gas`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct GasEstimate {
+	/// Lowest gas limit with which the transaction behaves as with unlimited gas.
+	///
+	/// Exceeds `intrinsic + execution` by the gas nested calls keep back under the 63/64 rule.
+	pub gas: U256,
+	/// Gas charged before execution (base cost and transaction data).
+	pub intrinsic: U256,
+	/// Gas consumed by the execution itself, before refunds.
+	pub execution: U256,
+	/// Gas refunded after the execution.
+	pub refund: U256,
+}
+
 /// Result of executing the transaction.
 #[derive(PartialEq, Debug, Clone)]
 pub enum ExecutionError {
@@ -158,6 +175,8 @@ pub enum CallError {
 	Exceptional,
 	/// Corrupt state.
 	StateCorrupt,
+	/// The execution didn't finish within the allowed time.
+	Timeout,
 	/// Error executing.
 	Execution(ExecutionError),
 }
@@ -177,6 +196,7 @@ impl fmt::Display for CallError {
 			StatePruned => "Couldn't find the transaction block's state in the chain".into(),
 			Exceptional => "An exception happened in the execution".into(),
 			StateCorrupt => "Stored state found to be corrupted.".into(),
+			Timeout => "The execution took too long and was aborted".into(),
 			Execution(ref e) => format!("{}", e),
 		};
 
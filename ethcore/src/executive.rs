@@ -17,6 +17,7 @@
 //! Transaction Execution environment.
 use std::cmp;
 use std::sync::Arc;
+use std::time::Instant;
 use hash::keccak;
 use bigint::prelude::{U256, U512};
 use bigint::hash::H256;
@@ -29,7 +30,7 @@ use evm::{CallType, Factory, Finalize, FinalizationResult};
 use vm::{self, Ext, CreateContractAddress, ReturnData, CleanDustMode, ActionParams, ActionValue};
 use wasm;
 use externalities::*;
-use trace::{self, FlatTrace, VMTrace, Tracer, VMTracer};
+use trace::{self, FlatTrace, VMTrace, StepTrace, Tracer, VMTracer};
 use transaction::{Action, SignedTransaction};
 use crossbeam;
 pub use executed::{Executed, ExecutionResult};
@@ -174,6 +175,7 @@ pub struct Executive<'a, B: 'a + StateBackend, E: 'a + Engine + ?Sized> {
 	engine: &'a E,
 	depth: usize,
 	static_flag: bool,
+	deadline: Option<Instant>,
 }
 
 impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
@@ -185,6 +187,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 			engine: engine,
 			depth: 0,
 			static_flag: false,
+			deadline: None,
 		}
 	}
 
@@ -196,9 +199,18 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 			engine: engine,
 			depth: parent_depth + 1,
 			static_flag: static_flag,
+			deadline: None,
 		}
 	}
 
+	/// Aborts the execution once given deadline passes.
+	///
+	/// The VM checks the deadline periodically, so the execution may run slightly longer.
+	pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+		self.deadline = deadline;
+		self
+	}
+
 	/// Creates `Externalities` from `Executive`.
 	pub fn as_externalities<'any, T, V>(
 		&'any mut self,
@@ -211,6 +223,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 	) -> Externalities<'any, T, V, B, E> where T: Tracer, V: VMTracer {
 		let is_static = self.static_flag || static_call;
 		Externalities::new(self.state, self.info, self.engine, self.depth, origin_info, substate, output, tracer, vm_tracer, is_static)
+			.with_deadline(self.deadline)
 	}
 
 	/// This function should be used to execute transaction.
@@ -334,7 +347,8 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 		};
 
 		// finalize here!
-		Ok(self.finalize(t, substate, result, output, tracer.drain(), vm_tracer.drain())?)
+		let step_trace = vm_tracer.take_steps();
+		Ok(self.finalize(t, substate, result, output, tracer.drain(), vm_tracer.drain(), step_trace)?)
 	}
 
 	fn exec_vm<T, V>(
@@ -402,14 +416,10 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 			self.state.transfer_balance(&params.sender, &params.address, &val, substate.to_cleanup_mode(&schedule))?;
 		}
 
-		// if destination is builtin, try to execute it
-		if let Some(builtin) = self.engine.builtin(&params.code_address, self.info.number) {
-			// Engines aren't supposed to return builtins until activation, but
-			// prefer to fail rather than silently break consensus.
-			if !builtin.is_active(self.info.number) {
-				panic!("Consensus failure: engine implementation prematurely enabled built-in at {}", params.code_address);
-			}
-
+		// if destination is an active builtin, try to execute it
+		let builtins = self.engine.builtins();
+		let number = self.info.number;
+		if let Some(builtin) = builtins.get(&params.code_address).and_then(|b| if b.is_active(number) { Some(b) } else { None }) {
 			let default = [];
 			let data = if let Some(ref d) = params.data { d as &[u8] } else { &default as &[u8] };
 
@@ -575,7 +585,8 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 		result: vm::Result<(U256, ReturnData)>,
 		output: Bytes,
 		trace: Vec<FlatTrace>,
-		vm_trace: Option<VMTrace>
+		vm_trace: Option<VMTrace>,
+		step_trace: Option<StepTrace>,
 	) -> ExecutionResult {
 		let schedule = self.engine.schedule(self.info.number);
 
@@ -627,6 +638,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 					output: output,
 					trace: trace,
 					vm_trace: vm_trace,
+					step_trace: step_trace,
 					state_diff: None,
 				})
 			},
@@ -642,6 +654,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 					output: output,
 					trace: trace,
 					vm_trace: vm_trace,
+					step_trace: step_trace,
 					state_diff: None,
 				})
 			},
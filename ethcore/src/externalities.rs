@@ -17,6 +17,7 @@
 //! Transaction Execution environment.
 use std::cmp;
 use std::sync::Arc;
+use std::time::Instant;
 use bigint::prelude::U256;
 use bigint::hash::H256;
 use util::*;
@@ -77,6 +78,7 @@ pub struct Externalities<'a, T: 'a, V: 'a, B: 'a, E: 'a + Engine + ?Sized>
 	tracer: &'a mut T,
 	vm_tracer: &'a mut V,
 	static_flag: bool,
+	deadline: Option<Instant>,
 }
 
 impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Externalities<'a, T, V, B, E>
@@ -107,8 +109,15 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Externalities<'a, T, V, B, E>
 			tracer: tracer,
 			vm_tracer: vm_tracer,
 			static_flag: static_flag,
+			deadline: None,
 		}
 	}
+
+	/// Aborts the execution (including nested calls) once given deadline passes.
+	pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+		self.deadline = deadline;
+		self
+	}
 }
 
 impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
@@ -221,7 +230,8 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
 				return ContractCreateResult::Failed
 			}
 		}
-		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.depth, self.static_flag);
+		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.depth, self.static_flag)
+			.with_deadline(self.deadline);
 
 		// TODO: handle internal error separately
 		match ex.create(params, self.substate, &mut None, self.tracer, self.vm_tracer) {
@@ -271,7 +281,8 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
 			params.value = ActionValue::Transfer(value);
 		}
 
-		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.depth, self.static_flag);
+		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.depth, self.static_flag)
+			.with_deadline(self.deadline);
 
 		match ex.call(params, self.substate, BytesRef::Fixed(output), self.tracer, self.vm_tracer) {
 			Ok((gas_left, return_data)) => MessageCallResult::Success(gas_left, return_data),
@@ -384,6 +395,10 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
 		self.substate.sstore_clears_count = self.substate.sstore_clears_count + U256::one();
 	}
 
+	fn deadline_exceeded(&self) -> bool {
+		self.deadline.map_or(false, |deadline| Instant::now() >= deadline)
+	}
+
 	fn trace_next_instruction(&mut self, pc: usize, instruction: u8) -> bool {
 		self.vm_tracer.trace_next_instruction(pc, instruction)
 	}
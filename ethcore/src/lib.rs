@@ -102,6 +102,7 @@ extern crate num;
 extern crate parking_lot;
 extern crate price_info;
 extern crate rand;
+extern crate rayon;
 extern crate rlp;
 extern crate hash;
 extern crate heapsize;
@@ -177,10 +178,12 @@ mod tx_filter;
 
 #[cfg(test)]
 mod tests;
+#[cfg(all(feature="benches", test))]
+mod benches;
 #[cfg(test)]
 #[cfg(feature="json-tests")]
 mod json_tests;
 
 pub use types::*;
-pub use executive::contract_address;
+pub use executive::{contract_address, Executive, TransactOptions};
 pub use evm::CreateContractAddress;
@@ -0,0 +1,61 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Receipts column migration.
+
+use std::sync::Arc;
+use db::{COL_EXTRA, COL_RECEIPTS};
+use blockchain::extras::ExtrasIndex;
+use util::migration::{Error, Migration, Progress, Batch, Config};
+use util::{snappy, Database};
+
+/// Moves block receipts from the extras column into their own column,
+/// compressing each block's receipts with snappy.
+#[derive(Default)]
+pub struct ToV13 {
+	progress: Progress,
+}
+
+impl ToV13 {
+	/// New v13 migration
+	pub fn new() -> ToV13 { ToV13 { progress: Progress::default() } }
+}
+
+impl Migration for ToV13 {
+	fn version(&self) -> u32 {
+		13
+	}
+
+	fn pre_columns(&self) -> Option<u32> { Some(8) }
+
+	fn columns(&self) -> Option<u32> { Some(9) }
+
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		let mut batch = Batch::new(config, col);
+		let mut receipts = Batch::new(config, COL_RECEIPTS);
+		for (key, value) in source.iter(col).into_iter().flat_map(|inner| inner) {
+			self.progress.tick();
+			if col == COL_EXTRA && key.len() == 33 && key[0] == ExtrasIndex::BlockReceipts as u8 {
+				receipts.insert(key.into_vec(), snappy::compress(&value), dest)?;
+			} else {
+				batch.insert(key.into_vec(), value.into_vec(), dest)?;
+			}
+		}
+		batch.commit(dest)?;
+		receipts.commit(dest)
+	}
+}
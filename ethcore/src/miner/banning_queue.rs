@@ -45,13 +45,30 @@ impl Default for Threshold {
 	}
 }
 
+/// Currently banned senders, recipients and contract codes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BanList {
+	/// Banned senders (including senders of repeatedly invalid transactions).
+	pub senders: Vec<Address>,
+	/// Banned recipients.
+	pub recipients: Vec<Address>,
+	/// Hashes of banned contract codes.
+	pub codes: Vec<H256>,
+}
+
 /// Transaction queue with banlist.
 pub struct BanningTransactionQueue {
 	queue: TransactionQueue,
 	ban_threshold: Threshold,
+	ban_lifetime: u32,
 	senders_bans: TransientHashMap<Address, Count>,
 	recipients_bans: TransientHashMap<Address, Count>,
 	codes_bans: TransientHashMap<H256, Count>,
+	invalid_threshold: Threshold,
+	invalid_ban_lifetime: u32,
+	invalid_senders: TransientHashMap<Address, Count>,
+	replace_cooldown: u32,
+	replacements: TransientHashMap<(Address, U256), ()>,
 }
 
 impl BanningTransactionQueue {
@@ -62,12 +79,77 @@ impl BanningTransactionQueue {
 		BanningTransactionQueue {
 			queue: queue,
 			ban_threshold: ban_threshold,
+			ban_lifetime: ban_lifetime_sec,
 			senders_bans: TransientHashMap::new(ban_lifetime_sec),
 			recipients_bans: TransientHashMap::new(ban_lifetime_sec),
 			codes_bans: TransientHashMap::new(ban_lifetime_sec),
+			invalid_threshold: Threshold::NeverBan,
+			invalid_ban_lifetime: ban_lifetime_sec,
+			invalid_senders: TransientHashMap::new(ban_lifetime_sec),
+			replace_cooldown: 0,
+			replacements: TransientHashMap::new(0),
 		}
 	}
 
+	/// Bans senders after given number of transactions failing validation
+	/// (e.g. underpriced or above the balance) for `ban_lifetime`.
+	pub fn set_invalid_sender_banning(&mut self, threshold: Threshold, ban_lifetime: Duration) {
+		let ban_lifetime_sec = ban_lifetime.as_secs() as u32;
+		assert!(ban_lifetime_sec > 0, "Lifetime has to be specified in seconds.");
+		self.invalid_threshold = threshold;
+		self.invalid_ban_lifetime = ban_lifetime_sec;
+		self.invalid_senders = TransientHashMap::new(ban_lifetime_sec);
+	}
+
+	/// Sets minimal time between replacements of transactions with the same `(sender, nonce)`.
+	/// Zero duration disables the cooldown.
+	pub fn set_replace_cooldown(&mut self, cooldown: Duration) {
+		self.replace_cooldown = cooldown.as_secs() as u32;
+		self.replacements = TransientHashMap::new(self.replace_cooldown);
+	}
+
+	/// Returns senders, recipients and codes which are currently banned.
+	pub fn bans(&self) -> BanList {
+		fn banned<K: Clone + ::std::hash::Hash + Eq>(threshold: &Threshold, bans: &TransientHashMap<K, Count>) -> Vec<K> {
+			match *threshold {
+				Threshold::BanAfter(threshold) => bans.direct().iter()
+					.filter(|&(_, count)| *count > threshold)
+					.map(|(key, _)| key.clone())
+					.collect(),
+				Threshold::NeverBan => Vec::new(),
+			}
+		}
+
+		let mut senders = banned(&self.ban_threshold, &self.senders_bans);
+		senders.extend(banned(&self.invalid_threshold, &self.invalid_senders));
+		senders.sort();
+		senders.dedup();
+		let mut recipients = banned(&self.ban_threshold, &self.recipients_bans);
+		recipients.sort();
+		let mut codes = banned(&self.ban_threshold, &self.codes_bans);
+		codes.sort();
+
+		BanList { senders: senders, recipients: recipients, codes: codes }
+	}
+
+	/// Lifts all bans and replacement cooldowns.
+	pub fn clear_bans(&mut self) {
+		self.senders_bans = TransientHashMap::new(self.ban_lifetime);
+		self.recipients_bans = TransientHashMap::new(self.ban_lifetime);
+		self.codes_bans = TransientHashMap::new(self.ban_lifetime);
+		self.invalid_senders = TransientHashMap::new(self.invalid_ban_lifetime);
+		self.replacements = TransientHashMap::new(self.replace_cooldown);
+	}
+
+	/// Removes expired bans and replacement cooldowns.
+	pub fn prune_bans(&mut self) {
+		self.senders_bans.prune();
+		self.recipients_bans.prune();
+		self.codes_bans.prune();
+		self.invalid_senders.prune();
+		self.replacements.prune();
+	}
+
 	/// Borrows internal queue.
 	/// NOTE: you can insert transactions to the queue even
 	/// if they would be rejected because of ban otherwise.
@@ -114,7 +196,57 @@ impl BanningTransactionQueue {
 				}
 			}
 		}
-		self.queue.add(transaction, TransactionOrigin::External, time, None, details_provider)
+
+		let sender = transaction.sender();
+		if let Threshold::BanAfter(threshold) = self.invalid_threshold {
+			let count = self.invalid_senders.direct().get(&sender).cloned().unwrap_or(0);
+			if count > threshold {
+				debug!(target: "txqueue", "Ignoring transaction {:?} because sender sent too many invalid transactions.", transaction.hash());
+				return Err(Error::Transaction(TransactionError::SenderBanned));
+			}
+		}
+
+		let replaced = (sender, transaction.nonce);
+		let is_replacement = self.replace_cooldown > 0 && self.queue.contains_nonce(&sender, &transaction.nonce);
+		if is_replacement {
+			self.replacements.prune();
+		}
+		let result = if is_replacement && self.replacements.direct().contains_key(&replaced) {
+			debug!(target: "txqueue", "Ignoring transaction {:?} because it replaces a recently replaced transaction.", transaction.hash());
+			Err(Error::Transaction(TransactionError::TooSoonToReplace))
+		} else {
+			self.queue.add(transaction, TransactionOrigin::External, time, None, details_provider)
+		};
+
+		match result {
+			Ok(_) if is_replacement => {
+				self.replacements.insert(replaced, ());
+			},
+			Err(Error::Transaction(ref err)) if is_invalid(err) => {
+				self.note_invalid_sender(sender);
+			},
+			_ => {},
+		}
+		result
+	}
+
+	/// Records a transaction of given sender which failed validation.
+	/// Reaching the threshold removes all existing transactions of this sender from the queue.
+	fn note_invalid_sender(&mut self, address: Address) -> bool {
+		let threshold = match self.invalid_threshold {
+			Threshold::BanAfter(threshold) => threshold,
+			Threshold::NeverBan => return false,
+		};
+		let count = {
+			let mut count = self.invalid_senders.entry(address).or_insert_with(|| 0);
+			*count = count.saturating_add(1);
+			*count
+		};
+		if count > threshold {
+			self.cull(address, !U256::zero());
+			return true;
+		}
+		false
 	}
 
 	/// Ban transaction with given hash.
@@ -196,6 +328,19 @@ impl BanningTransactionQueue {
 	}
 }
 
+/// Returns `true` for errors which indicate a cheap attempt to churn the queue.
+fn is_invalid(err: &TransactionError) -> bool {
+	match *err {
+		TransactionError::TooCheapToReplace { .. }
+			| TransactionError::TooSoonToReplace
+			| TransactionError::InsufficientGasPrice { .. }
+			| TransactionError::InsufficientGas { .. }
+			| TransactionError::InsufficientBalance { .. }
+			| TransactionError::GasLimitExceeded { .. } => true,
+		_ => false,
+	}
+}
+
 impl Deref for BanningTransactionQueue {
 	type Target = TransactionQueue;
 
@@ -214,8 +359,8 @@ mod tests {
 	use std::time::Duration;
 	use rustc_hex::FromHex;
 	use hash::keccak;
-	use super::{BanningTransactionQueue, Threshold};
-	use ethkey::{Random, Generator};
+	use super::{BanningTransactionQueue, BanList, Threshold};
+	use ethkey::{Random, Generator, KeyPair};
 	use transaction::{Transaction, SignedTransaction, Action};
 	use error::{Error, TransactionError};
 	use client::TransactionImportResult;
@@ -233,13 +378,16 @@ mod tests {
 	}
 
 	fn transaction(action: Action) -> SignedTransaction {
-		let keypair = Random.generate().unwrap();
+		transaction_with_gas_price(&Random.generate().unwrap(), action, 10)
+	}
+
+	fn transaction_with_gas_price(keypair: &KeyPair, action: Action, gas_price: u64) -> SignedTransaction {
 		Transaction {
 			action: action,
 			value: U256::from(100),
 			data: "3331600055".from_hex().unwrap(),
 			gas: U256::from(100_000),
-			gas_price: U256::from(10),
+			gas_price: U256::from(gas_price),
 			nonce: U256::from(0),
 		}.sign(keypair.secret(), None)
 	}
@@ -332,4 +480,46 @@ mod tests {
 		assert!(banlist2, "Threshold should be reached - banned.");
 		assert_eq!(unwrap_err(import2), TransactionError::CodeBanned);
 	}
+
+	#[test]
+	fn should_ban_senders_of_invalid_transactions() {
+		// given
+		let tx = transaction(Action::Create);
+		let mut txq = queue();
+		txq.set_invalid_sender_banning(Threshold::BanAfter(1), Duration::from_secs(180));
+		txq.set_minimal_gas_price(100.into());
+		let import1 = txq.add_with_banlist(tx.clone(), 0, &default_tx_provider());
+		let import2 = txq.add_with_banlist(tx.clone(), 0, &default_tx_provider());
+		assert_eq!(unwrap_err(import1), TransactionError::InsufficientGasPrice { minimal: 100.into(), got: 10.into() });
+		assert_eq!(unwrap_err(import2), TransactionError::InsufficientGasPrice { minimal: 100.into(), got: 10.into() });
+
+		// when
+		txq.set_minimal_gas_price(0.into());
+		let import3 = txq.add_with_banlist(tx.clone(), 0, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_err(import3), TransactionError::SenderBanned);
+		assert_eq!(txq.bans(), BanList { senders: vec![tx.sender()], ..Default::default() });
+		txq.clear_bans();
+		assert_eq!(txq.bans(), BanList::default());
+		assert_eq!(txq.add_with_banlist(tx.clone(), 0, &default_tx_provider()).unwrap(), TransactionImportResult::Current);
+	}
+
+	#[test]
+	fn should_not_allow_replacements_during_cooldown() {
+		// given
+		let keypair = Random.generate().unwrap();
+		let mut txq = queue();
+		txq.set_replace_cooldown(Duration::from_secs(180));
+		txq.add_with_banlist(transaction_with_gas_price(&keypair, Action::Create, 10), 0, &default_tx_provider()).unwrap();
+
+		// when
+		let import1 = txq.add_with_banlist(transaction_with_gas_price(&keypair, Action::Create, 20), 0, &default_tx_provider());
+		let import2 = txq.add_with_banlist(transaction_with_gas_price(&keypair, Action::Create, 40), 0, &default_tx_provider());
+
+		// then
+		assert_eq!(import1.unwrap(), TransactionImportResult::Current);
+		assert_eq!(unwrap_err(import2), TransactionError::TooSoonToReplace);
+		assert_eq!(txq.top_transactions()[0].gas_price, 20.into());
+	}
 }
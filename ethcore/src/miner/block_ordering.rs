@@ -0,0 +1,247 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Ordering of transactions included in authored blocks.
+
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use transaction::SignedTransaction;
+
+/// Transaction ready to be included in a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadyTransaction {
+	/// The transaction.
+	pub transaction: SignedTransaction,
+	/// Position of the transaction in the order of arrival to the queue (lower = earlier).
+	pub arrival: u64,
+	/// Whether the transaction is local.
+	pub is_local: bool,
+}
+
+/// Decides in which order ready transactions are pushed to an authored block.
+pub trait BlockOrdering: Send + Sync {
+	/// Orders given transactions.
+	///
+	/// Transactions are given in the priority order of the queue. Transactions of
+	/// a single sender come in increasing nonce order and have to stay in that order,
+	/// otherwise the later ones will be rejected by the block.
+	fn order(&self, transactions: Vec<ReadyTransaction>) -> Vec<SignedTransaction>;
+}
+
+/// Built-in block ordering strategies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlockOrderingStrategy {
+	/// Keep the priority order of the transaction queue.
+	Queue,
+	/// Highest gas price first, respecting nonce order of each sender.
+	GasPrice,
+	/// Earliest arrival first, respecting nonce order of each sender.
+	Arrival,
+	/// One transaction of each sender in turns (ordered by gas price within a turn).
+	RoundRobin,
+}
+
+impl Default for BlockOrderingStrategy {
+	fn default() -> Self {
+		BlockOrderingStrategy::Queue
+	}
+}
+
+impl FromStr for BlockOrderingStrategy {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"queue" => Ok(BlockOrderingStrategy::Queue),
+			"gas_price" => Ok(BlockOrderingStrategy::GasPrice),
+			"arrival" => Ok(BlockOrderingStrategy::Arrival),
+			"round_robin" => Ok(BlockOrderingStrategy::RoundRobin),
+			other => Err(format!("Invalid block ordering: {}", other)),
+		}
+	}
+}
+
+impl fmt::Display for BlockOrderingStrategy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match *self {
+			BlockOrderingStrategy::Queue => "queue",
+			BlockOrderingStrategy::GasPrice => "gas_price",
+			BlockOrderingStrategy::Arrival => "arrival",
+			BlockOrderingStrategy::RoundRobin => "round_robin",
+		})
+	}
+}
+
+impl BlockOrdering for BlockOrderingStrategy {
+	fn order(&self, transactions: Vec<ReadyTransaction>) -> Vec<SignedTransaction> {
+		// Local transactions always go first.
+		match *self {
+			BlockOrderingStrategy::Queue => transactions.into_iter().map(|tx| tx.transaction).collect(),
+			BlockOrderingStrategy::GasPrice => merge_by(transactions, |tx, _| {
+				(tx.is_local, tx.transaction.gas_price, u64::max_value() - tx.arrival)
+			}),
+			BlockOrderingStrategy::Arrival => merge_by(transactions, |tx, _| {
+				(tx.is_local, u64::max_value() - tx.arrival)
+			}),
+			BlockOrderingStrategy::RoundRobin => merge_by(transactions, |tx, turn| {
+				(tx.is_local, usize::max_value() - turn, tx.transaction.gas_price, u64::max_value() - tx.arrival)
+			}),
+		}
+	}
+}
+
+/// Transaction at the head of a sender's queue, ordered by priority.
+struct Head<K> {
+	priority: K,
+	sender: usize,
+}
+
+impl<K: Ord> PartialEq for Head<K> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<K: Ord> Eq for Head<K> {}
+
+impl<K: Ord> PartialOrd for Head<K> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<K: Ord> Ord for Head<K> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.priority.cmp(&other.priority).then_with(|| other.sender.cmp(&self.sender))
+	}
+}
+
+/// Merges nonce-ordered transactions of all senders, each time picking the head transaction
+/// with the highest priority. Priority is computed from the transaction and its position
+/// within transactions of the sender.
+fn merge_by<K, F>(transactions: Vec<ReadyTransaction>, priority: F) -> Vec<SignedTransaction> where
+	K: Ord,
+	F: Fn(&ReadyTransaction, usize) -> K,
+{
+	let len = transactions.len();
+	let mut senders = HashMap::new();
+	let mut by_sender: Vec<Vec<ReadyTransaction>> = Vec::new();
+	for tx in transactions {
+		let index = *senders.entry(tx.transaction.sender()).or_insert_with(|| {
+			by_sender.push(Vec::new());
+			by_sender.len() - 1
+		});
+		by_sender[index].push(tx);
+	}
+	for txs in &mut by_sender {
+		txs.sort_by_key(|tx| tx.transaction.nonce);
+		txs.reverse();
+	}
+
+	let mut heap = BinaryHeap::with_capacity(by_sender.len());
+	for (sender, txs) in by_sender.iter().enumerate() {
+		if let Some(tx) = txs.last() {
+			heap.push(Head { priority: priority(tx, 0), sender: sender });
+		}
+	}
+
+	let mut taken = vec![0; by_sender.len()];
+	let mut ordered = Vec::with_capacity(len);
+	while let Some(Head { sender, .. }) = heap.pop() {
+		let tx = by_sender[sender].pop().expect("only senders with pending transactions are in the heap; qed");
+		ordered.push(tx.transaction);
+		taken[sender] += 1;
+		if let Some(next) = by_sender[sender].last() {
+			heap.push(Head { priority: priority(next, taken[sender]), sender: sender });
+		}
+	}
+	ordered
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Random, Generator, KeyPair};
+	use transaction::{Transaction, Action, SignedTransaction};
+	use super::{BlockOrdering, BlockOrderingStrategy, ReadyTransaction};
+
+	fn tx(keypair: &KeyPair, nonce: u64, gas_price: u64, arrival: u64) -> ReadyTransaction {
+		ReadyTransaction {
+			transaction: Transaction {
+				action: Action::Create,
+				value: 0.into(),
+				data: vec![],
+				gas: 21_000.into(),
+				gas_price: gas_price.into(),
+				nonce: nonce.into(),
+			}.sign(keypair.secret(), None),
+			arrival: arrival,
+			is_local: false,
+		}
+	}
+
+	fn transactions() -> Vec<ReadyTransaction> {
+		let (a, b) = (Random.generate().unwrap(), Random.generate().unwrap());
+		vec![tx(&a, 0, 1, 0), tx(&b, 0, 5, 2), tx(&a, 1, 10, 1), tx(&b, 1, 3, 3)]
+	}
+
+	fn order(strategy: BlockOrderingStrategy, txs: &[ReadyTransaction]) -> Vec<usize> {
+		let hashes: Vec<_> = txs.iter().map(|tx| tx.transaction.hash()).collect();
+		strategy.order(txs.to_vec())
+			.iter()
+			.map(|tx: &SignedTransaction| hashes.iter().position(|h| *h == tx.hash()).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn should_keep_queue_order() {
+		assert_eq!(order(BlockOrderingStrategy::Queue, &transactions()), vec![0, 1, 2, 3]);
+	}
+
+	#[test]
+	fn should_order_by_gas_price() {
+		assert_eq!(order(BlockOrderingStrategy::GasPrice, &transactions()), vec![1, 3, 0, 2]);
+	}
+
+	#[test]
+	fn should_order_by_arrival() {
+		assert_eq!(order(BlockOrderingStrategy::Arrival, &transactions()), vec![0, 2, 1, 3]);
+	}
+
+	#[test]
+	fn should_order_senders_in_turns() {
+		assert_eq!(order(BlockOrderingStrategy::RoundRobin, &transactions()), vec![1, 0, 2, 3]);
+	}
+
+	#[test]
+	fn should_put_local_transactions_first() {
+		let mut txs = transactions();
+		txs[0].is_local = true;
+		txs[2].is_local = true;
+		assert_eq!(order(BlockOrderingStrategy::GasPrice, &txs), vec![0, 2, 1, 3]);
+	}
+
+	#[test]
+	fn should_parse_strategies() {
+		for strategy in &[BlockOrderingStrategy::Queue, BlockOrderingStrategy::GasPrice, BlockOrderingStrategy::Arrival, BlockOrderingStrategy::RoundRobin] {
+			assert_eq!(strategy.to_string().parse::<BlockOrderingStrategy>(), Ok(*strategy));
+		}
+		assert!("fifo".parse::<BlockOrderingStrategy>().is_err());
+	}
+}
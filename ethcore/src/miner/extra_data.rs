@@ -0,0 +1,214 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Templates of extra data expanded when sealing authored blocks.
+
+use std::fmt;
+use std::str::FromStr;
+
+use util::Bytes;
+
+/// Values substituted for template variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variables<'a> {
+	/// Client version (`{version}`).
+	pub version: &'a str,
+	/// Chain name (`{chain}`).
+	pub chain: &'a str,
+	/// Pool tag (`{tag}`).
+	pub tag: &'a str,
+	/// Number of blocks sealed so far (`{counter}`).
+	pub counter: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+	Literal(String),
+	Version,
+	Chain,
+	Tag,
+	Counter,
+}
+
+/// Error parsing or validating extra data template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtraDataError {
+	/// Template refers to unknown variable.
+	UnknownVariable(String),
+	/// Variable is not closed with `}`.
+	Unclosed,
+	/// Unmatched `}` outside of a variable.
+	Unmatched,
+	/// Expanded extra data exceeds engine limit.
+	TooLong {
+		/// Maximal size of extra data.
+		max: usize,
+		/// Size of expanded extra data.
+		found: usize,
+	},
+}
+
+impl fmt::Display for ExtraDataError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ExtraDataError::UnknownVariable(ref name) => write!(f, "Unknown variable {{{}}}. Expected one of: {{version}}, {{chain}}, {{tag}}, {{counter}}.", name),
+			ExtraDataError::Unclosed => write!(f, "Variable is not closed with '}}'."),
+			ExtraDataError::Unmatched => write!(f, "Unmatched '}}'. Use '}}}}' for a literal brace."),
+			ExtraDataError::TooLong { max, found } => write!(f, "Expanded extra data is {} bytes long, the maximum is {}.", found, max),
+		}
+	}
+}
+
+/// Extra data with `{version}`, `{chain}`, `{tag}` and `{counter}` variables.
+///
+/// Literal braces are written as `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtraDataTemplate {
+	template: String,
+	parts: Vec<Part>,
+}
+
+impl ExtraDataTemplate {
+	/// Parses the template.
+	pub fn new(template: &str) -> Result<Self, ExtraDataError> {
+		let mut parts = Vec::new();
+		let mut literal = String::new();
+		let mut chars = template.chars().peekable();
+
+		while let Some(c) = chars.next() {
+			match c {
+				'{' if chars.peek() == Some(&'{') => {
+					chars.next();
+					literal.push('{');
+				},
+				'}' if chars.peek() == Some(&'}') => {
+					chars.next();
+					literal.push('}');
+				},
+				'}' => return Err(ExtraDataError::Unmatched),
+				'{' => {
+					let mut name = String::new();
+					let mut closed = false;
+					while let Some(c) = chars.next() {
+						if c == '}' {
+							closed = true;
+							break;
+						}
+						name.push(c);
+					}
+					if !closed {
+						return Err(ExtraDataError::Unclosed);
+					}
+
+					let part = match name.as_str() {
+						"version" => Part::Version,
+						"chain" => Part::Chain,
+						"tag" => Part::Tag,
+						"counter" => Part::Counter,
+						_ => return Err(ExtraDataError::UnknownVariable(name)),
+					};
+					if !literal.is_empty() {
+						parts.push(Part::Literal(::std::mem::replace(&mut literal, String::new())));
+					}
+					parts.push(part);
+				},
+				c => literal.push(c),
+			}
+		}
+		if !literal.is_empty() {
+			parts.push(Part::Literal(literal));
+		}
+
+		Ok(ExtraDataTemplate {
+			template: template.into(),
+			parts: parts,
+		})
+	}
+
+	/// Returns the original template.
+	pub fn template(&self) -> &str {
+		&self.template
+	}
+
+	/// Expands the template using given variables.
+	pub fn expand(&self, variables: &Variables) -> Bytes {
+		let mut result = String::new();
+		for part in &self.parts {
+			match *part {
+				Part::Literal(ref literal) => result.push_str(literal),
+				Part::Version => result.push_str(variables.version),
+				Part::Chain => result.push_str(variables.chain),
+				Part::Tag => result.push_str(variables.tag),
+				Part::Counter => result.push_str(&variables.counter.to_string()),
+			}
+		}
+		result.into_bytes()
+	}
+
+	/// Expands the template and checks that the result fits in `max` bytes.
+	pub fn expand_checked(&self, variables: &Variables, max: usize) -> Result<Bytes, ExtraDataError> {
+		let extra_data = self.expand(variables);
+		match extra_data.len() {
+			found if found > max => Err(ExtraDataError::TooLong { max: max, found: found }),
+			_ => Ok(extra_data),
+		}
+	}
+}
+
+impl FromStr for ExtraDataTemplate {
+	type Err = ExtraDataError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		ExtraDataTemplate::new(s)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ExtraDataTemplate, ExtraDataError, Variables};
+
+	fn variables() -> Variables<'static> {
+		Variables {
+			version: "1.8.0",
+			chain: "foundation",
+			tag: "pool",
+			counter: 42,
+		}
+	}
+
+	#[test]
+	fn should_expand_variables() {
+		let template = ExtraDataTemplate::new("{tag}/{chain}/v{version}#{counter}").unwrap();
+		assert_eq!(template.expand(&variables()), b"pool/foundation/v1.8.0#42".to_vec());
+
+		let template = ExtraDataTemplate::new("{{tag}} }}").unwrap();
+		assert_eq!(template.expand(&variables()), b"{tag} }".to_vec());
+	}
+
+	#[test]
+	fn should_reject_invalid_templates() {
+		assert_eq!(ExtraDataTemplate::new("{height}"), Err(ExtraDataError::UnknownVariable("height".into())));
+		assert_eq!(ExtraDataTemplate::new("{tag"), Err(ExtraDataError::Unclosed));
+		assert_eq!(ExtraDataTemplate::new("tag}"), Err(ExtraDataError::Unmatched));
+	}
+
+	#[test]
+	fn should_check_expanded_length() {
+		let template = ExtraDataTemplate::new("{chain}-{chain}").unwrap();
+		assert_eq!(template.expand_checked(&variables(), 21), Ok(b"foundation-foundation".to_vec()));
+		assert_eq!(template.expand_checked(&variables(), 20), Err(ExtraDataError::TooLong { max: 20, found: 21 }));
+	}
+}
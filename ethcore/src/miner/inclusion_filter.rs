@@ -0,0 +1,197 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Allow and deny lists of transactions included in authored blocks.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use transaction::{Action, SignedTransaction};
+use util::Address;
+
+/// Addresses transactions are matched against.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AddressList {
+	/// Transaction senders.
+	pub senders: HashSet<Address>,
+	/// Transaction recipients (both plain accounts and contracts).
+	pub recipients: HashSet<Address>,
+	/// Called contracts (recipients with code).
+	pub contracts: HashSet<Address>,
+}
+
+impl AddressList {
+	/// Returns true if the list contains no addresses.
+	pub fn is_empty(&self) -> bool {
+		self.senders.is_empty() && self.recipients.is_empty() && self.contracts.is_empty()
+	}
+}
+
+/// Reason of excluding a transaction from authored blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exclusion {
+	/// Sender is on the deny list.
+	DeniedSender(Address),
+	/// Recipient is on the deny list.
+	DeniedRecipient(Address),
+	/// Called contract is on the deny list.
+	DeniedContract(Address),
+	/// Sender is not on the allow list.
+	SenderNotAllowed(Address),
+	/// Recipient is not on the allow list.
+	RecipientNotAllowed(Address),
+	/// Called contract is not on the allow list.
+	ContractNotAllowed(Address),
+}
+
+impl fmt::Display for Exclusion {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Exclusion::DeniedSender(ref a) => write!(f, "sender {:?} is denied", a),
+			Exclusion::DeniedRecipient(ref a) => write!(f, "recipient {:?} is denied", a),
+			Exclusion::DeniedContract(ref a) => write!(f, "contract {:?} is denied", a),
+			Exclusion::SenderNotAllowed(ref a) => write!(f, "sender {:?} is not allowed", a),
+			Exclusion::RecipientNotAllowed(ref a) => write!(f, "recipient {:?} is not allowed", a),
+			Exclusion::ContractNotAllowed(ref a) => write!(f, "contract {:?} is not allowed", a),
+		}
+	}
+}
+
+/// Allow and deny lists applied to transactions during block assembly.
+///
+/// A transaction is included only if it matches none of the deny lists and every non-empty allow list.
+/// Contract creations are only matched against sender lists.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InclusionFilter {
+	/// Addresses transactions must match (empty lists match everything).
+	pub allow: AddressList,
+	/// Addresses transactions must not match.
+	pub deny: AddressList,
+}
+
+impl InclusionFilter {
+	/// Returns true if the filter accepts every transaction.
+	pub fn is_empty(&self) -> bool {
+		self.allow.is_empty() && self.deny.is_empty()
+	}
+
+	/// Checks if given transaction may be included in a block.
+	/// `is_contract` is queried only when contract lists are in use.
+	pub fn check<F>(&self, tx: &SignedTransaction, is_contract: F) -> Result<(), Exclusion> where
+		F: Fn(&Address) -> bool,
+	{
+		let sender = tx.sender();
+		if self.deny.senders.contains(&sender) {
+			return Err(Exclusion::DeniedSender(sender));
+		}
+		if !self.allow.senders.is_empty() && !self.allow.senders.contains(&sender) {
+			return Err(Exclusion::SenderNotAllowed(sender));
+		}
+
+		let recipient = match tx.action {
+			Action::Call(ref to) => *to,
+			Action::Create => return Ok(()),
+		};
+		if self.deny.recipients.contains(&recipient) {
+			return Err(Exclusion::DeniedRecipient(recipient));
+		}
+		if !self.allow.recipients.is_empty() && !self.allow.recipients.contains(&recipient) {
+			return Err(Exclusion::RecipientNotAllowed(recipient));
+		}
+
+		if self.deny.contracts.is_empty() && self.allow.contracts.is_empty() {
+			return Ok(());
+		}
+		if !is_contract(&recipient) {
+			return Ok(());
+		}
+		if self.deny.contracts.contains(&recipient) {
+			return Err(Exclusion::DeniedContract(recipient));
+		}
+		if !self.allow.contracts.is_empty() && !self.allow.contracts.contains(&recipient) {
+			return Err(Exclusion::ContractNotAllowed(recipient));
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Random, Generator, KeyPair};
+	use transaction::{Action, Transaction, SignedTransaction};
+	use util::Address;
+	use super::{InclusionFilter, Exclusion};
+
+	fn transaction(keypair: &KeyPair, action: Action) -> SignedTransaction {
+		Transaction {
+			action: action,
+			value: 0.into(),
+			data: vec![],
+			gas: 100_000.into(),
+			gas_price: 1.into(),
+			nonce: 0.into(),
+		}.sign(keypair.secret(), None)
+	}
+
+	#[test]
+	fn should_accept_everything_when_empty() {
+		let keypair = Random.generate().unwrap();
+		let filter = InclusionFilter::default();
+
+		assert!(filter.is_empty());
+		assert_eq!(filter.check(&transaction(&keypair, Action::Create), |_| true), Ok(()));
+		assert_eq!(filter.check(&transaction(&keypair, Action::Call(5.into())), |_| true), Ok(()));
+	}
+
+	#[test]
+	fn should_reject_denied_addresses() {
+		let keypair = Random.generate().unwrap();
+		let other = Random.generate().unwrap();
+		let recipient: Address = 5.into();
+		let contract: Address = 6.into();
+		let mut filter = InclusionFilter::default();
+		filter.deny.senders.insert(keypair.address());
+		filter.deny.recipients.insert(recipient);
+		filter.deny.contracts.insert(contract);
+
+		assert_eq!(filter.check(&transaction(&keypair, Action::Create), |_| false), Err(Exclusion::DeniedSender(keypair.address())));
+		assert_eq!(filter.check(&transaction(&other, Action::Call(recipient)), |_| false), Err(Exclusion::DeniedRecipient(recipient)));
+		assert_eq!(filter.check(&transaction(&other, Action::Call(contract)), |_| true), Err(Exclusion::DeniedContract(contract)));
+		// not a contract (yet)
+		assert_eq!(filter.check(&transaction(&other, Action::Call(contract)), |_| false), Ok(()));
+		assert_eq!(filter.check(&transaction(&other, Action::Create), |_| true), Ok(()));
+	}
+
+	#[test]
+	fn should_reject_addresses_not_allowed() {
+		let keypair = Random.generate().unwrap();
+		let other = Random.generate().unwrap();
+		let contract: Address = 6.into();
+		let mut filter = InclusionFilter::default();
+		filter.allow.senders.insert(keypair.address());
+		filter.allow.contracts.insert(contract);
+
+		assert_eq!(filter.check(&transaction(&other, Action::Create), |_| false), Err(Exclusion::SenderNotAllowed(other.address())));
+		assert_eq!(filter.check(&transaction(&keypair, Action::Call(contract)), |_| true), Ok(()));
+		assert_eq!(filter.check(&transaction(&keypair, Action::Call(7.into())), |_| true), Err(Exclusion::ContractNotAllowed(7.into())));
+		// plain transfers are not subject to contract lists
+		assert_eq!(filter.check(&transaction(&keypair, Action::Call(7.into())), |_| false), Ok(()));
+
+		filter.allow.recipients.insert(contract);
+		assert_eq!(filter.check(&transaction(&keypair, Action::Call(7.into())), |_| false), Err(Exclusion::RecipientNotAllowed(7.into())));
+	}
+}
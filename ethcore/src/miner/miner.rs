@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::time::{Instant, Duration};
 use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
 
 use bigint::prelude::U256;
 use bigint::hash::H256;
@@ -26,18 +28,20 @@ use timer::PerfTimer;
 use using_queue::{UsingQueue, GetAction};
 use account_provider::{AccountProvider, SignError as AccountError};
 use state::State;
-use client::{MiningBlockChainClient, BlockId, TransactionId};
+use client::{MiningBlockChainClient, BlockId, TransactionId, BlockChainInfo};
 use client::TransactionImportResult;
 use executive::contract_address;
-use block::{ClosedBlock, IsBlock, Block};
+use block::{ClosedBlock, OpenBlock, SealedBlock, IsBlock, Block};
 use error::*;
 use transaction::{Action, UnverifiedTransaction, PendingTransaction, SignedTransaction, Condition as TransactionCondition};
 use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use engines::{Engine, Seal};
 use miner::{MinerService, MinerStatus, TransactionQueue, RemovalReason, TransactionQueueDetailsProvider, PrioritizationStrategy,
-	AccountDetails, TransactionOrigin};
+	AccountDetails, TransactionOrigin, EvictionStats, BanList, BlockOrdering, BlockOrderingStrategy, InclusionFilter, Exclusion,
+	ExtraDataTemplate, ExtraDataError, ExtraDataVariables};
 use miner::banning_queue::{BanningTransactionQueue, Threshold};
+use miner::transaction_queue::DEFAULT_REPLACE_BUMP_PERCENT;
 use miner::work_notify::{WorkPoster, NotifyWork};
 use miner::local_transactions::{Status as LocalTransactionStatus};
 use miner::service_transaction_checker::ServiceTransactionChecker;
@@ -85,6 +89,72 @@ pub enum Banning {
 	},
 }
 
+/// Banning of senders whose transactions repeatedly fail validation.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InvalidBanning {
+	/// Number of invalid transactions of a sender before banning.
+	pub min_offends: u16,
+	/// Number of seconds the offender is banned for.
+	pub ban_duration: Duration,
+}
+
+/// Selection of uncles included in authored blocks.
+///
+/// Limits are applied on top of the engine's own limits.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnclePolicy {
+	/// Maximum number of uncles per block or `None` for the engine's maximum. `Some(0)` disables inclusion.
+	pub max_count: Option<usize>,
+	/// Maximum age (in blocks) of included uncles or `None` for the engine's maximum.
+	pub max_age: Option<usize>,
+	/// Minimum reward the uncle's author has to receive for the uncle to be included.
+	/// Ignored by engines which don't reward uncles.
+	pub min_reward: U256,
+}
+
+impl Default for UnclePolicy {
+	fn default() -> Self {
+		UnclePolicy {
+			max_count: None,
+			max_age: None,
+			min_reward: U256::zero(),
+		}
+	}
+}
+
+impl UnclePolicy {
+	/// Maximum number of uncles to include in a block.
+	pub fn max_count(&self, engine: &Engine) -> usize {
+		cmp::min(self.max_count.unwrap_or(usize::max_value()), engine.maximum_uncle_count())
+	}
+
+	/// Maximum age of uncles to look for.
+	pub fn max_age(&self, engine: &Engine) -> usize {
+		cmp::min(self.max_age.unwrap_or(usize::max_value()), engine.maximum_uncle_age())
+	}
+
+	/// Whether given uncle may be included in a block with given number.
+	pub fn accepts(&self, engine: &Engine, uncle: &Header, block_number: BlockNumber) -> bool {
+		if self.min_reward.is_zero() {
+			return true;
+		}
+		match engine.uncle_reward(uncle, block_number) {
+			Some(reward) => reward >= self.min_reward,
+			None => true,
+		}
+	}
+
+	/// Picks uncles to include in a block with given number from candidates ordered by preference.
+	pub fn select<I>(&self, engine: &Engine, block_number: BlockNumber, candidates: I) -> Vec<Header> where
+		I: IntoIterator<Item=Header>,
+	{
+		candidates.into_iter()
+			.filter(|uncle| self.accepts(engine, uncle, block_number))
+			.take(self.max_count(engine))
+			.collect()
+	}
+}
+
 /// Configures the behaviour of the miner.
 #[derive(Debug, PartialEq)]
 pub struct MinerOptions {
@@ -102,14 +172,22 @@ pub struct MinerOptions {
 	pub reseal_min_period: Duration,
 	/// Maximum period between blocks (enables force sealing after that).
 	pub reseal_max_period: Duration,
+	/// Seal a new block with this period even if it's empty (only for engines sealing internally).
+	pub seal_interval: Option<Duration>,
 	/// Maximum amount of gas to bother considering for block insertion.
 	pub tx_gas_limit: U256,
 	/// Maximum size of the transaction queue.
 	pub tx_queue_size: usize,
 	/// Maximum memory usage of transactions in the queue (current / future).
 	pub tx_queue_memory_limit: Option<usize>,
+	/// Maximum number of external transactions of a single sender in the queue.
+	pub tx_queue_sender_limit: Option<usize>,
+	/// Maximum memory usage of external transactions of a single sender in the queue.
+	pub tx_queue_sender_memory_limit: Option<usize>,
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
+	/// Gas price bump (in percent) required to replace a queued transaction.
+	pub tx_queue_replace_bump: usize,
 	/// Whether we should fallback to providing all the queue's transactions or just pending.
 	pub pending_set: PendingSet,
 	/// How many historical work packages can we store before running out?
@@ -120,8 +198,18 @@ pub struct MinerOptions {
 	pub tx_queue_gas_limit: GasLimit,
 	/// Banning settings.
 	pub tx_queue_banning: Banning,
+	/// Banning of senders of invalid (e.g. underpriced) transactions.
+	pub tx_queue_invalid_banning: Option<InvalidBanning>,
+	/// Minimal time between replacements of a transaction with the same sender and nonce.
+	pub tx_queue_replace_cooldown: Option<Duration>,
 	/// Do we refuse to accept service transactions even if sender is certified.
 	pub refuse_service_transactions: bool,
+	/// Which uncles to include in authored blocks.
+	pub uncle_policy: UnclePolicy,
+	/// Order in which ready transactions are pushed into authored blocks.
+	pub block_ordering: BlockOrderingStrategy,
+	/// Allow and deny lists of transactions included in authored blocks.
+	pub inclusion_filter: InclusionFilter,
 }
 
 impl Default for MinerOptions {
@@ -135,15 +223,24 @@ impl Default for MinerOptions {
 			tx_gas_limit: !U256::zero(),
 			tx_queue_size: 8192,
 			tx_queue_memory_limit: Some(2 * 1024 * 1024),
+			tx_queue_sender_limit: None,
+			tx_queue_sender_memory_limit: None,
 			tx_queue_gas_limit: GasLimit::None,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_replace_bump: DEFAULT_REPLACE_BUMP_PERCENT,
 			pending_set: PendingSet::AlwaysQueue,
 			reseal_min_period: Duration::from_secs(2),
 			reseal_max_period: Duration::from_secs(120),
+			seal_interval: None,
 			work_queue_size: 20,
 			enable_resubmission: true,
 			tx_queue_banning: Banning::Disabled,
+			tx_queue_invalid_banning: None,
+			tx_queue_replace_cooldown: None,
 			refuse_service_transactions: false,
+			uncle_policy: UnclePolicy::default(),
+			block_ordering: BlockOrderingStrategy::default(),
+			inclusion_filter: InclusionFilter::default(),
 		}
 	}
 }
@@ -233,18 +330,30 @@ pub struct Miner {
 	next_allowed_reseal: Mutex<Instant>,
 	next_mandatory_reseal: RwLock<Instant>,
 	sealing_block_last_request: Mutex<u64>,
+	automine: AtomicBool,
 	// for sealing...
 	options: MinerOptions,
 
 	gas_range_target: RwLock<(U256, U256)>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
+	extra_data_template: RwLock<Option<ExtraDataTemplate>>,
+	extra_data_tag: RwLock<String>,
+	chain_name: String,
+	sealed_blocks: AtomicUsize,
 	engine: Arc<Engine>,
 
 	accounts: Option<Arc<AccountProvider>>,
 	notifiers: RwLock<Vec<Box<NotifyWork>>>,
+	transaction_listeners: RwLock<Vec<Box<Fn(&[SignedTransaction]) + Send + Sync>>>,
+	local_transaction_listeners: RwLock<Vec<Box<Fn(&PendingTransaction) + Send + Sync>>>,
+	pending_block_listeners: RwLock<Vec<Box<Fn() + Send + Sync>>>,
+	removed_transaction_listeners: RwLock<Vec<Box<Fn(&PendingTransaction) + Send + Sync>>>,
 	gas_pricer: Mutex<GasPricer>,
 	service_transaction_action: ServiceTransactionAction,
+	block_ordering: RwLock<Arc<BlockOrdering>>,
+	inclusion_filter: RwLock<InclusionFilter>,
+	excluded_transactions: Mutex<HashSet<H256>>,
 }
 
 impl Miner {
@@ -254,6 +363,64 @@ impl Miner {
 		self.sealing_work.lock().enabled = true;
 	}
 
+	/// Replace the strategy used to order ready transactions in authored blocks.
+	pub fn set_block_ordering(&self, ordering: Arc<BlockOrdering>) {
+		*self.block_ordering.write() = ordering;
+	}
+
+	/// Set the pool tag substituted for `{tag}` in the extra_data template.
+	pub fn set_extra_data_tag(&self, tag: String) {
+		*self.extra_data_tag.write() = tag;
+	}
+
+	fn extra_data_variables<'a>(&'a self, tag: &'a str) -> ExtraDataVariables<'a> {
+		ExtraDataVariables {
+			version: env!("CARGO_PKG_VERSION"),
+			chain: &self.chain_name,
+			tag: tag,
+			counter: self.sealed_blocks.load(AtomicOrdering::SeqCst) as u64,
+		}
+	}
+
+	/// Imports block sealed by this node, counting it for the `{counter}` extra_data variable.
+	fn import_sealed_block(&self, chain: &MiningBlockChainClient, block: SealedBlock) -> ImportResult {
+		let result = chain.import_sealed_block(block);
+		if result.is_ok() {
+			self.sealed_blocks.fetch_add(1, AtomicOrdering::SeqCst);
+		}
+		result
+	}
+
+	/// Set a callback to be notified about transactions newly imported to the queue.
+	pub fn add_transactions_listener(&self, f: Box<Fn(&[SignedTransaction]) + Send + Sync>) {
+		self.transaction_listeners.write().push(f);
+	}
+
+	/// Set a callback to be notified whenever the pending block is rebuilt.
+	pub fn add_pending_block_listener(&self, f: Box<Fn() + Send + Sync>) {
+		self.pending_block_listeners.write().push(f);
+	}
+
+	/// Set a callback to be notified about transactions removed from the queue on request.
+	pub fn add_removed_transactions_listener(&self, f: Box<Fn(&PendingTransaction) + Send + Sync>) {
+		self.removed_transaction_listeners.write().push(f);
+	}
+
+	fn notify_transactions_listeners(&self, transactions: &[SignedTransaction]) {
+		if transactions.is_empty() {
+			return;
+		}
+
+		for listener in self.transaction_listeners.read().iter() {
+			listener(transactions);
+		}
+	}
+
+	/// Set a callback to be notified about own transactions successfully imported to the queue.
+	pub fn add_local_transactions_listener(&self, f: Box<Fn(&PendingTransaction) + Send + Sync>) {
+		self.local_transaction_listeners.write().push(f);
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(options: MinerOptions, gas_pricer: GasPricer, spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Arc<Miner> {
 		Arc::new(Miner::new_raw(options, gas_pricer, spec, accounts))
@@ -267,14 +434,17 @@ impl Miner {
 		};
 		let mem_limit = options.tx_queue_memory_limit.unwrap_or_else(usize::max_value);
 
-		let txq = TransactionQueue::with_limits(
+		let mut txq = TransactionQueue::with_limits(
 			options.tx_queue_strategy,
 			options.tx_queue_size,
 			mem_limit,
 			gas_limit,
 			options.tx_gas_limit
 		);
-		let txq = match options.tx_queue_banning {
+		txq.set_replace_bump(options.tx_queue_replace_bump);
+		txq.set_sender_limit(options.tx_queue_sender_limit.unwrap_or_else(usize::max_value));
+		txq.set_sender_memory_limit(options.tx_queue_sender_memory_limit.unwrap_or_else(usize::max_value));
+		let mut txq = match options.tx_queue_banning {
 			Banning::Disabled => BanningTransactionQueue::new(txq, Threshold::NeverBan, Duration::from_secs(180)),
 			Banning::Enabled { ban_duration, min_offends, .. } => BanningTransactionQueue::new(
 				txq,
@@ -282,12 +452,21 @@ impl Miner {
 				ban_duration,
 			),
 		};
+		if let Some(ref banning) = options.tx_queue_invalid_banning {
+			txq.set_invalid_sender_banning(Threshold::BanAfter(banning.min_offends), banning.ban_duration);
+		}
+		if let Some(cooldown) = options.tx_queue_replace_cooldown {
+			txq.set_replace_cooldown(cooldown);
+		}
 
 		let notifiers: Vec<Box<NotifyWork>> = match options.new_work_notify.is_empty() {
 			true => Vec::new(),
 			false => vec![Box::new(WorkPoster::new(&options.new_work_notify))],
 		};
 
+		let block_ordering: Arc<BlockOrdering> = Arc::new(options.block_ordering);
+		let inclusion_filter = options.inclusion_filter.clone();
+
 		let service_transaction_action = match options.refuse_service_transactions {
 			true => ServiceTransactionAction::Refuse,
 			false => ServiceTransactionAction::Check(ServiceTransactionChecker::default()),
@@ -298,6 +477,7 @@ impl Miner {
 			next_allowed_reseal: Mutex::new(Instant::now()),
 			next_mandatory_reseal: RwLock::new(Instant::now() + options.reseal_max_period),
 			sealing_block_last_request: Mutex::new(0),
+			automine: AtomicBool::new(true),
 			sealing_work: Mutex::new(SealingWork{
 				queue: UsingQueue::new(options.work_queue_size),
 				enabled: options.force_sealing
@@ -307,12 +487,23 @@ impl Miner {
 			gas_range_target: RwLock::new((U256::zero(), U256::zero())),
 			author: RwLock::new(Address::default()),
 			extra_data: RwLock::new(Vec::new()),
+			extra_data_template: RwLock::new(None),
+			extra_data_tag: RwLock::new(String::new()),
+			chain_name: spec.name.clone(),
+			sealed_blocks: AtomicUsize::new(0),
 			options: options,
 			accounts: accounts,
 			engine: spec.engine.clone(),
 			notifiers: RwLock::new(notifiers),
+			transaction_listeners: RwLock::new(Vec::new()),
+			local_transaction_listeners: RwLock::new(Vec::new()),
+			pending_block_listeners: RwLock::new(Vec::new()),
+			removed_transaction_listeners: RwLock::new(Vec::new()),
 			gas_pricer: Mutex::new(gas_pricer),
 			service_transaction_action: service_transaction_action,
+			block_ordering: RwLock::new(block_ordering),
+			inclusion_filter: RwLock::new(inclusion_filter),
+			excluded_transactions: Mutex::new(HashSet::new()),
 		}
 	}
 
@@ -326,6 +517,11 @@ impl Miner {
 		Miner::new_raw(Default::default(), GasPricer::new_fixed(20_000_000_000u64.into()), spec, None)
 	}
 
+	/// Period with which blocks should be sealed regardless of incoming transactions.
+	pub fn seal_interval(&self) -> Option<Duration> {
+		self.options.seal_interval
+	}
+
 	fn forced_sealing(&self) -> bool {
 		self.options.force_sealing || !self.notifiers.read().is_empty()
 	}
@@ -360,16 +556,38 @@ impl Miner {
 		)
 	}
 
+	/// Returns ready transactions from the queue in the order they should be pushed to a block.
+	fn ordered_transactions(&self, chain_info: &BlockChainInfo, nonce_cap: Option<U256>) -> Vec<SignedTransaction> {
+		let ready = self.transaction_queue.read().ready_transactions_at(chain_info.best_block_number, chain_info.best_block_timestamp, nonce_cap);
+		self.block_ordering.read().order(ready)
+	}
+
+	/// Checks the transaction against the inclusion filter using the state of the block being assembled.
+	fn check_inclusion(filter: &InclusionFilter, open_block: &OpenBlock, tx: &SignedTransaction) -> Result<(), Exclusion> {
+		let state = open_block.state();
+		filter.check(tx, |address| match state.code_size(address) {
+			Ok(Some(size)) => size > 0,
+			_ => false,
+		})
+	}
+
+	/// Logs transaction excluded by the inclusion filter (once per transaction).
+	fn note_excluded(&self, hash: H256, block_number: BlockNumber, reason: Exclusion) {
+		if self.excluded_transactions.lock().insert(hash) {
+			info!(target: "miner", "Excluding transaction {:?} from block #{}: {}", hash, block_number, reason);
+		} else {
+			trace!(target: "miner", "Excluding transaction {:?} from block #{}: {}", hash, block_number, reason);
+		}
+	}
+
 	#[cfg_attr(feature="dev", allow(match_same_arms))]
 	/// Prepares new block for sealing including top transactions from queue.
 	fn prepare_block(&self, chain: &MiningBlockChainClient) -> (ClosedBlock, Option<H256>) {
 		let _timer = PerfTimer::new("prepare_block");
 		let chain_info = chain.chain_info();
 		let (transactions, mut open_block, original_work_hash) = {
-			let nonce_cap = if chain_info.best_block_number + 1 >= self.engine.params().dust_protection_transition {
-				Some((self.engine.params().nonce_cap_increment * (chain_info.best_block_number + 1)).into())
-			} else { None };
-			let transactions = {self.transaction_queue.read().top_transactions_at(chain_info.best_block_number, chain_info.best_block_timestamp, nonce_cap)};
+			let nonce_cap = self.nonce_cap(chain_info.best_block_number);
+			let transactions = self.ordered_transactions(&chain_info, nonce_cap);
 			let mut sealing_work = self.sealing_work.lock();
 			let last_work_hash = sealing_work.queue.peek_last_ref().map(|pb| pb.block().fields().header.hash());
 			let best_hash = chain_info.best_block_hash;
@@ -385,7 +603,7 @@ impl Miner {
 				Some(old_block) => {
 					trace!(target: "miner", "prepare_block: Already have previous work; updating and returning");
 					// add transactions to old_block
-					chain.reopen_block(old_block)
+					chain.reopen_block(old_block, &self.options.uncle_policy)
 				}
 				None => {
 					// block not found - create it.
@@ -393,7 +611,8 @@ impl Miner {
 					chain.prepare_open_block(
 						self.author(),
 						(self.gas_floor_target(), self.gas_ceil_target()),
-						self.extra_data()
+						self.extra_data(),
+						&self.options.uncle_policy,
 					)
 				}
 			};
@@ -402,12 +621,26 @@ impl Miner {
 
 		let mut invalid_transactions = HashSet::new();
 		let mut transactions_to_penalize = HashSet::new();
+		let mut excluded_senders: HashSet<Address> = HashSet::new();
 		let block_number = open_block.block().fields().header.number();
+		let filter = self.inclusion_filter.read().clone();
 
 		let mut tx_count: usize = 0;
 		let tx_total = transactions.len();
 		for tx in transactions {
 			let hash = tx.hash();
+			if !filter.is_empty() {
+				// following transactions of an excluded sender can't be included anyway.
+				if excluded_senders.contains(&tx.sender()) {
+					continue;
+				}
+				if let Err(reason) = Self::check_inclusion(&filter, &open_block, &tx) {
+					excluded_senders.insert(tx.sender());
+					self.note_excluded(hash, block_number, reason);
+					continue;
+				}
+			}
+
 			let start = Instant::now();
 			let result = open_block.push_transaction(tx, None);
 			let took = start.elapsed();
@@ -479,6 +712,13 @@ impl Miner {
 		(block, original_work_hash)
 	}
 
+	/// Maximal nonce of transactions that can be included in the block following `best_block`.
+	fn nonce_cap(&self, best_block: BlockNumber) -> Option<U256> {
+		if best_block + 1 >= self.engine.params().dust_protection_transition {
+			Some((self.engine.params().nonce_cap_increment * (best_block + 1)).into())
+		} else { None }
+	}
+
 	/// Asynchronously updates minimal gas price for transaction queue
 	pub fn recalibrate_minimal_gas_price(&self) {
 		debug!(target: "miner", "minimal_gas_price: recalibrating...");
@@ -522,6 +762,10 @@ impl Miner {
 
 	/// Attempts to perform internal sealing (one that does not require work) and handles the result depending on the type of Seal.
 	fn seal_and_import_block_internally(&self, chain: &MiningBlockChainClient, block: ClosedBlock) -> bool {
+		if !self.automine() {
+			trace!(target: "miner", "seal_block_internally: automine is disabled.");
+			return false;
+		}
 		if !block.transactions().is_empty() || self.forced_sealing() || Instant::now() > *self.next_mandatory_reseal.read() {
 			trace!(target: "miner", "seal_block_internally: attempting internal seal.");
 			match self.engine.generate_seal(block.block()) {
@@ -549,7 +793,7 @@ impl Miner {
 					block
 						.lock()
 						.seal(&*self.engine, seal)
-						.map(|sealed| chain.import_sealed_block(sealed).is_ok())
+						.map(|sealed| self.import_sealed_block(chain, sealed).is_ok())
 						.unwrap_or_else(|e| {
 							warn!("ERROR: seal failed when given internally generated seal: {}", e);
 							false
@@ -586,6 +830,11 @@ impl Miner {
 			trace!(target: "miner", "prepare_work: leaving (last={:?})", sealing_work.queue.peek_last_ref().map(|b| b.block().fields().header.hash()));
 			(work, is_new)
 		};
+		if work.is_some() {
+			for listener in self.pending_block_listeners.read().iter() {
+				listener();
+			}
+		}
 		if is_new {
 			work.map(|(pow_hash, difficulty, number)| {
 				for notifier in self.notifiers.read().iter() {
@@ -645,10 +894,13 @@ impl Miner {
 		default_origin: TransactionOrigin,
 		condition: Option<TransactionCondition>,
 		transaction_queue: &mut BanningTransactionQueue,
+		imported: &mut Vec<SignedTransaction>,
 	) -> Vec<Result<TransactionImportResult, Error>> {
 		let accounts = self.accounts.as_ref()
 			.and_then(|provider| provider.accounts().ok())
 			.map(|accounts| accounts.into_iter().collect::<HashSet<_>>());
+		// transactions are collected only if there is someone to notify
+		let is_listened = !self.transaction_listeners.read().is_empty();
 
 		let best_block_header = client.best_block_header().decode();
 		let insertion_time = client.chain_info().best_block_number;
@@ -660,6 +912,12 @@ impl Miner {
 					debug!(target: "miner", "Rejected tx {:?}: already in the blockchain", hash);
 					return Err(Error::Transaction(TransactionError::AlreadyImported));
 				}
+				let size = ::rlp::encode(&tx).len();
+				let limit = self.engine.params().max_transaction_size;
+				if size > limit {
+					debug!(target: "miner", "Rejected tx {:?}: {} bytes exceed the limit of {}", hash, size, limit);
+					return Err(Error::Transaction(TransactionError::TooBig { limit: limit, got: size }));
+				}
 				match self.engine.verify_transaction_basic(&tx, &best_block_header)
 					.and_then(|_| self.engine.verify_transaction(tx, &best_block_header))
 				{
@@ -679,14 +937,22 @@ impl Miner {
 						self.service_transaction_action.update_from_chain_client(client);
 
 						let details_provider = TransactionDetailsProvider::new(client, &self.service_transaction_action);
-						match origin {
+						let listened = match is_listened {
+							true => Some(transaction.clone()),
+							false => None,
+						};
+						let result = match origin {
 							TransactionOrigin::Local | TransactionOrigin::RetractedBlock => {
 								transaction_queue.add(transaction, origin, insertion_time, condition.clone(), &details_provider)
 							},
 							TransactionOrigin::External => {
 								transaction_queue.add_with_banlist(transaction, insertion_time, &details_provider)
 							},
+						};
+						if let (true, Some(transaction)) = (result.is_ok(), listened) {
+							imported.push(transaction);
 						}
+						result
 					},
 				}
 			})
@@ -773,9 +1039,20 @@ impl MinerService for Miner {
 	}
 
 	fn set_extra_data(&self, extra_data: Bytes) {
+		*self.extra_data_template.write() = None;
 		*self.extra_data.write() = extra_data;
 	}
 
+	fn set_extra_data_template(&self, template: ExtraDataTemplate) -> Result<(), ExtraDataError> {
+		let extra_data = {
+			let tag = self.extra_data_tag.read();
+			template.expand_checked(&self.extra_data_variables(&tag), self.engine.maximum_extra_data_size())?
+		};
+		info!(target: "miner", "Using extra_data template {:?} (currently {:?}).", template.template(), String::from_utf8_lossy(&extra_data));
+		*self.extra_data_template.write() = Some(template);
+		Ok(())
+	}
+
 	/// Set the gas limit we wish to target when sealing a new block.
 	fn set_gas_floor_target(&self, target: U256) {
 		self.gas_range_target.write().0 = target;
@@ -810,6 +1087,40 @@ impl MinerService for Miner {
 		self.transaction_queue.write().set_limit(limit)
 	}
 
+	fn set_transactions_memory_limit(&self, limit: usize) {
+		self.transaction_queue.write().set_memory_limit(limit)
+	}
+
+	fn set_sender_transactions_limit(&self, limit: usize) {
+		self.transaction_queue.write().set_sender_limit(limit)
+	}
+
+	fn set_sender_transactions_memory_limit(&self, limit: usize) {
+		self.transaction_queue.write().set_sender_memory_limit(limit)
+	}
+
+	fn transactions_evictions(&self) -> EvictionStats {
+		self.transaction_queue.read().eviction_stats()
+	}
+
+	fn transactions_bans(&self) -> BanList {
+		self.transaction_queue.read().bans()
+	}
+
+	fn clear_transactions_bans(&self) {
+		self.transaction_queue.write().clear_bans()
+	}
+
+	fn inclusion_filter(&self) -> InclusionFilter {
+		self.inclusion_filter.read().clone()
+	}
+
+	fn set_inclusion_filter(&self, filter: InclusionFilter) {
+		info!(target: "miner", "Block inclusion filter changed: allow={:?}, deny={:?}", filter.allow, filter.deny);
+		*self.inclusion_filter.write() = filter;
+		self.excluded_transactions.lock().clear();
+	}
+
 	fn set_tx_gas_limit(&self, limit: U256) {
 		self.transaction_queue.write().set_tx_gas_limit(limit)
 	}
@@ -821,7 +1132,19 @@ impl MinerService for Miner {
 
 	/// Get the extra_data that we will seal blocks with.
 	fn extra_data(&self) -> Bytes {
-		self.extra_data.read().clone()
+		match *self.extra_data_template.read() {
+			Some(ref template) => {
+				let tag = self.extra_data_tag.read();
+				let max = self.engine.maximum_extra_data_size();
+				let mut extra_data = template.expand(&self.extra_data_variables(&tag));
+				if extra_data.len() > max {
+					warn!(target: "miner", "Expanded extra_data template {:?} exceeds {} bytes; truncating.", template.template(), max);
+					extra_data.truncate(max);
+				}
+				extra_data
+			},
+			None => self.extra_data.read().clone(),
+		}
 	}
 
 	/// Get the gas limit we wish to target when sealing a new block.
@@ -840,12 +1163,14 @@ impl MinerService for Miner {
 		transactions: Vec<UnverifiedTransaction>
 	) -> Vec<Result<TransactionImportResult, Error>> {
 		trace!(target: "external_tx", "Importing external transactions");
+		let mut imported = Vec::new();
 		let results = {
 			let mut transaction_queue = self.transaction_queue.write();
 			self.add_transactions_to_queue(
-				chain, transactions, TransactionOrigin::External, None, &mut transaction_queue
+				chain, transactions, TransactionOrigin::External, None, &mut transaction_queue, &mut imported
 			)
 		};
+		self.notify_transactions_listeners(&imported);
 
 		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.tx_reseal_allowed() {
 			// --------------------------------------------------------------------------
@@ -866,12 +1191,17 @@ impl MinerService for Miner {
 
 		trace!(target: "own_tx", "Importing transaction: {:?}", pending);
 
+		let to_notify = match self.local_transaction_listeners.read().is_empty() {
+			true => None,
+			false => Some(pending.clone()),
+		};
+		let mut inserted = Vec::new();
 		let imported = {
 			// Be sure to release the lock before we call prepare_work_sealing
 			let mut transaction_queue = self.transaction_queue.write();
 			// We need to re-validate transactions
 			let import = self.add_transactions_to_queue(
-				chain, vec![pending.transaction.into()], TransactionOrigin::Local, pending.condition, &mut transaction_queue
+				chain, vec![pending.transaction.into()], TransactionOrigin::Local, pending.condition, &mut transaction_queue, &mut inserted
 			).pop().expect("one result returned per added transaction; one added => one result; qed");
 
 			match import {
@@ -885,6 +1215,12 @@ impl MinerService for Miner {
 			}
 			import
 		};
+		self.notify_transactions_listeners(&inserted);
+		if let (true, Some(pending)) = (imported.is_ok(), to_notify) {
+			for listener in self.local_transaction_listeners.read().iter() {
+				listener(&pending);
+			}
+		}
 
 		// --------------------------------------------------------------------------
 		// | NOTE Code below requires transaction_queue and sealing_work locks.     |
@@ -921,6 +1257,10 @@ impl MinerService for Miner {
 		self.transaction_queue.read().future_transactions()
 	}
 
+	fn scheduled_transactions(&self, best_block: BlockNumber, best_block_timestamp: u64) -> Vec<PendingTransaction> {
+		self.transaction_queue.read().scheduled_transactions(best_block, best_block_timestamp)
+	}
+
 	fn ready_transactions(&self, best_block: BlockNumber, best_block_timestamp: u64) -> Vec<PendingTransaction> {
 		let queue = self.transaction_queue.read();
 		match self.options.pending_set {
@@ -985,11 +1325,20 @@ impl MinerService for Miner {
 	}
 
 	fn remove_pending_transaction(&self, chain: &MiningBlockChainClient, hash: &H256) -> Option<PendingTransaction> {
-		let mut queue = self.transaction_queue.write();
-		let tx = queue.find(hash);
-		if tx.is_some() {
-			let fetch_nonce = |a: &Address| chain.latest_nonce(a);
-			queue.remove(hash, &fetch_nonce, RemovalReason::Canceled);
+		let tx = {
+			let mut queue = self.transaction_queue.write();
+			let tx = queue.find(hash);
+			if tx.is_some() {
+				let fetch_nonce = |a: &Address| chain.latest_nonce(a);
+				queue.remove(hash, &fetch_nonce, RemovalReason::Canceled);
+			}
+			tx
+		};
+		// Be sure to release the queue lock, listeners might want to read it.
+		if let Some(ref tx) = tx {
+			for listener in self.removed_transaction_listeners.read().iter() {
+				listener(tx);
+			}
 		}
 		tx
 	}
@@ -1090,6 +1439,43 @@ impl MinerService for Miner {
 		}
 	}
 
+	fn seal_block_now(&self, chain: &MiningBlockChainClient) -> Option<H256> {
+		if self.engine.seals_internally() != Some(true) {
+			trace!(target: "miner", "seal_block_now: engine is not able to seal right now");
+			return None;
+		}
+
+		// --------------------------------------------------------------------------
+		// | NOTE Code below requires transaction_queue and sealing_work locks.     |
+		// | Make sure to release the locks before calling that method.             |
+		// --------------------------------------------------------------------------
+		let (block, _) = self.prepare_block(chain);
+		match self.engine.generate_seal_on_demand(block.block()) {
+			Seal::Regular(seal) => {
+				*self.next_mandatory_reseal.write() = Instant::now() + self.options.reseal_max_period;
+				match block.lock().seal(&*self.engine, seal) {
+					Ok(sealed) => {
+						let hash = sealed.header().hash();
+						self.import_sealed_block(chain, sealed).ok().map(|_| hash)
+					},
+					Err(e) => {
+						warn!("ERROR: seal failed when given internally generated seal: {}", e);
+						None
+					},
+				}
+			},
+			_ => None,
+		}
+	}
+
+	fn set_automine(&self, enabled: bool) {
+		self.automine.store(enabled, AtomicOrdering::SeqCst);
+	}
+
+	fn automine(&self) -> bool {
+		self.automine.load(AtomicOrdering::SeqCst)
+	}
+
 	fn is_currently_sealing(&self) -> bool {
 		self.sealing_work.lock().queue.is_in_use()
 	}
@@ -1104,6 +1490,42 @@ impl MinerService for Miner {
 		ret.map(f)
 	}
 
+	fn dry_run_block(&self, chain: &MiningBlockChainClient, author: Address, gas_limit: U256) -> ClosedBlock {
+		let chain_info = chain.chain_info();
+		let nonce_cap = self.nonce_cap(chain_info.best_block_number);
+		let transactions = self.ordered_transactions(&chain_info, nonce_cap);
+
+		let mut open_block = chain.prepare_open_block(author, (gas_limit, gas_limit), self.extra_data(), &self.options.uncle_policy);
+		open_block.set_gas_limit(gas_limit);
+
+		// same selection as in `prepare_block`, but the queue is left untouched.
+		let filter = self.inclusion_filter.read().clone();
+		let mut excluded_senders: HashSet<Address> = HashSet::new();
+		for tx in transactions {
+			let hash = tx.hash();
+			if excluded_senders.contains(&tx.sender()) {
+				continue;
+			}
+			if let Err(reason) = Self::check_inclusion(&filter, &open_block, &tx) {
+				trace!(target: "miner", "Dry run excluded transaction {:?}: {}", hash, reason);
+				excluded_senders.insert(tx.sender());
+				continue;
+			}
+			match open_block.push_transaction(tx, None) {
+				Err(Error::Execution(ExecutionError::BlockGasLimitReached { gas_limit, gas_used, .. })) => {
+					let min_tx_gas: U256 = 21000.into();
+					if gas_limit - gas_used < min_tx_gas {
+						break;
+					}
+				},
+				Err(e) => trace!(target: "miner", "Dry run skipped transaction {:?}: {:?}", hash, e),
+				Ok(_) => {},
+			}
+		}
+
+		open_block.close()
+	}
+
 	fn submit_seal(&self, chain: &MiningBlockChainClient, block_hash: H256, seal: Vec<Bytes>) -> Result<(), Error> {
 		let result =
 			if let Some(b) = self.sealing_work.lock().queue.get_used_if(
@@ -1126,7 +1548,7 @@ impl MinerService for Miner {
 		result.and_then(|sealed| {
 			let n = sealed.header().number();
 			let h = sealed.header().hash();
-			chain.import_sealed_block(sealed)?;
+			self.import_sealed_block(chain, sealed)?;
 			info!(target: "miner", "Submitted block imported OK. #{}: {}", Colour::White.bold().paint(format!("{}", n)), Colour::White.bold().paint(h.hex()));
 			Ok(())
 		})
@@ -1153,8 +1575,9 @@ impl MinerService for Miner {
 				let block = chain.block(BlockId::Hash(*hash))
 					.expect("Client is sending message after commit to db and inserting to chain; the block is available; qed");
 				let txs = block.transactions();
+				// retracted transactions were already announced, no need to notify listeners
 				let _ = self.add_transactions_to_queue(
-					chain, txs, TransactionOrigin::RetractedBlock, None, &mut transaction_queue
+					chain, txs, TransactionOrigin::RetractedBlock, None, &mut transaction_queue, &mut Vec::new()
 				);
 			}
 		}
@@ -1168,6 +1591,8 @@ impl MinerService for Miner {
 			let time = chain.chain_info().best_block_number;
 			let mut transaction_queue = self.transaction_queue.write();
 			transaction_queue.remove_old(&fetch_account, time);
+			transaction_queue.prune_bans();
+			self.excluded_transactions.lock().retain(|hash| transaction_queue.find(hash).is_some());
 		}
 
 		if enacted.len() > 0 || (imported.len() > 0 && self.options.reseal_on_uncle) {
@@ -1294,16 +1719,25 @@ mod tests {
 				reseal_on_uncle: false,
 				reseal_min_period: Duration::from_secs(5),
 				reseal_max_period: Duration::from_secs(120),
+				seal_interval: None,
 				tx_gas_limit: !U256::zero(),
 				tx_queue_size: 1024,
 				tx_queue_memory_limit: None,
+				tx_queue_sender_limit: None,
+				tx_queue_sender_memory_limit: None,
 				tx_queue_gas_limit: GasLimit::None,
 				tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
+				tx_queue_replace_bump: 12,
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
 				enable_resubmission: true,
 				tx_queue_banning: Banning::Disabled,
+				tx_queue_invalid_banning: None,
+				tx_queue_replace_cooldown: None,
 				refuse_service_transactions: false,
+				uncle_policy: UnclePolicy::default(),
+				block_ordering: BlockOrderingStrategy::default(),
+				inclusion_filter: InclusionFilter::default(),
 			},
 			GasPricer::new_fixed(0u64.into()),
 			&Spec::new_test(),
@@ -1347,6 +1781,78 @@ mod tests {
 		assert!(!miner.prepare_work_sealing(&client));
 	}
 
+	#[test]
+	fn should_dry_run_block_without_touching_queue_or_sealing() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let author = Address::from(0x42);
+		let res = miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap();
+		assert_eq!(res.unwrap(), TransactionImportResult::Current);
+
+		// when
+		let block = miner.dry_run_block(&client, author, 1_000_000.into());
+		let too_small = miner.dry_run_block(&client, author, 50_000.into());
+
+		// then
+		let header = &block.block().fields().header;
+		assert_eq!(block.transactions().len(), 1);
+		assert_eq!(header.author(), &author);
+		assert_eq!(header.gas_limit(), &1_000_000.into());
+		assert!(header.gas_used() > &U256::zero());
+		assert_eq!(too_small.transactions().len(), 0);
+		assert_eq!(miner.pending_transactions().len(), 1);
+		assert!(miner.sealing_work.lock().queue.peek_last_ref().is_none());
+	}
+
+	#[test]
+	fn should_exclude_denied_transactions_from_blocks() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let denied = transaction();
+		let allowed = transaction();
+		let mut filter = InclusionFilter::default();
+		filter.deny.senders.insert(denied.sender());
+		miner.set_inclusion_filter(filter.clone());
+
+		// when
+		miner.import_external_transactions(&client, vec![denied.clone().into(), allowed.clone().into()]);
+		let block = miner.dry_run_block(&client, Address::default(), 1_000_000.into());
+
+		// then
+		assert_eq!(miner.inclusion_filter(), filter);
+		assert_eq!(block.transactions().len(), 1);
+		assert_eq!(block.transactions()[0], allowed);
+		miner.prepare_work_sealing(&client);
+		let sealing = miner.sealing_work.lock().queue.peek_last_ref().map(|b| b.transactions().to_vec()).unwrap();
+		assert_eq!(sealing, vec![allowed]);
+		// excluded transaction stays in the queue
+		assert_eq!(miner.pending_transactions().len(), 2);
+	}
+
+	#[test]
+	fn should_notify_pending_block_listeners() {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let rebuilds = Arc::new(AtomicUsize::new(0));
+		let r = rebuilds.clone();
+		miner.add_pending_block_listener(Box::new(move || { r.fetch_add(1, Ordering::SeqCst); }));
+
+		// when
+		let res = miner.import_own_transaction(&client, PendingTransaction::new(transaction(), None));
+
+		// then
+		assert_eq!(res.unwrap(), TransactionImportResult::Current);
+		assert_eq!(rebuilds.load(Ordering::SeqCst), 1);
+		// pending block is already there
+		assert!(!miner.prepare_work_sealing(&client));
+		assert_eq!(rebuilds.load(Ordering::SeqCst), 1);
+	}
+
 	#[test]
 	fn should_not_use_pending_block_if_best_block_is_higher() {
 		// given
@@ -1385,6 +1891,58 @@ mod tests {
 		assert!(miner.prepare_work_sealing(&client));
 	}
 
+	#[test]
+	fn should_reject_oversized_transaction() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let keypair = Random.generate().unwrap();
+		let transaction = Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: vec![0; 300 * 1024],
+			gas: U256::from(100_000_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), Some(2));
+
+		// when
+		let res = miner.import_external_transactions(&client, vec![transaction.into()]).pop().unwrap();
+
+		// then
+		match res {
+			Err(Error::Transaction(TransactionError::TooBig { limit, .. })) => assert_eq!(limit, 300 * 1024),
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert_eq!(miner.pending_transactions().len(), 0);
+	}
+
+	#[test]
+	fn should_notify_listeners_about_imported_transactions() {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let transaction: SignedTransaction = transaction();
+		let hash = transaction.hash();
+		let notified = Arc::new(AtomicUsize::new(0));
+		let n = notified.clone();
+		miner.add_transactions_listener(Box::new(move |txs| {
+			assert_eq!(txs.len(), 1);
+			assert_eq!(txs[0].hash(), hash);
+			n.fetch_add(1, Ordering::SeqCst);
+		}));
+
+		// when
+		miner.import_external_transactions(&client, vec![transaction.clone().into()]).pop().unwrap().unwrap();
+		// importing the same transaction again should not trigger the listener
+		assert!(miner.import_external_transactions(&client, vec![transaction.into()]).pop().unwrap().is_err());
+
+		// then
+		assert_eq!(notified.load(Ordering::SeqCst), 1);
+	}
+
 	#[test]
 	fn should_not_seal_unless_enabled() {
 		let miner = miner();
@@ -1420,6 +1978,67 @@ mod tests {
 		assert_eq!(client.chain_info().best_block_number, 4 as BlockNumber);
 	}
 
+	#[test]
+	fn internal_seals_on_demand_when_automine_is_disabled() {
+		let spec = Spec::new_instant();
+		let miner = Miner::with_spec(&spec);
+		miner.set_automine(false);
+
+		let client = generate_dummy_client(2);
+
+		assert_eq!(miner.import_own_transaction(&*client, PendingTransaction::new(transaction_with_chain_id(spec.chain_id()).into(), None)).unwrap(), TransactionImportResult::Current);
+		miner.update_sealing(&*client);
+		client.flush_queue();
+		assert_eq!(client.chain_info().best_block_number, 2 as BlockNumber);
+
+		let hash = miner.seal_block_now(&*client).unwrap();
+		client.flush_queue();
+		assert_eq!(client.chain_info().best_block_hash, hash);
+		assert_eq!(client.block_body(BlockId::Hash(hash)).unwrap().transactions_count(), 1);
+
+		// an empty block is sealed as well
+		let hash = miner.seal_block_now(&*client).unwrap();
+		client.flush_queue();
+		assert_eq!(client.chain_info().best_block_number, 4 as BlockNumber);
+		assert_eq!(client.block_body(BlockId::Hash(hash)).unwrap().transactions_count(), 0);
+		assert!(miner.seal_block_now(&*client).is_some());
+	}
+
+	#[test]
+	fn should_expand_extra_data_template_when_sealing() {
+		let spec = Spec::new_instant();
+		let miner = Miner::with_spec(&spec);
+		miner.set_automine(false);
+		miner.set_extra_data_tag("pool".into());
+		miner.set_extra_data_template("{tag}/{chain}#{counter}".parse().unwrap()).unwrap();
+
+		let client = generate_dummy_client(2);
+
+		let hash = miner.seal_block_now(&*client).unwrap();
+		client.flush_queue();
+		assert_eq!(client.block_header(BlockId::Hash(hash)).unwrap().extra_data(), b"pool/DevelopmentChain#0".to_vec());
+
+		let hash = miner.seal_block_now(&*client).unwrap();
+		client.flush_queue();
+		assert_eq!(client.block_header(BlockId::Hash(hash)).unwrap().extra_data(), b"pool/DevelopmentChain#1".to_vec());
+
+		// template exceeding the limit is rejected
+		assert_eq!(
+			miner.set_extra_data_template("{chain}/{chain}".parse().unwrap()),
+			Err(ExtraDataError::TooLong { max: 32, found: 33 })
+		);
+		// static extra_data discards the template
+		miner.set_extra_data(b"static".to_vec());
+		assert_eq!(miner.extra_data(), b"static".to_vec());
+	}
+
+	#[test]
+	fn should_not_seal_on_demand_with_work_based_engine() {
+		let miner = miner();
+		let client = TestBlockChainClient::default();
+		assert!(miner.seal_block_now(&client).is_none());
+	}
+
 	#[test]
 	fn should_fail_setting_engine_signer_on_pow() {
 		let spec = Spec::new_pow_test_spec;
@@ -1437,4 +2056,26 @@ mod tests {
 		let client = generate_dummy_client_with_spec_and_accounts(spec, None);
 		assert!(match client.miner().set_engine_signer(addr, "".into()) { Err(AccountError::NotFound) => true, _ => false });
 	}
+
+	#[test]
+	fn should_select_uncles_according_to_policy() {
+		let spec = Spec::new_pow_test_spec();
+		let engine = &*spec.engine;
+		let uncle = |number: BlockNumber| {
+			let mut header = Header::new();
+			header.set_number(number);
+			header
+		};
+		let candidates = || vec![uncle(9), uncle(5), uncle(8)];
+
+		let policy = UnclePolicy::default();
+		assert_eq!(policy.select(engine, 10, candidates()).len(), engine.maximum_uncle_count());
+
+		let policy = UnclePolicy { max_count: Some(0), ..Default::default() };
+		assert!(policy.select(engine, 10, candidates()).is_empty());
+
+		let policy = UnclePolicy { min_reward: engine.uncle_reward(&uncle(8), 10).unwrap(), ..Default::default() };
+		let selected: Vec<_> = policy.select(engine, 10, candidates()).into_iter().map(|h| h.number()).collect();
+		assert_eq!(selected, vec![9, 8]);
+	}
 }
@@ -42,7 +42,10 @@
 //! ```
 
 mod banning_queue;
+mod block_ordering;
 mod external;
+mod extra_data;
+mod inclusion_filter;
 mod local_transactions;
 mod miner;
 mod service_transaction_checker;
@@ -50,15 +53,21 @@ mod transaction_queue;
 mod work_notify;
 mod stratum;
 
+pub use self::banning_queue::BanList;
+pub use self::block_ordering::{BlockOrdering, BlockOrderingStrategy, ReadyTransaction};
 pub use self::external::{ExternalMiner, ExternalMinerService};
+pub use self::extra_data::{ExtraDataTemplate, ExtraDataError, Variables as ExtraDataVariables};
+pub use self::inclusion_filter::{InclusionFilter, AddressList, Exclusion};
 
-pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit};
+pub use self::miner::{Miner, MinerOptions, Banning, InvalidBanning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit, UnclePolicy};
 pub use self::transaction_queue::{TransactionQueue, RemovalReason, TransactionDetailsProvider as TransactionQueueDetailsProvider,
-	PrioritizationStrategy, AccountDetails, TransactionOrigin};
+	PrioritizationStrategy, AccountDetails, TransactionOrigin, EvictionStats};
 pub use self::local_transactions::{Status as LocalTransactionStatus};
 pub use client::TransactionImportResult;
 pub use self::work_notify::NotifyWork;
-pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions};
+pub use self::stratum::{
+	Stratum, Error as StratumError, Options as StratumOptions, StratumWorkers, VardiffConfig, WorkerStats,
+};
 
 use std::collections::BTreeMap;
 use bigint::prelude::U256;
@@ -92,6 +101,11 @@ pub trait MinerService : Send + Sync {
 	/// Set the extra_data that we will seal blocks with.
 	fn set_extra_data(&self, extra_data: Bytes);
 
+	/// Set the template of extra_data expanded whenever a block is prepared for sealing.
+	/// Fails if the expanded extra_data exceeds the engine limit.
+	/// Setting static extra_data with `set_extra_data` discards the template.
+	fn set_extra_data_template(&self, template: ExtraDataTemplate) -> Result<(), ExtraDataError>;
+
 	/// Get current minimal gas price for transactions accepted to queue.
 	fn minimal_gas_price(&self) -> U256;
 
@@ -117,6 +131,30 @@ pub trait MinerService : Send + Sync {
 	/// Set maximal number of transactions kept in the queue (both current and future).
 	fn set_transactions_limit(&self, limit: usize);
 
+	/// Set maximal memory usage (in bytes) of transactions kept in the queue.
+	fn set_transactions_memory_limit(&self, limit: usize);
+
+	/// Set maximal number of transactions of a single sender accepted to the queue.
+	fn set_sender_transactions_limit(&self, limit: usize);
+
+	/// Set maximal memory usage (in bytes) of transactions of a single sender accepted to the queue.
+	fn set_sender_transactions_memory_limit(&self, limit: usize);
+
+	/// Get number of transactions dropped from the queue because of its limits.
+	fn transactions_evictions(&self) -> EvictionStats;
+
+	/// Get senders, recipients and codes currently banned by the queue.
+	fn transactions_bans(&self) -> BanList;
+
+	/// Lift all bans of the queue.
+	fn clear_transactions_bans(&self);
+
+	/// Get allow and deny lists applied to transactions included in authored blocks.
+	fn inclusion_filter(&self) -> InclusionFilter;
+
+	/// Set allow and deny lists applied to transactions included in authored blocks.
+	fn set_inclusion_filter(&self, filter: InclusionFilter);
+
 	/// Set maximum amount of gas allowed for any single transaction to mine.
 	fn set_tx_gas_limit(&self, limit: U256);
 
@@ -143,6 +181,16 @@ pub trait MinerService : Send + Sync {
 	/// New chain head event. Restart mining operation.
 	fn update_sealing(&self, chain: &MiningBlockChainClient);
 
+	/// Seal a new block right away, even if it's empty, and return its hash.
+	/// Returns `None` if the engine is not able to seal internally.
+	fn seal_block_now(&self, chain: &MiningBlockChainClient) -> Option<H256>;
+
+	/// Set whether blocks should be sealed internally as soon as transactions arrive.
+	fn set_automine(&self, enabled: bool);
+
+	/// Whether blocks are sealed internally as soon as transactions arrive.
+	fn automine(&self) -> bool;
+
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, chain: &MiningBlockChainClient, pow_hash: H256, seal: Vec<Bytes>) -> Result<(), Error>;
@@ -151,6 +199,10 @@ pub trait MinerService : Send + Sync {
 	fn map_sealing_work<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> Option<T>
 		where F: FnOnce(&ClosedBlock) -> T, Self: Sized;
 
+	/// Assemble a block on top of the best block from the transactions in the queue,
+	/// using given beneficiary and gas limit. The block is not sealed nor used for sealing.
+	fn dry_run_block(&self, chain: &MiningBlockChainClient, author: Address, gas_limit: U256) -> ClosedBlock;
+
 	/// Query pending transactions for hash.
 	fn transaction(&self, best_block: BlockNumber, hash: &H256) -> Option<PendingTransaction>;
 
@@ -167,6 +219,9 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of all future transactions.
 	fn future_transactions(&self) -> Vec<PendingTransaction>;
 
+	/// Get a list of transactions which conditions are not yet met at given block.
+	fn scheduled_transactions(&self, best_block: BlockNumber, best_block_timestamp: u64) -> Vec<PendingTransaction>;
+
 	/// Get a list of local transactions with statuses.
 	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus>;
 
@@ -21,20 +21,28 @@ use ethcore_stratum::{
 	Stratum as StratumService, Error as StratumServiceError,
 };
 
+pub use ethcore_stratum::{VardiffConfig, WorkerStats};
+
+use std::cmp;
+use std::collections::VecDeque;
 use std::sync::{Arc, Weak};
 use std::net::{SocketAddr, AddrParseError};
 use std::fmt;
 
 use bigint::prelude::U256;
 use bigint::hash::{H64, H256, clean_0x};
+use error::Error as EthcoreError;
 use ethereum::ethash::Ethash;
-use ethash::SeedHashCompute;
+use ethash::{SeedHashCompute, quick_get_difficulty};
 use parking_lot::Mutex;
 use miner::{self, Miner, MinerService};
 use client::Client;
 use block::IsBlock;
 use rlp::encode;
 
+/// Number of most recent jobs shares are accepted for.
+const RECENT_JOBS: usize = 8;
+
 /// Configures stratum server options.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Options {
@@ -46,6 +54,8 @@ pub struct Options {
 	pub port: u16,
 	/// Secret for peers
 	pub secret: Option<H256>,
+	/// Variable share difficulty settings, shares have to seal a block if not set
+	pub vardiff: Option<VardiffConfig>,
 }
 
 struct SubmitPayload {
@@ -109,6 +119,8 @@ impl fmt::Display for PayloadError {
 /// Job dispatcher for stratum service
 pub struct StratumJobDispatcher {
 	seed_compute: Mutex<SeedHashCompute>,
+	/// Recently dispatched work (pow hash, block difficulty, block number), most recent last
+	jobs: Mutex<VecDeque<(H256, U256, u64)>>,
 	client: Weak<Client>,
 	miner: Weak<Miner>,
 }
@@ -126,7 +138,18 @@ impl JobDispatcher for StratumJobDispatcher {
 				let number = b.block().header().number();
 				let difficulty = b.block().header().difficulty();
 
-				self.payload(pow_hash, *difficulty, number)
+				self.payload(pow_hash, *difficulty, number, None)
+			})
+		)
+	}
+
+	fn job_with_difficulty(&self, share_difficulty: U256) -> Option<String> {
+		self.with_core(|client, miner| miner.map_sealing_work(&*client, |b| {
+				let pow_hash = b.hash();
+				let number = b.block().header().number();
+				let difficulty = b.block().header().difficulty();
+
+				self.payload(pow_hash, *difficulty, number, Some(share_difficulty))
 			})
 		)
 	}
@@ -148,6 +171,7 @@ impl JobDispatcher for StratumJobDispatcher {
 			let seal = vec![encode(&payload.mix_hash).into_vec(), encode(&payload.nonce).into_vec()];
 			match miner.submit_seal(&*client, payload.pow_hash, seal) {
 				Ok(_) => Ok(()),
+				Err(EthcoreError::PowHashInvalid) => Err(StratumServiceError::StaleShare),
 				Err(e) => {
 					warn!(target: "stratum", "submit_seal error: {:?}", e);
 					Err(StratumServiceError::Dispatch(e.to_string()))
@@ -155,6 +179,60 @@ impl JobDispatcher for StratumJobDispatcher {
 			}
 		})
 	}
+
+	fn submit_share(&self, payload: Vec<String>, share_difficulty: U256) -> Result<bool, StratumServiceError> {
+		let payload = SubmitPayload::from_args(payload).map_err(|e|
+			StratumServiceError::Dispatch(e.to_string())
+		)?;
+
+		let (block_difficulty, block_number, is_current) = {
+			let jobs = self.jobs.lock();
+			let is_current = jobs.back().map_or(false, |&(hash, _, _)| hash == payload.pow_hash);
+			match jobs.iter().find(|&&(hash, _, _)| hash == payload.pow_hash) {
+				Some(&(_, block_difficulty, block_number)) => (block_difficulty, block_number, is_current),
+				None => return Err(StratumServiceError::Dispatch(format!("Unknown work {}", payload.pow_hash))),
+			}
+		};
+
+		let client = match self.client.upgrade() {
+			Some(client) => client,
+			None => return Err(StratumServiceError::Dispatch("Client is shutting down".into())),
+		};
+		let progpow = client.engine().progpow_transition()
+			.map_or(false, |transition| block_number >= transition);
+
+		// the quick check trusts the worker's mix hash, so it only filters out shares cheaply
+		let claimed = Ethash::boundary_to_difficulty(&H256(quick_get_difficulty(
+			&payload.pow_hash.0,
+			payload.nonce.low_u64(),
+			&payload.mix_hash.0,
+			progpow
+		)));
+		if claimed < share_difficulty {
+			return Err(StratumServiceError::Dispatch(format!("Share below target: {} < {}", claimed, share_difficulty)));
+		}
+
+		let difficulty = match client.engine().pow_difficulty(block_number, &payload.pow_hash, payload.nonce.low_u64(), &payload.mix_hash) {
+			Some(difficulty) => difficulty,
+			None => return Err(StratumServiceError::Dispatch(format!("Invalid mix hash {}", payload.mix_hash))),
+		};
+		if difficulty < share_difficulty {
+			return Err(StratumServiceError::Dispatch(format!("Share below target: {} < {}", difficulty, share_difficulty)));
+		}
+		if difficulty < block_difficulty {
+			return if is_current { Ok(false) } else { Err(StratumServiceError::StaleShare) };
+		}
+
+		let seal = vec![encode(&payload.mix_hash).into_vec(), encode(&payload.nonce).into_vec()];
+		self.with_core_result(|client, miner| match miner.submit_seal(&*client, payload.pow_hash, seal.clone()) {
+			Ok(_) => Ok(()),
+			Err(EthcoreError::PowHashInvalid) => Err(StratumServiceError::StaleShare),
+			Err(e) => {
+				warn!(target: "stratum", "submit_seal error: {:?}", e);
+				Err(StratumServiceError::Dispatch(e.to_string()))
+			}
+		}).map(|_| true)
+	}
 }
 
 impl StratumJobDispatcher {
@@ -162,15 +240,29 @@ impl StratumJobDispatcher {
 	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
 		StratumJobDispatcher {
 			seed_compute: Mutex::new(SeedHashCompute::new()),
+			jobs: Mutex::new(VecDeque::with_capacity(RECENT_JOBS)),
 			client: client,
 			miner: miner,
 		}
 	}
 
-	/// Serializes payload for stratum service
-	fn payload(&self, pow_hash: H256, difficulty: U256, number: u64) -> String {
+	/// Serializes payload for stratum service, with the target of the share difficulty if given
+	fn payload(&self, pow_hash: H256, difficulty: U256, number: u64, share_difficulty: Option<U256>) -> String {
+		{
+			let mut jobs = self.jobs.lock();
+			if jobs.back().map_or(true, |&(hash, _, _)| hash != pow_hash) {
+				if jobs.len() == RECENT_JOBS {
+					jobs.pop_front();
+				}
+				jobs.push_back((pow_hash, difficulty, number));
+			}
+		}
+
 		// TODO: move this to engine
-		let target = Ethash::difficulty_to_boundary(&difficulty);
+		let target = match share_difficulty {
+			Some(share_difficulty) => Ethash::difficulty_to_boundary(&cmp::min(share_difficulty, difficulty)),
+			None => Ethash::difficulty_to_boundary(&difficulty),
+		};
 		let seed_hash = &self.seed_compute.lock().get_seedhash(number);
 		let seed_hash = H256::from_slice(&seed_hash[..]);
 		format!(
@@ -218,14 +310,26 @@ impl super::work_notify::NotifyWork for Stratum {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
 		trace!(target: "stratum", "Notify work");
 
-		self.service.push_work_all(
-			self.dispatcher.payload(pow_hash, difficulty, number)
+		self.service.push_work_each(
+			|share_difficulty| Some(self.dispatcher.payload(pow_hash, difficulty, number, share_difficulty))
 		).unwrap_or_else(
 			|e| warn!(target: "stratum", "Error while pushing work: {:?}", e)
 		);
 	}
 }
 
+/// Share statistics of the workers connected to the stratum server.
+pub trait StratumWorkers: Send + Sync {
+	/// Statistics of the authorized workers.
+	fn workers(&self) -> Vec<WorkerStats>;
+}
+
+impl StratumWorkers for StratumService {
+	fn workers(&self) -> Vec<WorkerStats> {
+		StratumService::workers(self)
+	}
+}
+
 impl Stratum {
 
 	/// New stratum job dispatcher, given the miner, client and dedicated stratum service
@@ -238,6 +342,7 @@ impl Stratum {
 			&SocketAddr::new(options.listen_addr.parse::<IpAddr>()?, options.port),
 			dispatcher.clone(),
 			options.secret.clone(),
+			options.vardiff.clone(),
 		)?;
 
 		Ok(Stratum {
@@ -246,10 +351,12 @@ impl Stratum {
 		})
 	}
 
-	/// Start STRATUM job dispatcher and register it in the miner
-	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
+	/// Start STRATUM job dispatcher and register it in the miner.
+	/// Returns share statistics of the connected workers.
+	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<Arc<StratumWorkers>, Error> {
 		let stratum = miner::Stratum::start(cfg, Arc::downgrade(&miner.clone()), client)?;
+		let workers = stratum.service.clone() as Arc<StratumWorkers>;
 		miner.push_notifier(Box::new(stratum) as Box<miner::NotifyWork>);
-		Ok(())
+		Ok(workers)
 	}
 }
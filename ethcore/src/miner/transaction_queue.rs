@@ -116,6 +116,7 @@ use transaction::*;
 use error::{Error, TransactionError};
 use client::TransactionImportResult;
 use header::BlockNumber;
+use miner::block_ordering::ReadyTransaction;
 use miner::local_transactions::{LocalTransactionsList, Status as LocalTransactionStatus};
 
 /// Transaction origin
@@ -380,6 +381,7 @@ struct TransactionSet {
 	limit: usize,
 	total_gas_limit: U256,
 	memory_limit: usize,
+	evictions: EvictionStats,
 }
 
 impl TransactionSet {
@@ -413,6 +415,8 @@ impl TransactionSet {
 		let mut count = 0;
 		let mut mem_usage = 0;
 		let mut gas: U256 = 0.into();
+		let mut evictions = EvictionStats::default();
+		let (limit, memory_limit, total_gas_limit) = (self.limit, self.memory_limit, self.total_gas_limit);
 		let to_drop : Vec<(Address, U256)> = {
 			self.by_priority
 				.iter()
@@ -428,13 +432,27 @@ impl TransactionSet {
 
 					let is_own_or_retracted = order.origin.is_local() || order.origin == TransactionOrigin::RetractedBlock;
 					// Own and retracted transactions are allowed to go above all limits.
-					!is_own_or_retracted && (mem_usage > self.memory_limit || count > self.limit || gas > self.total_gas_limit)
+					if is_own_or_retracted {
+						false
+					} else if mem_usage > memory_limit {
+						evictions.memory_limit += 1;
+						true
+					} else if count > limit {
+						evictions.count_limit += 1;
+						true
+					} else if gas > total_gas_limit {
+						evictions.gas_limit += 1;
+						true
+					} else {
+						false
+					}
 				})
 				.map(|order| by_hash.get(&order.hash)
 					.expect("All transactions in `self.by_priority` and `self.by_address` are kept in sync with `by_hash`."))
 				.map(|tx| (tx.sender(), tx.nonce()))
 				.collect()
 		};
+		self.evictions.add(&evictions);
 
 		Some(to_drop.into_iter()
 			.fold(HashMap::new(), |mut removed, (sender, nonce)| {
@@ -484,6 +502,13 @@ impl TransactionSet {
 		self.limit = limit;
 	}
 
+	/// Returns number of transactions and memory used by transactions of given sender.
+	fn sender_usage(&self, sender: &Address) -> (usize, usize) {
+		self.by_address.row(sender).map_or((0, 0), |by_nonce| {
+			(by_nonce.len(), by_nonce.values().map(|order| order.mem_usage).sum())
+		})
+	}
+
 	/// Get the minimum gas price that we can accept into this queue that wouldn't cause the transaction to
 	/// immediately be dropped. 0 if the queue isn't at capacity; 1 plus the lowest if it is.
 	fn gas_price_entry_limit(&self) -> U256 {
@@ -503,6 +528,28 @@ pub struct TransactionQueueStatus {
 	pub future: usize,
 }
 
+/// Number of transactions dropped from the queue because of its limits.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EvictionStats {
+	/// Transactions dropped because the queue was full.
+	pub count_limit: u64,
+	/// Transactions dropped because of the memory limit.
+	pub memory_limit: u64,
+	/// Transactions dropped because of the total gas limit.
+	pub gas_limit: u64,
+	/// Transactions rejected because their sender reached its limits.
+	pub sender_limit: u64,
+}
+
+impl EvictionStats {
+	fn add(&mut self, other: &EvictionStats) {
+		self.count_limit += other.count_limit;
+		self.memory_limit += other.memory_limit;
+		self.gas_limit += other.gas_limit;
+		self.sender_limit += other.sender_limit;
+	}
+}
+
 /// Details of account
 pub struct AccountDetails {
 	/// Most recent account nonce
@@ -512,8 +559,8 @@ pub struct AccountDetails {
 }
 
 /// Transaction with the same (sender, nonce) can be replaced only if
-/// `new_gas_price > old_gas_price + old_gas_price >> SHIFT`
-const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
+/// `new_gas_price >= old_gas_price + old_gas_price * PERCENT / 100`
+pub const DEFAULT_REPLACE_BUMP_PERCENT: usize = 12;
 
 /// Future queue limits are lower from current queue limits:
 /// `future_limit = current_limit >> SHIFT`
@@ -591,6 +638,14 @@ pub struct TransactionQueue {
 	local_transactions: LocalTransactionsList,
 	/// Next id that should be assigned to a transaction imported to the queue.
 	next_transaction_id: u64,
+	/// Gas price bump (in percent) required to replace a transaction with the same (sender, nonce).
+	replace_bump: usize,
+	/// Maximal number of transactions of a single sender (both current and future).
+	sender_limit: usize,
+	/// Maximal memory usage of transactions of a single sender (both current and future).
+	sender_memory_limit: usize,
+	/// Number of transactions rejected because of per-sender limits.
+	sender_rejections: u64,
 }
 
 impl Default for TransactionQueue {
@@ -620,6 +675,7 @@ impl TransactionQueue {
 			limit,
 			total_gas_limit,
 			memory_limit,
+			evictions: Default::default(),
 		};
 
 		let future = TransactionSet {
@@ -629,6 +685,7 @@ impl TransactionQueue {
 			total_gas_limit: total_gas_limit >> FUTURE_QUEUE_LIMITS_SHIFT,
 			limit: limit >> FUTURE_QUEUE_LIMITS_SHIFT,
 			memory_limit: memory_limit >> FUTURE_QUEUE_LIMITS_SHIFT,
+			evictions: Default::default(),
 		};
 
 		TransactionQueue {
@@ -643,6 +700,10 @@ impl TransactionQueue {
 			last_nonces: HashMap::new(),
 			local_transactions: LocalTransactionsList::default(),
 			next_transaction_id: 0,
+			replace_bump: DEFAULT_REPLACE_BUMP_PERCENT,
+			sender_limit: usize::max_value(),
+			sender_memory_limit: usize::max_value(),
+			sender_rejections: 0,
 		}
 	}
 
@@ -660,6 +721,55 @@ impl TransactionQueue {
 		self.current.limit
 	}
 
+	/// Sets the new memory limit (in bytes) for `current` and `future` queue.
+	pub fn set_memory_limit(&mut self, memory_limit: usize) {
+		self.current.memory_limit = memory_limit;
+		self.future.memory_limit = memory_limit >> FUTURE_QUEUE_LIMITS_SHIFT;
+		let removed = self.current.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+		self.update_last_nonces(&removed);
+		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+	}
+
+	/// Returns current memory limit (in bytes) of transactions in the queue.
+	pub fn memory_limit(&self) -> usize {
+		self.current.memory_limit
+	}
+
+	/// Sets maximal number of transactions of a single sender.
+	/// The limit is checked when importing external transactions only,
+	/// transactions already in the queue are not affected.
+	pub fn set_sender_limit(&mut self, limit: usize) {
+		self.sender_limit = limit;
+	}
+
+	/// Returns maximal number of transactions of a single sender.
+	pub fn sender_limit(&self) -> usize {
+		self.sender_limit
+	}
+
+	/// Sets maximal memory usage (in bytes) of transactions of a single sender.
+	/// The limit is checked when importing external transactions only,
+	/// transactions already in the queue are not affected.
+	pub fn set_sender_memory_limit(&mut self, memory_limit: usize) {
+		self.sender_memory_limit = memory_limit;
+	}
+
+	/// Returns maximal memory usage (in bytes) of transactions of a single sender.
+	pub fn sender_memory_limit(&self) -> usize {
+		self.sender_memory_limit
+	}
+
+	/// Returns number of transactions dropped because of the queue limits so far.
+	pub fn eviction_stats(&self) -> EvictionStats {
+		let mut stats = EvictionStats {
+			sender_limit: self.sender_rejections,
+			..Default::default()
+		};
+		stats.add(&self.current.evictions);
+		stats.add(&self.future.evictions);
+		stats
+	}
+
 	/// Get the minimal gas price.
 	pub fn minimal_gas_price(&self) -> &U256 {
 		&self.minimal_gas_price
@@ -696,6 +806,20 @@ impl TransactionQueue {
 		self.tx_gas_limit = limit;
 	}
 
+	/// Returns the gas price bump (in percent) required to replace a transaction.
+	pub fn replace_bump(&self) -> usize {
+		self.replace_bump
+	}
+
+	/// Sets the gas price bump (in percent) required to replace a transaction
+	/// with the same `(sender, nonce)`.
+	///
+	/// Cancellations (see `is_cancellation`) only need to pay strictly more than
+	/// the replaced transaction, regardless of this setting.
+	pub fn set_replace_bump(&mut self, percent: usize) {
+		self.replace_bump = percent;
+	}
+
 	/// Returns current status for this queue
 	pub fn status(&self) -> TransactionQueueStatus {
 		TransactionQueueStatus {
@@ -862,6 +986,12 @@ impl TransactionQueue {
 			}));
 		}
 		tx.check_low_s()?;
+
+		// Own and retracted transactions are allowed to go above all limits.
+		if !origin.is_local() && origin != TransactionOrigin::RetractedBlock {
+			self.check_sender_limits(&tx)?;
+		}
+
 		// No invalid transactions beyond this point.
 		let id = self.next_transaction_id;
 		self.next_transaction_id += 1;
@@ -871,6 +1001,32 @@ impl TransactionQueue {
 		r
 	}
 
+	/// Checks if the transaction would make its sender exceed per-sender limits.
+	/// Replacing a transaction with the same `(sender, nonce)` does not increase the count.
+	fn check_sender_limits(&mut self, tx: &SignedTransaction) -> Result<(), Error> {
+		let sender = tx.sender();
+		let (current_count, current_mem) = self.current.sender_usage(&sender);
+		let (future_count, future_mem) = self.future.sender_usage(&sender);
+		let replaced = self.current.by_address.get(&sender, &tx.nonce)
+			.or_else(|| self.future.by_address.get(&sender, &tx.nonce))
+			.map(|order| order.mem_usage);
+
+		let count = current_count + future_count + if replaced.is_some() { 0 } else { 1 };
+		let mem_usage = (current_mem + future_mem + tx.heap_size_of_children()).saturating_sub(replaced.unwrap_or(0));
+		if count > self.sender_limit || mem_usage > self.sender_memory_limit {
+			trace!(target: "txqueue",
+				"Dropping transaction of sender above limits: {:?} (sender: {:?}, count: {}, memory: {})",
+				tx.hash(),
+				sender,
+				count,
+				mem_usage,
+			);
+			self.sender_rejections += 1;
+			return Err(Error::Transaction(TransactionError::SenderLimitReached));
+		}
+		Ok(())
+	}
+
 	/// Removes all transactions from particular sender up to (excluding) given client (state) nonce.
 	/// Client (State) Nonce = next valid nonce for this sender.
 	pub fn cull(&mut self, sender: Address, client_nonce: U256) {
@@ -1110,7 +1266,7 @@ impl TransactionQueue {
 					self.local_transactions.mark_future(order.hash);
 				}
 				if let Some(old) = self.future.insert(*sender, k, order.clone()) {
-					Self::replace_orders(*sender, k, old, order, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
+					let _ = Self::replace_orders(*sender, k, old, order, self.replace_bump, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
 				}
 			} else {
 				trace!(target: "txqueue", "Removing old transaction: {:?} (nonce: {} < {})", order.hash, k, current_nonce);
@@ -1164,6 +1320,18 @@ impl TransactionQueue {
 		r
 	}
 
+	/// Returns top transactions from the queue ordered by priority together
+	/// with details used by block ordering strategies.
+	pub fn ready_transactions_at(&self, best_block: BlockNumber, best_timestamp: u64, nonce_cap: Option<U256>) -> Vec<ReadyTransaction> {
+		let mut r = Vec::new();
+		self.filter_pending_transaction(best_block, best_timestamp, nonce_cap, |tx| r.push(ReadyTransaction {
+			transaction: tx.transaction.clone(),
+			arrival: tx.insertion_id,
+			is_local: tx.origin.is_local(),
+		}));
+		r
+	}
+
 	/// Return all ready transactions.
 	pub fn pending_transactions(&self, best_block: BlockNumber, best_timestamp: u64) -> Vec<PendingTransaction> {
 		let mut r = Vec::new();
@@ -1180,6 +1348,21 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Return all transactions which conditions are not yet met at given block and timestamp.
+	pub fn scheduled_transactions(&self, best_block: BlockNumber, best_timestamp: u64) -> Vec<PendingTransaction> {
+		self.current.by_priority
+			.iter()
+			.chain(self.future.by_priority.iter())
+			.map(|t| self.by_hash.get(&t.hash).expect("All transactions in `current` and `future` are always included in `by_hash`"))
+			.filter(|t| match t.condition {
+				Some(Condition::Number(n)) => n > best_block,
+				Some(Condition::Timestamp(t)) => t > best_timestamp,
+				None => false,
+			})
+			.map(|t| PendingTransaction { transaction: t.transaction.clone(), condition: t.condition.clone() })
+			.collect()
+	}
+
 	/// Returns local transactions (some of them might not be part of the queue anymore).
 	pub fn local_transactions(&self) -> &LinkedHashMap<H256, LocalTransactionStatus> {
 		self.local_transactions.all_transactions()
@@ -1211,6 +1394,11 @@ impl TransactionQueue {
 		self.last_nonces.clear();
 	}
 
+	/// Checks if there is a transaction with given `(sender, nonce)` in the queue.
+	pub fn contains_nonce(&self, sender: &Address, nonce: &U256) -> bool {
+		self.current.by_address.get(sender, nonce).is_some() || self.future.by_address.get(sender, nonce).is_some()
+	}
+
 	/// Returns highest transaction nonce for given address.
 	pub fn last_nonce(&self, address: &Address) -> Option<U256> {
 		self.last_nonces.get(address).cloned()
@@ -1236,7 +1424,7 @@ impl TransactionQueue {
 					self.local_transactions.mark_pending(order.hash);
 				}
 				if let Some(old) = self.current.insert(address, current_nonce, order.clone()) {
-					Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
+					let _ = Self::replace_orders(address, current_nonce, old, order, self.replace_bump, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
 				}
 				update_last_nonce_to = Some(current_nonce);
 				current_nonce = current_nonce + U256::one();
@@ -1271,6 +1459,7 @@ impl TransactionQueue {
 		let address = tx.sender();
 		let nonce = tx.nonce();
 		let hash = tx.hash();
+		let gas_price = tx.transaction.gas_price;
 
 		// The transaction might be old, let's check that.
 		// This has to be the first test, otherwise calculating
@@ -1300,7 +1489,8 @@ impl TransactionQueue {
 			// We have a gap - put to future.
 			// Insert transaction (or replace old one with lower gas price)
 			check_too_cheap(
-				Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.future, &mut self.by_hash, &mut self.local_transactions)
+				gas_price,
+				Self::replace_transaction(tx, state_nonce, min_gas_price, self.replace_bump, &mut self.future, &mut self.by_hash, &mut self.local_transactions)
 			)?;
 			// Enforce limit in Future
 			let removed = self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
@@ -1318,7 +1508,8 @@ impl TransactionQueue {
 
 		// Replace transaction if any
 		check_too_cheap(
-			Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.current, &mut self.by_hash, &mut self.local_transactions)
+			gas_price,
+			Self::replace_transaction(tx, state_nonce, min_gas_price, self.replace_bump, &mut self.current, &mut self.by_hash, &mut self.local_transactions)
 		)?;
 		// Keep track of highest nonce stored in current
 		let new_max = self.last_nonces.get(&address).map_or(nonce, |n| cmp::max(nonce, *n));
@@ -1351,19 +1542,21 @@ impl TransactionQueue {
 
 	/// Replaces transaction in given set (could be `future` or `current`).
 	///
-	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher.
+	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher
+	/// by at least `bump` percent (see `replace_orders`).
 	/// One of the transactions is dropped from set and also removed from queue entirely (from `by_hash`).
 	///
-	/// Returns `true` if transaction actually got to the queue (`false` if there was already a transaction with higher
-	/// gas_price)
+	/// Returns an error containing the minimal gas price required for the replacement
+	/// if the transaction didn't get to the queue.
 	fn replace_transaction(
 		tx: VerifiedTransaction,
 		base_nonce: U256,
 		min_gas_price: (U256, PrioritizationStrategy),
+		bump: usize,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), U256> {
 		let order = TransactionOrder::for_transaction(&tx, base_nonce, min_gas_price.0, min_gas_price.1);
 		let hash = tx.hash();
 		let address = tx.sender();
@@ -1375,28 +1568,34 @@ impl TransactionQueue {
 		trace!(target: "txqueue", "Inserting: {:?}", order);
 
 		if let Some(old) = set.insert(address, nonce, order.clone()) {
-			Self::replace_orders(address, nonce, old, order, set, by_hash, local)
+			Self::replace_orders(address, nonce, old, order, bump, set, by_hash, local)
 		} else {
-			true
+			Ok(())
 		}
 	}
 
+	/// Decides which of two transactions with the same `(sender, nonce)` stays in the set.
+	///
+	/// The new transaction has to pay at least `bump` percent more than the old one,
+	/// unless it is a cancellation, which only has to pay strictly more.
 	fn replace_orders(
 		address: Address,
 		nonce: U256,
 		old: TransactionOrder,
 		order: TransactionOrder,
+		bump: usize,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), U256> {
 		// There was already transaction in queue. Let's check which one should stay
 		let old_hash = old.hash;
 		let new_hash = order.hash;
 
 		let old_gas_price = old.gas_price;
 		let new_gas_price = order.gas_price;
-		let min_required_gas_price = old_gas_price + (old_gas_price >> GAS_PRICE_BUMP_SHIFT);
+		let is_cancel = by_hash.get(&new_hash).map_or(false, |tx| is_cancellation(&tx.transaction));
+		let min_required_gas_price = replacement_gas_price(old_gas_price, bump, is_cancel);
 
 		if min_required_gas_price > new_gas_price {
 			trace!(target: "txqueue", "Didn't insert transaction because gas price was too low: {:?} ({:?} stays in the queue)", order.hash, old.hash);
@@ -1407,7 +1606,7 @@ impl TransactionQueue {
 			if order.origin.is_local() {
 				local.mark_replaced(order.transaction, old_gas_price, old_hash);
 			}
-			false
+			Err(min_required_gas_price)
 		} else {
 			trace!(target: "txqueue", "Replaced transaction: {:?} with transaction with higher gas price: {:?}", old.hash, order.hash);
 			// Make sure we remove old transaction entirely
@@ -1415,19 +1614,31 @@ impl TransactionQueue {
 			if old.origin.is_local() {
 				local.mark_replaced(old.transaction, new_gas_price, new_hash);
 			}
-			true
+			Ok(())
 		}
 	}
 }
 
-fn check_too_cheap(is_in: bool) -> Result<(), TransactionError> {
-	if is_in {
-		Ok(())
+/// Returns `true` if the transaction is an explicit cancellation:
+/// a zero-value call to the sender itself without any data.
+pub fn is_cancellation(tx: &SignedTransaction) -> bool {
+	tx.value.is_zero() && tx.data.is_empty() && tx.action == Action::Call(tx.sender())
+}
+
+/// Minimal gas price required to replace a transaction paying `old_gas_price`.
+fn replacement_gas_price(old_gas_price: U256, bump: usize, is_cancel: bool) -> U256 {
+	let bumped = old_gas_price.saturating_add(old_gas_price.saturating_mul(U256::from(bump as u64)) / U256::from(100));
+	if is_cancel {
+		cmp::min(bumped, old_gas_price.saturating_add(U256::one()))
 	} else {
-		Err(TransactionError::TooCheapToReplace)
+		bumped
 	}
 }
 
+fn check_too_cheap(got: U256, replaced: Result<(), U256>) -> Result<(), TransactionError> {
+	replaced.map_err(|minimal| TransactionError::TooCheapToReplace { minimal: minimal, got: got })
+}
+
 fn check_if_removed(sender: &Address, nonce: &U256, dropped: Option<HashMap<Address, U256>>) -> Result<(), TransactionError> {
 	match dropped {
 		Some(ref dropped) => match dropped.get(sender) {
@@ -1443,6 +1654,7 @@ fn check_if_removed(sender: &Address, nonce: &U256, dropped: Option<HashMap<Addr
 
 #[cfg(test)]
 pub mod test {
+	use heapsize::HeapSizeOf;
 	use rustc_hex::FromHex;
 	use table::Table;
 	use util::*;
@@ -1660,6 +1872,7 @@ pub mod test {
 			limit: 1,
 			total_gas_limit: !U256::zero(),
 			memory_limit: usize::max_value(),
+			evictions: Default::default(),
 		};
 		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
 		let tx1 = VerifiedTransaction::new(tx1, TransactionOrigin::External, None, 0, 0);
@@ -1702,6 +1915,7 @@ pub mod test {
 			limit: 1,
 			total_gas_limit: !U256::zero(),
 			memory_limit: 0,
+			evictions: Default::default(),
 		};
 		// Create two transactions with same nonce
 		// (same hash)
@@ -1752,6 +1966,7 @@ pub mod test {
 			limit: 2,
 			total_gas_limit: !U256::zero(),
 			memory_limit: 0,
+			evictions: Default::default(),
 		};
 		let tx = new_tx_default();
 		let tx1 = VerifiedTransaction::new(tx.clone(), TransactionOrigin::External, None, 0, 0);
@@ -1771,6 +1986,7 @@ pub mod test {
 			limit: 1,
 			total_gas_limit: !U256::zero(),
 			memory_limit: 0,
+			evictions: Default::default(),
 		};
 
 		assert_eq!(set.gas_price_entry_limit(), 0.into());
@@ -2270,6 +2486,45 @@ pub mod test {
 		assert_eq!(top.len(), 2);
 	}
 
+	#[test]
+	fn should_return_scheduled_transactions() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
+		let tx3 = new_tx_default();
+
+		// when
+		txq.add(tx.clone(), TransactionOrigin::Local, 0, Some(Condition::Number(2)), &default_tx_provider()).unwrap();
+		txq.add(tx2.clone(), TransactionOrigin::Local, 0, Some(Condition::Timestamp(100)), &default_tx_provider()).unwrap();
+		txq.add(tx3.clone(), TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		let hashes = |txs: Vec<PendingTransaction>| txs.into_iter().map(|tx| tx.hash()).collect::<HashSet<_>>();
+		assert_eq!(hashes(txq.scheduled_transactions(1, 50)), vec![tx.hash(), tx2.hash()].into_iter().collect());
+		assert_eq!(hashes(txq.scheduled_transactions(2, 50)), vec![tx2.hash()].into_iter().collect());
+		assert!(txq.scheduled_transactions(2, 100).is_empty());
+	}
+
+	#[test]
+	fn should_return_ready_transactions_with_details() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
+
+		// when
+		txq.add(tx.clone(), TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		let ready = txq.ready_transactions_at(BlockNumber::max_value(), u64::max_value(), None);
+		assert_eq!(ready.len(), 2);
+		assert_eq!(ready[0].transaction, tx);
+		assert!(ready[0].is_local);
+		assert_eq!(ready[1].transaction, tx2);
+		assert!(!ready[1].is_local);
+		assert!(ready[0].arrival < ready[1].arrival);
+	}
+
 	#[test]
 	fn should_correctly_update_futures_when_removing() {
 		// given
@@ -2450,6 +2705,65 @@ pub mod test {
 		// limited by gas
 		txq.add(tx4.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap_err();
 		assert_eq!(txq.status().pending, 2);
+		assert_eq!(txq.eviction_stats().gas_limit, 1);
+	}
+
+	#[test]
+	fn should_limit_transactions_per_sender() {
+		// given
+		let mut txq = TransactionQueue::default();
+		txq.set_sender_limit(2);
+		let keypair = Random.generate().unwrap();
+		let tx = |nonce: u64, gas_price: u64| new_unsigned_tx((123 + nonce).into(), default_gas_val(), gas_price.into()).sign(keypair.secret(), None);
+		txq.add(tx(0, 1), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx(2, 1), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		let res = txq.add(tx(1, 1), TransactionOrigin::External, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::SenderLimitReached);
+		assert_eq!(txq.eviction_stats().sender_limit, 1);
+		// replacements and own transactions are still accepted
+		txq.add(tx(2, 2), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx(1, 1), TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+		assert_eq!(txq.status().pending, 3);
+	}
+
+	#[test]
+	fn should_limit_memory_per_sender() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
+		let (tx3, _) = new_tx_pair_default(1.into(), 0.into());
+		txq.set_sender_memory_limit(tx1.heap_size_of_children() + tx2.heap_size_of_children() - 1);
+		txq.add(tx1.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		let res = txq.add(tx2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::SenderLimitReached);
+		txq.add(tx3.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		assert_eq!(txq.status().pending, 2);
+	}
+
+	#[test]
+	fn should_drop_transactions_when_memory_limit_is_lowered() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
+		txq.add(tx1.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		txq.set_memory_limit(tx1.heap_size_of_children());
+
+		// then
+		assert_eq!(txq.memory_limit(), tx1.heap_size_of_children());
+		assert_eq!(txq.top_transactions(), vec![tx1.clone()]);
+		assert_eq!(txq.last_nonce(&tx1.sender()), Some(tx1.nonce));
+		assert_eq!(txq.eviction_stats(), EvictionStats { memory_limit: 1, ..Default::default() });
 	}
 
 	#[test]
@@ -2570,7 +2884,10 @@ pub mod test {
 		let res = txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider());
 
 		// then
-		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace);
+		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace {
+			minimal: 22.into(),
+			got: 21.into(),
+		});
 		let stats = txq.status();
 		assert_eq!(stats.pending, 1);
 		assert_eq!(stats.future, 0);
@@ -2602,6 +2919,66 @@ pub mod test {
 		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(20));
 	}
 
+	#[test]
+	fn should_use_configured_replace_bump() {
+		// given
+		let mut txq = TransactionQueue::default();
+		txq.set_replace_bump(50);
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 100.into()).sign(keypair.secret(), None);
+		let with_gas_price = |gas_price: u64| {
+			let mut tx = (**tx).clone();
+			tx.gas_price = gas_price.into();
+			tx.sign(keypair.secret(), None)
+		};
+		let (tx2, tx3) = (with_gas_price(149), with_gas_price(150));
+
+		// when
+		txq.add(tx.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		let res = txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider());
+		txq.add(tx3, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace {
+			minimal: 150.into(),
+			got: 149.into(),
+		});
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(150));
+	}
+
+	#[test]
+	fn should_replace_with_cancellation_paying_more() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 100.into()).sign(keypair.secret(), None);
+		let cancel = |gas_price: u64| Transaction {
+			action: Action::Call(keypair.address()),
+			value: 0.into(),
+			data: vec![],
+			gas: 21_000.into(),
+			gas_price: gas_price.into(),
+			nonce: 123.into(),
+		}.sign(keypair.secret(), None);
+		let (cancel1, cancel2) = (cancel(100), cancel(101));
+		assert!(is_cancellation(&cancel1));
+		assert!(!is_cancellation(&tx));
+
+		// when
+		txq.add(tx, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		let res = txq.add(cancel1, TransactionOrigin::External, 0, None, &default_tx_provider());
+		txq.add(cancel2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace {
+			minimal: 101.into(),
+			got: 100.into(),
+		});
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.top_transactions()[0].hash(), cancel2.hash());
+	}
+
 	#[test]
 	fn should_replace_same_transaction_when_importing_to_futures() {
 		// given
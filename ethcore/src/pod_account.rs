@@ -61,27 +61,35 @@ impl PodAccount {
 
 	/// Returns the RLP for this account.
 	pub fn rlp(&self) -> Bytes {
+		self.rlp_with_storage_root(&sec_trie_root(self.storage.iter().map(|(k, v)| (k.to_vec(), rlp::encode(&U256::from(&**v)).to_vec())).collect()))
+	}
+
+	/// Returns the RLP for this account, given the root of its storage trie.
+	pub fn rlp_with_storage_root(&self, storage_root: &H256) -> Bytes {
 		let mut stream = RlpStream::new_list(4);
 		stream.append(&self.nonce);
 		stream.append(&self.balance);
-		stream.append(&sec_trie_root(self.storage.iter().map(|(k, v)| (k.to_vec(), rlp::encode(&U256::from(&**v)).to_vec())).collect()));
+		stream.append(storage_root);
 		stream.append(&keccak(&self.code.as_ref().unwrap_or(&vec![])));
 		stream.out()
 	}
 
-	/// Place additional data into given hash DB.
-	pub fn insert_additional(&self, db: &mut HashDB, factory: &TrieFactory) {
+	/// Place additional data into given hash DB and return the storage root.
+	pub fn insert_additional(&self, db: &mut HashDB, factory: &TrieFactory) -> H256 {
 		match self.code {
 			Some(ref c) if !c.is_empty() => { db.insert(c); }
 			_ => {}
 		}
 		let mut r = H256::new();
-		let mut t = factory.create(db, &mut r);
-		for (k, v) in &self.storage {
-			if let Err(e) = t.insert(k, &rlp::encode(&U256::from(&**v))) {
-				warn!("Encountered potential DB corruption: {}", e);
+		{
+			let mut t = factory.create(db, &mut r);
+			for (k, v) in &self.storage {
+				if let Err(e) = t.insert(k, &rlp::encode(&U256::from(&**v))) {
+					warn!("Encountered potential DB corruption: {}", e);
+				}
 			}
 		}
+		r
 	}
 }
 
@@ -24,7 +24,7 @@ use io::*;
 use spec::Spec;
 use error::*;
 use client::{Client, ClientConfig, ChainNotify};
-use miner::Miner;
+use miner::{Miner, MinerService};
 
 use snapshot::ManifestData;
 use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams};
@@ -52,7 +52,9 @@ pub enum ClientIoMessage {
 	/// Take a snapshot for the block with given number.
 	TakeSnapshot(u64),
 	/// New consensus message received.
-	NewMessage(Bytes)
+	NewMessage(Bytes),
+	/// State nodes blocks were waiting for have been fetched.
+	StateFetched,
 }
 
 /// Client service setup. Creates and registers client and network services with the IO subsystem.
@@ -97,6 +99,7 @@ impl ClientService {
 
 
 		let pruning = config.pruning;
+		let beam = config.beam_sync;
 		let client = Client::new(config, &spec, db.clone(), miner, io_service.channel())?;
 
 		let snapshot_params = SnapServiceParams {
@@ -107,6 +110,7 @@ impl ClientService {
 			channel: io_service.channel(),
 			snapshot_root: snapshot_path.into(),
 			db_restore: client.clone(),
+			beam: beam,
 		};
 		let snapshot = Arc::new(SnapshotService::new(snapshot_params)?);
 
@@ -167,6 +171,7 @@ struct ClientIoHandler {
 
 const CLIENT_TICK_TIMER: TimerToken = 0;
 const SNAPSHOT_TICK_TIMER: TimerToken = 1;
+const SEAL_INTERVAL_TIMER: TimerToken = 2;
 
 const CLIENT_TICK_MS: u64 = 5000;
 const SNAPSHOT_TICK_MS: u64 = 10000;
@@ -175,12 +180,21 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
 		io.register_timer(CLIENT_TICK_TIMER, CLIENT_TICK_MS).expect("Error registering client timer");
 		io.register_timer(SNAPSHOT_TICK_TIMER, SNAPSHOT_TICK_MS).expect("Error registering snapshot timer");
+		if let Some(interval) = self.client.miner().seal_interval() {
+			let ms = interval.as_secs() * 1000 + interval.subsec_nanos() as u64 / 1_000_000;
+			io.register_timer(SEAL_INTERVAL_TIMER, ms).expect("Error registering seal interval timer");
+		}
 	}
 
 	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
 		match timer {
 			CLIENT_TICK_TIMER => self.client.tick(),
 			SNAPSHOT_TICK_TIMER => self.snapshot.tick(),
+			SEAL_INTERVAL_TIMER => {
+				if self.client.miner().seal_block_now(&*self.client).is_none() {
+					debug!(target: "miner", "Unable to seal a block on interval");
+				}
+			},
 			_ => warn!("IO service triggered unregistered timer '{}'", timer),
 		}
 	}
@@ -191,6 +205,7 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 
 		match *net_message {
 			ClientIoMessage::BlockVerified => { self.client.import_verified_blocks(); }
+			ClientIoMessage::StateFetched => self.client.import_stalled_blocks(),
 			ClientIoMessage::NewTransactions(ref transactions, peer_id) => {
 				self.client.import_queued_transactions(transactions, peer_id);
 			}
@@ -23,8 +23,8 @@ use hash::{KECCAK_EMPTY, KECCAK_NULL_RLP};
 
 use bigint::prelude::U256;
 use bigint::hash::H256;
-use util::{Bytes, HashDB};
-use util::trie::{TrieDB, Trie};
+use util::{Bytes, HashDB, TrieFactory, TrieHasher};
+use util::trie::{TrieDB, Trie, TrieMut};
 use rlp::{RlpStream, UntrustedRlp};
 
 use std::collections::HashSet;
@@ -150,8 +150,9 @@ pub fn from_fat_rlp(
 	acct_db: &mut AccountDBMut,
 	rlp: UntrustedRlp,
 	mut storage_root: H256,
+	hasher: TrieHasher,
 ) -> Result<(BasicAccount, Option<Bytes>), Error> {
-	use util::{TrieDBMut, TrieMut};
+	use util::trie::TrieSpec;
 
 	// check for special case of empty account.
 	if rlp.is_empty() {
@@ -182,10 +183,11 @@ pub fn from_fat_rlp(
 	};
 
 	{
+		let factory = TrieFactory::new(TrieSpec::Generic).with_hasher(hasher);
 		let mut storage_trie = if storage_root.is_zero() {
-			TrieDBMut::new(acct_db, &mut storage_root)
+			factory.create(acct_db, &mut storage_root)
 		} else {
-			TrieDBMut::from_existing(acct_db, &mut storage_root)?
+			factory.from_existing(acct_db, &mut storage_root)?
 		};
 		let pairs = rlp.at(4)?;
 		for pair_rlp in pairs.iter() {
@@ -239,7 +241,7 @@ mod tests {
 
 		let fat_rlps = to_fat_rlps(&keccak(&addr), &account, &AccountDB::new(db.as_hashdb(), &addr), &mut Default::default(), usize::max_value(), usize::max_value()).unwrap();
 		let fat_rlp = UntrustedRlp::new(&fat_rlps[0]).at(1).unwrap();
-		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), fat_rlp, H256::zero()).unwrap().0, account);
+		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), fat_rlp, H256::zero(), Default::default()).unwrap().0, account);
 	}
 
 	#[test]
@@ -264,7 +266,7 @@ mod tests {
 
 		let fat_rlp = to_fat_rlps(&keccak(&addr), &account, &AccountDB::new(db.as_hashdb(), &addr), &mut Default::default(), usize::max_value(), usize::max_value()).unwrap();
 		let fat_rlp = UntrustedRlp::new(&fat_rlp[0]).at(1).unwrap();
-		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), fat_rlp, H256::zero()).unwrap().0, account);
+		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), fat_rlp, H256::zero(), Default::default()).unwrap().0, account);
 	}
 
 	#[test]
@@ -292,7 +294,7 @@ mod tests {
 		let mut restored_account = None;
 		for rlp in fat_rlps {
 			let fat_rlp = UntrustedRlp::new(&rlp).at(1).unwrap();
-			restored_account = Some(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), fat_rlp, root).unwrap().0);
+			restored_account = Some(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), fat_rlp, root, Default::default()).unwrap().0);
 			root = restored_account.as_ref().unwrap().storage_root.clone();
 		}
 		assert_eq!(restored_account, Some(account));
@@ -338,11 +340,11 @@ mod tests {
 		let fat_rlp1 = UntrustedRlp::new(&fat_rlp1[0]).at(1).unwrap();
 		let fat_rlp2 = UntrustedRlp::new(&fat_rlp2[0]).at(1).unwrap();
 
-		let (acc, maybe_code) = from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr2), fat_rlp2, H256::zero()).unwrap();
+		let (acc, maybe_code) = from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr2), fat_rlp2, H256::zero(), Default::default()).unwrap();
 		assert!(maybe_code.is_none());
 		assert_eq!(acc, account2);
 
-		let (acc, maybe_code) = from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr1), fat_rlp1, H256::zero()).unwrap();
+		let (acc, maybe_code) = from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr1), fat_rlp1, H256::zero(), Default::default()).unwrap();
 		assert_eq!(maybe_code, Some(b"this is definitely code".to_vec()));
 		assert_eq!(acc, account1);
 	}
@@ -350,6 +352,6 @@ mod tests {
 	#[test]
 	fn encoding_empty_acc() {
 		let mut db = get_temp_state_db();
-		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &Address::default()), UntrustedRlp::new(&::rlp::NULL_RLP), H256::zero()).unwrap(), (ACC_EMPTY, None));
+		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &Address::default()), UntrustedRlp::new(&::rlp::NULL_RLP), H256::zero(), Default::default()).unwrap(), (ACC_EMPTY, None));
 	}
 }
@@ -63,6 +63,10 @@ pub enum Error {
 	BadEpochProof(u64),
 	/// Wrong chunk format.
 	WrongChunkFormat(String),
+	/// Chunk contents don't match its hash.
+	ChunkHashMismatch(H256, H256),
+	/// Inconsistent manifest.
+	InvalidManifest(String),
 }
 
 impl fmt::Display for Error {
@@ -88,6 +92,8 @@ impl fmt::Display for Error {
 			Error::SnapshotsUnsupported => write!(f, "Snapshots unsupported by consensus engine."),
 			Error::BadEpochProof(i) => write!(f, "Bad epoch proof for transition to epoch {}", i),
 			Error::WrongChunkFormat(ref msg) => write!(f, "Wrong chunk format: {}", msg),
+			Error::ChunkHashMismatch(ref expected, ref found) => write!(f, "Mismatched chunk hash. Expected {:?}, got {:?}", expected, found),
+			Error::InvalidManifest(ref msg) => write!(f, "Invalid manifest: {}", msg),
 		}
 	}
 }
@@ -24,6 +24,8 @@ use std::collections::HashMap;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use util::Bytes;
 use bigint::hash::H256;
@@ -187,6 +189,65 @@ impl SnapshotWriter for LooseWriter {
 	}
 }
 
+/// A snapshot writer wrapper limiting the rate at which chunks are written.
+///
+/// Writing is suspended while `pause` returns true and otherwise delayed to keep
+/// the average throughput below the given budget of bytes per second.
+pub struct ThrottledWriter<W, F> {
+	inner: W,
+	bytes_per_sec: Option<usize>,
+	pause: F,
+	started: Instant,
+	written: u64,
+}
+
+impl<W: SnapshotWriter, F: Fn() -> bool> ThrottledWriter<W, F> {
+	/// Create a new throttled writer.
+	pub fn new(inner: W, bytes_per_sec: Option<usize>, pause: F) -> Self {
+		ThrottledWriter {
+			inner: inner,
+			bytes_per_sec: bytes_per_sec,
+			pause: pause,
+			started: Instant::now(),
+			written: 0,
+		}
+	}
+
+	fn throttle(&mut self, len: usize) {
+		const PAUSE_CHECK_INTERVAL_MS: u64 = 250;
+
+		while (self.pause)() {
+			thread::sleep(Duration::from_millis(PAUSE_CHECK_INTERVAL_MS));
+		}
+
+		self.written += len as u64;
+		if let Some(budget) = self.bytes_per_sec {
+			let budget = ::std::cmp::max(budget, 1) as u64;
+			let target = Duration::from_millis(self.written.saturating_mul(1000) / budget);
+			let elapsed = self.started.elapsed();
+			if target > elapsed {
+				thread::sleep(target - elapsed);
+			}
+		}
+	}
+}
+
+impl<W: SnapshotWriter, F: Fn() -> bool> SnapshotWriter for ThrottledWriter<W, F> {
+	fn write_state_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.throttle(chunk.len());
+		self.inner.write_state_chunk(hash, chunk)
+	}
+
+	fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.throttle(chunk.len());
+		self.inner.write_block_chunk(hash, chunk)
+	}
+
+	fn finish(self, manifest: ManifestData) -> io::Result<()> {
+		self.inner.finish(manifest)
+	}
+}
+
 /// Something which can read compressed snapshots.
 pub trait SnapshotReader {
 	/// Get the manifest data for this snapshot.
@@ -195,6 +256,32 @@ pub trait SnapshotReader {
 	/// Get raw chunk data by hash. implementation defined behavior
 	/// if a chunk not in the manifest is requested.
 	fn chunk(&self, hash: H256) -> io::Result<Bytes>;
+
+	/// Get the size of a chunk in bytes.
+	fn chunk_len(&self, hash: H256) -> io::Result<u64> {
+		self.chunk(hash).map(|chunk| chunk.len() as u64)
+	}
+
+	/// Get `len` bytes of a chunk, starting at `offset`.
+	fn chunk_range(&self, hash: H256, offset: u64, len: u64) -> io::Result<Bytes> {
+		let chunk = self.chunk(hash)?;
+		check_range(chunk.len() as u64, offset, len)?;
+		Ok(chunk[offset as usize..(offset + len) as usize].to_vec())
+	}
+}
+
+fn check_range(chunk_len: u64, offset: u64, len: u64) -> io::Result<()> {
+	match offset.checked_add(len) {
+		Some(end) if end <= chunk_len => Ok(()),
+		_ => Err(io::Error::new(io::ErrorKind::InvalidInput, "range exceeds the chunk")),
+	}
+}
+
+fn read_range(mut file: &File, offset: u64, len: u64) -> io::Result<Bytes> {
+	file.seek(SeekFrom::Start(offset))?;
+	let mut buf = vec![0; len as usize];
+	file.read_exact(&mut buf[..])?;
+	Ok(buf)
 }
 
 /// Packed snapshot reader.
@@ -272,6 +359,12 @@ impl PackedReader {
 			manifest: manifest
 		}))
 	}
+
+	fn chunk_info(&self, hash: &H256) -> io::Result<(u64, u64)> {
+		self.state_hashes.get(hash).or_else(|| self.block_hashes.get(hash))
+			.cloned()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "chunk not in the manifest"))
+	}
 }
 
 impl SnapshotReader for PackedReader {
@@ -292,6 +385,16 @@ impl SnapshotReader for PackedReader {
 
 		Ok(buf)
 	}
+
+	fn chunk_len(&self, hash: H256) -> io::Result<u64> {
+		self.chunk_info(&hash).map(|(len, _)| len)
+	}
+
+	fn chunk_range(&self, hash: H256, offset: u64, len: u64) -> io::Result<Bytes> {
+		let (chunk_len, chunk_off) = self.chunk_info(&hash)?;
+		check_range(chunk_len, offset, len)?;
+		read_range(&self.file, chunk_off + offset, len)
+	}
 }
 
 /// reader for "loose" snapshots
@@ -337,19 +440,45 @@ impl SnapshotReader for LooseReader {
 
 		Ok(buf)
 	}
+
+	fn chunk_len(&self, hash: H256) -> io::Result<u64> {
+		let mut path = self.dir.clone();
+		path.push(hash.hex());
+
+		fs::metadata(&path).map(|meta| meta.len())
+	}
+
+	fn chunk_range(&self, hash: H256, offset: u64, len: u64) -> io::Result<Bytes> {
+		let mut path = self.dir.clone();
+		path.push(hash.hex());
+
+		let file = File::open(&path)?;
+		check_range(file.metadata()?.len(), offset, len)?;
+		read_range(&file, offset, len)
+	}
 }
 
 #[cfg(test)]
 mod tests {
+	use bigint::hash::H256;
 	use devtools::RandomTempPath;
 	use hash::keccak;
 
 	use snapshot::ManifestData;
-	use super::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader, LooseWriter, LooseReader, SNAPSHOT_VERSION};
+	use super::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader, LooseWriter, LooseReader, ThrottledWriter, SNAPSHOT_VERSION};
 
 	const STATE_CHUNKS: &'static [&'static [u8]] = &[b"dog", b"cat", b"hello world", b"hi", b"notarealchunk"];
 	const BLOCK_CHUNKS: &'static [&'static [u8]] = &[b"hello!", b"goodbye!", b"abcdefg", b"hijklmnop", b"qrstuvwxy", b"and", b"z"];
 
+	fn check_ranges(reader: &SnapshotReader, hash: H256) {
+		let chunk = reader.chunk(hash).unwrap();
+		let len = chunk.len() as u64;
+		assert_eq!(reader.chunk_len(hash).unwrap(), len);
+		assert_eq!(reader.chunk_range(hash, 1, len - 1).unwrap(), &chunk[1..]);
+		assert!(reader.chunk_range(hash, 1, len).is_err());
+		assert!(reader.chunk_len(keccak(b"notachunk")).is_err());
+	}
+
 	#[test]
 	fn packed_write_and_read() {
 		let path = RandomTempPath::new();
@@ -385,7 +514,7 @@ mod tests {
 		assert_eq!(reader.manifest(), &manifest);
 
 		for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes) {
-			reader.chunk(hash.clone()).unwrap();
+			check_ranges(&reader, hash.clone());
 		}
 	}
 
@@ -424,7 +553,45 @@ mod tests {
 		assert_eq!(reader.manifest(), &manifest);
 
 		for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes) {
-			reader.chunk(hash.clone()).unwrap();
+			check_ranges(&reader, hash.clone());
 		}
 	}
+
+	#[test]
+	fn throttled_write_respects_budget() {
+		use std::cell::Cell;
+		use std::time::{Duration, Instant};
+
+		let path = RandomTempPath::new();
+		let pauses = Cell::new(1);
+		let started = Instant::now();
+
+		let mut writer = ThrottledWriter::new(LooseWriter::new(path.as_path().into()).unwrap(), Some(10_000), || {
+			let remaining = pauses.get();
+			pauses.set(remaining.saturating_sub(1));
+			remaining > 0
+		});
+
+		let chunk = [0u8; 1000];
+		let hash = keccak(&chunk[..]);
+		writer.write_state_chunk(hash, &chunk).unwrap();
+
+		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			state_hashes: vec![hash],
+			block_hashes: Vec::new(),
+			state_root: keccak(b"notarealroot"),
+			block_number: 12345678987654321,
+			block_hash: keccak(b"notarealblock"),
+		};
+		writer.finish(manifest.clone()).unwrap();
+
+		// paused once, then 1000 bytes at 10kB/s.
+		assert!(started.elapsed() >= Duration::from_millis(100));
+		assert_eq!(pauses.get(), 0);
+
+		let reader = LooseReader::new(path.as_path().into()).unwrap();
+		assert_eq!(reader.manifest(), &manifest);
+		assert_eq!(&reader.chunk(hash).unwrap()[..], &chunk[..]);
+	}
 }
@@ -36,7 +36,7 @@ use util::{Bytes, HashDB, DBValue, snappy};
 use parking_lot::Mutex;
 use util::journaldb::{self, Algorithm, JournalDB};
 use util::kvdb::KeyValueDB;
-use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
+use util::trie::{TrieDB, TrieFactory, TrieHasher, TrieSpec, Trie, TrieMut};
 use rlp::{RlpStream, UntrustedRlp};
 use bloom_journal::Bloom;
 
@@ -54,6 +54,7 @@ pub use self::consensus::*;
 pub use self::service::{Service, DatabaseRestore};
 pub use self::traits::SnapshotService;
 pub use self::watcher::Watcher;
+pub use self::verify::verify;
 pub use types::snapshot_manifest::ManifestData;
 pub use types::restoration_status::RestorationStatus;
 pub use types::basic_account::BasicAccount;
@@ -65,6 +66,7 @@ mod account;
 mod block;
 mod consensus;
 mod error;
+mod verify;
 mod watcher;
 
 #[cfg(test)]
@@ -312,6 +314,7 @@ pub struct StateRebuilder {
 	missing_code: HashMap<H256, Vec<H256>>, // maps code hashes to lists of accounts missing that code.
 	bloom: Bloom,
 	known_storage_roots: HashMap<H256, H256>, // maps account hashes to last known storage root. Only filled for last account per chunk.
+	hasher: TrieHasher,
 }
 
 impl StateRebuilder {
@@ -324,9 +327,16 @@ impl StateRebuilder {
 			missing_code: HashMap::new(),
 			bloom: StateDB::load_bloom(&*db),
 			known_storage_roots: HashMap::new(),
+			hasher: TrieHasher::default(),
 		}
 	}
 
+	/// Rebuild the tries keyed with given hasher.
+	pub fn with_hasher(mut self, hasher: TrieHasher) -> Self {
+		self.hasher = hasher;
+		self
+	}
+
 	/// Feed an uncompressed state chunk into the rebuilder.
 	pub fn feed(&mut self, chunk: &[u8], flag: &AtomicBool) -> Result<(), ::error::Error> {
 		let rlp = UntrustedRlp::new(chunk);
@@ -342,6 +352,7 @@ impl StateRebuilder {
 			&mut pairs,
 			&self.known_code,
 			&mut self.known_storage_roots,
+			self.hasher,
 			flag
 		)?;
 
@@ -363,10 +374,11 @@ impl StateRebuilder {
 
 		// batch trie writes
 		{
+			let factory = TrieFactory::new(TrieSpec::Generic).with_hasher(self.hasher);
 			let mut account_trie = if self.state_root != KECCAK_NULL_RLP {
-				TrieDBMut::from_existing(self.db.as_hashdb_mut(), &mut self.state_root)?
+				factory.from_existing(self.db.as_hashdb_mut(), &mut self.state_root)?
 			} else {
-				TrieDBMut::new(self.db.as_hashdb_mut(), &mut self.state_root)
+				factory.create(self.db.as_hashdb_mut(), &mut self.state_root)
 			};
 
 			for (hash, thin_rlp) in pairs {
@@ -421,6 +433,7 @@ fn rebuild_accounts(
 	out_chunk: &mut [(H256, Bytes)],
 	known_code: &HashMap<H256, H256>,
 	known_storage_roots: &mut HashMap<H256, H256>,
+	hasher: TrieHasher,
 	abort_flag: &AtomicBool,
 ) -> Result<RebuiltStatus, ::error::Error> {
 	let mut status = RebuiltStatus::default();
@@ -436,7 +449,7 @@ fn rebuild_accounts(
 			let (acc, maybe_code) = {
 				let mut acct_db = AccountDBMut::from_hash(db, hash);
 				let storage_root = known_storage_roots.get(&hash).cloned().unwrap_or(H256::zero());
-				account::from_fat_rlp(&mut acct_db, fat_rlp, storage_root)?
+				account::from_fat_rlp(&mut acct_db, fat_rlp, storage_root, hasher)?
 			};
 
 			let code_hash = acc.code_hash.clone();
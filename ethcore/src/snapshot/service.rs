@@ -19,7 +19,7 @@
 use std::collections::HashSet;
 use std::io::ErrorKind;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
@@ -36,6 +36,7 @@ use service::ClientIoMessage;
 use io::IoChannel;
 
 use bigint::hash::H256;
+use hash::keccak;
 use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use util::{Bytes, UtilError};
 use util::journaldb::Algorithm;
@@ -62,6 +63,17 @@ impl Drop for Guard {
 	}
 }
 
+// remove a directory, ignoring it if it doesn't exist.
+fn remove_dir_if_exists(path: &Path) -> Result<(), Error> {
+	if let Err(e) = fs::remove_dir_all(path) {
+		if e.kind() != ErrorKind::NotFound {
+			return Err(e.into())
+		}
+	}
+
+	Ok(())
+}
+
 /// External database restoration handler
 pub trait DatabaseRestore: Send + Sync {
 	/// Restart with a new backend. Takes ownership of passed database and moves it to a new location.
@@ -76,10 +88,12 @@ struct Restoration {
 	state: StateRebuilder,
 	secondary: Box<Rebuilder>,
 	writer: Option<LooseWriter>,
+	received: LooseWriter,
 	snappy_buffer: Bytes,
 	final_state_root: H256,
 	guard: Guard,
 	db: Arc<Database>,
+	beam: bool,
 }
 
 struct RestorationParams<'a> {
@@ -88,9 +102,11 @@ struct RestorationParams<'a> {
 	db_path: PathBuf, // database path
 	db_config: &'a DatabaseConfig, // configuration for the database.
 	writer: Option<LooseWriter>, // writer for recovered snapshot.
+	received: LooseWriter, // writer for received chunks, kept for resumption.
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
 	engine: &'a Engine,
+	beam: bool, // skip the state, to be fetched on demand.
 }
 
 impl Restoration {
@@ -98,7 +114,10 @@ impl Restoration {
 	fn new(params: RestorationParams) -> Result<Self, Error> {
 		let manifest = params.manifest;
 
-		let state_chunks = manifest.state_hashes.iter().cloned().collect();
+		let state_chunks = match params.beam {
+			true => HashSet::new(),
+			false => manifest.state_hashes.iter().cloned().collect(),
+		};
 		let block_chunks = manifest.block_hashes.iter().cloned().collect();
 
 		let raw_db = Arc::new(Database::open(params.db_config, &*params.db_path.to_string_lossy())
@@ -116,16 +135,27 @@ impl Restoration {
 			manifest: manifest,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
-			state: StateRebuilder::new(raw_db.clone(), params.pruning),
+			state: StateRebuilder::new(raw_db.clone(), params.pruning).with_hasher(params.engine.params().trie_hasher),
 			secondary: secondary,
 			writer: params.writer,
+			received: params.received,
 			snappy_buffer: Vec::new(),
 			final_state_root: root,
 			guard: params.guard,
 			db: raw_db,
+			beam: params.beam,
 		})
 	}
 
+	// stores a received chunk so the restoration can be resumed after a restart.
+	fn persist(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+		if self.state_chunks_left.contains(&hash) || self.block_chunks_left.contains(&hash) {
+			self.received.write_state_chunk(hash, chunk)?;
+		}
+
+		Ok(())
+	}
+
 	// feeds a state chunk, aborts early if `flag` becomes false.
 	fn feed_state(&mut self, hash: H256, chunk: &[u8], flag: &AtomicBool) -> Result<(), Error> {
 		if self.state_chunks_left.contains(&hash) {
@@ -165,9 +195,9 @@ impl Restoration {
 
 		if !self.is_done() { return Ok(()) }
 
-		// verify final state root.
+		// verify final state root, unless the state is fetched on demand.
 		let root = self.state.state_root();
-		if root != self.final_state_root {
+		if !self.beam && root != self.final_state_root {
 			warn!("Final restored state has wrong state root: expected {:?}, got {:?}", root, self.final_state_root);
 			return Err(TrieError::InvalidStateRoot(root).into());
 		}
@@ -186,6 +216,14 @@ impl Restoration {
 		Ok(())
 	}
 
+	// hashes of the chunks fed so far.
+	fn completed_chunks(&self) -> Vec<H256> {
+		self.manifest.state_hashes.iter().filter(|hash| !self.state_chunks_left.contains(hash))
+			.chain(self.manifest.block_hashes.iter().filter(|hash| !self.block_chunks_left.contains(hash)))
+			.cloned()
+			.collect()
+	}
+
 	// is everything done?
 	fn is_done(&self) -> bool {
 		self.block_chunks_left.is_empty() && self.state_chunks_left.is_empty()
@@ -212,6 +250,8 @@ pub struct ServiceParams {
 	pub snapshot_root: PathBuf,
 	/// A handle for database restoration.
 	pub db_restore: Arc<DatabaseRestore>,
+	/// Restore only the block chunks, leaving the state to be fetched on demand (beam sync).
+	pub beam: bool,
 }
 
 /// `SnapshotService` implementation.
@@ -229,6 +269,7 @@ pub struct Service {
 	state_chunks: AtomicUsize,
 	block_chunks: AtomicUsize,
 	db_restore: Arc<DatabaseRestore>,
+	beam: bool,
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
 	restoring_snapshot: AtomicBool,
@@ -250,6 +291,7 @@ impl Service {
 			state_chunks: AtomicUsize::new(0),
 			block_chunks: AtomicUsize::new(0),
 			db_restore: params.db_restore,
+			beam: params.beam,
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
 			restoring_snapshot: AtomicBool::new(false),
@@ -262,12 +304,8 @@ impl Service {
 			}
 		}
 
-		// delete the temporary restoration dir if it does exist.
-		if let Err(e) = fs::remove_dir_all(service.restoration_dir()) {
-			if e.kind() != ErrorKind::NotFound {
-				return Err(e.into())
-			}
-		}
+		// the restoration dir is kept so that an interrupted restoration can be
+		// resumed. `init_restore` clears it when the manifest doesn't match.
 
 		// delete the temporary snapshot dir if it does exist.
 		if let Err(e) = fs::remove_dir_all(service.temp_snapshot_dir()) {
@@ -310,6 +348,13 @@ impl Service {
 		dir
 	}
 
+	// path of the chunks received during restoration.
+	fn restoration_chunks(&self) -> PathBuf {
+		let mut dir = self.restoration_dir();
+		dir.push("chunks");
+		dir
+	}
+
 	// temporary snapshot recovery path.
 	fn temp_recovery_dir(&self) -> PathBuf {
 		let mut dir = self.restoration_dir();
@@ -408,16 +453,27 @@ impl Service {
 		// tear down existing restoration.
 		*res = None;
 
-		// delete and restore the restoration dir.
-		if let Err(e) = fs::remove_dir_all(&rest_dir) {
-			match e.kind() {
-				ErrorKind::NotFound => {},
-				_ => return Err(e.into()),
+		// chunks received for the same manifest before a restart can be fed again.
+		let resumed = LooseReader::new(self.restoration_chunks()).ok()
+			.and_then(|reader| match *reader.manifest() == manifest {
+				true => Some(reader),
+				false => None,
+			});
+
+		match resumed {
+			Some(_) => {
+				// only the received chunks survive, the database is rebuilt from them.
+				remove_dir_if_exists(&self.restoration_db())?;
+				remove_dir_if_exists(&self.temp_recovery_dir())?;
+			}
+			None => {
+				// delete and restore the restoration dir.
+				remove_dir_if_exists(&rest_dir)?;
+				fs::create_dir_all(&rest_dir)?;
+				LooseWriter::new(self.restoration_chunks())?.finish(manifest.clone())?;
 			}
 		}
 
-		fs::create_dir_all(&rest_dir)?;
-
 		// make new restoration.
 		let writer = match recover {
 			true => Some(LooseWriter::new(self.temp_recovery_dir())?),
@@ -430,12 +486,17 @@ impl Service {
 			db_path: self.restoration_db(),
 			db_config: &self.db_config,
 			writer: writer,
+			received: LooseWriter::new(self.restoration_chunks())?,
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
 			engine: &*self.engine,
+			beam: self.beam,
 		};
 
-		let state_chunks = params.manifest.state_hashes.len();
+		let state_chunks = match self.beam {
+			true => 0,
+			false => params.manifest.state_hashes.len(),
+		};
 		let block_chunks = params.manifest.block_hashes.len();
 
 		*res = Some(Restoration::new(params)?);
@@ -448,7 +509,63 @@ impl Service {
 		};
 
 		self.restoring_snapshot.store(true, Ordering::SeqCst);
-		Ok(())
+
+		match resumed {
+			Some(reader) => self.replay_chunks(&mut *res, &reader),
+			None => Ok(()),
+		}
+	}
+
+	// feed the chunks received before a restart. this accepts an already-locked
+	// restoration as an argument, like `finalize_restoration`.
+	fn replay_chunks(&self, rest: &mut Option<Restoration>, reader: &LooseReader) -> Result<(), Error> {
+		let manifest = reader.manifest();
+		let mut replayed = 0;
+
+		let is_done = {
+			let rest = match *rest {
+				Some(ref mut r) => r,
+				None => return Ok(()),
+			};
+
+			let chunks = manifest.state_hashes.iter().map(|hash| (hash, true))
+				.chain(manifest.block_hashes.iter().map(|hash| (hash, false)));
+
+			for (hash, is_state) in chunks {
+				let chunk = match reader.chunk(*hash) {
+					Ok(chunk) => chunk,
+					Err(_) => continue,
+				};
+
+				// skip chunks which didn't make it to disk intact.
+				if keccak(&chunk) != *hash { continue }
+
+				match is_state {
+					true => {
+						rest.feed_state(*hash, &chunk, &self.restoring_snapshot)?;
+						self.state_chunks.fetch_add(1, Ordering::SeqCst);
+					}
+					false => {
+						rest.feed_blocks(*hash, &chunk, &*self.engine, &self.restoring_snapshot)?;
+						self.block_chunks.fetch_add(1, Ordering::SeqCst);
+					}
+				}
+				replayed += 1;
+			}
+
+			rest.db.flush().map_err(UtilError::from)?;
+			rest.is_done()
+		};
+
+		if replayed == 0 { return Ok(()) }
+
+		info!("Resuming snapshot restoration with {} of {} chunks already received",
+			replayed, manifest.state_hashes.len() + manifest.block_hashes.len());
+
+		match is_done {
+			true => self.finalize_restoration(rest),
+			false => Ok(()),
+		}
 	}
 
 	// finalize the restoration. this accepts an already-locked
@@ -504,10 +621,10 @@ impl Service {
 							None => return Ok(()),
 						};
 
-						(match is_state {
+						(rest.persist(hash, chunk).and_then(|_| match is_state {
 							true => rest.feed_state(hash, chunk, &self.restoring_snapshot),
 							false => rest.feed_blocks(hash, chunk, &*self.engine, &self.restoring_snapshot),
-						}.map(|_| rest.is_done()), rest.db.clone())
+						}).map(|_| rest.is_done()), rest.db.clone())
 					};
 
 					let res = match res {
@@ -576,6 +693,14 @@ impl SnapshotService for Service {
 		self.reader.read().as_ref().and_then(|r| r.chunk(hash).ok())
 	}
 
+	fn chunk_size(&self, hash: H256) -> Option<u64> {
+		self.reader.read().as_ref().and_then(|r| r.chunk_len(hash).ok())
+	}
+
+	fn chunk_range(&self, hash: H256, offset: u64, len: u64) -> Option<Bytes> {
+		self.reader.read().as_ref().and_then(|r| r.chunk_range(hash, offset, len).ok())
+	}
+
 	fn status(&self) -> RestorationStatus {
 		let mut cur_status = self.status.lock();
 		if let RestorationStatus::Ongoing { ref mut state_chunks_done, ref mut block_chunks_done, .. } = *cur_status {
@@ -586,6 +711,10 @@ impl SnapshotService for Service {
 		cur_status.clone()
 	}
 
+	fn completed_chunks(&self) -> Option<Vec<H256>> {
+		self.restoration.lock().as_ref().map(|rest| rest.completed_chunks())
+	}
+
 	fn begin_restore(&self, manifest: ManifestData) {
 		if let Err(e) = self.io_channel.lock().send(ClientIoMessage::BeginRestoration(manifest)) {
 			trace!("Error sending snapshot service message: {:?}", e);
@@ -613,7 +742,12 @@ impl SnapshotService for Service {
 
 impl Drop for Service {
 	fn drop(&mut self) {
-		self.abort_restore();
+		self.restoring_snapshot.store(false, Ordering::SeqCst);
+
+		// keep the received chunks around to resume the restoration on the next start.
+		if let Some(restoration) = self.restoration.get_mut().take() {
+			restoration.guard.disarm();
+		}
 	}
 }
 
@@ -655,6 +789,7 @@ mod tests {
 			channel: service.channel(),
 			snapshot_root: dir,
 			db_restore: Arc::new(NoopDBRestore),
+			beam: false,
 		};
 
 		let service = Service::new(snapshot_params).unwrap();
@@ -705,9 +840,11 @@ mod tests {
 			db_path: dir.as_path().to_owned(),
 			db_config: &db_config,
 			writer: None,
+			received: LooseWriter::new(dir.as_path().join("chunks")).unwrap(),
 			genesis: &gb,
 			guard: Guard::benign(),
 			engine: &*spec.engine.clone(),
+			beam: false,
 		};
 
 		let mut restoration = Restoration::new(params).unwrap();
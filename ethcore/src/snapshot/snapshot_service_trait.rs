@@ -35,9 +35,20 @@ pub trait SnapshotService : Sync + Send {
 	/// Get raw chunk for a given hash.
 	fn chunk(&self, hash: H256) -> Option<Bytes>;
 
+	/// Get the size in bytes of the chunk with given hash.
+	fn chunk_size(&self, hash: H256) -> Option<u64>;
+
+	/// Get `len` bytes of the chunk with given hash, starting at `offset`.
+	/// Lets large chunks be served without holding them in memory twice.
+	fn chunk_range(&self, hash: H256, offset: u64, len: u64) -> Option<Bytes>;
+
 	/// Ask the snapshot service for the restoration status.
 	fn status(&self) -> RestorationStatus;
 
+	/// Get the hashes of chunks already restored by the ongoing restoration,
+	/// including those resumed from a previous run. `None` if not restoring.
+	fn completed_chunks(&self) -> Option<Vec<H256>>;
+
 	/// Begin snapshot restoration.
 	/// If restoration in-progress, this will reset it.
 	/// From this point on, any previous snapshot may become unavailable.
@@ -21,7 +21,7 @@ use std::sync::Arc;
 use client::{BlockChainClient, Client};
 use ids::BlockId;
 use snapshot::service::{Service, ServiceParams};
-use snapshot::{self, ManifestData, SnapshotService};
+use snapshot::{self, ManifestData, RestorationStatus, SnapshotService};
 use spec::Spec;
 use tests::helpers::generate_dummy_client_with_spec_and_data;
 
@@ -73,6 +73,7 @@ fn restored_is_equivalent() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path,
 		db_restore: client2.clone(),
+		beam: false,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -116,6 +117,7 @@ fn guards_delete_folders() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path.clone(),
 		db_restore: Arc::new(NoopDBRestore),
+		beam: false,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -139,6 +141,144 @@ fn guards_delete_folders() {
 	service.init_restore(manifest.clone(), true).unwrap();
 	assert!(path.exists());
 
+	// received chunks are kept to resume the restoration.
 	drop(service);
-	assert!(!path.exists());
+	assert!(path.exists());
+}
+
+#[test]
+fn resumes_restoration_after_restart() {
+	const NUM_BLOCKS: u32 = 100;
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 5, &[1.into()]);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+
+	client_db.push("client_db");
+	path.push("snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Database::open(&db_config, client_db.to_str().unwrap()).unwrap();
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		Arc::new(client_db),
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let service_params = || ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config.clone(),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.clone(),
+		db_restore: client2.clone(),
+		beam: false,
+	};
+
+	let service = Service::new(service_params()).unwrap();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+
+	let manifest = service.manifest().unwrap();
+	service.init_restore(manifest.clone(), false).unwrap();
+
+	for hash in &manifest.state_hashes {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_state_chunk(*hash, &chunk);
+	}
+
+	drop(service);
+
+	let service = Service::new(service_params()).unwrap();
+	service.init_restore(manifest.clone(), false).unwrap();
+
+	match service.status() {
+		RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } => {
+			assert_eq!(state_chunks_done as usize, manifest.state_hashes.len());
+			assert_eq!(block_chunks_done, 0);
+		}
+		other => panic!("unexpected restoration status: {:?}", other),
+	}
+
+	for hash in &manifest.block_hashes {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_block_chunk(*hash, &chunk);
+	}
+
+	assert_eq!(service.status(), RestorationStatus::Inactive);
+
+	for x in 0..NUM_BLOCKS {
+		let block1 = client.block(BlockId::Number(x as u64)).unwrap();
+		let block2 = client2.block(BlockId::Number(x as u64)).unwrap();
+
+		assert_eq!(block1, block2);
+	}
+}
+
+#[test]
+fn verifies_snapshot_offline() {
+	use bigint::hash::H256;
+	use snapshot::io::SnapshotReader;
+	use util::Bytes;
+
+	// serves the chunks of another reader under a tampered manifest.
+	struct Tampered<'a> {
+		inner: &'a SnapshotReader,
+		manifest: ManifestData,
+		corrupt: Option<H256>,
+	}
+
+	impl<'a> SnapshotReader for Tampered<'a> {
+		fn manifest(&self) -> &ManifestData { &self.manifest }
+
+		fn chunk(&self, hash: H256) -> ::std::io::Result<Bytes> {
+			let mut chunk = self.inner.chunk(hash)?;
+			if Some(hash) == self.corrupt {
+				chunk[0] ^= 1;
+			}
+			Ok(chunk)
+		}
+	}
+
+	let spec = Spec::new_null();
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, 100, 1, &[1.into()]);
+	let path = RandomTempPath::create_dir();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.as_path().clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		beam: false,
+	};
+
+	let service = Service::new(service_params).unwrap();
+	service.take_snapshot(&client, 100).unwrap();
+
+	let reader = service.reader();
+	let reader = reader.as_ref().unwrap();
+	let scratch = || Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0)));
+	let genesis = spec.genesis_block();
+
+	assert!(snapshot::verify(reader, &*spec.engine, &genesis, scratch()).is_ok());
+
+	let mut wrong_root = reader.manifest().clone();
+	wrong_root.state_root = H256::from(1);
+	let tampered = Tampered { inner: reader, manifest: wrong_root, corrupt: None };
+	assert!(snapshot::verify(&tampered, &*spec.engine, &genesis, scratch()).is_err());
+
+	let tampered = Tampered {
+		inner: reader,
+		manifest: reader.manifest().clone(),
+		corrupt: reader.manifest().block_hashes.first().cloned(),
+	};
+	assert!(snapshot::verify(&tampered, &*spec.engine, &genesis, scratch()).is_err());
 }
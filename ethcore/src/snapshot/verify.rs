@@ -0,0 +1,106 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline snapshot verification.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use hash::keccak;
+use bigint::hash::H256;
+use blockchain::BlockChain;
+use engines::Engine;
+use util::snappy;
+use util::journaldb::Algorithm;
+use util::kvdb::KeyValueDB;
+
+use super::{Error, ManifestData, StateRebuilder};
+use super::io::SnapshotReader;
+
+// checks the manifest is self-consistent and of a version the engine supports.
+fn verify_manifest(manifest: &ManifestData, min_version: u64, current_version: u64) -> Result<(), Error> {
+	if manifest.version < min_version || manifest.version > current_version {
+		return Err(Error::VersionNotSupported(manifest.version));
+	}
+
+	if manifest.state_hashes.is_empty() {
+		return Err(Error::InvalidManifest("no state chunks".into()));
+	}
+
+	let mut seen = HashSet::new();
+	for hash in manifest.state_hashes.iter().chain(manifest.block_hashes.iter()) {
+		if !seen.insert(*hash) {
+			return Err(Error::InvalidManifest(format!("chunk {:?} listed more than once", hash)));
+		}
+	}
+
+	Ok(())
+}
+
+// reads a chunk, checks its hash and decompresses it into the buffer.
+fn read_chunk<R: SnapshotReader + ?Sized>(reader: &R, hash: H256, buffer: &mut Vec<u8>) -> Result<usize, ::error::Error> {
+	let chunk = reader.chunk(hash).map_err(Error::Io)?;
+	let found = keccak(&chunk);
+	if found != hash {
+		return Err(Error::ChunkHashMismatch(hash, found).into());
+	}
+
+	Ok(snappy::decompress_into(&chunk, buffer)?)
+}
+
+/// Verify a snapshot by rebuilding it into the given scratch database.
+///
+/// Checks the manifest, the hash of every chunk, the final state root and the
+/// integrity of the block chunks. The database should be empty and is left
+/// populated with the rebuilt state and blocks.
+pub fn verify<R: SnapshotReader + ?Sized>(
+	reader: &R,
+	engine: &Engine,
+	genesis: &[u8],
+	db: Arc<KeyValueDB>,
+) -> Result<(), ::error::Error> {
+	let manifest = reader.manifest();
+	let components = engine.snapshot_components().ok_or(Error::SnapshotsUnsupported)?;
+	verify_manifest(manifest, components.min_supported_version(), components.current_version())?;
+
+	let flag = AtomicBool::new(true);
+	let mut buffer = Vec::new();
+
+	let mut state = StateRebuilder::new(db.clone(), Algorithm::Archive).with_hasher(engine.params().trie_hasher);
+	for (i, hash) in manifest.state_hashes.iter().enumerate() {
+		let len = read_chunk(reader, *hash, &mut buffer)?;
+		state.feed(&buffer[..len], &flag)?;
+		trace!(target: "snapshot", "Verified state chunk {}/{}", i + 1, manifest.state_hashes.len());
+	}
+
+	let root = state.state_root();
+	if root != manifest.state_root {
+		return Err(Error::WrongStateRoot(manifest.state_root, root).into());
+	}
+	state.finalize(manifest.block_number, manifest.block_hash)?;
+
+	let chain = BlockChain::new(Default::default(), genesis, db.clone());
+	let mut blocks = components.rebuilder(chain, db, manifest)?;
+	for (i, hash) in manifest.block_hashes.iter().enumerate() {
+		let len = read_chunk(reader, *hash, &mut buffer)?;
+		blocks.feed(&buffer[..len], engine, &flag)?;
+		trace!(target: "snapshot", "Verified block chunk {}/{}", i + 1, manifest.block_hashes.len());
+	}
+	blocks.finalize(engine)?;
+
+	Ok(())
+}
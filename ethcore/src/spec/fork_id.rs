@@ -0,0 +1,191 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fork identifier (EIP-2124) computed from the fork transitions of a chain.
+
+use bigint::hash::H256;
+use header::BlockNumber;
+use rlp::{Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
+
+/// Transitions at or beyond this block are placeholders for forks which aren't scheduled.
+const UNSCHEDULED: BlockNumber = 0x7fffffffffffff;
+
+/// Identifier of the set of forks passed and the next fork expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkId {
+	/// CRC32 checksum of the genesis hash and all fork blocks passed.
+	pub hash: u32,
+	/// Next scheduled fork block or 0 if there is none.
+	pub next: BlockNumber,
+}
+
+impl Encodable for ForkId {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.hash);
+		s.append(&self.next);
+	}
+}
+
+impl Decodable for ForkId {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		Ok(ForkId {
+			hash: rlp.val_at(0)?,
+			next: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// Computes local fork identifiers and validates the ones announced by peers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForkFilter {
+	forks: Vec<BlockNumber>,
+	// `sums[i]` is the checksum after passing first `i` forks.
+	sums: Vec<u32>,
+}
+
+impl ForkFilter {
+	/// Create a filter for chain with given genesis and fork transition blocks.
+	/// Transitions don't have to be sorted; duplicates, genesis and unscheduled ones are ignored.
+	pub fn new(genesis: H256, mut forks: Vec<BlockNumber>) -> Self {
+		forks.retain(|&n| n != 0 && n < UNSCHEDULED);
+		forks.sort();
+		forks.dedup();
+
+		let mut sums = Vec::with_capacity(forks.len() + 1);
+		let mut sum = crc32_update(0, &*genesis);
+		sums.push(sum);
+		for fork in &forks {
+			sum = crc32_update(sum, &u64_be(*fork));
+			sums.push(sum);
+		}
+
+		ForkFilter {
+			forks: forks,
+			sums: sums,
+		}
+	}
+
+	/// Fork identifier for a chain with given best block.
+	pub fn fork_id(&self, head: BlockNumber) -> ForkId {
+		let passed = self.passed(head);
+		ForkId {
+			hash: self.sums[passed],
+			next: self.forks.get(passed).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Check whether a peer announcing `remote` is on the same chain as we are at `head`.
+	pub fn is_compatible(&self, head: BlockNumber, remote: &ForkId) -> bool {
+		let passed = self.passed(head);
+		match self.sums.iter().position(|sum| *sum == remote.hash) {
+			// Same forks passed, unless the remote expects a fork we've gone past without knowing about it.
+			Some(i) if i == passed => remote.next == 0 || head < remote.next,
+			// Remote is behind us, it has to know about the next fork it's going to pass.
+			Some(i) if i < passed => remote.next == self.forks[i],
+			// Remote is ahead of us but it's passed forks we know about.
+			Some(_) => true,
+			None => false,
+		}
+	}
+
+	fn passed(&self, head: BlockNumber) -> usize {
+		self.forks.iter().take_while(|&&fork| fork <= head).count()
+	}
+}
+
+fn u64_be(n: u64) -> [u8; 8] {
+	let mut out = [0u8; 8];
+	for i in 0..8 {
+		out[i] = (n >> (56 - 8 * i)) as u8;
+	}
+	out
+}
+
+/// Continue IEEE CRC32 checksum `crc` over `data`.
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for byte in data {
+		crc ^= *byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+#[cfg(test)]
+mod tests {
+	use rlp;
+	use super::{ForkFilter, ForkId, crc32_update};
+
+	fn mainnet() -> ForkFilter {
+		let genesis = "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".parse().unwrap();
+		ForkFilter::new(genesis, vec![2675000, 1150000, 0, 2463000, 1920000, 2675000, 0x7fffffffffffff, u64::max_value()])
+	}
+
+	fn id(hash: u32, next: u64) -> ForkId {
+		ForkId { hash: hash, next: next }
+	}
+
+	#[test]
+	fn should_compute_crc32() {
+		assert_eq!(crc32_update(0, b"123456789"), 0xcbf43926);
+		assert_eq!(crc32_update(crc32_update(0, b"1234"), b"56789"), 0xcbf43926);
+	}
+
+	#[test]
+	fn should_compute_mainnet_fork_ids() {
+		let filter = mainnet();
+		assert_eq!(filter.fork_id(0), id(0xfc64ec04, 1150000));
+		assert_eq!(filter.fork_id(1149999), id(0xfc64ec04, 1150000));
+		assert_eq!(filter.fork_id(1150000), id(0x97c2c34c, 1920000));
+		assert_eq!(filter.fork_id(1920000), id(0x91d1f948, 2463000));
+		assert_eq!(filter.fork_id(2463000), id(0x7a64da13, 2675000));
+		assert_eq!(filter.fork_id(2675000), id(0x3edd5b10, 0));
+		assert_eq!(filter.fork_id(10000000), id(0x3edd5b10, 0));
+	}
+
+	#[test]
+	fn should_check_compatibility() {
+		let filter = mainnet();
+
+		// same forks, remote announces a fork we don't know about yet
+		assert!(filter.is_compatible(2675000, &id(0x3edd5b10, 4370000)));
+		// ... but we've already passed it
+		assert!(!filter.is_compatible(4370000, &id(0x3edd5b10, 4370000)));
+		// remote is syncing
+		assert!(filter.is_compatible(2675000, &id(0xfc64ec04, 1150000)));
+		assert!(filter.is_compatible(2675000, &id(0x91d1f948, 2463000)));
+		// remote is stale and doesn't know about our next fork
+		assert!(!filter.is_compatible(2675000, &id(0xfc64ec04, 1337)));
+		assert!(!filter.is_compatible(2675000, &id(0x91d1f948, 0)));
+		// we are syncing
+		assert!(filter.is_compatible(0, &id(0x3edd5b10, 0)));
+		assert!(filter.is_compatible(1150000, &id(0x7a64da13, 2675000)));
+		// different chain
+		assert!(!filter.is_compatible(2675000, &id(0xafec6b27, 0)));
+	}
+
+	#[test]
+	fn should_roundtrip_rlp() {
+		let fork_id = id(0xfc64ec04, 1150000);
+		assert_eq!(rlp::decode::<ForkId>(&rlp::encode(&fork_id)), fork_id);
+	}
+}
@@ -16,9 +16,13 @@
 
 //! Blockchain params.
 
+mod fork_id;
 mod genesis;
 mod seal;
+mod validate;
 pub mod spec;
 
 pub use self::spec::*;
+pub use self::fork_id::{ForkId, ForkFilter};
 pub use self::genesis::Genesis;
+pub use self::validate::{validate, Issue, Report, Severity};
@@ -22,11 +22,12 @@ use std::path::Path;
 use std::sync::Arc;
 use rustc_hex::FromHex;
 use hash::{KECCAK_NULL_RLP, keccak};
+use super::fork_id::ForkFilter;
 use super::genesis::Genesis;
 use super::seal::Generic as GenericSeal;
 
 use builtin::Builtin;
-use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, DEFAULT_BLOCKHASH_CONTRACT};
+use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, Clique, DEFAULT_BLOCKHASH_CONTRACT};
 use vm::{EnvInfo, CallType, ActionValue, ActionParams};
 use error::Error;
 use ethereum;
@@ -44,6 +45,9 @@ use bigint::hash::{H256, H2048};
 use parking_lot::RwLock;
 use util::*;
 
+/// Default maximum RLP size of a queued transaction.
+const MAX_TRANSACTION_SIZE: usize = 300 * 1024;
+
 /// Parameters common to ethereum-like blockchains.
 /// NOTE: when adding bugfix hard-fork parameters,
 /// add to `contains_bugfix_hard_fork`
@@ -97,16 +101,28 @@ pub struct CommonParams {
 	pub remove_dust_contracts: bool,
 	/// Wasm support
 	pub wasm: bool,
+	/// Wasm gas costs and host function availability.
+	pub wasm_schedule: ::vm::WasmSchedule,
 	/// Gas limit bound divisor (how much gas limit can change per block)
 	pub gas_limit_bound_divisor: U256,
 	/// Block reward in wei.
 	pub block_reward: U256,
+	/// Contract deciding block rewards, replacing `block_reward` if set.
+	pub block_reward_contract: Option<Address>,
 	/// Registrar contract address.
 	pub registrar: Address,
 	/// Node permission managing contract address.
 	pub node_permission_contract: Option<Address>,
 	/// Transaction permission managing contract address.
 	pub transaction_permission_contract: Option<Address>,
+	/// Chain-specific instructions with their opcodes and activation blocks.
+	pub custom_opcodes: Vec<(BlockNumber, u8, ::vm::CustomInstruction)>,
+	/// Maximum RLP size of a transaction accepted to the queue.
+	pub max_transaction_size: usize,
+	/// Maximum size of contract code deployed by a transaction or CREATE, overriding the engine default.
+	pub max_code_size: Option<u64>,
+	/// Hash function keying the state and storage tries.
+	pub trie_hasher: TrieHasher,
 }
 
 impl CommonParams {
@@ -132,6 +148,15 @@ impl CommonParams {
 				false => ::vm::CleanDustMode::BasicOnly,
 			};
 		}
+		schedule.wasm = self.wasm_schedule.clone();
+		if let Some(max_code_size) = self.max_code_size {
+			schedule.create_data_limit = max_code_size as usize;
+		}
+		for &(activate_at, opcode, ref instruction) in &self.custom_opcodes {
+			if block_number >= activate_at {
+				schedule.custom_instructions.insert(opcode, instruction.clone());
+			}
+		}
 	}
 
 	/// Whether these params contain any bug-fix hard forks.
@@ -146,6 +171,76 @@ impl CommonParams {
 			self.eip214_transition != 0 &&
 			self.dust_protection_transition != 0
 	}
+
+	/// Blocks at which any of the common rules change.
+	pub fn fork_blocks(&self) -> Vec<BlockNumber> {
+		let mut blocks = vec![
+			self.eip98_transition,
+			self.eip155_transition,
+			self.validate_receipts_transition,
+			self.eip86_transition,
+			self.eip140_transition,
+			self.eip210_transition,
+			self.eip211_transition,
+			self.eip214_transition,
+			self.dust_protection_transition,
+		];
+		blocks.extend(self.custom_opcodes.iter().map(|&(activate_at, _, _)| activate_at));
+		blocks
+	}
+
+	/// Check whether `new` parameters may replace these ones on a running chain with given best block,
+	/// i.e. they change only the rules of future blocks. Gas limit parameters, extra data size and
+	/// contract addresses used for lookups may be changed freely.
+	pub fn check_reload(&self, new: &CommonParams, best_block: BlockNumber) -> Result<(), String> {
+		macro_rules! fixed {
+			($($field: ident),*) => {$(
+				if self.$field != new.$field {
+					return Err(format!("`{}` can't be changed on an existing chain.", stringify!($field)));
+				}
+			)*}
+		}
+		macro_rules! transitions {
+			($($field: ident),*) => {$(
+				if self.$field != new.$field && ::std::cmp::min(self.$field, new.$field) <= best_block {
+					return Err(format!("`{}` can only be moved between future blocks (best block is #{}).", stringify!($field), best_block));
+				}
+			)*}
+		}
+
+		fixed!(account_start_nonce, network_id, chain_id, subprotocol_name, fork_block,
+			eip210_contract_address, eip210_contract_code, eip210_contract_gas,
+			nonce_cap_increment, remove_dust_contracts, wasm, wasm_schedule, block_reward, block_reward_contract, max_code_size, trie_hasher,
+			node_permission_contract, transaction_permission_contract);
+		transitions!(eip98_transition, eip155_transition, validate_receipts_transition, eip86_transition,
+			eip140_transition, eip210_transition, eip211_transition, eip214_transition, dust_protection_transition);
+
+		let active_opcodes = |params: &CommonParams| params.custom_opcodes.iter()
+			.filter(|&&(activate_at, _, _)| activate_at <= best_block)
+			.cloned()
+			.collect::<Vec<_>>();
+		if active_opcodes(self) != active_opcodes(new) {
+			return Err(format!("Custom opcodes can only be changed for future blocks (best block is #{}).", best_block));
+		}
+		Ok(())
+	}
+}
+
+/// Check whether `new` builtins may replace `old` ones on a running chain with given best block.
+/// Builtins active at the best block have to stay untouched, including their pricing.
+pub fn check_builtins_reload(old: &BTreeMap<Address, Builtin>, new: &BTreeMap<Address, Builtin>, best_block: BlockNumber) -> Result<(), String> {
+	for address in old.keys().chain(new.keys()) {
+		let old_activation = old.get(address).map(Builtin::activate_at);
+		let new_activation = new.get(address).map(Builtin::activate_at);
+		let active = |activation: Option<u64>| activation.map_or(false, |at| at <= best_block);
+		if old_activation != new_activation && (active(old_activation) || active(new_activation)) {
+			return Err(format!("Builtin {:?} can only be (de)activated at future blocks (best block is #{}).", address, best_block));
+		}
+		if active(old_activation) && old.get(address).and_then(Builtin::definition) != new.get(address).and_then(Builtin::definition) {
+			return Err(format!("Builtin {:?} is active at the best block #{}; its implementation and pricing can't be changed.", address, best_block));
+		}
+	}
+	Ok(())
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -175,13 +270,96 @@ impl From<ethjson::spec::Params> for CommonParams {
 			nonce_cap_increment: p.nonce_cap_increment.map_or(64, Into::into),
 			remove_dust_contracts: p.remove_dust_contracts.unwrap_or(false),
 			wasm: p.wasm.unwrap_or(false),
+			wasm_schedule: p.wasm_schedule.map_or_else(Default::default, wasm_schedule),
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
 			block_reward: p.block_reward.map_or_else(U256::zero, Into::into),
+			block_reward_contract: p.block_reward_contract.map(Into::into),
 			registrar: p.registrar.map_or_else(Address::new, Into::into),
 			node_permission_contract: p.node_permission_contract.map(Into::into),
 			transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
+			custom_opcodes: p.custom_opcodes.map_or_else(Vec::new, |opcodes| opcodes.into_iter().map(custom_opcode).collect()),
+			max_transaction_size: p.max_transaction_size.map_or(MAX_TRANSACTION_SIZE, Into::into),
+			max_code_size: p.max_code_size.map(Into::into),
+			trie_hasher: p.trie_hasher.and_then(|h| h.parse().ok()).unwrap_or_default(),
+		}
+	}
+}
+
+fn custom_opcode(o: ethjson::spec::CustomOpcode) -> (BlockNumber, u8, ::vm::CustomInstruction) {
+	let opcode: u64 = o.opcode.into();
+	let instruction = ::vm::CustomInstruction {
+		name: o.name,
+		args: o.args.into(),
+		ret: o.ret.into(),
+		gas: o.gas.into(),
+	};
+	(o.activate_at.map_or(0, Into::into), opcode as u8, instruction)
+}
+
+/// Checks that custom opcodes requested by the spec can be activated.
+#[cfg(feature = "custom-opcodes")]
+fn check_custom_opcodes(params: &ethjson::spec::Params) -> Result<(), Error> {
+	for o in params.custom_opcodes.iter().flat_map(|opcodes| opcodes.iter()) {
+		let opcode: u64 = o.opcode.into();
+		if opcode > 0xff {
+			return Err(UtilError::from(format!("Custom opcode {} of {} is out of range", opcode, o.name)).into());
+		}
+		if ::evm::custom::is_standard(opcode as u8) {
+			return Err(UtilError::from(format!("Custom opcode {:#x} of {} is already taken", opcode, o.name)).into());
+		}
+		let args: usize = o.args.into();
+		let ret: usize = o.ret.into();
+		if args > 1024 || ret > 1024 {
+			return Err(UtilError::from(format!("Custom opcode {} uses too many stack items", o.name)).into());
 		}
 	}
+	Ok(())
+}
+
+/// Checks that custom opcodes requested by the spec can be activated.
+#[cfg(not(feature = "custom-opcodes"))]
+fn check_custom_opcodes(params: &ethjson::spec::Params) -> Result<(), Error> {
+	match params.custom_opcodes {
+		Some(ref opcodes) if !opcodes.is_empty() => Err(UtilError::from(
+			"Custom opcodes require Parity to be built with the `custom-opcodes` feature".to_owned()
+		).into()),
+		_ => Ok(()),
+	}
+}
+
+/// Checks that the trie hasher requested by the spec is available.
+fn check_trie_hasher(params: &ethjson::spec::Params) -> Result<(), Error> {
+	match params.trie_hasher {
+		Some(ref hasher) => hasher.parse::<TrieHasher>().map(|_| ()).map_err(|e| UtilError::from(e).into()),
+		None => Ok(()),
+	}
+}
+
+/// Checks that the wasm host functions requested by the spec are implemented.
+fn check_wasm_host_functions(params: &ethjson::spec::Params) -> Result<(), Error> {
+	let functions = params.wasm_schedule.as_ref().and_then(|s| s.host_functions.as_ref());
+	for name in functions.iter().flat_map(|functions| functions.iter()) {
+		if !::wasm::is_host_function(name) {
+			return Err(UtilError::from(format!("Unknown wasm host function {}", name)).into());
+		}
+	}
+	Ok(())
+}
+
+fn wasm_schedule(s: ethjson::spec::WasmSchedule) -> ::vm::WasmSchedule {
+	let cost = |v: Option<ethjson::uint::Uint>, default: u32| v.map_or(default, |v| {
+		let v: u64 = v.into();
+		v as u32
+	});
+	let defaults = ::vm::WasmSchedule::default();
+
+	::vm::WasmSchedule {
+		regular: cost(s.regular, defaults.regular),
+		mem: cost(s.mem, defaults.mem),
+		storage_read: cost(s.storage_read, defaults.storage_read),
+		storage_write: cost(s.storage_write, defaults.storage_write),
+		host_functions: s.host_functions.unwrap_or(defaults.host_functions),
+	}
 }
 
 /// Parameters for a block chain; includes both those intrinsic to the design of the
@@ -196,6 +374,8 @@ pub struct Spec {
 
 	/// Known nodes on the network in enode format.
 	pub nodes: Vec<String>,
+	/// Signed DNS lists of nodes on the network (`enrtree://<key>@<domain>`).
+	pub node_lists: Vec<String>,
 
 	/// The genesis block's parent hash field.
 	pub parent_hash: H256,
@@ -228,10 +408,19 @@ pub struct Spec {
 	genesis_state: PodState,
 }
 
+fn load_builtins(accounts: &ethjson::spec::State) -> Result<BTreeMap<Address, Builtin>, String> {
+	accounts.builtins().into_iter()
+		.map(|(address, builtin)| Builtin::from_json(builtin).map(|b| (address.into(), b)))
+		.collect()
+}
+
 fn load_from<T: AsRef<Path>>(cache_dir: T, s: ethjson::spec::Spec) -> Result<Spec, Error> {
-	let builtins = s.accounts.builtins().into_iter().map(|p| (p.0.into(), From::from(p.1))).collect();
+	let builtins = load_builtins(&s.accounts).map_err(UtilError::from)?;
 	let g = Genesis::from(s.genesis);
 	let GenericSeal(seal_rlp) = g.seal.into();
+	check_custom_opcodes(&s.params)?;
+	check_trie_hasher(&s.params)?;
+	check_wasm_host_functions(&s.params)?;
 	let params = CommonParams::from(s.params);
 
 	let mut s = Spec {
@@ -239,6 +428,7 @@ fn load_from<T: AsRef<Path>>(cache_dir: T, s: ethjson::spec::Spec) -> Result<Spe
 		engine: Spec::engine(cache_dir, s.engine, params, builtins),
 		data_dir: s.data_dir.unwrap_or(s.name).into(),
 		nodes: s.nodes.unwrap_or_else(Vec::new),
+		node_lists: s.node_lists.unwrap_or_else(Vec::new),
 		parent_hash: g.parent_hash,
 		transactions_root: g.transactions_root,
 		receipts_root: g.receipts_root,
@@ -257,7 +447,7 @@ fn load_from<T: AsRef<Path>>(cache_dir: T, s: ethjson::spec::Spec) -> Result<Spe
 	// use memoized state root if provided.
 	match g.state_root {
 		Some(root) => *s.state_root_memo.get_mut() = root,
-		None => { let _ = s.run_constructors(&Default::default(), BasicBackend(MemoryDB::new()))?; },
+		None => { let _ = s.run_constructors(&s.genesis_factories(), BasicBackend(MemoryDB::new()))?; },
 	}
 
 	Ok(s)
@@ -288,6 +478,7 @@ impl Spec {
 			ethjson::spec::Engine::BasicAuthority(basic_authority) => Arc::new(BasicAuthority::new(params, From::from(basic_authority.params), builtins)),
 			ethjson::spec::Engine::AuthorityRound(authority_round) => AuthorityRound::new(params, From::from(authority_round.params), builtins).expect("Failed to start AuthorityRound consensus engine."),
 			ethjson::spec::Engine::Tendermint(tendermint) => Tendermint::new(params, From::from(tendermint.params), builtins).expect("Failed to start the Tendermint consensus engine."),
+			ethjson::spec::Engine::Clique(clique) => Arc::new(Clique::new(params, From::from(clique.params), builtins)),
 		}
 	}
 
@@ -295,23 +486,26 @@ impl Spec {
 	fn run_constructors<T: Backend>(&self, factories: &Factories, mut db: T) -> Result<T, Error> {
 		let mut root = KECCAK_NULL_RLP;
 
+		// storage of basic accounts in spec, hashed like the rest of the state.
+		let mut accounts = Vec::with_capacity(self.genesis_state.get().len());
+		for (address, account) in self.genesis_state.get().iter() {
+			db.note_non_null_account(address);
+			let storage_root = account.insert_additional(
+				&mut *factories.accountdb.create(db.as_hashdb_mut(), factories.trie.hasher().hash(address)),
+				&factories.trie
+			);
+			accounts.push((address, account.rlp_with_storage_root(&storage_root)));
+		}
+
 		// basic accounts in spec.
 		{
 			let mut t = factories.trie.create(db.as_hashdb_mut(), &mut root);
 
-			for (address, account) in self.genesis_state.get().iter() {
-				t.insert(&**address, &account.rlp())?;
+			for (address, rlp) in accounts {
+				t.insert(&**address, &rlp)?;
 			}
 		}
 
-		for (address, account) in self.genesis_state.get().iter() {
-			db.note_non_null_account(address);
-			account.insert_additional(
-				&mut *factories.accountdb.create(db.as_hashdb_mut(), keccak(address)),
-				&factories.trie
-			);
-		}
-
 		let start_nonce = self.engine.account_start_nonce(0);
 
 		let (root, db) = {
@@ -374,13 +568,21 @@ impl Spec {
 		Ok(db)
 	}
 
+	/// Factories for building the state of this chain outside of a client.
+	pub fn genesis_factories(&self) -> Factories {
+		Factories {
+			trie: TrieFactory::default().with_hasher(self.params().trie_hasher),
+			..Default::default()
+		}
+	}
+
 	/// Return the state root for the genesis state, memoising accordingly.
 	pub fn state_root(&self) -> H256 {
 		self.state_root_memo.read().clone()
 	}
 
 	/// Get common blockchain parameters.
-	pub fn params(&self) -> &CommonParams { &self.engine.params() }
+	pub fn params(&self) -> Arc<CommonParams> { self.engine.params() }
 
 	/// Get the known knodes of the network in enode format.
 	pub fn nodes(&self) -> &[String] { &self.nodes }
@@ -397,6 +599,11 @@ impl Spec {
 	/// Get the configured network fork block.
 	pub fn fork_block(&self) -> Option<(BlockNumber, H256)> { self.params().fork_block }
 
+	/// Get the fork identifier filter of this chain.
+	pub fn fork_filter(&self) -> ForkFilter {
+		ForkFilter::new(self.genesis_header().hash(), self.engine.fork_blocks())
+	}
+
 	/// Get the header of the genesis block.
 	pub fn genesis_header(&self) -> Header {
 		let mut header: Header = Default::default();
@@ -447,10 +654,15 @@ impl Spec {
 		self.seal_rlp = seal_rlp;
 	}
 
+	/// Genesis accounts defined by the spec.
+	pub fn genesis_state(&self) -> &PodState {
+		&self.genesis_state
+	}
+
 	/// Alter the value of the genesis state.
 	pub fn set_genesis_state(&mut self, s: PodState) -> Result<(), Error> {
 		self.genesis_state = s;
-		let _ = self.run_constructors(&Default::default(), BasicBackend(MemoryDB::new()))?;
+		let _ = self.run_constructors(&self.genesis_factories(), BasicBackend(MemoryDB::new()))?;
 
 		Ok(())
 	}
@@ -486,6 +698,16 @@ impl Spec {
 			.and_then(|x| load_from(cache_dir, x).map_err(fmt))
 	}
 
+	/// Loads only the chain parameters and builtins from json, e.g. to reload them on a running chain.
+	pub fn load_params<R>(reader: R) -> Result<(CommonParams, BTreeMap<Address, Builtin>), String> where R: Read {
+		let spec = ethjson::spec::Spec::load(reader).map_err(|e| format!("Spec json is invalid: {}", e))?;
+		check_custom_opcodes(&spec.params).map_err(|e| format!("Spec json is invalid: {}", e))?;
+		check_trie_hasher(&spec.params).map_err(|e| format!("Spec json is invalid: {}", e))?;
+		check_wasm_host_functions(&spec.params).map_err(|e| format!("Spec json is invalid: {}", e))?;
+		let builtins = load_builtins(&spec.accounts).map_err(|e| format!("Spec json is invalid: {}", e))?;
+		Ok((CommonParams::from(spec.params), builtins))
+	}
+
 	/// initialize genesis epoch data, using in-memory database for
 	/// constructor.
 	pub fn genesis_epoch_data(&self) -> Result<Vec<u8>, String> {
@@ -494,7 +716,7 @@ impl Spec {
 
 		let genesis = self.genesis_header();
 
-		let factories = Default::default();
+		let factories = self.genesis_factories();
 		let mut db = journaldb::new(
 			Arc::new(kvdb::in_memory(0)),
 			journaldb::Algorithm::Archive,
@@ -566,6 +788,10 @@ impl Spec {
 	/// Account keccak("0") and keccak("1") are a authorities.
 	pub fn new_test_tendermint() -> Self { load_bundled!("tendermint") }
 
+	/// Create a new Spec with Clique consensus, which can be followed but not sealed.
+	/// Accounts with secrets keccak("0") and keccak("1") are the signers.
+	pub fn new_test_clique() -> Self { load_bundled!("clique") }
+
 	/// TestList.sol used in both specs: https://github.com/paritytech/contracts/pull/30/files
 	/// Accounts with secrets keccak("0") and keccak("1") are initially the validators.
 	/// Create a new Spec with BasicAuthority which uses a contract at address 5 to determine the current validators using `getValidators`.
@@ -621,4 +847,88 @@ mod tests {
 		assert_eq!(state.storage_at(&address, &H256::zero()).unwrap(), expected);
 		assert_eq!(state.balance(&address).unwrap(), 1.into());
 	}
+
+	#[test]
+	fn custom_opcodes_are_activated_at_given_block() {
+		let instruction = ::vm::CustomInstruction { name: "test".into(), args: 1, ret: 1, gas: 10 };
+		let mut params = CommonParams::default();
+		params.custom_opcodes = vec![(10, 0x0c, instruction.clone())];
+
+		assert!(params.schedule(9).custom_instructions.is_empty());
+		assert_eq!(params.schedule(10).custom_instructions.get(&0x0c), Some(&instruction));
+		assert!(params.fork_blocks().contains(&10));
+	}
+
+	#[test]
+	fn max_code_size_overrides_engine_default() {
+		let mut params = CommonParams::default();
+		assert_eq!(params.schedule(0).create_data_limit, usize::max_value());
+
+		params.max_code_size = Some(0x6000);
+		assert_eq!(params.schedule(0).create_data_limit, 0x6000);
+	}
+
+	#[test]
+	fn unknown_trie_hasher_is_rejected() {
+		let json = include_str!("../../res/constructor.json").replacen("\"params\": {", "\"params\": { \"trieHasher\": \"md5\",", 1);
+		assert!(Spec::load(::std::env::temp_dir(), json.as_bytes()).is_err());
+	}
+
+	#[cfg(feature = "sha3-trie")]
+	#[test]
+	fn genesis_constructor_with_sha3_trie() {
+		let json = include_str!("../../res/constructor.json").replacen("\"params\": {", "\"params\": { \"trieHasher\": \"sha3\",", 1);
+		let spec = Spec::load(::std::env::temp_dir(), json.as_bytes()).unwrap();
+		assert_eq!(spec.params().trie_hasher, TrieHasher::Sha3);
+		assert!(spec.state_root() != Spec::new_test_constructor().state_root());
+
+		let factories = spec.genesis_factories();
+		let db = spec.ensure_db_good(get_temp_state_db(), &factories).unwrap();
+		let state = State::from_existing(db.boxed_clone(), spec.state_root(), spec.engine.account_start_nonce(0), factories).unwrap();
+		let address = Address::from_str("0000000000000000000000000000000000000005").unwrap();
+
+		assert_eq!(state.storage_at(&address, &H256::zero()).unwrap(), H256::from(1));
+		assert_eq!(state.balance(&address).unwrap(), 1.into());
+	}
+
+	#[test]
+	fn reloaded_params_may_only_change_future_transitions() {
+		let mut old = CommonParams::default();
+		old.eip140_transition = 30;
+		let mut new = old.clone();
+		new.eip140_transition = 20;
+		assert!(old.check_reload(&new, 10).is_ok());
+		assert!(old.check_reload(&new, 20).is_err());
+
+		let mut new = old.clone();
+		new.network_id = old.network_id + 1;
+		assert!(old.check_reload(&new, 0).is_err());
+
+		let mut new = old.clone();
+		new.transaction_permission_contract = Some(Address::from(0x42));
+		assert!(old.check_reload(&new, 0).is_err());
+
+		let mut new = old.clone();
+		new.node_permission_contract = Some(Address::from(0x42));
+		assert!(old.check_reload(&new, 0).is_err());
+	}
+
+	#[test]
+	fn reloaded_builtins_may_only_change_pricing_of_inactive_ones() {
+		let builtins = |activate_at: u64, word: usize| {
+			let builtin = ethjson::spec::Builtin {
+				name: "identity".into(),
+				pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear { base: 15, word: word }),
+				activate_at: Some(ethjson::uint::Uint(activate_at.into())),
+			};
+			let mut map = BTreeMap::new();
+			map.insert(Address::from(4), Builtin::from_json(builtin).unwrap());
+			map
+		};
+
+		assert!(check_builtins_reload(&builtins(10, 3), &builtins(10, 3), 20).is_ok());
+		assert!(check_builtins_reload(&builtins(10, 3), &builtins(10, 2), 5).is_ok());
+		assert!(check_builtins_reload(&builtins(10, 3), &builtins(10, 2), 20).is_err());
+		assert!(check_builtins_reload(&builtins(10, 3), &builtins(30, 3), 20).is_err());
+	}
 }
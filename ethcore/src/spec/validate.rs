@@ -0,0 +1,553 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Chain specification validation with diagnostics pointing at the offending fields.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use util::Address;
+use builtin;
+use ethjson;
+use ethjson::uint::Uint;
+use ethjson::spec::{Engine, Pricing, Seal, ValidatorSet};
+use super::Spec;
+
+/// Pricing schemes builtins are expected to use.
+const BUILTIN_PRICING: &'static [(&'static str, &'static [&'static str])] = &[
+	("identity", &["linear"]),
+	("ecrecover", &["linear"]),
+	("sha256", &["linear"]),
+	("ripemd160", &["linear"]),
+	("modexp", &["modexp"]),
+	("bn128_add", &["linear"]),
+	("bn128_mul", &["linear"]),
+	("bn128_pairing", &["alt_bn128_pairing", "linear"]),
+	("blake2_f", &["blake2_f"]),
+];
+
+/// How serious a problem found in a chain specification is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	/// The chain can't be started with the specification.
+	Error,
+	/// The specification can be used, but is likely not what was intended.
+	Warning,
+}
+
+/// A problem found in a chain specification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+	/// How serious the problem is.
+	pub severity: Severity,
+	/// Dotted path of the offending field, e.g. `engine.Ethash.params.minimumDifficulty`.
+	/// Empty if the problem doesn't concern a particular field.
+	pub path: String,
+	/// Line of the offending field in the source (starting at 1), if it could be located.
+	pub line: Option<usize>,
+	/// Description of the problem.
+	pub message: String,
+}
+
+impl fmt::Display for Issue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let severity = match self.severity {
+			Severity::Error => "error",
+			Severity::Warning => "warning",
+		};
+		match self.path.is_empty() {
+			true => write!(f, "{}: {}", severity, self.message),
+			false => write!(f, "{}: {}: {}", severity, self.path, self.message),
+		}
+	}
+}
+
+/// Outcome of chain specification validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+	/// Problems found in the specification.
+	pub issues: Vec<Issue>,
+	/// Hash of the genesis block, if the specification could be loaded.
+	pub genesis_hash: Option<H256>,
+}
+
+impl Report {
+	/// Returns true if any of the issues prevents the specification from being used.
+	pub fn has_errors(&self) -> bool {
+		self.issues.iter().any(|issue| issue.severity == Severity::Error)
+	}
+}
+
+/// Validates chain specification JSON: its structure, engine parameters, transition ordering
+/// and builtin pricing. If no errors are found the specification is loaded and the genesis hash
+/// computed.
+pub fn validate<T: AsRef<Path>>(cache_dir: T, source: &str) -> Report {
+	let mut checker = Checker {
+		lines: key_lines(source),
+		issues: Vec::new(),
+	};
+
+	let spec = match ethjson::spec::Spec::load(source.as_bytes()) {
+		Ok(spec) => spec,
+		Err(e) => {
+			let message = e.to_string();
+			let location = format!(" at line {} column {}", e.line(), e.column());
+			checker.issues.push(Issue {
+				severity: Severity::Error,
+				path: String::new(),
+				line: if e.line() > 0 { Some(e.line()) } else { None },
+				message: format!("{} (column {})", message.trim_right_matches(location.as_str()), e.column()),
+			});
+			return Report { issues: checker.issues, genesis_hash: None };
+		},
+	};
+
+	checker.check_params(&spec.params);
+	checker.check_engine(&spec.engine);
+	checker.check_genesis_seal(&spec.engine, &spec.genesis.seal);
+	for (address, builtin) in spec.accounts.builtins() {
+		let address: Address = address.into();
+		checker.check_builtin(&format!("{:?}", address), &builtin);
+	}
+
+	if checker.issues.iter().any(|issue| issue.severity == Severity::Error) {
+		return Report { issues: checker.issues, genesis_hash: None };
+	}
+
+	// memoized state root can only be verified without constructors, as they run on top of the
+	// plain genesis state.
+	let verify_state_root = spec.genesis.state_root.is_some()
+		&& spec.accounts.constructors().is_empty()
+		&& spec.params.trie_hasher.is_none();
+
+	let genesis_hash = match Spec::load(cache_dir, source.as_bytes()) {
+		Ok(spec) => {
+			if verify_state_root && !spec.is_state_root_valid() {
+				checker.error(&["genesis", "stateRoot"], "does not match the genesis accounts".into());
+				None
+			} else {
+				Some(spec.genesis_header().hash())
+			}
+		},
+		Err(e) => {
+			checker.error(&[], e);
+			None
+		},
+	};
+
+	Report { issues: checker.issues, genesis_hash: genesis_hash }
+}
+
+struct Checker {
+	lines: HashMap<String, usize>,
+	issues: Vec<Issue>,
+}
+
+impl Checker {
+	fn error(&mut self, path: &[&str], message: String) {
+		self.push(Severity::Error, path, message)
+	}
+
+	fn warning(&mut self, path: &[&str], message: String) {
+		self.push(Severity::Warning, path, message)
+	}
+
+	fn push(&mut self, severity: Severity, path: &[&str], message: String) {
+		// point at the closest enclosing field present in the source.
+		let line = (0..path.len() + 1).rev()
+			.filter_map(|len| self.lines.get(&normalized_path(&path[..len])).cloned())
+			.next();
+
+		self.issues.push(Issue {
+			severity: severity,
+			path: path.join("."),
+			line: line,
+			message: message,
+		});
+	}
+
+	fn check_non_zero(&mut self, path: &[&str], value: &Uint) {
+		if value.0.is_zero() {
+			self.error(path, "must be greater than zero".into());
+		}
+	}
+
+	/// Warns about transitions activating before the ones preceding them in the list.
+	/// Missing transitions are given their default values.
+	fn check_order(&mut self, base: &[&str], transitions: &[(&str, Option<&Uint>, U256)]) {
+		let mut latest: Option<(&str, U256)> = None;
+		for &(name, value, default) in transitions {
+			let block = value.map_or(default, |v| v.0);
+			if let Some((previous, previous_block)) = latest {
+				if block < previous_block {
+					let mut path = base.to_vec();
+					path.push(name);
+					self.warning(&path, format!("activates at block {} which is before {} at block {}", block, previous, previous_block));
+					continue;
+				}
+			}
+			latest = Some((name, block));
+		}
+	}
+
+	fn check_params(&mut self, params: &ethjson::spec::Params) {
+		self.check_non_zero(&["params", "gasLimitBoundDivisor"], &params.gas_limit_bound_divisor);
+
+		match (params.fork_block.is_some(), params.fork_hash.is_some()) {
+			(true, false) => self.warning(&["params", "forkBlock"], "is ignored without forkCanonHash".into()),
+			(false, true) => self.warning(&["params", "forkCanonHash"], "is ignored without forkBlock".into()),
+			_ => {},
+		}
+	}
+
+	fn check_engine(&mut self, engine: &Engine) {
+		let never = U256::from(u64::max_value());
+		match *engine {
+			Engine::Ethash(ref ethash) => {
+				let p = &ethash.params;
+				let base = ["engine", "Ethash", "params"];
+				self.check_non_zero(&["engine", "Ethash", "params", "minimumDifficulty"], &p.minimum_difficulty);
+				self.check_non_zero(&["engine", "Ethash", "params", "difficultyBoundDivisor"], &p.difficulty_bound_divisor);
+				if let Some(ref divisor) = p.difficulty_increment_divisor {
+					self.check_non_zero(&["engine", "Ethash", "params", "difficultyIncrementDivisor"], divisor);
+				}
+				if let Some(ref divisor) = p.metropolis_difficulty_increment_divisor {
+					self.check_non_zero(&["engine", "Ethash", "params", "metropolisDifficultyIncrementDivisor"], divisor);
+				}
+				if let Some(ref divisor) = p.difficulty_hardfork_bound_divisor {
+					self.check_non_zero(&["engine", "Ethash", "params", "difficultyHardforkBoundDivisor"], divisor);
+				}
+
+				// bundled specs disable transitions with 0x7fffffffffffffff.
+				let dao_enabled = p.dao_hardfork_transition.as_ref().map_or(false, |t| t.0 < U256::from(i64::max_value() as u64));
+				if dao_enabled && p.dao_hardfork_beneficiary.is_none() {
+					self.warning(&["engine", "Ethash", "params", "daoHardforkTransition"], "DAO hard fork is enabled without daoHardforkBeneficiary".into());
+				}
+
+				let pause = p.ecip1010_pause_transition.as_ref().map_or(never, |t| t.0);
+				let resume = p.ecip1010_continue_transition.as_ref().map_or(never, |t| t.0);
+				if resume < pause {
+					self.error(&["engine", "Ethash", "params", "ecip1010ContinueTransition"], format!("activates at block {} which is before ecip1010PauseTransition at block {}", resume, pause));
+				}
+
+				self.check_order(&base, &[
+					("homesteadTransition", p.homestead_transition.as_ref(), U256::zero()),
+					("eip150Transition", p.eip150_transition.as_ref(), U256::zero()),
+					("eip160Transition", p.eip160_transition.as_ref(), U256::zero()),
+					("eip161abcTransition", p.eip161abc_transition.as_ref(), U256::zero()),
+					("eip161dTransition", p.eip161d_transition.as_ref(), never),
+				]);
+			},
+			Engine::BasicAuthority(ref basic) => {
+				self.check_validators(&["engine", "basicAuthority", "params", "validators"], &basic.params.validators);
+			},
+			Engine::AuthorityRound(ref aura) => {
+				self.check_non_zero(&["engine", "authorityRound", "params", "stepDuration"], &aura.params.step_duration);
+				self.check_validators(&["engine", "authorityRound", "params", "validators"], &aura.params.validators);
+			},
+			Engine::Tendermint(ref tendermint) => {
+				self.check_validators(&["engine", "tendermint", "params", "validators"], &tendermint.params.validators);
+			},
+			Engine::Clique(ref clique) => {
+				if clique.params.epoch.as_ref().map_or(false, |epoch| epoch.0.is_zero()) {
+					self.warning(&["engine", "clique", "params", "epoch"], "zero epoch falls back to the default of 30000 blocks".into());
+				}
+			},
+			Engine::Null | Engine::InstantSeal => {},
+		}
+	}
+
+	fn check_validators(&mut self, path: &[&str], validators: &ValidatorSet) {
+		match *validators {
+			ValidatorSet::List(ref list) if list.is_empty() => {
+				let mut path = path.to_vec();
+				path.push("list");
+				self.error(&path, "must contain at least one validator".into());
+			},
+			ValidatorSet::Multi(ref sets) => {
+				let mut multi = path.to_vec();
+				multi.push("multi");
+				if sets.is_empty() || sets.keys().next().map_or(true, |first| !first.0.is_zero()) {
+					self.error(&multi, "must specify the validator set for block 0".into());
+				}
+				for (block, set) in sets {
+					let block = block.0.to_string();
+					let mut path = multi.clone();
+					path.push(&block);
+					self.check_validators(&path, set);
+				}
+			},
+			_ => {},
+		}
+	}
+
+	fn check_genesis_seal(&mut self, engine: &Engine, seal: &Seal) {
+		let expected = match *engine {
+			Engine::Ethash(_) => "ethereum",
+			Engine::AuthorityRound(_) => "authorityRound",
+			Engine::Tendermint(_) => "tendermint",
+			_ => return,
+		};
+		let actual = match *seal {
+			Seal::Ethereum(_) => "ethereum",
+			Seal::AuthorityRound(_) => "authorityRound",
+			Seal::Tendermint(_) => "tendermint",
+			Seal::Generic(_) => "generic",
+		};
+		if actual != expected && actual != "generic" {
+			self.warning(&["genesis", "seal", actual], format!("engine expects `{}` seal", expected));
+		}
+	}
+
+	fn check_builtin(&mut self, address: &str, builtin: &ethjson::spec::Builtin) {
+		let pricing = match builtin.pricing {
+			Pricing::Linear(ref linear) => {
+				if linear.base == 0 && linear.word == 0 {
+					self.warning(&["accounts", address, "builtin", "pricing", "linear"], format!("builtin `{}` is free to call", builtin.name));
+				}
+				"linear"
+			},
+			Pricing::Modexp(ref modexp) => {
+				if modexp.divisor == 0 {
+					self.warning(&["accounts", address, "builtin", "pricing", "modexp", "divisor"], "zero divisor falls back to the default of 10".into());
+				}
+				"modexp"
+			},
+			Pricing::AltBn128Pairing(_) => "alt_bn128_pairing",
+			Pricing::Blake2F(_) => "blake2_f",
+		};
+
+		if !builtin::is_known(&builtin.name) {
+			self.error(&["accounts", address, "builtin", "name"], format!("unknown builtin `{}`", builtin.name));
+			return;
+		}
+
+		match BUILTIN_PRICING.iter().find(|&&(name, _)| name == builtin.name) {
+			Some(&(_, expected)) if !expected.contains(&pricing) => {
+				self.warning(&["accounts", address, "builtin", "pricing", pricing], format!("`{}` pricing is unusual for builtin `{}`", pricing, builtin.name));
+			},
+			_ => {},
+		}
+	}
+}
+
+fn normalized_path(path: &[&str]) -> String {
+	path.iter()
+		.map(|segment| {
+			let segment = segment.to_lowercase();
+			match segment.starts_with("0x") {
+				true => segment[2..].to_owned(),
+				false => segment,
+			}
+		})
+		.collect::<Vec<_>>()
+		.join(".")
+}
+
+/// Maps normalized dotted paths of object keys in the JSON source to the lines they first appear on.
+fn key_lines(source: &str) -> HashMap<String, usize> {
+	// key currently open in each enclosing object, `None` for arrays and objects before their first key.
+	let mut keys: Vec<Option<String>> = Vec::new();
+	let mut lines = HashMap::new();
+	let mut last_string: Option<(String, usize)> = None;
+	let mut line = 1;
+	let mut chars = source.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'\n' => line += 1,
+			'"' => {
+				let start = line;
+				let mut string = String::new();
+				while let Some(c) = chars.next() {
+					match c {
+						'\\' => { chars.next(); },
+						'"' => break,
+						'\n' => { line += 1; },
+						c => string.push(c),
+					}
+				}
+				last_string = Some((string, start));
+			},
+			':' => if let Some((key, key_line)) = last_string.take() {
+				if let Some(current) = keys.last_mut() {
+					*current = Some(key);
+				}
+				let path: Vec<&str> = keys.iter().filter_map(|k| k.as_ref().map(|k| k.as_str())).collect();
+				lines.entry(normalized_path(&path)).or_insert(key_line);
+			},
+			'{' | '[' => {
+				keys.push(None);
+				last_string = None;
+			},
+			'}' | ']' => {
+				keys.pop();
+				last_string = None;
+			},
+			',' => last_string = None,
+			_ => {},
+		}
+	}
+
+	lines
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{validate, key_lines, Severity};
+
+	fn spec(engine: &str, params: &str, accounts: &str) -> String {
+		format!(r#"{{
+	"name": "Test",
+	"engine": {},
+	"params": {{
+		"gasLimitBoundDivisor": "0x0400",
+		"accountStartNonce": "0x0",
+		"maximumExtraDataSize": "0x20",
+		"minGasLimit": "0x1388",
+		"networkID" : "0x11"{}
+	}},
+	"genesis": {{
+		"seal": {{ "generic": "0x" }},
+		"difficulty": "0x20000",
+		"gasLimit": "0x5B8D80"
+	}},
+	"accounts": {{
+{}
+	}}
+}}"#, engine, params, accounts)
+	}
+
+	const ECRECOVER: &'static str = r#"		"0000000000000000000000000000000000000001": { "balance": "1", "builtin": { "name": "ecrecover", "pricing": { "linear": { "base": 3000, "word": 0 } } } }"#;
+
+	#[test]
+	fn should_map_keys_to_lines() {
+		let lines = key_lines("{\n\"a\": {\n\"b\": [1, {\"c\": \"x:y\"}],\n\"0xAB\": 1\n}\n}");
+		assert_eq!(lines.get("a"), Some(&2));
+		assert_eq!(lines.get("a.b"), Some(&3));
+		assert_eq!(lines.get("a.b.c"), Some(&3));
+		assert_eq!(lines.get("a.ab"), Some(&4));
+	}
+
+	#[test]
+	fn should_accept_bundled_specs() {
+		for source in &[
+			include_str!("../../res/instant_seal.json"),
+			include_str!("../../res/ethereum/foundation.json"),
+			include_str!("../../res/authority_round.json"),
+			include_str!("../../res/validator_multi.json"),
+		] {
+			let report = validate(::std::env::temp_dir(), source);
+			assert!(!report.has_errors(), "{:?}", report.issues);
+			assert!(report.genesis_hash.is_some());
+		}
+	}
+
+	#[test]
+	fn should_compute_genesis_hash() {
+		let source = spec(r#"{ "instantSeal": null }"#, "", ECRECOVER);
+		let report = validate(::std::env::temp_dir(), &source);
+		let spec = ::spec::Spec::load(::std::env::temp_dir(), source.as_bytes()).unwrap();
+		assert_eq!(report.issues, vec![]);
+		assert_eq!(report.genesis_hash, Some(spec.genesis_header().hash()));
+	}
+
+	#[test]
+	fn should_report_line_of_malformed_json() {
+		let source = spec(r#"{ "instantSeal": null }"#, ",\n\t\t\"eip155Transition\": \"0x\" 1", ECRECOVER);
+		let report = validate(::std::env::temp_dir(), &source);
+		assert!(report.has_errors());
+		assert_eq!(report.issues.len(), 1);
+		assert_eq!(report.issues[0].line, Some(10));
+		assert_eq!(report.genesis_hash, None);
+	}
+
+	#[test]
+	fn should_report_inconsistent_engine_params() {
+		let engine = r#"{
+		"authorityRound": {
+			"params": {
+				"stepDuration": 0,
+				"validators": { "list": [] }
+			}
+		}
+	}"#;
+		let report = validate(::std::env::temp_dir(), &spec(engine, "", ECRECOVER));
+		let paths: Vec<_> = report.issues.iter().map(|i| (i.severity, i.path.as_str(), i.line)).collect();
+		assert_eq!(paths, vec![
+			(Severity::Error, "engine.authorityRound.params.stepDuration", Some(6)),
+			(Severity::Error, "engine.authorityRound.params.validators.list", Some(7)),
+		]);
+		assert_eq!(report.genesis_hash, None);
+	}
+
+	#[test]
+	fn should_report_validator_set_missing_at_genesis() {
+		let engine = r#"{ "basicAuthority": { "params": { "durationLimit": "0x0d", "validators": { "multi": {
+			"10": { "list": ["0x0000000000000000000000000000000000000005"] }
+		} } } } }"#;
+		let report = validate(::std::env::temp_dir(), &spec(engine, "", ECRECOVER));
+		assert_eq!(report.issues.len(), 1);
+		assert_eq!(report.issues[0].path, "engine.basicAuthority.params.validators.multi");
+		assert_eq!(report.issues[0].line, Some(3));
+	}
+
+	#[test]
+	fn should_warn_about_transition_ordering() {
+		let engine = r#"{ "Ethash": { "params": {
+		"minimumDifficulty": "0x020000",
+		"difficultyBoundDivisor": "0x0800",
+		"homesteadTransition": 100,
+		"eip150Transition": 50
+	} } }"#;
+		let report = validate(::std::env::temp_dir(), &spec(engine, "", ECRECOVER));
+		assert!(!report.has_errors());
+		assert_eq!(report.issues.len(), 1);
+		assert_eq!(report.issues[0].severity, Severity::Warning);
+		assert_eq!(report.issues[0].path, "engine.Ethash.params.eip150Transition");
+		assert_eq!(report.issues[0].line, Some(7));
+	}
+
+	#[test]
+	fn should_report_builtin_problems() {
+		let accounts = r#"		"0000000000000000000000000000000000000001": { "balance": "1", "builtin": { "name": "ecrecover", "pricing": { "linear": { "base": 0, "word": 0 } } } },
+		"0000000000000000000000000000000000000005": { "builtin": { "name": "modexp", "pricing": { "linear": { "base": 10, "word": 1 } } } },
+		"0000000000000000000000000000000000000006": { "builtin": { "name": "unknown", "pricing": { "linear": { "base": 10, "word": 1 } } } }"#;
+		let report = validate(::std::env::temp_dir(), &spec(r#"{ "null": null }"#, "", accounts));
+		let issues: Vec<_> = report.issues.iter().map(|i| (i.severity, i.line)).collect();
+		assert_eq!(issues, vec![
+			(Severity::Warning, Some(17)),
+			(Severity::Warning, Some(18)),
+			(Severity::Error, Some(19)),
+		]);
+		assert_eq!(report.genesis_hash, None);
+	}
+
+	#[test]
+	fn should_detect_wrong_state_root() {
+		let source = spec(r#"{ "null": null }"#, "", ECRECOVER).replace(
+			r#""gasLimit": "0x5B8D80""#,
+			r#""gasLimit": "0x5B8D80", "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000001""#,
+		);
+		let report = validate(::std::env::temp_dir(), &source);
+		assert_eq!(report.issues.len(), 1);
+		assert_eq!(report.issues[0].path, "genesis.stateRoot");
+		assert_eq!(report.issues[0].line, Some(14));
+	}
+}
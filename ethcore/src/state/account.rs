@@ -168,6 +168,17 @@ impl Account {
 		self.init_code(code);
 	}
 
+	/// Reset this account's code and storage to given values.
+	pub fn reset_code_and_storage(&mut self, code: Arc<Bytes>, storage: HashMap<H256, H256>) {
+		self.code_hash = keccak(&*code);
+		self.code_size = Some(code.len());
+		self.code_cache = code;
+		self.code_filth = Filth::Dirty;
+		self.storage_root = KECCAK_NULL_RLP;
+		self.storage_cache = Self::empty_storage_cache();
+		self.storage_changes = storage;
+	}
+
 	/// Set (and cache) the contents of the trie's storage at `key` to `value`.
 	pub fn set_storage(&mut self, key: H256, value: H256) {
 		self.storage_changes.insert(key, value);
@@ -210,11 +221,11 @@ impl Account {
 		self.code_hash.clone()
 	}
 
-	/// return the code hash associated with this account.
-	pub fn address_hash(&self, address: &Address) -> H256 {
+	/// return the hash of the account's address, derived with the state's trie hasher.
+	pub fn address_hash(&self, address: &Address, hasher: TrieHasher) -> H256 {
 		let hash = self.address_hash.get();
 		hash.unwrap_or_else(|| {
-			let hash = keccak(address);
+			let hash = hasher.hash(address);
 			self.address_hash.set(Some(hash.clone()));
 			hash
 		})
@@ -27,8 +27,10 @@ use std::sync::Arc;
 use state::Account;
 use bigint::hash::H256;
 use parking_lot::Mutex;
-use util::{Address, MemoryDB};
+use util::{Address, Bytes, DBTransaction, MemoryDB, TrieHasher, UtilError};
 use util::hashdb::{AsHashDB, HashDB, DBValue};
+use util::journaldb::JournalDB;
+use util::kvdb::KeyValueDB;
 
 /// State backend. See module docs for more details.
 pub trait Backend: Send {
@@ -80,8 +82,19 @@ pub struct ProofCheck(MemoryDB);
 impl ProofCheck {
 	/// Create a new `ProofCheck` backend from the given state items.
 	pub fn new(proof: &[DBValue]) -> Self {
+		ProofCheck::with_hasher(proof, TrieHasher::Keccak)
+	}
+
+	/// Create a new `ProofCheck` backend from state items of tries keyed with `hasher`.
+	/// Items stay reachable by their keccak hash as well, which keys contract code.
+	pub fn with_hasher(proof: &[DBValue], hasher: TrieHasher) -> Self {
 		let mut db = MemoryDB::new();
-		for item in proof { db.insert(item); }
+		for item in proof {
+			db.insert(item);
+			if hasher != TrieHasher::Keccak {
+				db.emplace(hasher.hash(item), item.clone());
+			}
+		}
 		ProofCheck(db)
 	}
 }
@@ -225,6 +238,125 @@ impl<H: AsHashDB + Clone> Clone for Proving<H> {
 	}
 }
 
+/// Witness-recording journal database.
+/// Like `Proving`, this keeps track of all values loaded from the base database,
+/// but can back a `StateDB` and therefore be used to re-execute whole blocks.
+///
+/// Changes are kept in memory and never journalled into the base database.
+/// Clones share the recorded witness, which can be obtained with `witness`.
+pub struct Recording {
+	base: Box<JournalDB>,
+	changed: MemoryDB,
+	witness: Arc<Mutex<HashSet<DBValue>>>,
+}
+
+impl Recording {
+	/// Create a new `Recording` over a base database.
+	pub fn new(base: Box<JournalDB>) -> Self {
+		Recording {
+			base: base,
+			changed: MemoryDB::new(),
+			witness: Arc::new(Mutex::new(HashSet::new())),
+		}
+	}
+
+	/// Handle to the values recorded so far by this database and all of its clones.
+	pub fn witness(&self) -> Arc<Mutex<HashSet<DBValue>>> {
+		self.witness.clone()
+	}
+}
+
+impl HashDB for Recording {
+	fn keys(&self) -> HashMap<H256, i32> {
+		let mut keys = self.base.keys();
+		keys.extend(self.changed.keys());
+		keys
+	}
+
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		match self.base.get(key) {
+			Some(val) => {
+				self.witness.lock().insert(val.clone());
+				Some(val)
+			}
+			None => self.changed.get(key)
+		}
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		self.get(key).is_some()
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		self.changed.insert(value)
+	}
+
+	fn emplace(&mut self, key: H256, value: DBValue) {
+		self.changed.emplace(key, value)
+	}
+
+	fn remove(&mut self, key: &H256) {
+		// only remove from `changed`
+		if self.changed.contains(key) {
+			self.changed.remove(key)
+		}
+	}
+}
+
+impl JournalDB for Recording {
+	fn boxed_clone(&self) -> Box<JournalDB> {
+		Box::new(Recording {
+			base: self.base.boxed_clone(),
+			changed: self.changed.clone(),
+			witness: self.witness.clone(),
+		})
+	}
+
+	fn mem_used(&self) -> usize {
+		self.base.mem_used() + self.changed.mem_used()
+	}
+
+	fn is_empty(&self) -> bool {
+		self.base.is_empty()
+	}
+
+	fn earliest_era(&self) -> Option<u64> {
+		self.base.earliest_era()
+	}
+
+	fn latest_era(&self) -> Option<u64> {
+		self.base.latest_era()
+	}
+
+	fn journal_under(&mut self, _batch: &mut DBTransaction, _now: u64, _id: &H256) -> Result<u32, UtilError> {
+		Ok(0)
+	}
+
+	fn mark_canonical(&mut self, _batch: &mut DBTransaction, _era: u64, _id: &H256) -> Result<u32, UtilError> {
+		Ok(0)
+	}
+
+	fn inject(&mut self, _batch: &mut DBTransaction) -> Result<u32, UtilError> {
+		Ok(0)
+	}
+
+	fn state(&self, id: &H256) -> Option<Bytes> {
+		self.base.state(id)
+	}
+
+	fn is_pruned(&self) -> bool {
+		self.base.is_pruned()
+	}
+
+	fn backing(&self) -> &Arc<KeyValueDB> {
+		self.base.backing()
+	}
+
+	fn consolidate(&mut self, overlay: MemoryDB) {
+		self.changed.consolidate(overlay)
+	}
+}
+
 /// A basic backend. Just wraps the given database, directly inserting into and deleting from
 /// it. Doesn't cache anything.
 pub struct Basic<H>(pub H);
@@ -50,6 +50,7 @@ use util::trie;
 use util::trie::recorder::Recorder;
 
 mod account;
+mod parallel;
 mod substate;
 
 pub mod backend;
@@ -196,8 +197,10 @@ pub fn check_proof(
 	engine: &Engine,
 	env_info: &EnvInfo,
 ) -> ProvedExecution {
-	let backend = self::backend::ProofCheck::new(proof);
+	let hasher = engine.params().trie_hasher;
+	let backend = self::backend::ProofCheck::with_hasher(proof, hasher);
 	let mut factories = Factories::default();
+	factories.trie = factories.trie.with_hasher(hasher);
 	factories.accountdb = ::account_db::Factory::Plain;
 
 	let res = State::from_existing(
@@ -311,6 +314,8 @@ pub struct State<B: Backend> {
 	checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
 	account_start_nonce: U256,
 	factories: Factories,
+	// Accounts accessed since the log was set up. Used by optimistic parallel execution.
+	accesses: RefCell<Option<parallel::AccessLog>>,
 }
 
 #[derive(Copy, Clone)]
@@ -351,6 +356,7 @@ impl<B: Backend> State<B> {
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: account_start_nonce,
 			factories: factories,
+			accesses: RefCell::new(None),
 		}
 	}
 
@@ -366,7 +372,8 @@ impl<B: Backend> State<B> {
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: account_start_nonce,
-			factories: factories
+			factories: factories,
+			accesses: RefCell::new(None),
 		};
 
 		Ok(state)
@@ -387,6 +394,7 @@ impl<B: Backend> State<B> {
 			checkpoints: self.checkpoints,
 			account_start_nonce: self.account_start_nonce,
 			factories: self.factories,
+			accesses: self.accesses,
 		}
 	}
 
@@ -448,6 +456,8 @@ impl<B: Backend> State<B> {
 		// In all other cases account is read as clean first, and after that made
 		// dirty in and added to the checkpoint with `note_cache`.
 		if account.is_dirty() {
+			self.note_read(address);
+			self.note_write(address);
 			if let Some(ref mut checkpoint) = self.checkpoints.borrow_mut().last_mut() {
 				if !checkpoint.contains_key(address) {
 					checkpoint.insert(address.clone(), self.cache.borrow_mut().insert(address.clone(), account));
@@ -466,6 +476,19 @@ impl<B: Backend> State<B> {
 		}
 	}
 
+	fn note_read(&self, address: &Address) {
+		if let Some(ref mut log) = *self.accesses.borrow_mut() {
+			log.note_read(address);
+		}
+	}
+
+	fn note_write(&self, address: &Address) {
+		if let Some(ref mut log) = *self.accesses.borrow_mut() {
+			let cache = self.cache.borrow();
+			log.note_write(address, || cache.get(address).and_then(|entry| entry.account.as_ref().map(Account::clone_dirty)));
+		}
+	}
+
 	/// Destroy the current object and return root and database.
 	pub fn drop(mut self) -> (H256, B) {
 		self.propagate_to_global_cache();
@@ -530,6 +553,8 @@ impl<B: Backend> State<B> {
 		// 2. If there's an entry for the account in the global cache check for the key or load it into that account.
 		// 3. If account is missing in the global cache load it into the local cache and cache the key there.
 
+		self.note_read(address);
+
 		// check local cache first without updating
 		{
 			let local_cache = self.cache.borrow_mut();
@@ -550,7 +575,7 @@ impl<B: Backend> State<B> {
 			let trie_res = self.db.get_cached(address, |acc| match acc {
 				None => Ok(H256::new()),
 				Some(a) => {
-					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address));
+					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address, self.factories.trie.hasher()));
 					a.storage_at(account_db.as_hashdb(), key)
 				}
 			});
@@ -563,7 +588,7 @@ impl<B: Backend> State<B> {
 			// otherwise cache the account localy and cache storage key there.
 			if let Some(ref mut acc) = local_account {
 				if let Some(ref account) = acc.account {
-					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(address));
+					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(address, self.factories.trie.hasher()));
 					return account.storage_at(account_db.as_hashdb(), key)
 				} else {
 					return Ok(H256::new())
@@ -578,7 +603,7 @@ impl<B: Backend> State<B> {
 		let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root).expect(SEC_TRIE_DB_UNWRAP_STR);
 		let maybe_acc = db.get_with(address, Account::from_rlp)?;
 		let r = maybe_acc.as_ref().map_or(Ok(H256::new()), |a| {
-			let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address));
+			let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address, self.factories.trie.hasher()));
 			a.storage_at(account_db.as_hashdb(), key)
 		});
 		self.insert_cache(address, AccountEntry::new_clean(maybe_acc));
@@ -666,6 +691,22 @@ impl<B: Backend> State<B> {
 		Ok(())
 	}
 
+	/// Replace the code and storage of account `a` with given values.
+	pub fn patch_account(&self, a: &Address, code: Arc<Bytes>, storage: HashMap<H256, H256>) -> trie::Result<()> {
+		Ok(self.require(a, false)?.reset_code_and_storage(code, storage))
+	}
+
+	/// Consume the state and return code and storage modifications of account `a`.
+	/// Storage which has not been touched since the last commit is not included.
+	pub fn into_account(self, a: &Address) -> trie::Result<(Option<Arc<Bytes>>, HashMap<H256, H256>)> {
+		self.ensure_cached(a, RequireCache::Code, true, |_| ())?;
+		let entry = self.cache.borrow_mut().remove(a);
+		match entry {
+			Some(AccountEntry { account: Some(account), .. }) => Ok((account.code(), account.storage_changes().clone())),
+			_ => Ok((None, HashMap::new())),
+		}
+	}
+
 	/// Execute a given transaction, producing a receipt and an optional trace.
 	/// This will change the state accordingly.
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> ApplyResult {
@@ -741,7 +782,7 @@ impl<B: Backend> State<B> {
 		let mut accounts = self.cache.borrow_mut();
 		for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
 			if let Some(ref mut account) = a.account {
-				let addr_hash = account.address_hash(address);
+				let addr_hash = account.address_hash(address, self.factories.trie.hasher());
 				{
 					let mut account_db = self.factories.accountdb.create(self.db.as_hashdb_mut(), addr_hash);
 					account.commit_storage(&self.factories.trie, account_db.as_hashdb_mut())?;
@@ -884,10 +925,12 @@ impl<B: Backend> State<B> {
 	/// Populates local cache if nothing found.
 	fn ensure_cached<F, U>(&self, a: &Address, require: RequireCache, check_null: bool, f: F) -> trie::Result<U>
 		where F: Fn(Option<&Account>) -> U {
+		self.note_read(a);
+
 		// check local cache first
 		if let Some(ref mut maybe_acc) = self.cache.borrow_mut().get_mut(a) {
 			if let Some(ref mut account) = maybe_acc.account {
-				let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a));
+				let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a, self.factories.trie.hasher()));
 				Self::update_account_cache(require, account, &self.db, accountdb.as_hashdb());
 				return Ok(f(Some(account)));
 			}
@@ -896,7 +939,7 @@ impl<B: Backend> State<B> {
 		// check global cache
 		let result = self.db.get_cached(a, |mut acc| {
 			if let Some(ref mut account) = acc {
-				let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a));
+				let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a, self.factories.trie.hasher()));
 				Self::update_account_cache(require, account, &self.db, accountdb.as_hashdb());
 			}
 			f(acc.map(|a| &*a))
@@ -911,7 +954,7 @@ impl<B: Backend> State<B> {
 				let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root)?;
 				let mut maybe_acc = db.get_with(a, Account::from_rlp)?;
 				if let Some(ref mut account) = maybe_acc.as_mut() {
-					let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a));
+					let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a, self.factories.trie.hasher()));
 					Self::update_account_cache(require, account, &self.db, accountdb.as_hashdb());
 				}
 				let r = f(maybe_acc.as_ref());
@@ -947,6 +990,7 @@ impl<B: Backend> State<B> {
 			}
 		}
 		self.note_cache(a);
+		self.note_write(a);
 
 		// at this point the entry is guaranteed to be in the cache.
 		Ok(RefMut::map(self.cache.borrow_mut(), |c| {
@@ -962,7 +1006,7 @@ impl<B: Backend> State<B> {
 			match entry.account {
 				Some(ref mut account) => {
 					if require_code {
-						let addr_hash = account.address_hash(a);
+						let addr_hash = account.address_hash(a, self.factories.trie.hasher());
 						let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), addr_hash);
 						Self::update_account_cache(RequireCache::Code, account, &self.db, accountdb.as_hashdb());
 					}
@@ -980,7 +1024,7 @@ impl<B: Backend> State<B> {
 	/// Returns a merkle proof of the account's trie node omitted or an encountered trie error.
 	/// If the account doesn't exist in the trie, prove that and return defaults.
 	/// Requires a secure trie to be used for accurate results.
-	/// `account_key` == keccak(address)
+	/// `account_key` == hash of the address with the chain's trie hasher
 	pub fn prove_account(&self, account_key: H256) -> trie::Result<(Vec<Bytes>, BasicAccount)> {
 		let mut recorder = Recorder::new();
 		let trie = TrieDB::new(self.db.as_hashdb(), &self.root)?;
@@ -1001,8 +1045,8 @@ impl<B: Backend> State<B> {
 	/// Prove an account's storage key's existence or nonexistence in the state.
 	/// Returns a merkle proof of the account's storage trie.
 	/// Requires a secure trie to be used for correctness.
-	/// `account_key` == keccak(address)
-	/// `storage_key` == keccak(key)
+	/// `account_key` == hash of the address with the chain's trie hasher
+	/// `storage_key` == hash of the key with the chain's trie hasher
 	pub fn prove_storage(&self, account_key: H256, storage_key: H256) -> trie::Result<(Vec<Bytes>, H256)> {
 		// TODO: probably could look into cache somehow but it's keyed by
 		// address, not keccak(address).
@@ -1044,6 +1088,7 @@ impl Clone for State<StateDB> {
 			checkpoints: RefCell::new(Vec::new()),
 			account_start_nonce: self.account_start_nonce.clone(),
 			factories: self.factories.clone(),
+			accesses: RefCell::new(None),
 		}
 	}
 }
@@ -1928,6 +1973,26 @@ mod tests {
 		assert_eq!(s.storage_at(&a, &H256::from(&U256::from(1u64))).unwrap(), H256::from(&U256::from(69u64)));
 	}
 
+	#[test]
+	fn patch_account_replaces_code_and_storage() {
+		let a = Address::zero();
+		let (root, db) = {
+			let mut state = get_temp_state();
+			state.set_storage(&a, H256::from(&U256::from(1u64)), H256::from(&U256::from(69u64))).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		let mut storage = HashMap::new();
+		storage.insert(H256::from(&U256::from(2u64)), H256::from(&U256::from(42u64)));
+		state.patch_account(&a, Arc::new(vec![1, 2, 3]), storage.clone()).unwrap();
+
+		assert_eq!(state.storage_at(&a, &H256::from(&U256::from(1u64))).unwrap(), H256::zero());
+		assert_eq!(state.storage_at(&a, &H256::from(&U256::from(2u64))).unwrap(), H256::from(&U256::from(42u64)));
+		assert_eq!(state.into_account(&a).unwrap(), (Some(Arc::new(vec![1, 2, 3])), storage));
+	}
+
 	#[test]
 	fn get_from_database() {
 		let a = Address::zero();
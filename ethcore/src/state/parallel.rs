@@ -0,0 +1,349 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Optimistic parallel execution of transactions.
+//!
+//! Every transaction is first executed on its own copy of the state, recording the accounts
+//! it reads and writes. The results are then merged in order: a transaction which read an account
+//! written by any of its predecessors is discarded and executed again on the merged state.
+//!
+//! Accounts which were written without being read (e.g. the block author receiving fees)
+//! don't cause conflicts as long as the only change is a balance increment; such increments
+//! commute and are merged as deltas.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use hash::KECCAK_EMPTY;
+use rayon::prelude::*;
+
+use bigint::prelude::U256;
+use engines::Engine;
+use error::Error;
+use executed::{Executed, ExecutionError};
+use executive::TransactOptions;
+use receipt::Receipt;
+use state_db::StateDB;
+use transaction::SignedTransaction;
+use util::Address;
+use vm::EnvInfo;
+
+use super::{State, Account, AccountEntry, AccountState, ApplyOutcome, ApplyResult, CleanupMode};
+
+/// Accounts accessed by a transaction.
+#[derive(Default)]
+pub struct AccessLog {
+	/// Accounts whose data was observed.
+	reads: HashSet<Address>,
+	/// Modified accounts.
+	writes: HashSet<Address>,
+	/// Accounts modified before being observed and their data prior to the modification.
+	blind_writes: HashMap<Address, Option<Account>>,
+}
+
+impl AccessLog {
+	/// Note that account data was observed.
+	pub fn note_read(&mut self, address: &Address) {
+		self.reads.insert(*address);
+	}
+
+	/// Note that account is about to be modified. `original` yields current account data.
+	pub fn note_write<F>(&mut self, address: &Address, original: F) where F: FnOnce() -> Option<Account> {
+		if self.writes.insert(*address) && !self.reads.contains(address) {
+			self.blind_writes.insert(*address, original());
+		}
+	}
+}
+
+/// Result of executing a transaction on a copy of the state.
+struct Speculation {
+	executed: Result<Executed, ExecutionError>,
+	log: AccessLog,
+	state: State<StateDB>,
+}
+
+impl State<StateDB> {
+	/// Execute given transactions optimistically in parallel.
+	///
+	/// Outcomes and the resulting state are the same as when applying the transactions one by one
+	/// with `apply`. Falls back to sequential execution if receipts include intermediate state roots.
+	pub fn apply_parallel(&mut self, env_info: &EnvInfo, engine: &Engine, transactions: &[SignedTransaction], tracing: bool) -> Result<Vec<ApplyOutcome>, Error> {
+		let mut env_info = env_info.clone();
+		let mut outcomes = Vec::with_capacity(transactions.len());
+
+		let params = engine.params();
+		if transactions.len() < 2 || env_info.number < params.eip98_transition || env_info.number < params.validate_receipts_transition {
+			for t in transactions {
+				let outcome = self.apply(&env_info, engine, t, tracing)?;
+				env_info.gas_used = outcome.receipt.gas_used;
+				outcomes.push(outcome);
+			}
+			return Ok(outcomes);
+		}
+
+		let speculations: Vec<Speculation> = {
+			let env_info = &env_info;
+			let states: Vec<_> = transactions.iter().map(|t| (self.speculative_copy(), t)).collect();
+
+			states.into_par_iter().map(|(mut state, t)| {
+				let executed = if tracing {
+					state.execute(env_info, engine, t, TransactOptions::with_tracing(), false)
+				} else {
+					state.execute(env_info, engine, t, TransactOptions::with_no_tracing(), false)
+				};
+				let log = state.accesses.borrow_mut().take().expect("access log set up before execution; qed");
+
+				Speculation { executed: executed, log: log, state: state }
+			}).collect()
+		};
+
+		let mut written = HashSet::new();
+		let mut repeated = 0;
+		for (t, speculation) in transactions.iter().zip(speculations) {
+			let outcome = match self.merge_speculation(t, speculation, &env_info, &mut written)? {
+				Some(outcome) => outcome,
+				None => {
+					repeated += 1;
+					self.apply_logged(&env_info, engine, t, tracing, &mut written)?
+				},
+			};
+			env_info.gas_used = outcome.receipt.gas_used;
+			outcomes.push(outcome);
+		}
+
+		debug!(target: "state", "Executed {} transactions in parallel, {} of them repeated sequentially", transactions.len(), repeated);
+		Ok(outcomes)
+	}
+
+	// Copy of the state noting accessed accounts.
+	// Unlike `clone` it keeps committed accounts since the shared cache doesn't reflect them.
+	fn speculative_copy(&self) -> State<StateDB> {
+		let cache = self.cache.borrow().iter()
+			.filter(|&(_, entry)| entry.is_dirty() || entry.state == AccountState::Committed)
+			.map(|(address, entry)| (*address, entry.clone_dirty()))
+			.collect();
+
+		State {
+			db: self.db.boxed_clone(),
+			root: self.root,
+			cache: RefCell::new(cache),
+			checkpoints: RefCell::new(Vec::new()),
+			account_start_nonce: self.account_start_nonce,
+			factories: self.factories.clone(),
+			accesses: RefCell::new(Some(AccessLog::default())),
+		}
+	}
+
+	// Merge result of speculative execution into this state.
+	// Returns `None` without changing the state if the speculation is invalid.
+	fn merge_speculation(&mut self, t: &SignedTransaction, speculation: Speculation, env_info: &EnvInfo, written: &mut HashSet<Address>) -> Result<Option<ApplyOutcome>, Error> {
+		let Speculation { executed, log, state } = speculation;
+
+		// errors are reproduced sequentially.
+		let executed = match executed {
+			Ok(e) => e,
+			Err(_) => return Ok(None),
+		};
+
+		// speculation assumed gas used at the beginning of the batch.
+		if env_info.gas_used + t.gas > env_info.gas_limit || log.reads.iter().any(|address| written.contains(address)) {
+			return Ok(None);
+		}
+
+		let mut increments = Vec::new();
+		{
+			let cache = state.cache.borrow();
+			for (address, original) in log.blind_writes.iter().filter(|&(address, _)| !log.reads.contains(address)) {
+				match balance_increment(original.as_ref(), cache.get(address), &self.account_start_nonce) {
+					Some(increment) => increments.push((*address, increment)),
+					None => return Ok(None),
+				}
+			}
+
+			for address in log.writes.iter().filter(|address| log.reads.contains(address)) {
+				if let Some(entry) = cache.get(address).and_then(AccountEntry::clone_if_dirty) {
+					self.cache.get_mut().insert(*address, entry);
+				}
+			}
+		}
+
+		for (address, increment) in increments.into_iter().filter(|&(_, ref increment)| !increment.is_zero()) {
+			self.add_balance(&address, &increment, CleanupMode::NoEmpty)?;
+		}
+
+		written.extend(log.writes);
+
+		let receipt = Receipt::new(None, env_info.gas_used + executed.gas_used, executed.logs);
+		trace!(target: "state", "Transaction receipt: {:?}", receipt);
+
+		Ok(Some(ApplyOutcome {
+			receipt: receipt,
+			output: executed.output,
+			trace: executed.trace,
+			vm_trace: executed.vm_trace,
+		}))
+	}
+
+	// Apply the transaction noting all accounts it writes.
+	fn apply_logged(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool, written: &mut HashSet<Address>) -> ApplyResult {
+		*self.accesses.get_mut() = Some(AccessLog::default());
+		let outcome = self.apply(env_info, engine, t, tracing);
+		if let Some(log) = self.accesses.get_mut().take() {
+			written.extend(log.writes);
+		}
+		outcome
+	}
+}
+
+// Balance added to the account by a blind write.
+// Returns `None` if the account was changed in any other way.
+fn balance_increment(original: Option<&Account>, current: Option<&AccountEntry>, account_start_nonce: &U256) -> Option<U256> {
+	let current = match current.map(|entry| entry.account.as_ref()) {
+		None => return Some(U256::zero()),
+		Some(None) => return if original.is_none() { Some(U256::zero()) } else { None },
+		Some(Some(account)) => account,
+	};
+
+	let unchanged = match original {
+		Some(original) => current.nonce() == original.nonce()
+			&& current.code_hash() == original.code_hash()
+			&& current.storage_changes() == original.storage_changes()
+			&& current.balance() >= original.balance(),
+		// creating an empty account is not an increment.
+		None => current.nonce() == account_start_nonce
+			&& current.code_hash() == KECCAK_EMPTY
+			&& current.storage_changes().is_empty()
+			&& !current.balance().is_zero(),
+	};
+
+	if !unchanged {
+		return None;
+	}
+
+	Some(*current.balance() - original.map_or_else(U256::zero, |original| *original.balance()))
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::keccak;
+	use ethkey::{KeyPair, Secret};
+	use state::CleanupMode;
+	use tests::helpers::*;
+	use transaction::{Transaction, Action, SignedTransaction};
+	use util::Address;
+	use vm::EnvInfo;
+
+	fn secret(seed: &str) -> Secret {
+		keccak(seed).into()
+	}
+
+	fn transfer(secret: &Secret, nonce: u64, to: Address, value: u64) -> SignedTransaction {
+		Transaction {
+			nonce: nonce.into(),
+			gas_price: 1.into(),
+			gas: 21_000.into(),
+			action: Action::Call(to),
+			value: value.into(),
+			data: vec![],
+		}.sign(secret, None)
+	}
+
+	fn env_info() -> EnvInfo {
+		let mut info = EnvInfo::default();
+		info.gas_limit = 1_000_000.into();
+		info.author = Address::from(0xaa);
+		info
+	}
+
+	fn assert_same_as_sequential(info: EnvInfo, transactions: Vec<SignedTransaction>, funded: Vec<Address>) {
+		let engine = TestEngine::new_metropolis();
+		let mut state = get_temp_state();
+		for address in funded {
+			state.add_balance(&address, &1_000_000.into(), CleanupMode::NoEmpty).unwrap();
+		}
+		state.commit().unwrap();
+		let mut sequential = state.clone();
+
+		let outcomes = state.apply_parallel(&info, &engine, &transactions, true).unwrap();
+
+		let mut info = info;
+		let expected: Vec<_> = transactions.iter().map(|t| {
+			let outcome = sequential.apply(&info, &engine, t, true).unwrap();
+			info.gas_used = outcome.receipt.gas_used;
+			outcome
+		}).collect();
+
+		assert_eq!(outcomes.len(), expected.len());
+		for (outcome, expected) in outcomes.iter().zip(expected.iter()) {
+			assert_eq!(outcome.receipt, expected.receipt);
+			assert_eq!(outcome.trace, expected.trace);
+		}
+
+		sequential.commit().unwrap();
+		state.commit().unwrap();
+		assert_eq!(state.root(), sequential.root());
+	}
+
+	#[test]
+	fn should_apply_independent_transactions() {
+		let secrets: Vec<_> = (0..4).map(|i| secret(&i.to_string())).collect();
+		let transactions = secrets.iter().enumerate()
+			.map(|(i, s)| transfer(s, 0, Address::from(0x100 + i as u64), 1000))
+			.collect();
+		let funded = secrets.iter().map(|s| KeyPair::from_secret(s.clone()).unwrap().address()).collect();
+
+		assert_same_as_sequential(env_info(), transactions, funded);
+	}
+
+	#[test]
+	fn should_apply_conflicting_transactions() {
+		let (a, b, author) = (secret("a"), secret("b"), secret("author"));
+		let address = |s: &Secret| KeyPair::from_secret(s.clone()).unwrap().address();
+
+		let mut info = env_info();
+		info.author = address(&author);
+
+		let transactions = vec![
+			transfer(&a, 0, address(&b), 1000),
+			transfer(&a, 1, Address::from(0x100), 1000),
+			transfer(&b, 0, address(&a), 500),
+			transfer(&author, 0, Address::from(0x101), 10),
+			transfer(&b, 1, address(&author), 20),
+		];
+
+		assert_same_as_sequential(info, transactions, vec![address(&a), address(&b)]);
+	}
+
+	#[test]
+	fn should_note_fees_as_blind_write() {
+		let engine = TestEngine::new_metropolis();
+		let s = secret("a");
+		let sender = KeyPair::from_secret(s.clone()).unwrap().address();
+		let mut state = get_temp_state();
+		state.add_balance(&sender, &1_000_000.into(), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+
+		let info = env_info();
+		let mut copy = state.speculative_copy();
+		copy.apply(&info, &engine, &transfer(&s, 0, Address::from(0x100), 1000), false).unwrap();
+		let log = copy.accesses.borrow_mut().take().unwrap();
+
+		assert!(log.reads.contains(&sender));
+		assert!(log.writes.contains(&info.author));
+		assert!(!log.reads.contains(&info.author));
+		assert!(log.blind_writes.contains_key(&info.author));
+	}
+}
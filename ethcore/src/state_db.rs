@@ -16,20 +16,23 @@
 
 use std::collections::{VecDeque, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use lru_cache::LruCache;
 use util::cache::MemoryLruCache;
 use util::journaldb::JournalDB;
 use util::kvdb::KeyValueDB;
 use bigint::hash::H256;
 use util::hashdb::HashDB;
+use util::trie::{Trie, TrieDB, TrieHasher};
 use state::{self, Account};
 use header::BlockNumber;
-use hash::keccak;
+use hash::KECCAK_NULL_RLP;
 use parking_lot::Mutex;
 use util::{Address, DBTransaction, UtilError};
 use bloom_journal::{Bloom, BloomJournal};
 use db::COL_ACCOUNT_BLOOM;
 use byteorder::{LittleEndian, ByteOrder};
+use error::Error;
 
 pub const ACCOUNT_BLOOM_SPACE: usize = 1048576;
 pub const DEFAULT_ACCOUNT_PRESET: usize = 1000000;
@@ -38,6 +41,12 @@ pub const ACCOUNT_BLOOM_HASHCOUNT_KEY: &'static [u8] = b"account_hash_count";
 
 const STATE_CACHE_BLOCKS: usize = 12;
 
+/// Number of accounts from the state trie checked against the bloom on startup.
+const BLOOM_CHECK_SAMPLES: usize = 1000;
+
+/// Number of accounts added to the bloom at once during a rebuild.
+const BLOOM_REBUILD_CHUNK: usize = 10000;
+
 // The percentage of supplied cache size to go to accounts.
 const ACCOUNT_CACHE_RATIO: usize = 90;
 
@@ -63,6 +72,17 @@ struct CacheQueueItem {
 	modified: bool,
 }
 
+/// Outcome of the account bloom integrity check.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BloomStatus {
+	/// Bloom is consistent with the state.
+	Consistent,
+	/// Bloom is not in the database.
+	Missing,
+	/// Some accounts present in the state are not in the bloom.
+	Inconsistent,
+}
+
 #[derive(Debug)]
 /// Accumulates a list of accounts changed in a block.
 struct BlockChanges {
@@ -103,6 +123,12 @@ pub struct StateDB {
 	local_cache: Vec<CacheQueueItem>,
 	/// Shared account bloom. Does not handle chain reorganizations.
 	account_bloom: Arc<Mutex<Bloom>>,
+	/// Whether the bloom may be used to tell missing accounts. Unset while the bloom is rebuilt.
+	bloom_valid: Arc<AtomicBool>,
+	/// Held while the bloom is rebuilt.
+	bloom_rebuild: Arc<Mutex<()>>,
+	/// Hasher keying the state trie, also used for the account bloom.
+	hasher: TrieHasher,
 	cache_size: usize,
 	/// Hash of the block on top of which this instance was created or
 	/// `None` if cache is disabled
@@ -134,6 +160,9 @@ impl StateDB {
 			code_cache: Arc::new(Mutex::new(MemoryLruCache::new(code_cache_size))),
 			local_cache: Vec::new(),
 			account_bloom: Arc::new(Mutex::new(bloom)),
+			bloom_valid: Arc::new(AtomicBool::new(true)),
+			bloom_rebuild: Arc::new(Mutex::new(())),
+			hasher: TrieHasher::default(),
 			cache_size: cache_size,
 			parent_hash: None,
 			commit_hash: None,
@@ -141,6 +170,18 @@ impl StateDB {
 		}
 	}
 
+	/// Use given trie hasher to derive account keys of the bloom.
+	pub fn with_hasher(mut self, hasher: TrieHasher) -> Self {
+		self.hasher = hasher;
+		self
+	}
+
+	/// Never use the accounts bloom, so that missing accounts are always looked up in the state trie.
+	pub fn without_bloom(mut self) -> Self {
+		self.bloom_valid = Arc::new(AtomicBool::new(false));
+		self
+	}
+
 	/// Loads accounts bloom from the database
 	/// This bloom is used to handle request for the non-existant account fast
 	pub fn load_bloom(db: &KeyValueDB) -> Bloom {
@@ -183,6 +224,78 @@ impl StateDB {
 		Ok(())
 	}
 
+	/// Checks the account bloom against a sample of accounts from the state with given root.
+	pub fn check_bloom(&self, state_root: &H256) -> Result<BloomStatus, Error> {
+		if *state_root == KECCAK_NULL_RLP {
+			return Ok(BloomStatus::Consistent);
+		}
+
+		let hash_count = self.db.backing().get(COL_ACCOUNT_BLOOM, ACCOUNT_BLOOM_HASHCOUNT_KEY).map_err(UtilError::from)?;
+		if hash_count.is_none() {
+			return Ok(BloomStatus::Missing);
+		}
+
+		let trie = TrieDB::new(self.db.as_hashdb(), state_root)?;
+		let bloom = self.account_bloom.lock();
+		for item in trie.iter()?.take(BLOOM_CHECK_SAMPLES) {
+			let (account_key, _) = item?;
+			let account_key_hash = H256::from_slice(&account_key);
+			if !bloom.check(&*account_key_hash) {
+				return Ok(BloomStatus::Inconsistent);
+			}
+		}
+		Ok(BloomStatus::Consistent)
+	}
+
+	/// Rebuilds the account bloom from the state with given root and writes it to the database.
+	/// The bloom is not used until the rebuild finishes. Returns the number of accounts added.
+	pub fn rebuild_bloom(&self, state_root: &H256) -> Result<usize, Error> {
+		let _rebuild = self.bloom_rebuild.lock();
+		self.bloom_valid.store(false, AtomicOrdering::SeqCst);
+
+		{
+			let mut bloom = self.account_bloom.lock();
+			*bloom = Bloom::new(ACCOUNT_BLOOM_SPACE, DEFAULT_ACCOUNT_PRESET);
+			let mut batch = DBTransaction::new();
+			let mut key = [0u8; 8];
+			for i in 0..ACCOUNT_BLOOM_SPACE / 8 {
+				LittleEndian::write_u64(&mut key, i as u64);
+				batch.delete(COL_ACCOUNT_BLOOM, &key);
+			}
+			batch.delete(COL_ACCOUNT_BLOOM, ACCOUNT_BLOOM_HASHCOUNT_KEY);
+			self.db.backing().write(batch).map_err(UtilError::from)?;
+		}
+
+		let mut count = 0;
+		let trie = TrieDB::new(self.db.as_hashdb(), state_root)?;
+		let mut chunk = Vec::with_capacity(BLOOM_REBUILD_CHUNK);
+		for item in trie.iter()? {
+			let (account_key, _) = item?;
+			chunk.push(H256::from_slice(&account_key));
+			if chunk.len() == BLOOM_REBUILD_CHUNK {
+				count += chunk.len();
+				let mut bloom = self.account_bloom.lock();
+				for account_key_hash in chunk.drain(..) {
+					bloom.set(&*account_key_hash);
+				}
+			}
+		}
+
+		count += chunk.len();
+		let mut batch = DBTransaction::new();
+		{
+			let mut bloom = self.account_bloom.lock();
+			for account_key_hash in chunk {
+				bloom.set(&*account_key_hash);
+			}
+			Self::commit_bloom(&mut batch, bloom.drain_journal())?;
+		}
+		self.db.backing().write(batch).map_err(UtilError::from)?;
+
+		self.bloom_valid.store(true, AtomicOrdering::SeqCst);
+		Ok(count)
+	}
+
 	/// Journal all recent operations under the given era and ID.
 	pub fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, id: &H256) -> Result<u32, UtilError> {
 		{
@@ -313,6 +426,9 @@ impl StateDB {
 			code_cache: self.code_cache.clone(),
 			local_cache: Vec::new(),
 			account_bloom: self.account_bloom.clone(),
+			bloom_valid: self.bloom_valid.clone(),
+			bloom_rebuild: self.bloom_rebuild.clone(),
+			hasher: self.hasher,
 			cache_size: self.cache_size,
 			parent_hash: None,
 			commit_hash: None,
@@ -328,6 +444,9 @@ impl StateDB {
 			code_cache: self.code_cache.clone(),
 			local_cache: Vec::new(),
 			account_bloom: self.account_bloom.clone(),
+			bloom_valid: self.bloom_valid.clone(),
+			bloom_rebuild: self.bloom_rebuild.clone(),
+			hasher: self.hasher,
 			cache_size: self.cache_size,
 			parent_hash: Some(parent.clone()),
 			commit_hash: None,
@@ -355,6 +474,11 @@ impl StateDB {
 		&*self.db
 	}
 
+	/// Returns mutable reference to underlying `JournalDB`.
+	pub fn journal_db_mut(&mut self) -> &mut JournalDB {
+		&mut *self.db
+	}
+
 	/// Query how much memory is set aside for the accounts cache (in bytes).
 	pub fn cache_size(&self) -> usize {
 		self.cache_size
@@ -445,13 +569,16 @@ impl state::Backend for StateDB {
 	fn note_non_null_account(&self, address: &Address) {
 		trace!(target: "account_bloom", "Note account bloom: {:?}", address);
 		let mut bloom = self.account_bloom.lock();
-		bloom.set(&*keccak(address));
+		bloom.set(&*self.hasher.hash(address));
 	}
 
 	fn is_known_null(&self, address: &Address) -> bool {
 		trace!(target: "account_bloom", "Check account bloom: {:?}", address);
+		if !self.bloom_valid.load(AtomicOrdering::SeqCst) {
+			return false;
+		}
 		let bloom = self.account_bloom.lock();
-		let is_null = !bloom.check(&*keccak(address));
+		let is_null = !bloom.check(&*self.hasher.hash(address));
 		is_null
 	}
 }
@@ -462,8 +589,9 @@ mod tests {
 	use bigint::hash::H256;
 	use util::{Address, DBTransaction};
 	use tests::helpers::*;
-	use state::{Account, Backend};
+	use state::{Account, Backend, CleanupMode};
 	use ethcore_logger::init_log;
+	use super::BloomStatus;
 
 	#[test]
 	fn state_db_smoke() {
@@ -531,4 +659,20 @@ mod tests {
 		let s = state_db.boxed_clone_canon(&h3a);
 		assert!(s.get_cached_account(&address).is_none());
 	}
+
+	#[test]
+	fn should_rebuild_missing_bloom() {
+		let mut state = get_temp_state();
+		let address = Address::random();
+		state.add_balance(&address, &1.into(), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+		let (root, db) = state.drop();
+
+		// bloom has never been written to the database.
+		assert_eq!(db.check_bloom(&root).unwrap(), BloomStatus::Missing);
+		assert_eq!(db.rebuild_bloom(&root).unwrap(), 1);
+		assert_eq!(db.check_bloom(&root).unwrap(), BloomStatus::Consistent);
+		assert!(!db.is_known_null(&address));
+		assert!(db.is_known_null(&Address::random()));
+	}
 }
@@ -17,6 +17,7 @@
 use std::str::FromStr;
 use std::sync::Arc;
 use hash::keccak;
+use rustc_hex::FromHex;
 use io::IoChannel;
 use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
 use state::{self, State, CleanupMode};
@@ -25,6 +26,7 @@ use ethereum;
 use block::IsBlock;
 use tests::helpers::*;
 use types::filter::Filter;
+use types::account_diff::Diff;
 use bigint::prelude::U256;
 use util::*;
 use devtools::*;
@@ -108,6 +110,36 @@ fn imports_good_block() {
 	assert!(!block.into_inner().is_empty());
 }
 
+#[test]
+fn resumes_queued_blocks_after_restart() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+
+	let client = Client::new(
+		ClientConfig::default(),
+		&spec,
+		client_db.clone(),
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+	client.import_block(get_good_dummy_block()).unwrap();
+	drop(client);
+
+	let client = Client::new(
+		ClientConfig::default(),
+		&spec,
+		client_db,
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert_eq!(client.chain_info().best_block_number, 1);
+}
+
 #[test]
 fn query_none_block() {
 	let dir = RandomTempPath::new();
@@ -134,6 +166,22 @@ fn query_bad_block() {
 	assert!(bad_block.is_none());
 }
 
+#[test]
+fn records_bad_blocks() {
+	let client = get_test_client_with_blocks(vec![]);
+	let block = get_bad_state_dummy_block();
+
+	client.import_block_from(block.clone(), "Parity/v1.8.0".into()).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	let bad_blocks = client.bad_blocks();
+	assert_eq!(bad_blocks.len(), 1);
+	assert_eq!(bad_blocks[0].rlp, block);
+	assert_eq!(bad_blocks[0].origin, Some("Parity/v1.8.0".into()));
+	assert!(bad_blocks[0].reason.contains("InvalidStateRoot"));
+}
+
 #[test]
 fn returns_chain_info() {
 	let dummy_block = get_good_dummy_block();
@@ -191,6 +239,75 @@ fn imports_block_sequence() {
 	assert!(!block.into_inner().is_empty());
 }
 
+#[test]
+fn rewinds_chain_head() {
+	let client = generate_dummy_client(6);
+	let retracted_hash = client.block_hash(BlockId::Number(6)).unwrap();
+
+	let retracted = client.rewind_to(BlockId::Number(4)).unwrap();
+
+	assert_eq!(retracted.len(), 2);
+	assert_eq!(retracted[0], retracted_hash);
+	assert_eq!(client.chain_info().best_block_number, 4);
+	assert!(client.block_header(BlockId::Number(5)).is_none());
+	assert!(client.rewind_to(BlockId::Number(6)).unwrap().is_empty());
+}
+
+#[test]
+fn invalidates_canonical_block() {
+	let client = generate_dummy_client(6);
+	let bad = client.block_hash(BlockId::Number(3)).unwrap();
+
+	assert_eq!(client.invalidate_block(bad).unwrap().len(), 4);
+	assert_eq!(client.chain_info().best_block_number, 2);
+	assert_eq!(client.bad_blocks()[0].hash, bad);
+	assert!(client.invalidate_block(client.block_hash(BlockId::Number(0)).unwrap()).is_err());
+}
+
+#[test]
+fn halts_import_on_deep_reorg() {
+	use header::Header;
+
+	let test_spec = get_test_spec();
+	let mut config = ClientConfig::default();
+	config.max_reorg_depth = Some(2);
+	let client = Client::new(
+		config,
+		&test_spec,
+		Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0))),
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+	let genesis = test_spec.genesis_header().hash();
+
+	for block in get_good_dummy_block_fork_seq(1, 3, &genesis) {
+		client.import_block(block).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(client.chain_info().best_block_number, 4);
+
+	// heavier block replacing the whole chain.
+	let mut header = Header::new();
+	header.set_gas_limit(test_spec.engine.params().min_gas_limit);
+	header.set_difficulty(U256::from(100) * U256([0, 1, 0, 0]));
+	header.set_timestamp(5);
+	header.set_number(1);
+	header.set_parent_hash(genesis);
+	header.set_state_root(test_spec.genesis_header().state_root().clone());
+	let head = header.hash();
+
+	client.import_block(create_test_block(&header)).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(client.chain_info().best_block_number, 4);
+
+	assert!(client.accept_reorg(genesis).is_err());
+	client.accept_reorg(head).unwrap();
+	assert_eq!(client.chain_info().best_block_hash, head);
+	assert!(client.accept_reorg(head).is_err());
+}
+
 #[test]
 fn can_collect_garbage() {
 	let client = generate_dummy_client(100);
@@ -224,6 +341,22 @@ fn empty_gas_price_histogram() {
 	assert!(client.gas_price_corpus(20).histogram(5).is_none());
 }
 
+#[test]
+fn tracks_gas_prices_of_recent_blocks() {
+	let client = generate_dummy_client_with_data(3, 1, slice_into![1, 2, 3]);
+	assert_eq!(client.gas_price_median(), Some(2.into()));
+
+	let latest = client.block_gas_prices(BlockId::Latest).unwrap();
+	assert_eq!(latest.number, 3);
+	assert_eq!(latest.hash, client.chain_info().best_block_hash);
+	assert_eq!(latest.prices().to_vec(), vec![U256::from(3)]);
+	assert_eq!(latest.percentile(50.0), 3.into());
+	assert!(client.block_gas_prices(BlockId::Number(4)).is_none());
+
+	client.rewind_to(BlockId::Number(1)).unwrap();
+	assert_eq!(client.gas_price_median(), Some(1.into()));
+}
+
 #[test]
 fn corpus_is_sorted() {
 	let client = generate_dummy_client_with_data(2, 1, slice_into![U256::from_str("11426908979").unwrap(), U256::from_str("50426908979").unwrap()]);
@@ -254,7 +387,7 @@ fn can_mine() {
 	let dummy_blocks = get_good_dummy_block_seq(2);
 	let client = get_test_client_with_blocks(vec![dummy_blocks[0].clone()]);
 
-	let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close();
+	let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![], &Default::default()).close();
 
 	assert_eq!(*b.block().header().parent_hash(), BlockView::new(&dummy_blocks[0]).header_view().hash());
 }
@@ -279,7 +412,7 @@ fn change_history_size() {
 		).unwrap();
 
 		for _ in 0..20 {
-			let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+			let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![], &Default::default());
 			b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
 			b.block_mut().fields_mut().state.commit().unwrap();
 			let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
@@ -338,7 +471,7 @@ fn transaction_proof() {
 	let address = Address::random();
 	let test_spec = Spec::new_test();
 	for _ in 0..20 {
-		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![], &Default::default());
 		b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
 		b.block_mut().fields_mut().state.commit().unwrap();
 		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
@@ -368,3 +501,109 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+#[test]
+fn returns_state_diff_of_block() {
+	let client = generate_dummy_client_with_data(2, 2, slice_into![1]);
+	let sender = KeyPair::from_secret_slice(&keccak("")).unwrap().address();
+
+	let diff = client.block_state_diff(BlockId::Number(2)).unwrap();
+
+	assert_eq!(diff.raw[&sender].nonce, Diff::Changed(2.into(), 4.into()));
+	assert!(client.block_state_diff(BlockId::Number(0)).is_err());
+}
+
+#[test]
+fn block_witness_suffices_to_reexecute_block() {
+	use account_db::Factory as AccountDBFactory;
+	use block::enact;
+	use factory::Factories;
+	use state_db::StateDB;
+	use transaction::SignedTransaction;
+	use util::journaldb::{self, Algorithm};
+
+	let client = generate_dummy_client_with_data(2, 2, slice_into![1]);
+	let witness = client.block_witness(BlockId::Number(2)).unwrap();
+	assert!(client.block_witness(BlockId::Number(0)).is_err());
+
+	let block = client.block(BlockId::Number(2)).unwrap();
+	let header = block.decode_header();
+	let parent = client.block_header(BlockId::Number(1)).unwrap().decode();
+	let transactions: Vec<_> = block.transactions().into_iter()
+		.map(|t| SignedTransaction::new(t).unwrap())
+		.collect();
+
+	// a database holding nothing but the witness, keyed by hashes.
+	let mut jdb = journaldb::new(Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap())), Algorithm::Archive, ::db::COL_STATE);
+	for node in &witness {
+		jdb.insert(node);
+	}
+	let mut factories = Factories::default();
+	factories.accountdb = AccountDBFactory::Plain;
+
+	let enacted = enact(
+		&header,
+		&transactions,
+		&block.uncles(),
+		client.engine(),
+		false,
+		StateDB::new(jdb, 0).without_bloom(),
+		&parent,
+		Arc::new(vec![parent.hash()]),
+		factories,
+		false,
+		false,
+	).unwrap();
+
+	assert_eq!(enacted.header().state_root(), header.state_root());
+}
+
+#[test]
+fn estimates_gas_including_refunds() {
+	let client = generate_dummy_client(0);
+	let sender = Address::from(5);
+	// stores a value and clears it again, which is refunded.
+	let transaction = |gas: u64| Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: gas.into(),
+		action: Action::Create,
+		value: 0.into(),
+		data: "60016000556000600055".from_hex().unwrap(),
+	}.fake_sign(sender);
+
+	let estimate = client.estimate_gas_breakdown(&transaction(0), BlockId::Latest, None).unwrap();
+	assert!(estimate.refund > 0.into());
+	assert_eq!(estimate.gas, estimate.intrinsic + estimate.execution);
+	assert_eq!(client.estimate_gas(&transaction(0), BlockId::Latest, None).unwrap(), estimate.gas);
+
+	let gas = estimate.gas.low_u64();
+	let analytics = Default::default();
+	assert!(client.call(&transaction(gas), analytics, BlockId::Latest).unwrap().exception.is_none());
+	assert!(client.call(&transaction(gas - 1), analytics, BlockId::Latest).map_or(true, |executed| executed.exception.is_some()));
+}
+
+#[test]
+fn aborts_calls_running_past_timeout() {
+	use std::time::Duration;
+	use client::CallAnalytics;
+	use executed::CallError;
+
+	let client = generate_dummy_client(0);
+	// jumpdest, push 0, jump - loops until out of gas.
+	let transaction = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 1_000_000.into(),
+		action: Action::Create,
+		value: 0.into(),
+		data: "5b600056".from_hex().unwrap(),
+	}.fake_sign(Address::from(5));
+
+	let analytics = CallAnalytics { timeout: Some(Duration::from_secs(0)), ..Default::default() };
+	assert_eq!(client.call(&transaction, analytics, BlockId::Latest).unwrap_err(), CallError::Timeout);
+	assert_eq!(client.estimate_gas(&transaction, BlockId::Latest, Some(Duration::from_secs(0))).unwrap_err(), CallError::Timeout);
+
+	// without a timeout the call just runs out of gas.
+	assert!(client.call(&transaction, Default::default(), BlockId::Latest).unwrap().exception.is_some());
+}
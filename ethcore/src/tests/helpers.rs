@@ -76,11 +76,11 @@ impl Engine for TestEngine {
 		"TestEngine"
 	}
 
-	fn params(&self) -> &CommonParams {
+	fn params(&self) -> Arc<CommonParams> {
 		self.engine.params()
 	}
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
+	fn builtins(&self) -> Arc<BTreeMap<Address, Builtin>> {
 		self.engine.builtins()
 	}
 
@@ -426,5 +426,7 @@ pub fn get_default_ethash_params() -> EthashParams {
 		max_gas_limit: U256::max_value(),
 		min_gas_price_transition: u64::max_value(),
 		min_gas_price: U256::zero(),
+		progpow_transition: u64::max_value(),
+		cpu_sealing: false,
 	}
 }
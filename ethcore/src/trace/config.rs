@@ -29,6 +29,8 @@ pub struct Config {
 	pub pref_cache_size: usize,
 	/// Max cache-size.
 	pub max_cache_size: usize,
+	/// Number of recent blocks to keep traces of. Keeps everything if `None`.
+	pub retention: Option<u64>,
 }
 
 impl Default for Config {
@@ -41,6 +43,7 @@ impl Default for Config {
 			},
 			pref_cache_size: 15 * 1024 * 1024,
 			max_cache_size: 20 * 1024 * 1024,
+			retention: None,
 		}
 	}
 }
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Trace database.
+use std::cmp;
 use std::ops::Deref;
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
@@ -24,6 +25,7 @@ use heapsize::HeapSizeOf;
 use bigint::hash::{H256, H264};
 use util::{KeyValueDB, DBTransaction};
 use parking_lot::RwLock;
+use rlp::{encode, decode};
 use header::BlockNumber;
 use trace::{LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportRequest, DatabaseExtras};
 use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
@@ -114,6 +116,8 @@ pub struct TraceDB<T> where T: DatabaseExtras {
 	bloom_config: BloomConfig,
 	// tracing enabled
 	enabled: bool,
+	// first block which traces weren't pruned
+	traces_start: RwLock<BlockNumber>,
 	// extras
 	extras: Arc<T>,
 }
@@ -137,6 +141,10 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		batch.put(db::COL_TRACE, b"version", TRACE_DB_VER);
 		tracesdb.write(batch).expect("failed to update version");
 
+		let traces_start = tracesdb.get(db::COL_TRACE, b"traces_start")
+			.expect("Low level database error. Some issue with disk?")
+			.map_or(0, |raw| decode(&raw));
+
 		TraceDB {
 			traces: RwLock::new(HashMap::new()),
 			blooms: RwLock::new(HashMap::new()),
@@ -144,10 +152,64 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			tracesdb: tracesdb,
 			bloom_config: config.blooms,
 			enabled: config.enabled,
+			traces_start: RwLock::new(traces_start),
 			extras: extras,
 		}
 	}
 
+	/// Returns the number of the first block whose traces haven't been pruned.
+	/// Traces of the genesis block are always kept.
+	pub fn traces_start(&self) -> BlockNumber {
+		*self.traces_start.read()
+	}
+
+	/// Removes traces of canonical blocks before `until`, at most `max_blocks` of them.
+	/// Returns the new traces start.
+	pub fn prune(&self, batch: &mut DBTransaction, until: BlockNumber, max_blocks: u64) -> BlockNumber {
+		let start = cmp::max(self.traces_start(), 1);
+		let end = cmp::min(until, start.saturating_add(max_blocks));
+		if end <= start {
+			return self.traces_start();
+		}
+
+		let hashes: Vec<H256> = (start..end).filter_map(|number| self.extras.block_hash(number)).collect();
+		{
+			let mut traces = self.traces.write();
+			for hash in hashes {
+				batch.delete(db::COL_TRACE, &<H256 as Key<FlatBlockTraces>>::key(&hash));
+				traces.remove(&hash);
+			}
+		}
+
+		batch.put(db::COL_TRACE, b"traces_start", &encode(&end));
+		*self.traces_start.write() = end;
+		end
+	}
+
+	/// Removes traces of canonical blocks retracted by rewinding the chain to block `best`.
+	pub fn rewind(&self, batch: &mut DBTransaction, best: BlockNumber, retracted: &[H256]) {
+		if !self.tracing_enabled() || retracted.is_empty() {
+			return;
+		}
+
+		{
+			let mut traces = self.traces.write();
+			for hash in retracted {
+				batch.delete(db::COL_TRACE, &<H256 as Key<FlatBlockTraces>>::key(hash));
+				traces.remove(hash);
+			}
+		}
+
+		let range_start = best as Number + 1;
+		let chain = BloomGroupChain::new(self.bloom_config, self);
+		let trace_blooms = chain.replace(&(range_start..range_start + retracted.len()), Vec::new());
+		let blooms_to_insert = trace_blooms.into_iter()
+			.map(|p| (From::from(p.0), From::from(p.1)))
+			.collect::<HashMap<TraceGroupPosition, blooms::BloomGroup>>();
+		let mut blooms = self.blooms.write();
+		batch.extend_with_cache(db::COL_TRACE, &mut *blooms, blooms_to_insert, CacheUpdatePolicy::Remove);
+	}
+
 	fn cache_size(&self) -> usize {
 		let traces = self.traces.read().heap_size_of_children();
 		let blooms = self.blooms.read().heap_size_of_children();
@@ -396,7 +458,10 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
 		let chain = BloomGroupChain::new(self.bloom_config, self);
 		let numbers = chain.filter(filter);
+		let start = self.traces_start();
 		numbers.into_iter()
+			// blooms of pruned blocks are kept, but their traces are gone.
+			.filter(|n| *n as BlockNumber >= start)
 			.flat_map(|n| {
 				let number = n as BlockNumber;
 				let hash = self.extras.block_hash(number)
@@ -719,4 +784,88 @@ mod tests {
 
 		assert_eq!(traces.len(), 0);
 	}
+
+	#[test]
+	fn prune_traces() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from(0xa1);
+		let block_2 = H256::from(0xa2);
+		let tx_1 = H256::from(0xff);
+		let tx_2 = H256::from(0xaf);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::default());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2.clone());
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+		extras.transaction_hashes.insert(2, vec![tx_2.clone()]);
+
+		{
+			let tracedb = TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone()));
+			for (number, hash) in vec![(1, block_1.clone()), (2, block_2.clone())] {
+				let mut batch = DBTransaction::new();
+				tracedb.import(&mut batch, create_simple_import_request(number, hash));
+				db.write(batch).unwrap();
+			}
+
+			let mut batch = DBTransaction::new();
+			assert_eq!(tracedb.prune(&mut batch, 2, 10), 2);
+			db.write(batch).unwrap();
+			assert_eq!(tracedb.block_traces(1), None);
+		}
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+		assert_eq!(tracedb.traces_start(), 2);
+		assert!(tracedb.block_traces(0).is_some(), "Genesis trace should be always present.");
+		assert_eq!(tracedb.block_traces(1), None);
+		assert_eq!(tracedb.block_traces(2).unwrap(), vec![create_simple_localized_trace(2, block_2.clone(), tx_2.clone())]);
+
+		let filter = Filter {
+			range: (1..2),
+			from_address: AddressesFilter::from(vec![Address::from(1)]),
+			to_address: AddressesFilter::from(vec![]),
+		};
+		assert_eq!(tracedb.filter(&filter), vec![create_simple_localized_trace(2, block_2, tx_2)]);
+	}
+
+	#[test]
+	fn rewind_traces() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from(0xa1);
+		let block_2 = H256::from(0xa2);
+		let tx_1 = H256::from(0xff);
+		let tx_2 = H256::from(0xaf);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::default());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2.clone());
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+		extras.transaction_hashes.insert(2, vec![tx_2.clone()]);
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+		for (number, hash) in vec![(1, block_1.clone()), (2, block_2.clone())] {
+			let mut batch = DBTransaction::new();
+			tracedb.import(&mut batch, create_simple_import_request(number, hash));
+			db.write(batch).unwrap();
+		}
+
+		let mut batch = DBTransaction::new();
+		tracedb.rewind(&mut batch, 1, &[block_2.clone()]);
+		db.write(batch).unwrap();
+
+		assert_eq!(tracedb.block_traces(2), None);
+		assert_eq!(tracedb.block_traces(1).unwrap(), vec![create_simple_localized_trace(1, block_1.clone(), tx_1.clone())]);
+
+		let filter = Filter {
+			range: (1..2),
+			from_address: AddressesFilter::from(vec![Address::from(1)]),
+			to_address: AddressesFilter::from(vec![]),
+		};
+		assert_eq!(tracedb.filter(&filter), vec![create_simple_localized_trace(1, block_1, tx_1)]);
+	}
 }
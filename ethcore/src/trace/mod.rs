@@ -22,18 +22,20 @@ mod db;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod step_tracer;
 mod types;
 
 pub use self::config::Config;
 pub use self::db::TraceDB;
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
+pub use self::step_tracer::StepTracer;
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
 
 pub use self::types::{filter, flat, localized, trace};
 pub use self::types::error::Error as TraceError;
-pub use self::types::trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, RewardType};
+pub use self::types::trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, RewardType, VMStep, StepTrace};
 pub use self::types::flat::{FlatTrace, FlatTransactionTraces, FlatBlockTraces};
 pub use self::types::filter::{Filter, AddressesFilter};
 
@@ -115,6 +117,9 @@ pub trait VMTracer: Send {
 
 	/// Consumes self and returns the VM trace.
 	fn drain(self) -> Option<VMTrace>;
+
+	/// Takes the recorded execution steps. Only step tracer records them.
+	fn take_steps(&mut self) -> Option<StepTrace> { None }
 }
 
 /// `DbExtras` provides an interface to query extra data which is not stored in tracesdb,
@@ -0,0 +1,231 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracer recording a snapshot of the VM at each executed operation.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::mem;
+use bigint::prelude::U256;
+use util::Bytes;
+use evm::INSTRUCTIONS;
+use types::call_analytics::StepTracing;
+use trace::VMTracer;
+use trace::trace::{VMTrace, VMStep, StepTrace, StorageDiff};
+
+/// VM tracer recording operations of all calls/creates in execution order.
+///
+/// Stack, memory and storage are reconstructed from the operation diffs and captured
+/// only up to the configured limits; the number of steps is bounded as well.
+pub struct StepTracer {
+	config: StepTracing,
+	depth: usize,
+	// number of steps this tracer (including its subtracers) may still record.
+	budget: usize,
+	steps: Vec<VMStep>,
+	truncated: bool,
+	// index of the step being executed and the number of stack items it takes.
+	current: Option<(usize, usize)>,
+	gas_left: Option<U256>,
+	stack: Vec<U256>,
+	memory: Bytes,
+	storage: BTreeMap<U256, U256>,
+}
+
+impl StepTracer {
+	/// Create a new top-level instance.
+	pub fn new(config: StepTracing) -> Self {
+		StepTracer {
+			config: config,
+			depth: 0,
+			budget: config.max_steps,
+			steps: Vec::new(),
+			truncated: false,
+			current: None,
+			gas_left: None,
+			stack: Vec::new(),
+			memory: Vec::new(),
+			storage: BTreeMap::new(),
+		}
+	}
+
+	fn snapshot(&self, pc: usize, instruction: u8, gas_cost: U256) -> VMStep {
+		let mut truncated = false;
+
+		let stack = self.config.stack.map(|limit| {
+			let from = self.stack.len().saturating_sub(limit);
+			truncated |= from > 0;
+			self.stack[from..].to_vec()
+		});
+		let memory = self.config.memory.map(|limit| {
+			truncated |= self.memory.len() > limit;
+			self.memory[..cmp::min(limit, self.memory.len())].to_vec()
+		});
+		let storage = self.config.storage.map(|limit| {
+			truncated |= self.storage.len() > limit;
+			self.storage.iter()
+				.take(limit)
+				.map(|(location, value)| StorageDiff { location: *location, value: *value })
+				.collect()
+		});
+
+		VMStep {
+			pc: pc,
+			instruction: instruction,
+			gas: self.gas_left.unwrap_or_else(U256::zero),
+			gas_cost: gas_cost,
+			depth: self.depth,
+			stack: stack,
+			memory: memory,
+			storage: storage,
+			truncated: truncated,
+		}
+	}
+}
+
+impl VMTracer for StepTracer {
+	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8) -> bool {
+		if self.budget == 0 {
+			self.truncated = true;
+			return false;
+		}
+		true
+	}
+
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: U256) {
+		let step = self.snapshot(pc, instruction, gas_cost);
+		self.current = Some((self.steps.len(), INSTRUCTIONS[instruction as usize].args));
+		self.steps.push(step);
+		self.budget -= 1;
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		let (index, args) = self.current.take().expect("trace_executed is always called after a trace_prepare_execute; qed");
+		if self.gas_left.is_none() {
+			// gas available to the first operation of a call/create isn't known before it's executed.
+			let step = &mut self.steps[index];
+			step.gas = gas_used + step.gas_cost;
+		}
+		self.gas_left = Some(gas_used);
+
+		if self.config.stack.is_some() {
+			let len = self.stack.len();
+			self.stack.truncate(len.saturating_sub(args));
+			self.stack.extend_from_slice(stack_push);
+		}
+
+		if let (Some(_), Some((offset, data))) = (self.config.memory, mem_diff) {
+			if self.memory.len() < offset + data.len() {
+				self.memory.resize(offset + data.len(), 0);
+			}
+			self.memory[offset..offset + data.len()].copy_from_slice(data);
+		}
+
+		if let (Some(_), Some((location, value))) = (self.config.storage, store_diff) {
+			self.storage.insert(location, value);
+		}
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		StepTracer {
+			depth: self.depth + 1,
+			budget: self.budget,
+			..StepTracer::new(self.config)
+		}
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.budget = sub.budget;
+		self.truncated |= sub.truncated;
+		self.steps.extend(sub.steps);
+	}
+
+	fn drain(self) -> Option<VMTrace> { None }
+
+	fn take_steps(&mut self) -> Option<StepTrace> {
+		Some(StepTrace {
+			steps: mem::replace(&mut self.steps, Vec::new()),
+			truncated: self.truncated,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use types::call_analytics::StepTracing;
+	use trace::VMTracer;
+	use trace::trace::StorageDiff;
+	use super::StepTracer;
+
+	fn config(max_steps: usize, limit: usize) -> StepTracing {
+		StepTracing {
+			max_steps: max_steps,
+			stack: Some(limit),
+			memory: Some(limit),
+			storage: Some(limit),
+		}
+	}
+
+	// PUSH1 1, PUSH1 2, SSTORE (0x600160025500)
+	fn run(tracer: &mut StepTracer) {
+		let mut sub = tracer.prepare_subtrace(&[]);
+		for &(pc, push) in &[(0, 1), (2, 2)] {
+			if sub.trace_next_instruction(pc, 0x60) {
+				sub.trace_prepare_execute(pc, 0x60, 3.into());
+				sub.trace_executed(100.into(), &[push.into()], None, None);
+			}
+		}
+		if sub.trace_next_instruction(4, 0x55) {
+			sub.trace_prepare_execute(4, 0x55, 20000.into());
+			sub.trace_executed(50.into(), &[], None, Some((2.into(), 1.into())));
+		}
+		if sub.trace_next_instruction(5, 0x00) {
+			sub.trace_prepare_execute(5, 0x00, 0.into());
+			sub.trace_executed(50.into(), &[], None, None);
+		}
+		tracer.done_subtrace(sub);
+	}
+
+	#[test]
+	fn should_record_steps() {
+		let mut tracer = StepTracer::new(config(100, 100));
+		run(&mut tracer);
+		let trace = tracer.take_steps().unwrap();
+
+		assert!(!trace.truncated);
+		assert_eq!(trace.steps.len(), 4);
+		assert_eq!(trace.steps[0].depth, 1);
+		assert_eq!(trace.steps[0].gas, 103.into());
+		assert_eq!(trace.steps[2].pc, 4);
+		assert_eq!(trace.steps[2].stack, Some(vec![1.into(), 2.into()]));
+		assert_eq!(trace.steps[3].stack, Some(vec![]));
+		assert_eq!(trace.steps[3].gas, 50.into());
+		assert_eq!(trace.steps[3].storage, Some(vec![StorageDiff { location: 2.into(), value: 1.into() }]));
+	}
+
+	#[test]
+	fn should_truncate_captures() {
+		let mut tracer = StepTracer::new(config(3, 1));
+		run(&mut tracer);
+		let trace = tracer.take_steps().unwrap();
+
+		assert!(trace.truncated);
+		assert_eq!(trace.steps.len(), 3);
+		assert_eq!(trace.steps[2].stack, Some(vec![2.into()]));
+		assert!(trace.steps[2].truncated);
+		assert!(!trace.steps[1].truncated);
+	}
+}
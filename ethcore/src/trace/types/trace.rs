@@ -425,3 +425,35 @@ pub struct VMTrace {
 	/// Thre is a 1:1 correspondance between these and a CALL/CREATE/CALLCODE/DELEGATECALL instruction.
 	pub subs: Vec<VMTrace>,
 }
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// A snapshot of the VM taken before executing a single operation.
+pub struct VMStep {
+	/// The program counter.
+	pub pc: usize,
+	/// The instruction to execute.
+	pub instruction: u8,
+	/// The gas available before the operation.
+	pub gas: U256,
+	/// The gas cost of the operation.
+	pub gas_cost: U256,
+	/// Call depth, starting at 1 for the outermost call/create.
+	pub depth: usize,
+	/// Topmost stack items (top last), if captured.
+	pub stack: Option<Vec<U256>>,
+	/// Memory contents, if captured.
+	pub memory: Option<Bytes>,
+	/// Storage values written by the current call/create so far, if captured.
+	pub storage: Option<Vec<StorageDiff>>,
+	/// True if any of the captured snapshots has been cut at its limit.
+	pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Operations executed by a transaction, in execution order across all calls/creates.
+pub struct StepTrace {
+	/// The recorded steps.
+	pub steps: Vec<VMStep>,
+	/// True if recording stopped at the step limit.
+	pub truncated: bool,
+}
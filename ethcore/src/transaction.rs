@@ -16,6 +16,7 @@
 
 //! Transaction data structure.
 
+use std::fmt;
 use std::ops::Deref;
 use rlp::*;
 use hash::keccak;
@@ -271,8 +272,147 @@ impl Deref for UnverifiedTransaction {
 	}
 }
 
+/// Highest type byte of EIP-2718 typed transaction envelope.
+/// Legacy transactions are RLP lists and always start with a byte above `0xc0`.
+pub const MAX_TRANSACTION_TYPE: u8 = 0x7f;
+
+/// Errors concerning decoding of raw transaction.
+#[derive(Debug, PartialEq)]
+pub enum TransactionDecodeError {
+	/// Transaction (or typed transaction payload) is not valid RLP.
+	Rlp(DecoderError),
+	/// Typed transaction envelope of a type which is not supported.
+	UnsupportedType(u8),
+	/// Access list of typed transaction is not a list of addresses with storage keys.
+	InvalidAccessList,
+	/// Signature y-parity of typed transaction is neither 0 nor 1.
+	InvalidYParity(u8),
+	/// Typed transaction is signed for a different chain.
+	InvalidChainId {
+		/// Chain id of the network.
+		expected: u64,
+		/// Chain id of the transaction.
+		got: u64,
+	},
+}
+
+impl From<DecoderError> for TransactionDecodeError {
+	fn from(err: DecoderError) -> Self {
+		TransactionDecodeError::Rlp(err)
+	}
+}
+
+impl fmt::Display for TransactionDecodeError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			TransactionDecodeError::Rlp(ref err) => write!(f, "Invalid RLP: {:?}", err),
+			TransactionDecodeError::UnsupportedType(tx_type) => match typed_transaction_name(tx_type) {
+				Some(name) => write!(f, "{} transactions (type 0x{:02x}) are not supported", name, tx_type),
+				None => write!(f, "Unknown transaction type 0x{:02x}", tx_type),
+			},
+			TransactionDecodeError::InvalidAccessList => write!(f, "Access list must be a list of [address, [storage keys]] pairs"),
+			TransactionDecodeError::InvalidYParity(y_parity) => write!(f, "Invalid signature y-parity {} (expected 0 or 1)", y_parity),
+			TransactionDecodeError::InvalidChainId { expected, got } => write!(f, "Invalid chain id {} (expected {})", got, expected),
+		}
+	}
+}
+
+/// Returns the name of known typed transaction.
+fn typed_transaction_name(tx_type: u8) -> Option<&'static str> {
+	match tx_type {
+		0x01 => Some("EIP-2930 access list"),
+		0x02 => Some("EIP-1559 dynamic fee"),
+		_ => None,
+	}
+}
+
+/// Returns the number of RLP items in the payload of known typed transaction.
+fn typed_transaction_fields(tx_type: u8) -> Option<usize> {
+	match tx_type {
+		0x01 => Some(11),
+		0x02 => Some(12),
+		_ => None,
+	}
+}
+
+/// Validates access list of typed transaction: `[[address, [storage_key, ...]], ...]`.
+fn validate_access_list(rlp: &UntrustedRlp) -> Result<(), TransactionDecodeError> {
+	if !rlp.is_list() {
+		return Err(TransactionDecodeError::InvalidAccessList);
+	}
+	for item in rlp.iter() {
+		if !item.is_list() || item.item_count()? != 2 {
+			return Err(TransactionDecodeError::InvalidAccessList);
+		}
+		item.val_at::<Address>(0).map_err(|_| TransactionDecodeError::InvalidAccessList)?;
+		let keys = item.at(1)?;
+		if !keys.is_list() {
+			return Err(TransactionDecodeError::InvalidAccessList);
+		}
+		keys.as_list::<H256>().map_err(|_| TransactionDecodeError::InvalidAccessList)?;
+	}
+	Ok(())
+}
+
+/// Validates payload of known typed transaction. Both types end with the same fields:
+/// `[chain_id, nonce, <gas prices>, gas, to, value, data, access_list, y_parity, r, s]`.
+fn validate_typed_payload(rlp: &UntrustedRlp, fields: usize, chain_id: Option<u64>) -> Result<(), TransactionDecodeError> {
+	let tx_chain_id: u64 = rlp.val_at(0)?;
+	if let Some(expected) = chain_id {
+		if tx_chain_id != expected {
+			return Err(TransactionDecodeError::InvalidChainId { expected: expected, got: tx_chain_id });
+		}
+	}
+	// nonce, gas prices and gas
+	for i in 1..fields - 7 {
+		rlp.val_at::<U256>(i)?;
+	}
+	rlp.val_at::<Action>(fields - 7)?;
+	rlp.val_at::<U256>(fields - 6)?;
+	rlp.val_at::<Bytes>(fields - 5)?;
+	validate_access_list(&rlp.at(fields - 4)?)?;
+	let y_parity: u8 = rlp.val_at(fields - 3)?;
+	if y_parity > 1 {
+		return Err(TransactionDecodeError::InvalidYParity(y_parity));
+	}
+	rlp.val_at::<U256>(fields - 2)?;
+	rlp.val_at::<U256>(fields - 1)?;
+	Ok(())
+}
+
+/// Validates typed transaction envelope (EIP-2718). The type byte is followed by type-specific payload.
+/// The payload is signed for `chain_id` if given.
+fn decode_typed_transaction(tx_type: u8, payload: &[u8], chain_id: Option<u64>) -> Result<UnverifiedTransaction, TransactionDecodeError> {
+	if let Some(fields) = typed_transaction_fields(tx_type) {
+		let rlp = UntrustedRlp::new(payload);
+		if !rlp.is_list() {
+			return Err(DecoderError::RlpExpectedToBeList.into());
+		}
+		if rlp.item_count()? != fields {
+			return Err(DecoderError::RlpIncorrectListLen.into());
+		}
+		validate_typed_payload(&rlp, fields, chain_id)?;
+	}
+
+	// none of the typed transactions can be executed yet
+	Err(TransactionDecodeError::UnsupportedType(tx_type))
+}
+
 impl Decodable for UnverifiedTransaction {
 	fn decode(d: &UntrustedRlp) -> Result<Self, DecoderError> {
+		// typed transaction envelopes are embedded in lists as byte strings
+		if d.is_data() {
+			let envelope = d.data()?;
+			return match envelope.first() {
+				Some(&tx_type) if tx_type <= MAX_TRANSACTION_TYPE => decode_typed_transaction(tx_type, &envelope[1..], None).map_err(|err| match err {
+					TransactionDecodeError::Rlp(err) => err,
+					TransactionDecodeError::UnsupportedType(_) => DecoderError::Custom("Unsupported transaction type"),
+					_ => DecoderError::Custom("Invalid typed transaction"),
+				}),
+				_ => Err(DecoderError::RlpExpectedToBeList),
+			};
+		}
+
 		if d.item_count()? != 9 {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
@@ -299,6 +439,22 @@ impl Encodable for UnverifiedTransaction {
 }
 
 impl UnverifiedTransaction {
+	/// Decodes raw transaction bytes, either legacy RLP transaction
+	/// or EIP-2718 typed transaction envelope. Typed transactions must be signed for `chain_id` if given.
+	pub fn decode_raw(raw: &[u8], chain_id: Option<u64>) -> Result<Self, TransactionDecodeError> {
+		match raw.first() {
+			None => Err(DecoderError::RlpIsTooShort.into()),
+			Some(&tx_type) if tx_type <= MAX_TRANSACTION_TYPE => decode_typed_transaction(tx_type, &raw[1..], chain_id),
+			Some(_) => {
+				let rlp = UntrustedRlp::new(raw);
+				if !rlp.is_list() {
+					return Err(DecoderError::RlpExpectedToBeList.into());
+				}
+				rlp.as_val().map_err(Into::into)
+			},
+		}
+	}
+
 	/// Used to compute hash of created transactions
 	fn compute_hash(mut self) -> UnverifiedTransaction {
 		let hash = keccak(&*self.rlp_bytes());
@@ -550,6 +706,81 @@ mod tests {
 	use bigint::prelude::U256;
 	use hash::keccak;
 
+	#[test]
+	fn should_decode_legacy_raw_transaction() {
+		let raw = ::rustc_hex::FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap();
+		let t = UnverifiedTransaction::decode_raw(&raw, Some(1)).unwrap();
+		assert_eq!(t, decode(&raw));
+	}
+
+	fn typed_transaction<F>(tx_type: u8, fields: usize, f: F) -> Vec<u8> where F: Fn(usize, &mut RlpStream) {
+		let mut payload = RlpStream::new_list(fields);
+		for i in 0..fields {
+			f(i, &mut payload);
+		}
+		let mut raw = vec![tx_type];
+		raw.extend_from_slice(&payload.out());
+		raw
+	}
+
+	fn access_list_transaction<F>(f: F) -> Vec<u8> where F: Fn(usize, &mut RlpStream) -> bool {
+		typed_transaction(0x01, 11, |i, s| if !f(i, s) {
+			match i {
+				0 => { s.append(&1u64); },
+				4 => { s.append(&Address::from(0x42)); },
+				7 => {
+					s.begin_list(1).begin_list(2).append(&Address::from(0x42)).begin_list(1).append(&H256::from(1));
+				},
+				_ => { s.append_empty_data(); },
+			}
+		})
+	}
+
+	#[test]
+	fn should_reject_typed_raw_transactions() {
+		let raw = access_list_transaction(|_, _| false);
+		assert_eq!(UnverifiedTransaction::decode_raw(&raw, Some(1)), Err(TransactionDecodeError::UnsupportedType(0x01)));
+		assert_eq!(UnverifiedTransaction::decode_raw(&raw, None), Err(TransactionDecodeError::UnsupportedType(0x01)));
+		assert_eq!(format!("{}", TransactionDecodeError::UnsupportedType(0x01)), "EIP-2930 access list transactions (type 0x01) are not supported");
+
+		let raw = typed_transaction(0x02, 12, |i, s| if i == 8 { s.begin_list(0); } else { s.append_empty_data(); });
+		assert_eq!(UnverifiedTransaction::decode_raw(&raw, None), Err(TransactionDecodeError::UnsupportedType(0x02)));
+		// malformed payload
+		assert_eq!(UnverifiedTransaction::decode_raw(&[0x02, 0xc0], None), Err(TransactionDecodeError::Rlp(DecoderError::RlpIncorrectListLen)));
+		assert_eq!(UnverifiedTransaction::decode_raw(&[0x02, 0x80], None), Err(TransactionDecodeError::Rlp(DecoderError::RlpExpectedToBeList)));
+		// unknown type
+		assert_eq!(UnverifiedTransaction::decode_raw(&[0x42, 0xc0], None), Err(TransactionDecodeError::UnsupportedType(0x42)));
+		assert_eq!(format!("{}", TransactionDecodeError::UnsupportedType(0x42)), "Unknown transaction type 0x42");
+		assert_eq!(UnverifiedTransaction::decode_raw(&[], None), Err(TransactionDecodeError::Rlp(DecoderError::RlpIsTooShort)));
+	}
+
+	#[test]
+	fn should_validate_typed_transaction_payload() {
+		let wrong_chain = access_list_transaction(|i, s| i == 0 && { s.append(&2u64); true });
+		assert_eq!(UnverifiedTransaction::decode_raw(&wrong_chain, Some(1)), Err(TransactionDecodeError::InvalidChainId { expected: 1, got: 2 }));
+
+		let bad_parity = access_list_transaction(|i, s| i == 8 && { s.append(&2u8); true });
+		assert_eq!(UnverifiedTransaction::decode_raw(&bad_parity, Some(1)), Err(TransactionDecodeError::InvalidYParity(2)));
+
+		let not_a_list = access_list_transaction(|i, s| i == 7 && { s.append_empty_data(); true });
+		assert_eq!(UnverifiedTransaction::decode_raw(&not_a_list, Some(1)), Err(TransactionDecodeError::InvalidAccessList));
+
+		let short_address = access_list_transaction(|i, s| i == 7 && {
+			s.begin_list(1).begin_list(2).append(&H256::from(0x42)).begin_list(0);
+			true
+		});
+		assert_eq!(UnverifiedTransaction::decode_raw(&short_address, Some(1)), Err(TransactionDecodeError::InvalidAccessList));
+
+		let bad_keys = access_list_transaction(|i, s| i == 7 && {
+			s.begin_list(1).begin_list(2).append(&Address::from(0x42)).append(&H256::from(1));
+			true
+		});
+		assert_eq!(UnverifiedTransaction::decode_raw(&bad_keys, Some(1)), Err(TransactionDecodeError::InvalidAccessList));
+
+		let bad_destination = access_list_transaction(|i, s| i == 4 && { s.append(&H256::from(0x42)); true });
+		assert_eq!(UnverifiedTransaction::decode_raw(&bad_destination, Some(1)), Err(TransactionDecodeError::Rlp(DecoderError::RlpIsTooBig)));
+	}
+
 	#[test]
 	fn sender_test() {
 		let t: UnverifiedTransaction = decode(&::rustc_hex::FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
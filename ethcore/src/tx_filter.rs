@@ -210,7 +210,7 @@ mod test {
 		let key3 = KeyPair::from_secret(Secret::from("0000000000000000000000000000000000000000000000000000000000000003")).unwrap();
 		let key4 = KeyPair::from_secret(Secret::from("0000000000000000000000000000000000000000000000000000000000000004")).unwrap();
 
-		let filter = TransactionFilter::from_params(spec.params()).unwrap();
+		let filter = TransactionFilter::from_params(&spec.params()).unwrap();
 		filter.register_client(Arc::downgrade(&client) as Weak<_>);
 		let mut basic_tx = Transaction::default();
 		basic_tx.action = Action::Call(Address::from("000000000000000000000000000000000000032"));
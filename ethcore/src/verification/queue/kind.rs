@@ -126,6 +126,11 @@ pub mod blocks {
 				bytes: bytes,
 			}
 		}
+
+		/// Consume the block, returning its raw bytes.
+		pub fn into_bytes(self) -> Bytes {
+			self.bytes
+		}
 	}
 
 	impl HeapSizeOf for Unverified {
@@ -702,6 +702,35 @@ impl<K: Kind> VerificationQueue<K> {
 	}
 }
 
+impl VerificationQueue<self::kind::Blocks> {
+	/// Take all blocks out of the queue, in import order, and clear it.
+	///
+	/// Waits for the blocks currently being verified, so that the returned
+	/// blocks can be imported again without gaps.
+	pub fn take_blocks(&self) -> Vec<::util::Bytes> {
+		let unverified: Vec<_> = self.verification.unverified.lock()
+			.drain(..)
+			.map(|block| block.into_bytes())
+			.collect();
+
+		{
+			let mut lock = self.verification.empty.lock().unwrap();
+			while !self.verification.verifying.lock().is_empty() {
+				lock = self.empty.wait(lock).unwrap();
+			}
+		}
+
+		let mut blocks: Vec<_> = self.verification.verified.lock()
+			.drain(..)
+			.map(|block| block.bytes)
+			.collect();
+		blocks.extend(unverified);
+
+		self.clear();
+		blocks
+	}
+}
+
 impl<K: Kind> Drop for VerificationQueue<K> {
 	fn drop(&mut self) {
 		trace!(target: "shutdown", "[VerificationQueue] Closing...");
@@ -828,6 +857,19 @@ mod tests {
 		assert!(queue.queue_info().is_empty());
 	}
 
+	#[test]
+	fn takes_queued_blocks_in_order() {
+		let queue = get_test_queue(false);
+		let blocks = get_good_dummy_block_seq(10);
+		for b in &blocks {
+			queue.import(Unverified::new(b.clone())).unwrap();
+		}
+
+		assert_eq!(queue.take_blocks(), blocks);
+		assert!(queue.queue_info().is_empty());
+		assert!(queue.import(Unverified::new(blocks[0].clone())).is_ok());
+	}
+
 	#[test]
 	fn test_mem_limit() {
 		let spec = get_test_spec();
@@ -18,7 +18,7 @@
 //!
 //! Block verification is done in 3 steps
 //! 1. Quick verification upon adding to the block queue
-//! 2. Signatures verification done in the queue.
+//! 2. Signatures verification done in the queue (senders of a block are recovered in parallel).
 //! 3. Final verification against the blockchain done before enactment.
 
 use std::collections::HashSet;
@@ -32,6 +32,7 @@ use engines::Engine;
 use error::{BlockError, Error};
 use blockchain::*;
 use header::{BlockNumber, Header};
+use rayon::prelude::*;
 use rlp::UntrustedRlp;
 use transaction::SignedTransaction;
 use views::BlockView;
@@ -85,21 +86,25 @@ pub fn verify_block_unordered(header: Header, bytes: Bytes, engine: &Engine, che
 		}
 	}
 	// Verify transactions.
-	let mut transactions = Vec::new();
 	let nonce_cap = if header.number() >= engine.params().dust_protection_transition {
 		Some((engine.params().nonce_cap_increment * header.number()).into())
 	} else { None };
-	{
-		let v = BlockView::new(&bytes);
-		for t in v.transactions() {
-			let t = engine.verify_transaction(t, &header)?;
-			if let Some(max_nonce) = nonce_cap {
-				if t.nonce >= max_nonce {
-					return Err(BlockError::TooManyTransactions(t.sender()).into());
-				}
+	let verified: Vec<_> = {
+		let header = &header;
+		BlockView::new(&bytes).transactions()
+			.into_par_iter()
+			.map(|t| engine.verify_transaction(t, header))
+			.collect()
+	};
+	let mut transactions = Vec::with_capacity(verified.len());
+	for t in verified {
+		let t = t?;
+		if let Some(max_nonce) = nonce_cap {
+			if t.nonce >= max_nonce {
+				return Err(BlockError::TooManyTransactions(t.sender()).into());
 			}
-			transactions.push(t);
 		}
+		transactions.push(t);
 	}
 	Ok(PreverifiedBlock {
 		header: header,
@@ -16,6 +16,8 @@
 
 //! Call analytics related types
 
+use std::time::Duration;
+
 /// Options concerning what analytics we run on the call.
 #[derive(Eq, PartialEq, Default, Clone, Copy, Debug)]
 pub struct CallAnalytics {
@@ -25,4 +27,32 @@ pub struct CallAnalytics {
 	pub vm_tracing: bool,
 	/// Make a diff.
 	pub state_diffing: bool,
+	/// Record execution steps with given capture limits. Takes precedence over `vm_tracing`.
+	pub step_tracing: Option<StepTracing>,
+	/// Abort the call if it runs longer than given time.
+	pub timeout: Option<Duration>,
+}
+
+/// Capture limits of the step tracer.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct StepTracing {
+	/// Maximal number of recorded steps.
+	pub max_steps: usize,
+	/// Number of topmost stack items captured at each step, `None` to skip the stack.
+	pub stack: Option<usize>,
+	/// Number of memory bytes captured at each step, `None` to skip the memory.
+	pub memory: Option<usize>,
+	/// Number of storage slots captured at each step, `None` to skip the storage.
+	pub storage: Option<usize>,
+}
+
+impl Default for StepTracing {
+	fn default() -> Self {
+		StepTracing {
+			max_steps: 10_000,
+			stack: Some(1024),
+			memory: Some(4096),
+			storage: Some(256),
+		}
+	}
 }
@@ -27,4 +27,6 @@ pub struct PruningInfo {
 	pub earliest_chain: u64,
 	/// The first block where state requests may be served.
 	pub earliest_state: u64,
+	/// The first block whose traces may be served.
+	pub earliest_trace: u64,
 }
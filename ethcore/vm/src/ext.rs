@@ -134,6 +134,9 @@ pub trait Ext {
 	/// Increments sstore refunds count by 1.
 	fn inc_sstore_clears(&mut self);
 
+	/// Returns true if the execution ran past its deadline and should be aborted.
+	fn deadline_exceeded(&self) -> bool { false }
+
 	/// Decide if any more operations should be traced. Passthrough for the VM trace.
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8) -> bool { false }
 
@@ -36,7 +36,7 @@ pub mod tests;
 pub use action_params::{ActionParams, ActionValue};
 pub use call_type::CallType;
 pub use env_info::{EnvInfo, LastHashes};
-pub use schedule::{Schedule, CleanDustMode};
+pub use schedule::{Schedule, CleanDustMode, WasmSchedule, CustomInstruction};
 pub use ext::{Ext, MessageCallResult, ContractCreateResult, CreateContractAddress};
 pub use return_data::{ReturnData, GasLeft};
 pub use error::{Error, Result};
@@ -16,6 +16,8 @@
 
 //! Cost schedule and other parameterisations for the EVM.
 
+use std::collections::BTreeMap;
+
 /// Definition of the cost schedule and other parameterisations for the EVM.
 pub struct Schedule {
 	/// Does it support exceptional failed code deposit
@@ -111,6 +113,50 @@ pub struct Schedule {
 	pub have_return_data: bool,
 	/// Kill basic accounts below this balance if touched.
 	pub kill_dust: CleanDustMode,
+	/// WASM VM settings.
+	pub wasm: WasmSchedule,
+	/// Additional instructions activated by the chain, by opcode.
+	pub custom_instructions: BTreeMap<u8, CustomInstruction>,
+}
+
+/// Chain-specific instruction, executed by an implementation registered under its name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomInstruction {
+	/// Name of the registered implementation.
+	pub name: String,
+	/// Number of stack arguments.
+	pub args: usize,
+	/// Number of returned stack items.
+	pub ret: usize,
+	/// Gas charged for the instruction.
+	pub gas: usize,
+}
+
+/// WASM-specific part of the schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasmSchedule {
+	/// Gas charged per unit reported by the injected gas counter.
+	pub regular: u32,
+	/// Gas charged per byte allocated with `_malloc`.
+	pub mem: u32,
+	/// Gas charged for `_storage_read`.
+	pub storage_read: u32,
+	/// Gas charged for `_storage_write`.
+	pub storage_write: u32,
+	/// Optional host functions (e.g. `_blocknumber`) contracts may import, by name.
+	pub host_functions: Vec<String>,
+}
+
+impl Default for WasmSchedule {
+	fn default() -> Self {
+		WasmSchedule {
+			regular: 1,
+			mem: 0,
+			storage_read: 0,
+			storage_write: 0,
+			host_functions: Vec::new(),
+		}
+	}
 }
 
 /// Dust accounts cleanup mode.
@@ -184,6 +230,8 @@ impl Schedule {
 			blockhash_gas: 20,
 			have_static_call: false,
 			kill_dust: CleanDustMode::Off,
+			wasm: WasmSchedule::default(),
+			custom_instructions: BTreeMap::new(),
 		}
 	}
 
@@ -246,6 +294,8 @@ impl Schedule {
 			blockhash_gas: 20,
 			have_static_call: false,
 			kill_dust: CleanDustMode::Off,
+			wasm: WasmSchedule::default(),
+			custom_instructions: BTreeMap::new(),
 		}
 	}
 }
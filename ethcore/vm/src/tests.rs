@@ -64,6 +64,7 @@ pub struct FakeExt {
 	pub schedule: Schedule,
 	pub balances: HashMap<Address, U256>,
 	pub tracing: bool,
+	pub deadline_exceeded: bool,
 }
 
 // similar to the normal `finalize` function, but ignoring NeedsReturn.
@@ -188,6 +189,10 @@ impl Ext for FakeExt {
 		self.sstore_clears += 1;
 	}
 
+	fn deadline_exceeded(&self) -> bool {
+		self.deadline_exceeded
+	}
+
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8) -> bool {
 		self.tracing
 	}
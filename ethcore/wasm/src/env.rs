@@ -102,9 +102,51 @@ pub const SIGNATURES: &'static [UserFunctionDescriptor] = &[
 	),
 ];
 
+/// Host functions only exposed to contracts when the chain schedule enables them.
+pub const OPTIONAL_SIGNATURES: &'static [UserFunctionDescriptor] = &[
+	Static(
+		"_blocknumber",
+		&[I32],
+		None,
+	),
+	Static(
+		"_timestamp",
+		&[I32],
+		None,
+	),
+	Static(
+		"_difficulty",
+		&[I32],
+		None,
+	),
+	Static(
+		"_gaslimit",
+		&[I32],
+		None,
+	),
+	Static(
+		"_coinbase",
+		&[I32],
+		None,
+	),
+	Static(
+		"_blockhash",
+		&[I32; 2],
+		None,
+	),
+];
+
 pub fn native_bindings<'a>(runtime: &'a mut Runtime) -> interpreter::UserFunctions<'a> {
+	let functions = {
+		let enabled = &runtime.schedule().wasm.host_functions;
+		SIGNATURES.iter()
+			.chain(OPTIONAL_SIGNATURES.iter().filter(|f| enabled.iter().any(|name| name == f.name())))
+			.cloned()
+			.collect::<Vec<_>>()
+	};
+
 	interpreter::UserFunctions {
 		executor: runtime,
-		functions: ::std::borrow::Cow::from(SIGNATURES),
+		functions: ::std::borrow::Cow::from(functions),
 	}
 }
\ No newline at end of file
@@ -45,6 +45,11 @@ pub use self::runtime::Error as RuntimeError;
 
 const DEFAULT_RESULT_BUFFER: usize = 1024;
 
+/// Whether an optional host function with the given name can be enabled in the schedule.
+pub fn is_host_function(name: &str) -> bool {
+	env::OPTIONAL_SIGNATURES.iter().any(|f| f.name() == name)
+}
+
 /// Wasm interpreter instance
 pub struct WasmInterpreter {
 	program: interpreter::ProgramInstance,
@@ -114,6 +114,9 @@ impl<'a, 'b> Runtime<'a, 'b> {
 		let key = self.pop_h256(&mut context)?;
 		trace!(target: "wasm", "storage_write: value {} at @{}", &val, &key);
 
+		let cost = self.ext.schedule().wasm.storage_write as u64;
+		self.charge(cost)?;
+
 		self.ext.set_storage(key, val)
 			.map_err(|_| interpreter::Error::Trap("Storage update error".to_owned()))?;
 
@@ -128,6 +131,9 @@ impl<'a, 'b> Runtime<'a, 'b> {
 		let val_ptr = context.value_stack.pop_as::<i32>()?;
 		let key = self.pop_h256(&mut context)?;
 
+		let cost = self.ext.schedule().wasm.storage_read as u64;
+		self.charge(cost)?;
+
 		let val = self.ext.storage_at(&key)
 			.map_err(|_| interpreter::Error::Trap("Storage read error".to_owned()))?;
 
@@ -315,6 +321,9 @@ impl<'a, 'b> Runtime<'a, 'b> {
 		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
 	{
 		let amount = context.value_stack.pop_as::<i32>()? as u32;
+		let cost = amount as u64 * self.ext.schedule().wasm.mem as u64;
+		self.charge(cost)?;
+
 		let previous_top = self.dynamic_top;
 		self.dynamic_top = previous_top + amount;
 		Ok(Some((previous_top as i32).into()))
@@ -332,8 +341,14 @@ impl<'a, 'b> Runtime<'a, 'b> {
 		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
 	{
 		let amount = context.value_stack.pop_as::<i32>()? as u64;
+		let cost = amount * self.ext.schedule().wasm.regular as u64;
+		self.charge(cost)?;
+		Ok(None)
+	}
+
+	fn charge(&mut self, amount: u64) -> Result<(), interpreter::Error> {
 		if self.charge_gas(amount) {
-			Ok(None)
+			Ok(())
 		} else {
 			Err(interpreter::Error::Trap(format!("Gas exceeds limits of {}", self.gas_limit)))
 		}
@@ -380,6 +395,74 @@ impl<'a, 'b> Runtime<'a, 'b> {
 		self.address_at(ptr)
 	}
 
+	fn write_u256(&self, ptr: u32, value: U256) -> Result<Option<interpreter::RuntimeValue>, interpreter::Error> {
+		let value: H256 = value.into();
+		self.memory.set(ptr, &*value)?;
+		Ok(None)
+	}
+
+	/// Write current block number to wasm memory
+	fn block_number(&mut self, context: interpreter::CallerContext)
+		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
+	{
+		let ptr = context.value_stack.pop_as::<i32>()? as u32;
+		let number = self.ext.env_info().number;
+		self.write_u256(ptr, number.into())
+	}
+
+	/// Write current block timestamp to wasm memory
+	fn timestamp(&mut self, context: interpreter::CallerContext)
+		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
+	{
+		let ptr = context.value_stack.pop_as::<i32>()? as u32;
+		let timestamp = self.ext.env_info().timestamp;
+		self.write_u256(ptr, timestamp.into())
+	}
+
+	/// Write current block difficulty to wasm memory
+	fn difficulty(&mut self, context: interpreter::CallerContext)
+		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
+	{
+		let ptr = context.value_stack.pop_as::<i32>()? as u32;
+		let difficulty = self.ext.env_info().difficulty;
+		self.write_u256(ptr, difficulty)
+	}
+
+	/// Write current block gas limit to wasm memory
+	fn block_gas_limit(&mut self, context: interpreter::CallerContext)
+		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
+	{
+		let ptr = context.value_stack.pop_as::<i32>()? as u32;
+		let gas_limit = self.ext.env_info().gas_limit;
+		self.write_u256(ptr, gas_limit)
+	}
+
+	/// Write current block author to wasm memory
+	fn coinbase(&mut self, context: interpreter::CallerContext)
+		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
+	{
+		let ptr = context.value_stack.pop_as::<i32>()? as u32;
+		let author = self.ext.env_info().author;
+		self.memory.set(ptr, &*author)?;
+		Ok(None)
+	}
+
+	/// Write hash of the block with number read from wasm memory
+	fn block_hash(&mut self, context: interpreter::CallerContext)
+		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
+	{
+		let mut context = context;
+		let ptr = context.value_stack.pop_as::<i32>()? as u32;
+		let number = self.pop_u256(&mut context)?;
+
+		let cost = self.ext.schedule().blockhash_gas as u64;
+		self.charge(cost)?;
+
+		let hash = self.ext.blockhash(&number);
+		self.memory.set(ptr, &*hash)?;
+		Ok(None)
+	}
+
 	fn user_trap(&mut self, _context: interpreter::CallerContext)
 		-> Result<Option<interpreter::RuntimeValue>, interpreter::Error>
 	{
@@ -437,6 +520,11 @@ impl<'a, 'b> Runtime<'a, 'b> {
 		Ok(self.gas_limit - self.gas_counter)
 	}
 
+	/// Schedule of the chain the contract runs on
+	pub fn schedule(&self) -> &vm::Schedule {
+		self.ext.schedule()
+	}
+
 	/// Shared memory reference
 	pub fn memory(&self) -> &interpreter::MemoryInstance {
 		&*self.memory
@@ -551,6 +639,24 @@ impl<'a, 'b> interpreter::UserFunctionExecutor for Runtime<'a, 'b> {
 			"_llvm_bswap_i64" => {
 				self.bitswap_i64(context)
 			},
+			"_blocknumber" => {
+				self.block_number(context)
+			},
+			"_timestamp" => {
+				self.timestamp(context)
+			},
+			"_difficulty" => {
+				self.difficulty(context)
+			},
+			"_gaslimit" => {
+				self.block_gas_limit(context)
+			},
+			"_coinbase" => {
+				self.coinbase(context)
+			},
+			"_blockhash" => {
+				self.block_hash(context)
+			},
 			_ => {
 				trace!(target: "wasm", "Trapped due to unhandled function: '{}'", name);
 				self.user_trap(context)
@@ -535,3 +535,136 @@ fn math_div() {
 		(&result[..]).into()
 	);
 }
+
+// (module
+//   (import "env" "_blocknumber" (func $blocknumber (param i32)))
+//   (import "env" "_storage_write" (func $storage_write (param i32 i32) (result i32)))
+//   (import "env" "memory" (memory 1))
+//   (func (export "_call") (param i32)
+//     (call $blocknumber (i32.const 0))
+//     (drop (call $storage_write (i32.const 32) (i32.const 0)))))
+//
+// Stores the current block number under the zero key.
+const BLOCKNUMBER: &'static [u8] = &[
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x10, 0x03, 0x60, 0x01, 0x7f, 0x00, 0x60,
+	0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x02, 0x7f, 0x7f, 0x00, 0x02, 0x37, 0x03, 0x03, 0x65, 0x6e,
+	0x76, 0x0c, 0x5f, 0x62, 0x6c, 0x6f, 0x63, 0x6b, 0x6e, 0x75, 0x6d, 0x62, 0x65, 0x72, 0x00, 0x00,
+	0x03, 0x65, 0x6e, 0x76, 0x0e, 0x5f, 0x73, 0x74, 0x6f, 0x72, 0x61, 0x67, 0x65, 0x5f, 0x77, 0x72,
+	0x69, 0x74, 0x65, 0x00, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79,
+	0x02, 0x00, 0x01, 0x03, 0x02, 0x01, 0x00, 0x07, 0x09, 0x01, 0x05, 0x5f, 0x63, 0x61, 0x6c, 0x6c,
+	0x00, 0x02, 0x0a, 0x0f, 0x01, 0x0d, 0x00, 0x41, 0x00, 0x10, 0x00, 0x41, 0x20, 0x41, 0x00, 0x10,
+	0x01, 0x1a, 0x0b,
+];
+
+// (module
+//   (import "env" "_blockhash" (func $blockhash (param i32 i32)))
+//   (import "env" "_storage_write" (func $storage_write (param i32 i32) (result i32)))
+//   (import "env" "memory" (memory 1))
+//   (func (export "_call") (param i32)
+//     (call $blockhash (i32.const 32) (i32.const 0))
+//     (drop (call $storage_write (i32.const 32) (i32.const 0)))))
+//
+// Stores the hash of block zero under the zero key.
+const BLOCKHASH: &'static [u8] = &[
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x10, 0x03, 0x60, 0x01, 0x7f, 0x00, 0x60,
+	0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x02, 0x7f, 0x7f, 0x00, 0x02, 0x35, 0x03, 0x03, 0x65, 0x6e,
+	0x76, 0x0a, 0x5f, 0x62, 0x6c, 0x6f, 0x63, 0x6b, 0x68, 0x61, 0x73, 0x68, 0x00, 0x02, 0x03, 0x65,
+	0x6e, 0x76, 0x0e, 0x5f, 0x73, 0x74, 0x6f, 0x72, 0x61, 0x67, 0x65, 0x5f, 0x77, 0x72, 0x69, 0x74,
+	0x65, 0x00, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02, 0x00,
+	0x01, 0x03, 0x02, 0x01, 0x00, 0x07, 0x09, 0x01, 0x05, 0x5f, 0x63, 0x61, 0x6c, 0x6c, 0x00, 0x02,
+	0x0a, 0x11, 0x01, 0x0f, 0x00, 0x41, 0x20, 0x41, 0x00, 0x10, 0x00, 0x41, 0x20, 0x41, 0x00, 0x10,
+	0x01, 0x1a, 0x0b,
+];
+
+fn exec_with_ext(code: &[u8], ext: &mut FakeExt) -> Result<U256, vm::Error> {
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code.to_vec()));
+
+	let mut interpreter = wasm_interpreter();
+	test_finalize(interpreter.exec(params, ext))
+}
+
+#[test]
+fn blocknumber() {
+	let mut ext = FakeExt::new();
+	ext.schedule.wasm.host_functions = vec!["_blocknumber".into()];
+	ext.info.number = 0x1234;
+
+	exec_with_ext(BLOCKNUMBER, &mut ext).expect("Interpreter to execute without any errors");
+
+	assert_eq!(
+		ext.store.get(&H256::zero()).expect("storage key to exist"),
+		&H256::from(U256::from(0x1234)),
+	);
+}
+
+#[test]
+fn blockhash() {
+	let hash: H256 = "7a5b8e2cd8a2b8a2c0e8c5f3aa3a4f27e3c1b4b1f95a1c7a4d8e6d58c7b1a901".parse().unwrap();
+	let mut ext = FakeExt::new();
+	ext.schedule.wasm.host_functions = vec!["_blockhash".into()];
+	ext.blockhashes.insert(U256::zero(), hash.clone());
+
+	exec_with_ext(BLOCKHASH, &mut ext).expect("Interpreter to execute without any errors");
+
+	assert_eq!(ext.store.get(&H256::zero()).expect("storage key to exist"), &hash);
+}
+
+#[test]
+fn host_functions_are_disabled_by_default() {
+	let mut ext = FakeExt::new();
+	assert!(exec_with_ext(BLOCKNUMBER, &mut ext).is_err());
+
+	let mut ext = FakeExt::new();
+	ext.schedule.wasm.host_functions = vec!["_blocknumber".into()];
+	assert!(exec_with_ext(BLOCKHASH, &mut ext).is_err());
+	assert!(ext.store.is_empty());
+}
+
+#[test]
+fn blockhash_charges_blockhash_gas() {
+	let gas_used = |blockhash_gas| {
+		let mut ext = FakeExt::new();
+		ext.schedule.wasm.host_functions = vec!["_blockhash".into()];
+		ext.schedule.blockhash_gas = blockhash_gas;
+		U256::from(100_000) - exec_with_ext(BLOCKHASH, &mut ext).unwrap()
+	};
+
+	assert_eq!(gas_used(1020) - gas_used(20), U256::from(1000));
+}
+
+#[test]
+fn storage_write_charges_scheduled_gas() {
+	let gas_used = |storage_write| {
+		let mut ext = FakeExt::new();
+		ext.schedule.wasm.host_functions = vec!["_blocknumber".into()];
+		ext.schedule.wasm.storage_write = storage_write;
+		U256::from(100_000) - exec_with_ext(BLOCKNUMBER, &mut ext).unwrap()
+	};
+
+	assert_eq!(gas_used(5000) - gas_used(0), U256::from(5000));
+}
+
+#[test]
+fn regular_gas_scales_metered_instructions() {
+	let gas_used = |regular| {
+		let mut ext = FakeExt::new();
+		ext.schedule.wasm.host_functions = vec!["_blocknumber".into()];
+		ext.schedule.wasm.regular = regular;
+		U256::from(100_000) - exec_with_ext(BLOCKNUMBER, &mut ext).unwrap()
+	};
+
+	assert!(gas_used(1) > U256::zero());
+	assert_eq!(gas_used(3), gas_used(1) * U256::from(3));
+}
+
+#[test]
+fn out_of_gas_on_scheduled_charge() {
+	let mut ext = FakeExt::new();
+	ext.schedule.wasm.host_functions = vec!["_blocknumber".into()];
+	ext.schedule.wasm.storage_write = 200_000;
+
+	assert!(exec_with_ext(BLOCKNUMBER, &mut ext).is_err());
+	assert!(ext.store.is_empty());
+}
@@ -143,6 +143,11 @@ pub fn derive_mac(derived_left_bits: &[u8], cipher_text: &[u8]) -> Vec<u8> {
 	mac
 }
 
+/// Constant time comparison of two byte slices. Only the lengths are compared in variable time.
+pub fn is_equal(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && ::subtle::arrays_equal(a, b) == 1
+}
+
 /// AES encryption
 pub mod aes {
 	use rcrypto::blockmodes::{CtrMode, CbcDecryptor, PkcsPadding};
@@ -370,6 +375,15 @@ pub mod ecies {
 mod tests {
 	use ethkey::{Random, Generator};
 	use ecies;
+	use is_equal;
+
+	#[test]
+	fn compares_slices() {
+		assert!(is_equal(b"password", b"password"));
+		assert!(!is_equal(b"password", b"passwore"));
+		assert!(!is_equal(b"password", b"password1"));
+		assert!(is_equal(b"", b""));
+	}
 
 	#[test]
 	fn ecies_shared() {
@@ -77,6 +77,20 @@ pub fn generation_point() -> Public {
 	public
 }
 
+/// Decode public key from its compressed (33 bytes) or uncompressed (65 bytes) SEC1 encoding.
+pub fn public_from_sec1(data: &[u8]) -> Result<Public, Error> {
+	let key_public = key::PublicKey::from_slice(&SECP256K1, data)?;
+	let mut public = Public::default();
+	set_public(&mut public, &key_public);
+	Ok(public)
+}
+
+/// Return compressed (33 bytes) SEC1 encoding of the public key.
+pub fn public_to_compressed(public: &Public) -> Result<Vec<u8>, Error> {
+	let key_public = to_secp256k1_public(public)?;
+	Ok(key_public.serialize_vec(&SECP256K1, true).to_vec())
+}
+
 /// Return secp256k1 elliptic curve order
 pub fn curve_order() -> U256 {
 	H256::from_slice(&CURVE_ORDER).into()
@@ -100,7 +114,7 @@ fn set_public(public: &mut Public, key_public: &key::PublicKey) {
 #[cfg(test)]
 mod tests {
 	use super::super::{Random, Generator};
-	use super::{public_add, public_sub};
+	use super::{public_add, public_sub, public_from_sec1, public_to_compressed};
 
 	#[test]
 	fn public_addition_is_commutative() {
@@ -127,4 +141,13 @@ mod tests {
 
 		assert_eq!(sum, public1);
 	}
+
+	#[test]
+	fn public_compression_roundtrips() {
+		let public = Random.generate().unwrap().public().clone();
+
+		let compressed = public_to_compressed(&public).unwrap();
+		assert_eq!(compressed.len(), 33);
+		assert_eq!(public_from_sec1(&compressed).unwrap(), public);
+	}
 }
@@ -84,6 +84,8 @@ pub trait VaultKeyDirectory: KeyDirectory {
 	fn key(&self) -> VaultKey;
 	/// Set new key for vault
 	fn set_key(&self, key: VaultKey) -> Result<(), SetKeyError>;
+	/// Set new key for vault and re-encrypt the vault with a freshly generated secret
+	fn rotate_key(&self, key: VaultKey) -> Result<(), SetKeyError>;
 	/// Get vault meta
 	fn meta(&self) -> String;
 	/// Set vault meta
@@ -17,8 +17,10 @@
 use std::{fs, io};
 use std::path::{PathBuf, Path};
 use parking_lot::Mutex;
+use rustc_hex::ToHex;
 use {json, SafeAccount, Error};
 use crypto::Keccak256;
+use random::Random;
 use super::super::account::Crypto;
 use super::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
 use super::disk::{DiskDirectory, KeyFileManager};
@@ -35,12 +37,21 @@ pub type VaultDiskDirectory = DiskDirectory<VaultKeyFileManager>;
 pub struct VaultKeyFileManager {
 	name: String,
 	key: VaultKey,
+	/// Password the account files are encrypted with. Same as the vault password unless
+	/// the vault key has been rotated.
+	secret: String,
 	meta: Mutex<String>,
 }
 
 impl VaultDiskDirectory {
 	/// Create new vault directory with given key
 	pub fn create<P>(root: P, name: &str, key: VaultKey) -> Result<Self, Error> where P: AsRef<Path> {
+		let secret = key.password.clone();
+		Self::create_with_secret(root, name, key, secret)
+	}
+
+	/// Create new vault directory with given key, encrypting account files with `secret`.
+	fn create_with_secret<P>(root: P, name: &str, key: VaultKey, secret: String) -> Result<Self, Error> where P: AsRef<Path> {
 		// check that vault directory does not exists
 		let vault_dir_path = make_vault_dir_path(root, name, true)?;
 		if vault_dir_path.exists() {
@@ -50,12 +61,12 @@ impl VaultDiskDirectory {
 		// create vault && vault file
 		let vault_meta = "{}";
 		fs::create_dir_all(&vault_dir_path)?;
-		if let Err(err) = create_vault_file(&vault_dir_path, &key, vault_meta) {
+		if let Err(err) = create_vault_file(&vault_dir_path, &key, &secret, vault_meta) {
 			let _ = fs::remove_dir_all(&vault_dir_path); // can't do anything with this
 			return Err(err);
 		}
 
-		Ok(DiskDirectory::new(vault_dir_path, VaultKeyFileManager::new(name, key, vault_meta)))
+		Ok(DiskDirectory::new(vault_dir_path, VaultKeyFileManager::new(name, key, secret, vault_meta)))
 	}
 
 	/// Open existing vault directory with given key
@@ -67,9 +78,10 @@ impl VaultDiskDirectory {
 		}
 
 		// check that passed key matches vault file
-		let meta = read_vault_file(&vault_dir_path, Some(&key))?;
+		let (meta, secret) = read_vault_file(&vault_dir_path, Some(&key))?;
+		let secret = secret.expect("key is given; read_vault_file returns secret when key is given; qed");
 
-		Ok(DiskDirectory::new(vault_dir_path, VaultKeyFileManager::new(name, key, &meta)))
+		Ok(DiskDirectory::new(vault_dir_path, VaultKeyFileManager::new(name, key, secret, &meta)))
 	}
 
 	/// Read vault meta without actually opening the vault
@@ -81,10 +93,10 @@ impl VaultDiskDirectory {
 		}
 
 		// check that passed key matches vault file
-		read_vault_file(&vault_dir_path, None)
+		read_vault_file(&vault_dir_path, None).map(|(meta, _)| meta)
 	}
 
-	fn create_temp_vault(&self, key: VaultKey) -> Result<VaultDiskDirectory, Error> {
+	fn create_temp_vault(&self, key: VaultKey, secret: String) -> Result<VaultDiskDirectory, Error> {
 		let original_path = self.path().expect("self is instance of DiskDirectory; DiskDirectory always returns path; qed");
 		let mut path: PathBuf = original_path.clone();
 		let name = self.name();
@@ -96,7 +108,7 @@ impl VaultDiskDirectory {
 			let name = format!("{}_temp_{}", name, index);
 			path.set_file_name(&name);
 			if !path.exists() {
-				return VaultDiskDirectory::create(original_path, &name, key);
+				return VaultDiskDirectory::create_with_secret(original_path, &name, key, secret);
 			}
 
 			index += 1;
@@ -116,6 +128,55 @@ impl VaultDiskDirectory {
 		let path = self.path().expect("self is instance of DiskDirectory; DiskDirectory always returns path; qed");
 		fs::remove_dir_all(path).map_err(Into::into)
 	}
+
+	/// Re-encrypts all vault files with `new_key`, encrypting account files with `secret`.
+	fn replace_key(&self, new_key: VaultKey, secret: String) -> Result<(), SetKeyError> {
+		let temp_vault = VaultDiskDirectory::create_temp_vault(self, new_key, secret).map_err(|err| SetKeyError::NonFatalOld(err))?;
+		let source_path = temp_vault.path().expect("temp_vault is instance of DiskDirectory; DiskDirectory always returns path; qed").clone();
+		let target_path = self.path().expect("self is instance of DiskDirectory; DiskDirectory always returns path; qed").clone();
+
+		// preserve meta
+		temp_vault.set_meta(&self.meta()).map_err(SetKeyError::NonFatalOld)?;
+
+		let temp_accounts = self.copy_to_vault(&temp_vault)
+			.and_then(|_| temp_vault.load())
+			.map_err(|err| {
+				// ignore error, as we already processing error
+				let _ = temp_vault.delete();
+				SetKeyError::NonFatalOld(err)
+			})?;
+
+		// original files are moved to the backup vault before being replaced
+		// => when error happens here, original vault content can be restored
+		let backup_vault = VaultDiskDirectory::create_temp_vault(self, self.key(), self.key_manager().secret.clone()).map_err(|err| {
+			let _ = temp_vault.delete();
+			SetKeyError::NonFatalOld(err)
+		})?;
+		let backup_path = backup_vault.path().expect("backup_vault is instance of DiskDirectory; DiskDirectory always returns path; qed").clone();
+
+		let filenames: Vec<String> = temp_accounts.into_iter()
+			.map(|account| account.filename.expect("self is instance of DiskDirectory; DiskDirectory fills filename in load; qed"))
+			.chain(Some(VAULT_FILE_NAME.to_owned()))
+			.collect();
+
+		let mut replaced = Vec::with_capacity(filenames.len());
+		if let Err(err) = replace_files(&filenames, &source_path, &target_path, &backup_path, &mut replaced) {
+			return match restore_files(&replaced, &target_path, &backup_path) {
+				Ok(_) => {
+					// ignore errors, as vault content is already restored
+					let _ = temp_vault.delete();
+					let _ = backup_vault.delete();
+					Err(SetKeyError::NonFatalOld(err))
+				},
+				// when error or crash happens here, we can't do anything
+				Err(_) => Err(SetKeyError::Fatal(err)),
+			};
+		}
+
+		temp_vault.delete()
+			.and_then(|_| backup_vault.delete())
+			.map_err(|err| SetKeyError::NonFatalNew(err))
+	}
 }
 
 impl VaultKeyDirectory for VaultDiskDirectory {
@@ -132,39 +193,19 @@ impl VaultKeyDirectory for VaultDiskDirectory {
 	}
 
 	fn set_key(&self, new_key: VaultKey) -> Result<(), SetKeyError> {
-		let temp_vault = VaultDiskDirectory::create_temp_vault(self, new_key.clone()).map_err(|err| SetKeyError::NonFatalOld(err))?;
-		let mut source_path = temp_vault.path().expect("temp_vault is instance of DiskDirectory; DiskDirectory always returns path; qed").clone();
-		let mut target_path = self.path().expect("self is instance of DiskDirectory; DiskDirectory always returns path; qed").clone();
-
-		// preserve meta
-		temp_vault.set_meta(&self.meta()).map_err(SetKeyError::NonFatalOld)?;
-
-		// jump to next fs level
-		source_path.push("next");
-		target_path.push("next");
-
-		let temp_accounts = self.copy_to_vault(&temp_vault)
-			.and_then(|_| temp_vault.load())
-			.map_err(|err| {
-				// ignore error, as we already processing error
-				let _ = temp_vault.delete();
-				SetKeyError::NonFatalOld(err)
-			})?;
-
-		// we can't just delete temp vault until all files moved, because
-		// original vault content has already been partially replaced
-		// => when error or crash happens here, we can't do anything
-		for temp_account in temp_accounts {
-			let filename = temp_account.filename.expect("self is instance of DiskDirectory; DiskDirectory fills filename in load; qed");
-			source_path.set_file_name(&filename);
-			target_path.set_file_name(&filename);
-			fs::rename(&source_path, &target_path).map_err(|err| SetKeyError::Fatal(err.into()))?;
-		}
-		source_path.set_file_name(VAULT_FILE_NAME);
-		target_path.set_file_name(VAULT_FILE_NAME);
-		fs::rename(source_path, target_path).map_err(|err| SetKeyError::Fatal(err.into()))?;
+		// vaults which have never been rotated keep encrypting account files with the vault password
+		let key_manager = self.key_manager();
+		let secret = if key_manager.secret == key_manager.key.password {
+			new_key.password.clone()
+		} else {
+			key_manager.secret.clone()
+		};
+		self.replace_key(new_key, secret)
+	}
 
-		temp_vault.delete().map_err(|err| SetKeyError::NonFatalNew(err))
+	fn rotate_key(&self, new_key: VaultKey) -> Result<(), SetKeyError> {
+		let secret: [u8; 32] = Random::random();
+		self.replace_key(new_key, secret.to_hex())
 	}
 
 	fn meta(&self) -> String {
@@ -174,17 +215,18 @@ impl VaultKeyDirectory for VaultDiskDirectory {
 	fn set_meta(&self, meta: &str) -> Result<(), Error> {
 		let key_manager = self.key_manager();
 		let vault_path = self.path().expect("self is instance of DiskDirectory; DiskDirectory always returns path; qed");
-		create_vault_file(vault_path, &key_manager.key, meta)?;
+		create_vault_file(vault_path, &key_manager.key, &key_manager.secret, meta)?;
 		*key_manager.meta.lock() = meta.to_owned();
 		Ok(())
 	}
 }
 
 impl VaultKeyFileManager {
-	pub fn new(name: &str, key: VaultKey, meta: &str) -> Self {
+	pub fn new(name: &str, key: VaultKey, secret: String, meta: &str) -> Self {
 		VaultKeyFileManager {
 			name: name.into(),
 			key: key,
+			secret: secret,
 			meta: Mutex::new(meta.to_owned()),
 		}
 	}
@@ -193,7 +235,7 @@ impl VaultKeyFileManager {
 impl KeyFileManager for VaultKeyFileManager {
 	fn read<T>(&self, filename: Option<String>, reader: T) -> Result<SafeAccount, Error> where T: io::Read {
 		let vault_file = json::VaultKeyFile::load(reader).map_err(|e| Error::Custom(format!("{:?}", e)))?;
-		let mut safe_account = SafeAccount::from_vault_file(&self.key.password, vault_file, filename.clone())?;
+		let mut safe_account = SafeAccount::from_vault_file(&self.secret, vault_file, filename.clone())?;
 
 		safe_account.meta = json::insert_vault_name_to_json_meta(&safe_account.meta, &self.name)
 			.map_err(|err| Error::Custom(format!("{:?}", err)))?;
@@ -204,11 +246,41 @@ impl KeyFileManager for VaultKeyFileManager {
 		account.meta = json::remove_vault_name_from_json_meta(&account.meta)
 			.map_err(|err| Error::Custom(format!("{:?}", err)))?;
 
-		let vault_file: json::VaultKeyFile = account.into_vault_file(self.key.iterations, &self.key.password)?;
+		let vault_file: json::VaultKeyFile = account.into_vault_file(self.key.iterations, &self.secret)?;
 		vault_file.write(writer).map_err(|e| Error::Custom(format!("{:?}", e)))
 	}
 }
 
+/// Moves files from source directory to target directory, moving replaced files to backup directory.
+/// Every processed file is recorded in `replaced` along with the flag telling if it has been backed up.
+fn replace_files(filenames: &[String], source_dir: &Path, target_dir: &Path, backup_dir: &Path, replaced: &mut Vec<(String, bool)>) -> Result<(), Error> {
+	for filename in filenames {
+		let target_path = target_dir.join(filename);
+		let backed_up = target_path.exists();
+		if backed_up {
+			fs::rename(&target_path, backup_dir.join(filename))?;
+		}
+		replaced.push((filename.clone(), backed_up));
+		fs::rename(source_dir.join(filename), &target_path)?;
+	}
+
+	Ok(())
+}
+
+/// Reverts changes made by `replace_files`.
+fn restore_files(replaced: &[(String, bool)], target_dir: &Path, backup_dir: &Path) -> Result<(), Error> {
+	for &(ref filename, backed_up) in replaced.iter().rev() {
+		let target_path = target_dir.join(filename);
+		if backed_up {
+			fs::rename(backup_dir.join(filename), &target_path)?;
+		} else if target_path.exists() {
+			fs::remove_file(&target_path)?;
+		}
+	}
+
+	Ok(())
+}
+
 /// Makes path to vault directory, checking that vault name is appropriate
 fn make_vault_dir_path<P>(root: P, name: &str, check_name: bool) -> Result<PathBuf, Error> where P: AsRef<Path> {
 	// check vault name
@@ -233,9 +305,15 @@ fn check_vault_name(name: &str) -> bool {
 }
 
 /// Vault can be empty, but still must be pluggable => we store vault password in separate file
-fn create_vault_file<P>(vault_dir_path: P, key: &VaultKey, meta: &str) -> Result<(), Error> where P: AsRef<Path> {
-	let password_hash = key.password.keccak256();
-	let crypto = Crypto::with_plain(&password_hash, &key.password, key.iterations);
+///
+/// The file holds the hash of the password, or the secret account files are encrypted with
+/// if it differs from the password.
+fn create_vault_file<P>(vault_dir_path: P, key: &VaultKey, secret: &str, meta: &str) -> Result<(), Error> where P: AsRef<Path> {
+	let plain = match secret == key.password {
+		true => key.password.keccak256().to_vec(),
+		false => secret.as_bytes().to_vec(),
+	};
+	let crypto = Crypto::with_plain(&plain, &key.password, key.iterations);
 
 	let mut vault_file_path: PathBuf = vault_dir_path.as_ref().into();
 	vault_file_path.push(VAULT_FILE_NAME);
@@ -256,8 +334,8 @@ fn create_vault_file<P>(vault_dir_path: P, key: &VaultKey, meta: &str) -> Result
 	Ok(())
 }
 
-/// When vault is opened => we must check that password matches && read metadata
-fn read_vault_file<P>(vault_dir_path: P, key: Option<&VaultKey>) -> Result<String, Error> where P: AsRef<Path> {
+/// When vault is opened => we must check that password matches && read metadata and secret
+fn read_vault_file<P>(vault_dir_path: P, key: Option<&VaultKey>) -> Result<(String, Option<String>), Error> where P: AsRef<Path> {
 	let mut vault_file_path: PathBuf = vault_dir_path.as_ref().into();
 	vault_file_path.push(VAULT_FILE_NAME);
 
@@ -266,15 +344,20 @@ fn read_vault_file<P>(vault_dir_path: P, key: Option<&VaultKey>) -> Result<Strin
 	let vault_file_meta = vault_file_contents.meta.unwrap_or("{}".to_owned());
 	let vault_file_crypto: Crypto = vault_file_contents.crypto.into();
 
-	if let Some(key) = key {
-		let password_bytes = vault_file_crypto.decrypt(&key.password)?;
-		let password_hash = key.password.keccak256();
-		if password_hash != password_bytes.as_slice() {
-			return Err(Error::InvalidPassword);
-		}
-	}
+	let secret = match key {
+		Some(key) => {
+			let plain = vault_file_crypto.decrypt(&key.password)?;
+			let password_hash = key.password.keccak256();
+			if password_hash == plain.as_slice() {
+				Some(key.password.clone())
+			} else {
+				Some(String::from_utf8(plain).map_err(|_| Error::InvalidPassword)?)
+			}
+		},
+		None => None,
+	};
 
-	Ok(vault_file_meta)
+	Ok((vault_file_meta, secret))
 }
 
 #[cfg(test)]
@@ -284,7 +367,7 @@ mod test {
 	use std::fs;
 	use std::io::Write;
 	use std::path::PathBuf;
-	use dir::VaultKey;
+	use dir::{VaultKey, VaultKeyDirectory};
 	use super::{VAULT_FILE_NAME, check_vault_name, make_vault_dir_path, create_vault_file, read_vault_file, VaultDiskDirectory};
 	use self::tempdir::TempDir;
 
@@ -330,7 +413,7 @@ mod test {
 		fs::create_dir_all(&vault_dir).unwrap();
 
 		// when
-		let result = create_vault_file(&vault_dir, &key, "{}");
+		let result = create_vault_file(&vault_dir, &key, &key.password, "{}");
 
 		// then
 		assert!(result.is_ok());
@@ -439,4 +522,32 @@ mod test {
 		// then
 		assert!(vault.is_err());
 	}
+
+	#[test]
+	fn vault_key_rotation_generates_new_secret() {
+		// given
+		let temp_path = TempDir::new("").unwrap();
+		let key = VaultKey::new("password", 1024);
+		let dir: PathBuf = temp_path.path().into();
+		let vault = VaultDiskDirectory::create(&dir, "vault", key.clone()).unwrap();
+		assert_eq!(vault.key_manager().secret, "password");
+
+		// when
+		let new_key = VaultKey::new("new_password", 1024);
+		vault.rotate_key(new_key.clone()).unwrap();
+
+		// then
+		assert!(VaultDiskDirectory::at(&dir, "vault", key).is_err());
+		let vault = VaultDiskDirectory::at(&dir, "vault", new_key).unwrap();
+		let secret = vault.key_manager().secret.clone();
+		assert!(secret != "password" && secret != "new_password");
+
+		// and when
+		let another_key = VaultKey::new("another_password", 1024);
+		vault.set_key(another_key.clone()).unwrap();
+
+		// then
+		let vault = VaultDiskDirectory::at(&dir, "vault", another_key).unwrap();
+		assert_eq!(vault.key_manager().secret, secret);
+	}
 }
@@ -130,6 +130,10 @@ impl SimpleSecretStore for EthStore {
 		self.store.change_vault_password(name, new_password)
 	}
 
+	fn rotate_vault_key(&self, name: &str, password: &str, new_password: &str) -> Result<(), Error> {
+		self.store.rotate_vault_key(name, password, new_password)
+	}
+
 	fn change_account_vault(&self, vault: SecretVaultRef, account: StoreAccountRef) -> Result<StoreAccountRef, Error> {
 		self.store.change_account_vault(vault, account)
 	}
@@ -410,6 +414,30 @@ impl EthMultiStore {
 		}
 		Ok(extended)
 	}
+
+	fn set_vault_key<F>(&self, name: &str, new_password: &str, set_key: F) -> Result<(), Error>
+		where F: FnOnce(&VaultKeyDirectory, VaultKey) -> Result<(), SetKeyError>
+	{
+		let old_key = self.vaults.lock().get(name).map(|v| v.key()).ok_or(Error::VaultNotFound)?;
+		let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
+		let vault = vault_provider.open(name, old_key)?;
+		match set_key(&*vault, VaultKey::new(new_password, self.iterations)) {
+			Ok(_) => {
+				self.close_vault(name)
+					.and_then(|_| self.open_vault(name, new_password))
+			},
+			Err(SetKeyError::Fatal(err)) => {
+				let _ = self.close_vault(name);
+				Err(err)
+			},
+			Err(SetKeyError::NonFatalNew(err)) => {
+				let _ = self.close_vault(name)
+					.and_then(|_| self.open_vault(name, new_password));
+				Err(err)
+			},
+			Err(SetKeyError::NonFatalOld(err)) => Err(err),
+		}
+	}
 }
 
 impl SimpleSecretStore for EthMultiStore {
@@ -579,25 +607,16 @@ impl SimpleSecretStore for EthMultiStore {
 	}
 
 	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error> {
+		self.set_vault_key(name, new_password, |vault, key| vault.set_key(key))
+	}
+
+	fn rotate_vault_key(&self, name: &str, password: &str, new_password: &str) -> Result<(), Error> {
 		let old_key = self.vaults.lock().get(name).map(|v| v.key()).ok_or(Error::VaultNotFound)?;
-		let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
-		let vault = vault_provider.open(name, old_key)?;
-		match vault.set_key(VaultKey::new(new_password, self.iterations)) {
-			Ok(_) => {
-				self.close_vault(name)
-					.and_then(|_| self.open_vault(name, new_password))
-			},
-			Err(SetKeyError::Fatal(err)) => {
-				let _ = self.close_vault(name);
-				Err(err)
-			},
-			Err(SetKeyError::NonFatalNew(err)) => {
-				let _ = self.close_vault(name)
-					.and_then(|_| self.open_vault(name, new_password));
-				Err(err)
-			},
-			Err(SetKeyError::NonFatalOld(err)) => Err(err),
+		if !::crypto::is_equal(old_key.password.as_bytes(), password.as_bytes()) {
+			return Err(Error::InvalidPassword);
 		}
+
+		self.set_vault_key(name, new_password, |vault, key| vault.rotate_key(key))
 	}
 
 	fn change_account_vault(&self, vault: SecretVaultRef, account_ref: StoreAccountRef) -> Result<StoreAccountRef, Error> {
@@ -941,6 +960,35 @@ mod tests {
 		assert_eq!(store.accounts().unwrap().len(), 1);
 	}
 
+	#[test]
+	fn should_rotate_vault_key() {
+		// given
+		let mut dir = RootDiskDirectoryGuard::new();
+		let store = EthStore::open(dir.key_dir.take().unwrap()).unwrap();
+		let name = "vault"; let password = "password";
+		let keypair = keypair();
+		store.create_vault(name, password).unwrap();
+		let account = store.insert_account(SecretVaultRef::Vault(name.to_owned()), keypair.secret().clone(), "account_password").unwrap();
+
+		// when
+		let new_password = "new_password";
+		assert!(store.rotate_vault_key(name, "bad_password", new_password).is_err());
+		store.rotate_vault_key(name, password, new_password).unwrap();
+
+		// then
+		assert_eq!(store.list_vaults().unwrap(), vec![name.to_owned()]);
+		assert_eq!(store.accounts().unwrap(), vec![account.clone()]);
+		assert!(store.test_password(&account, "account_password").unwrap());
+
+		// and when
+		store.close_vault(name).unwrap();
+
+		// then
+		assert!(store.open_vault(name, password).is_err());
+		store.open_vault(name, new_password).unwrap();
+		assert_eq!(store.accounts().unwrap().len(), 1);
+	}
+
 	#[test]
 	fn should_have_different_passwords_for_vault_secret_and_meta() {
 		// given
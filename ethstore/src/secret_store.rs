@@ -81,6 +81,9 @@ pub trait SimpleSecretStore: Send + Sync {
 	fn list_opened_vaults(&self) -> Result<Vec<String>, Error>;
 	/// Change vault password
 	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error>;
+	/// Re-encrypt all keys in the vault under a freshly generated random key, stored encrypted with new password.
+	/// Vault content is restored if rotation fails.
+	fn rotate_vault_key(&self, name: &str, password: &str, new_password: &str) -> Result<(), Error>;
 	/// Cnage account' vault
 	fn change_account_vault(&self, vault: SecretVaultRef, account: StoreAccountRef) -> Result<StoreAccountRef, Error>;
 	/// Get vault metadata string.
@@ -145,6 +145,50 @@ The following options are possible for the \`defaultBlock\` parameter:
     }
   },
 
+  feeHistory: {
+    desc: 'Returns gas prices paid in a range of blocks.',
+    params: [
+      {
+        type: Quantity,
+        desc: 'Number of blocks in the range, at most 1024.',
+        example: fromDecimal(4)
+      },
+      {
+        type: BlockNumber,
+        desc: 'Newest block of the range, integer block number, or the string `\'latest\'`, `\'earliest\'` or `\'pending\'`.',
+        format: 'inputDefaultBlockNumberFormatter'
+      },
+      {
+        type: Array,
+        desc: 'Increasing percentiles (between 0 and 100) of gas prices to return for each block, weighted by gas used.',
+        example: [25, 75],
+        optional: true
+      }
+    ],
+    returns: {
+      type: Object,
+      desc: 'Fee history.',
+      details: {
+        oldestBlock: {
+          type: Quantity,
+          desc: 'Number of the first block of the range.'
+        },
+        baseFeePerGas: {
+          type: Array,
+          desc: 'Base fee of each block of the range and of the next block. Always zero.'
+        },
+        gasUsedRatio: {
+          type: Array,
+          desc: 'Ratio of the gas used to the gas limit of each block.'
+        },
+        reward: {
+          type: Array,
+          desc: 'Requested gas price percentiles of each block. Omitted when no percentiles were requested.'
+        }
+      }
+    }
+  },
+
   gasPrice: {
     desc: 'Returns the current price per gas in wei.',
     params: [],
@@ -314,6 +358,21 @@ The following options are possible for the \`defaultBlock\` parameter:
     returns: 'See [eth_getBlockByHash](#eth_getblockbyhash)'
   },
 
+  getBlockReceipts: {
+    desc: 'Returns receipts of all transactions in a block.',
+    params: [
+      {
+        type: BlockNumber,
+        desc: 'Block hash, integer block number, or the string `\'latest\'`, `\'earliest\'` or `\'pending\'`.',
+        example: fromDecimal(436)
+      }
+    ],
+    returns: {
+      type: Array,
+      desc: 'Array of transaction receipts (see [eth_getTransactionReceipt](#eth_gettransactionreceipt)) ordered by transaction index, or `null` when the block was not found.'
+    }
+  },
+
   getBlockTransactionCountByHash: {
     desc: 'Returns the number of transactions in a block from a block matching the given block hash.',
     params: [
@@ -784,7 +843,7 @@ The following options are possible for the \`defaultBlock\` parameter:
     params: [],
     returns: {
       type: Array,
-      desc: 'Array with the following properties:\n  - `Data`, 32 Bytes - current block header pow-hash.\n  - `Data`, 32 Bytes - the seed hash used for the DAG.\n  - `Data`, 32 Bytes - the boundary condition ("target"), 2^256 / difficulty.\n  - `Quantity`, the current block number.',
+      desc: 'Array with the following properties:\n  - `Data`, 32 Bytes - current block header pow-hash.\n  - `Data`, 32 Bytes - the seed hash used for the DAG.\n  - `Data`, 32 Bytes - the boundary condition ("target"), 2^256 / difficulty.\n  - `Quantity`, the current block number. Omitted in geth compatibility mode, except for blocks sealed with ProgPoW, which requires it to derive the program.',
       example: [
         '0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef',
         '0x5EED00000000000000000000000000005EED0000000000000000000000000000',
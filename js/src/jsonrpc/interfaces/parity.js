@@ -117,6 +117,33 @@ export default {
     }
   },
 
+  rotateVaultKey: {
+    section: SECTION_VAULT,
+    desc: 'Re-encrypts all keys in the vault under a fresh key and new password. The vault is left untouched on failure.',
+    params: [
+      {
+        type: String,
+        desc: 'Vault name',
+        example: 'StrongVault'
+      },
+      {
+        type: String,
+        desc: 'Current Password',
+        example: 'p@55w0rd'
+      },
+      {
+        type: String,
+        desc: 'New Password',
+        example: 'n3wp@55w0rd'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'True on success',
+      example: true
+    }
+  },
+
   closeVault: {
     section: SECTION_VAULT,
     desc: 'Closes a vault with the given name',
@@ -419,6 +446,17 @@ export default {
     }
   },
 
+  journaledTransactions: {
+    desc: 'Returns local transactions persisted by the node which are not mined yet.',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Journaled transactions',
+      details: TransactionResponse.details,
+      example: [new Dummy('{ ... }')]
+    }
+  },
+
   minGasPrice: {
     section: SECTION_MINING,
     desc: 'Returns currently set minimal gas price',
@@ -572,6 +610,36 @@ export default {
     }
   },
 
+  nonceGaps: {
+    section: SECTION_NET,
+    desc: 'Returns nonce gaps of transactions from given account in the transaction queue, together with the transactions blocked behind each gap.',
+    params: [
+      {
+        type: Address,
+        desc: 'Account',
+        example: '0x00A289B43e1e4825DbEDF2a78ba60a640634DC40'
+      }
+    ],
+    returns: {
+      type: Object,
+      desc: 'Nonce status of the account.',
+      details: {
+        chainNonce: {
+          type: Quantity,
+          desc: 'Nonce of the account in the latest block.'
+        },
+        pendingNonces: {
+          type: Array,
+          desc: 'Sorted nonces of transactions present in the queue.'
+        },
+        gaps: {
+          type: Array,
+          desc: 'Missing nonce ranges (`from` and `to` inclusive) with the `blocked` transactions waiting for each of them.'
+        }
+      }
+    }
+  },
+
   nodeName: {
     section: SECTION_NODE,
     desc: 'Returns node name, set when starting parity with `--identity NAME`.',
@@ -706,6 +774,21 @@ export default {
     }
   },
 
+  cancelScheduledTransaction: {
+    section: SECTION_NET,
+    desc: 'Cancels a transaction which block or time condition is not met yet. The cancellation is persisted, so the transaction is not re-queued after restart.',
+    params: [{
+      type: Hash,
+      desc: 'Hash of transaction to cancel.',
+      example: '0x2547ea3382099c7c76d33dd468063b32d41016aacb02cbd51ebc14ff5d2b6a43'
+    }],
+    returns: {
+      type: Boolean,
+      desc: '`true` if the transaction was canceled, `false` if no such transaction is scheduled.',
+      example: true
+    }
+  },
+
   phraseToAddress: {
     section: SECTION_ACCOUNTS,
     desc: 'Converts a secret phrase into the corresponding address.',
@@ -809,6 +892,118 @@ export default {
     }
   },
 
+  stratumWorkers: {
+    section: SECTION_MINING,
+    desc: 'Returns share statistics of the workers connected to the stratum server. Empty if stratum is disabled.',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Connected workers',
+      details: {
+        id: {
+          type: String,
+          desc: 'Worker name used for authorization'
+        },
+        address: {
+          type: String,
+          desc: 'Remote address of the worker'
+        },
+        difficulty: {
+          type: Quantity,
+          desc: 'Current share difficulty or `null` if variable difficulty is disabled'
+        },
+        accepted: {
+          type: Quantity,
+          desc: 'Number of accepted shares'
+        },
+        stale: {
+          type: Quantity,
+          desc: 'Number of shares submitted for outdated jobs'
+        },
+        invalid: {
+          type: Quantity,
+          desc: 'Number of invalid shares'
+        },
+        blocks: {
+          type: Quantity,
+          desc: 'Number of shares which sealed a block'
+        }
+      },
+      example: [{
+        id: 'miner1',
+        address: '127.0.0.1:4000',
+        difficulty: fromDecimal(1000000),
+        accepted: 10,
+        stale: 2,
+        invalid: 1,
+        blocks: 0
+      }]
+    }
+  },
+
+  transactionsBans: {
+    section: SECTION_MINING,
+    desc: 'Returns senders, recipients and contract codes currently banned by the transaction queue.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'Current bans',
+      details: {
+        senders: {
+          type: Array,
+          desc: 'Banned senders'
+        },
+        recipients: {
+          type: Array,
+          desc: 'Banned recipients'
+        },
+        codes: {
+          type: Array,
+          desc: 'Hashes of banned contract codes'
+        }
+      },
+      example: {
+        senders: ['0x00a329c0648769a73afac7f9381e08fb43dbea72'],
+        recipients: [],
+        codes: []
+      }
+    }
+  },
+
+  transactionsEvictions: {
+    section: SECTION_MINING,
+    desc: 'Returns number of transactions dropped from the queue because of its limits.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'Number of dropped transactions by reason',
+      details: {
+        countLimit: {
+          type: Quantity,
+          desc: 'Dropped because the queue was full'
+        },
+        memoryLimit: {
+          type: Quantity,
+          desc: 'Dropped because of the memory limit'
+        },
+        gasLimit: {
+          type: Quantity,
+          desc: 'Dropped because of the total gas limit'
+        },
+        senderLimit: {
+          type: Quantity,
+          desc: 'Rejected because the sender reached its limits'
+        }
+      },
+      example: {
+        countLimit: 0,
+        memoryLimit: 12,
+        gasLimit: 0,
+        senderLimit: 3
+      }
+    }
+  },
+
   transactionsLimit: {
     section: SECTION_MINING,
     desc: 'Changes limit for transactions in queue.',
@@ -994,6 +1189,19 @@ export default {
     }
   },
 
+  scheduledTransactions: {
+    desc: 'Returns transactions from transaction queue which block or time conditions are not met yet.',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Transaction list, see [parity_futureTransactions](#parity_futuretransactions).',
+      details: TransactionResponse.details,
+      example: [
+        new Dummy('{ ... }, { ... }, ...')
+      ]
+    }
+  },
+
   /*
    * `parity_accounts` module methods
    * ================================
@@ -1582,6 +1790,23 @@ export default {
     }
   },
 
+  setExtraDataTemplate: {
+    subdoc: SUBDOC_SET,
+    desc: 'Changes extra data for newly mined blocks to a template expanded when the block is prepared. Variables: `{version}`, `{chain}`, `{tag}`, `{counter}` (number of blocks sealed by the node); use `{{` and `}}` for literal braces. Fails if the expanded extra data exceeds the engine limit.',
+    params: [
+      {
+        type: String,
+        desc: 'Extra Data template',
+        example: '{tag}/{chain}#{counter}'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'whether the call was successful',
+      example: true
+    }
+  },
+
   setAuthor: {
     subdoc: SUBDOC_SET,
     desc: 'Changes author (coinbase) for mined blocks.',
@@ -1636,6 +1861,114 @@ export default {
     }
   },
 
+  clearTransactionsBans: {
+    subdoc: SUBDOC_SET,
+    desc: 'Lifts all bans of senders, recipients and contract codes in the transaction queue.',
+    params: [],
+    returns: {
+      type: Boolean,
+      desc: 'whether the call was successful',
+      example: true
+    }
+  },
+
+  inclusionFilter: {
+    subdoc: SUBDOC_SET,
+    desc: 'Returns allow and deny lists of transactions included in authored blocks.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'The inclusion filter',
+      details: {
+        allow: {
+          type: Object,
+          desc: '`senders`, `recipients` and `contracts` - if a list is not empty only matching transactions are included'
+        },
+        deny: {
+          type: Object,
+          desc: '`senders`, `recipients` and `contracts` - matching transactions are never included'
+        }
+      },
+      example: {
+        allow: { senders: [], recipients: [], contracts: [] },
+        deny: { senders: [], recipients: ['0x0000000000000000000000000000000000000005'], contracts: [] }
+      }
+    }
+  },
+
+  setInclusionFilter: {
+    subdoc: SUBDOC_SET,
+    desc: 'Sets allow and deny lists of transactions included in authored blocks. Omitted lists are cleared.',
+    params: [
+      {
+        type: Object,
+        desc: 'The inclusion filter, see `parity_inclusionFilter`',
+        example: {
+          deny: { recipients: ['0x0000000000000000000000000000000000000005'] }
+        }
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'whether the call was successful',
+      example: true
+    }
+  },
+
+  setTransactionsMemoryLimit: {
+    subdoc: SUBDOC_SET,
+    desc: 'Changes memory limit (in bytes) for transactions in queue.',
+    params: [
+      {
+        type: Quantity,
+        desc: 'New limit in bytes',
+        format: 'utils.toHex',
+        example: fromDecimal(4194304)
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'whether the call was successful',
+      example: true
+    }
+  },
+
+  setSenderTransactionsLimit: {
+    subdoc: SUBDOC_SET,
+    desc: 'Changes limit for external transactions of a single sender in queue.',
+    params: [
+      {
+        type: Quantity,
+        desc: 'New limit',
+        format: 'utils.toHex',
+        example: fromDecimal(16)
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'whether the call was successful',
+      example: true
+    }
+  },
+
+  setSenderTransactionsMemoryLimit: {
+    subdoc: SUBDOC_SET,
+    desc: 'Changes memory limit (in bytes) for external transactions of a single sender in queue.',
+    params: [
+      {
+        type: Quantity,
+        desc: 'New limit in bytes',
+        format: 'utils.toHex',
+        example: fromDecimal(65536)
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: 'whether the call was successful',
+      example: true
+    }
+  },
+
   addReservedPeer: {
     subdoc: SUBDOC_SET,
     desc: 'Add a reserved peer.',
@@ -17,6 +17,7 @@
 //! Authority params deserialization.
 
 use uint::Uint;
+use hash::Address;
 use super::ValidatorSet;
 
 /// Authority params deserialization.
@@ -40,6 +41,9 @@ pub struct AuthorityRoundParams {
 	/// Whether transitions should be immediate.
 	#[serde(rename="immediateTransitions")]
 	pub immediate_transitions: Option<bool>,
+	/// Contract receiving misbehaviour reports. Reports go to the validator set if not specified.
+	#[serde(rename="reportingContract")]
+	pub reporting_contract: Option<Address>,
 }
 
 /// Authority engine deserialization.
@@ -67,7 +71,8 @@ mod tests {
 					"list" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
 				},
 				"startStep" : 24,
-				"validateStepTransition": 150
+				"validateStepTransition": 150,
+				"reportingContract": "0x0000000000000000000000000000000000000005"
 			}
 		}"#;
 
@@ -76,5 +81,6 @@ mod tests {
 		assert_eq!(deserialized.params.validators, ValidatorSet::List(vec![Address(H160::from("0xc6d9d2cd449a754c494264e1809c50e34d64562b"))]));
 		assert_eq!(deserialized.params.start_step, Some(Uint(U256::from(24))));
 		assert_eq!(deserialized.params.immediate_transitions, None);
+		assert_eq!(deserialized.params.reporting_contract, Some(Address(H160::from(5))));
 	}
 }
@@ -34,6 +34,22 @@ pub struct Modexp {
 	pub divisor: usize,
 }
 
+/// Pricing for alt_bn128 pairing check.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct AltBn128Pairing {
+	/// Base price.
+	pub base: usize,
+	/// Price per point pair.
+	pub pair: usize,
+}
+
+/// Pricing for Blake2 F compression.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct Blake2F {
+	/// Price per round.
+	pub gas_per_round: u64,
+}
+
 /// Pricing variants.
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 pub enum Pricing {
@@ -43,6 +59,12 @@ pub enum Pricing {
 	/// Pricing for modular exponentiation.
 	#[serde(rename="modexp")]
 	Modexp(Modexp),
+	/// Pricing for alt_bn128 pairing check.
+	#[serde(rename="alt_bn128_pairing")]
+	AltBn128Pairing(AltBn128Pairing),
+	/// Pricing for Blake2 F compression.
+	#[serde(rename="blake2_f")]
+	Blake2F(Blake2F),
 }
 
 /// Spec builtin.
@@ -59,7 +81,7 @@ pub struct Builtin {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use spec::builtin::{Builtin, Pricing, Linear, Modexp};
+	use spec::builtin::{Builtin, Pricing, Linear, Modexp, AltBn128Pairing, Blake2F};
 	use uint::Uint;
 
 	#[test]
@@ -87,4 +109,30 @@ mod tests {
 		assert_eq!(deserialized.pricing, Pricing::Modexp(Modexp { divisor: 5 }));
 		assert_eq!(deserialized.activate_at, Some(Uint(100000.into())));
 	}
+
+	#[test]
+	fn alt_bn128_pairing_pricing() {
+		let s = r#"{
+			"name": "bn128_pairing",
+			"activate_at": 10,
+			"pricing": { "alt_bn128_pairing": { "base": 45000, "pair": 34000 } }
+		}"#;
+
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.pricing, Pricing::AltBn128Pairing(AltBn128Pairing { base: 45000, pair: 34000 }));
+	}
+
+	#[test]
+	fn blake2_f_pricing() {
+		let s = r#"{
+			"name": "blake2_f",
+			"activate_at": "0xffffff",
+			"pricing": { "blake2_f": { "gas_per_round": 1 } }
+		}"#;
+
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.name, "blake2_f");
+		assert_eq!(deserialized.pricing, Pricing::Blake2F(Blake2F { gas_per_round: 1 }));
+		assert_eq!(deserialized.activate_at, Some(Uint(0xffffff.into())));
+	}
 }
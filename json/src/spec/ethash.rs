@@ -111,6 +111,14 @@ pub struct EthashParams {
 	/// See main EthashParams docs.
 	#[serde(rename="minGasPrice")]
 	pub min_gas_price: Option<Uint>,
+
+	/// See main EthashParams docs.
+	#[serde(rename="progpowTransition")]
+	pub progpow_transition: Option<Uint>,
+
+	/// See main EthashParams docs.
+	#[serde(rename="cpuSealing")]
+	pub cpu_sealing: Option<bool>,
 }
 
 /// Ethash engine deserialization.
@@ -221,6 +229,8 @@ mod tests {
 				max_gas_limit: None,
 				min_gas_price_transition: None,
 				min_gas_price: None,
+				progpow_transition: None,
+				cpu_sealing: None,
 			}
 		});
 	}
@@ -262,6 +272,8 @@ mod tests {
 				max_gas_limit: None,
 				min_gas_price_transition: None,
 				min_gas_price: None,
+				progpow_transition: None,
+				cpu_sealing: None,
 			}
 		});
 	}
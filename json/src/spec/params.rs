@@ -95,12 +95,18 @@ pub struct Params {
 	/// Wasm support flag
 	pub wasm: Option<bool>,
 	/// See `CommonParams` docs.
+	#[serde(rename="wasmSchedule")]
+	pub wasm_schedule: Option<WasmSchedule>,
+	/// See `CommonParams` docs.
 	#[serde(rename="gasLimitBoundDivisor")]
 	pub gas_limit_bound_divisor: Uint,
 	/// See `CommonParams` docs.
 	#[serde(rename="blockReward")]
 	pub block_reward: Option<Uint>,
 	/// See `CommonParams` docs.
+	#[serde(rename="blockRewardContract")]
+	pub block_reward_contract: Option<Address>,
+	/// See `CommonParams` docs.
 	pub registrar: Option<Address>,
 	/// Apply reward flag
 	#[serde(rename="applyReward")]
@@ -111,14 +117,63 @@ pub struct Params {
 	/// Transaction permission contract address.
 	#[serde(rename="transactionPermissionContract")]
 	pub transaction_permission_contract: Option<Address>,
+	/// See `CommonParams` docs.
+	#[serde(rename="customOpcodes")]
+	pub custom_opcodes: Option<Vec<CustomOpcode>>,
+	/// See `CommonParams` docs.
+	#[serde(rename="maxTransactionSize")]
+	pub max_transaction_size: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="maxCodeSize")]
+	pub max_code_size: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="trieHasher")]
+	pub trie_hasher: Option<String>,
+}
+
+/// WASM VM gas costs and host function availability.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct WasmSchedule {
+	/// Gas charged per unit reported by the injected gas counter.
+	pub regular: Option<Uint>,
+	/// Gas charged per byte allocated by the contract.
+	pub mem: Option<Uint>,
+	/// Gas charged for a storage read.
+	#[serde(rename="storageRead")]
+	pub storage_read: Option<Uint>,
+	/// Gas charged for a storage write.
+	#[serde(rename="storageWrite")]
+	pub storage_write: Option<Uint>,
+	/// Optional host functions exposed to contracts.
+	#[serde(rename="hostFunctions")]
+	pub host_functions: Option<Vec<String>>,
+}
+
+/// Additional EVM instruction activated by the chain.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct CustomOpcode {
+	/// The opcode, must not be used by any standard instruction.
+	pub opcode: Uint,
+	/// Name of the registered implementation.
+	pub name: String,
+	/// Number of stack arguments.
+	pub args: Uint,
+	/// Number of returned stack items.
+	pub ret: Uint,
+	/// Gas charged for the instruction.
+	pub gas: Uint,
+	/// Block at which the instruction is activated, defaults to 0.
+	#[serde(rename="activateAt")]
+	pub activate_at: Option<Uint>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use uint::Uint;
-	use bigint::prelude::U256;
-	use spec::params::Params;
+	use bigint::prelude::{U256, H160};
+	use hash::Address;
+	use spec::params::{Params, WasmSchedule, CustomOpcode};
 
 	#[test]
 	fn params_deserialization() {
@@ -129,7 +184,11 @@ mod tests {
 			"subprotocolName" : "exp",
 			"minGasLimit": "0x1388",
 			"accountStartNonce": "0x01",
-			"gasLimitBoundDivisor": "0x20"
+			"gasLimitBoundDivisor": "0x20",
+			"blockRewardContract": "0x0000000000000000000000000000000000000042",
+			"maxTransactionSize": "0x100000",
+			"maxCodeSize": "0x8000",
+			"trieHasher": "keccak"
 		}"#;
 
 		let deserialized: Params = serde_json::from_str(s).unwrap();
@@ -140,5 +199,62 @@ mod tests {
 		assert_eq!(deserialized.min_gas_limit, Uint(U256::from(0x1388)));
 		assert_eq!(deserialized.account_start_nonce, Some(Uint(U256::from(0x01))));
 		assert_eq!(deserialized.gas_limit_bound_divisor, Uint(U256::from(0x20)));
+		assert_eq!(deserialized.block_reward_contract, Some(Address(H160::from("0x0000000000000000000000000000000000000042"))));
+		assert_eq!(deserialized.max_transaction_size, Some(Uint(U256::from(0x100000))));
+		assert_eq!(deserialized.max_code_size, Some(Uint(U256::from(0x8000))));
+		assert_eq!(deserialized.trie_hasher, Some("keccak".to_owned()));
+	}
+
+	#[test]
+	fn wasm_schedule_deserialization() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID" : "0x1",
+			"minGasLimit": "0x1388",
+			"gasLimitBoundDivisor": "0x20",
+			"wasm": true,
+			"wasmSchedule": {
+				"regular": "0x2",
+				"storageWrite": "0x1388",
+				"hostFunctions": ["_blocknumber", "_blockhash"]
+			}
+		}"#;
+
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.wasm_schedule, Some(WasmSchedule {
+			regular: Some(Uint(U256::from(2))),
+			mem: None,
+			storage_read: None,
+			storage_write: Some(Uint(U256::from(5000))),
+			host_functions: Some(vec!["_blocknumber".into(), "_blockhash".into()]),
+		}));
+	}
+
+	#[test]
+	fn custom_opcodes_deserialization() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID" : "0x1",
+			"minGasLimit": "0x1388",
+			"gasLimitBoundDivisor": "0x20",
+			"customOpcodes": [{
+				"opcode": "0xc0",
+				"name": "sqrt",
+				"args": 1,
+				"ret": 1,
+				"gas": "0x64",
+				"activateAt": 10
+			}]
+		}"#;
+
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.custom_opcodes, Some(vec![CustomOpcode {
+			opcode: Uint(U256::from(0xc0)),
+			name: "sqrt".into(),
+			args: Uint(U256::from(1)),
+			ret: Uint(U256::from(1)),
+			gas: Uint(U256::from(100)),
+			activate_at: Some(Uint(U256::from(10))),
+		}]));
 	}
 }
@@ -39,6 +39,9 @@ pub struct Spec {
 	pub accounts: State,
 	/// Boot nodes.
 	pub nodes: Option<Vec<String>>,
+	/// Signed DNS node lists (`enrtree://<key>@<domain>`).
+	#[serde(rename="nodeLists")]
+	pub node_lists: Option<Vec<String>>,
 }
 
 impl Spec {
@@ -98,6 +101,9 @@ mod tests {
 	"nodes": [
 		"enode://b1217cbaa440e35ed471157123fe468e19e8b5ad5bedb4b1fdbcbdab6fb2f5ed3e95dd9c24a22a79fdb2352204cea207df27d92bfd21bfd41545e8b16f637499@104.44.138.37:30303"
 	],
+	"nodeLists": [
+		"enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@nodes.example.org"
+	],
 	"accounts": {
 		"0000000000000000000000000000000000000001": { "balance": "1", "nonce": "1048576", "builtin": { "name": "ecrecover", "pricing": { "linear": { "base": 3000, "word": 0 } } } },
 		"0000000000000000000000000000000000000002": { "balance": "1", "nonce": "1048576", "builtin": { "name": "sha256", "pricing": { "linear": { "base": 60, "word": 12 } } } },
@@ -106,7 +112,8 @@ mod tests {
 		"102e61f5d8f9bc71d0ad4a084df4e65e05ce0e1c": { "balance": "1606938044258990275541962092341162602522202993782792835301376", "nonce": "1048576" }
 	}
 		}"#;
-		let _deserialized: Spec = serde_json::from_str(s).unwrap();
+		let deserialized: Spec = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.node_lists.unwrap().len(), 1);
 		// TODO: validate all fields
 	}
 }
@@ -29,6 +29,10 @@ pub enum ValidatorSet {
 	/// Address of a contract that indicates the list of authorities.
 	#[serde(rename="safeContract")]
 	SafeContract(Address),
+	/// Address of a contract that indicates the list of authorities, with changes taking effect
+	/// at the block after the signalling block instead of waiting for finality.
+	#[serde(rename="immediateSafeContract")]
+	ImmediateSafeContract(Address),
 	/// Address of a contract that indicates the list of authorities and enables reporting of theor misbehaviour using transactions.
 	#[serde(rename="contract")]
 	Contract(Address),
@@ -59,10 +63,12 @@ mod tests {
 				"10": { "list": ["0xd6d9d2cd449a754c494264e1809c50e34d64562b"] },
 				"20": { "contract": "0xc6d9d2cd449a754c494264e1809c50e34d64562b" }
 			}
+		}, {
+			"immediateSafeContract": "0xc6d9d2cd449a754c494264e1809c50e34d64562b"
 		}]"#;
 
 		let deserialized: Vec<ValidatorSet> = serde_json::from_str(s).unwrap();
-		assert_eq!(deserialized.len(), 4);
+		assert_eq!(deserialized.len(), 5);
 
 		assert_eq!(deserialized[0], ValidatorSet::List(vec![Address(H160::from("0xc6d9d2cd449a754c494264e1809c50e34d64562b"))]));
 		assert_eq!(deserialized[1], ValidatorSet::SafeContract(Address(H160::from("0xc6d9d2cd449a754c494264e1809c50e34d64562b"))));
@@ -76,5 +82,6 @@ mod tests {
 			},
 			_ => assert!(false),
 		}
+		assert_eq!(deserialized[4], ValidatorSet::ImmediateSafeContract(Address(H160::from("0xc6d9d2cd449a754c494264e1809c50e34d64562b"))));
 	}
 }
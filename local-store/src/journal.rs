@@ -0,0 +1,163 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Append-only journal of local transactions.
+//!
+//! Every record is a single JSON line and each append is synced to disk before returning,
+//! so transactions survive a crash between the periodic database updates.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+
+use {Error, TransactionEntry};
+
+/// The journal file.
+pub struct Journal {
+	path: PathBuf,
+	file: Mutex<File>,
+}
+
+impl Journal {
+	/// Opens (or creates) the journal at given path.
+	///
+	/// Existing records are compacted, dropping any record torn by a crash.
+	pub fn open(path: &Path) -> Result<Self, Error> {
+		let entries = read_entries(path)?;
+		write_entries(path, &entries)?;
+
+		Ok(Journal {
+			path: path.to_owned(),
+			file: Mutex::new(open_for_append(path)?),
+		})
+	}
+
+	/// Reads all the records.
+	pub fn read(&self) -> Result<Vec<TransactionEntry>, Error> {
+		let _file = self.file.lock();
+		read_entries(&self.path)
+	}
+
+	/// Appends records and waits until they are on disk.
+	pub fn append(&self, entries: &[TransactionEntry]) -> Result<(), Error> {
+		let buf = encode(entries)?;
+		let mut file = self.file.lock();
+		file.write_all(&buf)?;
+		file.sync_data()?;
+		Ok(())
+	}
+
+	/// Atomically replaces all the records.
+	pub fn rewrite(&self, entries: &[TransactionEntry]) -> Result<(), Error> {
+		let mut file = self.file.lock();
+		write_entries(&self.path, entries)?;
+		*file = open_for_append(&self.path)?;
+		Ok(())
+	}
+}
+
+fn open_for_append(path: &Path) -> io::Result<File> {
+	OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn encode(entries: &[TransactionEntry]) -> Result<Vec<u8>, Error> {
+	let mut buf = Vec::new();
+	for entry in entries {
+		::serde_json::to_writer(&mut buf, entry).map_err(Error::Json)?;
+		buf.push(b'\n');
+	}
+	Ok(buf)
+}
+
+fn read_entries(path: &Path) -> Result<Vec<TransactionEntry>, Error> {
+	let mut bytes = Vec::new();
+	match File::open(path) {
+		Ok(mut file) => { file.read_to_end(&mut bytes)?; },
+		Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e.into()),
+	}
+
+	Ok(bytes.split(|b| *b == b'\n')
+		.filter(|line| !line.is_empty())
+		.filter_map(|line| match ::serde_json::from_slice(line) {
+			Ok(entry) => Some(entry),
+			Err(e) => {
+				warn!(target: "local_store", "Skipping invalid journal record: {}", e);
+				None
+			}
+		})
+		.collect())
+}
+
+fn write_entries(path: &Path, entries: &[TransactionEntry]) -> Result<(), Error> {
+	let tmp = path.with_extension("tmp");
+	{
+		let mut file = File::create(&tmp)?;
+		file.write_all(&encode(entries)?)?;
+		file.sync_all()?;
+	}
+	fs::rename(&tmp, path)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::OpenOptions;
+	use std::io::Write;
+	use devtools::RandomTempPath;
+	use TransactionEntry;
+	use super::Journal;
+
+	fn entry(byte: u8) -> TransactionEntry {
+		TransactionEntry {
+			rlp_bytes: vec![byte],
+			condition: None,
+		}
+	}
+
+	#[test]
+	fn should_skip_torn_records() {
+		let path = RandomTempPath::new();
+		{
+			let journal = Journal::open(path.as_path()).unwrap();
+			journal.append(&[entry(1), entry(2)]).unwrap();
+		}
+		// simulate a crash in the middle of a write
+		OpenOptions::new().append(true).open(path.as_path()).unwrap().write_all(b"{\"rlp_by").unwrap();
+
+		let journal = Journal::open(path.as_path()).unwrap();
+		journal.append(&[entry(3)]).unwrap();
+
+		let bytes: Vec<_> = journal.read().unwrap().into_iter().map(|e| e.rlp_bytes).collect();
+		assert_eq!(bytes, vec![vec![1], vec![2], vec![3]]);
+	}
+
+	#[test]
+	fn should_rewrite_records() {
+		let path = RandomTempPath::new();
+		let journal = Journal::open(path.as_path()).unwrap();
+		journal.append(&[entry(1), entry(2)]).unwrap();
+
+		journal.rewrite(&[entry(2)]).unwrap();
+		journal.append(&[entry(3)]).unwrap();
+
+		let bytes: Vec<_> = journal.read().unwrap().into_iter().map(|e| e.rlp_bytes).collect();
+		assert_eq!(bytes, vec![vec![2], vec![3]]);
+	}
+}
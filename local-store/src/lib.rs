@@ -16,8 +16,10 @@
 
 //! Manages local node data: pending local transactions, sync security level
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::fmt;
+use std::path::Path;
 
 use ethcore::transaction::{
 	SignedTransaction, PendingTransaction, UnverifiedTransaction,
@@ -32,6 +34,7 @@ extern crate ethcore;
 extern crate ethcore_util as util;
 extern crate ethcore_io as io;
 extern crate rlp;
+extern crate parking_lot;
 extern crate serde_json;
 extern crate serde;
 
@@ -43,6 +46,12 @@ extern crate log;
 
 #[cfg(test)]
 extern crate ethkey;
+#[cfg(test)]
+extern crate ethcore_devtools as devtools;
+
+mod journal;
+
+use journal::Journal;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
 
@@ -56,6 +65,14 @@ pub enum Error {
 	Database(String),
 	/// JSON errors.
 	Json(::serde_json::Error),
+	/// Journal I/O errors.
+	Io(::std::io::Error),
+}
+
+impl From<::std::io::Error> for Error {
+	fn from(err: ::std::io::Error) -> Self {
+		Error::Io(err)
+	}
 }
 
 impl fmt::Display for Error {
@@ -63,6 +80,7 @@ impl fmt::Display for Error {
 		match *self {
 			Error::Database(ref val) => write!(f, "{}", val),
 			Error::Json(ref err) => write!(f, "{}", err),
+			Error::Io(ref err) => write!(f, "{}", err),
 		}
 	}
 }
@@ -133,6 +151,13 @@ pub trait NodeInfo: Send + Sync {
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
 }
 
+/// Access to the persisted local transactions.
+pub trait TransactionJournal: Send + Sync {
+	/// Returns all persisted local transactions.
+	/// Might include transactions mined since the last update.
+	fn journaled_transactions(&self) -> Result<Vec<PendingTransaction>, Error>;
+}
+
 /// Create a new local data store, given a database, a column to write to, and a node.
 /// Attempts to read data out of the store, and move it into the node.
 pub fn create<T: NodeInfo>(db: Arc<KeyValueDB>, col: Option<u32>, node: T) -> LocalDataStore<T> {
@@ -140,6 +165,7 @@ pub fn create<T: NodeInfo>(db: Arc<KeyValueDB>, col: Option<u32>, node: T) -> Lo
 		db: db,
 		col: col,
 		node: node,
+		journal: None,
 	}
 }
 
@@ -151,21 +177,43 @@ pub struct LocalDataStore<T: NodeInfo> {
 	db: Arc<KeyValueDB>,
 	col: Option<u32>,
 	node: T,
+	journal: Option<Journal>,
 }
 
 impl<T: NodeInfo> LocalDataStore<T> {
+	/// Additionally journal local transactions to a file at given path.
+	///
+	/// Transactions passed to `journal` are synced to disk immediately,
+	/// instead of waiting for the next periodic update.
+	pub fn open_journal(&mut self, path: &Path) -> Result<(), Error> {
+		self.journal = Some(Journal::open(path)?);
+		Ok(())
+	}
+
 	/// Attempt to read pending transactions out of the local store.
 	pub fn pending_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
-		if let Some(val) = self.db.get(self.col, LOCAL_TRANSACTIONS_KEY).map_err(Error::Database)? {
-			let local_txs: Vec<_> = ::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
-				.map_err(Error::Json)?
-				.into_iter()
-				.filter_map(TransactionEntry::into_pending)
-				.collect();
-
-			Ok(local_txs)
-		} else {
-			Ok(Vec::new())
+		let mut entries = match self.db.get(self.col, LOCAL_TRANSACTIONS_KEY).map_err(Error::Database)? {
+			Some(val) => ::serde_json::from_slice::<Vec<TransactionEntry>>(&val).map_err(Error::Json)?,
+			None => Vec::new(),
+		};
+
+		if let Some(ref journal) = self.journal {
+			entries.extend(journal.read()?);
+		}
+
+		let mut seen = HashSet::new();
+		Ok(entries.into_iter()
+			.filter_map(TransactionEntry::into_pending)
+			.filter(|tx| seen.insert(tx.hash()))
+			.collect())
+	}
+
+	/// Durably record a newly submitted local transaction.
+	/// Does nothing if the store has no journal.
+	pub fn journal(&self, transaction: PendingTransaction) -> Result<(), Error> {
+		match self.journal {
+			Some(ref journal) => journal.append(&[transaction.into()]),
+			None => Ok(()),
 		}
 	}
 
@@ -196,7 +244,19 @@ impl<T: NodeInfo> LocalDataStore<T> {
 		let json_str = format!("{}", local_json);
 
 		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
-		self.db.write(batch).map_err(Error::Database)
+		self.db.write(batch).map_err(Error::Database)?;
+
+		// compact the journal down to the current set, it doesn't rely on the database being synced.
+		match self.journal {
+			Some(ref journal) => journal.rewrite(txs),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<T: NodeInfo> TransactionJournal for LocalDataStore<T> {
+	fn journaled_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
+		self.pending_transactions()
 	}
 }
 
@@ -229,6 +289,7 @@ mod tests {
 	use super::NodeInfo;
 
 	use std::sync::Arc;
+	use devtools::RandomTempPath;
 	use ethcore::transaction::{Transaction, Condition, PendingTransaction};
 	use ethkey::{Brain, Generator};
 
@@ -324,4 +385,36 @@ mod tests {
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn journaled_survive_crash() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..3u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+			PendingTransaction::new(tx.sign(keypair.secret(), None), Some(Condition::Timestamp(10)))
+		}).collect();
+
+		let db = Arc::new(::util::kvdb::in_memory(0));
+		let path = RandomTempPath::new();
+		{
+			let mut store = super::create(db.clone(), None, Dummy(transactions[..1].to_vec()));
+			store.open_journal(path.as_path()).unwrap();
+			store.update().unwrap();
+			store.journal(transactions[1].clone()).unwrap();
+			store.journal(transactions[2].clone()).unwrap();
+			// crash before the update on shutdown.
+			::std::mem::forget(store);
+		}
+		{
+			let mut store = super::create(db.clone(), None, Dummy(vec![]));
+			store.open_journal(path.as_path()).unwrap();
+			assert_eq!(store.pending_transactions().unwrap(), transactions);
+		}
+		{
+			let mut store = super::create(db.clone(), None, Dummy(vec![]));
+			store.open_journal(path.as_path()).unwrap();
+			assert_eq!(store.pending_transactions().unwrap(), vec![]);
+		}
+	}
 }
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::{FromStr, from_utf8};
-use std::{io, fs};
+use std::{io, fs, cmp};
 use std::io::{BufReader, BufRead};
 use std::time::{Instant, Duration};
 use std::thread::sleep;
@@ -66,6 +66,9 @@ impl FromStr for DataFormat {
 #[derive(Debug, PartialEq)]
 pub enum BlockchainCmd {
 	Kill(KillBlockchain),
+	Rewind(RewindBlockchain),
+	RebuildBloom(RebuildBloom),
+	Check(CheckBlockchain),
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
@@ -78,6 +81,52 @@ pub struct KillBlockchain {
 	pub pruning: Pruning,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct RewindBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub tracing: Switch,
+	pub fat_db: Switch,
+	pub to: BlockId,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RebuildBloom {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub tracing: Switch,
+	pub fat_db: Switch,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub tracing: Switch,
+	pub fat_db: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+	pub repair: bool,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportBlockchain {
 	pub spec: SpecType,
@@ -135,6 +184,7 @@ pub struct ExportState {
 	pub at: BlockId,
 	pub storage: bool,
 	pub code: bool,
+	pub genesis: bool,
 	pub min_balance: Option<U256>,
 	pub max_balance: Option<U256>,
 }
@@ -142,6 +192,9 @@ pub struct ExportState {
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
+		BlockchainCmd::Rewind(rewind_cmd) => execute_rewind(rewind_cmd),
+		BlockchainCmd::RebuildBloom(rebuild_cmd) => execute_rebuild_bloom(rebuild_cmd),
+		BlockchainCmd::Check(check_cmd) => execute_check(check_cmd),
 		BlockchainCmd::Import(import_cmd) => {
 			if import_cmd.light {
 				execute_import_light(import_cmd)
@@ -621,11 +674,38 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 		None => Box::new(io::stdout()),
 	};
 
+	let export = StateExport {
+		at: cmd.at,
+		storage: cmd.storage,
+		code: cmd.code,
+		genesis: cmd.genesis,
+		min_balance: cmd.min_balance,
+		max_balance: cmd.max_balance,
+	};
+	export_state(&*client, &mut *out, &export)?;
+	info!("Export completed.");
+	Ok(())
+}
+
+/// Accounts and account fields written by a state export.
+struct StateExport {
+	at: BlockId,
+	storage: bool,
+	code: bool,
+	genesis: bool,
+	min_balance: Option<U256>,
+	max_balance: Option<U256>,
+}
+
+fn export_state(client: &BlockChainClient, out: &mut io::Write, cmd: &StateExport) -> Result<(), String> {
 	let mut last: Option<Address> = None;
 	let at = cmd.at;
 	let mut i = 0usize;
 
-	out.write_fmt(format_args!("{{ \"state\": {{", )).expect("Couldn't write to stream.");
+	// chain specification expects `0x`-prefixed values and no derived fields
+	let (section, prefix) = if cmd.genesis { ("accounts", "0x") } else { ("state", "") };
+
+	out.write_fmt(format_args!("{{ \"{}\": {{", section)).expect("Couldn't write to stream.");
 	loop {
 		let accounts = client.list_accounts(at, last.as_ref(), 1000).ok_or("Specified block not found")?;
 		if accounts.is_empty() {
@@ -642,17 +722,21 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 			if i != 0 {
 				out.write(b",").expect("Write error");
 			}
-			out.write_fmt(format_args!("\n\"0x{}\": {{\"balance\": \"{:x}\", \"nonce\": \"{:x}\"", account.hex(), balance, client.nonce(&account, at).unwrap_or_else(U256::zero))).expect("Write error");
+			out.write_fmt(format_args!("\n\"0x{}\": {{\"balance\": \"{}{:x}\", \"nonce\": \"{}{:x}\"", account.hex(), prefix, balance, prefix, client.nonce(&account, at).unwrap_or_else(U256::zero))).expect("Write error");
 			let code = client.code(&account, at).unwrap_or(None).unwrap_or_else(Vec::new);
 			if !code.is_empty() {
-				out.write_fmt(format_args!(", \"code_hash\": \"0x{}\"", keccak(&code).hex())).expect("Write error");
+				if !cmd.genesis {
+					out.write_fmt(format_args!(", \"code_hash\": \"0x{}\"", keccak(&code).hex())).expect("Write error");
+				}
 				if cmd.code {
-					out.write_fmt(format_args!(", \"code\": \"{}\"", code.to_hex())).expect("Write error");
+					out.write_fmt(format_args!(", \"code\": \"{}{}\"", prefix, code.to_hex())).expect("Write error");
 				}
 			}
 			let storage_root = client.storage_root(&account, at).unwrap_or(KECCAK_NULL_RLP);
 			if storage_root != KECCAK_NULL_RLP {
-				out.write_fmt(format_args!(", \"storage_root\": \"0x{}\"", storage_root.hex())).expect("Write error");
+				if !cmd.genesis {
+					out.write_fmt(format_args!(", \"storage_root\": \"0x{}\"", storage_root.hex())).expect("Write error");
+				}
 				if cmd.storage {
 					out.write_fmt(format_args!(", \"storage\": {{")).expect("Write error");
 					let mut last_storage: Option<H256> = None;
@@ -682,7 +766,98 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 		}
 	}
 	out.write_fmt(format_args!("\n}}}}")).expect("Write error");
-	info!("Export completed.");
+	Ok(())
+}
+
+fn execute_rewind(cmd: RewindBlockchain) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config,
+		false
+	)?;
+
+	let client = service.client();
+	let retracted = client.rewind_to(cmd.to)?;
+	let best = client.chain_info();
+	info!("Rewound {} blocks. Best block is now #{} ({})", retracted.len(), best.best_block_number, best.best_block_hash);
+	Ok(())
+}
+
+fn execute_rebuild_bloom(cmd: RebuildBloom) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config,
+		false
+	)?;
+
+	let count = service.client().rebuild_account_bloom().map_err(|e| format!("Failed to rebuild account bloom: {}", e))?;
+	info!("Account bloom rebuilt with {} accounts", count);
+	Ok(())
+}
+
+fn execute_check(cmd: CheckBlockchain) -> Result<(), String> {
+	const BATCH: u64 = 10_000;
+
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config,
+		false
+	)?;
+
+	let client = service.client();
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	let mut corrupted = Vec::new();
+	let mut number = from;
+	while number <= to {
+		let end = cmp::min(number + BATCH, to + 1);
+		for (n, corruption) in client.check_integrity(number, end) {
+			warn!("Block #{} is corrupted: {}", n, corruption);
+			corrupted.push(n);
+		}
+		info!("Checked blocks #{}..#{}", number, end - 1);
+		number = end;
+	}
+
+	let (first, last) = match (corrupted.first(), corrupted.last()) {
+		(Some(first), Some(last)) => (*first, *last),
+		_ => {
+			info!("No corruption found.");
+			return Ok(());
+		},
+	};
+
+	if !cmd.repair {
+		return Err(format!("Found {} corrupted blocks. Run with --repair to download them again.", corrupted.len()));
+	}
+
+	client.forget_blocks(first, last + 1)?;
+	info!("Removed blocks #{}..#{}. They will be downloaded from peers on the next start.", first, last);
 	Ok(())
 }
 
@@ -703,7 +878,14 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 
 #[cfg(test)]
 mod test {
-	use super::DataFormat;
+	use std::env;
+	use serde_json::{self, Value};
+	use bigint::prelude::U256;
+	use bigint::hash::H256;
+	use util::Address;
+	use ethcore::client::{BlockId, TestBlockChainClient};
+	use ethcore::spec::Spec;
+	use super::{DataFormat, StateExport, export_state};
 
 	#[test]
 	fn test_data_format_parsing() {
@@ -711,4 +893,42 @@ mod test {
 		assert_eq!(DataFormat::Binary, "bin".parse().unwrap());
 		assert_eq!(DataFormat::Hex, "hex".parse().unwrap());
 	}
+
+	#[test]
+	fn exported_genesis_accounts_load_as_chain_spec() {
+		let contract = Address::from(0x10);
+		let user = Address::from(0x20);
+		let client = TestBlockChainClient::new();
+		client.set_balance(contract, 1_000.into());
+		client.set_nonce(contract, 1.into());
+		client.set_code(contract, vec![0x60, 0x00, 0x60, 0x00]);
+		client.set_storage(contract, H256::from(1), H256::from(0xff));
+		client.set_balance(user, U256::from(10).pow(20.into()));
+
+		let mut out = Vec::new();
+		export_state(&client, &mut out, &StateExport {
+			at: BlockId::Latest,
+			storage: true,
+			code: true,
+			genesis: true,
+			min_balance: None,
+			max_balance: None,
+		}).unwrap();
+
+		let exported: Value = serde_json::from_slice(&out).unwrap();
+		let mut spec: Value = serde_json::from_str(include_str!("../ethcore/res/null.json")).unwrap();
+		spec["accounts"] = exported["accounts"].clone();
+		let spec = Spec::load(&env::temp_dir(), serde_json::to_string(&spec).unwrap().as_bytes()).unwrap();
+
+		let accounts = spec.genesis_state().get();
+		assert_eq!(accounts.len(), 2);
+		assert_eq!(accounts[&contract].balance, 1_000.into());
+		assert_eq!(accounts[&contract].nonce, 1.into());
+		assert_eq!(accounts[&contract].code, Some(vec![0x60, 0x00, 0x60, 0x00]));
+		assert_eq!(accounts[&contract].storage.get(&H256::from(1)), Some(&H256::from(0xff)));
+		assert_eq!(accounts[&user].balance, U256::from(10).pow(20.into()));
+		assert_eq!(accounts[&user].nonce, 0.into());
+		assert_eq!(accounts[&user].code, Some(vec![]));
+		assert!(accounts[&user].storage.is_empty());
+	}
 }
@@ -0,0 +1,55 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Chain specification commands.
+
+use std::fs::File;
+use std::io::Read;
+use ethcore::spec;
+
+#[derive(Debug, PartialEq)]
+pub struct ValidateSpec {
+	pub file: Option<String>,
+	pub cache_dir: String,
+}
+
+pub fn execute(cmd: ValidateSpec) -> Result<String, String> {
+	let file = cmd.file.ok_or_else(|| "Specify a chain specification file to validate.".to_owned())?;
+	let mut source = String::new();
+	File::open(&file)
+		.and_then(|mut f| f.read_to_string(&mut source))
+		.map_err(|e| format!("Unable to read {}: {}", file, e))?;
+
+	let report = spec::validate(&cmd.cache_dir, &source);
+	let mut output: Vec<String> = report.issues.iter()
+		.map(|issue| match issue.line {
+			Some(line) => format!("{}:{}: {}", file, line, issue),
+			None => format!("{}: {}", file, issue),
+		})
+		.collect();
+
+	match (report.genesis_hash, report.has_errors()) {
+		(Some(hash), false) => {
+			output.push(format!("Genesis hash: 0x{:?}", hash));
+			Ok(output.join("\n"))
+		},
+		_ => {
+			output.push(format!("{} is not a valid chain specification.", file));
+			Err(output.join("\n"))
+		},
+	}
+}
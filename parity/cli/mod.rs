@@ -141,6 +141,10 @@ usage! {
 				"--no-code",
 				"Don't export account code.",
 
+				FLAG flag_export_state_genesis: (bool) = false,
+				"--genesis",
+				"Export state as the accounts section of a chain specification, which can be used as genesis state of another chain.",
+
 				ARG arg_export_state_min_balance: (Option<String>) = None,
 				"--min-balance=[WEI]",
 				"Don't export accounts with balance less than specified.",
@@ -202,6 +206,15 @@ usage! {
 		{
 			"Make a snapshot of the database",
 
+			CMD cmd_snapshot_verify
+			{
+				"Verify a snapshot without restoring it",
+
+				ARG arg_snapshot_verify_path: (Option<String>) = None,
+				"<PATH>",
+				"Path to a snapshot file, or to a snapshot directory or its MANIFEST",
+			}
+
 			ARG arg_snapshot_at: (String) = "latest",
 			"--at=[BLOCK]",
 			"Take a snapshot at the given block, which may be an index, hash, or latest. Note that taking snapshots at non-recent blocks will only work with --pruning archive",
@@ -234,6 +247,20 @@ usage! {
 			}
 		}
 
+		CMD cmd_chain
+		{
+			"Manage chain specifications",
+
+			CMD cmd_chain_validate
+			{
+				"Validate a chain specification file and compute its genesis hash",
+
+				ARG arg_chain_validate_file: (Option<String>) = None,
+				"<SPEC>",
+				"Chain specification JSON file",
+			}
+		}
+
 		CMD cmd_db
 		{
 			"Manage the database representing the state of the blockchain on this system",
@@ -241,6 +268,34 @@ usage! {
 			CMD cmd_db_kill {
 				"Clean the database",
 			}
+
+			CMD cmd_db_rewind {
+				"Rewind the chain head to a previous block, e.g. to leave a bad fork",
+
+				ARG arg_db_rewind_block: (Option<String>) = None,
+				"<BLOCK>",
+				"Block to rewind to, which may be an index or hash. Its state must still be available.",
+			}
+
+			CMD cmd_db_rebuild_bloom {
+				"Rebuild the account bloom from the state of the best block",
+			}
+
+			CMD cmd_db_check {
+				"Check stored blocks for corruption and optionally schedule corrupted blocks for download",
+
+				FLAG flag_db_check_repair: (bool) = false,
+				"--repair",
+				"Remove corrupted blocks from the database so that they are downloaded again from peers on the next start.",
+
+				ARG arg_db_check_from: (String) = "1",
+				"--from=[BLOCK]",
+				"Check from block BLOCK, which may be an index or hash.",
+
+				ARG arg_db_check_to: (String) = "latest",
+				"--to=[BLOCK]",
+				"Check to (including) block BLOCK, which may be an index, hash or latest.",
+			}
 		}
 	}
 	{
@@ -303,6 +358,10 @@ usage! {
 			"--chain=[CHAIN]",
 			"Specify the blockchain type. CHAIN may be either a JSON chain specification file or olympic, frontier, homestead, mainnet, morden, ropsten, classic, expanse, testnet, kovan or dev.",
 
+			ARG arg_chains_dir: (Option<String>) = None, or |c: &Config| otry!(c.parity).chains_dir.clone(),
+			"--chains-dir=[PATH]",
+			"Directory with additional JSON chain specifications. A spec stored as PATH/NAME.json can be selected with --chain NAME.",
+
 			ARG arg_keys_path: (String) = "$BASE/keys", or |c: &Config| otry!(c.parity).keys_path.clone(),
 			"--keys-path=[PATH]",
 			"Specify the path for JSON key files to be found",
@@ -389,6 +448,14 @@ usage! {
 			"--no-warp",
 			"Disable syncing from the snapshot over the network.",
 
+			ARG arg_warp_snapshot_hash: (Option<String>) = None, or |c: &Config| otry!(c.network).warp_snapshot_hash.clone(),
+			"--warp-snapshot-hash=[HASH]",
+			"Only restore the snapshot with manifest hash HASH when warp syncing. Peers offering other snapshots are ignored.",
+
+			FLAG flag_beam_sync: (bool) = false, or |c: &Config| otry!(c.network).beam_sync.clone(),
+			"--beam-sync",
+			"Experimental: when warp syncing, restore only the blocks of the snapshot and start importing blocks right away, fetching the state from peers on demand and completing it in the background.",
+
 			FLAG flag_no_discovery: (bool) = false, or |c: &Config| otry!(c.network).discovery.map(|d| !d).clone(),
 			"--no-discovery",
 			"Disable new peer discovery.",
@@ -405,6 +472,14 @@ usage! {
 			"--no-serve-light",
 			"Disable serving of light peers.",
 
+			ARG arg_tx_rebroadcast_interval: (u64) = 60u64, or |c: &Config| otry!(c.network).tx_rebroadcast_interval.clone(),
+			"--tx-rebroadcast-interval=[SECS]",
+			"Delay before local transactions still in the queue are sent to peers again. The delay doubles after every re-broadcast. Setting this to 0 disables re-broadcasting.",
+
+			ARG arg_tx_rebroadcast_max_interval: (u64) = 900u64, or |c: &Config| otry!(c.network).tx_rebroadcast_max_interval.clone(),
+			"--tx-rebroadcast-max-interval=[SECS]",
+			"Maximal delay between re-broadcasts of a local transaction.",
+
 			ARG arg_port: (u16) = 30303u16, or |c: &Config| otry!(c.network).port.clone(),
 			"--port=[PORT]",
 			"Override the port on which the node should listen.",
@@ -423,16 +498,44 @@ usage! {
 
 			ARG arg_nat: (String) = "any", or |c: &Config| otry!(c.network).nat.clone(),
 			"--nat=[METHOD]",
-			"Specify method to use for determining public address. Must be one of: any, none, upnp, extip:<IP>.",
+			"Specify method to use for determining public address. Must be one of: any, none, upnp, natpmp, extip:<IP>.",
 
 			ARG arg_allow_ips: (String) = "all", or |c: &Config| otry!(c.network).allow_ips.clone(),
 			"--allow-ips=[FILTER]",
 			"Filter outbound connections. Must be one of: private - connect to private network IP addresses only; public - connect to public network IP addresses only; all - connect to any IP address.",
 
+			ARG arg_ip_version: (String) = "dual", or |c: &Config| otry!(c.network).ip_version.clone(),
+			"--ip-version=[VERSION]",
+			"IP versions to listen on, discover and connect to peers with. Must be one of: dual - IPv4 and IPv6, preferring IPv4 addresses of peers reachable over both; dual-v6 - IPv4 and IPv6, preferring IPv6; v4 - IPv4 only; v6 - IPv6 only.",
+
 			ARG arg_max_pending_peers: (u16) = 64u16, or |c: &Config| otry!(c.network).max_pending_peers.clone(),
 			"--max-pending-peers=[NUM]",
 			"Allow up to NUM pending connections.",
 
+			ARG arg_max_peers_per_ip: (u16) = 4u16, or |c: &Config| otry!(c.network).max_peers_per_ip.clone(),
+			"--max-peers-per-ip=[NUM]",
+			"Allow up to NUM inbound and NUM outbound peers sharing an IP address. 0 for no limit.",
+
+			ARG arg_max_peers_per_subnet: (u16) = 8u16, or |c: &Config| otry!(c.network).max_peers_per_subnet.clone(),
+			"--max-peers-per-subnet=[NUM]",
+			"Allow up to NUM inbound and NUM outbound peers from a single /24 (IPv4) or /64 (IPv6) subnet. 0 for no limit.",
+
+			ARG arg_max_upload_rate: (u32) = 0u32, or |c: &Config| otry!(c.network).max_upload_rate.clone(),
+			"--max-upload-rate=[KB/S]",
+			"Limit the total upload rate to KB/S kilobytes per second. 0 for no limit.",
+
+			ARG arg_max_download_rate: (u32) = 0u32, or |c: &Config| otry!(c.network).max_download_rate.clone(),
+			"--max-download-rate=[KB/S]",
+			"Limit the total download rate to KB/S kilobytes per second. 0 for no limit.",
+
+			ARG arg_max_peer_upload_rate: (u32) = 0u32, or |c: &Config| otry!(c.network).max_peer_upload_rate.clone(),
+			"--max-peer-upload-rate=[KB/S]",
+			"Limit the upload rate to a single peer to KB/S kilobytes per second. 0 for no limit.",
+
+			ARG arg_max_peer_download_rate: (u32) = 0u32, or |c: &Config| otry!(c.network).max_peer_download_rate.clone(),
+			"--max-peer-download-rate=[KB/S]",
+			"Limit the download rate from a single peer to KB/S kilobytes per second. 0 for no limit.",
+
 			ARG arg_network_id: (Option<u64>) = None, or |c: &Config| otry!(c.network).id.clone(),
 			"--network-id=[INDEX]",
 			"Override the network identifier from the chain we are on.",
@@ -447,7 +550,7 @@ usage! {
 
 			ARG arg_reserved_peers: (Option<String>) = None, or |c: &Config| otry!(c.network).reserved_peers.clone(),
 			"--reserved-peers=[FILE]",
-			"Provide a file containing enodes, one per line. These nodes will always have a reserved slot on top of the normal maximum peers.",
+			"Provide a file containing enodes, one per line. These nodes will always have a reserved slot on top of the normal maximum peers. An enode may be followed by a 32 byte hex key shared with that peer, which it must then prove to know when connecting.",
 
 		["API and console options – RPC"]
 			FLAG flag_no_jsonrpc: (bool) = false, or |c: &Config| otry!(c.rpc).disable.clone(),
@@ -464,7 +567,7 @@ usage! {
 
 			ARG arg_jsonrpc_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,traces,rpc,secretstore,shh,shh_pubsub", or |c: &Config| otry!(c.rpc).apis.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-apis=[APIS]",
-			"Specify the APIs available through the JSONRPC interface. APIS is a comma-delimited list of API name. Possible name are all, safe, web3, eth, net, personal, parity, parity_set, traces, rpc, parity_accounts. You can also disable a specific API by putting '-' in the front: all,-personal.",
+			"Specify the APIs available through the JSONRPC interface. APIS is a comma-delimited list of API name. Possible name are all, safe, web3, eth, net, personal, parity, parity_set, admin, traces, debug, rpc, parity_accounts. You can also disable a specific API by putting '-' in the front: all,-personal.",
 
 			ARG arg_jsonrpc_hosts: (String) = "none", or |c: &Config| otry!(c.rpc).hosts.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-hosts=[HOSTS]",
@@ -482,6 +585,30 @@ usage! {
 			"--jsonrpc-server-threads=[NUM]",
 			"Enables experimental faster implementation of JSON-RPC server. Requires Dapps server to be disabled using --no-dapps.",
 
+			ARG arg_jsonrpc_cache_size: (usize) = 1000usize, or |c: &Config| otry!(c.rpc).cache_size,
+			"--jsonrpc-cache-size=[ITEMS]",
+			"Number of responses concerning finalized blocks (blocks and receipts) to keep in the RPC response cache. Set to 0 to disable the cache.",
+
+			ARG arg_jsonrpc_cache_finality: (u64) = 64u64, or |c: &Config| otry!(c.rpc).cache_finality,
+			"--jsonrpc-cache-finality=[BLOCKS]",
+			"Number of confirmations after which block data is considered final and can be cached.",
+
+			ARG arg_jsonrpc_call_cache: (usize) = 16usize, or |c: &Config| otry!(c.rpc).call_cache,
+			"--jsonrpc-call-cache=[MB]",
+			"Size of the cache of eth_call results in megabytes. Results are dropped whenever the chain head changes. Set to 0 to disable the cache.",
+
+			ARG arg_jsonrpc_call_timeout: (u64) = 5000u64, or |c: &Config| otry!(c.rpc).call_timeout,
+			"--jsonrpc-call-timeout=[MS]",
+			"Abort eth_call and eth_estimateGas requests received over HTTP, WebSockets and from Dapps that run longer than MS milliseconds. Set to 0 to disable the limit.",
+
+			ARG arg_jsonrpc_trusted_call_timeout: (u64) = 60000u64, or |c: &Config| otry!(c.rpc).trusted_call_timeout,
+			"--jsonrpc-trusted-call-timeout=[MS]",
+			"Abort eth_call and eth_estimateGas requests received over IPC and from the Trusted Signer that run longer than MS milliseconds. Set to 0 to disable the limit.",
+
+			ARG arg_jsonrpc_authorization_url: (Option<String>) = None, or |c: &Config| otry!(c.rpc).authorization_url.clone(),
+			"--jsonrpc-authorization-url=[URL]",
+			"Authorize every RPC call (on all transports) against external service at URL. Calls are denied unless the service responds with a success status. Decisions are cached per method, origin and session token for 30 seconds.",
+
 		["API and console options – WebSockets"]
 			FLAG flag_no_ws: (bool) = false, or |c: &Config| otry!(c.websockets).disable.clone(),
 			"--no-ws",
@@ -497,7 +624,7 @@ usage! {
 
 			ARG arg_ws_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,traces,rpc,secretstore,shh,shh_pubsub", or |c: &Config| otry!(c.websockets).apis.as_ref().map(|vec| vec.join(",")),
 			"--ws-apis=[APIS]",
-			"Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, pubsub, net, personal, parity, parity_set, traces, rpc, parity_accounts..",
+			"Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, pubsub, net, personal, parity, parity_set, admin, traces, debug, rpc, parity_accounts..",
 
 			ARG arg_ws_origins: (String) = "chrome-extension://*,moz-extension://*", or |c: &Config| otry!(c.websockets).origins.as_ref().map(|vec| vec.join(",")),
 			"--ws-origins=[URL]",
@@ -591,6 +718,31 @@ usage! {
 			"--secretstore-secret=[SECRET]",
 			"Hex-encoded secret key of this node.",
 
+		["Private transactions options"]
+			FLAG flag_private_enabled: (bool) = false, or |c: &Config| otry!(c.private_tx).enabled,
+			"--private-tx-enabled",
+			"Enable private transactions.",
+
+			ARG arg_private_signer: (Option<String>) = None, or |c: &Config| otry!(c.private_tx).signer.clone(),
+			"--private-signer=[ACCOUNT]",
+			"Specify the account for signing public transactions created upon verified private transactions.",
+
+			ARG arg_private_validators: (Option<String>) = None, or |c: &Config| otry!(c.private_tx).validators.as_ref().map(|vec| vec.join(",")),
+			"--private-validators=[ACCOUNTS]",
+			"Specify the accounts for validating private transactions. ACCOUNTS is a comma-delimited list of addresses.",
+
+			ARG arg_private_account: (Option<String>) = None, or |c: &Config| otry!(c.private_tx).account.clone(),
+			"--private-account=[ACCOUNT]",
+			"Specify the account for signing requests to the secret store.",
+
+			ARG arg_private_sstore_url: (Option<String>) = None, or |c: &Config| otry!(c.private_tx).sstore_url.clone(),
+			"--private-sstore-url=[URL]",
+			"Specify secret store URL used for encrypting private transactions.",
+
+			ARG arg_private_passwords: (Option<String>) = None, or |c: &Config| otry!(c.private_tx).passwords.clone(),
+			"--private-passwords=[FILE]",
+			"Provide a file containing passwords for unlocking accounts (signer, private account, validators).",
+
 		["Sealing/Mining options"]
 			FLAG flag_force_sealing: (bool) = false, or |c: &Config| otry!(c.mining).force_sealing.clone(),
 			"--force-sealing",
@@ -608,6 +760,10 @@ usage! {
 			"--refuse-service-transactions",
 			"Always refuse service transactions..",
 
+			FLAG flag_notify_work_pubsub: (bool) = false, or |c: &Config| otry!(c.mining).notify_work_pubsub.clone(),
+			"--notify-work-pubsub",
+			"Push work package notifications to eth_subscribe(\"newWork\") subscribers over WebSockets and IPC. Like --notify-work it keeps the node sealing.",
+
 			FLAG flag_no_persistent_txqueue: (bool) = false, or |c: &Config| otry!(c.parity).no_persistent_txqueue,
 			"--no-persistent-txqueue",
 			"Don't save pending local transactions to disk to be restored whenever the node restarts.",
@@ -628,6 +784,10 @@ usage! {
 			"--reseal-max-period=[MS]",
 			"Specify the maximum time since last block to enable force-sealing. MS is time measured in milliseconds.",
 
+			ARG arg_seal_interval: (Option<u64>) = None, or |c: &Config| otry!(c.mining).seal_interval.clone(),
+			"--seal-interval=[MS]",
+			"Seal a new block every MS milliseconds, even if it contains no transactions. Only supported by engines sealing internally (e.g. --chain dev).",
+
 			ARG arg_work_queue_size: (usize) = 20usize, or |c: &Config| otry!(c.mining).work_queue_size.clone(),
 			"--work-queue-size=[ITEMS]",
 			"Specify the number of historical work packages which are kept cached lest a solution is found for them later. High values take more memory but result in fewer unusable solutions.",
@@ -664,6 +824,14 @@ usage! {
 			"--tx-queue-size=[LIMIT]",
 			"Maximum amount of transactions in the queue (waiting to be included in next block).",
 
+			ARG arg_tx_queue_per_sender: (usize) = 0usize, or |c: &Config| otry!(c.mining).tx_queue_per_sender.clone(),
+			"--tx-queue-per-sender=[LIMIT]",
+			"Maximum amount of external transactions of a single sender in the queue. Setting this parameter to 0 disables limiting.",
+
+			ARG arg_tx_queue_per_sender_mem_limit: (u32) = 0u32, or |c: &Config| otry!(c.mining).tx_queue_per_sender_mem_limit.clone(),
+			"--tx-queue-per-sender-mem-limit=[KB]",
+			"Maximum amount of memory that can be used by external transactions of a single sender in the queue. Setting this parameter to 0 disables limiting.",
+
 			ARG arg_tx_queue_gas: (String) = "off", or |c: &Config| otry!(c.mining).tx_queue_gas.clone(),
 			"--tx-queue-gas=[LIMIT]",
 			"Maximum amount of total gas for external transactions in the queue. LIMIT can be either an amount of gas or 'auto' or 'off'. 'auto' sets the limit to be 20x the current block gas limit..",
@@ -672,6 +840,10 @@ usage! {
 			"--tx-queue-strategy=[S]",
 			"Prioritization strategy used to order transactions in the queue. S may be: gas - Prioritize txs with low gas limit; gas_price - Prioritize txs with high gas price; gas_factor - Prioritize txs using gas price and gas limit ratio.",
 
+			ARG arg_tx_queue_replace_bump: (usize) = 12usize, or |c: &Config| otry!(c.mining).tx_queue_replace_bump.clone(),
+			"--tx-queue-replace-bump=[PERCENT]",
+			"Minimal gas price increase (in percent) required to replace a transaction with the same sender and nonce. Cancellations (zero-value calls to self without data) only need to pay more than the replaced transaction.",
+
 			ARG arg_tx_queue_ban_count: (u16) = 1u16, or |c: &Config| otry!(c.mining).tx_queue_ban_count.clone(),
 			"--tx-queue-ban-count=[C]",
 			"Number of times maximal time for execution (--tx-time-limit) can be exceeded before banning sender/recipient/code.",
@@ -680,6 +852,14 @@ usage! {
 			"--tx-queue-ban-time=[SEC]",
 			"Banning time (in seconds) for offenders of specified execution time limit. Also number of offending actions have to reach the threshold within that time.",
 
+			ARG arg_tx_queue_invalid_ban_count: (u16) = 0u16, or |c: &Config| otry!(c.mining).tx_queue_invalid_ban_count.clone(),
+			"--tx-queue-invalid-ban-count=[C]",
+			"Number of transactions of a sender failing validation (e.g. underpriced or above the balance) before the sender is banned for --tx-queue-ban-time. Setting this parameter to 0 disables banning.",
+
+			ARG arg_tx_queue_replace_cooldown: (u16) = 0u16, or |c: &Config| otry!(c.mining).tx_queue_replace_cooldown.clone(),
+			"--tx-queue-replace-cooldown=[SEC]",
+			"Minimal time (in seconds) before an external transaction with the same sender and nonce can be replaced again. Setting this parameter to 0 disables the cooldown.",
+
 			ARG arg_stratum_interface: (String) = "local", or |c: &Config| otry!(c.stratum).interface.clone(),
 			"--stratum-interface=[IP]",
 			"Interface address for Stratum server.",
@@ -710,7 +890,27 @@ usage! {
 
 			ARG arg_extra_data: (Option<String>) = None, or |c: &Config| otry!(c.mining).extra_data.clone(),
 			"--extra-data=[STRING]",
-			"Specify a custom extra-data for authored blocks, no more than 32 characters.",
+			"Specify a custom extra-data for authored blocks, no more than 32 characters. May be a template expanded when sealing, with variables: {version}, {chain}, {tag} and {counter} (number of blocks sealed by this node); use {{ and }} for literal braces.",
+
+			ARG arg_extra_data_tag: (String) = "", or |c: &Config| otry!(c.mining).extra_data_tag.clone(),
+			"--extra-data-tag=[STRING]",
+			"Specify the pool tag substituted for {tag} in the --extra-data template.",
+
+			ARG arg_uncles_max: (Option<usize>) = None, or |c: &Config| otry!(c.mining).uncles_max.clone(),
+			"--uncles-max=[NUM]",
+			"Include at most NUM uncles in authored blocks. 0 disables uncle inclusion. Defaults to the maximum allowed by the engine.",
+
+			ARG arg_uncles_max_age: (Option<usize>) = None, or |c: &Config| otry!(c.mining).uncles_max_age.clone(),
+			"--uncles-max-age=[BLOCKS]",
+			"Include only uncles at most BLOCKS generations old in authored blocks. Defaults to the maximum allowed by the engine.",
+
+			ARG arg_uncles_min_reward: (Option<String>) = None, or |c: &Config| otry!(c.mining).uncles_min_reward.clone(),
+			"--uncles-min-reward=[WEI]",
+			"Include only uncles whose author is rewarded at least WEI in authored blocks.",
+
+			ARG arg_block_ordering: (String) = "queue", or |c: &Config| otry!(c.mining).block_ordering.clone(),
+			"--block-ordering=[S]",
+			"Order in which ready transactions are included in authored blocks. S may be: queue - Use transaction queue priority; gas_price - Include txs with high gas price first; arrival - Include oldest txs first; round_robin - Include one tx per sender in turns. Nonce order of each sender is always preserved.",
 
 			ARG arg_notify_work: (Option<String>) = None, or |c: &Config| otry!(c.mining).notify_work.as_ref().map(|vec| vec.join(",")),
 			"--notify-work=[URLS]",
@@ -720,6 +920,14 @@ usage! {
 			"--stratum-secret=[STRING]",
 			"Secret for authorizing Stratum server for peers.",
 
+			ARG arg_stratum_share_time: (Option<u64>) = None, or |c: &Config| otry!(c.stratum).share_time.clone(),
+			"--stratum-share-time=[SECS]",
+			"Enable variable share difficulty, adjusting the difficulty of each Stratum worker to submit a share every SECS seconds. Without it shares have to seal a block.",
+
+			ARG arg_stratum_min_share_difficulty: (String) = "1000000", or |c: &Config| otry!(c.stratum).min_share_difficulty.clone(),
+			"--stratum-min-share-difficulty=[DIFF]",
+			"Share difficulty of new Stratum workers and the lowest one assigned with variable share difficulty.",
+
 		["Internal Options"]
 			FLAG flag_can_restart: (bool) = false, or |_| None,
 			"--can-restart",
@@ -750,6 +958,14 @@ usage! {
 			"--log-file=[FILENAME]",
 			"Specify a filename into which logging should be appended.",
 
+			ARG arg_head_feed: (Option<String>) = None, or |c: &Config| otry!(c.misc).head_feed.clone(),
+			"--head-feed=[TARGET]",
+			"Write newline-delimited JSON events about new, retracted and finalized chain heads to TARGET, either udp://HOST:PORT or unix://PATH.",
+
+			ARG arg_head_feed_finality: (u64) = 64u64, or |c: &Config| otry!(c.misc).head_feed_finality,
+			"--head-feed-finality=[BLOCKS]",
+			"Number of confirmations after which a block is reported as finalized in the head feed.",
+
 		["Footprint options"]
 			FLAG flag_fast_and_loose: (bool) = false, or |c: &Config| otry!(c.footprint).fast_and_loose.clone(),
 			"--fast-and-loose",
@@ -759,6 +975,10 @@ usage! {
 			"--scale-verifiers",
 			"Automatically scale amount of verifier threads based on workload. Not guaranteed to be faster.",
 
+			FLAG flag_parallel_transactions: (bool) = false, or |c: &Config| otry!(c.footprint).parallel_transactions.clone(),
+			"--parallel-transactions",
+			"Experimental: execute transactions of imported blocks optimistically in parallel. Conflicting transactions are executed again sequentially.",
+
 			ARG arg_tracing: (String) = "auto", or |c: &Config| otry!(c.footprint).tracing.clone(),
 			"--tracing=[BOOL]",
 			"Indicates if full transaction tracing should be enabled. Works only if client had been fully synced with tracing enabled. BOOL may be one of auto, on, off. auto uses last used value of this option (off if it does not exist).", // footprint option
@@ -775,6 +995,26 @@ usage! {
 			"--pruning-memory=[MB]",
 			"The ideal amount of memory in megabytes to use to store recent states. As many states as possible will be kept within this limit, and at least --pruning-history states will always be kept.",
 
+			ARG arg_history_retention: (Option<u64>) = None, or |c: &Config| otry!(c.footprint).history_retention.clone(),
+			"--history-retention=[NUM]",
+			"Keep bodies and receipts of only the given number of recent blocks (at least 10000). Headers are always kept.",
+
+			ARG arg_tracing_retention: (Option<u64>) = None, or |c: &Config| otry!(c.footprint).tracing_retention.clone(),
+			"--tracing-retention=[NUM]",
+			"Keep traces of only the given number of recent blocks (at least 10000). Older traces are pruned in the background.",
+
+			ARG arg_cold_db_path: (Option<String>) = None, or |c: &Config| otry!(c.footprint).cold_db_path.clone(),
+			"--cold-db-path=[PATH]",
+			"Move historical state of an archive node (--pruning archive) to a secondary database at PATH, e.g. on cheaper storage. Old states remain accessible.",
+
+			ARG arg_cold_db_after: (u64) = 100000u64, or |c: &Config| otry!(c.footprint).cold_db_after.clone(),
+			"--cold-db-after=[NUM]",
+			"Keep state of the given number of recent blocks in the main database when --cold-db-path is set.",
+
+			ARG arg_max_reorg_depth: (Option<u64>) = None, or |c: &Config| otry!(c.footprint).max_reorg_depth.clone(),
+			"--max-reorg-depth=[NUM]",
+			"Halt block import when a reorganization would retract more than NUM blocks. Import resumes only after the new branch is accepted with parity_acceptReorg.",
+
 			ARG arg_cache_size_db: (u32) = 32u32, or |c: &Config| otry!(c.footprint).cache_size_db.clone(),
 			"--cache-size-db=[MB]",
 			"Override database cache size.",
@@ -785,7 +1025,7 @@ usage! {
 
 			ARG arg_cache_size_queue: (u32) = 40u32, or |c: &Config| otry!(c.footprint).cache_size_queue.clone(),
 			"--cache-size-queue=[MB]",
-			"Specify the maximum size of memory to use for block queue.",
+			"Specify the maximum size of memory to use for block queue. Sync stops downloading block bodies while the queue is over this budget.",
 
 			ARG arg_cache_size_state: (u32) = 25u32, or |c: &Config| otry!(c.footprint).cache_size_state.clone(),
 			"--cache-size-state=[MB]",
@@ -817,11 +1057,27 @@ usage! {
 			"--no-periodic-snapshot",
 			"Disable automated snapshots which usually occur once every 10000 blocks.",
 
+			ARG arg_snapshot_io_budget: (Option<usize>) = None, or |c: &Config| otry!(c.snapshots).io_budget.clone(),
+			"--snapshot-io-budget=[MB]",
+			"Limit the average rate at which snapshot chunks are written to MB megabytes per second.",
+
+			FLAG flag_snapshot_pause_on_import: (bool) = false, or |c: &Config| otry!(c.snapshots).pause_on_import.clone(),
+			"--snapshot-pause-on-import",
+			"Suspend writing snapshot chunks while blocks are being imported.",
+
+			ARG arg_snapshot_mirrors: (Option<String>) = None, or |c: &Config| otry!(c.snapshots).mirrors.as_ref().map(|vec| vec.join(",")),
+			"--snapshot-mirrors=[URLS]",
+			"Download the snapshot from HTTP(S) mirrors when warp syncing, falling back to peers for missing chunks. URLS should be comma-delimited and point to loose snapshot directories.",
+
 		["Virtual Machine options"]
 			FLAG flag_jitvm: (bool) = false, or |c: &Config| otry!(c.vm).jit.clone(),
 			"--jitvm",
 			"Enable the JIT VM.",
 
+			FLAG flag_translated_evm: (bool) = false, or |c: &Config| otry!(c.vm).translated.clone(),
+			"--translated-evm",
+			"Execute contracts from code translated ahead of time, falling back to the interpreter when tracing or using chain-specific instructions. Requires Parity built with the translated-evm feature.",
+
 		["Whisper options"]
 			FLAG flag_whisper: (bool) = false, or |c: &Config| otry!(c.whisper).enabled,
 			"--whisper",
@@ -831,6 +1087,27 @@ usage! {
 			"--whisper-pool-size=[MB]",
 			"Target size of the whisper message pool in megabytes.",
 
+		["Node health options"]
+			ARG arg_health_min_peers: (usize) = 2usize, or |c: &Config| otry!(c.health).min_peers.clone(),
+			"--health-min-peers=[NUM]",
+			"Report the node as needing attention if it's connected to fewer than NUM peers.",
+
+			ARG arg_health_max_block_lag: (u64) = 10u64, or |c: &Config| otry!(c.health).max_block_lag.clone(),
+			"--health-max-block-lag=[BLOCKS]",
+			"Report the node as not ready if it's more than BLOCKS behind the best block seen on the network.",
+
+			ARG arg_health_max_time_drift: (i64) = 500i64, or |c: &Config| otry!(c.health).max_time_drift.clone(),
+			"--health-max-time-drift=[MS]",
+			"Report the node as unhealthy if its clock differs from NTP time by more than MS milliseconds.",
+
+			ARG arg_health_min_disk_space: (u64) = 1024u64, or |c: &Config| otry!(c.health).min_disk_space.clone(),
+			"--health-min-disk-space=[MB]",
+			"Report the node as unhealthy if there is less than MB megabytes of free space in the data directory.",
+
+			ARG arg_health_max_response_time: (u64) = 1000u64, or |c: &Config| otry!(c.health).max_response_time.clone(),
+			"--health-max-response-time=[MS]",
+			"Report the node as needing attention if it takes more than MS milliseconds to report its status.",
+
 		["Legacy options"]
 			FLAG flag_dapps_apis_all: (bool) = false, or |_| None,
 			"--dapps-apis-all",
@@ -975,6 +1252,7 @@ struct Config {
 	ipc: Option<Ipc>,
 	dapps: Option<Dapps>,
 	secretstore: Option<SecretStore>,
+	private_tx: Option<PrivateTransactions>,
 	ipfs: Option<Ipfs>,
 	mining: Option<Mining>,
 	footprint: Option<Footprint>,
@@ -983,6 +1261,7 @@ struct Config {
 	misc: Option<Misc>,
 	stratum: Option<Stratum>,
 	whisper: Option<Whisper>,
+	health: Option<Health>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -996,6 +1275,7 @@ struct Operating {
 	no_download: Option<bool>,
 	no_consensus: Option<bool>,
 	chain: Option<String>,
+	chains_dir: Option<String>,
 	base_path: Option<String>,
 	db_path: Option<String>,
 	keys_path: Option<String>,
@@ -1026,13 +1306,22 @@ struct Ui {
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct Network {
 	warp: Option<bool>,
+	warp_snapshot_hash: Option<String>,
+	beam_sync: Option<bool>,
 	port: Option<u16>,
 	min_peers: Option<u16>,
 	max_peers: Option<u16>,
 	snapshot_peers: Option<u16>,
 	max_pending_peers: Option<u16>,
+	max_peers_per_ip: Option<u16>,
+	max_peers_per_subnet: Option<u16>,
+	max_upload_rate: Option<u32>,
+	max_download_rate: Option<u32>,
+	max_peer_upload_rate: Option<u32>,
+	max_peer_download_rate: Option<u32>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
+	ip_version: Option<String>,
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
@@ -1040,6 +1329,8 @@ struct Network {
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
 	no_serve_light: Option<bool>,
+	tx_rebroadcast_interval: Option<u64>,
+	tx_rebroadcast_max_interval: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1052,6 +1343,12 @@ struct Rpc {
 	hosts: Option<Vec<String>>,
 	server_threads: Option<usize>,
 	processing_threads: Option<usize>,
+	cache_size: Option<usize>,
+	cache_finality: Option<u64>,
+	call_cache: Option<usize>,
+	call_timeout: Option<u64>,
+	trusted_call_timeout: Option<u64>,
+	authorization_url: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1097,6 +1394,16 @@ struct SecretStore {
 	path: Option<String>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+struct PrivateTransactions {
+	enabled: Option<bool>,
+	signer: Option<String>,
+	validators: Option<Vec<String>>,
+	account: Option<String>,
+	passwords: Option<String>,
+	sstore_url: Option<String>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct Ipfs {
 	enable: Option<bool>,
@@ -1115,6 +1422,7 @@ struct Mining {
 	reseal_on_txs: Option<String>,
 	reseal_min_period: Option<u64>,
 	reseal_max_period: Option<u64>,
+	seal_interval: Option<u64>,
 	work_queue_size: Option<usize>,
 	tx_gas_limit: Option<String>,
 	tx_time_limit: Option<u64>,
@@ -1126,14 +1434,25 @@ struct Mining {
 	gas_floor_target: Option<String>,
 	gas_cap: Option<String>,
 	extra_data: Option<String>,
+	extra_data_tag: Option<String>,
+	uncles_max: Option<usize>,
+	uncles_max_age: Option<usize>,
+	uncles_min_reward: Option<String>,
+	block_ordering: Option<String>,
 	tx_queue_size: Option<usize>,
 	tx_queue_mem_limit: Option<u32>,
+	tx_queue_per_sender: Option<usize>,
+	tx_queue_per_sender_mem_limit: Option<u32>,
 	tx_queue_gas: Option<String>,
 	tx_queue_strategy: Option<String>,
+	tx_queue_replace_bump: Option<usize>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
+	tx_queue_invalid_ban_count: Option<u16>,
+	tx_queue_replace_cooldown: Option<u16>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
+	notify_work_pubsub: Option<bool>,
 	refuse_service_transactions: Option<bool>,
 }
 
@@ -1142,6 +1461,8 @@ struct Stratum {
 	interface: Option<String>,
 	port: Option<u16>,
 	secret: Option<String>,
+	share_time: Option<u64>,
+	min_share_difficulty: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1150,6 +1471,11 @@ struct Footprint {
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	history_retention: Option<u64>,
+	tracing_retention: Option<u64>,
+	cold_db_path: Option<String>,
+	cold_db_after: Option<u64>,
+	max_reorg_depth: Option<u64>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -1160,16 +1486,21 @@ struct Footprint {
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	parallel_transactions: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct Snapshots {
 	disable_periodic: Option<bool>,
+	io_budget: Option<usize>,
+	pause_on_import: Option<bool>,
+	mirrors: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct VM {
 	jit: Option<bool>,
+	translated: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1177,6 +1508,8 @@ struct Misc {
 	ntp_servers: Option<Vec<String>>,
 	logging: Option<String>,
 	log_file: Option<String>,
+	head_feed: Option<String>,
+	head_feed_finality: Option<u64>,
 	color: Option<bool>,
 	ports_shift: Option<u16>,
 	unsafe_expose: Option<bool>,
@@ -1188,12 +1521,21 @@ struct Whisper {
 	pool_size: Option<usize>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+struct Health {
+	min_peers: Option<usize>,
+	max_block_lag: Option<u64>,
+	max_time_drift: Option<i64>,
+	min_disk_space: Option<u64>,
+	max_response_time: Option<u64>,
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{
 		Args, ArgsError,
 		Config, Operating, Account, Ui, Network, Ws, Rpc, Ipc, Dapps, Ipfs, Mining, Footprint,
-		Snapshots, VM, Misc, Whisper, SecretStore,
+		Snapshots, VM, Misc, Whisper, SecretStore, Health,
 	};
 	use toml;
 
@@ -1208,6 +1550,9 @@ mod tests {
 		let args = Args::parse(&["parity", "export", "state", "--no-storage"]).unwrap();
 		assert_eq!(args.flag_export_state_no_storage, true);
 
+		let args = Args::parse(&["parity", "export", "state", "--genesis"]).unwrap();
+		assert_eq!(args.flag_export_state_genesis, true);
+
 		let args = Args::parse(&["parity", "export", "state", "--min-balance","123"]).unwrap();
 		assert_eq!(args.arg_export_state_min_balance, Some("123".to_string()));
 	}
@@ -1229,6 +1574,7 @@ mod tests {
 		let args = Args::parse(&["parity", "snapshot", "file.dump"]).unwrap();
 		assert_eq!(args.arg_snapshot_at, "latest");
 		assert_eq!(args.arg_export_state_at, "latest");
+		assert!(!args.cmd_snapshot_verify);
 	}
 
 	#[test]
@@ -1330,11 +1676,17 @@ mod tests {
 			cmd_signer_reject: false,
 			cmd_signer_new_token: false,
 			cmd_snapshot: false,
+			cmd_snapshot_verify: false,
 			cmd_restore: false,
 			cmd_tools: false,
 			cmd_tools_hash: false,
+			cmd_chain: false,
+			cmd_chain_validate: false,
 			cmd_db: false,
 			cmd_db_kill: false,
+			cmd_db_rewind: false,
+			cmd_db_rebuild_bloom: false,
+			cmd_db_check: false,
 
 			// Arguments
 			arg_daemon_pid_file: None,
@@ -1345,8 +1697,11 @@ mod tests {
 			arg_export_state_file: None,
 			arg_export_state_format: None,
 			arg_snapshot_file: None,
+			arg_snapshot_verify_path: None,
 			arg_restore_file: None,
 			arg_tools_hash_file: None,
+			arg_chain_validate_file: None,
+			arg_db_rewind_block: None,
 
 			arg_account_new_password: None,
 			arg_signer_sign_password: None,
@@ -1367,6 +1722,7 @@ mod tests {
 			flag_no_download: false,
 			flag_no_consensus: false,
 			arg_chain: "xyz".into(),
+			arg_chains_dir: Some("$HOME/.parity/specs".into()),
 			arg_base_path: Some("$HOME/.parity".into()),
 			arg_db_path: Some("$HOME/.parity/chains".into()),
 			arg_keys_path: "$HOME/.parity/keys".into(),
@@ -1397,12 +1753,21 @@ mod tests {
 
 			// -- Networking Options
 			flag_no_warp: false,
+			arg_warp_snapshot_hash: None,
+			flag_beam_sync: false,
 			arg_port: 30303u16,
 			arg_min_peers: 25u16,
 			arg_max_peers: 50u16,
 			arg_max_pending_peers: 64u16,
+			arg_max_peers_per_ip: 4u16,
+			arg_max_peers_per_subnet: 8u16,
+			arg_max_upload_rate: 0u32,
+			arg_max_download_rate: 0u32,
+			arg_max_peer_upload_rate: 0u32,
+			arg_max_peer_download_rate: 0u32,
 			arg_snapshot_peers: 0u16,
 			arg_allow_ips: "all".into(),
+			arg_ip_version: "dual".into(),
 			arg_nat: "any".into(),
 			arg_network_id: Some(1),
 			arg_bootnodes: Some("".into()),
@@ -1412,6 +1777,8 @@ mod tests {
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
+			arg_tx_rebroadcast_interval: 60u64,
+			arg_tx_rebroadcast_max_interval: 900u64,
 
 			// -- API and Console Options
 			// RPC
@@ -1423,6 +1790,12 @@ mod tests {
 			arg_jsonrpc_hosts: "none".into(),
 			arg_jsonrpc_server_threads: None,
 			arg_jsonrpc_threads: 0,
+			arg_jsonrpc_cache_size: 1000usize,
+			arg_jsonrpc_cache_finality: 64u64,
+			arg_jsonrpc_call_cache: 16usize,
+			arg_jsonrpc_call_timeout: 5000u64,
+			arg_jsonrpc_trusted_call_timeout: 60000u64,
+			arg_jsonrpc_authorization_url: None,
 
 			// WS
 			flag_no_ws: false,
@@ -1452,6 +1825,14 @@ mod tests {
 			arg_secretstore_http_port: 8082u16,
 			arg_secretstore_path: "$HOME/.parity/secretstore".into(),
 
+			// -- Private Transactions Options
+			flag_private_enabled: true,
+			arg_private_signer: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
+			arg_private_validators: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
+			arg_private_account: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
+			arg_private_sstore_url: Some("http://localhost:8082".into()),
+			arg_private_passwords: Some("passwords.txt".into()),
+
 			// IPFS
 			flag_ipfs_api: false,
 			arg_ipfs_api_port: 5001u16,
@@ -1466,6 +1847,7 @@ mod tests {
 			arg_reseal_on_txs: "all".into(),
 			arg_reseal_min_period: 4000u64,
 			arg_reseal_max_period: 60000u64,
+			arg_seal_interval: None,
 			flag_reseal_on_uncle: false,
 			arg_work_queue_size: 20usize,
 			arg_tx_gas_limit: Some("6283184".into()),
@@ -1478,26 +1860,44 @@ mod tests {
 			arg_gas_floor_target: "4700000".into(),
 			arg_gas_cap: "6283184".into(),
 			arg_extra_data: Some("Parity".into()),
+			arg_extra_data_tag: "".into(),
+			arg_uncles_max: Some(1usize),
+			arg_uncles_max_age: Some(3usize),
+			arg_uncles_min_reward: Some("1000000000000000000".into()),
+			arg_block_ordering: "queue".into(),
 			arg_tx_queue_size: 8192usize,
 			arg_tx_queue_mem_limit: 2u32,
+			arg_tx_queue_per_sender: 16usize,
+			arg_tx_queue_per_sender_mem_limit: 0u32,
 			arg_tx_queue_gas: "off".into(),
 			arg_tx_queue_strategy: "gas_factor".into(),
+			arg_tx_queue_replace_bump: 12usize,
 			arg_tx_queue_ban_count: 1u16,
 			arg_tx_queue_ban_time: 180u16,
+			arg_tx_queue_invalid_ban_count: 5u16,
+			arg_tx_queue_replace_cooldown: 0u16,
 			flag_remove_solved: false,
 			arg_notify_work: Some("http://localhost:3001".into()),
+			flag_notify_work_pubsub: false,
 			flag_refuse_service_transactions: false,
 
 			flag_stratum: false,
 			arg_stratum_interface: "local".to_owned(),
 			arg_stratum_port: 8008u16,
 			arg_stratum_secret: None,
+			arg_stratum_share_time: None,
+			arg_stratum_min_share_difficulty: "1000000".to_owned(),
 
 			// -- Footprint Options
 			arg_tracing: "auto".into(),
 			arg_pruning: "auto".into(),
 			arg_pruning_history: 64u64,
 			arg_pruning_memory: 500usize,
+			arg_history_retention: None,
+			arg_tracing_retention: None,
+			arg_cold_db_path: Some("$HOME/cold".into()),
+			arg_cold_db_after: 50000u64,
+			arg_max_reorg_depth: Some(100u64),
 			arg_cache_size_db: 64u32,
 			arg_cache_size_blocks: 8u32,
 			arg_cache_size_queue: 50u32,
@@ -1508,6 +1908,7 @@ mod tests {
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
+			flag_parallel_transactions: false,
 
 			// -- Import/Export Options
 			arg_export_blocks_from: "1".into(),
@@ -1515,21 +1916,36 @@ mod tests {
 			flag_no_seal_check: false,
 			flag_export_state_no_code: false,
 			flag_export_state_no_storage: false,
+			flag_export_state_genesis: false,
 			arg_export_state_min_balance: None,
 			arg_export_state_max_balance: None,
+			flag_db_check_repair: false,
+			arg_db_check_from: "1".into(),
+			arg_db_check_to: "latest".into(),
 
 			// -- Snapshot Optons
 			arg_export_state_at: "latest".into(),
 			arg_snapshot_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
+			arg_snapshot_io_budget: Some(16),
+			flag_snapshot_pause_on_import: false,
+			arg_snapshot_mirrors: None,
 
 			// -- Virtual Machine Options
 			flag_jitvm: false,
+			flag_translated_evm: false,
 
 			// -- Whisper options.
 			flag_whisper: false,
 			arg_whisper_pool_size: 20,
 
+			// -- Node health options.
+			arg_health_min_peers: 2usize,
+			arg_health_max_block_lag: 10u64,
+			arg_health_max_time_drift: 500i64,
+			arg_health_min_disk_space: 2048u64,
+			arg_health_max_response_time: 1000u64,
+
 			// -- Legacy Options
 			flag_geth: false,
 			flag_testnet: false,
@@ -1573,6 +1989,8 @@ mod tests {
 			flag_version: false,
 			arg_logging: Some("own_tx=trace".into()),
 			arg_log_file: Some("/var/log/parity.log".into()),
+			arg_head_feed: Some("udp://127.0.0.1:7700".into()),
+			arg_head_feed_finality: 64u64,
 			flag_no_color: false,
 			flag_no_config: false,
 		});
@@ -1607,6 +2025,7 @@ mod tests {
 				no_download: None,
 				no_consensus: None,
 				chain: Some("./chain.json".into()),
+				chains_dir: None,
 				base_path: None,
 				db_path: None,
 				keys_path: None,
@@ -1631,12 +2050,21 @@ mod tests {
 			}),
 			network: Some(Network {
 				warp: Some(false),
+				warp_snapshot_hash: None,
+				beam_sync: None,
 				port: None,
 				min_peers: Some(10),
 				max_peers: Some(20),
 				max_pending_peers: Some(30),
+				max_peers_per_ip: None,
+				max_peers_per_subnet: None,
+				max_upload_rate: None,
+				max_download_rate: None,
+				max_peer_upload_rate: None,
+				max_peer_download_rate: None,
 				snapshot_peers: Some(40),
 				allow_ips: Some("public".into()),
+				ip_version: None,
 				nat: Some("any".into()),
 				id: None,
 				bootnodes: None,
@@ -1645,6 +2073,8 @@ mod tests {
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
 				no_serve_light: None,
+				tx_rebroadcast_interval: None,
+				tx_rebroadcast_max_interval: None,
 			}),
 			websockets: Some(Ws {
 				disable: Some(true),
@@ -1663,6 +2093,12 @@ mod tests {
 				hosts: None,
 				server_threads: None,
 				processing_threads: None,
+				cache_size: None,
+				cache_finality: None,
+				call_cache: None,
+				call_timeout: None,
+				trusted_call_timeout: None,
+				authorization_url: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -1691,6 +2127,7 @@ mod tests {
 				http_port: Some(8082),
 				path: None,
 			}),
+			private_tx: None,
 			ipfs: Some(Ipfs {
 				enable: Some(false),
 				port: Some(5001),
@@ -1706,6 +2143,7 @@ mod tests {
 				reseal_on_uncle: None,
 				reseal_min_period: Some(4000),
 				reseal_max_period: Some(60000),
+				seal_interval: None,
 				work_queue_size: None,
 				relay_set: None,
 				min_gas_price: None,
@@ -1716,15 +2154,26 @@ mod tests {
 				gas_cap: None,
 				tx_queue_size: Some(8192),
 				tx_queue_mem_limit: None,
+				tx_queue_per_sender: None,
+				tx_queue_per_sender_mem_limit: None,
 				tx_queue_gas: Some("off".into()),
 				tx_queue_strategy: None,
+				tx_queue_replace_bump: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
+				tx_queue_invalid_ban_count: None,
+				tx_queue_replace_cooldown: None,
 				tx_gas_limit: None,
 				tx_time_limit: None,
 				extra_data: None,
+				extra_data_tag: None,
+				uncles_max: None,
+				uncles_max_age: None,
+				uncles_min_reward: None,
+				block_ordering: None,
 				remove_solved: None,
 				notify_work: None,
+				notify_work_pubsub: None,
 				refuse_service_transactions: None,
 			}),
 			footprint: Some(Footprint {
@@ -1732,6 +2181,11 @@ mod tests {
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				history_retention: None,
+				tracing_retention: None,
+				cold_db_path: None,
+				cold_db_after: None,
+				max_reorg_depth: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(128),
@@ -1742,17 +2196,24 @@ mod tests {
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				parallel_transactions: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
+				io_budget: None,
+				pause_on_import: None,
+				mirrors: None,
 			}),
 			vm: Some(VM {
 				jit: Some(false),
+				translated: None,
 			}),
 			misc: Some(Misc {
 				ntp_servers: Some(vec!["0.parity.pool.ntp.org:123".into()]),
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				head_feed: None,
+				head_feed_finality: None,
 				color: Some(true),
 				ports_shift: Some(0),
 				unsafe_expose: Some(false),
@@ -1762,6 +2223,13 @@ mod tests {
 				pool_size: Some(50),
 			}),
 			stratum: None,
+			health: Some(Health {
+				min_peers: None,
+				max_block_lag: Some(5),
+				max_time_drift: None,
+				min_disk_space: None,
+				max_response_time: None,
+			}),
 		});
 	}
 }
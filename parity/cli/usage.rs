@@ -379,17 +379,17 @@ macro_rules! usage {
 							}
 						)*
 
-						// Print the subcommand on its own only if it has no subsubcommands
-						if !subc_subc_exist {
-							let subc_usages : Vec<&str> = vec![
-								$(
-									concat!("[",$subc_flag_usage,"]"),
-								)*
-								$(
-									$subc_arg_usage,
-								)*
-							];
+						let subc_usages : Vec<&str> = vec![
+							$(
+								concat!("[",$subc_flag_usage,"]"),
+							)*
+							$(
+								$subc_arg_usage,
+							)*
+						];
 
+						// Print the subcommand on its own only if it has no subsubcommands or takes arguments of its own
+						if !subc_subc_exist || !subc_usages.is_empty() {
 							if subc_usages.is_empty() {
 								help.push_str(&format!("parity [options] {}\n", underscore_to_hyphen!(&stringify!($subc)[4..])));
 							} else {
@@ -535,6 +535,11 @@ macro_rules! usage {
 					}
 				)*
 
+				// Subcommands with positional arguments may be run without a subsubcommand, e.g. `parity snapshot <FILE>`
+				let subc_positional = |subc: &str| subc_usages.get(subc).map_or(false, |usages: &Vec<String>| {
+					usages.iter().any(|u| u.split_whitespace().nth(1).map_or(false, |usage| !usage.starts_with('-')))
+				});
+
 				let matches = App::new("Parity")
 				    	.global_setting(AppSettings::VersionlessSubcommands)
 						.global_setting(AppSettings::AllowLeadingHyphen) // allow for example --allow-ips -10.0.0.0/8
@@ -547,7 +552,11 @@ macro_rules! usage {
 								.about($subc_help)
 								.args(&subc_usages.get(stringify!($subc)).unwrap().iter().map(|u| Arg::from_usage(u).use_delimiter(false)).collect::<Vec<Arg>>())
 								$(
-									.setting(AppSettings::SubcommandRequired) // prevent from running `parity account`
+									.setting(if subc_positional(stringify!($subc)) {
+										AppSettings::SubcommandsNegateReqs
+									} else {
+										AppSettings::SubcommandRequired // prevent from running `parity account`
+									})
 									.subcommand(
 										SubCommand::with_name(&underscore_to_hyphen!(&stringify!($subc_subc)[stringify!($subc).len()+1..]))
 										.about($subc_subc_help)
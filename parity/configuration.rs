@@ -24,35 +24,39 @@ use std::str::FromStr;
 use cli::{Args, ArgsError};
 use hash::keccak;
 use bigint::prelude::U256;
-use bigint::hash::H256;
+use bigint::hash::{H256, clean_0x};
 use util::{Bytes, version_data, Address};
 use util::journaldb::Algorithm;
 use ansi_term::Colour;
-use ethsync::{NetworkConfiguration, is_valid_node_url};
+use ethsync::{NetworkConfiguration, BandwidthLimits, RebroadcastPolicy, is_valid_reserved_node};
 use ethcore::ethstore::ethkey::{Secret, Public};
-use ethcore::client::{VMType};
-use ethcore::miner::{MinerOptions, Banning, StratumOptions};
+use ethcore::client::{VMType, SnapshotConfig, ColdStateConfig};
+use ethcore::miner::{MinerOptions, Banning, InvalidBanning, StratumOptions, UnclePolicy, VardiffConfig, BlockOrderingStrategy, ExtraDataTemplate};
 use ethcore::verification::queue::VerifierSettings;
 
 use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration, UiConfiguration};
 use rpc_apis::ApiSet;
-use parity_rpc::NetworkSettings;
+use parity_rpc::{NetworkSettings, CallTimeout};
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home, replace_home_and_local,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy};
-use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, Pruning, Switch};
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy, passwords_from_files};
+use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, Pruning, Switch, SpecType};
+use presets::ChainPresets;
 use ethcore_logger::Config as LogConfig;
+use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use dapps::Configuration as DappsConfiguration;
 use ipfs::Configuration as IpfsConfiguration;
 use secretstore::{Configuration as SecretStoreConfiguration, NodeSecretKey};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, RewindBlockchain, RebuildBloom, CheckBlockchain, ExportState, DataFormat};
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot::{self, SnapshotCommand};
-use network::{IpFilter};
+use chain::ValidateSpec;
+use network::{IpFilter, IpVersion};
+use head_feed::{Config as HeadFeedConfig, Target as HeadFeedTarget};
 
 #[derive(Debug, PartialEq)]
 pub enum Cmd {
@@ -79,6 +83,7 @@ pub enum Cmd {
 	},
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
+	ValidateSpec(ValidateSpec),
 }
 
 pub struct Execute {
@@ -109,7 +114,7 @@ impl Configuration {
 		let pruning = self.args.arg_pruning.parse()?;
 		let pruning_history = self.args.arg_pruning_history;
 		let vm_type = self.vm_type()?;
-		let spec = self.chain().parse()?;
+		let spec = self.spec_type()?;
 		let mode = match self.args.arg_mode.as_ref() {
 			"last" => None,
 			mode => Some(to_mode(&mode, self.args.arg_mode_timeout, self.args.arg_mode_alarm)?),
@@ -139,10 +144,19 @@ impl Configuration {
 			}
 		}
 		let warp_sync = !self.args.flag_no_warp && fat_db != Switch::On && tracing != Switch::On && pruning != Pruning::Specific(Algorithm::Archive);
+		let warp_snapshot_hash = self.warp_snapshot_hash()?;
+		if warp_snapshot_hash.is_some() && !warp_sync {
+			writeln!(&mut stderr(), "Warning: --warp-snapshot-hash is ignored because Warp Sync is disabled").expect("Error writing to stderr");
+		}
+		if self.args.flag_beam_sync && !warp_sync {
+			writeln!(&mut stderr(), "Warning: --beam-sync is ignored because Warp Sync is disabled").expect("Error writing to stderr");
+		}
+		let beam_sync = self.beam_sync(warp_sync);
 		let geth_compatibility = self.args.flag_geth;
 		let mut dapps_conf = self.dapps_config();
 		let ipfs_conf = self.ipfs_config();
 		let secretstore_conf = self.secretstore_config()?;
+		let (private_provider_conf, private_enc_conf, private_tx_enabled) = self.private_provider_config()?;
 		let format = self.format()?;
 
 		if self.args.arg_jsonrpc_server_threads.is_some() && dapps_conf.enabled {
@@ -183,12 +197,60 @@ impl Configuration {
 			}
 		} else if self.args.cmd_tools && self.args.cmd_tools_hash {
 			Cmd::Hash(self.args.arg_tools_hash_file)
+		} else if self.args.cmd_chain && self.args.cmd_chain_validate {
+			Cmd::ValidateSpec(ValidateSpec {
+				file: self.args.arg_chain_validate_file,
+				cache_dir: dirs.cache,
+			})
 		} else if self.args.cmd_db && self.args.cmd_db_kill {
 			Cmd::Blockchain(BlockchainCmd::Kill(KillBlockchain {
 				spec: spec,
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_db_rewind {
+			Cmd::Blockchain(BlockchainCmd::Rewind(RewindBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				tracing: tracing,
+				fat_db: fat_db,
+				to: to_block_id(self.args.arg_db_rewind_block.as_ref().map_or("latest", |b| b.as_str()))?,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_rebuild_bloom {
+			Cmd::Blockchain(BlockchainCmd::RebuildBloom(RebuildBloom {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				tracing: tracing,
+				fat_db: fat_db,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_check {
+			Cmd::Blockchain(BlockchainCmd::Check(CheckBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				tracing: tracing,
+				fat_db: fat_db,
+				from_block: to_block_id(&self.args.arg_db_check_from)?,
+				to_block: to_block_id(&self.args.arg_db_check_to)?,
+				repair: self.args.flag_db_check_repair,
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
@@ -291,6 +353,7 @@ impl Configuration {
 					at: to_block_id(&self.args.arg_export_state_at)?,
 					storage: !self.args.flag_export_state_no_storage,
 					code: !self.args.flag_export_state_no_code,
+					genesis: self.args.flag_export_state_genesis,
 					min_balance: self.args.arg_export_state_min_balance.and_then(|s| to_u256(&s).ok()),
 					max_balance: self.args.arg_export_state_max_balance.and_then(|s| to_u256(&s).ok()),
 				};
@@ -298,6 +361,23 @@ impl Configuration {
 			} else {
 				unreachable!();
 			}
+		} else if self.args.cmd_snapshot && self.args.cmd_snapshot_verify {
+			let verify_cmd = SnapshotCommand {
+				cache_config: cache_config,
+				dirs: dirs,
+				spec: spec,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				tracing: tracing,
+				fat_db: fat_db,
+				compaction: compaction,
+				file_path: self.args.arg_snapshot_verify_path.clone(),
+				wal: wal,
+				kind: snapshot::Kind::Verify,
+				block_at: to_block_id("latest")?, // unimportant.
+			};
+			Cmd::Snapshot(verify_cmd)
 		} else if self.args.cmd_snapshot {
 			let snapshot_cmd = SnapshotCommand {
 				cache_config: cache_config,
@@ -349,19 +429,31 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.arg_pruning_memory,
+				history_retention: self.args.arg_history_retention,
+				tracing_retention: self.args.arg_tracing_retention,
+				cold_state: self.cold_state_config(),
+				max_reorg_depth: self.args.arg_max_reorg_depth,
+				parallel_transactions: self.args.flag_parallel_transactions,
 				daemon: daemon,
 				logger_config: logger_config.clone(),
 				miner_options: self.miner_options(self.args.arg_reseal_min_period)?,
 				ntp_servers: self.ntp_servers(),
+				health_thresholds: self.health_thresholds(),
 				ws_conf: ws_conf,
 				http_conf: http_conf,
 				ipc_conf: ipc_conf,
+				rpc_cache_size: self.args.arg_jsonrpc_cache_size,
+				rpc_cache_finality: self.args.arg_jsonrpc_cache_finality,
+				rpc_call_cache_size: self.args.arg_jsonrpc_call_cache * 1024 * 1024,
+				rpc_call_timeout: self.rpc_call_timeout(),
+				rpc_authorization_url: self.args.arg_jsonrpc_authorization_url.clone(),
 				net_conf: net_conf,
 				network_id: network_id,
 				acc_conf: self.accounts_config()?,
 				gas_pricer_conf: self.gas_pricer_config()?,
 				miner_extras: self.miner_extras()?,
 				stratum: self.stratum_options()?,
+				notify_work_pubsub: self.args.flag_notify_work_pubsub,
 				update_policy: update_policy,
 				mode: mode,
 				tracing: tracing,
@@ -370,6 +462,8 @@ impl Configuration {
 				wal: wal,
 				vm_type: vm_type,
 				warp_sync: warp_sync,
+				warp_snapshot_hash: warp_snapshot_hash,
+				beam_sync: beam_sync,
 				public_node: public_node,
 				geth_compatibility: geth_compatibility,
 				net_settings: self.network_settings()?,
@@ -377,18 +471,25 @@ impl Configuration {
 				ipfs_conf: ipfs_conf,
 				ui_conf: ui_conf,
 				secretstore_conf: secretstore_conf,
+				private_provider_conf: private_provider_conf,
+				private_encryptor_conf: private_enc_conf,
+				private_tx_enabled: private_tx_enabled,
 				dapp: self.dapp_to_open()?,
 				ui: self.args.cmd_ui,
 				name: self.args.arg_identity,
 				custom_bootnodes: self.args.arg_bootnodes.is_some(),
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
+				snapshot_conf: self.snapshot_config(),
+				snapshot_mirrors: self.snapshot_mirrors()?,
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
 				serve_light: !self.args.flag_no_serve_light,
+				tx_rebroadcast: self.tx_rebroadcast_policy(),
 				light: self.args.flag_light,
 				no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
 				whisper: whisper_config,
+				head_feed: self.head_feed_config()?,
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -402,6 +503,8 @@ impl Configuration {
 	fn vm_type(&self) -> Result<VMType, String> {
 		if self.args.flag_jitvm {
 			VMType::jit().ok_or("Parity is built without the JIT EVM.".into())
+		} else if self.args.flag_translated_evm {
+			VMType::translated().ok_or("Parity is built without the translated EVM.".into())
 		} else {
 			Ok(VMType::Interpreter)
 		}
@@ -411,6 +514,8 @@ impl Configuration {
 		let extras = MinerExtras {
 			author: self.author()?,
 			extra_data: self.extra_data()?,
+			extra_data_template: self.extra_data_template()?,
+			extra_data_tag: self.args.arg_extra_data_tag.clone(),
 			gas_floor_target: to_u256(&self.args.arg_gas_floor_target)?,
 			gas_ceil_target: to_u256(&self.args.arg_gas_cap)?,
 			engine_signer: self.engine_signer()?,
@@ -467,6 +572,24 @@ impl Configuration {
 		}
 	}
 
+	fn spec_type(&self) -> Result<SpecType, String> {
+		let spec = self.chain().parse()?;
+		// names which aren't files may refer to specs in the chains directory.
+		let preset = match (&spec, self.args.arg_chains_dir.as_ref()) {
+			(&SpecType::Custom(ref name), Some(dir)) if !Path::new(name).exists() => Some((name.clone(), dir.clone())),
+			_ => None,
+		};
+
+		match preset {
+			Some((name, dir)) => {
+				let dirs = self.directories();
+				let presets = ChainPresets::load(Path::new(&replace_home(&dirs.base, &dir)), Path::new(&dirs.cache))?;
+				Ok(presets.get(&name).map_or(spec, |path| SpecType::Custom(path.to_string_lossy().into_owned())))
+			},
+			None => Ok(spec),
+		}
+	}
+
 	fn max_peers(&self) -> u32 {
 		let peers = self.args.arg_max_peers as u32;
 		max(self.min_peers(), peers)
@@ -479,6 +602,10 @@ impl Configuration {
 		}
 	}
 
+	fn ip_version(&self) -> Result<IpVersion, String> {
+		IpVersion::parse(&self.args.arg_ip_version).ok_or_else(|| format!("Invalid IP version: {}", self.args.arg_ip_version))
+	}
+
 	fn min_peers(&self) -> u32 {
 		self.args.arg_peers.unwrap_or(self.args.arg_min_peers) as u32
 	}
@@ -515,10 +642,22 @@ impl Configuration {
 				listen_addr: self.stratum_interface(),
 				port: self.args.arg_ports_shift + self.args.arg_stratum_port,
 				secret: self.args.arg_stratum_secret.as_ref().map(|s| s.parse::<H256>().unwrap_or_else(|_| keccak(s))),
+				vardiff: self.stratum_vardiff()?,
 			}))
 		} else { Ok(None) }
 	}
 
+	fn stratum_vardiff(&self) -> Result<Option<VardiffConfig>, String> {
+		match self.args.arg_stratum_share_time {
+			Some(0) => Err("--stratum-share-time must be greater than zero".into()),
+			Some(share_time) => Ok(Some(VardiffConfig {
+				min_difficulty: to_u256(&self.args.arg_stratum_min_share_difficulty)?,
+				share_time: Duration::from_secs(share_time),
+			})),
+			None => Ok(None),
+		}
+	}
+
 	fn miner_options(&self, reseal_min_period: u64) -> Result<MinerOptions, String> {
 		if self.args.flag_force_sealing && reseal_min_period == 0 {
 			return Err("Force sealing can't be used with reseal_min_period = 0".into());
@@ -540,11 +679,21 @@ impl Configuration {
 			tx_queue_memory_limit: if self.args.arg_tx_queue_mem_limit > 0 {
 				Some(self.args.arg_tx_queue_mem_limit as usize * 1024 * 1024)
 			} else { None },
+			tx_queue_sender_limit: match self.args.arg_tx_queue_per_sender {
+				0 => None,
+				limit => Some(limit),
+			},
+			tx_queue_sender_memory_limit: match self.args.arg_tx_queue_per_sender_mem_limit {
+				0 => None,
+				limit => Some(limit as usize * 1024),
+			},
 			tx_queue_gas_limit: to_gas_limit(&self.args.arg_tx_queue_gas)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
+			tx_queue_replace_bump: self.args.arg_tx_queue_replace_bump,
 			pending_set: to_pending_set(&self.args.arg_relay_set)?,
 			reseal_min_period: Duration::from_millis(reseal_min_period),
 			reseal_max_period: Duration::from_millis(self.args.arg_reseal_max_period),
+			seal_interval: self.args.arg_seal_interval.map(Duration::from_millis),
 			work_queue_size: self.args.arg_work_queue_size,
 			enable_resubmission: !self.args.flag_remove_solved,
 			tx_queue_banning: match self.args.arg_tx_time_limit {
@@ -555,7 +704,27 @@ impl Configuration {
 				},
 				None => Banning::Disabled,
 			},
+			tx_queue_invalid_banning: match self.args.arg_tx_queue_invalid_ban_count {
+				0 => None,
+				count => Some(InvalidBanning {
+					min_offends: count,
+					ban_duration: Duration::from_secs(self.args.arg_tx_queue_ban_time as u64),
+				}),
+			},
+			tx_queue_replace_cooldown: match self.args.arg_tx_queue_replace_cooldown {
+				0 => None,
+				secs => Some(Duration::from_secs(secs as u64)),
+			},
 			refuse_service_transactions: self.args.flag_refuse_service_transactions,
+			uncle_policy: UnclePolicy {
+				max_count: self.args.arg_uncles_max,
+				max_age: self.args.arg_uncles_max_age,
+				min_reward: match self.args.arg_uncles_min_reward {
+					Some(ref d) => to_u256(d)?,
+					None => U256::zero(),
+				},
+			},
+			block_ordering: self.args.arg_block_ordering.parse()?,
 		};
 
 		Ok(options)
@@ -565,6 +734,37 @@ impl Configuration {
 		self.args.arg_ports_shift + self.args.arg_ui_port
 	}
 
+	fn rpc_call_timeout(&self) -> CallTimeout {
+		let to_timeout = |ms| match ms {
+			0 => None,
+			ms => Some(Duration::from_millis(ms)),
+		};
+		CallTimeout {
+			trusted: to_timeout(self.args.arg_jsonrpc_trusted_call_timeout),
+			untrusted: to_timeout(self.args.arg_jsonrpc_call_timeout),
+		}
+	}
+
+	fn head_feed_config(&self) -> Result<Option<HeadFeedConfig>, String> {
+		match self.args.arg_head_feed {
+			Some(ref target) => Ok(Some(HeadFeedConfig {
+				target: HeadFeedTarget::parse(target)?,
+				finality: self.args.arg_head_feed_finality,
+			})),
+			None => Ok(None),
+		}
+	}
+
+	fn tx_rebroadcast_policy(&self) -> Option<RebroadcastPolicy> {
+		match self.args.arg_tx_rebroadcast_interval {
+			0 => None,
+			interval => Some(RebroadcastPolicy {
+				interval: Duration::from_secs(interval),
+				max_interval: Duration::from_secs(max(interval, self.args.arg_tx_rebroadcast_max_interval)),
+			}),
+		}
+	}
+
 	fn ntp_servers(&self) -> Vec<String> {
 		self.args.arg_ntp_servers.split(",").map(str::to_owned).collect()
 	}
@@ -628,6 +828,27 @@ impl Configuration {
 		})
 	}
 
+	fn private_provider_config(&self) -> Result<(ProviderConfig, EncryptorConfig, bool), String> {
+		let passwords = match self.args.arg_private_passwords.as_ref() {
+			Some(file) => passwords_from_files(&[file.clone()])?,
+			None => Vec::new(),
+		};
+
+		let provider_conf = ProviderConfig {
+			validator_accounts: to_addresses(&self.args.arg_private_validators)?,
+			signer_account: self.args.arg_private_signer.clone().and_then(|account| to_address(Some(account)).ok()),
+			passwords: passwords.clone(),
+		};
+
+		let encryptor_conf = EncryptorConfig {
+			base_url: self.args.arg_private_sstore_url.clone(),
+			key_server_account: self.args.arg_private_account.clone().and_then(|account| to_address(Some(account)).ok()),
+			passwords: passwords,
+		};
+
+		Ok((provider_conf, encryptor_conf, self.args.flag_private_enabled))
+	}
+
 	fn ipfs_config(&self) -> IpfsConfiguration {
 		IpfsConfiguration {
 			enabled: self.args.flag_ipfs_api,
@@ -692,12 +913,21 @@ impl Configuration {
 
 	fn extra_data(&self) -> Result<Bytes, String> {
 		match self.args.arg_extradata.as_ref().or(self.args.arg_extra_data.as_ref()) {
+			// templates are validated by the miner once the engine limit is known
+			Some(x) if is_extra_data_template(x) => Ok(version_data()),
 			Some(x) if x.len() <= 32 => Ok(x.as_bytes().to_owned()),
 			None => Ok(version_data()),
 			Some(_) => Err("Extra data must be at most 32 characters".into()),
 		}
 	}
 
+	fn extra_data_template(&self) -> Result<Option<ExtraDataTemplate>, String> {
+		match self.args.arg_extradata.as_ref().or(self.args.arg_extra_data.as_ref()) {
+			Some(x) if is_extra_data_template(x) => x.parse().map(Some).map_err(|e| format!("Invalid extra data template: {}", e)),
+			_ => Ok(None),
+		}
+	}
+
 	fn init_reserved_nodes(&self) -> Result<Vec<String>, String> {
 		use std::fs::File;
 
@@ -707,7 +937,7 @@ impl Configuration {
 				let mut node_file = File::open(path).map_err(|e| format!("Error opening reserved nodes file: {}", e))?;
 				node_file.read_to_string(&mut buffer).map_err(|_| "Error reading reserved node file")?;
 				let lines = buffer.lines().map(|s| s.trim().to_owned()).filter(|s| !s.is_empty() && !s.starts_with("#")).collect::<Vec<_>>();
-				if let Some(invalid) = lines.iter().find(|s| !is_valid_node_url(s)) {
+				if let Some(invalid) = lines.iter().find(|s| !is_valid_reserved_node(s)) {
 					return Err(format!("Invalid node address format given for a boot node: {}", invalid));
 				}
 				Ok(lines)
@@ -732,6 +962,7 @@ impl Configuration {
 	fn net_config(&self) -> Result<NetworkConfiguration, String> {
 		let mut ret = NetworkConfiguration::new();
 		ret.nat_enabled = self.args.arg_nat == "any" || self.args.arg_nat == "upnp";
+		ret.nat_pmp_enabled = self.args.arg_nat == "any" || self.args.arg_nat == "natpmp";
 		ret.boot_nodes = to_bootnodes(&self.args.arg_bootnodes)?;
 		let (listen, public) = self.net_addresses()?;
 		ret.listen_address = Some(format!("{}", listen));
@@ -748,7 +979,11 @@ impl Configuration {
 		ret.min_peers = self.min_peers();
 		ret.snapshot_peers = self.snapshot_peers();
 		ret.ip_filter = self.ip_filter()?;
+		ret.ip_version = self.ip_version()?;
 		ret.max_pending_peers = self.max_pending_peers();
+		ret.max_peers_per_ip = self.args.arg_max_peers_per_ip as u32;
+		ret.max_peers_per_subnet = self.args.arg_max_peers_per_subnet as u32;
+		ret.bandwidth = self.bandwidth_limits();
 		let mut net_path = PathBuf::from(self.directories().base);
 		net_path.push("network");
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
@@ -757,10 +992,31 @@ impl Configuration {
 		Ok(ret)
 	}
 
+	fn bandwidth_limits(&self) -> BandwidthLimits {
+		let rate = |kbps: u32| kbps as u64 * 1024;
+		BandwidthLimits {
+			upload: rate(self.args.arg_max_upload_rate),
+			download: rate(self.args.arg_max_download_rate),
+			peer_upload: rate(self.args.arg_max_peer_upload_rate),
+			peer_download: rate(self.args.arg_max_peer_download_rate),
+		}
+	}
+
 	fn network_id(&self) -> Option<u64> {
 		self.args.arg_network_id.or(self.args.arg_networkid)
 	}
 
+	fn beam_sync(&self, warp_sync: bool) -> bool {
+		self.args.flag_beam_sync && warp_sync
+	}
+
+	fn warp_snapshot_hash(&self) -> Result<Option<H256>, String> {
+		match self.args.arg_warp_snapshot_hash {
+			Some(ref hash) => clean_0x(hash).parse().map(Some).map_err(|_| format!("Invalid snapshot hash: {}", hash)),
+			None => Ok(None),
+		}
+	}
+
 	fn rpc_apis(&self) -> String {
 		let mut apis: Vec<&str> = self.args.arg_rpcapi
 			.as_ref()
@@ -928,6 +1184,13 @@ impl Configuration {
 		})
 	}
 
+	fn cold_state_config(&self) -> Option<ColdStateConfig> {
+		self.args.arg_cold_db_path.as_ref().map(|path| ColdStateConfig {
+			path: replace_home(&self.directories().base, path).into(),
+			after: self.args.arg_cold_db_after,
+		})
+	}
+
 	fn directories(&self) -> Directories {
 		use path;
 
@@ -1100,6 +1363,26 @@ impl Configuration {
 		!ui_disabled && cfg!(feature = "ui-enabled")
 	}
 
+	fn snapshot_config(&self) -> SnapshotConfig {
+		SnapshotConfig {
+			io_budget: self.args.arg_snapshot_io_budget.map(|mb| mb * 1024 * 1024),
+			pause_on_import: self.args.flag_snapshot_pause_on_import,
+		}
+	}
+
+	fn snapshot_mirrors(&self) -> Result<Vec<String>, String> {
+		match self.args.arg_snapshot_mirrors {
+			Some(ref mirrors) => mirrors.split(',')
+				.filter(|mirror| !mirror.is_empty())
+				.map(|mirror| match mirror.starts_with("http://") || mirror.starts_with("https://") {
+					true => Ok(mirror.to_owned()),
+					false => Err(format!("Invalid snapshot mirror: {}", mirror)),
+				})
+				.collect(),
+			None => Ok(Vec::new()),
+		}
+	}
+
 	fn verifier_settings(&self) -> VerifierSettings {
 		let mut settings = VerifierSettings::default();
 		settings.scale_verifiers = self.args.flag_scale_verifiers;
@@ -1116,6 +1399,21 @@ impl Configuration {
 			target_message_pool_size: self.args.arg_whisper_pool_size * 1024 * 1024,
 		}
 	}
+
+	fn health_thresholds(&self) -> ::node_health::Thresholds {
+		::node_health::Thresholds {
+			min_peers: self.args.arg_health_min_peers,
+			max_block_lag: self.args.arg_health_max_block_lag,
+			max_time_drift: self.args.arg_health_max_time_drift,
+			min_disk_space: self.args.arg_health_min_disk_space * 1024 * 1024,
+			max_response_time: self.args.arg_health_max_response_time,
+		}
+	}
+}
+
+/// Extra data containing braces is treated as a template.
+fn is_extra_data_template(extra_data: &str) -> bool {
+	extra_data.contains('{') || extra_data.contains('}')
 }
 
 #[cfg(test)]
@@ -1131,7 +1429,7 @@ mod tests {
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 
 	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, RewindBlockchain, RebuildBloom, CheckBlockchain, DataFormat, ExportState};
 	use cli::Args;
 	use dir::{Directories, default_hypervisor_path};
 	use helpers::{default_network_config};
@@ -1139,6 +1437,7 @@ mod tests {
 	use presale::ImportWallet;
 	use rpc::{WsConfiguration, UiConfiguration};
 	use run::RunCmd;
+	use snapshot::{SnapshotCommand, Kind as SnapshotKind};
 
 	use network::{AllowIP, IpFilter};
 
@@ -1164,6 +1463,16 @@ mod tests {
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Version);
 	}
 
+	#[test]
+	fn test_command_chain_validate() {
+		let args = vec!["parity", "chain", "validate", "spec.json"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::ValidateSpec(ValidateSpec {
+			file: Some("spec.json".into()),
+			cache_dir: Directories::default().cache,
+		}));
+	}
+
 	#[test]
 	fn test_command_account_new() {
 		let args = vec!["parity", "account", "new"];
@@ -1260,6 +1569,85 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_db_rewind() {
+		let args = vec!["parity", "db", "rewind", "100"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Rewind(RewindBlockchain {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			to: BlockId::Number(100),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_check() {
+		let args = vec!["parity", "db", "check", "--from", "100", "--repair"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Check(CheckBlockchain {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			from_block: BlockId::Number(100),
+			to_block: BlockId::Latest,
+			repair: true,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_rebuild_bloom() {
+		let args = vec!["parity", "db", "rebuild-bloom"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::RebuildBloom(RebuildBloom {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+		})));
+	}
+
+	#[test]
+	fn test_command_snapshot_verify() {
+		let args = vec!["parity", "snapshot", "verify", "snapshot.dump"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Snapshot(SnapshotCommand {
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			spec: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			compaction: Default::default(),
+			file_path: Some("snapshot.dump".into()),
+			wal: true,
+			kind: SnapshotKind::Verify,
+			block_at: BlockId::Latest,
+		}));
+	}
+
 	#[test]
 	fn test_command_state_export() {
 		let args = vec!["parity", "export", "state", "state.json"];
@@ -1280,6 +1668,7 @@ mod tests {
 			at: BlockId::Latest,
 			storage: true,
 			code: true,
+			genesis: false,
 			min_balance: None,
 			max_balance: None,
 		})));
@@ -1346,6 +1735,11 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			history_retention: None,
+			tracing_retention: None,
+			cold_state: None,
+			max_reorg_depth: None,
+			parallel_transactions: false,
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
@@ -1355,13 +1749,21 @@ mod tests {
 				"2.parity.pool.ntp.org:123".into(),
 				"3.parity.pool.ntp.org:123".into(),
 			],
+			health_thresholds: Default::default(),
 			ws_conf: Default::default(),
 			http_conf: Default::default(),
 			ipc_conf: Default::default(),
+			rpc_cache_size: 1000,
+			rpc_cache_finality: 64,
+			rpc_call_cache_size: 16 * 1024 * 1024,
+			rpc_call_timeout: Default::default(),
+			rpc_authorization_url: None,
 			net_conf: default_network_config(),
 			network_id: None,
 			public_node: false,
 			warp_sync: true,
+			warp_snapshot_hash: None,
+			beam_sync: false,
 			acc_conf: Default::default(),
 			gas_pricer_conf: Default::default(),
 			miner_extras: Default::default(),
@@ -1377,20 +1779,28 @@ mod tests {
 			ipfs_conf: Default::default(),
 			ui_conf: Default::default(),
 			secretstore_conf: Default::default(),
+			private_provider_conf: Default::default(),
+			private_encryptor_conf: Default::default(),
+			private_tx_enabled: false,
 			ui: false,
 			dapp: None,
 			name: "".into(),
 			custom_bootnodes: false,
 			fat_db: Default::default(),
 			no_periodic_snapshot: false,
+			snapshot_conf: Default::default(),
+			snapshot_mirrors: Vec::new(),
 			stratum: None,
+			notify_work_pubsub: false,
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
 			serve_light: true,
+			tx_rebroadcast: Some(RebroadcastPolicy::default()),
 			light: false,
 			no_persistent_txqueue: false,
 			whisper: Default::default(),
+			head_feed: None,
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		expected.secretstore_conf.http_enabled = cfg!(feature = "secretstore");
@@ -1407,6 +1817,9 @@ mod tests {
 		let conf1 = parse(&["parity", "--tx-queue-strategy", "gas_factor"]);
 		let conf2 = parse(&["parity", "--tx-queue-strategy", "gas_price"]);
 		let conf3 = parse(&["parity", "--tx-queue-strategy", "gas"]);
+		let conf4 = parse(&["parity", "--tx-queue-strategy", "gas", "--tx-queue-replace-bump", "25"]);
+		let conf5 = parse(&["parity", "--tx-queue-strategy", "gas", "--tx-queue-replace-bump", "25",
+			"--tx-queue-per-sender", "16", "--tx-queue-per-sender-mem-limit", "64"]);
 
 		// then
 		let min_period = conf0.args.arg_reseal_min_period;
@@ -1417,6 +1830,92 @@ mod tests {
 		assert_eq!(conf2.miner_options(min_period).unwrap(), mining_options);
 		mining_options.tx_queue_strategy = PrioritizationStrategy::GasAndGasPrice;
 		assert_eq!(conf3.miner_options(min_period).unwrap(), mining_options);
+		mining_options.tx_queue_replace_bump = 25;
+		assert_eq!(conf4.miner_options(min_period).unwrap(), mining_options);
+		mining_options.tx_queue_sender_limit = Some(16);
+		mining_options.tx_queue_sender_memory_limit = Some(64 * 1024);
+		assert_eq!(conf5.miner_options(min_period).unwrap(), mining_options);
+
+		let conf6 = parse(&["parity", "--tx-queue-invalid-ban-count", "3", "--tx-queue-ban-time", "60", "--tx-queue-replace-cooldown", "30"]);
+		let options = conf6.miner_options(min_period).unwrap();
+		assert_eq!(options.tx_queue_invalid_banning, Some(InvalidBanning { min_offends: 3, ban_duration: Duration::from_secs(60) }));
+		assert_eq!(options.tx_queue_replace_cooldown, Some(Duration::from_secs(30)));
+	}
+
+	#[test]
+	fn should_parse_block_ordering() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--block-ordering", "round_robin"]);
+		let conf2 = parse(&["parity", "--block-ordering", "fifo"]);
+
+		let min_period = conf0.args.arg_reseal_min_period;
+		assert_eq!(conf0.miner_options(min_period).unwrap().block_ordering, BlockOrderingStrategy::Queue);
+		assert_eq!(conf1.miner_options(min_period).unwrap().block_ordering, BlockOrderingStrategy::RoundRobin);
+		assert!(conf2.miner_options(min_period).is_err());
+	}
+
+	#[test]
+	fn should_parse_extra_data_template() {
+		let conf0 = parse(&["parity", "--extra-data", "pool"]);
+		let conf1 = parse(&["parity", "--extra-data", "{tag}/{chain}#{counter}", "--extra-data-tag", "pool"]);
+		let conf2 = parse(&["parity", "--extra-data", "{height}"]);
+
+		let extras0 = conf0.miner_extras().unwrap();
+		assert_eq!(extras0.extra_data, b"pool".to_vec());
+		assert_eq!(extras0.extra_data_template, None);
+
+		let extras1 = conf1.miner_extras().unwrap();
+		assert_eq!(extras1.extra_data_template, Some("{tag}/{chain}#{counter}".parse().unwrap()));
+		assert_eq!(extras1.extra_data_tag, "pool".to_owned());
+		assert!(conf2.miner_extras().is_err());
+	}
+
+	#[test]
+	fn should_parse_notify_work_pubsub() {
+		match parse(&["parity", "--notify-work-pubsub"]).into_command().unwrap().cmd {
+			Cmd::Run(c) => assert!(c.notify_work_pubsub),
+			_ => panic!("Should be Cmd::Run"),
+		}
+	}
+
+	#[test]
+	fn should_select_chain_from_chains_dir() {
+		let dir = RandomTempPath::create_dir();
+		let spec_path = dir.as_path().join("devnet.json");
+		File::create(&spec_path).unwrap().write_all(include_str!("../ethcore/res/instant_seal.json").as_bytes()).unwrap();
+		let base = RandomTempPath::create_dir();
+
+		let conf = parse(&["parity", "--base-path", base.as_str(), "--chain", "devnet", "--chains-dir", dir.as_str()]);
+		assert_eq!(conf.spec_type().unwrap(), SpecType::Custom(spec_path.to_string_lossy().into_owned()));
+		let conf = parse(&["parity", "--base-path", base.as_str(), "--chain", "other", "--chains-dir", dir.as_str()]);
+		assert_eq!(conf.spec_type().unwrap(), SpecType::Custom("other".into()));
+		let conf = parse(&["parity", "--base-path", base.as_str(), "--chain", "kovan", "--chains-dir", dir.as_str()]);
+		assert_eq!(conf.spec_type().unwrap(), SpecType::Kovan);
+	}
+
+	#[test]
+	fn should_parse_cold_state_config() {
+		let conf = parse(&["parity", "--base-path", "/test", "--cold-db-path", "$BASE/cold", "--cold-db-after", "1000"]);
+		assert_eq!(conf.cold_state_config(), Some(ColdStateConfig {
+			path: "/test/cold".into(),
+			after: 1000,
+		}));
+		assert_eq!(parse(&["parity"]).cold_state_config(), None);
+	}
+
+	#[test]
+	fn should_parse_uncle_policy() {
+		use ethcore::miner::UnclePolicy;
+
+		let conf = parse(&["parity", "--uncles-max", "0"]);
+		assert_eq!(conf.miner_options(2000).unwrap().uncle_policy.max_count, Some(0));
+
+		let conf = parse(&["parity", "--uncles-max-age", "3", "--uncles-min-reward", "0x10"]);
+		assert_eq!(conf.miner_options(2000).unwrap().uncle_policy, UnclePolicy {
+			max_count: None,
+			max_age: Some(3),
+			min_reward: 16.into(),
+		});
 	}
 
 	#[test]
@@ -1507,6 +2006,126 @@ mod tests {
 		assert_eq!(conf3.rpc_hosts(), Some(vec!["parity.io".into(), "something.io".into()]));
 	}
 
+	#[test]
+	fn should_parse_rpc_call_timeouts() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-call-timeout", "0", "--jsonrpc-trusted-call-timeout", "1500"]);
+
+		// then
+		assert_eq!(conf0.rpc_call_timeout(), CallTimeout::default());
+		assert_eq!(conf1.rpc_call_timeout(), CallTimeout {
+			trusted: Some(Duration::from_millis(1500)),
+			untrusted: None,
+		});
+	}
+
+	#[test]
+	fn should_parse_seal_interval() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--seal-interval", "1500"]);
+
+		// then
+		assert_eq!(conf0.miner_options(2000).unwrap().seal_interval, None);
+		assert_eq!(conf1.miner_options(2000).unwrap().seal_interval, Some(Duration::from_millis(1500)));
+	}
+
+	#[test]
+	fn should_parse_peer_limits() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]).net_config().unwrap();
+		let conf1 = parse(&["parity", "--max-peers-per-ip", "1", "--max-peers-per-subnet", "0"]).net_config().unwrap();
+
+		// then
+		assert_eq!((conf0.max_peers_per_ip, conf0.max_peers_per_subnet), (4, 8));
+		assert_eq!((conf1.max_peers_per_ip, conf1.max_peers_per_subnet), (1, 0));
+	}
+
+	#[test]
+	fn should_parse_bandwidth_limits() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]).net_config().unwrap();
+		let conf1 = parse(&["parity", "--max-upload-rate", "512", "--max-peer-download-rate", "64"]).net_config().unwrap();
+
+		// then
+		assert_eq!(conf0.bandwidth, BandwidthLimits::default());
+		assert_eq!(conf1.bandwidth, BandwidthLimits {
+			upload: 512 * 1024,
+			download: 0,
+			peer_upload: 0,
+			peer_download: 64 * 1024,
+		});
+	}
+
+	#[test]
+	fn should_parse_warp_snapshot_hash() {
+		// given
+		let hash = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--warp-snapshot-hash", hash]);
+		let conf2 = parse(&["parity", "--warp-snapshot-hash", "0x01"]);
+
+		// then
+		assert_eq!(conf0.warp_snapshot_hash(), Ok(None));
+		assert_eq!(conf1.warp_snapshot_hash(), Ok(Some(1.into())));
+		assert!(conf2.warp_snapshot_hash().is_err());
+	}
+
+	#[test]
+	fn should_enable_beam_sync_only_with_warp_sync() {
+		// given
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--beam-sync"]);
+
+		// then
+		assert!(!conf0.beam_sync(true));
+		assert!(conf1.beam_sync(true));
+		assert!(!conf1.beam_sync(false));
+	}
+
+	#[test]
+	fn should_parse_snapshot_mirrors() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--snapshot-mirrors", "https://a.example/snapshot,http://b.example/"]);
+		let conf2 = parse(&["parity", "--snapshot-mirrors", "ftp://a.example/snapshot"]);
+
+		// then
+		assert_eq!(conf0.snapshot_mirrors(), Ok(vec![]));
+		assert_eq!(conf1.snapshot_mirrors(), Ok(vec!["https://a.example/snapshot".into(), "http://b.example/".into()]));
+		assert!(conf2.snapshot_mirrors().is_err());
+	}
+
+	#[test]
+	fn should_select_nat_methods() {
+		// given
+
+		// when
+		let any = parse(&["parity"]).net_config().unwrap();
+		let upnp = parse(&["parity", "--nat", "upnp"]).net_config().unwrap();
+		let natpmp = parse(&["parity", "--nat", "natpmp"]).net_config().unwrap();
+		let none = parse(&["parity", "--nat", "none"]).net_config().unwrap();
+
+		// then
+		assert!(any.nat_enabled && any.nat_pmp_enabled);
+		assert!(upnp.nat_enabled && !upnp.nat_pmp_enabled);
+		assert!(!natpmp.nat_enabled && natpmp.nat_pmp_enabled);
+		assert!(!none.nat_enabled && !none.nat_pmp_enabled);
+	}
+
 	#[test]
 	fn should_parse_ipfs_hosts() {
 		// given
@@ -1853,6 +2472,49 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn ip_version() {
+		use network::IpVersion;
+
+		assert_eq!(parse(&["parity"]).ip_version(), Ok(IpVersion::Dual));
+		assert_eq!(parse(&["parity", "--ip-version", "dual-v6"]).ip_version(), Ok(IpVersion::DualPreferV6));
+		assert_eq!(parse(&["parity", "--ip-version", "v6"]).net_config().unwrap().ip_version, IpVersion::V6);
+		assert!(parse(&["parity", "--ip-version", "v5"]).ip_version().is_err());
+	}
+
+	#[test]
+	fn should_parse_head_feed_config() {
+		use head_feed::{Config, Target};
+
+		assert_eq!(parse(&["parity"]).head_feed_config(), Ok(None));
+		assert_eq!(parse(&["parity", "--head-feed", "udp://127.0.0.1:7700", "--head-feed-finality", "12"]).head_feed_config(), Ok(Some(Config {
+			target: Target::Udp("127.0.0.1:7700".parse().unwrap()),
+			finality: 12,
+		})));
+		assert!(parse(&["parity", "--head-feed", "127.0.0.1:7700"]).head_feed_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_tx_rebroadcast_policy() {
+		assert_eq!(parse(&["parity"]).tx_rebroadcast_policy(), Some(RebroadcastPolicy::default()));
+		assert_eq!(parse(&["parity", "--tx-rebroadcast-interval", "0"]).tx_rebroadcast_policy(), None);
+		assert_eq!(parse(&["parity", "--tx-rebroadcast-interval", "120", "--tx-rebroadcast-max-interval", "30"]).tx_rebroadcast_policy(), Some(RebroadcastPolicy {
+			interval: Duration::from_secs(120),
+			max_interval: Duration::from_secs(120),
+		}));
+	}
+
+	#[test]
+	fn should_parse_stratum_vardiff() {
+		assert_eq!(parse(&["parity", "--stratum"]).stratum_options().unwrap().unwrap().vardiff, None);
+		assert_eq!(parse(&["parity", "--stratum", "--stratum-share-time", "15"]).stratum_options().unwrap().unwrap().vardiff, Some(VardiffConfig {
+			min_difficulty: 1_000_000.into(),
+			share_time: Duration::from_secs(15),
+		}));
+		assert_eq!(parse(&["parity", "--stratum-share-time", "15", "--stratum-min-share-difficulty", "0x400"]).stratum_vardiff().unwrap().unwrap().min_difficulty, 1024.into());
+		assert!(parse(&["parity", "--stratum-share-time", "0"]).stratum_vardiff().is_err());
+	}
+
 	#[test]
 	fn should_use_correct_cache_path_if_base_is_set() {
 		let std = parse(&["parity"]);
@@ -211,6 +211,13 @@ impl DatabaseDirectories {
 		dir
 	}
 
+	/// Get the path for the local transactions journal.
+	pub fn transactions_journal_path(&self) -> PathBuf {
+		let mut dir = self.spec_root_path();
+		dir.push("local_transactions.journal");
+		dir
+	}
+
 	/// Get the path for the network directory.
 	pub fn network_path(&self) -> PathBuf {
 		let mut dir = self.spec_root_path();
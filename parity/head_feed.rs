@@ -0,0 +1,305 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Newline-delimited JSON feed of chain head events for external consumers.
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::io;
+#[cfg(unix)]
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use bigint::hash::H256;
+use ethcore::client::{BlockChainClient, BlockId, ChainNotify, Client};
+use parking_lot::Mutex;
+use serde_json;
+use util::Bytes;
+
+/// Number of events buffered for a slow consumer before new ones are dropped.
+const MAX_PENDING_EVENTS: usize = 1024;
+
+/// Where the events are written to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+	/// One datagram per event.
+	Udp(SocketAddr),
+	/// Stream socket of the consumer, reconnected on failure.
+	#[cfg(unix)]
+	Unix(PathBuf),
+}
+
+impl Target {
+	/// Parses `udp://HOST:PORT` or `unix://PATH`.
+	pub fn parse(s: &str) -> Result<Self, String> {
+		if s.starts_with("udp://") {
+			let addr = &s["udp://".len()..];
+			return addr.to_socket_addrs()
+				.ok()
+				.and_then(|mut addrs| addrs.next())
+				.map(Target::Udp)
+				.ok_or_else(|| format!("Invalid head feed address: {}", addr));
+		}
+
+		if s.starts_with("unix://") {
+			return Self::unix(&s["unix://".len()..]);
+		}
+
+		Err(format!("Invalid head feed target: {}. Expected udp://HOST:PORT or unix://PATH", s))
+	}
+
+	#[cfg(unix)]
+	fn unix(path: &str) -> Result<Self, String> {
+		match path.is_empty() {
+			true => Err("Head feed socket path is empty".into()),
+			false => Ok(Target::Unix(path.into())),
+		}
+	}
+
+	#[cfg(not(unix))]
+	fn unix(_path: &str) -> Result<Self, String> {
+		Err("Unix socket head feed is not supported on this platform".into())
+	}
+}
+
+/// Head feed configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+	/// Where to write the events.
+	pub target: Target,
+	/// Number of confirmations after which a block is reported as final.
+	pub finality: u64,
+}
+
+/// Single event of the feed.
+#[derive(Debug, PartialEq, Serialize)]
+struct Event {
+	/// `head`, `retracted` or `finalized`.
+	#[serde(rename="type")]
+	kind: &'static str,
+	number: u64,
+	hash: String,
+	#[serde(rename="parentHash", skip_serializing_if="Option::is_none")]
+	parent_hash: Option<String>,
+	#[serde(skip_serializing_if="Option::is_none")]
+	timestamp: Option<u64>,
+}
+
+fn hex(hash: &H256) -> String {
+	format!("0x{:?}", hash)
+}
+
+enum Sink {
+	Udp(UdpSocket, SocketAddr),
+	#[cfg(unix)]
+	Unix(UnixStream),
+}
+
+impl Sink {
+	fn open(target: &Target) -> io::Result<Self> {
+		match *target {
+			Target::Udp(addr) => {
+				let local: SocketAddr = match addr {
+					SocketAddr::V4(_) => "0.0.0.0:0",
+					SocketAddr::V6(_) => "[::]:0",
+				}.parse().expect("Valid socket address; qed");
+				Ok(Sink::Udp(UdpSocket::bind(local)?, addr))
+			},
+			#[cfg(unix)]
+			Target::Unix(ref path) => Ok(Sink::Unix(UnixStream::connect(path)?)),
+		}
+	}
+
+	fn send(&mut self, line: &[u8]) -> io::Result<()> {
+		match *self {
+			Sink::Udp(ref socket, ref addr) => socket.send_to(line, addr).map(|_| ()),
+			#[cfg(unix)]
+			Sink::Unix(ref mut stream) => stream.write_all(line),
+		}
+	}
+}
+
+fn write_events(target: Target, events: Receiver<String>) {
+	let mut sink = None;
+	for line in events {
+		if sink.is_none() {
+			sink = Sink::open(&target)
+				.map_err(|e| debug!(target: "head_feed", "Cannot open head feed {:?}: {}", target, e))
+				.ok();
+		}
+
+		let failed = match sink {
+			Some(ref mut sink) => sink.send(line.as_bytes()).is_err(),
+			None => false,
+		};
+		if failed {
+			debug!(target: "head_feed", "Head feed {:?} disconnected", target);
+			sink = None;
+		}
+	}
+}
+
+/// Publishes new heads, retracted blocks and finalized blocks of the chain.
+///
+/// Events are written from a separate thread; if the consumer can't keep up they are dropped.
+pub struct HeadFeed {
+	client: Arc<Client>,
+	finality: u64,
+	last_final: Mutex<Option<u64>>,
+	events: Mutex<SyncSender<String>>,
+}
+
+impl HeadFeed {
+	/// Starts the feed writer.
+	pub fn new(client: Arc<Client>, config: Config) -> Result<Self, String> {
+		let (tx, rx) = mpsc::sync_channel(MAX_PENDING_EVENTS);
+		let target = config.target;
+		thread::Builder::new()
+			.name("head-feed".into())
+			.spawn(move || write_events(target, rx))
+			.map_err(|e| format!("Cannot start head feed: {}", e))?;
+
+		Ok(HeadFeed {
+			client: client,
+			finality: config.finality,
+			last_final: Mutex::new(None),
+			events: Mutex::new(tx),
+		})
+	}
+
+	fn publish(&self, event: Event) {
+		let mut line = serde_json::to_string(&event).expect("Event serialization cannot fail; qed");
+		line.push('\n');
+		match self.events.lock().try_send(line) {
+			Err(TrySendError::Full(_)) => debug!(target: "head_feed", "Head feed consumer too slow, dropping event"),
+			Err(TrySendError::Disconnected(_)) => warn!(target: "head_feed", "Head feed writer stopped"),
+			Ok(()) => {},
+		}
+	}
+
+	fn block_event(&self, kind: &'static str, hash: &H256) -> Option<Event> {
+		self.client.block_header(BlockId::Hash(*hash)).map(|header| Event {
+			kind: kind,
+			number: header.number(),
+			hash: hex(hash),
+			parent_hash: if kind == "head" { Some(hex(&header.parent_hash())) } else { None },
+			timestamp: if kind == "head" { Some(header.timestamp()) } else { None },
+		})
+	}
+}
+
+/// Numbers of blocks which became final when the best block reached `best`.
+fn newly_final(last_final: Option<u64>, best: u64, finality: u64) -> ::std::ops::Range<u64> {
+	if best < finality {
+		return 0..0;
+	}
+	let latest = best - finality;
+	match last_final {
+		Some(last) if last >= latest => 0..0,
+		Some(last) => last + 1..latest + 1,
+		None => latest..latest + 1,
+	}
+}
+
+impl ChainNotify for HeadFeed {
+	fn new_blocks(
+		&self,
+		_imported: Vec<H256>,
+		_invalid: Vec<H256>,
+		enacted: Vec<H256>,
+		retracted: Vec<H256>,
+		_sealed: Vec<H256>,
+		_proposed: Vec<Bytes>,
+		_duration: u64,
+	) {
+		if enacted.is_empty() && retracted.is_empty() {
+			return;
+		}
+
+		for hash in &retracted {
+			if let Some(event) = self.block_event("retracted", hash) {
+				self.publish(event);
+			}
+		}
+		for hash in &enacted {
+			if let Some(event) = self.block_event("head", hash) {
+				self.publish(event);
+			}
+		}
+
+		let best = self.client.chain_info().best_block_number;
+		let mut last_final = self.last_final.lock();
+		for number in newly_final(*last_final, best, self.finality) {
+			if let Some(hash) = self.client.block_hash(BlockId::Number(number)) {
+				self.publish(Event {
+					kind: "finalized",
+					number: number,
+					hash: hex(&hash),
+					parent_hash: None,
+					timestamp: None,
+				});
+				*last_final = Some(number);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{Target, Event, newly_final};
+
+	#[test]
+	fn should_parse_targets() {
+		assert_eq!(Target::parse("udp://127.0.0.1:7700"), Ok(Target::Udp("127.0.0.1:7700".parse().unwrap())));
+		assert!(Target::parse("udp://127.0.0.1").is_err());
+		assert!(Target::parse("tcp://127.0.0.1:7700").is_err());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn should_parse_unix_target() {
+		assert_eq!(Target::parse("unix:///tmp/feed.sock"), Ok(Target::Unix("/tmp/feed.sock".into())));
+		assert!(Target::parse("unix://").is_err());
+	}
+
+	#[test]
+	fn should_report_each_block_once_final() {
+		assert_eq!(newly_final(None, 10, 64), 0..0);
+		assert_eq!(newly_final(None, 100, 64), 36..37);
+		assert_eq!(newly_final(Some(36), 102, 64), 37..39);
+		// reorganization below the last final block
+		assert_eq!(newly_final(Some(38), 101, 64), 0..0);
+	}
+
+	#[test]
+	fn should_serialize_events() {
+		let event = Event {
+			kind: "finalized",
+			number: 1,
+			hash: "0x01".into(),
+			parent_hash: None,
+			timestamp: None,
+		};
+		assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"type":"finalized","number":1,"hash":"0x01"}"#);
+	}
+}
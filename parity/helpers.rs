@@ -194,7 +194,7 @@ pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 #[cfg(test)]
 pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 	use ethsync::{NetworkConfiguration};
-	use super::network::IpFilter;
+	use super::network::{IpFilter, IpVersion};
 	NetworkConfiguration {
 		config_path: Some(replace_home(&::dir::default_data_path(), "$BASE/network")),
 		net_config_path: None,
@@ -202,14 +202,20 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		public_address: None,
 		udp_port: None,
 		nat_enabled: true,
+		nat_pmp_enabled: true,
 		discovery_enabled: true,
 		boot_nodes: Vec::new(),
+		node_lists: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
 		min_peers: 25,
 		snapshot_peers: 0,
 		max_pending_peers: 64,
+		max_peers_per_ip: 4,
+		max_peers_per_subnet: 8,
 		ip_filter: IpFilter::default(),
+		ip_version: IpVersion::default(),
+		bandwidth: Default::default(),
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
 	}
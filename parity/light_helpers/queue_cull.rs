@@ -17,8 +17,9 @@
 //! Service for culling the light client's transaction queue.
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use bigint::hash::H256;
 use ethcore::service::ClientIoMessage;
 use ethsync::LightSync;
 use io::{IoContext, IoHandler, TimerToken};
@@ -40,7 +41,12 @@ const TIMEOUT_MS: u64 = 1000 * 60 * 10;
 // But make each attempt last only 9 minutes
 const PURGE_TIMEOUT_MS: u64 = 1000 * 60 * 9;
 
-/// Periodically culls the transaction queue of mined transactions.
+// Check for expired transactions and transactions due for resubmission every minute.
+const RESUBMIT_TOKEN: TimerToken = 2;
+const RESUBMIT_TIMEOUT_MS: u64 = 1000 * 60;
+
+/// Periodically culls the transaction queue of mined transactions
+/// and resubmits the transactions which weren't mined yet.
 pub struct QueueCull<T> {
 	/// A handle to the client, for getting the latest block header.
 	pub client: Arc<T>,
@@ -57,24 +63,51 @@ pub struct QueueCull<T> {
 impl<T: LightChainClient + 'static> IoHandler<ClientIoMessage> for QueueCull<T> {
 	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
 		io.register_timer(TOKEN, TIMEOUT_MS).expect("Error registering timer");
+		io.register_timer(RESUBMIT_TOKEN, RESUBMIT_TIMEOUT_MS).expect("Error registering timer");
 	}
 
 	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
-		if timer != TOKEN { return }
+		match timer {
+			TOKEN => self.cull(Vec::new()),
+			RESUBMIT_TOKEN => {
+				let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+				let due = {
+					let mut txq = self.txq.write();
+					let expired = txq.expire(now);
+					if expired != 0 {
+						info!(target: "cull", "Dropped {} expired transactions.", expired);
+					}
+					txq.due_resubmissions(now)
+				};
+
+				// make sure the transactions weren't mined before resubmitting them.
+				if !due.is_empty() {
+					self.cull(due);
+				}
+			}
+			_ => {}
+		}
+	}
+}
 
+impl<T: LightChainClient + 'static> QueueCull<T> {
+	// cull the queue and resubmit the given transactions if they remain queued afterwards.
+	fn cull(&self, resubmit: Vec<H256>) {
 		let senders = self.txq.read().queued_senders();
 		if senders.is_empty() { return }
 
 		let (sync, on_demand, txq) = (self.sync.clone(), self.on_demand.clone(), self.txq.clone());
 		let best_header = self.client.best_block_header();
 		let start_nonce = self.client.engine().account_start_nonce(best_header.number());
+		let hasher = self.client.engine().params().trie_hasher;
 
 		info!(target: "cull", "Attempting to cull queued transactions from {} senders.", senders.len());
 		self.remote.spawn_with_timeout(move || {
+			let resubmit_sync = sync.clone();
 			let maybe_fetching = sync.with_context(move |ctx| {
 				// fetch the nonce of each sender in the queue.
 				let nonce_reqs = senders.iter()
-					.map(|&address| request::Account { header: best_header.clone().into(), address: address })
+					.map(|&address| request::Account { header: best_header.clone().into(), address: address, hasher: hasher })
 					.collect::<Vec<_>>();
 
 				// when they come in, update each sender to the new nonce.
@@ -82,13 +115,23 @@ impl<T: LightChainClient + 'static> IoHandler<ClientIoMessage> for QueueCull<T>
 					.expect("No back-references; therefore all back-references are valid; qed")
 					.map(move |accs| {
 						let txq = txq.write();
-						let _ = accs.into_iter()
+						let txq = accs.into_iter()
 							.map(|maybe_acc| maybe_acc.map_or(start_nonce, |acc| acc.nonce))
 							.zip(senders)
 							.fold(txq, |mut txq, (nonce, addr)| {
 								txq.cull(addr, nonce);
 								txq
 							});
+
+						let resubmit: Vec<_> = resubmit.into_iter()
+							.filter(|hash| txq.transaction(hash).is_some())
+							.collect();
+						drop(txq);
+
+						if !resubmit.is_empty() {
+							debug!(target: "cull", "Resubmitting {} queued transactions.", resubmit.len());
+							resubmit_sync.resubmit_transactions(&resubmit);
+						}
 					})
 					.map_err(|_| debug!(target: "cull", "OnDemand prematurely closed channel."))
 			});
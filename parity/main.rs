@@ -57,6 +57,7 @@ extern crate ethcore_ipc_hypervisor as hypervisor;
 extern crate ethcore_ipc_nano as nanoipc;
 extern crate ethcore_light as light;
 extern crate ethcore_logger;
+extern crate ethcore_private_tx;
 extern crate ethcore_util as util;
 extern crate ethcore_bigint as bigint;
 extern crate ethcore_network as network;
@@ -98,12 +99,14 @@ extern crate pretty_assertions;
 mod account;
 mod blockchain;
 mod cache;
+mod chain;
 mod cli;
 mod configuration;
 mod dapps;
 mod ipfs;
 mod deprecated;
 mod dir;
+mod head_feed;
 mod helpers;
 mod informant;
 mod light_helpers;
@@ -111,6 +114,7 @@ mod migration;
 mod modules;
 mod params;
 mod presale;
+mod presets;
 mod rpc;
 mod rpc_apis;
 mod run;
@@ -168,6 +172,7 @@ fn execute(command: Execute, can_restart: bool) -> Result<PostExecutionAction, S
 		},
 		Cmd::Version => Ok(PostExecutionAction::Print(Args::print_version())),
 		Cmd::Hash(maybe_file) => print_hash_of(maybe_file).map(|s| PostExecutionAction::Print(s)),
+		Cmd::ValidateSpec(validate_cmd) => chain::execute(validate_cmd).map(|s| PostExecutionAction::Print(s)),
 		Cmd::Account(account_cmd) => account::execute(account_cmd).map(|s| PostExecutionAction::Print(s)),
 		Cmd::ImportPresaleWallet(presale_cmd) => presale::execute(presale_cmd).map(|s| PostExecutionAction::Print(s)),
 		Cmd::Blockchain(blockchain_cmd) => blockchain::execute(blockchain_cmd).map(|_| PostExecutionAction::Quit),
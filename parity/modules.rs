@@ -19,7 +19,7 @@ use std::path::Path;
 
 use ethcore::client::BlockChainClient;
 use hypervisor::Hypervisor;
-use ethsync::{AttachedProtocol, SyncConfig, NetworkConfiguration, NetworkError, Params, ConnectionFilter};
+use ethsync::{AttachedProtocol, SyncConfig, NetworkConfiguration, NetworkError, Params, ConnectionFilter, PrivateTxHandler, SnapshotMirrors};
 use ethcore::snapshot::SnapshotService;
 use light::Provider;
 
@@ -152,6 +152,8 @@ pub fn sync(
 	_provider: Arc<Provider>,
 	log_settings: &LogConfig,
 	_attached_protos: Vec<AttachedProtocol>,
+	_private_tx_handler: Arc<PrivateTxHandler>,
+	_snapshot_mirrors: Option<SnapshotMirrors>,
 ) -> Result<SyncModules, NetworkError> {
 	let mut hypervisor = hypervisor_ref.take().expect("There should be hypervisor for ipc configuration");
 	let args = sync_arguments(&hypervisor.io_path, sync_cfg, net_cfg, log_settings);
@@ -183,6 +185,8 @@ pub fn sync(
 	provider: Arc<Provider>,
 	_log_settings: &LogConfig,
 	attached_protos: Vec<AttachedProtocol>,
+	private_tx_handler: Arc<PrivateTxHandler>,
+	snapshot_mirrors: Option<SnapshotMirrors>,
 	connection_filter: Option<Arc<ConnectionFilter>>,
 ) -> Result<SyncModules, NetworkError> {
 	let eth_sync = EthSync::new(Params {
@@ -192,6 +196,8 @@ pub fn sync(
 		snapshot_service: snapshot_service,
 		network_config: net_cfg,
 		attached_protos: attached_protos,
+		private_tx_handler: private_tx_handler,
+		snapshot_mirrors: snapshot_mirrors,
 	},
 	connection_filter)?;
 
@@ -22,7 +22,7 @@ use util::journaldb::Algorithm;
 use ethcore::spec::Spec;
 use ethcore::ethereum;
 use ethcore::client::Mode;
-use ethcore::miner::{GasPricer, GasPriceCalibratorOptions};
+use ethcore::miner::{GasPricer, GasPriceCalibratorOptions, ExtraDataTemplate};
 use hash_fetch::fetch::Client as FetchClient;
 use user_defaults::UserDefaults;
 
@@ -249,6 +249,8 @@ impl GasPricerConfig {
 pub struct MinerExtras {
 	pub author: Address,
 	pub extra_data: Vec<u8>,
+	pub extra_data_template: Option<ExtraDataTemplate>,
+	pub extra_data_tag: String,
 	pub gas_floor_target: U256,
 	pub gas_ceil_target: U256,
 	pub engine_signer: Address,
@@ -259,6 +261,8 @@ impl Default for MinerExtras {
 		MinerExtras {
 			author: Default::default(),
 			extra_data: version_data(),
+			extra_data_template: None,
+			extra_data_tag: String::new(),
 			gas_floor_target: U256::from(4_700_000),
 			gas_ceil_target: U256::from(6_283_184),
 			engine_signer: Default::default(),
@@ -0,0 +1,151 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain specs loaded at startup from `--chains-dir`.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use serde_json;
+use ethcore::spec::Spec;
+use params::SpecType;
+
+/// Name of the file (in the cache directory) remembering already validated specs.
+const VALIDATED_SPECS_FILE: &'static str = "chain_presets.json";
+
+/// Size and modification time of a spec file that was successfully parsed.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Validated {
+	len: u64,
+	modified: u64,
+}
+
+impl Validated {
+	fn of(path: &Path) -> Option<Self> {
+		let metadata = match fs::metadata(path) {
+			Ok(metadata) => metadata,
+			Err(_) => return None,
+		};
+		metadata.modified().ok()
+			.and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+			.map(|modified| Validated {
+				len: metadata.len(),
+				modified: modified.as_secs(),
+			})
+	}
+}
+
+/// Chain specs selectable by name (file name without `.json`) via `--chain`.
+#[derive(Debug, Default)]
+pub struct ChainPresets {
+	presets: BTreeMap<String, PathBuf>,
+}
+
+impl ChainPresets {
+	/// Loads all `*.json` specs in given directory, skipping invalid ones.
+	/// Specs not modified since they were last parsed are not parsed again.
+	pub fn load(dir: &Path, cache_dir: &Path) -> Result<Self, String> {
+		let entries = fs::read_dir(dir).map_err(|e| format!("Could not read chains directory {}: {}", dir.display(), e))?;
+		let cache_path = cache_dir.join(VALIDATED_SPECS_FILE);
+		let cached: BTreeMap<String, Validated> = File::open(&cache_path).ok()
+			.and_then(|file| serde_json::from_reader(file).ok())
+			.unwrap_or_default();
+
+		let mut presets = BTreeMap::new();
+		let mut validated = BTreeMap::new();
+		for entry in entries.filter_map(Result::ok) {
+			let path = entry.path();
+			if path.extension().map_or(true, |ext| ext != "json") {
+				continue;
+			}
+			let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+				Some(name) => name.to_owned(),
+				None => continue,
+			};
+			if name.parse::<SpecType>().ok().map_or(false, |spec| spec != SpecType::Custom(name.clone())) {
+				warn!("Ignoring chain spec {}: {} is a built-in chain.", path.display(), name);
+				continue;
+			}
+
+			let key = path.to_string_lossy().into_owned();
+			let current = match Validated::of(&path) {
+				Some(current) => current,
+				None => continue,
+			};
+			if cached.get(&key) != Some(&current) {
+				let parsed = File::open(&path)
+					.map_err(|e| e.to_string())
+					.and_then(|file| Spec::load(cache_dir, file));
+				if let Err(e) = parsed {
+					warn!("Ignoring invalid chain spec {}: {}", path.display(), e);
+					continue;
+				}
+			}
+
+			validated.insert(key, current);
+			presets.insert(name, path);
+		}
+
+		if validated != cached {
+			let saved = fs::create_dir_all(cache_dir)
+				.and_then(|_| File::create(&cache_path))
+				.map_err(|e| e.to_string())
+				.and_then(|file| serde_json::to_writer(file, &validated).map_err(|e| e.to_string()));
+			if let Err(e) = saved {
+				warn!("Could not save validated chain specs to {}: {}", cache_path.display(), e);
+			}
+		}
+
+		Ok(ChainPresets { presets: presets })
+	}
+
+	/// Path of the spec with given name.
+	pub fn get(&self, name: &str) -> Option<&PathBuf> {
+		self.presets.get(name)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::File;
+	use std::io::Write;
+	use devtools::RandomTempPath;
+	use super::{ChainPresets, VALIDATED_SPECS_FILE};
+
+	#[test]
+	fn loads_valid_specs() {
+		let dir = RandomTempPath::create_dir();
+		let cache = RandomTempPath::create_dir();
+		let write = |name: &str, content: &str| {
+			File::create(dir.as_path().join(name)).unwrap().write_all(content.as_bytes()).unwrap();
+		};
+		write("devnet.json", include_str!("../ethcore/res/instant_seal.json"));
+		write("kovan.json", include_str!("../ethcore/res/instant_seal.json"));
+		write("broken.json", "{}");
+		write("notes.txt", "");
+
+		let presets = ChainPresets::load(dir.as_path(), cache.as_path()).unwrap();
+		assert_eq!(presets.get("devnet"), Some(&dir.as_path().join("devnet.json")));
+		assert_eq!(presets.get("kovan"), None);
+		assert_eq!(presets.get("broken"), None);
+		assert_eq!(presets.get("notes"), None);
+		assert!(cache.as_path().join(VALIDATED_SPECS_FILE).exists());
+
+		let presets = ChainPresets::load(dir.as_path(), cache.as_path()).unwrap();
+		assert!(presets.get("devnet").is_some());
+	}
+}
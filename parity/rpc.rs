@@ -24,6 +24,7 @@ use dir::default_data_path;
 use helpers::{parity_ipc_path, replace_home};
 use jsonrpc_core::MetaIoHandler;
 use parity_reactor::TokioRemote;
+use parity_rpc::authorization::{self, Authorizer};
 use parity_rpc::informant::{RpcStats, Middleware};
 use parity_rpc::{self as rpc, Metadata, DomainsValidation};
 use rpc_apis::{self, ApiSet};
@@ -179,6 +180,7 @@ pub struct Dependencies<D: rpc_apis::Dependencies> {
 	pub remote: TokioRemote,
 	pub stats: Arc<RpcStats>,
 	pub pool: Option<CpuPool>,
+	pub authorizer: Option<Arc<Authorizer>>,
 }
 
 pub fn new_ws<D: rpc_apis::Dependencies>(
@@ -200,7 +202,10 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 	let handler = {
 		let mut handler = MetaIoHandler::with_middleware((
 			rpc::WsDispatcher::new(full_handler),
-			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), pool)
+			(
+				authorization::Middleware::new(deps.authorizer.clone()),
+				Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), pool),
+			),
 		));
 		let apis = conf.apis.list_apis();
 		deps.apis.extend_with_set(&mut handler, &apis);
@@ -324,12 +329,14 @@ fn with_domain(items: Option<Vec<String>>, domain: &str, addresses: &[Option<(St
 	})
 }
 
-fn setup_apis<D>(apis: ApiSet, deps: &Dependencies<D>, pool: Option<CpuPool>) -> MetaIoHandler<Metadata, Middleware<D::Notifier>>
+fn setup_apis<D>(apis: ApiSet, deps: &Dependencies<D>, pool: Option<CpuPool>)
+	-> MetaIoHandler<Metadata, (authorization::Middleware, Middleware<D::Notifier>)>
 	where D: rpc_apis::Dependencies
 {
-	let mut handler = MetaIoHandler::with_middleware(
-		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), pool)
-	);
+	let mut handler = MetaIoHandler::with_middleware((
+		authorization::Middleware::new(deps.authorizer.clone()),
+		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), pool),
+	));
 	let apis = apis.list_apis();
 	deps.apis.extend_with_set(&mut handler, &apis);
 
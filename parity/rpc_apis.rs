@@ -25,19 +25,21 @@ pub use parity_rpc::dapps::{DappsService, LocalDapp};
 
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::Client;
-use ethcore::miner::{Miner, ExternalMiner};
+use ethcore::miner::{Miner, ExternalMiner, StratumWorkers};
 use ethcore::snapshot::SnapshotService;
 use ethcore_logger::RotatingLogger;
+use ethcore_private_tx::Provider as PrivateTransactionManager;
 use ethsync::{ManageNetwork, SyncProvider, LightSync};
 use hash_fetch::fetch::Client as FetchClient;
 use jsonrpc_core::{self as core, MetaIoHandler};
 use light::{TransactionQueue as LightTransactionQueue, Cache as LightDataCache};
 use light::client::LightChainClient;
+use local_store::TransactionJournal;
 use node_health::NodeHealth;
 use parity_reactor;
 use parity_rpc::dispatch::{FullDispatcher, LightDispatcher};
 use parity_rpc::informant::{ActivityNotifier, ClientNotifier};
-use parity_rpc::{Metadata, NetworkSettings};
+use parity_rpc::{Metadata, NetworkSettings, ResponseCache, CallCache, CallTimeout};
 use updater::Updater;
 use parking_lot::{Mutex, RwLock};
 
@@ -63,8 +65,12 @@ pub enum Api {
 	ParityAccounts,
 	/// Parity - Set methods (UNSAFE: Side Effects affecting node operation)
 	ParitySet,
+	/// Admin - Peer management (UNSAFE: Side Effects affecting node operation)
+	Admin,
 	/// Traces (Safe)
 	Traces,
+	/// Debug - Step by step execution traces (UNSAFE: Resource intensive)
+	Debug,
 	/// Rpc (Safe)
 	Rpc,
 	/// SecretStore (Safe)
@@ -75,6 +81,8 @@ pub enum Api {
 	Whisper,
 	/// Whisper Pub-Sub (Safe but same concerns as above).
 	WhisperPubSub,
+	/// Private transactions (UNSAFE: Side Effects (sends transactions))
+	Private,
 }
 
 impl FromStr for Api {
@@ -94,11 +102,14 @@ impl FromStr for Api {
 			"parity_pubsub" => Ok(ParityPubSub),
 			"parity_accounts" => Ok(ParityAccounts),
 			"parity_set" => Ok(ParitySet),
+			"admin" => Ok(Admin),
 			"traces" => Ok(Traces),
+			"debug" => Ok(Debug),
 			"rpc" => Ok(Rpc),
 			"secretstore" => Ok(SecretStore),
 			"shh" => Ok(Whisper),
 			"shh_pubsub" => Ok(WhisperPubSub),
+			"private" => Ok(Private),
 			api => Err(format!("Unknown api: {}", api))
 		}
 	}
@@ -179,11 +190,14 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 			Api::ParityAccounts => ("parity_accounts", "1.0"),
 			Api::ParityPubSub => ("parity_pubsub", "1.0"),
 			Api::ParitySet => ("parity_set", "1.0"),
+			Api::Admin => ("admin", "1.0"),
 			Api::Traces => ("traces", "1.0"),
+			Api::Debug => ("debug", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
 			Api::SecretStore => ("secretstore", "1.0"),
 			Api::Whisper => ("shh", "1.0"),
 			Api::WhisperPubSub => ("shh_pubsub", "1.0"),
+			Api::Private => ("private", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
 	}
@@ -221,12 +235,19 @@ pub struct FullDependencies {
 	pub updater: Arc<Updater>,
 	pub health: NodeHealth,
 	pub geth_compatibility: bool,
+	pub response_cache: Option<Arc<ResponseCache>>,
+	pub call_cache: Option<Arc<CallCache>>,
+	pub call_timeout: CallTimeout,
 	pub dapps_service: Option<Arc<DappsService>>,
 	pub dapps_address: Option<(String, u16)>,
 	pub ws_address: Option<(String, u16)>,
 	pub fetch: FetchClient,
 	pub remote: parity_reactor::Remote,
 	pub whisper_rpc: Option<::whisper::RpcFactory>,
+	pub private_tx_service: Option<Arc<PrivateTransactionManager>>,
+	pub local_store: Option<Arc<TransactionJournal>>,
+	pub stratum: Option<Arc<StratumWorkers>>,
+	pub notify_work_pubsub: bool,
 }
 
 impl FullDependencies {
@@ -273,6 +294,10 @@ impl FullDependencies {
 							pending_nonce_from_queue: self.geth_compatibility,
 							allow_pending_receipt_query: !self.geth_compatibility,
 							send_block_number_in_get_work: !self.geth_compatibility,
+							progpow_transition: self.client.engine().progpow_transition(),
+							response_cache: self.response_cache.clone(),
+							call_cache: self.call_cache.clone(),
+							call_timeout: self.call_timeout,
 						}
 					);
 					handler.extend_with(client.to_delegate());
@@ -287,6 +312,17 @@ impl FullDependencies {
 				Api::EthPubSub => {
 					if !for_generic_pubsub {
 						let client = EthPubSubClient::new(self.client.clone(), self.remote.clone());
+						let h = Arc::downgrade(&client.handler());
+						self.miner.add_transactions_listener(Box::new(move |transactions| if let Some(h) = h.upgrade() {
+							h.notify_new_transactions(transactions);
+						}));
+						let h = Arc::downgrade(&client.handler());
+						self.miner.add_pending_block_listener(Box::new(move || if let Some(h) = h.upgrade() {
+							h.notify_pending_state();
+						}));
+						if self.notify_work_pubsub {
+							self.miner.push_notifier(Box::new(client.work_notifier()));
+						}
 						self.client.add_notify(client.handler());
 						handler.extend_with(client.to_delegate());
 					}
@@ -306,6 +342,7 @@ impl FullDependencies {
 						self.client.clone(),
 						self.miner.clone(),
 						self.sync.clone(),
+						self.snapshot.clone(),
 						self.updater.clone(),
 						self.net_service.clone(),
 						self.health.clone(),
@@ -315,6 +352,9 @@ impl FullDependencies {
 						signer,
 						self.dapps_address.clone(),
 						self.ws_address.clone(),
+						self.call_timeout,
+						self.local_store.clone(),
+						self.stratum.clone(),
 					).to_delegate());
 
 					if !for_generic_pubsub {
@@ -342,9 +382,15 @@ impl FullDependencies {
 						self.fetch.clone(),
 					).to_delegate())
 				},
+				Api::Admin => {
+					handler.extend_with(AdminClient::new(&self.sync, &self.net_service).to_delegate())
+				},
 				Api::Traces => {
 					handler.extend_with(TracesClient::new(&self.client, &self.miner).to_delegate())
 				},
+				Api::Debug => {
+					handler.extend_with(DebugClient::new(&self.client, &self.miner).to_delegate())
+				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
 					handler.extend_with(RpcClient::new(modules).to_delegate());
@@ -368,6 +414,9 @@ impl FullDependencies {
 						}
 					}
 				}
+				Api::Private => {
+					handler.extend_with(PrivateClient::new(self.private_tx_service.clone()).to_delegate());
+				},
 			}
 		}
 	}
@@ -541,9 +590,15 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						self.fetch.clone(),
 					).to_delegate())
 				},
+				Api::Admin => {
+					handler.extend_with(light::AdminClient::new(&self.sync, &self.net).to_delegate())
+				},
 				Api::Traces => {
 					handler.extend_with(light::TracesClient.to_delegate())
 				},
+				Api::Debug => {
+					// replaying transactions requires the full state.
+				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
 					handler.extend_with(RpcClient::new(modules).to_delegate());
@@ -564,6 +619,9 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						handler.extend_with(::parity_whisper::rpc::WhisperPubSub::to_delegate(whisper));
 					}
 				}
+				Api::Private => {
+					handler.extend_with(PrivateClient::new(None).to_delegate());
+				},
 			}
 		}
 	}
@@ -621,7 +679,10 @@ impl ApiSet {
 				public_list.insert(Api::ParityPubSub);
 				public_list.insert(Api::ParityAccounts);
 				public_list.insert(Api::ParitySet);
+				public_list.insert(Api::Admin);
 				public_list.insert(Api::Signer);
+				public_list.insert(Api::Private);
+				public_list.insert(Api::Debug);
 				public_list
 			},
 			ApiSet::All => {
@@ -629,8 +690,11 @@ impl ApiSet {
 				public_list.insert(Api::ParityPubSub);
 				public_list.insert(Api::ParityAccounts);
 				public_list.insert(Api::ParitySet);
+				public_list.insert(Api::Admin);
 				public_list.insert(Api::Signer);
 				public_list.insert(Api::Personal);
+				public_list.insert(Api::Private);
+				public_list.insert(Api::Debug);
 				public_list
 			},
 			ApiSet::PubSub => [
@@ -659,11 +723,14 @@ mod test {
 		assert_eq!(Api::Parity, "parity".parse().unwrap());
 		assert_eq!(Api::ParityAccounts, "parity_accounts".parse().unwrap());
 		assert_eq!(Api::ParitySet, "parity_set".parse().unwrap());
+		assert_eq!(Api::Admin, "admin".parse().unwrap());
 		assert_eq!(Api::Traces, "traces".parse().unwrap());
+		assert_eq!(Api::Debug, "debug".parse().unwrap());
 		assert_eq!(Api::Rpc, "rpc".parse().unwrap());
 		assert_eq!(Api::SecretStore, "secretstore".parse().unwrap());
 		assert_eq!(Api::Whisper, "shh".parse().unwrap());
 		assert_eq!(Api::WhisperPubSub, "shh_pubsub".parse().unwrap());
+		assert_eq!(Api::Private, "private".parse().unwrap());
 		assert!("rp".parse::<Api>().is_err());
 	}
 
@@ -705,7 +772,7 @@ mod test {
 			// semi-safe
 			Api::ParityAccounts,
 			// Unsafe
-			Api::ParitySet, Api::Signer,
+			Api::ParitySet, Api::Admin, Api::Signer, Api::Private, Api::Debug,
 		].into_iter().collect();
 		assert_eq!(ApiSet::SafeContext.list_apis(), expected);
 	}
@@ -715,7 +782,7 @@ mod test {
 		assert_eq!("all".parse::<ApiSet>().unwrap(), ApiSet::List(vec![
 			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::SecretStore, Api::Whisper, Api::WhisperPubSub,
 			Api::ParityAccounts,
-			Api::ParitySet, Api::Signer,
+			Api::ParitySet, Api::Admin, Api::Signer, Api::Private, Api::Debug,
 			Api::Personal
 		].into_iter().collect()));
 	}
@@ -725,7 +792,7 @@ mod test {
 		assert_eq!("personal,all,-personal".parse::<ApiSet>().unwrap(), ApiSet::List(vec![
 			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::SecretStore, Api::Whisper, Api::WhisperPubSub,
 			Api::ParityAccounts,
-			Api::ParitySet, Api::Signer,
+			Api::ParitySet, Api::Admin, Api::Signer, Api::Private, Api::Debug,
 		].into_iter().collect()));
 	}
 
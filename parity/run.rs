@@ -15,30 +15,36 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::fmt;
+use std::io::Read;
 use std::sync::{Arc, Weak};
 use std::net::{TcpListener};
 
 use ctrlc::CtrlC;
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
+use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
-use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
+use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient, SnapshotConfig, ColdStateConfig};
 use ethcore::ethstore::ethkey;
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
 use ethcore::miner::{StratumOptions, Stratum};
 use ethcore::service::ClientService;
 use ethcore::snapshot;
 use ethcore::verification::queue::VerifierSettings;
-use ethsync::{self, SyncConfig};
+use ethsync::{self, SyncConfig, RebroadcastPolicy};
 use fdlimit::raise_fd_limit;
 use hash_fetch::fetch::{Fetch, Client as FetchClient};
 use informant::{Informant, LightNodeInformantData, FullNodeInformantData};
 use light::Cache as LightDataCache;
+use light::cht_store::{self, ChtStore};
+use light::provider::ChtProvider;
 use node_health;
 use parity_reactor::EventLoop;
-use parity_rpc::{NetworkSettings, informant, is_major_importing};
+use parity_rpc::{NetworkSettings, ResponseCache, CallCache, informant, is_major_importing};
+use parity_rpc::authorization::{Authorizer, HttpAuthorizer};
 use updater::{UpdatePolicy, Updater};
 use ansi_term::Colour;
-use util::version;
+use bigint::hash::H256;
+use util::{version, Bytes};
 use parking_lot::{Condvar, Mutex};
 use node_filter::NodeFilter;
 
@@ -52,6 +58,7 @@ use dir::{Directories, DatabaseDirectories};
 use cache::CacheConfig;
 use user_defaults::UserDefaults;
 use dapps;
+use head_feed;
 use ipfs;
 use modules;
 use rpc;
@@ -81,17 +88,30 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub history_retention: Option<u64>,
+	pub tracing_retention: Option<u64>,
+	pub cold_state: Option<ColdStateConfig>,
+	pub max_reorg_depth: Option<u64>,
+	pub parallel_transactions: bool,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
 	pub miner_options: MinerOptions,
 	pub ntp_servers: Vec<String>,
+	pub health_thresholds: node_health::Thresholds,
 	pub ws_conf: rpc::WsConfiguration,
 	pub http_conf: rpc::HttpConfiguration,
 	pub ipc_conf: rpc::IpcConfiguration,
+	pub rpc_cache_size: usize,
+	pub rpc_cache_finality: u64,
+	pub rpc_call_cache_size: usize,
+	pub rpc_call_timeout: rpc::CallTimeout,
+	pub rpc_authorization_url: Option<String>,
 	pub net_conf: ethsync::NetworkConfiguration,
 	pub network_id: Option<u64>,
 	pub warp_sync: bool,
+	pub warp_snapshot_hash: Option<H256>,
+	pub beam_sync: bool,
 	pub public_node: bool,
 	pub acc_conf: AccountsConfig,
 	pub gas_pricer_conf: GasPricerConfig,
@@ -109,19 +129,27 @@ pub struct RunCmd {
 	pub ipfs_conf: ipfs::Configuration,
 	pub ui_conf: rpc::UiConfiguration,
 	pub secretstore_conf: secretstore::Configuration,
+	pub private_provider_conf: ProviderConfig,
+	pub private_encryptor_conf: EncryptorConfig,
+	pub private_tx_enabled: bool,
 	pub dapp: Option<String>,
 	pub ui: bool,
 	pub name: String,
 	pub custom_bootnodes: bool,
 	pub stratum: Option<StratumOptions>,
+	pub notify_work_pubsub: bool,
 	pub no_periodic_snapshot: bool,
+	pub snapshot_conf: SnapshotConfig,
+	pub snapshot_mirrors: Vec<String>,
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
 	pub serve_light: bool,
+	pub tx_rebroadcast: Option<RebroadcastPolicy>,
 	pub light: bool,
 	pub no_persistent_txqueue: bool,
-	pub whisper: ::whisper::Config
+	pub whisper: ::whisper::Config,
+	pub head_feed: Option<head_feed::Config>,
 }
 
 pub fn open_ui(ws_conf: &rpc::WsConfiguration, ui_conf: &rpc::UiConfiguration, logger_config: &LogConfig) -> Result<(), String> {
@@ -174,6 +202,10 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 	use ethsync::{LightSyncParams, LightSync, ManageNetwork};
 	use parking_lot::{Mutex, RwLock};
 
+	if cmd.head_feed.is_some() {
+		warn!("Head feed is not supported by the light client and will be disabled.");
+	}
+
 	// load spec
 	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
 
@@ -243,6 +275,7 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 	if !cmd.custom_bootnodes {
 		net_conf.boot_nodes = spec.nodes.clone();
 	}
+	net_conf.node_lists = spec.node_lists.clone();
 
 	let mut attached_protos = Vec::new();
 	let whisper_factory = if cmd.whisper.enabled {
@@ -303,25 +336,32 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 			on_demand: on_demand.clone(),
 		});
 
-		struct LightSyncStatus(Arc<LightSync>);
-		impl fmt::Debug for LightSyncStatus {
+		struct LightSyncStatus<T>(Arc<LightSync>, Arc<T>);
+		impl<T> fmt::Debug for LightSyncStatus<T> {
 			fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
 				write!(fmt, "Light Sync Status")
 			}
 		}
-		impl node_health::SyncStatus for LightSyncStatus {
+		impl<T: light_client::LightChainClient> node_health::SyncStatus for LightSyncStatus<T> {
 			fn is_major_importing(&self) -> bool { self.0.is_major_importing() }
 			fn peers(&self) -> (usize, usize) {
 				let peers = ethsync::LightSyncProvider::peer_numbers(&*self.0);
 				(peers.connected, peers.max)
 			}
+			fn block_lag(&self) -> Option<u64> {
+				let best = self.1.chain_info().best_block_number;
+				self.0.highest_block().map(|highest| highest.saturating_sub(best))
+			}
 		}
 
-		let sync_status = Arc::new(LightSyncStatus(light_sync.clone()));
+		let sync_status = Arc::new(LightSyncStatus(light_sync.clone(), service.client().clone()));
 		let node_health = node_health::NodeHealth::new(
 			sync_status.clone(),
 			node_health::TimeChecker::new(&cmd.ntp_servers, fetch.pool()),
+			fetch.pool(),
 			event_loop.remote(),
+			cmd.health_thresholds.clone(),
+			Some(db_dirs.db_root_path()),
 		);
 
 		(node_health.clone(), dapps::Dependencies {
@@ -355,7 +395,7 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 		dapps_service: dapps_service,
 		dapps_address: cmd.dapps_conf.address(cmd.http_conf.address()),
 		ws_address: cmd.ws_conf.address(),
-		fetch: fetch,
+		fetch: fetch.clone(),
 		geth_compatibility: cmd.geth_compatibility,
 		remote: event_loop.remote(),
 		whisper_rpc: whisper_factory,
@@ -370,6 +410,9 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 		} else {
 			None
 		},
+		authorizer: cmd.rpc_authorization_url.clone().map(|url| {
+			Arc::new(HttpAuthorizer::new(fetch.clone(), url)) as Arc<Authorizer>
+		}),
 	};
 
 	// start rpc servers
@@ -499,8 +542,11 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 
 	sync_config.fork_block = spec.fork_block();
 	sync_config.warp_sync = spec.engine.supports_warp() && cmd.warp_sync;
+	sync_config.warp_snapshot_hash = cmd.warp_snapshot_hash;
+	sync_config.beam_sync = sync_config.warp_sync && cmd.beam_sync;
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 	sync_config.serve_light = cmd.serve_light;
+	sync_config.local_tx_rebroadcast = cmd.tx_rebroadcast;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
@@ -517,6 +563,10 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	miner.set_gas_floor_target(cmd.miner_extras.gas_floor_target);
 	miner.set_gas_ceil_target(cmd.miner_extras.gas_ceil_target);
 	miner.set_extra_data(cmd.miner_extras.extra_data);
+	miner.set_extra_data_tag(cmd.miner_extras.extra_data_tag);
+	if let Some(template) = cmd.miner_extras.extra_data_template {
+		miner.set_extra_data_template(template).map_err(|e| format!("Invalid extra data template: {}", e))?;
+	}
 	miner.set_minimal_gas_price(initial_min_gas_price);
 	miner.recalibrate_minimal_gas_price();
 	let engine_signer = cmd.miner_extras.engine_signer;
@@ -556,12 +606,20 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.snapshot = cmd.snapshot_conf;
+	client_config.history_retention = cmd.history_retention;
+	client_config.tracing.retention = cmd.tracing_retention;
+	client_config.cold_state = cmd.cold_state;
+	client_config.max_reorg_depth = cmd.max_reorg_depth;
+	client_config.beam_sync = sync_config.beam_sync;
+	client_config.parallel_transactions = cmd.parallel_transactions;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
 	if !cmd.custom_bootnodes {
 		net_conf.boot_nodes = spec.nodes.clone();
 	}
+	net_conf.node_lists = spec.node_lists.clone();
 
 	// set network path.
 	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
@@ -598,7 +656,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			}
 		};
 
-		let store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
+		let mut store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
 
 		if cmd.no_persistent_txqueue {
 			info!("Running without a persistent transaction queue.");
@@ -606,6 +664,8 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			if let Err(e) = store.clear() {
 				warn!("Error clearing persistent transaction queue: {}", e);
 			}
+		} else if let Err(e) = store.open_journal(&db_dirs.transactions_journal_path()) {
+			warn!("Error opening local transactions journal: {}", e);
 		}
 
 		// re-queue pending transactions.
@@ -620,20 +680,47 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 		}
 
-		Arc::new(store)
+		// compact the journal, re-queued transactions are persisted from now on.
+		if let Err(e) = store.update() {
+			warn!("Error updating local store: {}", e);
+		}
+
+		let store = Arc::new(store);
+		let journal = Arc::downgrade(&store);
+		miner.add_local_transactions_listener(Box::new(move |tx| {
+			if let Some(store) = journal.upgrade() {
+				if let Err(e) = store.journal(tx.clone()) {
+					warn!("Error journaling local transaction {}: {}", tx.hash(), e);
+				}
+			}
+		}));
+		// persist cancellations right away, so canceled (e.g. scheduled) transactions are not re-queued on restart.
+		let journal = Arc::downgrade(&store);
+		miner.add_removed_transactions_listener(Box::new(move |tx| {
+			if let Some(store) = journal.upgrade() {
+				if let Err(e) = store.update() {
+					warn!("Error updating local store after removing transaction {}: {}", tx.hash(), e);
+				}
+			}
+		}));
+
+		store
 	};
 
 	// register it as an IO service to update periodically.
-	service.register_io_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
+	service.register_io_handler(store.clone()).map_err(|_| "Unable to register local store handler".to_owned())?;
 
 	// create external miner
 	let external_miner = Arc::new(ExternalMiner::default());
 
 	// start stratum
-	if let Some(ref stratum_config) = cmd.stratum {
-		Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
-			.map_err(|e| format!("Stratum start error: {:?}", e))?;
-	}
+	let stratum = match cmd.stratum {
+		Some(ref stratum_config) => Some(
+			Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
+				.map_err(|e| format!("Stratum start error: {:?}", e))?
+		),
+		None => None,
+	};
 
 	let mut attached_protos = Vec::new();
 
@@ -647,6 +734,33 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		None
 	};
 
+	let private_tx_service = if cmd.private_tx_enabled {
+		let encryptor = SecretStoreEncryptor::new(cmd.private_encryptor_conf.clone(), fetch.clone(), account_provider.clone());
+		Some(Arc::new(::ethcore_private_tx::Provider::new(
+			client.clone(),
+			account_provider.clone(),
+			Box::new(encryptor),
+			cmd.private_provider_conf.clone(),
+		)))
+	} else {
+		None
+	};
+	let private_tx_handler = match private_tx_service {
+		Some(ref provider) => Arc::new(::ethcore_private_tx::ImportQueue::new(provider.clone())) as Arc<ethsync::PrivateTxHandler>,
+		None => Arc::new(ethsync::NoopPrivateTxHandler) as Arc<ethsync::PrivateTxHandler>,
+	};
+
+	// serve header proofs to light clients from CHTs persisted in the database.
+	let light_provider: Arc<::light::Provider> = match cmd.serve_light {
+		true => {
+			let cht_store = ChtStore::new(service.db(), ::ethcore::db::COL_LIGHT_CHAIN, cht_store::DEFAULT_CACHE_SIZE);
+			let provider = Arc::new(ChtProvider::new(client.clone(), Arc::new(cht_store)));
+			service.add_notify(provider.clone());
+			provider
+		},
+		false => client.clone(),
+	};
+
 	// create sync object
 	let (sync_provider, manage_network, chain_notify) = modules::sync(
 		&mut hypervisor,
@@ -654,9 +768,11 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		net_conf.clone().into(),
 		client.clone(),
 		snapshot_service.clone(),
-		client.clone(),
+		light_provider,
 		&cmd.logger_config,
 		attached_protos,
+		private_tx_handler,
+		snapshot_mirrors(&cmd.snapshot_mirrors, &fetch),
 		connection_filter.clone().map(|f| f as Arc<::ethsync::ConnectionFilter + 'static>),
 	).map_err(|e| format!("Sync error: {}", e))?;
 
@@ -711,13 +827,20 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 				let status = self.0.status();
 				(status.num_peers, status.current_max_peers(self.2.min_peers, self.2.max_peers) as usize)
 			}
+			fn block_lag(&self) -> Option<u64> {
+				let best = self.1.chain_info().best_block_number;
+				self.0.status().highest_block_number.map(|highest| highest.saturating_sub(best))
+			}
 		}
 
 		let sync_status = Arc::new(SyncStatus(sync, client, net_conf));
 		let node_health = node_health::NodeHealth::new(
 			sync_status.clone(),
 			node_health::TimeChecker::new(&cmd.ntp_servers, fetch.pool()),
+			fetch.pool(),
 			event_loop.remote(),
+			cmd.health_thresholds.clone(),
+			Some(db_dirs.db_root_path()),
 		);
 		(node_health.clone(), dapps::Dependencies {
 			sync_status,
@@ -732,6 +855,26 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let dapps_middleware = dapps::new(cmd.dapps_conf.clone(), dapps_deps.clone())?;
 	let ui_middleware = dapps::new_ui(cmd.ui_conf.enabled, dapps_deps)?;
 
+	// cache of responses concerning finalized blocks
+	let response_cache = match cmd.rpc_cache_size {
+		0 => None,
+		size => {
+			let cache = Arc::new(ResponseCache::new(size, cmd.rpc_cache_finality));
+			client.add_notify(cache.clone());
+			Some(cache)
+		},
+	};
+
+	// cache of eth_call results, dropped on every new head
+	let call_cache = match cmd.rpc_call_cache_size {
+		0 => None,
+		size => {
+			let cache = Arc::new(CallCache::new(size));
+			client.add_notify(cache.clone());
+			Some(cache)
+		},
+	};
+
 	let dapps_service = dapps::service(&dapps_middleware);
 	let deps_for_rpc_apis = Arc::new(rpc_apis::FullDependencies {
 		signer_service: signer_service,
@@ -748,12 +891,19 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		net_service: manage_network.clone(),
 		updater: updater.clone(),
 		geth_compatibility: cmd.geth_compatibility,
+		response_cache: response_cache,
+		call_cache: call_cache,
+		call_timeout: cmd.rpc_call_timeout,
 		dapps_service: dapps_service,
 		dapps_address: cmd.dapps_conf.address(cmd.http_conf.address()),
 		ws_address: cmd.ws_conf.address(),
 		fetch: fetch.clone(),
 		remote: event_loop.remote(),
 		whisper_rpc: whisper_factory,
+		private_tx_service: private_tx_service,
+		local_store: Some(store.clone()),
+		stratum: stratum,
+		notify_work_pubsub: cmd.notify_work_pubsub,
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -765,6 +915,9 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		} else {
 			None
 		},
+		authorizer: cmd.rpc_authorization_url.clone().map(|url| {
+			Arc::new(HttpAuthorizer::new(fetch.clone(), url)) as Arc<Authorizer>
+		}),
 
 	};
 
@@ -800,6 +953,16 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	service.add_notify(informant.clone());
 	service.register_io_handler(informant.clone()).map_err(|_| "Unable to register informant handler".to_owned())?;
 
+	// the head feed must be kept alive.
+	let _head_feed = match cmd.head_feed.clone() {
+		Some(config) => {
+			let feed = Arc::new(head_feed::HeadFeed::new(service.client(), config)?);
+			service.add_notify(feed.clone());
+			Some(feed)
+		},
+		None => None,
+	};
+
 	// save user defaults
 	user_defaults.is_first_launch = false;
 	user_defaults.pruning = algorithm;
@@ -888,6 +1051,28 @@ fn print_running_environment(spec_name: &String, dirs: &Directories, db_dirs: &D
 	info!("Path to dapps {}", Colour::White.bold().paint(dapps_conf.dapps_path.to_string_lossy().into_owned()));
 }
 
+/// Fetches snapshots from mirrors using the fetch client.
+struct MirrorFetcher(FetchClient);
+
+impl ethsync::MirrorFetch for MirrorFetcher {
+	fn fetch(&self, url: &str) -> Result<Bytes, String> {
+		let mut response = self.0.fetch_sync(url).map_err(|e| format!("{:?}", e))?;
+		if !response.is_success() {
+			return Err(format!("Unexpected response status: {}", response.status()));
+		}
+		let mut data = Vec::new();
+		response.read_to_end(&mut data).map_err(|e| format!("{}", e))?;
+		Ok(data)
+	}
+}
+
+fn snapshot_mirrors(urls: &[String], fetch: &FetchClient) -> Option<ethsync::SnapshotMirrors> {
+	if urls.is_empty() {
+		return None;
+	}
+	Some(ethsync::SnapshotMirrors::new(urls.to_vec(), Arc::new(MirrorFetcher(fetch.clone()))))
+}
+
 fn prepare_account_provider(spec: &SpecType, dirs: &Directories, data_dir: &str, cfg: AccountsConfig, passwords: &[String]) -> Result<AccountProvider, String> {
 	use ethcore::ethstore::EthStore;
 	use ethcore::ethstore::dir::RootDiskDirectory;
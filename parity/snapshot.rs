@@ -16,18 +16,20 @@
 
 //! Snapshot and restoration commands.
 
+use std::fs;
 use std::time::Duration;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use hash::keccak;
 use ethcore::snapshot::{Progress, RestorationStatus, SnapshotService as SS};
-use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter};
+use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter, LooseReader};
 use ethcore::snapshot::service::Service as SnapshotService;
 use ethcore::service::ClientService;
 use ethcore::client::{Mode, DatabaseCompactionProfile, VMType};
 use ethcore::miner::Miner;
 use ethcore::ids::BlockId;
+use util::kvdb::{Database, DatabaseConfig};
 
 use cache::CacheConfig;
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
@@ -42,7 +44,9 @@ pub enum Kind {
 	/// Take a snapshot.
 	Take,
 	/// Restore a snapshot.
-	Restore
+	Restore,
+	/// Verify a snapshot without restoring it.
+	Verify,
 }
 
 /// Command for snapshot creation or restoration.
@@ -225,6 +229,45 @@ impl SnapshotCommand {
 		Ok(())
 	}
 
+	/// Verify a snapshot file or directory by rebuilding it into a scratch database.
+	pub fn verify(self) -> Result<(), String> {
+		let path: PathBuf = self.file_path.clone().ok_or("No snapshot path provided.".to_owned())?.into();
+		let spec = self.spec.spec(&self.dirs.cache)?;
+
+		// a loose snapshot may be given either by its directory or by its manifest.
+		let path = match path.file_name() {
+			Some(name) if name == "MANIFEST" => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+			_ => path,
+		};
+
+		let reader: Box<SnapshotReader> = if path.is_dir() {
+			Box::new(LooseReader::new(path.clone()).map_err(|e| format!("Couldn't open snapshot directory: {}", e))?)
+		} else {
+			let reader = PackedReader::new(&path).map_err(|e| format!("Couldn't open snapshot file: {}", e))?;
+			Box::new(reader.ok_or("Snapshot file has invalid format.".to_owned())?)
+		};
+
+		let manifest = reader.manifest().clone();
+		info!("Verifying snapshot of block #{} (0x{:?}): {} state chunks and {} block chunks",
+			manifest.block_number, manifest.block_hash, manifest.state_hashes.len(), manifest.block_hashes.len());
+
+		// the live database is left alone, everything is rebuilt from scratch.
+		let scratch_path = Path::new(&self.dirs.cache).join("snapshot-verification");
+		let _ = fs::remove_dir_all(&scratch_path);
+		let result = {
+			let db_config = DatabaseConfig::with_columns(::ethcore::db::NUM_COLUMNS);
+			let db = Database::open(&db_config, &scratch_path.to_string_lossy())
+				.map_err(|e| format!("Failed to open scratch database: {}", e))?;
+
+			::ethcore::snapshot::verify(&*reader, &*spec.engine, &spec.genesis_block(), Arc::new(db))
+		};
+		let _ = fs::remove_dir_all(&scratch_path);
+
+		result.map_err(|e| format!("Snapshot verification failed: {}", e))?;
+		info!("Snapshot verified: state root {:?} and all chunk hashes match.", manifest.state_root);
+		Ok(())
+	}
+
 	/// Take a snapshot from the head of the chain.
 	pub fn take_snapshot(self) -> Result<(), String> {
 		let file_path = self.file_path.clone().ok_or("No file path provided.".to_owned())?;
@@ -274,6 +317,7 @@ pub fn execute(cmd: SnapshotCommand) -> Result<String, String> {
 	match cmd.kind {
 		Kind::Take => cmd.take_snapshot()?,
 		Kind::Restore => cmd.restore()?,
+		Kind::Verify => cmd.verify()?,
 	}
 
 	Ok(String::new())
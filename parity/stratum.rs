@@ -72,7 +72,8 @@ pub fn main() {
 				service_config.port,
 			),
 			job_dispatcher.service().clone(),
-			service_config.secret
+			service_config.secret,
+			None,
 		).unwrap_or_else(
 			|e| {
 				println!("Fatal: cannot start stratum server({:?})", e);
@@ -23,7 +23,7 @@ use ethcore::client::ChainNotify;
 use ethcore::client::remote::RemoteClient;
 use ethcore::snapshot::remote::RemoteSnapshotService;
 use light::remote::LightProviderClient;
-use ethsync::{SyncProvider, EthSync, ManageNetwork, ServiceConfiguration};
+use ethsync::{SyncProvider, EthSync, ManageNetwork, ServiceConfiguration, NoopPrivateTxHandler};
 use modules::service_urls;
 use boot;
 use nanoipc;
@@ -58,6 +58,8 @@ pub fn main() {
 		provider: remote_provider.service().clone(),
 		network_config: service_config.net
 		attached_protos: Vec::new(),
+		private_tx_handler: Arc::new(NoopPrivateTxHandler),
+		snapshot_mirrors: None,
 	}).unwrap();
 
 	let _ = boot::main_thread();
@@ -26,6 +26,7 @@ extern crate crypto as rust_crypto;
 extern crate futures;
 extern crate futures_cpupool;
 extern crate itertools;
+extern crate lru_cache;
 extern crate multihash;
 extern crate order_stat;
 extern crate parking_lot;
@@ -56,9 +57,11 @@ extern crate ethkey;
 extern crate ethstore;
 extern crate ethsync;
 extern crate ethcore_logger;
+extern crate ethcore_private_tx;
 extern crate vm;
 extern crate fetch;
 extern crate node_health;
+extern crate parity_local_store as local_store;
 extern crate parity_reactor;
 extern crate parity_updater as updater;
 extern crate rlp;
@@ -97,7 +100,7 @@ pub use http::{
 	AccessControlAllowOrigin, Host, DomainsValidation
 };
 
-pub use v1::{NetworkSettings, Metadata, Origin, informant, dispatch, signer, dapps};
+pub use v1::{NetworkSettings, ResponseCache, CallCache, CallTimeout, Metadata, Origin, authorization, informant, dispatch, signer, dapps};
 pub use v1::block_import::is_major_importing;
 pub use v1::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
 pub use authcodes::{AuthCodes, TimeProvider};
@@ -0,0 +1,342 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC calls authorization.
+//!
+//! An external `Authorizer` approves or denies every call before it's processed.
+//! Any failure to reach a decision denies the call.
+//!
+//! Policies are plugged in either in-process (implementing `Authorizer`)
+//! or as an external HTTP service (`HttpAuthorizer`). Loading policies from
+//! dynamic libraries is not supported.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bigint::hash::H256;
+use fetch::Fetch;
+use futures::{future, Future};
+use hash::keccak;
+use jsonrpc_core as core;
+use lru_cache::LruCache;
+use parking_lot::Mutex;
+use serde_json;
+
+use v1::helpers::errors;
+use v1::metadata::Metadata;
+use v1::types::Origin;
+
+/// Number of decisions cached by `HttpAuthorizer`.
+const DECISIONS_CACHE_SIZE: usize = 4096;
+/// For how long `HttpAuthorizer` decisions are re-used.
+const DECISION_LIFETIME_SECS: u64 = 30;
+
+/// Call to be authorized.
+#[derive(Debug)]
+pub struct Call<'a> {
+	/// Called method.
+	pub method: &'a str,
+	/// Keccak of JSON-serialized call parameters.
+	pub params_digest: H256,
+	/// Origin of the call.
+	pub origin: &'a Origin,
+	/// Authorization token of the session (Signer connections only).
+	pub token: Option<&'a H256>,
+}
+
+/// Authorization policy.
+///
+/// NOTE: Called on the transport thread for every call, so implementations
+/// should decide quickly (or cache their decisions).
+pub trait Authorizer: Send + Sync {
+	/// Returns `Ok(())` if the call is allowed or `Err` with the reason otherwise.
+	fn authorize(&self, call: &Call) -> Result<(), String>;
+}
+
+/// Decisions cache key: method, serialized origin and token.
+type DecisionKey = (String, String, Option<H256>);
+
+/// Authorizer querying an external HTTP service.
+///
+/// Sends `GET <url>?method=..&origin=..` (with the session token in the `Authorization` header)
+/// and allows the call only if the service responds with a success status.
+/// The service decides per method, origin and token, so call parameters are not sent
+/// and decisions are re-used for `DECISION_LIFETIME_SECS`.
+pub struct HttpAuthorizer<F: Fetch> {
+	fetch: F,
+	url: String,
+	decisions: Mutex<LruCache<DecisionKey, (Instant, Result<(), String>)>>,
+}
+
+impl<F: Fetch> HttpAuthorizer<F> {
+	/// Creates new authorizer querying given URL.
+	pub fn new(fetch: F, url: String) -> Self {
+		HttpAuthorizer {
+			fetch: fetch,
+			url: url,
+			decisions: Mutex::new(LruCache::new(DECISIONS_CACHE_SIZE)),
+		}
+	}
+
+	fn query(&self, method: &str, origin: &str, token: Option<&H256>) -> Result<(), String> {
+		let url = format!("{}?method={}&origin={}", self.url, encode(method), encode(origin));
+		let headers = token.into_iter()
+			.map(|token| ("Authorization".to_owned(), format!("Bearer 0x{:?}", token)))
+			.collect();
+
+		match self.fetch.fetch_with_headers(&url, headers, Default::default()).wait() {
+			Ok(ref response) if response.is_success() => Ok(()),
+			Ok(response) => Err(format!("Denied with status {}", response.status())),
+			Err(err) => Err(format!("Authorization service unavailable: {:?}", err)),
+		}
+	}
+}
+
+impl<F: Fetch> Authorizer for HttpAuthorizer<F> {
+	fn authorize(&self, call: &Call) -> Result<(), String> {
+		let origin = serde_json::to_string(call.origin).map_err(|e| format!("{:?}", e))?;
+		let key = (call.method.to_owned(), origin, call.token.cloned());
+
+		if let Some(&mut (ref time, ref decision)) = self.decisions.lock().get_mut(&key) {
+			if time.elapsed() < Duration::from_secs(DECISION_LIFETIME_SECS) {
+				return decision.clone();
+			}
+		}
+
+		let decision = self.query(&key.0, &key.1, key.2.as_ref());
+		self.decisions.lock().insert(key, (Instant::now(), decision.clone()));
+		decision
+	}
+}
+
+/// Percent-encodes a query string value.
+fn encode(value: &str) -> String {
+	value.bytes().map(|b| match b {
+		b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+		b => format!("%{:02X}", b),
+	}).collect()
+}
+
+/// Middleware rejecting calls unless allowed by the authorizer.
+#[derive(Default)]
+pub struct Middleware {
+	authorizer: Option<Arc<Authorizer>>,
+}
+
+impl Middleware {
+	/// Creates new middleware. All calls are allowed if there is no authorizer.
+	pub fn new(authorizer: Option<Arc<Authorizer>>) -> Self {
+		Middleware {
+			authorizer: authorizer,
+		}
+	}
+
+	fn authorize(authorizer: &Authorizer, call: &core::Call, meta: &Metadata) -> Result<(), String> {
+		let (method, params) = match *call {
+			core::Call::MethodCall(ref call) => (&call.method, &call.params),
+			core::Call::Notification(ref call) => (&call.method, &call.params),
+			core::Call::Invalid(_) => return Ok(()),
+		};
+		let params = serde_json::to_vec(params).map_err(|e| format!("{:?}", e))?;
+		let token = match meta.origin {
+			Origin::Signer { ref session, .. } => Some(session),
+			_ => None,
+		};
+
+		authorizer.authorize(&Call {
+			method: method,
+			params_digest: keccak(params),
+			origin: &meta.origin,
+			token: token,
+		})
+	}
+
+	fn reject(call: core::Call, reason: &str) -> Option<core::Output> {
+		let (jsonrpc, id) = match call {
+			core::Call::MethodCall(call) => (call.jsonrpc, call.id),
+			core::Call::Invalid(id) => (Some(core::Version::V2), id),
+			core::Call::Notification(_) => return None,
+		};
+
+		Some(core::Output::Failure(core::Failure {
+			jsonrpc: jsonrpc,
+			error: errors::call_unauthorized(reason.into()),
+			id: id,
+		}))
+	}
+}
+
+impl core::Middleware<Metadata> for Middleware {
+	type Future = core::FutureResponse;
+
+	fn on_request<F, X>(&self, request: core::Request, meta: Metadata, process: F) -> Self::Future where
+		F: FnOnce(core::Request, Metadata) -> X,
+		X: core::futures::Future<Item=Option<core::Response>, Error=()> + Send + 'static,
+	{
+		let authorizer = match self.authorizer {
+			Some(ref authorizer) => authorizer,
+			None => return process(request, meta).boxed(),
+		};
+
+		let denied = match request {
+			core::Request::Single(ref call) => Self::authorize(&**authorizer, call, &meta).err(),
+			core::Request::Batch(ref calls) => calls.iter()
+				.filter_map(|call| Self::authorize(&**authorizer, call, &meta).err())
+				.next(),
+		};
+
+		let reason = match denied {
+			Some(reason) => reason,
+			None => return process(request, meta).boxed(),
+		};
+
+		debug!(target: "rpc", "Rejecting unauthorized request from {}: {}", meta.origin, reason);
+		let response = match request {
+			core::Request::Single(call) => Self::reject(call, &reason).map(core::Response::Single),
+			core::Request::Batch(calls) => {
+				let outputs: Vec<_> = calls.into_iter().filter_map(|call| Self::reject(call, &reason)).collect();
+				match outputs.is_empty() {
+					true => None,
+					false => Some(core::Response::Batch(outputs)),
+				}
+			},
+		};
+		future::ok(response).boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use std::sync::Arc;
+	use fetch::{self, Fetch};
+	use futures::{self, Future};
+	use jsonrpc_core::{MetaIoHandler, Params, Value};
+	use parking_lot::Mutex;
+	use v1::metadata::Metadata;
+	use v1::types::Origin;
+	use super::{Authorizer, Call, HttpAuthorizer, Middleware, encode};
+
+	/// Fetch recording requests and allowing only `eth_` calls.
+	#[derive(Default, Clone)]
+	struct RecordingFetch {
+		requests: Arc<Mutex<Vec<(String, Vec<(String, String)>)>>>,
+	}
+
+	impl Fetch for RecordingFetch {
+		type Result = futures::BoxFuture<fetch::Response, fetch::Error>;
+
+		fn new() -> Result<Self, fetch::Error> where Self: Sized {
+			Ok(Default::default())
+		}
+
+		fn fetch_with_abort(&self, url: &str, abort: fetch::Abort) -> Self::Result {
+			self.fetch_with_headers(url, Vec::new(), abort)
+		}
+
+		fn fetch_with_headers(&self, url: &str, headers: Vec<(String, String)>, _abort: fetch::Abort) -> Self::Result {
+			self.requests.lock().push((url.to_owned(), headers));
+			let response = match url.contains("method=eth_") {
+				true => fetch::Response::from_reader(io::Cursor::new(Vec::new())),
+				false => fetch::Response::not_found(),
+			};
+			futures::future::ok(response).boxed()
+		}
+	}
+
+	struct TestAuthorizer;
+
+	impl Authorizer for TestAuthorizer {
+		fn authorize(&self, call: &Call) -> Result<(), String> {
+			match (call.method, call.origin) {
+				("allowed", &Origin::Rpc(_)) => Ok(()),
+				_ => Err("Not allowed".into()),
+			}
+		}
+	}
+
+	fn io() -> MetaIoHandler<Metadata, Middleware> {
+		let mut io = MetaIoHandler::with_middleware(Middleware::new(Some(Arc::new(TestAuthorizer))));
+		io.add_method("allowed", |_: Params| Ok(Value::Bool(true)));
+		io.add_method("denied", |_: Params| Ok(Value::Bool(true)));
+		io
+	}
+
+	fn rpc_meta() -> Metadata {
+		Metadata {
+			origin: Origin::Rpc("localhost".into()),
+			session: None,
+		}
+	}
+
+	#[test]
+	fn should_allow_authorized_calls() {
+		let request = r#"{"jsonrpc":"2.0","method":"allowed","params":[],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+		assert_eq!(io().handle_request_sync(request, rpc_meta()), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_reject_unauthorized_calls() {
+		let request = r#"{"jsonrpc":"2.0","method":"denied","params":[],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32040,"message":"Call has been rejected by the authorization policy.","data":"Not allowed"},"id":1}"#;
+
+		assert_eq!(io().handle_request_sync(request, rpc_meta()), Some(response.to_owned()));
+		assert_eq!(io().handle_request_sync(request, Metadata::default()), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_reject_whole_batch_if_any_call_is_unauthorized() {
+		let request = r#"[{"jsonrpc":"2.0","method":"allowed","params":[],"id":1},{"jsonrpc":"2.0","method":"denied","params":[],"id":2}]"#;
+		let response = r#"[{"jsonrpc":"2.0","error":{"code":-32040,"message":"Call has been rejected by the authorization policy.","data":"Not allowed"},"id":1},{"jsonrpc":"2.0","error":{"code":-32040,"message":"Call has been rejected by the authorization policy.","data":"Not allowed"},"id":2}]"#;
+
+		assert_eq!(io().handle_request_sync(request, rpc_meta()), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_percent_encode_query_values() {
+		assert_eq!(encode("eth_call"), "eth_call");
+		assert_eq!(encode(r#"{"rpc":"a b"}"#), "%7B%22rpc%22%3A%22a%20b%22%7D");
+	}
+
+	#[test]
+	fn http_authorizer_should_send_token_in_header_and_cache_decisions() {
+		let fetch = RecordingFetch::default();
+		let authorizer = HttpAuthorizer::new(fetch.clone(), "http://policy".into());
+		let origin = Origin::Rpc("localhost".into());
+		let token = 5.into();
+		let call = |method| Call {
+			method: method,
+			params_digest: Default::default(),
+			origin: &origin,
+			token: Some(&token),
+		};
+
+		assert_eq!(authorizer.authorize(&call("eth_call")), Ok(()));
+		assert_eq!(authorizer.authorize(&call("eth_call")), Ok(()));
+		assert!(authorizer.authorize(&call("personal_sign")).is_err());
+		assert!(authorizer.authorize(&call("personal_sign")).is_err());
+
+		let requests = fetch.requests.lock();
+		assert_eq!(requests.len(), 2);
+		assert!(!requests[0].0.contains("token"));
+		assert_eq!(requests[0].1, vec![(
+			"Authorization".to_owned(),
+			"Bearer 0x0000000000000000000000000000000000000000000000000000000000000005".to_owned(),
+		)]);
+	}
+}
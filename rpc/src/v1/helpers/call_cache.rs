@@ -0,0 +1,159 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of `eth_call` results.
+
+use std::mem;
+
+use bigint::hash::H256;
+use ethcore::client::ChainNotify;
+use lru_cache::LruCache;
+use parking_lot::Mutex;
+use util::{Address, Bytes};
+
+/// Cache key: hash of the block the call was executed on (which determines the state root),
+/// sender and hash of the (fake-signed) transaction covering destination, data, gas and value.
+pub type Key = (H256, Address, H256);
+
+struct Inner {
+	entries: LruCache<Key, Bytes>,
+	size: usize,
+}
+
+fn entry_size(output: &Bytes) -> usize {
+	mem::size_of::<Key>() + output.len()
+}
+
+/// LRU cache of call outputs bounded by the total size of cached outputs.
+///
+/// Once the chain head changes all results are dropped.
+pub struct CallCache {
+	max_size: usize,
+	inner: Mutex<Inner>,
+}
+
+impl CallCache {
+	/// Creates new cache holding at most `max_size` bytes of call results.
+	pub fn new(max_size: usize) -> Self {
+		CallCache {
+			max_size: max_size,
+			inner: Mutex::new(Inner {
+				entries: LruCache::new(usize::max_value()),
+				size: 0,
+			}),
+		}
+	}
+
+	/// Returns cached output of the call (if any).
+	pub fn get(&self, key: &Key) -> Option<Bytes> {
+		self.inner.lock().entries.get_mut(key).map(|output| output.clone())
+	}
+
+	/// Caches output of the call, evicting least recently used results if needed.
+	pub fn insert(&self, key: Key, output: Bytes) {
+		let size = entry_size(&output);
+		if size > self.max_size {
+			return;
+		}
+
+		let mut inner = self.inner.lock();
+		if let Some(old) = inner.entries.remove(&key) {
+			inner.size -= entry_size(&old);
+		}
+		while inner.size + size > self.max_size {
+			match inner.entries.remove_lru() {
+				Some((_, old)) => inner.size -= entry_size(&old),
+				None => break,
+			}
+		}
+
+		inner.size += size;
+		inner.entries.insert(key, output);
+	}
+
+	/// Drops all cached results.
+	pub fn clear(&self) {
+		let mut inner = self.inner.lock();
+		inner.entries.clear();
+		inner.size = 0;
+	}
+
+	/// Number of cached results.
+	pub fn len(&self) -> usize {
+		self.inner.lock().entries.len()
+	}
+}
+
+impl ChainNotify for CallCache {
+	fn new_blocks(
+		&self,
+		_imported: Vec<H256>,
+		_invalid: Vec<H256>,
+		enacted: Vec<H256>,
+		_retracted: Vec<H256>,
+		_sealed: Vec<H256>,
+		_proposed: Vec<Bytes>,
+		_duration: u64,
+	) {
+		if !enacted.is_empty() {
+			self.clear();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CallCache;
+
+	fn key(n: u64) -> super::Key {
+		(n.into(), 1.into(), 2.into())
+	}
+
+	#[test]
+	fn should_evict_to_fit_size() {
+		let entry = ::std::mem::size_of::<super::Key>() + 10;
+		let cache = CallCache::new(2 * entry);
+		cache.insert(key(1), vec![0; 10]);
+		cache.insert(key(2), vec![0; 10]);
+		// touch first result
+		assert!(cache.get(&key(1)).is_some());
+
+		cache.insert(key(3), vec![0; 10]);
+
+		assert_eq!(cache.get(&key(1)), Some(vec![0; 10]));
+		assert_eq!(cache.get(&key(2)), None);
+		assert!(cache.get(&key(3)).is_some());
+		assert_eq!(cache.len(), 2);
+
+		// too large to cache at all
+		cache.insert(key(4), vec![0; 3 * entry]);
+		assert_eq!(cache.get(&key(4)), None);
+		assert_eq!(cache.len(), 2);
+	}
+
+	#[test]
+	fn should_clear_on_new_head() {
+		use ethcore::client::ChainNotify;
+
+		let cache = CallCache::new(1024);
+		cache.insert(key(1), vec![1]);
+		cache.new_blocks(vec![], vec![], vec![], vec![], vec![], vec![], 0);
+		assert_eq!(cache.len(), 1);
+
+		cache.new_blocks(vec![], vec![], vec![5.into()], vec![], vec![], vec![], 0);
+		assert_eq!(cache.len(), 0);
+	}
+}
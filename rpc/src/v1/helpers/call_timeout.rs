@@ -0,0 +1,68 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Execution time limits of `eth_call` and `eth_estimateGas`.
+
+use std::time::Duration;
+
+use v1::types::Origin;
+
+/// Wall-clock limits of call execution, depending on the transport the request came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CallTimeout {
+	/// Limit for requests over IPC and from the Trusted Signer.
+	pub trusted: Option<Duration>,
+	/// Limit for requests over HTTP, WebSockets and from Dapps.
+	pub untrusted: Option<Duration>,
+}
+
+impl CallTimeout {
+	/// Returns the limit for requests of given origin.
+	pub fn for_origin(&self, origin: &Origin) -> Option<Duration> {
+		match *origin {
+			Origin::Ipc(_) | Origin::Signer { .. } => self.trusted,
+			_ => self.untrusted,
+		}
+	}
+}
+
+impl Default for CallTimeout {
+	fn default() -> Self {
+		CallTimeout {
+			trusted: Some(Duration::from_secs(60)),
+			untrusted: Some(Duration::from_secs(5)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use v1::types::Origin;
+	use super::CallTimeout;
+
+	#[test]
+	fn should_use_lower_limit_for_unauthenticated_transports() {
+		let timeout = CallTimeout::default();
+
+		assert_eq!(timeout.for_origin(&Origin::Ipc(5.into())), Some(Duration::from_secs(60)));
+		assert_eq!(timeout.for_origin(&Origin::Signer { dapp: "x".into(), session: 5.into() }), Some(Duration::from_secs(60)));
+		assert_eq!(timeout.for_origin(&Origin::Rpc("curl".into())), Some(Duration::from_secs(5)));
+		assert_eq!(timeout.for_origin(&Origin::Ws { dapp: "x".into(), session: 5.into() }), Some(Duration::from_secs(5)));
+		assert_eq!(timeout.for_origin(&Origin::Unknown), Some(Duration::from_secs(5)));
+	}
+}
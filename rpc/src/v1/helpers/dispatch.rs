@@ -291,6 +291,7 @@ impl LightDispatcher {
 		let nonce_future = self.sync.with_context(|ctx| self.on_demand.request(ctx, request::Account {
 			header: best_header.into(),
 			address: addr,
+			hasher: self.client.engine().params().trie_hasher,
 		}).expect("no back-references; therefore all back-references valid; qed"));
 
 		match nonce_future {
@@ -591,7 +592,7 @@ pub fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where
 	C: MiningBlockChainClient,
 	M: MinerService,
 {
-	client.gas_price_corpus(100).median().cloned().unwrap_or_else(|| miner.sensible_gas_price())
+	client.gas_price_median().unwrap_or_else(|| miner.sensible_gas_price())
 }
 
 /// Convert RPC confirmation payload to signer confirmation payload.
@@ -20,6 +20,8 @@ use std::fmt;
 use rlp::DecoderError;
 use ethcore::error::{Error as EthcoreError, CallError, TransactionError};
 use ethcore::account_provider::{SignError as AccountError};
+use ethcore::transaction::TransactionDecodeError;
+use ethcore_private_tx::Error as PrivateTransactionError;
 use jsonrpc_core::{Error, ErrorCode, Value};
 
 mod codes {
@@ -34,6 +36,7 @@ mod codes {
 	pub const EXECUTION_ERROR: i64 = -32015;
 	pub const EXCEPTION_ERROR: i64 = -32016;
 	pub const DATABASE_ERROR: i64 = -32017;
+	pub const EXECUTION_TIMEOUT: i64 = -32018;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const ACCOUNT_ERROR: i64 = -32023;
@@ -43,6 +46,7 @@ mod codes {
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const ENCODING_ERROR: i64 = -32058;
 	pub const FETCH_ERROR: i64 = -32060;
+	pub const PRIVATE_ERROR: i64 = -32062;
 	pub const NO_LIGHT_PEERS: i64 = -32065;
 	pub const DEPRECATED: i64 = -32070;
 }
@@ -95,6 +99,14 @@ pub fn request_rejected() -> Error {
 	}
 }
 
+pub fn call_unauthorized(reason: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED),
+		message: "Call has been rejected by the authorization policy.".into(),
+		data: Some(Value::String(reason)),
+	}
+}
+
 pub fn request_rejected_limit() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -146,6 +158,22 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn history_pruned(earliest_chain: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Block body and receipts have been pruned from this node.".into(),
+		data: Some(Value::String(format!("Block bodies and receipts are available since block #{}. Use --history-retention to keep more of them.", earliest_chain))),
+	}
+}
+
+pub fn traces_pruned(earliest_trace: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Traces have been pruned from this node.".into(),
+		data: Some(Value::String(format!("Traces are available since block #{}. Use --tracing-retention to keep more of them.", earliest_trace))),
+	}
+}
+
 pub fn state_corrupt() -> Error {
 	internal("State corrupt", "")
 }
@@ -158,6 +186,14 @@ pub fn exceptional() -> Error {
 	}
 }
 
+pub fn call_timeout() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_TIMEOUT),
+		message: "The execution took too long and was aborted.".into(),
+		data: Some(Value::String("Use a transport with a higher limit (e.g. IPC) or raise the limit with --jsonrpc-call-timeout.".into())),
+	}
+}
+
 pub fn no_work() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NO_WORK),
@@ -190,6 +226,14 @@ pub fn no_work_required() -> Error {
 	}
 }
 
+pub fn cannot_seal() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "The engine is not able to seal blocks on demand.".into(),
+		data: None,
+	}
+}
+
 pub fn not_enough_data() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -270,6 +314,30 @@ pub fn fetch<T: fmt::Debug>(error: T) -> Error {
 	}
 }
 
+pub fn private_message(error: PrivateTransactionError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::PRIVATE_ERROR),
+		message: "Private transactions call failed.".into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
+pub fn private_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Private transactions are disabled. Restart parity with `--private-tx-enabled` to enable them.".into(),
+		data: None,
+	}
+}
+
+pub fn work_notifications_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Work notifications are disabled. Restart parity with `--notify-work-pubsub` to enable them.".into(),
+		data: None,
+	}
+}
+
 pub fn signing(error: AccountError) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ACCOUNT_LOCKED),
@@ -292,12 +360,18 @@ pub fn transaction_message(error: TransactionError) -> String {
 	match error {
 		AlreadyImported => "Transaction with the same hash was already imported.".into(),
 		Old => "Transaction nonce is too low. Try incrementing the nonce.".into(),
-		TooCheapToReplace => {
-			"Transaction gas price is too low. There is another transaction with same nonce in the queue. Try increasing the gas price or incrementing the nonce.".into()
+		TooCheapToReplace { minimal, got } => {
+			format!("Transaction gas price is too low. There is another transaction with same nonce in the queue (minimal gas price to replace: {}, got: {}). Try increasing the gas price or incrementing the nonce.", minimal, got)
+		},
+		TooSoonToReplace => {
+			"Transaction with the same nonce was replaced too recently. Try again later or increment the nonce.".into()
 		},
 		LimitReached => {
 			"There are too many transactions in the queue. Your transaction was dropped due to limit. Try increasing the fee.".into()
 		},
+		SenderLimitReached => {
+			"There are too many transactions from the sender in the queue. Try again after some of them are mined.".into()
+		},
 		InsufficientGas { minimal, got } => {
 			format!("Transaction gas is too low. There is not enough gas to cover minimal cost of the transaction (minimal: {}, got: {}). Try increasing supplied gas.", minimal, got)
 		},
@@ -316,6 +390,9 @@ pub fn transaction_message(error: TransactionError) -> String {
 		RecipientBanned => "Recipient is banned in local queue.".into(),
 		CodeBanned => "Code is banned in local queue.".into(),
 		NotAllowed => "Transaction is not permitted.".into(),
+		TooBig { limit, got } => {
+			format!("Transaction is too big. Limit: {} bytes, got: {} bytes.", limit, got)
+		},
 	}
 }
 
@@ -344,11 +421,23 @@ pub fn rlp(error: DecoderError) -> Error {
 	}
 }
 
+pub fn transaction_decode(error: TransactionDecodeError) -> Error {
+	match error {
+		TransactionDecodeError::Rlp(err) => rlp(err),
+		_ => Error {
+			code: ErrorCode::InvalidParams,
+			message: format!("{}.", error),
+			data: None,
+		},
+	}
+}
+
 pub fn call(error: CallError) -> Error {
 	match error {
 		CallError::StatePruned => state_pruned(),
 		CallError::StateCorrupt => state_corrupt(),
 		CallError::Exceptional => exceptional(),
+		CallError::Timeout => call_timeout(),
 		CallError::Execution(e) => execution(e),
 		CallError::TransactionNotFound => internal("{}, this should not be the case with eth_call, most likely a bug.", CallError::TransactionNotFound),
 	}
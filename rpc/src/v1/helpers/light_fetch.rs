@@ -141,7 +141,11 @@ impl LightFetch {
 			Err(e) => return future::err(e).boxed(),
 		};
 
-		reqs.push(request::Account { header: header_ref, address: address }.into());
+		reqs.push(request::Account {
+			header: header_ref,
+			address: address,
+			hasher: self.client.engine().params().trie_hasher,
+		}.into());
 
 		let maybe_future = self.sync.with_context(move |ctx| {
 			self.on_demand.request_raw(ctx, reqs)
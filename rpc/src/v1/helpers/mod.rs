@@ -19,11 +19,14 @@ pub mod errors;
 
 pub mod accounts;
 pub mod block_import;
+pub mod call_cache;
+pub mod call_timeout;
 pub mod dapps;
 pub mod dispatch;
 pub mod fake_sign;
 pub mod light_fetch;
 pub mod oneshot;
+pub mod response_cache;
 pub mod ipfs;
 pub mod secretstore;
 
@@ -36,10 +39,13 @@ mod signing_queue;
 mod subscribers;
 mod subscription_manager;
 
+pub use self::call_cache::CallCache;
+pub use self::call_timeout::CallTimeout;
 pub use self::dispatch::{Dispatcher, FullDispatcher};
 pub use self::network_settings::NetworkSettings;
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::{PollFilter, limit_logs};
+pub use self::response_cache::ResponseCache;
 pub use self::requests::{
 	TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest,
 };
@@ -0,0 +1,220 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of responses concerning finalized (immutable) chain data.
+
+use std::collections::{HashMap, HashSet};
+
+use bigint::hash::H256;
+use ethcore::client::ChainNotify;
+use lru_cache::LruCache;
+use parking_lot::Mutex;
+use util::Bytes;
+
+use v1::types::{RichBlock, Receipt};
+
+/// Cache key: RPC method name and serialized parameters.
+pub type Key = (&'static str, String);
+
+/// A cached response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+	/// Block (`eth_getBlockByHash`, `eth_getBlockByNumber`).
+	Block(RichBlock),
+	/// Transaction receipt (`eth_getTransactionReceipt`).
+	Receipt(Receipt),
+}
+
+struct Inner {
+	entries: LruCache<Key, (H256, Response)>,
+	by_block: HashMap<H256, HashSet<Key>>,
+}
+
+impl Inner {
+	fn forget(&mut self, key: &Key, block: &H256) {
+		let is_empty = match self.by_block.get_mut(block) {
+			Some(keys) => {
+				keys.remove(key);
+				keys.is_empty()
+			},
+			None => false,
+		};
+		if is_empty {
+			self.by_block.remove(block);
+		}
+	}
+}
+
+/// LRU cache of RPC responses for blocks behind the finality horizon.
+///
+/// Only responses concerning blocks at least `finality_depth` blocks deep are cached
+/// and all responses depending on a retracted block are dropped.
+pub struct ResponseCache {
+	finality_depth: u64,
+	inner: Mutex<Inner>,
+}
+
+impl ResponseCache {
+	/// Creates new cache holding at most `capacity` responses.
+	pub fn new(capacity: usize, finality_depth: u64) -> Self {
+		ResponseCache {
+			finality_depth: finality_depth,
+			inner: Mutex::new(Inner {
+				entries: LruCache::new(capacity),
+				by_block: HashMap::new(),
+			}),
+		}
+	}
+
+	/// Returns cached response (if any).
+	pub fn get(&self, key: &Key) -> Option<Response> {
+		self.inner.lock().entries.get_mut(key).map(|&mut (_, ref response)| response.clone())
+	}
+
+	/// Caches the response if the block it depends on is final.
+	pub fn insert(&self, key: Key, block_number: u64, block_hash: H256, best_block: u64, response: Response) {
+		if block_number.saturating_add(self.finality_depth) > best_block {
+			return;
+		}
+
+		let mut inner = self.inner.lock();
+		let capacity = inner.entries.capacity();
+		if capacity == 0 {
+			return;
+		}
+
+		if let Some((old, _)) = inner.entries.remove(&key) {
+			inner.forget(&key, &old);
+		}
+
+		if inner.entries.len() == capacity {
+			if let Some((key, (block, _))) = inner.entries.remove_lru() {
+				inner.forget(&key, &block);
+			}
+		}
+
+		inner.by_block.entry(block_hash).or_insert_with(HashSet::new).insert(key.clone());
+		inner.entries.insert(key, (block_hash, response));
+	}
+
+	/// Drops all responses depending on given blocks.
+	pub fn invalidate(&self, blocks: &[H256]) {
+		let mut inner = self.inner.lock();
+		for block in blocks {
+			if let Some(keys) = inner.by_block.remove(block) {
+				for key in keys {
+					inner.entries.remove(&key);
+				}
+			}
+		}
+	}
+
+	/// Number of cached responses.
+	pub fn len(&self) -> usize {
+		self.inner.lock().entries.len()
+	}
+}
+
+impl ChainNotify for ResponseCache {
+	fn new_blocks(
+		&self,
+		_imported: Vec<H256>,
+		_invalid: Vec<H256>,
+		_enacted: Vec<H256>,
+		retracted: Vec<H256>,
+		_sealed: Vec<H256>,
+		_proposed: Vec<Bytes>,
+		_duration: u64,
+	) {
+		if !retracted.is_empty() {
+			self.invalidate(&retracted);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use v1::types::Receipt;
+	use super::{ResponseCache, Response};
+
+	fn receipt() -> Response {
+		Response::Receipt(Receipt {
+			transaction_hash: None,
+			transaction_index: None,
+			block_hash: None,
+			block_number: None,
+			cumulative_gas_used: 0.into(),
+			gas_used: None,
+			contract_address: None,
+			logs: vec![],
+			state_root: None,
+			logs_bloom: Default::default(),
+		})
+	}
+
+	fn key(param: &str) -> (&'static str, String) {
+		("eth_getTransactionReceipt", param.into())
+	}
+
+	#[test]
+	fn should_cache_only_final_responses() {
+		let cache = ResponseCache::new(10, 5);
+
+		cache.insert(key("a"), 6, 1.into(), 10, receipt());
+		assert_eq!(cache.get(&key("a")), None);
+
+		cache.insert(key("b"), 5, 2.into(), 10, receipt());
+		assert_eq!(cache.get(&key("b")), Some(receipt()));
+	}
+
+	#[test]
+	fn should_invalidate_retracted_blocks() {
+		let cache = ResponseCache::new(10, 0);
+		cache.insert(key("a"), 1, 1.into(), 10, receipt());
+		cache.insert(key("b"), 1, 1.into(), 10, receipt());
+		cache.insert(key("c"), 2, 2.into(), 10, receipt());
+
+		cache.invalidate(&[1.into()]);
+
+		assert_eq!(cache.get(&key("a")), None);
+		assert_eq!(cache.get(&key("b")), None);
+		assert_eq!(cache.get(&key("c")), Some(receipt()));
+		assert_eq!(cache.len(), 1);
+	}
+
+	#[test]
+	fn should_evict_least_recently_used() {
+		let cache = ResponseCache::new(2, 0);
+		cache.insert(key("a"), 1, 1.into(), 10, receipt());
+		cache.insert(key("b"), 2, 2.into(), 10, receipt());
+		// touch `a`
+		assert!(cache.get(&key("a")).is_some());
+
+		cache.insert(key("c"), 3, 3.into(), 10, receipt());
+
+		assert!(cache.get(&key("a")).is_some());
+		assert_eq!(cache.get(&key("b")), None);
+		assert!(cache.get(&key("c")).is_some());
+		assert!(cache.inner.lock().by_block.get(&2.into()).is_none());
+	}
+
+	#[test]
+	fn should_not_cache_when_disabled() {
+		let cache = ResponseCache::new(0, 0);
+		cache.insert(key("a"), 1, 1.into(), 10, receipt());
+		assert_eq!(cache.len(), 0);
+	}
+}
@@ -0,0 +1,80 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug api implementation.
+
+use std::sync::Arc;
+
+use ethcore::client::{MiningBlockChainClient, CallAnalytics, Executed, TransactionId};
+use ethcore::miner::MinerService;
+
+use jsonrpc_core::Error;
+use jsonrpc_core::futures::{self, Future, BoxFuture};
+use jsonrpc_macros::Trailing;
+use v1::Metadata;
+use v1::traits::Debug;
+use v1::helpers::{errors, fake_sign};
+use v1::types::{BlockNumber, CallRequest, H256, StepTrace, StepTraceOptions};
+
+fn to_call_analytics(options: Trailing<StepTraceOptions>) -> CallAnalytics {
+	CallAnalytics {
+		step_tracing: Some(options.unwrap_or_default().into()),
+		..Default::default()
+	}
+}
+
+fn step_trace(executed: Executed) -> Result<StepTrace, Error> {
+	executed.step_trace
+		.map(Into::into)
+		.ok_or_else(|| errors::internal("Execution steps were not recorded", ""))
+}
+
+/// Debug api implementation.
+pub struct DebugClient<C, M> {
+	client: Arc<C>,
+	miner: Arc<M>,
+}
+
+impl<C, M> DebugClient<C, M> {
+	/// Creates new Debug client.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		DebugClient {
+			client: client.clone(),
+			miner: miner.clone(),
+		}
+	}
+}
+
+impl<C, M> Debug for DebugClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
+	type Metadata = Metadata;
+
+	fn trace_transaction(&self, transaction_hash: H256, options: Trailing<StepTraceOptions>) -> Result<StepTrace, Error> {
+		self.client.replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(options))
+			.map_err(errors::call)
+			.and_then(step_trace)
+	}
+
+	fn trace_call(&self, meta: Self::Metadata, request: CallRequest, block: BlockNumber, options: Trailing<StepTraceOptions>) -> BoxFuture<StepTrace, Error> {
+		let request = CallRequest::into(request);
+		let signed = try_bf!(fake_sign::sign_call(&self.client, &self.miner, request, meta.is_dapp()));
+
+		let res = self.client.call(&signed, to_call_analytics(options), block.into())
+			.map_err(errors::call)
+			.and_then(step_trace);
+
+		futures::done(res).boxed()
+	}
+}
@@ -21,7 +21,7 @@ use std::time::{Instant, Duration};
 use std::sync::Arc;
 
 use futures::{self, future, BoxFuture, Future};
-use rlp::{self, UntrustedRlp};
+use rlp;
 use time::get_time;
 use bigint::prelude::U256;
 use bigint::hash::{H64, H160, H256};
@@ -31,13 +31,14 @@ use parking_lot::Mutex;
 use ethash::SeedHashCompute;
 use ethcore::account_provider::{AccountProvider, DappId};
 use ethcore::block::IsBlock;
-use ethcore::client::{MiningBlockChainClient, BlockId, TransactionId, UncleId};
+use ethcore::client::{MiningBlockChainClient, BlockId, CallAnalytics, TransactionId, UncleId};
+use ethcore::error::CallError;
 use ethcore::ethereum::Ethash;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::header::{Header as BlockHeader, BlockNumber as EthBlockNumber};
 use ethcore::log_entry::LogEntry;
 use ethcore::miner::{MinerService, ExternalMinerService};
-use ethcore::transaction::SignedTransaction;
+use ethcore::transaction::{SignedTransaction, UnverifiedTransaction};
 use ethcore::snapshot::SnapshotService;
 use ethsync::{SyncProvider};
 
@@ -48,9 +49,11 @@ use v1::helpers::{errors, limit_logs, fake_sign};
 use v1::helpers::dispatch::{Dispatcher, FullDispatcher, default_gas_price};
 use v1::helpers::block_import::is_major_importing;
 use v1::helpers::accounts::unwrap_provider;
+use v1::helpers::response_cache::{self, ResponseCache, Response as CachedResponse};
+use v1::helpers::{CallCache, CallTimeout};
 use v1::traits::Eth;
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
+	RichBlock, Block, BlockTransactions, BlockNumber, BlockNumberOrHash, Bytes, SyncStatus, SyncInfo, FeeHistory,
 	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
@@ -58,6 +61,9 @@ use v1::metadata::Metadata;
 
 const EXTRA_INFO_PROOF: &'static str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
+/// Maximal number of blocks returned by `eth_feeHistory`.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
 /// Eth RPC options
 pub struct EthClientOptions {
 	/// Return nonce from transaction queue when pending block not available.
@@ -66,6 +72,14 @@ pub struct EthClientOptions {
 	pub allow_pending_receipt_query: bool,
 	/// Send additional block number when asking for work
 	pub send_block_number_in_get_work: bool,
+	/// First block sealed with ProgPoW; work for such blocks always includes the block number
+	pub progpow_transition: Option<u64>,
+	/// Cache of responses concerning finalized blocks
+	pub response_cache: Option<Arc<ResponseCache>>,
+	/// Cache of `eth_call` results
+	pub call_cache: Option<Arc<CallCache>>,
+	/// Execution time limits of `eth_call` and `eth_estimateGas`
+	pub call_timeout: CallTimeout,
 }
 
 impl EthClientOptions {
@@ -84,6 +98,10 @@ impl Default for EthClientOptions {
 			pending_nonce_from_queue: false,
 			allow_pending_receipt_query: true,
 			send_block_number_in_get_work: true,
+			progpow_transition: None,
+			response_cache: None,
+			call_cache: None,
+			call_timeout: CallTimeout::default(),
 		}
 	}
 }
@@ -143,6 +161,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 		unwrap_provider(&self.accounts)
 	}
 
+	/// ProgPoW miners need the block number to derive the program, so it's sent regardless of the options.
+	fn send_block_number_in_get_work(&self, block_number: u64) -> bool {
+		self.options.send_block_number_in_get_work || self.options.progpow_transition.map_or(false, |transition| block_number >= transition)
+	}
+
 	fn block(&self, id: BlockId, include_txs: bool) -> Result<Option<RichBlock>, Error> {
 		let client = &self.client;
 		match (client.block(id.clone()), client.block_total_difficulty(id)) {
@@ -177,14 +200,92 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 					extra_info: client.block_extra_info(id.clone()).expect(EXTRA_INFO_PROOF),
 				}))
 			},
-			_ => Ok(None)
+			_ => self.check_history(id).map(|_| None),
+		}
+	}
+
+	/// Errors if the block is known, but its body and receipts were pruned.
+	fn check_history(&self, id: BlockId) -> Result<(), Error> {
+		if self.client.block_header(id.clone()).is_some() && self.client.block_body(id).is_none() {
+			return Err(errors::history_pruned(self.client.pruning_info().earliest_chain));
+		}
+		Ok(())
+	}
+
+	/// Errors if the transaction is known, but the body and receipts of its block were pruned.
+	fn check_transaction_history(&self, id: TransactionId) -> Result<(), Error> {
+		match self.client.transaction_block(id) {
+			Some(hash) => self.check_history(BlockId::Hash(hash)),
+			None => Ok(()),
+		}
+	}
+
+	fn cached_block(&self, key: Option<response_cache::Key>, id: BlockId, include_txs: bool) -> Result<Option<RichBlock>, Error> {
+		let (cache, key) = match (self.options.response_cache.as_ref(), key) {
+			(Some(cache), Some(key)) => (cache, key),
+			_ => return self.block(id, include_txs),
+		};
+
+		if let Some(CachedResponse::Block(block)) = cache.get(&key) {
+			return Ok(Some(block));
+		}
+
+		let block = self.block(id, include_txs)?;
+		if let Some(ref block) = block {
+			if let (Some(number), Some(hash)) = (block.number, block.hash.clone()) {
+				let number: U256 = number.into();
+				let best_block = self.client.chain_info().best_block_number;
+				cache.insert(key, number.low_u64(), hash.into(), best_block, CachedResponse::Block(block.clone()));
+			}
+		}
+		Ok(block)
+	}
+
+	fn cached_receipt(&self, hash: H256) -> Option<Receipt> {
+		let cache = match self.options.response_cache {
+			Some(ref cache) => cache,
+			None => return self.client.transaction_receipt(TransactionId::Hash(hash)).map(Into::into),
+		};
+
+		let key = ("eth_getTransactionReceipt", format!("{:?}", hash));
+		if let Some(CachedResponse::Receipt(receipt)) = cache.get(&key) {
+			return Some(receipt);
+		}
+
+		self.client.transaction_receipt(TransactionId::Hash(hash)).map(|receipt| {
+			let (number, block_hash) = (receipt.block_number, receipt.block_hash);
+			let receipt: Receipt = receipt.into();
+			let best_block = self.client.chain_info().best_block_number;
+			cache.insert(key, number, block_hash, best_block, CachedResponse::Receipt(receipt.clone()));
+			receipt
+		})
+	}
+
+	fn cached_call(&self, signed: SignedTransaction, id: BlockId, timeout: Option<Duration>) -> Result<Vec<u8>, CallError> {
+		let analytics = CallAnalytics { timeout: timeout, ..Default::default() };
+		// pending state is not identified by any block.
+		let (cache, hash) = match (self.options.call_cache.as_ref(), id) {
+			(_, BlockId::Pending) | (None, _) => return self.client.call(&signed, analytics, id).map(|e| e.output),
+			(Some(cache), id) => match self.client.block_hash(id) {
+				Some(hash) => (cache, hash),
+				None => return self.client.call(&signed, analytics, id).map(|e| e.output),
+			},
+		};
+
+		let key = (hash, signed.sender(), signed.hash());
+		if let Some(output) = cache.get(&key) {
+			return Ok(output);
 		}
+
+		let output = self.client.call(&signed, analytics, BlockId::Hash(hash))?.output;
+		cache.insert(key, output.clone());
+		Ok(output)
 	}
 
 	fn transaction(&self, id: TransactionId) -> Result<Option<Transaction>, Error> {
-		match self.client.transaction(id) {
+		match self.client.transaction(id.clone()) {
 			Some(t) => Ok(Some(Transaction::from_localized(t, self.eip86_transition))),
-			None => Ok(None),
+			None => self.check_transaction_history(id).map(|_| None),
 		}
 	}
 
@@ -345,6 +446,38 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		Ok(RpcU256::from(default_gas_price(&*self.client, &*self.miner)))
 	}
 
+	fn fee_history(&self, block_count: RpcU256, newest_block: BlockNumber, reward_percentiles: Trailing<Vec<f64>>) -> Result<FeeHistory, Error> {
+		let percentiles = reward_percentiles.unwrap_or_default();
+		if percentiles.iter().any(|p| !(*p >= 0.0 && *p <= 100.0)) || percentiles.windows(2).any(|w| w[0] > w[1]) {
+			return Err(errors::invalid_params("rewardPercentiles", "Expected increasing percentiles between 0 and 100."));
+		}
+
+		let block_count: U256 = block_count.into();
+		let block_count = if block_count > MAX_FEE_HISTORY_BLOCKS.into() { MAX_FEE_HISTORY_BLOCKS } else { block_count.low_u64() };
+		let newest = match newest_block {
+			BlockNumber::Pending => BlockId::Latest,
+			number => number.into(),
+		};
+		let newest = self.client.block_header(newest).ok_or_else(errors::unknown_block)?.number();
+
+		// the genesis is included only if asked for more blocks than there are
+		let oldest = (newest + 1).saturating_sub(block_count);
+		let mut gas_used_ratio = Vec::new();
+		let mut reward: Vec<Vec<RpcU256>> = Vec::new();
+		for number in oldest..newest + 1 {
+			let prices = self.client.block_gas_prices(BlockId::Number(number)).ok_or_else(errors::unknown_block)?;
+			gas_used_ratio.push(prices.gas_used_ratio());
+			reward.push(percentiles.iter().map(|p| prices.percentile(*p).into()).collect());
+		}
+
+		Ok(FeeHistory {
+			oldest_block: oldest.into(),
+			base_fee_per_gas: vec![0.into(); gas_used_ratio.len() + 1],
+			gas_used_ratio: gas_used_ratio,
+			reward: if percentiles.is_empty() { None } else { Some(reward) },
+		})
+	}
+
 	fn accounts(&self, meta: Metadata) -> BoxFuture<Vec<RpcH160>, Error> {
 		let dapp = meta.dapp_id();
 
@@ -460,11 +593,17 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>, Error> {
-		future::done(self.block(BlockId::Hash(hash.into()), include_txs)).boxed()
+		let hash: H256 = hash.into();
+		let key = ("eth_getBlockByHash", format!("{:?},{}", hash, include_txs));
+		future::done(self.cached_block(Some(key), BlockId::Hash(hash), include_txs)).boxed()
 	}
 
 	fn block_by_number(&self, num: BlockNumber, include_txs: bool) -> BoxFuture<Option<RichBlock>, Error> {
-		future::done(self.block(num.into(), include_txs)).boxed()
+		let key = match num {
+			BlockNumber::Num(n) => Some(("eth_getBlockByNumber", format!("{},{}", n, include_txs))),
+			_ => None,
+		};
+		future::done(self.cached_block(key, num.into(), include_txs)).boxed()
 	}
 
 	fn transaction_by_hash(&self, hash: RpcH256) -> Result<Option<Transaction>, Error> {
@@ -486,10 +625,18 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		let hash: H256 = hash.into();
 		match (self.miner.pending_receipt(best_block, &hash), self.options.allow_pending_receipt_query) {
 			(Some(receipt), true) => Ok(Some(receipt.into())),
-			_ => {
-				let receipt = self.client.transaction_receipt(TransactionId::Hash(hash));
-				Ok(receipt.map(Into::into))
-			}
+			_ => match self.cached_receipt(hash) {
+				Some(receipt) => Ok(Some(receipt)),
+				None => self.check_transaction_history(TransactionId::Hash(hash)).map(|_| None),
+			},
+		}
+	}
+
+	fn block_receipts(&self, block: BlockNumberOrHash) -> Result<Option<Vec<Receipt>>, Error> {
+		let id: BlockId = block.into();
+		match self.client.localized_block_receipts(id.clone()) {
+			Some(receipts) => Ok(Some(receipts.into_iter().map(Into::into).collect())),
+			None => self.check_history(id).map(|_| None),
 		}
 	}
 
@@ -559,7 +706,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 
 			if no_new_work_timeout > 0 && b.block().header().timestamp() + no_new_work_timeout < get_time().sec as u64 {
 				Err(errors::no_new_work())
-			} else if self.options.send_block_number_in_get_work {
+			} else if self.send_block_number_in_get_work(b.block().header().number()) {
 				let block_number = b.block().header().number();
 				Ok(Work {
 					pow_hash: pow_hash.into(),
@@ -599,8 +746,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256, Error> {
-		UntrustedRlp::new(&raw.into_vec()).as_val()
-			.map_err(errors::rlp)
+		UnverifiedTransaction::decode_raw(&raw.into_vec(), self.client.signing_chain_id())
+			.map_err(errors::transaction_decode)
 			.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
 			.and_then(|signed_transaction| {
 				FullDispatcher::new(self.client.clone(), self.miner.clone())
@@ -621,10 +768,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		};
 
 		let num = num.unwrap_or_default();
-		let result = self.client.call(&signed, Default::default(), num.into());
+		let timeout = self.options.call_timeout.for_origin(&meta.origin);
+		let result = self.cached_call(signed, num.into(), timeout);
 
 		future::done(result
-			.map(|b| b.output.into())
+			.map(Into::into)
 			.map_err(errors::call)
 		).boxed()
 	}
@@ -635,7 +783,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 			Ok(signed) => signed,
 			Err(e) => return future::err(e).boxed(),
 		};
-		future::done(self.client.estimate_gas(&signed, num.unwrap_or_default().into())
+		let timeout = self.options.call_timeout.for_origin(&meta.origin);
+		future::done(self.client.estimate_gas(&signed, num.unwrap_or_default().into(), timeout)
 			.map(Into::into)
 			.map_err(errors::call)
 		).boxed()
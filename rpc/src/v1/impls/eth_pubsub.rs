@@ -16,8 +16,9 @@
 
 //! Eth PUB-SUB rpc implementation.
 
-use std::sync::Arc;
-use std::collections::BTreeMap;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashMap};
 
 use futures::{self, future, BoxFuture, Future};
 use jsonrpc_core::Error;
@@ -29,27 +30,52 @@ use v1::helpers::{errors, limit_logs, Subscribers};
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::EthPubSub;
-use v1::types::{pubsub, RichHeader, Log};
+use v1::types::{pubsub, RichHeader, Log, Transaction, Work};
 
+use ethash::SeedHashCompute;
 use ethcore::encoded;
+use ethcore::ethereum::Ethash;
 use ethcore::filter::Filter as EthFilter;
 use ethcore::client::{BlockChainClient, ChainNotify, BlockId};
+use ethcore::miner::NotifyWork;
+use ethcore::transaction::SignedTransaction;
 use ethsync::LightSync;
 use light::cache::Cache;
 use light::on_demand::OnDemand;
 use light::client::{LightChainClient, LightChainNotify};
 use parity_reactor::Remote;
+use bigint::prelude::U256;
 use bigint::hash::H256;
-use util::Bytes;
+use util::{Address, Bytes};
 use parking_lot::{RwLock, Mutex};
 
 type Client = Sink<pubsub::Result>;
 
+/// Accounts watched by a pending state subscription together with their last reported state.
+struct PendingStateWatch {
+	addresses: Vec<Address>,
+	last: Mutex<HashMap<Address, (U256, U256)>>,
+}
+
+impl PendingStateWatch {
+	fn new(addresses: Vec<Address>) -> Self {
+		PendingStateWatch {
+			addresses: addresses,
+			last: Mutex::new(HashMap::new()),
+		}
+	}
+}
+
 /// Eth PubSub implementation.
 pub struct EthPubSubClient<C> {
 	handler: Arc<ChainNotificationHandler<C>>,
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
+	transactions_subscribers: Arc<RwLock<Subscribers<(Client, bool)>>>,
+	reorgs_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	pending_state_subscribers: Arc<RwLock<Subscribers<(Client, PendingStateWatch)>>>,
+	work_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	work_notifications: AtomicBool,
 }
 
 impl<C> EthPubSubClient<C> {
@@ -57,15 +83,29 @@ impl<C> EthPubSubClient<C> {
 	pub fn new(client: Arc<C>, remote: Remote) -> Self {
 		let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let reorgs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let pending_state_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let work_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		EthPubSubClient {
 			handler: Arc::new(ChainNotificationHandler {
 				client,
 				remote,
 				heads_subscribers: heads_subscribers.clone(),
 				logs_subscribers: logs_subscribers.clone(),
+				transactions_subscribers: transactions_subscribers.clone(),
+				reorgs_subscribers: reorgs_subscribers.clone(),
+				pending_state_subscribers: pending_state_subscribers.clone(),
+				work_subscribers: work_subscribers.clone(),
+				seed_compute: Mutex::new(SeedHashCompute::new()),
 			}),
 			heads_subscribers,
 			logs_subscribers,
+			transactions_subscribers: transactions_subscribers,
+			reorgs_subscribers: reorgs_subscribers,
+			pending_state_subscribers: pending_state_subscribers,
+			work_subscribers: work_subscribers,
+			work_notifications: AtomicBool::new(false),
 		}
 	}
 
@@ -75,6 +115,10 @@ impl<C> EthPubSubClient<C> {
 		let client = Self::new(client, remote);
 		*client.heads_subscribers.write() = Subscribers::new_test();
 		*client.logs_subscribers.write() = Subscribers::new_test();
+		*client.transactions_subscribers.write() = Subscribers::new_test();
+		*client.reorgs_subscribers.write() = Subscribers::new_test();
+		*client.pending_state_subscribers.write() = Subscribers::new_test();
+		*client.work_subscribers.write() = Subscribers::new_test();
 		client
 	}
 
@@ -82,6 +126,28 @@ impl<C> EthPubSubClient<C> {
 	pub fn handler(&self) -> Arc<ChainNotificationHandler<C>> {
 		self.handler.clone()
 	}
+
+	/// Returns a miner work notifier pushing work packages to `newWork` subscribers.
+	/// `newWork` subscriptions are rejected unless a notifier was created.
+	pub fn work_notifier(&self) -> WorkNotifier<C> {
+		self.work_notifications.store(true, Ordering::SeqCst);
+		WorkNotifier {
+			handler: Arc::downgrade(&self.handler),
+		}
+	}
+}
+
+/// Forwards new work packages from the miner to `newWork` subscribers.
+pub struct WorkNotifier<C> {
+	handler: Weak<ChainNotificationHandler<C>>,
+}
+
+impl<C: Send + Sync> NotifyWork for WorkNotifier<C> {
+	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		if let Some(handler) = self.handler.upgrade() {
+			handler.notify_work(pow_hash, difficulty, number);
+		}
+	}
 }
 
 impl EthPubSubClient<LightFetch> {
@@ -109,6 +175,11 @@ pub struct ChainNotificationHandler<C> {
 	remote: Remote,
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
+	transactions_subscribers: Arc<RwLock<Subscribers<(Client, bool)>>>,
+	reorgs_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	pending_state_subscribers: Arc<RwLock<Subscribers<(Client, PendingStateWatch)>>>,
+	work_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	seed_compute: Mutex<SeedHashCompute>,
 }
 
 impl<C> ChainNotificationHandler<C> {
@@ -160,6 +231,118 @@ impl<C> ChainNotificationHandler<C> {
 			);
 		}
 	}
+
+	/// Notify work subscribers about a new work package (same format as `eth_getWork` with block number).
+	pub fn notify_work(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		let subscribers = self.work_subscribers.read();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let work = Work {
+			pow_hash: pow_hash.into(),
+			seed_hash: self.seed_compute.lock().get_seedhash(number).into(),
+			target: Ethash::difficulty_to_boundary(&difficulty).into(),
+			number: Some(number),
+		};
+		for subscriber in subscribers.values() {
+			Self::notify(&self.remote, subscriber, pubsub::Result::Work(work.clone()));
+		}
+	}
+}
+
+impl<C: BlockChainClient> ChainNotificationHandler<C> {
+	/// Notify all subscribers about transactions newly imported to the queue.
+	pub fn notify_new_transactions(&self, transactions: &[SignedTransaction]) {
+		let subscribers = self.transactions_subscribers.read();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let best_block = self.client.chain_info().best_block_number;
+		let eip86_transition = self.client.eip86_transition();
+		for &(ref subscriber, full) in subscribers.values() {
+			for transaction in transactions {
+				let result = match full {
+					true => pubsub::Result::FullTransaction(
+						Transaction::from_signed(transaction.clone(), best_block, eip86_transition)
+					),
+					false => pubsub::Result::TransactionHash(transaction.hash().into()),
+				};
+				Self::notify(&self.remote, subscriber, result);
+			}
+		}
+	}
+}
+
+impl<C: BlockChainClient> ChainNotificationHandler<C> {
+	/// Notify pending state subscribers about watched accounts changed since the last notification.
+	/// Should be called whenever the pending block is rebuilt.
+	pub fn notify_pending_state(&self) {
+		let subscribers = self.pending_state_subscribers.read();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let mut states = HashMap::new();
+		for &(ref subscriber, ref watch) in subscribers.values() {
+			let mut last = watch.last.lock();
+			let mut changes = Vec::new();
+			for address in &watch.addresses {
+				if !states.contains_key(address) {
+					let balance = self.client.balance(address, BlockId::Pending);
+					let nonce = self.client.nonce(address, BlockId::Pending);
+					states.insert(*address, balance.and_then(|balance| nonce.map(|nonce| (balance, nonce))));
+				}
+				let state = match states[address] {
+					Some(state) => state,
+					None => continue,
+				};
+				if last.get(address) == Some(&state) {
+					continue;
+				}
+				last.insert(*address, state);
+				changes.push(pubsub::PendingStateChange {
+					address: (*address).into(),
+					balance: state.0.into(),
+					nonce: state.1.into(),
+				});
+			}
+
+			if !changes.is_empty() {
+				Self::notify(&self.remote, subscriber, pubsub::Result::PendingState(changes));
+			}
+		}
+	}
+
+	fn notify_reorg(&self, enacted: &[H256], retracted: &[H256], halted: bool) {
+		let subscribers = self.reorgs_subscribers.read();
+		if subscribers.is_empty() || retracted.is_empty() {
+			return;
+		}
+
+		let oldest_retracted = retracted.iter()
+			.filter_map(|hash| self.client.block_header(BlockId::Hash(*hash)))
+			.min_by_key(|header| header.number());
+		let common_ancestor = match oldest_retracted {
+			Some(header) => header.parent_hash(),
+			None => {
+				warn!(target: "rpc", "Retracted blocks are unknown, skipping reorg notification.");
+				return;
+			},
+		};
+
+		let reorg = pubsub::Reorg {
+			common_ancestor: common_ancestor.into(),
+			depth: (retracted.len() as u64).into(),
+			retracted: retracted.iter().cloned().map(Into::into).collect(),
+			enacted: enacted.iter().cloned().map(Into::into).collect(),
+			halted: halted,
+		};
+		for subscriber in subscribers.values() {
+			Self::notify(&self.remote, subscriber, pubsub::Result::Reorg(reorg.clone()));
+		}
+	}
 }
 
 /// A light client wrapper struct.
@@ -234,6 +417,22 @@ impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
 				log
 			}).collect()).boxed()
 		});
+
+		// Reorganization
+		self.notify_reorg(&enacted, &retracted, false);
+
+		// Pending state (in case there is no pending block it's the latest state)
+		if !enacted.is_empty() {
+			self.notify_pending_state();
+		}
+	}
+
+	fn reorg_halted(
+		&self,
+		enacted: Vec<H256>,
+		retracted: Vec<H256>,
+	) {
+		self.notify_reorg(&enacted, &retracted, true);
 	}
 }
 
@@ -256,12 +455,48 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 				self.logs_subscribers.write().push(subscriber, filter.into());
 				return;
 			},
+			(pubsub::Kind::NewPendingTransactions, None) => {
+				self.transactions_subscribers.write().push(subscriber, false);
+				return;
+			},
+			(pubsub::Kind::NewPendingTransactions, Some(pubsub::Params::Transactions(full))) => {
+				self.transactions_subscribers.write().push(subscriber, full);
+				return;
+			},
+			(pubsub::Kind::Reorgs, None) => {
+				self.reorgs_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::PendingState, Some(pubsub::Params::Addresses(addresses))) => {
+				let addresses = addresses.into_iter().map(Into::into).collect();
+				self.pending_state_subscribers.write().push(subscriber, PendingStateWatch::new(addresses));
+				return;
+			},
+			(pubsub::Kind::NewWork, None) if self.work_notifications.load(Ordering::SeqCst) => {
+				self.work_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::NewWork, None) => {
+				errors::work_notifications_disabled()
+			},
 			(pubsub::Kind::NewHeads, _) => {
 				errors::invalid_params("newHeads", "Expected no parameters.")
 			},
 			(pubsub::Kind::Logs, _) => {
 				errors::invalid_params("logs", "Expected a filter object.")
 			},
+			(pubsub::Kind::NewPendingTransactions, _) => {
+				errors::invalid_params("newPendingTransactions", "Expected a boolean.")
+			},
+			(pubsub::Kind::Reorgs, _) => {
+				errors::invalid_params("reorgs", "Expected no parameters.")
+			},
+			(pubsub::Kind::PendingState, _) => {
+				errors::invalid_params("pendingState", "Expected an array of addresses.")
+			},
+			(pubsub::Kind::NewWork, _) => {
+				errors::invalid_params("newWork", "Expected no parameters.")
+			},
 			_ => {
 				errors::unimplemented(None)
 			},
@@ -273,7 +508,11 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 	fn unsubscribe(&self, id: SubscriptionId) -> BoxFuture<bool, Error> {
 		let res = self.heads_subscribers.write().remove(&id).is_some();
 		let res2 = self.logs_subscribers.write().remove(&id).is_some();
+		let res3 = self.transactions_subscribers.write().remove(&id).is_some();
+		let res4 = self.reorgs_subscribers.write().remove(&id).is_some();
+		let res5 = self.pending_state_subscribers.write().remove(&id).is_some();
+		let res6 = self.work_subscribers.write().remove(&id).is_some();
 
-		future::ok(res || res2).boxed()
+		future::ok(res || res2 || res3 || res4 || res5 || res6).boxed()
 	}
 }
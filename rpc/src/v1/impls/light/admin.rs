@@ -0,0 +1,76 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Admin rpc implementation for the light client.
+use std::sync::Arc;
+
+use ethsync::{LightSyncProvider, ManageNetwork};
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+
+use v1::helpers::errors;
+use v1::traits::Admin;
+use v1::types::{PeerInfo, PeerReputation};
+
+/// Admin rpc implementation.
+pub struct AdminClient<S: ?Sized> {
+	sync: Arc<S>,
+	net: Arc<ManageNetwork>,
+}
+
+impl<S: ?Sized> AdminClient<S> where S: LightSyncProvider {
+	/// Creates new AdminClient.
+	pub fn new(sync: &Arc<S>, net: &Arc<ManageNetwork>) -> Self {
+		AdminClient {
+			sync: sync.clone(),
+			net: net.clone(),
+		}
+	}
+}
+
+impl<S: ?Sized> Admin for AdminClient<S> where S: LightSyncProvider + Send + Sync + 'static {
+	fn add_reserved_peer(&self, peer: String) -> Result<bool, Error> {
+		self.net.add_reserved_peer(peer)
+			.map_err(|e| errors::invalid_params("Peer address", e))
+			.map(|_| true)
+	}
+
+	fn remove_reserved_peer(&self, peer: String) -> Result<bool, Error> {
+		self.net.remove_reserved_peer(peer)
+			.map_err(|e| errors::invalid_params("Peer address", e))
+			.map(|_| true)
+	}
+
+	fn disconnect_peer(&self, peer: String) -> Result<bool, Error> {
+		self.net.disconnect_peer(peer)
+			.map_err(|e| errors::invalid_params("Peer", e))
+			.map(|_| true)
+	}
+
+	fn peers(&self) -> Result<Vec<PeerInfo>, Error> {
+		Ok(self.sync.peers().into_iter().map(Into::into).collect())
+	}
+
+	fn peer_reputations(&self) -> Result<Vec<PeerReputation>, Error> {
+		Ok(self.net.peer_reputations().into_iter().map(Into::into).collect())
+	}
+
+	fn clear_peer_reputation(&self, peer: Trailing<String>) -> Result<bool, Error> {
+		self.net.clear_peer_reputation(peer.into())
+			.map_err(|e| errors::invalid_params("Peer", e))
+			.map(|_| true)
+	}
+}
@@ -35,9 +35,8 @@ use ethcore::encoded;
 use ethcore::executed::{Executed, ExecutionError};
 use ethcore::ids::BlockId;
 use ethcore::filter::Filter as EthcoreFilter;
-use ethcore::transaction::{Action, SignedTransaction, Transaction as EthTransaction};
+use ethcore::transaction::{Action, SignedTransaction, UnverifiedTransaction, Transaction as EthTransaction};
 use ethsync::LightSync;
-use rlp::UntrustedRlp;
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY_LIST_RLP};
 use bigint::prelude::U256;
 use parking_lot::{RwLock, Mutex};
@@ -52,7 +51,7 @@ use v1::helpers::block_import::is_major_importing;
 use v1::helpers::light_fetch::LightFetch;
 use v1::traits::Eth;
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
+	RichBlock, Block, BlockTransactions, BlockNumber, BlockNumberOrHash, Bytes, SyncStatus, SyncInfo, FeeHistory,
 	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
@@ -254,6 +253,10 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 			.unwrap_or_else(Default::default))
 	}
 
+	fn fee_history(&self, _block_count: RpcU256, _newest_block: BlockNumber, _reward_percentiles: Trailing<Vec<f64>>) -> Result<FeeHistory, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn accounts(&self, meta: Metadata) -> BoxFuture<Vec<RpcH160>, Error> {
 		let dapp: DappId = meta.dapp_id().into();
 
@@ -363,8 +366,8 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256, Error> {
 		let best_header = self.client.best_block_header().decode();
 
-		UntrustedRlp::new(&raw.into_vec()).as_val()
-			.map_err(errors::rlp)
+		UnverifiedTransaction::decode_raw(&raw.into_vec(), Some(self.client.engine().params().chain_id))
+			.map_err(errors::transaction_decode)
 			.and_then(|tx| {
 				self.client.engine().verify_transaction_basic(&tx, &best_header)
 					.map_err(errors::transaction)?;
@@ -419,6 +422,10 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 		Err(errors::unimplemented(None))
 	}
 
+	fn block_receipts(&self, block: BlockNumberOrHash) -> Result<Option<Vec<Receipt>>, Error> {
+		Err(errors::unimplemented(None))
+	}
+
 	fn uncle_by_block_hash_and_index(&self, hash: RpcH256, idx: Index) -> Result<Option<RichBlock>, Error> {
 		Err(errors::unimplemented(None))
 	}
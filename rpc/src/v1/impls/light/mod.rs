@@ -19,12 +19,14 @@
 //! This doesn't re-implement all of the RPC APIs, just those which aren't
 //! significantly generic to be reused.
 
+pub mod admin;
 pub mod eth;
 pub mod parity;
 pub mod parity_set;
 pub mod trace;
 pub mod net;
 
+pub use self::admin::AdminClient;
 pub use self::eth::EthClient;
 pub use self::parity::ParityClient;
 pub use self::parity_set::ParitySetClient;
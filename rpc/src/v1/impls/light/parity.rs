@@ -40,11 +40,12 @@ use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, H160, H256, H512, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, Transaction, RpcSettings, Histogram, NonceGaps, DryRunBlock,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader,
+	OperationsInfo, DappId, ChainStatus, ImportQueue, SyncProgress, ForkPartition,
+	AccountInfo, HwAccountInfo, Header, RichHeader, StateDiff, BadBlock, GasEstimate, StratumWorker,
+	TransactionEvictions, TransactionBans,
 };
 
 /// Parity implementation for light client.
@@ -270,6 +271,18 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn scheduled_transactions(&self) -> Result<Vec<Transaction>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn nonce_gaps(&self, _address: H160) -> Result<NonceGaps, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn dry_run_block(&self, _gas_limit: U256, _author: H160) -> Result<DryRunBlock, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>, Error> {
 		let stats = self.light_dispatch.sync.transactions_stats();
 		Ok(stats.into_iter()
@@ -278,6 +291,22 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn transactions_evictions(&self) -> Result<TransactionEvictions, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn transactions_bans(&self) -> Result<TransactionBans, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn journaled_transactions(&self) -> Result<Vec<Transaction>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error> {
 		let mut map = BTreeMap::new();
 		let chain_info = self.light_dispatch.client.chain_info();
@@ -343,9 +372,32 @@ impl Parity for ParityClient {
 
 		Ok(ChainStatus {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
+			warp_restoration: None,
 		})
 	}
 
+	fn import_queue(&self) -> Result<ImportQueue, Error> {
+		Ok(self.light_dispatch.client.queue_info().into())
+	}
+
+	fn sync_progress(&self) -> Result<SyncProgress, Error> {
+		let current_block = self.light_dispatch.client.chain_info().best_block_number;
+		let sync = &self.light_dispatch.sync;
+		let stage = if sync.is_major_importing() { "headers" } else { "idle" };
+
+		Ok(SyncProgress {
+			stage: stage.into(),
+			starting_block: sync.start_block().into(),
+			current_block: current_block.into(),
+			highest_block: sync.highest_block().unwrap_or(current_block).into(),
+			..Default::default()
+		})
+	}
+
+	fn fork_partition(&self) -> Result<ForkPartition, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -397,6 +449,22 @@ impl Parity for ParityClient {
 		future::err(errors::light_unimplemented(None)).boxed()
 	}
 
+	fn estimate_gas(&self, _meta: Self::Metadata, _request: CallRequest, _num: Trailing<BlockNumber>) -> BoxFuture<GasEstimate, Error> {
+		future::err(errors::light_unimplemented(None)).boxed()
+	}
+
+	fn block_state_diff(&self, _block: BlockNumber) -> Result<StateDiff, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_witness(&self, _block: BlockNumber) -> Result<Vec<Bytes>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn node_health(&self) -> BoxFuture<Health, Error> {
 		self.health.health()
 			.map_err(|err| errors::internal("Health API failure.", err))
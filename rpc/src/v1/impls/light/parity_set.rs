@@ -29,7 +29,7 @@ use jsonrpc_core::Error;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, InclusionFilter};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
@@ -66,6 +66,10 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_extra_data_template(&self, _template: String) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_author(&self, _author: H160) -> Result<bool, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -78,6 +82,30 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_transactions_memory_limit(&self, _limit: usize) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_sender_transactions_limit(&self, _limit: usize) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_sender_transactions_memory_limit(&self, _limit: usize) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn clear_transactions_bans(&self) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn inclusion_filter(&self) -> Result<InclusionFilter, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_inclusion_filter(&self, _filter: InclusionFilter) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_tx_gas_limit(&self, _limit: U256) -> Result<bool, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -154,4 +182,28 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn cancel_scheduled_transaction(&self, _hash: H256) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn invalidate_block(&self, _hash: H256) -> Result<usize, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn reload_chain_spec(&self, _spec: String) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn accept_reorg(&self, _head: H256) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn mine_block(&self) -> Result<H256, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_automine(&self, _enabled: bool) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -16,6 +16,8 @@
 
 //! Ethereum rpc interface implementation.
 
+mod admin;
+mod debug;
 mod eth;
 mod eth_filter;
 mod eth_pubsub;
@@ -24,6 +26,7 @@ mod parity;
 mod parity_accounts;
 mod parity_set;
 mod personal;
+mod private;
 mod pubsub;
 mod signer;
 mod signing;
@@ -35,14 +38,17 @@ mod web3;
 
 pub mod light;
 
+pub use self::admin::AdminClient;
+pub use self::debug::DebugClient;
 pub use self::eth::{EthClient, EthClientOptions};
 pub use self::eth_filter::EthFilterClient;
-pub use self::eth_pubsub::EthPubSubClient;
+pub use self::eth_pubsub::{EthPubSubClient, WorkNotifier};
 pub use self::net::NetClient;
 pub use self::parity::ParityClient;
 pub use self::parity_accounts::ParityAccountsClient;
 pub use self::parity_set::ParitySetClient;
 pub use self::personal::PersonalClient;
+pub use self::private::PrivateClient;
 pub use self::pubsub::PubSubClient;
 pub use self::signer::SignerClient;
 pub use self::signing::SigningQueueClient;
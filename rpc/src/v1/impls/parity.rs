@@ -28,28 +28,32 @@ use ethkey::{Brain, Generator};
 use ethstore::random_phrase;
 use ethsync::{SyncProvider, ManageNetwork};
 use ethcore::account_provider::AccountProvider;
+use ethcore::block::IsBlock;
 use ethcore::client::{MiningBlockChainClient};
-use ethcore::ids::BlockId;
-use ethcore::miner::MinerService;
+use ethcore::ids::{BlockId, TransactionId};
+use ethcore::miner::{MinerService, StratumWorkers};
 use ethcore::mode::Mode;
+use ethcore::snapshot::{SnapshotService, RestorationStatus};
 use ethcore::transaction::SignedTransaction;
 use ethcore_logger::RotatingLogger;
+use local_store::TransactionJournal;
 use node_health::{NodeHealth, Health};
 use updater::{Service as UpdateService};
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::helpers::{self, errors, fake_sign, ipfs, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::{self, errors, fake_sign, ipfs, CallTimeout, SigningQueue, SignerService, NetworkSettings};
 use v1::helpers::accounts::unwrap_provider;
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, H160, H256, H512, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, Transaction, RpcSettings, Histogram, NonceGaps, DryRunBlock,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader
+	OperationsInfo, DappId, ChainStatus, RestorationProgress, ImportQueue, SyncProgress, ForkPartition,
+	AccountInfo, HwAccountInfo, RichHeader, StateDiff, BadBlock, GasEstimate, StratumWorker,
+	TransactionEvictions, TransactionBans,
 };
 
 /// Parity implementation.
@@ -58,6 +62,7 @@ pub struct ParityClient<C, M, U>  {
 	miner: Arc<M>,
 	updater: Arc<U>,
 	sync: Arc<SyncProvider>,
+	snapshot: Arc<SnapshotService>,
 	net: Arc<ManageNetwork>,
 	health: NodeHealth,
 	accounts: Option<Arc<AccountProvider>>,
@@ -66,6 +71,9 @@ pub struct ParityClient<C, M, U>  {
 	signer: Option<Arc<SignerService>>,
 	dapps_address: Option<(String, u16)>,
 	ws_address: Option<(String, u16)>,
+	call_timeout: CallTimeout,
+	journal: Option<Arc<TransactionJournal>>,
+	stratum: Option<Arc<StratumWorkers>>,
 	eip86_transition: u64,
 }
 
@@ -77,6 +85,7 @@ impl<C, M, U> ParityClient<C, M, U> where
 		client: Arc<C>,
 		miner: Arc<M>,
 		sync: Arc<SyncProvider>,
+		snapshot: Arc<SnapshotService>,
 		updater: Arc<U>,
 		net: Arc<ManageNetwork>,
 		health: NodeHealth,
@@ -86,12 +95,16 @@ impl<C, M, U> ParityClient<C, M, U> where
 		signer: Option<Arc<SignerService>>,
 		dapps_address: Option<(String, u16)>,
 		ws_address: Option<(String, u16)>,
+		call_timeout: CallTimeout,
+		journal: Option<Arc<TransactionJournal>>,
+		stratum: Option<Arc<StratumWorkers>>,
 	) -> Self {
 		let eip86_transition = client.eip86_transition();
 		ParityClient {
 			client,
 			miner,
 			sync,
+			snapshot: snapshot,
 			updater,
 			net,
 			health,
@@ -101,6 +114,9 @@ impl<C, M, U> ParityClient<C, M, U> where
 			signer,
 			dapps_address,
 			ws_address,
+			call_timeout: call_timeout,
+			journal: journal,
+			stratum: stratum,
 			eip86_transition,
 		}
 	}
@@ -213,7 +229,7 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 	}
 
 	fn net_port(&self) -> Result<u16, Error> {
-		Ok(self.settings.network_port)
+		Ok(self.net.external_port().unwrap_or(self.settings.network_port))
 	}
 
 	fn node_name(&self) -> Result<String, Error> {
@@ -294,6 +310,45 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 		Ok(self.miner.future_transactions().into_iter().map(|t| Transaction::from_pending(t, block_number, self.eip86_transition)).collect::<Vec<_>>())
 	}
 
+	fn scheduled_transactions(&self) -> Result<Vec<Transaction>, Error> {
+		let chain_info = self.client.chain_info();
+		Ok(self.miner.scheduled_transactions(chain_info.best_block_number, chain_info.best_block_timestamp)
+			.into_iter()
+			.map(|t| Transaction::from_pending(t, chain_info.best_block_number, self.eip86_transition))
+			.collect::<Vec<_>>()
+		)
+	}
+
+	fn nonce_gaps(&self, address: H160) -> Result<NonceGaps, Error> {
+		let address: Address = address.into();
+		let block_number = self.client.chain_info().best_block_number;
+		let transactions = self.miner.pending_transactions().into_iter()
+			.chain(self.miner.future_transactions())
+			.filter(|t| t.sender() == address)
+			.map(|t| Transaction::from_pending(t, block_number, self.eip86_transition))
+			.collect();
+
+		Ok(NonceGaps::new(self.client.latest_nonce(&address), transactions))
+	}
+
+	fn dry_run_block(&self, gas_limit: U256, author: H160) -> Result<DryRunBlock, Error> {
+		let block = self.miner.dry_run_block(&*self.client, author.into(), gas_limit.into());
+		let header = &block.block().fields().header;
+		let number = header.number();
+
+		Ok(DryRunBlock {
+			number: number.into(),
+			parent_hash: (*header.parent_hash()).into(),
+			author: (*header.author()).into(),
+			gas_limit: (*header.gas_limit()).into(),
+			gas_used: (*header.gas_used()).into(),
+			state_root: (*header.state_root()).into(),
+			transactions: block.transactions().iter()
+				.map(|t| Transaction::from_signed(t.clone(), number, self.eip86_transition))
+				.collect(),
+		})
+	}
+
 	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>, Error> {
 		let stats = self.sync.transactions_stats();
 		Ok(stats.into_iter()
@@ -302,6 +357,14 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn transactions_evictions(&self) -> Result<TransactionEvictions, Error> {
+		Ok(self.miner.transactions_evictions().into())
+	}
+
+	fn transactions_bans(&self) -> Result<TransactionBans, Error> {
+		Ok(self.miner.transactions_bans().into())
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error> {
 		// Return nothing if accounts are disabled (running as public node)
 		if self.accounts.is_none() {
@@ -317,6 +380,32 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn journaled_transactions(&self) -> Result<Vec<Transaction>, Error> {
+		// Return nothing if accounts are disabled (running as public node)
+		if self.accounts.is_none() {
+			return Ok(Vec::new());
+		}
+
+		let transactions = match self.journal {
+			Some(ref journal) => journal.journaled_transactions().map_err(errors::database)?,
+			None => return Ok(Vec::new()),
+		};
+		let block_number = self.client.chain_info().best_block_number;
+		Ok(transactions
+			.into_iter()
+			.filter(|tx| self.client.transaction(TransactionId::Hash(tx.hash())).is_none())
+			.map(|tx| Transaction::from_pending(tx, block_number, self.eip86_transition))
+			.collect()
+		)
+	}
+
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>, Error> {
+		Ok(match self.stratum {
+			Some(ref stratum) => stratum.workers().into_iter().map(Into::into).collect(),
+			None => Vec::new(),
+		})
+	}
+
 	fn dapps_url(&self) -> Result<String, Error> {
 		helpers::to_url(&self.dapps_address)
 			.ok_or_else(|| errors::dapps_disabled())
@@ -368,11 +457,35 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 		let gap = chain_info.ancient_block_number.map(|x| U256::from(x + 1))
 			.and_then(|first| chain_info.first_block_number.map(|last| (first, U256::from(last))));
 
+		let warp_restoration = match self.snapshot.status() {
+			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } => Some(RestorationProgress {
+				state_chunks: state_chunks,
+				block_chunks: block_chunks,
+				state_chunks_done: state_chunks_done,
+				block_chunks_done: block_chunks_done,
+				chunks_received: self.sync.status().snapshot_chunks_done as u32,
+			}),
+			_ => None,
+		};
+
 		Ok(ChainStatus {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
+			warp_restoration: warp_restoration,
 		})
 	}
 
+	fn import_queue(&self) -> Result<ImportQueue, Error> {
+		Ok(self.client.queue_info().into())
+	}
+
+	fn sync_progress(&self) -> Result<SyncProgress, Error> {
+		Ok(self.sync.status().into())
+	}
+
+	fn fork_partition(&self) -> Result<ForkPartition, Error> {
+		Ok(self.sync.fork_partition().into())
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -425,6 +538,31 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 		future::done(result).boxed()
 	}
 
+	fn estimate_gas(&self, meta: Self::Metadata, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<GasEstimate, Error> {
+		let signed = try_bf!(fake_sign::sign_call(&self.client, &self.miner, request.into(), meta.is_dapp()));
+		let timeout = self.call_timeout.for_origin(&meta.origin);
+		future::done(self.client.estimate_gas_breakdown(&signed, num.unwrap_or_default().into(), timeout)
+			.map(Into::into)
+			.map_err(errors::call)
+		).boxed()
+	}
+
+	fn block_state_diff(&self, block: BlockNumber) -> Result<StateDiff, Error> {
+		self.client.block_state_diff(block.into())
+			.map(Into::into)
+			.map_err(errors::call)
+	}
+
+	fn block_witness(&self, block: BlockNumber) -> Result<Vec<Bytes>, Error> {
+		self.client.block_witness(block.into())
+			.map(|nodes| nodes.into_iter().map(Into::into).collect())
+			.map_err(errors::call)
+	}
+
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>, Error> {
+		Ok(self.client.bad_blocks().into_iter().map(Into::into).collect())
+	}
+
 	fn node_health(&self) -> BoxFuture<Health, Error> {
 		self.health.health()
 			.map_err(|err| errors::internal("Health API failure.", err))
@@ -288,6 +288,13 @@ impl ParityAccounts for ParityAccountsClient {
 			.map(|_| true)
 	}
 
+	fn rotate_vault_key(&self, name: String, password: String, new_password: String) -> Result<bool, Error> {
+		self.account_provider()?
+			.rotate_vault_key(&name, &password, &new_password)
+			.map_err(|e| errors::account("Could not rotate vault key.", e))
+			.map(|_| true)
+	}
+
 	fn change_vault(&self, address: RpcH160, new_vault: String) -> Result<bool, Error> {
 		self.account_provider()?
 			.change_vault(address.into(), &new_vault)
@@ -18,7 +18,7 @@
 use std::io;
 use std::sync::Arc;
 
-use ethcore::miner::MinerService;
+use ethcore::miner::{MinerService, ExtraDataError};
 use ethcore::client::MiningBlockChainClient;
 use ethcore::mode::Mode;
 use ethsync::ManageNetwork;
@@ -31,7 +31,7 @@ use jsonrpc_core::Error;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, InclusionFilter};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<C, M, U, F = fetch::Client> {
@@ -95,6 +95,12 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_extra_data_template(&self, template: String) -> Result<bool, Error> {
+		let template = template.parse().map_err(|e: ExtraDataError| errors::invalid_params("template", e.to_string()))?;
+		self.miner.set_extra_data_template(template).map_err(|e| errors::invalid_params("template", e.to_string()))?;
+		Ok(true)
+	}
+
 	fn set_author(&self, author: H160) -> Result<bool, Error> {
 		self.miner.set_author(author.into());
 		Ok(true)
@@ -110,6 +116,35 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_transactions_memory_limit(&self, limit: usize) -> Result<bool, Error> {
+		self.miner.set_transactions_memory_limit(limit);
+		Ok(true)
+	}
+
+	fn set_sender_transactions_limit(&self, limit: usize) -> Result<bool, Error> {
+		self.miner.set_sender_transactions_limit(limit);
+		Ok(true)
+	}
+
+	fn set_sender_transactions_memory_limit(&self, limit: usize) -> Result<bool, Error> {
+		self.miner.set_sender_transactions_memory_limit(limit);
+		Ok(true)
+	}
+
+	fn clear_transactions_bans(&self) -> Result<bool, Error> {
+		self.miner.clear_transactions_bans();
+		Ok(true)
+	}
+
+	fn inclusion_filter(&self) -> Result<InclusionFilter, Error> {
+		Ok(self.miner.inclusion_filter().into())
+	}
+
+	fn set_inclusion_filter(&self, filter: InclusionFilter) -> Result<bool, Error> {
+		self.miner.set_inclusion_filter(filter.into());
+		Ok(true)
+	}
+
 	fn set_tx_gas_limit(&self, limit: U256) -> Result<bool, Error> {
 		self.miner.set_tx_gas_limit(limit.into());
 		Ok(true)
@@ -198,4 +233,47 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 
 		Ok(self.miner.remove_pending_transaction(&*self.client, &hash).map(|t| Transaction::from_pending(t, block_number, self.eip86_transition)))
 	}
+
+	fn cancel_scheduled_transaction(&self, hash: H256) -> Result<bool, Error> {
+		let chain_info = self.client.chain_info();
+		let hash = hash.into();
+
+		let is_scheduled = self.miner.scheduled_transactions(chain_info.best_block_number, chain_info.best_block_timestamp)
+			.iter()
+			.any(|tx| tx.hash() == hash);
+		if !is_scheduled {
+			return Ok(false);
+		}
+
+		Ok(self.miner.remove_pending_transaction(&*self.client, &hash).is_some())
+	}
+
+	fn invalidate_block(&self, hash: H256) -> Result<usize, Error> {
+		self.client.invalidate_block(hash.into())
+			.map(|retracted| retracted.len())
+			.map_err(|e| errors::invalid_params("hash", e))
+	}
+
+	fn reload_chain_spec(&self, spec: String) -> Result<bool, Error> {
+		self.client.reload_chain_spec(spec.into_bytes())
+			.map(|_| true)
+			.map_err(|e| errors::invalid_params("spec", e))
+	}
+
+	fn accept_reorg(&self, head: H256) -> Result<bool, Error> {
+		self.client.accept_reorg(head.into())
+			.map(|_| true)
+			.map_err(|e| errors::invalid_params("head", e))
+	}
+
+	fn mine_block(&self) -> Result<H256, Error> {
+		self.miner.seal_block_now(&*self.client)
+			.map(Into::into)
+			.ok_or_else(errors::cannot_seal)
+	}
+
+	fn set_automine(&self, enabled: bool) -> Result<bool, Error> {
+		self.miner.set_automine(enabled);
+		Ok(true)
+	}
 }
@@ -0,0 +1,117 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transaction signing RPC implementation.
+
+use std::sync::Arc;
+
+use rlp::UntrustedRlp;
+
+use ethcore_private_tx::{Provider as PrivateTransactionManager, key_id};
+use ethcore::transaction::{Transaction, SignedTransaction, UnverifiedTransaction, Action};
+use bigint::prelude::U256;
+
+use jsonrpc_core::Error;
+use v1::helpers::{errors, CallRequest as Request};
+use v1::traits::Private;
+use v1::types::{Bytes, CallRequest, H160, H256, U256 as RpcU256, BlockNumber, TransactionRequest,
+	PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction};
+
+/// Private transaction manager API endpoint implementation.
+pub struct PrivateClient {
+	private: Option<Arc<PrivateTransactionManager>>,
+}
+
+impl PrivateClient {
+	/// Creates a new instance.
+	pub fn new(private: Option<Arc<PrivateTransactionManager>>) -> Self {
+		PrivateClient {
+			private: private,
+		}
+	}
+
+	fn unwrap_manager(&self) -> Result<&PrivateTransactionManager, Error> {
+		match self.private {
+			Some(ref client) => Ok(&**client),
+			None => Err(errors::private_disabled()),
+		}
+	}
+}
+
+fn decode_transaction(raw: Bytes) -> Result<SignedTransaction, Error> {
+	UntrustedRlp::new(&raw.into_vec()).as_val::<UnverifiedTransaction>()
+		.map_err(errors::rlp)
+		.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
+}
+
+impl Private for PrivateClient {
+	fn send_transaction(&self, request: Bytes) -> Result<PrivateTransactionReceipt, Error> {
+		let signed_transaction = decode_transaction(request)?;
+		let client = self.unwrap_manager()?;
+		let receipt = client.create_private_transaction(signed_transaction).map_err(errors::private_message)?;
+		Ok(receipt.into())
+	}
+
+	fn compose_deployment_transaction(&self, block_number: BlockNumber, request: Bytes, validators: Vec<H160>, gas_price: RpcU256) -> Result<PrivateTransactionReceiptAndTransaction, Error> {
+		let signed_transaction = decode_transaction(request)?;
+		let client = self.unwrap_manager()?;
+		let validators: Vec<_> = validators.into_iter().map(Into::into).collect();
+		let (transaction, contract_address) = client.public_creation_transaction(block_number.into(), &signed_transaction, &validators, gas_price.into())
+			.map_err(errors::private_message)?;
+		let sender = signed_transaction.sender();
+
+		Ok(PrivateTransactionReceiptAndTransaction {
+			transaction: TransactionRequest {
+				from: Some(sender.into()),
+				to: None,
+				gas_price: Some(transaction.gas_price.into()),
+				gas: Some(transaction.gas.into()),
+				value: Some(transaction.value.into()),
+				data: Some(transaction.data.into()),
+				nonce: Some(transaction.nonce.into()),
+				condition: None,
+			},
+			receipt: PrivateTransactionReceipt {
+				transaction_hash: signed_transaction.hash().into(),
+				contract_address: contract_address.into(),
+				status: "deployment transaction composed".into(),
+			},
+		})
+	}
+
+	fn private_call(&self, block_number: BlockNumber, request: CallRequest) -> Result<Bytes, Error> {
+		let request: Request = request.into();
+		let from = request.from.unwrap_or_default();
+		// private execution doesn't check the nonce, balance or gas limit
+		let signed = Transaction {
+			nonce: request.nonce.unwrap_or_default(),
+			action: request.to.map_or(Action::Create, Action::Call),
+			gas: request.gas.unwrap_or(U256::from(2) << 50),
+			gas_price: request.gas_price.unwrap_or_default(),
+			value: request.value.unwrap_or_default(),
+			data: request.data.unwrap_or_default(),
+		}.fake_sign(from);
+
+		let client = self.unwrap_manager()?;
+		let executed = client.private_call(block_number.into(), &signed).map_err(errors::private_message)?;
+		Ok(executed.output.into())
+	}
+
+	fn private_contract_key(&self, contract_address: H160) -> Result<H256, Error> {
+		self.unwrap_manager()?;
+		Ok(key_id(&contract_address.into()).into())
+	}
+}
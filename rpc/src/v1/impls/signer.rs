@@ -19,11 +19,10 @@
 use std::sync::Arc;
 
 use ethcore::account_provider::AccountProvider;
-use ethcore::transaction::{SignedTransaction, PendingTransaction};
+use ethcore::transaction::{SignedTransaction, PendingTransaction, UnverifiedTransaction};
 use ethkey;
 use futures::{future, BoxFuture, Future, IntoFuture};
 use parity_reactor::Remote;
-use rlp::UntrustedRlp;
 use parking_lot::Mutex;
 
 use jsonrpc_core::{futures, Error};
@@ -133,7 +132,7 @@ impl<D: Dispatcher + 'static> SignerClient<D> {
 	fn verify_transaction<F>(bytes: Bytes, request: FilledTransactionRequest, process: F) -> Result<ConfirmationResponse, Error> where
 		F: FnOnce(PendingTransaction) -> Result<ConfirmationResponse, Error>,
 	{
-		let signed_transaction = UntrustedRlp::new(&bytes.0).as_val().map_err(errors::rlp)?;
+		let signed_transaction = UnverifiedTransaction::decode_raw(&bytes.0, None).map_err(errors::transaction_decode)?;
 		let signed_transaction = SignedTransaction::new(signed_transaction).map_err(|e| errors::invalid_params("Invalid signature.", e))?;
 		let sender = signed_transaction.sender();
 
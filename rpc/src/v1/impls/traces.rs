@@ -18,10 +18,9 @@
 
 use std::sync::Arc;
 
-use ethcore::client::{MiningBlockChainClient, CallAnalytics, TransactionId, TraceId};
+use ethcore::client::{MiningBlockChainClient, CallAnalytics, StepTracing, BlockId, TransactionId, TraceId, TraceFilter as EthTraceFilter};
 use ethcore::miner::MinerService;
-use ethcore::transaction::SignedTransaction;
-use rlp::UntrustedRlp;
+use ethcore::transaction::{SignedTransaction, UnverifiedTransaction};
 
 use jsonrpc_core::Error;
 use jsonrpc_core::futures::{self, Future, BoxFuture};
@@ -31,12 +30,20 @@ use v1::traits::Traces;
 use v1::helpers::{errors, fake_sign};
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceOptions, H256};
 
-fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
-	CallAnalytics {
+const TRACE_TYPES: &'static [&'static str] = &["trace", "vmTrace", "stateDiff", "stepTrace"];
+
+fn to_call_analytics(flags: TraceOptions) -> Result<CallAnalytics, Error> {
+	if let Some(unknown) = flags.iter().find(|flag| !TRACE_TYPES.contains(&flag.as_str())) {
+		return Err(errors::invalid_params("traceTypes", format!("Unknown trace type: {}, expected one of {:?}", unknown, TRACE_TYPES)));
+	}
+
+	Ok(CallAnalytics {
 		transaction_tracing: flags.contains(&("trace".to_owned())),
 		vm_tracing: flags.contains(&("vmTrace".to_owned())),
 		state_diffing: flags.contains(&("stateDiff".to_owned())),
-	}
+		step_tracing: if flags.contains(&("stepTrace".to_owned())) { Some(StepTracing::default()) } else { None },
+		timeout: None,
+	})
 }
 
 /// Traces api implementation.
@@ -55,20 +62,43 @@ impl<C, M> TracesClient<C, M> {
 	}
 }
 
+impl<C, M> TracesClient<C, M> where C: MiningBlockChainClient {
+	/// Errors if the block is known, but its traces were pruned.
+	fn check_pruned(&self, id: BlockId) -> Result<(), Error> {
+		let earliest = self.client.pruning_info().earliest_trace;
+		match self.client.block_number(id) {
+			Some(number) if number != 0 && number < earliest => Err(errors::traces_pruned(earliest)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Errors if the transaction is known, but traces of its block were pruned.
+	fn check_transaction_pruned(&self, id: TransactionId) -> Result<(), Error> {
+		match self.client.transaction_block(id) {
+			Some(hash) => self.check_pruned(BlockId::Hash(hash)),
+			None => Ok(()),
+		}
+	}
+}
+
 impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
 	type Metadata = Metadata;
 
 	fn filter(&self, filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>, Error> {
-		Ok(self.client.filter_traces(filter.into())
+		let filter: EthTraceFilter = filter.into();
+		self.check_pruned(filter.range.start.clone())?;
+		Ok(self.client.filter_traces(filter)
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
 	fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>, Error> {
+		self.check_pruned(block_number.clone().into())?;
 		Ok(self.client.block_traces(block_number.into())
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
 	fn transaction_traces(&self, transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>, Error> {
+		self.check_transaction_pruned(TransactionId::Hash(transaction_hash.clone().into()))?;
 		Ok(self.client.transaction_traces(TransactionId::Hash(transaction_hash.into()))
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
@@ -79,6 +109,7 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 			address: address.into_iter().map(|i| i.value()).collect()
 		};
 
+		self.check_transaction_pruned(id.transaction.clone())?;
 		Ok(self.client.trace(id)
 			.map(LocalizedTrace::from))
 	}
@@ -88,8 +119,9 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 
 		let request = CallRequest::into(request);
 		let signed = try_bf!(fake_sign::sign_call(&self.client, &self.miner, request, meta.is_dapp()));
+		let analytics = try_bf!(to_call_analytics(flags));
 
-		let res = self.client.call(&signed, to_call_analytics(flags), block.into())
+		let res = self.client.call(&signed, analytics, block.into())
 			.map(TraceResults::from)
 			.map_err(errors::call);
 
@@ -103,7 +135,7 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 			.map(|(request, flags)| {
 				let request = CallRequest::into(request);
 				let signed = fake_sign::sign_call(&self.client, &self.miner, request, meta.is_dapp())?;
-				Ok((signed, to_call_analytics(flags)))
+				Ok((signed, to_call_analytics(flags)?))
 			})
 			.collect::<Result<Vec<_>, Error>>());
 
@@ -117,16 +149,16 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Trailing<BlockNumber>) -> Result<TraceResults, Error> {
 		let block = block.unwrap_or_default();
 
-		let tx = UntrustedRlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
+		let tx = UnverifiedTransaction::decode_raw(&raw_transaction.into_vec(), self.client.signing_chain_id()).map_err(errors::transaction_decode)?;
 		let signed = SignedTransaction::new(tx).map_err(errors::transaction)?;
 
-		self.client.call(&signed, to_call_analytics(flags), block.into())
+		self.client.call(&signed, to_call_analytics(flags)?, block.into())
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
 
 	fn replay_transaction(&self, transaction_hash: H256, flags: TraceOptions) -> Result<TraceResults, Error> {
-		self.client.replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(flags))
+		self.client.replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(flags)?)
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
@@ -36,14 +36,15 @@ mod types;
 #[cfg(test)]
 mod tests;
 
+pub mod authorization;
 pub mod extractors;
 pub mod informant;
 pub mod metadata;
 pub mod traits;
 
-pub use self::traits::{Web3, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, PubSub, Signer, Personal, Traces, Rpc, SecretStore};
+pub use self::traits::{Admin, Debug, Web3, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, PubSub, Signer, Personal, Private, Traces, Rpc, SecretStore};
 pub use self::impls::*;
-pub use self::helpers::{NetworkSettings, block_import, dispatch};
+pub use self::helpers::{NetworkSettings, ResponseCache, CallCache, CallTimeout, block_import, dispatch};
 pub use self::metadata::Metadata;
 pub use self::types::Origin;
 pub use self::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
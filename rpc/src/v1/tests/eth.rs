@@ -64,15 +64,24 @@ fn miner_service(spec: &Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
 			tx_queue_size: 1024,
 			tx_gas_limit: !U256::zero(),
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_replace_bump: 12,
 			tx_queue_gas_limit: GasLimit::None,
 			tx_queue_banning: Banning::Disabled,
+			tx_queue_invalid_banning: None,
+			tx_queue_replace_cooldown: None,
+			block_ordering: Default::default(),
+			inclusion_filter: Default::default(),
 			tx_queue_memory_limit: None,
+			tx_queue_sender_limit: None,
+			tx_queue_sender_memory_limit: None,
 			pending_set: PendingSet::SealingOrElseQueue,
 			reseal_min_period: Duration::from_secs(0),
 			reseal_max_period: Duration::from_secs(120),
+			seal_interval: None,
 			work_queue_size: 50,
 			enable_resubmission: true,
 			refuse_service_transactions: false,
+			uncle_policy: Default::default(),
 		},
 		GasPricer::new_fixed(20_000_000_000u64.into()),
 		&spec,
@@ -28,7 +28,8 @@ use ethcore::block::ClosedBlock;
 use ethcore::header::BlockNumber;
 use ethcore::transaction::{UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use ethcore::receipt::{Receipt, RichReceipt};
-use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus};
+use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus, EvictionStats, BanList, InclusionFilter,
+	ExtraDataTemplate, ExtraDataError, ExtraDataVariables};
 use ethcore::account_provider::SignError as AccountError;
 
 /// Test miner service.
@@ -39,6 +40,8 @@ pub struct TestMinerService {
 	pub latest_closed_block: Mutex<Option<ClosedBlock>>,
 	/// Pre-existed pending transactions
 	pub pending_transactions: Mutex<HashMap<H256, SignedTransaction>>,
+	/// Transactions waiting for their conditions
+	pub scheduled_transactions: Mutex<Vec<PendingTransaction>>,
 	/// Pre-existed local transactions
 	pub local_transactions: Mutex<BTreeMap<H256, LocalTransactionStatus>>,
 	/// Pre-existed pending receipts
@@ -47,6 +50,16 @@ pub struct TestMinerService {
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
 	/// Password held by Engine.
 	pub password: RwLock<String>,
+	/// Memory limit of the queue.
+	pub memory_limit: RwLock<usize>,
+	/// Transactions count and memory limits of a single sender.
+	pub sender_limits: RwLock<(usize, usize)>,
+	/// Number of transactions dropped from the queue.
+	pub evictions: RwLock<EvictionStats>,
+	/// Currently banned senders, recipients and codes.
+	pub bans: RwLock<BanList>,
+	/// Allow and deny lists of authored blocks.
+	pub inclusion_filter: RwLock<InclusionFilter>,
 
 	min_gas_price: RwLock<U256>,
 	gas_range_target: RwLock<(U256, U256)>,
@@ -54,6 +67,7 @@ pub struct TestMinerService {
 	extra_data: RwLock<Bytes>,
 	limit: RwLock<usize>,
 	tx_gas_limit: RwLock<U256>,
+	automine: RwLock<bool>,
 }
 
 impl Default for TestMinerService {
@@ -62,6 +76,7 @@ impl Default for TestMinerService {
 			imported_transactions: Mutex::new(Vec::new()),
 			latest_closed_block: Mutex::new(None),
 			pending_transactions: Mutex::new(HashMap::new()),
+			scheduled_transactions: Mutex::new(Vec::new()),
 			local_transactions: Mutex::new(BTreeMap::new()),
 			pending_receipts: Mutex::new(BTreeMap::new()),
 			last_nonces: RwLock::new(HashMap::new()),
@@ -69,9 +84,15 @@ impl Default for TestMinerService {
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
 			author: RwLock::new(Address::zero()),
 			password: RwLock::new(String::new()),
+			memory_limit: RwLock::new(usize::max_value()),
+			sender_limits: RwLock::new((usize::max_value(), usize::max_value())),
+			evictions: RwLock::new(Default::default()),
+			bans: RwLock::new(Default::default()),
+			inclusion_filter: RwLock::new(Default::default()),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
 			limit: RwLock::new(1024),
 			tx_gas_limit: RwLock::new(!U256::zero()),
+			automine: RwLock::new(true),
 		}
 	}
 }
@@ -117,6 +138,17 @@ impl MinerService for TestMinerService {
 		*self.extra_data.write() = extra_data;
 	}
 
+	fn set_extra_data_template(&self, template: ExtraDataTemplate) -> Result<(), ExtraDataError> {
+		let variables = ExtraDataVariables {
+			version: "1.8.0",
+			chain: "test",
+			tag: "",
+			counter: 0,
+		};
+		*self.extra_data.write() = template.expand_checked(&variables, 32)?;
+		Ok(())
+	}
+
 	/// Set the lower gas limit we wish to target when sealing a new block.
 	fn set_gas_floor_target(&self, target: U256) {
 		self.gas_range_target.write().0 = target;
@@ -135,6 +167,38 @@ impl MinerService for TestMinerService {
 		*self.limit.write() = limit;
 	}
 
+	fn set_transactions_memory_limit(&self, limit: usize) {
+		*self.memory_limit.write() = limit;
+	}
+
+	fn set_sender_transactions_limit(&self, limit: usize) {
+		self.sender_limits.write().0 = limit;
+	}
+
+	fn set_sender_transactions_memory_limit(&self, limit: usize) {
+		self.sender_limits.write().1 = limit;
+	}
+
+	fn transactions_evictions(&self) -> EvictionStats {
+		*self.evictions.read()
+	}
+
+	fn transactions_bans(&self) -> BanList {
+		self.bans.read().clone()
+	}
+
+	fn clear_transactions_bans(&self) {
+		*self.bans.write() = Default::default();
+	}
+
+	fn inclusion_filter(&self) -> InclusionFilter {
+		self.inclusion_filter.read().clone()
+	}
+
+	fn set_inclusion_filter(&self, filter: InclusionFilter) {
+		*self.inclusion_filter.write() = filter;
+	}
+
 	fn set_tx_gas_limit(&self, limit: U256) {
 		*self.tx_gas_limit.write() = limit;
 	}
@@ -220,16 +284,35 @@ impl MinerService for TestMinerService {
 		unimplemented!();
 	}
 
+	fn seal_block_now(&self, _chain: &MiningBlockChainClient) -> Option<H256> {
+		None
+	}
+
+	fn set_automine(&self, enabled: bool) {
+		*self.automine.write() = enabled;
+	}
+
+	fn automine(&self) -> bool {
+		*self.automine.read()
+	}
+
 	fn map_sealing_work<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> Option<T> where F: FnOnce(&ClosedBlock) -> T {
-		let open_block = chain.prepare_open_block(self.author(), *self.gas_range_target.write(), self.extra_data());
+		let open_block = chain.prepare_open_block(self.author(), *self.gas_range_target.write(), self.extra_data(), &Default::default());
 		Some(f(&open_block.close()))
 	}
 
+	fn dry_run_block(&self, chain: &MiningBlockChainClient, author: Address, gas_limit: U256) -> ClosedBlock {
+		let mut open_block = chain.prepare_open_block(author, (gas_limit, gas_limit), self.extra_data(), &Default::default());
+		open_block.set_gas_limit(gas_limit);
+		open_block.close()
+	}
+
 	fn transaction(&self, _best_block: BlockNumber, hash: &H256) -> Option<PendingTransaction> {
 		self.pending_transactions.lock().get(hash).cloned().map(Into::into)
 	}
 
 	fn remove_pending_transaction(&self, _chain: &MiningBlockChainClient, hash: &H256) -> Option<PendingTransaction> {
+		self.scheduled_transactions.lock().retain(|tx| tx.hash() != *hash);
 		self.pending_transactions.lock().remove(hash).map(Into::into)
 	}
 
@@ -249,6 +332,10 @@ impl MinerService for TestMinerService {
 		vec![]
 	}
 
+	fn scheduled_transactions(&self, _best_block: BlockNumber, _best_timestamp: u64) -> Vec<PendingTransaction> {
+		self.scheduled_transactions.lock().clone()
+	}
+
 	fn pending_receipt(&self, _best_block: BlockNumber, hash: &H256) -> Option<RichReceipt> {
 		// Not much point implementing this since the logic is complex and the only thing it relies on is pending_receipts, which is already tested.
 		self.pending_receipts(0).get(hash).map(|r|
@@ -44,7 +44,10 @@ impl SnapshotService for TestSnapshotService {
 	fn manifest(&self) -> Option<ManifestData> { None }
 	fn supported_versions(&self) -> Option<(u64, u64)> { None }
 	fn chunk(&self, _hash: H256) -> Option<Bytes> { None }
+	fn chunk_size(&self, _hash: H256) -> Option<u64> { None }
+	fn chunk_range(&self, _hash: H256, _offset: u64, _len: u64) -> Option<Bytes> { None }
 	fn status(&self) -> RestorationStatus { self.status.lock().clone() }
+	fn completed_chunks(&self) -> Option<Vec<H256>> { None }
 	fn begin_restore(&self, _manifest: ManifestData) { }
 	fn abort_restore(&self) { }
 	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
@@ -19,7 +19,8 @@
 use std::collections::BTreeMap;
 use bigint::hash::H256;
 use parking_lot::RwLock;
-use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats};
+use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats, ForkPartition, ForkBranch};
+use ethsync::{ProtocolTrafficInfo, PeerRequestStats};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -54,6 +55,12 @@ impl TestSyncProvider {
 				num_snapshot_chunks: 0,
 				snapshot_chunks_done: 0,
 				last_imported_old_block_number: None,
+				headers_downloaded: 0,
+				bodies_downloaded: 0,
+				receipts_downloaded: 0,
+				blocks_eta: None,
+				snapshot_eta: None,
+				num_conflicting_peers: 0,
 			}),
 		}
 	}
@@ -79,10 +86,23 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/62".to_owned(), "eth/63".to_owned()],
     			remote_address: "127.0.0.1:7777".to_owned(),
 				local_address: "127.0.0.1:8888".to_owned(),
+				ping_ms: Some(15),
+				traffic: vec![ProtocolTrafficInfo {
+					protocol: "eth".to_owned(),
+					packets_sent: 2,
+					packets_received: 3,
+					bytes_sent: 100,
+					bytes_received: 300,
+				}],
 				eth_info: Some(EthProtocolInfo {
 					version: 62,
 					difficulty: Some(40.into()),
 					head: 50.into(),
+					requests: PeerRequestStats {
+						sent: 4,
+						failed: 1,
+						useful: 2,
+					},
 				}),
 				pip_info: None,
 			},
@@ -92,10 +112,13 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/63".to_owned(), "eth/64".to_owned()],
     			remote_address: "Handshake".to_owned(),
 				local_address: "127.0.0.1:3333".to_owned(),
+				ping_ms: None,
+				traffic: vec![],
 				eth_info: Some(EthProtocolInfo {
 					version: 64,
 					difficulty: None,
-					head: 60.into()
+					head: 60.into(),
+					requests: PeerRequestStats::default(),
 				}),
 				pip_info: None,
 			}
@@ -122,5 +145,27 @@ impl SyncProvider for TestSyncProvider {
 			}
 		]
 	}
+
+	fn fork_partition(&self) -> ForkPartition {
+		ForkPartition {
+			detected: false,
+			peers: 2,
+			conflicting_peers: 1,
+			branches: vec![
+				ForkBranch {
+					head: 50.into(),
+					difficulty: Some(40.into()),
+					peers: 1,
+					conflicting: false,
+				},
+				ForkBranch {
+					head: 60.into(),
+					difficulty: None,
+					peers: 1,
+					conflicting: true,
+				},
+			],
+		}
+	}
 }
 
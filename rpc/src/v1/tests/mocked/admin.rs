@@ -0,0 +1,168 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use ethsync::ManageNetwork;
+use jsonrpc_core::IoHandler;
+use v1::{Admin, AdminClient};
+use v1::tests::helpers::{Config, TestSyncProvider};
+use super::manage_network::TestManageNetwork;
+
+fn io() -> IoHandler {
+	let sync = Arc::new(TestSyncProvider::new(Config {
+		network_id: 3,
+		num_peers: 120,
+	}));
+	let net: Arc<ManageNetwork> = Arc::new(TestManageNetwork);
+	let mut io = IoHandler::new();
+	io.extend_with(AdminClient::new(&sync, &net).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_admin_add_reserved_peer() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_addReservedPeer", "params":["enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_remove_reserved_peer() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_removeReservedPeer", "params":["enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_disconnect_peer() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_disconnectPeer", "params":["a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_peers() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_peers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"latency":15,"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"latency":null,"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_peer_reputations() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_peerReputations", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"bannedFor":0,"bans":1,"id":"node1","score":-25}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_clear_peer_reputation() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_clearPeerReputation", "params":["node1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_clearPeerReputation", "params":[], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_clearPeerReputation", "params":["node2"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Peer","data":"\"Invalid node id\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_access_lists() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_accessLists", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"allow":["10.0.0.0/8"],"deny":[]},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_add_access_entry() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_addAccessEntry", "params":["deny", "192.168.0.0/16"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_addAccessEntry", "params":["allow", "10.0.0.0/8"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_addAccessEntry", "params":["allow", "invalid"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Access list entry","data":"\"Invalid node id or IP range\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_remove_access_entry() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_removeAccessEntry", "params":["allow", "10.0.0.0/8"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_set_access_lists() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_setAccessLists", "params":[{"allow":[],"deny":["10.0.0.0/8"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_setAccessLists", "params":[{"allow":["invalid"],"deny":[]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Access list entry","data":"\"Invalid node id or IP range\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_bandwidth_limits() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_bandwidthLimits", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"download":0,"peerDownload":0,"peerUpload":0,"upload":1048576},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_set_bandwidth_limits() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_setBandwidthLimits", "params":[{"upload":65536,"peerUpload":16384}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
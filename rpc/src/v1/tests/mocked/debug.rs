@@ -0,0 +1,101 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ethcore::executed::{Executed, CallError};
+use ethcore::trace::{StepTrace, VMStep};
+use ethcore::client::TestBlockChainClient;
+
+use jsonrpc_core::IoHandler;
+use v1::tests::helpers::TestMinerService;
+use v1::{Metadata, Debug, DebugClient};
+
+struct Tester {
+	client: Arc<TestBlockChainClient>,
+	io: IoHandler<Metadata>,
+}
+
+fn io() -> Tester {
+	let client = Arc::new(TestBlockChainClient::new());
+	*client.execution_result.write() = Some(Ok(Executed {
+		exception: None,
+		gas: 20_000.into(),
+		gas_used: 10_000.into(),
+		refunded: 0.into(),
+		cumulative_gas_used: 10_000.into(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![],
+		trace: vec![],
+		vm_trace: None,
+		step_trace: Some(StepTrace {
+			steps: vec![VMStep {
+				pc: 0,
+				instruction: 0x60,
+				gas: 0x100.into(),
+				gas_cost: 3.into(),
+				depth: 1,
+				stack: Some(vec![]),
+				memory: None,
+				storage: None,
+				truncated: false,
+			}],
+			truncated: false,
+		}),
+		state_diff: None,
+	}));
+	let miner = Arc::new(TestMinerService::default());
+	let debug = DebugClient::new(&client, &miner);
+	let mut io = IoHandler::default();
+	io.extend_with(debug.to_delegate());
+
+	Tester {
+		client: client,
+		io: io,
+	}
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", {"disableMemory":true}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"steps":[{"depth":1,"gas":"0x100","gasCost":"0x3","op":96,"pc":0,"stack":[],"truncated":false}],"truncated":false},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_unknown_option() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", {"disableEverything":true}],"id":1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(r#""code":-32602"#));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_state_pruned() {
+	let tester = io();
+	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -368,6 +368,24 @@ fn rpc_eth_gas_price() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_fee_history() {
+	let tester = EthTester::default();
+	tester.add_blocks(2, EachBlockWith::Transaction);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x5", "latest", [10, 90]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"baseFeePerGas":["0x0","0x0","0x0","0x0"],"gasUsedRatio":[0.0,0.0,0.0],"oldestBlock":"0x0","reward":[["0x0","0x0"],["0x2e90edd000","0x2e90edd000"],["0x2e90edd000","0x2e90edd000"]]},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x1", "0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"baseFeePerGas":["0x0","0x0"],"gasUsedRatio":[0.0],"oldestBlock":"0x1"},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x1", "latest", [90, 10]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: rewardPercentiles","data":"\"Expected increasing percentiles between 0 and 100.\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_accounts() {
 	let tester = EthTester::default();
@@ -614,6 +632,7 @@ fn rpc_eth_call_latest() {
 		output: vec![0x12, 0x34, 0xff],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 
@@ -636,6 +655,54 @@ fn rpc_eth_call_latest() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_cached() {
+	use v1::helpers::CallCache;
+
+	let executed = |output: Vec<u8>| Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output,
+		trace: vec![],
+		vm_trace: None,
+		step_trace: None,
+		state_diff: None,
+	};
+	let tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.call_cache = Some(Arc::new(CallCache::new(1024)));
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0x12345678"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+	tester.client.set_execution_result(Ok(executed(vec![0x12, 0x34, 0xff])));
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// served from cache
+	tester.client.set_execution_result(Ok(executed(vec![0x56])));
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// pending state is never cached
+	let request = request.replace("\"latest\"", "\"pending\"");
+	let response = r#"{"jsonrpc":"2.0","result":"0x56","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_call() {
 	let tester = EthTester::default();
@@ -650,6 +717,7 @@ fn rpc_eth_call() {
 		output: vec![0x12, 0x34, 0xff],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 
@@ -686,6 +754,7 @@ fn rpc_eth_call_default_block() {
 		output: vec![0x12, 0x34, 0xff],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 
@@ -721,6 +790,7 @@ fn rpc_eth_estimate_gas() {
 		output: vec![0x12, 0x34, 0xff],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 
@@ -757,6 +827,7 @@ fn rpc_eth_estimate_gas_default_block() {
 		output: vec![0x12, 0x34, 0xff],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 
@@ -947,6 +1018,40 @@ fn rpc_eth_send_raw_transaction_error() {
 	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
 }
 
+#[test]
+fn rpc_eth_send_raw_typed_transaction_error() {
+	let tester = EthTester::default();
+
+	let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendRawTransaction",
+		"params": [
+			"0x01cb80808080808080c0808080"
+		],
+		"id": 1
+	}"#;
+	let res = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"EIP-2930 access list transactions (type 0x01) are not supported."},"id":1}"#.into();
+
+	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
+}
+
+#[test]
+fn rpc_eth_send_raw_typed_transaction_invalid_payload() {
+	let tester = EthTester::default();
+
+	let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendRawTransaction",
+		"params": [
+			"0x01cb80808080808080c0028080"
+		],
+		"id": 1
+	}"#;
+	let res = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid signature y-parity 2 (expected 0 or 1)."},"id":1}"#.into();
+
+	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
+}
+
 #[test]
 fn rpc_eth_send_raw_transaction() {
 	let tester = EthTester::default();
@@ -1040,6 +1145,59 @@ fn rpc_eth_transaction_receipt_null() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_block_receipts() {
+	let receipt = |index: usize, hash: H256| LocalizedReceipt {
+		transaction_hash: hash,
+		transaction_index: index,
+		block_hash: H256::from(0x10),
+		block_number: 0x4510c,
+		cumulative_gas_used: U256::from(0x10 * (index + 1)),
+		gas_used: U256::from(0x10),
+		contract_address: None,
+		logs: vec![],
+		log_bloom: 0.into(),
+		state_root: None,
+	};
+
+	let tester = EthTester::default();
+	tester.client.set_transaction_receipt(TransactionId::Hash(2.into()), receipt(1, 2.into()));
+	tester.client.set_transaction_receipt(TransactionId::Hash(1.into()), receipt(0, 1.into()));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockReceipts",
+		"params": ["0x4510c"],
+		"id": 1
+	}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+	let by_hash = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockReceipts",
+		"params": ["0x0000000000000000000000000000000000000000000000000000000000000010"],
+		"id": 1
+	}"#;
+
+	assert!(response.contains(r#""cumulativeGasUsed":"0x10","gasUsed":"0x10","logs":[]"#));
+	assert!(response.find(r#""transactionIndex":"0x0""#) < response.find(r#""transactionIndex":"0x1""#));
+	assert_eq!(tester.io.handle_request_sync(by_hash), Some(response));
+}
+
+#[test]
+fn rpc_eth_block_receipts_null() {
+	let tester = EthTester::default();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockReceipts",
+		"params": ["0x4510c"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 // These tests are incorrect: their output is undefined as long as eth_getCompilers is [].
 // Will ignore for now, but should probably be replaced by more substantial tests which check
 // the output of eth_getCompilers to determine whether to test. CI systems can then be preinstalled
@@ -1115,6 +1273,20 @@ fn rpc_get_work_should_not_return_block_number() {
 	assert_eq!(eth_tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_get_work_should_return_block_number_after_progpow_transition() {
+	let eth_tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.send_block_number_in_get_work = false;
+		options.progpow_transition = Some(1);
+	}));
+	eth_tester.miner.set_author(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getWork", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x3bbe93f74e7b97ae00784aeff8819c5cb600dd87e8b282a5d3446f3f871f0347","0x0000000000000000000000000000000000000000000000000000000000000000","0x0000800000000000000000000000000000000000000000000000000000000000","0x1"],"id":1}"#;
+
+	assert_eq!(eth_tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_get_work_should_timeout() {
 	let eth_tester = EthTester::default();
@@ -78,6 +78,54 @@ fn should_subscribe_to_new_heads() {
 	assert_eq!(res, None);
 }
 
+#[test]
+fn should_subscribe_to_reorgs() {
+	// given
+	let el = EventLoop::spawn();
+	let mut client = TestBlockChainClient::new();
+	client.add_blocks(3, EachBlockWith::Nothing);
+	let h3 = client.block_hash_delta_minus(1);
+	let h2 = client.block_hash_delta_minus(2);
+
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["reorgs"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// No notification without retracted blocks
+	handler.new_blocks(vec![], vec![], vec![h2], vec![], vec![], vec![], 0);
+	// Reorganization replacing block 2
+	handler.new_blocks(vec![], vec![], vec![h3], vec![h2], vec![], vec![], 0);
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"commonAncestor":"0x3457d2fa2e3dd33c78ac681cf542e429becf718859053448748383af67e23218","depth":"0x1","enacted":["0xdf04a98bb0c6fa8441bd429822f65a46d0cb553f6bcef602b973e65c81497f8e"],"halted":false,"retracted":["0x44e5ecf454ea99af9d8a8f2ca0daba96964c90de05db7a78f59b84ae9e749706"]},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// Halted reorganization
+	handler.reorg_halted(vec![h3], vec![h2]);
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"commonAncestor":"0x3457d2fa2e3dd33c78ac681cf542e429becf718859053448748383af67e23218","depth":"0x1","enacted":["0xdf04a98bb0c6fa8441bd429822f65a46d0cb553f6bcef602b973e65c81497f8e"],"halted":true,"retracted":["0x44e5ecf454ea99af9d8a8f2ca0daba96964c90de05db7a78f59b84ae9e749706"]},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
 #[test]
 fn should_subscribe_to_logs() {
 	use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
@@ -150,6 +198,191 @@ fn should_subscribe_to_logs() {
 	assert_eq!(res, None);
 }
 
+fn pending_transaction() -> ::ethcore::transaction::SignedTransaction {
+	use ethcore::transaction::{Transaction, Action};
+
+	Transaction {
+		nonce: 1.into(),
+		gas_price: 0x9184e72a000u64.into(),
+		gas: 0x76c0.into(),
+		action: Action::Call(5.into()),
+		value: 0x9184e72au64.into(),
+		data: vec![],
+	}.fake_sign(2.into())
+}
+
+#[test]
+fn should_subscribe_to_pending_transactions() {
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	let transaction = pending_transaction();
+	handler.notify_new_transactions(&[transaction.clone()]);
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":""#.to_owned()
+		+ &format!("0x{:?}", transaction.hash())
+		+ r#"","subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
+#[test]
+fn should_subscribe_to_full_pending_transactions() {
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	handler.notify_new_transactions(&[pending_transaction()]);
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"blockHash":null,"blockNumber":null,"chainId":null,"condition":null,"creates":null,"from":"0x0000000000000000000000000000000000000002","gas":"0x76c0","gasPrice":"0x9184e72a000","hash":"0xa2e0da8a8064e0b9f93e95a53c2db6d01280efb8ac72a708d25487e67dd0f8fc","input":"0x","nonce":"0x1","publicKey":null,"r":"0x1","raw":"0xe9018609184e72a0008276c0940000000000000000000000000000000000000005849184e72a80800101","s":"0x1","standardV":"0x4","to":"0x0000000000000000000000000000000000000005","transactionIndex":null,"v":"0x0","value":"0x9184e72a"},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+}
+
+#[test]
+fn should_reject_invalid_pending_transactions_params() {
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, _receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newPendingTransactions","data":"\"Expected a boolean.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+}
+
+#[test]
+fn should_subscribe_to_pending_state() {
+	// given
+	let el = EventLoop::spawn();
+	let client = Arc::new(TestBlockChainClient::new());
+	client.set_balance(5.into(), 10.into());
+	let pubsub = EthPubSubClient::new_test(client.clone(), el.remote());
+	let handler = pubsub.handler();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["pendingState", ["0x0000000000000000000000000000000000000005", "0x0000000000000000000000000000000000000006"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications: first all watched accounts are reported...
+	handler.notify_pending_state();
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":[{"address":"0x0000000000000000000000000000000000000005","balance":"0xa","nonce":"0x0"},{"address":"0x0000000000000000000000000000000000000006","balance":"0x0","nonce":"0x0"}],"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// ...then only the changed ones.
+	handler.notify_pending_state();
+	client.set_nonce(6.into(), 1.into());
+	handler.notify_pending_state();
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":[{"address":"0x0000000000000000000000000000000000000006","balance":"0x0","nonce":"0x1"}],"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// Invalid parameters
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["pendingState"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: pendingState","data":"\"Expected an array of addresses.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+}
+
+#[test]
+fn should_subscribe_to_new_work() {
+	// given
+	let el = EventLoop::spawn();
+	let client = Arc::new(TestBlockChainClient::new());
+	let disabled = EthPubSubClient::new_test(client.clone(), el.remote()).to_delegate();
+	let pubsub = EthPubSubClient::new_test(client, el.remote());
+	let handler = pubsub.handler();
+	let _notifier = pubsub.work_notifier();
+	let pubsub = pubsub.to_delegate();
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Rejected without a work notifier
+	let mut io = MetaIoHandler::default();
+	io.extend_with(disabled);
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newWork"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Work notifications are disabled. Restart parity with `--notify-work-pubsub` to enable them."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newWork"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	handler.notify_work(5.into(), 2.into(), 1);
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":["0x0000000000000000000000000000000000000000000000000000000000000005","0x0000000000000000000000000000000000000000000000000000000000000000","0x8000000000000000000000000000000000000000000000000000000000000000","0x1"],"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// Invalid parameters
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newWork", true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newWork","data":"\"Expected no parameters.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+}
+
 #[test]
 fn should_return_unimplemented() {
 	// given
@@ -167,8 +400,6 @@ fn should_return_unimplemented() {
 
 	// Subscribe
 	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not implemented yet. Please create an issue on Github repo."},"id":1}"#;
-	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions"], "id": 1}"#;
-	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["syncing"], "id": 1}"#;
 	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
 }
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethsync::{ManageNetwork, NetworkConfiguration};
+use ethsync::{ManageNetwork, NetworkConfiguration, PeerReputation, PeerAccessLists, BandwidthLimits};
 
 pub struct TestManageNetwork;
 
@@ -24,7 +24,41 @@ impl ManageNetwork for TestManageNetwork {
 	fn deny_unreserved_peers(&self) { }
 	fn remove_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
 	fn add_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
+	fn disconnect_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		vec![PeerReputation { id: "node1".into(), score: -25, bans: 1, banned_for: 0 }]
+	}
+	fn clear_peer_reputation(&self, peer: Option<String>) -> Result<(), String> {
+		match peer {
+			Some(ref peer) if peer != "node1" => Err("Invalid node id".into()),
+			_ => Ok(()),
+		}
+	}
+	fn access_lists(&self) -> PeerAccessLists {
+		PeerAccessLists { allow: vec!["10.0.0.0/8".into()], deny: vec![] }
+	}
+	fn add_access_entry(&self, allow: bool, entry: String) -> Result<bool, String> {
+		match entry.as_str() {
+			"10.0.0.0/8" => Ok(!allow),
+			"invalid" => Err("Invalid node id or IP range".into()),
+			_ => Ok(true),
+		}
+	}
+	fn remove_access_entry(&self, allow: bool, entry: String) -> Result<bool, String> {
+		Ok(allow && entry == "10.0.0.0/8")
+	}
+	fn set_access_lists(&self, lists: PeerAccessLists) -> Result<(), String> {
+		match lists.allow.iter().chain(lists.deny.iter()).any(|e| e == "invalid") {
+			true => Err("Invalid node id or IP range".into()),
+			false => Ok(()),
+		}
+	}
+	fn bandwidth_limits(&self) -> BandwidthLimits {
+		BandwidthLimits { upload: 1024 * 1024, ..Default::default() }
+	}
+	fn set_bandwidth_limits(&self, _limits: BandwidthLimits) {}
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
+	fn external_port(&self) -> Option<u16> { None }
 }
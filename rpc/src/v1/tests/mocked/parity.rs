@@ -17,27 +17,49 @@
 use std::sync::Arc;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{TestBlockChainClient, Executed};
-use ethcore::miner::LocalTransactionStatus;
+use ethcore::miner::{LocalTransactionStatus, StratumWorkers, WorkerStats};
+use ethcore::transaction::PendingTransaction;
 use ethcore_logger::RotatingLogger;
 use ethstore::ethkey::{Generator, Random};
-use ethsync::ManageNetwork;
+use ethsync::{ManageNetwork, SyncState};
+use local_store::{self, TransactionJournal};
 use node_health::{self, NodeHealth};
 use parity_reactor;
+use parking_lot::Mutex;
 use util::Address;
 
 use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::{SignerService, NetworkSettings};
-use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
+use v1::tests::helpers::{TestSyncProvider, TestSnapshotService, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 
 pub type TestParityClient = ParityClient<TestBlockChainClient, TestMinerService, TestUpdater>;
 
+#[derive(Default)]
+pub struct TestJournal(Mutex<Vec<PendingTransaction>>);
+
+impl TransactionJournal for TestJournal {
+	fn journaled_transactions(&self) -> Result<Vec<PendingTransaction>, local_store::Error> {
+		Ok(self.0.lock().clone())
+	}
+}
+
+#[derive(Default)]
+pub struct TestStratum(Mutex<Vec<WorkerStats>>);
+
+impl StratumWorkers for TestStratum {
+	fn workers(&self) -> Vec<WorkerStats> {
+		self.0.lock().clone()
+	}
+}
+
 pub struct Dependencies {
 	pub miner: Arc<TestMinerService>,
 	pub client: Arc<TestBlockChainClient>,
 	pub sync: Arc<TestSyncProvider>,
+	pub snapshot: Arc<TestSnapshotService>,
 	pub updater: Arc<TestUpdater>,
 	pub health: NodeHealth,
 	pub logger: Arc<RotatingLogger>,
@@ -46,6 +68,8 @@ pub struct Dependencies {
 	pub accounts: Arc<AccountProvider>,
 	pub dapps_address: Option<(String, u16)>,
 	pub ws_address: Option<(String, u16)>,
+	pub journal: Arc<TestJournal>,
+	pub stratum: Arc<TestStratum>,
 }
 
 impl Dependencies {
@@ -60,8 +84,12 @@ impl Dependencies {
 			health: NodeHealth::new(
 				Arc::new(FakeSync),
 				node_health::TimeChecker::new::<String>(&[], node_health::CpuPool::new(1)),
+				node_health::CpuPool::new(1),
 				parity_reactor::Remote::new_sync(),
+				Default::default(),
+				None,
 			),
+			snapshot: Arc::new(TestSnapshotService::new()),
 			updater: Arc::new(TestUpdater::default()),
 			logger: Arc::new(RotatingLogger::new("rpc=trace".to_owned())),
 			settings: Arc::new(NetworkSettings {
@@ -76,6 +104,8 @@ impl Dependencies {
 			accounts: Arc::new(AccountProvider::transient_provider()),
 			dapps_address: Some(("127.0.0.1".into(), 18080)),
 			ws_address: Some(("127.0.0.1".into(), 18546)),
+			journal: Default::default(),
+			stratum: Default::default(),
 		}
 	}
 
@@ -86,6 +116,7 @@ impl Dependencies {
 			self.client.clone(),
 			self.miner.clone(),
 			self.sync.clone(),
+			self.snapshot.clone(),
 			self.updater.clone(),
 			self.network.clone(),
 			self.health.clone(),
@@ -95,6 +126,9 @@ impl Dependencies {
 			signer,
 			self.dapps_address.clone(),
 			self.ws_address.clone(),
+			Default::default(),
+			Some(self.journal.clone()),
+			Some(self.stratum.clone()),
 		)
 	}
 
@@ -116,6 +150,7 @@ struct FakeSync;
 impl node_health::SyncStatus for FakeSync {
 	fn is_major_importing(&self) -> bool { false }
 	fn peers(&self) -> (usize, usize) { (4, 25) }
+	fn block_lag(&self) -> Option<u64> { Some(0) }
 }
 
 #[test]
@@ -320,7 +355,7 @@ fn rpc_parity_net_peers() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_netPeers", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"latency":15,"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777","traffic":{"eth":{"bytesReceived":300,"bytesSent":100,"packetsReceived":3,"packetsSent":2}}},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","requests":{"failed":1,"sent":4,"useful":2,"usefulRatio":0.5},"version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"latency":null,"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake","traffic":{}},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","requests":{"failed":0,"sent":0,"useful":0,"usefulRatio":null},"version":64},"pip":null}}]},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -391,6 +426,73 @@ fn rpc_parity_pending_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_scheduled_transactions() {
+	use ethcore::transaction::{Transaction, Action, Condition};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let signed = Transaction {
+		nonce: 1.into(),
+		gas_price: 0x9184e72a000u64.into(),
+		gas: 0x76c0.into(),
+		action: Action::Call(5.into()),
+		value: 0x9184e72au64.into(),
+		data: vec![]
+	}.fake_sign(2.into());
+	deps.miner.scheduled_transactions.lock().push(PendingTransaction::new(signed, Some(Condition::Number(100))));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_scheduledTransactions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"blockHash":null,"blockNumber":null,"chainId":null,"condition":{"block":100},"creates":null,"from":"0x0000000000000000000000000000000000000002","gas":"0x76c0","gasPrice":"0x9184e72a000","hash":"0xa2e0da8a8064e0b9f93e95a53c2db6d01280efb8ac72a708d25487e67dd0f8fc","input":"0x","nonce":"0x1","publicKey":null,"r":"0x1","raw":"0xe9018609184e72a0008276c0940000000000000000000000000000000000000005849184e72a80800101","s":"0x1","standardV":"0x4","to":"0x0000000000000000000000000000000000000005","transactionIndex":null,"v":"0x0","value":"0x9184e72a"}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_dry_run_block() {
+	use serde_json::{self, Value};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dryRunBlock", "params":["0x2dc6c0", "0x0000000000000000000000000000000000000042"], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let result = &response["result"];
+
+	assert_eq!(result["number"], "0x1");
+	assert_eq!(result["author"], "0x0000000000000000000000000000000000000042");
+	assert_eq!(result["gasLimit"], "0x2dc6c0");
+	assert_eq!(result["gasUsed"], "0x0");
+	assert_eq!(result["transactions"], Value::Array(vec![]));
+}
+
+#[test]
+fn rpc_parity_nonce_gaps() {
+	use ethcore::transaction::{Transaction, Action};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let tx = |nonce: u64| Transaction {
+		nonce: nonce.into(),
+		gas_price: 0x9184e72a000u64.into(),
+		gas: 0x76c0.into(),
+		action: Action::Call(5.into()),
+		value: 0x9184e72au64.into(),
+		data: vec![]
+	};
+	let signed = tx(1).fake_sign(2.into());
+	let other = tx(5).fake_sign(3.into());
+	deps.miner.pending_transactions.lock().insert(signed.hash(), signed);
+	deps.miner.pending_transactions.lock().insert(other.hash(), other);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nonceGaps", "params":["0x0000000000000000000000000000000000000002"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"chainNonce":"0x0","gaps":[{"blocked":[{"blockHash":null,"blockNumber":null,"chainId":null,"condition":null,"creates":null,"from":"0x0000000000000000000000000000000000000002","gas":"0x76c0","gasPrice":"0x9184e72a000","hash":"0xa2e0da8a8064e0b9f93e95a53c2db6d01280efb8ac72a708d25487e67dd0f8fc","input":"0x","nonce":"0x1","publicKey":null,"r":"0x1","raw":"0xe9018609184e72a0008276c0940000000000000000000000000000000000000005849184e72a80800101","s":"0x1","standardV":"0x4","to":"0x0000000000000000000000000000000000000005","transactionIndex":null,"v":"0x0","value":"0x9184e72a"}],"from":"0x0","to":"0x0"}],"pendingNonces":["0x1"]},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_encrypt() {
 	let deps = Dependencies::new();
@@ -483,6 +585,71 @@ fn rpc_parity_local_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_journaled_transactions() {
+	use serde_json::{self, Value};
+	use ethcore::transaction::Transaction;
+	use v1::types::H256;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let tx = Transaction::default().sign(Random.generate().unwrap().secret(), None);
+	let hash = tx.hash();
+	deps.journal.0.lock().push(tx.into());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_journaledTransactions", "params":[], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let result = response["result"].as_array().unwrap();
+
+	assert_eq!(result.len(), 1);
+	assert_eq!(result[0]["hash"], serde_json::to_value(H256::from(hash)).unwrap());
+}
+
+#[test]
+fn rpc_parity_transactions_evictions() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.miner.evictions.write().memory_limit = 5;
+	deps.miner.evictions.write().sender_limit = 2;
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionsEvictions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"countLimit":0,"gasLimit":0,"memoryLimit":5,"senderLimit":2},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_transactions_bans() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.miner.bans.write().senders.push(Address::from(5));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionsBans", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"codes":[],"recipients":[],"senders":["0x0000000000000000000000000000000000000005"]},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_stratum_workers() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.stratum.0.lock().push(WorkerStats {
+		id: "miner1".into(),
+		addr: "127.0.0.1:4000".parse().unwrap(),
+		difficulty: Some(0x400.into()),
+		accepted: 10,
+		stale: 2,
+		invalid: 1,
+		blocks: 0,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stratumWorkers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"accepted":10,"address":"127.0.0.1:4000","blocks":0,"difficulty":"0x400","id":"miner1","invalid":1,"stale":2}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_chain_status() {
 	use bigint::prelude::U256;
@@ -495,7 +662,72 @@ fn rpc_parity_chain_status() {
 	*deps.client.first_block.write() = Some((H256::from(U256::from(1234)), 3333));
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_chainStatus", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":["0x6","0xd05"]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":["0x6","0xd05"],"warpRestoration":null},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_chain_status_warp_restoration() {
+	use ethcore::snapshot::RestorationStatus;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	deps.snapshot.set_status(RestorationStatus::Ongoing {
+		state_chunks: 40,
+		block_chunks: 20,
+		state_chunks_done: 12,
+		block_chunks_done: 0,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_chainStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":null,"warpRestoration":{"blockChunks":20,"blockChunksDone":0,"chunksReceived":0,"stateChunks":40,"stateChunksDone":12}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_import_queue() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	deps.client.set_queue_size(3);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_importQueue", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"full":true,"maxSize":0,"memBudget":18446744073709551615,"memUsed":0,"unverified":0,"verified":3,"verifying":0},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_sync_progress() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	{
+		let mut status = deps.sync.status.write();
+		status.state = SyncState::Blocks;
+		status.last_imported_block_number = Some(1000);
+		status.highest_block_number = Some(2500);
+		status.headers_downloaded = 1200;
+		status.bodies_downloaded = 1100;
+		status.blocks_eta = Some(60);
+	}
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_syncProgress", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blocksEta":60,"bodiesDownloaded":1100,"currentBlock":"0x3e8","headersDownloaded":1200,"highestBlock":"0x9c4","receiptsDownloaded":0,"stage":"blocks","startingBlock":"0x0","warpChunksAmount":0,"warpChunksDownloaded":0,"warpEta":null},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_fork_partition() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_forkPartition", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"branches":[{"conflicting":false,"head":"0x0000000000000000000000000000000000000000000000000000000000000032","peers":1,"totalDifficulty":"0x28"},{"conflicting":true,"head":"0x000000000000000000000000000000000000000000000000000000000000003c","peers":1,"totalDifficulty":null}],"conflictingPeers":1,"detected":false,"peers":2},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -538,6 +770,7 @@ fn rpc_parity_call() {
 		output: vec![0x12, 0x34, 0xff],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 	let io = deps.default_client();
@@ -561,13 +794,66 @@ fn rpc_parity_call() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_estimate_gas() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_estimateGas",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"value": "0x9184e72a"
+		}],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"execution":"0x0","gas":"0x5208","intrinsic":"0x5208","refund":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_block_state_diff() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockStateDiff", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_block_witness() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockWitness", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_bad_blocks() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_badBlocks", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_node_health() {
 	let deps = Dependencies::new();
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"details":[4,25],"message":"","status":"ok"},"sync":{"details":false,"message":"","status":"ok"},"time":{"details":0,"message":"","status":"ok"}},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"disk":{"details":null,"message":"","status":"ok"},"lag":{"details":0,"message":"","status":"ok"},"peers":{"details":[4,25],"message":"","status":"ok"},"responsiveness":{"details":0,"message":"","status":"ok"},"sync":{"details":false,"message":"","status":"ok"},"time":{"details":0,"message":"","status":"ok"}},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
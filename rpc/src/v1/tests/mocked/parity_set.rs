@@ -146,6 +146,28 @@ fn rpc_parity_set_extra_data() {
 	assert_eq!(miner.extra_data(), "cd1722f3947def4cf144679da39c4c32bdc35681".from_hex().unwrap());
 }
 
+#[test]
+fn rpc_parity_set_extra_data_template() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setExtraDataTemplate", "params":["{chain}/v{version}"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.extra_data(), b"test/v1.8.0".to_vec());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setExtraDataTemplate", "params":["{height}"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: template","data":"\"Unknown variable {height}. Expected one of: {version}, {chain}, {tag}, {counter}.\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_author() {
 	let miner = miner_service();
@@ -196,6 +218,64 @@ fn rpc_parity_set_transactions_limit() {
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
 
+#[test]
+fn rpc_parity_set_transactions_memory_limits() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsMemoryLimit", "params":[4194304], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setSenderTransactionsLimit", "params":[16], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setSenderTransactionsMemoryLimit", "params":[65536], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	assert_eq!(*miner.memory_limit.read(), 4_194_304);
+	assert_eq!(*miner.sender_limits.read(), (16, 65_536));
+}
+
+#[test]
+fn rpc_parity_clear_transactions_bans() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+	miner.bans.write().senders.push(5.into());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_clearTransactionsBans", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(miner.bans.read().senders.is_empty());
+}
+
+#[test]
+fn rpc_parity_set_inclusion_filter() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setInclusionFilter", "params":[{"deny":{"recipients":["0x0000000000000000000000000000000000000005"]}}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(miner.inclusion_filter.read().deny.recipients.contains(&Address::from(5)));
+	assert!(miner.inclusion_filter.read().allow.is_empty());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_inclusionFilter", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"allow":{"contracts":[],"recipients":[],"senders":[]},"deny":{"contracts":[],"recipients":["0x0000000000000000000000000000000000000005"],"senders":[]}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_hash_content() {
 	let miner = miner_service();
@@ -240,6 +320,34 @@ fn rpc_parity_remove_transaction() {
 	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_cancel_scheduled_transaction() {
+	use ethcore::transaction::{Transaction, Action, Condition, PendingTransaction};
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let scheduled = Transaction::default().fake_sign(2.into());
+	let ready = Transaction::default().fake_sign(3.into());
+	miner.pending_transactions.lock().insert(scheduled.hash(), scheduled.clone());
+	miner.pending_transactions.lock().insert(ready.hash(), ready.clone());
+	miner.scheduled_transactions.lock().push(PendingTransaction::new(scheduled.clone(), Some(Condition::Timestamp(100))));
+
+	let request = |hash| r#"{"jsonrpc": "2.0", "method": "parity_cancelScheduledTransaction", "params":[""#.to_owned() + &format!("0x{:?}", hash) + r#""], "id": 1}"#;
+	let canceled = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	let not_found = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(&request(ready.hash())), Some(not_found.to_owned()));
+	assert_eq!(io.handle_request_sync(&request(scheduled.hash())), Some(canceled.to_owned()));
+	assert_eq!(io.handle_request_sync(&request(scheduled.hash())), Some(not_found.to_owned()));
+	assert!(miner.scheduled_transactions.lock().is_empty());
+	assert_eq!(miner.pending_transactions.lock().len(), 1);
+}
+
 #[test]
 fn rpc_parity_set_dapps_list() {
 	let miner = miner_service();
@@ -254,3 +362,49 @@ fn rpc_parity_set_dapps_list() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_invalidate_block() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_invalidateBlock", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: hash","data":"\"Not supported\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_set_automine() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setAutomine", "params":[false], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(!miner.automine());
+}
+
+#[test]
+fn rpc_parity_mine_block() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_mineBlock", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The engine is not able to seal blocks on demand."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -63,6 +63,7 @@ fn io() -> Tester {
 		output: vec![1, 2, 3],
 		trace: vec![],
 		vm_trace: None,
+		step_trace: None,
 		state_diff: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
@@ -170,6 +171,26 @@ fn rpc_trace_call() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_call_pending() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_call","params":[{}, ["stateDiff", "trace"], "pending"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_call_unknown_trace_type() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_call","params":[{}, ["stateDiff", "memory"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: traceTypes","data":"\"Unknown trace type: memory, expected one of [\\\"trace\\\", \\\"vmTrace\\\", \\\"stateDiff\\\"]\""},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_multi_call() {
 	let tester = io();
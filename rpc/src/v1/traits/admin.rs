@@ -0,0 +1,78 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Admin rpc interface.
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+
+use v1::types::{PeerInfo, PeerReputation, AccessLists, AccessListKind, BandwidthLimits};
+
+build_rpc_trait! {
+	/// Admin rpc interface, used for runtime peer management.
+	pub trait Admin {
+		/// Add a reserved peer (enode URL).
+		#[rpc(name = "admin_addReservedPeer")]
+		fn add_reserved_peer(&self, String) -> Result<bool, Error>;
+
+		/// Remove a reserved peer (enode URL).
+		#[rpc(name = "admin_removeReservedPeer")]
+		fn remove_reserved_peer(&self, String) -> Result<bool, Error>;
+
+		/// Disconnect a peer given by enode URL or node id.
+		/// The peer may connect again later.
+		#[rpc(name = "admin_disconnectPeer")]
+		fn disconnect_peer(&self, String) -> Result<bool, Error>;
+
+		/// Returns detailed information about connected peers.
+		#[rpc(name = "admin_peers")]
+		fn peers(&self) -> Result<Vec<PeerInfo>, Error>;
+
+		/// Returns reputations of peers which misbehaved recently or are banned.
+		#[rpc(name = "admin_peerReputations")]
+		fn peer_reputations(&self) -> Result<Vec<PeerReputation>, Error>;
+
+		/// Forget the reputation of a peer given by enode URL or node id, lifting its ban.
+		/// Clears reputations of all peers if no peer is given.
+		#[rpc(name = "admin_clearPeerReputation")]
+		fn clear_peer_reputation(&self, Trailing<String>) -> Result<bool, Error>;
+
+		/// Returns the peer allow and deny lists.
+		#[rpc(name = "admin_accessLists")]
+		fn access_lists(&self) -> Result<AccessLists, Error>;
+
+		/// Add a node id, enode URL or IP range to the allow or deny list.
+		/// Returns `false` if it's already there. Applies to new connections.
+		#[rpc(name = "admin_addAccessEntry")]
+		fn add_access_entry(&self, AccessListKind, String) -> Result<bool, Error>;
+
+		/// Remove a node id, enode URL or IP range from the allow or deny list.
+		/// Returns `false` if it's not there.
+		#[rpc(name = "admin_removeAccessEntry")]
+		fn remove_access_entry(&self, AccessListKind, String) -> Result<bool, Error>;
+
+		/// Replace the allow and deny lists, e.g. with ones exported by `admin_accessLists`.
+		#[rpc(name = "admin_setAccessLists")]
+		fn set_access_lists(&self, AccessLists) -> Result<bool, Error>;
+
+		/// Returns the upload and download rate limits in bytes per second.
+		#[rpc(name = "admin_bandwidthLimits")]
+		fn bandwidth_limits(&self) -> Result<BandwidthLimits, Error>;
+
+		/// Change the upload and download rate limits. Applies to established connections too.
+		#[rpc(name = "admin_setBandwidthLimits")]
+		fn set_bandwidth_limits(&self, BandwidthLimits) -> Result<bool, Error>;
+	}
+}
@@ -0,0 +1,37 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug rpc interface.
+
+use jsonrpc_core::Error;
+use jsonrpc_core::futures::BoxFuture;
+use jsonrpc_macros::Trailing;
+use v1::types::{BlockNumber, CallRequest, H256, StepTrace, StepTraceOptions};
+
+build_rpc_trait! {
+	/// Debug rpc interface.
+	pub trait Debug {
+		type Metadata;
+
+		/// Replays the transaction with given hash and returns the executed operations.
+		#[rpc(name = "debug_traceTransaction")]
+		fn trace_transaction(&self, H256, Trailing<StepTraceOptions>) -> Result<StepTrace, Error>;
+
+		/// Executes the given call and returns the executed operations.
+		#[rpc(meta, name = "debug_traceCall")]
+		fn trace_call(&self, Self::Metadata, CallRequest, BlockNumber, Trailing<StepTraceOptions>) -> BoxFuture<StepTrace, Error>;
+	}
+}
@@ -20,8 +20,8 @@ use jsonrpc_macros::Trailing;
 
 use futures::BoxFuture;
 
-use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
+use v1::types::{RichBlock, BlockNumber, BlockNumberOrHash, Bytes, CallRequest, Filter, FilterChanges, Index};
+use v1::types::{FeeHistory, Log, Receipt, SyncStatus, Transaction, Work};
 use v1::types::{H64, H160, H256, U256};
 
 build_rpc_trait! {
@@ -53,6 +53,10 @@ build_rpc_trait! {
 		#[rpc(name = "eth_gasPrice")]
 		fn gas_price(&self) -> Result<U256, Error>;
 
+		/// Returns gas prices paid in given number of blocks up to given block.
+		#[rpc(name = "eth_feeHistory")]
+		fn fee_history(&self, U256, BlockNumber, Trailing<Vec<f64>>) -> Result<FeeHistory, Error>;
+
 		/// Returns accounts list.
 		#[rpc(meta, name = "eth_accounts")]
 		fn accounts(&self, Self::Metadata) -> BoxFuture<Vec<H160>, Error>;
@@ -133,6 +137,10 @@ build_rpc_trait! {
 		#[rpc(name = "eth_getTransactionReceipt")]
 		fn transaction_receipt(&self, H256) -> Result<Option<Receipt>, Error>;
 
+		/// Returns receipts of all transactions in given block.
+		#[rpc(name = "eth_getBlockReceipts")]
+		fn block_receipts(&self, BlockNumberOrHash) -> Result<Option<Vec<Receipt>>, Error>;
+
 		/// Returns an uncles at given block and index.
 		#[rpc(name = "eth_getUncleByBlockHashAndIndex")]
 		fn uncle_by_block_hash_and_index(&self, H256, Index) -> Result<Option<RichBlock>, Error>;
@@ -16,6 +16,8 @@
 
 //! Ethereum rpc interfaces.
 
+pub mod admin;
+pub mod debug;
 pub mod web3;
 pub mod eth;
 pub mod eth_pubsub;
@@ -26,12 +28,15 @@ pub mod parity_accounts;
 pub mod parity_set;
 pub mod parity_signing;
 pub mod personal;
+pub mod private;
 pub mod pubsub;
 pub mod signer;
 pub mod traces;
 pub mod rpc;
 pub mod secretstore;
 
+pub use self::admin::Admin;
+pub use self::debug::Debug;
 pub use self::web3::Web3;
 pub use self::eth::{Eth, EthFilter};
 pub use self::eth_pubsub::EthPubSub;
@@ -42,6 +47,7 @@ pub use self::parity_accounts::ParityAccounts;
 pub use self::parity_set::ParitySet;
 pub use self::parity_signing::ParitySigning;
 pub use self::personal::Personal;
+pub use self::private::Private;
 pub use self::pubsub::PubSub;
 pub use self::signer::Signer;
 pub use self::traces::Traces;
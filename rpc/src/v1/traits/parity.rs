@@ -25,11 +25,12 @@ use futures::BoxFuture;
 use node_health::Health;
 use v1::types::{
 	H160, H256, H512, U256, Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, Transaction, RpcSettings, Histogram, NonceGaps, DryRunBlock,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader,
+	OperationsInfo, DappId, ChainStatus, ImportQueue, SyncProgress, ForkPartition,
+	AccountInfo, HwAccountInfo, RichHeader, StateDiff, BadBlock, GasEstimate, StratumWorker,
+	TransactionEvictions, TransactionBans,
 };
 
 build_rpc_trait! {
@@ -144,14 +145,43 @@ build_rpc_trait! {
 		#[rpc(name = "parity_futureTransactions")]
 		fn future_transactions(&self) -> Result<Vec<Transaction>, Error>;
 
+		/// Returns transactions from transaction queue which block or time conditions are not yet met.
+		#[rpc(name = "parity_scheduledTransactions")]
+		fn scheduled_transactions(&self) -> Result<Vec<Transaction>, Error>;
+
+		/// Returns nonce gaps of sender's transactions in the queue together with transactions blocked by them.
+		#[rpc(name = "parity_nonceGaps")]
+		fn nonce_gaps(&self, H160) -> Result<NonceGaps, Error>;
+
+		/// Assembles a block on top of the best block from the queued transactions using given gas limit
+		/// and beneficiary. The block is not sealed and sealing work is left untouched.
+		#[rpc(name = "parity_dryRunBlock")]
+		fn dry_run_block(&self, U256, H160) -> Result<DryRunBlock, Error>;
+
 		/// Returns propagation statistics on transactions pending in the queue.
 		#[rpc(name = "parity_pendingTransactionsStats")]
 		fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>, Error>;
 
+		/// Returns number of transactions dropped from the queue because of its limits.
+		#[rpc(name = "parity_transactionsEvictions")]
+		fn transactions_evictions(&self) -> Result<TransactionEvictions, Error>;
+
+		/// Returns senders, recipients and codes currently banned by the transaction queue.
+		#[rpc(name = "parity_transactionsBans")]
+		fn transactions_bans(&self) -> Result<TransactionBans, Error>;
+
 		/// Returns a list of current and past local transactions with status details.
 		#[rpc(name = "parity_localTransactions")]
 		fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error>;
 
+		/// Returns local transactions persisted by the node which are not mined yet.
+		#[rpc(name = "parity_journaledTransactions")]
+		fn journaled_transactions(&self) -> Result<Vec<Transaction>, Error>;
+
+		/// Returns share statistics of the connected stratum workers.
+		#[rpc(name = "parity_stratumWorkers")]
+		fn stratum_workers(&self) -> Result<Vec<StratumWorker>, Error>;
+
 		/// Returns current Dapps Server interface and port or an error if dapps server is disabled.
 		#[rpc(name = "parity_dappsUrl")]
 		fn dapps_url(&self) -> Result<String, Error>;
@@ -192,6 +222,19 @@ build_rpc_trait! {
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus, Error>;
 
+		/// Get the occupancy of the block import queue.
+		#[rpc(name = "parity_importQueue")]
+		fn import_queue(&self) -> Result<ImportQueue, Error>;
+
+		/// Get detailed sync progress.
+		/// Updates can be streamed with `parity_subscribe("parity_syncProgress", [])`.
+		#[rpc(name = "parity_syncProgress")]
+		fn sync_progress(&self) -> Result<SyncProgress, Error>;
+
+		/// Get the distribution of peers across chain branches and whether the network appears partitioned.
+		#[rpc(name = "parity_forkPartition")]
+		fn fork_partition(&self) -> Result<ForkPartition, Error>;
+
 		/// Get node kind info.
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind, Error>;
@@ -209,6 +252,23 @@ build_rpc_trait! {
 		#[rpc(meta, name = "parity_call")]
 		fn call(&self, Self::Metadata, Vec<CallRequest>, Trailing<BlockNumber>) -> BoxFuture<Vec<Bytes>, Error>;
 
+		/// Estimate gas needed for execution of given contract, along with what the gas is spent on.
+		#[rpc(meta, name = "parity_estimateGas")]
+		fn estimate_gas(&self, Self::Metadata, CallRequest, Trailing<BlockNumber>) -> BoxFuture<GasEstimate, Error>;
+
+		/// Re-executes given block, returning the aggregate state diff of its transactions and rewards.
+		#[rpc(name = "parity_getBlockStateDiff")]
+		fn block_state_diff(&self, BlockNumber) -> Result<StateDiff, Error>;
+
+		/// Re-executes given block, returning all state trie nodes and code it accessed.
+		/// Along with the parent state root, these suffice to re-execute the block without the state.
+		#[rpc(name = "parity_getBlockWitness")]
+		fn block_witness(&self, BlockNumber) -> Result<Vec<Bytes>, Error>;
+
+		/// Returns most recently rejected blocks with the reason of the rejection, newest first.
+		#[rpc(name = "parity_badBlocks")]
+		fn bad_blocks(&self) -> Result<Vec<BadBlock>, Error>;
+
 		/// Returns node's health report.
 		#[rpc(async, name = "parity_nodeHealth")]
 		fn node_health(&self) -> BoxFuture<Health, Error>;
@@ -154,6 +154,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_changeVaultPassword")]
 		fn change_vault_password(&self, String, String) -> Result<bool, Error>;
 
+		/// Re-encrypt all keys in the vault under a fresh key and new password.
+		/// Vault is left untouched if the operation fails.
+		#[rpc(name = "parity_rotateVaultKey")]
+		fn rotate_vault_key(&self, String, String, String) -> Result<bool, Error>;
+
 		/// Change vault of the given address.
 		#[rpc(name = "parity_changeVault")]
 		fn change_vault(&self, H160, String) -> Result<bool, Error>;
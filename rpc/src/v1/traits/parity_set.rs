@@ -19,7 +19,7 @@
 use jsonrpc_core::Error;
 use futures::BoxFuture;
 
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, InclusionFilter};
 
 build_rpc_trait! {
 	/// Parity-specific rpc interface for operations altering the settings.
@@ -40,6 +40,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setExtraData")]
 		fn set_extra_data(&self, Bytes) -> Result<bool, Error>;
 
+		/// Sets extra data template for mined blocks (variables: `{version}`, `{chain}`, `{tag}`, `{counter}`).
+		#[rpc(name = "parity_setExtraDataTemplate")]
+		fn set_extra_data_template(&self, String) -> Result<bool, Error>;
+
 		/// Sets new author for mined block.
 		#[rpc(name = "parity_setAuthor")]
 		fn set_author(&self, H160) -> Result<bool, Error>;
@@ -52,6 +56,30 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setTransactionsLimit")]
 		fn set_transactions_limit(&self, usize) -> Result<bool, Error>;
 
+		/// Sets the memory limit (in bytes) for transaction queue.
+		#[rpc(name = "parity_setTransactionsMemoryLimit")]
+		fn set_transactions_memory_limit(&self, usize) -> Result<bool, Error>;
+
+		/// Sets the maximal number of transactions of a single sender in transaction queue.
+		#[rpc(name = "parity_setSenderTransactionsLimit")]
+		fn set_sender_transactions_limit(&self, usize) -> Result<bool, Error>;
+
+		/// Sets the memory limit (in bytes) for transactions of a single sender in transaction queue.
+		#[rpc(name = "parity_setSenderTransactionsMemoryLimit")]
+		fn set_sender_transactions_memory_limit(&self, usize) -> Result<bool, Error>;
+
+		/// Lifts all bans of senders, recipients and codes in transaction queue.
+		#[rpc(name = "parity_clearTransactionsBans")]
+		fn clear_transactions_bans(&self) -> Result<bool, Error>;
+
+		/// Returns allow and deny lists of transactions included in authored blocks.
+		#[rpc(name = "parity_inclusionFilter")]
+		fn inclusion_filter(&self) -> Result<InclusionFilter, Error>;
+
+		/// Sets allow and deny lists of transactions included in authored blocks.
+		#[rpc(name = "parity_setInclusionFilter")]
+		fn set_inclusion_filter(&self, InclusionFilter) -> Result<bool, Error>;
+
 		/// Sets the maximum amount of gas a single transaction may consume.
 		#[rpc(name = "parity_setMaxTransactionGas")]
 		fn set_tx_gas_limit(&self, U256) -> Result<bool, Error>;
@@ -120,5 +148,36 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>, Error>;
+
+		/// Cancels a transaction which block or time condition is not yet met.
+		/// Returns `true` when transaction was canceled, `false` if no such transaction is scheduled.
+		#[rpc(name = "parity_cancelScheduledTransaction")]
+		fn cancel_scheduled_transaction(&self, H256) -> Result<bool, Error>;
+
+		/// Marks given block as invalid, rolling back the chain head if the block is canonical,
+		/// and restarts sync. Returns the number of retracted blocks.
+		#[rpc(name = "parity_invalidateBlock")]
+		fn invalidate_block(&self, H256) -> Result<usize, Error>;
+
+		/// Reloads parameters of the running chain from given JSON chain spec.
+		/// Changes have to affect only future blocks, e.g. new transitions or builtins activated later.
+		/// Supported by development (instant seal) and PoA chains.
+		#[rpc(name = "parity_reloadChainSpec")]
+		fn reload_chain_spec(&self, String) -> Result<bool, Error>;
+
+		/// Resumes block import halted by a reorganization deeper than `--max-reorg-depth`,
+		/// switching to the branch with given head.
+		#[rpc(name = "parity_acceptReorg")]
+		fn accept_reorg(&self, H256) -> Result<bool, Error>;
+
+		/// Seals a new block right away, even if it's empty, and returns its hash.
+		/// Only supported by engines sealing internally (e.g. `--chain dev`).
+		#[rpc(name = "parity_mineBlock")]
+		fn mine_block(&self) -> Result<H256, Error>;
+
+		/// Enables or disables sealing blocks as soon as transactions arrive.
+		/// With automine disabled blocks are only sealed on demand or by `--seal-interval`.
+		#[rpc(name = "parity_setAutomine")]
+		fn set_automine(&self, bool) -> Result<bool, Error>;
 	}
 }
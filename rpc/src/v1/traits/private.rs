@@ -0,0 +1,44 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SecretStore-backed private transactions rpc interface.
+
+use jsonrpc_core::Error;
+
+use v1::types::{Bytes, PrivateTransactionReceipt, H160, H256, U256, BlockNumber,
+	PrivateTransactionReceiptAndTransaction, CallRequest};
+
+build_rpc_trait! {
+	/// Private transaction management RPC interface.
+	pub trait Private {
+		/// Sends signed transaction calling a private contract to its validators.
+		#[rpc(name = "private_sendTransaction")]
+		fn send_transaction(&self, Bytes) -> Result<PrivateTransactionReceipt, Error>;
+
+		/// Creates the public transaction deploying a private contract
+		/// from a signed contract creation transaction and the list of validators.
+		#[rpc(name = "private_composeDeploymentTransaction")]
+		fn compose_deployment_transaction(&self, BlockNumber, Bytes, Vec<H160>, U256) -> Result<PrivateTransactionReceiptAndTransaction, Error>;
+
+		/// Makes a call to a private contract.
+		#[rpc(name = "private_call")]
+		fn private_call(&self, BlockNumber, CallRequest) -> Result<Bytes, Error>;
+
+		/// Retrieves the id of the key associated with a private contract.
+		#[rpc(name = "private_contractKey")]
+		fn private_contract_key(&self, H160) -> Result<H256, Error>;
+	}
+}
@@ -0,0 +1,68 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rejected block.
+
+use ethcore::client;
+use v1::types::{Bytes, H256};
+
+/// Block which failed to import.
+#[derive(Debug, Serialize)]
+pub struct BadBlock {
+	/// Block hash.
+	pub hash: H256,
+	/// Block RLP.
+	pub rlp: Bytes,
+	/// Why the block was rejected.
+	pub reason: String,
+	/// Peer the block was received from.
+	pub origin: Option<String>,
+	/// Unix timestamp of the rejection.
+	pub timestamp: u64,
+}
+
+impl From<client::BadBlock> for BadBlock {
+	fn from(b: client::BadBlock) -> Self {
+		BadBlock {
+			hash: b.hash.into(),
+			rlp: b.rlp.into(),
+			reason: b.reason,
+			origin: b.origin,
+			timestamp: b.timestamp,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::client;
+	use super::BadBlock;
+
+	#[test]
+	fn should_serialize_bad_block() {
+		let block: BadBlock = client::BadBlock {
+			hash: 5.into(),
+			rlp: vec![0xc0],
+			reason: "Stage 5 block verification failed".into(),
+			origin: None,
+			timestamp: 1_500_000_000,
+		}.into();
+
+		let serialized = serde_json::to_string(&block).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","rlp":"0xc0","reason":"Stage 5 block verification failed","origin":null,"timestamp":1500000000}"#);
+	}
+}
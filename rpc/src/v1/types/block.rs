@@ -24,7 +24,7 @@ use serde::ser::Error;
 use v1::types::{Bytes, Transaction, H160, H256, H2048, U256};
 
 /// Block Transactions
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BlockTransactions {
 	/// Only hashes
 	Hashes(Vec<H256>),
@@ -43,7 +43,7 @@ impl Serialize for BlockTransactions {
 }
 
 /// Block representation
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Block {
 	/// Hash of the block
 	pub hash: Option<H256>,
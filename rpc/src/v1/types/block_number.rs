@@ -18,6 +18,7 @@ use std::fmt;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Visitor};
 use ethcore::client::BlockId;
+use v1::types::H256;
 
 /// Represents rpc api block number param.
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
@@ -104,6 +105,54 @@ impl Into<BlockId> for BlockNumber {
 	}
 }
 
+/// Represents rpc api param identifying a block either by hash or by number.
+#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+pub enum BlockNumberOrHash {
+	/// Block hash
+	Hash(H256),
+	/// Block number
+	Number(BlockNumber),
+}
+
+impl<'a> Deserialize<'a> for BlockNumberOrHash {
+	fn deserialize<D>(deserializer: D) -> Result<BlockNumberOrHash, D::Error> where D: Deserializer<'a> {
+		deserializer.deserialize_any(BlockNumberOrHashVisitor)
+	}
+}
+
+struct BlockNumberOrHashVisitor;
+
+impl<'a> Visitor<'a> for BlockNumberOrHashVisitor {
+	type Value = BlockNumberOrHash;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a block hash, a block number or 'latest', 'earliest' or 'pending'")
+	}
+
+	fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: Error {
+		if value.starts_with("0x") && value.len() == 66 {
+			value[2..].parse::<H256>().map(BlockNumberOrHash::Hash).map_err(|e| {
+				Error::custom(format!("Invalid block hash: {:?}", e))
+			})
+		} else {
+			BlockNumberVisitor.visit_str(value).map(BlockNumberOrHash::Number)
+		}
+	}
+
+	fn visit_string<E>(self, value: String) -> Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
+impl Into<BlockId> for BlockNumberOrHash {
+	fn into(self) -> BlockId {
+		match self {
+			BlockNumberOrHash::Hash(hash) => BlockId::Hash(hash.into()),
+			BlockNumberOrHash::Number(number) => number.into(),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use ethcore::client::BlockId;
@@ -117,6 +166,17 @@ mod tests {
 		assert_eq!(deserialized, vec![BlockNumber::Num(10), BlockNumber::Num(10), BlockNumber::Latest, BlockNumber::Earliest, BlockNumber::Pending])
 	}
 
+	#[test]
+	fn block_number_or_hash_deserialization() {
+		let s = r#"["0x0000000000000000000000000000000000000000000000000000000000000001", "0xa", "latest"]"#;
+		let deserialized: Vec<BlockNumberOrHash> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, vec![
+			BlockNumberOrHash::Hash(1.into()),
+			BlockNumberOrHash::Number(BlockNumber::Num(10)),
+			BlockNumberOrHash::Number(BlockNumber::Latest),
+		]);
+	}
+
 	#[test]
 	fn block_number_into() {
 		assert_eq!(BlockId::Number(100), BlockNumber::Num(100).into());
@@ -0,0 +1,43 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Block assembled from the transaction queue without sealing.
+
+use v1::types::{H160, H256, U256, Transaction};
+
+/// Block which would be built on top of the best block.
+#[derive(Debug, Serialize)]
+pub struct DryRunBlock {
+	/// Number of the block.
+	pub number: U256,
+	/// Hash of the parent block.
+	#[serde(rename="parentHash")]
+	pub parent_hash: H256,
+	/// Beneficiary of the block.
+	pub author: H160,
+	/// Gas limit of the block.
+	#[serde(rename="gasLimit")]
+	pub gas_limit: U256,
+	/// Gas used by the included transactions.
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// State root after executing the block (including block rewards).
+	#[serde(rename="stateRoot")]
+	pub state_root: H256,
+	/// Included transactions in order of execution.
+	pub transactions: Vec<Transaction>,
+}
@@ -0,0 +1,64 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas estimate breakdown.
+
+use ethcore::client;
+use v1::types::U256;
+
+/// Gas required by a transaction.
+#[derive(Debug, Serialize)]
+pub struct GasEstimate {
+	/// Lowest sufficient gas limit.
+	pub gas: U256,
+	/// Gas charged before execution.
+	pub intrinsic: U256,
+	/// Gas consumed by the execution, before refunds.
+	pub execution: U256,
+	/// Gas refunded after the execution.
+	pub refund: U256,
+}
+
+impl From<client::GasEstimate> for GasEstimate {
+	fn from(e: client::GasEstimate) -> Self {
+		GasEstimate {
+			gas: e.gas.into(),
+			intrinsic: e.intrinsic.into(),
+			execution: e.execution.into(),
+			refund: e.refund.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::client;
+	use super::GasEstimate;
+
+	#[test]
+	fn should_serialize_gas_estimate() {
+		let estimate: GasEstimate = client::GasEstimate {
+			gas: 26_000.into(),
+			intrinsic: 21_000.into(),
+			execution: 5_000.into(),
+			refund: 0.into(),
+		}.into();
+
+		let serialized = serde_json::to_string(&estimate).unwrap();
+		assert_eq!(serialized, r#"{"gas":"0x6590","intrinsic":"0x5208","execution":"0x1388","refund":"0x0"}"#);
+	}
+}
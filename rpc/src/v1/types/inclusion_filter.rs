@@ -0,0 +1,105 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block inclusion filter.
+
+use std::collections::HashSet;
+
+use ethcore::miner::{AddressList as MinerAddressList, InclusionFilter as MinerInclusionFilter};
+use util::Address;
+use v1::types::H160;
+
+/// Senders, recipients and called contracts a transaction is matched against.
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AddressList {
+	/// Transaction senders
+	pub senders: Vec<H160>,
+	/// Transaction recipients (both accounts and contracts)
+	pub recipients: Vec<H160>,
+	/// Called contracts
+	pub contracts: Vec<H160>,
+}
+
+fn sorted(addresses: HashSet<Address>) -> Vec<H160> {
+	let mut addresses: Vec<_> = addresses.into_iter().collect();
+	addresses.sort();
+	addresses.into_iter().map(Into::into).collect()
+}
+
+impl From<MinerAddressList> for AddressList {
+	fn from(list: MinerAddressList) -> Self {
+		AddressList {
+			senders: sorted(list.senders),
+			recipients: sorted(list.recipients),
+			contracts: sorted(list.contracts),
+		}
+	}
+}
+
+impl Into<MinerAddressList> for AddressList {
+	fn into(self) -> MinerAddressList {
+		MinerAddressList {
+			senders: self.senders.into_iter().map(Into::into).collect(),
+			recipients: self.recipients.into_iter().map(Into::into).collect(),
+			contracts: self.contracts.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Allow and deny lists of transactions included in authored blocks
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InclusionFilter {
+	/// If a list is not empty, only matching transactions are included
+	pub allow: AddressList,
+	/// Matching transactions are never included
+	pub deny: AddressList,
+}
+
+impl From<MinerInclusionFilter> for InclusionFilter {
+	fn from(filter: MinerInclusionFilter) -> Self {
+		InclusionFilter {
+			allow: filter.allow.into(),
+			deny: filter.deny.into(),
+		}
+	}
+}
+
+impl Into<MinerInclusionFilter> for InclusionFilter {
+	fn into(self) -> MinerInclusionFilter {
+		MinerInclusionFilter {
+			allow: self.allow.into(),
+			deny: self.deny.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::InclusionFilter;
+
+	#[test]
+	fn should_deserialize_partial_filter() {
+		let s = r#"{"deny":{"senders":["0x0000000000000000000000000000000000000005"]}}"#;
+		let filter: InclusionFilter = serde_json::from_str(s).unwrap();
+
+		assert_eq!(filter.deny.senders, vec![5.into()]);
+		assert!(filter.deny.recipients.is_empty());
+		assert_eq!(filter.allow, Default::default());
+	}
+}
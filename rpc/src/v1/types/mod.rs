@@ -18,6 +18,7 @@
 //! RPC types
 
 mod account_info;
+mod bad_block;
 mod block;
 mod block_number;
 mod bytes;
@@ -26,30 +27,40 @@ mod confirmations;
 mod consensus_status;
 mod dapps;
 mod derivation;
+mod dry_run_block;
+mod fee_history;
 mod filter;
+mod gas_estimate;
 mod hash;
 mod histogram;
+mod inclusion_filter;
 mod index;
 mod log;
 mod node_kind;
+mod nonce_gaps;
+mod private_receipt;
 mod provenance;
 mod receipt;
 mod rpc_settings;
+mod stratum_worker;
 mod sync;
 mod trace;
 mod trace_filter;
 mod transaction;
 mod transaction_request;
+mod transaction_bans;
 mod transaction_condition;
+mod transaction_evictions;
 mod uint;
 mod work;
 
 pub mod pubsub;
 
 pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo};
+pub use self::bad_block::BadBlock;
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
-pub use self::block_number::BlockNumber;
+pub use self::block_number::{BlockNumber, BlockNumberOrHash};
 pub use self::call_request::CallRequest;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
@@ -58,24 +69,34 @@ pub use self::confirmations::{
 pub use self::consensus_status::*;
 pub use self::dapps::LocalDapp;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::dry_run_block::DryRunBlock;
+pub use self::fee_history::FeeHistory;
 pub use self::filter::{Filter, FilterChanges};
+pub use self::gas_estimate::GasEstimate;
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
+pub use self::inclusion_filter::{InclusionFilter, AddressList};
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::nonce_gaps::{NonceGaps, NonceGap};
+pub use self::private_receipt::{PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction};
 pub use self::provenance::{Origin, DappId};
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
+pub use self::stratum_worker::StratumWorker;
 pub use self::sync::{
-	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, ProtocolTraffic, PeerProtocolsInfo, PeerRequests, PeerReputation, AccessLists, AccessListKind, BandwidthLimits,
+	TransactionStats, ChainStatus, RestorationProgress, ImportQueue, SyncProgress, EthProtocolInfo, PipProtocolInfo,
+	ForkPartition, ForkBranch,
 };
-pub use self::trace::{LocalizedTrace, TraceResults};
+pub use self::trace::{LocalizedTrace, TraceResults, StepTrace, StepTraceOptions, StateDiff};
 pub use self::trace_filter::TraceFilter;
 pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
 pub use self::transaction_request::TransactionRequest;
+pub use self::transaction_bans::TransactionBans;
 pub use self::transaction_condition::TransactionCondition;
+pub use self::transaction_evictions::TransactionEvictions;
 pub use self::uint::{U128, U256};
 pub use self::work::Work;
 
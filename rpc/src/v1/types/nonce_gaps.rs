@@ -0,0 +1,124 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity. If not, see <http://www.gnu.org/licenses/>.
+
+//! Nonce gaps of a sender's queued transactions.
+
+use bigint::prelude::U256 as EthU256;
+use v1::types::{Transaction, U256};
+
+/// Range of nonces missing from the queue.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct NonceGap {
+	/// First missing nonce.
+	pub from: U256,
+	/// Last missing nonce (inclusive).
+	pub to: U256,
+	/// Queued transactions that can't be mined until the gap is filled.
+	pub blocked: Vec<Transaction>,
+}
+
+/// Nonce status of a sender's transactions in the queue.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct NonceGaps {
+	/// Nonce of the sender in the latest block.
+	#[serde(rename="chainNonce")]
+	pub chain_nonce: U256,
+	/// Nonces of the sender's transactions present in the queue (sorted).
+	#[serde(rename="pendingNonces")]
+	pub pending_nonces: Vec<U256>,
+	/// Detected gaps.
+	pub gaps: Vec<NonceGap>,
+}
+
+impl NonceGaps {
+	/// Detects nonce gaps given the chain nonce and all queued transactions of the sender.
+	pub fn new(chain_nonce: EthU256, mut transactions: Vec<Transaction>) -> Self {
+		transactions.sort_by_key(|tx| Into::<EthU256>::into(tx.nonce));
+
+		let mut pending_nonces: Vec<U256> = transactions.iter().map(|tx| tx.nonce).collect();
+		pending_nonces.dedup();
+
+		let mut gaps: Vec<NonceGap> = Vec::new();
+		let mut expected = chain_nonce;
+		for tx in transactions {
+			let nonce: EthU256 = tx.nonce.into();
+			if nonce < expected {
+				continue;
+			}
+			if nonce > expected {
+				gaps.push(NonceGap {
+					from: expected.into(),
+					to: (nonce - EthU256::one()).into(),
+					blocked: Vec::new(),
+				});
+			}
+			if let Some(gap) = gaps.last_mut() {
+				gap.blocked.push(tx);
+			}
+			expected = nonce + EthU256::one();
+		}
+
+		NonceGaps {
+			chain_nonce: chain_nonce.into(),
+			pending_nonces: pending_nonces,
+			gaps: gaps,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::Transaction;
+	use super::NonceGaps;
+
+	fn tx(nonce: u64) -> Transaction {
+		Transaction {
+			nonce: nonce.into(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn should_report_no_gaps_for_contiguous_nonces() {
+		let gaps = NonceGaps::new(5.into(), vec![tx(6), tx(5), tx(7)]);
+		assert_eq!(gaps.pending_nonces, vec![5.into(), 6.into(), 7.into()]);
+		assert!(gaps.gaps.is_empty());
+	}
+
+	#[test]
+	fn should_detect_gaps_and_blocked_transactions() {
+		let gaps = NonceGaps::new(5.into(), vec![tx(10), tx(3), tx(8), tx(5), tx(7)]);
+
+		assert_eq!(gaps.gaps.len(), 2);
+		assert_eq!(gaps.gaps[0].from, 6.into());
+		assert_eq!(gaps.gaps[0].to, 6.into());
+		assert_eq!(gaps.gaps[0].blocked, vec![tx(7), tx(8)]);
+		assert_eq!(gaps.gaps[1].from, 9.into());
+		assert_eq!(gaps.gaps[1].to, 9.into());
+		assert_eq!(gaps.gaps[1].blocked, vec![tx(10)]);
+	}
+
+	#[test]
+	fn should_detect_gap_at_chain_nonce() {
+		let gaps = NonceGaps::new(0.into(), vec![tx(2)]);
+		let serialized = serde_json::to_string(&gaps).unwrap();
+
+		assert_eq!(gaps.gaps[0].from, 0.into());
+		assert_eq!(gaps.gaps[0].to, 1.into());
+		assert!(serialized.starts_with(r#"{"chainNonce":"0x0","pendingNonces":["0x2"],"gaps":[{"from":"0x0","to":"0x1","blocked":[{"#));
+	}
+}
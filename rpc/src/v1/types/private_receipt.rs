@@ -0,0 +1,69 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions receipts.
+
+use ethcore_private_tx::Receipt as EthPrivateReceipt;
+use v1::types::{H160, H256, TransactionRequest};
+
+/// Receipt of a private transaction sent for validation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrivateTransactionReceipt {
+	/// Hash of the original transaction.
+	#[serde(rename="transactionHash")]
+	pub transaction_hash: H256,
+	/// Private contract address.
+	#[serde(rename="contractAddress")]
+	pub contract_address: H160,
+	/// Status of the transaction.
+	pub status: String,
+}
+
+impl From<EthPrivateReceipt> for PrivateTransactionReceipt {
+	fn from(r: EthPrivateReceipt) -> Self {
+		PrivateTransactionReceipt {
+			transaction_hash: r.hash.into(),
+			contract_address: r.contract_address.into(),
+			status: "sent for validation".into(),
+		}
+	}
+}
+
+/// Public transaction deploying a private contract.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrivateTransactionReceiptAndTransaction {
+	/// Receipt of the deployment.
+	pub receipt: PrivateTransactionReceipt,
+	/// Public transaction to sign and send.
+	pub transaction: TransactionRequest,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::PrivateTransactionReceipt;
+
+	#[test]
+	fn receipt_serialization() {
+		let receipt = PrivateTransactionReceipt {
+			transaction_hash: 1.into(),
+			contract_address: 2.into(),
+			status: "sent for validation".into(),
+		};
+		let serialized = serde_json::to_string(&receipt).unwrap();
+		assert_eq!(serialized, r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","contractAddress":"0x0000000000000000000000000000000000000002","status":"sent for validation"}"#);
+	}
+}
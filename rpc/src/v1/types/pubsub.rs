@@ -19,15 +19,52 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
-use v1::types::{RichHeader, Filter, Log};
+use v1::types::{RichHeader, Filter, Log, Transaction, Work, H160, H256, U256};
 
 /// Subscription result.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Result {
 	/// New block header.
 	Header(RichHeader),
 	/// Logs
 	Logs(Vec<Log>),
+	/// Transaction hash
+	TransactionHash(H256),
+	/// Full transaction
+	FullTransaction(Transaction),
+	/// Chain reorganization
+	Reorg(Reorg),
+	/// Changed pending state of watched accounts
+	PendingState(Vec<PendingStateChange>),
+	/// New work package
+	Work(Work),
+}
+
+/// Pending state of a watched account that changed since the last notification.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PendingStateChange {
+	/// Account address.
+	pub address: H160,
+	/// Balance in the pending block.
+	pub balance: U256,
+	/// Nonce in the pending block.
+	pub nonce: U256,
+}
+
+/// Chain reorganization details.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reorg {
+	/// Last block shared by the old and the new chain.
+	#[serde(rename="commonAncestor")]
+	pub common_ancestor: H256,
+	/// Number of retracted blocks.
+	pub depth: U256,
+	/// Hashes of blocks removed from the canonical chain.
+	pub retracted: Vec<H256>,
+	/// Hashes of blocks added to the canonical chain.
+	pub enacted: Vec<H256>,
+	/// Reorganization exceeds the maximal depth and block import is halted until it's accepted.
+	pub halted: bool,
 }
 
 impl Serialize for Result {
@@ -37,6 +74,11 @@ impl Serialize for Result {
 		match *self {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Logs(ref logs) => logs.serialize(serializer),
+			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::FullTransaction(ref tx) => tx.serialize(serializer),
+			Result::Reorg(ref reorg) => reorg.serialize(serializer),
+			Result::PendingState(ref changes) => changes.serialize(serializer),
+			Result::Work(ref work) => work.serialize(serializer),
 		}
 	}
 }
@@ -57,6 +99,15 @@ pub enum Kind {
 	/// Node syncing status subscription.
 	#[serde(rename="syncing")]
 	Syncing,
+	/// Chain reorganizations subscription.
+	#[serde(rename="reorgs")]
+	Reorgs,
+	/// Pending state of watched accounts subscription.
+	#[serde(rename="pendingState")]
+	PendingState,
+	/// New work packages subscription.
+	#[serde(rename="newWork")]
+	NewWork,
 }
 
 /// Subscription kind.
@@ -66,6 +117,11 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// New pending transactions parameters:
+	/// `true` to receive full transaction objects instead of hashes.
+	Transactions(bool),
+	/// Pending state parameters: addresses of watched accounts.
+	Addresses(Vec<H160>),
 }
 
 impl Default for Params {
@@ -83,6 +139,15 @@ impl<'a> Deserialize<'a> for Params {
 			return Ok(Params::None);
 		}
 
+		if let Some(full) = v.as_bool() {
+			return Ok(Params::Transactions(full));
+		}
+
+		if v.is_array() {
+			return from_value(v).map(Params::Addresses)
+				.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)));
+		}
+
 		from_value(v.clone()).map(Params::Logs)
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
@@ -101,6 +166,9 @@ mod tests {
 		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
 		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
 		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
+		assert_eq!(serde_json::from_str::<Kind>(r#""reorgs""#).unwrap(), Kind::Reorgs);
+		assert_eq!(serde_json::from_str::<Kind>(r#""pendingState""#).unwrap(), Kind::PendingState);
+		assert_eq!(serde_json::from_str::<Kind>(r#""newWork""#).unwrap(), Kind::NewWork);
 	}
 
 	#[test]
@@ -138,6 +206,21 @@ mod tests {
 		}));
 	}
 
+	#[test]
+	fn should_deserialize_transactions_params() {
+		assert_eq!(serde_json::from_str::<Params>(r#"true"#).unwrap(), Params::Transactions(true));
+		assert_eq!(serde_json::from_str::<Params>(r#"false"#).unwrap(), Params::Transactions(false));
+	}
+
+	#[test]
+	fn should_deserialize_addresses_params() {
+		assert_eq!(
+			serde_json::from_str::<Params>(r#"["0x0000000000000000000000000000000000000005"]"#).unwrap(),
+			Params::Addresses(vec![5.into()])
+		);
+		assert!(serde_json::from_str::<Params>(r#"["0x05"]"#).is_err());
+	}
+
 	#[test]
 	fn should_serialize_header() {
 		let header = Result::Header(RichHeader {
@@ -18,7 +18,7 @@ use v1::types::{Log, H160, H256, H2048, U256};
 use ethcore::receipt::{Receipt as EthReceipt, RichReceipt, LocalizedReceipt};
 
 /// Receipt
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Receipt {
 	/// Transaction Hash
 	#[serde(rename="transactionHash")]
@@ -0,0 +1,54 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Stratum worker statistics.
+
+use ethcore::miner::WorkerStats;
+use v1::types::U256;
+
+/// Shares submitted by a single stratum worker.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StratumWorker {
+	/// Worker name used for authorization.
+	pub id: String,
+	/// Remote address of the worker.
+	pub address: String,
+	/// Current share difficulty (`None` if variable difficulty is disabled).
+	pub difficulty: Option<U256>,
+	/// Number of accepted shares.
+	pub accepted: u64,
+	/// Number of shares for outdated jobs.
+	pub stale: u64,
+	/// Number of invalid shares.
+	pub invalid: u64,
+	/// Number of shares which sealed a block.
+	pub blocks: u64,
+}
+
+impl From<WorkerStats> for StratumWorker {
+	fn from(stats: WorkerStats) -> Self {
+		StratumWorker {
+			id: stats.id,
+			address: stats.addr.to_string(),
+			difficulty: stats.difficulty.map(Into::into),
+			accepted: stats.accepted,
+			stale: stats.stale,
+			invalid: stats.invalid,
+			blocks: stats.blocks,
+		}
+	}
+}
@@ -15,9 +15,12 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
-use ethsync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use ethcore::client::BlockQueueInfo;
+use ethsync::{self, PeerInfo as SyncPeerInfo, PeerReputation as SyncPeerReputation, PeerAccessLists, TransactionStats as SyncTransactionStats};
+use ethsync::{BandwidthLimits as SyncBandwidthLimits, ProtocolTrafficInfo as SyncProtocolTrafficInfo, PeerRequestStats};
+use ethsync::{SyncStatus as EthSyncStatus, SyncState, ForkPartition as SyncForkPartition, ForkBranch as SyncForkBranch};
 use serde::{Serialize, Serializer};
-use v1::types::{U256, H512};
+use v1::types::{U256, H256, H512};
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -76,6 +79,38 @@ pub struct PeerNetworkInfo {
 	/// Local endpoint address
 	#[serde(rename="localAddress")]
 	pub local_address: String,
+	/// Ping delay in milliseconds
+	pub latency: Option<u64>,
+	/// Traffic exchanged with the peer per subprotocol
+	pub traffic: BTreeMap<String, ProtocolTraffic>,
+}
+
+/// Subprotocol traffic exchanged with a peer
+#[derive(Default, Debug, Serialize)]
+pub struct ProtocolTraffic {
+	/// Number of packets sent
+	#[serde(rename="packetsSent")]
+	pub packets_sent: u64,
+	/// Number of packets received
+	#[serde(rename="packetsReceived")]
+	pub packets_received: u64,
+	/// Number of payload bytes sent
+	#[serde(rename="bytesSent")]
+	pub bytes_sent: u64,
+	/// Number of payload bytes received
+	#[serde(rename="bytesReceived")]
+	pub bytes_received: u64,
+}
+
+impl From<SyncProtocolTrafficInfo> for ProtocolTraffic {
+	fn from(t: SyncProtocolTrafficInfo) -> Self {
+		ProtocolTraffic {
+			packets_sent: t.packets_sent,
+			packets_received: t.packets_received,
+			bytes_sent: t.bytes_sent,
+			bytes_received: t.bytes_received,
+		}
+	}
 }
 
 /// Peer protocols information
@@ -96,6 +131,8 @@ pub struct EthProtocolInfo {
 	pub difficulty: Option<U256>,
 	/// SHA3 of peer best block hash
 	pub head: String,
+	/// Outcome of the requests sent to the peer
+	pub requests: PeerRequests,
 }
 
 impl From<ethsync::EthProtocolInfo> for EthProtocolInfo {
@@ -104,6 +141,35 @@ impl From<ethsync::EthProtocolInfo> for EthProtocolInfo {
 			version: info.version,
 			difficulty: info.difficulty.map(Into::into),
 			head: info.head.hex(),
+			requests: info.requests.into(),
+		}
+	}
+}
+
+/// Outcome of the sync requests sent to a peer
+#[derive(Default, Debug, Serialize)]
+pub struct PeerRequests {
+	/// Number of requests sent
+	pub sent: u64,
+	/// Number of requests answered with invalid or useless data
+	pub failed: u64,
+	/// Number of requests answered with data that was used
+	pub useful: u64,
+	/// Share of the sent requests answered with data that was used
+	#[serde(rename="usefulRatio")]
+	pub useful_ratio: Option<f64>,
+}
+
+impl From<PeerRequestStats> for PeerRequests {
+	fn from(stats: PeerRequestStats) -> Self {
+		PeerRequests {
+			sent: stats.sent,
+			failed: stats.failed,
+			useful: stats.useful,
+			useful_ratio: match stats.sent {
+				0 => None,
+				sent => Some(stats.useful as f64 / sent as f64),
+			},
 		}
 	}
 }
@@ -168,6 +234,8 @@ impl From<SyncPeerInfo> for PeerInfo {
 			network: PeerNetworkInfo {
 				remote_address: p.remote_address,
 				local_address: p.local_address,
+				latency: p.ping_ms,
+				traffic: p.traffic.into_iter().map(|t| (t.protocol.clone(), t.into())).collect(),
 			},
 			protocols: PeerProtocolsInfo {
 				eth: p.eth_info.map(Into::into),
@@ -177,6 +245,107 @@ impl From<SyncPeerInfo> for PeerInfo {
 	}
 }
 
+/// Reputation of a peer which misbehaved recently
+#[derive(Default, Debug, Serialize)]
+pub struct PeerReputation {
+	/// Public node id
+	pub id: String,
+	/// Reputation score, the peer gets banned once it drops to -100
+	pub score: i64,
+	/// Number of times the peer has been banned
+	pub bans: u32,
+	/// Seconds until the current ban expires
+	#[serde(rename="bannedFor")]
+	pub banned_for: u64,
+}
+
+impl From<SyncPeerReputation> for PeerReputation {
+	fn from(r: SyncPeerReputation) -> Self {
+		PeerReputation {
+			id: r.id,
+			score: r.score,
+			bans: r.bans,
+			banned_for: r.banned_for,
+		}
+	}
+}
+
+/// Kind of peer access list
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum AccessListKind {
+	/// Allow list: if not empty, only listed peers may connect
+	#[serde(rename="allow")]
+	Allow,
+	/// Deny list: listed peers may never connect
+	#[serde(rename="deny")]
+	Deny,
+}
+
+/// Peer allow and deny lists of node ids and IP ranges
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccessLists {
+	/// If not empty, only these nodes and IP ranges may connect
+	pub allow: Vec<String>,
+	/// These nodes and IP ranges may never connect
+	pub deny: Vec<String>,
+}
+
+impl From<PeerAccessLists> for AccessLists {
+	fn from(lists: PeerAccessLists) -> Self {
+		AccessLists {
+			allow: lists.allow,
+			deny: lists.deny,
+		}
+	}
+}
+
+impl Into<PeerAccessLists> for AccessLists {
+	fn into(self) -> PeerAccessLists {
+		PeerAccessLists {
+			allow: self.allow,
+			deny: self.deny,
+		}
+	}
+}
+
+/// Upload and download rate limits in bytes per second, 0 for no limit
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandwidthLimits {
+	/// Total upload rate
+	pub upload: u64,
+	/// Total download rate
+	pub download: u64,
+	/// Upload rate to a single peer
+	#[serde(rename="peerUpload")]
+	pub peer_upload: u64,
+	/// Download rate from a single peer
+	#[serde(rename="peerDownload")]
+	pub peer_download: u64,
+}
+
+impl From<SyncBandwidthLimits> for BandwidthLimits {
+	fn from(limits: SyncBandwidthLimits) -> Self {
+		BandwidthLimits {
+			upload: limits.upload,
+			download: limits.download,
+			peer_upload: limits.peer_upload,
+			peer_download: limits.peer_download,
+		}
+	}
+}
+
+impl Into<SyncBandwidthLimits> for BandwidthLimits {
+	fn into(self) -> SyncBandwidthLimits {
+		SyncBandwidthLimits {
+			upload: self.upload,
+			download: self.download,
+			peer_upload: self.peer_upload,
+			peer_download: self.peer_download,
+		}
+	}
+}
+
 impl From<SyncTransactionStats> for TransactionStats {
 	fn from(s: SyncTransactionStats) -> Self {
 		TransactionStats {
@@ -195,13 +364,189 @@ pub struct ChainStatus {
 	/// Describes the gap in the blockchain, if there is one: (first, last)
 	#[serde(rename="blockGap")]
 	pub block_gap: Option<(U256, U256)>,
+	/// Progress of the ongoing warp restoration, if there is one.
+	#[serde(rename="warpRestoration")]
+	pub warp_restoration: Option<RestorationProgress>,
+}
+
+/// Chunk-level progress of a warp restoration.
+#[derive(Default, Debug, Serialize)]
+pub struct RestorationProgress {
+	/// Total number of state chunks.
+	#[serde(rename="stateChunks")]
+	pub state_chunks: u32,
+	/// Total number of block chunks.
+	#[serde(rename="blockChunks")]
+	pub block_chunks: u32,
+	/// Number of state chunks restored.
+	#[serde(rename="stateChunksDone")]
+	pub state_chunks_done: u32,
+	/// Number of block chunks restored.
+	#[serde(rename="blockChunksDone")]
+	pub block_chunks_done: u32,
+	/// Number of chunks received, including those not restored yet.
+	#[serde(rename="chunksReceived")]
+	pub chunks_received: u32,
+}
+
+/// Detailed progress of the sync.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct SyncProgress {
+	/// Current sync stage.
+	pub stage: String,
+	/// Starting block
+	#[serde(rename="startingBlock")]
+	pub starting_block: U256,
+	/// Current block
+	#[serde(rename="currentBlock")]
+	pub current_block: U256,
+	/// Highest block seen so far
+	#[serde(rename="highestBlock")]
+	pub highest_block: U256,
+	/// Number of block headers downloaded.
+	#[serde(rename="headersDownloaded")]
+	pub headers_downloaded: u64,
+	/// Number of block bodies downloaded.
+	#[serde(rename="bodiesDownloaded")]
+	pub bodies_downloaded: u64,
+	/// Number of block receipts downloaded.
+	#[serde(rename="receiptsDownloaded")]
+	pub receipts_downloaded: u64,
+	/// Warp sync snapshot chunks total.
+	#[serde(rename="warpChunksAmount")]
+	pub warp_chunks_amount: u64,
+	/// Warp sync snapshot chunks downloaded.
+	#[serde(rename="warpChunksDownloaded")]
+	pub warp_chunks_downloaded: u64,
+	/// Estimated seconds until all blocks are downloaded.
+	#[serde(rename="blocksEta")]
+	pub blocks_eta: Option<u64>,
+	/// Estimated seconds until all snapshot chunks are downloaded.
+	#[serde(rename="warpEta")]
+	pub warp_eta: Option<u64>,
+}
+
+impl From<EthSyncStatus> for SyncProgress {
+	fn from(s: EthSyncStatus) -> Self {
+		let stage = match s.state {
+			SyncState::WaitingPeers => "waitingPeers",
+			SyncState::SnapshotManifest => "warpManifest",
+			SyncState::SnapshotData => "warpData",
+			SyncState::SnapshotWaiting => "warpWaiting",
+			SyncState::Blocks => "blocks",
+			SyncState::Idle => "idle",
+			SyncState::Waiting => "waitingQueue",
+			SyncState::NewBlocks => "newBlocks",
+		};
+		let current_block = s.last_imported_block_number.unwrap_or(s.start_block_number);
+
+		SyncProgress {
+			stage: stage.into(),
+			starting_block: s.start_block_number.into(),
+			current_block: current_block.into(),
+			highest_block: s.highest_block_number.unwrap_or(current_block).into(),
+			headers_downloaded: s.headers_downloaded,
+			bodies_downloaded: s.bodies_downloaded,
+			receipts_downloaded: s.receipts_downloaded,
+			warp_chunks_amount: s.num_snapshot_chunks as u64,
+			warp_chunks_downloaded: s.snapshot_chunks_done as u64,
+			blocks_eta: s.blocks_eta,
+			warp_eta: s.snapshot_eta,
+		}
+	}
+}
+
+/// Peers reporting the same best block.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct ForkBranch {
+	/// Best block hash
+	pub head: H256,
+	/// Total difficulty, if known
+	#[serde(rename="totalDifficulty")]
+	pub total_difficulty: Option<U256>,
+	/// Number of peers
+	pub peers: usize,
+	/// Whether the branch conflicts with the local chain
+	pub conflicting: bool,
+}
+
+impl From<SyncForkBranch> for ForkBranch {
+	fn from(b: SyncForkBranch) -> Self {
+		ForkBranch {
+			head: b.head.into(),
+			total_difficulty: b.difficulty.map(Into::into),
+			peers: b.peers,
+			conflicting: b.conflicting,
+		}
+	}
+}
+
+/// Distribution of peers across chain branches.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct ForkPartition {
+	/// Whether a significant share of peers follows conflicting branches
+	pub detected: bool,
+	/// Number of peers taken into account
+	pub peers: usize,
+	/// Number of peers following conflicting branches
+	#[serde(rename="conflictingPeers")]
+	pub conflicting_peers: usize,
+	/// Branches ordered by number of peers
+	pub branches: Vec<ForkBranch>,
+}
+
+impl From<SyncForkPartition> for ForkPartition {
+	fn from(p: SyncForkPartition) -> Self {
+		ForkPartition {
+			detected: p.detected,
+			peers: p.peers,
+			conflicting_peers: p.conflicting_peers,
+			branches: p.branches.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Occupancy of the block import queue.
+#[derive(Default, Debug, Serialize)]
+pub struct ImportQueue {
+	/// Number of blocks pending verification.
+	pub unverified: usize,
+	/// Number of blocks being verified.
+	pub verifying: usize,
+	/// Number of verified blocks pending import.
+	pub verified: usize,
+	/// Maximum number of queued blocks.
+	#[serde(rename="maxSize")]
+	pub max_size: usize,
+	/// Memory used by queued blocks in bytes.
+	#[serde(rename="memUsed")]
+	pub mem_used: usize,
+	/// Memory budget of the queue in bytes. Sync stops downloading block bodies once it's reached.
+	#[serde(rename="memBudget")]
+	pub mem_budget: usize,
+	/// Whether the queue is full.
+	pub full: bool,
+}
+
+impl From<BlockQueueInfo> for ImportQueue {
+	fn from(info: BlockQueueInfo) -> Self {
+		ImportQueue {
+			unverified: info.unverified_queue_size,
+			verifying: info.verifying_queue_size,
+			verified: info.verified_queue_size,
+			max_size: info.max_queue_size,
+			mem_used: info.mem_used,
+			mem_budget: info.max_mem_use,
+			full: info.is_full(),
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, RestorationProgress, ImportQueue, SyncProgress, AccessLists, AccessListKind};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -232,12 +577,80 @@ mod tests {
 	fn test_serialize_block_gap() {
 		let mut t = ChainStatus::default();
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"blockGap":null}"#);
+		assert_eq!(serialized, r#"{"blockGap":null,"warpRestoration":null}"#);
 
 		t.block_gap = Some((1.into(), 5.into()));
 
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
+		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"],"warpRestoration":null}"#);
+	}
+
+	#[test]
+	fn test_serialize_warp_restoration() {
+		let t = ChainStatus {
+			block_gap: None,
+			warp_restoration: Some(RestorationProgress {
+				state_chunks: 10,
+				block_chunks: 5,
+				state_chunks_done: 4,
+				block_chunks_done: 0,
+				chunks_received: 6,
+			}),
+		};
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"blockGap":null,"warpRestoration":{"stateChunks":10,"blockChunks":5,"stateChunksDone":4,"blockChunksDone":0,"chunksReceived":6}}"#);
+	}
+
+	#[test]
+	fn test_serialize_sync_progress() {
+		let t = SyncProgress {
+			stage: "blocks".into(),
+			starting_block: 1.into(),
+			current_block: 10.into(),
+			highest_block: 100.into(),
+			headers_downloaded: 20,
+			bodies_downloaded: 12,
+			receipts_downloaded: 0,
+			warp_chunks_amount: 0,
+			warp_chunks_downloaded: 0,
+			blocks_eta: Some(30),
+			warp_eta: None,
+		};
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"stage":"blocks","startingBlock":"0x1","currentBlock":"0xa","highestBlock":"0x64","headersDownloaded":20,"bodiesDownloaded":12,"receiptsDownloaded":0,"warpChunksAmount":0,"warpChunksDownloaded":0,"blocksEta":30,"warpEta":null}"#);
+	}
+
+	#[test]
+	fn test_serialize_access_lists() {
+		let lists = AccessLists {
+			allow: vec!["10.0.0.0/8".into()],
+			deny: vec![],
+		};
+		let serialized = serde_json::to_string(&lists).unwrap();
+		assert_eq!(serialized, r#"{"allow":["10.0.0.0/8"],"deny":[]}"#);
+		assert_eq!(serde_json::from_str::<AccessLists>(&serialized).unwrap(), lists);
+
+		assert_eq!(serde_json::from_str::<AccessListKind>(r#""allow""#).unwrap(), AccessListKind::Allow);
+		assert_eq!(serde_json::from_str::<AccessListKind>(r#""deny""#).unwrap(), AccessListKind::Deny);
+		assert!(serde_json::from_str::<AccessListKind>(r#""other""#).is_err());
+	}
+
+	#[test]
+	fn test_serialize_import_queue() {
+		let t = ImportQueue {
+			unverified: 10,
+			verifying: 2,
+			verified: 3,
+			max_size: 30000,
+			mem_used: 4096,
+			mem_budget: 1024,
+			full: true,
+		};
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"unverified":10,"verifying":2,"verified":3,"maxSize":30000,"memUsed":4096,"memBudget":1024,"full":true}"#);
 	}
 
 	#[test]
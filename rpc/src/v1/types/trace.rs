@@ -21,7 +21,7 @@ use ethcore::trace::{FlatTrace, LocalizedTrace as EthLocalizedTrace, trace, Trac
 use ethcore::trace as et;
 use ethcore::state_diff;
 use ethcore::account_diff;
-use ethcore::client::Executed;
+use ethcore::client::{Executed, StepTracing};
 use vm;
 use v1::types::{Bytes, H160, H256, U256};
 
@@ -611,6 +611,9 @@ pub struct TraceResults {
 	/// The transaction trace.
 	#[serde(rename="stateDiff")]
 	pub state_diff: Option<StateDiff>,
+	/// The execution steps, if requested.
+	#[serde(rename="stepTrace", skip_serializing_if = "Option::is_none")]
+	pub step_trace: Option<StepTrace>,
 }
 
 impl From<Executed> for TraceResults {
@@ -620,6 +623,110 @@ impl From<Executed> for TraceResults {
 			trace: t.trace.into_iter().map(Into::into).collect(),
 			vm_trace: t.vm_trace.map(Into::into),
 			state_diff: t.state_diff.map(Into::into),
+			step_trace: t.step_trace.map(Into::into),
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+/// A snapshot of the VM taken before executing an operation.
+pub struct VMStep {
+	/// The program counter.
+	pub pc: usize,
+	/// The instruction.
+	pub op: u8,
+	/// The gas available.
+	pub gas: U256,
+	/// The gas cost of the operation.
+	#[serde(rename="gasCost")]
+	pub gas_cost: U256,
+	/// The call depth.
+	pub depth: usize,
+	/// Topmost stack items, top last.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// The memory.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Bytes>,
+	/// Storage written by the current call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<Vec<StorageDiff>>,
+	/// Whether any of the snapshots was truncated.
+	pub truncated: bool,
+}
+
+impl From<et::VMStep> for VMStep {
+	fn from(s: et::VMStep) -> Self {
+		VMStep {
+			pc: s.pc,
+			op: s.instruction,
+			gas: s.gas.into(),
+			gas_cost: s.gas_cost.into(),
+			depth: s.depth,
+			stack: s.stack.map(|stack| stack.into_iter().map(Into::into).collect()),
+			memory: s.memory.map(Into::into),
+			storage: s.storage.map(|storage| storage.into_iter().map(Into::into).collect()),
+			truncated: s.truncated,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+/// Operations executed by a transaction.
+pub struct StepTrace {
+	/// The recorded steps.
+	pub steps: Vec<VMStep>,
+	/// Whether recording stopped at the step limit.
+	pub truncated: bool,
+}
+
+impl From<et::StepTrace> for StepTrace {
+	fn from(t: et::StepTrace) -> Self {
+		StepTrace {
+			steps: t.steps.into_iter().map(Into::into).collect(),
+			truncated: t.truncated,
+		}
+	}
+}
+
+/// Step tracer capture options.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepTraceOptions {
+	/// Maximal number of recorded steps.
+	pub limit: Option<usize>,
+	/// Don't capture the stack.
+	#[serde(rename="disableStack")]
+	pub disable_stack: Option<bool>,
+	/// Don't capture the memory.
+	#[serde(rename="disableMemory")]
+	pub disable_memory: Option<bool>,
+	/// Don't capture the storage.
+	#[serde(rename="disableStorage")]
+	pub disable_storage: Option<bool>,
+	/// Number of topmost stack items captured at each step.
+	#[serde(rename="stackLimit")]
+	pub stack_limit: Option<usize>,
+	/// Number of memory bytes captured at each step.
+	#[serde(rename="memoryLimit")]
+	pub memory_limit: Option<usize>,
+	/// Number of storage slots captured at each step.
+	#[serde(rename="storageLimit")]
+	pub storage_limit: Option<usize>,
+}
+
+impl Into<StepTracing> for StepTraceOptions {
+	fn into(self) -> StepTracing {
+		fn capture(disable: Option<bool>, limit: Option<usize>, default: Option<usize>) -> Option<usize> {
+			if disable.unwrap_or(false) { None } else { limit.or(default) }
+		}
+
+		let default = StepTracing::default();
+		StepTracing {
+			max_steps: self.limit.unwrap_or(default.max_steps),
+			stack: capture(self.disable_stack, self.stack_limit, default.stack),
+			memory: capture(self.disable_memory, self.memory_limit, default.memory),
+			storage: capture(self.disable_storage, self.storage_limit, default.storage),
 		}
 	}
 }
@@ -639,6 +746,7 @@ mod tests {
 			trace: vec![],
 			vm_trace: None,
 			state_diff: None,
+			step_trace: None,
 		};
 		let serialized = serde_json::to_string(&r).unwrap();
 		assert_eq!(serialized, r#"{"output":"0x60","trace":[],"vmTrace":null,"stateDiff":null}"#);
@@ -843,4 +951,36 @@ mod tests {
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"0x000000000000000000000000000000000000002a":{"balance":"=","nonce":{"+":"0x1"},"code":"=","storage":{"0x000000000000000000000000000000000000000000000000000000000000002a":"="}},"0x0000000000000000000000000000000000000045":{"balance":"=","nonce":{"*":{"from":"0x1","to":"0x0"}},"code":{"-":"0x60"},"storage":{}}}"#);
 	}
+
+	#[test]
+	fn should_serialize_step_trace() {
+		let t = StepTrace {
+			steps: vec![VMStep {
+				pc: 2,
+				op: 0x55,
+				gas: 0x10.into(),
+				gas_cost: 0x3.into(),
+				depth: 1,
+				stack: Some(vec![1.into(), 2.into()]),
+				memory: None,
+				storage: Some(vec![]),
+				truncated: false,
+			}],
+			truncated: true,
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"steps":[{"pc":2,"op":85,"gas":"0x10","gasCost":"0x3","depth":1,"stack":["0x1","0x2"],"storage":[],"truncated":false}],"truncated":true}"#);
+	}
+
+	#[test]
+	fn should_deserialize_step_trace_options() {
+		let options: StepTraceOptions = serde_json::from_str(r#"{"limit":10,"disableMemory":true,"stackLimit":2}"#).unwrap();
+		let tracing: StepTracing = options.into();
+		assert_eq!(tracing, StepTracing {
+			max_steps: 10,
+			stack: Some(2),
+			memory: None,
+			storage: StepTracing::default().storage,
+		});
+	}
 }
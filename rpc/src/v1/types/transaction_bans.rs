@@ -0,0 +1,42 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Transaction queue bans.
+
+use ethcore::miner::BanList;
+use v1::types::{H160, H256};
+
+/// Senders, recipients and codes currently banned by the transaction queue.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct TransactionBans {
+	/// Banned senders.
+	pub senders: Vec<H160>,
+	/// Banned recipients.
+	pub recipients: Vec<H160>,
+	/// Hashes of banned contract codes.
+	pub codes: Vec<H256>,
+}
+
+impl From<BanList> for TransactionBans {
+	fn from(bans: BanList) -> Self {
+		TransactionBans {
+			senders: bans.senders.into_iter().map(Into::into).collect(),
+			recipients: bans.recipients.into_iter().map(Into::into).collect(),
+			codes: bans.codes.into_iter().map(Into::into).collect(),
+		}
+	}
+}
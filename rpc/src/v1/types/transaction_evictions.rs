@@ -0,0 +1,48 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Transaction queue eviction statistics.
+
+use ethcore::miner::EvictionStats;
+
+/// Number of transactions dropped from the queue because of its limits.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct TransactionEvictions {
+	/// Dropped because the queue was full.
+	#[serde(rename="countLimit")]
+	pub count_limit: u64,
+	/// Dropped because of the memory limit.
+	#[serde(rename="memoryLimit")]
+	pub memory_limit: u64,
+	/// Dropped because of the total gas limit.
+	#[serde(rename="gasLimit")]
+	pub gas_limit: u64,
+	/// Rejected because the sender reached its limits.
+	#[serde(rename="senderLimit")]
+	pub sender_limit: u64,
+}
+
+impl From<EvictionStats> for TransactionEvictions {
+	fn from(stats: EvictionStats) -> Self {
+		TransactionEvictions {
+			count_limit: stats.count_limit,
+			memory_limit: stats.memory_limit,
+			gas_limit: stats.gas_limit,
+			sender_limit: stats.sender_limit,
+		}
+	}
+}
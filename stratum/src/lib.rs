@@ -36,6 +36,8 @@ extern crate ethcore_devtools as devtools;
 
 use futures::{future, BoxFuture, Future};
 
+mod workers;
+
 mod traits {
 	//! Stratum ipc interfaces specification
 	#![allow(dead_code, unused_assignments, unused_variables, missing_docs)] // codegen issues
@@ -46,6 +48,9 @@ pub use traits::{
 	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration,
 	RemoteWorkHandler, RemoteJobDispatcher,
 };
+pub use workers::{VardiffConfig, WorkerStats};
+
+use workers::{Worker, Share};
 
 use jsonrpc_tcp_server::{
 	Server as JsonRpcServer, ServerBuilder as JsonRpcServerBuilder,
@@ -57,7 +62,9 @@ use std::sync::Arc;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
+use std::time::Instant;
 use hash::keccak;
+use bigint::prelude::U256;
 use bigint::hash::H256;
 use parking_lot::{RwLock, RwLockReadGuard};
 
@@ -127,10 +134,12 @@ pub struct Stratum {
 	job_que: RwLock<HashSet<SocketAddr>>,
 	/// Payload manager
 	dispatcher: Arc<JobDispatcher>,
-	/// Authorized workers (socket - worker_id)
-	workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+	/// Authorized workers
+	workers: Arc<RwLock<HashMap<SocketAddr, Worker>>>,
 	/// Secret if any
 	secret: Option<H256>,
+	/// Variable share difficulty settings, shares have to meet the block difficulty if not set
+	vardiff: Option<VardiffConfig>,
 	/// Dispatch notify couinter
 	notify_counter: RwLock<u32>,
 	/// Message dispatcher (tcp/ip service)
@@ -148,6 +157,7 @@ impl Stratum {
 		addr: &SocketAddr,
 		dispatcher: Arc<JobDispatcher>,
 		secret: Option<H256>,
+		vardiff: Option<VardiffConfig>,
 	) -> Result<Arc<Stratum>, Error> {
 
 		let rpc = Arc::new(StratumRpc {
@@ -173,6 +183,7 @@ impl Stratum {
 			dispatcher: dispatcher,
 			workers: Arc::new(RwLock::new(HashMap::new())),
 			secret: secret,
+			vardiff: vardiff,
 			notify_counter: RwLock::new(NOTIFY_COUNTER_INITIAL),
 		});
 		*rpc.stratum.write() = Some(stratum.clone());
@@ -180,29 +191,45 @@ impl Stratum {
 	}
 
 	fn update_peers(&self) {
-		if let Some(job) = self.dispatcher.job() {
-			if let Err(e) = self.push_work_all(job) {
-				warn!("Failed to update some of the peers: {:?}", e);
-			}
+		let job = self.dispatcher.job();
+		let result = self.push_work_each(|difficulty| match difficulty {
+			Some(difficulty) => self.dispatcher.job_with_difficulty(difficulty),
+			None => job.clone(),
+		});
+		if let Err(e) = result {
+			warn!("Failed to update some of the peers: {:?}", e);
 		}
 	}
 
-	fn submit(&self, params: Params, _meta: SocketMetadata) -> RpcResult {
+	fn submit(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		future::ok(match params {
 			Params::Array(vals) => {
 				// first two elements are service messages (worker_id & job_id)
-				match self.dispatcher.submit(vals.iter().skip(2)
+				let payload = vals.iter().skip(2)
 					.filter_map(|val| match val { &Value::String(ref str) => Some(str.to_owned()), _ => None })
-					.collect::<Vec<String>>()) {
-						Ok(()) => {
-							self.update_peers();
-							to_value(true)
-						},
-						Err(submit_err) => {
-							warn!("Error while submitting share: {:?}", submit_err);
-							to_value(false)
-						}
-					}
+					.collect::<Vec<String>>();
+				let share_difficulty = self.workers.read().get(meta.addr()).and_then(Worker::share_difficulty);
+				let result = match share_difficulty {
+					Some(difficulty) => self.dispatcher.submit_share(payload, difficulty),
+					None => self.dispatcher.submit(payload).map(|_| true),
+				};
+				let share = match result {
+					Ok(true) => Share::Block,
+					Ok(false) => Share::Accepted,
+					Err(Error::StaleShare) => {
+						debug!(target: "stratum", "Stale share from {}", meta.addr());
+						Share::Stale
+					},
+					Err(submit_err) => {
+						warn!("Error while submitting share: {:?}", submit_err);
+						Share::Invalid
+					},
+				};
+				self.record_share(meta.addr(), share);
+				if share == Share::Block {
+					self.update_peers();
+				}
+				to_value(share == Share::Block || share == Share::Accepted)
 			},
 			_ => {
 				trace!(target: "stratum", "Invalid submit work format {:?}", params);
@@ -239,7 +266,12 @@ impl Stratum {
 				}
 			}
 			trace!(target: "stratum", "New worker #{} registered", worker_id);
-			self.workers.write().insert(meta.addr().clone(), worker_id);
+			let worker = Worker::new(worker_id, meta.addr().clone(), self.vardiff.as_ref(), Instant::now());
+			let difficulty = worker.difficulty();
+			self.workers.write().insert(meta.addr().clone(), worker);
+			if let Some(difficulty) = difficulty {
+				self.push_job(meta.addr(), difficulty);
+			}
 			to_value(true)
 		}).map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))).boxed()
 	}
@@ -248,37 +280,58 @@ impl Stratum {
 		self.subscribers.read()
 	}
 
+	/// Share statistics of the authorized workers.
+	pub fn workers(&self) -> Vec<WorkerStats> {
+		let mut workers = self.workers.read().values().map(|worker| worker.stats().clone()).collect::<Vec<_>>();
+		workers.sort_by(|a, b| a.id.cmp(&b.id));
+		workers
+	}
+
 	pub fn maintain(&self) {
 		let mut job_que = self.job_que.write();
 		let job_payload = self.dispatcher.job();
 		for socket_addr in job_que.drain() {
-			job_payload.as_ref().map(
-				|json| self.tcp_dispatcher.push_message(&socket_addr, json.to_owned())
+			let difficulty = self.workers.read().get(&socket_addr).and_then(Worker::difficulty);
+			let job = match difficulty {
+				Some(difficulty) => self.dispatcher.job_with_difficulty(difficulty),
+				None => job_payload.clone(),
+			};
+			job.map(
+				|json| self.tcp_dispatcher.push_message(&socket_addr, json)
 			);
 		}
+
+		// idle workers don't get their difficulty adjusted on submission
+		if let Some(ref vardiff) = self.vardiff {
+			let now = Instant::now();
+			let retargeted = self.workers.write().iter_mut()
+				.filter_map(|(addr, worker)| worker.retarget(vardiff, now).map(|difficulty| (*addr, difficulty)))
+				.collect::<Vec<_>>();
+			for (addr, difficulty) in retargeted {
+				self.push_job(&addr, difficulty);
+			}
+		}
 	}
-}
 
-impl PushWorkHandler for Stratum {
-	fn push_work_all(&self, payload: String) -> Result<(), Error> {
+	/// Push work to all workers, serialized by `payload` given the share difficulty of each worker.
+	pub fn push_work_each<F>(&self, payload: F) -> Result<(), Error> where F: Fn(Option<U256>) -> Option<String> {
 		let hup_peers = {
 			let workers = self.workers.read();
-			let next_request_id = {
-				let mut counter = self.notify_counter.write();
-				if *counter == ::std::u32::MAX { *counter = NOTIFY_COUNTER_INITIAL; }
-				else { *counter = *counter + 1 }
-				*counter
-			};
+			let next_request_id = self.next_request_id();
 
 			let mut hup_peers = HashSet::with_capacity(0); // most of the cases won't be needed, hence avoid allocation
-			let workers_msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload);
-			trace!(target: "stratum", "pushing work for {} workers (payload: '{}')", workers.len(), &workers_msg);
-			for (ref addr, _) in workers.iter() {
-				trace!(target: "stratum", "pusing work to {}", addr);
-				match self.tcp_dispatcher.push_message(addr, workers_msg.clone()) {
+			trace!(target: "stratum", "pushing work for {} workers", workers.len());
+			for (addr, worker) in workers.iter() {
+				let payload = match payload(worker.difficulty()) {
+					Some(payload) => payload,
+					None => continue,
+				};
+				let workers_msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload);
+				trace!(target: "stratum", "pusing work to {} (payload: '{}')", addr, &workers_msg);
+				match self.tcp_dispatcher.push_message(addr, workers_msg) {
 					Err(PushMessageError::NoSuchPeer) => {
 						trace!(target: "stratum", "Worker no longer connected: {}", &addr);
-						hup_peers.insert(*addr.clone());
+						hup_peers.insert(*addr);
 					},
 					Err(e) => {
 						warn!(target: "stratum", "Unexpected transport error: {:?}", e);
@@ -297,6 +350,44 @@ impl PushWorkHandler for Stratum {
 		Ok(())
 	}
 
+	fn next_request_id(&self) -> u32 {
+		let mut counter = self.notify_counter.write();
+		if *counter == ::std::u32::MAX { *counter = NOTIFY_COUNTER_INITIAL; }
+		else { *counter = *counter + 1 }
+		*counter
+	}
+
+	/// Push job with given share difficulty to a single worker.
+	fn push_job(&self, addr: &SocketAddr, difficulty: U256) {
+		if let Some(payload) = self.dispatcher.job_with_difficulty(difficulty) {
+			let msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", self.next_request_id(), payload);
+			if let Err(e) = self.tcp_dispatcher.push_message(addr, msg) {
+				trace!(target: "stratum", "Failed to push job to {}: {:?}", addr, e);
+			}
+		}
+	}
+
+	/// Record a submitted share and adjust the worker's share difficulty.
+	fn record_share(&self, addr: &SocketAddr, share: Share) {
+		let retargeted = match self.workers.write().get_mut(addr) {
+			Some(worker) => {
+				worker.record(share);
+				self.vardiff.as_ref().and_then(|vardiff| worker.retarget(vardiff, Instant::now()))
+			},
+			None => None,
+		};
+		if let Some(difficulty) = retargeted {
+			trace!(target: "stratum", "Share difficulty of {} set to {}", addr, difficulty);
+			self.push_job(addr, difficulty);
+		}
+	}
+}
+
+impl PushWorkHandler for Stratum {
+	fn push_work_all(&self, payload: String) -> Result<(), Error> {
+		self.push_work_each(|_| Some(payload.clone()))
+	}
+
 	fn push_work(&self, payloads: Vec<String>) -> Result<(), Error>  {
 		if !payloads.len() > 0 {
 			return Err(Error::NoWork);
@@ -327,6 +418,7 @@ mod tests {
 	use std::str::FromStr;
 	use std::net::SocketAddr;
 	use std::sync::Arc;
+	use bigint::prelude::U256;
 
 	use tokio_core::reactor::{Core, Timeout};
 	use tokio_core::net::TcpStream;
@@ -367,7 +459,7 @@ mod tests {
 
 	#[test]
 	fn can_be_started() {
-		let stratum = Stratum::start(&SocketAddr::from_str("127.0.0.1:19980").unwrap(), Arc::new(VoidManager), None);
+		let stratum = Stratum::start(&SocketAddr::from_str("127.0.0.1:19980").unwrap(), Arc::new(VoidManager), None, None);
 		assert!(stratum.is_ok());
 	}
 
@@ -376,7 +468,7 @@ mod tests {
 		init_log();
 
 		let addr = SocketAddr::from_str("127.0.0.1:19985").unwrap();
-		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None, None).unwrap();
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 1}"#;
 		dummy_request(&addr, request);
 		assert_eq!(1, stratum.subscribers.read().len());
@@ -421,7 +513,7 @@ mod tests {
 	#[test]
 	fn receives_initial_paylaod() {
 		let addr = SocketAddr::from_str("127.0.0.1:19975").unwrap();
-		Stratum::start(&addr, DummyManager::new(), None).expect("There should be no error starting stratum");
+		Stratum::start(&addr, DummyManager::new(), None, None).expect("There should be no error starting stratum");
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 2}"#;
 
 		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
@@ -435,7 +527,8 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
-			None
+			None,
+			None,
 		).expect("There should be no error starting stratum");
 
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#;
@@ -445,6 +538,57 @@ mod tests {
 		assert_eq!(1, stratum.workers.read().len());
 	}
 
+	struct ShareManager;
+
+	impl JobDispatcher for ShareManager {
+		fn submit(&self, _payload: Vec<String>) -> Result<(), Error> {
+			Err(Error::StaleShare)
+		}
+
+		fn submit_share(&self, payload: Vec<String>, difficulty: U256) -> Result<bool, Error> {
+			assert_eq!(difficulty, 1000.into());
+			match payload[0].as_str() {
+				"0x01" => Ok(false),
+				_ => Err(Error::Dispatch("Share below target".into())),
+			}
+		}
+	}
+
+	fn wait_for_shares(stratum: &Stratum, shares: u64) -> Vec<WorkerStats> {
+		for _ in 0..100 {
+			let workers = stratum.workers();
+			if workers.iter().map(|w| w.accepted + w.stale + w.invalid).sum::<u64>() >= shares {
+				return workers;
+			}
+			::std::thread::sleep(::std::time::Duration::from_millis(10));
+		}
+		stratum.workers()
+	}
+
+	#[test]
+	fn counts_worker_shares() {
+		let addr = SocketAddr::from_str("127.0.0.1:19990").unwrap();
+		let vardiff = VardiffConfig {
+			min_difficulty: 1000.into(),
+			share_time: ::std::time::Duration::from_secs(10),
+		};
+		let stratum = Stratum::start(&addr, Arc::new(ShareManager), None, Some(vardiff))
+			.expect("There should be no error starting stratum");
+
+		let requests = vec![
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#,
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "job", "0x01", "0x00", "0x00"], "id": 2}"#,
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "job", "0x02", "0x00", "0x00"], "id": 3}"#,
+		];
+		dummy_request(&addr, &requests.join("\n"));
+
+		let workers = wait_for_shares(&stratum, 2);
+		assert_eq!(workers.len(), 1);
+		assert_eq!(workers[0].id, "miner1");
+		assert_eq!(workers[0].difficulty, Some(1000.into()));
+		assert_eq!((workers[0].accepted, workers[0].stale, workers[0].invalid, workers[0].blocks), (1, 0, 1, 0));
+	}
+
 	#[test]
 	fn can_push_work() {
 		init_log();
@@ -453,7 +597,8 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
-			None
+			None,
+			None,
 		).expect("There should be no error starting stratum");
 
 		let mut auth_request =
@@ -16,6 +16,7 @@
 
 use std;
 use std::error::Error as StdError;
+use bigint::prelude::U256;
 use bigint::hash::H256;
 use ipc::IpcConfig;
 use jsonrpc_tcp_server::PushMessageError;
@@ -25,6 +26,7 @@ use jsonrpc_tcp_server::PushMessageError;
 pub enum Error {
 	NoWork,
 	NoWorkers,
+	StaleShare,
 	Io(String),
 	Tcp(String),
 	Dispatch(String),
@@ -51,8 +53,14 @@ pub trait JobDispatcher: Send + Sync {
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
 	fn job(&self) -> Option<String> { None }
+	// json for job update with given share difficulty
+	fn job_with_difficulty(&self, _difficulty: U256) -> Option<String> { self.job() }
 	// miner job result
 	fn submit(&self, payload: Vec<String>) -> Result<(), Error>;
+	// miner share result given the share difficulty of the worker, true if it sealed a block
+	fn submit_share(&self, payload: Vec<String>, _difficulty: U256) -> Result<bool, Error> {
+		self.submit(payload).map(|_| true)
+	}
 }
 
 /// Interface that can handle requests to push job for workers
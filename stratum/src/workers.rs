@@ -0,0 +1,257 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Share statistics and variable share difficulty of workers.
+
+use std::cmp;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use bigint::prelude::U256;
+
+/// Number of shares a worker is expected to submit between share difficulty adjustments.
+const RETARGET_SHARES: u64 = 8;
+/// Maximal factor of a single share difficulty adjustment.
+const MAX_RETARGET_FACTOR: u64 = 4;
+
+/// Variable share difficulty settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VardiffConfig {
+	/// Share difficulty of new workers and the lowest one ever assigned.
+	pub min_difficulty: U256,
+	/// Desired time between shares of a single worker.
+	pub share_time: Duration,
+}
+
+/// Share statistics of a worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+	/// Worker id given on authorization.
+	pub id: String,
+	/// Address of the worker's connection.
+	pub addr: SocketAddr,
+	/// Current share difficulty, `None` if shares have to meet the block difficulty.
+	pub difficulty: Option<U256>,
+	/// Accepted shares.
+	pub accepted: u64,
+	/// Shares for outdated work.
+	pub stale: u64,
+	/// Rejected shares.
+	pub invalid: u64,
+	/// Accepted shares which sealed a block.
+	pub blocks: u64,
+}
+
+/// Outcome of a submitted share.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Share {
+	/// Share sealed a block.
+	Block,
+	/// Share met the share difficulty.
+	Accepted,
+	/// Share for outdated work.
+	Stale,
+	/// Share rejected.
+	Invalid,
+}
+
+/// Authorized worker.
+pub struct Worker {
+	stats: WorkerStats,
+	/// Share difficulty before the last adjustment.
+	previous_difficulty: Option<U256>,
+	/// Start of the current adjustment window.
+	window_start: Instant,
+	/// Shares accepted within the current adjustment window.
+	window_shares: u64,
+}
+
+impl Worker {
+	/// New worker, starting at the lowest share difficulty if variable difficulty is enabled.
+	pub fn new(id: String, addr: SocketAddr, vardiff: Option<&VardiffConfig>, now: Instant) -> Self {
+		Worker {
+			stats: WorkerStats {
+				id: id,
+				addr: addr,
+				difficulty: vardiff.map(|config| config.min_difficulty),
+				accepted: 0,
+				stale: 0,
+				invalid: 0,
+				blocks: 0,
+			},
+			previous_difficulty: None,
+			window_start: now,
+			window_shares: 0,
+		}
+	}
+
+	/// Share statistics.
+	pub fn stats(&self) -> &WorkerStats {
+		&self.stats
+	}
+
+	/// Current share difficulty, `None` if shares have to meet the block difficulty.
+	pub fn difficulty(&self) -> Option<U256> {
+		self.stats.difficulty
+	}
+
+	/// Difficulty submitted shares have to meet.
+	/// Shares for jobs sent before the last adjustment are still accepted.
+	pub fn share_difficulty(&self) -> Option<U256> {
+		match (self.stats.difficulty, self.previous_difficulty) {
+			(Some(current), Some(previous)) => Some(cmp::min(current, previous)),
+			(current, _) => current,
+		}
+	}
+
+	/// Record a submitted share.
+	pub fn record(&mut self, share: Share) {
+		match share {
+			Share::Block => {
+				self.stats.accepted += 1;
+				self.stats.blocks += 1;
+				self.window_shares += 1;
+			},
+			Share::Accepted => {
+				self.stats.accepted += 1;
+				self.window_shares += 1;
+			},
+			Share::Stale => self.stats.stale += 1,
+			Share::Invalid => self.stats.invalid += 1,
+		}
+	}
+
+	/// Adjust the share difficulty towards the desired share rate once enough shares were
+	/// submitted or enough time passed. Returns the new difficulty if it changed.
+	pub fn retarget(&mut self, config: &VardiffConfig, now: Instant) -> Option<U256> {
+		let difficulty = match self.stats.difficulty {
+			Some(difficulty) => difficulty,
+			None => return None,
+		};
+		let elapsed = now.duration_since(self.window_start);
+		if self.window_shares < RETARGET_SHARES && elapsed < config.share_time * RETARGET_SHARES as u32 {
+			return None;
+		}
+
+		// difficulty scaled by the ratio of the time the shares should have taken to the time they took
+		let expected = self.window_shares * as_millis(config.share_time);
+		let elapsed = cmp::max(as_millis(elapsed), 1);
+		let target = difficulty.saturating_mul(expected.into()) / U256::from(elapsed);
+
+		let lowest = cmp::max(difficulty / U256::from(MAX_RETARGET_FACTOR), config.min_difficulty);
+		let highest = difficulty.saturating_mul(MAX_RETARGET_FACTOR.into());
+		let target = cmp::min(cmp::max(target, lowest), highest);
+
+		self.window_start = now;
+		self.window_shares = 0;
+		if target == difficulty {
+			return None;
+		}
+
+		self.previous_difficulty = Some(difficulty);
+		self.stats.difficulty = Some(target);
+		Some(target)
+	}
+}
+
+fn as_millis(duration: Duration) -> u64 {
+	duration.as_secs() * 1000 + duration.subsec_nanos() as u64 / 1_000_000
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use bigint::prelude::U256;
+	use super::{Worker, Share, VardiffConfig};
+
+	fn config() -> VardiffConfig {
+		VardiffConfig {
+			min_difficulty: 1000.into(),
+			share_time: Duration::from_secs(10),
+		}
+	}
+
+	fn worker(now: Instant) -> Worker {
+		Worker::new("miner1".into(), "127.0.0.1:30303".parse().unwrap(), Some(&config()), now)
+	}
+
+	#[test]
+	fn should_count_shares() {
+		let mut worker = worker(Instant::now());
+		worker.record(Share::Block);
+		worker.record(Share::Accepted);
+		worker.record(Share::Stale);
+		worker.record(Share::Invalid);
+		worker.record(Share::Invalid);
+
+		let stats = worker.stats();
+		assert_eq!((stats.accepted, stats.stale, stats.invalid, stats.blocks), (2, 1, 2, 1));
+	}
+
+	#[test]
+	fn should_raise_difficulty_of_fast_workers() {
+		let start = Instant::now();
+		let mut worker = worker(start);
+		for _ in 0..7 {
+			worker.record(Share::Accepted);
+		}
+		assert_eq!(worker.retarget(&config(), start + Duration::from_secs(20)), None);
+
+		// 8 shares in 40 seconds instead of 80
+		worker.record(Share::Accepted);
+		assert_eq!(worker.retarget(&config(), start + Duration::from_secs(40)), Some(2000.into()));
+		assert_eq!(worker.difficulty(), Some(2000.into()));
+		assert_eq!(worker.share_difficulty(), Some(1000.into()));
+
+		// at most 4 times higher
+		let next = start + Duration::from_secs(41);
+		for _ in 0..8 {
+			worker.record(Share::Accepted);
+		}
+		assert_eq!(worker.retarget(&config(), next), Some(8000.into()));
+		assert_eq!(worker.share_difficulty(), Some(2000.into()));
+	}
+
+	#[test]
+	fn should_lower_difficulty_of_slow_workers() {
+		let start = Instant::now();
+		let mut worker = worker(start);
+		for _ in 0..8 {
+			worker.record(Share::Accepted);
+		}
+		worker.retarget(&config(), start + Duration::from_secs(10));
+		assert_eq!(worker.difficulty(), Some(4000.into()));
+
+		// 2 shares in 80 seconds instead of 20
+		worker.record(Share::Accepted);
+		worker.record(Share::Stale);
+		worker.record(Share::Accepted);
+		assert_eq!(worker.retarget(&config(), start + Duration::from_secs(90)), Some(1000.into()));
+
+		// never below the minimum
+		assert_eq!(worker.retarget(&config(), start + Duration::from_secs(200)), None);
+		assert_eq!(worker.difficulty(), Some(U256::from(1000)));
+	}
+
+	#[test]
+	fn should_keep_block_difficulty_without_vardiff() {
+		let now = Instant::now();
+		let mut worker = Worker::new("miner1".into(), "127.0.0.1:30303".parse().unwrap(), None, now);
+		worker.record(Share::Accepted);
+		assert_eq!(worker.retarget(&config(), now + Duration::from_secs(100)), None);
+		assert_eq!(worker.share_difficulty(), None);
+	}
+}
@@ -15,11 +15,13 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::collections::{HashMap, BTreeMap};
 use std::io;
 use util::Bytes;
 use network::{NetworkProtocolHandler, NetworkService, NetworkContext, HostInfo, PeerId, ProtocolId,
-	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError, ConnectionFilter};
+	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError, ConnectionFilter,
+	AccessList, AccessEntry, BandwidthLimits, ProtocolTraffic};
 use bigint::prelude::U256;
 use bigint::hash::{H256, H512};
 use io::{TimerToken};
@@ -27,17 +29,20 @@ use ethcore::ethstore::ethkey::Secret;
 use ethcore::client::{BlockChainClient, ChainNotify};
 use ethcore::snapshot::SnapshotService;
 use ethcore::header::BlockNumber;
-use sync_io::NetSyncIo;
-use chain::{ChainSync, SyncStatus as EthSyncStatus};
+use sync_io::{NetSyncIo, SyncIo};
+use chain::{ChainSync, SyncState, SyncStatus as EthSyncStatus};
 use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::str::FromStr;
 use parking_lot::RwLock;
 use chain::{ETH_PACKET_COUNT, SNAPSHOT_SYNC_PACKET_COUNT};
+use private_tx::PrivateTxHandler;
+use rebroadcast::RebroadcastPolicy;
+use snapshot_mirror::{self, MirrorSync, SnapshotMirrors};
 use light::client::AsLightClient;
 use light::Provider;
 use light::net::{self as light_net, LightProtocol, Params as LightParams, Capabilities, Handler as LightHandler, EventContext};
-use network::IpFilter;
+use network::{IpFilter, IpVersion};
 
 /// Parity sync protocol
 pub const WARP_SYNC_PROTOCOL_ID: ProtocolId = *b"par";
@@ -45,6 +50,8 @@ pub const WARP_SYNC_PROTOCOL_ID: ProtocolId = *b"par";
 pub const ETH_PROTOCOL: ProtocolId = *b"eth";
 /// Ethereum light protocol
 pub const LIGHT_PROTOCOL: ProtocolId = *b"pip";
+/// Discovery topic nodes serving snapshots are advertised under
+const SNAPSHOT_TOPIC: &'static str = "par/snapshot";
 
 /// Sync configuration
 #[derive(Debug, Clone, Copy)]
@@ -63,8 +70,14 @@ pub struct SyncConfig {
 	pub fork_block: Option<(BlockNumber, H256)>,
 	/// Enable snapshot sync
 	pub warp_sync: bool,
+	/// Only restore the snapshot with given manifest hash.
+	pub warp_snapshot_hash: Option<H256>,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Experimental: restore only the blocks of a snapshot and fetch the state on demand.
+	pub beam_sync: bool,
+	/// Re-broadcast of local transactions still in the queue.
+	pub local_tx_rebroadcast: Option<RebroadcastPolicy>,
 }
 
 impl Default for SyncConfig {
@@ -77,7 +90,10 @@ impl Default for SyncConfig {
 			light_subprotocol_name: LIGHT_PROTOCOL,
 			fork_block: None,
 			warp_sync: false,
+			warp_snapshot_hash: None,
 			serve_light: false,
+			beam_sync: false,
+			local_tx_rebroadcast: Some(RebroadcastPolicy::default()),
 		}
 	}
 }
@@ -98,6 +114,9 @@ pub trait SyncProvider: Send + Sync {
 
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
+
+	/// Returns distribution of peers across chain branches.
+	fn fork_partition(&self) -> ForkPartition;
 }
 
 /// Transaction stats
@@ -124,12 +143,110 @@ pub struct PeerInfo {
 	pub remote_address: String,
 	/// Local endpoint address
 	pub local_address: String,
+	/// Peer ping delay in milliseconds
+	pub ping_ms: Option<u64>,
+	/// Traffic exchanged with the peer per subprotocol.
+	pub traffic: Vec<ProtocolTrafficInfo>,
 	/// Eth protocol info.
 	pub eth_info: Option<EthProtocolInfo>,
 	/// Light protocol info.
 	pub pip_info: Option<PipProtocolInfo>,
 }
 
+/// Peers reporting the same best block.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct ForkBranch {
+	/// Best block hash
+	pub head: H256,
+	/// Total difficulty reported by the first peer, if known
+	pub difficulty: Option<U256>,
+	/// Number of peers
+	pub peers: usize,
+	/// Whether the branch conflicts with the local chain
+	pub conflicting: bool,
+}
+
+/// Distribution of peers across chain branches.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct ForkPartition {
+	/// Whether a significant share of peers follows conflicting branches
+	pub detected: bool,
+	/// Number of peers taken into account
+	pub peers: usize,
+	/// Number of peers following conflicting branches
+	pub conflicting_peers: usize,
+	/// Branches ordered by number of peers
+	pub branches: Vec<ForkBranch>,
+}
+
+/// Reputation of a node which misbehaved recently.
+#[derive(Debug)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct PeerReputation {
+	/// Public node id
+	pub id: String,
+	/// Reputation score, zero for nodes in good standing
+	pub score: i64,
+	/// Number of times the node has been banned
+	pub bans: u32,
+	/// Seconds until the current ban expires, zero if not banned
+	pub banned_for: u64,
+}
+
+/// Peer allow and deny lists. Entries are node ids or IP ranges.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct PeerAccessLists {
+	/// If not empty, only these nodes and IP ranges may connect
+	pub allow: Vec<String>,
+	/// These nodes and IP ranges may never connect
+	pub deny: Vec<String>,
+}
+
+/// Subprotocol traffic exchanged with a peer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct ProtocolTrafficInfo {
+	/// Subprotocol name
+	pub protocol: String,
+	/// Number of packets sent
+	pub packets_sent: u64,
+	/// Number of packets received
+	pub packets_received: u64,
+	/// Number of payload bytes sent
+	pub bytes_sent: u64,
+	/// Number of payload bytes received
+	pub bytes_received: u64,
+}
+
+impl ProtocolTrafficInfo {
+	fn from_session(traffic: HashMap<ProtocolId, ProtocolTraffic>) -> Vec<Self> {
+		let mut traffic: Vec<_> = traffic.into_iter().map(|(protocol, t)| ProtocolTrafficInfo {
+			protocol: String::from_utf8_lossy(&protocol).into_owned(),
+			packets_sent: t.packets_sent,
+			packets_received: t.packets_received,
+			bytes_sent: t.bytes_sent,
+			bytes_received: t.bytes_received,
+		}).collect();
+		traffic.sort_by(|a, b| a.protocol.cmp(&b.protocol));
+		traffic
+	}
+}
+
+/// Outcome of the sync requests sent to a peer.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct PeerRequestStats {
+	/// Number of requests sent
+	pub sent: u64,
+	/// Number of requests answered with invalid or useless data
+	pub failed: u64,
+	/// Number of requests answered with data that was used
+	pub useful: u64,
+}
+
 /// Ethereum protocol info.
 #[derive(Debug)]
 #[cfg_attr(feature = "ipc", derive(Binary))]
@@ -140,6 +257,8 @@ pub struct EthProtocolInfo {
 	pub head: H256,
 	/// Peer total difficulty if known
 	pub difficulty: Option<U256>,
+	/// Outcome of the requests sent to the peer
+	pub requests: PeerRequestStats,
 }
 
 /// PIP protocol info.
@@ -166,6 +285,10 @@ impl From<light_net::Status> for PipProtocolInfo {
 
 /// Configuration to attach alternate protocol handlers.
 /// Only works when IPC is disabled.
+///
+/// The protocol is advertised to peers with the given versions; the highest version
+/// supported by both sides is negotiated during the handshake and the handler is notified
+/// of connected peers and their packets through the protocol's own `NetworkContext`.
 #[cfg(not(feature = "ipc"))]
 pub struct AttachedProtocol {
 	/// The protocol handler in question.
@@ -184,7 +307,7 @@ pub struct AttachedProtocol {
 pub struct AttachedProtocol;
 
 impl AttachedProtocol {
-	#[cfg(feature = "ipc")]
+	#[cfg(not(feature = "ipc"))]
 	fn register(&self, network: &NetworkService) {
 		let res = network.register_protocol(
 			self.handler.clone(),
@@ -194,14 +317,37 @@ impl AttachedProtocol {
 		);
 
 		if let Err(e) = res {
-			warn!(target: "sync","Error attaching protocol {:?}", protocol_id);
+			warn!(target: "sync", "Error attaching protocol {:?}: {:?}", self.protocol_id, e);
 		}
 	}
 
-	#[cfg(not(feature = "ipc"))]
+	#[cfg(feature = "ipc")]
 	fn register(&self, _network: &NetworkService) {}
 }
 
+// attach a protocol to the network, registering it right away if the network is running.
+// protocols are registered again whenever the network is (re)started.
+#[cfg(not(feature = "ipc"))]
+fn attach_protocol(
+	network: &NetworkService,
+	attached: &RwLock<Vec<AttachedProtocol>>,
+	reserved: &[ProtocolId],
+	proto: AttachedProtocol,
+) -> Result<(), NetworkError> {
+	let mut attached = attached.write();
+	let in_use = reserved.iter().any(|id| *id == proto.protocol_id)
+		|| attached.iter().any(|p| p.protocol_id == proto.protocol_id);
+	if in_use || proto.versions.is_empty() {
+		return Err(NetworkError::BadProtocol);
+	}
+
+	if network.is_started() {
+		proto.register(network);
+	}
+	attached.push(proto);
+	Ok(())
+}
+
 /// EthSync initialization parameters.
 #[cfg_attr(feature = "ipc", derive(Binary))]
 pub struct Params {
@@ -217,6 +363,10 @@ pub struct Params {
 	pub network_config: NetworkConfiguration,
 	/// Other protocols to attach.
 	pub attached_protos: Vec<AttachedProtocol>,
+	/// Private transactions handler.
+	pub private_tx_handler: Arc<PrivateTxHandler>,
+	/// HTTP(S) mirrors to download the snapshot from when warp syncing.
+	pub snapshot_mirrors: Option<SnapshotMirrors>,
 }
 
 /// Ethereum network protocol handler
@@ -228,7 +378,7 @@ pub struct EthSync {
 	/// Light (pip) protocol handler
 	light_proto: Option<Arc<LightProtocol>>,
 	/// Other protocols to attach.
-	attached_protos: Vec<AttachedProtocol>,
+	attached_protos: RwLock<Vec<AttachedProtocol>>,
 	/// The main subprotocol name
 	subprotocol_name: [u8; 3],
 	/// Light subprotocol name.
@@ -272,7 +422,7 @@ impl EthSync {
 			})
 		};
 
-		let chain_sync = ChainSync::new(params.config, &*params.chain);
+		let chain_sync = ChainSync::new(params.config, &*params.chain, params.private_tx_handler);
 		let service = NetworkService::new(params.network_config.clone().into_basic()?, connection_filter)?;
 
 		let sync = Arc::new(EthSync {
@@ -282,15 +432,38 @@ impl EthSync {
 				chain: params.chain,
 				snapshot_service: params.snapshot_service,
 				overlay: RwLock::new(HashMap::new()),
+				advertising_snapshot: AtomicBool::new(false),
 			}),
 			light_proto: light_proto,
 			subprotocol_name: params.config.subprotocol_name,
 			light_subprotocol_name: params.config.light_subprotocol_name,
-			attached_protos: params.attached_protos,
+			attached_protos: RwLock::new(params.attached_protos),
 		});
 
+		match params.snapshot_mirrors {
+			Some(mirrors) if params.config.warp_sync => snapshot_mirror::spawn(Arc::downgrade(&sync), mirrors),
+			_ => {},
+		}
+
 		Ok(sync)
 	}
+
+	/// Attach a custom subprotocol to the network. It's registered right away if the network is running
+	/// and whenever it's started. Fails if the protocol ID is already taken or no versions are given.
+	#[cfg(not(feature = "ipc"))]
+	pub fn attach_protocol(&self, proto: AttachedProtocol) -> Result<(), NetworkError> {
+		let reserved = [self.subprotocol_name, WARP_SYNC_PROTOCOL_ID, self.light_subprotocol_name];
+		attach_protocol(&self.network, &self.attached_protos, &reserved, proto)
+	}
+}
+
+impl MirrorSync for EthSync {
+	fn with_sync<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&mut ChainSync, &mut SyncIo) -> T {
+		self.network.with_context_eval(self.subprotocol_name, |context| {
+			let mut sync_io = NetSyncIo::new(context, &*self.eth_handler.chain, &*self.eth_handler.snapshot_service, &self.eth_handler.overlay);
+			f(&mut *self.eth_handler.sync.write(), &mut sync_io)
+		})
+	}
 }
 
 #[cfg_attr(feature = "ipc", ipc(client_ident="SyncClient"))]
@@ -319,6 +492,8 @@ impl SyncProvider for EthSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					ping_ms: session_info.ping_ms,
+					traffic: ProtocolTrafficInfo::from_session(session_info.traffic),
 					eth_info: eth_sync.peer_info(&peer_id),
 					pip_info: light_proto.as_ref().and_then(|lp| lp.peer_status(&peer_id)).map(Into::into),
 				})
@@ -337,6 +512,10 @@ impl SyncProvider for EthSync {
 			.map(|(hash, stats)| (*hash, stats.into()))
 			.collect()
 	}
+
+	fn fork_partition(&self) -> ForkPartition {
+		self.eth_handler.sync.read().fork_partition().clone()
+	}
 }
 
 struct SyncProtocolHandler {
@@ -348,12 +527,18 @@ struct SyncProtocolHandler {
 	sync: RwLock<ChainSync>,
 	/// Chain overlay used to cache data such as fork block.
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
+	/// Whether our snapshot has been advertised in discovery.
+	advertising_snapshot: AtomicBool,
 }
 
 impl NetworkProtocolHandler for SyncProtocolHandler {
 	fn initialize(&self, io: &NetworkContext, _host_info: &HostInfo) {
 		if io.subprotocol_name() != WARP_SYNC_PROTOCOL_ID {
 			io.register_timer(0, 1000).expect("Error registering sync timer");
+			self.advertising_snapshot.store(false, AtomicOrdering::SeqCst);
+		} else if self.sync.read().status().state == SyncState::WaitingPeers {
+			// look for nodes to warp sync from
+			io.search_topic(SNAPSHOT_TOPIC);
 		}
 	}
 
@@ -377,6 +562,10 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 	}
 
 	fn timeout(&self, io: &NetworkContext, _timer: TimerToken) {
+		if !self.advertising_snapshot.load(AtomicOrdering::SeqCst) && self.snapshot_service.manifest().is_some() {
+			io.advertise_topic(SNAPSHOT_TOPIC);
+			self.advertising_snapshot.store(true, AtomicOrdering::SeqCst);
+		}
 		self.sync.write().maintain_peers(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
 		self.sync.write().maintain_sync(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
 		self.sync.write().propagate_new_transactions(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
@@ -436,7 +625,7 @@ impl ChainNotify for EthSync {
 		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, ETH_PACKET_COUNT, &[62u8, 63u8])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
-		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, SNAPSHOT_SYNC_PACKET_COUNT, &[1u8, 2u8])
+		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, SNAPSHOT_SYNC_PACKET_COUNT, &[1u8, 2u8, 3u8, 4u8])
 			.unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
 
 		// register the light protocol.
@@ -446,7 +635,7 @@ impl ChainNotify for EthSync {
 		}
 
 		// register any attached protocols.
-		for proto in &self.attached_protos { proto.register(&self.network) }
+		for proto in self.attached_protos.read().iter() { proto.register(&self.network) }
 	}
 
 	fn stop(&self) {
@@ -461,6 +650,20 @@ impl ChainNotify for EthSync {
 		});
 	}
 
+	fn broadcast_private_transaction(&self, packet: Vec<u8>) {
+		self.network.with_context(WARP_SYNC_PROTOCOL_ID, |context| {
+			let mut sync_io = NetSyncIo::new(context, &*self.eth_handler.chain, &*self.eth_handler.snapshot_service, &self.eth_handler.overlay);
+			self.eth_handler.sync.write().propagate_private_transaction(&mut sync_io, packet.clone());
+		});
+	}
+
+	fn broadcast_signed_private_transaction(&self, packet: Vec<u8>) {
+		self.network.with_context(WARP_SYNC_PROTOCOL_ID, |context| {
+			let mut sync_io = NetSyncIo::new(context, &*self.eth_handler.chain, &*self.eth_handler.snapshot_service, &self.eth_handler.overlay);
+			self.eth_handler.sync.write().propagate_signed_private_transaction(&mut sync_io, packet.clone());
+		});
+	}
+
 	fn transactions_received(&self, hashes: Vec<H256>, peer_id: PeerId) {
 		let mut sync = self.eth_handler.sync.write();
 		sync.transactions_received(hashes, peer_id);
@@ -491,16 +694,87 @@ pub trait ManageNetwork : Send + Sync {
 	fn remove_reserved_peer(&self, peer: String) -> Result<(), String>;
 	/// Add reserved peer
 	fn add_reserved_peer(&self, peer: String) -> Result<(), String>;
+	/// Disconnect the peer; it may connect again later
+	fn disconnect_peer(&self, peer: String) -> Result<(), String>;
+	/// Reputations of peers which misbehaved recently
+	fn peer_reputations(&self) -> Vec<PeerReputation>;
+	/// Forget the reputation of given peer or of all peers, lifting any bans
+	fn clear_peer_reputation(&self, peer: Option<String>) -> Result<(), String>;
+	/// Peer allow and deny lists
+	fn access_lists(&self) -> PeerAccessLists;
+	/// Add node id, enode URL or IP range to the allow (`true`) or deny (`false`) list.
+	/// Returns `false` if it's already there
+	fn add_access_entry(&self, allow: bool, entry: String) -> Result<bool, String>;
+	/// Remove node id, enode URL or IP range from the allow (`true`) or deny (`false`) list.
+	/// Returns `false` if it's not there
+	fn remove_access_entry(&self, allow: bool, entry: String) -> Result<bool, String>;
+	/// Replace the allow and deny lists
+	fn set_access_lists(&self, lists: PeerAccessLists) -> Result<(), String>;
+	/// Upload and download rate limits
+	fn bandwidth_limits(&self) -> BandwidthLimits;
+	/// Change upload and download rate limits, including those of established connections
+	fn set_bandwidth_limits(&self, limits: BandwidthLimits);
 	/// Start network
 	fn start_network(&self);
 	/// Stop network
 	fn stop_network(&self);
 	/// Query the current configuration of the network
 	fn network_config(&self) -> NetworkConfiguration;
+	/// Externally visible TCP port, taking NAT port mappings into account
+	fn external_port(&self) -> Option<u16>;
+}
+
+fn peer_reputations(network: &NetworkService) -> Vec<PeerReputation> {
+	network.reputations().into_iter().map(|(id, reputation)| PeerReputation {
+		id: format!("{:?}", id),
+		score: reputation.score,
+		bans: reputation.bans,
+		banned_for: reputation.banned_for(),
+	}).collect()
+}
+
+fn access_list(allow: bool) -> AccessList {
+	if allow { AccessList::Allow } else { AccessList::Deny }
+}
+
+fn access_lists(network: &NetworkService) -> PeerAccessLists {
+	let lists = network.access_lists().read();
+	let entries = |list| -> Vec<String> { lists.entries(list).iter().map(|e| e.to_string()).collect() };
+	PeerAccessLists {
+		allow: entries(AccessList::Allow),
+		deny: entries(AccessList::Deny),
+	}
+}
+
+fn add_access_entry(network: &NetworkService, allow: bool, entry: &str) -> Result<bool, String> {
+	let entry = entry.parse::<AccessEntry>()?;
+	Ok(network.access_lists().write().add(access_list(allow), entry))
+}
+
+fn remove_access_entry(network: &NetworkService, allow: bool, entry: &str) -> Result<bool, String> {
+	let entry = entry.parse::<AccessEntry>()?;
+	Ok(network.access_lists().write().remove(access_list(allow), &entry))
+}
+
+fn set_access_lists(network: &NetworkService, lists: PeerAccessLists) -> Result<(), String> {
+	let parse = |entries: Vec<String>| entries.iter().map(|e| e.parse()).collect::<Result<Vec<AccessEntry>, String>>();
+	let (allow, deny) = (parse(lists.allow)?, parse(lists.deny)?);
+	network.access_lists().write().set(allow, deny);
+	Ok(())
 }
 
 
 #[cfg_attr(feature = "ipc", ipc(client_ident="NetworkManagerClient"))]
+impl Drop for EthSync {
+	fn drop(&mut self) {
+		// hand the downloaded blocks over to the client, which persists its queue on shutdown.
+		self.network.with_context(self.subprotocol_name, |context| {
+			let mut sync_io = NetSyncIo::new(context, &*self.eth_handler.chain, &*self.eth_handler.snapshot_service, &self.eth_handler.overlay);
+			self.eth_handler.sync.write().flush_blocks(&mut sync_io);
+		});
+	}
+}
+
 impl ManageNetwork for EthSync {
 	fn accept_unreserved_peers(&self) {
 		self.network.set_non_reserved_mode(NonReservedPeerMode::Accept);
@@ -518,6 +792,42 @@ impl ManageNetwork for EthSync {
 		self.network.add_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
 	}
 
+	fn disconnect_peer(&self, peer: String) -> Result<(), String> {
+		self.network.disconnect_peer(&peer).map_err(|e| format!("{:?}", e))
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		peer_reputations(&self.network)
+	}
+
+	fn clear_peer_reputation(&self, peer: Option<String>) -> Result<(), String> {
+		self.network.clear_reputation(peer.as_ref().map(|p| p.as_str())).map_err(|e| format!("{:?}", e))
+	}
+
+	fn access_lists(&self) -> PeerAccessLists {
+		access_lists(&self.network)
+	}
+
+	fn add_access_entry(&self, allow: bool, entry: String) -> Result<bool, String> {
+		add_access_entry(&self.network, allow, &entry)
+	}
+
+	fn remove_access_entry(&self, allow: bool, entry: String) -> Result<bool, String> {
+		remove_access_entry(&self.network, allow, &entry)
+	}
+
+	fn set_access_lists(&self, lists: PeerAccessLists) -> Result<(), String> {
+		set_access_lists(&self.network, lists)
+	}
+
+	fn bandwidth_limits(&self) -> BandwidthLimits {
+		self.network.bandwidth_limits()
+	}
+
+	fn set_bandwidth_limits(&self, limits: BandwidthLimits) {
+		self.network.set_bandwidth_limits(limits)
+	}
+
 	fn start_network(&self) {
 		self.start();
 	}
@@ -538,6 +848,10 @@ impl ManageNetwork for EthSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn external_port(&self) -> Option<u16> {
+		self.network.external_port()
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -554,12 +868,16 @@ pub struct NetworkConfiguration {
 	pub public_address: Option<String>,
 	/// Port for UDP connections, same as TCP by default
 	pub udp_port: Option<u16>,
-	/// Enable NAT configuration
+	/// Enable NAT configuration with UPnP
 	pub nat_enabled: bool,
+	/// Enable NAT configuration with NAT-PMP
+	pub nat_pmp_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
+	/// Signed DNS node lists to discover nodes from
+	pub node_lists: Vec<String>,
 	/// Use provided node key instead of default
 	pub use_secret: Option<Secret>,
 	/// Max number of connected peers to maintain
@@ -568,6 +886,10 @@ pub struct NetworkConfiguration {
 	pub min_peers: u32,
 	/// Max pending peers.
 	pub max_pending_peers: u32,
+	/// Max inbound and max outbound peers sharing an IP address, 0 for no limit
+	pub max_peers_per_ip: u32,
+	/// Max inbound and max outbound peers sharing a /24 (IPv4) or /64 (IPv6) subnet, 0 for no limit
+	pub max_peers_per_subnet: u32,
 	/// Reserved snapshot sync peers.
 	pub snapshot_peers: u32,
 	/// List of reserved node addresses.
@@ -576,6 +898,10 @@ pub struct NetworkConfiguration {
 	pub allow_non_reserved: bool,
 	/// IP Filtering
 	pub ip_filter: IpFilter,
+	/// IP versions to use
+	pub ip_version: IpVersion,
+	/// Upload and download rate limits
+	pub bandwidth: BandwidthLimits,
 }
 
 impl NetworkConfiguration {
@@ -598,15 +924,21 @@ impl NetworkConfiguration {
 			public_address:  match self.public_address { None => None, Some(addr) => Some(SocketAddr::from_str(&addr)?) },
 			udp_port: self.udp_port,
 			nat_enabled: self.nat_enabled,
+			nat_pmp_enabled: self.nat_pmp_enabled,
 			discovery_enabled: self.discovery_enabled,
 			boot_nodes: self.boot_nodes,
+			node_lists: self.node_lists,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
 			min_peers: self.min_peers,
 			max_handshakes: self.max_pending_peers,
+			max_peers_per_ip: self.max_peers_per_ip,
+			max_peers_per_subnet: self.max_peers_per_subnet,
 			reserved_protocols: hash_map![WARP_SYNC_PROTOCOL_ID => self.snapshot_peers],
 			reserved_nodes: self.reserved_nodes,
 			ip_filter: self.ip_filter,
+			ip_version: self.ip_version,
+			bandwidth: self.bandwidth,
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
 		})
 	}
@@ -621,15 +953,21 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			public_address: other.public_address.and_then(|addr| Some(format!("{}", addr))),
 			udp_port: other.udp_port,
 			nat_enabled: other.nat_enabled,
+			nat_pmp_enabled: other.nat_pmp_enabled,
 			discovery_enabled: other.discovery_enabled,
 			boot_nodes: other.boot_nodes,
+			node_lists: other.node_lists,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
 			min_peers: other.min_peers,
 			max_pending_peers: other.max_handshakes,
+			max_peers_per_ip: other.max_peers_per_ip,
+			max_peers_per_subnet: other.max_peers_per_subnet,
 			snapshot_peers: *other.reserved_protocols.get(&WARP_SYNC_PROTOCOL_ID).unwrap_or(&0),
 			reserved_nodes: other.reserved_nodes,
 			ip_filter: other.ip_filter,
+			ip_version: other.ip_version,
+			bandwidth: other.bandwidth,
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
 		}
 	}
@@ -699,7 +1037,7 @@ pub struct LightSyncParams<L> {
 pub struct LightSync {
 	proto: Arc<LightProtocol>,
 	sync: Arc<::light_sync::SyncInfo + Sync + Send>,
-	attached_protos: Vec<AttachedProtocol>,
+	attached_protos: RwLock<Vec<AttachedProtocol>>,
 	network: NetworkService,
 	subprotocol_name: [u8; 3],
 	network_id: u64,
@@ -742,13 +1080,25 @@ impl LightSync {
 		Ok(LightSync {
 			proto: light_proto,
 			sync: sync,
-			attached_protos: params.attached_protos,
+			attached_protos: RwLock::new(params.attached_protos),
 			network: service,
 			subprotocol_name: params.subprotocol_name,
 			network_id: params.network_id,
 		})
 	}
 
+	/// Re-broadcast the given transactions to all relay peers.
+	pub fn resubmit_transactions(&self, hashes: &[H256]) {
+		self.proto.resubmit_transactions(hashes)
+	}
+
+	/// Attach a custom subprotocol to the network. It's registered right away if the network is running
+	/// and whenever it's started. Fails if the protocol ID is already taken or no versions are given.
+	#[cfg(not(feature = "ipc"))]
+	pub fn attach_protocol(&self, proto: AttachedProtocol) -> Result<(), NetworkError> {
+		attach_protocol(&self.network, &self.attached_protos, &[self.subprotocol_name], proto)
+	}
+
 	/// Execute a closure with a protocol context.
 	pub fn with_context<F, T>(&self, f: F) -> Option<T>
 		where F: FnOnce(&::light::net::BasicContext) -> T
@@ -783,6 +1133,42 @@ impl ManageNetwork for LightSync {
 		self.network.add_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
 	}
 
+	fn disconnect_peer(&self, peer: String) -> Result<(), String> {
+		self.network.disconnect_peer(&peer).map_err(|e| format!("{:?}", e))
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		peer_reputations(&self.network)
+	}
+
+	fn clear_peer_reputation(&self, peer: Option<String>) -> Result<(), String> {
+		self.network.clear_reputation(peer.as_ref().map(|p| p.as_str())).map_err(|e| format!("{:?}", e))
+	}
+
+	fn access_lists(&self) -> PeerAccessLists {
+		access_lists(&self.network)
+	}
+
+	fn add_access_entry(&self, allow: bool, entry: String) -> Result<bool, String> {
+		add_access_entry(&self.network, allow, &entry)
+	}
+
+	fn remove_access_entry(&self, allow: bool, entry: String) -> Result<bool, String> {
+		remove_access_entry(&self.network, allow, &entry)
+	}
+
+	fn set_access_lists(&self, lists: PeerAccessLists) -> Result<(), String> {
+		set_access_lists(&self.network, lists)
+	}
+
+	fn bandwidth_limits(&self) -> BandwidthLimits {
+		self.network.bandwidth_limits()
+	}
+
+	fn set_bandwidth_limits(&self, limits: BandwidthLimits) {
+		self.network.set_bandwidth_limits(limits)
+	}
+
 	fn start_network(&self) {
 		match self.network.start() {
 			Err(NetworkError::StdIo(ref e)) if  e.kind() == io::ErrorKind::AddrInUse => warn!("Network port {:?} is already in use, make sure that another instance of an Ethereum client is not running or change the port using the --port option.", self.network.config().listen_address.expect("Listen address is not set.")),
@@ -795,7 +1181,7 @@ impl ManageNetwork for LightSync {
 		self.network.register_protocol(light_proto, self.subprotocol_name, ::light::net::PACKET_COUNT, ::light::net::PROTOCOL_VERSIONS)
 			.unwrap_or_else(|e| warn!("Error registering light client protocol: {:?}", e));
 
-		for proto in &self.attached_protos { proto.register(&self.network) }
+		for proto in self.attached_protos.read().iter() { proto.register(&self.network) }
 	}
 
 	fn stop_network(&self) {
@@ -808,6 +1194,10 @@ impl ManageNetwork for LightSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn external_port(&self) -> Option<u16> {
+		self.network.external_port()
+	}
 }
 
 impl LightSyncProvider for LightSync {
@@ -838,6 +1228,8 @@ impl LightSyncProvider for LightSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					ping_ms: session_info.ping_ms,
+					traffic: ProtocolTrafficInfo::from_session(session_info.traffic),
 					eth_info: None,
 					pip_info: self.proto.peer_status(&peer_id).map(Into::into),
 				})
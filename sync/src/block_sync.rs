@@ -37,6 +37,10 @@ const MAX_RECEPITS_TO_REQUEST: usize = 128;
 const SUBCHAIN_SIZE: u64 = 256;
 const MAX_ROUND_PARENTS: usize = 16;
 const MAX_PARALLEL_SUBCHAIN_DOWNLOAD: usize = 5;
+const MIN_BATCH_SIZE: usize = 8;
+const MAX_BATCH_SIZE: usize = 256;
+// Requests are sized so that a peer is expected to serve them in this time.
+const TARGET_RESPONSE_MS: u64 = 1000;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Downloader state
@@ -66,6 +70,71 @@ pub enum BlockRequest {
 	},
 }
 
+/// Block body and receipt throughput of a peer, used to size requests to it.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct PeerThroughput {
+	/// Average block bodies served per second.
+	bodies: Option<f64>,
+	/// Average block receipts served per second.
+	receipts: Option<f64>,
+}
+
+impl PeerThroughput {
+	/// Number of block bodies to request from the peer.
+	pub fn bodies_batch(&self) -> usize {
+		Self::batch(self.bodies, MAX_BODIES_TO_REQUEST)
+	}
+
+	/// Number of block receipts to request from the peer.
+	pub fn receipts_batch(&self) -> usize {
+		Self::batch(self.receipts, MAX_RECEPITS_TO_REQUEST)
+	}
+
+	/// Note that the peer served `count` block bodies in `elapsed_ns` nanoseconds.
+	pub fn note_bodies(&mut self, count: usize, elapsed_ns: u64) {
+		Self::sample(&mut self.bodies, count, elapsed_ns)
+	}
+
+	/// Note that the peer served `count` block receipts in `elapsed_ns` nanoseconds.
+	pub fn note_receipts(&mut self, count: usize, elapsed_ns: u64) {
+		Self::sample(&mut self.receipts, count, elapsed_ns)
+	}
+
+	fn batch(rate: Option<f64>, default: usize) -> usize {
+		match rate {
+			Some(rate) => {
+				let batch = (rate * TARGET_RESPONSE_MS as f64 / 1000f64) as usize;
+				cmp::max(MIN_BATCH_SIZE, cmp::min(MAX_BATCH_SIZE, batch))
+			},
+			None => default,
+		}
+	}
+
+	fn sample(rate: &mut Option<f64>, count: usize, elapsed_ns: u64) {
+		if count == 0 {
+			return;
+		}
+		// don't let timer granularity inflate the estimate.
+		let elapsed = cmp::max(elapsed_ns, 1_000_000) as f64 / 1_000_000_000f64;
+		let sample = count as f64 / elapsed;
+		*rate = Some(match *rate {
+			Some(rate) => rate * 0.75 + sample * 0.25,
+			None => sample,
+		});
+	}
+}
+
+/// Numbers of headers, bodies and receipts downloaded so far.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct DownloadStats {
+	/// Block headers downloaded.
+	pub headers: u64,
+	/// Block bodies downloaded.
+	pub bodies: u64,
+	/// Block receipts downloaded.
+	pub receipts: u64,
+}
+
 /// Indicates sync action
 pub enum DownloadAction {
 	/// Do nothing
@@ -110,6 +179,8 @@ pub struct BlockDownloader {
 	retract_step: u64,
 	/// Whether reorg should be limited.
 	limit_reorg: bool,
+	/// Download statistics.
+	stats: DownloadStats,
 }
 
 impl BlockDownloader {
@@ -130,6 +201,7 @@ impl BlockDownloader {
 			target_hash: None,
 			retract_step: 1,
 			limit_reorg: true,
+			stats: DownloadStats::default(),
 		}
 	}
 
@@ -149,6 +221,7 @@ impl BlockDownloader {
 			target_hash: None,
 			retract_step: 1,
 			limit_reorg: false,
+			stats: DownloadStats::default(),
 		}
 	}
 
@@ -215,6 +288,11 @@ impl BlockDownloader {
 		self.last_imported_block
 	}
 
+	/// Returns download statistics.
+	pub fn stats(&self) -> DownloadStats {
+		self.stats
+	}
+
 	/// Add new block headers.
 	pub fn import_headers(&mut self, io: &mut SyncIo, r: &UntrustedRlp, expected_hash: Option<H256>) -> Result<DownloadAction, BlockDownloaderImportError> {
 		let item_count = r.item_count().unwrap_or(0);
@@ -307,6 +385,7 @@ impl BlockDownloader {
 					return Err(BlockDownloaderImportError::Useless);
 				}
 				self.blocks.insert_headers(headers);
+				self.stats.headers += count as u64;
 				trace!(target: "sync", "Inserted {} headers", count);
 			},
 			_ => trace!(target: "sync", "Unexpected headers({})", headers.len()),
@@ -337,6 +416,7 @@ impl BlockDownloader {
 				trace!(target: "sync", "Deactivating peer for giving invalid block bodies");
 				return Err(BlockDownloaderImportError::Invalid);
 			}
+			self.stats.bodies += item_count as u64;
 		}
 		Ok(())
 	}
@@ -363,6 +443,7 @@ impl BlockDownloader {
 				trace!(target: "sync", "Deactivating peer for giving invalid block receipts");
 				return Err(BlockDownloaderImportError::Invalid);
 			}
+			self.stats.receipts += item_count as u64;
 		}
 		Ok(())
 	}
@@ -415,12 +496,13 @@ impl BlockDownloader {
 	}
 
 	/// Find some headers or blocks to download for a peer.
-	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize) -> Option<BlockRequest> {
+	/// Bodies and receipts are requested in batches sized for the peer's throughput.
+	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize, throughput: &PeerThroughput) -> Option<BlockRequest> {
 		match self.state {
 			State::Idle => {
 				self.start_sync_round(io);
 				if self.state == State::ChainHead {
-					return self.request_blocks(io, num_active_peers);
+					return self.request_blocks(io, num_active_peers, throughput);
 				}
 			},
 			State::ChainHead => {
@@ -437,21 +519,27 @@ impl BlockDownloader {
 				}
 			},
 			State::Blocks => {
-				// check to see if we need to download any block bodies first
-				let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, false);
-				if !needed_bodies.is_empty() {
-					return Some(BlockRequest::Bodies {
-						hashes: needed_bodies,
-					});
-				}
-
-				if self.download_receipts {
-					let needed_receipts = self.blocks.needed_receipts(MAX_RECEPITS_TO_REQUEST, false);
-					if !needed_receipts.is_empty() {
-						return Some(BlockRequest::Receipts {
-							hashes: needed_receipts,
+				// don't download more bodies than the import queue can take.
+				let over_budget = self.queue_budget(io).map_or(false, |budget| self.blocks.heap_size() > budget);
+				if over_budget {
+					trace!(target: "sync", "Import queue memory budget exceeded, not requesting bodies");
+				} else {
+					// check to see if we need to download any block bodies first
+					let needed_bodies = self.blocks.needed_bodies(throughput.bodies_batch(), false);
+					if !needed_bodies.is_empty() {
+						return Some(BlockRequest::Bodies {
+							hashes: needed_bodies,
 						});
 					}
+
+					if self.download_receipts {
+						let needed_receipts = self.blocks.needed_receipts(throughput.receipts_batch(), false);
+						if !needed_receipts.is_empty() {
+							return Some(BlockRequest::Receipts {
+								hashes: needed_receipts,
+							});
+						}
+					}
 				}
 
 				// find subchain to download
@@ -470,9 +558,20 @@ impl BlockDownloader {
 
 	/// Checks if there are blocks fully downloaded that can be imported into the blockchain and does the import.
 	pub fn collect_blocks(&mut self, io: &mut SyncIo, allow_out_of_order: bool) -> Result<(), BlockDownloaderImportError> {
+		let budget = self.queue_budget(io).unwrap_or_else(usize::max_value);
+		self.import_downloaded(io, allow_out_of_order, budget)
+	}
+
+	/// Hands all fully downloaded blocks over to the blockchain, regardless of the queue budget.
+	/// Used on shutdown, so the downloaded blocks are persisted with the verification queue.
+	pub fn flush_blocks(&mut self, io: &mut SyncIo) -> Result<(), BlockDownloaderImportError> {
+		self.import_downloaded(io, false, usize::max_value())
+	}
+
+	fn import_downloaded(&mut self, io: &mut SyncIo, allow_out_of_order: bool, max_bytes: usize) -> Result<(), BlockDownloaderImportError> {
 		let mut bad = false;
 		let mut imported = HashSet::new();
-		let blocks = self.blocks.drain();
+		let blocks = self.blocks.drain(max_bytes);
 		let count = blocks.len();
 		for block_and_receipts in blocks {
 			let block = block_and_receipts.block;
@@ -544,6 +643,16 @@ impl BlockDownloader {
 		Ok(())
 	}
 
+	/// Number of bytes of downloaded blocks the verification queue may still take.
+	/// Ancient blocks are imported directly and aren't limited.
+	fn queue_budget(&self, io: &SyncIo) -> Option<usize> {
+		if self.download_receipts {
+			return None;
+		}
+		let queue_info = io.chain().queue_info();
+		Some(queue_info.max_mem_use.saturating_sub(queue_info.mem_used))
+	}
+
 	fn block_imported(&mut self, hash: &H256, number: BlockNumber, parent: &H256) {
 		self.last_imported_block = number;
 		self.last_imported_hash = hash.clone();
@@ -555,3 +664,39 @@ impl BlockDownloader {
 }
 
 //TODO: module tests
+
+#[cfg(test)]
+mod tests {
+	use super::{PeerThroughput, MAX_BODIES_TO_REQUEST, MAX_RECEPITS_TO_REQUEST, MIN_BATCH_SIZE, MAX_BATCH_SIZE};
+
+	#[test]
+	fn throughput_sizes_batches() {
+		let mut throughput = PeerThroughput::default();
+		assert_eq!(throughput.bodies_batch(), MAX_BODIES_TO_REQUEST);
+		assert_eq!(throughput.receipts_batch(), MAX_RECEPITS_TO_REQUEST);
+
+		// 64 bodies in 500ms is 128 bodies per second.
+		throughput.note_bodies(64, 500_000_000);
+		assert_eq!(throughput.bodies_batch(), 128);
+		assert_eq!(throughput.receipts_batch(), MAX_RECEPITS_TO_REQUEST);
+
+		// slow response moves the average down.
+		throughput.note_bodies(32, 4_000_000_000);
+		assert_eq!(throughput.bodies_batch(), 98);
+
+		// empty responses are ignored.
+		throughput.note_bodies(0, 10_000_000_000);
+		assert_eq!(throughput.bodies_batch(), 98);
+	}
+
+	#[test]
+	fn throughput_batches_are_bounded() {
+		let mut fast = PeerThroughput::default();
+		fast.note_receipts(128, 0);
+		assert_eq!(fast.receipts_batch(), MAX_BATCH_SIZE);
+
+		let mut slow = PeerThroughput::default();
+		slow.note_bodies(1, 60_000_000_000);
+		assert_eq!(slow.bodies_batch(), MIN_BATCH_SIZE);
+	}
+}
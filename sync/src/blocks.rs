@@ -265,7 +265,8 @@ impl BlockCollection {
 	}
 
 	/// Get a valid chain of blocks ordered in descending order and ready for importing into blockchain.
-	pub fn drain(&mut self) -> Vec<BlockAndReceipts> {
+	/// Stops once the blocks take more than `max_bytes`, but always drains at least one block.
+	pub fn drain(&mut self, max_bytes: usize) -> Vec<BlockAndReceipts> {
 		if self.blocks.is_empty() || self.head.is_none() {
 			return Vec::new();
 		}
@@ -274,12 +275,17 @@ impl BlockCollection {
 		let mut hashes = Vec::new();
 		{
 			let mut blocks = Vec::new();
+			let mut bytes = 0;
 			let mut head = self.head;
 			while let Some(h) = head {
 				head = self.parents.get(&h).cloned();
 				if let Some(head) = head {
 					match self.blocks.get(&head) {
 						Some(block) if block.body.is_some() && (!self.need_receipts || block.receipts.is_some()) => {
+							bytes += block.header.len() + block.body.as_ref().map_or(0, Vec::len);
+							if bytes > max_bytes && !blocks.is_empty() {
+								break;
+							}
 							blocks.push(block);
 							hashes.push(head);
 							self.head = Some(head);
@@ -543,7 +549,7 @@ mod test {
 		assert_eq!(hashes[0], h);
 		assert_eq!(n, 6);
 		assert_eq!(bc.downloading_headers.len(), 1);
-		assert!(bc.drain().is_empty());
+		assert!(bc.drain(usize::max_value()).is_empty());
 
 		bc.insert_headers(headers[0..6].to_vec());
 		assert_eq!(hashes[5], bc.heads[0]);
@@ -554,7 +560,7 @@ mod test {
 		assert!(!bc.is_downloading(&hashes[0]));
 		assert!(bc.contains(&hashes[0]));
 
-		assert_eq!(&bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>()[..], &blocks[0..6]);
+		assert_eq!(&bc.drain(usize::max_value()).into_iter().map(|b| b.block).collect::<Vec<_>>()[..], &blocks[0..6]);
 		assert!(!bc.contains(&hashes[0]));
 		assert_eq!(hashes[5], bc.head.unwrap());
 
@@ -563,13 +569,13 @@ mod test {
 		let (h, _) = bc.needed_headers(6, false).unwrap();
 		assert_eq!(hashes[20], h);
 		bc.insert_headers(headers[10..16].to_vec());
-		assert!(bc.drain().is_empty());
+		assert!(bc.drain(usize::max_value()).is_empty());
 		bc.insert_headers(headers[5..10].to_vec());
-		assert_eq!(&bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>()[..], &blocks[6..16]);
+		assert_eq!(&bc.drain(usize::max_value()).into_iter().map(|b| b.block).collect::<Vec<_>>()[..], &blocks[6..16]);
 		assert_eq!(hashes[15], bc.heads[0]);
 
 		bc.insert_headers(headers[15..].to_vec());
-		bc.drain();
+		bc.drain(usize::max_value());
 		assert!(bc.is_empty());
 	}
 
@@ -597,6 +603,24 @@ mod test {
 		assert_eq!(hashes[21], bc.heads[0]);
 	}
 
+	#[test]
+	fn drain_respects_byte_budget() {
+		let mut bc = BlockCollection::new(false);
+		let client = TestBlockChainClient::new();
+		let nblocks = 20;
+		client.add_blocks(nblocks, EachBlockWith::Nothing);
+		let blocks: Vec<_> = (0..nblocks)
+			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
+			.collect();
+		let headers: Vec<_> = blocks.iter().map(|b| Rlp::new(b).at(0).as_raw().to_vec()).collect();
+		let hashes: Vec<_> = headers.iter().map(|h| HeaderView::new(h).hash()).collect();
+		bc.reset_to(vec![hashes[0].clone()]);
+		bc.insert_headers(headers[0..6].to_vec());
+
+		assert_eq!(&bc.drain(0).into_iter().map(|b| b.block).collect::<Vec<_>>()[..], &blocks[0..1]);
+		assert_eq!(&bc.drain(usize::max_value()).into_iter().map(|b| b.block).collect::<Vec<_>>()[..], &blocks[1..6]);
+	}
+
 	#[test]
 	fn insert_headers_no_gap() {
 		let mut bc = BlockCollection::new(false);
@@ -613,9 +637,9 @@ mod test {
 		bc.reset_to(heads);
 
 		bc.insert_headers(headers[1..2].to_vec());
-		assert!(bc.drain().is_empty());
+		assert!(bc.drain(usize::max_value()).is_empty());
 		bc.insert_headers(headers[0..1].to_vec());
-		assert_eq!(bc.drain().len(), 2);
+		assert_eq!(bc.drain(usize::max_value()).len(), 2);
 	}
 }
 
@@ -89,8 +89,10 @@
 /// All other messages are ignored.
 ///
 
+use std::sync::Arc;
 use std::collections::{HashSet, HashMap};
 use std::cmp;
+use std::time::Instant;
 use hash::keccak;
 use heapsize::HeapSizeOf;
 use bigint::prelude::U256;
@@ -103,15 +105,22 @@ use ethcore::header::{BlockNumber, Header as BlockHeader};
 use ethcore::client::{BlockChainClient, BlockStatus, BlockId, BlockChainInfo, BlockImportError, BlockQueueInfo};
 use ethcore::error::*;
 use ethcore::snapshot::{ManifestData, RestorationStatus};
+use ethcore::spec::ForkId;
 use ethcore::transaction::PendingTransaction;
 use sync_io::SyncIo;
 use time;
 use super::SyncConfig;
-use block_sync::{BlockDownloader, BlockRequest, BlockDownloaderImportError as DownloaderImportError, DownloadAction};
+use block_sync::{BlockDownloader, BlockRequest, BlockDownloaderImportError as DownloaderImportError, DownloadAction, DownloadStats, PeerThroughput};
 use rand::Rng;
 use snapshot::{Snapshot, ChunkType};
-use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
+use snapshot_mirror::MirrorTask;
+use progress::ProgressMeter;
+use partition::{PartitionDetector, HeadStatus};
+use api::{EthProtocolInfo as PeerInfoDigest, PeerRequestStats, ForkPartition, WARP_SYNC_PROTOCOL_ID};
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
+use private_tx::PrivateTxHandler;
+use chunk_serving::ChunkServing;
+use rebroadcast::Rebroadcast;
 
 known_heap_size!(0, PeerInfo);
 
@@ -121,9 +130,12 @@ const PROTOCOL_VERSION_63: u8 = 63;
 const PROTOCOL_VERSION_62: u8 = 62;
 const PROTOCOL_VERSION_1: u8 = 1;
 const PROTOCOL_VERSION_2: u8 = 2;
+const PROTOCOL_VERSION_3: u8 = 3;
+const PROTOCOL_VERSION_4: u8 = 4;
 const MAX_BODIES_TO_SEND: usize = 256;
 const MAX_HEADERS_TO_SEND: usize = 512;
 const MAX_NODE_DATA_TO_SEND: usize = 1024;
+const MAX_NODE_DATA_TO_REQUEST: usize = 384;
 const MAX_RECEIPTS_TO_SEND: usize = 1024;
 const MAX_RECEIPTS_HEADERS_TO_SEND: usize = 256;
 const MIN_PEERS_PROPAGATION: usize = 4;
@@ -132,11 +144,18 @@ const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
 const MAX_NEW_HASHES: usize = 64;
 const MAX_TX_TO_IMPORT: usize = 512;
 const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
-const MAX_TRANSACTION_SIZE: usize = 300*1024;
 // maximal packet size with transactions (cannot be greater than 16MB - protocol limitation).
 const MAX_TRANSACTION_PACKET_SIZE: usize = 8 * 1024 * 1024;
 // Maximal number of transactions in sent in single packet.
 const MAX_TRANSACTIONS_TO_PROPAGATE: usize = 64;
+// Maximal number of transaction hashes announced in single packet.
+const MAX_TRANSACTION_HASHES_TO_PROPAGATE: usize = 4096;
+// Maximal number of announced transaction hashes processed from single packet.
+const MAX_TX_HASHES_TO_IMPORT: usize = 4096;
+// Maximal number of transactions requested from or served to a peer in single packet.
+const MAX_TRANSACTIONS_TO_REQUEST: usize = 256;
+// Maximal number of transactions remembered as known to a single peer.
+const MAX_KNOWN_TRANSACTIONS: usize = 32768;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 10000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
@@ -162,19 +181,29 @@ const SNAPSHOT_MANIFEST_PACKET: u8 = 0x12;
 const GET_SNAPSHOT_DATA_PACKET: u8 = 0x13;
 const SNAPSHOT_DATA_PACKET: u8 = 0x14;
 const CONSENSUS_DATA_PACKET: u8 = 0x15;
+const PRIVATE_TRANSACTION_PACKET: u8 = 0x16;
+const SIGNED_PRIVATE_TRANSACTION_PACKET: u8 = 0x17;
+const NEW_TRANSACTION_HASHES_PACKET: u8 = 0x18;
+const GET_TRANSACTIONS_PACKET: u8 = 0x19;
 
-pub const SNAPSHOT_SYNC_PACKET_COUNT: u8 = 0x16;
+pub const SNAPSHOT_SYNC_PACKET_COUNT: u8 = 0x1a;
 
 const MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD: usize = 3;
+/// Number of bytes of a served snapshot chunk read at once.
+const SNAPSHOT_READ_STEP: u64 = 64 * 1024;
 
 const WAIT_PEERS_TIMEOUT_SEC: u64 = 5;
 const STATUS_TIMEOUT_SEC: u64 = 5;
+const TRANSACTIONS_REQUEST_TIMEOUT_SEC: u64 = 20;
 const HEADERS_TIMEOUT_SEC: u64 = 15;
 const BODIES_TIMEOUT_SEC: u64 = 10;
 const RECEIPTS_TIMEOUT_SEC: u64 = 10;
 const FORK_HEADER_TIMEOUT_SEC: u64 = 3;
 const SNAPSHOT_MANIFEST_TIMEOUT_SEC: u64 = 5;
 const SNAPSHOT_DATA_TIMEOUT_SEC: u64 = 120;
+// Time without any snapshot chunk downloaded after which the snapshot is abandoned.
+const SNAPSHOT_STARVATION_TIMEOUT_SEC: u64 = 300;
+const NODE_DATA_TIMEOUT_SEC: u64 = 10;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -228,6 +257,18 @@ pub struct SyncStatus {
 	pub snapshot_chunks_done: usize,
 	/// Last fully downloaded and imported ancient block number (if any).
 	pub last_imported_old_block_number: Option<BlockNumber>,
+	/// Block headers downloaded so far.
+	pub headers_downloaded: u64,
+	/// Block bodies downloaded so far.
+	pub bodies_downloaded: u64,
+	/// Block receipts downloaded so far.
+	pub receipts_downloaded: u64,
+	/// Estimated number of seconds until the block download completes.
+	pub blocks_eta: Option<u64>,
+	/// Estimated number of seconds until all snapshot chunks are downloaded.
+	pub snapshot_eta: Option<u64>,
+	/// Number of peers following branches conflicting with the local chain.
+	pub num_conflicting_peers: usize,
 }
 
 impl SyncStatus {
@@ -265,6 +306,7 @@ enum PeerAsking {
 	BlockReceipts,
 	SnapshotManifest,
 	SnapshotData,
+	NodeData,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -306,10 +348,12 @@ struct PeerInfo {
 	asking_hash: Option<H256>,
 	/// Holds requested snapshot chunk hash if any.
 	asking_snapshot_data: Option<H256>,
+	/// Keys of the requested state nodes.
+	asking_state_nodes: Vec<H256>,
 	/// Request timestamp
 	ask_time: u64,
-	/// Holds a set of transactions recently sent to this peer to avoid spamming.
-	last_sent_transactions: HashSet<H256>,
+	/// Holds a set of transactions recently sent to, received from or announced by this peer to avoid spamming.
+	known_transactions: HashSet<H256>,
 	/// Pending request is expired and result should be ignored
 	expired: bool,
 	/// Peer fork confirmation status
@@ -320,6 +364,10 @@ struct PeerInfo {
 	snapshot_number: Option<BlockNumber>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
+	/// Block body and receipt throughput
+	throughput: PeerThroughput,
+	/// Outcome of the requests sent to the peer
+	requests: PeerRequestStats,
 }
 
 impl PeerInfo {
@@ -339,6 +387,18 @@ impl PeerInfo {
 			self.expired = true;
 		}
 	}
+
+	fn note_known_transactions<I: IntoIterator<Item=H256>>(&mut self, hashes: I) {
+		self.known_transactions.extend(hashes);
+		if self.known_transactions.len() > MAX_KNOWN_TRANSACTIONS {
+			// Forgetting only makes us resend some transactions to this peer.
+			self.known_transactions.clear();
+		}
+	}
+
+	fn supports_transaction_hashes(&self) -> bool {
+		self.protocol_version >= PROTOCOL_VERSION_4 && self.protocol_version < PROTOCOL_VERSION_62
+	}
 }
 
 #[cfg(not(test))]
@@ -384,17 +444,42 @@ pub struct ChainSync {
 	sync_start_time: Option<u64>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// Announced transactions requested from peers.
+	/// Value is request timestamp.
+	requested_transactions: H256FastMap<u64>,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
 	/// Enable warp sync.
 	enable_warp_sync: bool,
+	/// Manifest hash of the only snapshot allowed to be restored.
+	warp_snapshot_hash: Option<H256>,
+	/// Whether a snapshot mirror is downloading chunks.
+	mirror_active: bool,
+	/// Fetch the state on demand instead of downloading state chunks.
+	beam_sync: bool,
+	/// State nodes being downloaded.
+	downloading_state_nodes: HashSet<H256>,
+	/// Block download progress.
+	blocks_progress: ProgressMeter,
+	/// Snapshot download progress.
+	snapshot_progress: ProgressMeter,
+	/// Timestamp and number of downloaded chunks when the snapshot download last made progress.
+	snapshot_last_progress: Option<(u64, usize)>,
+	/// Distribution of peers across chain branches.
+	partition: PartitionDetector,
+	/// Handler of incoming private transactions.
+	private_tx_handler: Arc<PrivateTxHandler>,
+	/// Limits of the snapshot chunks served to peers.
+	chunk_serving: Arc<ChunkServing>,
+	/// Re-broadcast schedule of local transactions.
+	local_rebroadcast: Option<Rebroadcast>,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
 
 impl ChainSync {
 	/// Create a new instance of syncing strategy.
-	pub fn new(config: SyncConfig, chain: &BlockChainClient) -> ChainSync {
+	pub fn new(config: SyncConfig, chain: &BlockChainClient, private_tx_handler: Arc<PrivateTxHandler>) -> ChainSync {
 		let chain_info = chain.chain_info();
 		let mut sync = ChainSync {
 			state: if config.warp_sync { SyncState::WaitingPeers } else { SyncState::Idle },
@@ -412,7 +497,19 @@ impl ChainSync {
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			requested_transactions: H256FastMap::default(),
 			enable_warp_sync: config.warp_sync,
+			warp_snapshot_hash: config.warp_snapshot_hash,
+			mirror_active: false,
+			beam_sync: config.beam_sync,
+			downloading_state_nodes: HashSet::new(),
+			blocks_progress: ProgressMeter::default(),
+			snapshot_progress: ProgressMeter::default(),
+			snapshot_last_progress: None,
+			partition: PartitionDetector::default(),
+			private_tx_handler: private_tx_handler,
+			chunk_serving: Arc::new(ChunkServing::default()),
+			local_rebroadcast: config.local_tx_rebroadcast.map(Rebroadcast::new),
 		};
 		sync.update_targets(chain);
 		sync
@@ -421,6 +518,9 @@ impl ChainSync {
 	/// Returns synchonization status
 	pub fn status(&self) -> SyncStatus {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
+		let stats = self.old_blocks.as_ref().map_or(DownloadStats::default(), |d| d.stats());
+		let new_stats = self.new_blocks.stats();
+		let now = time::precise_time_ns();
 		SyncStatus {
 			state: self.state.clone(),
 			protocol_version: PROTOCOL_VERSION_63,
@@ -435,6 +535,12 @@ impl ChainSync {
 			num_active_peers: self.peers.values().filter(|p| p.is_allowed() && p.asking != PeerAsking::Nothing).count(),
 			num_snapshot_chunks: self.snapshot.total_chunks(),
 			snapshot_chunks_done: self.snapshot.done_chunks(),
+			headers_downloaded: stats.headers + new_stats.headers,
+			bodies_downloaded: stats.bodies + new_stats.bodies,
+			receipts_downloaded: stats.receipts + new_stats.receipts,
+			blocks_eta: self.highest_block.and_then(|highest| self.blocks_progress.eta(now, last_imported_number, highest)),
+			snapshot_eta: self.snapshot_progress.eta(now, self.snapshot.done_chunks() as u64, self.snapshot.total_chunks() as u64),
+			num_conflicting_peers: self.partition.partition().conflicting_peers,
 			mem_used:
 				self.new_blocks.heap_size()
 				+ self.old_blocks.as_ref().map_or(0, |d| d.heap_size())
@@ -449,6 +555,7 @@ impl ChainSync {
 				version: peer_data.protocol_version as u32,
 				difficulty: peer_data.difficulty,
 				head: peer_data.latest_hash,
+				requests: peer_data.requests,
 			}
 		})
 	}
@@ -461,7 +568,7 @@ impl ChainSync {
 	/// Updates transactions were received by a peer
 	pub fn transactions_received(&mut self, hashes: Vec<H256>, peer_id: PeerId) {
 		if let Some(mut peer_info) = self.peers.get_mut(&peer_id) {
-			peer_info.last_sent_transactions.extend(&hashes);
+			peer_info.note_known_transactions(hashes);
 		}
 	}
 
@@ -521,17 +628,21 @@ impl ChainSync {
 		// that it is higher than fork detection block
 		let our_best_block = io.chain().chain_info().best_block_number;
 		let fork_block = self.fork_block.as_ref().map(|&(n, _)| n).unwrap_or(0);
+		// A pinned snapshot is trusted regardless of its age and the number of peers serving it.
+		let pinned = self.warp_snapshot_hash;
 
 		let (best_hash, max_peers, snapshot_peers) = {
 			//collect snapshot infos from peers
 			let snapshots = self.peers.iter()
 				.filter(|&(_, p)| p.is_allowed() && p.snapshot_number.map_or(false, |sn|
-					our_best_block < sn && (sn - our_best_block) > SNAPSHOT_RESTORE_THRESHOLD &&
-					sn > fork_block &&
-					self.highest_block.map_or(true, |highest| highest >= sn && (highest - sn) <= SNAPSHOT_RESTORE_THRESHOLD)
+					our_best_block < sn && sn > fork_block && (pinned.is_some() || (
+						(sn - our_best_block) > SNAPSHOT_RESTORE_THRESHOLD &&
+						self.highest_block.map_or(true, |highest| highest >= sn && (highest - sn) <= SNAPSHOT_RESTORE_THRESHOLD)
+					))
 				))
 				.filter_map(|(p, peer)| peer.snapshot_hash.map(|hash| (p, hash.clone())))
-				.filter(|&(_, ref hash)| !self.snapshot.is_known_bad(hash));
+				.filter(|&(_, ref hash)| pinned.map_or(true, |pinned| pinned == *hash))
+				.filter(|&(_, ref hash)| !self.snapshot.is_known_bad(hash) && !self.snapshot.is_abandoned(hash));
 
 			let mut snapshot_peers = HashMap::new();
 			let mut max_peers: usize = 0;
@@ -550,13 +661,15 @@ impl ChainSync {
 		let timeout = (self.state == SyncState::WaitingPeers) && self.sync_start_time.map_or(false, |t| ((time::precise_time_ns() - t) / 1_000_000_000) > WAIT_PEERS_TIMEOUT_SEC);
 
 		if let (Some(hash), Some(peers)) = (best_hash, best_hash.map_or(None, |h| snapshot_peers.get(&h))) {
-			if max_peers >= SNAPSHOT_MIN_PEERS {
+			if max_peers >= SNAPSHOT_MIN_PEERS || pinned.is_some() {
 				trace!(target: "sync", "Starting confirmed snapshot sync {:?} with {:?}", hash, peers);
 				self.start_snapshot_sync(io, peers);
 			} else if timeout {
 				trace!(target: "sync", "Starting unconfirmed snapshot sync {:?} with {:?}", hash, peers);
 				self.start_snapshot_sync(io, peers);
 			}
+		} else if timeout && pinned.is_some() {
+			trace!(target: "sync", "Pinned snapshot {:?} not found, waiting for peers", pinned);
 		} else if timeout {
 			trace!(target: "sync", "No snapshots found, starting full sync");
 			self.state = SyncState::Idle;
@@ -620,13 +733,16 @@ impl ChainSync {
 			asking_blocks: Vec::new(),
 			asking_hash: None,
 			ask_time: 0,
-			last_sent_transactions: HashSet::new(),
+			known_transactions: HashSet::new(),
 			expired: false,
 			confirmation: if self.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
 			asking_snapshot_data: None,
+			asking_state_nodes: Vec::new(),
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
 			block_set: None,
+			throughput: PeerThroughput::default(),
+			requests: PeerRequestStats::default(),
 		};
 
 		if self.sync_start_time.is_none() {
@@ -655,11 +771,22 @@ impl ChainSync {
 			trace!(target: "sync", "Peer {} network id mismatch (ours: {}, theirs: {})", peer_id, self.network_id, peer.network_id);
 			return Ok(());
 		}
-		if (warp_protocol && peer.protocol_version != PROTOCOL_VERSION_1 && peer.protocol_version != PROTOCOL_VERSION_2) || (!warp_protocol && peer.protocol_version != PROTOCOL_VERSION_63 && peer.protocol_version != PROTOCOL_VERSION_62) {
+		if (warp_protocol && peer.protocol_version != PROTOCOL_VERSION_1 && peer.protocol_version != PROTOCOL_VERSION_2 && peer.protocol_version != PROTOCOL_VERSION_3 && peer.protocol_version != PROTOCOL_VERSION_4) || (!warp_protocol && peer.protocol_version != PROTOCOL_VERSION_63 && peer.protocol_version != PROTOCOL_VERSION_62) {
 			io.disable_peer(peer_id);
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
 			return Ok(());
 		}
+		// Fork identifier is appended to the status by warp protocol peers since version 3.
+		if warp_protocol && r.item_count()? > 7 {
+			let fork_id: ForkId = r.val_at(7)?;
+			// computed on demand, since forks may be rescheduled when the chain spec is reloaded.
+			let fork_filter = io.chain().fork_filter();
+			if !fork_filter.is_compatible(chain_info.best_block_number, &fork_id) {
+				io.disable_peer(peer_id);
+				debug!(target: "sync", "Peer {} fork id mismatch (ours: {:?}, theirs: {:?})", peer_id, fork_filter.fork_id(chain_info.best_block_number), fork_id);
+				return Ok(());
+			}
+		}
 
 		self.peers.insert(peer_id.clone(), peer);
 		// Don't activate peer immediatelly when searching for common block.
@@ -747,12 +874,13 @@ impl ChainSync {
 			downloader.import_headers(io, r, expected_hash)
 		};
 
+		self.note_response(peer_id, result.is_ok());
 		match result {
 			Err(DownloaderImportError::Useless) => {
 				self.deactivate_peer(io, peer_id);
 			},
 			Err(DownloaderImportError::Invalid) => {
-				io.disable_peer(peer_id);
+				io.report_peer(peer_id, Offense::BadBlock);
 				self.deactivate_peer(io, peer_id);
 				self.continue_sync(io);
 				return Ok(());
@@ -780,12 +908,16 @@ impl ChainSync {
 	fn on_peer_block_bodies(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
+		let ask_time = self.peers.get(&peer_id).map_or(0, |p| p.ask_time);
 		if !self.reset_peer_asking(peer_id, PeerAsking::BlockBodies) {
 			trace!(target: "sync", "{}: Ignored unexpected bodies", peer_id);
 			self.continue_sync(io);
 			return Ok(());
 		}
 		let item_count = r.item_count()?;
+		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
+			peer.throughput.note_bodies(item_count, time::precise_time_ns().saturating_sub(ask_time));
+		}
 		trace!(target: "sync", "{} -> BlockBodies ({} entries), set = {:?}", peer_id, item_count, block_set);
 		if item_count == 0 {
 			self.deactivate_peer(io, peer_id);
@@ -810,9 +942,10 @@ impl ChainSync {
 				downloader.import_bodies(io, r)
 			};
 
+			self.note_response(peer_id, result.is_ok());
 			match result {
 				Err(DownloaderImportError::Invalid) => {
-					io.disable_peer(peer_id);
+					io.report_peer(peer_id, Offense::BadBlock);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
 					return Ok(());
@@ -834,12 +967,16 @@ impl ChainSync {
 	fn on_peer_block_receipts(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
+		let ask_time = self.peers.get(&peer_id).map_or(0, |p| p.ask_time);
 		if !self.reset_peer_asking(peer_id, PeerAsking::BlockReceipts) {
 			trace!(target: "sync", "{}: Ignored unexpected receipts", peer_id);
 			self.continue_sync(io);
 			return Ok(());
 		}
 		let item_count = r.item_count()?;
+		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
+			peer.throughput.note_receipts(item_count, time::precise_time_ns().saturating_sub(ask_time));
+		}
 		trace!(target: "sync", "{} -> BlockReceipts ({} entries)", peer_id, item_count);
 		if item_count == 0 {
 			self.deactivate_peer(io, peer_id);
@@ -864,9 +1001,10 @@ impl ChainSync {
 				downloader.import_receipts(io, r)
 			};
 
+			self.note_response(peer_id, result.is_ok());
 			match result {
 				Err(DownloaderImportError::Invalid) => {
-					io.disable_peer(peer_id);
+					io.report_peer(peer_id, Offense::BadBlock);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
 					return Ok(());
@@ -914,10 +1052,11 @@ impl ChainSync {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
 		if last_imported_number > header.number() && last_imported_number - header.number() > MAX_NEW_BLOCK_AGE {
 			trace!(target: "sync", "Ignored ancient new block {:?}", h);
-			io.disable_peer(peer_id);
+			io.report_peer(peer_id, Offense::Spam);
 			return Ok(());
 		}
-		match io.chain().import_block(block_rlp.as_raw().to_vec()) {
+		let origin = io.peer_info(peer_id);
+		match io.chain().import_block_from(block_rlp.as_raw().to_vec(), origin) {
 			Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
 				trace!(target: "sync", "New block already in chain {:?}", h);
 			},
@@ -936,7 +1075,7 @@ impl ChainSync {
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
-				io.disable_peer(peer_id);
+				io.report_peer(peer_id, Offense::BadBlock);
 			}
 		};
 		if unknown {
@@ -990,7 +1129,7 @@ impl ChainSync {
 			}
 			if last_imported_number > number && last_imported_number - number > MAX_NEW_BLOCK_AGE {
 				trace!(target: "sync", "Ignored ancient new block hash {:?}", hash);
-				io.disable_peer(peer_id);
+				io.report_peer(peer_id, Offense::Spam);
 				continue;
 			}
 			match io.chain().block_status(BlockId::Hash(hash.clone())) {
@@ -1012,7 +1151,7 @@ impl ChainSync {
 				},
 				BlockStatus::Bad => {
 					debug!(target: "sync", "Bad new block hash {:?}", hash);
-					io.disable_peer(peer_id);
+					io.report_peer(peer_id, Offense::BadBlock);
 					return Ok(());
 				}
 			}
@@ -1044,7 +1183,7 @@ impl ChainSync {
 		let manifest = match ManifestData::from_rlp(manifest_rlp.as_raw()) {
 			Err(e) => {
 				trace!(target: "sync", "{}: Ignored bad manifest: {:?}", peer_id, e);
-				io.disable_peer(peer_id);
+				io.report_peer(peer_id, Offense::Malformed);
 				self.continue_sync(io);
 				return Ok(());
 			}
@@ -1060,7 +1199,17 @@ impl ChainSync {
 			self.continue_sync(io);
 			return Ok(());
 		}
-		self.snapshot.reset_to(&manifest, &keccak(manifest_rlp.as_raw()));
+		let manifest_hash = keccak(manifest_rlp.as_raw());
+		if self.warp_snapshot_hash.map_or(false, |pinned| pinned != manifest_hash) {
+			trace!(target: "sync", "{}: Ignored manifest {:?} not matching the pinned snapshot", peer_id, manifest_hash);
+			io.report_peer(peer_id, Offense::Malformed);
+			self.continue_sync(io);
+			return Ok(());
+		}
+		self.snapshot.reset_to(&manifest, &manifest_hash);
+		if self.beam_sync {
+			self.snapshot.skip_state_chunks();
+		}
 		io.snapshot_service().begin_restore(manifest);
 		self.state = SyncState::SnapshotData;
 
@@ -1084,11 +1233,67 @@ impl ChainSync {
 			return Ok(());
 		}
 
-		// check service status
+		if self.snapshot_restoration_aborted(io) {
+			trace!(target: "sync", "{}: Snapshot restoration aborted", peer_id);
+			return Ok(());
+		}
+		trace!(target: "sync", "{}: Snapshot restoration is ongoing", peer_id);
+
+		let snapshot_data: Bytes = r.val_at(0)?;
+		let chunk = self.snapshot.validate_chunk(&snapshot_data);
+		self.note_response(peer_id, chunk.is_ok());
+		match chunk {
+			Ok(ChunkType::Block(hash)) => {
+				trace!(target: "sync", "{}: Processing block chunk", peer_id);
+				io.snapshot_service().restore_block_chunk(hash, snapshot_data);
+			}
+			Ok(ChunkType::State(hash)) => {
+				trace!(target: "sync", "{}: Processing state chunk", peer_id);
+				io.snapshot_service().restore_state_chunk(hash, snapshot_data);
+			}
+			Err(()) => {
+				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+				io.report_peer(peer_id, Offense::Malformed);
+				self.continue_sync(io);
+				return Ok(());
+			}
+		}
+
+		if self.snapshot.is_complete() {
+			// wait for snapshot restoration process to complete
+			self.state = SyncState::SnapshotWaiting;
+		}
+		// give a task to the same peer first.
+		self.sync_peer(io, peer_id, false);
+		// give tasks to other peers
+		self.continue_sync(io);
+		Ok(())
+	}
+
+	/// Called when peer sends us state nodes
+	fn on_peer_node_data(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.clear_peer_download(peer_id);
+		if !self.reset_peer_asking(peer_id, PeerAsking::NodeData) {
+			trace!(target: "sync", "{}: Ignored unexpected node data", peer_id);
+			return Ok(());
+		}
+
+		let nodes: Vec<Bytes> = r.as_list()?;
+		let received = nodes.len();
+		let imported = io.chain().import_state(nodes);
+		trace!(target: "sync", "{} -> NodeData: {} entries, {} imported", peer_id, received, imported);
+		self.note_response(peer_id, imported > 0);
+
+		self.request_state_nodes(io);
+		self.sync_peer(io, peer_id, false);
+		Ok(())
+	}
+
+	/// Resets snapshot sync if the snapshot service is no longer restoring.
+	fn snapshot_restoration_aborted(&mut self, io: &mut SyncIo) -> bool {
 		let status = io.snapshot_service().status();
 		match status {
 			RestorationStatus::Inactive | RestorationStatus::Failed => {
-				trace!(target: "sync", "{}: Snapshot restoration aborted", peer_id);
 				self.state = SyncState::WaitingPeers;
 
 				// only note bad if restoration failed.
@@ -1099,28 +1304,126 @@ impl ChainSync {
 
 				self.snapshot.clear();
 				self.continue_sync(io);
-				return Ok(());
-			},
-			RestorationStatus::Ongoing { .. } => {
-				trace!(target: "sync", "{}: Snapshot restoration is ongoing", peer_id);
+				true
 			},
+			RestorationStatus::Ongoing { .. } => false,
 		}
+	}
 
-		let snapshot_data: Bytes = r.val_at(0)?;
-		match self.snapshot.validate_chunk(&snapshot_data) {
+	/// Called when a snapshot manifest is downloaded from a mirror.
+	/// Starts restoring it unless another snapshot is being synced already.
+	/// Returns the manifest hash if the mirror should download its chunks.
+	pub fn on_mirror_manifest(&mut self, io: &mut SyncIo, manifest_rlp: &[u8]) -> Option<H256> {
+		let hash = keccak(manifest_rlp);
+		if self.snapshot.snapshot_hash() == Some(hash) {
+			self.mirror_active = true;
+			return Some(hash);
+		}
+		if !self.enable_warp_sync || self.snapshot.have_manifest() || self.snapshot.is_known_bad(&hash) {
+			return None;
+		}
+		if self.state != SyncState::WaitingPeers && self.state != SyncState::Blocks && self.state != SyncState::Waiting {
+			return None;
+		}
+		if self.warp_snapshot_hash.map_or(false, |pinned| pinned != hash) {
+			trace!(target: "sync", "Ignored mirror manifest {:?} not matching the pinned snapshot", hash);
+			return None;
+		}
+
+		let manifest = match ManifestData::from_rlp(manifest_rlp) {
+			Err(e) => {
+				trace!(target: "sync", "Ignored bad mirror manifest: {:?}", e);
+				return None;
+			}
+			Ok(manifest) => manifest,
+		};
+		let is_supported_version = io.snapshot_service().supported_versions()
+			.map_or(false, |(l, h)| manifest.version >= l && manifest.version <= h);
+		if !is_supported_version {
+			trace!(target: "sync", "Mirror snapshot manifest version not supported: {}", manifest.version);
+			return None;
+		}
+
+		// same restrictions as for snapshots offered by peers.
+		let our_best_block = io.chain().chain_info().best_block_number;
+		let fork_block = self.fork_block.as_ref().map(|&(n, _)| n).unwrap_or(0);
+		let sn = manifest.block_number;
+		if sn <= our_best_block || sn <= fork_block || (self.warp_snapshot_hash.is_none() && (sn - our_best_block) <= SNAPSHOT_RESTORE_THRESHOLD) {
+			trace!(target: "sync", "Ignored mirror snapshot at block {}", sn);
+			return None;
+		}
+
+		trace!(target: "sync", "Starting snapshot sync {:?} from mirror", hash);
+		self.snapshot.reset_to(&manifest, &hash);
+		if self.beam_sync {
+			self.snapshot.skip_state_chunks();
+		}
+		io.snapshot_service().begin_restore(manifest);
+		self.state = SyncState::SnapshotData;
+		self.mirror_active = true;
+		self.continue_sync(io);
+		Some(hash)
+	}
+
+	/// Find a chunk of given snapshot for a mirror to download.
+	pub fn next_mirror_chunk(&mut self, io: &mut SyncIo, snapshot_hash: &H256) -> MirrorTask {
+		if self.snapshot.snapshot_hash().as_ref() != Some(snapshot_hash) {
+			return MirrorTask::Done;
+		}
+		match self.state {
+			SyncState::SnapshotData => {},
+			SyncState::SnapshotWaiting if !self.snapshot.is_complete() => return MirrorTask::Wait,
+			_ => return MirrorTask::Done,
+		}
+		if self.pause_snapshot_download(io) {
+			return MirrorTask::Wait;
+		}
+		match self.snapshot.needed_chunk() {
+			Some(hash) => MirrorTask::Chunk(hash),
+			None if self.snapshot.is_complete() => MirrorTask::Done,
+			None => MirrorTask::Wait,
+		}
+	}
+
+	/// Called when a mirror finished downloading a chunk. Chunks which could not be downloaded
+	/// are left for the peers.
+	pub fn on_mirror_chunk(&mut self, io: &mut SyncIo, hash: H256, chunk: Option<Bytes>) {
+		let chunk = match chunk {
+			Some(chunk) => chunk,
+			None => {
+				self.snapshot.clear_chunk_download(&hash);
+				self.continue_sync(io);
+				return;
+			}
+		};
+		if keccak(&chunk) != hash {
+			trace!(target: "sync", "Got unexpected chunk from mirror, expected {:?}", hash);
+			self.snapshot.clear_chunk_download(&hash);
+			self.continue_sync(io);
+			return;
+		}
+		if self.state != SyncState::SnapshotData && self.state != SyncState::SnapshotWaiting {
+			trace!(target: "sync", "Ignored unexpected mirror chunk");
+			return;
+		}
+		if self.snapshot_restoration_aborted(io) {
+			trace!(target: "sync", "Snapshot restoration aborted");
+			return;
+		}
+
+		match self.snapshot.validate_chunk(&chunk) {
 			Ok(ChunkType::Block(hash)) => {
-				trace!(target: "sync", "{}: Processing block chunk", peer_id);
-				io.snapshot_service().restore_block_chunk(hash, snapshot_data);
+				trace!(target: "sync", "Processing block chunk from mirror");
+				io.snapshot_service().restore_block_chunk(hash, chunk);
 			}
 			Ok(ChunkType::State(hash)) => {
-				trace!(target: "sync", "{}: Processing state chunk", peer_id);
-				io.snapshot_service().restore_state_chunk(hash, snapshot_data);
+				trace!(target: "sync", "Processing state chunk from mirror");
+				io.snapshot_service().restore_state_chunk(hash, chunk);
 			}
 			Err(()) => {
-				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
-				io.disconnect_peer(peer_id);
+				trace!(target: "sync", "Got bad snapshot chunk from mirror");
 				self.continue_sync(io);
-				return Ok(());
+				return;
 			}
 		}
 
@@ -1128,11 +1431,13 @@ impl ChainSync {
 			// wait for snapshot restoration process to complete
 			self.state = SyncState::SnapshotWaiting;
 		}
-		// give a task to the same peer first.
-		self.sync_peer(io, peer_id, false);
-		// give tasks to other peers
 		self.continue_sync(io);
-		Ok(())
+	}
+
+	/// Called when a mirror stopped downloading chunks.
+	pub fn on_mirror_finished(&mut self, io: &mut SyncIo) {
+		self.mirror_active = false;
+		self.continue_sync(io);
 	}
 
 	/// Called by peer when it is disconnecting
@@ -1173,6 +1478,7 @@ impl ChainSync {
 			}
 		}
 		if (self.state != SyncState::WaitingPeers && self.state != SyncState::SnapshotWaiting && self.state != SyncState::Waiting && self.state != SyncState::Idle)
+			&& !(self.mirror_active && self.state == SyncState::SnapshotData)
 			&& !self.peers.values().any(|p| p.asking != PeerAsking::Nothing && p.block_set != Some(BlockSet::OldBlocks) && p.can_sync()) {
 
 			self.complete_sync(io);
@@ -1198,7 +1504,7 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer {}", peer_id);
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash, peer_throughput) = {
 			if let Some(peer) = self.peers.get_mut(&peer_id) {
 				if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
 					trace!(target: "sync", "Skipping busy peer {}", peer_id);
@@ -1212,7 +1518,7 @@ impl ChainSync {
 					trace!(target: "sync", "Waiting for the snapshot restoration");
 					return;
 				}
-				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned())
+				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned(), peer.throughput.clone())
 			} else {
 				return;
 			}
@@ -1239,7 +1545,7 @@ impl ChainSync {
 					if !have_latest && (higher_difficulty || force || self.state == SyncState::NewBlocks) {
 						// check if got new blocks to download
 						trace!(target: "sync", "Syncing with peer {}, force={}, td={:?}, our td={}, state={:?}", peer_id, force, peer_difficulty, syncing_difficulty, self.state);
-						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers) {
+						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers, &peer_throughput) {
 							self.request_blocks(io, peer_id, request, BlockSet::NewBlocks);
 							if self.state == SyncState::Idle {
 								self.state = SyncState::Blocks;
@@ -1248,18 +1554,14 @@ impl ChainSync {
 						}
 					}
 
-					if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers)) {
+					if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers, &peer_throughput)) {
 						self.request_blocks(io, peer_id, request, BlockSet::OldBlocks);
 						return;
 					}
 				},
 				SyncState::SnapshotData => {
-					if let RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } = io.snapshot_service().status() {
-						if self.snapshot.done_chunks() - (state_chunks_done + block_chunks_done) as usize > MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
-							trace!(target: "sync", "Snapshot queue full, pausing sync");
-							self.state = SyncState::SnapshotWaiting;
-							return;
-						}
+					if self.pause_snapshot_download(io) {
+						return;
 					}
 					if peer_snapshot_hash.is_some() && peer_snapshot_hash == self.snapshot.snapshot_hash() {
 						self.request_snapshot_data(io, peer_id);
@@ -1273,6 +1575,25 @@ impl ChainSync {
 		}
 	}
 
+	/// Pause snapshot download if too many chunks are waiting to be restored.
+	fn pause_snapshot_download(&mut self, io: &mut SyncIo) -> bool {
+		if let RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } = io.snapshot_service().status() {
+			let restored = (state_chunks_done + block_chunks_done) as usize;
+			// chunks restored before a restart don't have to be downloaded again.
+			if self.snapshot.done_chunks() < restored {
+				if let Some(completed) = io.snapshot_service().completed_chunks() {
+					self.snapshot.note_completed(completed);
+				}
+			}
+			if self.snapshot.done_chunks().saturating_sub(restored) > MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
+				trace!(target: "sync", "Snapshot queue full, pausing sync");
+				self.state = SyncState::SnapshotWaiting;
+				return true;
+			}
+		}
+		false
+	}
+
 	/// Perofrm block download request`
 	fn request_blocks(&mut self, io: &mut SyncIo, peer_id: PeerId, request: BlockRequest, block_set: BlockSet) {
 		match request {
@@ -1329,6 +1650,11 @@ impl ChainSync {
 						self.snapshot.clear_chunk_download(&hash);
 					}
 				},
+				PeerAsking::NodeData => {
+					for key in peer.asking_state_nodes.drain(..) {
+						self.downloading_state_nodes.remove(&key);
+					}
+				},
 				_ => (),
 			}
 		}
@@ -1354,6 +1680,18 @@ impl ChainSync {
 		}
 	}
 
+	/// Hands all fully downloaded blocks over to the blockchain. Called on shutdown.
+	pub fn flush_blocks(&mut self, io: &mut SyncIo) {
+		if self.new_blocks.flush_blocks(io).is_err() {
+			debug!(target: "sync", "Discarded invalid downloaded blocks");
+		}
+		if let Some(ref mut downloader) = self.old_blocks {
+			if downloader.flush_blocks(io).is_err() {
+				debug!(target: "sync", "Discarded invalid downloaded ancient blocks");
+			}
+		}
+	}
+
 	/// Request headers from a peer by block hash
 	#[cfg_attr(feature="dev", allow(too_many_arguments))]
 	fn request_headers_by_hash(&mut self, sync: &mut SyncIo, peer_id: PeerId, h: &H256, count: u64, skip: u64, reverse: bool, set: BlockSet) {
@@ -1396,6 +1734,42 @@ impl ChainSync {
 		self.send_request(sync, peer_id, PeerAsking::SnapshotData, GET_SNAPSHOT_DATA_PACKET, rlp.out());
 	}
 
+	/// Request state nodes missing from the database from idle peers while beam syncing.
+	fn request_state_nodes(&mut self, io: &mut SyncIo) {
+		if !self.beam_sync {
+			return;
+		}
+
+		let peers: Vec<PeerId> = self.peers.iter()
+			.filter(|&(_, p)| p.asking == PeerAsking::Nothing && p.can_sync() && p.protocol_version != PROTOCOL_VERSION_62)
+			.map(|(id, _)| *id)
+			.collect();
+		if peers.is_empty() {
+			return;
+		}
+
+		let mut missing: Vec<H256> = io.chain().missing_state(peers.len() * MAX_NODE_DATA_TO_REQUEST).into_iter()
+			.filter(|key| !self.downloading_state_nodes.contains(key))
+			.collect();
+		for peer_id in peers {
+			if missing.is_empty() {
+				break;
+			}
+			let count = cmp::min(missing.len(), MAX_NODE_DATA_TO_REQUEST);
+			let keys: Vec<H256> = missing.drain(..count).collect();
+			trace!(target: "sync", "{} <- GetNodeData: {} entries", peer_id, keys.len());
+			let mut rlp = RlpStream::new_list(keys.len());
+			for key in &keys {
+				rlp.append(key);
+				self.downloading_state_nodes.insert(*key);
+			}
+			self.send_request(io, peer_id, PeerAsking::NodeData, GET_NODE_DATA_PACKET, rlp.out());
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.asking_state_nodes = keys;
+			}
+		}
+	}
+
 	/// Request block bodies from a peer
 	fn request_bodies(&mut self, sync: &mut SyncIo, peer_id: PeerId, hashes: Vec<H256>, set: BlockSet) {
 		let mut rlp = RlpStream::new_list(hashes.len());
@@ -1422,6 +1796,17 @@ impl ChainSync {
 		peer.block_set = Some(set);
 	}
 
+	/// Note whether a response of a peer provided data that was used.
+	fn note_response(&mut self, peer_id: PeerId, useful: bool) {
+		if let Some(peer) = self.peers.get_mut(&peer_id) {
+			if useful {
+				peer.requests.useful += 1;
+			} else {
+				peer.requests.failed += 1;
+			}
+		}
+	}
+
 	/// Reset peer status after request is complete.
 	fn reset_peer_asking(&mut self, peer_id: PeerId, asking: PeerAsking) -> bool {
 		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
@@ -1447,6 +1832,7 @@ impl ChainSync {
 			}
 			peer.asking = asking;
 			peer.ask_time = time::precise_time_ns();
+			peer.requests.sent += 1;
 			let result = if packet_id >= ETH_PACKET_COUNT {
 				sync.send_protocol(WARP_SYNC_PROTOCOL_ID, peer_id, packet_id, packet)
 			} else {
@@ -1459,9 +1845,21 @@ impl ChainSync {
 		}
 	}
 
-	/// Generic packet sender
+	/// Generic packet sender. Block announcements and consensus messages overtake other queued packets.
 	fn send_packet(&mut self, sync: &mut SyncIo, peer_id: PeerId, packet_id: PacketId, packet: Bytes) {
-		if let Err(e) = sync.send(peer_id, packet_id, packet) {
+		let priority = match packet_id {
+			NEW_BLOCK_PACKET | NEW_BLOCK_HASHES_PACKET | CONSENSUS_DATA_PACKET => PacketPriority::High,
+			_ => PacketPriority::Normal,
+		};
+		if let Err(e) = sync.send_with_priority(peer_id, packet_id, packet, priority) {
+			debug!(target:"sync", "Error sending packet: {:?}", e);
+			sync.disable_peer(peer_id);
+		}
+	}
+
+	/// Warp protocol packet sender. Used for packets that may be sent from the context of the eth protocol.
+	fn send_warp_packet(&mut self, sync: &mut SyncIo, peer_id: PeerId, packet_id: PacketId, packet: Bytes) {
+		if let Err(e) = sync.send_protocol(WARP_SYNC_PROTOCOL_ID, peer_id, packet_id, packet) {
 			debug!(target:"sync", "Error sending packet: {:?}", e);
 			sync.disable_peer(peer_id);
 		}
@@ -1482,26 +1880,115 @@ impl ChainSync {
 		trace!(target: "sync", "{:02} -> Transactions ({} entries)", peer_id, item_count);
 		item_count = cmp::min(item_count, MAX_TX_TO_IMPORT);
 		let mut transactions = Vec::with_capacity(item_count);
+		let mut hashes = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
-			let rlp = r.at(i)?;
-			if rlp.as_raw().len() > MAX_TRANSACTION_SIZE {
-				debug!("Skipped oversized transaction of {} bytes", rlp.as_raw().len());
-				continue;
-			}
-			let tx = rlp.as_raw().to_vec();
+			// size is validated against the chain spec when the transaction is imported to the queue
+			let tx = r.at(i)?.as_raw().to_vec();
+			hashes.push(keccak(&tx));
 			transactions.push(tx);
 		}
+		for hash in &hashes {
+			self.requested_transactions.remove(hash);
+		}
+		if let Some(peer_info) = self.peers.get_mut(&peer_id) {
+			peer_info.note_known_transactions(hashes);
+		}
 		io.chain().queue_transactions(transactions, peer_id);
 		Ok(())
 	}
 
+	/// Called when peer announces hashes of new transactions
+	fn on_peer_new_transaction_hashes(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		// Accept transactions only when fully synced
+		if !io.is_chain_queue_empty() || (self.state != SyncState::Idle && self.state != SyncState::NewBlocks) {
+			trace!(target: "sync", "{} Ignoring transaction hashes while syncing", peer_id);
+			return Ok(());
+		}
+		if !self.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
+			trace!(target: "sync", "{} Ignoring transaction hashes from unconfirmed/unknown peer", peer_id);
+			return Ok(());
+		}
+
+		let item_count = cmp::min(r.item_count()?, MAX_TX_HASHES_TO_IMPORT);
+		trace!(target: "sync", "{:02} -> NewTransactionHashes ({} entries)", peer_id, item_count);
+		let mut hashes = Vec::with_capacity(item_count);
+		for i in 0 .. item_count {
+			hashes.push(r.val_at::<H256>(i)?);
+		}
+		if let Some(peer_info) = self.peers.get_mut(&peer_id) {
+			peer_info.note_known_transactions(hashes.iter().cloned());
+		}
+
+		let to_request = hashes.into_iter()
+			.filter(|hash| !self.requested_transactions.contains_key(hash))
+			.take(MAX_TRANSACTIONS_TO_REQUEST)
+			.filter(|hash| io.chain().pending_transaction(hash).is_none())
+			.collect::<Vec<_>>();
+		if to_request.is_empty() {
+			return Ok(());
+		}
+
+		let now = time::precise_time_ns();
+		let mut packet = RlpStream::new_list(to_request.len());
+		for hash in &to_request {
+			packet.append(hash);
+			self.requested_transactions.insert(*hash, now);
+		}
+		trace!(target: "sync", "{:02} <- GetTransactions ({} entries)", peer_id, to_request.len());
+		self.send_warp_packet(io, peer_id, GET_TRANSACTIONS_PACKET, packet.out());
+		Ok(())
+	}
+
+	/// Called when peer requests transactions it has been announced
+	fn on_peer_get_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		let item_count = cmp::min(r.item_count()?, MAX_TRANSACTIONS_TO_REQUEST);
+		trace!(target: "sync", "{:02} -> GetTransactions ({} entries)", peer_id, item_count);
+		let mut requested = HashSet::with_capacity(item_count);
+		for i in 0 .. item_count {
+			requested.insert(r.val_at::<H256>(i)?);
+		}
+		if requested.is_empty() {
+			return Ok(());
+		}
+
+		let block_number = io.chain().chain_info().best_block_number;
+		let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+		let mut sent = Vec::new();
+		let mut packet = RlpStream::new();
+		packet.begin_unbounded_list();
+		for hash in requested.iter() {
+			let tx = match io.chain().pending_transaction(hash) {
+				Some(tx) => tx,
+				None => continue,
+			};
+			let mut transaction = RlpStream::new();
+			tx.transaction.rlp_append(&mut transaction);
+			if !packet.append_raw_checked(&transaction.drain(), 1, MAX_TRANSACTION_PACKET_SIZE) {
+				debug!(target: "sync", "Transaction packet size limit reached. Sending incomplete set of {}/{} transactions.", sent.len(), requested.len());
+				break;
+			}
+			self.transactions_stats.propagated(hash, id, block_number);
+			sent.push(*hash);
+		}
+		packet.complete_unbounded_list();
+
+		trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent.len());
+		if let Some(peer_info) = self.peers.get_mut(&peer_id) {
+			peer_info.note_known_transactions(sent);
+		}
+		io.respond(TRANSACTIONS_PACKET, packet.out()).unwrap_or_else(
+			|e| debug!(target: "sync", "Error sending transactions: {:?}", e));
+		Ok(())
+	}
+
 	/// Send Status message
 	fn send_status(&mut self, io: &mut SyncIo, peer: PeerId) -> Result<(), NetworkError> {
 		let warp_protocol_version = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer);
 		let warp_protocol = warp_protocol_version != 0;
 		let protocol = if warp_protocol { warp_protocol_version } else { PROTOCOL_VERSION_63 };
 		trace!(target: "sync", "Sending status to {}, protocol version {}", peer, protocol);
-		let mut packet = RlpStream::new_list(if warp_protocol { 7 } else { 5 });
+		let with_fork_id = warp_protocol_version >= PROTOCOL_VERSION_3;
+		let mut packet = RlpStream::new_list(if with_fork_id { 8 } else if warp_protocol { 7 } else { 5 });
 		let chain = io.chain().chain_info();
 		packet.append(&(protocol as u32));
 		packet.append(&self.network_id);
@@ -1518,6 +2005,9 @@ impl ChainSync {
 			packet.append(&manifest_hash);
 			packet.append(&block_number);
 		}
+		if with_fork_id {
+			packet.append(&io.chain().fork_filter().fork_id(chain.best_block_number));
+		}
 		io.respond(STATUS_PACKET, packet.out())
 	}
 
@@ -1689,21 +2179,69 @@ impl ChainSync {
 	}
 
 	/// Respond to GetSnapshotData request
-	fn return_snapshot_data(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
+	/// Responds with the requested chunk, read piecewise into the packet. The chunk is
+	/// accounted against the serving limits until it's handed over to the network.
+	fn return_snapshot_data(io: &mut SyncIo, r: &UntrustedRlp, peer_id: PeerId, serving: &ChunkServing) -> Result<(), PacketDecodeError> {
 		let hash: H256 = r.val_at(0)?;
 		trace!(target: "sync", "{} -> GetSnapshotData {:?}", peer_id, hash);
-		let rlp = match io.snapshot_service().chunk(hash) {
-			Some(data) => {
-				let mut rlp = RlpStream::new_list(1);
+		let size = io.snapshot_service().chunk_size(hash);
+		let reservation = size.and_then(|size| serving.reserve(peer_id, size));
+		let rlp = match (size, reservation.is_some()) {
+			(Some(size), true) => {
+				let service = io.snapshot_service();
+				ChainSync::snapshot_data_packet(size, |offset, len| service.chunk_range(hash, offset, len))
+			},
+			(Some(_), false) => {
+				debug!(target: "sync", "{}: Not serving chunk {:?}, serving limits reached", peer_id, hash);
+				None
+			},
+			(None, _) => None,
+		};
+		let rlp = match rlp {
+			Some(rlp) => {
 				trace!(target: "sync", "{} <- SnapshotData", peer_id);
-				rlp.append(&data);
 				rlp
 			},
-			None => {
-				RlpStream::new_list(0)
-			}
+			None => RlpStream::new_list(0),
 		};
-		Ok(Some((SNAPSHOT_DATA_PACKET, rlp)))
+		io.respond_with_priority(SNAPSHOT_DATA_PACKET, rlp.out(), PacketPriority::Low).unwrap_or_else(
+			|e| debug!(target: "sync", "Error sending snapshot data: {:?}", e));
+		Ok(())
+	}
+
+	/// Builds the `SnapshotData` packet of a chunk of given size, reading it piecewise.
+	fn snapshot_data_packet<F>(size: u64, read: F) -> Option<RlpStream> where F: Fn(u64, u64) -> Option<Bytes> {
+		let mut rlp = RlpStream::new_list(1);
+		if size < 56 {
+			return read(0, size).map(|data| {
+				rlp.append(&data);
+				rlp
+			});
+		}
+
+		// long byte string header: 0xb7 + length of the big endian size, followed by the size.
+		let size_bytes: Vec<u8> = (0..8).rev()
+			.map(|i| (size >> (i * 8)) as u8)
+			.skip_while(|b| *b == 0)
+			.collect();
+		let mut header = vec![0xb7 + size_bytes.len() as u8];
+		header.extend(size_bytes);
+		rlp.append_raw(&header, 0);
+
+		let mut offset = 0;
+		while offset < size {
+			let len = cmp::min(SNAPSHOT_READ_STEP, size - offset);
+			let data = match read(offset, len) {
+				Some(data) => data,
+				None => return None,
+			};
+			if data.len() as u64 != len {
+				return None;
+			}
+			offset += len;
+			rlp.append_raw(&data, if offset == size { 1 } else { 0 });
+		}
+		Some(rlp)
 	}
 
 	fn return_rlp<FRlp, FError>(io: &mut SyncIo, rlp: &UntrustedRlp, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
@@ -1714,7 +2252,8 @@ impl ChainSync {
 		match response {
 			Err(e) => Err(e),
 			Ok(Some((packet_id, rlp_stream))) => {
-				io.respond(packet_id, rlp_stream.out()).unwrap_or_else(
+				// Serving chain data must not delay our own announcements.
+				io.respond_with_priority(packet_id, rlp_stream.out(), PacketPriority::Low).unwrap_or_else(
 					|e| debug!(target: "sync", "{:?}", error_func(e)));
 				Ok(())
 			}
@@ -1746,10 +2285,20 @@ impl ChainSync {
 				ChainSync::return_snapshot_manifest,
 				|e| format!("Error sending snapshot manifest: {:?}", e)),
 
-			GET_SNAPSHOT_DATA_PACKET => ChainSync::return_rlp(io, &rlp, peer,
-				ChainSync::return_snapshot_data,
-				|e| format!("Error sending snapshot data: {:?}", e)),
+			GET_SNAPSHOT_DATA_PACKET => {
+				let serving = sync.read().chunk_serving.clone();
+				ChainSync::return_snapshot_data(io, &rlp, peer, &serving)
+			},
 			CONSENSUS_DATA_PACKET => ChainSync::on_consensus_packet(io, peer, &rlp),
+			// Importing may broadcast through `EthSync`, which has to lock `sync` again.
+			PRIVATE_TRANSACTION_PACKET => {
+				let handler = sync.read().private_tx_handler.clone();
+				ChainSync::on_private_transaction(&*handler, peer, &rlp)
+			},
+			SIGNED_PRIVATE_TRANSACTION_PACKET => {
+				let handler = sync.read().private_tx_handler.clone();
+				ChainSync::on_signed_private_transaction(&*handler, peer, &rlp)
+			},
 			_ => {
 				sync.write().on_packet(io, peer, packet_id, data);
 				Ok(())
@@ -1769,6 +2318,8 @@ impl ChainSync {
 		let result = match packet_id {
 			STATUS_PACKET => self.on_peer_status(io, peer, &rlp),
 			TRANSACTIONS_PACKET => self.on_peer_transactions(io, peer, &rlp),
+			NEW_TRANSACTION_HASHES_PACKET => self.on_peer_new_transaction_hashes(io, peer, &rlp),
+			GET_TRANSACTIONS_PACKET => self.on_peer_get_transactions(io, peer, &rlp),
 			BLOCK_HEADERS_PACKET => self.on_peer_block_headers(io, peer, &rlp),
 			BLOCK_BODIES_PACKET => self.on_peer_block_bodies(io, peer, &rlp),
 			RECEIPTS_PACKET => self.on_peer_block_receipts(io, peer, &rlp),
@@ -1776,6 +2327,7 @@ impl ChainSync {
 			NEW_BLOCK_HASHES_PACKET => self.on_peer_new_hashes(io, peer, &rlp),
 			SNAPSHOT_MANIFEST_PACKET => self.on_snapshot_manifest(io, peer, &rlp),
 			SNAPSHOT_DATA_PACKET => self.on_snapshot_data(io, peer, &rlp),
+			NODE_DATA_PACKET => self.on_peer_node_data(io, peer, &rlp),
 			_ => {
 				debug!(target: "sync", "{}: Unknown packet {}", peer, packet_id);
 				Ok(())
@@ -1783,6 +2335,7 @@ impl ChainSync {
 		};
 		result.unwrap_or_else(|e| {
 			debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+			io.report_peer(peer, Offense::Malformed);
 		})
 	}
 
@@ -1800,10 +2353,11 @@ impl ChainSync {
 				PeerAsking::ForkHeader => elapsed > FORK_HEADER_TIMEOUT_SEC,
 				PeerAsking::SnapshotManifest => elapsed > SNAPSHOT_MANIFEST_TIMEOUT_SEC,
 				PeerAsking::SnapshotData => elapsed > SNAPSHOT_DATA_TIMEOUT_SEC,
+				PeerAsking::NodeData => elapsed > NODE_DATA_TIMEOUT_SEC,
 			};
 			if timeout {
 				trace!(target:"sync", "Timeout {}", peer_id);
-				io.disconnect_peer(*peer_id);
+				io.report_peer(*peer_id, Offense::Timeout);
 				aborting.push(*peer_id);
 			}
 		}
@@ -1816,14 +2370,19 @@ impl ChainSync {
 			let elapsed = (tick - ask_time) / 1_000_000_000;
 			if elapsed > STATUS_TIMEOUT_SEC {
 				trace!(target:"sync", "Status timeout {}", peer);
-				io.disconnect_peer(*peer);
+				io.report_peer(*peer, Offense::Timeout);
 			}
 		}
+
+		// Allow announced transactions to be requested from another peer
+		self.requested_transactions.retain(|_, ask_time| (tick - *ask_time) / 1_000_000_000 <= TRANSACTIONS_REQUEST_TIMEOUT_SEC);
 	}
 
 	fn check_resume(&mut self, io: &mut SyncIo) {
 		if self.state == SyncState::Waiting && !io.chain().queue_info().is_full() && self.state == SyncState::Waiting {
 			self.state = SyncState::Blocks;
+			// import blocks held back while the queue was full.
+			self.collect_blocks(io, BlockSet::NewBlocks);
 			self.continue_sync(io);
 		} else if self.state == SyncState::SnapshotWaiting {
 			match io.snapshot_service().status() {
@@ -1833,7 +2392,7 @@ impl ChainSync {
 					self.continue_sync(io);
 				},
 				RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } => {
-					if !self.snapshot.is_complete() && self.snapshot.done_chunks() - (state_chunks_done + block_chunks_done) as usize <= MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
+					if !self.snapshot.is_complete() && self.snapshot.done_chunks().saturating_sub((state_chunks_done + block_chunks_done) as usize) <= MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
 						trace!(target:"sync", "Resuming snapshot sync");
 						self.state = SyncState::SnapshotData;
 						self.continue_sync(io);
@@ -1930,7 +2489,11 @@ impl ChainSync {
 	}
 
 	fn get_consensus_peers(&self) -> Vec<PeerId> {
-		self.peers.iter().filter_map(|(id, p)| if p.protocol_version == PROTOCOL_VERSION_2 { Some(*id) } else { None }).collect()
+		self.peers.iter().filter_map(|(id, p)| if p.protocol_version >= PROTOCOL_VERSION_2 { Some(*id) } else { None }).collect()
+	}
+
+	fn get_private_transaction_peers(&self) -> Vec<PeerId> {
+		self.peers.iter().filter_map(|(id, p)| if p.protocol_version >= PROTOCOL_VERSION_3 { Some(*id) } else { None }).collect()
 	}
 
 	/// propagates latest block to a set of peers
@@ -1989,6 +2552,8 @@ impl ChainSync {
 			return 0;
 		}
 
+		self.schedule_local_rebroadcast(io);
+
 		let (transactions, service_transactions): (Vec<_>, Vec<_>) = transactions.into_iter()
 			.partition(|tx| !tx.transaction.gas_price.is_zero());
 
@@ -2010,22 +2575,50 @@ impl ChainSync {
 		affected_peers.len()
 	}
 
-	fn select_peers_for_transactions<F>(&self, filter: F) -> Vec<PeerId>
+	/// Makes peers forget local transactions which are due for a re-broadcast,
+	/// so that they are propagated again.
+	fn schedule_local_rebroadcast(&mut self, io: &mut SyncIo) {
+		let due = match self.local_rebroadcast {
+			Some(ref mut rebroadcast) => rebroadcast.due(&io.chain().local_transaction_hashes(), Instant::now()),
+			None => return,
+		};
+		if due.is_empty() {
+			return;
+		}
+
+		trace!(target: "sync", "Re-broadcasting {} local transactions", due.len());
+		for peer in self.peers.values_mut() {
+			for hash in &due {
+				peer.known_transactions.remove(hash);
+			}
+		}
+	}
+
+	/// Selects peers to propagate transactions to. Transactions are sent in full only to about sqrt(x) of the peers,
+	/// the remaining peers supporting transaction hashes get just the announcement (`false` in the result).
+	fn select_peers_for_transactions<F>(&self, filter: F) -> Vec<(PeerId, bool)>
 		where F: Fn(&PeerId) -> bool {
 		// sqrt(x)/x scaled to max u32
 		let fraction = ((self.peers.len() as f64).powf(-0.5) * (u32::max_value() as f64).round()) as u32;
 		let small = self.peers.len() < MIN_PEERS_PROPAGATION;
 
 		let mut random = random::new();
-		self.peers.keys()
-			.cloned()
-			.filter(filter)
-			.filter(|_| small || random.next_u32() < fraction)
+		self.peers.iter()
+			.filter(|&(peer_id, _)| filter(peer_id))
+			.filter_map(|(peer_id, peer_info)| {
+				if small || random.next_u32() < fraction {
+					Some((*peer_id, true))
+				} else if peer_info.supports_transaction_hashes() {
+					Some((*peer_id, false))
+				} else {
+					None
+				}
+			})
 			.take(MAX_PEERS_PROPAGATION)
 			.collect()
 	}
 
-	fn propagate_transactions_to_peers(&mut self, io: &mut SyncIo, peers: Vec<PeerId>, transactions: Vec<PendingTransaction>) -> HashSet<PeerId> {
+	fn propagate_transactions_to_peers(&mut self, io: &mut SyncIo, peers: Vec<(PeerId, bool)>, transactions: Vec<PendingTransaction>) -> HashSet<PeerId> {
 		let all_transactions_hashes = transactions.iter()
 			.map(|tx| tx.transaction.hash())
 			.collect::<HashSet<H256>>();
@@ -2043,24 +2636,45 @@ impl ChainSync {
 
 		let lucky_peers = {
 			peers.into_iter()
-				.filter_map(|peer_id| {
+				.filter_map(|(peer_id, full)| {
 					let stats = &mut self.transactions_stats;
 					let peer_info = self.peers.get_mut(&peer_id)
 						.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
 
+					// Announce hashes only, the peer requests the transactions it doesn't know yet
+					if !full {
+						let to_announce = all_transactions_hashes.difference(&peer_info.known_transactions)
+							.take(MAX_TRANSACTION_HASHES_TO_PROPAGATE)
+							.cloned()
+							.collect::<Vec<_>>();
+						if to_announce.is_empty() {
+							return None;
+						}
+						let mut packet = RlpStream::new_list(to_announce.len());
+						for hash in &to_announce {
+							packet.append(hash);
+						}
+						peer_info.known_transactions = all_transactions_hashes
+							.intersection(&peer_info.known_transactions)
+							.chain(&to_announce)
+							.cloned()
+							.collect();
+						return Some((peer_id, NEW_TRANSACTION_HASHES_PACKET, to_announce.len(), packet.out()));
+					}
+
 					// Send all transactions
-					if peer_info.last_sent_transactions.is_empty() {
+					if peer_info.known_transactions.is_empty() {
 						// update stats
 						for hash in &all_transactions_hashes {
 							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
 							stats.propagated(hash, id, block_number);
 						}
-						peer_info.last_sent_transactions = all_transactions_hashes.clone();
-						return Some((peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone()));
+						peer_info.known_transactions = all_transactions_hashes.clone();
+						return Some((peer_id, TRANSACTIONS_PACKET, all_transactions_hashes.len(), all_transactions_rlp.clone()));
 					}
 
 					// Get hashes of all transactions to send to this peer
-					let to_send = all_transactions_hashes.difference(&peer_info.last_sent_transactions)
+					let to_send = all_transactions_hashes.difference(&peer_info.known_transactions)
 						.take(MAX_TRANSACTIONS_TO_PROPAGATE)
 						.cloned()
 						.collect::<HashSet<_>>();
@@ -2100,12 +2714,12 @@ impl ChainSync {
 						stats.propagated(hash, id, block_number);
 					}
 
-					peer_info.last_sent_transactions = all_transactions_hashes
-						.intersection(&peer_info.last_sent_transactions)
+					peer_info.known_transactions = all_transactions_hashes
+						.intersection(&peer_info.known_transactions)
 						.chain(&to_send)
 						.cloned()
 						.collect();
-					Some((peer_id, to_send.len(), packet.out()))
+					Some((peer_id, TRANSACTIONS_PACKET, to_send.len(), packet.out()))
 				})
 				.collect::<Vec<_>>()
 		};
@@ -2114,14 +2728,21 @@ impl ChainSync {
 		let mut peers = HashSet::new();
 		if lucky_peers.len() > 0 {
 			let mut max_sent = 0;
+			let mut announced = 0;
 			let lucky_peers_len = lucky_peers.len();
-			for (peer_id, sent, rlp) in lucky_peers {
+			for (peer_id, packet_id, sent, rlp) in lucky_peers {
 				peers.insert(peer_id);
-				self.send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp);
-				trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
-				max_sent = cmp::max(max_sent, sent);
+				if packet_id == TRANSACTIONS_PACKET {
+					self.send_packet(io, peer_id, packet_id, rlp);
+					trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
+					max_sent = cmp::max(max_sent, sent);
+				} else {
+					self.send_warp_packet(io, peer_id, packet_id, rlp);
+					trace!(target: "sync", "{:02} <- NewTransactionHashes ({} entries)", peer_id, sent);
+					announced += 1;
+				}
 			}
-			debug!(target: "sync", "Sent up to {} transactions to {} peers.", max_sent, lucky_peers_len);
+			debug!(target: "sync", "Sent up to {} transactions to {} peers, announced to {} of them.", max_sent, lucky_peers_len, announced);
 		}
 
 		peers
@@ -2166,6 +2787,102 @@ impl ChainSync {
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
 		self.maybe_start_snapshot_sync(io);
 		self.check_resume(io);
+		self.request_state_nodes(io);
+		self.update_progress();
+		self.check_snapshot_starvation(io);
+		self.update_partition(io);
+	}
+
+	/// Abandon the snapshot being downloaded if no chunks arrived for a long time,
+	/// e.g. because all peers serving it went away, and look for another one.
+	/// A pinned snapshot is never abandoned.
+	fn check_snapshot_starvation(&mut self, io: &mut SyncIo) {
+		if self.state != SyncState::SnapshotData || self.warp_snapshot_hash.is_some() {
+			self.snapshot_last_progress = None;
+			return;
+		}
+
+		let now = time::precise_time_ns();
+		let done = self.snapshot.done_chunks();
+		let since = match self.snapshot_last_progress {
+			Some((since, last_done)) if last_done == done => since,
+			_ => {
+				self.snapshot_last_progress = Some((now, done));
+				return;
+			}
+		};
+		let elapsed = (now - since) / 1_000_000_000;
+		if elapsed <= SNAPSHOT_STARVATION_TIMEOUT_SEC {
+			return;
+		}
+
+		if let Some(hash) = self.snapshot.snapshot_hash() {
+			info!(target: "sync", "No snapshot chunks received for {} seconds, abandoning snapshot {:?}", elapsed, hash);
+			io.snapshot_service().abort_restore();
+			self.snapshot.note_abandoned(hash);
+		}
+		self.snapshot.clear();
+		self.snapshot_last_progress = None;
+		self.state = SyncState::WaitingPeers;
+		self.continue_sync(io);
+	}
+
+	/// Track the progress of the current sync stage.
+	fn update_progress(&mut self) {
+		let now = time::precise_time_ns();
+		match self.state {
+			SyncState::Blocks | SyncState::NewBlocks | SyncState::Waiting =>
+				self.blocks_progress.update(now, self.new_blocks.last_imported_block_number()),
+			_ => self.blocks_progress.reset(),
+		}
+		match self.state {
+			SyncState::SnapshotData | SyncState::SnapshotWaiting =>
+				self.snapshot_progress.update(now, self.snapshot.done_chunks() as u64),
+			_ => self.snapshot_progress.reset(),
+		}
+	}
+
+	/// Check whether peers follow branches conflicting with the local chain.
+	/// Skipped during major sync, when most peers are expected to be ahead of us.
+	fn update_partition(&mut self, io: &mut SyncIo) {
+		let changed = match self.state {
+			SyncState::Idle | SyncState::NewBlocks => {
+				let chain = io.chain();
+				let local_difficulty = chain.chain_info().total_difficulty;
+				let heads: Vec<_> = self.peers.values()
+					.filter(|p| p.is_allowed())
+					.map(|p| (p.latest_hash, p.difficulty, ChainSync::head_status(chain, &p.latest_hash)))
+					.collect();
+				self.partition.update(time::precise_time_ns(), local_difficulty, heads)
+			},
+			_ => self.partition.reset(),
+		};
+
+		if changed {
+			let partition = self.partition.partition();
+			if partition.detected {
+				warn!(target: "sync", "Possible network partition: {} of {} peers follow a branch conflicting with the local chain", partition.conflicting_peers, partition.peers);
+			} else {
+				info!(target: "sync", "Network partition resolved");
+			}
+		}
+	}
+
+	fn head_status(chain: &BlockChainClient, hash: &H256) -> HeadStatus {
+		match chain.block_status(BlockId::Hash(*hash)) {
+			BlockStatus::InChain => {
+				let canonical = chain.block_header(BlockId::Hash(*hash))
+					.map_or(false, |header| chain.block_hash(BlockId::Number(header.number())) == Some(*hash));
+				if canonical { HeadStatus::Canonical } else { HeadStatus::Retracted }
+			},
+			BlockStatus::Bad => HeadStatus::Retracted,
+			BlockStatus::Queued | BlockStatus::Pending | BlockStatus::Unknown => HeadStatus::Unknown,
+		}
+	}
+
+	/// Returns distribution of peers across chain branches.
+	pub fn fork_partition(&self) -> &ForkPartition {
+		self.partition.partition()
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
@@ -2188,7 +2905,7 @@ impl ChainSync {
 			let peer = random::new().gen_range(0, self.peers.len());
 			trace!(target: "sync", "Re-broadcasting transactions to a random peer.");
 			self.peers.values_mut().nth(peer).map(|mut peer_info|
-				peer_info.last_sent_transactions.clear()
+				peer_info.known_transactions.clear()
 			);
 		}
 	}
@@ -2208,6 +2925,42 @@ impl ChainSync {
 			self.send_packet(io, peer_id, CONSENSUS_DATA_PACKET, packet.clone());
 		}
 	}
+
+	/// Called when peer sends us new private transaction packet
+	fn on_private_transaction(handler: &PrivateTxHandler, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		trace!(target: "sync", "Received private transaction packet from {:?}", peer_id);
+		if let Err(e) = handler.import_private_transaction(r.as_raw()) {
+			trace!(target: "sync", "Ignoring the private transaction: {}", e);
+		}
+		Ok(())
+	}
+
+	/// Called when peer sends us signed private transaction packet
+	fn on_signed_private_transaction(handler: &PrivateTxHandler, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		trace!(target: "sync", "Received signed private transaction packet from {:?}", peer_id);
+		if let Err(e) = handler.import_signed_private_transaction(r.as_raw()) {
+			trace!(target: "sync", "Ignoring the signed private transaction: {}", e);
+		}
+		Ok(())
+	}
+
+	/// Broadcast private transaction message to peers.
+	pub fn propagate_private_transaction(&mut self, io: &mut SyncIo, packet: Bytes) {
+		let lucky_peers = ChainSync::select_random_peers(&self.get_private_transaction_peers());
+		trace!(target: "sync", "Sending private transaction packet to {:?}", lucky_peers);
+		for peer_id in lucky_peers {
+			self.send_packet(io, peer_id, PRIVATE_TRANSACTION_PACKET, packet.clone());
+		}
+	}
+
+	/// Broadcast signed private transaction message to peers.
+	pub fn propagate_signed_private_transaction(&mut self, io: &mut SyncIo, packet: Bytes) {
+		let lucky_peers = ChainSync::select_random_peers(&self.get_private_transaction_peers());
+		trace!(target: "sync", "Sending signed private transaction packet to {:?}", lucky_peers);
+		for peer_id in lucky_peers {
+			self.send_packet(io, peer_id, SIGNED_PRIVATE_TRANSACTION_PACKET, packet.clone());
+		}
+	}
 }
 
 /// Checks if peer is able to process service transactions
@@ -2242,11 +2995,13 @@ mod tests {
 	use super::*;
 	use ::SyncConfig;
 	use super::{PeerInfo, PeerAsking};
+	use private_tx::NoopPrivateTxHandler;
 	use ethkey;
 	use ethcore::header::*;
 	use ethcore::client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
 	use ethcore::transaction::UnverifiedTransaction;
 	use ethcore::miner::MinerService;
+	use ethcore::spec::ForkId;
 
 	fn get_dummy_block(order: u32, parent_hash: H256) -> Bytes {
 		let mut header = Header::new();
@@ -2314,6 +3069,12 @@ mod tests {
 			num_snapshot_chunks: 0,
 			snapshot_chunks_done: 0,
 			last_imported_old_block_number: None,
+			headers_downloaded: 0,
+			bodies_downloaded: 0,
+			receipts_downloaded: 0,
+			blocks_eta: None,
+			snapshot_eta: None,
+			num_conflicting_peers: 0,
 		}
 	}
 
@@ -2470,7 +3231,7 @@ mod tests {
 	}
 
 	fn dummy_sync_with_peer(peer_latest_hash: H256, client: &BlockChainClient) -> ChainSync {
-		let mut sync = ChainSync::new(SyncConfig::default(), client);
+		let mut sync = ChainSync::new(SyncConfig::default(), client, Arc::new(NoopPrivateTxHandler));
 		insert_dummy_peer(&mut sync, 0, peer_latest_hash);
 		sync
 	}
@@ -2487,13 +3248,16 @@ mod tests {
 				asking_blocks: Vec::new(),
 				asking_hash: None,
 				ask_time: 0,
-				last_sent_transactions: HashSet::new(),
+				known_transactions: HashSet::new(),
 				expired: false,
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_state_nodes: Vec::new(),
 				block_set: None,
+				throughput: PeerThroughput::default(),
+				requests: PeerRequestStats::default(),
 			});
 
 	}
@@ -2595,7 +3359,7 @@ mod tests {
 		client.add_blocks(2, EachBlockWith::Uncle);
 		let queue = RwLock::new(VecDeque::new());
 		let block = client.block(BlockId::Latest).unwrap().into_inner();
-		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
 		sync.peers.insert(0,
 			PeerInfo {
 				// Messaging protocol
@@ -2608,13 +3372,16 @@ mod tests {
 				asking_blocks: Vec::new(),
 				asking_hash: None,
 				ask_time: 0,
-				last_sent_transactions: HashSet::new(),
+				known_transactions: HashSet::new(),
 				expired: false,
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_state_nodes: Vec::new(),
 				block_set: None,
+				throughput: PeerThroughput::default(),
+				requests: PeerRequestStats::default(),
 			});
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -2653,6 +3420,57 @@ mod tests {
 		assert_eq!(0x02, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn rebroadcasts_local_transactions() {
+		use std::time::Duration;
+		use ethkey::{Generator, Random};
+		use ethcore::transaction::{Action, Transaction};
+		use rebroadcast::RebroadcastPolicy;
+
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let external = client.insert_transaction_to_queue();
+		let local = {
+			let keypair = Random.generate().unwrap();
+			let tx = Transaction {
+				action: Action::Create,
+				value: 100.into(),
+				data: vec![],
+				gas: 100_000.into(),
+				gas_price: 20_000_000_000u64.into(),
+				nonce: 0.into(),
+			}.sign(keypair.secret(), None);
+			client.set_balance(tx.sender(), 10_000_000_000_000_000_000u64.into());
+			let hash = tx.hash();
+			client.miner.import_own_transaction(&client, tx.into()).unwrap();
+			hash
+		};
+		assert_eq!(client.local_transaction_hashes(), vec![local]);
+
+		let config = SyncConfig {
+			local_tx_rebroadcast: Some(RebroadcastPolicy {
+				interval: Duration::from_secs(0),
+				max_interval: Duration::from_secs(0),
+			}),
+			..SyncConfig::default()
+		};
+		let mut sync = ChainSync::new(config, &client, Arc::new(NoopPrivateTxHandler));
+		insert_dummy_peer(&mut sync, 0, client.block_hash_delta_minus(1));
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		assert_eq!(1, sync.propagate_new_transactions(&mut io));
+		// only the local transaction is sent again
+		assert_eq!(1, sync.propagate_new_transactions(&mut io));
+		assert_eq!(2, io.packets.len());
+		let resent = UntrustedRlp::new(&io.packets[1].data);
+		assert_eq!(1, resent.item_count().unwrap());
+		let resent: UnverifiedTransaction = resent.val_at(0).unwrap();
+		assert_eq!(resent.hash(), local);
+		assert!(resent.hash() != external);
+	}
+
 	#[test]
 	fn does_not_propagate_new_transactions_after_new_block() {
 		let mut client = TestBlockChainClient::new();
@@ -2682,7 +3500,7 @@ mod tests {
 		client.add_blocks(100, EachBlockWith::Uncle);
 		client.insert_transaction_to_queue();
 		// Sync with no peers
-		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
 		let queue = RwLock::new(VecDeque::new());
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -2752,7 +3570,7 @@ mod tests {
 		let mut client = TestBlockChainClient::new();
 		client.insert_transaction_with_gas_price_to_queue(U256::zero());
 		let block_hash = client.block_hash_delta_minus(1);
-		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
 		let queue = RwLock::new(VecDeque::new());
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -2785,7 +3603,7 @@ mod tests {
 		let tx1_hash = client.insert_transaction_to_queue();
 		let tx2_hash = client.insert_transaction_with_gas_price_to_queue(U256::zero());
 		let block_hash = client.block_hash_delta_minus(1);
-		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
 		let queue = RwLock::new(VecDeque::new());
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -2820,6 +3638,172 @@ mod tests {
 		assert!(sent_transactions.iter().any(|tx| tx.hash() == tx2_hash));
 	}
 
+	#[test]
+	fn announces_transaction_hashes_to_remaining_peers() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		for peer_id in 0..16 {
+			insert_dummy_peer(&mut sync, peer_id, block_hash);
+			sync.peers.get_mut(&peer_id).unwrap().protocol_version = 4;
+		}
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		let peer_count = sync.propagate_new_transactions(&mut io);
+
+		// every peer gets either the transaction or its hash, but only once
+		assert_eq!(16, peer_count);
+		assert_eq!(16, io.packets.len());
+		let full = io.packets.iter().filter(|p| p.packet_id == 0x02).count(); // TRANSACTIONS_PACKET
+		let announced = io.packets.iter().filter(|p| p.packet_id == 0x18).count(); // NEW_TRANSACTION_HASHES_PACKET
+		assert!(full < announced);
+		assert_eq!(16, full + announced);
+		assert_eq!(0, sync.propagate_new_transactions(&mut io));
+	}
+
+	#[test]
+	fn requests_announced_transactions_once() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		insert_dummy_peer(&mut sync, 0, block_hash);
+		insert_dummy_peer(&mut sync, 1, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut announcement = RlpStream::new_list(1);
+		announcement.append(&H256::from(5));
+		let announcement = announcement.out();
+		sync.on_packet(&mut io, 0, super::NEW_TRANSACTION_HASHES_PACKET, &announcement);
+		sync.on_packet(&mut io, 1, super::NEW_TRANSACTION_HASHES_PACKET, &announcement);
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(0x19, io.packets[0].packet_id); // GET_TRANSACTIONS_PACKET
+		assert_eq!(0, io.packets[0].recipient);
+		assert_eq!(vec![H256::from(5)], UntrustedRlp::new(&io.packets[0].data).as_list::<H256>().unwrap());
+		assert!(sync.peers[&1].known_transactions.contains(&H256::from(5)));
+	}
+
+	#[test]
+	fn does_not_request_queued_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx_hash = client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		insert_dummy_peer(&mut sync, 0, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut announcement = RlpStream::new_list(2);
+		announcement.append(&tx_hash);
+		announcement.append(&H256::from(5));
+		sync.on_packet(&mut io, 0, super::NEW_TRANSACTION_HASHES_PACKET, &announcement.out());
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(vec![H256::from(5)], UntrustedRlp::new(&io.packets[0].data).as_list::<H256>().unwrap());
+	}
+
+	#[test]
+	fn limits_transactions_requested_per_announcement() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		insert_dummy_peer(&mut sync, 0, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut announcement = RlpStream::new_list(super::MAX_TRANSACTIONS_TO_REQUEST + 10);
+		for i in 0..super::MAX_TRANSACTIONS_TO_REQUEST + 10 {
+			announcement.append(&H256::from(i as u64 + 1));
+		}
+		sync.on_packet(&mut io, 0, super::NEW_TRANSACTION_HASHES_PACKET, &announcement.out());
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(super::MAX_TRANSACTIONS_TO_REQUEST, UntrustedRlp::new(&io.packets[0].data).item_count().unwrap());
+	}
+
+	#[test]
+	fn returns_requested_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let tx_hash = client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		insert_dummy_peer(&mut sync, 0, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, Some(0));
+
+		let mut request = RlpStream::new_list(2);
+		request.append(&tx_hash);
+		request.append(&H256::from(5));
+		sync.on_packet(&mut io, 0, super::GET_TRANSACTIONS_PACKET, &request.out());
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(0x02, io.packets[0].packet_id); // TRANSACTIONS_PACKET
+		let sent: Vec<UnverifiedTransaction> = UntrustedRlp::new(&io.packets[0].data).as_list().unwrap();
+		assert_eq!(1, sent.len());
+		assert_eq!(tx_hash, sent[0].hash());
+		assert!(sync.peers[&0].known_transactions.contains(&tx_hash));
+	}
+
+	#[test]
+	fn reports_request_outcome() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(10), &client);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		sync.request_snapshot_manifest(&mut io, 0);
+		sync.note_response(0, true);
+		sync.note_response(0, false);
+
+		assert_eq!(sync.peer_info(&0).unwrap().requests, PeerRequestStats { sent: 1, failed: 1, useful: 1 });
+	}
+
+	#[test]
+	fn abandons_starving_snapshot() {
+		let mut client = TestBlockChainClient::new();
+		let mut sync = ChainSync::new(SyncConfig { warp_sync: true, ..SyncConfig::default() }, &client, Arc::new(NoopPrivateTxHandler));
+		let manifest = ManifestData {
+			version: 2,
+			state_hashes: vec![H256::from(1)],
+			block_hashes: vec![H256::from(2)],
+			state_root: H256::new(),
+			block_number: 42,
+			block_hash: H256::new(),
+		};
+		let hash = keccak(manifest.clone().into_rlp());
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		sync.snapshot.reset_to(&manifest, &hash);
+		io.snapshot_service().begin_restore(manifest);
+		sync.state = SyncState::SnapshotData;
+
+		sync.check_snapshot_starvation(&mut io);
+		assert_eq!(sync.state, SyncState::SnapshotData);
+
+		// no chunk downloaded for too long
+		sync.snapshot_last_progress = Some((0, 0));
+		sync.check_snapshot_starvation(&mut io);
+		assert_eq!(sync.state, SyncState::WaitingPeers);
+		assert!(sync.snapshot.is_abandoned(&hash));
+		assert!(!sync.snapshot.have_manifest());
+		assert_eq!(io.snapshot_service().status(), RestorationStatus::Inactive);
+	}
+
 	#[test]
 	fn handles_peer_new_block_malformed() {
 		let mut client = TestBlockChainClient::new();
@@ -3031,4 +4015,53 @@ mod tests {
 		assert_eq!(status.transactions_in_pending_queue, 0);
 		assert_eq!(status.transactions_in_future_queue, 0);
 	}
+
+	fn status_packet(client: &TestBlockChainClient, fork_id: ForkId) -> Bytes {
+		let chain = client.chain_info();
+		let mut packet = RlpStream::new_list(8);
+		packet.append(&2u32);
+		packet.append(&1u64);
+		packet.append(&chain.total_difficulty);
+		packet.append(&chain.best_block_hash);
+		packet.append(&chain.genesis_hash);
+		packet.append(&H256::zero());
+		packet.append(&0u64);
+		packet.append(&fork_id);
+		packet.out()
+	}
+
+	#[test]
+	fn should_disconnect_peer_with_incompatible_fork_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		let fork_id = client.fork_filter().fork_id(10);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let compatible = status_packet(io.chain, fork_id);
+		sync.on_peer_status(&mut io, 0, &UntrustedRlp::new(&compatible)).unwrap();
+		assert!(sync.peers.contains_key(&0));
+
+		let incompatible = status_packet(io.chain, ForkId { hash: !fork_id.hash, next: 0 });
+		sync.on_peer_status(&mut io, 1, &UntrustedRlp::new(&incompatible)).unwrap();
+		assert!(!sync.peers.contains_key(&1));
+		assert!(io.to_disconnect.contains(&1));
+	}
+
+	#[test]
+	fn should_build_snapshot_data_packet_from_ranges() {
+		for &size in &[0usize, 55, 56, 300_000] {
+			let chunk: Bytes = (0..size).map(|i| i as u8).collect();
+			let read = |offset: u64, len: u64| Some(chunk[offset as usize..(offset + len) as usize].to_vec());
+			let packet = ChainSync::snapshot_data_packet(size as u64, read).unwrap();
+
+			let mut expected = RlpStream::new_list(1);
+			expected.append(&chunk);
+			assert_eq!(packet.out(), expected.out());
+		}
+
+		assert!(ChainSync::snapshot_data_packet(300_000, |_, _| Some(vec![0u8; 10])).is_none());
+	}
 }
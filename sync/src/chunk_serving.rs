@@ -0,0 +1,143 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Memory and concurrency limits for serving snapshot chunks to peers.
+
+use std::collections::HashMap;
+use parking_lot::Mutex;
+use network::PeerId;
+
+/// Maximal number of bytes of snapshot chunks being served at once.
+const MAX_SERVING_BYTES: u64 = 64 * 1024 * 1024;
+/// Maximal number of chunks being served to a single peer at once.
+const MAX_CHUNKS_PER_PEER: usize = 2;
+
+#[derive(Default)]
+struct State {
+	bytes: u64,
+	peers: HashMap<PeerId, usize>,
+}
+
+/// Keeps track of the chunks being read and sent to peers.
+pub struct ChunkServing {
+	max_bytes: u64,
+	max_per_peer: usize,
+	state: Mutex<State>,
+}
+
+impl Default for ChunkServing {
+	fn default() -> Self {
+		ChunkServing::new(MAX_SERVING_BYTES, MAX_CHUNKS_PER_PEER)
+	}
+}
+
+impl ChunkServing {
+	pub fn new(max_bytes: u64, max_per_peer: usize) -> Self {
+		ChunkServing {
+			max_bytes: max_bytes,
+			max_per_peer: max_per_peer,
+			state: Mutex::new(State::default()),
+		}
+	}
+
+	/// Reserves memory for serving a chunk of `bytes` to given peer.
+	/// Returns `None` if either limit would be exceeded. A chunk bigger than
+	/// the whole budget is only served when nothing else is.
+	pub fn reserve(&self, peer: PeerId, bytes: u64) -> Option<Reservation> {
+		let mut state = self.state.lock();
+		if state.bytes > 0 && state.bytes.saturating_add(bytes) > self.max_bytes {
+			return None;
+		}
+		let serving = state.peers.get(&peer).cloned().unwrap_or(0);
+		if serving >= self.max_per_peer {
+			return None;
+		}
+
+		state.bytes += bytes;
+		state.peers.insert(peer, serving + 1);
+		Some(Reservation {
+			serving: self,
+			peer: peer,
+			bytes: bytes,
+		})
+	}
+
+	/// Number of bytes currently reserved.
+	pub fn reserved(&self) -> u64 {
+		self.state.lock().bytes
+	}
+
+	fn release(&self, peer: PeerId, bytes: u64) {
+		let mut state = self.state.lock();
+		state.bytes -= bytes;
+		let remaining = match state.peers.get_mut(&peer) {
+			Some(count) => {
+				*count -= 1;
+				*count
+			},
+			None => 0,
+		};
+		if remaining == 0 {
+			state.peers.remove(&peer);
+		}
+	}
+}
+
+/// Memory reserved for a chunk being served; released on drop.
+pub struct Reservation<'a> {
+	serving: &'a ChunkServing,
+	peer: PeerId,
+	bytes: u64,
+}
+
+impl<'a> Drop for Reservation<'a> {
+	fn drop(&mut self) {
+		self.serving.release(self.peer, self.bytes);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ChunkServing;
+
+	#[test]
+	fn limits_memory() {
+		let serving = ChunkServing::new(100, 10);
+		let first = serving.reserve(1, 60);
+		assert!(first.is_some());
+		assert!(serving.reserve(2, 60).is_none());
+		assert!(serving.reserve(2, 40).is_some());
+
+		drop(first);
+		assert_eq!(serving.reserved(), 0);
+		// a chunk exceeding the budget is served alone.
+		assert!(serving.reserve(2, 200).is_some());
+	}
+
+	#[test]
+	fn limits_chunks_per_peer() {
+		let serving = ChunkServing::new(100, 2);
+		let _first = serving.reserve(1, 10).unwrap();
+		let second = serving.reserve(1, 10).unwrap();
+		assert!(serving.reserve(1, 10).is_none());
+		assert!(serving.reserve(2, 10).is_some());
+
+		drop(second);
+		assert!(serving.reserve(1, 10).is_some());
+		assert_eq!(serving.reserved(), 10);
+	}
+}
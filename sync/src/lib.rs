@@ -61,7 +61,13 @@ mod blocks;
 mod block_sync;
 mod sync_io;
 mod snapshot;
+mod snapshot_mirror;
+mod chunk_serving;
+mod progress;
+mod partition;
 mod transactions_stats;
+mod rebroadcast;
+mod private_tx;
 
 pub mod light_sync;
 
@@ -79,7 +85,10 @@ mod api;
 
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
-pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError, ConnectionFilter, ConnectionDirection};
+pub use private_tx::{PrivateTxHandler, NoopPrivateTxHandler};
+pub use rebroadcast::RebroadcastPolicy;
+pub use snapshot_mirror::{SnapshotMirrors, MirrorFetch};
+pub use network::{is_valid_node_url, is_valid_reserved_node, NonReservedPeerMode, NetworkError, ConnectionFilter, ConnectionDirection, BandwidthLimits};
 
 /// IPC interfaces
 #[cfg(feature="ipc")]
@@ -0,0 +1,160 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Detection of peers splitting across conflicting chain branches.
+
+use std::collections::HashMap;
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use api::{ForkPartition, ForkBranch};
+
+/// Number of seconds a peer may report an unknown, heavier head before it is considered to follow a conflicting branch.
+const UNKNOWN_HEAD_TIMEOUT_SEC: u64 = 60;
+/// Minimal number of peers on conflicting branches to report a partition.
+const MIN_CONFLICTING_PEERS: usize = 2;
+/// Minimal percentage of peers on conflicting branches to report a partition.
+const MIN_CONFLICTING_PERCENT: usize = 25;
+
+/// Relation of a peer's best block to the local chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeadStatus {
+	/// Block is part of the canonical chain.
+	Canonical,
+	/// Block is known but not part of the canonical chain.
+	Retracted,
+	/// Block is not known.
+	Unknown,
+}
+
+/// Classifies peers' best blocks against the local chain.
+///
+/// Peers reporting a non-canonical block we know of, or a heavier block that stays unknown
+/// for longer than `UNKNOWN_HEAD_TIMEOUT_SEC`, are considered to follow a conflicting branch.
+#[derive(Default)]
+pub struct PartitionDetector {
+	// timestamp (in nanoseconds) when an unknown head was first reported.
+	unknown_since: HashMap<H256, u64>,
+	partition: ForkPartition,
+}
+
+impl PartitionDetector {
+	/// Update the distribution with best blocks of all peers at given timestamp (in nanoseconds).
+	/// Returns `true` if the partition has just been detected or resolved.
+	pub fn update<I>(&mut self, now: u64, local_difficulty: U256, heads: I) -> bool
+		where I: IntoIterator<Item = (H256, Option<U256>, HeadStatus)>
+	{
+		let mut branches: Vec<ForkBranch> = Vec::new();
+		let mut unknown_since = HashMap::new();
+		let mut peers = 0;
+
+		for (head, difficulty, status) in heads {
+			peers += 1;
+			if let Some(branch) = branches.iter_mut().find(|b| b.head == head) {
+				branch.peers += 1;
+				continue;
+			}
+
+			let conflicting = match status {
+				HeadStatus::Canonical => false,
+				HeadStatus::Retracted => true,
+				HeadStatus::Unknown => {
+					let since = *self.unknown_since.get(&head).unwrap_or(&now);
+					unknown_since.insert(head, since);
+					difficulty.map_or(false, |d| d > local_difficulty)
+						&& now.saturating_sub(since) >= UNKNOWN_HEAD_TIMEOUT_SEC * 1_000_000_000
+				},
+			};
+			branches.push(ForkBranch {
+				head: head,
+				difficulty: difficulty,
+				peers: 1,
+				conflicting: conflicting,
+			});
+		}
+
+		let conflicting_peers: usize = branches.iter().filter(|b| b.conflicting).map(|b| b.peers).sum();
+		let detected = conflicting_peers >= MIN_CONFLICTING_PEERS
+			&& conflicting_peers * 100 >= peers * MIN_CONFLICTING_PERCENT;
+		let changed = detected != self.partition.detected;
+
+		branches.sort_by(|a, b| b.peers.cmp(&a.peers));
+		self.unknown_since = unknown_since;
+		self.partition = ForkPartition {
+			detected: detected,
+			peers: peers,
+			conflicting_peers: conflicting_peers,
+			branches: branches,
+		};
+		changed
+	}
+
+	/// Forget all peers.
+	pub fn reset(&mut self) -> bool {
+		self.update(0, U256::zero(), Vec::new())
+	}
+
+	/// Current distribution of peers.
+	pub fn partition(&self) -> &ForkPartition {
+		&self.partition
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{PartitionDetector, HeadStatus};
+
+	const SEC: u64 = 1_000_000_000;
+
+	#[test]
+	fn detects_peers_on_retracted_branch() {
+		let mut detector = PartitionDetector::default();
+		let heads = vec![
+			(1.into(), Some(10.into()), HeadStatus::Canonical),
+			(1.into(), Some(10.into()), HeadStatus::Canonical),
+			(2.into(), Some(9.into()), HeadStatus::Retracted),
+		];
+		assert!(!detector.update(0, 10.into(), heads.clone()));
+		assert_eq!(detector.partition().conflicting_peers, 1);
+
+		let mut heads = heads;
+		heads.push((3.into(), Some(9.into()), HeadStatus::Retracted));
+		assert!(detector.update(0, 10.into(), heads));
+		let partition = detector.partition();
+		assert!(partition.detected);
+		assert_eq!(partition.peers, 4);
+		assert_eq!(partition.conflicting_peers, 2);
+		assert_eq!(partition.branches.len(), 3);
+		assert_eq!(partition.branches[0].peers, 2);
+
+		assert!(detector.reset());
+		assert!(!detector.partition().detected);
+	}
+
+	#[test]
+	fn heavier_unknown_heads_conflict_after_timeout() {
+		let mut detector = PartitionDetector::default();
+		let heads = vec![
+			(1.into(), Some(20.into()), HeadStatus::Unknown),
+			(1.into(), Some(20.into()), HeadStatus::Unknown),
+			(2.into(), Some(5.into()), HeadStatus::Unknown),
+		];
+		assert!(!detector.update(10 * SEC, 10.into(), heads.clone()));
+		assert!(!detector.update(60 * SEC, 10.into(), heads.clone()));
+		assert!(detector.update(70 * SEC, 10.into(), heads));
+		assert_eq!(detector.partition().conflicting_peers, 2);
+	}
+}
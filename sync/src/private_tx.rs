@@ -0,0 +1,39 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions handling.
+
+/// Handler of private transaction packets received from the network.
+pub trait PrivateTxHandler: Send + Sync + 'static {
+	/// Called when a new private transaction is received.
+	fn import_private_transaction(&self, rlp: &[u8]) -> Result<(), String>;
+
+	/// Called when a signature of a private transaction is received.
+	fn import_signed_private_transaction(&self, rlp: &[u8]) -> Result<(), String>;
+}
+
+/// Handler that ignores all private transactions.
+pub struct NoopPrivateTxHandler;
+
+impl PrivateTxHandler for NoopPrivateTxHandler {
+	fn import_private_transaction(&self, _rlp: &[u8]) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn import_signed_private_transaction(&self, _rlp: &[u8]) -> Result<(), String> {
+		Ok(())
+	}
+}
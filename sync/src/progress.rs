@@ -0,0 +1,90 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Estimation of sync stage completion times.
+
+/// Measures the progress of a sync stage to estimate when it completes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgressMeter {
+	// timestamp (in nanoseconds) and progress when the measurement started.
+	start: Option<(u64, u64)>,
+}
+
+impl ProgressMeter {
+	/// Start measuring at given timestamp (in nanoseconds) unless already started.
+	pub fn update(&mut self, now: u64, progress: u64) {
+		if self.start.map_or(true, |(_, start)| progress < start) {
+			self.start = Some((now, progress));
+		}
+	}
+
+	/// Stop measuring.
+	pub fn reset(&mut self) {
+		self.start = None;
+	}
+
+	/// Estimated number of seconds until `progress` reaches `total`, given the average rate since the start.
+	pub fn eta(&self, now: u64, progress: u64, total: u64) -> Option<u64> {
+		let (started, start) = match self.start {
+			Some(start) => start,
+			None => return None,
+		};
+		if progress >= total {
+			return Some(0);
+		}
+
+		let done = progress.saturating_sub(start);
+		let elapsed = now.saturating_sub(started);
+		if done == 0 || elapsed == 0 {
+			return None;
+		}
+
+		let remaining = (total - progress) as f64;
+		Some((remaining * elapsed as f64 / done as f64 / 1_000_000_000f64) as u64)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ProgressMeter;
+
+	const SEC: u64 = 1_000_000_000;
+
+	#[test]
+	fn estimates_completion() {
+		let mut meter = ProgressMeter::default();
+		assert_eq!(meter.eta(10 * SEC, 10, 100), None);
+
+		meter.update(10 * SEC, 10);
+		assert_eq!(meter.eta(10 * SEC, 10, 100), None);
+
+		// 20 items in 10 seconds, 70 to go.
+		meter.update(20 * SEC, 30);
+		assert_eq!(meter.eta(20 * SEC, 30, 100), Some(35));
+		assert_eq!(meter.eta(20 * SEC, 100, 100), Some(0));
+
+		meter.reset();
+		assert_eq!(meter.eta(20 * SEC, 30, 100), None);
+	}
+
+	#[test]
+	fn restarts_when_progress_goes_back() {
+		let mut meter = ProgressMeter::default();
+		meter.update(0, 50);
+		meter.update(10 * SEC, 10);
+		assert_eq!(meter.eta(20 * SEC, 20, 30), Some(10));
+	}
+}
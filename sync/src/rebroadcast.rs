@@ -0,0 +1,128 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Re-broadcast schedule of local transactions.
+
+use std::cmp;
+use std::time::{Duration, Instant};
+use bigint::hash::{H256, H256FastMap};
+
+/// How often local transactions still in the queue are sent to peers again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebroadcastPolicy {
+	/// Delay before the first re-broadcast.
+	pub interval: Duration,
+	/// The delay doubles after every re-broadcast up to this value.
+	pub max_interval: Duration,
+}
+
+impl Default for RebroadcastPolicy {
+	fn default() -> Self {
+		RebroadcastPolicy {
+			interval: Duration::from_secs(60),
+			max_interval: Duration::from_secs(15 * 60),
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Entry {
+	next: Instant,
+	delay: Duration,
+}
+
+/// Tracks when each local transaction should be re-broadcast.
+#[derive(Debug)]
+pub struct Rebroadcast {
+	policy: RebroadcastPolicy,
+	entries: H256FastMap<Entry>,
+}
+
+impl Rebroadcast {
+	pub fn new(policy: RebroadcastPolicy) -> Self {
+		Rebroadcast {
+			policy: policy,
+			entries: Default::default(),
+		}
+	}
+
+	/// Returns the local transactions due for a re-broadcast at `now` and schedules the next one.
+	/// Transactions not in `local` anymore are forgotten.
+	pub fn due(&mut self, local: &[H256], now: Instant) -> Vec<H256> {
+		let policy = self.policy;
+		let mut entries = ::std::mem::replace(&mut self.entries, Default::default());
+
+		let mut due = Vec::new();
+		for hash in local {
+			let mut entry = entries.remove(hash).unwrap_or_else(|| Entry {
+				next: now + policy.interval,
+				delay: policy.interval,
+			});
+			if entry.next <= now {
+				entry.delay = cmp::min(entry.delay * 2, policy.max_interval);
+				entry.next = now + entry.delay;
+				due.push(*hash);
+			}
+			self.entries.insert(*hash, entry);
+		}
+		due
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use super::{Rebroadcast, RebroadcastPolicy};
+
+	#[test]
+	fn should_back_off_exponentially() {
+		let mut rebroadcast = Rebroadcast::new(RebroadcastPolicy {
+			interval: Duration::from_secs(10),
+			max_interval: Duration::from_secs(30),
+		});
+		let start = Instant::now();
+		let at = |secs| start + Duration::from_secs(secs);
+		let local = vec![1.into()];
+
+		assert!(rebroadcast.due(&local, at(0)).is_empty());
+		assert!(rebroadcast.due(&local, at(9)).is_empty());
+		assert_eq!(rebroadcast.due(&local, at(10)), local);
+		// next one after 20s
+		assert!(rebroadcast.due(&local, at(29)).is_empty());
+		assert_eq!(rebroadcast.due(&local, at(30)), local);
+		// capped at 30s
+		assert!(rebroadcast.due(&local, at(59)).is_empty());
+		assert_eq!(rebroadcast.due(&local, at(60)), local);
+		assert_eq!(rebroadcast.due(&local, at(90)), local);
+	}
+
+	#[test]
+	fn should_forget_transactions_no_longer_local() {
+		let mut rebroadcast = Rebroadcast::new(RebroadcastPolicy {
+			interval: Duration::from_secs(10),
+			max_interval: Duration::from_secs(30),
+		});
+		let start = Instant::now();
+
+		rebroadcast.due(&[1.into(), 2.into()], start);
+		rebroadcast.due(&[2.into()], start);
+		assert_eq!(rebroadcast.entries.len(), 1);
+
+		// re-added transaction starts from scratch
+		assert!(rebroadcast.due(&[1.into()], start + Duration::from_secs(10)).is_empty());
+	}
+}
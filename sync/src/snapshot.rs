@@ -32,6 +32,7 @@ pub struct Snapshot {
 	completed_chunks: HashSet<H256>,
 	snapshot_hash: Option<H256>,
 	bad_hashes: HashSet<H256>,
+	abandoned_hashes: HashSet<H256>,
 }
 
 impl Snapshot {
@@ -44,6 +45,7 @@ impl Snapshot {
 			completed_chunks: HashSet::new(),
 			snapshot_hash: None,
 			bad_hashes: HashSet::new(),
+			abandoned_hashes: HashSet::new(),
 		}
 	}
 
@@ -69,6 +71,11 @@ impl Snapshot {
 		self.snapshot_hash = Some(hash.clone());
 	}
 
+	/// Skip the state chunks of the current manifest, the state being fetched on demand instead.
+	pub fn skip_state_chunks(&mut self) {
+		self.pending_state_chunks.clear();
+	}
+
 	/// Validate chunk and mark it as downloaded
 	pub fn validate_chunk(&mut self, chunk: &[u8]) -> Result<ChunkType, ()> {
 		let hash = keccak(chunk);
@@ -103,6 +110,16 @@ impl Snapshot {
 		chunk
 	}
 
+	/// Mark chunks restored from an earlier run as downloaded.
+	pub fn note_completed(&mut self, hashes: Vec<H256>) {
+		for hash in hashes {
+			if self.pending_state_chunks.contains(&hash) || self.pending_block_chunks.contains(&hash) {
+				self.downloading_chunks.remove(&hash);
+				self.completed_chunks.insert(hash);
+			}
+		}
+	}
+
 	pub fn clear_chunk_download(&mut self, hash: &H256) {
 		self.downloading_chunks.remove(hash);
 	}
@@ -117,6 +134,16 @@ impl Snapshot {
 		self.bad_hashes.contains(hash)
 	}
 
+	// note snapshot hash as abandoned because no peer served its chunks.
+	pub fn note_abandoned(&mut self, hash: H256) {
+		self.abandoned_hashes.insert(hash);
+	}
+
+	// whether snapshot hash was abandoned.
+	pub fn is_abandoned(&self, hash: &H256) -> bool {
+		self.abandoned_hashes.contains(hash)
+	}
+
 	pub fn snapshot_hash(&self) -> Option<H256> {
 		self.snapshot_hash
 	}
@@ -215,6 +242,35 @@ mod test {
 		assert_eq!(snapshot.snapshot_hash(), Some(keccak(manifest.into_rlp())));
 	}
 
+	#[test]
+	fn skips_state_chunks() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, state_chunks, block_chunks) = test_manifest();
+		snapshot.reset_to(&manifest, &mhash);
+		snapshot.skip_state_chunks();
+
+		assert_eq!(snapshot.total_chunks(), 20);
+		assert_eq!(snapshot.needed_chunk(), Some(manifest.block_hashes[0].clone()));
+		assert!(snapshot.validate_chunk(&state_chunks[0]).is_err());
+
+		for data in &block_chunks {
+			assert!(snapshot.validate_chunk(data).is_ok());
+		}
+		assert!(snapshot.is_complete());
+	}
+
+	#[test]
+	fn notes_resumed_chunks() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, state_chunks, _) = test_manifest();
+		snapshot.reset_to(&manifest, &mhash);
+
+		snapshot.note_completed(vec![manifest.state_hashes[0].clone(), H256::random()]);
+		assert_eq!(snapshot.done_chunks(), 1);
+		assert!(snapshot.validate_chunk(&state_chunks[0]).is_err());
+		assert_eq!(snapshot.needed_chunk(), Some(manifest.state_hashes[1].clone()));
+	}
+
 	#[test]
 	fn tracks_known_bad() {
 		let mut snapshot = Snapshot::new();
@@ -0,0 +1,228 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshot download from HTTP(S) mirrors.
+//!
+//! A mirror serves a loose snapshot directory: the manifest RLP at `<url>/MANIFEST`
+//! and every chunk at `<url>/<chunk hash>`. Chunks are verified exactly like the ones
+//! delivered by peers and chunks the mirrors fail to serve are left for the peers.
+
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+use hash::keccak;
+use bigint::hash::H256;
+use util::Bytes;
+use chain::ChainSync;
+use sync_io::SyncIo;
+
+/// Number of chunks downloaded from mirrors in parallel.
+const MIRROR_WORKERS: usize = 4;
+/// Give up on mirrors after this many chunks in a row none of them could serve.
+const MAX_MIRROR_FAILURES: usize = 3;
+/// Time to wait when no chunk is needed right now.
+const MIRROR_WAIT_MS: u64 = 1000;
+
+/// Fetches resources from snapshot mirrors.
+pub trait MirrorFetch: Send + Sync {
+	/// Fetch the content at given URL.
+	fn fetch(&self, url: &str) -> Result<Bytes, String>;
+}
+
+/// Work for a snapshot mirror.
+#[derive(Debug, PartialEq)]
+pub enum MirrorTask {
+	/// Download chunk with given hash.
+	Chunk(H256),
+	/// Nothing to download right now.
+	Wait,
+	/// The snapshot is no longer being synced.
+	Done,
+}
+
+/// Access to the chain sync for mirror downloads.
+pub trait MirrorSync: Send + Sync {
+	/// Evaluate `f` with the chain sync. Returns `None` if the network is not running.
+	fn with_sync<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&mut ChainSync, &mut SyncIo) -> T;
+}
+
+/// HTTP(S) mirrors serving snapshots.
+#[derive(Clone)]
+pub struct SnapshotMirrors {
+	urls: Vec<String>,
+	fetch: Arc<MirrorFetch>,
+}
+
+impl SnapshotMirrors {
+	/// Create new set of mirrors using given fetcher.
+	pub fn new(urls: Vec<String>, fetch: Arc<MirrorFetch>) -> Self {
+		SnapshotMirrors {
+			urls: urls,
+			fetch: fetch,
+		}
+	}
+
+	fn url(&self, mirror: usize, name: &str) -> String {
+		format!("{}/{}", self.urls[mirror].trim_right_matches('/'), name)
+	}
+
+	/// Download the snapshot manifest from the mirror with given index.
+	fn manifest(&self, mirror: usize) -> Result<Bytes, String> {
+		self.fetch.fetch(&self.url(mirror, "MANIFEST"))
+	}
+
+	/// Download a chunk from the first of given mirrors which serves it.
+	fn chunk(&self, mirrors: &[usize], hash: &H256) -> Option<Bytes> {
+		for &mirror in mirrors {
+			let url = self.url(mirror, &hash.hex());
+			match self.fetch.fetch(&url) {
+				Ok(ref chunk) if keccak(chunk) != *hash => debug!(target: "sync", "Snapshot mirror served bad chunk {}", url),
+				Ok(chunk) => return Some(chunk),
+				Err(e) => debug!(target: "sync", "Error fetching snapshot chunk {}: {}", url, e),
+			}
+		}
+		None
+	}
+}
+
+fn with_sync<S, F, T>(sync: &Weak<S>, f: F) -> Option<T> where
+	S: MirrorSync,
+	F: FnOnce(&mut ChainSync, &mut SyncIo) -> T,
+{
+	sync.upgrade().and_then(|sync| sync.with_sync(f))
+}
+
+/// Spawn a thread downloading a snapshot from the mirrors once the network is started.
+pub fn spawn<S: MirrorSync + 'static>(sync: Weak<S>, mirrors: SnapshotMirrors) {
+	let spawned = thread::Builder::new()
+		.name("snapshot-mirror".into())
+		.spawn(move || run(sync, mirrors));
+	if let Err(e) = spawned {
+		warn!(target: "sync", "Error starting snapshot mirror download: {}", e);
+	}
+}
+
+fn run<S: MirrorSync + 'static>(sync: Weak<S>, mirrors: SnapshotMirrors) {
+	// wait for the network.
+	loop {
+		match sync.upgrade() {
+			None => return,
+			Some(ref sync) if sync.with_sync(|_, _| ()).is_some() => break,
+			Some(_) => thread::sleep(Duration::from_millis(MIRROR_WAIT_MS)),
+		}
+	}
+
+	let manifests: Vec<(usize, Bytes)> = (0..mirrors.urls.len())
+		.filter_map(|mirror| match mirrors.manifest(mirror) {
+			Ok(manifest) => Some((mirror, manifest)),
+			Err(e) => {
+				warn!(target: "sync", "Error fetching snapshot manifest from {}: {}", mirrors.urls[mirror], e);
+				None
+			},
+		})
+		.collect();
+
+	let mut snapshot_hash = None;
+	for &(_, ref manifest) in &manifests {
+		match with_sync(&sync, |sync, io| sync.on_mirror_manifest(io, manifest)) {
+			Some(Some(hash)) => {
+				snapshot_hash = Some(hash);
+				break;
+			},
+			Some(None) => {},
+			None => return,
+		}
+	}
+	let snapshot_hash = match snapshot_hash {
+		Some(hash) => hash,
+		None => {
+			info!(target: "sync", "No usable snapshot found on the snapshot mirrors");
+			return;
+		},
+	};
+	let serving: Arc<Vec<usize>> = Arc::new(manifests.iter()
+		.filter(|&&(_, ref manifest)| keccak(manifest) == snapshot_hash)
+		.map(|&(mirror, _)| mirror)
+		.collect());
+	info!(target: "sync", "Downloading snapshot {} from {} mirror(s)", snapshot_hash, serving.len());
+
+	let workers: Vec<_> = (0..MIRROR_WORKERS).map(|_| {
+		let sync = sync.clone();
+		let mirrors = mirrors.clone();
+		let serving = serving.clone();
+		thread::spawn(move || download(&sync, &mirrors, &serving, &snapshot_hash))
+	}).collect();
+	for worker in workers {
+		let _ = worker.join();
+	}
+
+	with_sync(&sync, |sync, io| sync.on_mirror_finished(io));
+}
+
+fn download<S: MirrorSync>(sync: &Weak<S>, mirrors: &SnapshotMirrors, serving: &[usize], snapshot_hash: &H256) {
+	let mut failures = 0;
+	loop {
+		match with_sync(sync, |sync, io| sync.next_mirror_chunk(io, snapshot_hash)) {
+			Some(MirrorTask::Chunk(hash)) => {
+				let chunk = mirrors.chunk(serving, &hash);
+				failures = if chunk.is_some() { 0 } else { failures + 1 };
+				if with_sync(sync, move |sync, io| sync.on_mirror_chunk(io, hash, chunk)).is_none() {
+					return;
+				}
+				if failures >= MAX_MIRROR_FAILURES {
+					warn!(target: "sync", "Snapshot mirrors keep failing, leaving the remaining chunks to peers");
+					return;
+				}
+			},
+			Some(MirrorTask::Wait) => thread::sleep(Duration::from_millis(MIRROR_WAIT_MS)),
+			Some(MirrorTask::Done) | None => return,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::sync::Arc;
+	use hash::keccak;
+	use util::Bytes;
+	use super::{MirrorFetch, SnapshotMirrors};
+
+	struct TestFetch(HashMap<String, Bytes>);
+
+	impl MirrorFetch for TestFetch {
+		fn fetch(&self, url: &str) -> Result<Bytes, String> {
+			self.0.get(url).cloned().ok_or_else(|| "404".into())
+		}
+	}
+
+	#[test]
+	fn should_fetch_chunks_from_serving_mirrors() {
+		let chunk = b"chunk".to_vec();
+		let hash = keccak(&chunk);
+		let mut content = HashMap::new();
+		content.insert("http://a/MANIFEST".to_owned(), b"manifest".to_vec());
+		content.insert(format!("http://b/{}", hash.hex()), chunk.clone());
+		content.insert(format!("http://c/{}", hash.hex()), b"tampered".to_vec());
+		let mirrors = SnapshotMirrors::new(vec!["http://a/".into(), "http://b".into(), "http://c".into()], Arc::new(TestFetch(content)));
+
+		assert_eq!(mirrors.manifest(0), Ok(b"manifest".to_vec()));
+		assert!(mirrors.manifest(1).is_err());
+		assert_eq!(mirrors.chunk(&[0, 1], &hash), Some(chunk.clone()));
+		assert_eq!(mirrors.chunk(&[2, 1], &hash), Some(chunk));
+		assert_eq!(mirrors.chunk(&[0, 2], &hash), None);
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
-use network::{NetworkContext, PeerId, PacketId, NetworkError, SessionInfo, ProtocolId};
+use network::{NetworkContext, PeerId, PacketId, NetworkError, SessionInfo, ProtocolId, Offense, PacketPriority};
 use util::Bytes;
 use ethcore::client::BlockChainClient;
 use ethcore::header::BlockNumber;
@@ -24,18 +24,27 @@ use parking_lot::RwLock;
 
 /// IO interface for the syncing handler.
 /// Provides peer connection management and an interface to the blockchain client.
-// TODO: ratings
 pub trait SyncIo {
 	/// Disable a peer
 	fn disable_peer(&mut self, peer_id: PeerId);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
+	/// Penalize and disconnect a misbehaving peer
+	fn report_peer(&mut self, peer_id: PeerId, offense: Offense);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>;
 	/// Send a packet to a peer.
 	fn send(&mut self, peer_id: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>;
 	/// Send a packet to a peer using specified protocol.
 	fn send_protocol(&mut self, protocol: ProtocolId, peer_id: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>;
+	/// Respond to current request with a packet of given priority.
+	fn respond_with_priority(&mut self, packet_id: PacketId, data: Vec<u8>, _priority: PacketPriority) -> Result<(), NetworkError> {
+		self.respond(packet_id, data)
+	}
+	/// Send a packet of given priority to a peer.
+	fn send_with_priority(&mut self, peer_id: PeerId, packet_id: PacketId, data: Vec<u8>, _priority: PacketPriority) -> Result<(), NetworkError> {
+		self.send(peer_id, packet_id, data)
+	}
 	/// Get the blockchain
 	fn chain(&self) -> &BlockChainClient;
 	/// Get the snapshot service.
@@ -92,6 +101,10 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 		self.network.disconnect_peer(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, offense: Offense) {
+		self.network.report_peer(peer_id, offense);
+	}
+
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>{
 		self.network.respond(packet_id, data)
 	}
@@ -104,6 +117,14 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 		self.network.send_protocol(protocol, peer_id, packet_id, data)
 	}
 
+	fn respond_with_priority(&mut self, packet_id: PacketId, data: Vec<u8>, priority: PacketPriority) -> Result<(), NetworkError>{
+		self.network.respond_with_priority(packet_id, data, priority)
+	}
+
+	fn send_with_priority(&mut self, peer_id: PeerId, packet_id: PacketId, data: Vec<u8>, priority: PacketPriority) -> Result<(), NetworkError>{
+		self.network.send_with_priority(peer_id, packet_id, data, priority)
+	}
+
 	fn chain(&self) -> &BlockChainClient {
 		self.chain
 	}
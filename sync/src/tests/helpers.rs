@@ -31,6 +31,7 @@ use sync_io::SyncIo;
 use io::IoChannel;
 use api::WARP_SYNC_PROTOCOL_ID;
 use chain::ChainSync;
+use private_tx::NoopPrivateTxHandler;
 use ::SyncConfig;
 
 pub trait FlushingBlockChainClient: BlockChainClient {
@@ -86,6 +87,10 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		self.to_disconnect.insert(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, _offense: Offense) {
+		self.disconnect_peer(peer_id);
+	}
+
 	fn is_expired(&self) -> bool {
 		false
 	}
@@ -260,7 +265,7 @@ impl TestNet<EthPeer<TestBlockChainClient>> {
 		for _ in 0..n {
 			let chain = TestBlockChainClient::new();
 			let ss = Arc::new(TestSnapshotService::new());
-			let sync = ChainSync::new(config.clone(), &chain);
+			let sync = ChainSync::new(config.clone(), &chain, Arc::new(NoopPrivateTxHandler));
 			net.peers.push(Arc::new(EthPeer {
 				sync: RwLock::new(sync),
 				snapshot_service: ss,
@@ -297,7 +302,7 @@ impl TestNet<EthPeer<EthcoreClient>> {
 		).unwrap();
 
 		let ss = Arc::new(TestSnapshotService::new());
-		let sync = ChainSync::new(config, &*client);
+		let sync = ChainSync::new(config, &*client, Arc::new(NoopPrivateTxHandler));
 		let peer = Arc::new(EthPeer {
 			sync: RwLock::new(sync),
 			snapshot_service: ss,
@@ -24,6 +24,7 @@ use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus};
 use ethcore::header::BlockNumber;
 use ethcore::client::{EachBlockWith};
 use super::helpers::*;
+use snapshot_mirror::MirrorTask;
 use SyncConfig;
 
 pub struct TestSnapshotService {
@@ -84,6 +85,16 @@ impl SnapshotService for TestSnapshotService {
 		self.chunks.get(&hash).cloned()
 	}
 
+	fn chunk_size(&self, hash: H256) -> Option<u64> {
+		self.chunks.get(&hash).map(|chunk| chunk.len() as u64)
+	}
+
+	fn chunk_range(&self, hash: H256, offset: u64, len: u64) -> Option<Bytes> {
+		self.chunks.get(&hash)
+			.and_then(|chunk| chunk.get(offset as usize..(offset + len) as usize))
+			.map(|range| range.to_vec())
+	}
+
 	fn status(&self) -> RestorationStatus {
 		match *self.restoration_manifest.lock() {
 			Some(ref manifest) if self.state_restoration_chunks.lock().len() == manifest.state_hashes.len() &&
@@ -98,6 +109,15 @@ impl SnapshotService for TestSnapshotService {
 		}
 	}
 
+	fn completed_chunks(&self) -> Option<Vec<H256>> {
+		self.restoration_manifest.lock().as_ref().map(|_| {
+			self.state_restoration_chunks.lock().keys()
+				.chain(self.block_restoration_chunks.lock().keys())
+				.cloned()
+				.collect()
+		})
+	}
+
 	fn begin_restore(&self, manifest: ManifestData) {
 		*self.restoration_manifest.lock() = Some(manifest);
 		self.state_restoration_chunks.lock().clear();
@@ -139,3 +159,67 @@ fn snapshot_sync() {
 	assert_eq!(net.peer(4).snapshot_service.block_restoration_chunks.lock().len(), net.peer(0).snapshot_service.manifest.as_ref().unwrap().block_hashes.len());
 }
 
+
+#[test]
+fn snapshot_sync_pinned_to_manifest_hash() {
+	::env_logger::init().ok();
+	let snapshot_service = Arc::new(TestSnapshotService::new_with_snapshot(16, H256::new(), 500000));
+	let mut config = SyncConfig::default();
+	config.warp_sync = true;
+	config.warp_snapshot_hash = Some(keccak(snapshot_service.manifest.as_ref().unwrap().clone().into_rlp()));
+	let mut net = TestNet::new_with_config(2, config);
+	net.peer_mut(0).snapshot_service = snapshot_service.clone();
+	net.peer(0).chain.add_blocks(1, EachBlockWith::Nothing);
+	net.sync_steps(50);
+	assert_eq!(net.peer(1).snapshot_service.state_restoration_chunks.lock().len(), snapshot_service.manifest.as_ref().unwrap().state_hashes.len());
+	assert_eq!(net.peer(1).snapshot_service.block_restoration_chunks.lock().len(), snapshot_service.manifest.as_ref().unwrap().block_hashes.len());
+}
+
+#[test]
+fn snapshot_sync_ignores_unpinned_snapshots() {
+	::env_logger::init().ok();
+	let mut config = SyncConfig::default();
+	config.warp_sync = true;
+	config.warp_snapshot_hash = Some(1.into());
+	let mut net = TestNet::new_with_config(5, config);
+	let snapshot_service = Arc::new(TestSnapshotService::new_with_snapshot(16, H256::new(), 500000));
+	for i in 0..4 {
+		net.peer_mut(i).snapshot_service = snapshot_service.clone();
+		net.peer(i).chain.add_blocks(1, EachBlockWith::Nothing);
+	}
+	net.sync_steps(50);
+	assert!(net.peer(4).snapshot_service.restoration_manifest.lock().is_none());
+}
+
+#[test]
+fn snapshot_sync_from_mirror() {
+	::env_logger::init().ok();
+	let mut config = SyncConfig::default();
+	config.warp_sync = true;
+	let net = TestNet::new_with_config(1, config);
+	let mirror = TestSnapshotService::new_with_snapshot(16, H256::new(), 500000);
+	let manifest = mirror.manifest.as_ref().unwrap().clone();
+	let peer = net.peer(0);
+	let mut io = TestIo::new(&*peer.chain, &peer.snapshot_service, &peer.queue, None);
+	let mut sync = peer.sync.write();
+
+	let hash = sync.on_mirror_manifest(&mut io, &manifest.clone().into_rlp()).unwrap();
+	assert_eq!(hash, keccak(manifest.clone().into_rlp()));
+
+	// a chunk the mirror fails to serve is requested again.
+	let first = match sync.next_mirror_chunk(&mut io, &hash) {
+		MirrorTask::Chunk(chunk) => chunk,
+		task => panic!("Unexpected task {:?}", task),
+	};
+	sync.on_mirror_chunk(&mut io, first, None);
+	assert_eq!(sync.next_mirror_chunk(&mut io, &hash), MirrorTask::Chunk(first));
+	sync.on_mirror_chunk(&mut io, first, Some(b"bad chunk".to_vec()));
+
+	while let MirrorTask::Chunk(chunk) = sync.next_mirror_chunk(&mut io, &hash) {
+		sync.on_mirror_chunk(&mut io, chunk, mirror.chunks.get(&chunk).cloned());
+	}
+
+	assert_eq!(sync.next_mirror_chunk(&mut io, &hash), MirrorTask::Done);
+	assert_eq!(peer.snapshot_service.state_restoration_chunks.lock().len(), manifest.state_hashes.len());
+	assert_eq!(peer.snapshot_service.block_restoration_chunks.lock().len(), manifest.block_hashes.len());
+}
@@ -73,6 +73,12 @@ pub trait Fetch: Clone + Send + Sync + 'static {
 	/// Supports aborting the request in the middle of execution.
 	fn fetch_with_abort(&self, url: &str, abort: Abort) -> Self::Result;
 
+	/// Fetch URL sending additional raw request headers and get a future for the result.
+	/// Implementations not supporting custom headers ignore them.
+	fn fetch_with_headers(&self, url: &str, _headers: Vec<(String, String)>, abort: Abort) -> Self::Result {
+		self.fetch_with_abort(url, abort)
+	}
+
 	/// Fetch URL and get a future for the result.
 	fn fetch(&self, url: &str) -> Self::Result {
 		self.fetch_with_abort(url, Default::default())
@@ -166,12 +172,17 @@ impl Fetch for Client {
 	}
 
 	fn fetch_with_abort(&self, url: &str, abort: Abort) -> Self::Result {
+		self.fetch_with_headers(url, Vec::new(), abort)
+	}
+
+	fn fetch_with_headers(&self, url: &str, headers: Vec<(String, String)>, abort: Abort) -> Self::Result {
 		debug!(target: "fetch", "Fetching from: {:?}", url);
 
 		match self.client() {
 			Ok(client) => {
 				self.pool.spawn(FetchTask {
 					url: url.into(),
+					headers: headers,
 					client: client,
 					limit: self.limit,
 					abort: abort,
@@ -186,6 +197,7 @@ impl Fetch for Client {
 
 struct FetchTask {
 	url: String,
+	headers: Vec<(String, String)>,
 	client: Arc<reqwest::Client>,
 	limit: Option<usize>,
 	abort: Abort,
@@ -203,7 +215,12 @@ impl Future for FetchTask {
 		}
 
 		trace!(target: "fetch", "Starting fetch task: {:?}", self.url);
+		let mut headers = reqwest::header::Headers::new();
+		for &(ref name, ref value) in &self.headers {
+			headers.set_raw(name.clone(), vec![value.clone().into_bytes()]);
+		}
 		let result = self.client.get(&self.url)
+						  .headers(headers)
 						  .header(reqwest::header::UserAgent("Parity Fetch".into()))
 						  .send()?;
 
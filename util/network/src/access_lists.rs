@@ -0,0 +1,306 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persisted lists of allowed and denied nodes and IP ranges, checked on every handshake.
+
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use ipnetwork::IpNetwork;
+use ip_utils::SocketAddrExt;
+use node_table::{Node, NodeId, Json};
+
+/// Kind of access list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessList {
+	/// If not empty, only the listed nodes and IP ranges may connect.
+	Allow,
+	/// The listed nodes and IP ranges may never connect.
+	Deny,
+}
+
+/// Entry of an access list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessEntry {
+	/// Node with given id.
+	Node(NodeId),
+	/// Range of IP addresses.
+	Ip(IpNetwork),
+}
+
+impl AccessEntry {
+	fn matches(&self, id: &NodeId, ip: Option<&IpAddr>) -> bool {
+		match *self {
+			AccessEntry::Node(ref node) => node == id,
+			AccessEntry::Ip(ref network) => ip.map_or(false, |ip| ip.is_within(network)),
+		}
+	}
+}
+
+impl fmt::Display for AccessEntry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			AccessEntry::Node(ref id) => write!(f, "{:?}", id),
+			AccessEntry::Ip(ref network) => write!(f, "{}", network),
+		}
+	}
+}
+
+impl FromStr for AccessEntry {
+	type Err = String;
+
+	/// Parses enode URL, node id or IP range in CIDR notation. Single IP addresses are accepted too.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.starts_with("enode://") {
+			return Node::from_str(s)
+				.map(|node| AccessEntry::Node(node.id))
+				.map_err(|e| format!("Invalid enode URL {}: {:?}", s, e));
+		}
+		if let Ok(id) = s.trim_left_matches("0x").parse() {
+			return Ok(AccessEntry::Node(id));
+		}
+
+		let network = match s.parse() {
+			Ok(IpAddr::V4(_)) => format!("{}/32", s),
+			Ok(IpAddr::V6(_)) => format!("{}/128", s),
+			Err(_) => s.to_owned(),
+		};
+		IpNetwork::from_str(&network)
+			.map(AccessEntry::Ip)
+			.map_err(|e| format!("Invalid node id or IP range {}: {:?}", s, e))
+	}
+}
+
+/// Allow and deny lists, backed by disk file.
+pub struct AccessLists {
+	allow: Vec<AccessEntry>,
+	deny: Vec<AccessEntry>,
+	path: Option<String>,
+}
+
+impl AccessLists {
+	pub fn new(path: Option<String>) -> Self {
+		let (allow, deny) = AccessLists::load(path.clone());
+		AccessLists {
+			allow: allow,
+			deny: deny,
+			path: path,
+		}
+	}
+
+	/// Whether the node connecting from given address may connect.
+	/// Denied entries take precedence over allowed ones.
+	pub fn is_allowed(&self, id: &NodeId, ip: Option<&IpAddr>) -> bool {
+		!self.deny.iter().any(|e| e.matches(id, ip))
+			&& (self.allow.is_empty() || self.allow.iter().any(|e| e.matches(id, ip)))
+	}
+
+	/// Entries of given list.
+	pub fn entries(&self, list: AccessList) -> &[AccessEntry] {
+		match list {
+			AccessList::Allow => &self.allow,
+			AccessList::Deny => &self.deny,
+		}
+	}
+
+	/// Add an entry to given list. Returns `false` if it's already there.
+	pub fn add(&mut self, list: AccessList, entry: AccessEntry) -> bool {
+		{
+			let entries = self.list_mut(list);
+			if entries.contains(&entry) {
+				return false;
+			}
+			entries.push(entry);
+		}
+		self.save();
+		true
+	}
+
+	/// Remove an entry from given list. Returns `false` if it's not there.
+	pub fn remove(&mut self, list: AccessList, entry: &AccessEntry) -> bool {
+		{
+			let entries = self.list_mut(list);
+			match entries.iter().position(|e| e == entry) {
+				Some(index) => { entries.remove(index); },
+				None => return false,
+			}
+		}
+		self.save();
+		true
+	}
+
+	/// Replace both lists.
+	pub fn set(&mut self, allow: Vec<AccessEntry>, deny: Vec<AccessEntry>) {
+		self.allow = allow;
+		self.deny = deny;
+		self.save();
+	}
+
+	fn list_mut(&mut self, list: AccessList) -> &mut Vec<AccessEntry> {
+		match list {
+			AccessList::Allow => &mut self.allow,
+			AccessList::Deny => &mut self.deny,
+		}
+	}
+
+	/// Serialize both lists to JSON.
+	pub fn to_json(&self) -> String {
+		let list = |entries: &[AccessEntry]| entries.iter()
+			.map(|e| format!("\t\t\"{}\"", e))
+			.collect::<Vec<_>>()
+			.join(",\n");
+		format!("{{\n\t\"allow\": [\n{}\n\t],\n\t\"deny\": [\n{}\n\t]\n}}", list(&self.allow), list(&self.deny))
+	}
+
+	/// Parse both lists from JSON.
+	pub fn from_json(json: &str) -> Result<(Vec<AccessEntry>, Vec<AccessEntry>), String> {
+		let json = Json::from_str(json).map_err(|e| format!("Invalid JSON: {:?}", e))?;
+		let list = |name: &str| -> Result<Vec<AccessEntry>, String> {
+			match json.as_object().and_then(|o| o.get(name)) {
+				Some(entries) => entries.as_array()
+					.ok_or_else(|| format!("Expected \"{}\" to be an array", name))?
+					.iter()
+					.map(|e| e.as_string().ok_or_else(|| format!("Expected \"{}\" entries to be strings", name)).and_then(AccessEntry::from_str))
+					.collect(),
+				None => Ok(Vec::new()),
+			}
+		};
+		Ok((list("allow")?, list("deny")?))
+	}
+
+	/// Save the access_lists.json file.
+	pub fn save(&self) {
+		if let Some(ref path) = self.path {
+			let mut path_buf = PathBuf::from(path);
+			if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
+				warn!("Error creating access lists directory: {:?}", e);
+				return;
+			};
+			path_buf.push("access_lists.json");
+			let mut file = match fs::File::create(path_buf.as_path()) {
+				Ok(file) => file,
+				Err(e) => {
+					warn!("Error creating access lists file: {:?}", e);
+					return;
+				}
+			};
+			if let Err(e) = file.write(&self.to_json().into_bytes()) {
+				warn!("Error writing access lists file: {:?}", e);
+			}
+		}
+	}
+
+	fn load(path: Option<String>) -> (Vec<AccessEntry>, Vec<AccessEntry>) {
+		if let Some(path) = path {
+			let mut path_buf = PathBuf::from(path);
+			path_buf.push("access_lists.json");
+			let mut file = match fs::File::open(path_buf.as_path()) {
+				Ok(file) => file,
+				Err(e) => {
+					debug!("Error opening access lists file: {:?}", e);
+					return (Vec::new(), Vec::new());
+				}
+			};
+			let mut buf = String::new();
+			if let Err(e) = file.read_to_string(&mut buf) {
+				warn!("Error reading access lists file: {:?}", e);
+				return (Vec::new(), Vec::new());
+			}
+			match AccessLists::from_json(&buf) {
+				Ok(lists) => return lists,
+				Err(e) => warn!("Error parsing access lists file: {}", e),
+			}
+		}
+		(Vec::new(), Vec::new())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use devtools::RandomTempPath;
+
+	#[test]
+	fn parses_entries() {
+		let id = "a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c";
+		let node = AccessEntry::Node(id.parse().unwrap());
+		assert_eq!(AccessEntry::from_str(id), Ok(node.clone()));
+		assert_eq!(AccessEntry::from_str(&format!("0x{}", id)), Ok(node.clone()));
+		assert_eq!(AccessEntry::from_str(&format!("enode://{}@22.99.55.44:7770", id)), Ok(node.clone()));
+		assert_eq!(format!("{}", node), id);
+
+		let range = AccessEntry::from_str("10.0.0.0/8").unwrap();
+		assert_eq!(format!("{}", range), "10.0.0.0/8");
+		assert_eq!(AccessEntry::from_str("10.1.2.3"), AccessEntry::from_str("10.1.2.3/32"));
+		assert!(AccessEntry::from_str("not an entry").is_err());
+	}
+
+	#[test]
+	fn checks_access() {
+		let mut lists = AccessLists::new(None);
+		let (a, b) = (NodeId::from(1), NodeId::from(2));
+		let (private, public): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "8.8.8.8".parse().unwrap());
+		assert!(lists.is_allowed(&a, Some(&public)));
+
+		assert!(lists.add(AccessList::Allow, "10.0.0.0/8".parse().unwrap()));
+		assert!(!lists.add(AccessList::Allow, "10.0.0.0/8".parse().unwrap()));
+		assert!(lists.is_allowed(&a, Some(&private)));
+		assert!(!lists.is_allowed(&a, Some(&public)));
+		assert!(!lists.is_allowed(&a, None));
+
+		assert!(lists.add(AccessList::Allow, AccessEntry::Node(b.clone())));
+		assert!(lists.is_allowed(&b, Some(&public)));
+
+		// deny takes precedence.
+		assert!(lists.add(AccessList::Deny, AccessEntry::Node(b.clone())));
+		assert!(!lists.is_allowed(&b, Some(&private)));
+
+		assert!(lists.remove(AccessList::Deny, &AccessEntry::Node(b.clone())));
+		assert!(!lists.remove(AccessList::Deny, &AccessEntry::Node(b.clone())));
+		assert!(lists.is_allowed(&b, Some(&private)));
+	}
+
+	#[test]
+	fn json_roundtrip() {
+		let json = r#"{ "allow": ["10.0.0.0/8"], "deny": ["0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a"] }"#;
+		let (allow, deny) = AccessLists::from_json(json).unwrap();
+		assert_eq!(allow, vec!["10.0.0.0/8".parse().unwrap()]);
+		assert_eq!(deny, vec![AccessEntry::Node(NodeId::from(10))]);
+
+		let mut lists = AccessLists::new(None);
+		lists.set(allow.clone(), deny.clone());
+		assert_eq!(AccessLists::from_json(&lists.to_json()), Ok((allow, deny)));
+
+		assert_eq!(AccessLists::from_json("{}"), Ok((vec![], vec![])));
+		assert!(AccessLists::from_json(r#"{ "allow": ["nope"] }"#).is_err());
+		assert!(AccessLists::from_json(r#"{ "deny": "10.0.0.0/8" }"#).is_err());
+	}
+
+	#[test]
+	fn persists_lists() {
+		let temp_path = RandomTempPath::create_dir();
+		{
+			let mut lists = AccessLists::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			lists.add(AccessList::Deny, "192.168.0.0/16".parse().unwrap());
+		}
+		let lists = AccessLists::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+		assert_eq!(lists.entries(AccessList::Deny), &["192.168.0.0/16".parse().unwrap()][..]);
+		assert!(lists.entries(AccessList::Allow).is_empty());
+	}
+}
@@ -0,0 +1,215 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Upload and download rate limiting.
+
+use std::cmp;
+use std::sync::Arc;
+use std::time::Instant;
+use std::usize;
+use parking_lot::{Mutex, RwLock};
+
+/// Transfer rate caps in bytes per second, 0 for no limit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BandwidthLimits {
+	/// Total upload rate.
+	pub upload: u64,
+	/// Total download rate.
+	pub download: u64,
+	/// Upload rate to a single peer.
+	pub peer_upload: u64,
+	/// Download rate from a single peer.
+	pub peer_download: u64,
+}
+
+/// Token bucket holding up to one second worth of transfer.
+#[derive(Clone)]
+struct TokenBucket {
+	rate: u64,
+	tokens: u64,
+	refilled: Instant,
+}
+
+impl TokenBucket {
+	fn new(now: Instant) -> Self {
+		TokenBucket {
+			rate: 0,
+			tokens: 0,
+			refilled: now,
+		}
+	}
+
+	/// Number of bytes which may be transferred at given rate.
+	fn available(&mut self, rate: u64, now: Instant) -> usize {
+		if rate == 0 {
+			self.rate = 0;
+			return usize::MAX;
+		}
+		if rate != self.rate {
+			// start full whenever the limit is set or changed
+			self.rate = rate;
+			self.tokens = rate;
+			self.refilled = now;
+		}
+
+		let elapsed = now.duration_since(self.refilled);
+		let nanos = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+		let refill = (nanos as f64 * rate as f64 / 1_000_000_000f64) as u64;
+		if refill > 0 {
+			self.tokens = cmp::min(rate, self.tokens.saturating_add(refill));
+			self.refilled = now;
+		}
+		cmp::min(self.tokens, usize::MAX as u64) as usize
+	}
+
+	/// Take bytes transferred out of the bucket.
+	fn consume(&mut self, size: usize) {
+		if self.rate != 0 {
+			self.tokens = self.tokens.saturating_sub(size as u64);
+		}
+	}
+}
+
+/// Limits and total transfer shared by all connections.
+pub struct Bandwidth {
+	limits: RwLock<BandwidthLimits>,
+	upload: Mutex<TokenBucket>,
+	download: Mutex<TokenBucket>,
+}
+
+impl Bandwidth {
+	/// Create new shared limiter.
+	pub fn new(limits: BandwidthLimits) -> Self {
+		let now = Instant::now();
+		Bandwidth {
+			limits: RwLock::new(limits),
+			upload: Mutex::new(TokenBucket::new(now)),
+			download: Mutex::new(TokenBucket::new(now)),
+		}
+	}
+
+	/// Current limits.
+	pub fn limits(&self) -> BandwidthLimits {
+		*self.limits.read()
+	}
+
+	/// Change the limits. Applies to established connections immediately.
+	pub fn set_limits(&self, limits: BandwidthLimits) {
+		*self.limits.write() = limits;
+	}
+}
+
+/// Rate limiter of a single connection.
+#[derive(Clone)]
+pub struct PeerBandwidth {
+	shared: Arc<Bandwidth>,
+	upload: TokenBucket,
+	download: TokenBucket,
+}
+
+impl PeerBandwidth {
+	/// Create new limiter of a connection.
+	pub fn new(shared: Arc<Bandwidth>) -> Self {
+		let now = Instant::now();
+		PeerBandwidth {
+			shared: shared,
+			upload: TokenBucket::new(now),
+			download: TokenBucket::new(now),
+		}
+	}
+
+	/// Number of bytes which may be sent now.
+	pub fn upload_allowance(&mut self) -> usize {
+		self.upload_allowance_at(Instant::now())
+	}
+
+	/// Number of bytes which may be received now.
+	pub fn download_allowance(&mut self) -> usize {
+		self.download_allowance_at(Instant::now())
+	}
+
+	fn upload_allowance_at(&mut self, now: Instant) -> usize {
+		let limits = self.shared.limits();
+		let peer = self.upload.available(limits.peer_upload, now);
+		cmp::min(peer, self.shared.upload.lock().available(limits.upload, now))
+	}
+
+	fn download_allowance_at(&mut self, now: Instant) -> usize {
+		let limits = self.shared.limits();
+		let peer = self.download.available(limits.peer_download, now);
+		cmp::min(peer, self.shared.download.lock().available(limits.download, now))
+	}
+
+	/// Account for sent bytes.
+	pub fn uploaded(&mut self, size: usize) {
+		self.upload.consume(size);
+		self.shared.upload.lock().consume(size);
+	}
+
+	/// Account for received bytes.
+	pub fn downloaded(&mut self, size: usize) {
+		self.download.consume(size);
+		self.shared.download.lock().consume(size);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+	use std::usize;
+	use super::{Bandwidth, BandwidthLimits, PeerBandwidth};
+
+	fn limits(upload: u64, peer_upload: u64) -> BandwidthLimits {
+		BandwidthLimits { upload: upload, peer_upload: peer_upload, ..Default::default() }
+	}
+
+	#[test]
+	fn unlimited_by_default() {
+		let mut peer = PeerBandwidth::new(Arc::new(Bandwidth::new(Default::default())));
+		assert_eq!(peer.upload_allowance(), usize::MAX);
+		peer.uploaded(1_000_000);
+		assert_eq!(peer.upload_allowance(), usize::MAX);
+		assert_eq!(peer.download_allowance(), usize::MAX);
+	}
+
+	#[test]
+	fn refills_peer_bucket_at_rate() {
+		let mut peer = PeerBandwidth::new(Arc::new(Bandwidth::new(limits(0, 1000))));
+		let now = Instant::now();
+		assert_eq!(peer.upload_allowance_at(now), 1000);
+		peer.uploaded(1000);
+		assert_eq!(peer.upload_allowance_at(now), 0);
+		assert_eq!(peer.upload_allowance_at(now + Duration::from_millis(250)), 250);
+		// never holds more than a second worth
+		assert_eq!(peer.upload_allowance_at(now + Duration::from_secs(10)), 1000);
+	}
+
+	#[test]
+	fn global_limit_is_shared() {
+		let shared = Arc::new(Bandwidth::new(limits(1000, 800)));
+		let mut a = PeerBandwidth::new(shared.clone());
+		let mut b = PeerBandwidth::new(shared.clone());
+		let now = Instant::now();
+		assert_eq!(a.upload_allowance_at(now), 800);
+		a.uploaded(800);
+		assert_eq!(b.upload_allowance_at(now), 200);
+
+		shared.set_limits(Default::default());
+		assert_eq!(b.upload_allowance_at(now), usize::MAX);
+	}
+}
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::sync::Arc;
 use std::collections::VecDeque;
 use std::net::SocketAddr;
@@ -30,6 +31,7 @@ use error::*;
 use io::{IoContext, StreamToken};
 use handshake::Handshake;
 use stats::NetworkStats;
+use bandwidth::{Bandwidth, PeerBandwidth};
 use rcrypto::blockmodes::*;
 use rcrypto::aessafe::*;
 use rcrypto::symmetriccipher::*;
@@ -62,6 +64,12 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	interest: Ready,
 	/// Shared network statistics
 	stats: Arc<NetworkStats>,
+	/// Transfer rate limiter
+	bandwidth: PeerBandwidth,
+	/// Whether reading stopped because of the rate limits
+	read_throttled: bool,
+	/// Whether writing stopped because of the rate limits
+	write_throttled: bool,
 	/// Registered flag
 	registered: AtomicBool,
 }
@@ -77,16 +85,24 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 
 	/// Readable IO handler. Called when there is some data to be read.
 	pub fn readable(&mut self) -> io::Result<Option<Bytes>> {
+		self.read_throttled = false;
 		if self.rec_size == 0 || self.rec_buf.len() >= self.rec_size {
 			return Ok(None);
 		}
 		let sock_ref = <Socket as Read>::by_ref(&mut self.socket);
 		loop {
-			let max = self.rec_size - self.rec_buf.len();
+			let allowance = self.bandwidth.download_allowance();
+			if allowance == 0 {
+				trace!(target:"network", "{}: Download throttled", self.token);
+				self.read_throttled = true;
+				return Ok(None);
+			}
+			let max = cmp::min(self.rec_size - self.rec_buf.len(), allowance);
 			match sock_ref.take(max as u64).try_read(unsafe { self.rec_buf.bytes_mut() }) {
 				Ok(Some(size)) if size != 0  => {
 					unsafe { self.rec_buf.advance_mut(size); }
 					self.stats.inc_recv(size);
+					self.bandwidth.downloaded(size);
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
@@ -123,8 +139,21 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		self.interest.is_writable()
 	}
 
+	/// Check if the last read was cut short by the rate limits.
+	/// No IO event fires for the data left behind, so the handler has to be called again later.
+	pub fn is_read_throttled(&self) -> bool {
+		self.read_throttled
+	}
+
+	/// Check if the last write was cut short by the rate limits.
+	/// No IO event fires for the data left behind, so the handler has to be called again later.
+	pub fn is_write_throttled(&self) -> bool {
+		self.write_throttled
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, NetworkError> where Message: Send + Clone + Sync + 'static {
+		self.write_throttled = false;
 		{
 			let buf = match self.send_queue.front_mut() {
 				Some(buf) => buf,
@@ -137,14 +166,24 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 				return Ok(WriteStatus::Complete)
 			}
 
-			match self.socket.try_write(Buf::bytes(&buf)) {
+			let allowance = self.bandwidth.upload_allowance();
+			if allowance == 0 {
+				trace!(target:"network", "{}: Upload throttled", self.token);
+				self.write_throttled = true;
+				return Ok(WriteStatus::Ongoing)
+			}
+			let max = cmp::min(send_size - pos, allowance);
+
+			match self.socket.try_write(&Buf::bytes(&buf)[..max]) {
 				Ok(Some(size)) if (pos + size) < send_size => {
 					buf.advance(size);
 					self.stats.inc_send(size);
+					self.bandwidth.uploaded(size);
 					Ok(WriteStatus::Ongoing)
 				},
 				Ok(Some(size)) if (pos + size) == send_size => {
 					self.stats.inc_send(size);
+					self.bandwidth.uploaded(size);
 					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
 					Ok(WriteStatus::Complete)
 				},
@@ -170,7 +209,7 @@ pub type Connection = GenericConnection<TcpStream>;
 
 impl Connection {
 	/// Create a new connection with given id and socket.
-	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>) -> Connection {
+	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>, bandwidth: Arc<Bandwidth>) -> Connection {
 		Connection {
 			token: token,
 			socket: socket,
@@ -179,6 +218,9 @@ impl Connection {
 			rec_size: 0,
 			interest: Ready::hup() | Ready::readable(),
 			stats: stats,
+			bandwidth: PeerBandwidth::new(bandwidth),
+			read_throttled: false,
+			write_throttled: false,
 			registered: AtomicBool::new(false),
 		}
 	}
@@ -213,6 +255,9 @@ impl Connection {
 			send_queue: self.send_queue.clone(),
 			interest: Ready::hup(),
 			stats: self.stats.clone(),
+			bandwidth: self.bandwidth.clone(),
+			read_throttled: false,
+			write_throttled: false,
 			registered: AtomicBool::new(false),
 		})
 	}
@@ -507,6 +552,7 @@ mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::AtomicBool;
 	use super::super::stats::*;
+	use bandwidth::{Bandwidth, BandwidthLimits, PeerBandwidth};
 	use std::io::{Read, Write, Error, Cursor, ErrorKind};
 	use mio::{Ready};
 	use std::collections::VecDeque;
@@ -556,6 +602,9 @@ mod tests {
 				rec_size: 0,
 				interest: Ready::hup() | Ready::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+				bandwidth: PeerBandwidth::new(Arc::new(Bandwidth::new(Default::default()))),
+				read_throttled: false,
+			write_throttled: false,
 				registered: AtomicBool::new(false),
 			}
 		}
@@ -579,6 +628,9 @@ mod tests {
 				rec_size: 0,
 				interest: Ready::hup() | Ready::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+				bandwidth: PeerBandwidth::new(Arc::new(Bandwidth::new(Default::default()))),
+				read_throttled: false,
+			write_throttled: false,
 				registered: AtomicBool::new(false),
 			}
 		}
@@ -629,6 +681,36 @@ mod tests {
 		assert_eq!(1024, connection.socket.write_buffer.len());
 	}
 
+	#[test]
+	fn connection_write_is_throttled() {
+		let mut connection = TestConnection::new();
+		let limits = BandwidthLimits { peer_upload: 4096, ..Default::default() };
+		connection.bandwidth = PeerBandwidth::new(Arc::new(Bandwidth::new(limits)));
+		let data = Cursor::new(vec![0; 10240]);
+		connection.send_queue.push_back(data);
+
+		let status = connection.writable(&test_io());
+		assert!(WriteStatus::Ongoing == status.unwrap());
+		assert_eq!(4096, connection.socket.write_buffer.len());
+	}
+
+	#[test]
+	fn connection_read_stays_throttled_after_write() {
+		let mut connection = TestConnection::new();
+		let limits = BandwidthLimits { peer_download: 1024, ..Default::default() };
+		connection.bandwidth = PeerBandwidth::new(Arc::new(Bandwidth::new(limits)));
+		connection.rec_size = 4096;
+		connection.socket.read_buffer = vec![99; 4096];
+
+		assert!(connection.readable().unwrap().is_none());
+		assert!(connection.is_read_throttled());
+		assert!(connection.socket.cursor < 4096);
+
+		assert!(WriteStatus::Complete == connection.writable(&test_io()).unwrap());
+		assert!(connection.is_read_throttled());
+		assert!(!connection.is_write_throttled());
+	}
+
 	#[test]
 	fn connection_write_to_broken() {
 		let mut connection = TestBrokenConnection::new();
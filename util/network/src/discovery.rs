@@ -30,9 +30,8 @@ use node_table::*;
 use error::NetworkError;
 use io::{StreamToken, IoContext};
 use ethkey::{Secret, KeyPair, sign, recover};
-use IpFilter;
-
-use PROTOCOL_VERSION;
+use ip_utils::bind_udp_socket;
+use {IpFilter, IpVersion};
 
 const ADDRESS_BYTES_SIZE: u32 = 32;							// Size of address type in bytes.
 const ADDRESS_BITS: u32 = 8 * ADDRESS_BYTES_SIZE;			// Denoted by n in [Kademlia].
@@ -42,14 +41,28 @@ const BUCKET_SIZE: usize = 16;		// Denoted by k in [Kademlia]. Number of nodes s
 const ALPHA: usize = 3;				// Denoted by \alpha in [Kademlia]. Number of concurrent FindNode requests.
 const MAX_DATAGRAM_SIZE: usize = 1280;
 
+// Node discovery version, independent of the RLPx version.
+const PROTOCOL_VERSION: u32 = 4;
+
 const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+// Topic advertisement, loosely following discv5. Packets 5 and 6 are taken by ENR (EIP-868).
+const PACKET_TOPIC_REGISTER: u8 = 8;
+const PACKET_TOPIC_QUERY: u8 = 9;
+const PACKET_TOPIC_NODES: u8 = 10;
 
 const PING_TIMEOUT_MS: u64 = 300;
 const MAX_NODES_PING: usize = 32; // Max nodes to add/ping at once
 
+const MAX_TOPIC_LEN: usize = 32;
+const TOPIC_REPLICATION: usize = 8;				// Number of nodes nearest to the topic hash an advertisement is registered with.
+const MAX_TOPICS: usize = 64;					// Max distinct topics advertisements are stored for.
+const MAX_TOPIC_ADS: usize = 32;				// Max advertisements stored per topic.
+const MAX_TOPIC_RESULTS: usize = 128;			// Max nodes remembered per searched topic.
+const TOPIC_AD_LIFETIME_SEC: i64 = 15 * 60;
+
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
 	pub id: NodeId,
@@ -60,10 +73,36 @@ pub struct BucketEntry {
 	pub address: NodeEntry,
 	pub id_hash: H256,
 	pub timeout: Option<u64>,
+	/// When the node first answered or pinged us.
+	pub first_seen: Option<u64>,
+	/// When the node last answered or pinged us.
+	pub last_seen: Option<u64>,
+}
+
+impl BucketEntry {
+	fn new(address: NodeEntry, id_hash: H256) -> Self {
+		BucketEntry {
+			address: address,
+			id_hash: id_hash,
+			timeout: None,
+			first_seen: None,
+			last_seen: None,
+		}
+	}
+
+	fn seen(&mut self, now: u64) {
+		self.timeout = None;
+		self.last_seen = Some(now);
+		if self.first_seen.is_none() {
+			self.first_seen = Some(now);
+		}
+	}
 }
 
 pub struct NodeBucket {
-	nodes: VecDeque<BucketEntry>, //sorted by last active
+	nodes: VecDeque<BucketEntry>, //sorted by last seen, most recent first
+	/// Candidates for the slots of unresponsive nodes, most recent first.
+	replacements: VecDeque<BucketEntry>,
 }
 
 impl Default for NodeBucket {
@@ -75,9 +114,26 @@ impl Default for NodeBucket {
 impl NodeBucket {
 	fn new() -> Self {
 		NodeBucket {
-			nodes: VecDeque::new()
+			nodes: VecDeque::new(),
+			replacements: VecDeque::new(),
 		}
 	}
+
+	/// Takes the replacement which has been responsive for the longest time,
+	/// or the most recently added one if none of them answered yet.
+	fn take_replacement(&mut self) -> Option<BucketEntry> {
+		let index = self.replacements.iter()
+			.enumerate()
+			.filter_map(|(i, e)| e.first_seen.map(|t| (t, i)))
+			.min()
+			.map_or(0, |(_, i)| i);
+		self.replacements.remove(index)
+	}
+}
+
+struct TopicAd {
+	entry: NodeEntry,
+	expires: i64,
 }
 
 struct Datagramm {
@@ -85,6 +141,13 @@ struct Datagramm {
 	address: SocketAddr,
 }
 
+/// Socket of the other IP version, used alongside the primary one on dual-stack hosts.
+struct SecondarySocket {
+	udp_socket: UdpSocket,
+	token: StreamToken,
+	public_endpoint: NodeEndpoint,
+}
+
 pub struct Discovery {
 	id: NodeId,
 	id_hash: H256,
@@ -92,6 +155,7 @@ pub struct Discovery {
 	public_endpoint: NodeEndpoint,
 	udp_socket: UdpSocket,
 	token: StreamToken,
+	secondary: Option<SecondarySocket>,
 	discovery_round: u16,
 	discovery_id: NodeId,
 	discovery_nodes: HashSet<NodeId>,
@@ -100,22 +164,32 @@ pub struct Discovery {
 	check_timestamps: bool,
 	adding_nodes: Vec<NodeEntry>,
 	ip_filter: IpFilter,
+	ip_version: IpVersion,
+	/// Topics we advertise ourselves under.
+	advertised_topics: HashSet<String>,
+	/// Topics we look for nodes of, with the nodes found so far.
+	searched_topics: HashMap<String, HashSet<NodeId>>,
+	/// Advertisements registered with us by other nodes.
+	topic_ads: HashMap<String, VecDeque<TopicAd>>,
 }
 
 pub struct TableUpdates {
 	pub added: HashMap<NodeId, NodeEntry>,
 	pub removed: HashSet<NodeId>,
+	/// Nodes which proved to be alive.
+	pub seen: HashSet<NodeId>,
 }
 
 impl Discovery {
-	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, ip_filter: IpFilter) -> Discovery {
-		let socket = UdpSocket::bind(&listen).expect("Error binding UDP socket");
+	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, ip_filter: IpFilter, ip_version: IpVersion) -> Discovery {
+		let socket = bind_udp_socket(&listen).expect("Error binding UDP socket");
 		Discovery {
 			id: key.public().clone(),
 			id_hash: keccak(key.public()),
 			secret: key.secret().clone(),
 			public_endpoint: public,
 			token: token,
+			secondary: None,
 			discovery_round: 0,
 			discovery_id: NodeId::new(),
 			discovery_nodes: HashSet::new(),
@@ -125,9 +199,58 @@ impl Discovery {
 			check_timestamps: true,
 			adding_nodes: Vec::new(),
 			ip_filter: ip_filter,
+			ip_version: ip_version,
+			advertised_topics: HashSet::new(),
+			searched_topics: HashMap::new(),
+			topic_ads: HashMap::new(),
 		}
 	}
 
+	/// Bind a socket of the other IP version, for dual-stack hosts. Datagrams are sent from the
+	/// socket matching the IP version of the destination.
+	pub fn bind_secondary(&mut self, listen: SocketAddr, public: NodeEndpoint, token: StreamToken) -> Result<(), NetworkError> {
+		let socket = bind_udp_socket(&listen)?;
+		self.secondary = Some(SecondarySocket { udp_socket: socket, token: token, public_endpoint: public });
+		Ok(())
+	}
+
+	/// Endpoint to announce to a node at given address.
+	fn public_endpoint_for(&self, address: &SocketAddr) -> &NodeEndpoint {
+		match self.secondary {
+			Some(ref s) if s.public_endpoint.address.is_ipv6() == address.is_ipv6() => &s.public_endpoint,
+			_ => &self.public_endpoint,
+		}
+	}
+
+	/// Socket to send datagrams to given address from.
+	fn socket_for(&self, address: &SocketAddr) -> &UdpSocket {
+		match self.secondary {
+			Some(ref s) if s.public_endpoint.address.is_ipv6() == address.is_ipv6() => &s.udp_socket,
+			_ => &self.udp_socket,
+		}
+	}
+
+	/// Advertise this node under given topic. The advertisement is registered with the nodes
+	/// nearest to the topic hash and renewed on every refresh.
+	pub fn advertise_topic(&mut self, topic: &str) {
+		if topic.len() <= MAX_TOPIC_LEN && self.advertised_topics.insert(topic.to_owned()) {
+			self.register_topic(topic);
+		}
+	}
+
+	/// Look for nodes advertised under given topic. The search is repeated on every refresh.
+	pub fn search_topic(&mut self, topic: &str) {
+		if topic.len() <= MAX_TOPIC_LEN && !self.searched_topics.contains_key(topic) {
+			self.searched_topics.insert(topic.to_owned(), HashSet::new());
+			self.query_topic(topic);
+		}
+	}
+
+	/// Nodes found advertised under any of the searched topics.
+	pub fn topic_nodes(&self) -> HashSet<NodeId> {
+		self.searched_topics.values().flat_map(|nodes| nodes.iter().cloned()).collect()
+	}
+
 	/// Add a new node to discovery table. Pings the node.
 	pub fn add_node(&mut self, e: NodeEntry) {
 		if self.is_allowed(&e) {
@@ -152,37 +275,60 @@ impl Discovery {
 		}
 	}
 
-	fn update_node(&mut self, e: NodeEntry) {
+	/// Insert or update a node. Of the nodes reachable over both IP versions, the endpoint of
+	/// the preferred version is kept. Returns the resulting entry.
+	///
+	/// New nodes don't displace the nodes of a full bucket. They are kept as replacements
+	/// while the least recently seen node of the bucket is pinged, and take its slot
+	/// only if it doesn't answer.
+	fn update_node(&mut self, e: NodeEntry) -> NodeEntry {
 		trace!(target: "discovery", "Inserting {:?}", &e);
 		let id_hash = keccak(e.id);
-		let ping = {
+		let ip_version = self.ip_version;
+		let (entry, ping) = {
 			let mut bucket = &mut self.node_buckets[Discovery::distance(&self.id_hash, &id_hash) as usize];
-			let updated = if let Some(node) = bucket.nodes.iter_mut().find(|n| n.address.id == e.id) {
-				node.address = e.clone();
-				node.timeout = None;
-				true
-			} else { false };
-
-			if !updated {
-				bucket.nodes.push_front(BucketEntry { address: e, timeout: None, id_hash: id_hash, });
+			let updated = if let Some(node) = bucket.nodes.iter_mut().chain(bucket.replacements.iter_mut()).find(|n| n.address.id == e.id) {
+				if ip_version.is_preferred(&e.endpoint.address.ip(), &node.address.endpoint.address.ip()) {
+					node.address = e.clone();
+				}
+				Some(node.address.clone())
+			} else { None };
+
+			match updated {
+				Some(entry) => (entry, None),
+				None if bucket.nodes.len() < BUCKET_SIZE => {
+					bucket.nodes.push_back(BucketEntry::new(e.clone(), id_hash));
+					(e, None)
+				},
+				None => {
+					bucket.replacements.push_front(BucketEntry::new(e.clone(), id_hash));
+					bucket.replacements.truncate(BUCKET_SIZE);
+
+					// check if the least recently seen node is still alive.
+					let last = bucket.nodes.back_mut().expect("Bucket is full, so the last item is present; qed");
+					if last.timeout.is_none() {
+						last.timeout = Some(time::precise_time_ns());
+						(e, Some(last.address.endpoint.clone()))
+					} else { (e, None) }
+				},
 			}
-
-			if bucket.nodes.len() > BUCKET_SIZE {
-				//ping least active node
-				let mut last = bucket.nodes.back_mut().expect("Last item is always present when len() > 0");
-				last.timeout = Some(time::precise_time_ns());
-				Some(last.address.endpoint.clone())
-			} else { None }
 		};
 		if let Some(endpoint) = ping {
 			self.ping(&endpoint);
 		}
+		entry
 	}
 
-	fn clear_ping(&mut self, id: &NodeId) {
+	/// Notes that the node answered or pinged us, moving it to the front of its bucket.
+	fn note_seen(&mut self, id: &NodeId) {
+		let now = time::precise_time_ns();
 		let mut bucket = &mut self.node_buckets[Discovery::distance(&self.id_hash, &keccak(id)) as usize];
-		if let Some(node) = bucket.nodes.iter_mut().find(|n| &n.address.id == id) {
-			node.timeout = None;
+		if let Some(index) = bucket.nodes.iter().position(|n| &n.address.id == id) {
+			let mut node = bucket.nodes.remove(index).expect("Index is returned by position; qed");
+			node.seen(now);
+			bucket.nodes.push_front(node);
+		} else if let Some(node) = bucket.replacements.iter_mut().find(|n| &n.address.id == id) {
+			node.seen(now);
 		}
 	}
 
@@ -246,7 +392,7 @@ impl Discovery {
 	fn ping(&mut self, node: &NodeEndpoint) {
 		let mut rlp = RlpStream::new_list(3);
 		rlp.append(&PROTOCOL_VERSION);
-		self.public_endpoint.to_rlp_list(&mut rlp);
+		self.public_endpoint_for(&node.address).to_rlp_list(&mut rlp);
 		node.to_rlp_list(&mut rlp);
 		trace!(target: "discovery", "Sent Ping to {:?}", &node);
 		self.send_packet(PACKET_PING, &node.udp_address(), &rlp.drain());
@@ -281,16 +427,19 @@ impl Discovery {
 		self.send_to(packet, address.clone());
 	}
 
-	#[cfg_attr(feature="dev", allow(map_clone))]
 	fn nearest_node_entries(target: &NodeId, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
+		Discovery::nearest_node_entries_to_hash(&keccak(target), buckets)
+	}
+
+	#[cfg_attr(feature="dev", allow(map_clone))]
+	fn nearest_node_entries_to_hash(target_hash: &H256, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
 		let mut found: BTreeMap<u32, Vec<&NodeEntry>> = BTreeMap::new();
 		let mut count = 0;
-		let target_hash = keccak(target);
 
 		// Sort nodes by distance to target
 		for bucket in buckets {
 			for node in &bucket.nodes {
-				let distance = Discovery::distance(&target_hash, &node.id_hash);
+				let distance = Discovery::distance(target_hash, &node.id_hash);
 				found.entry(distance).or_insert_with(Vec::new).push(&node.address);
 				if count == BUCKET_SIZE {
 					// delete the most distant element
@@ -318,7 +467,8 @@ impl Discovery {
 
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) where Message: Send + Sync + Clone {
 		while let Some(data) = self.send_queue.pop_front() {
-			match self.udp_socket.send_to(&data.payload, &data.address) {
+			let sent = self.socket_for(&data.address).send_to(&data.payload, &data.address);
+			match sent {
 				Ok(Some(size)) if size == data.payload.len() => {
 				},
 				Ok(Some(_)) => {
@@ -341,10 +491,14 @@ impl Discovery {
 		self.send_queue.push_back(Datagramm { payload: payload, address: address });
 	}
 
-	pub fn readable<Message>(&mut self, io: &IoContext<Message>) -> Option<TableUpdates> where Message: Send + Sync + Clone {
+	pub fn readable<Message>(&mut self, io: &IoContext<Message>, token: StreamToken) -> Option<TableUpdates> where Message: Send + Sync + Clone {
 		let mut buf: [u8; MAX_DATAGRAM_SIZE] = unsafe { mem::uninitialized() };
 		let writable = !self.send_queue.is_empty();
-		let res = match self.udp_socket.recv_from(&mut buf) {
+		let received = match self.secondary {
+			Some(ref s) if s.token == token => s.udp_socket.recv_from(&mut buf),
+			_ => self.udp_socket.recv_from(&mut buf),
+		};
+		let res = match received {
 			Ok(Some((len, address))) => self.on_packet(&buf[0..len], address).unwrap_or_else(|e| {
 				debug!("Error processing UDP packet: {:?}", e);
 				None
@@ -384,6 +538,9 @@ impl Discovery {
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
 			PACKET_FIND_NODE => self.on_find_node(&rlp, &node_id, &from),
 			PACKET_NEIGHBOURS => self.on_neighbours(&rlp, &node_id, &from),
+			PACKET_TOPIC_REGISTER => self.on_topic_register(&rlp, &node_id, &from),
+			PACKET_TOPIC_QUERY => self.on_topic_query(&rlp, &node_id, &from),
+			PACKET_TOPIC_NODES => self.on_topic_nodes(&rlp, &node_id, &from),
 			_ => {
 				debug!("Unknown UDP packet: {}", packet_id);
 				Ok(None)
@@ -400,13 +557,12 @@ impl Discovery {
 	}
 
 	fn is_allowed(&self, entry: &NodeEntry) -> bool {
-		entry.endpoint.is_allowed(&self.ip_filter) && entry.id != self.id
+		entry.endpoint.is_allowed(&self.ip_filter) && self.ip_version.allows(&entry.endpoint.address.ip()) && entry.id != self.id
 	}
 
 	fn on_ping(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
 		trace!(target: "discovery", "Got Ping from {:?}", &from);
 		let source = NodeEndpoint::from_rlp(&rlp.at(1)?)?;
-		let dest = NodeEndpoint::from_rlp(&rlp.at(2)?)?;
 		let timestamp: u64 = rlp.val_at(3)?;
 		self.check_timestamp(timestamp)?;
 		let mut added_map = HashMap::new();
@@ -416,16 +572,21 @@ impl Discovery {
 		} else if !self.is_allowed(&entry) {
 			debug!(target: "discovery", "Address not allowed: {:?}", entry);
 		} else {
-			self.update_node(entry.clone());
+			let entry = self.update_node(entry);
 			added_map.insert(node.clone(), entry);
 		}
+		self.note_seen(node);
+		// The pong proves the endpoint the ping was received from.
+		let to = NodeEndpoint { address: SocketAddr::new(from.ip(), source.address.port()), udp_port: from.port() };
 		let hash = keccak(rlp.as_raw());
 		let mut response = RlpStream::new_list(2);
-		dest.to_rlp_list(&mut response);
+		to.to_rlp_list(&mut response);
 		response.append(&hash);
 		self.send_packet(PACKET_PONG, from, &response.drain());
 
-		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new() }))
+		let mut seen = HashSet::new();
+		seen.insert(node.clone());
+		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new(), seen: seen }))
 	}
 
 	fn on_pong(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
@@ -434,15 +595,13 @@ impl Discovery {
 		let dest = NodeEndpoint::from_rlp(&rlp.at(0)?)?;
 		let timestamp: u64 = rlp.val_at(2)?;
 		self.check_timestamp(timestamp)?;
-		let mut entry = NodeEntry { id: node.clone(), endpoint: dest };
-		if !entry.endpoint.is_valid() {
-			debug!(target: "discovery", "Bad address: {:?}", entry);
-			entry.endpoint.address = from.clone();
+		if !dest.is_valid() {
+			debug!(target: "discovery", "Bad address: {:?}", dest);
 		}
-		self.clear_ping(node);
-		let mut added_map = HashMap::new();
-		added_map.insert(node.clone(), entry);
-		Ok(None)
+		self.note_seen(node);
+		let mut seen = HashSet::new();
+		seen.insert(node.clone());
+		Ok(Some(TableUpdates { added: HashMap::new(), removed: HashSet::new(), seen: seen }))
 	}
 
 	fn on_find_node(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
@@ -497,11 +656,119 @@ impl Discovery {
 				debug!(target: "discovery", "Address not allowed: {:?}", entry);
 				continue;
 			}
-			added.insert(node_id, entry.clone());
 			self.ping(&entry.endpoint);
-			self.update_node(entry);
+			let entry = self.update_node(entry);
+			added.insert(node_id, entry);
 		}
-		Ok(Some(TableUpdates { added: added, removed: HashSet::new() }))
+		Ok(Some(TableUpdates { added: added, removed: HashSet::new(), seen: HashSet::new() }))
+	}
+
+	fn on_topic_register(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let source = NodeEndpoint::from_rlp(&rlp.at(0)?)?;
+		let topic: String = rlp.val_at(1)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		trace!(target: "discovery", "Got TopicRegister {} from {:?}", topic, &from);
+		if topic.len() > MAX_TOPIC_LEN {
+			return Err(NetworkError::BadProtocol);
+		}
+		// Only the address the request came from can be advertised.
+		let mut address = from.clone();
+		address.set_port(source.address.port());
+		let entry = NodeEntry { id: node.clone(), endpoint: NodeEndpoint { address: address, udp_port: from.port() } };
+		if !entry.endpoint.is_valid() || !self.is_allowed(&entry) {
+			debug!(target: "discovery", "Rejected topic advertisement of {:?}", entry);
+			return Ok(None);
+		}
+
+		self.expire_topic_ads();
+		if self.topic_ads.len() >= MAX_TOPICS && !self.topic_ads.contains_key(&topic) {
+			trace!(target: "discovery", "Too many topics, ignoring {}", topic);
+			return Ok(None);
+		}
+		let ads = self.topic_ads.entry(topic).or_insert_with(VecDeque::new);
+		ads.retain(|ad| ad.entry.id != entry.id);
+		if ads.len() >= MAX_TOPIC_ADS {
+			ads.pop_front();
+		}
+		ads.push_back(TopicAd { entry: entry, expires: time::get_time().sec + TOPIC_AD_LIFETIME_SEC });
+		Ok(None)
+	}
+
+	fn on_topic_query(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let topic: String = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(1)?;
+		self.check_timestamp(timestamp)?;
+		trace!(target: "discovery", "Got TopicQuery {} from {:?}", topic, &from);
+		self.expire_topic_ads();
+		let nodes: Vec<NodeEntry> = match self.topic_ads.get(&topic) {
+			Some(ads) => ads.iter().map(|ad| ad.entry.clone()).collect(),
+			None => return Ok(None),
+		};
+		let mut packets = Discovery::prepare_topic_nodes_packets(&topic, &nodes);
+		for p in packets.drain(..) {
+			self.send_packet(PACKET_TOPIC_NODES, from, &p);
+		}
+		trace!(target: "discovery", "Sent {} TopicNodes to {:?}", nodes.len(), &from);
+		Ok(None)
+	}
+
+	fn prepare_topic_nodes_packets(topic: &str, nodes: &[NodeEntry]) -> Vec<Bytes> {
+		let limit = (MAX_DATAGRAM_SIZE - 109 - MAX_TOPIC_LEN - 1) / 90;
+		nodes.chunks(limit).map(|c| {
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(&topic);
+			rlp.begin_list(c.len());
+			for n in c {
+				rlp.begin_list(4);
+				n.endpoint.to_rlp(&mut rlp);
+				rlp.append(&n.id);
+			}
+			rlp.out()
+		}).collect()
+	}
+
+	fn on_topic_nodes(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let topic: String = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		if !self.searched_topics.contains_key(&topic) {
+			debug!(target: "discovery", "Unexpected TopicNodes {} from {:?}", topic, &from);
+			return Ok(None);
+		}
+		trace!(target: "discovery", "Got {} TopicNodes {} from {:?}", rlp.at(1)?.item_count()?, topic, &from);
+		let mut added = HashMap::new();
+		for r in rlp.at(1)?.iter() {
+			let endpoint = NodeEndpoint::from_rlp(&r)?;
+			if !endpoint.is_valid() {
+				debug!(target: "discovery", "Bad address: {:?}", endpoint);
+				continue;
+			}
+			let node_id: NodeId = r.val_at(3)?;
+			let entry = NodeEntry { id: node_id.clone(), endpoint: endpoint };
+			if !self.is_allowed(&entry) {
+				debug!(target: "discovery", "Address not allowed: {:?}", entry);
+				continue;
+			}
+			{
+				let found = self.searched_topics.get_mut(&topic).expect("Checked above; qed");
+				if found.len() < MAX_TOPIC_RESULTS {
+					found.insert(node_id.clone());
+				}
+			}
+			self.ping(&entry.endpoint);
+			let entry = self.update_node(entry);
+			added.insert(node_id, entry);
+		}
+		Ok(Some(TableUpdates { added: added, removed: HashSet::new(), seen: HashSet::new() }))
+	}
+
+	fn expire_topic_ads(&mut self) {
+		let now = time::get_time().sec;
+		for ads in self.topic_ads.values_mut() {
+			ads.retain(|ad| ad.expires > now);
+		}
+		self.topic_ads.retain(|_, ads| !ads.is_empty());
 	}
 
 	fn check_expired(&mut self, force: bool) -> HashSet<NodeId> {
@@ -520,6 +787,12 @@ impl Discovery {
 					}
 				} else { true }
 			});
+			while bucket.nodes.len() < BUCKET_SIZE {
+				match bucket.take_replacement() {
+					Some(node) => bucket.nodes.push_back(node),
+					None => break,
+				}
+			}
 		}
 		removed
 	}
@@ -528,16 +801,48 @@ impl Discovery {
 		let removed = self.check_expired(false);
 		self.discover();
 		if !removed.is_empty() {
-			Some(TableUpdates { added: HashMap::new(), removed: removed })
+			Some(TableUpdates { added: HashMap::new(), removed: removed, seen: HashSet::new() })
 		} else { None }
 	}
 
 	pub fn refresh(&mut self) {
 		self.start();
+		let advertised: Vec<_> = self.advertised_topics.iter().cloned().collect();
+		for topic in advertised {
+			self.register_topic(&topic);
+		}
+		let searched: Vec<_> = self.searched_topics.keys().cloned().collect();
+		for topic in searched {
+			self.query_topic(&topic);
+		}
+	}
+
+	fn register_topic(&mut self, topic: &str) {
+		let nearest = Discovery::nearest_node_entries_to_hash(&keccak(topic.as_bytes()), &self.node_buckets);
+		for node in nearest.into_iter().take(TOPIC_REPLICATION) {
+			let mut rlp = RlpStream::new_list(2);
+			self.public_endpoint_for(&node.endpoint.address).to_rlp_list(&mut rlp);
+			rlp.append(&topic);
+			self.send_packet(PACKET_TOPIC_REGISTER, &node.endpoint.udp_address(), &rlp.drain());
+			trace!(target: "discovery", "Sent TopicRegister {} to {:?}", topic, &node.endpoint);
+		}
+	}
+
+	fn query_topic(&mut self, topic: &str) {
+		let nearest = Discovery::nearest_node_entries_to_hash(&keccak(topic.as_bytes()), &self.node_buckets);
+		for node in nearest.into_iter().take(TOPIC_REPLICATION) {
+			let mut rlp = RlpStream::new_list(1);
+			rlp.append(&topic);
+			self.send_packet(PACKET_TOPIC_QUERY, &node.endpoint.udp_address(), &rlp.drain());
+			trace!(target: "discovery", "Sent TopicQuery {} to {:?}", topic, &node.endpoint);
+		}
 	}
 
 	pub fn register_socket<Host:Handler>(&self, event_loop: &mut EventLoop<Host>) -> Result<(), NetworkError> {
 		event_loop.register(&self.udp_socket, Token(self.token), Ready::all(), PollOpt::edge()).expect("Error registering UDP socket");
+		if let Some(ref s) = self.secondary {
+			event_loop.register(&s.udp_socket, Token(s.token), Ready::all(), PollOpt::edge()).expect("Error registering UDP socket");
+		}
 		Ok(())
 	}
 
@@ -548,6 +853,9 @@ impl Discovery {
 			Ready::readable()
 		};
 		event_loop.reregister(&self.udp_socket, Token(self.token), registration, PollOpt::edge()).expect("Error reregistering UDP socket");
+		if let Some(ref s) = self.secondary {
+			event_loop.reregister(&s.udp_socket, Token(s.token), registration, PollOpt::edge()).expect("Error reregistering UDP socket");
+		}
 		Ok(())
 	}
 }
@@ -585,8 +893,8 @@ mod tests {
 		let key2 = Random.generate().unwrap();
 		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40444").unwrap(), udp_port: 40444 };
 		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40445").unwrap(), udp_port: 40445 };
-		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default());
-		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default());
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), IpVersion::default());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), IpVersion::default());
 
 		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7770").unwrap();
 		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7771").unwrap();
@@ -614,11 +922,119 @@ mod tests {
 		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 3)
 	}
 
+	#[test]
+	fn topic_search() {
+		let keys: Vec<_> = (0..3).map(|_| Random.generate().unwrap()).collect();
+		let eps: Vec<_> = (0..3).map(|i| {
+			let address = SocketAddr::from_str(&format!("127.0.0.1:{}", 40450 + i)).unwrap();
+			NodeEndpoint { address: address, udp_port: address.port() }
+		}).collect();
+		let mut nodes: Vec<_> = (0..3).map(|i| Discovery::new(&keys[i], eps[i].address.clone(), eps[i].clone(), 0, IpFilter::default(), IpVersion::default())).collect();
+
+		// 0 advertises, 1 stores the advertisement, 2 searches.
+		nodes[0].init_node_list(vec![NodeEntry { id: keys[1].public().clone(), endpoint: eps[1].clone() }]);
+		nodes[2].init_node_list(vec![NodeEntry { id: keys[1].public().clone(), endpoint: eps[1].clone() }]);
+		nodes[0].advertise_topic("pip");
+		exchange(&mut nodes, &eps);
+		nodes[2].search_topic("pip");
+		nodes[2].search_topic("other");
+		exchange(&mut nodes, &eps);
+
+		let found = nodes[2].topic_nodes();
+		assert_eq!(found.len(), 1);
+		assert!(found.contains(keys[0].public()));
+	}
+
+	#[test]
+	fn topic_ads_are_limited() {
+		let key = Random.generate().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40453").unwrap(), udp_port: 40453 };
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), IpVersion::default());
+		let from = SocketAddr::from_str("127.0.0.1:40454").unwrap();
+
+		let register = |topic: &str| {
+			let mut rlp = RlpStream::new_list(3);
+			ep.to_rlp_list(&mut rlp);
+			rlp.append(&topic);
+			rlp.append(&(time::get_time().sec as u64 + 60));
+			rlp.out()
+		};
+		for _ in 0..(MAX_TOPIC_ADS + 5) {
+			discovery.on_topic_register(&UntrustedRlp::new(&register("pip")), &NodeId::random(), &from).unwrap();
+		}
+		assert_eq!(discovery.topic_ads["pip"].len(), MAX_TOPIC_ADS);
+
+		let long_topic: String = ::std::iter::repeat('x').take(MAX_TOPIC_LEN + 1).collect();
+		assert!(discovery.on_topic_register(&UntrustedRlp::new(&register(&long_topic)), &NodeId::random(), &from).is_err());
+
+		for ad in discovery.topic_ads.get_mut("pip").unwrap().iter_mut() {
+			ad.expires = 0;
+		}
+		discovery.expire_topic_ads();
+		assert!(discovery.topic_ads.is_empty());
+	}
+
+	fn exchange(nodes: &mut [Discovery], eps: &[NodeEndpoint]) {
+		for _ in 0..5 {
+			for i in 0..nodes.len() {
+				let datagrams: Vec<_> = nodes[i].send_queue.drain(..).collect();
+				for datagram in datagrams {
+					if let Some(j) = eps.iter().position(|ep| ep.udp_address() == datagram.address) {
+						nodes[j].on_packet(&datagram.payload, eps[i].udp_address()).ok();
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn keeps_endpoint_of_preferred_ip_version() {
+		let key = Random.generate().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40455").unwrap(), udp_port: 40455 };
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), IpVersion::DualPreferV6);
+		let id = NodeId::random();
+		let v4 = NodeEndpoint::from_str("10.0.0.1:30303").unwrap();
+		let v6 = NodeEndpoint::from_str("[2001:db8::1]:30303").unwrap();
+
+		assert_eq!(discovery.update_node(NodeEntry { id: id.clone(), endpoint: v6.clone() }).endpoint.address, v6.address);
+		assert_eq!(discovery.update_node(NodeEntry { id: id.clone(), endpoint: v4.clone() }).endpoint.address, v6.address);
+
+		discovery.ip_version = IpVersion::Dual;
+		assert_eq!(discovery.update_node(NodeEntry { id: id.clone(), endpoint: v4.clone() }).endpoint.address, v4.address);
+
+		discovery.ip_version = IpVersion::V6;
+		assert!(!discovery.is_allowed(&NodeEntry { id: id.clone(), endpoint: v4 }));
+		assert!(discovery.is_allowed(&NodeEntry { id: id, endpoint: v6 }));
+	}
+
+	#[test]
+	fn pong_proves_ping_source() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40456").unwrap(), udp_port: 40456 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40457").unwrap(), udp_port: 40457 };
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), IpVersion::default());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), IpVersion::default());
+
+		discovery1.ping(&ep2);
+		let ping = discovery1.send_queue.pop_front().unwrap();
+		// as seen by the second node, e.g. through NAT
+		let from = SocketAddr::from_str("10.0.0.1:40999").unwrap();
+		discovery2.on_packet(&ping.payload, from.clone()).unwrap();
+
+		let pong = discovery2.send_queue.pop_front().unwrap();
+		assert_eq!(pong.address, from);
+		let rlp = UntrustedRlp::new(&pong.payload[(32 + 65 + 1)..]);
+		let to = NodeEndpoint::from_rlp(&rlp.at(0).unwrap()).unwrap();
+		assert_eq!(to.address, SocketAddr::from_str("10.0.0.1:40456").unwrap());
+		assert_eq!(to.udp_port, 40999);
+	}
+
 	#[test]
 	fn removes_expired() {
 		let key = Random.generate().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40446").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), IpVersion::default());
 		for _ in 0..1200 {
 			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
 		}
@@ -627,17 +1043,48 @@ mod tests {
 		assert!(removed > 0);
 	}
 
+	#[test]
+	fn replaces_only_unresponsive_nodes() {
+		use super::*;
+		let key = Random.generate().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40446").unwrap(), udp_port: 40447 };
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), IpVersion::default());
+		for _ in 0..200 {
+			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
+		}
+		let index = discovery.node_buckets.iter().position(|b| b.replacements.len() > 1).unwrap();
+
+		// the pinged node answers and keeps its slot.
+		let pinged = discovery.node_buckets[index].nodes.back().unwrap().address.id.clone();
+		assert!(discovery.node_buckets[index].nodes.back().unwrap().timeout.is_some());
+		discovery.note_seen(&pinged);
+		assert!(discovery.check_expired(false).is_empty());
+		assert_eq!(discovery.node_buckets[index].nodes.front().unwrap().address.id, pinged);
+
+		// the next one doesn't and is replaced by the longest responsive candidate.
+		let (unresponsive, candidate) = {
+			let bucket = &mut discovery.node_buckets[index];
+			bucket.nodes.back_mut().unwrap().timeout = Some(0);
+			(bucket.nodes.back().unwrap().address.id.clone(), bucket.replacements.back().unwrap().address.id.clone())
+		};
+		discovery.note_seen(&candidate);
+		assert_eq!(discovery.check_expired(false).into_iter().collect::<Vec<_>>(), vec![unresponsive]);
+		let bucket = &discovery.node_buckets[index];
+		assert_eq!(bucket.nodes.len(), BUCKET_SIZE);
+		assert_eq!(bucket.nodes.back().unwrap().address.id, candidate);
+		assert!(bucket.nodes.iter().all(|n| n.address.id != unresponsive));
+	}
+
 	#[test]
 	fn find_nearest_saturated() {
 		use super::*;
 		let mut buckets: Vec<_> = (0..256).map(|_| NodeBucket::new()).collect();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
 		for _ in 0..(16 + 10) {
-			buckets[0].nodes.push_back(BucketEntry {
-				address: NodeEntry { id: NodeId::new(), endpoint: ep.clone() },
-				timeout: None,
-				id_hash: keccak(NodeId::new()),
-			});
+			buckets[0].nodes.push_back(BucketEntry::new(
+				NodeEntry { id: NodeId::new(), endpoint: ep.clone() },
+				keccak(NodeId::new()),
+			));
 		}
 		let nearest = Discovery::nearest_node_entries(&NodeId::new(), &buckets);
 		assert_eq!(nearest.len(), 16)
@@ -647,7 +1094,7 @@ mod tests {
 	fn packets() {
 		let key = Random.generate().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), IpVersion::default());
 		discovery.check_timestamps = false;
 		let from = SocketAddr::from_str("99.99.99.99:40445").unwrap();
 
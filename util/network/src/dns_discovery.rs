@@ -0,0 +1,616 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Node discovery from signed DNS node lists (EIP-1459).
+//!
+//! A list is published as a merkle tree of TXT records under a domain and referenced
+//! with `enrtree://<base32 compressed public key>@<domain>`. The root record is signed
+//! with the key, all other records are authenticated by their hashes.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::Duration;
+use ethkey::{recover, Public, Signature};
+use ethkey::math::public_from_sec1;
+use bigint::hash::H256;
+use hash::keccak;
+use rand;
+use rlp::{UntrustedRlp, RlpStream};
+use rustc_serialize::base64::FromBase64;
+use node_table::{Node, NodeEndpoint};
+
+/// Maximal number of records resolved in one refresh of a single list.
+const MAX_LOOKUPS: usize = 2000;
+const DNS_PORT: u16 = 53;
+const DNS_TIMEOUT_MS: u64 = 2000;
+const DNS_ATTEMPTS: usize = 3;
+const TXT: u16 = 16;
+
+/// Source of TXT records.
+pub trait Resolver {
+	/// Resolve the TXT record of given name, concatenating its strings.
+	fn txt(&self, name: &str) -> Result<String, String>;
+}
+
+/// Resolver querying a DNS server over UDP.
+pub struct UdpResolver {
+	server: SocketAddr,
+}
+
+impl UdpResolver {
+	/// Resolver using the first name server from `/etc/resolv.conf`.
+	pub fn system() -> Option<Self> {
+		let mut conf = String::new();
+		if let Err(e) = File::open("/etc/resolv.conf").and_then(|mut f| f.read_to_string(&mut conf)) {
+			debug!(target: "network", "Error reading resolver configuration: {:?}", e);
+			return None;
+		}
+		conf.lines()
+			.filter_map(|line| {
+				let mut words = line.split_whitespace();
+				match (words.next(), words.next()) {
+					(Some("nameserver"), Some(ip)) => ip.parse::<IpAddr>().ok(),
+					_ => None,
+				}
+			})
+			.next()
+			.map(|ip| UdpResolver { server: SocketAddr::new(ip, DNS_PORT) })
+	}
+}
+
+impl Resolver for UdpResolver {
+	fn txt(&self, name: &str) -> Result<String, String> {
+		let id = rand::random::<u16>();
+		let query = encode_query(id, name)?;
+		let local: SocketAddr = match self.server {
+			SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0),
+			SocketAddr::V6(_) => "[::]:0".parse().expect("valid socket address; qed"),
+		};
+		let socket = UdpSocket::bind(local).map_err(|e| format!("{:?}", e))?;
+		socket.connect(self.server).map_err(|e| format!("{:?}", e))?;
+		socket.set_read_timeout(Some(Duration::from_millis(DNS_TIMEOUT_MS))).map_err(|e| format!("{:?}", e))?;
+		let mut buf = [0u8; 4096];
+		for _ in 0..DNS_ATTEMPTS {
+			socket.send(&query).map_err(|e| format!("{:?}", e))?;
+			if let Ok(len) = socket.recv(&mut buf) {
+				return decode_txt(id, &buf[..len]);
+			}
+		}
+		Err(format!("DNS query for {} timed out", name))
+	}
+}
+
+fn encode_query(id: u16, name: &str) -> Result<Vec<u8>, String> {
+	// header: recursion desired, single question.
+	let mut query = vec![(id >> 8) as u8, id as u8, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+	for label in name.trim_right_matches('.').split('.') {
+		if label.is_empty() || label.len() > 63 {
+			return Err(format!("Invalid domain name {}", name));
+		}
+		query.push(label.len() as u8);
+		query.extend_from_slice(label.as_bytes());
+	}
+	query.push(0);
+	query.extend_from_slice(&[0, TXT as u8, 0, 1]);
+	Ok(query)
+}
+
+fn read_u16(data: &[u8], pos: usize) -> u16 {
+	(data[pos] as u16) << 8 | data[pos + 1] as u16
+}
+
+fn skip_name(data: &[u8], mut pos: usize) -> Result<usize, String> {
+	loop {
+		let len = *data.get(pos).ok_or_else(|| "Truncated DNS response".to_owned())? as usize;
+		if len & 0xc0 == 0xc0 {
+			// compression pointer
+			return Ok(pos + 2);
+		}
+		pos += 1 + len;
+		if len == 0 {
+			return Ok(pos);
+		}
+	}
+}
+
+fn decode_txt(id: u16, data: &[u8]) -> Result<String, String> {
+	let truncated = || "Truncated DNS response".to_owned();
+	if data.len() < 12 || read_u16(data, 0) != id {
+		return Err("Invalid DNS response".into());
+	}
+	match data[3] & 0x0f {
+		0 => {},
+		3 => return Err("Name not found".into()),
+		code => return Err(format!("DNS server error {}", code)),
+	}
+
+	let mut pos = 12;
+	for _ in 0..read_u16(data, 4) {
+		pos = skip_name(data, pos)? + 4;
+	}
+	for _ in 0..read_u16(data, 6) {
+		pos = skip_name(data, pos)?;
+		if pos + 10 > data.len() {
+			return Err(truncated());
+		}
+		let kind = read_u16(data, pos);
+		let end = pos + 10 + read_u16(data, pos + 8) as usize;
+		pos += 10;
+		if end > data.len() {
+			return Err(truncated());
+		}
+		if kind == TXT {
+			let mut txt = Vec::new();
+			while pos < end {
+				let len = data[pos] as usize;
+				if pos + 1 + len > end {
+					return Err(truncated());
+				}
+				txt.extend_from_slice(&data[pos + 1..pos + 1 + len]);
+				pos += 1 + len;
+			}
+			return String::from_utf8(txt).map_err(|_| "Invalid TXT record".to_owned());
+		}
+		pos = end;
+	}
+	Err("No TXT record".into())
+}
+
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+	let mut out = Vec::new();
+	let mut buffer = 0u32;
+	let mut bits = 0;
+	for c in s.trim_right_matches('=').bytes() {
+		let value = match c {
+			b'A'...b'Z' => c - b'A',
+			b'a'...b'z' => c - b'a',
+			b'2'...b'7' => c - b'2' + 26,
+			_ => return None,
+		};
+		buffer = (buffer << 5) | value as u32;
+		bits += 5;
+		if bits >= 8 {
+			bits -= 8;
+			out.push((buffer >> bits) as u8);
+			buffer &= (1 << bits) - 1;
+		}
+	}
+	Some(out)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+	const ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+	let mut out = String::new();
+	let mut buffer = 0u32;
+	let mut bits = 0;
+	for byte in data {
+		buffer = (buffer << 8) | *byte as u32;
+		bits += 8;
+		while bits >= 5 {
+			bits -= 5;
+			out.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+		}
+	}
+	if bits > 0 {
+		out.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+	}
+	out
+}
+
+/// Label of the subdomain holding given record.
+fn record_hash(record: &str) -> String {
+	base32_encode(&keccak(record)[..16])
+}
+
+/// Reference to a node list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+	/// Key signing the list.
+	pub public: Public,
+	/// Domain of the list.
+	pub domain: String,
+}
+
+impl Link {
+	/// Parse `enrtree://<key>@<domain>` URL.
+	pub fn parse(url: &str) -> Result<Self, String> {
+		if !url.starts_with("enrtree://") {
+			return Err(format!("Invalid node list URL {}", url));
+		}
+		let mut parts = url[10..].splitn(2, '@');
+		let (key, domain) = match (parts.next(), parts.next()) {
+			(Some(key), Some(domain)) if !domain.is_empty() => (key, domain),
+			_ => return Err(format!("Invalid node list URL {}", url)),
+		};
+		let public = base32_decode(key)
+			.and_then(|key| public_from_sec1(&key).ok())
+			.ok_or_else(|| format!("Invalid public key in node list URL {}", url))?;
+		Ok(Link { public: public, domain: domain.to_lowercase() })
+	}
+}
+
+/// Root record of a list.
+#[derive(Debug, PartialEq)]
+struct Root {
+	enr_root: String,
+	link_root: String,
+	seq: u64,
+}
+
+impl Root {
+	/// Parse the root record and check it's signed by the key.
+	fn parse(record: &str, public: &Public) -> Result<Self, String> {
+		let invalid = || format!("Invalid root record {}", record);
+		let sig_at = record.find(" sig=").ok_or_else(&invalid)?;
+		let (signed, signature) = (&record[..sig_at], &record[sig_at + 5..]);
+
+		let mut words = signed.split(' ');
+		if words.next() != Some("enrtree-root:v1") {
+			return Err(invalid());
+		}
+		let (mut enr_root, mut link_root, mut seq) = (None, None, None);
+		for word in words {
+			if word.starts_with("e=") {
+				enr_root = Some(word[2..].to_owned());
+			} else if word.starts_with("l=") {
+				link_root = Some(word[2..].to_owned());
+			} else if word.starts_with("seq=") {
+				seq = word[4..].parse().ok();
+			}
+		}
+
+		let signature = match signature.from_base64() {
+			Ok(ref s) if s.len() == 65 => {
+				let mut sig = [0u8; 65];
+				sig.copy_from_slice(s);
+				Signature::from(sig)
+			},
+			_ => return Err(invalid()),
+		};
+		match recover(&signature, &keccak(signed)) {
+			Ok(ref signer) if signer == public => {},
+			_ => return Err(format!("Invalid signature of root record {}", record)),
+		}
+
+		match (enr_root, link_root, seq) {
+			(Some(enr_root), Some(link_root), Some(seq)) => Ok(Root { enr_root: enr_root, link_root: link_root, seq: seq }),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+/// Decode `enr:` record of a node, checking its signature.
+fn parse_enr(record: &str) -> Result<Node, String> {
+	let invalid = |e: &str| format!("Invalid node record {}: {}", record, e);
+	let data = record[4..].from_base64().map_err(|_| invalid("bad encoding"))?;
+	let rlp = UntrustedRlp::new(&data);
+	let count = rlp.item_count().map_err(|e| invalid(&format!("{:?}", e)))?;
+	if count < 2 || count % 2 != 0 {
+		return Err(invalid("bad item count"));
+	}
+
+	let (mut id, mut key, mut ip, mut tcp, mut udp) = (None, None, None, None, None);
+	let mut content = RlpStream::new_list(count - 1);
+	content.append_raw(rlp.at(1).map_err(|e| invalid(&format!("{:?}", e)))?.as_raw(), 1);
+	for i in 1..count / 2 {
+		let (k, v) = match (rlp.at(i * 2), rlp.at(i * 2 + 1)) {
+			(Ok(k), Ok(v)) => (k, v),
+			_ => return Err(invalid("bad item")),
+		};
+		content.append_raw(k.as_raw(), 1);
+		content.append_raw(v.as_raw(), 1);
+		let k = k.data().unwrap_or(&[]);
+		if k == &b"id"[..] {
+			id = v.data().ok().map(|d| d.to_vec());
+		} else if k == &b"secp256k1"[..] {
+			key = v.data().ok().and_then(|d| public_from_sec1(d).ok());
+		} else if k == &b"ip"[..] {
+			ip = match v.data() {
+				Ok(d) if d.len() == 4 => Some(Ipv4Addr::new(d[0], d[1], d[2], d[3])),
+				_ => None,
+			};
+		} else if k == &b"tcp"[..] {
+			tcp = v.as_val::<u16>().ok();
+		} else if k == &b"udp"[..] {
+			udp = v.as_val::<u16>().ok();
+		}
+	}
+
+	if !id.map_or(false, |id| &id[..] == &b"v4"[..]) {
+		return Err(invalid("unsupported identity scheme"));
+	}
+	let key = key.ok_or_else(|| invalid("missing public key"))?;
+	let signature = rlp.at(0).and_then(|s| s.data()).map_err(|e| invalid(&format!("{:?}", e)))?;
+	if signature.len() != 64 {
+		return Err(invalid("bad signature"));
+	}
+	let message = keccak(content.out());
+	let (r, s) = (H256::from_slice(&signature[..32]), H256::from_slice(&signature[32..]));
+	let signed = (0..2).any(|v| recover(&Signature::from_rsv(&r, &s, v), &message).ok().as_ref() == Some(&key));
+	if !signed {
+		return Err(invalid("bad signature"));
+	}
+
+	match (ip, tcp) {
+		(Some(ip), Some(tcp)) => Ok(Node::new(key, NodeEndpoint {
+			address: SocketAddr::new(IpAddr::V4(ip), tcp),
+			udp_port: udp.unwrap_or(tcp),
+		})),
+		_ => Err(invalid("missing endpoint")),
+	}
+}
+
+struct Tree {
+	url: String,
+	link: Link,
+	seq: Option<u64>,
+}
+
+/// Node lists to resolve.
+pub struct DnsDiscovery {
+	trees: Vec<Tree>,
+}
+
+impl DnsDiscovery {
+	/// Create discovery of lists given by `enrtree://` URLs. Invalid URLs are skipped.
+	pub fn new(urls: &[String]) -> Self {
+		let trees = urls.iter().filter_map(|url| match Link::parse(url) {
+			Ok(link) => Some(Tree { url: url.clone(), link: link, seq: None }),
+			Err(e) => {
+				warn!(target: "network", "{}", e);
+				None
+			}
+		}).collect();
+		DnsDiscovery { trees: trees }
+	}
+
+	/// Whether there are no lists to resolve.
+	pub fn is_empty(&self) -> bool {
+		self.trees.is_empty()
+	}
+
+	/// Resolve lists which changed since the last refresh and return their nodes.
+	pub fn refresh<R: Resolver>(&mut self, resolver: &R) -> Vec<Node> {
+		let mut nodes = Vec::new();
+		for tree in &mut self.trees {
+			let root = match resolver.txt(&tree.link.domain).and_then(|r| Root::parse(&r, &tree.link.public)) {
+				Ok(root) => root,
+				Err(e) => {
+					debug!(target: "network", "Error resolving node list {}: {}", tree.url, e);
+					continue;
+				}
+			};
+			if tree.seq.map_or(false, |seq| seq >= root.seq) {
+				continue;
+			}
+
+			let mut visited = HashSet::new();
+			visited.insert(tree.link.domain.clone());
+			let mut lookups = MAX_LOOKUPS;
+			let before = nodes.len();
+			crawl(resolver, &tree.link, &root, &mut visited, &mut lookups, &mut nodes);
+			debug!(target: "network", "Node list {} (seq {}): {} nodes", tree.url, root.seq, nodes.len() - before);
+			tree.seq = Some(root.seq);
+		}
+		nodes
+	}
+}
+
+/// Walk both subtrees of the list, following links to other lists.
+fn crawl<R: Resolver>(resolver: &R, link: &Link, root: &Root, visited: &mut HashSet<String>, lookups: &mut usize, nodes: &mut Vec<Node>) {
+	// (hash, whether links are allowed in the subtree)
+	let mut pending = vec![(root.enr_root.clone(), false), (root.link_root.clone(), true)];
+	let mut links = Vec::new();
+	while let Some((hash, with_links)) = pending.pop() {
+		if *lookups == 0 {
+			debug!(target: "network", "Node list {} is too big", link.domain);
+			break;
+		}
+		*lookups -= 1;
+
+		let record = match resolver.txt(&format!("{}.{}", hash, link.domain)) {
+			Ok(record) => record,
+			Err(e) => {
+				trace!(target: "network", "Error resolving {}.{}: {}", hash, link.domain, e);
+				continue;
+			}
+		};
+		if !record_hash(&record).eq_ignore_ascii_case(&hash) {
+			debug!(target: "network", "Hash mismatch of record {}.{}", hash, link.domain);
+			continue;
+		}
+
+		if record.starts_with("enrtree-branch:") {
+			pending.extend(record[15..].split(',').filter(|h| !h.is_empty()).map(|h| (h.to_owned(), with_links)));
+		} else if record.starts_with("enr:") && !with_links {
+			match parse_enr(&record) {
+				Ok(node) => nodes.push(node),
+				Err(e) => debug!(target: "network", "{}", e),
+			}
+		} else if record.starts_with("enrtree://") && with_links {
+			match Link::parse(&record) {
+				Ok(link) => links.push(link),
+				Err(e) => debug!(target: "network", "{}", e),
+			}
+		} else {
+			debug!(target: "network", "Unexpected record {}.{}: {}", hash, link.domain, record);
+		}
+	}
+
+	for link in links {
+		if !visited.insert(link.domain.clone()) {
+			continue;
+		}
+		match resolver.txt(&link.domain).and_then(|r| Root::parse(&r, &link.public)) {
+			Ok(root) => crawl(resolver, &link, &root, visited, lookups, nodes),
+			Err(e) => debug!(target: "network", "Error resolving linked node list {}: {}", link.domain, e),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use ethkey::{sign, Generator, KeyPair, Random};
+	use ethkey::math::public_to_compressed;
+	use rustc_serialize::base64::{ToBase64, URL_SAFE};
+	use super::*;
+
+	struct TestResolver(HashMap<String, String>);
+
+	impl Resolver for TestResolver {
+		fn txt(&self, name: &str) -> Result<String, String> {
+			self.0.get(name).cloned().ok_or_else(|| "Name not found".to_owned())
+		}
+	}
+
+	impl TestResolver {
+		fn add(&mut self, domain: &str, record: String) -> String {
+			let hash = record_hash(&record);
+			self.0.insert(format!("{}.{}", hash, domain), record);
+			hash
+		}
+
+		fn add_root(&mut self, key: &KeyPair, domain: &str, enr_root: &str, link_root: &str, seq: u64) {
+			let signed = format!("enrtree-root:v1 e={} l={} seq={}", enr_root, link_root, seq);
+			let signature = sign(key.secret(), &keccak(&signed)).unwrap();
+			self.0.insert(domain.into(), format!("{} sig={}", signed, signature.to_base64(URL_SAFE)));
+		}
+	}
+
+	fn url(key: &KeyPair, domain: &str) -> String {
+		format!("enrtree://{}@{}", base32_encode(&public_to_compressed(key.public()).unwrap()), domain)
+	}
+
+	fn enr(key: &KeyPair, port: u16) -> String {
+		let compressed = public_to_compressed(key.public()).unwrap();
+		let mut content = RlpStream::new_list(9);
+		content.append(&1u64);
+		content.append(&"id").append(&"v4");
+		content.append(&"ip").append(&vec![127u8, 0, 0, 1]);
+		content.append(&"secp256k1").append(&compressed);
+		content.append(&"tcp").append(&port);
+		let content = content.out();
+		let signature = sign(key.secret(), &keccak(&content)).unwrap();
+
+		let mut record = RlpStream::new_list(10);
+		record.append(&&signature[..64]);
+		let content = UntrustedRlp::new(&content);
+		for i in 0..9 {
+			record.append_raw(content.at(i).unwrap().as_raw(), 1);
+		}
+		format!("enr:{}", record.out().to_base64(URL_SAFE))
+	}
+
+	#[test]
+	fn base32_roundtrips() {
+		let data = b"some data to encode";
+		assert_eq!(base32_decode(&base32_encode(data)).unwrap(), data.to_vec());
+		assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+		assert_eq!(base32_decode("mzxw6ytboi").unwrap(), b"foobar".to_vec());
+		assert!(base32_decode("0").is_none());
+	}
+
+	#[test]
+	fn parses_links() {
+		let key = Random.generate().unwrap();
+		let link = Link::parse(&url(&key, "Nodes.Example.org")).unwrap();
+		assert_eq!(&link.public, key.public());
+		assert_eq!(link.domain, "nodes.example.org");
+
+		assert!(Link::parse("enrtree://nodes.example.org").is_err());
+		assert!(Link::parse("enode://AAAA@nodes.example.org").is_err());
+	}
+
+	#[test]
+	fn checks_root_signature() {
+		let key = Random.generate().unwrap();
+		let other = Random.generate().unwrap();
+		let mut resolver = TestResolver(HashMap::new());
+		resolver.add_root(&key, "nodes.example.org", "AAAA", "BBBB", 3);
+		let record = resolver.txt("nodes.example.org").unwrap();
+
+		assert_eq!(Root::parse(&record, key.public()).unwrap(), Root { enr_root: "AAAA".into(), link_root: "BBBB".into(), seq: 3 });
+		assert!(Root::parse(&record, other.public()).is_err());
+		assert!(Root::parse(&record.replace("seq=3", "seq=4"), key.public()).is_err());
+	}
+
+	#[test]
+	fn parses_node_records() {
+		let key = Random.generate().unwrap();
+		let node = parse_enr(&enr(&key, 30303)).unwrap();
+		assert_eq!(&node.id, key.public());
+		assert_eq!(node.endpoint.address, "127.0.0.1:30303".parse().unwrap());
+		assert_eq!(node.endpoint.udp_port, 30303);
+
+		let other = enr(&Random.generate().unwrap(), 30303);
+		let forged = format!("enr:{}", {
+			let mut data = other[4..].from_base64().unwrap();
+			let last = data.len() - 1;
+			data[last] ^= 1;
+			data.to_base64(URL_SAFE)
+		});
+		assert!(parse_enr(&forged).is_err());
+	}
+
+	#[test]
+	fn resolves_trees_with_links() {
+		let key = Random.generate().unwrap();
+		let linked_key = Random.generate().unwrap();
+		let (node1, node2, node3) = (Random.generate().unwrap(), Random.generate().unwrap(), Random.generate().unwrap());
+		let mut resolver = TestResolver(HashMap::new());
+
+		// linked list with a single node
+		let linked_enr = resolver.add("linked.example.org", enr(&node3, 30305));
+		let linked_links = resolver.add("linked.example.org", "enrtree-branch:".into());
+		resolver.add_root(&linked_key, "linked.example.org", &linked_enr, &linked_links, 1);
+
+		let enr1 = resolver.add("nodes.example.org", enr(&node1, 30303));
+		let enr2 = resolver.add("nodes.example.org", enr(&node2, 30304));
+		let branch = resolver.add("nodes.example.org", format!("enrtree-branch:{},{}", enr1, enr2));
+		let link = resolver.add("nodes.example.org", url(&linked_key, "linked.example.org"));
+		resolver.add_root(&key, "nodes.example.org", &branch, &link, 1);
+
+		let mut discovery = DnsDiscovery::new(&[url(&key, "nodes.example.org"), "invalid".into()]);
+		let mut ids: Vec<_> = discovery.refresh(&resolver).into_iter().map(|n| n.id).collect();
+		ids.sort();
+		let mut expected = vec![node1.public().clone(), node2.public().clone(), node3.public().clone()];
+		expected.sort();
+		assert_eq!(ids, expected);
+
+		// unchanged lists are not resolved again
+		assert!(discovery.refresh(&resolver).is_empty());
+	}
+
+	#[test]
+	fn decodes_txt_responses() {
+		let query = encode_query(0x1234, "nodes.example.org").unwrap();
+		let mut response = query.clone();
+		response[2] = 0x81;
+		response[3] = 0x80;
+		response[7] = 1;
+		// answer: pointer to the question name, TXT, IN, TTL, two character strings
+		response.extend_from_slice(&[0xc0, 12, 0, 16, 0, 1, 0, 0, 0, 60, 0, 8, 3, b'f', b'o', b'o', 3, b'b', b'a', b'r']);
+		assert_eq!(decode_txt(0x1234, &response).unwrap(), "foobar");
+		assert!(decode_txt(0x4321, &response).is_err());
+
+		response[3] = 0x83;
+		assert_eq!(decode_txt(0x1234, &response), Err("Name not found".into()));
+	}
+}
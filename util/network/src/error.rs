@@ -107,6 +107,8 @@ pub enum NetworkError {
 	StdIo(::std::io::Error),
 	/// Packet size is over the protocol limit.
 	OversizedPacket,
+	/// Invalid pre-shared key of a reserved peer.
+	InvalidPeerKey,
 }
 
 impl fmt::Display for NetworkError {
@@ -126,6 +128,7 @@ impl fmt::Display for NetworkError {
 			StdIo(ref err) => format!("{}", err),
 			InvalidNodeId => "Invalid node id".into(),
 			OversizedPacket => "Packet is too large".into(),
+			InvalidPeerKey => "Invalid pre-shared peer key, expected 32 bytes hex".into(),
 		};
 
 		f.write_fmt(format_args!("Network error ({})", msg))
@@ -35,14 +35,21 @@ use rlp::*;
 use session::{Session, SessionInfo, SessionData};
 use error::*;
 use io::*;
-use {NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION, IpFilter};
+use {NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION, IpFilter, IpVersion};
 use node_table::*;
 use stats::NetworkStats;
 use discovery::{Discovery, TableUpdates, NodeEntry};
-use ip_utils::{map_external_address, select_public_address};
+use ip_utils::{map_external_address, select_public_address_of_version, other_version_address, bind_tcp_listener, PortMapping, MAPPING_LIFETIME};
 use path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
 use connection_filter::{ConnectionFilter, ConnectionDirection};
+use access_lists::AccessLists;
+use bandwidth::{Bandwidth, BandwidthLimits};
+use send_queue::PacketPriority;
+use reputation::{Reputations, NodeReputation, Offense};
+use dns_discovery::{DnsDiscovery, UdpResolver};
+use peer_limits::{PeerLimits, Exceeded};
+use peer_keys::parse_reserved_node;
 
 type Slab<T> = ::slab::Slab<T, usize>;
 
@@ -58,6 +65,11 @@ const DISCOVERY: StreamToken = SYS_TIMER + 3;
 const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 5;
 const NODE_TABLE: TimerToken = SYS_TIMER + 6;
+const NAT_RENEWAL: TimerToken = SYS_TIMER + 7;
+const DNS_DISCOVERY: TimerToken = SYS_TIMER + 8;
+const THROTTLED_SESSIONS: TimerToken = SYS_TIMER + 9;
+const TCP_ACCEPT_SECONDARY: StreamToken = SYS_TIMER + 10;
+const DISCOVERY_SECONDARY: StreamToken = SYS_TIMER + 11;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -72,6 +84,12 @@ const DISCOVERY_REFRESH_TIMEOUT: u64 = 60_000;
 const DISCOVERY_ROUND_TIMEOUT: u64 = 300;
 // for NODE_TABLE TimerToken
 const NODE_TABLE_TIMEOUT: u64 = 300_000;
+// renew port mappings halfway through the lease
+const NAT_RENEWAL_TIMEOUT: u64 = MAPPING_LIFETIME as u64 * 1000 / 2;
+// for DNS node lists refresh
+const DNS_DISCOVERY_TIMEOUT: u64 = 1_800_000;
+// for THROTTLED_SESSIONS TimerToken
+const THROTTLED_SESSIONS_TIMEOUT: u64 = 100;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Network service configuration
@@ -86,12 +104,16 @@ pub struct NetworkConfiguration {
 	pub public_address: Option<SocketAddr>,
 	/// Port for UDP connections, same as TCP by default
 	pub udp_port: Option<u16>,
-	/// Enable NAT configuration
+	/// Enable NAT configuration with UPnP
 	pub nat_enabled: bool,
+	/// Enable NAT configuration with NAT-PMP
+	pub nat_pmp_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
+	/// Signed DNS node lists (`enrtree://<key>@<domain>`) to discover nodes from
+	pub node_lists: Vec<String>,
 	/// Use provided node key instead of default
 	pub use_secret: Option<Secret>,
 	/// Minimum number of connected peers to maintain
@@ -100,6 +122,10 @@ pub struct NetworkConfiguration {
 	pub max_peers: u32,
 	/// Maximum handshakes
 	pub max_handshakes: u32,
+	/// Maximum number of inbound and of outbound peers sharing an IP address, 0 for no limit
+	pub max_peers_per_ip: u32,
+	/// Maximum number of inbound and of outbound peers sharing a /24 (IPv4) or /64 (IPv6) subnet, 0 for no limit
+	pub max_peers_per_subnet: u32,
 	/// Reserved protocols. Peers with <key> protocol get additional <value> connection slots.
 	pub reserved_protocols: HashMap<ProtocolId, u32>,
 	/// List of reserved node addresses.
@@ -108,6 +134,10 @@ pub struct NetworkConfiguration {
 	pub non_reserved_mode: NonReservedPeerMode,
 	/// IP filter
 	pub ip_filter: IpFilter,
+	/// IP versions to listen on, discover and connect to nodes with
+	pub ip_version: IpVersion,
+	/// Upload and download rate limits
+	pub bandwidth: BandwidthLimits,
 }
 
 impl Default for NetworkConfiguration {
@@ -126,16 +156,22 @@ impl NetworkConfiguration {
 			public_address: None,
 			udp_port: None,
 			nat_enabled: true,
+			nat_pmp_enabled: true,
 			discovery_enabled: true,
 			boot_nodes: Vec::new(),
+			node_lists: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
 			max_peers: 50,
 			max_handshakes: 64,
+			max_peers_per_ip: 0,
+			max_peers_per_subnet: 0,
 			reserved_protocols: HashMap::new(),
 			ip_filter: IpFilter::default(),
+			ip_version: IpVersion::default(),
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			bandwidth: BandwidthLimits::default(),
 		}
 	}
 
@@ -151,6 +187,7 @@ impl NetworkConfiguration {
 		let mut config = NetworkConfiguration::new();
 		config.listen_address = Some(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 0)));
 		config.nat_enabled = false;
+		config.nat_pmp_enabled = false;
 		config
 	}
 }
@@ -189,8 +226,14 @@ pub enum NetworkIoMessage {
 	Disconnect(PeerId),
 	/// Disconnect and temporary disable peer.
 	DisablePeer(PeerId),
+	/// Penalize peer for misbehaviour.
+	ReportPeer(PeerId, Offense),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
+	/// Advertise this node under a discovery topic.
+	AdvertiseTopic(String),
+	/// Look for nodes advertised under a discovery topic.
+	SearchTopic(String),
 }
 
 /// Local (temporary) peer session ID.
@@ -249,14 +292,24 @@ impl<'s> NetworkContext<'s> {
 
 	/// Send a packet over the network to another peer.
 	pub fn send(&self, peer: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError> {
-		self.send_protocol(self.protocol, peer, packet_id, data)
+		self.send_with_priority(peer, packet_id, data, PacketPriority::Normal)
+	}
+
+	/// Send a packet over the network to another peer with given priority.
+	pub fn send_with_priority(&self, peer: PeerId, packet_id: PacketId, data: Vec<u8>, priority: PacketPriority) -> Result<(), NetworkError> {
+		self.send_protocol_with_priority(self.protocol, peer, packet_id, data, priority)
 	}
 
 	/// Send a packet over the network to another peer using specified protocol.
 	pub fn send_protocol(&self, protocol: ProtocolId, peer: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError> {
+		self.send_protocol_with_priority(protocol, peer, packet_id, data, PacketPriority::Normal)
+	}
+
+	/// Send a packet over the network to another peer using specified protocol and priority.
+	pub fn send_protocol_with_priority(&self, protocol: ProtocolId, peer: PeerId, packet_id: PacketId, data: Vec<u8>, priority: PacketPriority) -> Result<(), NetworkError> {
 		let session = self.resolve_session(peer);
 		if let Some(session) = session {
-			session.lock().send_packet(self.io, protocol, packet_id as u8, &data)?;
+			session.lock().send_packet(self.io, protocol, packet_id as u8, &data, priority)?;
 		} else  {
 			trace!(target: "network", "Send: Peer no longer exist")
 		}
@@ -265,8 +318,13 @@ impl<'s> NetworkContext<'s> {
 
 	/// Respond to a current network message. Panics if no there is no packet in the context. If the session is expired returns nothing.
 	pub fn respond(&self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError> {
+		self.respond_with_priority(packet_id, data, PacketPriority::Normal)
+	}
+
+	/// Respond to a current network message with given priority. Panics if no there is no packet in the context.
+	pub fn respond_with_priority(&self, packet_id: PacketId, data: Vec<u8>, priority: PacketPriority) -> Result<(), NetworkError> {
 		assert!(self.session.is_some(), "Respond called without network context");
-		self.session_id.map_or_else(|| Err(NetworkError::Expired), |id| self.send(id, packet_id, data))
+		self.session_id.map_or_else(|| Err(NetworkError::Expired), |id| self.send_with_priority(id, packet_id, data, priority))
 	}
 
 	/// Get an IoChannel.
@@ -286,6 +344,25 @@ impl<'s> NetworkContext<'s> {
 			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
 	}
 
+	/// Penalize and disconnect a misbehaving peer. Repeated offenses get the node banned.
+	pub fn report_peer(&self, peer: PeerId, offense: Offense) {
+		self.io.message(NetworkIoMessage::ReportPeer(peer, offense))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
+	/// Advertise this node in discovery under given topic, e.g. as a server of some optional
+	/// service, so that nodes searching for the topic can find it.
+	pub fn advertise_topic(&self, topic: &str) {
+		self.io.message(NetworkIoMessage::AdvertiseTopic(topic.to_owned()))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
+	/// Look for nodes advertised under given topic. Nodes found are connected to first.
+	pub fn search_topic(&self, topic: &str) {
+		self.io.message(NetworkIoMessage::SearchTopic(topic.to_owned()))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
 	/// Check if the session is still active.
 	pub fn is_expired(&self) -> bool {
 		self.session.as_ref().map_or(false, |s| s.lock().expired())
@@ -339,6 +416,12 @@ pub struct HostInfo {
 	pub local_endpoint: NodeEndpoint,
 	/// Public address + discovery port
 	pub public_endpoint: Option<NodeEndpoint>,
+	/// Local address + discovery port of the other IP version on dual-stack hosts
+	pub secondary_local_endpoint: Option<NodeEndpoint>,
+	/// Public address + discovery port of the other IP version on dual-stack hosts
+	pub secondary_public_endpoint: Option<NodeEndpoint>,
+	/// Pre-shared keys of reserved peers.
+	reserved_keys: HashMap<NodeId, H256>,
 }
 
 impl HostInfo {
@@ -352,6 +435,11 @@ impl HostInfo {
 		self.keys.secret()
 	}
 
+	/// Returns the pre-shared key of given reserved peer, if it has one.
+	pub fn reserved_key(&self, id: &NodeId) -> Option<&H256> {
+		self.reserved_keys.get(id)
+	}
+
 	/// Increments and returns connection nonce.
 	pub fn next_nonce(&mut self) -> H256 {
 		self.nonce = keccak(&self.nonce);
@@ -371,9 +459,17 @@ struct ProtocolTimer {
 pub struct Host {
 	pub info: RwLock<HostInfo>,
 	tcp_listener: Mutex<TcpListener>,
+	secondary_tcp_listener: Mutex<Option<TcpListener>>,
 	sessions: Arc<RwLock<Slab<SharedSession>>>,
 	discovery: Mutex<Option<Discovery>>,
+	advertised_topics: RwLock<HashSet<String>>,
+	searched_topics: RwLock<HashSet<String>>,
+	port_mapping: Mutex<Option<PortMapping>>,
+	dns_discovery: Mutex<DnsDiscovery>,
 	nodes: RwLock<NodeTable>,
+	reputations: RwLock<Reputations>,
+	access_lists: Arc<RwLock<AccessLists>>,
+	bandwidth: Arc<Bandwidth>,
 	handlers: RwLock<HashMap<ProtocolId, Arc<NetworkProtocolHandler>>>,
 	timers: RwLock<HashMap<TimerToken, ProtocolTimer>>,
 	timer_counter: RwLock<usize>,
@@ -386,11 +482,30 @@ pub struct Host {
 
 impl Host {
 	/// Create a new instance
-	pub fn new(mut config: NetworkConfiguration, stats: Arc<NetworkStats>, filter: Option<Arc<ConnectionFilter>>) -> Result<Host, NetworkError> {
-		let mut listen_address = match config.listen_address {
+	pub fn new(
+		mut config: NetworkConfiguration,
+		stats: Arc<NetworkStats>,
+		filter: Option<Arc<ConnectionFilter>>,
+		access_lists: Arc<RwLock<AccessLists>>,
+		bandwidth: Arc<Bandwidth>,
+	) -> Result<Host, NetworkError> {
+		let listen_address = match config.listen_address {
 			None => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), DEFAULT_PORT)),
 			Some(addr) => addr,
 		};
+		// Listen on the preferred IP version and, on dual-stack hosts, on the other one as well.
+		let ip_version = config.ip_version;
+		let other_address = other_version_address(&listen_address);
+		let (preferred_address, other_address) = if listen_address.is_ipv6() == ip_version.prefers_v6() {
+			(Some(listen_address), other_address)
+		} else {
+			(other_address, Some(listen_address))
+		};
+		let (mut listen_address, secondary_address) = match (preferred_address, other_address) {
+			(Some(address), other) => (address, if ip_version.is_dual() { other } else { None }),
+			(None, Some(address)) if ip_version.is_dual() => (address, None),
+			_ => return Err(NetworkError::StdIo(::std::io::Error::new(ErrorKind::AddrNotAvailable, "Listen address is not of an enabled IP version"))),
+		};
 
 		let keys = if let Some(ref secret) = config.use_secret {
 			KeyPair::from_secret(secret.clone())?
@@ -407,13 +522,28 @@ impl Host {
 		};
 		let path = config.net_config_path.clone();
 		// Setup the server socket
-		let tcp_listener = TcpListener::bind(&listen_address)?;
+		let tcp_listener = bind_tcp_listener(&listen_address)?;
 		listen_address = SocketAddr::new(listen_address.ip(), tcp_listener.local_addr()?.port());
 		debug!(target: "network", "Listening at {:?}", listen_address);
 		let udp_port = config.udp_port.unwrap_or(listen_address.port());
 		let local_endpoint = NodeEndpoint { address: listen_address, udp_port: udp_port };
+		let secondary_tcp_listener = secondary_address.and_then(|mut address| {
+			address.set_port(listen_address.port());
+			match bind_tcp_listener(&address) {
+				Ok(listener) => {
+					debug!(target: "network", "Listening at {:?}", address);
+					Some((listener, address))
+				},
+				Err(e) => {
+					debug!(target: "network", "Can't listen at {:?}: {:?}", address, e);
+					None
+				},
+			}
+		});
+		let secondary_local_endpoint = secondary_tcp_listener.as_ref().map(|&(_, address)| NodeEndpoint { address: address, udp_port: udp_port });
 
 		let boot_nodes = config.boot_nodes.clone();
+		let dns_discovery = DnsDiscovery::new(&config.node_lists);
 		let reserved_nodes = config.reserved_nodes.clone();
 		config.max_handshakes = min(config.max_handshakes, MAX_HANDSHAKES as u32);
 
@@ -426,12 +556,23 @@ impl Host {
 				client_version: version(),
 				capabilities: Vec::new(),
 				public_endpoint: None,
+				reserved_keys: HashMap::new(),
 				local_endpoint: local_endpoint,
+				secondary_local_endpoint: secondary_local_endpoint,
+				secondary_public_endpoint: None,
 			}),
 			discovery: Mutex::new(None),
+			advertised_topics: RwLock::new(HashSet::new()),
+			searched_topics: RwLock::new(HashSet::new()),
+			port_mapping: Mutex::new(None),
+			dns_discovery: Mutex::new(dns_discovery),
 			tcp_listener: Mutex::new(tcp_listener),
+			secondary_tcp_listener: Mutex::new(secondary_tcp_listener.map(|(listener, _)| listener)),
 			sessions: Arc::new(RwLock::new(Slab::new_starting_at(FIRST_SESSION, MAX_SESSIONS))),
-			nodes: RwLock::new(NodeTable::new(path)),
+			nodes: RwLock::new(NodeTable::new(path.clone())),
+			reputations: RwLock::new(Reputations::new(path)),
+			access_lists: access_lists,
+			bandwidth: bandwidth,
 			handlers: RwLock::new(HashMap::new()),
 			timers: RwLock::new(HashMap::new()),
 			timer_counter: RwLock::new(USER_TIMER),
@@ -468,8 +609,13 @@ impl Host {
 		}
 	}
 
+	/// Adds a reserved node. The enode URL may be followed by a pre-shared key the peer must prove to know.
 	pub fn add_reserved_node(&self, id: &str) -> Result<(), NetworkError> {
-		let n = Node::from_str(id)?;
+		let (n, key) = parse_reserved_node(id)?;
+		match key {
+			Some(key) => { self.info.write().reserved_keys.insert(n.id.clone(), key); },
+			None => { self.info.write().reserved_keys.remove(&n.id); },
+		}
 
 		let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id.clone() };
 		self.reserved_nodes.write().insert(n.id.clone());
@@ -513,9 +659,49 @@ impl Host {
 	}
 
 	pub fn remove_reserved_node(&self, id: &str) -> Result<(), NetworkError> {
-		let n = Node::from_str(id)?;
+		let (n, _) = parse_reserved_node(id)?;
 		self.reserved_nodes.write().remove(&n.id);
+		self.info.write().reserved_keys.remove(&n.id);
+
+		Ok(())
+	}
+
+	/// Disconnect all sessions with given node. Accepts either enode URL or node id.
+	pub fn disconnect_node(&self, id: &str, io: &IoContext<NetworkIoMessage>) -> Result<(), NetworkError> {
+		let id = parse_node_id(id)?;
+
+		let mut to_kill = Vec::new();
+		for e in self.sessions.write().iter_mut() {
+			let mut s = e.lock();
+			if s.id() == Some(&id) {
+				s.disconnect(io, DisconnectReason::DisconnectRequested);
+				to_kill.push(s.token());
+			}
+		}
 
+		if to_kill.is_empty() {
+			return Err(NetworkError::PeerNotFound);
+		}
+
+		for p in to_kill {
+			trace!(target: "network", "Disconnecting on request: {}", p);
+			self.kill_connection(p, io, false);
+		}
+		Ok(())
+	}
+
+	/// Reputations of all nodes which misbehaved recently or have been banned.
+	pub fn reputations(&self) -> Vec<(NodeId, NodeReputation)> {
+		self.reputations.read().entries()
+	}
+
+	/// Forget the reputation of given node (enode URL or node id), or of all nodes.
+	pub fn clear_reputation(&self, id: Option<&str>) -> Result<(), NetworkError> {
+		let id = match id {
+			Some(id) => Some(parse_node_id(id)?),
+			None => None,
+		};
+		self.reputations.write().clear(id.as_ref());
 		Ok(())
 	}
 
@@ -528,6 +714,11 @@ impl Host {
 		info.public_endpoint.as_ref().map(|e| format!("{}", Node::new(info.id().clone(), e.clone())))
 	}
 
+	/// Externally visible TCP port, if the public interface is initialized.
+	pub fn external_port(&self) -> Option<u16> {
+		self.info.read().public_endpoint.as_ref().map(|e| e.address.port())
+	}
+
 	pub fn local_url(&self) -> String {
 		let info = self.info.read();
 		format!("{}", Node::new(info.id().clone(), info.local_endpoint.clone()))
@@ -568,16 +759,28 @@ impl Host {
 			return Ok(());
 		}
 		let local_endpoint = self.info.read().local_endpoint.clone();
+		let secondary_local_endpoint = self.info.read().secondary_local_endpoint.clone();
 		let public_address = self.info.read().config.public_address.clone();
 		let allow_ips = self.info.read().config.ip_filter.clone();
+		// A configured public address is announced on the endpoint of its IP version.
+		let (public_address, secondary_public_address) = match (public_address, secondary_local_endpoint.as_ref()) {
+			(Some(addr), Some(secondary)) if addr.is_ipv6() == secondary.address.is_ipv6() => (None, Some(addr)),
+			(addr, _) => (addr, None),
+		};
 		let public_endpoint = match public_address {
 			None => {
-				let public_address = select_public_address(local_endpoint.address.port());
+				let public_address = select_public_address_of_version(local_endpoint.address.port(), local_endpoint.address.is_ipv6());
 				let public_endpoint = NodeEndpoint { address: public_address, udp_port: local_endpoint.udp_port };
-				if self.info.read().config.nat_enabled {
-					match map_external_address(&local_endpoint) {
-						Some(endpoint) => {
-							info!("NAT mapped to external address {}", endpoint.address);
+				let (upnp, nat_pmp) = {
+					let config = &self.info.read().config;
+					(config.nat_enabled, config.nat_pmp_enabled)
+				};
+				if upnp || nat_pmp {
+					match map_external_address(&local_endpoint, upnp, nat_pmp) {
+						Some(mapping) => {
+							info!("NAT mapped to external address {} using {}", mapping.external.address, mapping.protocol());
+							let endpoint = mapping.external.clone();
+							*self.port_mapping.lock() = Some(mapping);
 							endpoint
 						},
 						None => public_endpoint
@@ -588,8 +791,14 @@ impl Host {
 			}
 			Some(addr) => NodeEndpoint { address: addr, udp_port: local_endpoint.udp_port }
 		};
+		// Port mappings are only set up for the preferred IP version.
+		let secondary_public_endpoint = secondary_local_endpoint.map(|local| {
+			let address = secondary_public_address.unwrap_or_else(|| select_public_address_of_version(local.address.port(), local.address.is_ipv6()));
+			NodeEndpoint { address: address, udp_port: local.udp_port }
+		});
 
 		self.info.write().public_endpoint = Some(public_endpoint.clone());
+		self.info.write().secondary_public_endpoint = secondary_public_endpoint.clone();
 
 		if let Some(url) = self.external_url() {
 			io.message(NetworkIoMessage::NetworkStarted(url)).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
@@ -599,30 +808,134 @@ impl Host {
 		let discovery = {
 			let info = self.info.read();
 			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
-				let mut udp_addr = local_endpoint.address.clone();
-				udp_addr.set_port(local_endpoint.udp_port);
-				Some(Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, allow_ips))
+				let mut discovery = Discovery::new(&info.keys, local_endpoint.udp_address(), public_endpoint, DISCOVERY, allow_ips, info.config.ip_version);
+				if let (Some(local), Some(public)) = (info.secondary_local_endpoint.clone(), secondary_public_endpoint) {
+					if let Err(e) = discovery.bind_secondary(local.udp_address(), public, DISCOVERY_SECONDARY) {
+						debug!(target: "network", "Can't bind discovery socket at {:?}: {:?}", local.udp_address(), e);
+					}
+				}
+				Some(discovery)
 			} else { None }
 		};
 
 		if let Some(mut discovery) = discovery {
 			discovery.init_node_list(self.nodes.read().unordered_entries());
 			discovery.add_node_list(self.nodes.read().unordered_entries());
+			for topic in self.advertised_topics.read().iter() {
+				discovery.advertise_topic(topic);
+			}
+			for topic in self.searched_topics.read().iter() {
+				discovery.search_topic(topic);
+			}
 			*self.discovery.lock() = Some(discovery);
 			io.register_stream(DISCOVERY)?;
 			io.register_timer(DISCOVERY_REFRESH, DISCOVERY_REFRESH_TIMEOUT)?;
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
 		}
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
+		if self.port_mapping.lock().is_some() {
+			io.register_timer(NAT_RENEWAL, NAT_RENEWAL_TIMEOUT)?;
+		}
+		let dns_discovery_enabled = {
+			let info = self.info.read();
+			info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept
+		};
+		if dns_discovery_enabled && !self.dns_discovery.lock().is_empty() {
+			self.refresh_node_lists();
+			io.register_timer(DNS_DISCOVERY, DNS_DISCOVERY_TIMEOUT)?;
+		}
 		io.register_stream(TCP_ACCEPT)?;
 		Ok(())
 	}
 
+	fn refresh_node_lists(&self) {
+		let resolver = match UdpResolver::system() {
+			Some(resolver) => resolver,
+			None => {
+				debug!(target: "network", "No DNS resolver configured, skipping node lists");
+				return;
+			}
+		};
+		let nodes = self.dns_discovery.lock().refresh(&resolver);
+		if nodes.is_empty() {
+			return;
+		}
+		debug!(target: "network", "Discovered {} nodes from DNS node lists", nodes.len());
+		let mut discovery = self.discovery.lock();
+		let mut table = self.nodes.write();
+		for node in nodes {
+			if let Some(ref mut d) = *discovery {
+				d.add_node(NodeEntry { endpoint: node.endpoint.clone(), id: node.id.clone() });
+			}
+			table.add_node(node);
+		}
+	}
+
+	fn renew_port_mapping(&self, io: &IoContext<NetworkIoMessage>) {
+		let renewed = match *self.port_mapping.lock() {
+			Some(ref mut mapping) => mapping.renew(),
+			None => return,
+		};
+		match renewed {
+			Ok(endpoint) => {
+				let changed = self.info.read().public_endpoint.as_ref()
+					.map_or(true, |e| e.address != endpoint.address || e.udp_port != endpoint.udp_port);
+				if changed {
+					info!("NAT mapping changed to external address {}", endpoint.address);
+					self.info.write().public_endpoint = Some(endpoint);
+					if let Some(url) = self.external_url() {
+						io.message(NetworkIoMessage::NetworkStarted(url)).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
+					}
+				}
+			},
+			Err(e) => warn!(target: "network", "Error renewing NAT port mapping: {}", e),
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
 	}
 
+	/// Resume reading and writing of sessions stalled by the bandwidth limits.
+	fn resume_throttled(&self, io: &IoContext<NetworkIoMessage>) {
+		let throttled: Vec<(StreamToken, bool, bool)> = self.sessions.read().iter()
+			.filter_map(|e| {
+				let s = e.lock();
+				match (s.is_read_throttled(), s.is_write_throttled()) {
+					(false, false) => None,
+					(read, write) => Some((s.token(), read, write)),
+				}
+			})
+			.collect();
+		for (token, read, write) in throttled {
+			if write {
+				self.session_writable(token, io);
+			}
+			if read {
+				self.session_readable(token, io);
+			}
+		}
+	}
+
+	/// Count ready sessions in given direction per IP address and subnet.
+	fn peer_limits(&self, originated: bool, skip: Option<StreamToken>) -> PeerLimits {
+		let mut limits = {
+			let config = &self.info.read().config;
+			PeerLimits::new(config.max_peers_per_ip, config.max_peers_per_subnet)
+		};
+		for e in self.sessions.read().iter() {
+			let s = e.lock();
+			if Some(s.token()) == skip || !s.is_ready() || s.info.originated != originated {
+				continue;
+			}
+			if let Ok(address) = s.remote_addr() {
+				limits.add(&address.ip());
+			}
+		}
+		limits
+	}
+
 	fn have_session(&self, id: &NodeId) -> bool {
 		self.sessions.read().iter().any(|e| e.lock().info.id == Some(id.clone()))
 	}
@@ -656,14 +969,14 @@ impl Host {
 	}
 
 	fn connect_peers(&self, io: &IoContext<NetworkIoMessage>) {
-		let (min_peers, mut pin, max_handshakes, allow_ips, self_id) = {
+		let (min_peers, mut pin, max_handshakes, allow_ips, ip_version, self_id) = {
 			let info = self.info.read();
 			if info.capabilities.is_empty() {
 				return;
 			}
 			let config = &info.config;
 
-			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.ip_filter.clone(), info.id().clone())
+			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.ip_filter.clone(), config.ip_version, info.id().clone())
 		};
 
 		let session_count = self.session_count();
@@ -684,22 +997,44 @@ impl Host {
 			return;
 		}
 
-		// iterate over all nodes, reserved ones coming first.
+		// iterate over all nodes, reserved ones coming first, then the ones found through searched topics.
 		// if we are pinned to only reserved nodes, ignore all others.
 		let nodes = reserved_nodes.iter().cloned().chain(if !pin {
-			self.nodes.read().nodes(allow_ips)
+			let mut nodes = self.nodes.read().nodes(allow_ips);
+			let topic_nodes = self.discovery.lock().as_ref().map_or_else(HashSet::new, |d| d.topic_nodes());
+			if !topic_nodes.is_empty() {
+				nodes.sort_by_key(|id| !topic_nodes.contains(id));
+			}
+			nodes
 		} else {
 			Vec::new()
 		});
 
 		let max_handshakes_per_round = max_handshakes / 2;
+		let max_started = min(max_handshakes_per_round, max_handshakes - handshake_count);
+		let mut limits = self.peer_limits(true, None);
 		let mut started: usize = 0;
 		for id in nodes.filter(|id|
 				!self.have_session(id) &&
 				!self.connecting_to(id) &&
 				*id != self_id &&
+				!self.reputations.read().is_banned(id) &&
+				self.access_lists.read().is_allowed(id, self.nodes.read().get(id).map(|n| n.endpoint.address.ip()).as_ref()) &&
+				self.nodes.read().get(id).map_or(true, |n| ip_version.allows(&n.endpoint.address.ip())) &&
 				self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Outbound))
-			).take(min(max_handshakes_per_round, max_handshakes - handshake_count)) {
+			) {
+			if started >= max_started {
+				break;
+			}
+			let ip = self.nodes.read().get(&id).map(|n| n.endpoint.address.ip());
+			if let Some(ip) = ip {
+				if !reserved_nodes.contains(&id) {
+					if limits.check(&ip).is_err() {
+						continue;
+					}
+					limits.add(&ip);
+				}
+			}
 			self.connect_peer(&id, io);
 			started += 1;
 		}
@@ -751,7 +1086,7 @@ impl Host {
 		let mut sessions = self.sessions.write();
 
 		let token = sessions.insert_with_opt(|token| {
-			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), &self.info.read()) {
+			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), self.bandwidth.clone(), &self.info.read()) {
 				Ok(s) => Some(Arc::new(Mutex::new(s))),
 				Err(e) => {
 					debug!(target: "network", "Session create error: {:?}", e);
@@ -769,10 +1104,18 @@ impl Host {
 		}
 	}
 
-	fn accept(&self, io: &IoContext<NetworkIoMessage>) {
+	fn accept(&self, io: &IoContext<NetworkIoMessage>, secondary: bool) {
 		trace!(target: "network", "Accepting incoming connection");
 		loop {
-			let socket = match self.tcp_listener.lock().accept() {
+			let accepted = if secondary {
+				match *self.secondary_tcp_listener.lock() {
+					Some(ref listener) => listener.accept(),
+					None => break,
+				}
+			} else {
+				self.tcp_listener.lock().accept()
+			};
+			let socket = match accepted {
 				Ok((sock, _addr)) => sock,
 				Err(e) => {
 					if e.kind() != ErrorKind::WouldBlock {
@@ -860,6 +1203,21 @@ impl Host {
 								}
 							}
 
+							if !self.reserved_nodes.read().contains(&id) && self.reputations.read().is_banned(&id) {
+								trace!(target: "network", "Rejecting banned node {:?}", id);
+								s.disconnect(io, DisconnectReason::UselessPeer);
+								kill = true;
+								break;
+							}
+
+							let remote_ip = s.remote_addr().ok().map(|address| address.ip());
+							if !self.access_lists.read().is_allowed(&id, remote_ip.as_ref()) {
+								trace!(target: "network", "Rejecting node {:?} by access lists", id);
+								s.disconnect(io, DisconnectReason::UselessPeer);
+								kill = true;
+								break;
+							}
+
 							if !self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Inbound)) {
 								trace!(target: "network", "Inbound connection not allowed for {:?}", id);
 								s.disconnect(io, DisconnectReason::UnexpectedIdentity);
@@ -923,6 +1281,23 @@ impl Host {
 					self.kill_connection(token, io, false);
 					return;
 				}
+				let (originated, ip) = {
+					let s = session.lock();
+					(s.info.originated, s.remote_addr().ok().map(|a| a.ip()))
+				};
+				let reserved = ready_id.as_ref().map_or(false, |id| self.reserved_nodes.read().contains(id));
+				if let (Some(ip), false) = (ip, reserved) {
+					if let Err(exceeded) = self.peer_limits(originated, Some(token)).check(&ip) {
+						trace!(target: "network", "Rejected connection {} from {}: too many peers in the same {:?}", token, ip, exceeded);
+						match exceeded {
+							Exceeded::Ip => self.stats.inc_ip_limit_rejections(),
+							Exceeded::Subnet => self.stats.inc_subnet_limit_rejections(),
+						}
+						session.lock().disconnect(io, DisconnectReason::TooManyPeers);
+						self.kill_connection(token, io, false);
+						return;
+					}
+				}
 				for p in ready_data {
 					self.stats.inc_sessions();
 					let reserved = self.reserved_nodes.read();
@@ -1028,6 +1403,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 	/// Initialize networking
 	fn initialize(&self, io: &IoContext<NetworkIoMessage>) {
 		io.register_timer(IDLE, MAINTENANCE_TIMEOUT).expect("Error registering Network idle timer");
+		io.register_timer(THROTTLED_SESSIONS, THROTTLED_SESSIONS_TIMEOUT).expect("Error registering throttled sessions timer");
 		io.message(NetworkIoMessage::InitPublicInterface).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
 		self.maintain_network(io)
 	}
@@ -1046,13 +1422,14 @@ impl IoHandler<NetworkIoMessage> for Host {
 		}
 		match stream {
 			FIRST_SESSION ... LAST_SESSION => self.session_readable(stream, io),
-			DISCOVERY => {
-				let node_changes = { self.discovery.lock().as_mut().map_or(None, |d| d.readable(io)) };
+			DISCOVERY | DISCOVERY_SECONDARY => {
+				let node_changes = { self.discovery.lock().as_mut().map_or(None, |d| d.readable(io, stream)) };
 				if let Some(node_changes) = node_changes {
 					self.update_nodes(io, node_changes);
 				}
 			},
-			TCP_ACCEPT => self.accept(io),
+			TCP_ACCEPT => self.accept(io, false),
+			TCP_ACCEPT_SECONDARY => self.accept(io, true),
 			_ => panic!("Received unknown readable token"),
 		}
 	}
@@ -1063,7 +1440,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 		}
 		match stream {
 			FIRST_SESSION ... LAST_SESSION => self.session_writable(stream, io),
-			DISCOVERY => {
+			DISCOVERY | DISCOVERY_SECONDARY => {
 				self.discovery.lock().as_mut().map(|d| d.writable(io));
 			}
 			_ => panic!("Received unknown writable token"),
@@ -1076,6 +1453,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 		}
 		match token {
 			IDLE => self.maintain_network(io),
+			THROTTLED_SESSIONS => self.resume_throttled(io),
 			FIRST_SESSION ... LAST_SESSION => self.connection_timeout(token, io),
 			DISCOVERY_REFRESH => {
 				self.discovery.lock().as_mut().map(|d| d.refresh());
@@ -1092,7 +1470,10 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Refreshing node table");
 				self.nodes.write().clear_useless();
 				self.nodes.write().save();
+				self.reputations.read().save();
 			},
+			NAT_RENEWAL => self.renew_port_mapping(io),
+			DNS_DISCOVERY => self.refresh_node_lists(),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1163,8 +1544,41 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::ReportPeer(ref peer, offense) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				if let Some(session) = session {
+					session.lock().disconnect(io, DisconnectReason::DisconnectRequested);
+					let id = session.lock().id().cloned();
+					if let Some(id) = id {
+						if !self.reserved_nodes.read().contains(&id) {
+							self.reputations.write().report(&id, offense);
+							if offense != Offense::Timeout {
+								self.nodes.write().mark_as_useless(&id);
+							}
+						}
+					}
+				}
+				trace!(target: "network", "Reporting peer {} for {:?}", peer, offense);
+				self.kill_connection(*peer, io, false);
+			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
+			NetworkIoMessage::AdvertiseTopic(ref topic) => {
+				if self.advertised_topics.write().insert(topic.clone()) {
+					let started = { self.discovery.lock().as_mut().map(|d| d.advertise_topic(topic)).is_some() };
+					if started {
+						io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+					}
+				}
+			},
+			NetworkIoMessage::SearchTopic(ref topic) => {
+				if self.searched_topics.write().insert(topic.clone()) {
+					let started = { self.discovery.lock().as_mut().map(|d| d.search_topic(topic)).is_some() };
+					if started {
+						io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+					}
+				}
+			},
 			_ => {}	// ignore others.
 		}
 	}
@@ -1178,7 +1592,12 @@ impl IoHandler<NetworkIoMessage> for Host {
 				}
 			}
 			DISCOVERY => self.discovery.lock().as_ref().and_then(|d| d.register_socket(event_loop).ok()).expect("Error registering discovery socket"),
-			TCP_ACCEPT => event_loop.register(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error registering stream"),
+			TCP_ACCEPT => {
+				event_loop.register(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error registering stream");
+				if let Some(ref listener) = *self.secondary_tcp_listener.lock() {
+					event_loop.register(listener, Token(TCP_ACCEPT_SECONDARY), Ready::all(), PollOpt::edge()).expect("Error registering stream");
+				}
+			},
 			_ => warn!("Unexpected stream registration")
 		}
 	}
@@ -1209,12 +1628,26 @@ impl IoHandler<NetworkIoMessage> for Host {
 				}
 			}
 			DISCOVERY => self.discovery.lock().as_ref().and_then(|d| d.update_registration(event_loop).ok()).expect("Error reregistering discovery socket"),
-			TCP_ACCEPT => event_loop.reregister(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error reregistering stream"),
+			TCP_ACCEPT => {
+				event_loop.reregister(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error reregistering stream");
+				if let Some(ref listener) = *self.secondary_tcp_listener.lock() {
+					event_loop.reregister(listener, Token(TCP_ACCEPT_SECONDARY), Ready::all(), PollOpt::edge()).expect("Error reregistering stream");
+				}
+			},
 			_ => warn!("Unexpected stream update")
 		}
 	}
 }
 
+/// Parse either enode URL or node id.
+fn parse_node_id(id: &str) -> Result<NodeId, NetworkError> {
+	if id.starts_with("enode://") {
+		Ok(Node::from_str(id)?.id)
+	} else {
+		id.trim_left_matches("0x").parse().map_err(|_| NetworkError::InvalidNodeId)
+	}
+}
+
 fn save_key(path: &Path, key: &Secret) {
 	let mut path_buf = PathBuf::from(path);
 	if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
@@ -1281,7 +1714,7 @@ fn host_client_url() {
 	let mut config = NetworkConfiguration::new_local();
 	let key = "6f7b0d801bc7b5ce7bbd930b84fd0369b3eb25d09be58d64ba811091046f3aa2".parse().unwrap();
 	config.use_secret = Some(key);
-	let host: Host = Host::new(config, Arc::new(NetworkStats::new()), None).unwrap();
+	let host: Host = Host::new(config, Arc::new(NetworkStats::new()), None, Arc::new(RwLock::new(AccessLists::new(None))), Arc::new(Bandwidth::new(Default::default()))).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
 
@@ -18,10 +18,14 @@
 
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::io;
-use igd::{PortMappingProtocol, search_gateway_from_timeout};
+use igd::{Gateway, PortMappingProtocol, search_gateway_from_timeout};
 use std::time::Duration;
+use mio::tcp::TcpListener;
+use mio::udp::UdpSocket;
+use net2::{TcpBuilder, UdpBuilder};
 use node_table::{NodeEndpoint};
 use ipnetwork::{IpNetwork};
+use nat_pmp;
 
 /// Socket address extension for rustc beta. To be replaces with now unstable API
 pub trait SocketAddrExt {
@@ -236,17 +240,7 @@ mod getinterfaces {
 				let sa: *const sockaddr_in6 = unsafe { mem::transmute(sa) };
 				let sa = & unsafe { *sa };
 				let (addr, port) = (sa.sin6_addr.s6_addr, sa.sin6_port);
-				let addr: [u16; 8] = unsafe { mem::transmute(addr) };
-				(IpAddr::V6(Ipv6Addr::new(
-					addr[0],
-					addr[1],
-					addr[2],
-					addr[3],
-					addr[4],
-					addr[5],
-					addr[6],
-					addr[7])),
-					port)
+				(IpAddr::V6(Ipv6Addr::from(addr)), port)
 			},
 			_ => return None,
 		};
@@ -292,21 +286,31 @@ fn get_if_addrs() -> io::Result<Vec<IpAddr>> {
 
 /// Select the best available public address
 pub fn select_public_address(port: u16) -> SocketAddr {
+	//prefer IPV4 bindings
+	find_public_address(port, false)
+		.or_else(|| find_public_address(port, true))
+		.unwrap_or_else(|| SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port)))
+}
+
+/// Select the best available public address of given IP version, falling back to loopback.
+pub fn select_public_address_of_version(port: u16, v6: bool) -> SocketAddr {
+	find_public_address(port, v6).unwrap_or_else(|| if v6 {
+		SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), port, 0, 0))
+	} else {
+		SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port))
+	})
+}
+
+fn find_public_address(port: u16, v6: bool) -> Option<SocketAddr> {
 	match get_if_addrs() {
 		Ok(list) => {
-			//prefer IPV4 bindings
 			for addr in &list { //TODO: use better criteria than just the first in the list
 				match addr {
-					&IpAddr::V4(a) if !a.is_reserved() => {
-						return SocketAddr::V4(SocketAddrV4::new(a, port));
+					&IpAddr::V4(a) if !v6 && !a.is_reserved() => {
+						return Some(SocketAddr::V4(SocketAddrV4::new(a, port)));
 					},
-					_ => {},
-				}
-			}
-			for addr in &list {
-				match addr {
-					&IpAddr::V6(a) if !a.is_reserved() => {
-						return SocketAddr::V6(SocketAddrV6::new(a, port, 0, 0));
+					&IpAddr::V6(a) if v6 && !a.is_reserved() => {
+						return Some(SocketAddr::V6(SocketAddrV6::new(a, port, 0, 0)));
 					},
 					_ => {},
 				}
@@ -314,37 +318,162 @@ pub fn select_public_address(port: u16) -> SocketAddr {
 		},
 		Err(e) => debug!("Error listing public interfaces: {:?}", e)
 	}
-	SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port))
+	None
 }
 
-pub fn map_external_address(local: &NodeEndpoint) -> Option<NodeEndpoint> {
-	if let SocketAddr::V4(ref local_addr) = local.address {
-		match search_gateway_from_timeout(local_addr.ip().clone(), Duration::new(5, 0)) {
-			Err(ref err) => debug!("Gateway search error: {}", err),
-			Ok(gateway) => {
-				match gateway.get_external_ip() {
-					Err(ref err) => {
-						debug!("IP request error: {}", err);
-					},
-					Ok(external_addr) => {
-						match gateway.add_any_port(PortMappingProtocol::TCP, SocketAddrV4::new(local_addr.ip().clone(), local_addr.port()), 0, "Parity Node/TCP") {
-							Err(ref err) => {
-								debug!("Port mapping error: {}", err);
-							},
-							Ok(tcp_port) => {
-								match gateway.add_any_port(PortMappingProtocol::UDP, SocketAddrV4::new(local_addr.ip().clone(), local.udp_port), 0, "Parity Node/UDP") {
-									Err(ref err) => {
-										debug!("Port mapping error: {}", err);
-									},
-									Ok(udp_port) => {
-										return Some(NodeEndpoint { address: SocketAddr::V4(SocketAddrV4::new(external_addr, tcp_port)), udp_port: udp_port });
-									},
-								}
-							},
-						}
-					},
-				}
+/// Address of the other IP version to listen on alongside `address`. Only unspecified
+/// and loopback addresses have a counterpart, a specific interface address has none.
+pub fn other_version_address(address: &SocketAddr) -> Option<SocketAddr> {
+	let ip = match address.ip() {
+		IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0)),
+		IpAddr::V4(ip) if ip.is_loopback() => IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
+		IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+		IpAddr::V6(ip) if ip.is_loopback() => IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+		_ => return None,
+	};
+	Some(SocketAddr::new(ip, address.port()))
+}
+
+/// Bind a TCP listener. IPv6 listeners only accept IPv6 connections, so that an IPv4
+/// listener can be bound to the same port.
+pub fn bind_tcp_listener(address: &SocketAddr) -> io::Result<TcpListener> {
+	match *address {
+		SocketAddr::V4(_) => TcpListener::bind(address),
+		SocketAddr::V6(_) => {
+			let builder = TcpBuilder::new_v6()?;
+			builder.only_v6(true)?;
+			builder.reuse_address(true)?;
+			builder.bind(address)?;
+			TcpListener::from_listener(builder.listen(1024)?, address)
+		},
+	}
+}
+
+/// Bind a UDP socket. IPv6 sockets only receive IPv6 datagrams, so that an IPv4
+/// socket can be bound to the same port.
+pub fn bind_udp_socket(address: &SocketAddr) -> io::Result<UdpSocket> {
+	match *address {
+		SocketAddr::V4(_) => UdpSocket::bind(address),
+		SocketAddr::V6(_) => {
+			let builder = UdpBuilder::new_v6()?;
+			builder.only_v6(true)?;
+			UdpSocket::from_socket(builder.bind(address)?)
+		},
+	}
+}
+
+/// Lifetime of port mappings in seconds. Mappings should be renewed before they expire.
+pub const MAPPING_LIFETIME: u32 = 20 * 60;
+
+enum MappingGateway {
+	Upnp(Gateway),
+	NatPmp(nat_pmp::Gateway),
+}
+
+/// Port mapping set up on the gateway for both TCP and UDP ports of the node.
+pub struct PortMapping {
+	gateway: MappingGateway,
+	local_address: SocketAddrV4,
+	local_udp_port: u16,
+	/// Externally visible endpoint.
+	pub external: NodeEndpoint,
+}
+
+impl PortMapping {
+	/// Name of the protocol used for the mapping.
+	pub fn protocol(&self) -> &'static str {
+		match self.gateway {
+			MappingGateway::Upnp(_) => "UPnP",
+			MappingGateway::NatPmp(_) => "NAT-PMP",
+		}
+	}
+
+	/// Renew the mapping lease, keeping the external ports if possible.
+	/// Returns the external endpoint, which might have changed.
+	pub fn renew(&mut self) -> Result<NodeEndpoint, String> {
+		let local_tcp = self.local_address;
+		let local_udp = SocketAddrV4::new(local_tcp.ip().clone(), self.local_udp_port);
+		let external = match self.gateway {
+			MappingGateway::Upnp(ref gateway) => {
+				let ip = gateway.get_external_ip().map_err(|e| format!("IP request error: {}", e))?;
+				let tcp_port = upnp_renew(gateway, PortMappingProtocol::TCP, self.external.address.port(), local_tcp, "Parity Node/TCP")?;
+				let udp_port = upnp_renew(gateway, PortMappingProtocol::UDP, self.external.udp_port, local_udp, "Parity Node/UDP")?;
+				NodeEndpoint { address: SocketAddr::V4(SocketAddrV4::new(ip, tcp_port)), udp_port: udp_port }
+			},
+			MappingGateway::NatPmp(ref gateway) => {
+				let ip = gateway.external_address().map_err(|e| format!("NAT-PMP address request error: {:?}", e))?;
+				let tcp = gateway.map_port(nat_pmp::Protocol::Tcp, local_tcp.port(), self.external.address.port(), MAPPING_LIFETIME)
+					.map_err(|e| format!("NAT-PMP mapping error: {:?}", e))?;
+				let udp = gateway.map_port(nat_pmp::Protocol::Udp, local_udp.port(), self.external.udp_port, MAPPING_LIFETIME)
+					.map_err(|e| format!("NAT-PMP mapping error: {:?}", e))?;
+				NodeEndpoint { address: SocketAddr::V4(SocketAddrV4::new(ip, tcp.external_port)), udp_port: udp.external_port }
 			},
+		};
+		self.external = external.clone();
+		Ok(external)
+	}
+}
+
+/// Add a mapping with `MAPPING_LIFETIME` lease, falling back to a permanent one
+/// for gateways which only support these.
+fn upnp_add_any_port(gateway: &Gateway, protocol: PortMappingProtocol, local: SocketAddrV4, description: &str) -> Result<u16, String> {
+	gateway.add_any_port(protocol, local, MAPPING_LIFETIME, description)
+		.or_else(|_| gateway.add_any_port(protocol, local, 0, description))
+		.map_err(|e| format!("Port mapping error: {}", e))
+}
+
+/// Refresh the mapping of given external port, mapping any port if it's been taken meanwhile.
+fn upnp_renew(gateway: &Gateway, protocol: PortMappingProtocol, external_port: u16, local: SocketAddrV4, description: &str) -> Result<u16, String> {
+	match gateway.add_port(protocol, external_port, local, MAPPING_LIFETIME, description)
+		.or_else(|_| gateway.add_port(protocol, external_port, local, 0, description)) {
+		Ok(()) => Ok(external_port),
+		Err(e) => {
+			debug!("Error renewing port mapping {}: {}", external_port, e);
+			upnp_add_any_port(gateway, protocol, local, description)
+		}
+	}
+}
+
+fn map_upnp(local_addr: &SocketAddrV4, udp_port: u16) -> Result<PortMapping, String> {
+	let gateway = search_gateway_from_timeout(local_addr.ip().clone(), Duration::new(5, 0))
+		.map_err(|e| format!("Gateway search error: {}", e))?;
+	let external_addr = gateway.get_external_ip().map_err(|e| format!("IP request error: {}", e))?;
+	let tcp_port = upnp_add_any_port(&gateway, PortMappingProtocol::TCP, SocketAddrV4::new(local_addr.ip().clone(), local_addr.port()), "Parity Node/TCP")?;
+	let udp_port = upnp_add_any_port(&gateway, PortMappingProtocol::UDP, SocketAddrV4::new(local_addr.ip().clone(), udp_port), "Parity Node/UDP")?;
+	Ok(PortMapping {
+		gateway: MappingGateway::Upnp(gateway),
+		local_address: local_addr.clone(),
+		local_udp_port: udp_port,
+		external: NodeEndpoint { address: SocketAddr::V4(SocketAddrV4::new(external_addr, tcp_port)), udp_port: udp_port },
+	})
+}
+
+fn map_nat_pmp(local_addr: &SocketAddrV4, udp_port: u16) -> Result<PortMapping, String> {
+	let gateway = nat_pmp::Gateway::default_route().ok_or_else(|| "Default gateway not found".to_owned())?;
+	let mut mapping = PortMapping {
+		gateway: MappingGateway::NatPmp(gateway),
+		local_address: local_addr.clone(),
+		local_udp_port: udp_port,
+		external: NodeEndpoint { address: SocketAddr::V4(local_addr.clone()), udp_port: udp_port },
+	};
+	mapping.renew()?;
+	Ok(mapping)
+}
+
+/// Map the node ports on the gateway using UPnP and/or NAT-PMP, UPnP being tried first.
+pub fn map_external_address(local: &NodeEndpoint, upnp: bool, nat_pmp: bool) -> Option<PortMapping> {
+	if let SocketAddr::V4(ref local_addr) = local.address {
+		if upnp {
+			match map_upnp(local_addr, local.udp_port) {
+				Ok(mapping) => return Some(mapping),
+				Err(e) => debug!("UPnP: {}", e),
+			}
+		}
+		if nat_pmp {
+			match map_nat_pmp(local_addr, local.udp_port) {
+				Ok(mapping) => return Some(mapping),
+				Err(e) => debug!("NAT-PMP: {}", e),
+			}
 		}
 	}
 	None
@@ -356,11 +485,34 @@ fn can_select_public_address() {
 	assert!(pub_address.port() == 40477);
 }
 
+#[test]
+fn can_select_public_address_of_version() {
+	assert!(select_public_address_of_version(40479, true).is_ipv6());
+	assert!(select_public_address_of_version(40479, false).is_ipv4());
+}
+
+#[test]
+fn selects_other_version_address() {
+	let address = |s: &str| s.parse::<SocketAddr>().unwrap();
+	assert_eq!(other_version_address(&address("0.0.0.0:30303")), Some(address("[::]:30303")));
+	assert_eq!(other_version_address(&address("[::1]:30303")), Some(address("127.0.0.1:30303")));
+	assert_eq!(other_version_address(&address("10.0.0.1:30303")), None);
+}
+
+#[test]
+fn binds_both_versions_to_one_port() {
+	let v4 = bind_udp_socket(&"127.0.0.1:0".parse().unwrap()).unwrap();
+	let port = v4.local_addr().unwrap().port();
+	if let Ok(v6) = bind_udp_socket(&SocketAddr::new("::1".parse().unwrap(), port)) {
+		assert_eq!(v6.local_addr().unwrap().port(), port);
+	}
+}
+
 #[ignore]
 #[test]
 fn can_map_external_address_or_fail() {
 	let pub_address = select_public_address(40478);
-	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40478 });
+	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40478 }, true, true);
 }
 
 #[test]
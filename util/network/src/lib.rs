@@ -62,6 +62,7 @@ extern crate ethcore_util as util;
 extern crate ethcore_bigint as bigint;
 extern crate parking_lot;
 extern crate mio;
+extern crate net2;
 extern crate tiny_keccak;
 extern crate crypto as rcrypto;
 extern crate rand;
@@ -92,12 +93,20 @@ mod connection;
 mod handshake;
 mod session;
 mod discovery;
+mod dns_discovery;
 mod service;
 mod error;
 mod node_table;
 mod stats;
 mod ip_utils;
+mod peer_limits;
+mod nat_pmp;
 mod connection_filter;
+mod reputation;
+mod access_lists;
+mod send_queue;
+mod peer_keys;
+mod bandwidth;
 
 #[cfg(test)]
 mod tests;
@@ -106,15 +115,21 @@ pub use host::{HostInfo, PeerId, PacketId, ProtocolId, NetworkContext, NetworkIo
 pub use service::NetworkService;
 pub use error::NetworkError;
 pub use stats::NetworkStats;
-pub use session::SessionInfo;
+pub use session::{SessionInfo, ProtocolTraffic};
 pub use connection_filter::{ConnectionFilter, ConnectionDirection};
+pub use reputation::{Offense, NodeReputation};
+pub use access_lists::{AccessList, AccessEntry, AccessLists};
+pub use send_queue::PacketPriority;
+pub use peer_keys::is_valid_reserved_node;
+pub use bandwidth::BandwidthLimits;
 
 pub use io::TimerToken;
 pub use node_table::{is_valid_node_url, NodeId};
 use ipnetwork::{IpNetwork, IpNetworkError};
 use std::str::FromStr;
+use std::net::IpAddr;
 
-const PROTOCOL_VERSION: u32 = 4;
+const PROTOCOL_VERSION: u32 = 5;
 
 /// Network IO protocol handler. This needs to be implemented for each new subprotocol.
 /// All the handler function are called from within IO event loop.
@@ -152,6 +167,62 @@ impl NonReservedPeerMode {
 	}
 }
 
+/// IP versions used for discovery and connections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub enum IpVersion {
+	/// Use both, preferring IPv4 addresses of nodes reachable over both. This is the default.
+	Dual,
+	/// Use both, preferring IPv6 addresses of nodes reachable over both.
+	DualPreferV6,
+	/// Use IPv4 only.
+	V4,
+	/// Use IPv6 only.
+	V6,
+}
+
+impl Default for IpVersion {
+	fn default() -> Self {
+		IpVersion::Dual
+	}
+}
+
+impl IpVersion {
+	/// Attempt to parse the IP version policy from a string.
+	pub fn parse(s: &str) -> Option<Self> {
+		match s {
+			"dual" => Some(IpVersion::Dual),
+			"dual-v6" => Some(IpVersion::DualPreferV6),
+			"v4" => Some(IpVersion::V4),
+			"v6" => Some(IpVersion::V6),
+			_ => None,
+		}
+	}
+
+	/// Whether addresses of given IP version are used.
+	pub fn allows(&self, ip: &IpAddr) -> bool {
+		match (*self, *ip) {
+			(IpVersion::V4, IpAddr::V6(_)) | (IpVersion::V6, IpAddr::V4(_)) => false,
+			_ => true,
+		}
+	}
+
+	/// Whether IPv6 is preferred over IPv4.
+	pub fn prefers_v6(&self) -> bool {
+		*self == IpVersion::DualPreferV6 || *self == IpVersion::V6
+	}
+
+	/// Whether both IP versions are used.
+	pub fn is_dual(&self) -> bool {
+		*self == IpVersion::Dual || *self == IpVersion::DualPreferV6
+	}
+
+	/// Whether address `a` should be used rather than `b` for a node reachable over both.
+	pub fn is_preferred(&self, a: &IpAddr, b: &IpAddr) -> bool {
+		self.allows(a) && (!self.allows(b) || a.is_ipv6() == self.prefers_v6() || a.is_ipv6() == b.is_ipv6())
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "ipc", binary)]
 pub struct IpFilter {
@@ -0,0 +1,233 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Minimal NAT-PMP (RFC 6886) client.
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::time::Duration;
+
+/// Port NAT-PMP gateways listen on.
+const NAT_PMP_PORT: u16 = 5351;
+/// Initial response timeout, doubled with every retransmission.
+const INITIAL_TIMEOUT_MS: u64 = 250;
+/// Number of attempts before giving up on the gateway.
+const MAX_ATTEMPTS: usize = 4;
+
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+const OP_MAP_UDP: u8 = 1;
+const OP_MAP_TCP: u8 = 2;
+const OP_RESPONSE: u8 = 128;
+
+/// Transport protocol of a port mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+	Udp,
+	Tcp,
+}
+
+/// Port mapping granted by the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+	/// Externally visible port.
+	pub external_port: u16,
+	/// Lifetime of the mapping in seconds.
+	pub lifetime: u32,
+}
+
+#[derive(Debug)]
+pub enum Error {
+	Io(io::Error),
+	/// Gateway replied with a non-zero result code.
+	Rejected(u16),
+	/// Gateway replied with a packet which couldn't be decoded.
+	InvalidResponse,
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Error::Io(err)
+	}
+}
+
+/// NAT-PMP gateway.
+#[derive(Debug, Clone)]
+pub struct Gateway {
+	address: SocketAddrV4,
+}
+
+impl Gateway {
+	pub fn new(ip: Ipv4Addr) -> Self {
+		Gateway { address: SocketAddrV4::new(ip, NAT_PMP_PORT) }
+	}
+
+	/// Gateway of the default route, if it can be determined.
+	pub fn default_route() -> Option<Self> {
+		default_gateway().map(Gateway::new)
+	}
+
+	/// Query the external address of the gateway.
+	pub fn external_address(&self) -> Result<Ipv4Addr, Error> {
+		let response = self.request(&[0, OP_EXTERNAL_ADDRESS], 12)?;
+		decode_external_address(&response)
+	}
+
+	/// Request a mapping of the local port, preferably to the same external port.
+	/// Requesting the mapping again before its lifetime expires renews it.
+	pub fn map_port(&self, protocol: Protocol, local_port: u16, external_port: u16, lifetime: u32) -> Result<Mapping, Error> {
+		let request = encode_mapping_request(protocol, local_port, external_port, lifetime);
+		let response = self.request(&request, 16)?;
+		decode_mapping(&response, protocol)
+	}
+
+	fn request(&self, request: &[u8], response_len: usize) -> Result<Vec<u8>, Error> {
+		let socket = UdpSocket::bind("0.0.0.0:0")?;
+		socket.connect(self.address)?;
+		let mut buf = [0u8; 16];
+		let mut timeout = INITIAL_TIMEOUT_MS;
+		let mut last_err = None;
+		for _ in 0..MAX_ATTEMPTS {
+			socket.send(request)?;
+			socket.set_read_timeout(Some(Duration::from_millis(timeout)))?;
+			match socket.recv(&mut buf) {
+				Ok(len) if len >= response_len => return Ok(buf[..response_len].to_vec()),
+				Ok(_) => return Err(Error::InvalidResponse),
+				Err(e) => last_err = Some(e),
+			}
+			timeout *= 2;
+		}
+		Err(last_err.map_or(Error::InvalidResponse, Error::Io))
+	}
+}
+
+fn mapping_op(protocol: Protocol) -> u8 {
+	match protocol {
+		Protocol::Udp => OP_MAP_UDP,
+		Protocol::Tcp => OP_MAP_TCP,
+	}
+}
+
+fn encode_mapping_request(protocol: Protocol, local_port: u16, external_port: u16, lifetime: u32) -> [u8; 12] {
+	[
+		0, mapping_op(protocol), 0, 0,
+		(local_port >> 8) as u8, local_port as u8,
+		(external_port >> 8) as u8, external_port as u8,
+		(lifetime >> 24) as u8, (lifetime >> 16) as u8, (lifetime >> 8) as u8, lifetime as u8,
+	]
+}
+
+fn read_u16(data: &[u8]) -> u16 {
+	(data[0] as u16) << 8 | data[1] as u16
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+	(read_u16(&data[0..2]) as u32) << 16 | read_u16(&data[2..4]) as u32
+}
+
+fn check_header(data: &[u8], op: u8) -> Result<(), Error> {
+	if data[0] != 0 || data[1] != OP_RESPONSE + op {
+		return Err(Error::InvalidResponse);
+	}
+	match read_u16(&data[2..4]) {
+		0 => Ok(()),
+		code => Err(Error::Rejected(code)),
+	}
+}
+
+fn decode_external_address(data: &[u8]) -> Result<Ipv4Addr, Error> {
+	check_header(data, OP_EXTERNAL_ADDRESS)?;
+	Ok(Ipv4Addr::new(data[8], data[9], data[10], data[11]))
+}
+
+fn decode_mapping(data: &[u8], protocol: Protocol) -> Result<Mapping, Error> {
+	check_header(data, mapping_op(protocol))?;
+	Ok(Mapping {
+		external_port: read_u16(&data[10..12]),
+		lifetime: read_u32(&data[12..16]),
+	})
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+	use std::fs::File;
+	use std::io::Read;
+
+	let mut routes = String::new();
+	match File::open("/proc/net/route").and_then(|mut f| f.read_to_string(&mut routes)) {
+		Ok(_) => parse_route_table(&routes),
+		Err(e) => {
+			debug!(target: "network", "Error reading routing table: {:?}", e);
+			None
+		}
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Option<Ipv4Addr> {
+	None
+}
+
+/// Find the default gateway in the `/proc/net/route` format.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_route_table(routes: &str) -> Option<Ipv4Addr> {
+	routes.lines().skip(1)
+		.map(|line| line.split_whitespace().collect::<Vec<_>>())
+		.filter(|fields| fields.len() > 2 && fields[1] == "00000000")
+		.filter_map(|fields| u32::from_str_radix(fields[2], 16).ok())
+		.filter(|gateway| *gateway != 0)
+		// addresses are in host (little endian) byte order.
+		.map(|gateway| Ipv4Addr::new(gateway as u8, (gateway >> 8) as u8, (gateway >> 16) as u8, (gateway >> 24) as u8))
+		.next()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encodes_mapping_request() {
+		assert_eq!(
+			encode_mapping_request(Protocol::Tcp, 30303, 30304, 7200),
+			[0, 2, 0, 0, 0x76, 0x5f, 0x76, 0x60, 0, 0, 0x1c, 0x20]
+		);
+	}
+
+	#[test]
+	fn decodes_responses() {
+		let address = [0, 128, 0, 0, 0, 0, 0, 1, 93, 184, 216, 34];
+		assert_eq!(decode_external_address(&address).unwrap(), Ipv4Addr::new(93, 184, 216, 34));
+
+		let mapping = [0, 129, 0, 0, 0, 0, 0, 1, 0x76, 0x5f, 0x76, 0x60, 0, 0, 0x1c, 0x20];
+		assert_eq!(decode_mapping(&mapping, Protocol::Udp).unwrap(), Mapping { external_port: 30304, lifetime: 7200 });
+		assert!(decode_mapping(&mapping, Protocol::Tcp).is_err());
+
+		let rejected = [0, 130, 0, 2, 0, 0, 0, 1, 0x76, 0x5f, 0, 0, 0, 0, 0, 0];
+		match decode_mapping(&rejected, Protocol::Tcp) {
+			Err(Error::Rejected(2)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn parses_default_gateway() {
+		let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+			eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+			eth0\t00000000\t0100A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+		assert_eq!(parse_route_table(routes), Some(Ipv4Addr::new(192, 168, 0, 1)));
+		assert_eq!(parse_route_table("Iface\tDestination\tGateway\n"), None);
+	}
+}
@@ -14,8 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::mem;
-use std::slice::from_raw_parts;
 use std::net::{SocketAddr, ToSocketAddrs, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
 use std::hash::{Hash, Hasher};
 use std::str::{FromStr};
@@ -37,6 +35,9 @@ pub use rustc_serialize::json::Json;
 /// Node public key
 pub type NodeId = H512;
 
+/// Nodes which failed to connect and weren't seen alive for this long are dropped from the table.
+const MAX_NODE_AGE_SEC: u64 = 7 * 24 * 60 * 60;
+
 #[derive(Debug, Clone)]
 /// Node address info
 pub struct NodeEndpoint {
@@ -78,10 +79,10 @@ impl NodeEndpoint {
 		let addr_bytes = rlp.at(0)?.data()?;
 		let address = match addr_bytes.len() {
 			4 => Ok(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(addr_bytes[0], addr_bytes[1], addr_bytes[2], addr_bytes[3]), tcp_port))),
-			16 => unsafe {
-				let o: *const u16 = mem::transmute(addr_bytes.as_ptr());
-				let o = from_raw_parts(o, 8);
-				Ok(SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::new(o[0], o[1], o[2], o[3], o[4], o[5], o[6], o[7]), tcp_port, 0, 0)))
+			16 => {
+				let mut octets = [0u8; 16];
+				octets.copy_from_slice(addr_bytes);
+				Ok(SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(octets), tcp_port, 0, 0)))
 			},
 			_ => Err(DecoderError::RlpInconsistentLengthAndData)
 		}?;
@@ -93,9 +94,8 @@ impl NodeEndpoint {
 			SocketAddr::V4(a) => {
 				rlp.append(&(&a.ip().octets()[..]));
 			}
-			SocketAddr::V6(a) => unsafe {
-				let o: *const u8 = mem::transmute(a.ip().segments().as_ptr());
-				rlp.append(&from_raw_parts(o, 16));
+			SocketAddr::V6(a) => {
+				rlp.append(&(&a.ip().octets()[..]));
 			}
 		};
 		rlp.append(&self.udp_port);
@@ -145,6 +145,8 @@ pub struct Node {
 	pub peer_type: PeerType,
 	pub failures: u32,
 	pub last_attempted: Option<Tm>,
+	/// Unix timestamp of the last time the node proved to be alive.
+	pub last_seen: Option<u64>,
 }
 
 impl Node {
@@ -155,6 +157,7 @@ impl Node {
 			peer_type: PeerType::Optional,
 			failures: 0,
 			last_attempted: None,
+			last_seen: None,
 		}
 	}
 }
@@ -186,6 +189,7 @@ impl FromStr for Node {
 			peer_type: PeerType::Optional,
 			last_attempted: None,
 			failures: 0,
+			last_seen: None,
 		})
 	}
 }
@@ -221,16 +225,18 @@ impl NodeTable {
 
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
-		// preserve failure counter
-		let failures = self.nodes.get(&node.id).map_or(0, |n| n.failures);
-		node.failures = failures;
+		// preserve failure counter and liveness
+		if let Some(n) = self.nodes.get(&node.id) {
+			node.failures = n.failures;
+			node.last_seen = n.last_seen;
+		}
 		self.nodes.insert(node.id.clone(), node);
 	}
 
-	/// Returns node ids sorted by number of failures
+	/// Returns node ids sorted by number of failures, then by the most recently seen.
 	pub fn nodes(&self, filter: IpFilter) -> Vec<NodeId> {
 		let mut refs: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id) && n.endpoint.is_allowed(&filter)).collect();
-		refs.sort_by(|a, b| a.failures.cmp(&b.failures));
+		refs.sort_by(|a, b| a.failures.cmp(&b.failures).then_with(|| b.last_seen.cmp(&a.last_seen)));
 		refs.iter().map(|n| n.id.clone()).collect()
 	}
 
@@ -240,6 +246,11 @@ impl NodeTable {
 		self.nodes.values().map(|n| NodeEntry { endpoint: n.endpoint.clone(), id: n.id.clone() }).collect()
 	}
 
+	/// Get particular node
+	pub fn get(&self, id: &NodeId) -> Option<&Node> {
+		self.nodes.get(id)
+	}
+
 	/// Get particular node
 	pub fn get_mut(&mut self, id: &NodeId) -> Option<&mut Node> {
 		self.nodes.get_mut(id)
@@ -261,6 +272,12 @@ impl NodeTable {
 				self.nodes.remove(&r);
 			}
 		}
+		let now = unix_now();
+		for id in update.seen {
+			if let Some(node) = self.nodes.get_mut(&id) {
+				node.last_seen = Some(now);
+			}
+		}
 	}
 
 	/// Increase failure counte for a node
@@ -295,7 +312,8 @@ impl NodeTable {
 			let node_ids = self.nodes(IpFilter::default());
 			for i in 0 .. node_ids.len() {
 				let node = self.nodes.get(&node_ids[i]).expect("self.nodes() only returns node IDs from self.nodes");
-				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == node_ids.len() - 1 {""} else {","}))
+				let last_seen = node.last_seen.map_or_else(String::new, |t| format!(", \"last_seen\": {}", t));
+				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {}{} }}{}\n", node, node.failures, last_seen, if i == node_ids.len() - 1 {""} else {","}))
 			}
 			json.push_str("]\n");
 			json.push_str("}");
@@ -339,6 +357,7 @@ impl NodeTable {
 					return nodes;
 				}
 			};
+			let now = unix_now();
 			if let Some(list) = json.as_object().and_then(|o| o.get("nodes")).and_then(|n| n.as_array()) {
 				for n in list.iter().filter_map(|n| n.as_object()) {
 					if let Some(url) = n.get("url").and_then(|u| u.as_string()) {
@@ -346,6 +365,11 @@ impl NodeTable {
 							if let Some(failures) = n.get("failures").and_then(|f| f.as_u64()) {
 								node.failures = failures as u32;
 							}
+							node.last_seen = n.get("last_seen").and_then(|t| t.as_u64());
+							if is_dead(&node, now) {
+								trace!(target: "network", "Dropping dead node {}", node);
+								continue;
+							}
 							nodes.insert(node.id.clone(), node);
 						}
 					}
@@ -362,6 +386,15 @@ impl Drop for NodeTable {
 	}
 }
 
+fn unix_now() -> u64 {
+	::time::get_time().sec as u64
+}
+
+/// Whether the node keeps failing and wasn't seen alive recently.
+fn is_dead(node: &Node, now: u64) -> bool {
+	node.failures > 0 && node.last_seen.map_or(true, |t| now.saturating_sub(t) > MAX_NODE_AGE_SEC)
+}
+
 /// Check if node url is valid
 pub fn is_valid_node_url(url: &str) -> bool {
 	use std::str::FromStr;
@@ -388,6 +421,19 @@ mod tests {
 		assert_eq!(SocketAddrV4::new(Ipv4Addr::new(123, 99, 55, 44), 7770), v4);
 	}
 
+	#[test]
+	fn endpoint_rlp_ipv6() {
+		let endpoint = NodeEndpoint::from_str("[2001:db8::1]:30303").unwrap();
+		let mut rlp = RlpStream::new_list(3);
+		endpoint.to_rlp(&mut rlp);
+		let encoded = rlp.out();
+		// address bytes are in network order
+		assert_eq!(&encoded[1..18], &[0x90, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01][..]);
+		let decoded = NodeEndpoint::from_rlp(&UntrustedRlp::new(&encoded)).unwrap();
+		assert_eq!(decoded.address, endpoint.address);
+		assert_eq!(decoded.udp_port, 30303);
+	}
+
 	#[test]
 	fn node_parse() {
 		assert!(is_valid_node_url("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770"));
@@ -449,6 +495,34 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn table_drops_dead_nodes() {
+		let temp_path = RandomTempPath::create_dir();
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node3 = Node::from_str("enode://c979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let (id1, id2, id3) = (node1.id.clone(), node2.id.clone(), node3.id.clone());
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			table.add_node(node1);
+			table.add_node(node2);
+			table.add_node(node3);
+			let mut seen = HashSet::new();
+			seen.insert(id1.clone());
+			table.update(TableUpdates { added: HashMap::new(), removed: HashSet::new(), seen: seen }, &HashSet::new());
+			table.note_failure(&id1);
+			table.note_failure(&id2);
+			assert!(table.get(&id1).unwrap().last_seen.is_some());
+		}
+
+		{
+			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert!(table.get(&id1).unwrap().last_seen.is_some());
+			assert!(table.get(&id2).is_none());
+			assert!(table.get(&id3).is_some());
+		}
+	}
+
 	#[test]
 	fn custom_allow() {
 		let filter = IpFilter {
@@ -0,0 +1,106 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pre-shared keys authenticating reserved peers beyond possession of their node key.
+//!
+//! A reserved node entry may be followed by a 32 byte hex secret shared with that peer.
+//! Both sides then append to their Hello a proof of knowing the secret, bound to the nonces
+//! of the RLPx handshake so it can't be replayed, and disconnect if the peer's proof is
+//! missing or wrong.
+
+use std::str::FromStr;
+use bigint::hash::H256;
+use hash::keccak;
+use error::NetworkError;
+use node_table::Node;
+
+/// Nonces of both sides of the handshake a session was established with.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HandshakeNonces {
+	/// Nonce of the side which originated the connection.
+	pub initiator: H256,
+	/// Nonce of the side which accepted the connection.
+	pub recipient: H256,
+}
+
+/// Parses a reserved node entry: an enode URL, optionally followed by the pre-shared key.
+pub fn parse_reserved_node(entry: &str) -> Result<(Node, Option<H256>), NetworkError> {
+	let mut parts = entry.split_whitespace();
+	let node = Node::from_str(parts.next().unwrap_or(""))?;
+	let key = match parts.next() {
+		Some(key) => Some(parse_key(key)?),
+		None => None,
+	};
+	if parts.next().is_some() {
+		return Err(NetworkError::InvalidPeerKey);
+	}
+	Ok((node, key))
+}
+
+/// Checks if the reserved node entry is valid. See `parse_reserved_node`.
+pub fn is_valid_reserved_node(entry: &str) -> bool {
+	parse_reserved_node(entry).is_ok()
+}
+
+fn parse_key(key: &str) -> Result<H256, NetworkError> {
+	let key = if key.starts_with("0x") { &key[2..] } else { key };
+	H256::from_str(key).map_err(|_| NetworkError::InvalidPeerKey)
+}
+
+/// Proof of knowing `key`, sent by the given side of the session.
+pub fn proof(key: &H256, nonces: &HandshakeNonces, from_initiator: bool) -> H256 {
+	let mut data = Vec::with_capacity(97);
+	data.extend_from_slice(&**key);
+	data.extend_from_slice(&*nonces.initiator);
+	data.extend_from_slice(&*nonces.recipient);
+	data.push(from_initiator as u8);
+	keccak(&data)
+}
+
+#[cfg(test)]
+mod tests {
+	use bigint::hash::H256;
+	use super::*;
+
+	const NODE: &'static str = "enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770";
+
+	#[test]
+	fn parses_reserved_nodes() {
+		let (node, key) = parse_reserved_node(NODE).unwrap();
+		assert_eq!(node.endpoint.address.port(), 7770);
+		assert_eq!(key, None);
+
+		let entry = format!("{}  0x{}", NODE, "11".repeat(32));
+		let (_, key) = parse_reserved_node(&entry).unwrap();
+		assert_eq!(key, Some(H256::from_slice(&[0x11; 32])));
+
+		assert!(!is_valid_reserved_node(&format!("{} 0x1234", NODE)));
+		assert!(!is_valid_reserved_node(&format!("{} {} extra", NODE, "11".repeat(32))));
+		assert!(!is_valid_reserved_node("not a node"));
+	}
+
+	#[test]
+	fn proofs_are_bound_to_session_and_side() {
+		let key = H256::from(1);
+		let nonces = HandshakeNonces { initiator: 2.into(), recipient: 3.into() };
+		let other = HandshakeNonces { initiator: 2.into(), recipient: 4.into() };
+
+		assert_eq!(proof(&key, &nonces, true), proof(&key, &nonces.clone(), true));
+		assert!(proof(&key, &nonces, true) != proof(&key, &nonces, false));
+		assert!(proof(&key, &nonces, true) != proof(&key, &other, true));
+		assert!(proof(&key, &nonces, true) != proof(&H256::from(5), &nonces, true));
+	}
+}
@@ -0,0 +1,134 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Limits of peers connected from a single IP address or subnet.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Limit which prevents a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exceeded {
+	/// Too many peers share the IP address.
+	Ip,
+	/// Too many peers share the subnet.
+	Subnet,
+}
+
+/// Subnet of the address the limits apply to: /24 for IPv4 and /64 for IPv6.
+pub fn subnet(ip: &IpAddr) -> IpAddr {
+	match *ip {
+		IpAddr::V4(ref ip) => {
+			let o = ip.octets();
+			IpAddr::V4(Ipv4Addr::new(o[0], o[1], o[2], 0))
+		},
+		IpAddr::V6(ref ip) => {
+			let s = ip.segments();
+			IpAddr::V6(Ipv6Addr::new(s[0], s[1], s[2], s[3], 0, 0, 0, 0))
+		},
+	}
+}
+
+/// Whether the limits apply to the address. Local peers are never limited.
+fn is_limited(ip: &IpAddr) -> bool {
+	match *ip {
+		IpAddr::V4(ref ip) => !ip.is_loopback() && !ip.is_unspecified(),
+		IpAddr::V6(ref ip) => !ip.is_loopback() && !ip.is_unspecified(),
+	}
+}
+
+/// Numbers of peers connected per IP address and subnet.
+pub struct PeerLimits {
+	max_per_ip: u32,
+	max_per_subnet: u32,
+	ips: HashMap<IpAddr, u32>,
+	subnets: HashMap<IpAddr, u32>,
+}
+
+impl PeerLimits {
+	/// Create new counter with given limits, zero meaning no limit.
+	pub fn new(max_per_ip: u32, max_per_subnet: u32) -> Self {
+		PeerLimits {
+			max_per_ip: max_per_ip,
+			max_per_subnet: max_per_subnet,
+			ips: HashMap::new(),
+			subnets: HashMap::new(),
+		}
+	}
+
+	/// Count a connected peer.
+	pub fn add(&mut self, ip: &IpAddr) {
+		*self.ips.entry(*ip).or_insert(0) += 1;
+		*self.subnets.entry(subnet(ip)).or_insert(0) += 1;
+	}
+
+	/// Check whether another peer with given address can be connected.
+	pub fn check(&self, ip: &IpAddr) -> Result<(), Exceeded> {
+		if !is_limited(ip) {
+			return Ok(());
+		}
+		if self.max_per_ip != 0 && self.ips.get(ip).map_or(false, |n| *n >= self.max_per_ip) {
+			return Err(Exceeded::Ip);
+		}
+		if self.max_per_subnet != 0 && self.subnets.get(&subnet(ip)).map_or(false, |n| *n >= self.max_per_subnet) {
+			return Err(Exceeded::Subnet);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn computes_subnets() {
+		assert_eq!(subnet(&"10.1.2.3".parse().unwrap()), "10.1.2.0".parse::<IpAddr>().unwrap());
+		assert_eq!(subnet(&"2001:db8:1:2:3:4:5:6".parse().unwrap()), "2001:db8:1:2::".parse::<IpAddr>().unwrap());
+	}
+
+	#[test]
+	fn limits_peers_per_ip_and_subnet() {
+		let mut limits = PeerLimits::new(2, 3);
+		let ip1: IpAddr = "10.1.2.3".parse().unwrap();
+		let ip2: IpAddr = "10.1.2.4".parse().unwrap();
+		let other: IpAddr = "10.1.3.3".parse().unwrap();
+
+		limits.add(&ip1);
+		assert_eq!(limits.check(&ip1), Ok(()));
+		limits.add(&ip1);
+		assert_eq!(limits.check(&ip1), Err(Exceeded::Ip));
+		assert_eq!(limits.check(&ip2), Ok(()));
+		limits.add(&ip2);
+		assert_eq!(limits.check(&ip2), Err(Exceeded::Subnet));
+		assert_eq!(limits.check(&other), Ok(()));
+	}
+
+	#[test]
+	fn does_not_limit_local_or_disabled() {
+		let mut limits = PeerLimits::new(1, 1);
+		let local: IpAddr = "127.0.0.1".parse().unwrap();
+		limits.add(&local);
+		assert_eq!(limits.check(&local), Ok(()));
+
+		let mut unlimited = PeerLimits::new(0, 0);
+		let ip: IpAddr = "10.1.2.3".parse().unwrap();
+		unlimited.add(&ip);
+		unlimited.add(&ip);
+		assert_eq!(unlimited.check(&ip), Ok(()));
+	}
+}
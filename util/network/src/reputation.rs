@@ -0,0 +1,345 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Node reputation: misbehaving nodes lose score and get banned for escalating periods.
+
+use std::collections::HashMap;
+use std::cmp;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use node_table::{NodeId, Json};
+
+/// Score at or below which a node gets banned.
+pub const BAN_THRESHOLD: i64 = -100;
+/// Score regained per minute without offenses.
+const RECOVERY_PER_MINUTE: i64 = 1;
+/// Duration of the first ban in seconds, doubled with every subsequent one.
+const BASE_BAN_SECS: u64 = 10 * 60;
+/// Longest ban in seconds.
+const MAX_BAN_SECS: u64 = 24 * 60 * 60;
+
+/// Kind of peer misbehaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offense {
+	/// Didn't respond to a request in time.
+	Timeout,
+	/// Sent data which wasn't requested or is of no use, e.g. ancient blocks.
+	Spam,
+	/// Sent a packet which couldn't be decoded.
+	Malformed,
+	/// Sent an invalid block.
+	BadBlock,
+}
+
+impl Offense {
+	/// Score the offense costs.
+	pub fn penalty(&self) -> i64 {
+		match *self {
+			Offense::Timeout => 5,
+			Offense::Spam => 10,
+			Offense::Malformed => 25,
+			Offense::BadBlock => 50,
+		}
+	}
+}
+
+/// Reputation of a single node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeReputation {
+	/// Current score. Zero for nodes in good standing, negative after offenses.
+	pub score: i64,
+	/// Number of times the node has been banned.
+	pub bans: u32,
+	/// UNIX timestamp (in seconds) the latest ban expires at.
+	pub banned_until: Option<u64>,
+	/// UNIX timestamp (in seconds) the score was last updated at.
+	updated: u64,
+}
+
+impl NodeReputation {
+	fn new(now: u64) -> Self {
+		NodeReputation {
+			score: 0,
+			bans: 0,
+			banned_until: None,
+			updated: now,
+		}
+	}
+
+	/// Whether the node is banned at given time.
+	pub fn is_banned(&self, now: u64) -> bool {
+		self.banned_until.map_or(false, |until| until > now)
+	}
+
+	/// Seconds left until the ban expires, zero if the node isn't banned.
+	pub fn banned_for(&self) -> u64 {
+		let now = unix_now();
+		self.banned_until.map_or(0, |until| until.saturating_sub(now))
+	}
+
+	fn recover(&mut self, now: u64) {
+		let minutes = now.saturating_sub(self.updated) / 60;
+		self.score = cmp::min(0, self.score.saturating_add((minutes as i64).saturating_mul(RECOVERY_PER_MINUTE)));
+		self.updated += minutes * 60;
+	}
+
+	fn is_neutral(&self, now: u64) -> bool {
+		self.score == 0 && self.bans == 0 && !self.is_banned(now)
+	}
+}
+
+/// Duration of the ban with given (1-based) number.
+fn ban_duration(ban: u32) -> u64 {
+	let shift = cmp::min(ban.saturating_sub(1), 32);
+	cmp::min(BASE_BAN_SECS.saturating_mul(1u64 << shift), MAX_BAN_SECS)
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Reputations of nodes, backed by disk file.
+pub struct Reputations {
+	nodes: HashMap<NodeId, NodeReputation>,
+	path: Option<String>,
+}
+
+impl Reputations {
+	pub fn new(path: Option<String>) -> Self {
+		Reputations {
+			nodes: Reputations::load(path.clone()),
+			path: path,
+		}
+	}
+
+	/// Penalizes the node for the offense. Returns `true` if the node got banned.
+	pub fn report(&mut self, id: &NodeId, offense: Offense) -> bool {
+		self.report_at(id, offense, unix_now())
+	}
+
+	fn report_at(&mut self, id: &NodeId, offense: Offense, now: u64) -> bool {
+		let reputation = self.nodes.entry(id.clone()).or_insert_with(|| NodeReputation::new(now));
+		reputation.recover(now);
+		reputation.score = reputation.score.saturating_sub(offense.penalty());
+		trace!(target: "network", "Node {} penalized for {:?}, score {}", id, offense, reputation.score);
+		if reputation.score > BAN_THRESHOLD {
+			return false;
+		}
+
+		// start over after the ban, repeated offenders get longer bans.
+		reputation.score = 0;
+		reputation.bans += 1;
+		let duration = ban_duration(reputation.bans);
+		reputation.banned_until = Some(now + duration);
+		debug!(target: "network", "Node {} banned for {}s", id, duration);
+		true
+	}
+
+	/// Whether the node is currently banned.
+	pub fn is_banned(&self, id: &NodeId) -> bool {
+		self.is_banned_at(id, unix_now())
+	}
+
+	fn is_banned_at(&self, id: &NodeId, now: u64) -> bool {
+		self.nodes.get(id).map_or(false, |r| r.is_banned(now))
+	}
+
+	/// All known reputations with scores updated to the current time.
+	pub fn entries(&self) -> Vec<(NodeId, NodeReputation)> {
+		let now = unix_now();
+		self.nodes.iter()
+			.map(|(id, reputation)| {
+				let mut reputation = reputation.clone();
+				reputation.recover(now);
+				(id.clone(), reputation)
+			})
+			.filter(|&(_, ref reputation)| !reputation.is_neutral(now))
+			.collect()
+	}
+
+	/// Forgets the reputation of given node, or of all nodes.
+	pub fn clear(&mut self, id: Option<&NodeId>) {
+		match id {
+			Some(id) => { self.nodes.remove(id); },
+			None => self.nodes.clear(),
+		}
+	}
+
+	/// Save the reputation.json file.
+	pub fn save(&self) {
+		if let Some(ref path) = self.path {
+			let mut path_buf = PathBuf::from(path);
+			if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
+				warn!("Error creating reputation directory: {:?}", e);
+				return;
+			};
+			path_buf.push("reputation.json");
+			let now = unix_now();
+			let entries: Vec<String> = self.nodes.iter()
+				.filter(|&(_, reputation)| !reputation.is_neutral(now))
+				.map(|(id, reputation)| format!(
+					"\t{{ \"id\": \"{:?}\", \"score\": {}, \"bans\": {}, \"bannedUntil\": {}, \"updated\": {} }}",
+					id,
+					reputation.score,
+					reputation.bans,
+					reputation.banned_until.unwrap_or(0),
+					reputation.updated,
+				))
+				.collect();
+			let json = format!("{{\n\"reputations\": [\n{}\n]\n}}", entries.join(",\n"));
+			let mut file = match fs::File::create(path_buf.as_path()) {
+				Ok(file) => file,
+				Err(e) => {
+					warn!("Error creating reputation file: {:?}", e);
+					return;
+				}
+			};
+			if let Err(e) = file.write(&json.into_bytes()) {
+				warn!("Error writing reputation file: {:?}", e);
+			}
+		}
+	}
+
+	fn load(path: Option<String>) -> HashMap<NodeId, NodeReputation> {
+		let mut nodes = HashMap::new();
+		if let Some(path) = path {
+			let mut path_buf = PathBuf::from(path);
+			path_buf.push("reputation.json");
+			let mut file = match fs::File::open(path_buf.as_path()) {
+				Ok(file) => file,
+				Err(e) => {
+					debug!("Error opening reputation file: {:?}", e);
+					return nodes;
+				}
+			};
+			let mut buf = String::new();
+			if let Err(e) = file.read_to_string(&mut buf) {
+				warn!("Error reading reputation file: {:?}", e);
+				return nodes;
+			}
+			let json = match Json::from_str(&buf) {
+				Ok(json) => json,
+				Err(e) => {
+					warn!("Error parsing reputation file: {:?}", e);
+					return nodes;
+				}
+			};
+			if let Some(list) = json.as_object().and_then(|o| o.get("reputations")).and_then(|n| n.as_array()) {
+				for n in list.iter().filter_map(|n| n.as_object()) {
+					let id = match n.get("id").and_then(|id| id.as_string()).and_then(|id| NodeId::from_str(id).ok()) {
+						Some(id) => id,
+						None => continue,
+					};
+					let field = |name: &str| n.get(name).and_then(|v| v.as_i64()).unwrap_or(0);
+					let banned_until = field("bannedUntil");
+					nodes.insert(id, NodeReputation {
+						score: cmp::min(0, field("score")),
+						bans: field("bans") as u32,
+						banned_until: if banned_until > 0 { Some(banned_until as u64) } else { None },
+						updated: field("updated") as u64,
+					});
+				}
+			}
+		}
+		nodes
+	}
+}
+
+impl Drop for Reputations {
+	fn drop(&mut self) {
+		self.save();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use devtools::RandomTempPath;
+
+	#[test]
+	fn weighs_offenses() {
+		let mut reputations = Reputations::new(None);
+		let timeouts = NodeId::from(1);
+		let bad_blocks = NodeId::from(2);
+
+		assert!(!reputations.report_at(&timeouts, Offense::Timeout, 0));
+		assert!(!reputations.report_at(&timeouts, Offense::Timeout, 0));
+		assert!(!reputations.report_at(&bad_blocks, Offense::BadBlock, 0));
+		assert!(reputations.report_at(&bad_blocks, Offense::BadBlock, 0));
+		assert!(!reputations.is_banned_at(&timeouts, 0));
+		assert!(reputations.is_banned_at(&bad_blocks, 0));
+		assert_eq!(reputations.nodes[&timeouts].score, -10);
+	}
+
+	#[test]
+	fn escalates_bans() {
+		let mut reputations = Reputations::new(None);
+		let id = NodeId::from(1);
+
+		assert!(!reputations.report_at(&id, Offense::BadBlock, 0));
+		assert!(reputations.report_at(&id, Offense::BadBlock, 0));
+		assert_eq!(reputations.nodes[&id].banned_until, Some(BASE_BAN_SECS));
+		assert!(!reputations.is_banned_at(&id, BASE_BAN_SECS));
+
+		let now = BASE_BAN_SECS;
+		reputations.report_at(&id, Offense::BadBlock, now);
+		assert!(reputations.report_at(&id, Offense::BadBlock, now));
+		assert_eq!(reputations.nodes[&id].banned_until, Some(now + 2 * BASE_BAN_SECS));
+		assert_eq!(ban_duration(100), MAX_BAN_SECS);
+	}
+
+	#[test]
+	fn recovers_over_time() {
+		let mut reputations = Reputations::new(None);
+		let id = NodeId::from(1);
+
+		reputations.report_at(&id, Offense::BadBlock, 0);
+		// 30 minutes without offenses bring the score back to -20.
+		assert!(!reputations.report_at(&id, Offense::BadBlock, 30 * 60));
+		assert_eq!(reputations.nodes[&id].score, -70);
+	}
+
+	#[test]
+	fn clears_reputation() {
+		let mut reputations = Reputations::new(None);
+		let id = NodeId::from(1);
+		reputations.report_at(&id, Offense::BadBlock, 0);
+		reputations.report_at(&id, Offense::BadBlock, 0);
+
+		reputations.clear(Some(&id));
+		assert!(!reputations.is_banned_at(&id, 0));
+		assert!(reputations.nodes.is_empty());
+	}
+
+	#[test]
+	fn persists_reputation() {
+		let temp_path = RandomTempPath::create_dir();
+		let id = NodeId::from(1);
+		{
+			let mut reputations = Reputations::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			reputations.report(&id, Offense::BadBlock);
+			reputations.report(&id, Offense::BadBlock);
+		}
+
+		let reputations = Reputations::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+		assert!(reputations.is_banned(&id));
+		assert_eq!(reputations.nodes[&id].bans, 1);
+	}
+}
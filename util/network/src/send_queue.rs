@@ -0,0 +1,155 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Weighted priority queue of outbound session packets.
+
+use std::collections::VecDeque;
+use util::Bytes;
+
+/// Priority class of an outbound packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketPriority {
+	/// Consensus messages and new block announcements.
+	High,
+	/// Transaction propagation and requests.
+	Normal,
+	/// Responses serving historical chain data.
+	Low,
+}
+
+/// Bytes credited to a class per round, multiplied by the class weight.
+const QUANTUM: usize = 16 * 1024;
+/// Bandwidth shares of the `High`, `Normal` and `Low` classes when all are backlogged.
+const WEIGHTS: [usize; 3] = [8, 4, 1];
+
+#[derive(Default)]
+struct Class {
+	packets: VecDeque<Bytes>,
+	deficit: usize,
+}
+
+/// Deficit round robin queue. Each backlogged class gets a share of bandwidth
+/// proportional to its weight, so low priority traffic is delayed but never starved.
+#[derive(Default)]
+pub struct SendQueue {
+	classes: [Class; 3],
+	current: usize,
+}
+
+impl SendQueue {
+	/// Queue a packet.
+	pub fn push(&mut self, priority: PacketPriority, packet: Bytes) {
+		let index = match priority {
+			PacketPriority::High => 0,
+			PacketPriority::Normal => 1,
+			PacketPriority::Low => 2,
+		};
+		self.classes[index].packets.push_back(packet);
+	}
+
+	/// Take the next packet to be sent.
+	pub fn pop(&mut self) -> Option<Bytes> {
+		if self.is_empty() {
+			return None;
+		}
+		loop {
+			{
+				let class = &mut self.classes[self.current];
+				match class.packets.front().map(|p| p.len()) {
+					Some(len) if class.deficit >= len => {
+						class.deficit -= len;
+						return class.packets.pop_front();
+					},
+					Some(_) => {},
+					// Idle classes don't accumulate credit.
+					None => class.deficit = 0,
+				}
+			}
+			self.current = (self.current + 1) % WEIGHTS.len();
+			self.classes[self.current].deficit += QUANTUM * WEIGHTS[self.current];
+		}
+	}
+
+	/// Drop all queued packets.
+	pub fn clear(&mut self) {
+		for class in &mut self.classes {
+			class.packets.clear();
+			class.deficit = 0;
+		}
+	}
+
+	/// Check if there are no queued packets.
+	pub fn is_empty(&self) -> bool {
+		self.classes.iter().all(|c| c.packets.is_empty())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{SendQueue, PacketPriority, QUANTUM};
+
+	#[test]
+	fn keeps_order_within_class() {
+		let mut queue = SendQueue::default();
+		queue.push(PacketPriority::Normal, vec![1]);
+		queue.push(PacketPriority::Normal, vec![2]);
+		assert_eq!(queue.pop(), Some(vec![1]));
+		assert_eq!(queue.pop(), Some(vec![2]));
+		assert_eq!(queue.pop(), None);
+		assert!(queue.is_empty());
+	}
+
+	#[test]
+	fn high_priority_overtakes_backlog() {
+		let mut queue = SendQueue::default();
+		for _ in 0..10 {
+			queue.push(PacketPriority::Low, vec![0; QUANTUM]);
+		}
+		queue.push(PacketPriority::High, vec![1]);
+		assert_eq!(queue.pop(), Some(vec![1]));
+	}
+
+	#[test]
+	fn shares_bandwidth_by_weight() {
+		let mut queue = SendQueue::default();
+		for _ in 0..100 {
+			queue.push(PacketPriority::High, vec![0; QUANTUM]);
+			queue.push(PacketPriority::Low, vec![2; QUANTUM]);
+		}
+		let low = (0..90).filter_map(|_| queue.pop()).filter(|p| p[0] == 2).count();
+		assert_eq!(low, 10);
+	}
+
+	#[test]
+	fn low_priority_is_not_starved() {
+		let mut queue = SendQueue::default();
+		queue.push(PacketPriority::Low, vec![2; 4 * QUANTUM]);
+		for _ in 0..1000 {
+			queue.push(PacketPriority::High, vec![0; QUANTUM]);
+		}
+		assert!((0..100).filter_map(|_| queue.pop()).any(|p| p[0] == 2));
+	}
+
+	#[test]
+	fn clear_drops_packets() {
+		let mut queue = SendQueue::default();
+		queue.push(PacketPriority::High, vec![1]);
+		queue.push(PacketPriority::Low, vec![2]);
+		queue.clear();
+		assert_eq!(queue.pop(), None);
+	}
+}
@@ -23,6 +23,10 @@ use parking_lot::RwLock;
 use std::sync::Arc;
 use ansi_term::Colour;
 use connection_filter::ConnectionFilter;
+use access_lists::AccessLists;
+use bandwidth::{Bandwidth, BandwidthLimits};
+use node_table::NodeId;
+use reputation::NodeReputation;
 
 struct HostHandler {
 	public_url: RwLock<Option<String>>
@@ -50,6 +54,8 @@ pub struct NetworkService {
 	host_handler: Arc<HostHandler>,
 	config: NetworkConfiguration,
 	filter: Option<Arc<ConnectionFilter>>,
+	access_lists: Arc<RwLock<AccessLists>>,
+	bandwidth: Arc<Bandwidth>,
 }
 
 impl NetworkService {
@@ -60,6 +66,8 @@ impl NetworkService {
 
 		let stats = Arc::new(NetworkStats::new());
 		let host_info = Host::client_version();
+		let access_lists = Arc::new(RwLock::new(AccessLists::new(config.net_config_path.clone())));
+		let bandwidth = Arc::new(Bandwidth::new(config.bandwidth));
 		Ok(NetworkService {
 			io_service: io_service,
 			host_info: host_info,
@@ -68,6 +76,8 @@ impl NetworkService {
 			config: config,
 			host_handler: host_handler,
 			filter: filter,
+			access_lists: access_lists,
+			bandwidth: bandwidth,
 		})
 	}
 
@@ -108,17 +118,28 @@ impl NetworkService {
 		host.as_ref().and_then(|h| h.external_url())
 	}
 
+	/// Returns externally visible TCP port if available.
+	pub fn external_port(&self) -> Option<u16> {
+		let host = self.host.read();
+		host.as_ref().and_then(|h| h.external_port())
+	}
+
 	/// Returns external url if available.
 	pub fn local_url(&self) -> Option<String> {
 		let host = self.host.read();
 		host.as_ref().map(|h| h.local_url())
 	}
 
+	/// Whether network IO is started.
+	pub fn is_started(&self) -> bool {
+		self.host.read().is_some()
+	}
+
 	/// Start network IO
 	pub fn start(&self) -> Result<(), NetworkError> {
 		let mut host = self.host.write();
 		if host.is_none() {
-			let h = Arc::new(Host::new(self.config.clone(), self.stats.clone(), self.filter.clone())?);
+			let h = Arc::new(Host::new(self.config.clone(), self.stats.clone(), self.filter.clone(), self.access_lists.clone(), self.bandwidth.clone())?);
 			self.io_service.register_handler(h.clone())?;
 			*host = Some(h);
 		}
@@ -166,6 +187,48 @@ impl NetworkService {
 		}
 	}
 
+	/// Disconnect given peer (enode URL or node id).
+	pub fn disconnect_peer(&self, peer: &str) -> Result<(), NetworkError> {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			let io_ctxt = IoContext::new(self.io_service.channel(), 0);
+			host.disconnect_node(peer, &io_ctxt)
+		} else {
+			Err(NetworkError::PeerNotFound)
+		}
+	}
+
+	/// Reputations of nodes which misbehaved recently or have been banned.
+	pub fn reputations(&self) -> Vec<(NodeId, NodeReputation)> {
+		self.host.read().as_ref().map_or_else(Vec::new, |h| h.reputations())
+	}
+
+	/// Forget the reputation of given peer (enode URL or node id), or of all peers.
+	pub fn clear_reputation(&self, peer: Option<&str>) -> Result<(), NetworkError> {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			host.clear_reputation(peer)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Allow and deny lists checked on every handshake. Changes are persisted and
+	/// apply to new connections, including those made after the network is restarted.
+	pub fn access_lists(&self) -> &RwLock<AccessLists> {
+		&self.access_lists
+	}
+
+	/// Current upload and download rate limits.
+	pub fn bandwidth_limits(&self) -> BandwidthLimits {
+		self.bandwidth.limits()
+	}
+
+	/// Change upload and download rate limits of all connections, including established ones.
+	pub fn set_bandwidth_limits(&self, limits: BandwidthLimits) {
+		self.bandwidth.set_limits(limits)
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
@@ -32,11 +32,21 @@ use error::{NetworkError, DisconnectReason};
 use host::*;
 use node_table::NodeId;
 use stats::NetworkStats;
+use bandwidth::Bandwidth;
+use send_queue::{SendQueue, PacketPriority};
+use peer_keys::{self, HandshakeNonces};
+use util::snappy;
 use time;
 
 // Timeout must be less than (interval - 1).
 const PING_TIMEOUT_SEC: u64 = 60;
 const PING_INTERVAL_SEC: u64 = 120;
+// Lowest RLPx version we talk to.
+const MIN_PROTOCOL_VERSION: u32 = 4;
+// Lowest RLPx version supporting snappy compressed payloads.
+const MIN_COMPRESSION_PROTOCOL_VERSION: u32 = 5;
+// Largest payload allowed, before compression or after decompression.
+const MAX_PAYLOAD_SIZE: usize = (1 << 24) - 1;
 
 #[derive(Debug, Clone)]
 enum ProtocolState {
@@ -56,11 +66,17 @@ pub struct Session {
 	had_hello: bool,
 	/// Session is no longer active flag.
 	expired: bool,
+	/// Payloads of packets following Hello are snappy compressed.
+	compression: bool,
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
 	state: State,
 	// Protocol states -- accumulates pending packets until signaled as ready.
 	protocol_states: HashMap<ProtocolId, ProtocolState>,
+	// Protocol packets waiting for the connection to become idle.
+	send_queue: SendQueue,
+	// Nonces of the handshake, binding pre-shared key proofs to this session.
+	nonces: HandshakeNonces,
 }
 
 enum State {
@@ -107,6 +123,21 @@ pub struct SessionInfo {
 	pub remote_address: String,
 	/// Local endpoint address of the session
 	pub local_address: String,
+	/// Traffic exchanged with the peer per subprotocol
+	pub traffic: HashMap<ProtocolId, ProtocolTraffic>,
+}
+
+/// Subprotocol packets exchanged with a peer. Sizes are of the uncompressed packet payloads.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProtocolTraffic {
+	/// Number of packets sent
+	pub packets_sent: u64,
+	/// Number of packets received
+	pub packets_received: u64,
+	/// Number of bytes sent
+	pub bytes_sent: u64,
+	/// Number of bytes received
+	pub bytes_received: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -174,10 +205,10 @@ impl Session {
 	/// Create a new session out of comepleted handshake. This clones the handshake connection object
 	/// and leaves the handhsake in limbo to be deregistered from the event loop.
 	pub fn new<Message>(io: &IoContext<Message>, socket: TcpStream, token: StreamToken, id: Option<&NodeId>,
-		nonce: &H256, stats: Arc<NetworkStats>, host: &HostInfo) -> Result<Session, NetworkError>
+		nonce: &H256, stats: Arc<NetworkStats>, bandwidth: Arc<Bandwidth>, host: &HostInfo) -> Result<Session, NetworkError>
 		where Message: Send + Clone + Sync + 'static {
 		let originated = id.is_some();
-		let mut handshake = Handshake::new(token, id, socket, nonce, stats).expect("Can't create handshake");
+		let mut handshake = Handshake::new(token, id, socket, nonce, stats, bandwidth).expect("Can't create handshake");
 		let local_addr = handshake.connection.local_addr_str();
 		handshake.start(io, host, originated)?;
 		Ok(Session {
@@ -193,11 +224,15 @@ impl Session {
 				originated: originated,
 				remote_address: "Handshake".to_owned(),
 				local_address: local_addr,
+				traffic: HashMap::new(),
 			},
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
+			compression: false,
 			protocol_states: HashMap::new(),
+			send_queue: SendQueue::default(),
+			nonces: HandshakeNonces::default(),
 		})
 	}
 
@@ -205,13 +240,16 @@ impl Session {
 		let connection = if let State::Handshake(ref mut h) = self.state {
 			self.info.id = Some(h.id.clone());
 			self.info.remote_address = h.connection.remote_addr_str();
+			self.nonces = match self.info.originated {
+				true => HandshakeNonces { initiator: h.nonce, recipient: h.remote_nonce },
+				false => HandshakeNonces { initiator: h.remote_nonce, recipient: h.nonce },
+			};
 			EncryptedConnection::new(h)?
 		} else {
 			panic!("Unexpected state");
 		};
 		self.state = State::Session(connection);
 		self.write_hello(io, host)?;
-		self.send_ping(io)?;
 		Ok(())
 	}
 
@@ -222,6 +260,16 @@ impl Session {
 		}
 	}
 
+	/// Check if reading was cut short by the bandwidth limits.
+	pub fn is_read_throttled(&self) -> bool {
+		self.connection().is_read_throttled()
+	}
+
+	/// Check if writing was cut short by the bandwidth limits.
+	pub fn is_write_throttled(&self) -> bool {
+		self.connection().is_write_throttled()
+	}
+
 	/// Get id of the remote peer
 	pub fn id(&self) -> Option<&NodeId> {
 		self.info.id.as_ref()
@@ -289,9 +337,10 @@ impl Session {
 	/// Writable IO handler. Sends pending packets.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>, _host: &HostInfo) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
 		match self.state {
-			State::Handshake(ref mut h) => h.writable(io),
-			State::Session(ref mut s) => s.writable(io),
+			State::Handshake(ref mut h) => return h.writable(io),
+			State::Session(ref mut s) => s.writable(io)?,
 		}
+		self.flush(io)
 	}
 
 	/// Checks if peer supports given capability
@@ -325,8 +374,9 @@ impl Session {
 		Ok(())
 	}
 
-	/// Send a protocol packet to peer.
-	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, protocol: [u8; 3], packet_id: u8, data: &[u8]) -> Result<(), NetworkError>
+	/// Queue a protocol packet to be sent to peer. Packets of higher priority overtake the ones
+	/// already queued, but a packet is never interrupted once the connection starts sending it.
+	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, protocol: [u8; 3], packet_id: u8, data: &[u8], priority: PacketPriority) -> Result<(), NetworkError>
         where Message: Send + Sync + Clone {
 		if self.info.capabilities.is_empty() || !self.had_hello {
 			debug!(target: "network", "Sending to unconfirmed session {}, protocol: {}, packet: {}", self.token(), str::from_utf8(&protocol[..]).unwrap_or("??"), packet_id);
@@ -344,10 +394,28 @@ impl Session {
 			}
 		}
 		let pid = self.info.capabilities[i].id_offset + packet_id;
-		let mut rlp = RlpStream::new();
-		rlp.append(&(pid as u32));
-		rlp.append_raw(data, 1);
-		self.send(io, rlp)
+		let packet = self.encode(pid, data)?;
+		{
+			let traffic = self.info.traffic.entry(protocol).or_insert_with(Default::default);
+			traffic.packets_sent += 1;
+			traffic.bytes_sent += data.len() as u64;
+		}
+		self.send_queue.push(priority, packet);
+		self.flush(io)
+	}
+
+	/// Hand queued packets over to the connection while it has nothing else to send.
+	/// Frames are encrypted in order, so reordering is only possible before this point.
+	fn flush<Message>(&mut self, io: &IoContext<Message>) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
+		if let State::Session(ref mut s) = self.state {
+			while !s.connection.is_sending() {
+				match self.send_queue.pop() {
+					Some(packet) => s.send_packet(io, &packet)?,
+					None => break,
+				}
+			}
+		}
+		Ok(())
 	}
 
 	/// Keep this session alive. Returns false if ping timeout happened
@@ -387,7 +455,7 @@ impl Session {
 		}
 	}
 
-	fn read_packet<Message>(&mut self, io: &IoContext<Message>, packet: Packet, host: &HostInfo) -> Result<SessionData, NetworkError>
+	fn read_packet<Message>(&mut self, io: &IoContext<Message>, mut packet: Packet, host: &HostInfo) -> Result<SessionData, NetworkError>
 	where Message: Send + Sync + Clone {
 		if packet.data.len() < 2 {
 			return Err(From::from(NetworkError::BadProtocol));
@@ -396,6 +464,14 @@ impl Session {
 		if packet_id != PACKET_HELLO && packet_id != PACKET_DISCONNECT && !self.had_hello {
 			return Err(From::from(NetworkError::BadProtocol));
 		}
+		if self.compression && packet_id != PACKET_HELLO {
+			let payload = decompress(&packet.data[1..])?;
+			if payload.is_empty() {
+				return Err(From::from(NetworkError::BadProtocol));
+			}
+			packet.data.truncate(1);
+			packet.data.extend_from_slice(&payload);
+		}
 		match packet_id {
 			PACKET_HELLO => {
 				let rlp = UntrustedRlp::new(&packet.data[1..]); //TODO: validate rlp expected size
@@ -435,6 +511,11 @@ impl Session {
 				// map to protocol
 				let protocol = self.info.capabilities[i].protocol;
 				let protocol_packet_id = packet_id - self.info.capabilities[i].id_offset;
+				{
+					let traffic = self.info.traffic.entry(protocol).or_insert_with(Default::default);
+					traffic.packets_received += 1;
+					traffic.bytes_received += (packet.data.len() - 1) as u64;
+				}
 
 				match *self.protocol_states.entry(protocol).or_insert_with(|| ProtocolState::Pending(Vec::new())) {
 					ProtocolState::Connected => {
@@ -457,15 +538,19 @@ impl Session {
 	}
 
 	fn write_hello<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
-		let mut rlp = RlpStream::new();
-		rlp.append_raw(&[PACKET_HELLO as u8], 0);
-		rlp.begin_list(5)
-			.append(&host.protocol_version)
+		let proof = self.info.id.as_ref()
+			.and_then(|id| host.reserved_key(id))
+			.map(|key| peer_keys::proof(key, &self.nonces, self.info.originated));
+		let mut rlp = RlpStream::new_list(if proof.is_some() { 6 } else { 5 });
+		rlp.append(&host.protocol_version)
 			.append(&host.client_version)
 			.append_list(&host.capabilities)
 			.append(&host.local_endpoint.address.port())
 			.append(host.id());
-		self.send(io, rlp)
+		if let Some(ref proof) = proof {
+			rlp.append(proof);
+		}
+		self.send(io, PACKET_HELLO, &rlp.drain())
 	}
 
 	fn read_hello<Message>(&mut self, io: &IoContext<Message>, rlp: &UntrustedRlp, host: &HostInfo) -> Result<(), NetworkError>
@@ -475,6 +560,20 @@ impl Session {
 		let peer_caps: Vec<PeerCapabilityInfo> = rlp.list_at(2)?;
 		let id = rlp.val_at::<NodeId>(4)?;
 
+		// reserved peers with a pre-shared key must prove they know it.
+		let key = self.info.id.as_ref().and_then(|id| host.reserved_key(id)).cloned();
+		if let Some(key) = key {
+			let expected = peer_keys::proof(&key, &self.nonces, !self.info.originated);
+			let proof = match rlp.item_count()? {
+				count if count > 5 => Some(rlp.val_at::<H256>(5)?),
+				_ => None,
+			};
+			if proof != Some(expected) {
+				debug!(target: "network", "Reserved peer {} failed pre-shared key authentication", id);
+				return Err(From::from(self.disconnect(io, DisconnectReason::UnexpectedIdentity)));
+			}
+		}
+
 		// Intersect with host capabilities
 		// Leave only highset mutually supported capability version
 		let mut caps: Vec<SessionCapabilityInfo> = Vec::new();
@@ -519,55 +618,117 @@ impl Session {
 			trace!(target: "network", "No common capabilities with peer.");
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
-		if protocol != host.protocol_version {
+		if protocol < MIN_PROTOCOL_VERSION {
 			trace!(target: "network", "Peer protocol version mismatch: {}", protocol);
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
+		// Both sides switch to compressed payloads once Hello is exchanged, so
+		// nothing but Hello may be sent before the peer's Hello is read.
+		self.compression = protocol >= MIN_COMPRESSION_PROTOCOL_VERSION && host.protocol_version >= MIN_COMPRESSION_PROTOCOL_VERSION;
+		self.send_ping(io)?;
 		self.had_hello = true;
 		Ok(())
 	}
 
 	/// Senf ping packet
 	pub fn send_ping<Message>(&mut self, io: &IoContext<Message>) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
-		self.send(io, Session::prepare(PACKET_PING)?)?;
+		self.send(io, PACKET_PING, &EMPTY_LIST_RLP)?;
 		self.ping_time_ns = time::precise_time_ns();
 		self.pong_time_ns = None;
 		Ok(())
 	}
 
 	fn send_pong<Message>(&mut self, io: &IoContext<Message>) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
-		self.send(io, Session::prepare(PACKET_PONG)?)
+		self.send(io, PACKET_PONG, &EMPTY_LIST_RLP)
 	}
 
 	/// Disconnect this session
 	pub fn disconnect<Message>(&mut self, io: &IoContext<Message>, reason: DisconnectReason) -> NetworkError where Message: Send + Sync + Clone {
 		if let State::Session(_) = self.state {
-			let mut rlp = RlpStream::new();
-			rlp.append(&(PACKET_DISCONNECT as u32));
-			rlp.begin_list(1);
+			self.send_queue.clear();
+			let mut rlp = RlpStream::new_list(1);
 			rlp.append(&(reason as u32));
-			self.send(io, rlp).ok();
+			self.send(io, PACKET_DISCONNECT, &rlp.drain()).ok();
 		}
 		NetworkError::Disconnect(reason)
 	}
 
-	fn prepare(packet_id: u8) -> Result<RlpStream, NetworkError> {
-		let mut rlp = RlpStream::new();
-		rlp.append(&(packet_id as u32));
-		rlp.begin_list(0);
-		Ok(rlp)
-	}
-
-	fn send<Message>(&mut self, io: &IoContext<Message>, rlp: RlpStream) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
+	/// Send a control packet right away, ahead of any queued protocol packets.
+	fn send<Message>(&mut self, io: &IoContext<Message>, packet_id: u8, payload: &[u8]) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
+		let packet = self.encode(packet_id, payload)?;
 		match self.state {
 			State::Handshake(_) => {
 				warn!(target:"network", "Unexpected send request");
 			},
 			State::Session(ref mut s) => {
-				s.send_packet(io, &rlp.out())?
+				s.send_packet(io, &packet)?
 			},
 		}
 		Ok(())
 	}
+
+	fn encode(&self, packet_id: u8, payload: &[u8]) -> Result<Vec<u8>, NetworkError> {
+		if payload.len() > MAX_PAYLOAD_SIZE {
+			return Err(NetworkError::OversizedPacket);
+		}
+		let mut rlp = RlpStream::new();
+		if packet_id == PACKET_HELLO {
+			// Hello is never compressed; its id is the RLP encoding of 0.
+			rlp.append_raw(&[PACKET_HELLO], 0);
+			rlp.append_raw(payload, 1);
+		} else {
+			rlp.append(&(packet_id as u32));
+			if self.compression {
+				rlp.append_raw(&snappy::compress(payload), 1);
+			} else {
+				rlp.append_raw(payload, 1);
+			}
+		}
+		Ok(rlp.out())
+	}
+}
+
+/// Decompress a packet payload, refusing ones that would expand beyond `MAX_PAYLOAD_SIZE`.
+fn decompress(compressed: &[u8]) -> Result<Vec<u8>, NetworkError> {
+	match snappy::decompressed_len(compressed) {
+		Ok(len) if len > MAX_PAYLOAD_SIZE => {
+			debug!(target: "network", "Compressed packet expands to {} bytes, dropping", len);
+			Err(NetworkError::OversizedPacket)
+		},
+		Ok(_) => snappy::decompress(compressed).map_err(|_| NetworkError::BadProtocol),
+		Err(_) => Err(NetworkError::BadProtocol),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::snappy;
+	use error::NetworkError;
+	use super::{decompress, MAX_PAYLOAD_SIZE};
+
+	#[test]
+	fn decompress_roundtrip() {
+		let payload = vec![0xc2, 0x01, 0x02];
+		assert_eq!(decompress(&snappy::compress(&payload)).unwrap(), payload);
+	}
+
+	#[test]
+	fn decompress_rejects_invalid_input() {
+		match decompress(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff]) {
+			Err(NetworkError::BadProtocol) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn decompress_rejects_oversized_payload() {
+		let payload = vec![0u8; MAX_PAYLOAD_SIZE + 1];
+		let compressed = snappy::compress(&payload);
+		assert!(compressed.len() < 1 << 24);
+		match decompress(&compressed) {
+			Err(NetworkError::OversizedPacket) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
 }
 
@@ -26,6 +26,10 @@ pub struct NetworkStats {
 	send: AtomicUsize,
 	/// Total number of sessions created
 	sessions: AtomicUsize,
+	/// Connections rejected for too many peers sharing the IP address
+	ip_limit_rejections: AtomicUsize,
+	/// Connections rejected for too many peers sharing the subnet
+	subnet_limit_rejections: AtomicUsize,
 }
 
 impl NetworkStats {
@@ -47,6 +51,18 @@ impl NetworkStats {
 		self.sessions.fetch_add(1, Ordering::Relaxed);
 	}
 
+	/// Increase number of connections rejected by the per-IP limit.
+	#[inline]
+	pub fn inc_ip_limit_rejections(&self) {
+		self.ip_limit_rejections.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Increase number of connections rejected by the per-subnet limit.
+	#[inline]
+	pub fn inc_subnet_limit_rejections(&self) {
+		self.subnet_limit_rejections.fetch_add(1, Ordering::Relaxed);
+	}
+
 	/// Get bytes sent.
 	#[inline]
 	pub fn send(&self) -> usize {
@@ -65,12 +81,26 @@ impl NetworkStats {
 		self.sessions.load(Ordering::Relaxed)
 	}
 
+	/// Get number of connections rejected by the per-IP limit.
+	#[inline]
+	pub fn ip_limit_rejections(&self) -> usize {
+		self.ip_limit_rejections.load(Ordering::Relaxed)
+	}
+
+	/// Get number of connections rejected by the per-subnet limit.
+	#[inline]
+	pub fn subnet_limit_rejections(&self) -> usize {
+		self.subnet_limit_rejections.load(Ordering::Relaxed)
+	}
+
 	/// Create a new empty instance.
 	pub fn new() -> NetworkStats {
 		NetworkStats {
 			recv: AtomicUsize::new(0),
 			send: AtomicUsize::new(0),
 			sessions: AtomicUsize::new(0),
+			ip_limit_rejections: AtomicUsize::new(0),
+			subnet_limit_rejections: AtomicUsize::new(0),
 		}
 	}
 }
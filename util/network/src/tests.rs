@@ -125,8 +125,11 @@ fn net_connect() {
 fn net_start_stop() {
 	let config = NetworkConfiguration::new_local();
 	let service = NetworkService::new(config, None).unwrap();
+	assert!(!service.is_started());
 	service.start().unwrap();
+	assert!(service.is_started());
 	service.stop().unwrap();
+	assert!(!service.is_started());
 	service.start().unwrap();
 }
 
@@ -16,7 +16,7 @@
 
 //! Disk-backed `HashDB` implementation.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::Entry;
 use std::sync::Arc;
 use rlp::*;
@@ -36,11 +36,62 @@ use {Bytes};
 /// write operations out to disk. Unlike `OverlayDB`, `remove()` operations do not take effect
 /// immediately. As this is an "archive" database, nothing is ever removed. This means
 /// that the states of any block the node has ever processed will be accessible.
+///
+/// Optionally nodes last inserted more than a given number of eras ago are moved to a secondary
+/// "cold" database. Lookups fall through to it, so old states remain accessible.
+/// Nodes inserted again (e.g. when a later state contains them again) are kept in the hot database
+/// until they are old again.
 pub struct ArchiveDB {
 	overlay: MemoryDB,
 	backing: Arc<KeyValueDB>,
 	latest_era: Option<u64>,
 	column: Option<u32>,
+	cold: Option<ColdStorage>,
+}
+
+/// Secondary database for ancient nodes.
+#[derive(Clone)]
+struct ColdStorage {
+	db: Arc<KeyValueDB>,
+	after: u64,
+}
+
+// journal of nodes inserted at given era, to be moved to cold storage.
+// keys are `COLD_JOURNAL_PREFIX ++ era (big endian) ++ id`.
+const COLD_JOURNAL_PREFIX: &'static [u8] = b"cold";
+const COLD_JOURNAL_KEY_LEN: usize = 4 + 8 + 32;
+
+fn cold_journal_key(era: u64, id: &H256) -> Vec<u8> {
+	let mut key = Vec::with_capacity(COLD_JOURNAL_KEY_LEN);
+	key.extend_from_slice(COLD_JOURNAL_PREFIX);
+	for i in (0..8).rev() {
+		key.push((era >> (i * 8)) as u8);
+	}
+	key.extend_from_slice(&**id);
+	key
+}
+
+fn cold_journal_era(key: &[u8]) -> Option<u64> {
+	if key.len() != COLD_JOURNAL_KEY_LEN || !key.starts_with(COLD_JOURNAL_PREFIX) {
+		return None;
+	}
+	Some(key[4..12].iter().fold(0u64, |era, b| (era << 8) | *b as u64))
+}
+
+// era each node was last inserted at, so that re-inserted nodes aren't moved with the old era.
+// keys are `COLD_ERA_PREFIX ++ node`.
+const COLD_ERA_PREFIX: &'static [u8] = b"cera";
+const COLD_ERA_KEY_LEN: usize = 4 + 32;
+
+fn cold_era_key(node: &H256) -> Vec<u8> {
+	let mut key = Vec::with_capacity(COLD_ERA_KEY_LEN);
+	key.extend_from_slice(COLD_ERA_PREFIX);
+	key.extend_from_slice(&**node);
+	key
+}
+
+fn is_cold_metadata(key: &[u8]) -> bool {
+	cold_journal_era(key).is_some() || (key.len() == COLD_ERA_KEY_LEN && key.starts_with(COLD_ERA_PREFIX))
 }
 
 impl ArchiveDB {
@@ -52,9 +103,65 @@ impl ArchiveDB {
 			backing: backing,
 			latest_era: latest_era,
 			column: col,
+			cold: None,
 		}
 	}
 
+	/// Create a new instance moving nodes older than `after` eras to the default column of `cold`.
+	pub fn with_cold(backing: Arc<KeyValueDB>, col: Option<u32>, cold: Arc<KeyValueDB>, after: u64) -> ArchiveDB {
+		let mut db = Self::new(backing, col);
+		db.cold = Some(ColdStorage {
+			db: cold,
+			after: after,
+		});
+		db
+	}
+
+	/// Move nodes last inserted before `era - after` to the cold database.
+	/// `inserted` are the nodes inserted at `era`, which stay in the hot one.
+	fn move_to_cold(&self, batch: &mut DBTransaction, era: u64, inserted: &HashSet<H256>) -> Result<(), UtilError> {
+		let cold = match self.cold {
+			Some(ref cold) => cold,
+			None => return Ok(()),
+		};
+
+		let mut cold_batch = DBTransaction::new();
+		let mut moved = 0usize;
+		for (key, value) in self.backing.iter_from_prefix(self.column, COLD_JOURNAL_PREFIX) {
+			if !key.starts_with(COLD_JOURNAL_PREFIX) {
+				break;
+			}
+			let journal_era = match cold_journal_era(&key) {
+				Some(e) if e + cold.after < era => e,
+				Some(_) => break,
+				None => continue,
+			};
+
+			for node in UntrustedRlp::new(&value).iter() {
+				let node: H256 = node.as_val()?;
+				if inserted.contains(&node) {
+					continue;
+				}
+				let last_inserted = self.backing.get(self.column, &cold_era_key(&node))?.map(|era| decode::<u64>(&era));
+				if last_inserted.map_or(false, |last| last > journal_era) {
+					continue;
+				}
+				batch.delete(self.column, &cold_era_key(&node));
+				if let Some(value) = self.backing.get(self.column, &node)? {
+					cold_batch.put(None, &node, &value);
+					batch.delete(self.column, &node);
+					moved += 1;
+				}
+			}
+			batch.delete(self.column, &key);
+		}
+
+		// nodes are written to the cold database before they are removed from the hot one.
+		cold.db.write(cold_batch)?;
+		trace!(target: "journaldb", "Moved {} nodes to cold storage at era {}", moved, era);
+		Ok(())
+	}
+
 	/// Create a new instance with an anonymous temporary database.
 	#[cfg(test)]
 	fn new_temp() -> ArchiveDB {
@@ -63,16 +170,26 @@ impl ArchiveDB {
 	}
 
 	fn payload(&self, key: &H256) -> Option<DBValue> {
-		self.backing.get(self.column, key).expect("Low-level database error. Some issue with your hard disk?")
+		match self.backing.get(self.column, key).expect("Low-level database error. Some issue with your hard disk?") {
+			Some(value) => Some(value),
+			None => self.cold.as_ref().and_then(|cold|
+				cold.db.get(None, key).expect("Low-level database error. Some issue with your hard disk?")
+			),
+		}
 	}
 }
 
 impl HashDB for ArchiveDB {
 	fn keys(&self) -> HashMap<H256, i32> {
 		let mut ret: HashMap<H256, i32> = self.backing.iter(self.column)
+			.filter(|&(ref key, _)| !is_cold_metadata(key))
 			.map(|(key, _)| (H256::from_slice(&*key), 1))
 			.collect();
 
+		if let Some(ref cold) = self.cold {
+			ret.extend(cold.db.iter(None).map(|(key, _)| (H256::from_slice(&*key), 1)));
+		}
+
 		for (key, refs) in self.overlay.keys() {
 			match ret.entry(key) {
 				Entry::Occupied(mut entry) => {
@@ -119,6 +236,7 @@ impl JournalDB for ArchiveDB {
 			backing: self.backing.clone(),
 			latest_era: self.latest_era,
 			column: self.column.clone(),
+			cold: self.cold.clone(),
 		})
 	}
 
@@ -130,14 +248,16 @@ impl JournalDB for ArchiveDB {
 		self.latest_era.is_none()
 	}
 
-	fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, _id: &H256) -> Result<u32, UtilError> {
+	fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, id: &H256) -> Result<u32, UtilError> {
 		let mut inserts = 0usize;
 		let mut deletes = 0usize;
+		let mut inserted = Vec::new();
 
 		for i in self.overlay.drain() {
 			let (key, (value, rc)) = i;
 			if rc > 0 {
 				batch.put(self.column, &key, &value);
+				inserted.push(key);
 				inserts += 1;
 			}
 			if rc < 0 {
@@ -146,6 +266,16 @@ impl JournalDB for ArchiveDB {
 			}
 		}
 
+		if self.cold.is_some() {
+			if !inserted.is_empty() {
+				batch.put(self.column, &cold_journal_key(now, id), &encode_list(&inserted));
+			}
+			for node in &inserted {
+				batch.put(self.column, &cold_era_key(node), &encode(&now));
+			}
+			self.move_to_cold(batch, now, &inserted.into_iter().collect())?;
+		}
+
 		if self.latest_era.map_or(true, |e| now > e) {
 			batch.put(self.column, &LATEST_ERA_KEY, &encode(&now));
 			self.latest_era = Some(now);
@@ -484,4 +614,60 @@ mod tests {
 
 		assert!(jdb.get(&key).is_none());
 	}
+
+	#[test]
+	fn moves_old_nodes_to_cold_storage() {
+		let hot = Arc::new(::kvdb::in_memory(0));
+		let cold = Arc::new(::kvdb::in_memory(0));
+		let mut jdb = ArchiveDB::with_cold(hot.clone(), None, cold.clone(), 2);
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		jdb.commit_batch(1, &keccak(b"1"), None).unwrap();
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(2, &keccak(b"2"), None).unwrap();
+		assert!(cold.get(None, &foo).unwrap().is_none());
+
+		jdb.commit_batch(3, &keccak(b"3"), None).unwrap();
+		assert!(hot.get(None, &foo).unwrap().is_none());
+		assert!(cold.get(None, &foo).unwrap().is_some());
+		assert!(hot.get(None, &bar).unwrap().is_some());
+		assert!(jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+
+		let keys = jdb.keys();
+		assert!(keys.contains_key(&foo));
+		assert!(keys.contains_key(&bar));
+		assert!(!keys.keys().any(|key| key[..4] == COLD_ERA_PREFIX[..]));
+	}
+
+	#[test]
+	fn keeps_reinserted_nodes_in_hot_storage() {
+		let hot = Arc::new(::kvdb::in_memory(0));
+		let cold = Arc::new(::kvdb::in_memory(0));
+		let mut jdb = ArchiveDB::with_cold(hot.clone(), None, cold.clone(), 2);
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		jdb.commit_batch(1, &keccak(b"1"), None).unwrap();
+		jdb.insert(b"foo");
+		jdb.commit_batch(2, &keccak(b"2"), None).unwrap();
+
+		// inserted at era 0, but again at era 2.
+		jdb.commit_batch(3, &keccak(b"3"), None).unwrap();
+		assert!(hot.get(None, &foo).unwrap().is_some());
+		assert!(cold.get(None, &foo).unwrap().is_none());
+
+		// old again.
+		jdb.commit_batch(4, &keccak(b"4"), None).unwrap();
+		jdb.commit_batch(5, &keccak(b"5"), None).unwrap();
+		assert!(hot.get(None, &foo).unwrap().is_none());
+		assert!(cold.get(None, &foo).unwrap().is_some());
+		assert!(jdb.contains(&foo));
+
+		// re-inserted while cold.
+		jdb.insert(b"foo");
+		jdb.commit_batch(6, &keccak(b"6"), None).unwrap();
+		assert!(hot.get(None, &foo).unwrap().is_some());
+	}
 }
@@ -125,6 +125,12 @@ pub fn new(backing: Arc<::kvdb::KeyValueDB>, algorithm: Algorithm, col: Option<u
 	}
 }
 
+/// Create a new archive `JournalDB` which moves nodes older than `cold_after` eras from `backing`
+/// to the default column of `cold`.
+pub fn new_tiered(backing: Arc<::kvdb::KeyValueDB>, cold: Arc<::kvdb::KeyValueDB>, cold_after: u64, col: Option<u32>) -> Box<JournalDB> {
+	Box::new(archivedb::ArchiveDB::with_cold(backing, col, cold, cold_after))
+}
+
 // all keys must be at least 12 bytes
 const DB_PREFIX_LEN : usize = ::kvdb::PREFIX_LEN;
 const LATEST_ERA_KEY : [u8; ::kvdb::PREFIX_LEN] = [ b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0 ];
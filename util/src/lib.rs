@@ -133,7 +133,7 @@ pub use hashdb::*;
 pub use memorydb::MemoryDB;
 pub use overlaydb::*;
 pub use journaldb::JournalDB;
-pub use trie::{Trie, TrieMut, TrieDB, TrieDBMut, TrieFactory, TrieError, SecTrieDB, SecTrieDBMut};
+pub use trie::{Trie, TrieMut, TrieDB, TrieDBMut, TrieFactory, TrieHasher, TrieError, SecTrieDB, SecTrieDBMut};
 pub use kvdb::*;
 pub use error::UtilError;
 pub use bytes::*;
@@ -15,16 +15,18 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use bigint::hash::H256;
-use keccak::keccak;
+use std::marker::PhantomData;
 use hashdb::HashDB;
+use super::hasher::{Hasher, KeccakHasher};
 use super::{TrieDB, Trie, TrieDBIterator, TrieItem, TrieIterator, Query};
 
 /// A `Trie` implementation which hashes keys and uses a generic `HashDB` backing database.
 /// Additionaly it stores inserted hash-key mappings for later retrieval.
 ///
 /// Use it as a `Trie` or `TrieMut` trait object.
-pub struct FatDB<'db> {
+pub struct FatDB<'db, H: Hasher = KeccakHasher> {
 	raw: TrieDB<'db>,
+	_hasher: PhantomData<H>,
 }
 
 impl<'db> FatDB<'db> {
@@ -32,8 +34,16 @@ impl<'db> FatDB<'db> {
 	/// Initialise to the state entailed by the genesis block.
 	/// This guarantees the trie is built correctly.
 	pub fn new(db: &'db HashDB, root: &'db H256) -> super::Result<Self> {
+		FatDB::with_hasher(db, root)
+	}
+}
+
+impl<'db, H: Hasher> FatDB<'db, H> {
+	/// Create a new trie with the backing database `db` and `root`, hashing keys with `H`.
+	pub fn with_hasher(db: &'db HashDB, root: &'db H256) -> super::Result<Self> {
 		let fatdb = FatDB {
-			raw: TrieDB::new(db, root)?
+			raw: TrieDB::new(db, root)?,
+			_hasher: PhantomData,
 		};
 
 		Ok(fatdb)
@@ -45,9 +55,9 @@ impl<'db> FatDB<'db> {
 	}
 }
 
-impl<'db> Trie for FatDB<'db> {
+impl<'db, H: Hasher> Trie for FatDB<'db, H> {
 	fn iter<'a>(&'a self) -> super::Result<Box<TrieIterator<Item = TrieItem> + 'a>> {
-		FatDBIterator::new(&self.raw).map(|iter| Box::new(iter) as Box<_>)
+		FatDBIterator::<H>::with_hasher(&self.raw).map(|iter| Box::new(iter) as Box<_>)
 	}
 
 	fn root(&self) -> &H256 {
@@ -55,46 +65,55 @@ impl<'db> Trie for FatDB<'db> {
 	}
 
 	fn contains(&self, key: &[u8]) -> super::Result<bool> {
-		self.raw.contains(&keccak(key))
+		self.raw.contains(&H::hash(key))
 	}
 
 	fn get_with<'a, 'key, Q: Query>(&'a self, key: &'key [u8], query: Q) -> super::Result<Option<Q::Item>>
 		where 'a: 'key
 	{
-		self.raw.get_with(&keccak(key), query)
+		self.raw.get_with(&H::hash(key), query)
 	}
 }
 
 /// Itarator over inserted pairs of key values.
-pub struct FatDBIterator<'db> {
+pub struct FatDBIterator<'db, H: Hasher = KeccakHasher> {
 	trie_iterator: TrieDBIterator<'db>,
 	trie: &'db TrieDB<'db>,
+	_hasher: PhantomData<H>,
 }
 
 impl<'db> FatDBIterator<'db> {
 	/// Creates new iterator.
 	pub fn new(trie: &'db TrieDB) -> super::Result<Self> {
+		FatDBIterator::with_hasher(trie)
+	}
+}
+
+impl<'db, H: Hasher> FatDBIterator<'db, H> {
+	/// Creates new iterator over a trie keyed with `H`.
+	pub fn with_hasher(trie: &'db TrieDB) -> super::Result<Self> {
 		Ok(FatDBIterator {
 			trie_iterator: TrieDBIterator::new(trie)?,
 			trie: trie,
+			_hasher: PhantomData,
 		})
 	}
 }
 
-impl<'db> TrieIterator for FatDBIterator<'db> {
+impl<'db, H: Hasher> TrieIterator for FatDBIterator<'db, H> {
 	fn seek(&mut self, key: &[u8]) -> super::Result<()> {
-		self.trie_iterator.seek(&keccak(key))
+		self.trie_iterator.seek(&H::hash(key))
 	}
 }
 
-impl<'db> Iterator for FatDBIterator<'db> {
+impl<'db, H: Hasher> Iterator for FatDBIterator<'db, H> {
 	type Item = TrieItem<'db>;
 
 	fn next(&mut self) -> Option<Self::Item> {
 		self.trie_iterator.next()
 			.map(|res|
 				res.map(|(hash, value)| {
-					let aux_hash = keccak(hash);
+					let aux_hash = H::hash(&hash);
 					(self.trie.db().get(&aux_hash).expect("Missing fatdb hash").into_vec(), value)
 				})
 			)
@@ -15,16 +15,16 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use bigint::hash::H256;
-use keccak::keccak;
 use hashdb::{HashDB, DBValue};
+use super::hasher::{Hasher, KeccakHasher};
 use super::{TrieDBMut, TrieMut};
 
 /// A mutable `Trie` implementation which hashes keys and uses a generic `HashDB` backing database.
 /// Additionaly it stores inserted hash-key mappings for later retrieval.
 ///
 /// Use it as a `Trie` or `TrieMut` trait object.
-pub struct FatDBMut<'db> {
-	raw: TrieDBMut<'db>,
+pub struct FatDBMut<'db, H: Hasher = KeccakHasher> {
+	raw: TrieDBMut<'db, H>,
 }
 
 impl<'db> FatDBMut<'db> {
@@ -32,14 +32,28 @@ impl<'db> FatDBMut<'db> {
 	/// Initialise to the state entailed by the genesis block.
 	/// This guarantees the trie is built correctly.
 	pub fn new(db: &'db mut HashDB, root: &'db mut H256) -> Self {
-		FatDBMut { raw: TrieDBMut::new(db, root) }
+		FatDBMut::with_hasher(db, root)
 	}
 
 	/// Create a new trie with the backing database `db` and `root`.
 	///
 	/// Returns an error if root does not exist.
 	pub fn from_existing(db: &'db mut HashDB, root: &'db mut H256) -> super::Result<Self> {
-		Ok(FatDBMut { raw: TrieDBMut::from_existing(db, root)? })
+		FatDBMut::existing_with_hasher(db, root)
+	}
+}
+
+impl<'db, H: Hasher> FatDBMut<'db, H> {
+	/// Create a new trie with the backing database `db` and empty `root`, hashing with `H`.
+	pub fn with_hasher(db: &'db mut HashDB, root: &'db mut H256) -> Self {
+		FatDBMut { raw: TrieDBMut::with_hasher(db, root) }
+	}
+
+	/// Create a new trie with the backing database `db` and `root`, hashing with `H`.
+	///
+	/// Returns an error if root does not exist.
+	pub fn existing_with_hasher(db: &'db mut HashDB, root: &'db mut H256) -> super::Result<Self> {
+		Ok(FatDBMut { raw: TrieDBMut::existing_with_hasher(db, root)? })
 	}
 
 	/// Get the backing database.
@@ -53,11 +67,11 @@ impl<'db> FatDBMut<'db> {
 	}
 
 	fn to_aux_key(key: &[u8]) -> H256 {
-		keccak(key)
+		H::hash(key)
 	}
 }
 
-impl<'db> TrieMut for FatDBMut<'db> {
+impl<'db, H: Hasher> TrieMut for FatDBMut<'db, H> {
 	fn root(&mut self) -> &H256 {
 		self.raw.root()
 	}
@@ -67,17 +81,17 @@ impl<'db> TrieMut for FatDBMut<'db> {
 	}
 
 	fn contains(&self, key: &[u8]) -> super::Result<bool> {
-		self.raw.contains(&keccak(key))
+		self.raw.contains(&H::hash(key))
 	}
 
 	fn get<'a, 'key>(&'a self, key: &'key [u8]) -> super::Result<Option<DBValue>>
 		where 'a: 'key
 	{
-		self.raw.get(&keccak(key))
+		self.raw.get(&H::hash(key))
 	}
 
 	fn insert(&mut self, key: &[u8], value: &[u8]) -> super::Result<Option<DBValue>> {
-		let hash = keccak(key);
+		let hash = H::hash(key);
 		let out = self.raw.insert(&hash, value)?;
 		let db = self.raw.db_mut();
 
@@ -89,7 +103,7 @@ impl<'db> TrieMut for FatDBMut<'db> {
 	}
 
 	fn remove(&mut self, key: &[u8]) -> super::Result<Option<DBValue>> {
-		let hash = keccak(key);
+		let hash = H::hash(key);
 		let out = self.raw.remove(&hash)?;
 
 		// don't remove if it already exists.
@@ -104,6 +118,7 @@ impl<'db> TrieMut for FatDBMut<'db> {
 #[test]
 fn fatdb_to_trie() {
 	use memorydb::MemoryDB;
+	use keccak::keccak;
 	use super::TrieDB;
 	use super::Trie;
 
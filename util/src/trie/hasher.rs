@@ -0,0 +1,141 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hash functions used to key trie nodes.
+
+use std::fmt;
+use std::str::FromStr;
+use bigint::hash::H256;
+use keccak::keccak;
+
+/// Hash function used to derive the database keys of trie nodes and secure trie keys.
+///
+/// The root of an empty trie is always `KECCAK_NULL_RLP`, regardless of the hasher.
+pub trait Hasher: Send + Sync + 'static {
+	/// Hash given data.
+	fn hash(data: &[u8]) -> H256;
+}
+
+/// Keccak-256 hasher used by Ethereum.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct KeccakHasher;
+
+impl Hasher for KeccakHasher {
+	fn hash(data: &[u8]) -> H256 {
+		keccak(data)
+	}
+}
+
+/// FIPS-202 SHA3-256 hasher.
+#[cfg(feature = "sha3-trie")]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Sha3Hasher;
+
+#[cfg(feature = "sha3-trie")]
+impl Hasher for Sha3Hasher {
+	fn hash(data: &[u8]) -> H256 {
+		H256(::tiny_keccak::sha3_256(data))
+	}
+}
+
+/// Trie hasher selected at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrieHasher {
+	/// Keccak-256.
+	Keccak,
+	/// SHA3-256.
+	#[cfg(feature = "sha3-trie")]
+	Sha3,
+}
+
+impl Default for TrieHasher {
+	fn default() -> Self {
+		TrieHasher::Keccak
+	}
+}
+
+impl TrieHasher {
+	/// Hash given data with the selected function.
+	pub fn hash(&self, data: &[u8]) -> H256 {
+		match *self {
+			TrieHasher::Keccak => KeccakHasher::hash(data),
+			#[cfg(feature = "sha3-trie")]
+			TrieHasher::Sha3 => Sha3Hasher::hash(data),
+		}
+	}
+}
+
+impl FromStr for TrieHasher {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"keccak" => Ok(TrieHasher::Keccak),
+			#[cfg(feature = "sha3-trie")]
+			"sha3" => Ok(TrieHasher::Sha3),
+			#[cfg(not(feature = "sha3-trie"))]
+			"sha3" => Err("SHA3 trie hasher requires Parity to be built with the `sha3-trie` feature".into()),
+			other => Err(format!("Unsupported trie hasher: {}", other)),
+		}
+	}
+}
+
+impl fmt::Display for TrieHasher {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			TrieHasher::Keccak => write!(f, "keccak"),
+			#[cfg(feature = "sha3-trie")]
+			TrieHasher::Sha3 => write!(f, "sha3"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::TrieHasher;
+
+	#[test]
+	fn should_parse_trie_hasher() {
+		assert_eq!("keccak".parse::<TrieHasher>(), Ok(TrieHasher::Keccak));
+		assert!("blake2".parse::<TrieHasher>().is_err());
+		assert_eq!(TrieHasher::default().to_string(), "keccak");
+	}
+
+	#[cfg(feature = "sha3-trie")]
+	#[test]
+	fn sha3_trie_has_distinct_root() {
+		use bigint::hash::H256;
+		use keccak::KECCAK_NULL_RLP;
+		use memorydb::MemoryDB;
+		use hashdb::DBValue;
+		use trie::{Trie, TrieMut, TrieFactory, TrieSpec};
+
+		let factory = TrieFactory::new(TrieSpec::Secure).with_hasher(TrieHasher::Sha3);
+		let (mut keccak_db, mut keccak_root) = (MemoryDB::new(), H256::default());
+		let (mut sha3_db, mut sha3_root) = (MemoryDB::new(), H256::default());
+		TrieFactory::new(TrieSpec::Secure).create(&mut keccak_db, &mut keccak_root).insert(b"foo", b"bar").unwrap();
+		{
+			let mut t = factory.create(&mut sha3_db, &mut sha3_root);
+			assert!(t.is_empty());
+			assert_eq!(t.root(), &KECCAK_NULL_RLP);
+			t.insert(b"foo", b"bar").unwrap();
+		}
+
+		assert!(sha3_root != keccak_root);
+		let t = factory.readonly(&sha3_db, &sha3_root).unwrap();
+		assert_eq!(t.get(b"foo").unwrap(), Some(DBValue::from_slice(b"bar")));
+	}
+}
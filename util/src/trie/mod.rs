@@ -35,6 +35,8 @@ pub mod sectriedb;
 pub mod sectriedbmut;
 /// Trie query recording.
 pub mod recorder;
+/// Trie node hashers.
+pub mod hasher;
 
 
 mod fatdb;
@@ -49,6 +51,9 @@ pub use self::sectriedb::SecTrieDB;
 pub use self::fatdb::{FatDB, FatDBIterator};
 pub use self::fatdbmut::FatDBMut;
 pub use self::recorder::Recorder;
+pub use self::hasher::{Hasher, KeccakHasher, TrieHasher};
+#[cfg(feature = "sha3-trie")]
+pub use self::hasher::Sha3Hasher;
 
 /// Trie Errors.
 ///
@@ -197,6 +202,7 @@ impl Default for TrieSpec {
 #[derive(Default, Clone)]
 pub struct TrieFactory {
 	spec: TrieSpec,
+	hasher: TrieHasher,
 }
 
 /// All different kinds of tries.
@@ -208,6 +214,12 @@ pub enum TrieKinds<'db> {
 	Secure(SecTrieDB<'db>),
 	/// A fat trie db.
 	Fat(FatDB<'db>),
+	/// A secure trie db keyed with SHA3-256.
+	#[cfg(feature = "sha3-trie")]
+	Sha3Secure(SecTrieDB<'db, Sha3Hasher>),
+	/// A fat trie db keyed with SHA3-256.
+	#[cfg(feature = "sha3-trie")]
+	Sha3Fat(FatDB<'db, Sha3Hasher>),
 }
 
 // wrapper macro for making the match easier to deal with.
@@ -217,6 +229,10 @@ macro_rules! wrapper {
 			TrieKinds::Generic(ref t) => t.$f_name($($param),*),
 			TrieKinds::Secure(ref t) => t.$f_name($($param),*),
 			TrieKinds::Fat(ref t) => t.$f_name($($param),*),
+			#[cfg(feature = "sha3-trie")]
+			TrieKinds::Sha3Secure(ref t) => t.$f_name($($param),*),
+			#[cfg(feature = "sha3-trie")]
+			TrieKinds::Sha3Fat(ref t) => t.$f_name($($param),*),
 		}
 	}
 }
@@ -251,33 +267,61 @@ impl TrieFactory {
 	pub fn new(spec: TrieSpec) -> Self {
 		TrieFactory {
 			spec: spec,
+			hasher: TrieHasher::default(),
 		}
 	}
 
+	/// Use given hasher for trie nodes and secure keys.
+	pub fn with_hasher(mut self, hasher: TrieHasher) -> Self {
+		self.hasher = hasher;
+		self
+	}
+
+	/// Hasher used by the created tries.
+	pub fn hasher(&self) -> TrieHasher {
+		self.hasher
+	}
+
 	/// Create new immutable instance of Trie.
 	pub fn readonly<'db>(&self, db: &'db HashDB, root: &'db H256) -> Result<TrieKinds<'db>> {
-		match self.spec {
-			TrieSpec::Generic => Ok(TrieKinds::Generic(TrieDB::new(db, root)?)),
-			TrieSpec::Secure => Ok(TrieKinds::Secure(SecTrieDB::new(db, root)?)),
-			TrieSpec::Fat => Ok(TrieKinds::Fat(FatDB::new(db, root)?)),
+		match (self.spec.clone(), self.hasher) {
+			(TrieSpec::Generic, _) => Ok(TrieKinds::Generic(TrieDB::new(db, root)?)),
+			(TrieSpec::Secure, TrieHasher::Keccak) => Ok(TrieKinds::Secure(SecTrieDB::new(db, root)?)),
+			(TrieSpec::Fat, TrieHasher::Keccak) => Ok(TrieKinds::Fat(FatDB::new(db, root)?)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Secure, TrieHasher::Sha3) => Ok(TrieKinds::Sha3Secure(SecTrieDB::with_hasher(db, root)?)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Fat, TrieHasher::Sha3) => Ok(TrieKinds::Sha3Fat(FatDB::with_hasher(db, root)?)),
 		}
 	}
 
 	/// Create new mutable instance of Trie.
 	pub fn create<'db>(&self, db: &'db mut HashDB, root: &'db mut H256) -> Box<TrieMut + 'db> {
-		match self.spec {
-			TrieSpec::Generic => Box::new(TrieDBMut::new(db, root)),
-			TrieSpec::Secure => Box::new(SecTrieDBMut::new(db, root)),
-			TrieSpec::Fat => Box::new(FatDBMut::new(db, root)),
+		match (self.spec.clone(), self.hasher) {
+			(TrieSpec::Generic, TrieHasher::Keccak) => Box::new(TrieDBMut::new(db, root)),
+			(TrieSpec::Secure, TrieHasher::Keccak) => Box::new(SecTrieDBMut::new(db, root)),
+			(TrieSpec::Fat, TrieHasher::Keccak) => Box::new(FatDBMut::new(db, root)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Generic, TrieHasher::Sha3) => Box::new(TrieDBMut::<Sha3Hasher>::with_hasher(db, root)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Secure, TrieHasher::Sha3) => Box::new(SecTrieDBMut::<Sha3Hasher>::with_hasher(db, root)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Fat, TrieHasher::Sha3) => Box::new(FatDBMut::<Sha3Hasher>::with_hasher(db, root)),
 		}
 	}
 
 	/// Create new mutable instance of trie and check for errors.
 	pub fn from_existing<'db>(&self, db: &'db mut HashDB, root: &'db mut H256) -> Result<Box<TrieMut + 'db>> {
-		match self.spec {
-			TrieSpec::Generic => Ok(Box::new(TrieDBMut::from_existing(db, root)?)),
-			TrieSpec::Secure => Ok(Box::new(SecTrieDBMut::from_existing(db, root)?)),
-			TrieSpec::Fat => Ok(Box::new(FatDBMut::from_existing(db, root)?)),
+		match (self.spec.clone(), self.hasher) {
+			(TrieSpec::Generic, TrieHasher::Keccak) => Ok(Box::new(TrieDBMut::from_existing(db, root)?)),
+			(TrieSpec::Secure, TrieHasher::Keccak) => Ok(Box::new(SecTrieDBMut::from_existing(db, root)?)),
+			(TrieSpec::Fat, TrieHasher::Keccak) => Ok(Box::new(FatDBMut::from_existing(db, root)?)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Generic, TrieHasher::Sha3) => Ok(Box::new(TrieDBMut::<Sha3Hasher>::existing_with_hasher(db, root)?)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Secure, TrieHasher::Sha3) => Ok(Box::new(SecTrieDBMut::<Sha3Hasher>::existing_with_hasher(db, root)?)),
+			#[cfg(feature = "sha3-trie")]
+			(TrieSpec::Fat, TrieHasher::Sha3) => Ok(Box::new(FatDBMut::<Sha3Hasher>::existing_with_hasher(db, root)?)),
 		}
 	}
 
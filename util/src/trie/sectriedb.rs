@@ -15,16 +15,18 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use bigint::hash::H256;
-use keccak::keccak;
+use std::marker::PhantomData;
 use hashdb::HashDB;
+use super::hasher::{Hasher, KeccakHasher};
 use super::triedb::TrieDB;
 use super::{Trie, TrieItem, TrieIterator, Query};
 
 /// A `Trie` implementation which hashes keys and uses a generic `HashDB` backing database.
 ///
 /// Use it as a `Trie` trait object. You can use `raw()` to get the backing `TrieDB` object.
-pub struct SecTrieDB<'db> {
-	raw: TrieDB<'db>
+pub struct SecTrieDB<'db, H: Hasher = KeccakHasher> {
+	raw: TrieDB<'db>,
+	_hasher: PhantomData<H>,
 }
 
 impl<'db> SecTrieDB<'db> {
@@ -34,7 +36,16 @@ impl<'db> SecTrieDB<'db> {
 	/// This guarantees the trie is built correctly.
 	/// Returns an error if root does not exist.
 	pub fn new(db: &'db HashDB, root: &'db H256) -> super::Result<Self> {
-		Ok(SecTrieDB { raw: TrieDB::new(db, root)? })
+		SecTrieDB::with_hasher(db, root)
+	}
+}
+
+impl<'db, H: Hasher> SecTrieDB<'db, H> {
+	/// Create a new trie with the backing database `db` and `root`, hashing keys with `H`.
+	///
+	/// Returns an error if root does not exist.
+	pub fn with_hasher(db: &'db HashDB, root: &'db H256) -> super::Result<Self> {
+		Ok(SecTrieDB { raw: TrieDB::new(db, root)?, _hasher: PhantomData })
 	}
 
 	/// Get a reference to the underlying raw `TrieDB` struct.
@@ -48,7 +59,7 @@ impl<'db> SecTrieDB<'db> {
 	}
 }
 
-impl<'db> Trie for SecTrieDB<'db> {
+impl<'db, H: Hasher> Trie for SecTrieDB<'db, H> {
 	fn iter<'a>(&'a self) -> super::Result<Box<TrieIterator<Item = TrieItem> + 'a>> {
 		TrieDB::iter(&self.raw)
 	}
@@ -56,13 +67,13 @@ impl<'db> Trie for SecTrieDB<'db> {
 	fn root(&self) -> &H256 { self.raw.root() }
 
 	fn contains(&self, key: &[u8]) -> super::Result<bool> {
-		self.raw.contains(&keccak(key))
+		self.raw.contains(&H::hash(key))
 	}
 
 	fn get_with<'a, 'key, Q: Query>(&'a self, key: &'key [u8], query: Q) -> super::Result<Option<Q::Item>>
 		where 'a: 'key
 	{
-		self.raw.get_with(&keccak(key), query)
+		self.raw.get_with(&H::hash(key), query)
 	}
 }
 
@@ -70,6 +81,7 @@ impl<'db> Trie for SecTrieDB<'db> {
 fn trie_to_sectrie() {
 	use memorydb::MemoryDB;
 	use hashdb::DBValue;
+	use keccak::keccak;
 	use super::triedbmut::TrieDBMut;
 	use super::super::TrieMut;
 
@@ -15,16 +15,16 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use bigint::hash::H256;
-use keccak::keccak;
 use hashdb::{HashDB, DBValue};
+use super::hasher::{Hasher, KeccakHasher};
 use super::triedbmut::TrieDBMut;
 use super::TrieMut;
 
 /// A mutable `Trie` implementation which hashes keys and uses a generic `HashDB` backing database.
 ///
 /// Use it as a `Trie` or `TrieMut` trait object. You can use `raw()` to get the backing `TrieDBMut` object.
-pub struct SecTrieDBMut<'db> {
-	raw: TrieDBMut<'db>
+pub struct SecTrieDBMut<'db, H: Hasher = KeccakHasher> {
+	raw: TrieDBMut<'db, H>
 }
 
 impl<'db> SecTrieDBMut<'db> {
@@ -32,14 +32,28 @@ impl<'db> SecTrieDBMut<'db> {
 	/// Initialise to the state entailed by the genesis block.
 	/// This guarantees the trie is built correctly.
 	pub fn new(db: &'db mut HashDB, root: &'db mut H256) -> Self {
-		SecTrieDBMut { raw: TrieDBMut::new(db, root) }
+		SecTrieDBMut::with_hasher(db, root)
 	}
 
 	/// Create a new trie with the backing database `db` and `root`.
 	///
 	/// Returns an error if root does not exist.
 	pub fn from_existing(db: &'db mut HashDB, root: &'db mut H256) -> super::Result<Self> {
-		Ok(SecTrieDBMut { raw: TrieDBMut::from_existing(db, root)? })
+		SecTrieDBMut::existing_with_hasher(db, root)
+	}
+}
+
+impl<'db, H: Hasher> SecTrieDBMut<'db, H> {
+	/// Create a new trie with the backing database `db` and empty `root`, hashing with `H`.
+	pub fn with_hasher(db: &'db mut HashDB, root: &'db mut H256) -> Self {
+		SecTrieDBMut { raw: TrieDBMut::with_hasher(db, root) }
+	}
+
+	/// Create a new trie with the backing database `db` and `root`, hashing with `H`.
+	///
+	/// Returns an error if root does not exist.
+	pub fn existing_with_hasher(db: &'db mut HashDB, root: &'db mut H256) -> super::Result<Self> {
+		Ok(SecTrieDBMut { raw: TrieDBMut::existing_with_hasher(db, root)? })
 	}
 
 	/// Get the backing database.
@@ -49,7 +63,7 @@ impl<'db> SecTrieDBMut<'db> {
 	pub fn db_mut(&mut self) -> &mut HashDB { self.raw.db_mut() }
 }
 
-impl<'db> TrieMut for SecTrieDBMut<'db> {
+impl<'db, H: Hasher> TrieMut for SecTrieDBMut<'db, H> {
 	fn root(&mut self) -> &H256 {
 		self.raw.root()
 	}
@@ -59,27 +73,28 @@ impl<'db> TrieMut for SecTrieDBMut<'db> {
 	}
 
 	fn contains(&self, key: &[u8]) -> super::Result<bool> {
-		self.raw.contains(&keccak(key))
+		self.raw.contains(&H::hash(key))
 	}
 
 	fn get<'a, 'key>(&'a self, key: &'key [u8]) -> super::Result<Option<DBValue>>
 		where 'a: 'key
 	{
-		self.raw.get(&keccak(key))
+		self.raw.get(&H::hash(key))
 	}
 
 	fn insert(&mut self, key: &[u8], value: &[u8]) -> super::Result<Option<DBValue>> {
-		self.raw.insert(&keccak(key), value)
+		self.raw.insert(&H::hash(key), value)
 	}
 
 	fn remove(&mut self, key: &[u8]) -> super::Result<Option<DBValue>> {
-		self.raw.remove(&keccak(key))
+		self.raw.remove(&H::hash(key))
 	}
 }
 
 #[test]
 fn sectrie_to_trie() {
 	use memorydb::*;
+	use keccak::keccak;
 	use super::triedb::*;
 	use super::Trie;
 
@@ -17,6 +17,7 @@
 //! In-memory trie representation.
 
 use super::{TrieError, TrieMut};
+use super::hasher::{Hasher, KeccakHasher};
 use super::lookup::Lookup;
 use super::node::Node as RlpNode;
 use super::node::NodeKey;
@@ -28,6 +29,7 @@ use ::rlp::{Rlp, RlpStream};
 use hashdb::DBValue;
 
 use std::collections::{HashSet, VecDeque};
+use std::marker::PhantomData;
 use std::mem;
 use std::ops::Index;
 use bigint::hash::H256;
@@ -284,7 +286,7 @@ impl<'a> Index<&'a StorageHandle> for NodeStorage {
 ///   assert!(!t.contains(b"foo").unwrap());
 /// }
 /// ```
-pub struct TrieDBMut<'a> {
+pub struct TrieDBMut<'a, H: Hasher = KeccakHasher> {
 	storage: NodeStorage,
 	db: &'a mut HashDB,
 	root: &'a mut H256,
@@ -293,11 +295,25 @@ pub struct TrieDBMut<'a> {
 	/// The number of hash operations this trie has performed.
 	/// Note that none are performed until changes are committed.
 	pub hash_count: usize,
+	_hasher: PhantomData<H>,
 }
 
 impl<'a> TrieDBMut<'a> {
 	/// Create a new trie with backing database `db` and empty `root`.
 	pub fn new(db: &'a mut HashDB, root: &'a mut H256) -> Self {
+		TrieDBMut::with_hasher(db, root)
+	}
+
+	/// Create a new trie with the backing database `db` and `root.
+	/// Returns an error if `root` does not exist.
+	pub fn from_existing(db: &'a mut HashDB, root: &'a mut H256) -> super::Result<Self> {
+		TrieDBMut::existing_with_hasher(db, root)
+	}
+}
+
+impl<'a, H: Hasher> TrieDBMut<'a, H> {
+	/// Create a new trie with backing database `db` and empty `root`, hashing nodes with `H`.
+	pub fn with_hasher(db: &'a mut HashDB, root: &'a mut H256) -> Self {
 		*root = KECCAK_NULL_RLP;
 		let root_handle = NodeHandle::Hash(KECCAK_NULL_RLP);
 
@@ -308,12 +324,13 @@ impl<'a> TrieDBMut<'a> {
 			root_handle: root_handle,
 			death_row: HashSet::new(),
 			hash_count: 0,
+			_hasher: PhantomData,
 		}
 	}
 
-	/// Create a new trie with the backing database `db` and `root.
+	/// Create a new trie with the backing database `db` and `root`, hashing nodes with `H`.
 	/// Returns an error if `root` does not exist.
-	pub fn from_existing(db: &'a mut HashDB, root: &'a mut H256) -> super::Result<Self> {
+	pub fn existing_with_hasher(db: &'a mut HashDB, root: &'a mut H256) -> super::Result<Self> {
 		if !db.contains(root) {
 			return Err(Box::new(TrieError::InvalidStateRoot(*root)));
 		}
@@ -326,6 +343,7 @@ impl<'a> TrieDBMut<'a> {
 			root_handle: root_handle,
 			death_row: HashSet::new(),
 			hash_count: 0,
+			_hasher: PhantomData,
 		})
 	}
 	/// Get the backing database.
@@ -822,7 +840,14 @@ impl<'a> TrieDBMut<'a> {
 		match self.storage.destroy(handle) {
 			Stored::New(node) => {
 				let root_rlp = node.into_rlp(|child, stream| self.commit_node(child, stream));
-				*self.root = self.db.insert(&root_rlp[..]);
+				// the empty trie is always rooted at `KECCAK_NULL_RLP`.
+				*self.root = if &root_rlp[..] == &::rlp::NULL_RLP[..] {
+					KECCAK_NULL_RLP
+				} else {
+					let hash = H::hash(&root_rlp);
+					self.db.emplace(hash, DBValue::from_slice(&root_rlp));
+					hash
+				};
 				self.hash_count += 1;
 
 				trace!(target: "trie", "root node rlp: {:?}", (&root_rlp[..]).pretty());
@@ -846,7 +871,8 @@ impl<'a> TrieDBMut<'a> {
 				Stored::New(node) => {
 					let node_rlp = node.into_rlp(|child, stream| self.commit_node(child, stream));
 					if node_rlp.len() >= 32 {
-						let hash = self.db.insert(&node_rlp[..]);
+						let hash = H::hash(&node_rlp);
+						self.db.emplace(hash, DBValue::from_slice(&node_rlp));
 						self.hash_count += 1;
 						stream.append(&hash)
 					} else {
@@ -866,7 +892,7 @@ impl<'a> TrieDBMut<'a> {
 	}
 }
 
-impl<'a> TrieMut for TrieDBMut<'a> {
+impl<'a, H: Hasher> TrieMut for TrieDBMut<'a, H> {
 	fn root(&mut self) -> &H256 {
 		self.commit();
 		self.root
@@ -931,7 +957,7 @@ impl<'a> TrieMut for TrieDBMut<'a> {
 	}
 }
 
-impl<'a> Drop for TrieDBMut<'a> {
+impl<'a, H: Hasher> Drop for TrieDBMut<'a, H> {
 	fn drop(&mut self) {
 		self.commit();
 	}